@@ -3,7 +3,7 @@
 //! This module provides communication between Bevy WASM and the Svelte UI.
 //! Messages are passed via CustomEvents on the window object.
 
-use pentimento_ipc::{BevyToUi, UiToBevy};
+use pentimento_ipc::{BevyToUi, IpcError, UiToBevy, parse_ui_to_bevy};
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use wasm_bindgen::prelude::*;
@@ -20,12 +20,17 @@ pub fn init_bridge() {
 
     let closure = Closure::wrap(Box::new(move |event: web_sys::CustomEvent| {
         if let Some(detail) = event.detail().as_string() {
-            match serde_json::from_str::<UiToBevy>(&detail) {
+            match parse_ui_to_bevy(&detail) {
                 Ok(msg) => {
                     MESSAGE_QUEUE.with(|queue| {
                         queue.borrow_mut().push_back(msg);
                     });
                 }
+                Err(IpcError::UnknownMessage(type_name)) => {
+                    web_sys::console::warn_1(
+                        &format!("Unsupported UI message type: {}", type_name).into(),
+                    );
+                }
                 Err(e) => {
                     web_sys::console::error_1(&format!("Failed to parse UI message: {}", e).into());
                 }
@@ -0,0 +1,346 @@
+//! Autosave and crash recovery
+//!
+//! Any paint stroke, object transform, or material change marks the project
+//! dirty. Once per interval (5 minutes unless `autosave_interval_secs` is set
+//! in the settings file) a dirty project is snapshotted: the serializable
+//! scene state is cloned on the main thread and written on a background
+//! thread, so the frame never waits on the disk. Completed paint strokes are
+//! appended to the session's stroke journal as they happen, so a session that
+//! crashes between snapshots keeps its strokes.
+//!
+//! At startup the newest previous session is offered to the UI with
+//! `RecoveryAvailable`.
+//!
+//! There is no project file format yet, so a snapshot holds the scene object
+//! list and restoring applies the saved transforms, visibility, and locks to
+//! the objects they match (`RestoreObjectsEvent`: by ID, then by name), which
+//! get their saved IDs back. The journaled strokes are kept for restore to replay
+//! (`PaintingPipeline::replay_packets`) once it rebuilds canvases.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use painting::{StrokeLogEvent, StrokePacket};
+use pentimento_config::{
+    AutosaveSession, DEFAULT_AUTOSAVE_INTERVAL_SECS, KEPT_SESSIONS, SettingsFile,
+    default_autosave_root, find_recovery, latest_snapshot, prune_sessions, write_atomic,
+};
+use pentimento_ipc::{BevyToUi, SceneObject};
+use pentimento_scene::{OutboundUiMessages, PaintingResource, RestoreObjectsEvent};
+use serde::{Deserialize, Serialize};
+
+use crate::query::SceneInfoSource;
+
+/// Autosave configuration, inserted by `main` before the plugin runs
+#[derive(Resource, Clone)]
+pub struct AutosaveConfig {
+    /// Time between snapshots of a dirty project (zero disables autosave)
+    pub interval: Duration,
+    /// Autosave root directory (None when no config directory is available)
+    pub root: Option<PathBuf>,
+}
+
+impl AutosaveConfig {
+    /// Configuration from the settings file
+    pub fn from_settings(settings: &SettingsFile) -> Self {
+        let interval_secs = settings
+            .autosave_interval_secs
+            .unwrap_or(DEFAULT_AUTOSAVE_INTERVAL_SECS);
+        let root = match default_autosave_root() {
+            Ok(root) => Some(root),
+            Err(e) => {
+                warn!("Autosave disabled: {}", e);
+                None
+            }
+        };
+
+        Self {
+            interval: Duration::from_secs(interval_secs),
+            root,
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        !self.interval.is_zero() && self.root.is_some()
+    }
+}
+
+/// Message for autosave operations requested by the UI
+#[derive(Message, Debug, Clone)]
+pub enum AutosaveEvent {
+    /// Restore a session (or snapshot file) offered by `RecoveryAvailable`
+    Restore { path: PathBuf },
+}
+
+/// Serialized scene state of one autosave
+#[derive(Debug, Serialize, Deserialize)]
+struct AutosaveSnapshot {
+    /// Snapshot time in Unix milliseconds
+    saved_at_ms: u64,
+    objects: Vec<SceneObject>,
+}
+
+/// Running session state
+#[derive(Resource, Default)]
+struct AutosaveState {
+    session: Option<AutosaveSession>,
+    /// Set by change detection and by the stroke journal listener
+    dirty: Arc<AtomicBool>,
+    /// Time of the last snapshot (or of the session start)
+    last_snapshot: Option<Instant>,
+    /// Background snapshot write in flight
+    writer: Option<JoinHandle<()>>,
+}
+
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutosaveState>()
+            .add_message::<AutosaveEvent>()
+            .add_systems(Startup, start_autosave_session)
+            .add_systems(
+                Update,
+                (
+                    mark_dirty_on_change,
+                    write_autosave.after(mark_dirty_on_change),
+                    handle_autosave_events,
+                ),
+            );
+    }
+}
+
+/// Offer the newest previous session for recovery, then start this session's
+/// directory and stroke journal
+fn start_autosave_session(
+    config: Res<AutosaveConfig>,
+    mut state: ResMut<AutosaveState>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    mut painting: ResMut<PaintingResource>,
+) {
+    let Some(root) = config.root.as_deref().filter(|_| config.enabled()) else {
+        return;
+    };
+
+    // No project files yet, so there is no explicit save to compare against
+    if let Some(candidate) = find_recovery(root, None) {
+        info!(
+            "Autosave available for recovery: {}",
+            candidate.dir.display()
+        );
+        outbound.send(BevyToUi::RecoveryAvailable {
+            path: candidate.dir.display().to_string(),
+            timestamp: unix_ms(candidate.modified),
+        });
+    }
+
+    // Leave room for this session; the recovery candidate is the newest, so it stays
+    if let Err(e) = prune_sessions(root, KEPT_SESSIONS.saturating_sub(1)) {
+        warn!("Failed to prune old autosaves: {}", e);
+    }
+
+    let session = match AutosaveSession::create(root, unix_ms(SystemTime::now())) {
+        Ok(session) => session,
+        Err(e) => {
+            warn!("Autosave disabled, cannot create {}: {}", root.display(), e);
+            return;
+        }
+    };
+
+    let journal = spawn_stroke_journal(session.journal_path());
+    let dirty = Arc::clone(&state.dirty);
+    painting.add_stroke_listener(move |event| {
+        if let StrokeLogEvent::StrokeCompleted { packet } = event {
+            dirty.store(true, Ordering::Relaxed);
+            // The journal thread only stops if its file can't be written
+            let _ = journal.send(packet);
+        }
+    });
+
+    info!("Autosaving to {}", session.dir().display());
+    state.session = Some(session);
+    state.last_snapshot = Some(Instant::now());
+}
+
+/// Append completed stroke packets to the journal (one JSON packet per line)
+/// on a background thread. The file is created on the first stroke.
+fn spawn_stroke_journal(path: PathBuf) -> mpsc::Sender<StrokePacket> {
+    let (sender, receiver) = mpsc::channel::<StrokePacket>();
+    let spawned = std::thread::Builder::new()
+        .name("stroke-journal".into())
+        .spawn(move || {
+            let mut file = None;
+            for packet in receiver {
+                if file.is_none() {
+                    match OpenOptions::new().create(true).append(true).open(&path) {
+                        Ok(opened) => file = Some(opened),
+                        Err(e) => {
+                            warn!(
+                                "Stroke journal disabled, cannot open {}: {}",
+                                path.display(),
+                                e
+                            );
+                            return;
+                        }
+                    }
+                }
+                let Some(file) = file.as_mut() else {
+                    return;
+                };
+
+                let written = serde_json::to_string(&packet)
+                    .map_err(std::io::Error::from)
+                    .and_then(|line| writeln!(file, "{}", line))
+                    .and_then(|()| file.flush());
+                if let Err(e) = written {
+                    warn!("Stroke journal write failed: {}", e);
+                    return;
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to start stroke journal thread: {}", e);
+    }
+    sender
+}
+
+/// Mark the project dirty on object transform and material changes.
+/// Strokes mark it dirty from the stroke journal listener.
+fn mark_dirty_on_change(
+    state: Res<AutosaveState>,
+    moved: Query<(), (Changed<Transform>, With<Mesh3d>)>,
+    mut material_events: MessageReader<AssetEvent<StandardMaterial>>,
+    mut initialized: Local<bool>,
+) {
+    let material_changed = material_events
+        .read()
+        .any(|event| matches!(event, AssetEvent::Modified { .. }));
+
+    // Everything counts as changed on the first run; the startup scene isn't an edit
+    if !*initialized {
+        *initialized = true;
+        return;
+    }
+
+    if material_changed || !moved.is_empty() {
+        state.dirty.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Snapshot a dirty project once the interval has passed
+fn write_autosave(
+    config: Res<AutosaveConfig>,
+    mut state: ResMut<AutosaveState>,
+    scene: SceneInfoSource,
+) {
+    let Some(last_snapshot) = state.last_snapshot else {
+        return;
+    };
+
+    // One write at a time; a slow disk delays the next snapshot instead of piling up threads
+    if let Some(writer) = state.writer.take() {
+        if !writer.is_finished() {
+            state.writer = Some(writer);
+            return;
+        }
+        let _ = writer.join();
+    }
+
+    if last_snapshot.elapsed() < config.interval || !state.dirty.swap(false, Ordering::Relaxed) {
+        return;
+    }
+
+    let snapshot = AutosaveSnapshot {
+        saved_at_ms: unix_ms(SystemTime::now()),
+        // Objects with the IDs object commands take, so a restore keeps them
+        objects: scene.scene_info().objects,
+    };
+    let Some(session) = state.session.as_mut() else {
+        return;
+    };
+    let path = session.next_snapshot_path();
+
+    let spawned = std::thread::Builder::new()
+        .name("autosave".into())
+        .spawn(move || {
+            let written = serde_json::to_vec_pretty(&snapshot)
+                .map_err(std::io::Error::from)
+                .and_then(|json| write_atomic(&path, &json));
+            match written {
+                Ok(()) => debug!("Autosaved to {}", path.display()),
+                Err(e) => warn!("Autosave to {} failed: {}", path.display(), e),
+            }
+        });
+
+    match spawned {
+        Ok(writer) => state.writer = Some(writer),
+        Err(e) => {
+            warn!("Failed to start autosave thread: {}", e);
+            state.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+    state.last_snapshot = Some(Instant::now());
+}
+
+/// Restore an autosave chosen in the UI
+fn handle_autosave_events(
+    mut events: MessageReader<AutosaveEvent>,
+    state: Res<AutosaveState>,
+    mut restores: MessageWriter<RestoreObjectsEvent>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for event in events.read() {
+        match event {
+            AutosaveEvent::Restore { path } => match read_snapshot(path) {
+                Ok(snapshot) => {
+                    info!(
+                        "Restoring {} objects from autosave {}",
+                        snapshot.objects.len(),
+                        path.display()
+                    );
+                    restores.write(RestoreObjectsEvent(snapshot.objects));
+                    state.dirty.store(true, Ordering::Relaxed);
+                }
+                Err(message) => {
+                    warn!("Autosave restore failed: {}", message);
+                    outbound.send(BevyToUi::Error {
+                        code: "autosave_restore_failed".to_string(),
+                        message,
+                    });
+                }
+            },
+        }
+    }
+}
+
+/// Read a snapshot (or a session directory's newest snapshot)
+fn read_snapshot(path: &Path) -> Result<AutosaveSnapshot, String> {
+    let snapshot_path = if path.is_dir() {
+        latest_snapshot(path).ok_or_else(|| {
+            format!(
+                "{} only has journaled strokes, which can't be replayed yet",
+                path.display()
+            )
+        })?
+    } else {
+        path.to_path_buf()
+    };
+
+    let contents = std::fs::read(&snapshot_path)
+        .map_err(|e| format!("Cannot read {}: {}", snapshot_path.display(), e))?;
+    serde_json::from_slice(&contents)
+        .map_err(|e| format!("{} is not a valid autosave: {}", snapshot_path.display(), e))
+}
+
+fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
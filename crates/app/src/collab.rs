@@ -0,0 +1,191 @@
+//! Shared painting sessions (collab feature)
+//!
+//! `UiToBevy::Collab` hosts, joins, or leaves a `pentimento_collab` session.
+//! While in one, every stroke packet painted on a canvas is shared as it is
+//! logged, and strokes from other peers are replayed onto the canvas with
+//! the same plane ID. When a remote stroke sorts before strokes already on
+//! the canvas, its active layer is cleared and every stroke of the session
+//! is replayed in session order, so all peers converge on the same pixels.
+//! Updates wait while a stroke is being painted.
+//!
+//! Only canvas strokes are applied so far. Documents aren't transferred, so
+//! peers should start from the same blank canvases, and undo stays local.
+//! Sessions run over plain TCP: the host listens on the loopback interface
+//! and its ticket (`tcp://127.0.0.1:<port>`) is reported in `CollabStatus`.
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver};
+
+use bevy::prelude::*;
+use painting::{BrushTip, StrokeLogEvent, StrokePacket, builtin_tips};
+use pentimento_collab::{CollabSession, Target, stroke_packets};
+use pentimento_ipc::{BevyToUi, CollabCommand, CollabState};
+use pentimento_scene::{OutboundUiMessages, PaintingResource};
+
+/// Message for session commands from the UI
+#[derive(Message, Debug, Clone)]
+pub struct CollabEvent(pub CollabCommand);
+
+/// The session this app is in, if any
+///
+/// The session and the stroke channel are behind mutexes only because
+/// resources must be `Sync`; they are used from one system at a time.
+#[derive(Resource, Default)]
+struct Collab {
+    session: Option<Mutex<CollabSession>>,
+    state: CollabState,
+    /// Packets painted locally, from the stroke log listener
+    local_packets: Option<Mutex<Receiver<StrokePacket>>>,
+    /// Tips remote stamp strokes may have been painted with
+    tips: Vec<BrushTip>,
+    /// Peer count last reported to the UI
+    reported_peers: usize,
+}
+
+pub struct CollabPlugin;
+
+impl Plugin for CollabPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Collab>()
+            .add_message::<CollabEvent>()
+            .add_systems(Startup, listen_for_local_strokes)
+            .add_systems(
+                Update,
+                (
+                    handle_collab_events,
+                    sync_collab_session.after(handle_collab_events),
+                ),
+            );
+    }
+}
+
+/// Forward every logged stroke packet to the session system
+fn listen_for_local_strokes(mut collab: ResMut<Collab>, mut painting: ResMut<PaintingResource>) {
+    let (sender, receiver) = mpsc::channel();
+    painting.add_stroke_listener(move |event| {
+        if let StrokeLogEvent::StrokeCompleted { packet } = event {
+            let _ = sender.send(packet);
+        }
+    });
+    collab.local_packets = Some(Mutex::new(receiver));
+    collab.tips = builtin_tips();
+}
+
+/// Host, join, or leave a session
+fn handle_collab_events(
+    mut events: MessageReader<CollabEvent>,
+    mut collab: ResMut<Collab>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for CollabEvent(command) in events.read() {
+        let started = match command {
+            CollabCommand::Host => CollabSession::host(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+                .map(|session| (session, CollabState::Hosting)),
+            CollabCommand::Join { ticket } => {
+                CollabSession::join(ticket).map(|session| (session, CollabState::Joined))
+            }
+            CollabCommand::Leave => {
+                if collab.session.take().is_some() {
+                    info!("Left collab session");
+                }
+                collab.state = CollabState::Offline;
+                outbound.send(status(&collab, None));
+                continue;
+            }
+        };
+
+        match started {
+            Ok((session, state)) => {
+                collab.session = Some(Mutex::new(session));
+                collab.state = state;
+                collab.reported_peers = 0;
+                outbound.send(status(&collab, None));
+            }
+            Err(e) => {
+                warn!("Collab session failed: {}", e);
+                outbound.send(status(&collab, Some(e.to_string())));
+            }
+        }
+    }
+}
+
+/// Share local strokes, collect remote ones, and replay them onto canvases
+fn sync_collab_session(
+    mut collab: ResMut<Collab>,
+    mut painting: ResMut<PaintingResource>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let local_packets: Vec<StrokePacket> = match &collab.local_packets {
+        Some(receiver) => receiver.lock().unwrap().try_iter().collect(),
+        None => Vec::new(),
+    };
+    let Some(session) = collab.session.as_ref() else {
+        // Strokes painted outside a session aren't shared
+        return;
+    };
+    let mut session = session.lock().unwrap();
+
+    for packet in local_packets {
+        session.record_stroke(packet);
+    }
+    session.poll();
+
+    // Replaying mid-stroke would paint under the stroke's pending pixels
+    let stroking = session.log().targets().any(|target| match target {
+        Target::Canvas(plane_id) => painting
+            .get_pipeline(plane_id)
+            .is_some_and(|pipeline| pipeline.is_stroking()),
+        _ => false,
+    });
+    if !stroking {
+        for update in session.take_updates() {
+            let Target::Canvas(plane_id) = update.target else {
+                debug!("Skipping collab strokes for {:?}", update.target);
+                continue;
+            };
+            let Some(pipeline) = painting.get_pipeline_mut(plane_id) else {
+                warn!("Collab strokes for unknown canvas {}", plane_id);
+                continue;
+            };
+            if update.rebuild {
+                pipeline.clear([0.0, 0.0, 0.0, 0.0]);
+            }
+            for stroke in stroke_packets(&update.entries) {
+                pipeline.replay_packets(&stroke, &collab.tips);
+            }
+        }
+    }
+
+    let connected = session.is_connected();
+    let peers = session.peer_count();
+    drop(session);
+
+    if !connected {
+        warn!("Lost the collab session host");
+        collab.session = None;
+        collab.state = CollabState::Offline;
+        outbound.send(status(
+            &collab,
+            Some("Lost connection to the session host".to_string()),
+        ));
+    } else if peers != collab.reported_peers {
+        collab.reported_peers = peers;
+        outbound.send(status(&collab, None));
+    }
+}
+
+fn status(collab: &Collab, error: Option<String>) -> BevyToUi {
+    let session = collab
+        .session
+        .as_ref()
+        .map(|session| session.lock().unwrap());
+    BevyToUi::CollabStatus {
+        state: collab.state,
+        ticket: session.as_ref().and_then(|session| session.ticket()),
+        peers: session
+            .as_ref()
+            .map_or(0, |session| session.peer_count() as u32),
+        error,
+    }
+}
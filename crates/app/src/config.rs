@@ -1,9 +1,15 @@
 //! Application configuration and compositing mode selection
+//!
+//! Configuration comes from the command line, with environment variable
+//! equivalents for every option (CLI flags take precedence).
+
+use std::path::PathBuf;
 
 use bevy::prelude::*;
+use clap::{Parser, ValueEnum};
 
 /// Compositing mode for combining 3D scene with UI overlay
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Resource)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Resource, ValueEnum)]
 pub enum CompositeMode {
     /// Offscreen capture with texture upload (default, most compatible)
     /// Works on all display servers but has higher CPU usage
@@ -21,31 +27,387 @@ pub enum CompositeMode {
     /// Native Dioxus UI with Blitz WGPU renderer
     /// Fast startup, low memory, pure Rust alternative to CEF
     Dioxus,
+    /// UI served to a desktop browser at http://localhost:<remote-port>
+    /// The browser draws the UI; Bevy shows the 3D scene full-window
+    Remote,
+    /// Scripted in-memory UI for headless integration tests
+    #[value(hide = true)]
+    Mock,
 }
 
 impl CompositeMode {
-    /// Parse from environment variable PENTIMENTO_COMPOSITE
-    pub fn from_env() -> Self {
-        match std::env::var("PENTIMENTO_COMPOSITE").as_deref() {
-            Ok("overlay") => Self::Overlay,
-            Ok("cef") => Self::Cef,
-            Ok("tauri") => Self::Tauri,
-            Ok("dioxus") => Self::Dioxus,
-            Ok("capture") | _ => Self::Capture,
+    /// All modes, in the order they are listed to the user
+    pub const ALL: [Self; 6] = [
+        Self::Capture,
+        Self::Overlay,
+        Self::Cef,
+        Self::Tauri,
+        Self::Dioxus,
+        Self::Remote,
+    ];
+
+    /// Modes tried, in order, when the selected mode fails to start
+    pub const DEFAULT_FALLBACK: [Self; 3] = [Self::Cef, Self::Capture, Self::Overlay];
+
+    /// Whether this mode can run in the current native build.
+    ///
+    /// CEF and Dioxus require their cargo features; Tauri runs as a separate
+    /// WASM build and can never be selected from the native binary. Mock is
+    /// only built for tests and with the `mock` feature.
+    pub fn is_available(self) -> bool {
+        match self {
+            Self::Capture | Self::Overlay | Self::Remote => true,
+            Self::Cef => cfg!(feature = "cef"),
+            Self::Dioxus => cfg!(feature = "dioxus"),
+            Self::Tauri => false,
+            Self::Mock => cfg!(any(test, feature = "mock")),
+        }
+    }
+
+    /// Modes that can be selected in this build
+    pub fn available() -> Vec<Self> {
+        Self::ALL.into_iter().filter(|m| m.is_available()).collect()
+    }
+
+    /// Command-line name of this mode (as accepted by `--mode`)
+    pub fn cli_name(self) -> &'static str {
+        match self {
+            Self::Capture => "capture",
+            Self::Overlay => "overlay",
+            Self::Cef => "cef",
+            Self::Tauri => "tauri",
+            Self::Dioxus => "dioxus",
+            Self::Remote => "remote",
+            Self::Mock => "mock",
+        }
+    }
+}
+
+impl From<pentimento_ipc::CompositeMode> for CompositeMode {
+    fn from(mode: pentimento_ipc::CompositeMode) -> Self {
+        match mode {
+            pentimento_ipc::CompositeMode::Capture => Self::Capture,
+            pentimento_ipc::CompositeMode::Overlay => Self::Overlay,
+            pentimento_ipc::CompositeMode::Cef => Self::Cef,
+            pentimento_ipc::CompositeMode::Dioxus => Self::Dioxus,
+            pentimento_ipc::CompositeMode::Tauri => Self::Tauri,
         }
     }
 }
 
+/// Command-line arguments
+#[derive(Parser, Debug, Clone)]
+#[command(
+    name = "pentimento",
+    version,
+    about = "Pentimento 3D painting and sculpting"
+)]
+pub struct Cli {
+    /// UI compositing mode
+    #[arg(long, value_enum, env = "PENTIMENTO_COMPOSITE", default_value_t = CompositeMode::Capture)]
+    pub mode: CompositeMode,
+
+    /// Modes to try, in order, when the UI mode fails to start
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        env = "PENTIMENTO_FALLBACK",
+        default_values_t = CompositeMode::DEFAULT_FALLBACK
+    )]
+    pub fallback: Vec<CompositeMode>,
+
+    /// Load the UI from this URL instead of the embedded assets
+    #[arg(long, value_name = "URL", env = "PENTIMENTO_UI_URL")]
+    pub ui_url: Option<String>,
+
+    /// Port the UI is served on in remote mode
+    #[arg(
+        long,
+        value_name = "PORT",
+        env = "PENTIMENTO_REMOTE_PORT",
+        default_value_t = pentimento_frontend_remote::DEFAULT_PORT
+    )]
+    pub remote_port: u16,
+
+    /// Project file to open once the scene is initialized (hidden and
+    /// rejected by `validate` until a project file format exists)
+    #[arg(long, value_name = "PROJECT", env = "PENTIMENTO_OPEN", hide = true)]
+    pub open: Option<PathBuf>,
+
+    /// Initial window width in pixels (overrides the saved window size)
+    #[arg(long, env = "PENTIMENTO_WIDTH", value_parser = clap::value_parser!(u32).range(1..))]
+    pub width: Option<u32>,
+
+    /// Initial window height in pixels (overrides the saved window size)
+    #[arg(long, env = "PENTIMENTO_HEIGHT", value_parser = clap::value_parser!(u32).range(1..))]
+    pub height: Option<u32>,
+
+    /// Window scale factor (1.0 = one logical pixel per physical pixel)
+    #[arg(long, env = "PENTIMENTO_SCALE", value_parser = parse_scale)]
+    pub scale: Option<f32>,
+
+    /// Diffusion server URL
+    #[arg(long, value_name = "URL", env = "PENTIMENTO_DIFFUSION_SERVER")]
+    pub diffusion_server: Option<String>,
+
+    /// Ignore the saved window size and position (recovers an off-screen window)
+    #[arg(long)]
+    pub reset_window: bool,
+
+    /// Show the node graph panel on a quad in the scene (second UI surface)
+    #[arg(long, env = "PENTIMENTO_NODE_GRAPH_PANEL")]
+    pub node_graph_panel: bool,
+
+    /// Show a clickable clock page on a floating quad in the scene (world-space UI demo)
+    #[arg(long, env = "PENTIMENTO_WORLD_UI_DEMO")]
+    pub world_ui_demo: bool,
+
+    /// Stream a synthetic 1024x1024 diffusion preview to the side panel
+    /// (exercises the binary blob channel without a diffusion server)
+    #[arg(long, env = "PENTIMENTO_DIFFUSION_PREVIEW_DEMO")]
+    pub diffusion_preview_demo: bool,
+
+    /// Frame time the performance governor keeps the scene under, in milliseconds
+    #[arg(
+        long,
+        value_name = "MS",
+        env = "PENTIMENTO_TARGET_FRAME_TIME",
+        default_value_t = 16.6,
+        value_parser = parse_frame_time
+    )]
+    pub target_frame_time: f32,
+
+    /// Record window input and UI messages to FILE for reproducing a bug
+    /// (starts with the default scene and window size)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["replay_input", "open"])]
+    pub record_input: Option<PathBuf>,
+
+    /// Replay input recorded with --record-input
+    #[arg(long, value_name = "FILE", conflicts_with = "open")]
+    pub replay_input: Option<PathBuf>,
+}
+
+impl Cli {
+    /// Reject modes that aren't compiled into this binary, and `--open`
+    /// while project files can't be loaded.
+    ///
+    /// Returns a user-facing message listing the modes that are available.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(path) = &self.open {
+            return Err(format!(
+                "cannot open {}: project files are not supported yet",
+                path.display()
+            ));
+        }
+
+        if self.mode.is_available() {
+            return Ok(());
+        }
+
+        let available: Vec<_> = CompositeMode::available()
+            .into_iter()
+            .map(CompositeMode::cli_name)
+            .collect();
+
+        let hint = match self.mode {
+            CompositeMode::Cef => " (rebuild with --features cef)",
+            CompositeMode::Dioxus => " (rebuild with --features dioxus)",
+            CompositeMode::Tauri => " (Tauri mode runs as a separate WASM build)",
+            CompositeMode::Mock => " (rebuild with --features mock)",
+            _ => "",
+        };
+
+        Err(format!(
+            "composite mode '{}' is not available in this build{}\navailable modes: {}",
+            self.mode.cli_name(),
+            hint,
+            available.join(", ")
+        ))
+    }
+}
+
+fn parse_scale(value: &str) -> Result<f32, String> {
+    let scale: f32 = value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+    if scale.is_finite() && (0.25..=8.0).contains(&scale) {
+        Ok(scale)
+    } else {
+        Err(format!("scale must be between 0.25 and 8.0, got {scale}"))
+    }
+}
+
+fn parse_frame_time(value: &str) -> Result<f32, String> {
+    let ms: f32 = value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+    if ms.is_finite() && (1.0..=1000.0).contains(&ms) {
+        Ok(ms)
+    } else {
+        Err(format!(
+            "frame time must be between 1 and 1000 ms, got {ms}"
+        ))
+    }
+}
+
 /// Application configuration resource
 #[derive(Resource, Clone)]
 pub struct PentimentoConfig {
     pub composite_mode: CompositeMode,
+    /// Modes tried when `composite_mode` fails to start
+    pub fallback_modes: Vec<CompositeMode>,
+    /// UI URL override (None = embedded UI or Vite dev server)
+    pub ui_url: Option<String>,
+    /// Port the UI is served on in remote mode
+    pub remote_port: u16,
+    /// Diffusion server URL override
+    pub diffusion_server_url: Option<String>,
+    /// Whether to open the node graph panel surface
+    pub node_graph_panel: bool,
+    /// Whether to open the world-space UI demo surface
+    pub world_ui_demo: bool,
+    /// Whether to stream the synthetic diffusion preview
+    pub diffusion_preview_demo: bool,
+    /// Frame time budget of the performance governor, in milliseconds
+    pub target_frame_ms: f32,
 }
 
-impl Default for PentimentoConfig {
-    fn default() -> Self {
+impl PentimentoConfig {
+    /// Build the configuration from parsed command-line arguments
+    pub fn from_cli(cli: &Cli) -> Self {
         Self {
-            composite_mode: CompositeMode::from_env(),
+            composite_mode: cli.mode,
+            fallback_modes: cli.fallback.clone(),
+            ui_url: cli.ui_url.clone(),
+            remote_port: cli.remote_port,
+            diffusion_server_url: cli.diffusion_server.clone(),
+            node_graph_panel: cli.node_graph_panel,
+            world_ui_demo: cli.world_ui_demo,
+            diffusion_preview_demo: cli.diffusion_preview_demo,
+            target_frame_ms: cli.target_frame_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_help_lists_options() {
+        let help = Cli::command().render_long_help().to_string();
+        for option in [
+            "--mode",
+            "--fallback",
+            "--ui-url",
+            "--remote-port",
+            "--width",
+            "--height",
+            "--scale",
+            "--diffusion-server",
+            "--reset-window",
+            "--node-graph-panel",
+            "--world-ui-demo",
+            "--diffusion-preview-demo",
+            "--target-frame-time",
+            "--record-input",
+            "--replay-input",
+        ] {
+            assert!(help.contains(option), "--help is missing {option}:\n{help}");
+        }
+        assert!(!help.contains("--open"));
+        assert!(help.contains("PENTIMENTO_COMPOSITE"));
+        assert!(help.contains("capture"));
+        assert!(help.contains("dioxus"));
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let cli = Cli::try_parse_from([
+            "pentimento",
+            "--mode",
+            "overlay",
+            "--width",
+            "1280",
+            "--height",
+            "720",
+            "--scale",
+            "1.5",
+        ])
+        .unwrap();
+        assert_eq!(cli.mode, CompositeMode::Overlay);
+        assert_eq!(cli.width, Some(1280));
+        assert_eq!(cli.height, Some(720));
+        assert_eq!(cli.scale, Some(1.5));
+        assert!(cli.validate().is_ok());
+    }
+
+    #[test]
+    fn test_open_is_rejected_until_projects_load() {
+        let cli = Cli::try_parse_from(["pentimento", "--open", "scene.pentimento"]).unwrap();
+        assert_eq!(cli.open, Some(PathBuf::from("scene.pentimento")));
+        let message = cli.validate().unwrap_err();
+        assert!(message.contains("scene.pentimento"));
+        assert!(message.contains("not supported"));
+    }
+
+    #[test]
+    fn test_parse_remote_mode() {
+        let cli = Cli::try_parse_from(["pentimento", "--mode", "remote"]).unwrap();
+        assert_eq!(cli.mode, CompositeMode::Remote);
+        assert_eq!(cli.remote_port, 7777);
+        assert!(cli.validate().is_ok());
+
+        let cli = Cli::try_parse_from(["pentimento", "--mode", "remote", "--remote-port", "9000"])
+            .unwrap();
+        assert_eq!(PentimentoConfig::from_cli(&cli).remote_port, 9000);
+    }
+
+    #[test]
+    fn test_parse_fallback_modes() {
+        let cli = Cli::try_parse_from(["pentimento"]).unwrap();
+        assert_eq!(cli.fallback, CompositeMode::DEFAULT_FALLBACK);
+
+        let cli = Cli::try_parse_from(["pentimento", "--fallback", "overlay,capture"]).unwrap();
+        assert_eq!(
+            cli.fallback,
+            vec![CompositeMode::Overlay, CompositeMode::Capture]
+        );
+    }
+
+    #[test]
+    fn test_recording_and_replaying_input_exclude_each_other() {
+        let cli = Cli::try_parse_from(["pentimento", "--replay-input", "bug.jsonl"]).unwrap();
+        assert_eq!(cli.replay_input, Some(PathBuf::from("bug.jsonl")));
+
+        for args in [
+            ["--record-input", "a.jsonl", "--replay-input", "b.jsonl"],
+            ["--replay-input", "a.jsonl", "--open", "scene.pentimento"],
+        ] {
+            assert!(Cli::try_parse_from(std::iter::once("pentimento").chain(args)).is_err());
         }
     }
+
+    #[test]
+    fn test_rejects_invalid_values() {
+        assert!(Cli::try_parse_from(["pentimento", "--mode", "electron"]).is_err());
+        assert!(Cli::try_parse_from(["pentimento", "--width", "0"]).is_err());
+        assert!(Cli::try_parse_from(["pentimento", "--scale", "100"]).is_err());
+        assert!(Cli::try_parse_from(["pentimento", "--target-frame-time", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_unavailable_mode_lists_alternatives() {
+        let cli = Cli::try_parse_from(["pentimento", "--mode", "tauri"]).unwrap();
+        let message = cli.validate().unwrap_err();
+        assert!(message.contains("'tauri' is not available"));
+        assert!(message.contains("capture, overlay"));
+    }
 }
@@ -24,6 +24,32 @@ impl UiAssets {
         Self::embedded_html()
     }
 
+    /// Get an HTML document that loads the UI from an external URL (`--ui-url`)
+    ///
+    /// The webview is created from HTML content, so redirect to the URL rather
+    /// than embedding it. The IPC bridge is injected per page load and survives
+    /// the navigation.
+    pub fn url_html(url: &str) -> String {
+        let url = serde_json::to_string(url).unwrap_or_else(|_| "\"\"".to_string());
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+        html, body {{
+            margin: 0;
+            padding: 0;
+            background: transparent;
+        }}
+    </style>
+    <script>window.location.replace({url});</script>
+</head>
+<body></body>
+</html>"#
+        )
+    }
+
     fn embedded_html() -> String {
         // Get the HTML file
         let html = match Self::get("ui/index.html") {
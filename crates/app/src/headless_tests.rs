@@ -0,0 +1,733 @@
+//! Headless integration tests for the frontend pipeline and IPC
+//!
+//! Each test builds the app's scene, render, and input plugins in a Bevy app
+//! without a window server or GPU, with the UI replaced by a `MockUi` (Mock
+//! composite mode). Tests script the UI through the handle and assert on
+//! what the app sent back.
+
+use std::time::Duration;
+
+use bevy::camera::RenderTarget;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::render::RenderPlugin as BevyRenderPlugin;
+use bevy::render::settings::{RenderCreation, WgpuSettings};
+use bevy::window::{CursorMoved, PrimaryWindow, WindowRef, WindowResolution};
+use bevy::winit::WinitPlugin;
+use clap::Parser;
+use pentimento_config::DisplayConfig;
+use pentimento_frontend_core::BackendLifecycle;
+use pentimento_frontend_core::mock::MockUi;
+use pentimento_ipc::{
+    AppSettings, BevyToUi, EditMode, FrontendLifecycle, MouseEvent, PROTOCOL_VERSION, QueryKind,
+    UiToBevy,
+};
+use pentimento_scene::{MainCamera, ScenePlugin};
+
+use crate::config::{Cli, CompositeMode, PentimentoConfig};
+use crate::input::{InputPlayer, Recording};
+use crate::query::QueryRouterPlugin;
+use crate::render::{
+    FrontendStatus, Frontends, MockFrontend, PerformanceGovernor, PowerSaving, ScenePresenter,
+    SceneRenderTarget, SurfaceConfig, SurfacePlacement, UI_CAMERA_ORDER, UiCamera,
+    UiLoadingSpinner, UiOverlay, UiTextureHandle, create_surface,
+};
+use crate::{input, render};
+
+/// Updates to run before giving up on something the app should do
+const MAX_UPDATES: usize = 20;
+
+/// App running the frontend pipeline in Mock mode against `ui`
+fn mock_app(ui: &MockUi) -> App {
+    mock_app_with(ui, |_| {})
+}
+
+/// Mock app with `setup` applied after the plugins are added
+fn mock_app_with(ui: &MockUi, setup: impl FnOnce(&mut App)) -> App {
+    let cli = Cli::try_parse_from(["pentimento", "--mode", "mock"]).unwrap();
+    let mut config = PentimentoConfig::from_cli(&cli);
+    // A mock that fails to start must fail the test, not open a webview
+    config.fallback_modes.clear();
+
+    let mut app = App::new();
+    app.insert_resource(config)
+        .insert_resource(DisplayConfig::default())
+        .insert_resource(MockFrontend(ui.clone()));
+
+    // No winit event loop and no renderer: the window is only an entity,
+    // and without backends the render sub-app is never created
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: WindowResolution::new(800, 600).with_scale_factor_override(1.0),
+                    ..default()
+                }),
+                ..default()
+            })
+            .set(BevyRenderPlugin {
+                render_creation: RenderCreation::Automatic(WgpuSettings {
+                    backends: None,
+                    ..default()
+                }),
+                ..default()
+            })
+            .disable::<WinitPlugin>()
+            .disable::<LogPlugin>(),
+    );
+
+    app.add_plugins(ScenePlugin)
+        .add_plugins(render::RenderPlugin)
+        .add_plugins(input::InputPlugin)
+        .add_plugins(input::NavigationDevicePlugin)
+        .add_plugins(QueryRouterPlugin);
+    setup(&mut app);
+
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// Update until `done` holds, panicking after `MAX_UPDATES` updates
+fn update_until(app: &mut App, what: &str, mut done: impl FnMut(&mut App) -> bool) {
+    for _ in 0..MAX_UPDATES {
+        app.update();
+        if done(app) {
+            return;
+        }
+    }
+    panic!("gave up waiting for {what} after {MAX_UPDATES} updates");
+}
+
+/// Mock app whose UI is ready and has received `Initialize`
+fn ready_app(ui: &MockUi) -> App {
+    let mut app = mock_app(ui);
+    update_until(&mut app, "the UI to be initialized", |_| {
+        ui.sent()
+            .iter()
+            .any(|msg| matches!(msg, BevyToUi::Initialize { .. }))
+    });
+    ui.take_sent();
+    app
+}
+
+/// Update until the app sends a message matching `pick`, and return what it picked
+fn wait_for_reply<T>(
+    app: &mut App,
+    ui: &MockUi,
+    what: &str,
+    mut pick: impl FnMut(&BevyToUi) -> Option<T>,
+) -> T {
+    let mut found = None;
+    update_until(app, what, |_| {
+        found = ui.take_sent().iter().find_map(&mut pick);
+        found.is_some()
+    });
+    found.unwrap()
+}
+
+#[test]
+fn test_initialize_is_sent_once_the_ui_is_ready() {
+    let ui = MockUi::new(3, 1);
+    let mut app = mock_app(&ui);
+    assert_eq!(
+        app.world().resource::<PentimentoConfig>().composite_mode,
+        CompositeMode::Mock
+    );
+
+    app.update();
+    app.update();
+    assert_eq!(ui.lifecycle(), BackendLifecycle::Initializing);
+    assert!(
+        ui.sent().is_empty(),
+        "messages are held until the UI is ready"
+    );
+
+    app.update();
+    assert_eq!(ui.lifecycle(), BackendLifecycle::Ready);
+    let sent = ui.sent();
+    assert!(matches!(sent[0], BevyToUi::Initialize { .. }));
+    assert!(matches!(
+        sent[1],
+        BevyToUi::ProtocolVersion {
+            version: PROTOCOL_VERSION
+        }
+    ));
+}
+
+#[cfg(feature = "selection")]
+#[test]
+fn test_add_object_replies_object_added() {
+    use pentimento_ipc::{AddObjectRequest, PrimitiveType};
+    use pentimento_scene::Selectable;
+
+    let ui = MockUi::default();
+    let mut app = ready_app(&ui);
+
+    ui.inject(UiToBevy::AddObject(AddObjectRequest {
+        primitive_type: PrimitiveType::Cube,
+        position: Some([1.0, 2.0, 3.0]),
+        name: Some("Test Cube".to_string()),
+        subdivisions: None,
+    }));
+    let object = wait_for_reply(&mut app, &ui, "ObjectAdded", |msg| match msg {
+        BevyToUi::ObjectAdded { object, .. } => Some(object.clone()),
+        _ => None,
+    });
+    assert_eq!(object.name, "Test Cube");
+    assert_eq!(object.transform.position, [1.0, 2.0, 3.0]);
+
+    let mut selectables = app.world_mut().query::<(&Selectable, &Name)>();
+    let names: Vec<_> = selectables
+        .iter(app.world())
+        .filter(|(selectable, _)| selectable.id == object.id)
+        .map(|(_, name)| name.to_string())
+        .collect();
+    assert_eq!(names, ["Test Cube"]);
+}
+
+#[test]
+fn test_dirty_frame_is_uploaded_to_the_ui_texture() {
+    let ui = MockUi::new(2, 1);
+    ui.paint(400, 300, [10, 20, 30, 255]);
+    let mut app = mock_app(&ui);
+
+    update_until(&mut app, "the first capture", |app| {
+        app.world().resource::<FrontendStatus>().first_capture_done
+    });
+    assert!(!ui.is_dirty());
+    assert_eq!(app.world().resource::<FrontendStatus>().captures, 1);
+
+    // The texture follows the captured frame's size
+    let handle = app.world().resource::<UiTextureHandle>().handle.clone();
+    let image = app
+        .world()
+        .resource::<Assets<Image>>()
+        .get(&handle)
+        .unwrap();
+    assert_eq!((image.width(), image.height()), (400, 300));
+}
+
+#[test]
+fn test_ui_renders_through_the_ui_camera() {
+    let ui = MockUi::new(1, 0);
+    ui.fill([10, 20, 30, 255]);
+    let mut app = mock_app(&ui);
+
+    update_until(&mut app, "the overlay to get a target camera", |app| {
+        let world = app.world_mut();
+        world
+            .query_filtered::<&ComputedUiTargetCamera, With<UiOverlay>>()
+            .iter(world)
+            .any(|target| target.get().is_some())
+    });
+
+    let world = app.world_mut();
+    let (ui_camera, order, tonemapping) = world
+        .query_filtered::<(Entity, &Camera, &Tonemapping), With<UiCamera>>()
+        .single(world)
+        .map(|(entity, camera, tonemapping)| (entity, camera.order, *tonemapping))
+        .unwrap();
+    // Drawn after the scene, with nothing between the UI texture and the screen
+    assert_eq!(order, UI_CAMERA_ORDER);
+    assert_eq!(tonemapping, Tonemapping::None);
+    let scene_order = world
+        .query_filtered::<&Camera, With<MainCamera>>()
+        .single(world)
+        .unwrap()
+        .order;
+    assert!(scene_order < UI_CAMERA_ORDER);
+
+    let targets: Vec<Option<Entity>> = world
+        .query_filtered::<&ComputedUiTargetCamera, Or<(With<UiOverlay>, With<UiLoadingSpinner>)>>()
+        .iter(world)
+        .map(ComputedUiTargetCamera::get)
+        .collect();
+    assert_eq!(targets, [Some(ui_camera), Some(ui_camera)]);
+}
+
+/// Render target of the main camera, if it has one set
+fn main_camera_target(app: &mut App) -> Option<RenderTarget> {
+    let world = app.world_mut();
+    world
+        .query_filtered::<Option<&RenderTarget>, With<MainCamera>>()
+        .single(world)
+        .unwrap()
+        .cloned()
+}
+
+#[test]
+fn test_manual_render_scale_renders_the_scene_offscreen() {
+    let ui = MockUi::new(1, 0);
+    let mut app = ready_app(&ui);
+    assert!(app.world().resource::<PerformanceGovernor>().enabled);
+
+    ui.inject(UiToBevy::UpdateSettings(AppSettings {
+        render_scale: 0.75,
+        msaa_samples: 1,
+        ..default()
+    }));
+    update_until(&mut app, "the scene to render offscreen", |app| {
+        app.world()
+            .resource::<SceneRenderTarget>()
+            .target()
+            .is_some()
+    });
+
+    // A manual change turns the governor off
+    let governor = app.world().resource::<PerformanceGovernor>();
+    assert!(!governor.enabled);
+    assert_eq!(governor.render_scale(), 0.75);
+
+    let target = app
+        .world()
+        .resource::<SceneRenderTarget>()
+        .target()
+        .cloned()
+        .unwrap();
+    let size = app
+        .world()
+        .resource::<Assets<Image>>()
+        .get(&target.handle)
+        .unwrap()
+        .size();
+    assert_eq!(size, UVec2::new(600, 450));
+    // Same logical size as the 800x600 window, so cursor rays still line up
+    assert_eq!(target.scale_factor, 0.75);
+
+    app.update();
+    assert!(matches!(
+        main_camera_target(&mut app),
+        Some(RenderTarget::Image(image)) if image == target
+    ));
+    let world = app.world_mut();
+    let msaa = *world
+        .query_filtered::<&Msaa, With<MainCamera>>()
+        .single(world)
+        .unwrap();
+    assert_eq!(msaa, Msaa::Off);
+    let presenters = world
+        .query_filtered::<(), With<ScenePresenter>>()
+        .iter(world)
+        .count();
+    assert_eq!(presenters, 1);
+
+    ui.inject(UiToBevy::UpdateSettings(AppSettings {
+        render_scale: 1.0,
+        auto_performance: false,
+        ..default()
+    }));
+    update_until(&mut app, "the scene to render to the window again", |app| {
+        app.world()
+            .resource::<SceneRenderTarget>()
+            .target()
+            .is_none()
+    });
+    app.update();
+    assert!(matches!(
+        main_camera_target(&mut app),
+        Some(RenderTarget::Window(WindowRef::Primary))
+    ));
+    let world = app.world_mut();
+    let presenters = world
+        .query_filtered::<(), With<ScenePresenter>>()
+        .iter(world)
+        .count();
+    assert_eq!(presenters, 0);
+}
+
+/// Resize the primary window
+fn resize_window(app: &mut App, width: u32, height: u32) {
+    let mut windows = app.world_mut().query::<&mut Window>();
+    windows
+        .single_mut(app.world_mut())
+        .unwrap()
+        .resolution
+        .set_physical_resolution(width, height);
+}
+
+/// Write a wheel event on the primary window
+fn scroll_wheel(app: &mut App) {
+    let window = app
+        .world_mut()
+        .query_filtered::<Entity, With<PrimaryWindow>>()
+        .single(app.world())
+        .unwrap();
+    app.world_mut().write_message(MouseWheel {
+        unit: MouseScrollUnit::Line,
+        x: 0.0,
+        y: 1.0,
+        window,
+    });
+}
+
+/// Lifecycle states reported to the UI since the last `take_sent`
+fn lifecycle_changes(ui: &MockUi) -> Vec<FrontendLifecycle> {
+    ui.take_sent()
+        .iter()
+        .filter_map(|msg| match msg {
+            BevyToUi::BackendLifecycleChanged { state } => Some(*state),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_window_resize_settles_before_the_ui_is_ready_again() {
+    let ui = MockUi::new(1, 3);
+    let mut app = ready_app(&ui);
+    assert_eq!(ui.size(), (800, 600));
+
+    resize_window(&mut app, 1024, 768);
+    app.update();
+    assert_eq!(ui.size(), (1024, 768));
+    assert!(matches!(ui.lifecycle(), BackendLifecycle::Resizing { .. }));
+
+    update_until(&mut app, "the resize to settle", |_| {
+        ui.lifecycle() == BackendLifecycle::Ready
+    });
+    app.update();
+    assert_eq!(
+        lifecycle_changes(&ui),
+        [FrontendLifecycle::Resizing, FrontendLifecycle::Ready]
+    );
+}
+
+#[test]
+fn test_moving_to_a_hidpi_monitor_rescales_the_ui() {
+    let ui = MockUi::new(1, 1);
+    let mut app = ready_app(&ui);
+    assert_eq!(ui.scale_factor(), 1.0);
+
+    // Same physical size, now at 2x
+    let mut windows = app.world_mut().query::<&mut Window>();
+    windows
+        .single_mut(app.world_mut())
+        .unwrap()
+        .resolution
+        .set_scale_factor_override(Some(2.0));
+    app.update();
+    assert_eq!(ui.scale_factor(), 2.0);
+    assert_eq!(ui.size(), (800, 600));
+}
+
+#[test]
+fn test_captures_pause_while_the_window_is_unfocused() {
+    let ui = MockUi::new(1, 1);
+    let mut app = ready_app(&ui);
+
+    let mut windows = app.world_mut().query::<&mut Window>();
+    windows.single_mut(app.world_mut()).unwrap().focused = false;
+    app.update();
+    assert!(ui.is_background());
+    assert!(app.world().resource::<PowerSaving>().is_background());
+
+    // A repaint waits for the window to come back
+    ui.paint(800, 600, [10, 20, 30, 255]);
+    app.update();
+    assert!(ui.is_dirty());
+
+    windows.single_mut(app.world_mut()).unwrap().focused = true;
+    app.update();
+    assert!(!ui.is_background());
+    assert!(!ui.is_dirty());
+}
+
+#[cfg(feature = "selection")]
+#[test]
+fn test_replayed_gizmo_translate_cancel_restores_the_cube() {
+    // Select the cube, grab it with G, drag, and cancel with Escape
+    let recording =
+        Recording::parse(include_str!("../replays/gizmo_translate_cancel.jsonl")).unwrap();
+    assert_eq!(
+        (recording.header.width, recording.header.height),
+        (800, 600)
+    );
+    let expected = [
+        ("Cube", Vec3::new(0.0, 0.5, 0.0)),
+        ("Sphere", Vec3::new(2.0, 0.5, 0.0)),
+    ];
+
+    let ui = MockUi::default();
+    let mut app = mock_app_with(&ui, |app| {
+        app.insert_resource(InputPlayer::new(recording))
+            .add_plugins(input::InputReplayPlugin);
+    });
+    let position = |app: &mut App, name: &str| {
+        let world = app.world_mut();
+        world
+            .query::<(&Name, &Transform)>()
+            .iter(world)
+            .find(|(object, _)| object.as_str() == name)
+            .map(|(_, transform)| transform.translation)
+            .unwrap()
+    };
+
+    // Mid-drag the cube follows the recorded mouse motion
+    for _ in 0..8 {
+        app.update();
+    }
+    assert_ne!(position(&mut app, "Cube"), expected[0].1);
+
+    update_until(&mut app, "the replay to finish", |app| {
+        app.world().resource::<InputPlayer>().is_finished()
+    });
+    app.update();
+    for (name, translation) in expected {
+        assert_eq!(position(&mut app, name), translation, "{name}");
+    }
+}
+
+#[test]
+fn test_input_is_held_while_resizing() {
+    let ui = MockUi::new(1, 8);
+    let mut app = ready_app(&ui);
+
+    resize_window(&mut app, 1024, 768);
+    app.update();
+    app.update();
+    assert!(matches!(
+        app.world().resource::<FrontendStatus>().lifecycle,
+        BackendLifecycle::Resizing { .. }
+    ));
+    scroll_wheel(&mut app);
+    app.update();
+    assert!(ui.mouse_events().is_empty());
+
+    update_until(&mut app, "the resize to settle", |app| {
+        app.world().resource::<FrontendStatus>().lifecycle == BackendLifecycle::Ready
+    });
+    scroll_wheel(&mut app);
+    app.update();
+    assert!(matches!(ui.mouse_events()[..], [MouseEvent::Scroll { .. }]));
+}
+
+/// Visibility of the loading spinner
+fn spinner_visibility(app: &mut App) -> Visibility {
+    let mut spinners = app
+        .world_mut()
+        .query_filtered::<&Visibility, With<UiLoadingSpinner>>();
+    *spinners.single(app.world()).unwrap()
+}
+
+#[test]
+fn test_loading_spinner_shows_until_ready() {
+    let ui = MockUi::new(3, 1);
+    let mut app = mock_app(&ui);
+
+    app.update();
+    assert_eq!(spinner_visibility(&mut app), Visibility::Inherited);
+    update_until(&mut app, "the UI to be ready", |_| {
+        ui.lifecycle() == BackendLifecycle::Ready
+    });
+    app.update();
+    assert_eq!(spinner_visibility(&mut app), Visibility::Hidden);
+}
+
+#[test]
+fn test_failed_backend_is_restarted() {
+    let ui = MockUi::default();
+    let mut app = ready_app(&ui);
+
+    ui.crash();
+    app.update();
+    assert_eq!(
+        app.world().resource::<FrontendStatus>().lifecycle,
+        BackendLifecycle::Error
+    );
+
+    // First restart waits out the recovery backoff
+    std::thread::sleep(Duration::from_millis(600));
+    update_until(&mut app, "the UI to be initialized again", |_| {
+        ui.sent()
+            .iter()
+            .any(|msg| matches!(msg, BevyToUi::Initialize { .. }))
+    });
+    app.update();
+    let code = ui.sent().iter().find_map(|msg| match msg {
+        BevyToUi::Error { code, .. } => Some(code.clone()),
+        _ => None,
+    });
+    assert_eq!(
+        code.as_deref(),
+        Some(pentimento_frontend_core::recovery::UI_CRASHED_CODE)
+    );
+}
+
+/// The initialization sequence sent after the last `Initialize`
+fn initialization_sequence(sent: &[BevyToUi]) -> &[BevyToUi] {
+    let start = sent
+        .iter()
+        .rposition(|msg| matches!(msg, BevyToUi::Initialize { .. }))
+        .expect("no Initialize sent");
+    &sent[start..]
+}
+
+/// Assert `sequence` starts with the object-mode initialization sequence
+fn assert_object_mode_sequence(sequence: &[BevyToUi]) {
+    assert!(
+        matches!(
+            sequence,
+            [
+                BevyToUi::Initialize { .. },
+                BevyToUi::ProtocolVersion {
+                    version: PROTOCOL_VERSION
+                },
+                BevyToUi::LightingChanged { .. },
+                BevyToUi::AmbientOcclusionChanged { .. },
+                BevyToUi::EditModeChanged {
+                    mode: EditMode::None
+                },
+                BevyToUi::GizmoModeChanged { .. },
+                BevyToUi::SelectionChanged { .. },
+                ..
+            ]
+        ),
+        "unexpected initialization sequence: {sequence:?}"
+    );
+    // Paint-mode state only follows in paint mode
+    assert!(!sequence.iter().any(|msg| matches!(
+        msg,
+        BevyToUi::LayerStateChanged { .. } | BevyToUi::ProjectionModeChanged { .. }
+    )));
+}
+
+#[test]
+fn test_reloaded_page_gets_the_full_state_again() {
+    // Ready after two polls, so the app sees the reload
+    let ui = MockUi::new(2, 1);
+    let mut app = ready_app(&ui);
+
+    ui.reload();
+    update_until(&mut app, "the reloaded UI to be initialized", |_| {
+        ui.sent()
+            .iter()
+            .any(|msg| matches!(msg, BevyToUi::Initialize { .. }))
+    });
+    assert_object_mode_sequence(initialization_sequence(&ui.take_sent()));
+
+    // A page that reloads without the backend noticing asks for it
+    ui.inject(UiToBevy::RequestInitialize);
+    update_until(&mut app, "the requested initialization", |_| {
+        ui.sent()
+            .iter()
+            .any(|msg| matches!(msg, BevyToUi::Initialize { .. }))
+    });
+    assert_object_mode_sequence(initialization_sequence(&ui.take_sent()));
+}
+
+#[test]
+fn test_query_gets_exactly_one_result() {
+    let ui = MockUi::default();
+    let mut app = ready_app(&ui);
+
+    ui.inject(UiToBevy::Query {
+        request_id: 7,
+        query: QueryKind::SceneInfo,
+    });
+    let result = wait_for_reply(&mut app, &ui, "QueryResult", |msg| match msg {
+        BevyToUi::QueryResult { request_id, result } => Some((*request_id, result.clone())),
+        _ => None,
+    });
+    assert_eq!(result.0, 7);
+    assert!(result.1.is_ok(), "SceneInfo query failed: {:?}", result.1);
+
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(
+        !ui.sent()
+            .iter()
+            .any(|msg| matches!(msg, BevyToUi::QueryResult { .. })),
+        "a query is answered once"
+    );
+}
+
+#[test]
+fn test_protocol_mismatch_is_reported_and_input_forwarded() {
+    let ui = MockUi::default();
+    let mut app = ready_app(&ui);
+
+    ui.inject(UiToBevy::ProtocolVersion {
+        version: PROTOCOL_VERSION + 1,
+    });
+    let code = wait_for_reply(&mut app, &ui, "the mismatch warning", |msg| match msg {
+        BevyToUi::Warning { code, .. } => Some(code.clone()),
+        _ => None,
+    });
+    assert_eq!(code, pentimento_ipc::PROTOCOL_MISMATCH_CODE);
+
+    scroll_wheel(&mut app);
+    app.update();
+    assert!(matches!(ui.mouse_events()[..], [MouseEvent::Scroll { .. }]));
+}
+
+/// Move the cursor over the primary window
+fn move_cursor(app: &mut App, position: Vec2) {
+    let mut windows = app
+        .world_mut()
+        .query_filtered::<(Entity, &mut Window), With<PrimaryWindow>>();
+    let window = {
+        let (entity, mut window) = windows.single_mut(app.world_mut()).unwrap();
+        window.set_cursor_position(Some(position));
+        entity
+    };
+    app.world_mut().write_message(CursorMoved {
+        window,
+        position,
+        delta: None,
+    });
+}
+
+/// Whether `events` contains a scroll at `at`
+fn scrolled_at(events: &[MouseEvent], at: (f32, f32)) -> bool {
+    events
+        .iter()
+        .any(|event| matches!(*event, MouseEvent::Scroll { x, y, .. } if (x, y) == at))
+}
+
+#[test]
+fn test_secondary_surface_takes_input_over_its_region() {
+    let ui = MockUi::default();
+    let mut app = ready_app(&ui);
+
+    let panel = MockUi::default();
+    panel.paint(200, 100, [40, 40, 40, 255]);
+    let id = create_surface(
+        app.world_mut(),
+        SurfaceConfig {
+            html: String::new(),
+            size: (200, 100),
+            placement: SurfacePlacement::Screen(Rect::new(100.0, 100.0, 300.0, 200.0)),
+            mock_ui: Some(panel.clone()),
+        },
+    )
+    .unwrap();
+    update_until(&mut app, "the surface to be ready", |app| {
+        app.world().non_send_resource::<Frontends>().lifecycle(id) == Some(BackendLifecycle::Ready)
+    });
+
+    // Over the surface, events arrive in the surface's own pixels
+    move_cursor(&mut app, Vec2::new(150.0, 120.0));
+    scroll_wheel(&mut app);
+    app.update();
+    assert!(scrolled_at(&panel.mouse_events(), (50.0, 20.0)));
+    assert!(
+        !ui.mouse_events()
+            .iter()
+            .any(|event| matches!(event, MouseEvent::Scroll { .. }))
+    );
+
+    // Everywhere else they go to the main UI
+    move_cursor(&mut app, Vec2::new(500.0, 400.0));
+    scroll_wheel(&mut app);
+    app.update();
+    assert!(scrolled_at(&ui.mouse_events(), (500.0, 400.0)));
+
+    // Surfaces close with the app
+    app.world_mut().write_message(AppExit::Success);
+    app.update();
+    assert_eq!(panel.shutdowns(), 1);
+}
@@ -12,17 +12,24 @@
 //! - Keyboard events
 //! - Coordinate scaling (logical vs physical pixels)
 //!
+//! Input is held back while the backend is `Resizing`: coordinates would be
+//! resolved against a layout that is about to change.
+//!
+//! Secondary UI surfaces (`render::Frontends`) take mouse events while the
+//! cursor is over them, and keyboard events after they were clicked.
+//!
 //! The Dioxus renderer is kept separate as it uses a different render pipeline.
 
 use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use pentimento_config::DisplayConfig;
+use pentimento_frontend_core::BackendLifecycle;
 use pentimento_ipc::{KeyboardEvent, MouseEvent};
 
 use crate::config::{CompositeMode, PentimentoConfig};
 #[cfg(feature = "dioxus")]
 use crate::render::DioxusRendererResource;
-use crate::render::FrontendResource;
+use crate::render::{FrontendResource, FrontendStatus, Frontends, SurfaceHover, UiRenderScale};
 
 /// Unified system parameter for accessing the frontend backend.
 ///
@@ -34,8 +41,16 @@ pub struct FrontendBackend<'w, 's> {
     config: Res<'w, PentimentoConfig>,
     #[allow(dead_code)]
     display_config: Res<'w, DisplayConfig>,
+    /// UI render scale (only present for the unified pipeline)
+    render_scale: Option<Res<'w, UiRenderScale>>,
     /// Unified frontend resource for Capture, Overlay, and CEF modes
     frontend: Option<NonSendMut<'w, FrontendResource>>,
+    /// Lifecycle of the unified frontend (only present for the unified pipeline)
+    status: Option<Res<'w, FrontendStatus>>,
+    /// Secondary UI surfaces (only present for the unified pipeline)
+    surfaces: Option<NonSendMut<'w, Frontends>>,
+    /// Surface under the cursor
+    surface_hover: Option<Res<'w, SurfaceHover>>,
     /// Dioxus renderer (uses separate render pipeline)
     /// NOTE: Must be NonSendMut because DioxusRendererResource is inserted as NonSend
     #[cfg(feature = "dioxus")]
@@ -50,7 +65,12 @@ impl<'w, 's> FrontendBackend<'w, 's> {
     /// Different backends have different coordinate expectations:
     /// - CEF: Uses logical/CSS coordinates (no scaling needed)
     /// - Dioxus: Uses logical/CSS coordinates (no scaling needed)
-    /// - Capture/Overlay: Uses physical pixel coordinates (scaled by DPI)
+    /// - Capture/Overlay: Uses physical pixel coordinates (scaled by DPI and,
+    ///   in Capture mode, by the UI render scale); the backends map them back
+    ///   to CSS pixels with the scale last given to `set_scale_factor`
+    ///
+    /// `scale_factor` must be the window's live scale factor, which changes
+    /// when the window moves to a monitor with a different DPI.
     pub fn scale_coordinates(&self, x: f32, y: f32, scale_factor: f32) -> (f32, f32) {
         match self.config.composite_mode {
             #[cfg(feature = "cef")]
@@ -64,8 +84,14 @@ impl<'w, 's> FrontendBackend<'w, 's> {
                 (x, y)
             }
             CompositeMode::Capture | CompositeMode::Overlay => {
-                // WebKit-based backends use physical resolution
-                (x * scale_factor, y * scale_factor)
+                // WebKit-based backends use the physical resolution of their surface,
+                // which is smaller than the window when the UI render scale is < 1
+                let ui_render_scale = self
+                    .render_scale
+                    .as_ref()
+                    .map_or(1.0, |scale| scale.effective(self.config.composite_mode));
+                let scale = scale_factor * ui_render_scale;
+                (x * scale, y * scale)
             }
             _ => {
                 // Fallback for other modes
@@ -74,12 +100,29 @@ impl<'w, 's> FrontendBackend<'w, 's> {
         }
     }
 
+    /// Whether the unified frontend is resizing and input should be held back.
+    fn is_resizing(&self) -> bool {
+        self.status
+            .as_ref()
+            .is_some_and(|status| matches!(status.lifecycle, BackendLifecycle::Resizing { .. }))
+    }
+
     /// Send a mouse event to the backend.
     ///
-    /// Returns true if the event was sent successfully, false if no backend is available.
+    /// Returns true if the event was sent successfully, false if no backend is
+    /// available or it is resizing.
     pub fn send_mouse_event(&mut self, event: MouseEvent) -> bool {
+        // A secondary surface under the cursor takes the event instead
+        if let (Some(surfaces), Some(hover)) = (self.surfaces.as_mut(), self.surface_hover.as_ref())
+            && surfaces.route_mouse_event(hover, &event)
+        {
+            return true;
+        }
+        if self.is_resizing() {
+            return false;
+        }
         match self.config.composite_mode {
-            CompositeMode::Capture | CompositeMode::Overlay => {
+            CompositeMode::Capture | CompositeMode::Overlay | CompositeMode::Mock => {
                 if let Some(ref mut frontend) = self.frontend {
                     frontend.backend.send_mouse_event(event);
                     return true;
@@ -103,8 +146,8 @@ impl<'w, 's> FrontendBackend<'w, 's> {
             }
             #[cfg(not(feature = "dioxus"))]
             CompositeMode::Dioxus => {}
-            CompositeMode::Tauri => {
-                // Tauri mode handles input in the browser
+            CompositeMode::Tauri | CompositeMode::Remote => {
+                // Tauri and Remote modes handle input in the browser
             }
         }
         false
@@ -112,10 +155,20 @@ impl<'w, 's> FrontendBackend<'w, 's> {
 
     /// Send a keyboard event to the backend.
     ///
-    /// Returns true if the event was sent successfully, false if no backend is available.
+    /// Returns true if the event was sent successfully, false if no backend is
+    /// available or it is resizing.
     pub fn send_keyboard_event(&mut self, event: KeyboardEvent) -> bool {
+        // ...and so does the last surface clicked
+        if let Some(surfaces) = self.surfaces.as_mut()
+            && surfaces.route_keyboard_event(&event)
+        {
+            return true;
+        }
+        if self.is_resizing() {
+            return false;
+        }
         match self.config.composite_mode {
-            CompositeMode::Capture | CompositeMode::Overlay => {
+            CompositeMode::Capture | CompositeMode::Overlay | CompositeMode::Mock => {
                 if let Some(ref mut frontend) = self.frontend {
                     frontend.backend.send_keyboard_event(event);
                     return true;
@@ -139,8 +192,8 @@ impl<'w, 's> FrontendBackend<'w, 's> {
             }
             #[cfg(not(feature = "dioxus"))]
             CompositeMode::Dioxus => {}
-            CompositeMode::Tauri => {
-                // Tauri mode handles input in the browser
+            CompositeMode::Tauri | CompositeMode::Remote => {
+                // Tauri and Remote modes handle input in the browser
             }
         }
         false
@@ -1,24 +1,30 @@
 //! Hotkey handling for Pentimento
 //!
-//! This module handles global hotkeys that aren't forwarded to the webview:
+//! This module handles global hotkeys that aren't forwarded to the webview
+//! (default chords shown; all of them can be rebound through the [`Keymap`]):
 //! - Ctrl+Shift+I: Open DevTools (CEF mode only)
-//! - Ctrl+Z: Undo paint stroke
-//! - Shift+A: Open add object menu
+//! - Ctrl+Z: Undo paint stroke (mesh edit mode handles its own undo)
+//! - Shift+A: Open add object menu at the cursor
+//! - Ctrl+Shift+M: Cycle composite mode (debug builds only)
 
 use bevy::prelude::*;
+use pentimento_config::{Keymap, actions};
 
 use super::MouseState;
-#[cfg(feature = "cef")]
+#[cfg(any(feature = "cef", debug_assertions))]
 use crate::config::CompositeMode;
-#[cfg(feature = "cef")]
+#[cfg(any(feature = "cef", debug_assertions))]
 use crate::config::PentimentoConfig;
 #[cfg(feature = "cef")]
 use crate::render::FrontendResource;
+#[cfg(debug_assertions)]
+use crate::render::{CompositeModeSwitch, uses_frontend_pipeline};
 
 /// Handle Ctrl+Shift+I to open DevTools (CEF mode only)
 #[cfg(feature = "cef")]
 pub fn handle_devtools_hotkey(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     config: Res<PentimentoConfig>,
     frontend: Option<NonSend<FrontendResource>>,
 ) {
@@ -27,14 +33,9 @@ pub fn handle_devtools_hotkey(
         return;
     }
 
-    // Check for Ctrl+Shift+I
-    let ctrl = key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
-    let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
-    let i_pressed = key_input.just_pressed(KeyCode::KeyI);
-
-    if ctrl && shift && i_pressed {
+    if keymap.just_pressed(actions::DEVTOOLS_TOGGLE, &key_input) {
         if let Some(frontend) = frontend {
-            info!("Opening CEF DevTools (Ctrl+Shift+I)");
+            info!("Opening CEF DevTools");
             frontend.backend.show_dev_tools();
         }
     }
@@ -43,36 +44,45 @@ pub fn handle_devtools_hotkey(
 /// Handle Ctrl+Z for paint undo
 pub fn handle_paint_undo_hotkey(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    edit_mode: Option<Res<pentimento_scene::EditModeState>>,
     mut painting_res: Option<ResMut<pentimento_scene::PaintingResource>>,
 ) {
-    let ctrl = key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
-    let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
-    let z_pressed = key_input.just_pressed(KeyCode::KeyZ);
-
-    // Ctrl+Z (without shift) for undo
-    if ctrl && !shift && z_pressed {
+    if edit_mode.is_some_and(|state| state.mode == pentimento_scene::EditorMode::MeshEdit) {
+        return;
+    }
+    if keymap.just_pressed(actions::PAINT_UNDO, &key_input) {
         if let Some(ref mut painting) = painting_res {
             if painting.undo_any() {
-                info!("Paint undo (Ctrl+Z)");
+                info!("Paint undo");
             }
         }
     }
 }
 
 /// Handle Shift+A to open the add object menu
+///
+/// The cursor position is kept as the [`pentimento_scene::AddMenuAnchor`] so
+/// the object picked from the menu is placed under it.
 pub fn handle_add_menu_hotkey(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     mouse_state: Res<MouseState>,
     mut outbound: Option<ResMut<pentimento_scene::OutboundUiMessages>>,
+    anchor: Option<ResMut<pentimento_scene::AddMenuAnchor>>,
 ) {
-    let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
-    let ctrl = key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
-    let a_pressed = key_input.just_pressed(KeyCode::KeyA);
-
-    // Shift+A (without ctrl) opens add menu
-    if shift && !ctrl && a_pressed {
+    if key_input.just_pressed(KeyCode::Escape) {
+        if let Some(mut anchor) = anchor {
+            anchor.0 = None;
+        }
+        return;
+    }
+    if keymap.just_pressed(actions::ADD_MENU, &key_input) {
+        if let Some(mut anchor) = anchor {
+            anchor.0 = Some(Vec2::new(mouse_state.window_x, mouse_state.window_y));
+        }
         if let Some(ref mut outbound) = outbound {
-            info!("Opening add object menu (Shift+A)");
+            info!("Opening add object menu");
             outbound.send(pentimento_ipc::BevyToUi::ShowAddObjectMenu {
                 show: true,
                 position: Some([mouse_state.webview_x, mouse_state.webview_y]),
@@ -80,3 +90,38 @@ pub fn handle_add_menu_hotkey(
         }
     }
 }
+
+/// Handle Ctrl+Shift+M to switch to the next available composite mode (debug builds only)
+#[cfg(debug_assertions)]
+pub fn handle_composite_switch_hotkey(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    config: Res<PentimentoConfig>,
+    switch: Option<ResMut<CompositeModeSwitch>>,
+) {
+    if !keymap.just_pressed(actions::COMPOSITE_MODE_NEXT, &key_input) {
+        return;
+    }
+
+    // Only present when running one of the switchable (unified pipeline) modes
+    let Some(mut switch) = switch else {
+        return;
+    };
+    // Remote mode shares the pipeline but is never switched away from
+    if !uses_frontend_pipeline(config.composite_mode) {
+        return;
+    }
+
+    let modes: Vec<CompositeMode> = CompositeMode::available()
+        .into_iter()
+        .filter(|mode| uses_frontend_pipeline(*mode))
+        .collect();
+    let current = modes
+        .iter()
+        .position(|mode| *mode == config.composite_mode)
+        .unwrap_or(0);
+    let next = modes[(current + 1) % modes.len()];
+
+    info!("Switching composite mode to {:?}", next);
+    switch.requested = Some(next);
+}
@@ -3,9 +3,16 @@
 //! This module handles:
 //! - Keyboard event forwarding
 //! - Modifier key tracking (shift, ctrl, alt, meta)
-//! - Bevy KeyCode to web key string conversion
+//! - Bevy logical keys and physical key codes to web `key`/`code` strings
+//!   (and back, for input replays)
+//!
+//! The `key` string follows the user's layout (AZERTY sends "a" from the key
+//! QWERTY calls Q), while `code` names the physical key. Hotkeys don't go
+//! through here; they match physical positions through the `Keymap`.
+
+use std::borrow::Cow;
 
-use bevy::input::keyboard::KeyboardInput;
+use bevy::input::keyboard::{Key, KeyboardInput, NativeKey};
 use bevy::prelude::*;
 use pentimento_ipc::{KeyboardEvent, Modifiers};
 
@@ -20,15 +27,10 @@ pub fn forward_keyboard(
     // Build current modifier state
     let modifiers = build_modifiers(&key_input);
 
-    let events: Vec<_> = key_events.read().cloned().collect();
-    if events.is_empty() {
-        return;
-    }
-
-    for event in &events {
-        let key = bevy_keycode_to_web_key(event.key_code);
+    for event in key_events.read() {
         backend.send_keyboard_event(KeyboardEvent {
-            key,
+            key: bevy_key_to_web_key(&event.logical_key).into_owned(),
+            code: bevy_keycode_to_web_code(event.key_code).into_owned(),
             pressed: event.state.is_pressed(),
             modifiers: modifiers.clone(),
         });
@@ -45,121 +47,236 @@ pub fn build_modifiers(key_input: &ButtonInput<KeyCode>) -> Modifiers {
     }
 }
 
-/// Convert Bevy KeyCode to web key string
+/// Convert a Bevy logical key to a web key string.
+/// Named keys already use the web names, except Space and Super.
+/// Only typed characters and the Debug-named keys allocate.
 /// See: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/key/Key_Values
-pub fn bevy_keycode_to_web_key(key_code: KeyCode) -> String {
+pub fn bevy_key_to_web_key(key: &Key) -> Cow<'static, str> {
+    match key {
+        Key::Character(text) => Cow::Owned(text.to_string()),
+        Key::Space => Cow::Borrowed(" "),
+        Key::Super => Cow::Borrowed("Meta"),
+        // Composition is up to IME handling; the web reports the key itself as "Dead"
+        Key::Dead(_) => Cow::Borrowed("Dead"),
+        Key::Unidentified(_) => Cow::Borrowed("Unidentified"),
+        named => Cow::Owned(format!("{:?}", named)),
+    }
+}
+
+/// Convert a Bevy physical KeyCode to a web code string.
+/// KeyCode variants are named after the web codes, except the Super keys.
+/// See: https://developer.mozilla.org/en-US/docs/Web/API/UI_Events/Keyboard_event_code_values
+pub fn bevy_keycode_to_web_code(key_code: KeyCode) -> Cow<'static, str> {
     match key_code {
-        // Alphabet
-        KeyCode::KeyA => "a".to_string(),
-        KeyCode::KeyB => "b".to_string(),
-        KeyCode::KeyC => "c".to_string(),
-        KeyCode::KeyD => "d".to_string(),
-        KeyCode::KeyE => "e".to_string(),
-        KeyCode::KeyF => "f".to_string(),
-        KeyCode::KeyG => "g".to_string(),
-        KeyCode::KeyH => "h".to_string(),
-        KeyCode::KeyI => "i".to_string(),
-        KeyCode::KeyJ => "j".to_string(),
-        KeyCode::KeyK => "k".to_string(),
-        KeyCode::KeyL => "l".to_string(),
-        KeyCode::KeyM => "m".to_string(),
-        KeyCode::KeyN => "n".to_string(),
-        KeyCode::KeyO => "o".to_string(),
-        KeyCode::KeyP => "p".to_string(),
-        KeyCode::KeyQ => "q".to_string(),
-        KeyCode::KeyR => "r".to_string(),
-        KeyCode::KeyS => "s".to_string(),
-        KeyCode::KeyT => "t".to_string(),
-        KeyCode::KeyU => "u".to_string(),
-        KeyCode::KeyV => "v".to_string(),
-        KeyCode::KeyW => "w".to_string(),
-        KeyCode::KeyX => "x".to_string(),
-        KeyCode::KeyY => "y".to_string(),
-        KeyCode::KeyZ => "z".to_string(),
-
-        // Numbers
-        KeyCode::Digit0 => "0".to_string(),
-        KeyCode::Digit1 => "1".to_string(),
-        KeyCode::Digit2 => "2".to_string(),
-        KeyCode::Digit3 => "3".to_string(),
-        KeyCode::Digit4 => "4".to_string(),
-        KeyCode::Digit5 => "5".to_string(),
-        KeyCode::Digit6 => "6".to_string(),
-        KeyCode::Digit7 => "7".to_string(),
-        KeyCode::Digit8 => "8".to_string(),
-        KeyCode::Digit9 => "9".to_string(),
-
-        // Function keys
-        KeyCode::F1 => "F1".to_string(),
-        KeyCode::F2 => "F2".to_string(),
-        KeyCode::F3 => "F3".to_string(),
-        KeyCode::F4 => "F4".to_string(),
-        KeyCode::F5 => "F5".to_string(),
-        KeyCode::F6 => "F6".to_string(),
-        KeyCode::F7 => "F7".to_string(),
-        KeyCode::F8 => "F8".to_string(),
-        KeyCode::F9 => "F9".to_string(),
-        KeyCode::F10 => "F10".to_string(),
-        KeyCode::F11 => "F11".to_string(),
-        KeyCode::F12 => "F12".to_string(),
-
-        // Special keys
-        KeyCode::Space => " ".to_string(),
-        KeyCode::Enter => "Enter".to_string(),
-        KeyCode::Escape => "Escape".to_string(),
-        KeyCode::Backspace => "Backspace".to_string(),
-        KeyCode::Tab => "Tab".to_string(),
-        KeyCode::Delete => "Delete".to_string(),
-        KeyCode::Insert => "Insert".to_string(),
-        KeyCode::Home => "Home".to_string(),
-        KeyCode::End => "End".to_string(),
-        KeyCode::PageUp => "PageUp".to_string(),
-        KeyCode::PageDown => "PageDown".to_string(),
-
-        // Arrow keys
-        KeyCode::ArrowUp => "ArrowUp".to_string(),
-        KeyCode::ArrowDown => "ArrowDown".to_string(),
-        KeyCode::ArrowLeft => "ArrowLeft".to_string(),
-        KeyCode::ArrowRight => "ArrowRight".to_string(),
-
-        // Modifier keys
-        KeyCode::ShiftLeft | KeyCode::ShiftRight => "Shift".to_string(),
-        KeyCode::ControlLeft | KeyCode::ControlRight => "Control".to_string(),
-        KeyCode::AltLeft | KeyCode::AltRight => "Alt".to_string(),
-        KeyCode::SuperLeft | KeyCode::SuperRight => "Meta".to_string(),
-
-        // Punctuation and symbols
-        KeyCode::Comma => ",".to_string(),
-        KeyCode::Period => ".".to_string(),
-        KeyCode::Slash => "/".to_string(),
-        KeyCode::Backslash => "\\".to_string(),
-        KeyCode::Semicolon => ";".to_string(),
-        KeyCode::Quote => "'".to_string(),
-        KeyCode::BracketLeft => "[".to_string(),
-        KeyCode::BracketRight => "]".to_string(),
-        KeyCode::Minus => "-".to_string(),
-        KeyCode::Equal => "=".to_string(),
-        KeyCode::Backquote => "`".to_string(),
-
-        // Numpad
-        KeyCode::Numpad0 => "0".to_string(),
-        KeyCode::Numpad1 => "1".to_string(),
-        KeyCode::Numpad2 => "2".to_string(),
-        KeyCode::Numpad3 => "3".to_string(),
-        KeyCode::Numpad4 => "4".to_string(),
-        KeyCode::Numpad5 => "5".to_string(),
-        KeyCode::Numpad6 => "6".to_string(),
-        KeyCode::Numpad7 => "7".to_string(),
-        KeyCode::Numpad8 => "8".to_string(),
-        KeyCode::Numpad9 => "9".to_string(),
-        KeyCode::NumpadAdd => "+".to_string(),
-        KeyCode::NumpadSubtract => "-".to_string(),
-        KeyCode::NumpadMultiply => "*".to_string(),
-        KeyCode::NumpadDivide => "/".to_string(),
-        KeyCode::NumpadDecimal => ".".to_string(),
-        KeyCode::NumpadEnter => "Enter".to_string(),
-
-        // Default for unmapped keys
-        _ => format!("{:?}", key_code),
+        KeyCode::SuperLeft => Cow::Borrowed("MetaLeft"),
+        KeyCode::SuperRight => Cow::Borrowed("MetaRight"),
+        KeyCode::Unidentified(_) => Cow::Borrowed("Unidentified"),
+        code => Cow::Owned(format!("{:?}", code)),
+    }
+}
+
+/// Bevy KeyCode for a web code string, for replaying recorded keys.
+/// Covers the keys hotkeys and text entry use; `None` for anything else.
+pub fn web_code_to_bevy_keycode(code: &str) -> Option<KeyCode> {
+    let key_code = match code {
+        "KeyA" => KeyCode::KeyA,
+        "KeyB" => KeyCode::KeyB,
+        "KeyC" => KeyCode::KeyC,
+        "KeyD" => KeyCode::KeyD,
+        "KeyE" => KeyCode::KeyE,
+        "KeyF" => KeyCode::KeyF,
+        "KeyG" => KeyCode::KeyG,
+        "KeyH" => KeyCode::KeyH,
+        "KeyI" => KeyCode::KeyI,
+        "KeyJ" => KeyCode::KeyJ,
+        "KeyK" => KeyCode::KeyK,
+        "KeyL" => KeyCode::KeyL,
+        "KeyM" => KeyCode::KeyM,
+        "KeyN" => KeyCode::KeyN,
+        "KeyO" => KeyCode::KeyO,
+        "KeyP" => KeyCode::KeyP,
+        "KeyQ" => KeyCode::KeyQ,
+        "KeyR" => KeyCode::KeyR,
+        "KeyS" => KeyCode::KeyS,
+        "KeyT" => KeyCode::KeyT,
+        "KeyU" => KeyCode::KeyU,
+        "KeyV" => KeyCode::KeyV,
+        "KeyW" => KeyCode::KeyW,
+        "KeyX" => KeyCode::KeyX,
+        "KeyY" => KeyCode::KeyY,
+        "KeyZ" => KeyCode::KeyZ,
+        "Digit0" => KeyCode::Digit0,
+        "Digit1" => KeyCode::Digit1,
+        "Digit2" => KeyCode::Digit2,
+        "Digit3" => KeyCode::Digit3,
+        "Digit4" => KeyCode::Digit4,
+        "Digit5" => KeyCode::Digit5,
+        "Digit6" => KeyCode::Digit6,
+        "Digit7" => KeyCode::Digit7,
+        "Digit8" => KeyCode::Digit8,
+        "Digit9" => KeyCode::Digit9,
+        "Numpad0" => KeyCode::Numpad0,
+        "Numpad1" => KeyCode::Numpad1,
+        "Numpad2" => KeyCode::Numpad2,
+        "Numpad3" => KeyCode::Numpad3,
+        "Numpad4" => KeyCode::Numpad4,
+        "Numpad5" => KeyCode::Numpad5,
+        "Numpad6" => KeyCode::Numpad6,
+        "Numpad7" => KeyCode::Numpad7,
+        "Numpad8" => KeyCode::Numpad8,
+        "Numpad9" => KeyCode::Numpad9,
+        "NumpadAdd" => KeyCode::NumpadAdd,
+        "NumpadSubtract" => KeyCode::NumpadSubtract,
+        "NumpadMultiply" => KeyCode::NumpadMultiply,
+        "NumpadDivide" => KeyCode::NumpadDivide,
+        "NumpadDecimal" => KeyCode::NumpadDecimal,
+        "NumpadEnter" => KeyCode::NumpadEnter,
+        "F1" => KeyCode::F1,
+        "F2" => KeyCode::F2,
+        "F3" => KeyCode::F3,
+        "F4" => KeyCode::F4,
+        "F5" => KeyCode::F5,
+        "F6" => KeyCode::F6,
+        "F7" => KeyCode::F7,
+        "F8" => KeyCode::F8,
+        "F9" => KeyCode::F9,
+        "F10" => KeyCode::F10,
+        "F11" => KeyCode::F11,
+        "F12" => KeyCode::F12,
+        "ShiftLeft" => KeyCode::ShiftLeft,
+        "ShiftRight" => KeyCode::ShiftRight,
+        "ControlLeft" => KeyCode::ControlLeft,
+        "ControlRight" => KeyCode::ControlRight,
+        "AltLeft" => KeyCode::AltLeft,
+        "AltRight" => KeyCode::AltRight,
+        "MetaLeft" => KeyCode::SuperLeft,
+        "MetaRight" => KeyCode::SuperRight,
+        "CapsLock" => KeyCode::CapsLock,
+        "Enter" => KeyCode::Enter,
+        "Escape" => KeyCode::Escape,
+        "Tab" => KeyCode::Tab,
+        "Space" => KeyCode::Space,
+        "Backspace" => KeyCode::Backspace,
+        "Delete" => KeyCode::Delete,
+        "Insert" => KeyCode::Insert,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        "ArrowUp" => KeyCode::ArrowUp,
+        "ArrowDown" => KeyCode::ArrowDown,
+        "ArrowLeft" => KeyCode::ArrowLeft,
+        "ArrowRight" => KeyCode::ArrowRight,
+        "Comma" => KeyCode::Comma,
+        "Period" => KeyCode::Period,
+        "Slash" => KeyCode::Slash,
+        "Backslash" => KeyCode::Backslash,
+        "Semicolon" => KeyCode::Semicolon,
+        "Quote" => KeyCode::Quote,
+        "BracketLeft" => KeyCode::BracketLeft,
+        "BracketRight" => KeyCode::BracketRight,
+        "Minus" => KeyCode::Minus,
+        "Equal" => KeyCode::Equal,
+        "Backquote" => KeyCode::Backquote,
+        _ => return None,
+    };
+    Some(key_code)
+}
+
+/// Bevy logical key for a web key string, for replaying recorded keys.
+/// Single characters are typed text; unknown names are `Unidentified`.
+pub fn web_key_to_bevy_key(key: &str) -> Key {
+    if key.chars().count() == 1 {
+        return match key {
+            " " => Key::Space,
+            text => Key::Character(text.into()),
+        };
+    }
+    match key {
+        "Meta" => Key::Super,
+        "Dead" => Key::Dead(None),
+        "Enter" => Key::Enter,
+        "Escape" => Key::Escape,
+        "Tab" => Key::Tab,
+        "Backspace" => Key::Backspace,
+        "Delete" => Key::Delete,
+        "Insert" => Key::Insert,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+        "ArrowUp" => Key::ArrowUp,
+        "ArrowDown" => Key::ArrowDown,
+        "ArrowLeft" => Key::ArrowLeft,
+        "ArrowRight" => Key::ArrowRight,
+        "Shift" => Key::Shift,
+        "Control" => Key::Control,
+        "Alt" => Key::Alt,
+        "CapsLock" => Key::CapsLock,
+        "F1" => Key::F1,
+        "F2" => Key::F2,
+        "F3" => Key::F3,
+        "F4" => Key::F4,
+        "F5" => Key::F5,
+        "F6" => Key::F6,
+        "F7" => Key::F7,
+        "F8" => Key::F8,
+        "F9" => Key::F9,
+        "F10" => Key::F10,
+        "F11" => Key::F11,
+        "F12" => Key::F12,
+        _ => Key::Unidentified(NativeKey::Unidentified),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logical_key_follows_layout() {
+        // AZERTY: the physical Q key types "a"
+        let key = Key::Character("a".into());
+        assert_eq!(bevy_key_to_web_key(&key), "a");
+        assert_eq!(bevy_keycode_to_web_code(KeyCode::KeyQ), "KeyQ");
+
+        assert_eq!(bevy_key_to_web_key(&Key::Character("é".into())), "é");
+        assert_eq!(bevy_key_to_web_key(&Key::Dead(Some('^'))), "Dead");
+    }
+
+    #[test]
+    fn test_named_keys_use_web_names() {
+        assert_eq!(bevy_key_to_web_key(&Key::Enter), "Enter");
+        assert_eq!(bevy_key_to_web_key(&Key::ArrowLeft), "ArrowLeft");
+        assert_eq!(bevy_key_to_web_key(&Key::Space), " ");
+        assert_eq!(bevy_key_to_web_key(&Key::Super), "Meta");
+        assert_eq!(bevy_keycode_to_web_code(KeyCode::Numpad7), "Numpad7");
+        assert_eq!(bevy_keycode_to_web_code(KeyCode::SuperLeft), "MetaLeft");
+    }
+
+    #[test]
+    fn test_web_names_map_back_for_replay() {
+        for code in [
+            KeyCode::KeyG,
+            KeyCode::Escape,
+            KeyCode::SuperLeft,
+            KeyCode::Numpad7,
+        ] {
+            assert_eq!(
+                web_code_to_bevy_keycode(&bevy_keycode_to_web_code(code)),
+                Some(code)
+            );
+        }
+        assert_eq!(web_code_to_bevy_keycode("LaunchMail"), None);
+
+        for key in [
+            Key::Character("g".into()),
+            Key::Space,
+            Key::Super,
+            Key::Escape,
+            Key::ArrowLeft,
+        ] {
+            assert_eq!(web_key_to_bevy_key(&bevy_key_to_web_key(&key)), key);
+        }
     }
 }
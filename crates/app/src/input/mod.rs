@@ -10,7 +10,9 @@
 //! - `backend`: Unified backend abstraction for sending events
 //! - `mouse`: Mouse position tracking and event forwarding
 //! - `keyboard`: Keyboard event forwarding and key conversion
-//! - `hotkeys`: Global hotkey handling (DevTools, Undo, Add Menu)
+//! - `hotkeys`: Global hotkey handling (DevTools, Undo, Add Menu, Mode Switch)
+//! - `navigation`: Gamepad / SpaceMouse camera navigation (`NavigationDevicePlugin`)
+//! - `replay`: Input recording and replay for bug reports (`InputReplayPlugin`)
 //!
 //! # Usage
 //!
@@ -32,6 +34,14 @@ mod backend;
 mod hotkeys;
 mod keyboard;
 mod mouse;
+mod navigation;
+mod replay;
+#[cfg(all(feature = "spacenav", target_os = "linux"))]
+mod spacenav;
+
+pub use navigation::{NavigationDevicePlugin, NavigationSettings};
+pub(crate) use replay::tap_ui_messages;
+pub use replay::{InputPlayer, InputRecorder, InputReplayPlugin, Recording, RecordingHeader};
 
 pub struct InputPlugin;
 
@@ -43,7 +53,8 @@ impl Plugin for InputPlugin {
                 PreUpdate,
                 (clear_motion_events, mouse::track_mouse_position)
                     .chain()
-                    .after(InputSystems),
+                    .after(InputSystems)
+                    .after(crate::render::update_surface_hover),
             )
             .add_systems(
                 PreUpdate,
@@ -74,6 +85,13 @@ impl Plugin for InputPlugin {
             hotkeys::handle_add_menu_hotkey.after(InputSystems),
         );
 
+        // Composite mode switch hotkey (Ctrl+Shift+M, debug builds only)
+        #[cfg(debug_assertions)]
+        app.add_systems(
+            PreUpdate,
+            hotkeys::handle_composite_switch_hotkey.after(InputSystems),
+        );
+
         info!("Input plugin initialized");
     }
 }
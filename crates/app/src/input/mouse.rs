@@ -1,14 +1,22 @@
 //! Mouse input handling - forwards Bevy mouse events to the frontend backend
 //!
 //! This module handles:
-//! - Mouse position tracking (window and webview coordinates)
+//! - Mouse position tracking (window and webview coordinates), rescaled with
+//!   the live scale factor when the window moves to a monitor with another DPI
 //! - Mouse button forwarding (click, press, release)
 //! - Mouse scroll forwarding
+//!
+//! A press on a gizmo handle where the UI is transparent belongs to the scene
+//! alone: it and its release are not forwarded, so the UI can't act on them.
 
+#[cfg(feature = "selection")]
+use bevy::ecs::system::SystemParam;
 use bevy::input::mouse::{MouseButtonInput, MouseWheel};
 use bevy::prelude::*;
-use bevy::window::CursorMoved;
+use bevy::window::{CursorMoved, WindowScaleFactorChanged};
 use pentimento_ipc::{MouseButton as IpcMouseButton, MouseEvent};
+#[cfg(feature = "selection")]
+use pentimento_scene::{GizmoHandle, GizmoState, PointerOverUi};
 use std::time::{Duration, Instant};
 
 use super::MouseState;
@@ -22,34 +30,44 @@ pub const MOUSE_MOVE_THROTTLE: Duration = Duration::from_millis(16); // ~60fps m
 pub fn track_mouse_position(
     mut mouse_state: ResMut<MouseState>,
     mut cursor_events: MessageReader<CursorMoved>,
+    mut scale_events: MessageReader<WindowScaleFactorChanged>,
     mut backend: FrontendBackend,
     windows: Query<&Window>,
 ) {
     let Ok(window) = windows.single() else {
         cursor_events.clear();
+        scale_events.clear();
         return;
     };
 
     // Process CursorMoved events - these contain the actual cursor position
     // Use the LAST event position as that's the most recent
-    let mut had_cursor_event = false;
-    for event in cursor_events.read() {
-        // Scale coordinates based on backend requirements
-        let (webview_x, webview_y) = backend.scale_coordinates(
-            event.position.x,
-            event.position.y,
-            window.resolution.scale_factor(),
-        );
-
-        mouse_state.window_x = event.position.x;
-        mouse_state.window_y = event.position.y;
-        mouse_state.webview_x = webview_x;
-        mouse_state.webview_y = webview_y;
-        had_cursor_event = true;
+    let moved = cursor_events.read().last().map(|event| event.position);
+    let rescaled = scale_events.read().count() > 0;
+    if let Some(position) = moved {
+        mouse_state.window_x = position.x;
+        mouse_state.window_y = position.y;
+    } else if rescaled {
+        // The cursor's logical position changes with the scale even if it didn't move
+        if let Some(position) = window.cursor_position() {
+            mouse_state.window_x = position.x;
+            mouse_state.window_y = position.y;
+        }
     }
 
-    // Only send mouse move to webview if there was cursor movement AND throttle allows
-    if !had_cursor_event {
+    // Scale coordinates based on backend requirements, always with the live
+    // scale factor so buttons and scrolls after a monitor change aren't offset
+    let (webview_x, webview_y) = backend.scale_coordinates(
+        mouse_state.window_x,
+        mouse_state.window_y,
+        window.resolution.scale_factor(),
+    );
+    mouse_state.webview_x = webview_x;
+    mouse_state.webview_y = webview_y;
+
+    // Only send mouse move to webview if the cursor moved (or was rescaled)
+    // AND throttle allows
+    if moved.is_none() && !rescaled {
         return;
     }
 
@@ -73,23 +91,35 @@ pub fn track_mouse_position(
 pub fn forward_mouse_buttons(
     mut button_events: MessageReader<MouseButtonInput>,
     mouse_state: Res<MouseState>,
+    #[cfg(feature = "selection")] gizmo_grab: GizmoGrab,
+    mut scene_buttons: Local<Vec<IpcMouseButton>>,
     mut backend: FrontendBackend,
 ) {
     // Use the tracked position (updated by track_mouse_position which runs first)
     let click_x = mouse_state.webview_x;
     let click_y = mouse_state.webview_y;
+    #[cfg(feature = "selection")]
+    let on_gizmo = gizmo_grab.on_handle();
+    #[cfg(not(feature = "selection"))]
+    let on_gizmo = false;
 
-    // Collect events first to avoid borrow issues
-    let events: Vec<_> = button_events.read().cloned().collect();
-    if events.is_empty() {
-        return;
-    }
-
-    for event in &events {
+    for event in button_events.read() {
         let Some(button) = convert_mouse_button(event.button) else {
             continue;
         };
 
+        // Presses the gizmo takes, and their releases, stay with the scene
+        if event.state.is_pressed() && on_gizmo {
+            scene_buttons.push(button);
+            continue;
+        }
+        if !event.state.is_pressed()
+            && let Some(index) = scene_buttons.iter().position(|held| *held == button)
+        {
+            scene_buttons.swap_remove(index);
+            continue;
+        }
+
         if event.state.is_pressed() {
             info!("Click at webview ({:.1}, {:.1})", click_x, click_y);
         }
@@ -112,6 +142,22 @@ pub fn forward_mouse_buttons(
     }
 }
 
+/// Whether a press would grab a gizmo handle rather than reach the UI
+#[cfg(feature = "selection")]
+#[derive(SystemParam)]
+pub struct GizmoGrab<'w> {
+    gizmo_state: Res<'w, GizmoState>,
+    pointer_over_ui: Res<'w, PointerOverUi>,
+}
+
+#[cfg(feature = "selection")]
+impl GizmoGrab<'_> {
+    /// A handle is hovered and the UI is transparent under the cursor
+    fn on_handle(&self) -> bool {
+        !self.pointer_over_ui.0 && self.gizmo_state.hovered_handle != GizmoHandle::None
+    }
+}
+
 /// Forward mouse scroll events to the webview
 /// Runs after track_mouse_position so MouseState is up-to-date
 pub fn forward_mouse_scroll(
@@ -122,12 +168,7 @@ pub fn forward_mouse_scroll(
     let scroll_x = mouse_state.webview_x;
     let scroll_y = mouse_state.webview_y;
 
-    let events: Vec<_> = scroll_events.read().cloned().collect();
-    if events.is_empty() {
-        return;
-    }
-
-    for event in &events {
+    for event in scroll_events.read() {
         let (delta_x, delta_y) = convert_scroll_delta(event);
         backend.send_mouse_event(MouseEvent::Scroll {
             delta_x,
@@ -139,7 +180,9 @@ pub fn forward_mouse_scroll(
 }
 
 /// Convert Bevy mouse button to IPC mouse button
-fn convert_mouse_button(button: bevy::input::mouse::MouseButton) -> Option<IpcMouseButton> {
+pub(super) fn convert_mouse_button(
+    button: bevy::input::mouse::MouseButton,
+) -> Option<IpcMouseButton> {
     match button {
         bevy::input::mouse::MouseButton::Left => Some(IpcMouseButton::Left),
         bevy::input::mouse::MouseButton::Right => Some(IpcMouseButton::Right),
@@ -0,0 +1,170 @@
+//! Navigation devices - gamepad and SpaceMouse camera control
+//!
+//! Device axes become camera commands on the orbit camera, the same path the
+//! UI's camera commands take:
+//! - Orbit stick: orbit around the target
+//! - Pan stick: pan; with the left shoulder button held, nudge the selection
+//!   through the gizmo instead
+//! - Triggers: zoom (right zooms in, left zooms out)
+//!
+//! With the `spacenav` feature on Linux, a SpaceMouse (via libspnav) drives
+//! the same controls; its first button is the nudge modifier.
+//!
+//! Motion is scaled by frame time, so it stays smooth at any frame rate.
+//! Device input is ignored while a mouse button is held: the pointer is then
+//! captured by a UI slider or a viewport drag, and the two would fight.
+
+use bevy::prelude::*;
+use pentimento_ipc::{CameraCommand, GamepadStick, NavigationDeviceSettings};
+use pentimento_scene::{CameraCommandEvent, GizmoNudgeEvent};
+
+#[cfg(all(feature = "spacenav", target_os = "linux"))]
+use super::spacenav;
+
+/// Navigation device settings (`AppSettings::navigation`), updated from the UI
+#[derive(Resource, Default)]
+pub struct NavigationSettings(pub NavigationDeviceSettings);
+
+/// Plugin turning gamepad (and SpaceMouse) axes into camera commands
+pub struct NavigationDevicePlugin;
+
+impl Plugin for NavigationDevicePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavigationSettings>()
+            .add_systems(Update, gamepad_navigation);
+
+        #[cfg(all(feature = "spacenav", target_os = "linux"))]
+        app.add_plugins(spacenav::SpaceNavPlugin);
+    }
+}
+
+/// Normalized device motion for one frame, axes in -1.0..=1.0 with +y up
+#[derive(Debug, Default, Clone, Copy)]
+pub(super) struct NavigationMotion {
+    pub orbit: Vec2,
+    pub pan: Vec2,
+    /// Positive zooms in
+    pub zoom: f32,
+    /// Pan axes nudge the selection instead of panning
+    pub nudge: bool,
+}
+
+/// Read every connected gamepad and send the combined motion
+fn gamepad_navigation(
+    time: Res<Time>,
+    settings: Res<NavigationSettings>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut camera_events: MessageWriter<CameraCommandEvent>,
+    mut nudge_events: MessageWriter<GizmoNudgeEvent>,
+) {
+    let settings = &settings.0;
+    if !settings.enabled || mouse_buttons.get_pressed().next().is_some() {
+        return;
+    }
+
+    let mut motion = NavigationMotion::default();
+    for gamepad in gamepads.iter() {
+        motion.orbit += stick(gamepad, settings.orbit_stick);
+        motion.pan += stick(gamepad, settings.pan_stick);
+        motion.zoom += gamepad.get(GamepadButton::RightTrigger2).unwrap_or(0.0)
+            - gamepad.get(GamepadButton::LeftTrigger2).unwrap_or(0.0);
+        motion.nudge |= gamepad.pressed(GamepadButton::LeftTrigger);
+    }
+
+    send_navigation(
+        motion,
+        settings,
+        time.delta_secs(),
+        &mut camera_events,
+        &mut nudge_events,
+    );
+}
+
+fn stick(gamepad: &Gamepad, stick: GamepadStick) -> Vec2 {
+    match stick {
+        GamepadStick::Left => gamepad.left_stick(),
+        GamepadStick::Right => gamepad.right_stick(),
+    }
+}
+
+/// Apply dead zones and speeds to `motion` and send it as camera commands
+/// (or selection nudges). Shared by the gamepad and SpaceMouse paths.
+pub(super) fn send_navigation(
+    motion: NavigationMotion,
+    settings: &NavigationDeviceSettings,
+    dt: f32,
+    camera_events: &mut MessageWriter<CameraCommandEvent>,
+    nudge_events: &mut MessageWriter<GizmoNudgeEvent>,
+) {
+    let y_sign = if settings.invert_y { -1.0 } else { 1.0 };
+    let orbit = apply_dead_zone(motion.orbit, settings.dead_zone) * settings.orbit_speed * dt;
+    let pan = apply_dead_zone(motion.pan, settings.dead_zone) * settings.pan_speed * dt;
+    let zoom = apply_dead_zone(Vec2::new(motion.zoom, 0.0), settings.dead_zone).x
+        * settings.zoom_speed
+        * dt;
+
+    // Camera commands take mouse pixels (+y down); pushing a stick up tilts
+    // the view up like dragging the mouse up
+    if orbit != Vec2::ZERO {
+        camera_events.write(CameraCommandEvent(CameraCommand::Orbit {
+            delta_x: orbit.x,
+            delta_y: -orbit.y * y_sign,
+        }));
+    }
+
+    if pan != Vec2::ZERO {
+        if motion.nudge {
+            // Object follows the stick
+            nudge_events.write(GizmoNudgeEvent {
+                delta: Vec2::new(pan.x, -pan.y * y_sign),
+            });
+        } else {
+            // View follows the stick (mouse panning drags the scene the other way)
+            camera_events.write(CameraCommandEvent(CameraCommand::Pan {
+                delta_x: -pan.x,
+                delta_y: pan.y * y_sign,
+            }));
+        }
+    }
+
+    if zoom != 0.0 {
+        camera_events.write(CameraCommandEvent(CameraCommand::Zoom { delta: zoom }));
+    }
+}
+
+/// Radial dead zone: zero inside `dead_zone`, rescaled to 0..=1 outside it so
+/// motion starts smoothly at the edge instead of jumping
+pub fn apply_dead_zone(axes: Vec2, dead_zone: f32) -> Vec2 {
+    let dead_zone = dead_zone.clamp(0.0, 0.99);
+    let magnitude = axes.length();
+    if magnitude <= dead_zone {
+        return Vec2::ZERO;
+    }
+
+    let scaled = ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0);
+    axes * (scaled / magnitude)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dead_zone_rescales_outside() {
+        assert_eq!(apply_dead_zone(Vec2::new(0.1, 0.05), 0.15), Vec2::ZERO);
+        assert_eq!(
+            apply_dead_zone(Vec2::new(0.0, -1.0), 0.15),
+            Vec2::new(0.0, -1.0)
+        );
+
+        // Halfway between the dead zone and full deflection maps to 0.5
+        let half = apply_dead_zone(Vec2::new(0.6, 0.0), 0.2);
+        assert!((half.x - 0.5).abs() < 1e-6);
+
+        // Direction is kept, magnitude never exceeds 1
+        let diagonal = apply_dead_zone(Vec2::new(1.0, 1.0), 0.1);
+        assert!((diagonal.length() - 1.0).abs() < 1e-6);
+        assert!((diagonal.x - diagonal.y).abs() < 1e-6);
+    }
+}
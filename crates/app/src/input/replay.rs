@@ -0,0 +1,484 @@
+//! Input recording and replay for reproducing bugs
+//!
+//! `--record-input <file>` logs the window input of every frame (cursor
+//! position, mouse motion, buttons, wheel, keys) and the messages the main UI
+//! sent, tagged with the frame they arrived on. `--replay-input <file>` writes
+//! the input back as the same Bevy messages on the same frames, bypassing
+//! winit, so the scene systems and the forwarding to the UI backend run as
+//! they did. The recorded UI messages are dispatched in place of the ones the
+//! live UI sends.
+//!
+//! The window input is recorded rather than the `MouseEvent`s and
+//! `KeyboardEvent`s forwarded to the UI: presses a gizmo takes aren't
+//! forwarded, moves are throttled, and the gizmo follows raw mouse motion, so
+//! the forwarded events alone can't drive the scene. Replaying the window
+//! input forwards them again.
+//!
+//! Both flags start the app the same way: the window at the recorded size and
+//! scale, the default scene (no `--open`, autosave, or recovery), sequential
+//! object IDs (`IdAllocator::sequential`), and a fixed frame time. Nothing
+//! else in the app draws random numbers.
+//!
+//! The file is JSON lines: a [`RecordingHeader`], then a [`RecordedFrame`]
+//! for every frame that had input. Frames count from the first update. Within
+//! a frame, input is grouped by kind, which is also the order the input
+//! systems see it in.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::input::ButtonState;
+use bevy::input::InputSystems;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{CursorMoved, PrimaryWindow};
+use pentimento_config::DisplayConfig;
+use pentimento_ipc::{MouseButton as IpcMouseButton, UiToBevy};
+use pentimento_scene::IdAllocator;
+use serde::{Deserialize, Serialize};
+
+use super::keyboard::{
+    bevy_key_to_web_key, bevy_keycode_to_web_code, web_code_to_bevy_keycode, web_key_to_bevy_key,
+};
+use super::mouse::convert_mouse_button;
+
+/// Format version written to the header
+const RECORDING_VERSION: u32 = 1;
+
+/// Time every frame advances by while recording or replaying
+const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+
+/// First line of a recording: how the app was started
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub version: u32,
+    /// Window size in logical pixels
+    pub width: u32,
+    pub height: u32,
+    /// Window scale factor
+    pub scale: f32,
+}
+
+impl RecordingHeader {
+    /// Header for an app started with `display`
+    pub fn new(display: &DisplayConfig) -> Self {
+        Self {
+            version: RECORDING_VERSION,
+            width: display.width,
+            height: display.height,
+            scale: display.scale,
+        }
+    }
+}
+
+/// One piece of recorded input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedInput {
+    /// Cursor position in logical window pixels (the last one of the frame)
+    Cursor { x: f32, y: f32 },
+    /// Raw mouse motion, summed over the frame
+    Motion { x: f32, y: f32 },
+    Button {
+        button: IpcMouseButton,
+        pressed: bool,
+    },
+    /// Wheel delta, in lines or pixels
+    Wheel { x: f32, y: f32, lines: bool },
+    /// Key press or release, named like the `KeyboardEvent` sent to the UI
+    Key {
+        code: String,
+        key: String,
+        pressed: bool,
+    },
+    /// Message from the main UI
+    Ui { message: UiToBevy },
+}
+
+/// Input of one frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub frame: u64,
+    pub input: Vec<RecordedInput>,
+}
+
+/// A parsed recording
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub header: RecordingHeader,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    /// Read a recording file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("can't read input recording {}: {e}", path.display()))?;
+        Self::parse(&text).map_err(|e| format!("invalid input recording {}: {e}", path.display()))
+    }
+
+    /// Parse the lines of a recording
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let (_, header) = lines.next().ok_or("the file is empty")?;
+        let header: RecordingHeader =
+            serde_json::from_str(header).map_err(|e| format!("line 1: {e}"))?;
+        if header.version != RECORDING_VERSION {
+            return Err(format!(
+                "format version {} isn't supported (expected {RECORDING_VERSION})",
+                header.version
+            ));
+        }
+        let frames = lines
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| format!("line {}: {e}", index + 1))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { header, frames })
+    }
+}
+
+/// Writes the recording while `--record-input` is given
+#[derive(Resource)]
+pub struct InputRecorder {
+    file: LineWriter<File>,
+    frame: u64,
+    /// Input of the current frame
+    pending: Vec<RecordedInput>,
+}
+
+impl InputRecorder {
+    /// Create the recording file and write its header
+    pub fn create(path: &Path, header: &RecordingHeader) -> io::Result<Self> {
+        let mut file = LineWriter::new(File::create(path)?);
+        writeln!(file, "{}", serde_json::to_string(header)?)?;
+        Ok(Self {
+            file,
+            frame: 0,
+            pending: Vec::new(),
+        })
+    }
+}
+
+/// Feeds a recording back in while `--replay-input` is given
+#[derive(Resource)]
+pub struct InputPlayer {
+    frames: VecDeque<RecordedFrame>,
+    frame: u64,
+    /// Recorded UI messages of the current frame
+    ui_messages: Vec<UiToBevy>,
+    finished: bool,
+}
+
+impl InputPlayer {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            frames: recording.frames.into(),
+            frame: 0,
+            ui_messages: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Whether every recorded frame has been replayed
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Plugin recording or replaying input, added by `main` after inserting an
+/// `InputRecorder` or `InputPlayer`
+pub struct InputReplayPlugin;
+
+impl Plugin for InputReplayPlugin {
+    fn build(&self, app: &mut App) {
+        // Same object IDs and time steps on every run
+        app.insert_resource(IdAllocator::sequential())
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+            .add_systems(
+                PreUpdate,
+                (
+                    replay_input
+                        .before(InputSystems)
+                        .run_if(resource_exists::<InputPlayer>),
+                    record_input
+                        .after(InputSystems)
+                        .run_if(resource_exists::<InputRecorder>),
+                ),
+            )
+            .add_systems(
+                Last,
+                (
+                    write_recorded_frame.run_if(resource_exists::<InputRecorder>),
+                    advance_replay.run_if(resource_exists::<InputPlayer>),
+                ),
+            );
+    }
+}
+
+/// Log what the main UI sent while recording; while replaying, return the
+/// recorded messages of this frame instead of the live ones
+pub(crate) fn tap_ui_messages(world: &mut World, messages: Vec<UiToBevy>) -> Vec<UiToBevy> {
+    if let Some(mut recorder) = world.get_resource_mut::<InputRecorder>() {
+        recorder
+            .pending
+            .extend(messages.iter().map(|message| RecordedInput::Ui {
+                message: message.clone(),
+            }));
+        return messages;
+    }
+    if let Some(mut player) = world.get_resource_mut::<InputPlayer>() {
+        if !messages.is_empty() {
+            debug!("Input replay: ignoring {} live UI messages", messages.len());
+        }
+        return std::mem::take(&mut player.ui_messages);
+    }
+    messages
+}
+
+/// Collect this frame's window input for the recording
+fn record_input(
+    mut recorder: ResMut<InputRecorder>,
+    primary: Query<Entity, With<PrimaryWindow>>,
+    mut cursor_events: MessageReader<CursorMoved>,
+    mut motion_events: MessageReader<MouseMotion>,
+    mut button_events: MessageReader<MouseButtonInput>,
+    mut wheel_events: MessageReader<MouseWheel>,
+    mut key_events: MessageReader<KeyboardInput>,
+) {
+    let Ok(window) = primary.single() else {
+        return;
+    };
+    let input = &mut recorder.pending;
+
+    if let Some(event) = cursor_events.read().filter(|e| e.window == window).last() {
+        input.push(RecordedInput::Cursor {
+            x: event.position.x,
+            y: event.position.y,
+        });
+    }
+    let motion: Vec2 = motion_events.read().map(|event| event.delta).sum();
+    if motion != Vec2::ZERO {
+        input.push(RecordedInput::Motion {
+            x: motion.x,
+            y: motion.y,
+        });
+    }
+    for event in button_events.read().filter(|e| e.window == window) {
+        if let Some(button) = convert_mouse_button(event.button) {
+            input.push(RecordedInput::Button {
+                button,
+                pressed: event.state.is_pressed(),
+            });
+        }
+    }
+    for event in wheel_events.read().filter(|e| e.window == window) {
+        input.push(RecordedInput::Wheel {
+            x: event.x,
+            y: event.y,
+            lines: event.unit == MouseScrollUnit::Line,
+        });
+    }
+    for event in key_events.read().filter(|e| e.window == window) {
+        input.push(RecordedInput::Key {
+            code: bevy_keycode_to_web_code(event.key_code).into_owned(),
+            key: bevy_key_to_web_key(&event.logical_key).into_owned(),
+            pressed: event.state.is_pressed(),
+        });
+    }
+}
+
+/// Append the frame's input to the file and move on to the next frame
+fn write_recorded_frame(mut commands: Commands, mut recorder: ResMut<InputRecorder>) {
+    let frame = recorder.frame;
+    recorder.frame += 1;
+    if recorder.pending.is_empty() {
+        return;
+    }
+
+    let recorded = RecordedFrame {
+        frame,
+        input: std::mem::take(&mut recorder.pending),
+    };
+    let written = serde_json::to_string(&recorded)
+        .map_err(io::Error::from)
+        .and_then(|line| writeln!(recorder.file, "{line}"));
+    if let Err(e) = written {
+        warn!("Input recording stopped, write failed: {}", e);
+        commands.remove_resource::<InputRecorder>();
+    }
+}
+
+/// Write the recorded window input of this frame as Bevy input messages
+#[allow(clippy::too_many_arguments)]
+fn replay_input(
+    mut player: ResMut<InputPlayer>,
+    mut primary: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut cursor_events: MessageWriter<CursorMoved>,
+    mut motion_events: MessageWriter<MouseMotion>,
+    mut button_events: MessageWriter<MouseButtonInput>,
+    mut wheel_events: MessageWriter<MouseWheel>,
+    mut key_events: MessageWriter<KeyboardInput>,
+) {
+    let Ok((window, mut window_state)) = primary.single_mut() else {
+        return;
+    };
+
+    let frame = player.frame;
+    while player
+        .frames
+        .front()
+        .is_some_and(|next| next.frame <= frame)
+    {
+        let Some(recorded) = player.frames.pop_front() else {
+            break;
+        };
+        for input in recorded.input {
+            match input {
+                RecordedInput::Cursor { x, y } => {
+                    let position = Vec2::new(x, y);
+                    window_state.set_cursor_position(Some(position));
+                    cursor_events.write(CursorMoved {
+                        window,
+                        position,
+                        delta: None,
+                    });
+                }
+                RecordedInput::Motion { x, y } => {
+                    motion_events.write(MouseMotion {
+                        delta: Vec2::new(x, y),
+                    });
+                }
+                RecordedInput::Button { button, pressed } => {
+                    button_events.write(MouseButtonInput {
+                        button: bevy_mouse_button(button),
+                        state: button_state(pressed),
+                        window,
+                    });
+                }
+                RecordedInput::Wheel { x, y, lines } => {
+                    wheel_events.write(MouseWheel {
+                        unit: if lines {
+                            MouseScrollUnit::Line
+                        } else {
+                            MouseScrollUnit::Pixel
+                        },
+                        x,
+                        y,
+                        window,
+                    });
+                }
+                RecordedInput::Key { code, key, pressed } => {
+                    let Some(key_code) = web_code_to_bevy_keycode(&code) else {
+                        warn!("Input replay: skipping unknown key code {}", code);
+                        continue;
+                    };
+                    let logical_key = web_key_to_bevy_key(&key);
+                    let text = match &logical_key {
+                        Key::Character(text) if pressed => Some(text.clone()),
+                        _ => None,
+                    };
+                    key_events.write(KeyboardInput {
+                        key_code,
+                        logical_key,
+                        state: button_state(pressed),
+                        text,
+                        repeat: false,
+                        window,
+                    });
+                }
+                RecordedInput::Ui { message } => player.ui_messages.push(message),
+            }
+        }
+    }
+}
+
+/// Move on to the next frame, reporting when the recording runs out
+fn advance_replay(mut player: ResMut<InputPlayer>) {
+    if !player.ui_messages.is_empty() {
+        debug!(
+            "Input replay: {} UI messages had no UI to go to",
+            player.ui_messages.len()
+        );
+        player.ui_messages.clear();
+    }
+    player.frame += 1;
+    if player.frames.is_empty() && !player.finished {
+        info!("Input replay finished after {} frames", player.frame);
+        player.finished = true;
+    }
+}
+
+fn bevy_mouse_button(button: IpcMouseButton) -> MouseButton {
+    match button {
+        IpcMouseButton::Left => MouseButton::Left,
+        IpcMouseButton::Right => MouseButton::Right,
+        IpcMouseButton::Middle => MouseButton::Middle,
+    }
+}
+
+fn button_state(pressed: bool) -> ButtonState {
+    if pressed {
+        ButtonState::Pressed
+    } else {
+        ButtonState::Released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pentimento_ipc::ObjectCommand;
+
+    use super::*;
+
+    #[test]
+    fn test_recording_round_trips_through_json_lines() {
+        let header = RecordingHeader::new(&DisplayConfig::default());
+        let frame = RecordedFrame {
+            frame: 3,
+            input: vec![
+                RecordedInput::Cursor { x: 10.0, y: 20.0 },
+                RecordedInput::Key {
+                    code: "KeyG".into(),
+                    key: "g".into(),
+                    pressed: true,
+                },
+                RecordedInput::Ui {
+                    message: UiToBevy::ObjectCommand(ObjectCommand::Select {
+                        ids: vec!["a".into()],
+                    }),
+                },
+            ],
+        };
+        let text = format!(
+            "{}\n{}\n",
+            serde_json::to_string(&header).unwrap(),
+            serde_json::to_string(&frame).unwrap()
+        );
+
+        let recording = Recording::parse(&text).unwrap();
+        assert_eq!(recording.header, header);
+        assert_eq!(
+            serde_json::to_string(&recording.frames).unwrap(),
+            serde_json::to_string(&[frame]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_versions_and_bad_lines() {
+        let header = r#"{"version":99,"width":800,"height":600,"scale":1.0}"#;
+        assert!(Recording::parse(header).unwrap_err().contains("version 99"));
+
+        let text = "{\"version\":1,\"width\":800,\"height\":600,\"scale\":1.0}\n\n{\"frame\":1}";
+        assert!(Recording::parse(text).unwrap_err().starts_with("line 3"));
+        assert!(Recording::parse("").is_err());
+    }
+}
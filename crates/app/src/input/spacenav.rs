@@ -0,0 +1,156 @@
+//! SpaceMouse support through libspnav (Linux, `spacenav` feature)
+//!
+//! Talks to the spacenavd daemon. Motion events report the current puck
+//! displacement, which is held until the next event, so a puck at rest sends
+//! one final zero event. If the daemon isn't running the plugin stays idle.
+
+use std::os::raw::{c_int, c_uint};
+
+use bevy::prelude::*;
+use pentimento_scene::{CameraCommandEvent, GizmoNudgeEvent};
+
+use super::navigation::{NavigationMotion, NavigationSettings, send_navigation};
+
+const SPNAV_EVENT_MOTION: c_int = 1;
+const SPNAV_EVENT_BUTTON: c_int = 2;
+
+/// Typical full-deflection axis value reported by spacenavd
+const FULL_DEFLECTION: f32 = 350.0;
+
+// The event structs mirror libspnav's `spnav_event` layout; not every field is read
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+struct SpnavEventMotion {
+    kind: c_int,
+    x: c_int,
+    y: c_int,
+    z: c_int,
+    rx: c_int,
+    ry: c_int,
+    rz: c_int,
+    period: c_uint,
+    data: *mut c_int,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+struct SpnavEventButton {
+    kind: c_int,
+    press: c_int,
+    bnum: c_int,
+}
+
+#[repr(C)]
+union SpnavEvent {
+    kind: c_int,
+    motion: SpnavEventMotion,
+    button: SpnavEventButton,
+}
+
+#[link(name = "spnav")]
+unsafe extern "C" {
+    fn spnav_open() -> c_int;
+    fn spnav_close() -> c_int;
+    fn spnav_poll_event(event: *mut SpnavEvent) -> c_int;
+}
+
+/// Latest SpaceMouse state
+#[derive(Resource, Default)]
+struct SpaceNavState {
+    connected: bool,
+    /// Translation (x right, y up, z toward the user) in -1.0..=1.0
+    translation: Vec3,
+    /// Rotation about the same axes in -1.0..=1.0
+    rotation: Vec3,
+    /// First button held (nudge modifier)
+    nudge: bool,
+}
+
+pub struct SpaceNavPlugin;
+
+impl Plugin for SpaceNavPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpaceNavState>()
+            .add_systems(Startup, open_spacenav)
+            .add_systems(Update, spacenav_navigation)
+            .add_systems(Last, close_spacenav.run_if(on_message::<AppExit>));
+    }
+}
+
+fn open_spacenav(mut state: ResMut<SpaceNavState>) {
+    // SAFETY: spnav_open has no preconditions; it fails if spacenavd isn't running
+    state.connected = unsafe { spnav_open() } != -1;
+    if state.connected {
+        info!("SpaceMouse connected through spacenavd");
+    } else {
+        debug!("spacenavd not available, SpaceMouse navigation disabled");
+    }
+}
+
+fn close_spacenav(mut state: ResMut<SpaceNavState>) {
+    if state.connected {
+        // SAFETY: only called after a successful spnav_open
+        unsafe { spnav_close() };
+        state.connected = false;
+    }
+}
+
+/// Drain pending spacenavd events and send the held motion
+fn spacenav_navigation(
+    time: Res<Time>,
+    settings: Res<NavigationSettings>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut state: ResMut<SpaceNavState>,
+    mut camera_events: MessageWriter<CameraCommandEvent>,
+    mut nudge_events: MessageWriter<GizmoNudgeEvent>,
+) {
+    if !state.connected {
+        return;
+    }
+
+    let mut event = SpnavEvent { kind: 0 };
+    // SAFETY: `event` is a valid, writable spnav_event for the call
+    while unsafe { spnav_poll_event(&mut event) } != 0 {
+        // SAFETY: `kind` is the first field of every variant and tells which one was written
+        match unsafe { event.kind } {
+            SPNAV_EVENT_MOTION => {
+                let motion = unsafe { event.motion };
+                state.translation = axes(motion.x, motion.y, motion.z);
+                state.rotation = axes(motion.rx, motion.ry, motion.rz);
+            }
+            SPNAV_EVENT_BUTTON => {
+                let button = unsafe { event.button };
+                if button.bnum == 0 {
+                    state.nudge = button.press != 0;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let settings = &settings.0;
+    if !settings.enabled || mouse_buttons.get_pressed().next().is_some() {
+        return;
+    }
+
+    // Twist (y) and tilt (x) orbit, sliding pans, pushing/pulling zooms
+    let motion = NavigationMotion {
+        orbit: Vec2::new(-state.rotation.y, state.rotation.x),
+        pan: state.translation.truncate(),
+        zoom: -state.translation.z,
+        nudge: state.nudge,
+    };
+    send_navigation(
+        motion,
+        settings,
+        time.delta_secs(),
+        &mut camera_events,
+        &mut nudge_events,
+    );
+}
+
+fn axes(x: c_int, y: c_int, z: c_int) -> Vec3 {
+    (Vec3::new(x as f32, y as f32, z as f32) / FULL_DEFLECTION).clamp(Vec3::NEG_ONE, Vec3::ONE)
+}
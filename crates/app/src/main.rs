@@ -1,8 +1,10 @@
 //! Pentimento - Bevy + Svelte Compositing Desktop Application
 
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy::window::WindowResolution;
-use pentimento_config::{DEFAULT_HEIGHT, DEFAULT_WIDTH, DisplayConfig};
+use pentimento_config::{Keymap, SettingsFile};
 
 #[cfg(feature = "wireframe")]
 use bevy::render::{
@@ -11,22 +13,54 @@ use bevy::render::{
     settings::{RenderCreation, WgpuSettings},
 };
 
+mod autosave;
+#[cfg(feature = "collab")]
+mod collab;
 mod config;
 mod embedded_ui;
+#[cfg(test)]
+mod headless_tests;
 mod input;
+mod query;
 mod render;
+mod window_close;
+mod window_state;
 
-use config::{CompositeMode, PentimentoConfig};
+use autosave::{AutosaveConfig, AutosavePlugin};
+use clap::Parser;
+use config::{Cli, CompositeMode, PentimentoConfig};
+use input::{InputPlayer, InputRecorder, Recording, RecordingHeader};
 use pentimento_scene::ScenePlugin;
+use query::QueryRouterPlugin;
+use window_close::WindowClosePlugin;
+use window_state::{WindowStatePlugin, WindowStateTracker};
 
 fn main() {
-    // Parse configuration from environment
-    let config = PentimentoConfig::default();
+    // Parse configuration from the command line (with env var fallbacks)
+    let cli = Cli::parse();
+    if let Err(message) = cli.validate() {
+        eprintln!("error: {message}");
+        std::process::exit(2);
+    }
+    let config = PentimentoConfig::from_cli(&cli);
+
+    // Recording and replaying input start the app the same way every time
+    // (see input::replay)
+    let recording = cli.replay_input.as_deref().map(|path| {
+        Recording::load(path).unwrap_or_else(|message| {
+            eprintln!("error: {message}");
+            std::process::exit(2);
+        })
+    });
+    let fixed_startup = recording.is_some() || cli.record_input.is_some();
 
     info!(
         "Starting Pentimento with {:?} compositing mode",
         config.composite_mode
     );
+    if let Some(url) = &config.diffusion_server_url {
+        info!("Using diffusion server at {}", url);
+    }
 
     // Initialize GTK for webview on Linux (needed for both modes)
     // Note: For CEF mode, GTK is not strictly required, but we initialize it
@@ -36,16 +70,70 @@ fn main() {
         gtk::init().expect("Failed to initialize GTK");
     }
 
-    // Display configuration - single source of truth for window size
-    let display_config = DisplayConfig::default();
+    // Restore the last window geometry unless --reset-window was passed
+    // (recovery path for a window saved on a now-disconnected monitor)
+    if cli.reset_window {
+        info!("--reset-window: ignoring saved window geometry");
+    }
+    let settings = SettingsFile::load_or_default();
+
+    // Hotkeys: defaults plus the user's overrides from the settings file
+    let (keymap, keymap_errors) = Keymap::from_overrides(&settings.keymap);
+    for e in &keymap_errors {
+        warn!("Ignoring keymap override: {}", e);
+    }
+
+    // Autosave interval from the settings file
+    let mut autosave_config = AutosaveConfig::from_settings(&settings);
+    if fixed_startup {
+        autosave_config.interval = Duration::ZERO;
+    }
+
+    // Face budget for subdivision previews
+    #[cfg(feature = "selection")]
+    let subdivision_settings = pentimento_scene::SubdivisionSettings::from_settings(&settings);
+
+    // Memory budget for canvas stroke logs and undo stacks
+    let history_budget = pentimento_scene::HistoryBudget::from_settings(&settings);
+
+    let window_state = WindowStateTracker::new(settings, cli.reset_window || fixed_startup);
+
+    // Display configuration - single source of truth for window size.
+    // Explicit --width/--height/--scale take precedence over the saved geometry.
+    let mut display_config = window_state.display_config();
+    if let Some(width) = cli.width {
+        display_config.width = width;
+    }
+    if let Some(height) = cli.height {
+        display_config.height = height;
+    }
+    if let Some(scale) = cli.scale {
+        display_config.scale = scale;
+    }
+    if let Some(recording) = &recording {
+        display_config.width = recording.header.width;
+        display_config.height = recording.header.height;
+        display_config.scale = recording.header.scale;
+    }
+    let recorder = cli.record_input.as_deref().map(|path| {
+        InputRecorder::create(path, &RecordingHeader::new(&display_config)).unwrap_or_else(|e| {
+            eprintln!(
+                "error: can't create input recording {}: {e}",
+                path.display()
+            );
+            std::process::exit(2);
+        })
+    });
 
     // Configure window based on compositing mode
     let window_config = Window {
         title: "Pentimento".into(),
-        // Force scale factor to 1.0 to prevent winit from incorrectly guessing HiDPI.
-        // This ensures 1:1 pixel mapping between logical and physical coordinates.
-        resolution: WindowResolution::new(DEFAULT_WIDTH, DEFAULT_HEIGHT)
-            .with_scale_factor_override(1.0),
+        // Override the scale factor (1.0 unless --scale is given) to prevent winit
+        // from incorrectly guessing HiDPI. At 1.0 this ensures 1:1 pixel mapping
+        // between logical and physical coordinates.
+        resolution: WindowResolution::new(display_config.width, display_config.height)
+            .with_scale_factor_override(display_config.scale),
+        position: window_state.initial_position(),
         present_mode: bevy::window::PresentMode::AutoVsync,
         // Transparent window helps with overlay mode blending
         transparent: config.composite_mode == CompositeMode::Overlay,
@@ -54,7 +142,14 @@ fn main() {
 
     let mut app = App::new();
 
-    app.insert_resource(config).insert_resource(display_config);
+    app.insert_resource(config)
+        .insert_resource(display_config)
+        .insert_resource(window_state)
+        .insert_resource(keymap)
+        .insert_resource(autosave_config)
+        .insert_resource(history_budget);
+    #[cfg(feature = "selection")]
+    app.insert_resource(subdivision_settings);
 
     // Configure plugins with optional wireframe support
     #[cfg(feature = "wireframe")]
@@ -63,6 +158,8 @@ fn main() {
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(window_config),
+                    // Close requests go to the UI first (see window_close)
+                    close_when_requested: false,
                     ..default()
                 })
                 .set(bevy::log::LogPlugin {
@@ -85,6 +182,8 @@ fn main() {
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(window_config),
+                    // Close requests go to the UI first (see window_close)
+                    close_when_requested: false,
                     ..default()
                 })
                 .set(bevy::log::LogPlugin {
@@ -97,5 +196,25 @@ fn main() {
     app.add_plugins(ScenePlugin)
         .add_plugins(render::RenderPlugin)
         .add_plugins(input::InputPlugin)
-        .run();
+        .add_plugins(input::NavigationDevicePlugin)
+        .add_plugins(WindowStatePlugin)
+        .add_plugins(WindowClosePlugin)
+        .add_plugins(AutosavePlugin)
+        .add_plugins(QueryRouterPlugin);
+    #[cfg(feature = "collab")]
+    app.add_plugins(collab::CollabPlugin);
+
+    if let (Some(recorder), Some(path)) = (recorder, &cli.record_input) {
+        info!("Recording input to {}", path.display());
+        app.insert_resource(recorder);
+    }
+    if let Some(recording) = recording {
+        info!("Replaying {} frames of input", recording.frames.len());
+        app.insert_resource(InputPlayer::new(recording));
+    }
+    if fixed_startup {
+        app.add_plugins(input::InputReplayPlugin);
+    }
+
+    app.run();
 }
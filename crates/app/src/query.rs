@@ -0,0 +1,233 @@
+//! Request/response queries from the UI
+//!
+//! The UI sends `UiToBevy::Query` with a `request_id` of its choosing and
+//! awaits the `BevyToUi::QueryResult` carrying the same ID. The IPC handlers
+//! turn each query into a `QueryRequest` message, and `route_queries`
+//! answers every request it reads exactly once, with an `Err` for anything
+//! it can't answer, so the UI never waits on a reply that won't come.
+//!
+//! To let the UI ask something new, add a `QueryKind` variant (documenting
+//! the JSON it answers with) and a match arm in `route_queries` instead of
+//! another ad-hoc message pair.
+
+use bevy::ecs::system::SystemParam;
+#[cfg(feature = "selection")]
+use bevy::picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings};
+use bevy::prelude::*;
+use pentimento_ipc::{BevyToUi, QueryKind, SceneInfo};
+#[cfg(feature = "selection")]
+use pentimento_ipc::{MaterialProperties, TextureSlot};
+#[cfg(not(feature = "selection"))]
+use pentimento_ipc::{SceneObject, Transform3D};
+#[cfg(feature = "selection")]
+use pentimento_scene::{MainCamera, SceneObjects, Selectable};
+use pentimento_scene::{OutboundUiMessages, SceneLights, SceneMeasurements};
+#[cfg(feature = "selection")]
+use serde_json::json;
+
+/// A `UiToBevy::Query` waiting for its answer
+#[derive(Message, Debug, Clone)]
+pub struct QueryRequest {
+    pub request_id: u64,
+    pub query: QueryKind,
+}
+
+pub struct QueryRouterPlugin;
+
+impl Plugin for QueryRouterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<QueryRequest>()
+            .add_systems(Update, route_queries);
+    }
+}
+
+/// Read access to everything `SceneInfo` is built from
+#[derive(SystemParam)]
+pub struct SceneInfoSource<'w, 's> {
+    // With selection, objects have the IDs that object commands take
+    #[cfg(feature = "selection")]
+    scene_objects: SceneObjects<'w, 's>,
+    #[cfg(not(feature = "selection"))]
+    objects: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Name,
+            &'static Transform,
+            &'static Visibility,
+        ),
+        With<Mesh3d>,
+    >,
+    scene_lights: SceneLights<'w, 's>,
+    scene_measurements: SceneMeasurements<'w, 's>,
+}
+
+impl SceneInfoSource<'_, '_> {
+    pub fn scene_info(&self) -> SceneInfo {
+        #[cfg(feature = "selection")]
+        let objects = self.scene_objects.infos();
+        #[cfg(not(feature = "selection"))]
+        let objects = self
+            .objects
+            .iter()
+            .map(|(entity, name, transform, visibility)| SceneObject {
+                id: entity.to_bits().to_string(),
+                name: name.to_string(),
+                transform: Transform3D {
+                    position: transform.translation.to_array(),
+                    rotation: transform.rotation.to_array(),
+                    scale: transform.scale.to_array(),
+                },
+                material_id: None,
+                visible: *visibility != Visibility::Hidden,
+                locked: false,
+                parent_id: None,
+                subdivision_levels: 0,
+                wireframe: None,
+                instanced: false,
+            })
+            .collect();
+
+        SceneInfo {
+            objects,
+            lights: self.scene_lights.infos(),
+            #[cfg(feature = "selection")]
+            wireframe: self.scene_objects.wireframe(),
+            #[cfg(feature = "selection")]
+            isolated: self.scene_objects.isolated(),
+            annotations: self.scene_measurements.infos(),
+            ..default()
+        }
+    }
+}
+
+/// What queries are answered from
+#[derive(SystemParam)]
+struct QueryContext<'w, 's> {
+    scene: SceneInfoSource<'w, 's>,
+    #[cfg(feature = "selection")]
+    selectables: Query<'w, 's, (Entity, &'static Selectable)>,
+    #[cfg(feature = "selection")]
+    ray_cast: MeshRayCast<'w, 's>,
+    #[cfg(feature = "selection")]
+    camera: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<MainCamera>>,
+    #[cfg(feature = "selection")]
+    mesh_materials: Query<'w, 's, &'static MeshMaterial3d<StandardMaterial>>,
+    #[cfg(feature = "selection")]
+    materials: Res<'w, Assets<StandardMaterial>>,
+}
+
+impl QueryContext<'_, '_> {
+    fn answer(&mut self, query: &QueryKind) -> Result<serde_json::Value, String> {
+        match query {
+            QueryKind::ObjectAtPoint { x, y } => self.object_at_point(Vec2::new(*x, *y)),
+            QueryKind::MaterialProperties { material_id } => self.material_properties(material_id),
+            QueryKind::SceneInfo => to_json(&self.scene.scene_info()),
+        }
+    }
+
+    /// Nearest selectable object under `point`, picked like a click would be
+    #[cfg(feature = "selection")]
+    fn object_at_point(&mut self, point: Vec2) -> Result<serde_json::Value, String> {
+        let (camera, camera_transform) = self
+            .camera
+            .single()
+            .map_err(|_| "No main camera to pick from".to_string())?;
+        let ray = camera
+            .viewport_to_world(camera_transform, point)
+            .map_err(|_| format!("Point ({}, {}) is outside the viewport", point.x, point.y))?;
+
+        let selectables = &self.selectables;
+        let filter = |entity: Entity| selectables.contains(entity);
+        let settings = MeshRayCastSettings::default().with_filter(&filter);
+        let Some((entity, hit)) = self.ray_cast.cast_ray(ray, &settings).first() else {
+            return Ok(serde_json::Value::Null);
+        };
+        let (_, selectable) = selectables
+            .get(*entity)
+            .map_err(|_| "Picked entity is not selectable".to_string())?;
+
+        Ok(json!({
+            "object_id": selectable.id,
+            "position": hit.point.to_array(),
+            "distance": hit.distance,
+        }))
+    }
+
+    #[cfg(not(feature = "selection"))]
+    fn object_at_point(&mut self, _point: Vec2) -> Result<serde_json::Value, String> {
+        Err(selection_unavailable())
+    }
+
+    /// Materials aren't shared between objects yet, so a material ID is the
+    /// ID of the object that uses it (`SceneObject::material_id`)
+    #[cfg(feature = "selection")]
+    fn material_properties(&self, material_id: &str) -> Result<serde_json::Value, String> {
+        let (entity, _) = self
+            .selectables
+            .iter()
+            .find(|(_, selectable)| selectable.id == material_id)
+            .ok_or_else(|| format!("Unknown material {}", material_id))?;
+        let material = self
+            .mesh_materials
+            .get(entity)
+            .ok()
+            .and_then(|handle| self.materials.get(&handle.0))
+            .ok_or_else(|| format!("Unknown material {}", material_id))?;
+
+        let texture_slots = [
+            ("base_color", &material.base_color_texture),
+            ("metallic_roughness", &material.metallic_roughness_texture),
+            ("normal", &material.normal_map_texture),
+            ("emissive", &material.emissive_texture),
+            ("occlusion", &material.occlusion_texture),
+        ]
+        .into_iter()
+        .map(|(slot_name, texture)| TextureSlot {
+            slot_name: slot_name.to_string(),
+            texture_id: texture.as_ref().map(|handle| handle.id().to_string()),
+        })
+        .collect();
+
+        to_json(&MaterialProperties {
+            base_color: material.base_color.to_srgba().to_f32_array(),
+            metallic: material.metallic,
+            roughness: material.perceptual_roughness,
+            emissive: material.emissive.to_f32_array_no_alpha(),
+            texture_slots,
+        })
+    }
+
+    #[cfg(not(feature = "selection"))]
+    fn material_properties(&self, _material_id: &str) -> Result<serde_json::Value, String> {
+        Err(selection_unavailable())
+    }
+}
+
+#[cfg(not(feature = "selection"))]
+fn selection_unavailable() -> String {
+    "Objects can't be looked up in this build (selection feature disabled)".to_string()
+}
+
+fn to_json(value: &impl serde::Serialize) -> Result<serde_json::Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize query result: {}", e))
+}
+
+/// Answer each `QueryRequest` with exactly one `QueryResult`
+fn route_queries(
+    mut requests: MessageReader<QueryRequest>,
+    mut context: QueryContext,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for QueryRequest { request_id, query } in requests.read() {
+        let result = context.answer(query);
+        if let Err(e) = &result {
+            debug!("Query {} ({:?}) failed: {}", request_id, query, e);
+        }
+        outbound.send(BevyToUi::QueryResult {
+            request_id: *request_id,
+            result,
+        });
+    }
+}
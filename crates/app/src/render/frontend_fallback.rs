@@ -0,0 +1,159 @@
+//! Composite mode fallback chain
+//!
+//! When the configured mode can't start (CEF binaries missing, WebKitGTK not
+//! installed, ...), the next candidate mode is tried instead of leaving the
+//! app without a UI. Every failure is kept so it can be reported.
+
+use pentimento_frontend_core::FrontendError;
+
+use crate::config::CompositeMode;
+
+/// A mode that failed to start, with the reason
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeFailure {
+    pub mode: CompositeMode,
+    pub reason: String,
+}
+
+/// Result of walking a fallback chain
+#[derive(Debug)]
+pub struct FallbackOutcome<T> {
+    /// First mode that started, with what its factory returned
+    pub started: Option<(CompositeMode, T)>,
+    /// Modes that failed before it (every mode when nothing started)
+    pub failures: Vec<ModeFailure>,
+}
+
+/// Modes to try: `preferred` first, then each fallback that runs through the
+/// unified frontend pipeline in this build. No mode is tried twice.
+pub fn fallback_chain(preferred: CompositeMode, fallbacks: &[CompositeMode]) -> Vec<CompositeMode> {
+    let mut chain = vec![preferred];
+    for &mode in fallbacks {
+        if super::uses_frontend_pipeline(mode) && !chain.contains(&mode) {
+            chain.push(mode);
+        }
+    }
+    chain
+}
+
+/// Call `create` for each mode of `chain` until one succeeds
+pub fn try_modes<T>(
+    chain: &[CompositeMode],
+    mut create: impl FnMut(CompositeMode) -> Result<T, FrontendError>,
+) -> FallbackOutcome<T> {
+    let mut failures = Vec::new();
+    for &mode in chain {
+        match create(mode) {
+            Ok(value) => {
+                return FallbackOutcome {
+                    started: Some((mode, value)),
+                    failures,
+                };
+            }
+            Err(e) => failures.push(ModeFailure {
+                mode,
+                reason: e.to_string(),
+            }),
+        }
+    }
+    FallbackOutcome {
+        started: None,
+        failures,
+    }
+}
+
+/// One line per failed mode, e.g. `cef: CEF binaries not found`
+pub fn failure_summary(failures: &[ModeFailure]) -> String {
+    failures
+        .iter()
+        .map(|failure| format!("{}: {}", failure.mode.cli_name(), failure.reason))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail(reason: &str) -> FrontendError {
+        FrontendError::Backend(reason.to_string())
+    }
+
+    #[test]
+    fn test_chain_starts_with_preferred_and_skips_duplicates() {
+        let chain = fallback_chain(
+            CompositeMode::Overlay,
+            &[
+                CompositeMode::Capture,
+                CompositeMode::Overlay,
+                CompositeMode::Capture,
+            ],
+        );
+        assert_eq!(chain, vec![CompositeMode::Overlay, CompositeMode::Capture]);
+    }
+
+    #[test]
+    fn test_chain_skips_modes_outside_the_pipeline() {
+        let chain = fallback_chain(
+            CompositeMode::Capture,
+            &[
+                CompositeMode::Tauri,
+                CompositeMode::Dioxus,
+                CompositeMode::Cef,
+                CompositeMode::Overlay,
+            ],
+        );
+        let mut expected = vec![CompositeMode::Capture];
+        if CompositeMode::Cef.is_available() {
+            expected.push(CompositeMode::Cef);
+        }
+        expected.push(CompositeMode::Overlay);
+        assert_eq!(chain, expected);
+    }
+
+    #[test]
+    fn test_first_success_stops_the_chain() {
+        let mut tried = Vec::new();
+        let outcome = try_modes(
+            &[
+                CompositeMode::Cef,
+                CompositeMode::Capture,
+                CompositeMode::Overlay,
+            ],
+            |mode| {
+                tried.push(mode);
+                match mode {
+                    CompositeMode::Cef => Err(fail("CEF binaries not found")),
+                    _ => Ok(mode.cli_name()),
+                }
+            },
+        );
+
+        assert_eq!(tried, vec![CompositeMode::Cef, CompositeMode::Capture]);
+        assert_eq!(outcome.started, Some((CompositeMode::Capture, "capture")));
+        assert_eq!(outcome.failures.len(), 1);
+        assert_eq!(outcome.failures[0].mode, CompositeMode::Cef);
+        assert!(
+            outcome.failures[0]
+                .reason
+                .contains("CEF binaries not found")
+        );
+    }
+
+    #[test]
+    fn test_total_failure_records_every_mode() {
+        let outcome: FallbackOutcome<()> =
+            try_modes(&[CompositeMode::Capture, CompositeMode::Overlay], |mode| {
+                Err(fail(&format!("{} broken", mode.cli_name())))
+            });
+
+        assert!(outcome.started.is_none());
+        let modes: Vec<_> = outcome.failures.iter().map(|f| f.mode).collect();
+        assert_eq!(modes, vec![CompositeMode::Capture, CompositeMode::Overlay]);
+
+        let summary = failure_summary(&outcome.failures);
+        assert_eq!(summary.lines().count(), 2);
+        assert!(summary.starts_with("capture: "));
+        assert!(summary.contains("overlay broken"));
+    }
+}
@@ -6,7 +6,11 @@
 //!
 //! - `CaptureResult::Rgba` - Upload RGBA texture (WebKit/Capture mode)
 //! - `CaptureResult::Bgra` - Upload BGRA texture (CEF mode)
-//! - `CaptureResult::CompositorManaged` - No texture update (Overlay/Dioxus modes)
+//!
+//! CPU captures are tagged with their `AlphaMode8` and premultiplied on upload
+//! when they are straight; the overlay blends premultiplied alpha.
+//! - `CaptureResult::GpuExternal` - GPU copy from a shared texture (CEF with `cef-gpu`)
+//! - `CaptureResult::CompositorManaged` - No texture update (Overlay/Dioxus/Remote modes)
 //!
 //! # Supported Modes
 //!
@@ -15,24 +19,52 @@
 //! - **Cef**: CEF (Chromium) offscreen rendering with framebuffer capture
 //! - **Dioxus**: Native Rust UI with Vello GPU renderer (zero-copy, uses separate plugin)
 //! - **Tauri**: Bevy WASM in Tauri webview (requires separate build)
+//! - **Remote**: UI served to a desktop browser over HTTP/WebSocket
+//! - **Mock**: Scripted in-memory UI for headless integration tests (`mock` feature)
 
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use bevy::asset::RenderAssetUsages;
+use bevy::ecs::system::SystemParam;
+use bevy::image::ImageSampler;
 use bevy::picking::prelude::Pickable;
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
-use bevy::window::RawHandleWrapper;
-use pentimento_frontend_core::{CaptureResult, CompositeBackend, FrontendError};
-use pentimento_ipc::UiToBevy;
+use bevy::window::{RawHandleWrapper, WindowMoved, WindowOccluded};
+use pentimento_frontend_core::blob::BlobRegistry;
+#[cfg(any(test, feature = "mock"))]
+use pentimento_frontend_core::mock::MockUi;
+use pentimento_frontend_core::recovery::{CrashRecovery, MAX_RECOVERY_ATTEMPTS};
+use pentimento_frontend_core::{
+    BackendLifecycle, CaptureResult, CompositeBackend, ExternalTextureHandle, FrontendError,
+};
+use pentimento_ipc::{AppSettings, BevyToUi, MaterialCommand, PaintCommand, UiToBevy, ViewMode};
+#[cfg(feature = "mesh_painting")]
+use pentimento_scene::MeshPaintEvent;
+#[cfg(feature = "selection")]
+use pentimento_scene::OutlineSettings;
+#[cfg(feature = "sculpting")]
+use pentimento_scene::SculptEvent;
+#[cfg(feature = "wireframe")]
+use pentimento_scene::WireframeEvent;
 use pentimento_scene::{
-    AddObjectEvent, CanvasPlaneEvent, DepthViewSettings, OutboundUiMessages, SceneAmbientOcclusion,
-    SceneLighting,
+    AddObjectEvent, BrushTipEvent, CameraCommandEvent, CanvasFileEvent, CanvasPlacement,
+    CanvasPlaneEvent, CanvasResizeEvent, CanvasToolEvent, EditModeEvent, EditorMode,
+    GizmoCommandEvent, HistoryBudget, KeymapEvent, LightCommandEvent, MeasureEvent, NodeGraphEvent,
+    ObjectCommandEvent, OrbitSettings, OutboundUiMessages, PixelSelectionEvent, ProjectionStats,
+    ReferenceImageEvent, SceneAmbientOcclusion, SceneGrid, SceneLighting, TextureRegistryEvent,
+    TextureUploadStats, TurntableEvent, TurntableRequest, ViewModeSettings,
 };
 
+use crate::autosave::AutosaveEvent;
+#[cfg(feature = "collab")]
+use crate::collab::CollabEvent;
 use crate::config::{CompositeMode, PentimentoConfig};
 use crate::embedded_ui::UiAssets;
+use crate::input::NavigationSettings;
+use crate::query::{QueryRequest, SceneInfoSource};
+use crate::window_close::CloseResponseEvent;
 
 // Keep submodules for mode-specific initialization helpers
 #[cfg(feature = "dioxus")]
@@ -40,8 +72,38 @@ mod ui_blend_material;
 #[cfg(feature = "dioxus")]
 mod ui_dioxus;
 
+mod frontend_fallback;
+mod performance;
+mod power_saving;
+mod ui_blobs;
+mod ui_camera;
+#[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+mod ui_dmabuf;
+mod ui_hit_test;
+mod ui_premultiplied_material;
+mod ui_state;
+mod ui_surfaces;
+mod ui_texture_upload;
+
+use frontend_fallback::{FallbackOutcome, ModeFailure, failure_summary, fallback_chain, try_modes};
+pub use performance::PerformanceGovernor;
+#[cfg(test)]
+pub use performance::{ScenePresenter, SceneRenderTarget};
+pub use power_saving::PowerSaving;
+pub use ui_blobs::UiBlobs;
+#[cfg(test)]
+pub use ui_camera::{UI_CAMERA_ORDER, UiCamera};
 #[cfg(feature = "dioxus")]
 pub use ui_dioxus::DioxusRendererResource;
+pub use ui_hit_test::UiHitTest;
+use ui_premultiplied_material::{UiPremultipliedMaterial, UiPremultipliedMaterialPlugin};
+use ui_state::UiStateSource;
+pub use ui_surfaces::{Frontends, SurfaceHover, update_surface_hover};
+#[cfg(test)]
+pub use ui_surfaces::{SurfaceConfig, SurfacePlacement, create_surface};
+use ui_texture_upload::{
+    GpuImportStatus, UiTextureUpload, UiTextureUploadPlugin, UiUploadFrame, UiUploadPixels,
+};
 
 // ============================================================================
 // Unified Frontend Resource
@@ -51,12 +113,14 @@ pub use ui_dioxus::DioxusRendererResource;
 ///
 /// This abstraction allows the render system to work polymorphically with all
 /// capture-based backends (WebKit, CEF, Overlay). The system calls `poll()`,
-/// checks `is_ready()`, and handles `capture_if_dirty()` results uniformly.
+/// follows `lifecycle()`, and handles `capture_if_dirty()` results uniformly.
 pub struct FrontendResource {
     /// The backend implementation (boxed trait object for dynamic dispatch)
     pub backend: Box<dyn CompositeBackend>,
     /// Texture format used by this backend (RGBA or BGRA)
     pub texture_format: TextureFormat,
+    /// Mode the backend runs in (Capture when Overlay fell back)
+    pub mode: CompositeMode,
 }
 
 /// Resource holding the UI texture handle for capture-based modes.
@@ -69,23 +133,43 @@ pub struct UiTextureHandle {
 #[derive(Component)]
 pub struct UiOverlay;
 
+/// Marker component for the spinner shown until the UI is ready.
+#[derive(Component)]
+pub struct UiLoadingSpinner;
+
 /// Track frontend initialization and capture state.
 #[derive(Resource)]
 pub struct FrontendStatus {
     pub initialized: bool,
+    /// Whether `Initialize` has been queued for the current backend
+    pub initialize_sent: bool,
     pub first_capture_done: bool,
+    /// Whether the backend was told to stop producing shared GPU textures
+    pub gpu_capture_disabled: bool,
+    /// Time of the last capture (or safety heartbeat)
     pub last_capture: Instant,
+    /// Captures taken since the last `RenderStats` report
+    pub captures: u32,
+    /// Frames since the last `RenderStats` report where the UI was clean
+    pub skipped_captures: u32,
     /// Current composite mode
     pub mode: CompositeMode,
+    /// Backend lifecycle as of the last poll
+    pub lifecycle: BackendLifecycle,
 }
 
 impl Default for FrontendStatus {
     fn default() -> Self {
         Self {
             initialized: false,
+            initialize_sent: false,
             first_capture_done: false,
+            gpu_capture_disabled: false,
             last_capture: Instant::now(),
+            captures: 0,
+            skipped_captures: 0,
             mode: CompositeMode::default(),
+            lifecycle: BackendLifecycle::Initializing,
         }
     }
 }
@@ -96,10 +180,63 @@ pub struct LastWindowSize {
     pub width: u32,
     pub height: u32,
     pub scale_factor: f64,
+    /// UI render scale the backend was last sized with
+    pub ui_render_scale: f32,
+}
+
+/// Resolution of the UI texture relative to the window (`AppSettings::ui_render_scale`).
+///
+/// The backend renders at `window_size * scale` and the `UiOverlay` node stretches
+/// the texture back to the full window. Only applies to Capture mode; other
+/// backends always render at full resolution.
+#[derive(Resource)]
+pub struct UiRenderScale {
+    pub scale: f32,
+}
+
+impl Default for UiRenderScale {
+    fn default() -> Self {
+        Self { scale: 1.0 }
+    }
+}
+
+impl UiRenderScale {
+    /// Scale actually applied for `mode` (1.0 for backends that don't support it).
+    ///
+    /// Overlay renders directly to the screen, and CEF has no device scale
+    /// factor, so a smaller surface would change its CSS layout instead of
+    /// just its resolution.
+    pub fn effective(&self, mode: CompositeMode) -> f32 {
+        if mode == CompositeMode::Capture {
+            self.scale
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Scale a physical window size by the UI render scale (never below 1px).
+fn scaled_size(width: u32, height: u32, scale: f32) -> (u32, u32) {
+    (
+        ((width as f32) * scale).round().max(1.0) as u32,
+        ((height as f32) * scale).round().max(1.0) as u32,
+    )
 }
 
-/// Heartbeat interval for marking the UI dirty (forces periodic capture).
-const CAPTURE_HEARTBEAT_INTERVAL: Duration = Duration::from_millis(16);
+/// Safety-net heartbeat: force a capture if nothing marked the UI dirty for this long.
+///
+/// Captures are normally driven by dirty signals (UiDirty IPC, the injected DOM
+/// mutation/animation notifier, input, and resizes); this only covers missed ones.
+const CAPTURE_SAFETY_HEARTBEAT: Duration = Duration::from_millis(500);
+
+/// Interval between `RenderStats` messages sent to the UI.
+const RENDER_STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Diameter of the loading spinner in logical pixels.
+const LOADING_SPINNER_SIZE: f32 = 48.0;
+
+/// Loading spinner speed in turns per second.
+const LOADING_SPINNER_SPEED: f32 = 1.2;
 
 // ============================================================================
 // Factory Function
@@ -115,12 +252,28 @@ pub struct FrontendConfig {
     pub scale_factor: f64,
     /// Raw window handle (needed for overlay mode)
     pub window_handle: Option<raw_window_handle::RawWindowHandle>,
+    /// Blobs the page can fetch at `pentimento-blob://<id>`
+    pub blobs: BlobRegistry,
+    /// Port the UI is served on (remote mode)
+    pub remote_port: u16,
+    /// UI the mock backend is driven by (mock mode)
+    #[cfg(any(test, feature = "mock"))]
+    pub mock_ui: Option<MockUi>,
 }
 
+/// Handle to the UI a Mock mode frontend is created from.
+///
+/// Insert before `RenderPlugin` and keep a clone to script the UI and read
+/// back what the app sent it.
+#[cfg(any(test, feature = "mock"))]
+#[derive(Resource, Clone, Default)]
+pub struct MockFrontend(pub MockUi);
+
 /// Create the appropriate frontend backend based on the composite mode.
 ///
 /// Returns a `FrontendResource` containing the backend and its texture format,
-/// or an error if backend creation fails.
+/// or an error if backend creation fails. Overlay mode falls back to Capture
+/// mode on Wayland sessions it can't attach to; check `FrontendResource::mode`.
 ///
 /// # Arguments
 ///
@@ -137,13 +290,18 @@ pub fn create_frontend(
     match mode {
         CompositeMode::Capture => {
             // WebKit capture mode - RGBA format
-            let mut webview = pentimento_webview::OffscreenWebview::new(&config.html, config.size)
-                .map_err(|e| FrontendError::Backend(e.to_string()))?;
+            let mut webview = pentimento_webview::OffscreenWebview::new(
+                &config.html,
+                config.size,
+                config.blobs.clone(),
+            )
+            .map_err(|e| FrontendError::Backend(e.to_string()))?;
             webview.set_scale_factor(config.scale_factor);
 
             Ok(FrontendResource {
                 backend: Box::new(webview),
                 texture_format: TextureFormat::Rgba8UnormSrgb,
+                mode,
             })
         }
 
@@ -153,26 +311,42 @@ pub fn create_frontend(
                 FrontendError::Backend("Overlay mode requires window handle".into())
             })?;
 
-            let webview =
-                pentimento_webview::OverlayWebview::new(window_handle, &config.html, config.size)
-                    .map_err(|e| FrontendError::Backend(e.to_string()))?;
+            let webview = match pentimento_webview::OverlayWebview::new(
+                window_handle,
+                &config.html,
+                config.size,
+                config.blobs.clone(),
+            ) {
+                Ok(webview) => webview,
+                Err(pentimento_webview::WebviewError::WaylandUnsupported(reason)) => {
+                    warn!(
+                        "Overlay mode unavailable ({}), falling back to Capture mode",
+                        reason
+                    );
+                    return create_frontend(CompositeMode::Capture, config);
+                }
+                Err(e) => return Err(FrontendError::Backend(e.to_string())),
+            };
 
             // Overlay uses RGBA format for the placeholder texture (not actually used for capture)
             Ok(FrontendResource {
                 backend: Box::new(webview),
                 texture_format: TextureFormat::Rgba8UnormSrgb,
+                mode,
             })
         }
 
         #[cfg(feature = "cef")]
         CompositeMode::Cef => {
             // CEF mode - BGRA format (native Chromium format)
-            let webview = pentimento_webview::CefWebview::new(&config.html, config.size)
-                .map_err(|e| FrontendError::Backend(e.to_string()))?;
+            let webview =
+                pentimento_webview::CefWebview::new(&config.html, config.size, config.blobs)
+                    .map_err(|e| FrontendError::Backend(e.to_string()))?;
 
             Ok(FrontendResource {
                 backend: Box::new(webview),
                 texture_format: TextureFormat::Bgra8UnormSrgb,
+                mode,
             })
         }
 
@@ -195,6 +369,46 @@ pub fn create_frontend(
                 "Tauri mode requires building for WASM and running inside Tauri".into(),
             ))
         }
+
+        CompositeMode::Remote => {
+            // Remote mode - the browser renders the UI, so the texture stays empty
+            let backend = pentimento_frontend_remote::RemoteBackend::new(
+                &config.html,
+                config.size,
+                config.blobs,
+                config.remote_port,
+            )
+            .map_err(|e| FrontendError::Backend(e.to_string()))?;
+            info!(
+                "Open http://localhost:{} in a browser to use the UI",
+                backend.addr().port()
+            );
+
+            Ok(FrontendResource {
+                backend: Box::new(backend),
+                texture_format: TextureFormat::Rgba8UnormSrgb,
+                mode,
+            })
+        }
+
+        #[cfg(any(test, feature = "mock"))]
+        CompositeMode::Mock => {
+            // Mock mode - in-memory UI scripted by tests, RGBA like Capture
+            let ui = config.mock_ui.unwrap_or_default();
+            let mut backend = ui.backend(config.size);
+            backend.set_scale_factor(config.scale_factor);
+
+            Ok(FrontendResource {
+                backend: Box::new(backend),
+                texture_format: TextureFormat::Rgba8UnormSrgb,
+                mode,
+            })
+        }
+
+        #[cfg(not(any(test, feature = "mock")))]
+        CompositeMode::Mock => Err(FrontendError::Backend(
+            "Mock mode requires the 'mock' feature. Build with: cargo build --features mock".into(),
+        )),
     }
 }
 
@@ -203,84 +417,239 @@ pub fn create_frontend(
 // ============================================================================
 
 /// Initialize the frontend backend and UI overlay (startup system).
+///
+/// Walks the fallback chain when the configured mode fails to start, and shows
+/// a diagnostic in the Bevy window when no mode starts at all.
 pub fn setup_frontend(world: &mut World) {
     let config = world.resource::<PentimentoConfig>();
-    let mode = config.composite_mode;
+    let preferred = config.composite_mode;
+    let chain = fallback_chain(preferred, &config.fallback_modes);
 
     // Dioxus and Tauri modes use separate plugins
-    if matches!(mode, CompositeMode::Dioxus | CompositeMode::Tauri) {
+    if matches!(preferred, CompositeMode::Dioxus | CompositeMode::Tauri) {
         return;
     }
 
-    // Get window properties
-    let (width, height, scale_factor, window_handle) = {
-        let mut window_query = world.query::<(Entity, &Window)>();
-        let Some((window_entity, window)) = window_query.iter(world).next() else {
-            error!("No window found for frontend setup");
-            return;
-        };
+    if query_frontend_window(world, preferred).is_none() {
+        error!("No window found for frontend setup");
+        return;
+    }
 
-        let resolution = &window.resolution;
-        let size = (resolution.physical_width(), resolution.physical_height());
-        let scale = f64::from(resolution.scale_factor());
+    let FallbackOutcome { started, failures } = try_modes(&chain, |mode| {
+        let window = query_frontend_window(world, mode)
+            .ok_or_else(|| FrontendError::Backend("no window found".into()))?;
 
-        // Get raw window handle for overlay mode
-        let handle = if mode == CompositeMode::Overlay {
-            world
-                .get::<RawHandleWrapper>(window_entity)
-                .map(|wrapper| wrapper.get_window_handle())
-        } else {
-            None
-        };
+        info!(
+            "Setting up frontend ({:?} mode, {}x{} physical, scale {:.2})",
+            mode, window.size.0, window.size.1, window.scale_factor
+        );
+
+        let frontend = create_frontend(mode, startup_frontend_config(world, &window))?;
+        Ok((frontend, window))
+    });
 
-        (size.0, size.1, scale, handle)
+    for failure in &failures {
+        error!(
+            "Failed to create {:?} frontend: {}",
+            failure.mode, failure.reason
+        );
+    }
+
+    let Some((_, (frontend, window))) = started else {
+        error!("No UI backend could be started");
+        show_frontend_diagnostic(world, &failures);
+        return;
     };
 
-    info!(
-        "Setting up frontend ({:?} mode, {}x{} physical, scale {:.2})",
-        mode, width, height, scale_factor
+    // Input forwarding, hotkeys, and the UI texture follow the mode actually running
+    let mode = frontend.mode;
+    world.resource_mut::<PentimentoConfig>().composite_mode = mode;
+    install_frontend(world, mode, frontend, &window);
+
+    if mode != preferred {
+        let message = format!(
+            "Started in {} mode because {} mode failed:\n{}",
+            mode.cli_name(),
+            preferred.cli_name(),
+            failure_summary(&failures)
+        );
+        warn!("{}", message);
+        // Delivered once the fallback backend is ready
+        if let Some(mut outbound) = world.get_resource_mut::<OutboundUiMessages>() {
+            outbound.send(BevyToUi::Error {
+                code: "composite_fallback".to_string(),
+                message,
+            });
+        }
+    }
+
+    info!("Frontend initialized ({:?} mode)", mode);
+}
+
+/// Full-window text shown when no frontend backend could be started.
+#[derive(Component)]
+pub struct FrontendDiagnostic;
+
+/// Replace the missing UI with a plain-text list of why each mode failed.
+fn show_frontend_diagnostic(world: &mut World, failures: &[ModeFailure]) {
+    let text = format!(
+        "The UI could not be started.\n\n{}\n\nSee the log for details, or pick a \
+         different mode with --mode.",
+        failure_summary(failures)
     );
 
-    // Get HTML content
-    let html = UiAssets::get_html();
+    world.spawn((
+        Node {
+            width: Val::Vw(100.0),
+            height: Val::Vh(100.0),
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(24.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        ZIndex(i32::MAX),
+        Pickable::IGNORE,
+        FrontendDiagnostic,
+        children![(
+            Text::new(text),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        )],
+    ));
+}
+
+/// Window properties a frontend backend is created from.
+struct FrontendWindow {
+    /// Physical size (width, height)
+    size: (u32, u32),
+    scale_factor: f64,
+    /// Effective UI render scale for the mode being created
+    ui_render_scale: f32,
+    /// Raw window handle (only queried for overlay mode)
+    window_handle: Option<raw_window_handle::RawWindowHandle>,
+    /// Window position, if known
+    position: Option<IVec2>,
+}
+
+impl FrontendWindow {
+    /// Size of the backend surface (window size scaled by the UI render scale)
+    fn render_size(&self) -> (u32, u32) {
+        scaled_size(self.size.0, self.size.1, self.ui_render_scale)
+    }
+
+    fn frontend_config(
+        &self,
+        html: String,
+        blobs: BlobRegistry,
+        remote_port: u16,
+    ) -> FrontendConfig {
+        FrontendConfig {
+            html,
+            size: self.render_size(),
+            // Shrinking the device scale with the surface keeps the CSS layout unchanged
+            scale_factor: self.scale_factor * f64::from(self.ui_render_scale),
+            window_handle: self.window_handle,
+            blobs,
+            remote_port,
+            #[cfg(any(test, feature = "mock"))]
+            mock_ui: None,
+        }
+    }
+}
+
+/// Read the current window properties for creating a `mode` backend.
+fn query_frontend_window(world: &mut World, mode: CompositeMode) -> Option<FrontendWindow> {
+    let ui_render_scale = world
+        .get_resource::<UiRenderScale>()
+        .map_or(1.0, |scale| scale.effective(mode));
+
+    let mut window_query = world.query::<(Entity, &Window)>();
+    let (window_entity, window) = window_query.iter(world).next()?;
+
+    let resolution = &window.resolution;
+    let size = (resolution.physical_width(), resolution.physical_height());
+    let scale_factor = f64::from(resolution.scale_factor());
 
-    // Create the frontend backend
-    let frontend_config = FrontendConfig {
-        html,
-        size: (width, height),
+    let position = match window.position {
+        WindowPosition::At(position) => Some(position),
+        _ => None,
+    };
+
+    // Get raw window handle for overlay mode
+    let window_handle = if mode == CompositeMode::Overlay {
+        world
+            .get::<RawHandleWrapper>(window_entity)
+            .map(|wrapper| wrapper.get_window_handle())
+    } else {
+        None
+    };
+
+    Some(FrontendWindow {
+        size,
         scale_factor,
+        ui_render_scale,
         window_handle,
-    };
+        position,
+    })
+}
 
-    let frontend = match create_frontend(mode, frontend_config) {
-        Ok(f) => f,
-        Err(e) => {
-            error!("Failed to create frontend: {}", e);
-            return;
+/// Config for a backend started by the app itself (at startup or to recover),
+/// with the test's `MockUi` in Mock mode.
+fn startup_frontend_config(world: &World, window: &FrontendWindow) -> FrontendConfig {
+    let blobs = world.resource::<UiBlobs>().0.clone();
+    let remote_port = world.resource::<PentimentoConfig>().remote_port;
+    FrontendConfig {
+        #[cfg(any(test, feature = "mock"))]
+        mock_ui: world
+            .get_resource::<MockFrontend>()
+            .map(|mock| mock.0.clone()),
+        ..window.frontend_config(ui_html(world), blobs, remote_port)
+    }
+}
+
+/// HTML to load in the webview (--ui-url overrides the embedded UI).
+fn ui_html(world: &World) -> String {
+    match world.resource::<PentimentoConfig>().ui_url.as_deref() {
+        Some(url) => {
+            info!("Loading UI from {}", url);
+            UiAssets::url_html(url)
         }
-    };
+        None => UiAssets::get_html(),
+    }
+}
+
+/// Insert a newly created frontend and create its UI texture and overlay node.
+fn install_frontend(
+    world: &mut World,
+    mode: CompositeMode,
+    mut frontend: FrontendResource,
+    window: &FrontendWindow,
+) {
+    let (width, height) = window.render_size();
+
+    // A working backend replaces the startup failure diagnostic
+    let diagnostics: Vec<Entity> = world
+        .query_filtered::<Entity, With<FrontendDiagnostic>>()
+        .iter(world)
+        .collect();
+    for entity in diagnostics {
+        world.despawn(entity);
+    }
+
+    // Overlay windows start at the desktop origin; move to the Bevy window position
+    if let Some(position) = window.position {
+        frontend.backend.set_position(position.x, position.y);
+    }
 
     let texture_format = frontend.texture_format;
 
     // Insert the frontend resource (NonSend because GTK is single-threaded)
     world.insert_non_send_resource(frontend);
 
-    // Create the UI texture
-    let mut image = Image::new_fill(
-        Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        },
-        TextureDimension::D2,
-        &[0, 0, 0, 0], // Transparent
-        texture_format,
-        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-    );
-
-    image.texture_descriptor.usage =
-        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
-
+    let image = new_ui_texture(width, height, texture_format);
     let texture_handle = world.resource_mut::<Assets<Image>>().add(image);
 
     world.insert_resource(UiTextureHandle {
@@ -293,17 +662,21 @@ pub fn setup_frontend(world: &mut World) {
     });
 
     world.insert_resource(LastWindowSize {
-        width,
-        height,
-        scale_factor,
+        width: window.size.0,
+        height: window.size.1,
+        scale_factor: window.scale_factor,
+        ui_render_scale: window.ui_render_scale,
     });
 
-    // Create full-screen UI overlay node
+    // Create full-screen UI overlay node (premultiplied blending, see `ui_texture_upload`)
+    let material = UiPremultipliedMaterial {
+        texture: texture_handle,
+    };
+    let material = world
+        .resource_mut::<Assets<UiPremultipliedMaterial>>()
+        .add(material);
     world.spawn((
-        ImageNode {
-            image: texture_handle,
-            ..default()
-        },
+        MaterialNode(material),
         Node {
             width: Val::Vw(100.0),
             height: Val::Vh(100.0),
@@ -317,191 +690,826 @@ pub fn setup_frontend(world: &mut World) {
         Pickable::IGNORE,
     ));
 
-    info!("Frontend initialized ({:?} mode)", mode);
+    // Spins in the middle of the window until the UI is ready
+    world.spawn((
+        Node {
+            width: Val::Px(LOADING_SPINNER_SIZE),
+            height: Val::Px(LOADING_SPINNER_SIZE),
+            position_type: PositionType::Absolute,
+            left: Val::Percent(50.0),
+            top: Val::Percent(50.0),
+            margin: UiRect::all(Val::Px(-LOADING_SPINNER_SIZE / 2.0)),
+            border: UiRect::all(Val::Px(4.0)),
+            border_radius: BorderRadius::MAX,
+            ..default()
+        },
+        BorderColor {
+            top: Color::WHITE,
+            ..BorderColor::all(Color::srgba(1.0, 1.0, 1.0, 0.2))
+        },
+        UiTransform::IDENTITY,
+        ZIndex(i32::MAX),
+        UiLoadingSpinner,
+        Pickable::IGNORE,
+    ));
 }
 
-/// Update the UI texture from the frontend capture (runs every frame).
-///
-/// This system:
-/// 1. Polls the backend to process events and advance state
-/// 2. Checks if the backend is ready
-/// 3. Captures the framebuffer if dirty and uploads to the GPU texture
+/// Create an empty UI texture for a backend surface.
 ///
-/// Handles all capture result types polymorphically:
-/// - `Rgba`: Upload RGBA data directly
-/// - `Bgra`: Upload BGRA data (Arc-wrapped for zero-copy when possible)
-/// - `CompositorManaged`: No texture update needed (compositor handles blending)
-pub fn update_ui_texture(
-    frontend_res: Option<NonSendMut<FrontendResource>>,
-    ui_texture: Option<Res<UiTextureHandle>>,
-    mut images: ResMut<Assets<Image>>,
-    mut status: ResMut<FrontendStatus>,
-) {
-    let Some(mut frontend) = frontend_res else {
-        return;
-    };
-    let Some(ui_texture) = ui_texture else {
-        return;
-    };
+/// It never holds pixel data in the main world: captures are written to the
+/// GPU texture by `UiTextureUploadPlugin` (wgpu zero-initializes it, so it
+/// starts out transparent).
+fn new_ui_texture(width: u32, height: u32, texture_format: TextureFormat) -> Image {
+    let mut image = Image::new_uninit(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        texture_format,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
 
-    // Poll the backend to process events and advance state machine
-    frontend.backend.poll();
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
 
-    // Check if backend is ready
-    if !frontend.backend.is_ready() {
-        return;
-    }
+    // Linear filtering keeps a downscaled UI texture from shimmering when stretched
+    image.sampler = ImageSampler::linear();
+    image
+}
 
-    if !status.initialized {
-        info!("Frontend ready ({:?} mode), enabling captures", status.mode);
-        status.initialized = true;
+/// Drop the current frontend, its overlay and spinner nodes, and its UI texture.
+fn teardown_frontend(world: &mut World) {
+    // Dropping the backend closes its webview/browser. The CEF context itself
+    // is process-global and stays initialized for the next CEF browser.
+    world.remove_non_send_resource::<FrontendResource>();
+
+    let overlays: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<UiOverlay>, With<UiLoadingSpinner>)>>()
+        .iter(world)
+        .collect();
+    for entity in overlays {
+        world.despawn(entity);
     }
 
-    // Periodic heartbeat to force capture (ensures UI updates are visible)
-    if status.last_capture.elapsed() >= CAPTURE_HEARTBEAT_INTERVAL {
-        // For backends that support marking dirty externally, we'd call it here
-        // Most backends handle this internally via their dirty flags
-        status.last_capture = Instant::now();
+    if let Some(mut upload) = world.get_resource_mut::<UiTextureUpload>() {
+        upload.frame = None;
     }
 
-    // Capture and upload texture if dirty
-    if let Some(capture_result) = frontend.backend.capture_if_dirty() {
-        match capture_result {
-            CaptureResult::Rgba(data, cap_width, cap_height) => {
-                // RGBA format (WebKit/Capture mode)
-                upload_texture_data(
-                    &mut images,
-                    &ui_texture.handle,
-                    data,
-                    cap_width,
-                    cap_height,
-                    &mut status,
-                );
-            }
-
-            CaptureResult::Bgra(arc_data, cap_width, cap_height) => {
-                // BGRA format (CEF mode) - unwrap Arc to get owned Vec
-                let bgra_data = Arc::try_unwrap(arc_data).unwrap_or_else(|arc| (*arc).clone());
-                upload_texture_data(
-                    &mut images,
-                    &ui_texture.handle,
-                    bgra_data,
-                    cap_width,
-                    cap_height,
-                    &mut status,
-                );
-            }
-
-            CaptureResult::CompositorManaged => {
-                // Compositor handles blending (Overlay mode)
-                // No texture upload needed
-            }
-        }
+    if let Some(ui_texture) = world.remove_resource::<UiTextureHandle>() {
+        world
+            .resource_mut::<Assets<Image>>()
+            .remove(&ui_texture.handle);
     }
 }
 
-/// Upload captured data to the Bevy texture.
-fn upload_texture_data(
-    images: &mut Assets<Image>,
-    handle: &Handle<Image>,
-    data: Vec<u8>,
-    width: u32,
-    height: u32,
-    status: &mut FrontendStatus,
-) {
-    if !status.first_capture_done {
-        let non_transparent = data.chunks(4).filter(|p| p.len() == 4 && p[3] > 0).count();
-        info!(
-            "First capture ({:?} mode): {}x{}, non-transparent pixels: {}",
-            status.mode, width, height, non_transparent
-        );
-        status.first_capture_done = true;
-    }
-
-    if let Some(image) = images.get_mut(handle) {
-        // Resize texture if dimensions changed
-        if image.width() != width || image.height() != height {
-            info!(
-                "Resizing texture from {}x{} to {}x{}",
-                image.width(),
-                image.height(),
-                width,
-                height
-            );
-            image.resize(Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            });
-        }
+/// Whether `mode` runs through the unified `FrontendResource` pipeline in this
+/// build (and can therefore be switched to at runtime).
+///
+/// Remote mode also uses the pipeline but is only chosen at startup: moving
+/// the UI out of the window into a browser tab is never a useful fallback.
+pub fn uses_frontend_pipeline(mode: CompositeMode) -> bool {
+    matches!(
+        mode,
+        CompositeMode::Capture | CompositeMode::Overlay | CompositeMode::Cef
+    ) && mode.is_available()
+}
 
-        // Copy pixel data
-        image.data = Some(data);
-    }
+/// Pending request to replace the running frontend with another composite mode.
+///
+/// Set by `UiToBevy::SetCompositeMode` or the debug hotkey and applied by
+/// `apply_composite_mode_switch` on the next frame.
+#[derive(Resource, Default)]
+pub struct CompositeModeSwitch {
+    pub requested: Option<CompositeMode>,
 }
 
-/// Handle window resize for the frontend.
-pub fn handle_frontend_resize(
-    frontend_res: Option<NonSendMut<FrontendResource>>,
-    ui_texture: Option<Res<UiTextureHandle>>,
-    mut images: ResMut<Assets<Image>>,
-    mut last_size: ResMut<LastWindowSize>,
-    status: Res<FrontendStatus>,
-    windows: Query<&Window>,
-) {
-    if !status.initialized {
+/// Hot-switch the frontend backend to the requested composite mode.
+///
+/// The new backend is created before the old one is torn down, so a failure
+/// leaves the previous mode running. `OutboundUiMessages` is untouched; queued
+/// messages are delivered once the new backend is ready (after `Initialize`).
+fn apply_composite_mode_switch(world: &mut World) {
+    let Some(mode) = world
+        .get_resource_mut::<CompositeModeSwitch>()
+        .and_then(|mut switch| switch.requested.take())
+    else {
         return;
-    }
+    };
 
-    let Some(mut frontend) = frontend_res else {
+    let current = world.resource::<PentimentoConfig>().composite_mode;
+    if mode == current {
         return;
-    };
-    let Some(ui_texture) = ui_texture else {
+    }
+
+    if !uses_frontend_pipeline(mode) {
+        report_switch_failure(
+            world,
+            mode,
+            "mode is not available for runtime switching in this build".to_string(),
+        );
         return;
-    };
-    let Ok(window) = windows.single() else {
+    }
+
+    let Some(window) = query_frontend_window(world, mode) else {
+        report_switch_failure(world, mode, "no window found".to_string());
         return;
     };
 
-    let width = window.resolution.physical_width();
-    let height = window.resolution.physical_height();
-    let scale_factor = f64::from(window.resolution.scale_factor());
+    info!("Switching composite mode: {:?} -> {:?}", current, mode);
 
-    // Check if size or scale changed
-    let size_changed = width != last_size.width || height != last_size.height;
-    let scale_changed = (scale_factor - last_size.scale_factor).abs() > f64::EPSILON;
+    let blobs = world.resource::<UiBlobs>().0.clone();
+    let remote_port = world.resource::<PentimentoConfig>().remote_port;
+    let config = window.frontend_config(ui_html(world), blobs, remote_port);
+    let frontend = match create_frontend(mode, config) {
+        Ok(f) => f,
+        Err(e) => {
+            report_switch_failure(world, mode, e.to_string());
+            return;
+        }
+    };
 
-    if !size_changed && !scale_changed {
+    // A fallback to the running mode leaves it in place
+    if frontend.mode == current {
+        report_switch_failure(world, mode, "not supported in this session".to_string());
         return;
     }
+    let mode = frontend.mode;
 
-    if width == 0 || height == 0 {
+    teardown_frontend(world);
+    install_frontend(world, mode, frontend, &window);
+
+    // Input forwarding and hotkeys follow the active mode
+    world.resource_mut::<PentimentoConfig>().composite_mode = mode;
+
+    info!("Frontend switched to {:?} mode", mode);
+}
+
+/// Log a failed mode switch and tell the UI (still served by the previous backend).
+fn report_switch_failure(world: &mut World, mode: CompositeMode, reason: String) {
+    error!("Failed to switch to {:?} mode: {}", mode, reason);
+    if let Some(mut outbound) = world.get_resource_mut::<OutboundUiMessages>() {
+        outbound.send(BevyToUi::Error {
+            code: "composite_switch_failed".to_string(),
+            message: format!("Could not switch to {} mode: {}", mode.cli_name(), reason),
+        });
+    }
+}
+
+/// Crash recovery for backends that report `BackendLifecycle::Error`.
+///
+/// Outlives the backend (unlike `FrontendStatus`), so attempts add up across
+/// restarts until `MAX_RECOVERY_ATTEMPTS` is exceeded.
+#[derive(Resource, Default)]
+pub struct FrontendRecovery {
+    recovery: CrashRecovery,
+    /// Whether a restart is scheduled
+    restart_pending: bool,
+}
+
+/// Restart a failed backend in the same mode after a backoff.
+///
+/// Backends that reload a crashed page themselves only report `Error` once
+/// they give up, so this is the next step: a fresh backend. The UI is
+/// re-initialized once it is ready and told about the crash. When the
+/// attempts are used up the frontend is torn down and the diagnostic shown.
+fn recover_failed_frontend(world: &mut World) {
+    let failed = world
+        .get_resource::<FrontendStatus>()
+        .is_some_and(|status| status.lifecycle == BackendLifecycle::Error);
+    if !failed || world.get_non_send_resource::<FrontendResource>().is_none() {
+        return;
+    }
+    let mode = world.resource::<FrontendStatus>().mode;
+
+    let mut recovery = world.resource_mut::<FrontendRecovery>();
+    if !recovery.restart_pending {
+        if recovery.recovery.crashed(Instant::now()) {
+            warn!(
+                "{:?} frontend failed, restarting it (attempt {} of {})",
+                mode,
+                recovery.recovery.attempts(),
+                MAX_RECOVERY_ATTEMPTS
+            );
+            recovery.restart_pending = true;
+        } else {
+            error!(
+                "{:?} frontend failed more than {} times, giving up",
+                mode, MAX_RECOVERY_ATTEMPTS
+            );
+            teardown_frontend(world);
+            show_frontend_diagnostic(
+                world,
+                &[ModeFailure {
+                    mode,
+                    reason: format!("failed more than {} times", MAX_RECOVERY_ATTEMPTS),
+                }],
+            );
+        }
+        return;
+    }
+    if !recovery.recovery.reload_due(Instant::now()) {
+        return;
+    }
+    recovery.restart_pending = false;
+
+    let Some(window) = query_frontend_window(world, mode) else {
+        error!("No window found to restart the frontend in");
+        return;
+    };
+    let frontend = match create_frontend(mode, startup_frontend_config(world, &window)) {
+        Ok(frontend) => frontend,
+        // Still failed: the next frame counts another attempt
+        Err(e) => {
+            error!("Failed to restart {:?} frontend: {}", mode, e);
+            return;
+        }
+    };
+
+    let mode = frontend.mode;
+    teardown_frontend(world);
+    install_frontend(world, mode, frontend, &window);
+    world.resource_mut::<PentimentoConfig>().composite_mode = mode;
+
+    // Delivered after `Initialize` once the new backend is ready
+    let crash_error = world
+        .resource::<FrontendRecovery>()
+        .recovery
+        .crash_error("backend error");
+    world.resource_mut::<OutboundUiMessages>().send(crash_error);
+    info!("Frontend restarted ({:?} mode)", mode);
+}
+
+/// Spin the loading spinner, shown until the backend is first ready (or
+/// while it recovers from an error).
+fn animate_loading_spinner(
+    time: Res<Time>,
+    status: Res<FrontendStatus>,
+    mut spinners: Query<(&mut Visibility, &mut UiTransform), With<UiLoadingSpinner>>,
+) {
+    let loading = !matches!(
+        status.lifecycle,
+        BackendLifecycle::Ready | BackendLifecycle::Resizing { .. }
+    );
+    for (mut visibility, mut transform) in &mut spinners {
+        visibility.set_if_neq(if loading {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        if loading {
+            let turns = time.elapsed_secs() * LOADING_SPINNER_SPEED;
+            transform.rotation = Rot2::turn_fraction(turns.fract());
+        }
+    }
+}
+
+/// Send the initialization sequence once a (new) frontend is ready.
+///
+/// Runs after every backend creation and page reload, and when the UI sends
+/// `RequestInitialize`, so the UI is re-initialized after a composite mode
+/// switch or a reload as well as at startup. The order of the sequence is
+/// the contract documented in `pentimento_ipc::protocol`.
+#[allow(clippy::too_many_arguments)]
+fn send_initialize_on_ready(
+    mut status: ResMut<FrontendStatus>,
+    config: Res<PentimentoConfig>,
+    navigation: Res<NavigationSettings>,
+    #[cfg(feature = "selection")] outline: Res<OutlineSettings>,
+    grid: Res<SceneGrid>,
+    orbit: Res<OrbitSettings>,
+    governor: Res<PerformanceGovernor>,
+    scene: SceneInfoSource,
+    ui_state: UiStateSource,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    if !status.initialized || status.initialize_sent {
         return;
     }
+    status.initialize_sent = true;
+
+    let scene_info = scene.scene_info();
+    let mut settings = AppSettings {
+        diffusion_server_url: config.diffusion_server_url.clone(),
+        navigation: navigation.0.clone(),
+        show_grid: grid.visible,
+        orbit: orbit.0,
+        #[cfg(feature = "selection")]
+        outline: outline.to_ipc(),
+        ..default()
+    };
+    governor.report(&mut settings);
+
+    // Deliver before anything queued while the backend was loading, with the
+    // protocol version handshake right behind
+    let sequence = [
+        BevyToUi::Initialize {
+            scene_info,
+            settings,
+        },
+        BevyToUi::ProtocolVersion {
+            version: pentimento_ipc::PROTOCOL_VERSION,
+        },
+    ]
+    .into_iter()
+    .chain(ui_state.messages());
+    outbound.messages.splice(0..0, sequence);
+}
+
+/// Update the UI texture from the frontend capture (runs every frame).
+///
+/// This system:
+/// 1. Polls the backend to process events and advance state
+/// 2. Records the backend lifecycle, telling the UI about changes once it is up
+/// 3. Captures the framebuffer if dirty and uploads to the GPU texture
+///
+/// There is no per-frame heartbeat: captures only happen when the backend is
+/// dirty, plus a slow `CAPTURE_SAFETY_HEARTBEAT` fallback.
+///
+/// Handles all capture result types polymorphically:
+/// - `Rgba`: Upload RGBA data directly
+/// - `Bgra`: Upload BGRA data without copying (the Arc is handed to the render world)
+/// - `GpuExternal`: Copy a shared GPU texture (falls back to CPU captures if unsupported)
+/// - `CompositorManaged`: No texture update needed (compositor handles blending)
+///
+/// CPU captures also refresh the `UiHitTest` alpha mask.
+#[allow(clippy::too_many_arguments)]
+pub fn update_ui_texture(
+    frontend_res: Option<NonSendMut<FrontendResource>>,
+    ui_texture: Option<Res<UiTextureHandle>>,
+    mut images: ResMut<Assets<Image>>,
+    mut upload: ResMut<UiTextureUpload>,
+    gpu_import: Res<GpuImportStatus>,
+    mut status: ResMut<FrontendStatus>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    mut hit_test: ResMut<UiHitTest>,
+    power_saving: Res<PowerSaving>,
+) {
+    // The previous frame was extracted at the end of last frame; release our
+    // reference so the backend can reuse its buffer
+    if upload.frame.is_some() {
+        upload.bypass_change_detection().frame = None;
+    }
+
+    let Some(mut frontend) = frontend_res else {
+        return;
+    };
+    let Some(ui_texture) = ui_texture else {
+        return;
+    };
+
+    // The renderer can't use shared textures: switch to CPU captures. CEF
+    // reloads the page for this, so go through ready/Initialize again.
+    if gpu_import.is_unsupported() && !status.gpu_capture_disabled {
+        frontend.backend.disable_gpu_capture();
+        *status = FrontendStatus {
+            mode: status.mode,
+            gpu_capture_disabled: true,
+            ..default()
+        };
+    }
+
+    // Poll the backend to process events and advance state machine
+    frontend.backend.poll();
+
+    let lifecycle = frontend.backend.lifecycle();
+    if lifecycle != status.lifecycle {
+        debug!(
+            "Frontend lifecycle: {:?} -> {:?}",
+            status.lifecycle, lifecycle
+        );
+        // Before the first ready the page isn't there to listen
+        if status.initialized {
+            outbound.send(BevyToUi::BackendLifecycleChanged {
+                state: (&lifecycle).into(),
+            });
+        }
+        // Ready again after anything but a resize: the page was (re)loaded
+        // and starts without state
+        if lifecycle == BackendLifecycle::Ready
+            && !matches!(status.lifecycle, BackendLifecycle::Resizing { .. })
+        {
+            status.initialize_sent = false;
+        }
+        status.lifecycle = lifecycle;
+    }
+
+    // Loading, resizing, and failed backends have nothing to capture
+    if status.lifecycle != BackendLifecycle::Ready {
+        return;
+    }
+
+    if !status.initialized {
+        info!("Frontend ready ({:?} mode), enabling captures", status.mode);
+        status.initialized = true;
+    }
+
+    // The backend stays dirty until the window is back in the foreground
+    if power_saving.is_background() {
+        return;
+    }
+
+    // Slow safety-net heartbeat in case a dirty signal was missed
+    if status.last_capture.elapsed() >= CAPTURE_SAFETY_HEARTBEAT {
+        frontend.backend.mark_dirty();
+        status.last_capture = Instant::now();
+    }
+
+    // Capture and upload texture if dirty
+    let Some(capture_result) = frontend.backend.capture_if_dirty() else {
+        status.skipped_captures += 1;
+        return;
+    };
+
+    if !matches!(capture_result, CaptureResult::CompositorManaged) {
+        status.captures += 1;
+        status.last_capture = Instant::now();
+    }
+
+    match capture_result {
+        CaptureResult::Rgba(data, cap_width, cap_height, alpha) => {
+            // RGBA format (WebKit/Capture mode), straight alpha
+            hit_test.set_capture(&data, cap_width, cap_height);
+            upload_texture_data(
+                &mut images,
+                &mut upload,
+                &ui_texture.handle,
+                UiUploadPixels::premultiplied(Arc::new(data), alpha),
+                cap_width,
+                cap_height,
+                &mut status,
+            );
+        }
+
+        CaptureResult::Bgra(arc_data, cap_width, cap_height, alpha) => {
+            // BGRA format (CEF mode) - already premultiplied, so the Arc is
+            // shared with the render world, not copied
+            hit_test.set_capture(&arc_data, cap_width, cap_height);
+            upload_texture_data(
+                &mut images,
+                &mut upload,
+                &ui_texture.handle,
+                UiUploadPixels::premultiplied(arc_data, alpha),
+                cap_width,
+                cap_height,
+                &mut status,
+            );
+        }
+
+        CaptureResult::GpuExternal(handle) => {
+            // Shared GPU texture (CEF with cef-gpu) - copied on the GPU,
+            // so input goes by the layout regions
+            hit_test.clear_capture();
+            let (width, height) = external_texture_size(&handle);
+            upload_texture_data(
+                &mut images,
+                &mut upload,
+                &ui_texture.handle,
+                UiUploadPixels::External(handle),
+                width,
+                height,
+                &mut status,
+            );
+        }
+
+        CaptureResult::CompositorManaged => {
+            // Compositor handles blending (Overlay mode)
+            // No texture upload needed
+            hit_test.clear_capture();
+        }
+    }
+}
+
+/// Frame counter for the current `RenderStats` interval.
+#[derive(Resource)]
+pub struct RenderStatsWindow {
+    started: Instant,
+    frames: u32,
+}
+
+impl Default for RenderStatsWindow {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            frames: 0,
+        }
+    }
+}
+
+impl RenderStatsWindow {
+    /// Count a frame, and once `RENDER_STATS_INTERVAL` has passed start a new
+    /// window, returning the seconds and frames of the one that ended
+    fn tick(&mut self) -> Option<(f32, u32)> {
+        self.frames += 1;
+
+        let elapsed = self.started.elapsed();
+        if elapsed < RENDER_STATS_INTERVAL {
+            return None;
+        }
+
+        let frames = self.frames;
+        *self = Self::default();
+        Some((elapsed.as_secs_f32(), frames))
+    }
+}
+
+/// Triangles in the meshes some camera can see this frame.
+///
+/// Reported in `RenderStats`, e.g. to show how much sculpt chunk LOD saves.
+#[derive(SystemParam)]
+pub struct VisibleTriangles<'w, 's> {
+    meshes: Res<'w, Assets<Mesh>>,
+    visible: Query<'w, 's, (&'static Mesh3d, &'static ViewVisibility)>,
+}
+
+impl VisibleTriangles<'_, '_> {
+    pub fn count(&self) -> u32 {
+        self.visible
+            .iter()
+            .filter(|(_, visibility)| visibility.get())
+            .filter_map(|(mesh, _)| self.meshes.get(&mesh.0))
+            .map(|mesh| {
+                let vertices = mesh.indices().map_or(mesh.count_vertices(), |i| i.len());
+                (vertices / 3) as u32
+            })
+            .sum()
+    }
+}
+
+/// Send frame rate and capture counters to the UI once per `RENDER_STATS_INTERVAL`.
+#[allow(clippy::too_many_arguments)]
+fn send_render_stats(
+    mut window: ResMut<RenderStatsWindow>,
+    mut status: ResMut<FrontendStatus>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    projection_stats: Option<ResMut<ProjectionStats>>,
+    upload_stats: Option<ResMut<TextureUploadStats>>,
+    governor: Option<Res<PerformanceGovernor>>,
+    history_budget: Option<Res<HistoryBudget>>,
+    last_size: Res<LastWindowSize>,
+    power_saving: Res<PowerSaving>,
+    triangles: VisibleTriangles,
+) {
+    // Start a fresh window when back in the foreground
+    if power_saving.is_background() {
+        *window = RenderStatsWindow::default();
+        return;
+    }
+    let Some((seconds, frames)) = window.tick() else {
+        return;
+    };
+
+    let captures_per_second = status.captures as f32 / seconds;
+    let projected_texels = projection_stats.map_or(0, |mut stats| stats.take_texels_reprojected());
+    let tiles_uploaded = upload_stats.map_or(0, |mut stats| stats.take_tiles_uploaded());
+    debug!(
+        "UI captures: {:.1}/s, skipped: {}",
+        captures_per_second, status.skipped_captures
+    );
+
+    outbound.send(BevyToUi::RenderStats {
+        fps: frames as f32 / seconds,
+        frame_time_ms: seconds * 1000.0 / frames as f32,
+        // Not tracked yet
+        draw_calls: 0,
+        triangles: triangles.count(),
+        captures_per_second,
+        skipped_captures: status.skipped_captures,
+        projected_texels_per_frame: projected_texels as f32 / frames as f32,
+        tiles_uploaded_per_frame: tiles_uploaded as f32 / frames as f32,
+        // Captured frontends have no Vello render to skip
+        ui_renders_skipped: 0,
+        render_scale: governor.map_or(1.0, |governor| governor.render_scale()),
+        scale_factor: last_size.scale_factor as f32,
+        history_bytes: history_budget
+            .as_ref()
+            .map_or(0, |budget| budget.used_bytes()),
+        history_budget_bytes: history_budget.map_or(0, |budget| budget.budget_bytes),
+    });
+
+    status.captures = 0;
+    status.skipped_captures = 0;
+}
+
+/// Queue captured data for upload to the UI texture.
+///
+/// The `Image` asset is only touched when the capture size changes, which makes
+/// Bevy recreate the GPU texture at the new size before the frame is written.
+fn upload_texture_data(
+    images: &mut Assets<Image>,
+    upload: &mut UiTextureUpload,
+    handle: &Handle<Image>,
+    pixels: UiUploadPixels,
+    width: u32,
+    height: u32,
+    status: &mut FrontendStatus,
+) {
+    if !status.first_capture_done {
+        match &pixels {
+            UiUploadPixels::Cpu(data) => {
+                let non_transparent = data.chunks(4).filter(|p| p.len() == 4 && p[3] > 0).count();
+                info!(
+                    "First capture ({:?} mode): {}x{}, non-transparent pixels: {}",
+                    status.mode, width, height, non_transparent
+                );
+            }
+            UiUploadPixels::External(_) => {
+                info!(
+                    "First capture ({:?} mode): {}x{}, shared GPU texture",
+                    status.mode, width, height
+                );
+            }
+        }
+        status.first_capture_done = true;
+    }
+
+    resize_ui_texture(images, handle, width, height);
+
+    upload.frame = Some(UiUploadFrame {
+        pixels,
+        width,
+        height,
+        image: handle.id(),
+    });
+}
+
+/// Match a UI texture to the size of the frame about to be written to it.
+fn resize_ui_texture(images: &mut Assets<Image>, handle: &Handle<Image>, width: u32, height: u32) {
+    // Check with `get` first: `get_mut` marks the asset modified and would
+    // recreate the GPU texture every frame
+    let size_changed = images
+        .get(handle)
+        .is_some_and(|image| image.width() != width || image.height() != height);
+
+    if size_changed && let Some(image) = images.get_mut(handle) {
+        info!(
+            "Resizing texture from {}x{} to {}x{}",
+            image.width(),
+            image.height(),
+            width,
+            height
+        );
+        image.resize(Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        });
+    }
+}
+
+/// Pixel size of a shared GPU texture.
+fn external_texture_size(handle: &ExternalTextureHandle) -> (u32, u32) {
+    match *handle {
+        #[cfg(target_os = "linux")]
+        ExternalTextureHandle::Dmabuf(ref dmabuf) => (dmabuf.width, dmabuf.height),
+    }
+}
+
+/// Handle window resize and UI render scale changes for the frontend.
+pub fn handle_frontend_resize(
+    frontend_res: Option<NonSendMut<FrontendResource>>,
+    ui_texture: Option<Res<UiTextureHandle>>,
+    mut images: ResMut<Assets<Image>>,
+    mut last_size: ResMut<LastWindowSize>,
+    status: Res<FrontendStatus>,
+    render_scale: Res<UiRenderScale>,
+    windows: Query<&Window>,
+) {
+    if !status.initialized {
+        return;
+    }
+
+    let Some(mut frontend) = frontend_res else {
+        return;
+    };
+    let Some(ui_texture) = ui_texture else {
+        return;
+    };
+    let Ok(window) = windows.single() else {
+        return;
+    };
+
+    let width = window.resolution.physical_width();
+    let height = window.resolution.physical_height();
+    let scale_factor = f64::from(window.resolution.scale_factor());
+    let ui_render_scale = render_scale.effective(status.mode);
+
+    // Check if size, scale, or UI render scale changed
+    let size_changed = width != last_size.width || height != last_size.height;
+    let scale_changed = (scale_factor - last_size.scale_factor).abs() > f64::EPSILON;
+    let render_scale_changed = (ui_render_scale - last_size.ui_render_scale).abs() > f32::EPSILON;
+
+    if !size_changed && !scale_changed && !render_scale_changed {
+        return;
+    }
+
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let (render_width, render_height) = scaled_size(width, height, ui_render_scale);
 
     info!(
-        "Window resized to {}x{} physical (scale {:.2}), updating frontend",
-        width, height, scale_factor
+        "Window resized to {}x{} physical (scale {:.2}), rendering UI at {}x{} (UI scale {:.2})",
+        width, height, scale_factor, render_width, render_height, ui_render_scale
     );
     last_size.width = width;
     last_size.height = height;
     last_size.scale_factor = scale_factor;
+    last_size.ui_render_scale = ui_render_scale;
 
-    // Resize the backend
-    frontend.backend.resize(width, height);
+    // Resize the backend (scale factor first, it is applied on resize)
+    frontend
+        .backend
+        .set_scale_factor(scale_factor * f64::from(ui_render_scale));
+    frontend.backend.resize(render_width, render_height);
 
     // Resize the texture (only if using capture-based mode)
     if !matches!(status.mode, CompositeMode::Overlay) {
         if let Some(image) = images.get_mut(&ui_texture.handle) {
             image.resize(Extent3d {
-                width,
-                height,
+                width: render_width,
+                height: render_height,
                 depth_or_array_layers: 1,
             });
         }
     }
 }
 
+/// Keep separate-window backends (Overlay) aligned with the Bevy window when it moves.
+pub fn sync_frontend_position(
+    mut moved_events: MessageReader<WindowMoved>,
+    frontend_res: Option<NonSendMut<FrontendResource>>,
+    status: Res<FrontendStatus>,
+) {
+    let Some(mut frontend) = frontend_res else {
+        moved_events.clear();
+        return;
+    };
+
+    // Only the latest position matters when several moves arrive in one frame
+    let Some(event) = moved_events.read().last() else {
+        return;
+    };
+
+    if status.mode == CompositeMode::Overlay {
+        frontend
+            .backend
+            .set_position(event.position.x, event.position.y);
+    }
+}
+
+/// Hide separate-window backends (Overlay) while the Bevy window is minimized or occluded.
+///
+/// Follows the parent's own window events, so it also works on Wayland where the
+/// overlay can't poll the parent's state.
+pub fn sync_frontend_visibility(
+    mut occluded_events: MessageReader<WindowOccluded>,
+    frontend_res: Option<NonSendMut<FrontendResource>>,
+    status: Res<FrontendStatus>,
+) {
+    let Some(mut frontend) = frontend_res else {
+        occluded_events.clear();
+        return;
+    };
+
+    let Some(event) = occluded_events.read().last() else {
+        return;
+    };
+
+    if status.mode == CompositeMode::Overlay {
+        frontend.backend.set_parent_visible(!event.occluded);
+    }
+}
+
+/// Tell the frontend when the window enters or leaves the background
+pub fn sync_frontend_background(
+    frontend_res: Option<NonSendMut<FrontendResource>>,
+    power_saving: Res<PowerSaving>,
+) {
+    if !power_saving.is_changed() {
+        return;
+    }
+    if let Some(mut frontend) = frontend_res {
+        frontend
+            .backend
+            .set_background(power_saving.is_background());
+    }
+}
+
+/// Shut the frontend down while the app is exiting.
+///
+/// Runs in `Last` on `AppExit` so the webview/browser closes before the window
+/// and GPU resources are torn down, instead of whenever the NonSend resource
+/// happens to be dropped.
+fn shutdown_frontend(frontend_res: Option<NonSendMut<FrontendResource>>) {
+    if let Some(mut frontend) = frontend_res {
+        info!("Shutting down {:?} frontend", frontend.mode);
+        frontend.backend.shutdown();
+    }
+}
+
 // ============================================================================
 // IPC Message Handling
 // ============================================================================
@@ -512,8 +1520,15 @@ pub fn handle_frontend_resize(
 /// messages (UI→Bevy) using the unified `FrontendResource` / `CompositeBackend`
 /// trait, so it works identically for all capture-based backends.
 fn handle_frontend_ipc_messages(world: &mut World) {
+    // Hold outbound messages until the backend is ready (e.g. during a mode switch)
+    let ready = world
+        .get_resource::<FrontendStatus>()
+        .is_some_and(|status| status.initialized);
+
     // Send outbound messages to the UI first
-    let outbound_msgs = {
+    let outbound_msgs = if !ready {
+        Vec::new()
+    } else {
         let Some(mut outbound) = world.get_resource_mut::<OutboundUiMessages>() else {
             return;
         };
@@ -539,59 +1554,536 @@ fn handle_frontend_ipc_messages(world: &mut World) {
         while let Some(msg) = frontend.backend.try_recv_from_ui() {
             msgs.push(msg);
         }
-        msgs
+        // Only the newest layout of the frame matters (see `Coalesce`)
+        pentimento_ipc::coalesce(msgs)
     };
+    // Recordings log them; replays swap in the recorded ones
+    let messages = crate::input::tap_ui_messages(world, messages);
 
-    // Process messages
     for msg in messages {
-        match msg {
-            UiToBevy::AddObject(request) => {
-                if let Some(mut events) =
-                    world.get_resource_mut::<bevy::ecs::message::Messages<AddObjectEvent>>()
-                {
-                    events.write(AddObjectEvent(request));
-                    info!("Dispatched AddObjectEvent from UI");
-                }
+        dispatch_ui_message(world, msg);
+    }
+}
+
+/// Apply one UI→Bevy message, from the main UI or a secondary surface.
+pub(crate) fn dispatch_ui_message(world: &mut World, msg: UiToBevy) {
+    match msg {
+        UiToBevy::AddObject(request) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<AddObjectEvent>>()
+            {
+                events.write(AddObjectEvent(request));
+                info!("Dispatched AddObjectEvent from UI");
             }
-            UiToBevy::AddPaintCanvas(request) => {
-                if let Some(mut events) =
-                    world.get_resource_mut::<bevy::ecs::message::Messages<CanvasPlaneEvent>>()
-                {
-                    events.write(CanvasPlaneEvent::CreateInFrontOfCamera {
-                        width: request.width.unwrap_or(1024),
-                        height: request.height.unwrap_or(1024),
-                    });
-                    info!("Dispatched CanvasPlaneEvent::CreateInFrontOfCamera from UI");
-                }
+        }
+        UiToBevy::AddPaintCanvas(request) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CanvasPlaneEvent>>()
+            {
+                events.write(CanvasPlaneEvent::CreateInFrontOfCamera {
+                    width: request.width.unwrap_or(1024),
+                    height: request.height.unwrap_or(1024),
+                    placement: CanvasPlacement::from_request(&request),
+                });
+                info!("Dispatched CanvasPlaneEvent::CreateInFrontOfCamera from UI");
             }
-            UiToBevy::UiDirty => {
-                // Already handled by dirty flag in webview
+        }
+        UiToBevy::UiDirty => {
+            // Already handled by dirty flag in webview
+        }
+        UiToBevy::LayoutUpdate(layout) => {
+            // Panels of the main UI take the mouse where there is no captured alpha
+            if let Some(mut hit_test) = world.get_resource_mut::<UiHitTest>() {
+                hit_test.set_layout(&layout);
             }
-            UiToBevy::UpdateLighting(settings) => {
-                if let Some(mut lighting) = world.get_resource_mut::<SceneLighting>() {
-                    lighting.settings = settings;
-                    info!("Updated lighting settings from UI");
-                }
+        }
+        #[cfg(feature = "sculpting")]
+        UiToBevy::SculptCommand(cmd) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<SculptEvent>>()
+            {
+                debug!("Sculpt command from UI: {:?}", cmd);
+                events.write(SculptEvent::from(cmd));
+            }
+        }
+        UiToBevy::MeasureCommand(cmd) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<MeasureEvent>>()
+            {
+                debug!("Measure command from UI: {:?}", cmd);
+                events.write(MeasureEvent(cmd));
             }
-            UiToBevy::UpdateAmbientOcclusion(settings) => {
-                if let Some(mut ao_resource) = world.get_resource_mut::<SceneAmbientOcclusion>() {
-                    ao_resource.update(settings);
-                    info!("Updated ambient occlusion settings from UI");
+        }
+        UiToBevy::SetEditMode { mode } => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<EditModeEvent>>()
+            {
+                debug!("Edit mode request from UI: {:?}", mode);
+                events.write(EditModeEvent {
+                    mode: EditorMode::from_ipc(mode),
+                    target: None,
+                });
+            }
+        }
+        UiToBevy::UpdateLighting(settings) => {
+            if let Some(mut lighting) = world.get_resource_mut::<SceneLighting>() {
+                lighting.settings = settings;
+                info!("Updated lighting settings from UI");
+            }
+        }
+        UiToBevy::UpdateAmbientOcclusion(settings) => {
+            if let Some(mut ao_resource) = world.get_resource_mut::<SceneAmbientOcclusion>() {
+                ao_resource.update(settings);
+                info!("Updated ambient occlusion settings from UI");
+            }
+        }
+        UiToBevy::SetDepthView { enabled } => {
+            if let Some(mut settings) = world.get_resource_mut::<ViewModeSettings>() {
+                settings.mode = if enabled {
+                    ViewMode::Depth
+                } else {
+                    ViewMode::Shaded
+                };
+            }
+        }
+        UiToBevy::SetViewMode { mode } => {
+            if let Some(mut settings) = world.get_resource_mut::<ViewModeSettings>() {
+                settings.mode = mode;
+            }
+        }
+        UiToBevy::UpdateSettings(settings) => {
+            // Applied by handle_frontend_resize on the next frame
+            if let Some(mut render_scale) = world.get_resource_mut::<UiRenderScale>() {
+                let scale = settings.clamped_ui_render_scale();
+                if (render_scale.scale - scale).abs() > f32::EPSILON {
+                    render_scale.scale = scale;
+                    info!("UI render scale set to {:.2}", scale);
                 }
             }
-            UiToBevy::SetDepthView { enabled } => {
-                if let Some(mut settings) = world.get_resource_mut::<DepthViewSettings>() {
-                    settings.enabled = enabled;
-                    info!(
-                        "Depth view mode: {}",
-                        if enabled { "enabled" } else { "disabled" }
+            if let Some(mut governor) = world.get_resource_mut::<PerformanceGovernor>() {
+                governor.apply_settings(&settings);
+            }
+            if let Some(mut navigation) = world.get_resource_mut::<NavigationSettings>() {
+                navigation.0 = settings.navigation;
+            }
+            if let Some(mut grid) = world.get_resource_mut::<SceneGrid>() {
+                grid.visible = settings.show_grid;
+            }
+            if let Some(mut orbit) = world.get_resource_mut::<OrbitSettings>() {
+                orbit.0 = settings.orbit;
+            }
+            #[cfg(feature = "selection")]
+            if let Some(mut outline) = world.get_resource_mut::<OutlineSettings>() {
+                *outline = OutlineSettings::from(&settings.outline);
+            }
+        }
+        UiToBevy::CameraCommand(cmd) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CameraCommandEvent>>()
+            {
+                events.write(CameraCommandEvent(cmd));
+            }
+        }
+        UiToBevy::GizmoCommand(cmd) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<GizmoCommandEvent>>()
+            {
+                events.write(GizmoCommandEvent(cmd));
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::ExportCanvas { path }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CanvasFileEvent>>()
+            {
+                events.write(CanvasFileEvent::Export {
+                    path: path.map(Into::into),
+                });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::ImportCanvas { path }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CanvasFileEvent>>()
+            {
+                events.write(CanvasFileEvent::Import { path: path.into() });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::ResizeCanvas {
+            width,
+            height,
+            anchor,
+        }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CanvasResizeEvent>>()
+            {
+                events.write(CanvasResizeEvent::Resize {
+                    width,
+                    height,
+                    anchor,
+                });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::CropCanvas {
+            x,
+            y,
+            width,
+            height,
+        }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CanvasResizeEvent>>()
+            {
+                events.write(CanvasResizeEvent::Crop {
+                    x,
+                    y,
+                    width,
+                    height,
+                });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::BeginPixelSelection { mode }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<PixelSelectionEvent>>()
+            {
+                events.write(PixelSelectionEvent::Begin { mode });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::TransformSelection {
+            translate,
+            rotate_deg,
+            scale,
+        }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<PixelSelectionEvent>>()
+            {
+                events.write(PixelSelectionEvent::Transform {
+                    translate: Vec2::from(translate),
+                    rotate_deg,
+                    scale: Vec2::from(scale),
+                });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::CommitSelection) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<PixelSelectionEvent>>()
+            {
+                events.write(PixelSelectionEvent::Commit);
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::CancelSelection) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<PixelSelectionEvent>>()
+            {
+                events.write(PixelSelectionEvent::Cancel);
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::SetBrushTip { source }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<BrushTipEvent>>()
+            {
+                events.write(BrushTipEvent::SetTip { source });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::SetBrushTipRotation { mode }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<BrushTipEvent>>()
+            {
+                events.write(BrushTipEvent::SetRotation { mode });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::Fill {
+            x,
+            y,
+            tolerance,
+            contiguous,
+        }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CanvasToolEvent>>()
+            {
+                events.write(CanvasToolEvent::Fill {
+                    position: Vec2::new(x, y),
+                    tolerance,
+                    contiguous,
+                });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::Gradient {
+            start,
+            end,
+            kind,
+            colors,
+        }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CanvasToolEvent>>()
+            {
+                events.write(CanvasToolEvent::Gradient {
+                    start: Vec2::from(start),
+                    end: Vec2::from(end),
+                    kind,
+                    colors,
+                });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::ArmCanvasTool { tool }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CanvasToolEvent>>()
+            {
+                events.write(CanvasToolEvent::Arm { tool });
+            }
+        }
+        #[cfg(feature = "mesh_painting")]
+        UiToBevy::PaintCommand(PaintCommand::SetMeshPaintChannel { channel }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<MeshPaintEvent>>()
+            {
+                events.write(MeshPaintEvent::SetTargetChannel { channel });
+            }
+        }
+        #[cfg(feature = "mesh_painting")]
+        UiToBevy::PaintCommand(PaintCommand::SetMeshPaintValue { value }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<MeshPaintEvent>>()
+            {
+                events.write(MeshPaintEvent::SetBrushValue { value });
+            }
+        }
+        #[cfg(feature = "mesh_painting")]
+        UiToBevy::PaintCommand(PaintCommand::SetNormalStrength { strength }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<MeshPaintEvent>>()
+            {
+                events.write(MeshPaintEvent::SetNormalStrength { strength });
+            }
+        }
+        #[cfg(feature = "mesh_painting")]
+        UiToBevy::PaintCommand(PaintCommand::SetMeshPaintTarget { target }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<MeshPaintEvent>>()
+            {
+                events.write(MeshPaintEvent::SetPaintTarget { target });
+            }
+        }
+        UiToBevy::PaintCommand(PaintCommand::ReanchorCanvas { plane_id }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CanvasPlaneEvent>>()
+            {
+                events.write(CanvasPlaneEvent::Reanchor { plane_id });
+            }
+        }
+        UiToBevy::RestoreAutosave { path } => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<AutosaveEvent>>()
+            {
+                info!("Restoring autosave {} from UI", path);
+                events.write(AutosaveEvent::Restore { path: path.into() });
+            }
+        }
+        UiToBevy::LightCommand(cmd) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<LightCommandEvent>>()
+            {
+                events.write(LightCommandEvent(cmd));
+            }
+        }
+        UiToBevy::NodeGraphUpdate(state) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<NodeGraphEvent>>()
+            {
+                events.write(NodeGraphEvent(state));
+            }
+        }
+        UiToBevy::ObjectCommand(cmd) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<ObjectCommandEvent>>()
+            {
+                events.write(ObjectCommandEvent(cmd));
+            }
+        }
+        UiToBevy::AddReferenceImage { path, mode } => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<ReferenceImageEvent>>()
+            {
+                events.write(ReferenceImageEvent::Add {
+                    path: path.into(),
+                    mode,
+                });
+            }
+        }
+        UiToBevy::SetReferenceImageOpacity { id, opacity } => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<ReferenceImageEvent>>()
+            {
+                events.write(ReferenceImageEvent::SetOpacity { id, opacity });
+            }
+        }
+        UiToBevy::MaterialCommand(MaterialCommand::AssignTexture {
+            material_id,
+            slot,
+            texture_id,
+        }) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<TextureRegistryEvent>>()
+            {
+                events.write(TextureRegistryEvent::Assign {
+                    material_id,
+                    slot,
+                    texture_id,
+                });
+            }
+        }
+        UiToBevy::DeleteTexture { texture_id } => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<TextureRegistryEvent>>()
+            {
+                events.write(TextureRegistryEvent::Delete { texture_id });
+            }
+        }
+        UiToBevy::RenderTurntable {
+            frames,
+            seconds,
+            width,
+            height,
+            path,
+        } => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<TurntableEvent>>()
+            {
+                events.write(TurntableEvent::Start(TurntableRequest {
+                    frames,
+                    seconds,
+                    width,
+                    height,
+                    path: path.into(),
+                }));
+            }
+        }
+        UiToBevy::CancelRender => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<TurntableEvent>>()
+            {
+                events.write(TurntableEvent::Cancel);
+            }
+        }
+        UiToBevy::SetCompositeMode { mode } => {
+            if let Some(mut switch) = world.get_resource_mut::<CompositeModeSwitch>() {
+                switch.requested = Some(mode.into());
+                info!("Composite mode switch to {:?} requested from UI", mode);
+            }
+        }
+        UiToBevy::SetKeybinding { action, chord } => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<KeymapEvent>>()
+            {
+                events.write(KeymapEvent::SetBinding { action, chord });
+            }
+        }
+        #[cfg(feature = "wireframe")]
+        UiToBevy::SetWireframe {
+            target,
+            enabled,
+            color,
+            depth_test,
+        } => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<WireframeEvent>>()
+            {
+                events.write(WireframeEvent {
+                    target,
+                    enabled,
+                    color,
+                    depth_test,
+                });
+            }
+        }
+        #[cfg(not(feature = "wireframe"))]
+        UiToBevy::SetWireframe { .. } => {
+            if let Some(mut outbound) = world.get_resource_mut::<OutboundUiMessages>() {
+                warn!("SetWireframe received, but the wireframe feature is disabled");
+                outbound.send(wireframe_unavailable_error());
+            }
+        }
+        UiToBevy::BlobConsumed { id } => {
+            if let Some(blobs) = world.get_resource::<UiBlobs>() {
+                blobs.consume(id);
+            }
+        }
+        UiToBevy::Query { request_id, query } => {
+            if let Some(mut requests) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<QueryRequest>>()
+            {
+                requests.write(QueryRequest { request_id, query });
+            }
+        }
+        #[cfg(feature = "collab")]
+        UiToBevy::Collab(cmd) => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CollabEvent>>()
+            {
+                events.write(CollabEvent(cmd));
+            }
+        }
+        #[cfg(not(feature = "collab"))]
+        UiToBevy::Collab(_) => {
+            if let Some(mut outbound) = world.get_resource_mut::<OutboundUiMessages>() {
+                warn!("Collab command received, but the collab feature is disabled");
+                outbound.send(collab_unavailable_error());
+            }
+        }
+        UiToBevy::CloseResponse { decision } => {
+            if let Some(mut events) =
+                world.get_resource_mut::<bevy::ecs::message::Messages<CloseResponseEvent>>()
+            {
+                events.write(CloseResponseEvent(decision));
+            }
+        }
+        UiToBevy::RequestInitialize => {
+            // Sent by `send_initialize_on_ready` on the next frame
+            if let Some(mut status) = world.get_resource_mut::<FrontendStatus>() {
+                info!("UI requested initialization");
+                status.initialize_sent = false;
+            }
+        }
+        UiToBevy::CompactHistory => {
+            if let Some(mut budget) = world.get_resource_mut::<HistoryBudget>() {
+                budget.request_compaction();
+            }
+        }
+        UiToBevy::ProtocolVersion { version } => {
+            match pentimento_ipc::protocol_mismatch_warning(version) {
+                Some(warning) => {
+                    warn!(
+                        "UI uses IPC protocol {}, app uses {}",
+                        version,
+                        pentimento_ipc::PROTOCOL_VERSION
                     );
+                    if let Some(mut outbound) = world.get_resource_mut::<OutboundUiMessages>() {
+                        outbound.send(warning);
+                    }
                 }
+                None => debug!("UI IPC protocol version {} matches", version),
             }
-            _ => {
-                debug!("Unhandled frontend IPC message: {:?}", msg);
-            }
         }
+        _ => {
+            debug!("Unhandled frontend IPC message: {:?}", msg);
+        }
+    }
+}
+
+/// Answer to `SetWireframe` in builds without the `wireframe` feature
+#[cfg(not(feature = "wireframe"))]
+pub(crate) fn wireframe_unavailable_error() -> BevyToUi {
+    BevyToUi::Error {
+        code: "wireframe_unavailable".to_string(),
+        message: "Wireframe overlays are not available in this build (wireframe feature disabled)"
+            .to_string(),
+    }
+}
+
+/// Answer to `Collab` in builds without the `collab` feature
+#[cfg(not(feature = "collab"))]
+pub(crate) fn collab_unavailable_error() -> BevyToUi {
+    BevyToUi::Error {
+        code: "collab_unavailable".to_string(),
+        message: "Shared sessions are not available in this build (collab feature disabled)"
+            .to_string(),
     }
 }
 
@@ -606,15 +2098,16 @@ impl Plugin for RenderPlugin {
         let config = app.world().resource::<PentimentoConfig>();
         let mode = config.composite_mode;
 
+        app.add_plugins((
+            ui_camera::UiCameraPlugin,
+            performance::PerformancePlugin,
+            power_saving::PowerSavingPlugin,
+        ));
+
         match mode {
             CompositeMode::Capture | CompositeMode::Overlay => {
                 // Unified capture-based pipeline
-                app.init_resource::<FrontendStatus>()
-                    .init_resource::<LastWindowSize>()
-                    .add_systems(Startup, setup_frontend)
-                    .add_systems(Update, update_ui_texture)
-                    .add_systems(Update, handle_frontend_resize)
-                    .add_systems(Update, handle_frontend_ipc_messages);
+                add_frontend_pipeline(app);
 
                 info!(
                     "Render plugin initialized with {:?} mode (unified pipeline)",
@@ -625,12 +2118,7 @@ impl Plugin for RenderPlugin {
             #[cfg(feature = "cef")]
             CompositeMode::Cef => {
                 // CEF also uses the unified pipeline
-                app.init_resource::<FrontendStatus>()
-                    .init_resource::<LastWindowSize>()
-                    .add_systems(Startup, setup_frontend)
-                    .add_systems(Update, update_ui_texture)
-                    .add_systems(Update, handle_frontend_resize)
-                    .add_systems(Update, handle_frontend_ipc_messages);
+                add_frontend_pipeline(app);
 
                 info!("Render plugin initialized with CEF mode (unified pipeline)");
             }
@@ -664,6 +2152,72 @@ impl Plugin for RenderPlugin {
                 warn!("Tauri mode requires building for WASM and running inside Tauri");
                 info!("Render plugin: Tauri mode - no native render setup needed");
             }
+
+            CompositeMode::Remote => {
+                // The remote backend is a `CompositeBackend` too; it never captures
+                add_frontend_pipeline(app);
+
+                info!("Render plugin initialized with REMOTE mode (UI served to a browser)");
+            }
+
+            CompositeMode::Mock => {
+                // Fails to start in setup_frontend when the mock backend isn't built
+                add_frontend_pipeline(app);
+
+                info!("Render plugin initialized with MOCK mode (scripted in-memory UI)");
+            }
         }
     }
 }
+
+/// Register the unified `FrontendResource` pipeline (Capture, Overlay, CEF, Remote, and Mock modes).
+///
+/// Capture, Overlay, and CEF share the same systems, which is what allows
+/// switching between them at runtime.
+fn add_frontend_pipeline(app: &mut App) {
+    app.init_resource::<FrontendStatus>()
+        .init_resource::<LastWindowSize>()
+        .init_resource::<CompositeModeSwitch>()
+        .init_resource::<FrontendRecovery>()
+        .init_resource::<UiRenderScale>()
+        .init_resource::<RenderStatsWindow>()
+        .init_resource::<UiBlobs>()
+        .init_resource::<UiHitTest>()
+        .add_plugins(UiTextureUploadPlugin)
+        .add_plugins(UiPremultipliedMaterialPlugin)
+        .add_plugins(ui_surfaces::UiSurfacesPlugin)
+        .add_systems(Startup, setup_frontend)
+        .add_systems(
+            PreUpdate,
+            ui_hit_test::update_pointer_over_ui.after(bevy::input::InputSystems),
+        )
+        .add_systems(
+            Update,
+            (
+                apply_composite_mode_switch,
+                update_ui_texture,
+                recover_failed_frontend,
+                send_render_stats,
+                send_initialize_on_ready,
+                handle_frontend_ipc_messages,
+            )
+                .chain(),
+        )
+        .add_systems(Update, handle_frontend_resize)
+        .add_systems(
+            Update,
+            (
+                sync_frontend_position,
+                sync_frontend_visibility,
+                sync_frontend_background,
+            ),
+        )
+        .add_systems(Update, animate_loading_spinner.after(update_ui_texture))
+        .add_systems(Update, ui_blobs::evict_expired_ui_blobs)
+        .add_systems(
+            Update,
+            ui_blobs::stream_demo_diffusion_preview
+                .run_if(|config: Res<PentimentoConfig>| config.diffusion_preview_demo),
+        )
+        .add_systems(Last, shutdown_frontend.run_if(on_message::<AppExit>));
+}
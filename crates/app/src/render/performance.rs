@@ -0,0 +1,674 @@
+//! Adaptive scene render scale and MSAA
+//!
+//! With `AppSettings::auto_performance` on, `PerformanceGovernor` watches the
+//! smoothed frame time from `FrameTimeDiagnosticsPlugin`. After
+//! `SLOW_FRAMES` frames over the target (`--target-frame-time`) it steps down
+//! one level: MSAA off first, then the next smaller render scale. It steps
+//! back up after `FAST_FRAMES` frames in which the next level up is predicted
+//! (from its pixel count) to fit with some margin. The gap between the two
+//! thresholds and the `SETTLE_FRAMES` pause after every change keep it from
+//! bouncing between two levels. Vsync hides headroom, so after
+//! `PROBE_FRAMES` calm frames a step up is tried anyway; a probe that has to
+//! be undone doubles the wait for the next one.
+//!
+//! Sculpt and paint strokes get priority: during a stroke the governor steps
+//! down after `STROKE_SLOW_FRAMES`, may go down to `STROKE_MIN_SCALE`, and
+//! never steps up. Once the stroke ends the scale comes back to at least
+//! `IDLE_MIN_SCALE`.
+//!
+//! Below a scale of 1.0 the main camera renders into an offscreen image of
+//! the scaled window size, which is stretched over the window beneath the UI.
+//! The image's `scale_factor` keeps its logical size equal to the window's,
+//! so cursor rays (`Camera::viewport_to_world`) work unchanged, and mouse
+//! picking input is mirrored onto the image with `SCENE_POINTER`.
+//!
+//! `UiToBevy::UpdateSettings` with `auto_performance` off applies
+//! `render_scale` and `msaa_samples` as given. Changing either of them turns
+//! the governor off until `auto_performance` is switched on again.
+
+use bevy::asset::uuid::Uuid;
+use bevy::camera::{ImageRenderTarget, NormalizedRenderTarget, RenderTarget};
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::message::MessageCursor;
+use bevy::ecs::system::SystemParam;
+use bevy::picking::PickingSystems;
+use bevy::picking::pointer::{Location, PointerId, PointerInput};
+use bevy::picking::prelude::Pickable;
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+use bevy::window::PrimaryWindow;
+use pentimento_ipc::{AppSettings, ViewMode};
+#[cfg(feature = "mesh_painting")]
+use pentimento_scene::MeshPaintState;
+#[cfg(feature = "sculpting")]
+use pentimento_scene::SculptState;
+use pentimento_scene::{MainCamera, PaintMode, ViewModeSettings};
+
+use super::scaled_size;
+use crate::config::PentimentoConfig;
+
+/// Render scales the governor steps through, from full resolution down
+const RENDER_SCALE_STEPS: [f32; 5] = [1.0, 0.85, 0.75, 0.6, 0.5];
+
+/// Lowest render scale outside of strokes
+const IDLE_MIN_SCALE: f32 = 0.6;
+
+/// Lowest render scale while a sculpt or paint stroke is in progress
+const STROKE_MIN_SCALE: f32 = 0.5;
+
+/// Frames over budget before stepping down
+const SLOW_FRAMES: u32 = 30;
+
+/// Frames over budget before stepping down during a stroke
+const STROKE_SLOW_FRAMES: u32 = 5;
+
+/// Frames with headroom for the next level up before stepping up
+const FAST_FRAMES: u32 = 120;
+
+/// Frames after a change in which the smoothed frame time catches up
+const SETTLE_FRAMES: u32 = 30;
+
+/// A frame is over budget above `target * SLOW_MARGIN`
+const SLOW_MARGIN: f32 = 1.1;
+
+/// The next level up has to be predicted under `target * FAST_MARGIN`
+const FAST_MARGIN: f32 = 0.9;
+
+/// Estimated frame time factor of turning MSAA back on
+const MSAA_COST: f32 = 1.25;
+
+/// Calm frames after which a step up is tried without predicted headroom
+const PROBE_FRAMES: u32 = 600;
+
+/// Longest wait between probes
+const MAX_PROBE_FRAMES: u32 = 4800;
+
+/// A step down this soon after a step up undoes a failed probe
+const REVERT_FRAMES: u32 = 120;
+
+/// Picking pointer that mirrors the mouse onto the offscreen scene image
+pub const SCENE_POINTER: PointerId =
+    PointerId::Custom(Uuid::from_u128(0x3c6f_0a52_8d1e_4b7a_9e0f_5a2d_7c41_b9e3));
+
+/// Plugin adding the performance governor and applying the scene's render
+/// scale and MSAA to the main camera.
+pub struct PerformancePlugin;
+
+impl Plugin for PerformancePlugin {
+    fn build(&self, app: &mut App) {
+        let target_frame_ms = app.world().resource::<PentimentoConfig>().target_frame_ms;
+
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+
+        app.insert_resource(PerformanceGovernor::new(target_frame_ms))
+            .init_resource::<SceneRenderTarget>()
+            .add_systems(Startup, spawn_scene_pointer)
+            .add_systems(
+                First,
+                mirror_pointer_input.in_set(PickingSystems::PostInput),
+            )
+            .add_systems(
+                PostUpdate,
+                (update_performance_governor, apply_render_quality).chain(),
+            );
+    }
+}
+
+/// Render scale and MSAA chosen by the governor, or set by hand.
+#[derive(Resource)]
+pub struct PerformanceGovernor {
+    /// Whether the governor picks the quality (`AppSettings::auto_performance`)
+    pub enabled: bool,
+    /// Frame time budget in milliseconds
+    pub target_frame_ms: f32,
+    /// Whether the governor may turn MSAA off
+    pub adjust_msaa: bool,
+    /// Last `auto_performance` the UI sent
+    auto_requested: bool,
+    /// Render scale used while the governor is off
+    manual_scale: f32,
+    /// MSAA used while the governor is off, and at the top level
+    manual_msaa: Msaa,
+    /// Current level; 0 is full quality
+    level: usize,
+    slow_frames: u32,
+    fast_frames: u32,
+    calm_frames: u32,
+    frames_since_change: u32,
+    last_step_up: bool,
+    probe_frames: u32,
+}
+
+impl PerformanceGovernor {
+    pub fn new(target_frame_ms: f32) -> Self {
+        let settings = AppSettings::default();
+        Self {
+            enabled: settings.auto_performance,
+            target_frame_ms,
+            adjust_msaa: true,
+            auto_requested: settings.auto_performance,
+            manual_scale: settings.clamped_render_scale(),
+            manual_msaa: Msaa::from_samples(settings.supported_msaa_samples()),
+            level: 0,
+            slow_frames: 0,
+            fast_frames: 0,
+            calm_frames: 0,
+            frames_since_change: 0,
+            last_step_up: false,
+            probe_frames: PROBE_FRAMES,
+        }
+    }
+
+    /// Resolution the scene is rendered at, relative to the window
+    pub fn render_scale(&self) -> f32 {
+        if self.enabled {
+            RENDER_SCALE_STEPS[self.level - self.msaa_levels().min(self.level)]
+        } else {
+            self.manual_scale
+        }
+    }
+
+    /// MSAA of the main camera
+    pub fn msaa(&self) -> Msaa {
+        if self.enabled && self.level >= self.msaa_levels() && self.msaa_levels() > 0 {
+            Msaa::Off
+        } else {
+            self.manual_msaa
+        }
+    }
+
+    /// Fill in the quality fields of settings sent to the UI
+    pub fn report(&self, settings: &mut AppSettings) {
+        settings.render_scale = self.manual_scale;
+        settings.msaa_samples = self.manual_msaa.samples();
+        settings.auto_performance = self.enabled;
+    }
+
+    /// Apply `UiToBevy::UpdateSettings`.
+    ///
+    /// A changed `render_scale` or `msaa_samples` is a manual choice and turns
+    /// the governor off; it comes back when `auto_performance` is switched on.
+    pub fn apply_settings(&mut self, settings: &AppSettings) {
+        let scale = settings.clamped_render_scale();
+        let msaa = Msaa::from_samples(settings.supported_msaa_samples());
+        let manual_changed =
+            (scale - self.manual_scale).abs() > f32::EPSILON || msaa != self.manual_msaa;
+        let switched_on = settings.auto_performance && !self.auto_requested;
+
+        self.manual_scale = scale;
+        self.manual_msaa = msaa;
+        self.auto_requested = settings.auto_performance;
+
+        let enabled =
+            settings.auto_performance && (switched_on || (self.enabled && !manual_changed));
+        if enabled != self.enabled {
+            self.enabled = enabled;
+            self.reset();
+            info!(
+                "Performance governor {}",
+                if enabled { "enabled" } else { "disabled" }
+            );
+        }
+    }
+
+    /// Feed one frame's smoothed frame time, returning whether the level changed
+    pub fn update(&mut self, frame_ms: f32, stroke_active: bool) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        self.frames_since_change = self.frames_since_change.saturating_add(1);
+
+        // A stroke may have pushed the scale below the idle floor
+        if !stroke_active && self.render_scale() < IDLE_MIN_SCALE {
+            self.step(false);
+            return true;
+        }
+
+        if self.frames_since_change < SETTLE_FRAMES {
+            return false;
+        }
+
+        if frame_ms > self.target_frame_ms * SLOW_MARGIN {
+            self.slow_frames += 1;
+            self.fast_frames = 0;
+            self.calm_frames = 0;
+        } else {
+            self.slow_frames = 0;
+            self.calm_frames += 1;
+            if self.predicted_step_up_ms(frame_ms) < self.target_frame_ms * FAST_MARGIN {
+                self.fast_frames += 1;
+            } else {
+                self.fast_frames = 0;
+            }
+        }
+
+        let slow_needed = if stroke_active {
+            STROKE_SLOW_FRAMES
+        } else {
+            SLOW_FRAMES
+        };
+        let min_scale = if stroke_active {
+            STROKE_MIN_SCALE
+        } else {
+            IDLE_MIN_SCALE
+        };
+        if self.slow_frames >= slow_needed && self.can_step_down(min_scale) {
+            if self.last_step_up && self.frames_since_change < REVERT_FRAMES {
+                self.probe_frames = (self.probe_frames * 2).min(MAX_PROBE_FRAMES);
+            }
+            self.step(true);
+            return true;
+        }
+
+        if !stroke_active
+            && self.level > 0
+            && (self.fast_frames >= FAST_FRAMES || self.calm_frames >= self.probe_frames)
+        {
+            self.step(false);
+            return true;
+        }
+
+        false
+    }
+
+    /// Levels spent on MSAA before the render scale drops
+    fn msaa_levels(&self) -> usize {
+        usize::from(self.adjust_msaa && self.manual_msaa != Msaa::Off)
+    }
+
+    fn level_count(&self) -> usize {
+        self.msaa_levels() + RENDER_SCALE_STEPS.len()
+    }
+
+    fn can_step_down(&self, min_scale: f32) -> bool {
+        let next = self.level + 1;
+        next < self.level_count()
+            && RENDER_SCALE_STEPS[next - self.msaa_levels().min(next)] >= min_scale
+    }
+
+    /// Frame time expected one level up, from its pixel count (or MSAA cost)
+    fn predicted_step_up_ms(&self, frame_ms: f32) -> f32 {
+        if self.level == 0 {
+            return frame_ms;
+        }
+        if self.level == self.msaa_levels() {
+            return frame_ms * MSAA_COST;
+        }
+        let current = self.render_scale();
+        let up = RENDER_SCALE_STEPS[self.level - 1 - self.msaa_levels()];
+        frame_ms * (up / current).powi(2)
+    }
+
+    fn step(&mut self, down: bool) {
+        if down {
+            self.level += 1;
+        } else {
+            self.level -= 1;
+        }
+        self.last_step_up = !down;
+        self.slow_frames = 0;
+        self.fast_frames = 0;
+        self.calm_frames = 0;
+        self.frames_since_change = 0;
+    }
+
+    fn reset(&mut self) {
+        self.level = 0;
+        self.slow_frames = 0;
+        self.fast_frames = 0;
+        self.calm_frames = 0;
+        self.frames_since_change = 0;
+        self.last_step_up = false;
+        self.probe_frames = PROBE_FRAMES;
+    }
+}
+
+/// Offscreen image the main camera renders to while the scale is below 1.0.
+#[derive(Resource, Default)]
+pub struct SceneRenderTarget {
+    target: Option<ImageRenderTarget>,
+    size: UVec2,
+}
+
+impl SceneRenderTarget {
+    /// The image target, if the scene is currently scaled
+    pub fn target(&self) -> Option<&ImageRenderTarget> {
+        self.target.as_ref()
+    }
+}
+
+/// Full-window node showing the scaled scene image beneath the UI.
+#[derive(Component)]
+pub struct ScenePresenter;
+
+/// Whether a sculpt or paint stroke is in progress
+#[derive(SystemParam)]
+struct StrokeActivity<'w> {
+    paint: Option<Res<'w, PaintMode>>,
+    #[cfg(feature = "mesh_painting")]
+    mesh_paint: Option<Res<'w, MeshPaintState>>,
+    #[cfg(feature = "sculpting")]
+    sculpt: Option<Res<'w, SculptState>>,
+}
+
+impl StrokeActivity<'_> {
+    fn active(&self) -> bool {
+        let active = self
+            .paint
+            .as_ref()
+            .is_some_and(|paint| paint.current_stroke.is_some());
+        #[cfg(feature = "mesh_painting")]
+        let active = active
+            || self
+                .mesh_paint
+                .as_ref()
+                .is_some_and(|state| state.current_stroke.is_some());
+        #[cfg(feature = "sculpting")]
+        let active = active
+            || self
+                .sculpt
+                .as_ref()
+                .is_some_and(|state| state.current_stroke_id.is_some());
+        active
+    }
+}
+
+fn update_performance_governor(
+    mut governor: ResMut<PerformanceGovernor>,
+    diagnostics: Res<DiagnosticsStore>,
+    strokes: StrokeActivity,
+) {
+    if !governor.enabled {
+        return;
+    }
+    let Some(frame_ms) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|diagnostic| diagnostic.smoothed())
+    else {
+        return;
+    };
+
+    if governor.update(frame_ms as f32, strokes.active()) {
+        info!(
+            "Performance governor: {:.1}ms, render scale {:.0}%, MSAA {}x",
+            frame_ms,
+            governor.render_scale() * 100.0,
+            governor.msaa().samples()
+        );
+    }
+}
+
+/// Give the main camera the current MSAA, and move it to (or off) the
+/// offscreen scene image when the render scale or window size changes.
+#[allow(clippy::too_many_arguments)]
+fn apply_render_quality(
+    mut commands: Commands,
+    governor: Res<PerformanceGovernor>,
+    mut scene_target: ResMut<SceneRenderTarget>,
+    mut images: ResMut<Assets<Image>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    view_mode: Option<Res<ViewModeSettings>>,
+    mut camera: Query<(Entity, &mut Msaa), With<MainCamera>>,
+    mut presenter: Query<(Entity, &mut ImageNode), With<ScenePresenter>>,
+) {
+    let Ok((camera, mut msaa)) = camera.single_mut() else {
+        return;
+    };
+
+    // The ambient occlusion view turns MSAA off and puts it back itself
+    if view_mode.is_none_or(|settings| settings.mode != ViewMode::AmbientOcclusion) {
+        msaa.set_if_neq(governor.msaa());
+    }
+
+    let Ok(window) = window.single() else {
+        return;
+    };
+    let scale = governor.render_scale();
+
+    if scale >= 1.0 {
+        if let Some(target) = scene_target.target.take() {
+            commands.entity(camera).insert(RenderTarget::default());
+            for (entity, _) in presenter.iter() {
+                commands.entity(entity).despawn();
+            }
+            images.remove(&target.handle);
+            scene_target.size = UVec2::ZERO;
+        }
+        return;
+    }
+
+    let (width, height) = scaled_size(window.physical_width(), window.physical_height(), scale);
+    let size = UVec2::new(width, height);
+    // Logical size of the image equals the window's
+    let scale_factor = window.scale_factor() * scale;
+    if scene_target
+        .target
+        .as_ref()
+        .is_some_and(|target| target.scale_factor == scale_factor && scene_target.size == size)
+    {
+        return;
+    }
+
+    let handle = images.add(Image::new_target_texture(
+        width,
+        height,
+        TextureFormat::Rgba8UnormSrgb,
+        None,
+    ));
+    let target = ImageRenderTarget {
+        handle: handle.clone(),
+        scale_factor,
+    };
+    commands
+        .entity(camera)
+        .insert(RenderTarget::Image(target.clone()));
+
+    if let Ok((_, mut image)) = presenter.single_mut() {
+        image.image = handle;
+    } else {
+        commands.spawn((
+            ImageNode::new(handle),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            // Beneath every UI node
+            GlobalZIndex(i32::MIN),
+            Pickable::IGNORE,
+            ScenePresenter,
+            Name::new("Scene Presenter"),
+        ));
+    }
+
+    if let Some(previous) = scene_target.target.replace(target) {
+        images.remove(&previous.handle);
+    }
+    scene_target.size = size;
+    debug!(
+        "Scene rendering at {}x{} ({:.0}%)",
+        width,
+        height,
+        scale * 100.0
+    );
+}
+
+fn spawn_scene_pointer(mut commands: Commands) {
+    commands.spawn((SCENE_POINTER, Name::new("Scene Pointer")));
+}
+
+/// Repeat mouse picking input for `SCENE_POINTER` on the offscreen scene
+/// image, so mesh picking keeps working while the scene is scaled.
+fn mirror_pointer_input(
+    scene_target: Res<SceneRenderTarget>,
+    mut inputs: ResMut<Messages<PointerInput>>,
+    mut cursor: Local<MessageCursor<PointerInput>>,
+) {
+    let mouse: Vec<PointerInput> = cursor
+        .read(&inputs)
+        .filter(|input| input.pointer_id == PointerId::Mouse)
+        .cloned()
+        .collect();
+    let Some(target) = scene_target.target() else {
+        return;
+    };
+
+    for input in mouse {
+        inputs.write(PointerInput::new(
+            SCENE_POINTER,
+            Location {
+                target: NormalizedRenderTarget::Image(target.clone()),
+                position: input.location.position,
+            },
+            input.action,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `frames` frames at `frame_ms`, returning how many changed the level
+    fn run(governor: &mut PerformanceGovernor, frames: u32, frame_ms: f32, stroke: bool) -> u32 {
+        (0..frames)
+            .filter(|_| governor.update(frame_ms, stroke))
+            .count() as u32
+    }
+
+    #[test]
+    fn test_steps_down_msaa_first_then_scale() {
+        let mut governor = PerformanceGovernor::new(16.6);
+        assert_eq!(governor.render_scale(), 1.0);
+        assert_eq!(governor.msaa(), Msaa::Sample4);
+
+        run(&mut governor, SETTLE_FRAMES + SLOW_FRAMES, 30.0, false);
+        assert_eq!(governor.msaa(), Msaa::Off);
+        assert_eq!(governor.render_scale(), 1.0);
+
+        run(&mut governor, SETTLE_FRAMES + SLOW_FRAMES, 30.0, false);
+        assert_eq!(governor.render_scale(), 0.85);
+    }
+
+    #[test]
+    fn test_idle_floor_and_lower_floor_during_strokes() {
+        let mut governor = PerformanceGovernor::new(16.6);
+        run(&mut governor, 2000, 100.0, false);
+        assert_eq!(governor.render_scale(), IDLE_MIN_SCALE);
+
+        run(&mut governor, 200, 100.0, true);
+        assert_eq!(governor.render_scale(), STROKE_MIN_SCALE);
+
+        // Back to the idle floor as soon as the stroke ends
+        assert!(governor.update(100.0, false));
+        assert_eq!(governor.render_scale(), IDLE_MIN_SCALE);
+    }
+
+    #[test]
+    fn test_strokes_step_down_sooner_and_never_up() {
+        let mut idle = PerformanceGovernor::new(16.6);
+        let mut stroke = PerformanceGovernor::new(16.6);
+        let frames = SETTLE_FRAMES + STROKE_SLOW_FRAMES;
+        assert_eq!(run(&mut idle, frames, 30.0, false), 0);
+        assert_eq!(run(&mut stroke, frames, 30.0, true), 1);
+
+        assert_eq!(run(&mut stroke, 10_000, 1.0, true), 0);
+        assert_eq!(stroke.msaa(), Msaa::Off);
+    }
+
+    #[test]
+    fn test_steps_up_with_headroom() {
+        let mut governor = PerformanceGovernor::new(16.6);
+        run(&mut governor, 2000, 100.0, false);
+        assert_eq!(governor.render_scale(), IDLE_MIN_SCALE);
+
+        run(&mut governor, 5000, 2.0, false);
+        assert_eq!(governor.render_scale(), 1.0);
+        assert_eq!(governor.msaa(), Msaa::Sample4);
+    }
+
+    #[test]
+    fn test_holds_between_thresholds() {
+        // 0.85 -> 1.0 would be predicted at 16.6ms, over the step-up margin,
+        // and 16.6ms is not over budget either
+        let mut governor = PerformanceGovernor::new(16.6);
+        run(
+            &mut governor,
+            2 * (SETTLE_FRAMES + SLOW_FRAMES),
+            30.0,
+            false,
+        );
+        assert_eq!(governor.render_scale(), 0.85);
+
+        let frame_ms = 16.6 * 0.85 * 0.85;
+        assert_eq!(run(&mut governor, PROBE_FRAMES - 1, frame_ms, false), 0);
+    }
+
+    #[test]
+    fn test_failed_probe_backs_off() {
+        let mut governor = PerformanceGovernor::new(16.6);
+        run(
+            &mut governor,
+            2 * (SETTLE_FRAMES + SLOW_FRAMES),
+            30.0,
+            false,
+        );
+        assert_eq!(governor.render_scale(), 0.85);
+
+        // Vsync-bound: calm but no predicted headroom, so a probe steps up...
+        let changes = run(&mut governor, SETTLE_FRAMES + PROBE_FRAMES, 16.6, false);
+        assert_eq!(changes, 1);
+        assert_eq!(governor.msaa(), Msaa::Off);
+        assert_eq!(governor.render_scale(), 1.0);
+
+        // ...finds it too slow and goes back, waiting twice as long next time
+        run(&mut governor, SETTLE_FRAMES + SLOW_FRAMES, 30.0, false);
+        assert_eq!(governor.render_scale(), 0.85);
+        assert_eq!(governor.probe_frames, 2 * PROBE_FRAMES);
+    }
+
+    #[test]
+    fn test_manual_change_disables_until_switched_on() {
+        let mut governor = PerformanceGovernor::new(16.6);
+        run(&mut governor, 2000, 100.0, false);
+
+        let mut settings = AppSettings {
+            render_scale: 0.75,
+            ..default()
+        };
+        governor.apply_settings(&settings);
+        assert!(!governor.enabled);
+        assert_eq!(governor.render_scale(), 0.75);
+        assert_eq!(governor.msaa(), Msaa::Sample4);
+        assert!(!governor.update(100.0, false));
+
+        // Still requested on, but the manual choice stands
+        governor.apply_settings(&settings);
+        assert!(!governor.enabled);
+
+        settings.auto_performance = false;
+        governor.apply_settings(&settings);
+        assert!(!governor.enabled);
+
+        settings.auto_performance = true;
+        governor.apply_settings(&settings);
+        assert!(governor.enabled);
+        assert_eq!(governor.render_scale(), 1.0);
+    }
+
+    #[test]
+    fn test_manual_settings_are_clamped() {
+        let mut governor = PerformanceGovernor::new(16.6);
+        governor.apply_settings(&AppSettings {
+            render_scale: 0.1,
+            msaa_samples: 3,
+            auto_performance: false,
+            ..default()
+        });
+        assert_eq!(governor.render_scale(), 0.5);
+        assert_eq!(governor.msaa(), Msaa::Sample2);
+    }
+}
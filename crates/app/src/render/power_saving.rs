@@ -0,0 +1,125 @@
+//! Power saving while the window is in the background
+//!
+//! When the window loses focus or is minimized, the event loop drops to
+//! reactive low-power updates (at least every `BACKGROUND_WAIT`), UI captures
+//! and `RenderStats` pause, and the frontend is told through
+//! `CompositeBackend::set_background` (CEF stops painting). A capture skipped
+//! in the background leaves the backend dirty, so the first foreground frame
+//! catches up.
+//!
+//! In Overlay mode the UI is its own window, and clicking it takes focus from
+//! the Bevy window, so only minimizing counts there. Diffusion previews are
+//! pushed by the UI's own requests (`UiBlobs`), so there's nothing to pause.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowOccluded};
+use bevy::winit::{UpdateMode, WinitSettings};
+
+use crate::config::{CompositeMode, PentimentoConfig};
+
+use super::FrontendStatus;
+
+/// Longest wait between updates in the background
+const BACKGROUND_WAIT: Duration = Duration::from_millis(250);
+
+/// Whether the app is in the background and saving power
+#[derive(Resource, Debug, Default)]
+pub struct PowerSaving {
+    /// The primary window was reported occluded (minimized)
+    occluded: bool,
+    background: bool,
+}
+
+impl PowerSaving {
+    /// Captures, stats, and frontend painting are paused
+    pub fn is_background(&self) -> bool {
+        self.background
+    }
+}
+
+/// Plugin tracking focus and occlusion of the primary window
+pub(super) struct PowerSavingPlugin;
+
+impl Plugin for PowerSavingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerSaving>()
+            .add_systems(PreUpdate, update_power_saving);
+    }
+}
+
+/// Enter or leave the background as the window's focus and occlusion change
+fn update_power_saving(
+    mut occluded_events: MessageReader<WindowOccluded>,
+    windows: Query<(Entity, &Window), With<PrimaryWindow>>,
+    config: Res<PentimentoConfig>,
+    status: Option<Res<FrontendStatus>>,
+    winit_settings: Option<ResMut<WinitSettings>>,
+    mut power_saving: ResMut<PowerSaving>,
+) {
+    let Ok((primary, window)) = windows.single() else {
+        occluded_events.clear();
+        return;
+    };
+    if let Some(event) = occluded_events
+        .read()
+        .filter(|event| event.window == primary)
+        .last()
+    {
+        power_saving.bypass_change_detection().occluded = event.occluded;
+    }
+
+    // A mode switch or fallback may have replaced the configured mode
+    let mode = status.map_or(config.composite_mode, |status| status.mode);
+    let background = in_background(window.focused, power_saving.occluded, mode);
+    if background == power_saving.background {
+        return;
+    }
+    info!(
+        "Power saving {} (focused: {}, occluded: {})",
+        if background { "on" } else { "off" },
+        window.focused,
+        power_saving.occluded
+    );
+    power_saving.background = background;
+    if let Some(mut settings) = winit_settings {
+        *settings = winit_settings_for(background);
+    }
+}
+
+/// Whether a window in this state is in the background
+fn in_background(focused: bool, occluded: bool, mode: CompositeMode) -> bool {
+    occluded || (!focused && mode != CompositeMode::Overlay)
+}
+
+/// Event loop settings for the foreground or the background
+fn winit_settings_for(background: bool) -> WinitSettings {
+    if background {
+        WinitSettings {
+            focused_mode: UpdateMode::reactive_low_power(BACKGROUND_WAIT),
+            unfocused_mode: UpdateMode::reactive_low_power(BACKGROUND_WAIT),
+        }
+    } else {
+        WinitSettings::game()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfocused_or_minimized_is_background() {
+        assert!(!in_background(true, false, CompositeMode::Capture));
+        assert!(in_background(false, false, CompositeMode::Capture));
+        assert!(in_background(true, true, CompositeMode::Capture));
+    }
+
+    #[test]
+    fn test_overlay_only_saves_power_when_minimized() {
+        // Clicking the overlay UI unfocuses the Bevy window
+        assert!(!in_background(false, false, CompositeMode::Overlay));
+        assert!(in_background(false, true, CompositeMode::Overlay));
+    }
+}
@@ -7,16 +7,25 @@
 @group(1) @binding(0) var ui_texture: texture_2d<f32>;
 @group(1) @binding(1) var ui_sampler: sampler;
 
+// Exact sRGB EOTF (the hardware decode of an *Srgb texture format)
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3(2.4));
+    return select(high, low, c <= vec3(0.04045));
+}
+
 @fragment
 fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
     let color = textureSample(ui_texture, ui_sampler, in.uv);
 
-    // The texture is in linear color space from Vello
-    // Convert to sRGB for display
-    let srgb = pow(color.rgb, vec3(1.0 / 2.2));
+    // Vello blends and stores sRGB-encoded values, but the texture is
+    // Rgba8Unorm (storage textures can't be *Srgb), so the sampler doesn't
+    // decode them. Decode here; the UI camera's sRGB target re-encodes on
+    // write, so each texel reaches the screen unchanged.
+    let linear = srgb_to_linear(color.rgb);
 
     // Output STRAIGHT alpha (not premultiplied)
     // Bevy's UiMaterial uses BlendState::ALPHA_BLENDING which expects straight alpha
     // Blend formula: output = src.rgb * src.a + dst.rgb * (1 - src.a)
-    return vec4(srgb, color.a);
+    return vec4(linear, color.a);
 }
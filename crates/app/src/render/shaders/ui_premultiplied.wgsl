@@ -0,0 +1,14 @@
+// UI blend shader for captured webview frames
+// The texture holds premultiplied alpha (see ui_texture_upload.rs), and the
+// pipeline blends with BlendState::PREMULTIPLIED_ALPHA_BLENDING.
+
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+@group(1) @binding(0) var ui_texture: texture_2d<f32>;
+@group(1) @binding(1) var ui_sampler: sampler;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    // Blend formula: output = src.rgb + dst.rgb * (1 - src.a)
+    return textureSample(ui_texture, ui_sampler, in.uv);
+}
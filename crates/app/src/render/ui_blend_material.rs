@@ -1,7 +1,8 @@
 //! Custom UI material for alpha-blended Dioxus/Vello overlay
 //!
-//! This material properly samples the Vello-rendered texture (linear color space)
-//! and blends it over the 3D scene with correct alpha compositing.
+//! This material samples the Vello-rendered texture (sRGB values in an
+//! `Rgba8Unorm` texture, decoded in the shader) and blends it over the 3D
+//! scene with correct alpha compositing.
 
 use bevy::asset::embedded_asset;
 use bevy::prelude::*;
@@ -21,8 +22,8 @@ impl Plugin for UiBlendMaterialPlugin {
 
 /// Custom UI material that samples a texture with proper alpha blending
 ///
-/// This is used for the Dioxus/Vello UI overlay, which renders to a linear
-/// color space texture that needs to be composited over the 3D scene.
+/// This is used for the Dioxus/Vello UI overlay, which renders sRGB values to
+/// a Unorm texture that needs to be composited over the 3D scene.
 #[derive(Asset, AsBindGroup, TypePath, Clone)]
 pub struct UiBlendMaterial {
     #[texture(0)]
@@ -0,0 +1,109 @@
+//! Binary payloads waiting for the UI
+//!
+//! Large data (diffusion previews, thumbnails) skips the JSON channel: it is
+//! stored in `UiBlobs` and only announced with `BevyToUi::BinaryBlob`. The
+//! webview backends serve it from the same registry, which they get through
+//! `FrontendConfig`. See `pentimento_frontend_core::blob`.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use bevy::prelude::*;
+use pentimento_frontend_core::blob::BlobRegistry;
+use pentimento_ipc::{BevyToUi, BlobKind};
+use pentimento_scene::OutboundUiMessages;
+
+use super::FrontendStatus;
+
+/// Width and height of the `--diffusion-preview-demo` frames
+const DEMO_PREVIEW_SIZE: u32 = 1024;
+
+/// Denoising steps in one demo generation
+const DEMO_PREVIEW_STEPS: u32 = 30;
+
+/// Seconds between demo preview frames
+const DEMO_PREVIEW_INTERVAL: f32 = 0.1;
+
+/// Blobs announced to the UI and not yet consumed or expired
+#[derive(Resource, Clone, Default, Deref)]
+pub struct UiBlobs(pub BlobRegistry);
+
+impl UiBlobs {
+    /// Store `bytes` and queue their `BinaryBlob` announcement
+    pub fn publish(&self, kind: BlobKind, bytes: Arc<[u8]>, outbound: &mut OutboundUiMessages) {
+        outbound.send(self.0.insert(kind, bytes, Instant::now()));
+    }
+
+    /// Free a blob the UI acknowledged with `BlobConsumed`
+    pub fn consume(&self, id: u64) {
+        if !self.0.consume(id) {
+            debug!("UI consumed blob {} after it expired", id);
+        }
+    }
+}
+
+/// Drop blobs the UI never fetched
+pub fn evict_expired_ui_blobs(blobs: Res<UiBlobs>) {
+    let evicted = blobs.evict_expired(Instant::now());
+    if evicted > 0 {
+        debug!("Evicted {} unconsumed UI blobs", evicted);
+    }
+}
+
+/// Stream a synthetic diffusion preview for `--diffusion-preview-demo`
+///
+/// Stands in for a diffusion backend's progress callback: every step renders
+/// a frame that sharpens from noise and publishes it through the same
+/// `preview_blob` path generation will use, then the demo starts over.
+pub fn stream_demo_diffusion_preview(
+    time: Res<Time>,
+    status: Res<FrontendStatus>,
+    blobs: Res<UiBlobs>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    mut since_frame: Local<f32>,
+    mut step: Local<u32>,
+) {
+    if !status.initialized {
+        return;
+    }
+    *since_frame += time.delta_secs();
+    if *since_frame < DEMO_PREVIEW_INTERVAL {
+        return;
+    }
+    *since_frame = 0.0;
+
+    *step = *step % DEMO_PREVIEW_STEPS + 1;
+    let progress = *step as f32 / DEMO_PREVIEW_STEPS as f32;
+    let frame = demo_preview_frame(*step, progress);
+
+    let start = Instant::now();
+    let (kind, bytes) = pentimento_diffusion::preview_blob("demo", &frame);
+    blobs.publish(kind, bytes, &mut outbound);
+    outbound.send(BevyToUi::DiffusionProgress {
+        task_id: "demo".to_string(),
+        progress,
+        preview_available: true,
+    });
+    debug!(
+        "Published {}x{} demo preview in {:.2}ms",
+        DEMO_PREVIEW_SIZE,
+        DEMO_PREVIEW_SIZE,
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+}
+
+/// A gradient blended with per-step hash noise that fades as `progress` nears 1
+fn demo_preview_frame(step: u32, progress: f32) -> image::RgbaImage {
+    let size = DEMO_PREVIEW_SIZE;
+    let clarity = (progress * 255.0) as u32;
+    image::RgbaImage::from_fn(size, size, |x, y| {
+        let noise = (x.wrapping_mul(374_761_393)
+            ^ y.wrapping_mul(668_265_263)
+            ^ step.wrapping_mul(2_246_822_519))
+        .wrapping_mul(1_274_126_177)
+            >> 24;
+        let clean = [x * 255 / size, y * 255 / size, 255 - x * 255 / size];
+        let channel = |c: u32| ((c * clarity + noise * (255 - clarity)) / 255) as u8;
+        image::Rgba([channel(clean[0]), channel(clean[1]), channel(clean[2]), 255])
+    })
+}
@@ -0,0 +1,60 @@
+//! Dedicated camera for the UI
+//!
+//! All Bevy UI nodes (the captured UI overlay, screen surfaces, the loading
+//! spinner, the Dioxus overlay) render through this camera instead of the
+//! scene camera. It draws after every scene camera with no tonemapping, no
+//! exposure, and no dithering, so a UI texel reaches the screen as the exact
+//! sRGB value the backend captured. On the scene camera the UI would follow
+//! its tonemapping and HDR settings (Reinhard, or AcesFitted with the
+//! atmosphere) and whichever camera happened to be the default UI camera.
+//!
+//! The camera clears to transparent and its output is blended over the scene
+//! with premultiplied alpha. UI textures are premultiplied (see
+//! `ui_texture_upload`), and straight-alpha nodes drawn over a transparent
+//! clear leave premultiplied color behind too.
+
+use bevy::camera::CameraOutputMode;
+use bevy::camera::visibility::RenderLayers;
+use bevy::core_pipeline::tonemapping::{DebandDither, Tonemapping};
+use bevy::prelude::*;
+use bevy::render::render_resource::BlendState;
+
+/// Order of the UI camera: after the scene, turntable, and outline cameras
+pub const UI_CAMERA_ORDER: isize = 100;
+
+/// Marker for the camera the UI renders through.
+#[derive(Component)]
+pub struct UiCamera;
+
+/// Plugin spawning the UI camera (every composite mode).
+pub struct UiCameraPlugin;
+
+impl Plugin for UiCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_ui_camera);
+    }
+}
+
+fn spawn_ui_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: UI_CAMERA_ORDER,
+            clear_color: ClearColorConfig::Custom(Color::NONE),
+            output_mode: CameraOutputMode::Write {
+                blend_state: Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                clear_color: ClearColorConfig::None,
+            },
+            ..default()
+        },
+        Tonemapping::None,
+        DebandDither::Disabled,
+        Msaa::Off,
+        // UI ignores render layers; this keeps sprites, gizmos, and mesh
+        // picking off the UI camera
+        RenderLayers::none(),
+        IsDefaultUiCamera,
+        UiCamera,
+        Name::new("UI Camera"),
+    ));
+}
@@ -3,13 +3,34 @@
 use bevy::ecs::message::Messages;
 use bevy::prelude::*;
 use painting::PaintingPipeline;
-use pentimento_ipc::{BevyToUi, LayerInfo, PaintCommand, UiToBevy};
+use pentimento_ipc::{BevyToUi, LayerInfo, MaterialCommand, PaintCommand, UiToBevy, ViewMode};
 use pentimento_scene::{
-    ActiveCanvasPlane, AddObjectEvent, CanvasPlane, CanvasPlaneEvent, DepthViewSettings,
-    OutboundUiMessages, PaintingResource, SceneAmbientOcclusion, SceneLighting,
+    ActiveCanvasPlane, AddObjectEvent, BrushTipEvent, CameraCommandEvent, CanvasFileEvent,
+    CanvasPlacement, CanvasPlane, CanvasPlaneEvent, CanvasResizeEvent, CanvasToolEvent,
+    EditModeEvent, EditorMode,
+    GizmoCommandEvent, HistoryBudget, KeymapEvent, LightCommandEvent, MeasureEvent, NodeGraphEvent,
+    ObjectCommandEvent, OrbitSettings, OutboundUiMessages, PaintingResource, PixelSelectionEvent, ProjectionEvent,
+    ReferenceImageEvent, SceneAmbientOcclusion, SceneGrid, SceneLighting, TextureRegistryEvent,
+    TurntableEvent, TurntableRequest, ViewModeSettings,
 };
 
+#[cfg(feature = "mesh_painting")]
+use pentimento_scene::MeshPaintEvent;
+#[cfg(feature = "selection")]
+use pentimento_scene::OutlineSettings;
+#[cfg(feature = "sculpting")]
+use pentimento_scene::SculptEvent;
+#[cfg(feature = "wireframe")]
+use pentimento_scene::WireframeEvent;
+
 use super::event_bridge::{BlitzDocumentResource, DioxusBridgeResource};
+use crate::autosave::AutosaveEvent;
+#[cfg(feature = "collab")]
+use crate::collab::CollabEvent;
+use crate::input::NavigationSettings;
+use crate::query::QueryRequest;
+use crate::render::{PerformanceGovernor, UiBlobs};
+use crate::window_close::CloseResponseEvent;
 
 /// Handle IPC messages from the Dioxus UI and dispatch to appropriate Bevy events.
 /// This is an exclusive system because DioxusBridgeResource is NonSend.
@@ -28,8 +49,22 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
             for msg in &outbound_msgs {
                 eprintln!(">>> IPC forwarding outbound to UI: {:?}", msg);
             }
+            let blobs = world.get_resource::<UiBlobs>();
             for msg in outbound_msgs {
-                bridge.bridge_handle.send(msg);
+                // Hand blob bytes over directly; Dioxus has no URL to fetch them from
+                let blob = match &msg {
+                    BevyToUi::BinaryBlob { id, .. } => {
+                        blobs.and_then(|blobs| Some((blobs, *id, blobs.get(*id)?)))
+                    }
+                    _ => None,
+                };
+                match blob {
+                    Some((blobs, id, bytes)) => {
+                        bridge.bridge_handle.send_blob(msg, bytes);
+                        blobs.consume(id);
+                    }
+                    None => bridge.bridge_handle.send(msg),
+                }
             }
         } else {
             warn!("No DioxusBridgeResource found!");
@@ -54,6 +89,7 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
         }
         msgs
     };
+    let messages = crate::input::tap_ui_messages(world, messages);
 
     if messages.is_empty() {
         return;
@@ -68,6 +104,14 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
 
     // Process each message, collecting events to send
     let mut canvas_events: Vec<CanvasPlaneEvent> = Vec::new();
+    let mut canvas_file_events: Vec<CanvasFileEvent> = Vec::new();
+    let mut canvas_resize_events: Vec<CanvasResizeEvent> = Vec::new();
+    let mut pixel_selection_events: Vec<PixelSelectionEvent> = Vec::new();
+    let mut brush_tip_events: Vec<BrushTipEvent> = Vec::new();
+    let mut canvas_tool_events: Vec<CanvasToolEvent> = Vec::new();
+    let mut projection_events: Vec<ProjectionEvent> = Vec::new();
+    #[cfg(feature = "mesh_painting")]
+    let mut mesh_paint_events: Vec<MeshPaintEvent> = Vec::new();
     let mut outbound_layer_msgs: Vec<BevyToUi> = Vec::new();
 
     for msg in messages {
@@ -76,6 +120,7 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
                 canvas_events.push(CanvasPlaneEvent::CreateInFrontOfCamera {
                     width: request.width.unwrap_or(1024),
                     height: request.height.unwrap_or(1024),
+                    placement: CanvasPlacement::from_request(&request),
                 });
                 info!("Received AddPaintCanvas request, creating canvas in front of camera");
             }
@@ -87,7 +132,12 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
                     match cmd {
                         PaintCommand::SelectBrushPreset { preset_id } => {
                             let presets = painting::brush::builtin_presets();
-                            if let Some(preset) = presets.into_iter().find(|p| p.id == preset_id) {
+                            if let Some(mut preset) =
+                                presets.into_iter().find(|p| p.id == preset_id)
+                            {
+                                // The stamp tip is chosen separately from the preset
+                                preset.tip = painting_res.brush_preset.tip.clone();
+                                preset.tip_rotation = painting_res.brush_preset.tip_rotation;
                                 painting_res.set_brush_preset(preset);
                                 info!("Selected brush preset: id={}", preset_id);
                             }
@@ -108,6 +158,12 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
                             painting_res.set_brush_preset(preset);
                             debug!("Set brush opacity to {}", opacity);
                         }
+                        PaintCommand::SetBrushFlow { flow } => {
+                            painting_res.brush_preset.flow = flow;
+                            let preset = painting_res.brush_preset.clone();
+                            painting_res.set_brush_preset(preset);
+                            debug!("Set brush flow to {}", flow);
+                        }
                         PaintCommand::SetBrushHardness { hardness } => {
                             painting_res.brush_preset.hardness = hardness;
                             let preset = painting_res.brush_preset.clone();
@@ -122,16 +178,23 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
                             if painting_res.undo_any() {
                                 info!("Paint undo performed");
                             } else {
-                                debug!("Paint undo: nothing to undo");
+                                // Nothing on the canvases; take back a mesh stroke instead
+                                #[cfg(feature = "mesh_painting")]
+                                mesh_paint_events.push(MeshPaintEvent::Undo);
+                                debug!("Paint undo: nothing to undo on canvases");
                             }
                         }
                         PaintCommand::SetLiveProjection { enabled } => {
-                            debug!("Set live projection to {}", enabled);
-                            // TODO: Implement live projection toggle
+                            projection_events.push(ProjectionEvent::SetLiveProjection { enabled });
+                        }
+                        PaintCommand::ProjectToScene { options } => {
+                            projection_events.push(ProjectionEvent::ProjectToScene { options });
                         }
-                        PaintCommand::ProjectToScene => {
-                            debug!("Project to scene requested");
-                            // TODO: Implement one-shot projection
+                        PaintCommand::ProjectToSelection { options } => {
+                            projection_events.push(ProjectionEvent::ProjectToSelection { options });
+                        }
+                        PaintCommand::ReprojectLast => {
+                            projection_events.push(ProjectionEvent::ReprojectLast);
                         }
                         PaintCommand::AddLayer { name } => {
                             if let Some(plane_id) = active_plane_id {
@@ -213,9 +276,140 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
                                 }
                             }
                         }
+                        PaintCommand::ExportCanvas { path } => {
+                            canvas_file_events.push(CanvasFileEvent::Export {
+                                path: path.map(Into::into),
+                            });
+                        }
+                        PaintCommand::ImportCanvas { path } => {
+                            canvas_file_events.push(CanvasFileEvent::Import { path: path.into() });
+                        }
+                        PaintCommand::ResizeCanvas {
+                            width,
+                            height,
+                            anchor,
+                        } => {
+                            canvas_resize_events.push(CanvasResizeEvent::Resize {
+                                width,
+                                height,
+                                anchor,
+                            });
+                        }
+                        PaintCommand::CropCanvas {
+                            x,
+                            y,
+                            width,
+                            height,
+                        } => {
+                            canvas_resize_events.push(CanvasResizeEvent::Crop {
+                                x,
+                                y,
+                                width,
+                                height,
+                            });
+                        }
+                        PaintCommand::BeginPixelSelection { mode } => {
+                            pixel_selection_events.push(PixelSelectionEvent::Begin { mode });
+                        }
+                        PaintCommand::TransformSelection {
+                            translate,
+                            rotate_deg,
+                            scale,
+                        } => {
+                            pixel_selection_events.push(PixelSelectionEvent::Transform {
+                                translate: Vec2::from(translate),
+                                rotate_deg,
+                                scale: Vec2::from(scale),
+                            });
+                        }
+                        PaintCommand::CommitSelection => {
+                            pixel_selection_events.push(PixelSelectionEvent::Commit);
+                        }
+                        PaintCommand::CancelSelection => {
+                            pixel_selection_events.push(PixelSelectionEvent::Cancel);
+                        }
+                        PaintCommand::SetBrushTip { source } => {
+                            brush_tip_events.push(BrushTipEvent::SetTip { source });
+                        }
+                        PaintCommand::SetBrushTipRotation { mode } => {
+                            brush_tip_events.push(BrushTipEvent::SetRotation { mode });
+                        }
+                        PaintCommand::Fill {
+                            x,
+                            y,
+                            tolerance,
+                            contiguous,
+                        } => {
+                            canvas_tool_events.push(CanvasToolEvent::Fill {
+                                position: Vec2::new(x, y),
+                                tolerance,
+                                contiguous,
+                            });
+                        }
+                        PaintCommand::Gradient {
+                            start,
+                            end,
+                            kind,
+                            colors,
+                        } => {
+                            canvas_tool_events.push(CanvasToolEvent::Gradient {
+                                start: Vec2::from(start),
+                                end: Vec2::from(end),
+                                kind,
+                                colors,
+                            });
+                        }
+                        PaintCommand::ArmCanvasTool { tool } => {
+                            canvas_tool_events.push(CanvasToolEvent::Arm { tool });
+                        }
+                        PaintCommand::SetMeshPaintChannel { channel } => {
+                            #[cfg(feature = "mesh_painting")]
+                            mesh_paint_events.push(MeshPaintEvent::SetTargetChannel { channel });
+                            debug!("Set mesh paint channel to {:?}", channel);
+                        }
+                        PaintCommand::SetMeshPaintValue { value } => {
+                            #[cfg(feature = "mesh_painting")]
+                            mesh_paint_events.push(MeshPaintEvent::SetBrushValue { value });
+                            debug!("Set mesh paint value to {}", value);
+                        }
+                        PaintCommand::SetNormalStrength { strength } => {
+                            #[cfg(feature = "mesh_painting")]
+                            mesh_paint_events.push(MeshPaintEvent::SetNormalStrength { strength });
+                            debug!("Set normal strength to {}", strength);
+                        }
+                        PaintCommand::SetMeshPaintTarget { target } => {
+                            #[cfg(feature = "mesh_painting")]
+                            mesh_paint_events.push(MeshPaintEvent::SetPaintTarget { target });
+                            debug!("Set mesh paint target to {:?}", target);
+                        }
+                        PaintCommand::ReanchorCanvas { plane_id } => {
+                            canvas_events.push(CanvasPlaneEvent::Reanchor { plane_id });
+                        }
                     }
                 }
             }
+            #[cfg(feature = "sculpting")]
+            UiToBevy::SculptCommand(cmd) => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<SculptEvent>>() {
+                    debug!("Sculpt command from UI: {:?}", cmd);
+                    events.write(SculptEvent::from(cmd));
+                }
+            }
+            UiToBevy::MeasureCommand(cmd) => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<MeasureEvent>>() {
+                    debug!("Measure command from UI: {:?}", cmd);
+                    events.write(MeasureEvent(cmd));
+                }
+            }
+            UiToBevy::SetEditMode { mode } => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<EditModeEvent>>() {
+                    debug!("Edit mode request from UI: {:?}", mode);
+                    events.write(EditModeEvent {
+                        mode: EditorMode::from_ipc(mode),
+                        target: None,
+                    });
+                }
+            }
             UiToBevy::AddObject(request) => {
                 if let Some(mut events) = world.get_resource_mut::<Messages<AddObjectEvent>>() {
                     events.write(AddObjectEvent(request));
@@ -235,12 +429,182 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
                 }
             }
             UiToBevy::SetDepthView { enabled } => {
-                if let Some(mut settings) = world.get_resource_mut::<DepthViewSettings>() {
-                    settings.enabled = enabled;
-                    info!(
-                        "Depth view mode: {}",
-                        if enabled { "enabled" } else { "disabled" }
-                    );
+                if let Some(mut settings) = world.get_resource_mut::<ViewModeSettings>() {
+                    settings.mode = if enabled {
+                        ViewMode::Depth
+                    } else {
+                        ViewMode::Shaded
+                    };
+                }
+            }
+            UiToBevy::SetViewMode { mode } => {
+                if let Some(mut settings) = world.get_resource_mut::<ViewModeSettings>() {
+                    settings.mode = mode;
+                }
+            }
+            UiToBevy::SetCompositeMode { mode } => {
+                // Dioxus uses its own render plugin; only the unified pipeline can hot-switch
+                warn!(
+                    "Cannot switch to {:?} mode at runtime from Dioxus mode, restart with --mode",
+                    mode
+                );
+            }
+            UiToBevy::SetKeybinding { action, chord } => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<KeymapEvent>>() {
+                    events.write(KeymapEvent::SetBinding { action, chord });
+                }
+            }
+            UiToBevy::CameraCommand(cmd) => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<CameraCommandEvent>>() {
+                    events.write(CameraCommandEvent(cmd));
+                }
+            }
+            UiToBevy::GizmoCommand(cmd) => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<GizmoCommandEvent>>() {
+                    events.write(GizmoCommandEvent(cmd));
+                }
+            }
+            UiToBevy::RestoreAutosave { path } => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<AutosaveEvent>>() {
+                    info!("Restoring autosave {} from Dioxus UI", path);
+                    events.write(AutosaveEvent::Restore { path: path.into() });
+                }
+            }
+            UiToBevy::LightCommand(cmd) => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<LightCommandEvent>>() {
+                    events.write(LightCommandEvent(cmd));
+                }
+            }
+            UiToBevy::NodeGraphUpdate(state) => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<NodeGraphEvent>>() {
+                    events.write(NodeGraphEvent(state));
+                }
+            }
+            UiToBevy::ObjectCommand(cmd) => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<ObjectCommandEvent>>() {
+                    events.write(ObjectCommandEvent(cmd));
+                }
+            }
+            UiToBevy::AddReferenceImage { path, mode } => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<ReferenceImageEvent>>()
+                {
+                    events.write(ReferenceImageEvent::Add {
+                        path: path.into(),
+                        mode,
+                    });
+                }
+            }
+            UiToBevy::SetReferenceImageOpacity { id, opacity } => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<ReferenceImageEvent>>()
+                {
+                    events.write(ReferenceImageEvent::SetOpacity { id, opacity });
+                }
+            }
+            UiToBevy::MaterialCommand(MaterialCommand::AssignTexture {
+                material_id,
+                slot,
+                texture_id,
+            }) => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<TextureRegistryEvent>>()
+                {
+                    events.write(TextureRegistryEvent::Assign {
+                        material_id,
+                        slot,
+                        texture_id,
+                    });
+                }
+            }
+            UiToBevy::DeleteTexture { texture_id } => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<TextureRegistryEvent>>()
+                {
+                    events.write(TextureRegistryEvent::Delete { texture_id });
+                }
+            }
+            UiToBevy::RenderTurntable {
+                frames,
+                seconds,
+                width,
+                height,
+                path,
+            } => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<TurntableEvent>>() {
+                    events.write(TurntableEvent::Start(TurntableRequest {
+                        frames,
+                        seconds,
+                        width,
+                        height,
+                        path: path.into(),
+                    }));
+                }
+            }
+            UiToBevy::CancelRender => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<TurntableEvent>>() {
+                    events.write(TurntableEvent::Cancel);
+                }
+            }
+            UiToBevy::UpdateSettings(settings) => {
+                if let Some(mut governor) = world.get_resource_mut::<PerformanceGovernor>() {
+                    governor.apply_settings(&settings);
+                }
+                if let Some(mut navigation) = world.get_resource_mut::<NavigationSettings>() {
+                    navigation.0 = settings.navigation;
+                }
+                if let Some(mut grid) = world.get_resource_mut::<SceneGrid>() {
+                    grid.visible = settings.show_grid;
+                }
+                if let Some(mut orbit) = world.get_resource_mut::<OrbitSettings>() {
+                    orbit.0 = settings.orbit;
+                }
+                #[cfg(feature = "selection")]
+                if let Some(mut outline) = world.get_resource_mut::<OutlineSettings>() {
+                    *outline = OutlineSettings::from(&settings.outline);
+                }
+            }
+            UiToBevy::Query { request_id, query } => {
+                if let Some(mut requests) = world.get_resource_mut::<Messages<QueryRequest>>() {
+                    requests.write(QueryRequest { request_id, query });
+                }
+            }
+            #[cfg(feature = "collab")]
+            UiToBevy::Collab(cmd) => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<CollabEvent>>() {
+                    events.write(CollabEvent(cmd));
+                }
+            }
+            #[cfg(not(feature = "collab"))]
+            UiToBevy::Collab(_) => {
+                warn!("Collab command received, but the collab feature is disabled");
+                outbound_layer_msgs.push(crate::render::collab_unavailable_error());
+            }
+            #[cfg(feature = "wireframe")]
+            UiToBevy::SetWireframe {
+                target,
+                enabled,
+                color,
+                depth_test,
+            } => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<WireframeEvent>>() {
+                    events.write(WireframeEvent {
+                        target,
+                        enabled,
+                        color,
+                        depth_test,
+                    });
+                }
+            }
+            #[cfg(not(feature = "wireframe"))]
+            UiToBevy::SetWireframe { .. } => {
+                warn!("SetWireframe received, but the wireframe feature is disabled");
+                outbound_layer_msgs.push(crate::render::wireframe_unavailable_error());
+            }
+            UiToBevy::CloseResponse { decision } => {
+                if let Some(mut events) = world.get_resource_mut::<Messages<CloseResponseEvent>>() {
+                    events.write(CloseResponseEvent(decision));
+                }
+            }
+            UiToBevy::CompactHistory => {
+                if let Some(mut budget) = world.get_resource_mut::<HistoryBudget>() {
+                    budget.request_compaction();
                 }
             }
             _ => {
@@ -259,6 +623,70 @@ pub fn handle_ui_to_bevy_messages(world: &mut World) {
         }
     }
 
+    // Send collected canvas export/import events
+    if !canvas_file_events.is_empty() {
+        if let Some(mut messages) = world.get_resource_mut::<Messages<CanvasFileEvent>>() {
+            for event in canvas_file_events {
+                messages.write(event);
+            }
+        }
+    }
+
+    // Send collected canvas resize/crop events
+    if !canvas_resize_events.is_empty() {
+        if let Some(mut messages) = world.get_resource_mut::<Messages<CanvasResizeEvent>>() {
+            for event in canvas_resize_events {
+                messages.write(event);
+            }
+        }
+    }
+
+    // Send collected pixel selection events
+    if !pixel_selection_events.is_empty() {
+        if let Some(mut messages) = world.get_resource_mut::<Messages<PixelSelectionEvent>>() {
+            for event in pixel_selection_events {
+                messages.write(event);
+            }
+        }
+    }
+
+    // Send collected brush tip events
+    if !brush_tip_events.is_empty() {
+        if let Some(mut messages) = world.get_resource_mut::<Messages<BrushTipEvent>>() {
+            for event in brush_tip_events {
+                messages.write(event);
+            }
+        }
+    }
+
+    // Send collected fill/gradient tool events
+    if !canvas_tool_events.is_empty() {
+        if let Some(mut messages) = world.get_resource_mut::<Messages<CanvasToolEvent>>() {
+            for event in canvas_tool_events {
+                messages.write(event);
+            }
+        }
+    }
+
+    // Send collected projection events
+    if !projection_events.is_empty() {
+        if let Some(mut messages) = world.get_resource_mut::<Messages<ProjectionEvent>>() {
+            for event in projection_events {
+                messages.write(event);
+            }
+        }
+    }
+
+    // Send collected mesh paint channel and undo events
+    #[cfg(feature = "mesh_painting")]
+    if !mesh_paint_events.is_empty() {
+        if let Some(mut messages) = world.get_resource_mut::<Messages<MeshPaintEvent>>() {
+            for event in mesh_paint_events {
+                messages.write(event);
+            }
+        }
+    }
+
     // Send layer state messages to UI (forwarded to bridge on next frame)
     if !outbound_layer_msgs.is_empty() {
         if let Some(mut outbound) = world.get_resource_mut::<OutboundUiMessages>() {
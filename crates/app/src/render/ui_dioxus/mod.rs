@@ -7,8 +7,10 @@
 //!
 //! 1. Main world: BlitzDocument manages Dioxus VirtualDom + Blitz DOM/layout
 //! 2. Main world (Update): poll() processes state changes, paint_to_scene() builds Vello scene
-//! 3. Extraction: Scene is cloned to render world
-//! 4. Render world: Vello renders the scene directly to Bevy's GpuImage
+//!    (only when the document reports damage)
+//! 3. Extraction: Scene is cloned to render world when it was repainted
+//! 4. Render world: Vello renders the scene directly to Bevy's GpuImage, unless that
+//!    scene generation was already rendered
 //! 5. Bevy composites the texture over the 3D scene
 //!
 //! # Thread Safety
@@ -30,10 +32,13 @@ use bevy::render::renderer::RenderDevice;
 use bevy::render::{Render, RenderApp, RenderSystems};
 use pentimento_dioxus_ui::SharedVelloRenderer;
 
+use super::RenderStatsWindow;
 use super::ui_blend_material::UiBlendMaterialPlugin;
+use super::ui_blobs::{UiBlobs, evict_expired_ui_blobs};
 use render::RenderWorldVelloRenderer;
 use resources::{
-    DioxusRenderTargetId, DioxusSetupStatus, DioxusUiState, VelloRenderStatus, VelloSceneBuffer,
+    DioxusRenderTargetId, DioxusSetupStatus, DioxusUiState, UiRenderCounters, VelloRenderStatus,
+    VelloSceneBuffer,
 };
 
 // Re-export public types
@@ -42,8 +47,8 @@ pub use event_bridge::DioxusRendererResource;
 // Import systems for plugin registration
 use ipc_handler::handle_ui_to_bevy_messages;
 use render::render_vello_to_texture;
-use scene_builder::{build_ui_scene, handle_window_resize};
-use setup::deferred_setup_dioxus_texture;
+use scene_builder::{build_ui_scene, handle_window_resize, send_dioxus_render_stats};
+use setup::{deferred_setup_dioxus_texture, shutdown_dioxus_ui};
 
 /// Plugin for Dioxus UI rendering with zero-copy GPU integration.
 pub struct DioxusRenderPlugin;
@@ -54,6 +59,9 @@ impl Plugin for DioxusRenderPlugin {
         app.init_resource::<DioxusUiState>()
             .init_resource::<VelloSceneBuffer>()
             .init_resource::<DioxusSetupStatus>()
+            .init_resource::<UiRenderCounters>()
+            .init_resource::<RenderStatsWindow>()
+            .init_resource::<UiBlobs>()
             .add_plugins(UiBlendMaterialPlugin)
             .add_plugins(ExtractResourcePlugin::<DioxusUiState>::default())
             .add_plugins(ExtractResourcePlugin::<DioxusRenderTargetId>::default())
@@ -68,10 +76,13 @@ impl Plugin for DioxusRenderPlugin {
                     deferred_setup_dioxus_texture,
                     handle_ui_to_bevy_messages, // Process IPC messages first
                     build_ui_scene,             // Then build scene with updated state
+                    send_dioxus_render_stats,
                     handle_window_resize,
                 )
                     .chain(),
-            );
+            )
+            .add_systems(Update, evict_expired_ui_blobs)
+            .add_systems(Last, shutdown_dioxus_ui.run_if(on_message::<AppExit>));
 
         // Render world setup
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
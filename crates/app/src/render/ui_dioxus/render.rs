@@ -15,6 +15,10 @@ pub struct RenderWorldVelloRenderer {
 }
 
 /// Render Vello scene directly to Bevy's GPU texture (runs in Render set).
+///
+/// Skipped when the scene generation and target texture are the ones already
+/// rendered, so the texture keeps presenting the previous frame. Vello has no
+/// partial render region, so a repaint always covers the whole target.
 pub fn render_vello_to_texture(
     ui_state: Option<Res<DioxusUiState>>,
     render_target: Option<Res<DioxusRenderTargetId>>,
@@ -43,6 +47,12 @@ pub fn render_vello_to_texture(
         return;
     };
 
+    // Nothing changed since the last render (a recreated texture starts blank)
+    let target = (scene.generation, gpu_image.texture.id());
+    if status.last_rendered == Some(target) {
+        return;
+    }
+
     // Log dimensions on first render to help diagnose fuzzy/sharp alternation
     if !status.first_render_done {
         let tex_size = gpu_image.size;
@@ -68,6 +78,7 @@ pub fn render_vello_to_texture(
         error!("Vello render failed: {}", e);
         return;
     }
+    status.last_rendered = Some(target);
 
     if !status.first_render_done {
         info!("First Vello render completed (zero-copy to GpuImage)");
@@ -3,6 +3,7 @@
 use bevy::asset::AssetId;
 use bevy::prelude::*;
 use bevy::render::extract_resource::ExtractResource;
+use bevy::render::render_resource::TextureId;
 use pentimento_dioxus_ui::Scene;
 
 // ============================================================================
@@ -13,8 +14,12 @@ use pentimento_dioxus_ui::Scene;
 /// This contains viewport dimensions needed for Vello rendering.
 #[derive(Resource, Clone, ExtractResource, Default)]
 pub struct DioxusUiState {
+    /// Texture width in physical pixels
     pub width: u32,
+    /// Texture height in physical pixels
     pub height: u32,
+    /// Device scale the document is painted at
+    pub scale_factor: f64,
 }
 
 /// Handle to the render target texture (extracted to render world via AssetId).
@@ -33,9 +38,23 @@ pub struct DioxusUiOverlay;
 
 /// Pre-built Vello scene for the current frame.
 /// Built in main world, extracted to render world.
+///
+/// Only touched when the document repaints, so an unchanged scene isn't
+/// re-extracted either.
 #[derive(Resource, Clone, Default, ExtractResource)]
 pub struct VelloSceneBuffer {
     pub scene: Scene,
+    /// Bumped on every repaint; the render world skips generations it already rendered
+    pub generation: u64,
+}
+
+/// Counters for the current `RenderStats` interval.
+#[derive(Resource, Default)]
+pub struct UiRenderCounters {
+    /// Frames where the document repainted
+    pub ui_renders: u32,
+    /// Frames where the document was clean and the Vello render was skipped
+    pub ui_renders_skipped: u32,
 }
 
 // ============================================================================
@@ -46,6 +65,8 @@ pub struct VelloSceneBuffer {
 #[derive(Resource, Default)]
 pub struct VelloRenderStatus {
     pub first_render_done: bool,
+    /// Scene generation and target texture of the last render
+    pub last_rendered: Option<(u64, TextureId)>,
 }
 
 /// Track whether Dioxus UI setup is complete (main world).
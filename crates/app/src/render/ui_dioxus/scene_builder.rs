@@ -3,12 +3,19 @@
 use bevy::prelude::*;
 use bevy::render::render_resource::Extent3d;
 use pentimento_dioxus_ui::{BlitzDocument, UiEvent};
-use pentimento_scene::OutboundUiMessages;
+use pentimento_ipc::BevyToUi;
+use pentimento_scene::{
+    AddMenuAnchor, HistoryBudget, OutboundUiMessages, ProjectionStats, TextureUploadStats,
+};
 
+use super::super::{PerformanceGovernor, PowerSaving, RenderStatsWindow, VisibleTriangles};
 use super::event_bridge::{BlitzDocumentResource, DioxusEventReceiver};
-use super::resources::{DioxusRenderTarget, DioxusSetupStatus, DioxusUiState, VelloSceneBuffer};
+use super::resources::{
+    DioxusRenderTarget, DioxusSetupStatus, DioxusUiState, UiRenderCounters, VelloSceneBuffer,
+};
 
 /// Build the UI scene from BlitzDocument (runs every frame in main world).
+/// Only repaints when the document reports damage.
 /// This is an exclusive system because BlitzDocumentResource is NonSend.
 pub fn build_ui_scene(world: &mut World) {
     // Skip if setup hasn't completed yet (receiver doesn't exist until then)
@@ -66,10 +73,33 @@ pub fn build_ui_scene(world: &mut World) {
     // If viewport was clicked, notify UI to close menus
     if viewport_clicked {
         if let Some(mut outbound) = world.get_resource_mut::<OutboundUiMessages>() {
-            outbound.send(pentimento_ipc::BevyToUi::CloseMenus);
+            outbound.send(BevyToUi::CloseMenus);
+        }
+        if let Some(mut anchor) = world.get_resource_mut::<AddMenuAnchor>() {
+            anchor.0 = None;
         }
     }
 
+    // Keep the previous texture when nothing changed. Not touching
+    // VelloSceneBuffer also keeps it from being re-extracted.
+    let damage = {
+        let Some(mut doc_resource) = world.get_non_send_resource_mut::<BlitzDocumentResource>()
+        else {
+            return;
+        };
+        doc_resource.document.take_damage()
+    };
+    let Some(damage) = damage else {
+        if let Some(mut counters) = world.get_resource_mut::<UiRenderCounters>() {
+            counters.ui_renders_skipped += 1;
+        }
+        return;
+    };
+    trace!("Repainting Dioxus UI, damage {:?}", damage);
+    if let Some(mut counters) = world.get_resource_mut::<UiRenderCounters>() {
+        counters.ui_renders += 1;
+    }
+
     // Get a raw pointer to the document for painting
     // SAFETY: We only hold an immutable reference to the document while mutating the scene buffer.
     // The document and scene buffer are independent resources with no aliasing.
@@ -89,20 +119,78 @@ pub fn build_ui_scene(world: &mut World) {
     unsafe {
         (*doc_ptr).paint_to_scene(&mut scene_buffer.scene);
     }
+    scene_buffer.generation += 1;
+}
+
+/// Send frame rate and repaint counters to the UI once per `RenderStatsWindow` interval.
+#[allow(clippy::too_many_arguments)]
+pub fn send_dioxus_render_stats(
+    mut window: ResMut<RenderStatsWindow>,
+    mut counters: ResMut<UiRenderCounters>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    projection_stats: Option<ResMut<ProjectionStats>>,
+    upload_stats: Option<ResMut<TextureUploadStats>>,
+    governor: Option<Res<PerformanceGovernor>>,
+    history_budget: Option<Res<HistoryBudget>>,
+    ui_state: Option<Res<DioxusUiState>>,
+    power_saving: Res<PowerSaving>,
+    triangles: VisibleTriangles,
+) {
+    // Start a fresh window when back in the foreground
+    if power_saving.is_background() {
+        *window = RenderStatsWindow::default();
+        return;
+    }
+    let Some((seconds, frames)) = window.tick() else {
+        return;
+    };
+
+    let projected_texels = projection_stats.map_or(0, |mut stats| stats.take_texels_reprojected());
+    let tiles_uploaded = upload_stats.map_or(0, |mut stats| stats.take_tiles_uploaded());
+    debug!(
+        "Dioxus UI renders: {}, skipped: {}",
+        counters.ui_renders, counters.ui_renders_skipped
+    );
+
+    outbound.send(BevyToUi::RenderStats {
+        fps: frames as f32 / seconds,
+        frame_time_ms: seconds * 1000.0 / frames as f32,
+        // Not tracked yet
+        draw_calls: 0,
+        triangles: triangles.count(),
+        // Vello renders straight to the texture; nothing is captured
+        captures_per_second: 0.0,
+        skipped_captures: 0,
+        projected_texels_per_frame: projected_texels as f32 / frames as f32,
+        tiles_uploaded_per_frame: tiles_uploaded as f32 / frames as f32,
+        ui_renders_skipped: counters.ui_renders_skipped,
+        render_scale: governor.map_or(1.0, |governor| governor.render_scale()),
+        scale_factor: ui_state.map_or(1.0, |state| state.scale_factor as f32),
+        history_bytes: history_budget
+            .as_ref()
+            .map_or(0, |budget| budget.used_bytes()),
+        history_budget_bytes: history_budget.map_or(0, |budget| budget.budget_bytes),
+    });
+
+    *counters = UiRenderCounters::default();
 }
 
-/// Handle window resize - update texture, UI state, and BlitzDocument.
+/// Handle window resize and scale changes - update texture, UI state, and BlitzDocument.
 /// This is an exclusive system because BlitzDocumentResource is NonSend.
+///
+/// The document lays out in logical pixels; the texture follows the physical
+/// size, so moving the window to a monitor with a different scale re-renders
+/// the UI sharp at the new density.
 pub fn handle_window_resize(world: &mut World) {
     // Check if window changed
     // Use LOGICAL dimensions to match initial setup and mouse coordinates
-    let (width, height, _changed) = {
+    let (width, height, scale_factor) = {
         let mut query = world.query_filtered::<&Window, Changed<Window>>();
         match query.iter(world).next() {
             Some(window) => (
                 window.resolution.width() as u32,  // logical width
                 window.resolution.height() as u32, // logical height
-                true,
+                f64::from(window.resolution.scale_factor()),
             ),
             None => return,
         }
@@ -112,33 +200,38 @@ pub fn handle_window_resize(world: &mut World) {
         return;
     }
 
-    // Check if size actually changed
+    // Resize and rescale the BlitzDocument; both are no-ops when unchanged
+    let physical_size = {
+        let Some(mut doc_resource) = world.get_non_send_resource_mut::<BlitzDocumentResource>()
+        else {
+            return;
+        };
+        doc_resource.document.set_scale(scale_factor);
+        doc_resource.document.resize(width, height);
+        doc_resource.document.physical_size()
+    };
+
+    // Check if the texture size actually changed
     let current_size = {
         world
             .get_resource::<DioxusUiState>()
             .map(|s| (s.width, s.height))
     };
-
-    if let Some((cur_w, cur_h)) = current_size {
-        if cur_w == width && cur_h == height {
-            return;
-        }
+    if current_size == Some(physical_size) {
+        return;
     }
 
+    let (texture_width, texture_height) = physical_size;
     info!(
-        "Window resized to {}x{} logical, updating UI texture",
-        width, height
+        "Window resized to {}x{} logical (scale {:.2}), updating UI texture to {}x{}",
+        width, height, scale_factor, texture_width, texture_height
     );
 
     // Update UI state
     if let Some(mut ui_state) = world.get_resource_mut::<DioxusUiState>() {
-        ui_state.width = width;
-        ui_state.height = height;
-    }
-
-    // Resize the BlitzDocument
-    if let Some(mut doc_resource) = world.get_non_send_resource_mut::<BlitzDocumentResource>() {
-        doc_resource.document.resize(width, height);
+        ui_state.width = texture_width;
+        ui_state.height = texture_height;
+        ui_state.scale_factor = scale_factor;
     }
 
     // Resize the Bevy Image asset
@@ -149,8 +242,8 @@ pub fn handle_window_resize(world: &mut World) {
         if let Some(mut images) = world.get_resource_mut::<Assets<Image>>() {
             if let Some(image) = images.get_mut(&handle) {
                 image.resize(Extent3d {
-                    width,
-                    height,
+                    width: texture_width,
+                    height: texture_height,
                     depth_or_array_layers: 1,
                 });
             }
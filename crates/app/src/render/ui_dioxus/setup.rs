@@ -1,10 +1,12 @@
 //! Dioxus UI texture setup and initialization.
 
+use std::sync::Arc;
+
 use bevy::asset::RenderAssetUsages;
 use bevy::picking::prelude::Pickable;
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
-use pentimento_dioxus_ui::{BlitzDocument, DioxusBridge};
+use pentimento_dioxus_ui::{AssetSource, BlitzDocument, DioxusBridge};
 
 use super::event_bridge::{
     BlitzDocumentResource, DioxusBridgeResource, DioxusRendererResource, create_event_channel,
@@ -12,6 +14,7 @@ use super::event_bridge::{
 use super::resources::{
     DioxusRenderTarget, DioxusRenderTargetId, DioxusSetupStatus, DioxusUiOverlay, DioxusUiState,
 };
+use crate::embedded_ui::UiAssets;
 use crate::render::ui_blend_material::UiBlendMaterial;
 
 /// Deferred setup that waits for window size to stabilize before initializing.
@@ -46,8 +49,9 @@ pub fn deferred_setup_dioxus_texture(world: &mut World) {
 /// This is an exclusive system because BlitzDocumentResource is NonSend.
 pub fn setup_dioxus_texture(world: &mut World) {
     // Get window dimensions in LOGICAL pixels
-    // We use logical coordinates throughout to match Bevy's mouse event coordinates
-    let (width, height) = {
+    // Layout and input use logical coordinates to match Bevy's mouse event
+    // coordinates; the document is painted at the device scale
+    let (width, height, scale_factor) = {
         let mut window_query = world.query::<&Window>();
         let Some(window) = window_query.iter(world).next() else {
             error!("No window found for Dioxus UI setup");
@@ -56,24 +60,9 @@ pub fn setup_dioxus_texture(world: &mut World) {
         (
             window.resolution.width() as u32,  // logical width
             window.resolution.height() as u32, // logical height
+            f64::from(window.resolution.scale_factor()),
         )
     };
-    // Use scale_factor of 1.0 since we're working in logical pixels
-    let scale_factor = 1.0_f64;
-
-    // Get the actual scale factor for logging
-    let actual_scale = {
-        let mut window_query = world.query::<&Window>();
-        window_query
-            .iter(world)
-            .next()
-            .map(|w| w.resolution.scale_factor())
-            .unwrap_or(1.0)
-    };
-    info!(
-        "Setting up Dioxus UI texture: {}x{} logical (device scale={}, using scale=1.0)",
-        width, height, actual_scale
-    );
 
     // Create the IPC bridge (non-send due to mpsc::Receiver)
     let (bridge, bridge_handle) = DioxusBridge::new();
@@ -85,14 +74,23 @@ pub fn setup_dioxus_texture(world: &mut World) {
     world.insert_non_send_resource(event_receiver);
 
     // Create the BlitzDocument with our Dioxus UI components
-    let document = BlitzDocument::new(width, height, scale_factor, bridge);
+    // `asset:///` URLs fall back to the embedded UI bundle
+    let assets: AssetSource = Arc::new(|path: &str| {
+        UiAssets::get(&format!("ui/{path}")).map(|file| file.data.into_owned())
+    });
+    let document = BlitzDocument::new(width, height, scale_factor, bridge, Some(assets));
+    let (texture_width, texture_height) = document.physical_size();
+    info!(
+        "Setting up Dioxus UI texture: {}x{} logical, {}x{} physical (scale={})",
+        width, height, texture_width, texture_height, scale_factor
+    );
     world.insert_non_send_resource(BlitzDocumentResource { document });
 
-    // Create a Bevy Image for the UI texture
+    // Create a Bevy Image for the UI texture at the physical size
     let mut image = Image::new_fill(
         Extent3d {
-            width,
-            height,
+            width: texture_width,
+            height: texture_height,
             depth_or_array_layers: 1,
         },
         TextureDimension::D2,
@@ -114,7 +112,11 @@ pub fn setup_dioxus_texture(world: &mut World) {
         handle: handle.clone(),
     });
     world.insert_resource(DioxusRenderTargetId(handle.id()));
-    world.insert_resource(DioxusUiState { width, height });
+    world.insert_resource(DioxusUiState {
+        width: texture_width,
+        height: texture_height,
+        scale_factor,
+    });
 
     // Create the blend material for proper alpha compositing
     let material_handle = world
@@ -140,3 +142,17 @@ pub fn setup_dioxus_texture(world: &mut World) {
 
     info!("Dioxus UI overlay created with Blitz+Vello rendering");
 }
+
+/// Tear down the Dioxus UI while the app is exiting.
+///
+/// Dropping the document drops the VirtualDom, which cancels its spawned
+/// tasks (e.g. net provider fetches) before the async runtime goes away.
+pub fn shutdown_dioxus_ui(world: &mut World) {
+    if world
+        .remove_non_send_resource::<BlitzDocumentResource>()
+        .is_some()
+    {
+        info!("Dioxus UI shut down");
+    }
+    world.remove_non_send_resource::<DioxusBridgeResource>();
+}
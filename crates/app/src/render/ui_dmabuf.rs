@@ -0,0 +1,277 @@
+//! DMA-BUF import for shared-texture UI frames (`cef-gpu` feature, Linux).
+//!
+//! `CaptureResult::GpuExternal` frames are imported as a Vulkan image that
+//! aliases the DMA-BUF memory, wrapped as a wgpu texture, and copied into the
+//! UI texture on the GPU. The pixels never touch the CPU.
+//!
+//! Supported (anything else falls back to CPU captures):
+//! - Vulkan backend with `VK_KHR_external_memory_fd` and
+//!   `VK_EXT_external_memory_dma_buf` (wgpu enables both when available)
+//! - Single-plane `DRM_FORMAT_MOD_LINEAR` buffers whose stride matches the
+//!   driver's linear layout. wgpu doesn't enable `VK_EXT_image_drm_format_modifier`,
+//!   so tiled layouts can't be described.
+//!
+//! wgpu transitions the imported image from `UNDEFINED` on first use. Mesa
+//! keeps linear image contents across that transition, which this relies on.
+
+use std::os::fd::{AsRawFd, IntoRawFd};
+
+use ash::{ext, khr, vk};
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use bevy::render::texture::GpuImage;
+use pentimento_frontend_core::{DmabufPlane, DmabufTexture, ExternalTextureFormat};
+use wgpu::hal::api::Vulkan;
+
+const DMA_BUF: vk::ExternalMemoryHandleTypeFlags = vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT;
+
+/// Check that the render device can import DMA-BUFs at all.
+pub fn check_support(device: &RenderDevice) -> Result<(), String> {
+    // Safety: the hal device is only used to query its enabled extensions
+    let hal_device = unsafe { device.wgpu_device().as_hal::<Vulkan>() }
+        .ok_or_else(|| "renderer is not using Vulkan".to_string())?;
+
+    let enabled = hal_device.enabled_device_extensions();
+    for required in [
+        khr::external_memory_fd::NAME,
+        ext::external_memory_dma_buf::NAME,
+    ] {
+        if !enabled.contains(&required) {
+            return Err(format!("{} is not enabled", required.to_string_lossy()));
+        }
+    }
+    Ok(())
+}
+
+/// Import `dmabuf` and copy it into `target` (sizes must already match).
+pub fn copy_to_texture(
+    device: &RenderDevice,
+    queue: &RenderQueue,
+    dmabuf: &DmabufTexture,
+    target: &GpuImage,
+) -> Result<(), String> {
+    let source = import(device, dmabuf, target.texture_format)?;
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("ui_dmabuf_copy"),
+    });
+    encoder.copy_texture_to_texture(
+        source.as_image_copy(),
+        target.texture.as_image_copy(),
+        wgpu::Extent3d {
+            width: dmabuf.width,
+            height: dmabuf.height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit([encoder.finish()]);
+
+    // The Vulkan image and memory are released by wgpu once the copy completes
+    Ok(())
+}
+
+/// Wrap `dmabuf` in a wgpu texture with the UI texture's format.
+fn import(
+    device: &RenderDevice,
+    dmabuf: &DmabufTexture,
+    format: wgpu::TextureFormat,
+) -> Result<wgpu::Texture, String> {
+    if dmabuf.modifier != DmabufTexture::MODIFIER_LINEAR {
+        return Err(format!("DRM modifier {:#x} is not linear", dmabuf.modifier));
+    }
+    let [plane] = dmabuf.planes.as_slice() else {
+        return Err(format!("{} planes, expected 1", dmabuf.planes.len()));
+    };
+    if plane.offset != 0 {
+        return Err(format!("plane offset {} is not supported", plane.offset));
+    }
+    let vk_format = vk_format(dmabuf.format, format)?;
+
+    let size = wgpu::Extent3d {
+        width: dmabuf.width,
+        height: dmabuf.height,
+        depth_or_array_layers: 1,
+    };
+
+    let hal_texture = {
+        // Safety: raw handles are only used to create resources owned by the
+        // returned texture
+        let hal_device = unsafe { device.wgpu_device().as_hal::<Vulkan>() }
+            .ok_or_else(|| "renderer is not using Vulkan".to_string())?;
+        let raw_device = hal_device.raw_device().clone();
+        let instance = hal_device.shared_instance().raw_instance();
+
+        let (image, memory) =
+            unsafe { create_dmabuf_image(instance, &raw_device, dmabuf, plane, vk_format) }?;
+
+        let hal_desc = wgpu::hal::TextureDescriptor {
+            label: Some("ui_dmabuf"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUses::COPY_SRC,
+            memory_flags: wgpu::hal::MemoryFlags::empty(),
+            view_formats: Vec::new(),
+        };
+
+        // wgpu calls this once the texture is dropped and no longer in use
+        let release: wgpu::hal::DropCallback = Box::new(move || unsafe {
+            raw_device.destroy_image(image, None);
+            raw_device.free_memory(memory, None);
+        });
+
+        // Safety: `image` was created from `hal_desc` and stays valid until
+        // `release` runs
+        unsafe { hal_device.texture_from_raw(image, &hal_desc, Some(release)) }
+    };
+
+    // Safety: the hal texture was created on this device from the same descriptor
+    Ok(unsafe {
+        device.wgpu_device().create_texture_from_hal::<Vulkan>(
+            hal_texture,
+            &wgpu::TextureDescriptor {
+                label: Some("ui_dmabuf"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            },
+        )
+    })
+}
+
+/// Create a linear Vulkan image bound to the DMA-BUF's memory.
+///
+/// # Safety
+///
+/// `device` must have been created from `instance` with the extensions checked
+/// by `check_support`.
+unsafe fn create_dmabuf_image(
+    instance: &ash::Instance,
+    device: &ash::Device,
+    dmabuf: &DmabufTexture,
+    plane: &DmabufPlane,
+    format: vk::Format,
+) -> Result<(vk::Image, vk::DeviceMemory), String> {
+    let mut external_info = vk::ExternalMemoryImageCreateInfo::default().handle_types(DMA_BUF);
+    let image_info = vk::ImageCreateInfo::default()
+        .image_type(vk::ImageType::TYPE_2D)
+        .format(format)
+        .extent(vk::Extent3D {
+            width: dmabuf.width,
+            height: dmabuf.height,
+            depth: 1,
+        })
+        .mip_levels(1)
+        .array_layers(1)
+        .samples(vk::SampleCountFlags::TYPE_1)
+        .tiling(vk::ImageTiling::LINEAR)
+        .usage(vk::ImageUsageFlags::TRANSFER_SRC)
+        .sharing_mode(vk::SharingMode::EXCLUSIVE)
+        .initial_layout(vk::ImageLayout::UNDEFINED)
+        .push_next(&mut external_info);
+
+    let image = unsafe { device.create_image(&image_info, None) }
+        .map_err(|e| format!("vkCreateImage failed: {e}"))?;
+
+    match unsafe { bind_dmabuf_memory(instance, device, image, plane) } {
+        Ok(memory) => Ok((image, memory)),
+        Err(e) => {
+            unsafe { device.destroy_image(image, None) };
+            Err(e)
+        }
+    }
+}
+
+/// Import the plane's fd as dedicated memory for `image` and bind it.
+unsafe fn bind_dmabuf_memory(
+    instance: &ash::Instance,
+    device: &ash::Device,
+    image: vk::Image,
+    plane: &DmabufPlane,
+) -> Result<vk::DeviceMemory, String> {
+    // Without explicit modifiers the driver picks the linear row pitch, so it
+    // has to match what the exporter wrote
+    let layout = unsafe {
+        device.get_image_subresource_layout(
+            image,
+            vk::ImageSubresource {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                array_layer: 0,
+            },
+        )
+    };
+    if layout.offset != 0 || layout.row_pitch != u64::from(plane.stride) {
+        return Err(format!(
+            "stride {} does not match the driver's linear row pitch {}",
+            plane.stride, layout.row_pitch
+        ));
+    }
+
+    // Vulkan takes ownership of the fd on a successful import
+    let fd = plane
+        .fd
+        .try_clone()
+        .map_err(|e| format!("failed to duplicate DMA-BUF fd: {e}"))?;
+
+    let fd_device = khr::external_memory_fd::Device::new(instance, device);
+    let mut fd_properties = vk::MemoryFdPropertiesKHR::default();
+    unsafe { fd_device.get_memory_fd_properties(DMA_BUF, fd.as_raw_fd(), &mut fd_properties) }
+        .map_err(|e| format!("vkGetMemoryFdPropertiesKHR failed: {e}"))?;
+
+    let requirements = unsafe { device.get_image_memory_requirements(image) };
+    let type_bits = requirements.memory_type_bits & fd_properties.memory_type_bits;
+    if type_bits == 0 {
+        return Err("no memory type can import this DMA-BUF".to_string());
+    }
+
+    let mut import_info = vk::ImportMemoryFdInfoKHR::default()
+        .handle_type(DMA_BUF)
+        .fd(fd.as_raw_fd());
+    let mut dedicated_info = vk::MemoryDedicatedAllocateInfo::default().image(image);
+    let allocate_info = vk::MemoryAllocateInfo::default()
+        .allocation_size(requirements.size)
+        .memory_type_index(type_bits.trailing_zeros())
+        .push_next(&mut import_info)
+        .push_next(&mut dedicated_info);
+
+    let memory = unsafe { device.allocate_memory(&allocate_info, None) }
+        .map_err(|e| format!("DMA-BUF import failed: {e}"))?;
+    let _ = fd.into_raw_fd();
+
+    if let Err(e) = unsafe { device.bind_image_memory(image, memory, 0) } {
+        unsafe { device.free_memory(memory, None) };
+        return Err(format!("vkBindImageMemory failed: {e}"));
+    }
+
+    Ok(memory)
+}
+
+/// Vulkan format for copying `source` frames into a `target` texture.
+fn vk_format(
+    source: ExternalTextureFormat,
+    target: wgpu::TextureFormat,
+) -> Result<vk::Format, String> {
+    match (source, target) {
+        (ExternalTextureFormat::Bgra8, wgpu::TextureFormat::Bgra8Unorm) => {
+            Ok(vk::Format::B8G8R8A8_UNORM)
+        }
+        (ExternalTextureFormat::Bgra8, wgpu::TextureFormat::Bgra8UnormSrgb) => {
+            Ok(vk::Format::B8G8R8A8_SRGB)
+        }
+        (ExternalTextureFormat::Rgba8, wgpu::TextureFormat::Rgba8Unorm) => {
+            Ok(vk::Format::R8G8B8A8_UNORM)
+        }
+        (ExternalTextureFormat::Rgba8, wgpu::TextureFormat::Rgba8UnormSrgb) => {
+            Ok(vk::Format::R8G8B8A8_SRGB)
+        }
+        _ => Err(format!(
+            "{source:?} frames can't be copied into a {target:?} texture"
+        )),
+    }
+}
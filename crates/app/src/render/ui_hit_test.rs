@@ -0,0 +1,220 @@
+//! Which parts of the window the main UI takes the mouse in
+//!
+//! The UI texture covers the whole window, but most of it is transparent.
+//! Each CPU capture is reduced to a low-resolution alpha mask, and the cursor
+//! is over the UI where the mask is at least half opaque. Transparent pixels,
+//! panel shadows, and the soft edges of semi-transparent panels stay with the
+//! scene, so gizmo handles poking out from under a panel can still be grabbed.
+//!
+//! Without CPU pixels (shared GPU textures, Overlay mode) the panel rectangles
+//! from `UiToBevy::LayoutUpdate` are used instead.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use pentimento_ipc::LayoutInfo;
+use pentimento_scene::PointerOverUi;
+
+/// Capture pixels per mask cell along each axis
+const MASK_CELL: u32 = 4;
+
+/// Alpha from which a UI pixel takes the mouse
+const UI_HIT_ALPHA: u8 = 128;
+
+/// Highest alpha in each `MASK_CELL`-sized block of a capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiAlphaMask {
+    width: u32,
+    height: u32,
+    alpha: Vec<u8>,
+}
+
+impl UiAlphaMask {
+    /// Reduce tightly packed 4-byte pixels (RGBA or BGRA) to a mask
+    pub fn from_pixels(pixels: &[u8], width: u32, height: u32) -> Self {
+        let mask_width = width.div_ceil(MASK_CELL);
+        let mask_height = height.div_ceil(MASK_CELL);
+        let mut alpha = vec![0u8; (mask_width * mask_height) as usize];
+        for (y, row) in pixels
+            .chunks_exact(width as usize * 4)
+            .take(height as usize)
+            .enumerate()
+        {
+            let mask_row = (y as u32 / MASK_CELL * mask_width) as usize;
+            for (x, pixel) in row.chunks_exact(4).enumerate() {
+                let cell = &mut alpha[mask_row + x / MASK_CELL as usize];
+                *cell = (*cell).max(pixel[3]);
+            }
+        }
+        Self {
+            width: mask_width,
+            height: mask_height,
+            alpha,
+        }
+    }
+
+    /// Alpha at `uv` (0..1 across the capture); 0 outside it
+    pub fn alpha_at(&self, uv: Vec2) -> u8 {
+        if !(0.0..1.0).contains(&uv.x) || !(0.0..1.0).contains(&uv.y) {
+            return 0;
+        }
+        let x = (uv.x * self.width as f32) as usize;
+        let y = (uv.y * self.height as f32) as usize;
+        self.alpha
+            .get(y * self.width as usize + x)
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// Hit test for the main UI, updated on every capture and layout change
+#[derive(Resource, Default)]
+pub struct UiHitTest {
+    /// Mask of the last CPU capture, `None` when the UI isn't captured to the CPU
+    mask: Option<UiAlphaMask>,
+    /// Panels of the main UI in logical pixels
+    regions: Vec<Rect>,
+}
+
+impl UiHitTest {
+    /// Record the alpha of a new capture
+    pub fn set_capture(&mut self, pixels: &[u8], width: u32, height: u32) {
+        self.mask = Some(UiAlphaMask::from_pixels(pixels, width, height));
+    }
+
+    /// Fall back to the layout regions (no CPU pixels for this capture)
+    pub fn clear_capture(&mut self) {
+        self.mask = None;
+    }
+
+    /// Record the main UI's panel layout (`UiToBevy::LayoutUpdate`)
+    ///
+    /// Regions with `z_index < 0` are click-through.
+    pub fn set_layout(&mut self, layout: &LayoutInfo) {
+        self.regions = layout
+            .regions
+            .iter()
+            .filter(|region| region.z_index >= 0)
+            .map(|region| {
+                Rect::new(
+                    region.x,
+                    region.y,
+                    region.x + region.width,
+                    region.y + region.height,
+                )
+            })
+            .collect();
+    }
+
+    /// Whether the UI takes the mouse at `cursor` in a window of `window_size`
+    /// (both logical pixels)
+    pub fn covers(&self, cursor: Vec2, window_size: Vec2) -> bool {
+        match &self.mask {
+            Some(mask) if window_size.x > 0.0 && window_size.y > 0.0 => {
+                mask.alpha_at(cursor / window_size) >= UI_HIT_ALPHA
+            }
+            _ => self.regions.iter().any(|region| region.contains(cursor)),
+        }
+    }
+}
+
+/// Tell the scene whether the cursor is over the main UI, before gizmos are hovered
+pub fn update_pointer_over_ui(
+    hit_test: Res<UiHitTest>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut pointer_over_ui: ResMut<PointerOverUi>,
+) {
+    let over_ui = windows.single().ok().is_some_and(|window| {
+        window
+            .cursor_position()
+            .is_some_and(|cursor| hit_test.covers(cursor, window.size()))
+    });
+    if pointer_over_ui.0 != over_ui {
+        pointer_over_ui.0 = over_ui;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pentimento_ipc::LayoutRegion;
+
+    use super::*;
+
+    /// 64x32 capture: an opaque panel over the left 24 columns whose last
+    /// 8 columns fade out, transparent elsewhere
+    fn panel_capture() -> Vec<u8> {
+        let mut pixels = vec![0u8; 64 * 32 * 4];
+        for y in 0..32 {
+            for x in 0..24 {
+                let alpha = if x < 16 {
+                    235
+                } else {
+                    100 - (x as u8 - 16) * 12
+                };
+                pixels[(y * 64 + x) * 4..][..4].copy_from_slice(&[40, 40, 40, alpha]);
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_mask_keeps_the_highest_alpha_per_cell() {
+        let mask = UiAlphaMask::from_pixels(&panel_capture(), 64, 32);
+        assert_eq!((mask.width, mask.height), (16, 8));
+        assert_eq!(mask.alpha_at(Vec2::new(0.1, 0.5)), 235);
+        // Cell of columns 16..20 holds the most opaque fading pixel
+        assert_eq!(mask.alpha_at(Vec2::new(17.0 / 64.0, 0.5)), 100);
+        assert_eq!(mask.alpha_at(Vec2::new(0.75, 0.5)), 0);
+        assert_eq!(mask.alpha_at(Vec2::new(1.5, 0.5)), 0);
+    }
+
+    #[test]
+    fn test_semi_transparent_panel_edge_passes_through() {
+        let mut hit_test = UiHitTest::default();
+        hit_test.set_capture(&panel_capture(), 64, 32);
+        // The capture may be at a different resolution than the window
+        let window = Vec2::new(128.0, 64.0);
+
+        assert!(hit_test.covers(Vec2::new(10.0, 30.0), window));
+        // The fading edge (capture columns 16..24) belongs to the scene
+        assert!(!hit_test.covers(Vec2::new(34.0, 30.0), window));
+        assert!(!hit_test.covers(Vec2::new(100.0, 30.0), window));
+    }
+
+    #[test]
+    fn test_layout_regions_without_a_capture() {
+        let mut hit_test = UiHitTest::default();
+        hit_test.set_layout(&LayoutInfo {
+            regions: vec![
+                LayoutRegion {
+                    id: "toolbar".into(),
+                    x: 0.0,
+                    y: 0.0,
+                    width: 200.0,
+                    height: 40.0,
+                    z_index: 0,
+                    accepts_keyboard: true,
+                },
+                LayoutRegion {
+                    id: "backdrop".into(),
+                    x: 0.0,
+                    y: 100.0,
+                    width: 200.0,
+                    height: 40.0,
+                    z_index: -1,
+                    accepts_keyboard: false,
+                },
+            ],
+        });
+        let window = Vec2::new(800.0, 600.0);
+        assert!(hit_test.covers(Vec2::new(50.0, 20.0), window));
+        // Click-through regions and empty space go to the scene
+        assert!(!hit_test.covers(Vec2::new(50.0, 120.0), window));
+        assert!(!hit_test.covers(Vec2::new(400.0, 300.0), window));
+
+        // A capture takes over until it is cleared again
+        hit_test.set_capture(&vec![0u8; 8 * 8 * 4], 8, 8);
+        assert!(!hit_test.covers(Vec2::new(50.0, 20.0), window));
+        hit_test.clear_capture();
+        assert!(hit_test.covers(Vec2::new(50.0, 20.0), window));
+    }
+}
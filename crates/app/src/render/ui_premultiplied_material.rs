@@ -0,0 +1,46 @@
+//! UI material for premultiplied-alpha UI textures
+//!
+//! Bevy's `ImageNode` blends straight alpha, which leaves dark fringes along
+//! anti-aliased edges of a premultiplied texture. Captured webview frames are
+//! premultiplied on upload, so the overlay and screen surfaces are drawn with
+//! this material instead.
+
+use bevy::asset::embedded_asset;
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, BlendState, RenderPipelineDescriptor};
+use bevy::shader::ShaderRef;
+
+/// Plugin to register the premultiplied UI material
+pub struct UiPremultipliedMaterialPlugin;
+
+impl Plugin for UiPremultipliedMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/ui_premultiplied.wgsl");
+        app.add_plugins(UiMaterialPlugin::<UiPremultipliedMaterial>::default());
+    }
+}
+
+/// UI material drawing a premultiplied-alpha texture with premultiplied blending
+#[derive(Asset, AsBindGroup, TypePath, Clone)]
+pub struct UiPremultipliedMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    pub texture: Handle<Image>,
+}
+
+impl UiMaterial for UiPremultipliedMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://pentimento/render/shaders/ui_premultiplied.wgsl".into()
+    }
+
+    fn specialize(descriptor: &mut RenderPipelineDescriptor, _key: UiMaterialKey<Self>) {
+        if let Some(target) = descriptor
+            .fragment
+            .as_mut()
+            .and_then(|fragment| fragment.targets.first_mut())
+            .and_then(Option::as_mut)
+        {
+            target.blend = Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING);
+        }
+    }
+}
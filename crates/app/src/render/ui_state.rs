@@ -0,0 +1,71 @@
+//! App state the UI needs after `Initialize`
+//!
+//! Lighting, ambient occlusion, modes, selection, and the paint-mode canvas
+//! state aren't part of `Initialize`, so a freshly loaded page gets them as
+//! the messages that report their changes, in the order documented in
+//! `pentimento_ipc::protocol`.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use pentimento_ipc::BevyToUi;
+#[cfg(feature = "selection")]
+use pentimento_scene::SelectionState;
+use pentimento_scene::{
+    ActiveCanvasPlane, CanvasPlane, EditModeState, EditorMode, GizmoState, PaintingResource,
+    ProjectionMode, SceneAmbientOcclusion, SceneLighting,
+};
+
+/// Read access to the state sent after `Initialize`
+#[derive(SystemParam)]
+pub(crate) struct UiStateSource<'w, 's> {
+    lighting: Res<'w, SceneLighting>,
+    ambient_occlusion: Res<'w, SceneAmbientOcclusion>,
+    edit_mode: Res<'w, EditModeState>,
+    gizmo: Res<'w, GizmoState>,
+    #[cfg(feature = "selection")]
+    selection: Res<'w, SelectionState>,
+    active_canvas: Res<'w, ActiveCanvasPlane>,
+    canvases: Query<'w, 's, &'static CanvasPlane>,
+    painting: Res<'w, PaintingResource>,
+    projection: Res<'w, ProjectionMode>,
+}
+
+impl UiStateSource<'_, '_> {
+    /// Messages following `Initialize` and `ProtocolVersion`, in contract order
+    pub(crate) fn messages(&self) -> Vec<BevyToUi> {
+        #[cfg(feature = "selection")]
+        let selected_ids = self.selection.selected_ids.clone();
+        #[cfg(not(feature = "selection"))]
+        let selected_ids = Vec::new();
+
+        let mut messages = vec![
+            BevyToUi::LightingChanged {
+                settings: self.lighting.settings.clone(),
+            },
+            BevyToUi::AmbientOcclusionChanged {
+                settings: self.ambient_occlusion.settings.clone(),
+            },
+            BevyToUi::EditModeChanged {
+                mode: self.edit_mode.mode.to_ipc(),
+            },
+            BevyToUi::GizmoModeChanged {
+                mode: self.gizmo.mode,
+            },
+            BevyToUi::SelectionChanged { selected_ids },
+        ];
+
+        if self.edit_mode.mode == EditorMode::Paint {
+            // A canvas without a pipeline yet reports its layers once it has one
+            let layers = self
+                .active_canvas
+                .entity
+                .and_then(|entity| self.canvases.get(entity).ok())
+                .and_then(|canvas| self.painting.layer_state(canvas.plane_id));
+            messages.extend(layers);
+            messages.push(BevyToUi::ProjectionModeChanged {
+                live_projection: self.projection.live_projection,
+            });
+        }
+        messages
+    }
+}
@@ -0,0 +1,876 @@
+//! Secondary UI surfaces
+//!
+//! The main UI is the `FrontendResource` backend drawn over the whole window.
+//! Panels detached from it (e.g. a node graph) are extra backends kept in the
+//! `Frontends` registry, each with its own texture and lifecycle. A surface is drawn in a rectangle of the window, or in the
+//! scene on a quad or any other mesh marked with `WorldUiSurface`. It receives
+//! mouse input while the cursor is over it (and not over a panel of the main
+//! UI), and keyboard input after it was clicked.
+//!
+//! World-space surfaces are hit-tested with a mesh ray cast from the main
+//! camera: the hit UV times the surface size is the cursor position in the
+//! surface's pixels. Scene objects in front of a surface block it, and
+//! clicks on a surface don't change the scene selection.
+//!
+//! Surfaces run in Capture mode, or in CEF mode when the main UI does (one
+//! CEF context hosts several browsers). Their messages are dispatched like the
+//! main UI's, but Bevy's replies only go to the main UI. Dioxus mode has its
+//! own render path and no surfaces.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use bevy::picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings};
+use bevy::picking::prelude::Pickable;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+#[cfg(any(test, feature = "mock"))]
+use pentimento_frontend_core::mock::MockUi;
+use pentimento_frontend_core::{BackendLifecycle, CaptureResult, CompositeBackend, FrontendError};
+use pentimento_ipc::{KeyboardEvent, MouseEvent, UiToBevy};
+#[cfg(feature = "selection")]
+use pentimento_scene::IgnoreSelectionClicks;
+use pentimento_scene::MainCamera;
+
+use super::ui_hit_test::UiHitTest;
+use super::ui_premultiplied_material::UiPremultipliedMaterial;
+use super::ui_texture_upload::{GpuImportStatus, UiTextureUpload, UiUploadFrame, UiUploadPixels};
+use super::{
+    CAPTURE_SAFETY_HEARTBEAT, FrontendConfig, FrontendStatus, PowerSaving, UiBlobs,
+    create_frontend, dispatch_ui_message, external_texture_size, new_ui_texture, resize_ui_texture,
+};
+use crate::config::{CompositeMode, PentimentoConfig};
+
+/// Identifier of a secondary UI surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SurfaceId(u32);
+
+/// Where a surface's texture is shown.
+#[derive(Debug, Clone)]
+pub enum SurfacePlacement {
+    /// Rectangle of the window in logical pixels, drawn below the main UI
+    Screen(Rect),
+    /// Quad of `size` world units in the scene, facing +Z in local space
+    Quad { transform: Transform, size: Vec2 },
+    /// Existing mesh entity in the scene, textured through its UVs
+    Mesh(Entity),
+}
+
+/// Configuration of a secondary surface.
+pub struct SurfaceConfig {
+    /// HTML content to load in the surface
+    pub html: String,
+    /// Surface size in pixels (width, height)
+    pub size: (u32, u32),
+    pub placement: SurfacePlacement,
+    /// UI the surface is driven by (mock mode)
+    #[cfg(any(test, feature = "mock"))]
+    pub mock_ui: Option<MockUi>,
+}
+
+/// Marker for the entity (UI node or quad) showing a surface.
+#[derive(Component)]
+pub struct UiSurface;
+
+/// Mesh in the scene showing a surface texture and forwarding input to it.
+///
+/// The mesh gets an unlit, alpha-blended copy of its material with the
+/// surface texture; its UVs map onto the surface's pixels.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct WorldUiSurface {
+    pub surface: SurfaceId,
+    /// Surface size in pixels (width, height)
+    pub size: (u32, u32),
+}
+
+/// One secondary surface and its backend.
+struct FrontendSurface {
+    backend: Box<dyn CompositeBackend>,
+    texture: Handle<Image>,
+    placement: SurfacePlacement,
+    /// Node or quad showing the texture
+    entity: Entity,
+    /// Backend size in pixels
+    size: (u32, u32),
+    /// Lifecycle as of the last poll
+    lifecycle: BackendLifecycle,
+    /// Time of the last capture (or safety heartbeat)
+    last_capture: Instant,
+    /// Whether the backend was told to stop producing shared GPU textures
+    gpu_capture_disabled: bool,
+}
+
+/// Registry of secondary UI surfaces (NonSend: backends are tied to the main thread).
+#[derive(Default)]
+pub struct Frontends {
+    surfaces: BTreeMap<SurfaceId, FrontendSurface>,
+    next_id: u32,
+    /// Surface receiving keyboard input (the last one clicked)
+    focused: Option<SurfaceId>,
+}
+
+impl Frontends {
+    /// Texture a surface is drawn into
+    pub fn texture(&self, id: SurfaceId) -> Option<Handle<Image>> {
+        self.surfaces
+            .get(&id)
+            .map(|surface| surface.texture.clone())
+    }
+
+    /// Lifecycle of a surface as of the last poll
+    #[cfg(test)]
+    pub fn lifecycle(&self, id: SurfaceId) -> Option<BackendLifecycle> {
+        self.surfaces
+            .get(&id)
+            .map(|surface| surface.lifecycle.clone())
+    }
+
+    /// Send a mouse event to the surface under the cursor, in its pixels.
+    ///
+    /// A button press also moves keyboard focus to that surface, or back to
+    /// the main UI. Returns false when the event belongs to the main UI.
+    pub(crate) fn route_mouse_event(&mut self, hover: &SurfaceHover, event: &MouseEvent) -> bool {
+        if matches!(event, MouseEvent::ButtonDown { .. }) {
+            self.focused = hover.target.map(|(id, _)| id);
+        }
+
+        let Some((id, position)) = hover.target else {
+            return false;
+        };
+        let Some(surface) = self.surfaces.get_mut(&id) else {
+            return false;
+        };
+
+        // Swallowed while loading so it doesn't reach the UI underneath
+        if surface.lifecycle == BackendLifecycle::Ready {
+            surface
+                .backend
+                .send_mouse_event(at_position(event, position));
+        }
+        true
+    }
+
+    /// Send a keyboard event to the focused surface.
+    ///
+    /// Returns false when the main UI has keyboard focus.
+    pub(crate) fn route_keyboard_event(&mut self, event: &KeyboardEvent) -> bool {
+        let Some(surface) = self.focused.and_then(|id| self.surfaces.get_mut(&id)) else {
+            return false;
+        };
+
+        if surface.lifecycle == BackendLifecycle::Ready {
+            surface.backend.send_keyboard_event(event.clone());
+        }
+        true
+    }
+}
+
+/// Move a mouse event to `position`, keeping its button and scroll deltas
+fn at_position(event: &MouseEvent, position: Vec2) -> MouseEvent {
+    let (x, y) = (position.x, position.y);
+    match *event {
+        MouseEvent::Move { .. } => MouseEvent::Move { x, y },
+        MouseEvent::ButtonDown { button, .. } => MouseEvent::ButtonDown { button, x, y },
+        MouseEvent::ButtonUp { button, .. } => MouseEvent::ButtonUp { button, x, y },
+        MouseEvent::Scroll {
+            delta_x, delta_y, ..
+        } => MouseEvent::Scroll {
+            delta_x,
+            delta_y,
+            x,
+            y,
+        },
+    }
+}
+
+/// Surface under the cursor, updated every frame before input is forwarded.
+#[derive(Resource, Default)]
+pub struct SurfaceHover {
+    /// Surface under the cursor and the cursor position in its pixels
+    pub target: Option<(SurfaceId, Vec2)>,
+}
+
+/// Plugin for secondary surfaces (part of the unified frontend pipeline).
+pub struct UiSurfacesPlugin;
+
+impl Plugin for UiSurfacesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_non_send_resource::<Frontends>()
+            .init_resource::<SurfaceHover>()
+            .add_systems(
+                PreUpdate,
+                update_surface_hover.after(bevy::input::InputSystems),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_surface_textures.after(super::update_ui_texture),
+                    handle_surface_ipc_messages,
+                    (open_node_graph_panel, open_world_ui_demo),
+                    apply_world_surface_materials,
+                )
+                    .chain(),
+            )
+            .add_systems(Last, shutdown_surfaces.run_if(on_message::<AppExit>));
+    }
+}
+
+/// Mode surfaces run in next to a `main` mode frontend
+fn surface_mode(main: CompositeMode) -> CompositeMode {
+    match main {
+        CompositeMode::Cef | CompositeMode::Mock => main,
+        _ => CompositeMode::Capture,
+    }
+}
+
+/// Create a secondary surface and show it at its placement.
+///
+/// # Errors
+///
+/// Returns `FrontendError` if the backend fails to initialize.
+pub fn create_surface(
+    world: &mut World,
+    config: SurfaceConfig,
+) -> Result<SurfaceId, FrontendError> {
+    let app_config = world.resource::<PentimentoConfig>();
+    let mode = surface_mode(app_config.composite_mode);
+    let remote_port = app_config.remote_port;
+    let blobs = world.resource::<UiBlobs>().0.clone();
+
+    let frontend = create_frontend(
+        mode,
+        FrontendConfig {
+            html: config.html,
+            size: config.size,
+            // Surfaces are sized in their own pixels, independent of the window
+            scale_factor: 1.0,
+            window_handle: None,
+            blobs,
+            remote_port,
+            #[cfg(any(test, feature = "mock"))]
+            mock_ui: config.mock_ui,
+        },
+    )?;
+
+    let (width, height) = config.size;
+    let texture = world.resource_mut::<Assets<Image>>().add(new_ui_texture(
+        width,
+        height,
+        frontend.texture_format,
+    ));
+
+    let id = {
+        let mut frontends = world.non_send_resource_mut::<Frontends>();
+        frontends.next_id += 1;
+        SurfaceId(frontends.next_id)
+    };
+    let entity = spawn_surface_entity(world, id, config.size, &config.placement, texture.clone());
+
+    info!(
+        "Created UI surface {:?} ({:?} mode, {}x{}) at {:?}",
+        id, mode, width, height, config.placement
+    );
+
+    world.non_send_resource_mut::<Frontends>().surfaces.insert(
+        id,
+        FrontendSurface {
+            backend: frontend.backend,
+            texture,
+            placement: config.placement,
+            entity,
+            size: config.size,
+            lifecycle: BackendLifecycle::Initializing,
+            last_capture: Instant::now(),
+            gpu_capture_disabled: false,
+        },
+    );
+    Ok(id)
+}
+
+/// Spawn the UI node or scene quad that shows a surface texture, or mark
+/// the placement's mesh as showing it
+fn spawn_surface_entity(
+    world: &mut World,
+    id: SurfaceId,
+    size: (u32, u32),
+    placement: &SurfacePlacement,
+    texture: Handle<Image>,
+) -> Entity {
+    let world_surface = WorldUiSurface { surface: id, size };
+    match placement {
+        SurfacePlacement::Screen(rect) => {
+            let material = world
+                .resource_mut::<Assets<UiPremultipliedMaterial>>()
+                .add(UiPremultipliedMaterial { texture });
+            world
+                .spawn((
+                    MaterialNode(material),
+                    Node {
+                        width: Val::Px(rect.width()),
+                        height: Val::Px(rect.height()),
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(rect.min.x),
+                        top: Val::Px(rect.min.y),
+                        ..default()
+                    },
+                    // Below the main UI overlay, so its panels stay on top
+                    ZIndex(i32::MAX - 1),
+                    UiSurface,
+                    Pickable::IGNORE,
+                ))
+                .id()
+        }
+        SurfacePlacement::Quad { transform, size } => {
+            let mesh = world
+                .resource_mut::<Assets<Mesh>>()
+                .add(Rectangle::from_size(*size));
+            let entity = world
+                .spawn((
+                    Mesh3d(mesh),
+                    *transform,
+                    Name::new(format!("UI Surface {}", id.0)),
+                    UiSurface,
+                ))
+                .id();
+            mark_world_surface(world, entity, world_surface);
+            entity
+        }
+        SurfacePlacement::Mesh(entity) => {
+            mark_world_surface(world, *entity, world_surface);
+            *entity
+        }
+    }
+}
+
+/// Make a scene mesh show a surface and take its clicks
+fn mark_world_surface(world: &mut World, entity: Entity, surface: WorldUiSurface) {
+    let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+        warn!(
+            "UI surface {:?} placed on missing entity {:?}",
+            surface.surface, entity
+        );
+        return;
+    };
+    entity_mut.insert(surface);
+    // Meshes stay pickable, so a click stops at the surface instead of
+    // selecting what is behind it; it must not clear the selection either
+    #[cfg(feature = "selection")]
+    entity_mut.insert(IgnoreSelectionClicks);
+}
+
+/// Give new world surfaces an unlit, premultiplied-alpha material with their texture.
+///
+/// The mesh's material is copied rather than edited, since other meshes may
+/// share it.
+fn apply_world_surface_materials(
+    mut commands: Commands,
+    frontends: Option<NonSend<Frontends>>,
+    surfaces: Query<
+        (
+            Entity,
+            &WorldUiSurface,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+        ),
+        Changed<WorldUiSurface>,
+    >,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(frontends) = frontends else {
+        return;
+    };
+    for (entity, world_surface, material) in &surfaces {
+        let Some(texture) = frontends.texture(world_surface.surface) else {
+            continue;
+        };
+        let base = material
+            .and_then(|material| materials.get(&material.0))
+            .cloned()
+            .unwrap_or_default();
+        let material = materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            base_color_texture: Some(texture),
+            unlit: true,
+            // Surface textures are premultiplied (see `ui_texture_upload`)
+            alpha_mode: AlphaMode::Premultiplied,
+            double_sided: true,
+            cull_mode: None,
+            ..base
+        });
+        commands.entity(entity).insert(MeshMaterial3d(material));
+    }
+}
+
+/// Find the surface under the cursor.
+///
+/// Screen surfaces are drawn over the scene, so they win over world
+/// surfaces. A world surface is hovered when it is the nearest mesh under
+/// the cursor. Nothing is hovered while the cursor is over the main UI.
+pub fn update_surface_hover(
+    frontends: Option<NonSend<Frontends>>,
+    mut hover: ResMut<SurfaceHover>,
+    hit_test: Res<UiHitTest>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    world_surfaces: Query<&WorldUiSurface>,
+    mut ray_cast: MeshRayCast,
+) {
+    hover.target = None;
+
+    let Some(frontends) = frontends else {
+        return;
+    };
+    if frontends.surfaces.is_empty() {
+        return;
+    }
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    if hit_test.covers(cursor, window.size()) {
+        return;
+    }
+
+    // Newest surfaces are drawn last, so they are on top
+    for (&id, surface) in frontends.surfaces.iter().rev() {
+        if let SurfacePlacement::Screen(rect) = surface.placement
+            && rect.contains(cursor)
+        {
+            let uv = (cursor - rect.min) / rect.size();
+            hover.target = Some((id, uv_to_surface_pixels(uv, surface.size)));
+            return;
+        }
+    }
+
+    let Some((camera, camera_transform)) = cameras.iter().find(|(camera, _)| camera.is_active)
+    else {
+        return;
+    };
+    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+        return;
+    };
+
+    hover.target = world_surface_under_ray(ray, &mut ray_cast, &world_surfaces);
+}
+
+/// World surface hit by `ray` and the hit position in its pixels.
+///
+/// The nearest mesh decides: a scene object in front hides the surface.
+fn world_surface_under_ray(
+    ray: Ray3d,
+    ray_cast: &mut MeshRayCast,
+    world_surfaces: &Query<&WorldUiSurface>,
+) -> Option<(SurfaceId, Vec2)> {
+    let (entity, hit) = ray_cast
+        .cast_ray(ray, &MeshRayCastSettings::default())
+        .first()?;
+    let world_surface = world_surfaces.get(*entity).ok()?;
+    Some((
+        world_surface.surface,
+        uv_to_surface_pixels(hit.uv?, world_surface.size),
+    ))
+}
+
+/// Position in a surface's pixels of texture coordinates `uv`
+/// (origin at the top-left corner, like the webview's)
+fn uv_to_surface_pixels(uv: Vec2, size: (u32, u32)) -> Vec2 {
+    uv * Vec2::new(size.0 as f32, size.1 as f32)
+}
+
+/// Poll every surface and upload dirty frames.
+fn update_surface_textures(
+    frontends: Option<NonSendMut<Frontends>>,
+    mut images: ResMut<Assets<Image>>,
+    mut upload: ResMut<UiTextureUpload>,
+    gpu_import: Res<GpuImportStatus>,
+    power_saving: Res<PowerSaving>,
+) {
+    // The previous frames were extracted at the end of last frame
+    if !upload.surfaces.is_empty() {
+        upload.bypass_change_detection().surfaces.clear();
+    }
+
+    let Some(mut frontends) = frontends else {
+        return;
+    };
+
+    for (id, surface) in &mut frontends.surfaces {
+        if gpu_import.is_unsupported() && !surface.gpu_capture_disabled {
+            surface.backend.disable_gpu_capture();
+            surface.gpu_capture_disabled = true;
+        }
+
+        if power_saving.is_changed() {
+            surface.backend.set_background(power_saving.is_background());
+        }
+
+        surface.backend.poll();
+        let lifecycle = surface.backend.lifecycle();
+        if lifecycle != surface.lifecycle {
+            debug!(
+                "UI surface {:?} lifecycle: {:?} -> {:?}",
+                id, surface.lifecycle, lifecycle
+            );
+            surface.lifecycle = lifecycle;
+        }
+        if surface.lifecycle != BackendLifecycle::Ready {
+            continue;
+        }
+
+        if power_saving.is_background() {
+            continue;
+        }
+
+        if surface.last_capture.elapsed() >= CAPTURE_SAFETY_HEARTBEAT {
+            surface.backend.mark_dirty();
+            surface.last_capture = Instant::now();
+        }
+
+        let Some(capture) = surface.backend.capture_if_dirty() else {
+            continue;
+        };
+        let (pixels, width, height) = match capture {
+            CaptureResult::Rgba(data, width, height, alpha) => (
+                UiUploadPixels::premultiplied(Arc::new(data), alpha),
+                width,
+                height,
+            ),
+            CaptureResult::Bgra(data, width, height, alpha) => {
+                (UiUploadPixels::premultiplied(data, alpha), width, height)
+            }
+            CaptureResult::GpuExternal(handle) => {
+                let (width, height) = external_texture_size(&handle);
+                (UiUploadPixels::External(handle), width, height)
+            }
+            CaptureResult::CompositorManaged => continue,
+        };
+        surface.last_capture = Instant::now();
+
+        resize_ui_texture(&mut images, &surface.texture, width, height);
+        upload.surfaces.push(UiUploadFrame {
+            pixels,
+            width,
+            height,
+            image: surface.texture.id(),
+        });
+    }
+}
+
+/// Dispatch messages from every surface like the main UI's.
+fn handle_surface_ipc_messages(world: &mut World) {
+    let messages: Vec<(SurfaceId, UiToBevy)> = {
+        let Some(mut frontends) = world.get_non_send_resource_mut::<Frontends>() else {
+            return;
+        };
+        let mut messages = Vec::new();
+        for (&id, surface) in &mut frontends.surfaces {
+            let mut received = Vec::new();
+            while let Some(msg) = surface.backend.try_recv_from_ui() {
+                received.push(msg);
+            }
+            messages.extend(
+                pentimento_ipc::coalesce(received)
+                    .into_iter()
+                    .map(|msg| (id, msg)),
+            );
+        }
+        messages
+    };
+
+    for (id, msg) in messages {
+        match msg {
+            // The surface's own layout and dirty state, not the main UI's
+            UiToBevy::LayoutUpdate(_) | UiToBevy::UiDirty => {}
+            msg => {
+                trace!("Dispatching {:?} from UI surface {:?}", msg, id);
+                dispatch_ui_message(world, msg);
+            }
+        }
+    }
+}
+
+/// Close every surface while the app is exiting
+fn shutdown_surfaces(frontends: Option<NonSendMut<Frontends>>) {
+    if let Some(mut frontends) = frontends {
+        for surface in frontends.surfaces.values_mut() {
+            surface.backend.shutdown();
+        }
+    }
+}
+
+/// Pixel size of the node graph panel
+const NODE_GRAPH_PANEL_SIZE: (u32, u32) = (640, 400);
+
+/// Placeholder node graph panel shown with `--node-graph-panel`
+const NODE_GRAPH_PANEL_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+        html, body {
+            margin: 0;
+            height: 100%;
+            background: rgba(24, 26, 32, 0.92);
+            color: #d8dce6;
+            font: 14px sans-serif;
+        }
+        header {
+            padding: 8px 12px;
+            background: #2b2f3a;
+            font-weight: bold;
+        }
+        .node {
+            position: absolute;
+            width: 150px;
+            padding: 8px;
+            border: 1px solid #4a5060;
+            border-radius: 6px;
+            background: #323744;
+        }
+        .node:hover {
+            border-color: #7aa2f7;
+        }
+    </style>
+</head>
+<body>
+    <header>Node Graph</header>
+    <div class="node" style="left: 40px; top: 90px">Image Texture</div>
+    <div class="node" style="left: 260px; top: 160px">Mix</div>
+    <div class="node" style="left: 460px; top: 110px">Material Output</div>
+</body>
+</html>"#;
+
+/// Open the demo node graph panel on a quad once the main UI is up.
+fn open_node_graph_panel(world: &mut World, mut opened: Local<bool>) {
+    if *opened || !world.resource::<PentimentoConfig>().node_graph_panel || !main_ui_up(world) {
+        return;
+    }
+    *opened = true;
+
+    let config = SurfaceConfig {
+        html: NODE_GRAPH_PANEL_HTML.to_string(),
+        size: NODE_GRAPH_PANEL_SIZE,
+        placement: SurfacePlacement::Quad {
+            transform: Transform::from_xyz(0.0, 1.5, -3.0),
+            size: Vec2::new(1.6, 1.0),
+        },
+        #[cfg(any(test, feature = "mock"))]
+        mock_ui: None,
+    };
+    if let Err(e) = create_surface(world, config) {
+        error!("Failed to open the node graph panel: {}", e);
+    }
+}
+
+/// Pixel size of the world-space UI demo
+const WORLD_UI_DEMO_SIZE: (u32, u32) = (480, 360);
+
+/// Clock page shown on a floating quad with `--world-ui-demo`: the button and
+/// the scrolling list exercise clicks and wheel input through the ray cast
+const WORLD_UI_DEMO_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+        html, body {
+            margin: 0;
+            height: 100%;
+            background: rgba(16, 18, 24, 0.9);
+            color: #e4e7ef;
+            font: 16px sans-serif;
+        }
+        #clock {
+            padding: 24px 0 12px;
+            text-align: center;
+            font: bold 64px monospace;
+        }
+        button {
+            display: block;
+            margin: 0 auto 12px;
+            padding: 8px 20px;
+            border: 1px solid #7aa2f7;
+            border-radius: 6px;
+            background: #2b2f3a;
+            color: inherit;
+            font: inherit;
+        }
+        button:hover {
+            background: #3b4252;
+        }
+        ul {
+            height: 130px;
+            margin: 0 24px;
+            padding: 0 8px;
+            overflow-y: scroll;
+            border: 1px solid #4a5060;
+            list-style: none;
+        }
+        li {
+            padding: 4px 0;
+        }
+    </style>
+</head>
+<body>
+    <div id="clock">--:--:--</div>
+    <button id="lap">Lap</button>
+    <ul id="laps"></ul>
+    <script>
+        const clock = document.getElementById("clock");
+        const laps = document.getElementById("laps");
+        const tick = () => clock.textContent = new Date().toLocaleTimeString();
+        tick();
+        setInterval(tick, 1000);
+        document.getElementById("lap").addEventListener("click", () => {
+            const lap = document.createElement("li");
+            lap.textContent = `Lap ${laps.children.length + 1}: ${clock.textContent}`;
+            laps.prepend(lap);
+        });
+        for (let hour = 0; hour < 24; hour++) {
+            const item = document.createElement("li");
+            item.textContent = `${String(hour).padStart(2, "0")}:00`;
+            laps.append(item);
+        }
+    </script>
+</body>
+</html>"#;
+
+/// Open the world-space UI demo on a floating, tilted quad once the main UI is up.
+fn open_world_ui_demo(world: &mut World, mut opened: Local<bool>) {
+    if *opened || !world.resource::<PentimentoConfig>().world_ui_demo || !main_ui_up(world) {
+        return;
+    }
+    *opened = true;
+
+    let config = SurfaceConfig {
+        html: WORLD_UI_DEMO_HTML.to_string(),
+        size: WORLD_UI_DEMO_SIZE,
+        placement: SurfacePlacement::Quad {
+            transform: Transform::from_xyz(2.0, 1.2, -1.0)
+                .with_rotation(Quat::from_rotation_y(-0.5)),
+            size: Vec2::new(1.2, 0.9),
+        },
+        #[cfg(any(test, feature = "mock"))]
+        mock_ui: None,
+    };
+    if let Err(e) = create_surface(world, config) {
+        error!("Failed to open the world-space UI demo: {}", e);
+    }
+}
+
+/// Whether the main UI has initialized (demo surfaces wait for it)
+fn main_ui_up(world: &World) -> bool {
+    world
+        .get_resource::<FrontendStatus>()
+        .is_some_and(|status| status.initialized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::camera::primitives::MeshAabb;
+    use bevy::camera::visibility::SetViewVisibility;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// Surface pixel size used by the tests
+    const SIZE: (u32, u32) = (200, 100);
+
+    fn ray(origin: Vec3, direction: Vec3) -> Ray3d {
+        Ray3d::new(origin, Dir3::new(direction).unwrap())
+    }
+
+    /// Spawn a visible mesh at `transform` that the ray cast can hit
+    fn spawn_mesh(app: &mut App, mesh: Mesh, transform: Transform) -> Entity {
+        let aabb = mesh.compute_aabb().unwrap();
+        let mesh = app.world_mut().resource_mut::<Assets<Mesh>>().add(mesh);
+        let entity = app
+            .world_mut()
+            .spawn((
+                Mesh3d(mesh),
+                aabb,
+                GlobalTransform::from(transform),
+                InheritedVisibility::VISIBLE,
+                ViewVisibility::HIDDEN,
+            ))
+            .id();
+        app.world_mut()
+            .get_mut::<ViewVisibility>(entity)
+            .unwrap()
+            .set_visible();
+        entity
+    }
+
+    /// App with a 2x1 surface quad 5 units down -Z from the origin
+    fn surface_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>();
+        let quad = spawn_mesh(
+            &mut app,
+            Rectangle::new(2.0, 1.0).into(),
+            Transform::from_xyz(0.0, 0.0, -5.0),
+        );
+        app.world_mut().entity_mut(quad).insert(WorldUiSurface {
+            surface: SurfaceId(1),
+            size: SIZE,
+        });
+        app
+    }
+
+    fn surface_under(app: &mut App, ray: Ray3d) -> Option<(SurfaceId, Vec2)> {
+        app.world_mut()
+            .run_system_once(
+                move |mut ray_cast: MeshRayCast, world_surfaces: Query<&WorldUiSurface>| {
+                    world_surface_under_ray(ray, &mut ray_cast, &world_surfaces)
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_uv_to_surface_pixels() {
+        assert_eq!(uv_to_surface_pixels(Vec2::ZERO, SIZE), Vec2::ZERO);
+        assert_eq!(
+            uv_to_surface_pixels(Vec2::new(0.5, 0.25), SIZE),
+            Vec2::new(100.0, 25.0)
+        );
+        assert_eq!(
+            uv_to_surface_pixels(Vec2::ONE, SIZE),
+            Vec2::new(200.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn test_ray_hit_maps_to_surface_pixels() {
+        let mut app = surface_app();
+
+        let (id, position) = surface_under(&mut app, ray(Vec3::ZERO, Vec3::NEG_Z)).unwrap();
+        assert_eq!(id, SurfaceId(1));
+        assert!(position.abs_diff_eq(Vec2::new(100.0, 50.0), 1e-3));
+
+        // The quad's top-left corner is the surface's pixel origin
+        let (_, position) =
+            surface_under(&mut app, ray(Vec3::new(-0.9, 0.4, 0.0), Vec3::NEG_Z)).unwrap();
+        assert!(position.abs_diff_eq(Vec2::new(10.0, 10.0), 1e-3));
+
+        assert!(surface_under(&mut app, ray(Vec3::new(1.5, 0.0, 0.0), Vec3::NEG_Z)).is_none());
+        assert!(surface_under(&mut app, ray(Vec3::ZERO, Vec3::Z)).is_none());
+    }
+
+    #[test]
+    fn test_mesh_in_front_blocks_surface() {
+        let mut app = surface_app();
+        spawn_mesh(
+            &mut app,
+            Cuboid::from_length(0.5).into(),
+            Transform::from_xyz(0.0, 0.0, -3.0),
+        );
+
+        assert!(surface_under(&mut app, ray(Vec3::ZERO, Vec3::NEG_Z)).is_none());
+        // Beside the occluder the surface is still reachable
+        let (_, position) =
+            surface_under(&mut app, ray(Vec3::new(-0.9, 0.4, 0.0), Vec3::NEG_Z)).unwrap();
+        assert!(position.abs_diff_eq(Vec2::new(10.0, 10.0), 1e-3));
+    }
+}
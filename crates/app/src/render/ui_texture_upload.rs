@@ -0,0 +1,324 @@
+//! UI texture upload from the render world.
+//!
+//! Captured frames are handed to the render world as `Arc`'d pixel buffers and
+//! written straight into the existing `GpuImage` with `RenderQueue::write_texture`.
+//! The main-world `Image` only tracks the texture size and never holds pixel
+//! data, so a frame is never copied into the asset (and never re-uploaded by
+//! Bevy's asset preparation).
+//!
+//! Shared GPU textures (`CaptureResult::GpuExternal`) are imported and copied
+//! on the GPU instead (`cef-gpu` feature, see `ui_dmabuf`). When that isn't
+//! supported, `GpuImportStatus` tells the main world to switch the backend
+//! back to CPU captures.
+//!
+//! UI textures hold premultiplied alpha and are drawn with premultiplied
+//! blending (`UiPremultipliedMaterial`). CEF buffers already are; straight
+//! captures (WebKit) are premultiplied here before upload.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bevy::asset::AssetId;
+use bevy::prelude::*;
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_resource::{
+    Extent3d, Origin3d, TexelCopyBufferLayout, TexelCopyTextureInfo, TextureAspect,
+};
+#[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+use bevy::render::renderer::RenderDevice;
+use bevy::render::renderer::RenderQueue;
+use bevy::render::texture::GpuImage;
+use bevy::render::{Render, RenderApp, RenderSystems};
+use pentimento_frontend_core::{AlphaMode8, ExternalTextureHandle};
+
+#[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+use super::ui_dmabuf;
+
+/// Pixel source of a captured frame.
+#[derive(Clone)]
+pub enum UiUploadPixels {
+    /// Tightly packed 4-byte pixels (RGBA or BGRA, matching the texture format)
+    Cpu(Arc<Vec<u8>>),
+    /// Texture shared by the backend, copied on the GPU
+    External(ExternalTextureHandle),
+}
+
+impl UiUploadPixels {
+    /// CPU pixels of a capture, premultiplied if they aren't already.
+    ///
+    /// A premultiplied buffer is passed through without a copy.
+    pub fn premultiplied(pixels: Arc<Vec<u8>>, alpha: AlphaMode8) -> Self {
+        match alpha {
+            AlphaMode8::Premultiplied => Self::Cpu(pixels),
+            AlphaMode8::Straight => {
+                let mut pixels = Arc::unwrap_or_clone(pixels);
+                premultiply_alpha(&mut pixels);
+                Self::Cpu(Arc::new(pixels))
+            }
+        }
+    }
+}
+
+/// Multiply the color of 4-byte pixels (RGBA or BGRA) by their alpha in place.
+///
+/// Branch-free integer math (exact `c * a / 255`, rounded) so the loop
+/// auto-vectorizes; a full-window frame takes well under a millisecond.
+fn premultiply_alpha(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = u16::from(pixel[3]);
+        for channel in &mut pixel[..3] {
+            let product = u16::from(*channel) * alpha + 128;
+            *channel = ((product + (product >> 8)) >> 8) as u8;
+        }
+    }
+}
+
+/// A captured frame waiting to be written to the UI texture.
+#[derive(Clone)]
+pub struct UiUploadFrame {
+    pub pixels: UiUploadPixels,
+    pub width: u32,
+    pub height: u32,
+    /// Target UI texture
+    pub image: AssetId<Image>,
+}
+
+/// Latest captured frame, extracted to the render world when it changes.
+///
+/// The main world clears its copy (without triggering change detection) on the
+/// next frame, so the render world holds the only other reference to the
+/// buffer and the backend can reuse it once the upload is done.
+#[derive(Resource, Clone, Default, ExtractResource)]
+pub struct UiTextureUpload {
+    /// Frame of the main UI
+    pub frame: Option<UiUploadFrame>,
+    /// Frames of secondary surfaces (`ui_surfaces`), one per surface at most
+    pub surfaces: Vec<UiUploadFrame>,
+}
+
+/// Whether shared GPU textures can be imported, shared by the main and render worlds.
+///
+/// Set by the render world when the device lacks support or an import fails;
+/// the main world then calls `CompositeBackend::disable_gpu_capture`.
+#[derive(Resource, Clone, Default)]
+pub struct GpuImportStatus {
+    unsupported: Arc<AtomicBool>,
+}
+
+impl GpuImportStatus {
+    pub fn is_unsupported(&self) -> bool {
+        self.unsupported.load(Ordering::Relaxed)
+    }
+
+    fn mark_unsupported(&self, reason: &str) {
+        if !self.unsupported.swap(true, Ordering::Relaxed) {
+            warn!(
+                "Shared UI textures unavailable ({}), using CPU captures",
+                reason
+            );
+        }
+    }
+}
+
+/// Plugin that writes pending UI frames to the GPU in the render world.
+pub struct UiTextureUploadPlugin;
+
+impl Plugin for UiTextureUploadPlugin {
+    fn build(&self, app: &mut App) {
+        let import_status = GpuImportStatus::default();
+
+        app.init_resource::<UiTextureUpload>()
+            .insert_resource(import_status.clone())
+            .add_plugins(ExtractResourcePlugin::<UiTextureUpload>::default());
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            warn!("UiTextureUploadPlugin: RenderApp not available, UI texture will not update");
+            return;
+        };
+
+        // After PrepareAssets so a resized GpuImage already exists this frame
+        render_app.insert_resource(import_status).add_systems(
+            Render,
+            write_ui_texture.in_set(RenderSystems::PrepareResources),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        // Without cef-gpu, shared textures are rejected on arrival instead
+        #[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+        {
+            let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+                return;
+            };
+            let world = render_app.world();
+            if let Err(reason) = ui_dmabuf::check_support(world.resource::<RenderDevice>()) {
+                world
+                    .resource::<GpuImportStatus>()
+                    .mark_unsupported(&reason);
+            }
+        }
+        #[cfg(not(all(feature = "cef-gpu", target_os = "linux")))]
+        let _ = app;
+    }
+}
+
+/// Write the pending frames into their UI textures (runs in PrepareResources).
+fn write_ui_texture(
+    mut upload: ResMut<UiTextureUpload>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    #[cfg(all(feature = "cef-gpu", target_os = "linux"))] render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    import_status: Res<GpuImportStatus>,
+) {
+    let frames = upload
+        .frame
+        .take()
+        .into_iter()
+        .chain(std::mem::take(&mut upload.surfaces));
+
+    for frame in frames {
+        write_frame(
+            frame,
+            &gpu_images,
+            #[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+            &render_device,
+            &render_queue,
+            &import_status,
+        );
+    }
+}
+
+/// Write one captured frame into its texture.
+fn write_frame(
+    frame: UiUploadFrame,
+    gpu_images: &RenderAssets<GpuImage>,
+    #[cfg(all(feature = "cef-gpu", target_os = "linux"))] render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+    import_status: &GpuImportStatus,
+) {
+    let Some(gpu_image) = gpu_images.get(frame.image) else {
+        // Texture not yet prepared - the next capture will fill it
+        return;
+    };
+
+    // A frame captured before a resize no longer fits the texture
+    if gpu_image.size.width != frame.width || gpu_image.size.height != frame.height {
+        debug!(
+            "Skipping UI upload: frame {}x{} does not match texture {}x{}",
+            frame.width, frame.height, gpu_image.size.width, gpu_image.size.height
+        );
+        return;
+    }
+
+    let _span = info_span!(
+        "ui_texture_upload",
+        width = frame.width,
+        height = frame.height
+    )
+    .entered();
+
+    let data = match frame.pixels {
+        UiUploadPixels::Cpu(data) => data,
+        UiUploadPixels::External(handle) => {
+            if import_status.is_unsupported() {
+                return;
+            }
+
+            #[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+            match handle {
+                ExternalTextureHandle::Dmabuf(dmabuf) => {
+                    // Import errors include device loss; the CPU path takes over
+                    if let Err(reason) =
+                        ui_dmabuf::copy_to_texture(render_device, render_queue, &dmabuf, gpu_image)
+                    {
+                        import_status.mark_unsupported(&reason);
+                    }
+                }
+            }
+
+            #[cfg(not(all(feature = "cef-gpu", target_os = "linux")))]
+            {
+                let _ = handle;
+                import_status.mark_unsupported("built without the cef-gpu feature");
+            }
+            return;
+        }
+    };
+
+    render_queue.write_texture(
+        TexelCopyTextureInfo {
+            texture: &gpu_image.texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        &data,
+        TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(frame.width * 4),
+            rows_per_image: Some(frame.height),
+        },
+        Extent3d {
+            width: frame.width,
+            height: frame.height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Premultiplied source-over (`One`, `OneMinusSrcAlpha`) of a UI texel onto
+    /// an sRGB scene pixel, blended in linear space like the GPU does
+    fn composite_over(ui: [u8; 4], scene: [u8; 3]) -> [u8; 3] {
+        let decode = |c: u8| Srgba::gamma_function(f32::from(c) / 255.0);
+        let alpha = f32::from(ui[3]) / 255.0;
+        std::array::from_fn(|i| {
+            let linear = decode(ui[i]) + decode(scene[i]) * (1.0 - alpha);
+            (Srgba::gamma_function_inverse(linear) * 255.0).round() as u8
+        })
+    }
+
+    #[test]
+    fn test_premultiply_alpha() {
+        let mut pixels = vec![255, 255, 255, 128, 200, 100, 50, 0, 10, 20, 30, 255];
+        premultiply_alpha(&mut pixels);
+        assert_eq!(pixels, [128, 128, 128, 128, 0, 0, 0, 0, 10, 20, 30, 255]);
+
+        // Exact rounding over the whole range
+        for color in 0..=255u16 {
+            for alpha in 0..=255u16 {
+                let mut pixel = [color as u8, 0, 0, alpha as u8];
+                premultiply_alpha(&mut pixel);
+                let expected = (f32::from(color) * f32::from(alpha) / 255.0).round() as u8;
+                assert_eq!(pixel[0], expected, "{color} * {alpha}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_half_alpha_white_composites_to_mid_grey() {
+        let straight = Arc::new(vec![255, 255, 255, 128]);
+        let UiUploadPixels::Cpu(texel) =
+            UiUploadPixels::premultiplied(straight, AlphaMode8::Straight)
+        else {
+            panic!("expected CPU pixels");
+        };
+        let texel: [u8; 4] = texel.as_slice().try_into().unwrap();
+        for channel in composite_over(texel, [0, 0, 0]) {
+            assert!(channel.abs_diff(127) <= 1, "got {channel}, expected 127±1");
+        }
+
+        // Already premultiplied buffers are uploaded as they are
+        let premultiplied = Arc::new(vec![128, 128, 128, 128]);
+        let UiUploadPixels::Cpu(uploaded) =
+            UiUploadPixels::premultiplied(premultiplied.clone(), AlphaMode8::Premultiplied)
+        else {
+            panic!("expected CPU pixels");
+        };
+        assert!(Arc::ptr_eq(&uploaded, &premultiplied));
+    }
+}
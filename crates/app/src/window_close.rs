@@ -0,0 +1,112 @@
+//! Window close confirmation
+//!
+//! Bevy's default close handling is disabled in `main`, so a close request
+//! (title bar button, window manager shortcut) is forwarded to the UI as
+//! `CloseRequested` instead. The UI answers with `CloseResponse`: `Proceed`
+//! closes the window, `Cancel` keeps it open, and `Asking` means a dialog is
+//! showing and the answer will follow.
+//!
+//! A UI that never answers (crashed, still loading, protocol mismatch) must
+//! not make the window impossible to close, so an unanswered request closes
+//! the window after `CLOSE_CONFIRM_TIMEOUT`.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+use pentimento_ipc::{BevyToUi, CloseDecision};
+use pentimento_scene::OutboundUiMessages;
+
+/// How long to wait for the UI to answer `CloseRequested` before closing anyway
+const CLOSE_CONFIRM_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// UI answer to `CloseRequested`, written by the frontend IPC dispatchers
+#[derive(Message, Debug, Clone, Copy)]
+pub struct CloseResponseEvent(pub CloseDecision);
+
+/// Close request waiting for the UI's answer
+#[derive(Resource, Default)]
+struct PendingClose {
+    /// Window the close was requested for (None when no close is pending)
+    window: Option<Entity>,
+    /// Time at which the window closes without an answer (None while the UI is asking)
+    deadline: Option<Instant>,
+}
+
+pub struct WindowClosePlugin;
+
+impl Plugin for WindowClosePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingClose>()
+            .add_message::<CloseResponseEvent>()
+            .add_systems(
+                Update,
+                (forward_close_requests, resolve_close_requests).chain(),
+            );
+    }
+}
+
+/// Ask the UI to confirm a window close request
+fn forward_close_requests(
+    mut requests: MessageReader<WindowCloseRequested>,
+    mut pending: ResMut<PendingClose>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for request in requests.read() {
+        if pending.window.is_some() {
+            // Already waiting on the UI; repeated clicks don't re-prompt
+            continue;
+        }
+        info!("Window close requested, asking the UI to confirm");
+        pending.window = Some(request.window);
+        pending.deadline = Some(Instant::now() + CLOSE_CONFIRM_TIMEOUT);
+        outbound.send(BevyToUi::CloseRequested);
+    }
+}
+
+/// Apply the UI's answer, or close the window once the deadline has passed
+fn resolve_close_requests(
+    mut commands: Commands,
+    mut responses: MessageReader<CloseResponseEvent>,
+    mut pending: ResMut<PendingClose>,
+) {
+    let Some(window) = pending.window else {
+        // Answers without a pending close (e.g. a late reply after the timeout)
+        responses.clear();
+        return;
+    };
+
+    let mut proceed = false;
+    for CloseResponseEvent(decision) in responses.read() {
+        match decision {
+            CloseDecision::Proceed => proceed = true,
+            CloseDecision::Cancel => {
+                info!("Window close cancelled by the UI");
+                *pending = PendingClose::default();
+                return;
+            }
+            CloseDecision::Asking => {
+                debug!("UI is asking the user before closing");
+                pending.deadline = None;
+            }
+        }
+    }
+
+    let timed_out = pending
+        .deadline
+        .is_some_and(|deadline| Instant::now() >= deadline);
+    if timed_out {
+        warn!(
+            "UI did not answer CloseRequested within {:?}, closing",
+            CLOSE_CONFIRM_TIMEOUT
+        );
+    }
+
+    if proceed || timed_out {
+        // Despawning the primary window exits the app (ExitCondition::OnPrimaryClosed)
+        if let Ok(mut entity) = commands.get_entity(window) {
+            entity.despawn();
+        }
+        *pending = PendingClose::default();
+    }
+}
@@ -0,0 +1,268 @@
+//! Window geometry persistence
+//!
+//! Restores the last window size, position, maximized state, and monitor at
+//! startup and writes changes back to the settings file. Saves are debounced
+//! so dragging or resizing the window doesn't hammer the disk.
+//!
+//! The tracker owns the settings file, so it also writes keymap overrides
+//! back whenever a hotkey is rebound.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::window::{Monitor, PrimaryWindow, WindowMoved, WindowPosition, WindowResized};
+use pentimento_config::{DisplayConfig, Keymap, SettingsFile, WindowGeometry};
+
+/// Quiet period after the last move/resize before geometry is written to disk
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Fraction of the monitor a window must cover to be treated as maximized
+const MAXIMIZED_COVERAGE: f32 = 0.9;
+
+/// Resource tracking the persisted window geometry
+#[derive(Resource)]
+pub struct WindowStateTracker {
+    /// Settings file contents (written back with updated geometry)
+    settings: SettingsFile,
+    /// Geometry restored at startup (None when starting fresh or with --reset-window)
+    restored: Option<WindowGeometry>,
+    /// Current geometry, updated from window events
+    current: Option<WindowGeometry>,
+    /// Time of the last unsaved geometry change
+    dirty_since: Option<Instant>,
+    /// Whether the restored position has been checked against connected monitors
+    validated: bool,
+}
+
+impl WindowStateTracker {
+    /// Create the tracker from the loaded settings file.
+    ///
+    /// When `reset` is true the saved geometry is ignored (but overwritten on
+    /// the next move/resize), which recovers from off-screen placement.
+    pub fn new(settings: SettingsFile, reset: bool) -> Self {
+        let restored = if reset { None } else { settings.window.clone() };
+
+        Self {
+            settings,
+            current: restored.clone(),
+            restored,
+            dirty_since: None,
+            validated: false,
+        }
+    }
+
+    /// Display configuration for the initial window size
+    pub fn display_config(&self) -> DisplayConfig {
+        match &self.restored {
+            Some(geometry) if geometry.width > 0 && geometry.height > 0 => {
+                DisplayConfig::new(geometry.width, geometry.height)
+            }
+            _ => DisplayConfig::default(),
+        }
+    }
+
+    /// Initial window position: the saved position, or centered on the primary monitor
+    pub fn initial_position(&self) -> WindowPosition {
+        match self
+            .restored
+            .as_ref()
+            .and_then(|geometry| geometry.position)
+        {
+            Some([x, y]) => WindowPosition::At(IVec2::new(x, y)),
+            None => WindowPosition::Centered(MonitorSelection::Primary),
+        }
+    }
+
+    fn write_to_disk(&mut self) {
+        self.settings.window = self.current.clone();
+        if let Err(e) = self.settings.save() {
+            warn!("Failed to save window geometry: {}", e);
+        }
+        self.dirty_since = None;
+    }
+
+    /// Store the keymap's overrides and write the settings file immediately
+    fn save_keymap(&mut self, keymap: &Keymap) {
+        self.settings.keymap = keymap.overrides();
+        if let Err(e) = self.settings.save() {
+            warn!("Failed to save keymap: {}", e);
+        }
+    }
+}
+
+pub struct WindowStatePlugin;
+
+impl Plugin for WindowStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                validate_restored_position,
+                track_window_geometry,
+                persist_window_geometry,
+            )
+                .chain(),
+        )
+        .add_systems(Update, persist_keymap)
+        .add_systems(Last, flush_on_exit);
+    }
+}
+
+/// Verify the restored position is still on a connected monitor.
+///
+/// Monitors are only known once winit has started, so this runs on the first
+/// frames rather than in `main`. Falls back to centering on the primary
+/// monitor when the saved monitor has been disconnected.
+fn validate_restored_position(
+    mut tracker: ResMut<WindowStateTracker>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    monitors: Query<&Monitor>,
+) {
+    if tracker.validated {
+        return;
+    }
+
+    // Wait until winit has reported the connected monitors
+    if monitors.is_empty() {
+        return;
+    }
+    tracker.validated = true;
+
+    let Some(restored) = tracker.restored.clone() else {
+        return;
+    };
+    let Ok(mut window) = windows.single_mut() else {
+        return;
+    };
+
+    let on_screen = monitors.iter().any(|monitor| {
+        restored.is_visible_on(
+            monitor.physical_position.to_array(),
+            [monitor.physical_width, monitor.physical_height],
+        )
+    });
+
+    if on_screen {
+        if restored.maximized {
+            window.set_maximized(true);
+        }
+        info!(
+            "Restored window geometry {}x{} at {:?} (monitor {:?})",
+            restored.width, restored.height, restored.position, restored.monitor
+        );
+    } else {
+        warn!(
+            "Saved window position {:?} is not on a connected monitor, centering on primary",
+            restored.position
+        );
+        window.position.center(MonitorSelection::Primary);
+    }
+}
+
+/// Record window size/position changes
+fn track_window_geometry(
+    mut tracker: ResMut<WindowStateTracker>,
+    mut resized_events: MessageReader<WindowResized>,
+    mut moved_events: MessageReader<WindowMoved>,
+    windows: Query<(Entity, &Window), With<PrimaryWindow>>,
+    monitors: Query<&Monitor>,
+) {
+    let Ok((primary, window)) = windows.single() else {
+        resized_events.clear();
+        moved_events.clear();
+        return;
+    };
+
+    let resized = resized_events.read().any(|event| event.window == primary);
+    let moved = moved_events
+        .read()
+        .filter(|event| event.window == primary)
+        .last()
+        .map(|event| event.position);
+
+    if !resized && moved.is_none() {
+        return;
+    }
+
+    let width = window.resolution.physical_width();
+    let height = window.resolution.physical_height();
+    if width == 0 || height == 0 {
+        // Minimized - keep the last real geometry
+        return;
+    }
+
+    let previous = tracker.current.clone();
+    let position = moved
+        .map(|p| p.to_array())
+        .or_else(|| match window.position {
+            WindowPosition::At(p) => Some(p.to_array()),
+            _ => None,
+        })
+        .or_else(|| previous.as_ref().and_then(|g| g.position));
+
+    // Find the monitor containing the window's top-left corner
+    let monitor = position.and_then(|[x, y]| {
+        monitors.iter().find(|monitor| {
+            let min = monitor.physical_position;
+            let max = min
+                + IVec2::new(
+                    monitor.physical_width as i32,
+                    monitor.physical_height as i32,
+                );
+            x >= min.x && x < max.x && y >= min.y && y < max.y
+        })
+    });
+
+    let maximized = monitor.is_some_and(|monitor| {
+        width as f32 >= monitor.physical_width as f32 * MAXIMIZED_COVERAGE
+            && height as f32 >= monitor.physical_height as f32 * MAXIMIZED_COVERAGE
+    });
+
+    // Keep the pre-maximize size and position so un-maximizing after a
+    // restart returns to the user's chosen geometry.
+    let geometry = match (&previous, maximized) {
+        (Some(previous), true) => WindowGeometry {
+            maximized: true,
+            monitor: monitor.and_then(|m| m.name.clone()),
+            ..previous.clone()
+        },
+        _ => WindowGeometry {
+            width,
+            height,
+            position,
+            maximized,
+            monitor: monitor.and_then(|m| m.name.clone()),
+        },
+    };
+
+    if previous.as_ref() != Some(&geometry) {
+        tracker.current = Some(geometry);
+        tracker.dirty_since = Some(Instant::now());
+    }
+}
+
+/// Write geometry to disk once the window has been still for `SAVE_DEBOUNCE`
+fn persist_window_geometry(mut tracker: ResMut<WindowStateTracker>) {
+    let Some(dirty_since) = tracker.dirty_since else {
+        return;
+    };
+
+    if dirty_since.elapsed() >= SAVE_DEBOUNCE {
+        tracker.write_to_disk();
+        debug!("Saved window geometry: {:?}", tracker.current);
+    }
+}
+
+/// Write keymap overrides to disk after a rebinding
+fn persist_keymap(keymap: Res<Keymap>, mut tracker: ResMut<WindowStateTracker>) {
+    if keymap.is_changed() && !keymap.is_added() {
+        tracker.save_keymap(&keymap);
+    }
+}
+
+/// Flush pending geometry changes when the app is exiting
+fn flush_on_exit(mut exit_events: MessageReader<AppExit>, mut tracker: ResMut<WindowStateTracker>) {
+    if exit_events.read().next().is_some() && tracker.dirty_since.is_some() {
+        tracker.write_to_disk();
+    }
+}
@@ -9,17 +9,28 @@
 //! - GTK/Bevy initialization conflicts
 //! - Runaway subprocess spawning issues
 //!
-//! This binary does one thing: calls cef::execute_process() and exits.
+//! This binary calls cef::execute_process() and exits. Render processes also
+//! get the native half of the UI IPC channel (see `render_process`).
+
+mod render_process;
 
 use cef::args::Args;
+use render_process::{HelperApp, HelperAppBuilder};
 
 fn main() {
     // Create Args from command line (captures argc/argv properly)
     let args = Args::new();
 
+    // App whose render process handler installs the IPC send function
+    let mut app = HelperAppBuilder::build(HelperApp);
+
     // Execute the CEF subprocess logic
     // CEF will determine what type of subprocess this is from command line args
-    let exit_code = cef::execute_process(Some(args.as_main_args()), None, std::ptr::null_mut());
+    let exit_code = cef::execute_process(
+        Some(args.as_main_args()),
+        Some(&mut app),
+        std::ptr::null_mut(),
+    );
 
     // exit_code >= 0 means this was a subprocess and CEF handled it
     // exit_code < 0 means this is the browser process (shouldn't happen for helper)
@@ -0,0 +1,168 @@
+//! Render process side of the UI IPC channel
+//!
+//! Installs `window.__pentimentoIpcSend(message)` in every V8 context. It
+//! forwards the string to the browser process as a `CefProcessMessage`,
+//! where the client's `OnProcessMessageReceived` hands it to Bevy. Process
+//! messages keep their order and carry multi-megabyte strings intact, which
+//! the old console.log channel couldn't guarantee.
+//!
+//! The helper's `App` also registers the `pentimento-blob` scheme, which
+//! the browser process serves large binary payloads under.
+
+use cef::rc::Rc as _;
+use cef::{
+    App, Browser, CefString, Frame, ImplApp, ImplFrame, ImplListValue, ImplProcessMessage,
+    ImplRenderProcessHandler, ImplSchemeRegistrar, ImplV8Context, ImplV8Handler, ImplV8Value,
+    ProcessId, RenderProcessHandler, SchemeRegistrar, V8Context, V8Handler, V8Propertyattribute,
+    V8Value, WrapApp, WrapRenderProcessHandler, WrapV8Handler, sys, wrap_app,
+    wrap_render_process_handler, wrap_v8_handler,
+};
+use pentimento_frontend_core::blob::BLOB_SCHEME;
+use std::ffi::c_int;
+
+/// Name of the process message carrying one UI→Bevy message.
+/// Must match `IPC_PROCESS_MESSAGE` in `pentimento-frontend-cef`.
+pub const IPC_PROCESS_MESSAGE: &str = "pentimento_ipc";
+
+/// Global function the IPC bridge calls with each serialized message.
+/// Must match `IPC_SEND_FUNCTION` in `pentimento-frontend-cef`.
+pub const IPC_SEND_FUNCTION: &str = "__pentimentoIpcSend";
+
+/// Send `message` to the browser process from the current V8 context's frame
+fn send_to_browser(message: &CefString) -> Result<(), &'static str> {
+    let frame = cef::v8_context_get_current_context()
+        .and_then(|context| context.frame())
+        .ok_or("no frame for the current V8 context")?;
+
+    let name = CefString::from(IPC_PROCESS_MESSAGE);
+    let mut process_message =
+        cef::process_message_create(Some(&name)).ok_or("failed to create process message")?;
+    let arguments = process_message
+        .argument_list()
+        .ok_or("process message has no argument list")?;
+    arguments.set_string(0, Some(message));
+
+    frame.send_process_message(ProcessId::BROWSER, Some(&mut process_message));
+    Ok(())
+}
+
+/// Native implementation of `__pentimentoIpcSend`
+#[derive(Clone)]
+pub struct IpcSendHandler;
+
+// Macro generates IpcSendHandlerBuilder which wraps IpcSendHandler
+wrap_v8_handler! {
+    pub struct IpcSendHandlerBuilder {
+        handler: IpcSendHandler,
+    }
+
+    impl V8Handler {
+        fn execute(
+            &self,
+            _name: Option<&CefString>,
+            _object: Option<&mut V8Value>,
+            arguments: Option<&[Option<V8Value>]>,
+            _retval: Option<&mut Option<V8Value>>,
+            exception: Option<&mut CefString>,
+        ) -> c_int {
+            let message = arguments
+                .and_then(|arguments| arguments.first())
+                .and_then(|argument| argument.as_ref())
+                .filter(|argument| argument.is_string() != 0)
+                .map(|argument| CefString::from(&argument.string_value()));
+
+            let result = match message {
+                Some(message) => send_to_browser(&message),
+                None => Err("expected one string argument"),
+            };
+
+            // Thrown as a JavaScript exception in the calling page
+            if let (Err(e), Some(exception)) = (result, exception) {
+                *exception = CefString::from(format!("{}: {}", IPC_SEND_FUNCTION, e).as_str());
+            }
+            1
+        }
+    }
+}
+
+impl IpcSendHandlerBuilder {
+    pub fn build(handler: IpcSendHandler) -> V8Handler {
+        Self::new(handler)
+    }
+}
+
+/// Render process handler that installs the IPC send function
+#[derive(Clone)]
+pub struct IpcRenderProcessHandler;
+
+// Macro generates RenderProcessHandlerBuilder which wraps IpcRenderProcessHandler
+wrap_render_process_handler! {
+    pub struct RenderProcessHandlerBuilder {
+        handler: IpcRenderProcessHandler,
+    }
+
+    impl RenderProcessHandler {
+        fn on_context_created(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            context: Option<&mut V8Context>,
+        ) {
+            let Some(global) = context.and_then(|context| context.global()) else {
+                return;
+            };
+
+            let name = CefString::from(IPC_SEND_FUNCTION);
+            let mut handler = IpcSendHandlerBuilder::build(IpcSendHandler);
+            let Some(mut function) = cef::v8_value_create_function(Some(&name), Some(&mut handler))
+            else {
+                return;
+            };
+            global.set_value_bykey(Some(&name), Some(&mut function), V8Propertyattribute::READONLY);
+        }
+    }
+}
+
+impl RenderProcessHandlerBuilder {
+    pub fn build(handler: IpcRenderProcessHandler) -> RenderProcessHandler {
+        Self::new(handler)
+    }
+}
+
+/// Declare the blob scheme exactly as the browser process does, so render
+/// processes allow `fetch()` of it
+fn register_blob_scheme(registrar: &mut SchemeRegistrar) {
+    let options = sys::cef_scheme_options_t::CEF_SCHEME_OPTION_STANDARD as c_int
+        | sys::cef_scheme_options_t::CEF_SCHEME_OPTION_CORS_ENABLED as c_int
+        | sys::cef_scheme_options_t::CEF_SCHEME_OPTION_FETCH_ENABLED as c_int;
+    registrar.add_custom_scheme(Some(&CefString::from(BLOB_SCHEME)), options);
+}
+
+/// App for the helper's subprocesses; only render processes use its handler
+#[derive(Clone)]
+pub struct HelperApp;
+
+// Macro generates HelperAppBuilder which wraps HelperApp
+wrap_app! {
+    pub struct HelperAppBuilder {
+        app: HelperApp,
+    }
+
+    impl App {
+        fn render_process_handler(&self) -> Option<RenderProcessHandler> {
+            Some(RenderProcessHandlerBuilder::build(IpcRenderProcessHandler))
+        }
+
+        fn on_register_custom_schemes(&self, registrar: Option<&mut SchemeRegistrar>) {
+            if let Some(registrar) = registrar {
+                register_blob_scheme(registrar);
+            }
+        }
+    }
+}
+
+impl HelperAppBuilder {
+    pub fn build(app: HelperApp) -> App {
+        Self::new(app)
+    }
+}
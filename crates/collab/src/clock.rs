@@ -0,0 +1,51 @@
+//! Lamport clock ordering strokes across peers
+
+/// Logical clock of one peer
+///
+/// Every local stroke ticks the clock, and every remote stroke moves it past
+/// the stroke's time, so a stroke made after seeing another always sorts
+/// after it. Concurrent strokes tie and are ordered by peer ID.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LamportClock {
+    time: u64,
+}
+
+impl LamportClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time of the last event seen
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Advance for a local event and return its time
+    pub fn tick(&mut self) -> u64 {
+        self.time += 1;
+        self.time
+    }
+
+    /// Catch up with a remote event's time
+    pub fn observe(&mut self, time: u64) {
+        self.time = self.time.max(time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_orders_after_observed_events() {
+        let mut a = LamportClock::new();
+        let mut b = LamportClock::new();
+        assert_eq!(a.tick(), 1);
+        assert_eq!(a.tick(), 2);
+
+        b.observe(2);
+        assert_eq!(b.tick(), 3);
+        b.observe(1);
+        assert_eq!(b.time(), 3);
+    }
+}
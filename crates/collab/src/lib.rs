@@ -0,0 +1,55 @@
+//! Peer-to-peer sessions for Pentimento
+//!
+//! Peers in a session share the stroke packets they paint and sculpt. Each
+//! packet becomes an [`Entry`] in a [`SessionLog`] that every peer keeps in
+//! the same order, using a Lamport time stamped in the packet header, so
+//! replaying a canvas's or mesh's entries gives the same result everywhere.
+//! Remote strokes are applied through the deterministic replay of the
+//! `painting` and `sculpting` pipelines.
+//!
+//! - [`clock`] - Lamport clock ordering strokes across peers
+//! - [`log`] - Ordered entries, per canvas or mesh
+//! - [`transport`] - How entries travel; [`TcpTransport`] for now
+//! - [`session`] - A peer's membership: recording local strokes and
+//!   collecting remote ones
+//!
+//! Peers that join late are sent every entry from the start of the session.
+//! Documents aren't transferred, so peers should start from the same blank
+//! canvases and base meshes.
+
+pub mod clock;
+pub mod log;
+pub mod session;
+pub mod transport;
+
+use std::net::SocketAddr;
+
+pub use clock::LamportClock;
+pub use log::{Entry, Payload, SessionLog, Target, sculpt_packets, stroke_packets};
+pub use session::{CollabSession, Update};
+pub use transport::{TcpTransport, Transport};
+
+/// Errors from hosting or joining a session
+#[derive(Debug, thiserror::Error)]
+pub enum CollabError {
+    /// The host address is taken or can't be listened on
+    #[error("Failed to listen on {addr}: {source}")]
+    Bind {
+        addr: SocketAddr,
+        source: std::io::Error,
+    },
+
+    /// The ticket isn't one this build can join
+    #[error("Invalid session ticket: {0}")]
+    InvalidTicket(String),
+
+    /// The host behind a ticket couldn't be reached
+    #[error("Failed to join {ticket}: {source}")]
+    Connect {
+        ticket: String,
+        source: std::io::Error,
+    },
+
+    #[error("Collab I/O error: {0}")]
+    Io(std::io::Error),
+}
@@ -0,0 +1,236 @@
+//! Ordered log of the strokes in a session
+//!
+//! Every peer keeps the same entries in the same order: by the stroke's
+//! Lamport time, then the peer that made it, then the order the peer sent
+//! its packets in. Replaying a target's entries in that order gives the same
+//! result on every peer, so a stroke that lands on top of another wins
+//! (last writer wins, per stroke).
+
+use std::collections::HashMap;
+
+use painting::{SpaceKind, StrokePacket};
+use sculpting::SculptStrokePacket;
+use serde::{Deserialize, Serialize};
+
+/// What a stroke changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Target {
+    /// Canvas plane, by plane ID
+    Canvas(u32),
+    /// Painted mesh (Ptex), by mesh ID
+    MeshPaint(u32),
+    /// Sculpted mesh, by mesh ID
+    Sculpt(u32),
+}
+
+/// A stroke packet carried in the session log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Payload {
+    Stroke(StrokePacket),
+    Sculpt(SculptStrokePacket),
+}
+
+impl Payload {
+    pub fn target(&self) -> Target {
+        match self {
+            Payload::Stroke(packet) => match packet.header.space_kind {
+                SpaceKind::CanvasPlane => Target::Canvas(packet.header.space_id),
+                SpaceKind::MeshPtex | SpaceKind::MeshVertexColors => {
+                    Target::MeshPaint(packet.header.space_id)
+                }
+            },
+            Payload::Sculpt(packet) => Target::Sculpt(packet.header.mesh_id),
+        }
+    }
+
+    /// Lamport time stamped in the packet header
+    pub fn lamport(&self) -> u64 {
+        match self {
+            Payload::Stroke(packet) => packet.header.lamport,
+            Payload::Sculpt(packet) => packet.header.lamport,
+        }
+    }
+
+    /// Stroke the packet belongs to (long strokes span several packets)
+    pub fn stroke_id(&self) -> u64 {
+        match self {
+            Payload::Stroke(packet) => packet.header.stroke_id,
+            Payload::Sculpt(packet) => packet.header.stroke_id,
+        }
+    }
+}
+
+/// One packet from one peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Peer that made the stroke
+    pub peer: u64,
+    /// Position among the packets the peer sent
+    pub seq: u64,
+    pub payload: Payload,
+}
+
+impl Entry {
+    /// Session order of the entry, unique per entry
+    pub fn key(&self) -> (u64, u64, u64) {
+        (self.payload.lamport(), self.peer, self.seq)
+    }
+}
+
+/// Entries of a session, kept in session order per target
+#[derive(Debug, Default)]
+pub struct SessionLog {
+    targets: HashMap<Target, Vec<Entry>>,
+    len: usize,
+}
+
+impl SessionLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry in session order
+    ///
+    /// Returns its index among the target's entries, or None if the log
+    /// already holds it (the first copy is kept).
+    pub fn insert(&mut self, entry: Entry) -> Option<usize> {
+        let entries = self.targets.entry(entry.payload.target()).or_default();
+        let key = entry.key();
+        let index = entries.partition_point(|existing| existing.key() < key);
+        if entries
+            .get(index)
+            .is_some_and(|existing| existing.key() == key)
+        {
+            return None;
+        }
+        entries.insert(index, entry);
+        self.len += 1;
+        Some(index)
+    }
+
+    /// Entries for a target in session order
+    pub fn entries(&self, target: Target) -> &[Entry] {
+        self.targets.get(&target).map_or(&[], Vec::as_slice)
+    }
+
+    /// Targets with at least one entry
+    pub fn targets(&self) -> impl Iterator<Item = Target> + '_ {
+        self.targets.keys().copied()
+    }
+
+    /// Every entry, target by target
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.targets.values().flatten()
+    }
+
+    /// Total number of entries
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Paint strokes in `entries`, each as its packets, for
+/// `PaintingPipeline::replay_packets`
+///
+/// Peers number their strokes independently, so strokes are told apart by
+/// peer as well as stroke ID.
+pub fn stroke_packets(entries: &[Entry]) -> Vec<Vec<StrokePacket>> {
+    group_strokes(entries, |payload| match payload {
+        Payload::Stroke(packet) => Some(packet.clone()),
+        Payload::Sculpt(_) => None,
+    })
+}
+
+/// Sculpt strokes in `entries`, each as its packets, for
+/// `sculpting::replay_packets`
+pub fn sculpt_packets(entries: &[Entry]) -> Vec<Vec<SculptStrokePacket>> {
+    group_strokes(entries, |payload| match payload {
+        Payload::Sculpt(packet) => Some(packet.clone()),
+        Payload::Stroke(_) => None,
+    })
+}
+
+fn group_strokes<T>(entries: &[Entry], packet: impl Fn(&Payload) -> Option<T>) -> Vec<Vec<T>> {
+    entries
+        .chunk_by(|a, b| (a.peer, a.payload.stroke_id()) == (b.peer, b.payload.stroke_id()))
+        .map(|stroke| {
+            stroke
+                .iter()
+                .filter_map(|entry| packet(&entry.payload))
+                .collect::<Vec<_>>()
+        })
+        .filter(|packets| !packets.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use painting::{BlendMode, Dab, Quantization, StrokeHeader};
+
+    pub(crate) fn stroke(space_id: u32, stroke_id: u64, lamport: u64) -> StrokePacket {
+        StrokePacket {
+            header: StrokeHeader {
+                version: 1,
+                space_kind: SpaceKind::CanvasPlane,
+                space_id,
+                stroke_id,
+                timestamp_ms: 0,
+                tool_id: 0,
+                blend_mode: BlendMode::Normal,
+                color: [0.0, 0.0, 0.0, 1.0],
+                flags: 0,
+                base_x: 40,
+                base_y: 40,
+                face_id: 0,
+                ptex_tile: 0,
+                pressure_quant: Quantization::U8,
+                speed_quant: Quantization::U8,
+                tip_hash: 0,
+                opacity: 255,
+                lamport,
+            },
+            dabs: vec![Dab {
+                size: 2560,
+                pressure: 65535,
+                opacity: 255,
+                hardness: 255,
+                ..Zeroable::zeroed()
+            }],
+        }
+    }
+
+    fn entry(peer: u64, seq: u64, lamport: u64) -> Entry {
+        Entry {
+            peer,
+            seq,
+            payload: Payload::Stroke(stroke(0, peer * 100 + seq, lamport)),
+        }
+    }
+
+    #[test]
+    fn test_entries_sort_by_lamport_then_peer() {
+        let mut log = SessionLog::new();
+        assert_eq!(log.insert(entry(2, 0, 1)), Some(0));
+        assert_eq!(log.insert(entry(2, 1, 2)), Some(1));
+        // Concurrent with peer 2's second stroke; the lower peer ID sorts first
+        assert_eq!(log.insert(entry(1, 0, 2)), Some(1));
+        assert_eq!(log.insert(entry(1, 1, 3)), Some(3));
+        // Retransmitted entries are dropped
+        assert_eq!(log.insert(entry(2, 1, 2)), None);
+
+        let order: Vec<_> = log
+            .entries(Target::Canvas(0))
+            .iter()
+            .map(Entry::key)
+            .collect();
+        assert_eq!(order, [(1, 2, 0), (2, 1, 0), (2, 2, 1), (3, 1, 1)]);
+        assert_eq!(log.len(), 4);
+        assert!(log.entries(Target::Canvas(1)).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+//! A peer's view of a shared session
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
+use std::net::SocketAddr;
+
+use painting::StrokePacket;
+use sculpting::SculptStrokePacket;
+
+use crate::CollabError;
+use crate::clock::LamportClock;
+use crate::log::{Entry, Payload, SessionLog, Target};
+use crate::transport::{TcpTransport, Transport};
+
+/// Entries a target needs applied since the last `take_updates`
+#[derive(Debug)]
+pub struct Update {
+    pub target: Target,
+    /// Whether the target must be reset and every entry replayed, because
+    /// an entry landed before ones already applied
+    pub rebuild: bool,
+    /// The entries to apply, in session order
+    pub entries: Vec<Entry>,
+}
+
+/// This peer's membership of a session
+///
+/// Local strokes are recorded with `record_stroke`/`record_sculpt` after
+/// they have been painted, and remote strokes are collected with `poll` and
+/// applied from `take_updates`. The session tracks how many of each target's
+/// entries the local document holds, so remote strokes are painted on top
+/// while they arrive in order, and the target is rebuilt when one doesn't.
+pub struct CollabSession {
+    /// Random ID breaking ties between concurrent strokes
+    peer: u64,
+    clock: LamportClock,
+    next_seq: u64,
+    /// Local stroke being recorded and its Lamport time; its packets share it
+    current_stroke: Option<(Target, u64, u64)>,
+    log: SessionLog,
+    /// Entries per target the local document holds
+    applied: HashMap<Target, usize>,
+    /// Targets to rebuild at the next `take_updates`
+    stale: HashSet<Target>,
+    transport: Box<dyn Transport>,
+}
+
+impl CollabSession {
+    /// Start a session over `transport`
+    pub fn new(transport: Box<dyn Transport>) -> Self {
+        Self {
+            peer: RandomState::new().hash_one(std::process::id()),
+            clock: LamportClock::new(),
+            next_seq: 0,
+            current_stroke: None,
+            log: SessionLog::new(),
+            applied: HashMap::new(),
+            stale: HashSet::new(),
+            transport,
+        }
+    }
+
+    /// Host a session over TCP on `addr`
+    pub fn host(addr: SocketAddr) -> Result<Self, CollabError> {
+        Ok(Self::new(Box::new(TcpTransport::host(addr)?)))
+    }
+
+    /// Join the session behind a host's ticket
+    pub fn join(ticket: &str) -> Result<Self, CollabError> {
+        Ok(Self::new(Box::new(TcpTransport::join(ticket)?)))
+    }
+
+    pub fn peer_id(&self) -> u64 {
+        self.peer
+    }
+
+    /// Ticket for other peers to join with (hosts only)
+    pub fn ticket(&self) -> Option<String> {
+        self.transport.ticket()
+    }
+
+    /// Number of peers connected to this one
+    pub fn peer_count(&self) -> usize {
+        self.transport.peer_count()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.transport.is_connected()
+    }
+
+    pub fn log(&self) -> &SessionLog {
+        &self.log
+    }
+
+    /// Share a paint packet already painted on this peer
+    ///
+    /// The packet's header is stamped with the stroke's Lamport time.
+    pub fn record_stroke(&mut self, packet: StrokePacket) {
+        self.record(Payload::Stroke(packet));
+    }
+
+    /// Share a sculpt packet already applied on this peer
+    pub fn record_sculpt(&mut self, packet: SculptStrokePacket) {
+        self.record(Payload::Sculpt(packet));
+    }
+
+    fn record(&mut self, mut payload: Payload) {
+        let target = payload.target();
+        let stroke_id = payload.stroke_id();
+        let lamport = match self.current_stroke {
+            Some((current_target, current_id, lamport))
+                if current_target == target && current_id == stroke_id =>
+            {
+                lamport
+            }
+            _ => self.clock.tick(),
+        };
+        self.current_stroke = Some((target, stroke_id, lamport));
+        match &mut payload {
+            Payload::Stroke(packet) => packet.header.lamport = lamport,
+            Payload::Sculpt(packet) => packet.header.lamport = lamport,
+        }
+
+        let entry = Entry {
+            peer: self.peer,
+            seq: self.next_seq,
+            payload,
+        };
+        self.next_seq += 1;
+        self.transport.send(&entry);
+
+        // Later than anything seen, so it lands at the end
+        if let Some(index) = self.log.insert(entry) {
+            let applied = self.applied.entry(target).or_default();
+            if *applied == index {
+                *applied += 1;
+            } else {
+                // Painted ahead of remote strokes not applied yet
+                self.stale.insert(target);
+            }
+        }
+    }
+
+    /// Collect entries received from other peers; returns how many were new
+    pub fn poll(&mut self) -> usize {
+        let mut received = 0;
+        while let Some(entry) = self.transport.try_recv() {
+            self.clock.observe(entry.payload.lamport());
+            let target = entry.payload.target();
+            let Some(index) = self.log.insert(entry) else {
+                continue;
+            };
+            received += 1;
+            if index < self.applied.get(&target).copied().unwrap_or(0) {
+                self.stale.insert(target);
+            }
+        }
+        received
+    }
+
+    /// Entries each target needs applied, after which the session counts
+    /// them as applied
+    pub fn take_updates(&mut self) -> Vec<Update> {
+        let mut updates = Vec::new();
+        for target in self.log.targets().collect::<Vec<_>>() {
+            let entries = self.log.entries(target);
+            let applied = self.applied.entry(target).or_default();
+            let rebuild = self.stale.remove(&target);
+            let start = if rebuild { 0 } else { *applied };
+            if start < entries.len() {
+                updates.push(Update {
+                    target,
+                    rebuild,
+                    entries: entries[start..].to_vec(),
+                });
+            }
+            *applied = entries.len();
+        }
+        updates.sort_by_key(|update| update.target);
+        updates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::tests::stroke;
+    use std::sync::mpsc::{self, Receiver, Sender};
+
+    /// Transport handing entries to a channel, delivered by hand
+    struct Loopback {
+        outbox: Sender<Entry>,
+        inbox: Receiver<Entry>,
+    }
+
+    impl Transport for Loopback {
+        fn ticket(&self) -> Option<String> {
+            None
+        }
+        fn send(&mut self, entry: &Entry) {
+            let _ = self.outbox.send(entry.clone());
+        }
+        fn try_recv(&mut self) -> Option<Entry> {
+            self.inbox.try_recv().ok()
+        }
+        fn peer_count(&self) -> usize {
+            1
+        }
+        fn is_connected(&self) -> bool {
+            true
+        }
+    }
+
+    fn pair() -> (CollabSession, CollabSession) {
+        let (a_tx, a_rx) = mpsc::channel();
+        let (b_tx, b_rx) = mpsc::channel();
+        let a = CollabSession::new(Box::new(Loopback {
+            outbox: b_tx,
+            inbox: a_rx,
+        }));
+        let b = CollabSession::new(Box::new(Loopback {
+            outbox: a_tx,
+            inbox: b_rx,
+        }));
+        (a, b)
+    }
+
+    #[test]
+    fn test_in_order_strokes_are_applied_on_top() {
+        let (mut a, mut b) = pair();
+        a.record_stroke(stroke(0, 1, 0));
+        a.record_stroke(stroke(0, 1, 0));
+        assert!(
+            a.take_updates().is_empty(),
+            "local strokes are already painted"
+        );
+
+        assert_eq!(b.poll(), 2);
+        let updates = b.take_updates();
+        assert_eq!(updates.len(), 1);
+        assert!(!updates[0].rebuild);
+        // Both packets of the stroke share its Lamport time
+        let keys: Vec<_> = updates[0].entries.iter().map(Entry::key).collect();
+        assert_eq!(keys, [(1, a.peer_id(), 0), (1, a.peer_id(), 1)]);
+
+        // B's next stroke sorts after what it has seen
+        b.record_stroke(stroke(0, 2, 0));
+        assert_eq!(b.log().entries(Target::Canvas(0))[2].key().0, 2);
+        assert_eq!(a.poll(), 1);
+        assert!(!a.take_updates()[0].rebuild);
+    }
+
+    #[test]
+    fn test_concurrent_strokes_rebuild_the_earlier_peer() {
+        let (mut a, mut b) = pair();
+        a.record_stroke(stroke(0, 1, 0));
+        b.record_stroke(stroke(0, 1, 0));
+        a.poll();
+        b.poll();
+
+        // Both strokes have time 1; the one from the lower peer ID goes first,
+        // so that peer paints the other on top and the other peer rebuilds
+        let (low, high) = if a.peer_id() < b.peer_id() {
+            (&mut a, &mut b)
+        } else {
+            (&mut b, &mut a)
+        };
+        let low_updates = low.take_updates();
+        assert!(!low_updates[0].rebuild);
+        assert_eq!(low_updates[0].entries.len(), 1);
+        let high_updates = high.take_updates();
+        assert!(high_updates[0].rebuild);
+        assert_eq!(high_updates[0].entries.len(), 2);
+
+        let order = |session: &CollabSession| -> Vec<_> {
+            session
+                .log()
+                .entries(Target::Canvas(0))
+                .iter()
+                .map(Entry::key)
+                .collect()
+        };
+        assert_eq!(order(&a), order(&b));
+    }
+}
@@ -0,0 +1,36 @@
+//! How session entries travel between peers
+//!
+//! A transport delivers every entry a peer sends to every other peer, and
+//! brings a peer that joins late up to date with the entries sent before it
+//! joined. Entries may arrive more than once or out of session order; the
+//! session log sorts that out.
+//!
+//! `TcpTransport` is the only transport so far: the host relays entries
+//! between the peers connected to it. An Iroh transport (document sync with
+//! NAT traversal and authenticated peers) is meant to slot in behind the same
+//! trait.
+
+mod tcp;
+
+pub use tcp::TcpTransport;
+
+use crate::log::Entry;
+
+/// Connection of one peer to a session
+pub trait Transport: Send {
+    /// Ticket other peers join with, if this peer can be joined
+    fn ticket(&self) -> Option<String>;
+
+    /// Send an entry made on this peer to every other peer
+    fn send(&mut self, entry: &Entry);
+
+    /// Next entry received from another peer
+    fn try_recv(&mut self) -> Option<Entry>;
+
+    /// Number of peers this one is connected to directly
+    fn peer_count(&self) -> usize;
+
+    /// Whether the session can still be reached (false once a joiner loses
+    /// its host)
+    fn is_connected(&self) -> bool;
+}
@@ -0,0 +1,295 @@
+//! Plain TCP transport
+//!
+//! The host listens for joiners and relays every entry it hears to every
+//! other peer, keeping a copy of each to send to peers that join later.
+//! Entries travel as JSON, one per line. There is no authentication or
+//! encryption, so the host only listens on the loopback interface unless
+//! given another address.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::CollabError;
+use crate::log::Entry;
+
+use super::Transport;
+
+/// Ticket scheme of TCP sessions (`tcp://<host>:<port>`)
+pub const TICKET_SCHEME: &str = "tcp://";
+
+/// Longest line accepted from a peer
+const MAX_LINE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// How long a joiner waits for the host to accept
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A peer connected to the host
+struct Peer {
+    id: u64,
+    stream: TcpStream,
+}
+
+/// Host side: the connected peers and every entry relayed so far
+#[derive(Default)]
+struct Relay {
+    peers: Vec<Peer>,
+    /// Serialized entries, newline included, replayed to late joiners
+    history: Vec<String>,
+}
+
+impl Relay {
+    /// Record a line and write it to every peer but `from`
+    fn relay(&mut self, line: String, from: Option<u64>) {
+        self.peers.retain_mut(|peer| {
+            if Some(peer.id) == from {
+                return true;
+            }
+            match peer.stream.write_all(line.as_bytes()) {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Dropping collab peer {}: {}", peer.id, e);
+                    let _ = peer.stream.shutdown(Shutdown::Both);
+                    false
+                }
+            }
+        });
+        self.history.push(line);
+    }
+}
+
+enum Role {
+    Host {
+        addr: SocketAddr,
+        relay: Arc<Mutex<Relay>>,
+        stopped: Arc<AtomicBool>,
+    },
+    Joiner {
+        stream: TcpStream,
+    },
+}
+
+/// Session over plain TCP, hosted by one peer
+pub struct TcpTransport {
+    role: Role,
+    inbox: Receiver<Entry>,
+    connected: Arc<AtomicBool>,
+}
+
+impl TcpTransport {
+    /// Host a session on `addr` (port 0 picks a free port)
+    pub fn host(addr: SocketAddr) -> Result<Self, CollabError> {
+        let listener =
+            TcpListener::bind(addr).map_err(|source| CollabError::Bind { addr, source })?;
+        let addr = listener.local_addr().map_err(CollabError::Io)?;
+
+        let relay = Arc::new(Mutex::new(Relay::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (inbox_tx, inbox) = mpsc::channel();
+
+        let accept_relay = relay.clone();
+        let accept_stopped = stopped.clone();
+        std::thread::Builder::new()
+            .name("pentimento-collab-host".to_string())
+            .spawn(move || accept_peers(listener, accept_relay, accept_stopped, inbox_tx))
+            .map_err(CollabError::Io)?;
+
+        tracing::info!("Hosting collab session on {}", addr);
+        Ok(Self {
+            role: Role::Host {
+                addr,
+                relay,
+                stopped,
+            },
+            inbox,
+            connected: Arc::new(AtomicBool::new(true)),
+        })
+    }
+
+    /// Join the session a host's ticket points to
+    pub fn join(ticket: &str) -> Result<Self, CollabError> {
+        let addr: SocketAddr = ticket
+            .strip_prefix(TICKET_SCHEME)
+            .and_then(|addr| addr.parse().ok())
+            .ok_or_else(|| CollabError::InvalidTicket(ticket.to_string()))?;
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(|source| {
+            CollabError::Connect {
+                ticket: ticket.to_string(),
+                source,
+            }
+        })?;
+        let _ = stream.set_nodelay(true);
+        let reader = stream.try_clone().map_err(CollabError::Io)?;
+
+        let connected = Arc::new(AtomicBool::new(true));
+        let (inbox_tx, inbox) = mpsc::channel();
+        let reader_connected = connected.clone();
+        std::thread::Builder::new()
+            .name("pentimento-collab-peer".to_string())
+            .spawn(move || {
+                read_entries(reader, |_, entry| {
+                    let _ = inbox_tx.send(entry);
+                });
+                tracing::info!("Collab host {} closed the connection", addr);
+                reader_connected.store(false, Ordering::Relaxed);
+            })
+            .map_err(CollabError::Io)?;
+
+        tracing::info!("Joined collab session at {}", addr);
+        Ok(Self {
+            role: Role::Joiner { stream },
+            inbox,
+            connected,
+        })
+    }
+}
+
+impl Transport for TcpTransport {
+    fn ticket(&self) -> Option<String> {
+        match &self.role {
+            Role::Host { addr, .. } => Some(format!("{}{}", TICKET_SCHEME, addr)),
+            Role::Joiner { .. } => None,
+        }
+    }
+
+    fn send(&mut self, entry: &Entry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(json) => json + "\n",
+            Err(e) => {
+                tracing::error!("Failed to serialize collab entry: {}", e);
+                return;
+            }
+        };
+        match &mut self.role {
+            Role::Host { relay, .. } => relay.lock().unwrap().relay(line, None),
+            Role::Joiner { stream } => {
+                if let Err(e) = stream.write_all(line.as_bytes()) {
+                    tracing::warn!("Failed to send stroke to collab host: {}", e);
+                    self.connected.store(false, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    fn try_recv(&mut self) -> Option<Entry> {
+        self.inbox.try_recv().ok()
+    }
+
+    fn peer_count(&self) -> usize {
+        match &self.role {
+            Role::Host { relay, .. } => relay.lock().unwrap().peers.len(),
+            Role::Joiner { .. } => usize::from(self.is_connected()),
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for TcpTransport {
+    fn drop(&mut self) {
+        match &self.role {
+            Role::Host {
+                addr,
+                relay,
+                stopped,
+            } => {
+                stopped.store(true, Ordering::Relaxed);
+                // Wake the accept loop so it sees the flag
+                let _ = TcpStream::connect_timeout(addr, CONNECT_TIMEOUT);
+                for peer in relay.lock().unwrap().peers.drain(..) {
+                    let _ = peer.stream.shutdown(Shutdown::Both);
+                }
+            }
+            Role::Joiner { stream } => {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+/// Accept joiners until the host is dropped
+fn accept_peers(
+    listener: TcpListener,
+    relay: Arc<Mutex<Relay>>,
+    stopped: Arc<AtomicBool>,
+    inbox: Sender<Entry>,
+) {
+    let next_id = AtomicU64::new(1);
+    for stream in listener.incoming() {
+        if stopped.load(Ordering::Relaxed) {
+            break;
+        }
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::warn!("Failed to accept collab peer: {}", e);
+                continue;
+            }
+        };
+        let _ = stream.set_nodelay(true);
+        let Ok(reader) = stream.try_clone() else {
+            continue;
+        };
+        let id = next_id.fetch_add(1, Ordering::Relaxed);
+
+        // Under the lock, so the joiner gets every entry exactly once
+        {
+            let mut relay = relay.lock().unwrap();
+            if let Err(e) = relay
+                .history
+                .iter()
+                .try_for_each(|line| stream.write_all(line.as_bytes()))
+            {
+                tracing::warn!("Failed to catch up collab peer {}: {}", id, e);
+                continue;
+            }
+            relay.peers.push(Peer { id, stream });
+        }
+        tracing::info!("Collab peer {} joined", id);
+
+        let relay = relay.clone();
+        let inbox = inbox.clone();
+        let spawned = std::thread::Builder::new()
+            .name(format!("pentimento-collab-peer-{}", id))
+            .spawn(move || {
+                read_entries(reader, |line, entry| {
+                    relay.lock().unwrap().relay(line, Some(id));
+                    let _ = inbox.send(entry);
+                });
+                relay.lock().unwrap().peers.retain(|peer| peer.id != id);
+                tracing::info!("Collab peer {} left", id);
+            });
+        if let Err(e) = spawned {
+            tracing::error!("Failed to start collab peer reader: {}", e);
+        }
+    }
+}
+
+/// Read entries until the connection closes, handing each to `on_entry`
+/// with its line
+fn read_entries(stream: TcpStream, mut on_entry: impl FnMut(String, Entry)) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        match reader.by_ref().take(MAX_LINE_BYTES).read_line(&mut line) {
+            Ok(0) => return,
+            Ok(_) if !line.ends_with('\n') => {
+                tracing::warn!("Collab peer sent an oversized or truncated entry");
+                return;
+            }
+            Ok(_) => match serde_json::from_str::<Entry>(&line) {
+                Ok(entry) => on_entry(line, entry),
+                Err(e) => tracing::warn!("Ignoring malformed collab entry: {}", e),
+            },
+            Err(e) => {
+                tracing::debug!("Collab connection closed: {}", e);
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Two peers in one session over localhost TCP, painting at the same time.
+//!
+//! Each peer paints live, shares its packets, and applies the other's the
+//! way the app does. Both canvases must end up identical, including for a
+//! peer that joins after the painting is done.
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use painting::{BrushPreset, PaintingPipeline};
+use pentimento_collab::{CollabSession, Target, stroke_packets};
+
+const SIZE: u32 = 128;
+
+/// A peer's canvas 0 and its membership of the session
+struct Peer {
+    session: CollabSession,
+    canvas: PaintingPipeline,
+    next_stroke_id: u64,
+}
+
+impl Peer {
+    fn new(session: CollabSession) -> Self {
+        Self {
+            session,
+            canvas: PaintingPipeline::new(SIZE, SIZE),
+            next_stroke_id: 1,
+        }
+    }
+
+    /// Paint a horizontal stroke live and share it
+    fn paint(&mut self, color: [f32; 4], y: f32) {
+        let logged = self.canvas.log().query_by_space(0).len();
+        self.canvas.set_color(color);
+        self.canvas.begin_stroke(0, self.next_stroke_id, 0);
+        self.next_stroke_id += 1;
+        // Long enough to span several packets
+        for i in 0..60 {
+            self.canvas.stroke_to(4.0 + i as f32 * 2.0, y, 1.0);
+        }
+        self.canvas.end_stroke();
+
+        // The app gets these from a stroke log listener
+        for packet in self.canvas.log().query_by_space(0).split_off(logged) {
+            self.session.record_stroke(packet);
+        }
+    }
+
+    /// Apply what the session has collected
+    fn apply(&mut self) {
+        for update in self.session.take_updates() {
+            assert_eq!(update.target, Target::Canvas(0));
+            if update.rebuild {
+                self.canvas = PaintingPipeline::new(SIZE, SIZE);
+            }
+            for stroke in stroke_packets(&update.entries) {
+                assert!(self.canvas.replay_packets(&stroke, &[]));
+            }
+        }
+    }
+
+    /// Poll until the session holds `count` entries
+    fn receive(&mut self, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while self.session.log().len() < count {
+            assert!(Instant::now() < deadline, "timed out waiting for strokes");
+            self.session.poll();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        self.apply();
+    }
+}
+
+#[test]
+fn test_two_peers_converge_to_the_same_canvas() {
+    let host = CollabSession::host(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).unwrap();
+    let ticket = host.ticket().unwrap();
+    let mut host = Peer::new(host);
+    let mut guest = Peer::new(CollabSession::join(&ticket).unwrap());
+
+    host.canvas.set_brush(BrushPreset {
+        opacity: 0.7,
+        ..Default::default()
+    });
+
+    // Overlapping strokes painted at the same time on both sides
+    host.paint([1.0, 0.0, 0.0, 1.0], 60.0);
+    guest.paint([0.0, 0.0, 1.0, 1.0], 62.0);
+    host.paint([0.0, 1.0, 0.0, 1.0], 64.0);
+
+    let total = host.session.log().len() + guest.session.log().len();
+    host.receive(total);
+    guest.receive(total);
+    assert_eq!(host.canvas.canvas_hash(), guest.canvas.canvas_hash());
+
+    // Strokes made after seeing each other's stay in order without a rebuild
+    guest.paint([1.0, 1.0, 0.0, 1.0], 66.0);
+    host.receive(guest.session.log().len());
+    assert_eq!(host.canvas.canvas_hash(), guest.canvas.canvas_hash());
+
+    // A late joiner is sent the whole session
+    let mut late = Peer::new(CollabSession::join(&ticket).unwrap());
+    late.receive(host.session.log().len());
+    assert_eq!(late.canvas.canvas_hash(), host.canvas.canvas_hash());
+    assert_eq!(host.session.peer_count(), 2);
+}
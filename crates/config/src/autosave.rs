@@ -0,0 +1,208 @@
+//! Autosave file layout
+//!
+//! Autosaves live in an `autosave` directory next to the settings file. Each
+//! session writes into its own directory, named after its start time in Unix
+//! milliseconds, containing a rotating set of scene snapshots
+//! (`snapshot-0.json`, `snapshot-1.json`, ...) and an append-only stroke
+//! journal (`strokes.jsonl`). Only the newest few sessions are kept.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::settings_file::{SettingsError, SettingsFile};
+
+/// Directory name of the autosave root inside the config directory
+pub const AUTOSAVE_DIR_NAME: &str = "autosave";
+
+/// File name of the per-session stroke journal (one JSON stroke packet per line)
+pub const STROKE_JOURNAL_NAME: &str = "strokes.jsonl";
+
+/// Number of rotating snapshot files per session
+pub const SNAPSHOT_SLOTS: usize = 3;
+
+/// Number of session directories kept (older ones are pruned)
+pub const KEPT_SESSIONS: usize = 3;
+
+/// Default interval between autosaves
+pub const DEFAULT_AUTOSAVE_INTERVAL_SECS: u64 = 5 * 60;
+
+/// Resolve the autosave root directory (`<config dir>/autosave`)
+pub fn default_autosave_root() -> Result<PathBuf, SettingsError> {
+    let settings_path = SettingsFile::default_path()?;
+    let config_dir = settings_path.parent().ok_or(SettingsError::NoConfigDir)?;
+    Ok(config_dir.join(AUTOSAVE_DIR_NAME))
+}
+
+/// Autosave directory of the running session
+#[derive(Debug, Clone)]
+pub struct AutosaveSession {
+    dir: PathBuf,
+    next_slot: usize,
+}
+
+impl AutosaveSession {
+    /// Create the session directory for a session started at `started_ms`
+    pub fn create(root: &Path, started_ms: u64) -> std::io::Result<Self> {
+        let dir = root.join(started_ms.to_string());
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir, next_slot: 0 })
+    }
+
+    /// Session directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the session's stroke journal
+    pub fn journal_path(&self) -> PathBuf {
+        self.dir.join(STROKE_JOURNAL_NAME)
+    }
+
+    /// Path for the next snapshot, cycling through the slots so the oldest
+    /// snapshot is the one overwritten
+    pub fn next_snapshot_path(&mut self) -> PathBuf {
+        let path = snapshot_path(&self.dir, self.next_slot);
+        self.next_slot = (self.next_slot + 1) % SNAPSHOT_SLOTS;
+        path
+    }
+}
+
+fn snapshot_path(dir: &Path, slot: usize) -> PathBuf {
+    dir.join(format!("snapshot-{slot}.json"))
+}
+
+/// Write `contents` to `path` through a temporary file, so a crash mid-write
+/// leaves the previous file intact
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, path)
+}
+
+/// Newest snapshot in a session directory
+pub fn latest_snapshot(session_dir: &Path) -> Option<PathBuf> {
+    (0..SNAPSHOT_SLOTS)
+        .map(|slot| snapshot_path(session_dir, slot))
+        .filter_map(|path| Some((modified(&path)?, path)))
+        .max_by_key(|(time, _)| *time)
+        .map(|(_, path)| path)
+}
+
+/// A previous session with autosaved work
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryCandidate {
+    /// Session directory
+    pub dir: PathBuf,
+    /// Time of the session's newest snapshot or journal write
+    pub modified: SystemTime,
+}
+
+/// Find the most recently written session under `root`, if it is newer than
+/// `last_save` (the last explicit save, when there is one). Sessions that
+/// wrote nothing are ignored.
+pub fn find_recovery(root: &Path, last_save: Option<SystemTime>) -> Option<RecoveryCandidate> {
+    session_dirs(root)
+        .into_iter()
+        .filter_map(|(_, dir)| {
+            let newest = latest_snapshot(&dir)
+                .into_iter()
+                .chain(std::iter::once(dir.join(STROKE_JOURNAL_NAME)))
+                .filter_map(|path| modified(&path))
+                .max()?;
+            Some(RecoveryCandidate {
+                dir,
+                modified: newest,
+            })
+        })
+        .max_by_key(|candidate| candidate.modified)
+        .filter(|candidate| last_save.is_none_or(|saved| candidate.modified > saved))
+}
+
+/// Remove all but the newest `keep` session directories
+pub fn prune_sessions(root: &Path, keep: usize) -> std::io::Result<()> {
+    let mut sessions = session_dirs(root);
+    sessions.sort_by_key(|(started, _)| std::cmp::Reverse(*started));
+    for (_, dir) in sessions.into_iter().skip(keep) {
+        std::fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Session directories under `root` with their start times
+fn session_dirs(root: &Path) -> Vec<(u64, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let started = entry.file_name().to_str()?.parse::<u64>().ok()?;
+            Some((started, entry.path()))
+        })
+        .collect()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("pentimento-autosave-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn test_snapshot_slots_rotate() {
+        let root = temp_root("rotate");
+        let mut session = AutosaveSession::create(&root, 1000).unwrap();
+
+        let paths: Vec<_> = (0..SNAPSHOT_SLOTS + 1)
+            .map(|_| session.next_snapshot_path())
+            .collect();
+        assert_eq!(paths[0], paths[SNAPSHOT_SLOTS]);
+        assert_ne!(paths[0], paths[1]);
+
+        assert_eq!(latest_snapshot(session.dir()), None);
+        write_atomic(&paths[1], b"{}").unwrap();
+        assert_eq!(latest_snapshot(session.dir()), Some(paths[1].clone()));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_recovery_and_pruning() {
+        let root = temp_root("recovery");
+        assert_eq!(find_recovery(&root, None), None);
+
+        let mut old = AutosaveSession::create(&root, 1000).unwrap();
+        write_atomic(&old.next_snapshot_path(), b"{}").unwrap();
+        // A session that only journaled strokes is still recoverable
+        let journaled = AutosaveSession::create(&root, 2000).unwrap();
+        std::fs::write(journaled.journal_path(), b"{}\n").unwrap();
+        // Empty sessions are skipped
+        AutosaveSession::create(&root, 3000).unwrap();
+
+        let candidate = find_recovery(&root, None).unwrap();
+        assert!(candidate.dir == old.dir() || candidate.dir == journaled.dir());
+
+        // Nothing newer than an explicit save made after the autosaves
+        let saved_later = candidate.modified + std::time::Duration::from_secs(1);
+        assert_eq!(find_recovery(&root, Some(saved_later)), None);
+
+        prune_sessions(&root, 2).unwrap();
+        assert!(!old.dir().exists());
+        assert!(journaled.dir().exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
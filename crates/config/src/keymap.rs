@@ -0,0 +1,559 @@
+//! Configurable hotkeys
+//!
+//! Maps named actions ("gizmo.translate", "add.menu", ...) to key chords
+//! (a key plus Ctrl/Shift/Alt modifiers). The defaults match the built-in
+//! hotkeys; the settings file only stores bindings that differ from them, so
+//! new defaults reach users who never touched the keymap.
+//!
+//! Each action belongs to a [`KeyContext`]. Two actions may share a chord
+//! only if their contexts can never be active at the same time.
+//!
+//! Keys name physical positions (US QWERTY labels), not typed characters, so
+//! on AZERTY the default `gizmo.translate` is still the key right of F.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "bevy")]
+use bevy::input::{ButtonInput, keyboard::KeyCode};
+#[cfg(feature = "bevy")]
+use bevy::prelude::Resource;
+
+/// Action names, so call sites can't drift from the binding table by a typo
+pub mod actions {
+    pub const ADD_MENU: &str = "add.menu";
+    pub const PAINT_UNDO: &str = "paint.undo";
+    pub const DEVTOOLS_TOGGLE: &str = "devtools.toggle";
+    pub const COMPOSITE_MODE_NEXT: &str = "debug.composite_mode_next";
+    pub const MODE_TOGGLE: &str = "mode.toggle";
+    pub const MODE_PAINT: &str = "mode.paint";
+    pub const MODE_SCULPT: &str = "mode.sculpt";
+    pub const GIZMO_TRANSLATE: &str = "gizmo.translate";
+    pub const GIZMO_ROTATE: &str = "gizmo.rotate";
+    pub const GIZMO_SCALE: &str = "gizmo.scale";
+    pub const GIZMO_ROTATE_CYCLE: &str = "gizmo.rotate_cycle";
+    pub const GIZMO_AXIS_X: &str = "gizmo.axis_x";
+    pub const GIZMO_AXIS_Y: &str = "gizmo.axis_y";
+    pub const GIZMO_AXIS_Z: &str = "gizmo.axis_z";
+    pub const MESH_EDIT_VERTEX_MODE: &str = "mesh_edit.vertex_mode";
+    pub const MESH_EDIT_EDGE_MODE: &str = "mesh_edit.edge_mode";
+    pub const MESH_EDIT_FACE_MODE: &str = "mesh_edit.face_mode";
+    pub const MESH_EDIT_SELECT_ALL: &str = "mesh_edit.select_all";
+    pub const MESH_EDIT_EXTRUDE: &str = "mesh_edit.extrude";
+    pub const MESH_EDIT_INSET: &str = "mesh_edit.inset";
+    pub const SCULPT_ADJUST_RADIUS: &str = "sculpt.adjust_radius";
+    pub const SCULPT_ADJUST_STRENGTH: &str = "sculpt.adjust_strength";
+    pub const PAINT_SUSPEND: &str = "paint.suspend";
+    pub const OBJECT_HIDE: &str = "object.hide";
+    pub const OBJECT_UNHIDE_ALL: &str = "object.unhide_all";
+    pub const OBJECT_ISOLATE_TOGGLE: &str = "object.isolate_toggle";
+}
+
+/// When an action's hotkey is live
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyContext {
+    /// Always
+    Global,
+    /// Something is selected and no transform is running
+    Object,
+    /// A gizmo transform is running
+    Transform,
+    /// Mesh edit mode
+    MeshEdit,
+    /// Sculpt mode
+    Sculpt,
+    /// Any brush mode: canvas paint, mesh paint, or sculpt
+    Brush,
+}
+
+impl KeyContext {
+    /// Whether hotkeys in both contexts can fire on the same key press.
+    ///
+    /// Gizmo hotkeys stay live in mesh edit and the brush modes, since those
+    /// modes keep the object selected.
+    pub fn overlaps(self, other: KeyContext) -> bool {
+        use KeyContext::*;
+        match (self, other) {
+            (Global, _) | (_, Global) => true,
+            (Object | Transform, MeshEdit | Sculpt | Brush)
+            | (MeshEdit | Sculpt | Brush, Object | Transform) => true,
+            (Sculpt, Brush) | (Brush, Sculpt) => true,
+            _ => self == other,
+        }
+    }
+}
+
+/// Every bindable action: name, context, and default chord
+#[rustfmt::skip]
+const ACTIONS: &[(&str, KeyContext, &str)] = &[
+    (actions::ADD_MENU, KeyContext::Global, "Shift+A"),
+    // Undoes the last mesh edit operation in mesh edit mode
+    (actions::PAINT_UNDO, KeyContext::Global, "Ctrl+Z"),
+    (actions::DEVTOOLS_TOGGLE, KeyContext::Global, "Ctrl+Shift+I"),
+    (actions::COMPOSITE_MODE_NEXT, KeyContext::Global, "Ctrl+Shift+M"),
+    // Mesh edit, canvas camera lock, or render camera view, depending on selection
+    (actions::MODE_TOGGLE, KeyContext::Global, "Tab"),
+    (actions::MODE_PAINT, KeyContext::Global, "Shift+Tab"),
+    (actions::MODE_SCULPT, KeyContext::Global, "Ctrl+Tab"),
+    (actions::GIZMO_TRANSLATE, KeyContext::Object, "G"),
+    (actions::GIZMO_ROTATE, KeyContext::Object, "R"),
+    (actions::GIZMO_SCALE, KeyContext::Object, "S"),
+    (actions::GIZMO_ROTATE_CYCLE, KeyContext::Transform, "R"),
+    (actions::GIZMO_AXIS_X, KeyContext::Transform, "X"),
+    (actions::GIZMO_AXIS_Y, KeyContext::Transform, "Y"),
+    (actions::GIZMO_AXIS_Z, KeyContext::Transform, "Z"),
+    (actions::MESH_EDIT_VERTEX_MODE, KeyContext::MeshEdit, "1"),
+    (actions::MESH_EDIT_EDGE_MODE, KeyContext::MeshEdit, "2"),
+    (actions::MESH_EDIT_FACE_MODE, KeyContext::MeshEdit, "3"),
+    (actions::MESH_EDIT_SELECT_ALL, KeyContext::MeshEdit, "A"),
+    (actions::MESH_EDIT_EXTRUDE, KeyContext::MeshEdit, "E"),
+    (actions::MESH_EDIT_INSET, KeyContext::MeshEdit, "I"),
+    (actions::SCULPT_ADJUST_RADIUS, KeyContext::Sculpt, "F"),
+    (actions::SCULPT_ADJUST_STRENGTH, KeyContext::Sculpt, "Shift+F"),
+    // Held, not pressed: holds back brush dabs while navigating
+    (actions::PAINT_SUSPEND, KeyContext::Brush, "Alt"),
+    // Hides the selected objects
+    (actions::OBJECT_HIDE, KeyContext::Object, "H"),
+    (actions::OBJECT_UNHIDE_ALL, KeyContext::Global, "Alt+H"),
+    (actions::OBJECT_ISOLATE_TOGGLE, KeyContext::Global, "Slash"),
+];
+
+/// Key names accepted in chords
+#[rustfmt::skip]
+const KEY_NAMES: &[&str] = &[
+    "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R",
+    "S", "T", "U", "V", "W", "X", "Y", "Z", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9",
+    "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "Tab", "Escape",
+    "Enter", "Space", "Backspace", "Delete", "Insert", "Home", "End", "PageUp", "PageDown",
+    "ArrowUp", "ArrowDown", "ArrowLeft", "ArrowRight", "Comma", "Period", "Slash", "Backslash",
+    "Semicolon", "Quote", "BracketLeft", "BracketRight", "Minus", "Equal", "Backquote",
+    "Numpad0", "Numpad1", "Numpad2", "Numpad3", "Numpad4", "Numpad5", "Numpad6", "Numpad7",
+    "Numpad8", "Numpad9", "Ctrl", "Shift", "Alt",
+];
+
+/// Errors from parsing chords or changing bindings
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum KeymapError {
+    #[error("Unknown keymap action {0:?}")]
+    UnknownAction(String),
+
+    #[error("Invalid key chord {0:?}")]
+    InvalidChord(String),
+
+    #[error("{chord} is already bound to {existing}")]
+    Conflict {
+        action: String,
+        chord: KeyChord,
+        existing: String,
+    },
+}
+
+/// A key plus the modifiers that must be held with it.
+///
+/// Written as `Ctrl+Shift+I`. Modifiers not listed must not be held, so
+/// `S` and `Shift+S` are different chords. A modifier on its own (`Alt`) is
+/// a key too, for actions that are held rather than pressed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyChord {
+    /// Key name from the accepted key list (`"A"`, `"Tab"`, `"F5"`, ...)
+    pub key: String,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl FromStr for KeyChord {
+    type Err = KeymapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || KeymapError::InvalidChord(s.to_string());
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key_part = parts.pop().ok_or_else(invalid)?;
+        let key = KEY_NAMES
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(key_part))
+            .ok_or_else(invalid)?;
+
+        let mut chord = KeyChord {
+            key: key.to_string(),
+            ctrl: false,
+            shift: false,
+            alt: false,
+        };
+        for modifier in parts {
+            let flag = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut chord.ctrl,
+                "shift" => &mut chord.shift,
+                "alt" => &mut chord.alt,
+                _ => return Err(invalid()),
+            };
+            if *flag {
+                return Err(invalid());
+            }
+            *flag = true;
+        }
+        Ok(chord)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = KeymapError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<KeyChord> for String {
+    fn from(chord: KeyChord) -> Self {
+        chord.to_string()
+    }
+}
+
+#[cfg(feature = "bevy")]
+impl KeyChord {
+    /// Bevy key code for the chord's key
+    pub fn key_code(&self) -> KeyCode {
+        match self.key.as_str() {
+            "A" => KeyCode::KeyA,
+            "B" => KeyCode::KeyB,
+            "C" => KeyCode::KeyC,
+            "D" => KeyCode::KeyD,
+            "E" => KeyCode::KeyE,
+            "F" => KeyCode::KeyF,
+            "G" => KeyCode::KeyG,
+            "H" => KeyCode::KeyH,
+            "I" => KeyCode::KeyI,
+            "J" => KeyCode::KeyJ,
+            "K" => KeyCode::KeyK,
+            "L" => KeyCode::KeyL,
+            "M" => KeyCode::KeyM,
+            "N" => KeyCode::KeyN,
+            "O" => KeyCode::KeyO,
+            "P" => KeyCode::KeyP,
+            "Q" => KeyCode::KeyQ,
+            "R" => KeyCode::KeyR,
+            "S" => KeyCode::KeyS,
+            "T" => KeyCode::KeyT,
+            "U" => KeyCode::KeyU,
+            "V" => KeyCode::KeyV,
+            "W" => KeyCode::KeyW,
+            "X" => KeyCode::KeyX,
+            "Y" => KeyCode::KeyY,
+            "Z" => KeyCode::KeyZ,
+            "0" => KeyCode::Digit0,
+            "1" => KeyCode::Digit1,
+            "2" => KeyCode::Digit2,
+            "3" => KeyCode::Digit3,
+            "4" => KeyCode::Digit4,
+            "5" => KeyCode::Digit5,
+            "6" => KeyCode::Digit6,
+            "7" => KeyCode::Digit7,
+            "8" => KeyCode::Digit8,
+            "9" => KeyCode::Digit9,
+            "F1" => KeyCode::F1,
+            "F2" => KeyCode::F2,
+            "F3" => KeyCode::F3,
+            "F4" => KeyCode::F4,
+            "F5" => KeyCode::F5,
+            "F6" => KeyCode::F6,
+            "F7" => KeyCode::F7,
+            "F8" => KeyCode::F8,
+            "F9" => KeyCode::F9,
+            "F10" => KeyCode::F10,
+            "F11" => KeyCode::F11,
+            "F12" => KeyCode::F12,
+            "Tab" => KeyCode::Tab,
+            "Escape" => KeyCode::Escape,
+            "Enter" => KeyCode::Enter,
+            "Space" => KeyCode::Space,
+            "Backspace" => KeyCode::Backspace,
+            "Delete" => KeyCode::Delete,
+            "Insert" => KeyCode::Insert,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            "ArrowUp" => KeyCode::ArrowUp,
+            "ArrowDown" => KeyCode::ArrowDown,
+            "ArrowLeft" => KeyCode::ArrowLeft,
+            "ArrowRight" => KeyCode::ArrowRight,
+            "Comma" => KeyCode::Comma,
+            "Period" => KeyCode::Period,
+            "Slash" => KeyCode::Slash,
+            "Backslash" => KeyCode::Backslash,
+            "Semicolon" => KeyCode::Semicolon,
+            "Quote" => KeyCode::Quote,
+            "BracketLeft" => KeyCode::BracketLeft,
+            "BracketRight" => KeyCode::BracketRight,
+            "Minus" => KeyCode::Minus,
+            "Equal" => KeyCode::Equal,
+            "Backquote" => KeyCode::Backquote,
+            "Numpad0" => KeyCode::Numpad0,
+            "Numpad1" => KeyCode::Numpad1,
+            "Numpad2" => KeyCode::Numpad2,
+            "Numpad3" => KeyCode::Numpad3,
+            "Numpad4" => KeyCode::Numpad4,
+            "Numpad5" => KeyCode::Numpad5,
+            "Numpad6" => KeyCode::Numpad6,
+            "Numpad7" => KeyCode::Numpad7,
+            "Numpad8" => KeyCode::Numpad8,
+            "Numpad9" => KeyCode::Numpad9,
+            "Ctrl" => KeyCode::ControlLeft,
+            "Shift" => KeyCode::ShiftLeft,
+            "Alt" => KeyCode::AltLeft,
+            // Parsing only accepts names from KEY_NAMES
+            other => unreachable!("unmapped key name {other}"),
+        }
+    }
+
+    /// Whether the chord's key was just pressed with exactly its modifiers held
+    pub fn just_pressed(&self, input: &ButtonInput<KeyCode>) -> bool {
+        let held = |left, right| input.pressed(left) || input.pressed(right);
+        input.just_pressed(self.key_code())
+            && held(KeyCode::ControlLeft, KeyCode::ControlRight) == self.ctrl
+            && held(KeyCode::ShiftLeft, KeyCode::ShiftRight) == self.shift
+            && held(KeyCode::AltLeft, KeyCode::AltRight) == self.alt
+    }
+
+    /// Whether the chord's key and at least its modifiers are held; either
+    /// side counts for modifier keys
+    pub fn held(&self, input: &ButtonInput<KeyCode>) -> bool {
+        let held = |left, right| input.pressed(left) || input.pressed(right);
+        let ctrl = held(KeyCode::ControlLeft, KeyCode::ControlRight);
+        let shift = held(KeyCode::ShiftLeft, KeyCode::ShiftRight);
+        let alt = held(KeyCode::AltLeft, KeyCode::AltRight);
+        let key = match self.key.as_str() {
+            "Ctrl" => ctrl,
+            "Shift" => shift,
+            "Alt" => alt,
+            _ => input.pressed(self.key_code()),
+        };
+        key && (ctrl || !self.ctrl) && (shift || !self.shift) && (alt || !self.alt)
+    }
+}
+
+/// Bindings for every action, starting from the defaults
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+pub struct Keymap {
+    bindings: BTreeMap<String, KeyChord>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = ACTIONS
+            .iter()
+            .map(|&(action, _, chord)| {
+                (
+                    action.to_string(),
+                    chord.parse().expect("valid default chord"),
+                )
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Build a keymap from the defaults plus user overrides (action -> chord).
+    ///
+    /// Overrides that name an unknown action, don't parse, or conflict with
+    /// another binding are skipped and returned as errors, leaving that
+    /// action on its default.
+    pub fn from_overrides(overrides: &BTreeMap<String, String>) -> (Self, Vec<KeymapError>) {
+        let mut keymap = Self::default();
+        let mut errors = Vec::new();
+        for (action, chord) in overrides {
+            if let Err(e) = chord.parse().and_then(|chord| keymap.bind(action, chord)) {
+                errors.push(e);
+            }
+        }
+        (keymap, errors)
+    }
+
+    /// Chord bound to an action
+    pub fn chord(&self, action: &str) -> Option<&KeyChord> {
+        self.bindings.get(action)
+    }
+
+    /// All bindings, sorted by action name
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, &KeyChord)> {
+        self.bindings
+            .iter()
+            .map(|(action, chord)| (action.as_str(), chord))
+    }
+
+    /// Bindings that differ from the defaults, for the settings file
+    pub fn overrides(&self) -> BTreeMap<String, String> {
+        let defaults = Self::default();
+        self.bindings()
+            .filter(|&(action, chord)| defaults.chord(action) != Some(chord))
+            .map(|(action, chord)| (action.to_string(), chord.to_string()))
+            .collect()
+    }
+
+    /// Rebind an action, rejecting chords already used by an action that
+    /// can be live at the same time.
+    pub fn bind(&mut self, action: &str, chord: KeyChord) -> Result<(), KeymapError> {
+        let context =
+            context_of(action).ok_or_else(|| KeymapError::UnknownAction(action.into()))?;
+
+        let conflict = self.bindings().find(|&(other, other_chord)| {
+            other != action
+                && *other_chord == chord
+                && context_of(other).is_some_and(|c| c.overlaps(context))
+        });
+        if let Some((existing, _)) = conflict {
+            return Err(KeymapError::Conflict {
+                action: action.to_string(),
+                chord,
+                existing: existing.to_string(),
+            });
+        }
+
+        self.bindings.insert(action.to_string(), chord);
+        Ok(())
+    }
+
+    /// Whether an action's chord was just pressed
+    #[cfg(feature = "bevy")]
+    pub fn just_pressed(&self, action: &str, input: &ButtonInput<KeyCode>) -> bool {
+        self.chord(action)
+            .is_some_and(|chord| chord.just_pressed(input))
+    }
+
+    /// Whether an action's key was just pressed, whatever modifiers are held.
+    /// For keys like the gizmo axes where Shift changes the meaning rather
+    /// than the action.
+    #[cfg(feature = "bevy")]
+    pub fn key_just_pressed(&self, action: &str, input: &ButtonInput<KeyCode>) -> bool {
+        self.chord(action)
+            .is_some_and(|chord| input.just_pressed(chord.key_code()))
+    }
+
+    /// Whether an action's chord is held down, for hold-to-activate actions
+    #[cfg(feature = "bevy")]
+    pub fn held(&self, action: &str, input: &ButtonInput<KeyCode>) -> bool {
+        self.chord(action).is_some_and(|chord| chord.held(input))
+    }
+}
+
+fn context_of(action: &str) -> Option<KeyContext> {
+    ACTIONS
+        .iter()
+        .find(|&&(name, _, _)| name == action)
+        .map(|&(_, context, _)| context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chord_roundtrip() {
+        let chord: KeyChord = "ctrl+shift+i".parse().unwrap();
+        assert!(chord.ctrl && chord.shift && !chord.alt);
+        assert_eq!(chord.key, "I");
+        assert_eq!(chord.to_string(), "Ctrl+Shift+I");
+
+        assert!("Ctrl+".parse::<KeyChord>().is_err());
+        assert!("Hyper+A".parse::<KeyChord>().is_err());
+        assert!("Shift+Shift+A".parse::<KeyChord>().is_err());
+
+        // A lone modifier is a key for held actions
+        let alt: KeyChord = "alt".parse().unwrap();
+        assert!(!alt.alt);
+        assert_eq!(alt.to_string(), "Alt");
+        assert_eq!(
+            "Ctrl+Alt".parse::<KeyChord>().unwrap().to_string(),
+            "Ctrl+Alt"
+        );
+    }
+
+    #[test]
+    fn test_defaults_have_no_conflicts() {
+        let defaults = Keymap::default();
+        let mut rebuilt = defaults.clone();
+        for (action, chord) in defaults.bindings() {
+            rebuilt.bind(action, chord.clone()).unwrap();
+        }
+        assert!(defaults.overrides().is_empty());
+    }
+
+    #[test]
+    fn test_conflicts_are_context_scoped() {
+        let mut keymap = Keymap::default();
+        let r: KeyChord = "R".parse().unwrap();
+
+        // Rotate is already R, and the add menu is live everywhere
+        assert!(matches!(
+            keymap.bind(actions::GIZMO_TRANSLATE, r.clone()),
+            Err(KeymapError::Conflict { existing, .. }) if existing == actions::GIZMO_ROTATE
+        ));
+        assert!(
+            keymap
+                .bind(actions::SCULPT_ADJUST_RADIUS, "Shift+A".parse().unwrap())
+                .is_err()
+        );
+
+        // Axis keys only fire during a transform, and mesh edit and sculpt
+        // mode never overlap
+        keymap
+            .bind(actions::GIZMO_SCALE, "X".parse().unwrap())
+            .unwrap();
+        keymap
+            .bind(actions::SCULPT_ADJUST_RADIUS, "1".parse().unwrap())
+            .unwrap();
+        assert!(
+            keymap
+                .bind(actions::MESH_EDIT_SELECT_ALL, "Y".parse().unwrap())
+                .is_err()
+        );
+        assert!(
+            keymap
+                .bind("gizmo.unknown", r)
+                .is_err_and(|e| e == KeymapError::UnknownAction("gizmo.unknown".into()))
+        );
+    }
+
+    #[test]
+    fn test_overrides() {
+        let overrides = BTreeMap::from([
+            (actions::GIZMO_TRANSLATE.to_string(), "T".to_string()),
+            (actions::GIZMO_SCALE.to_string(), "R".to_string()),
+            ("nope".to_string(), "Q".to_string()),
+        ]);
+        let (keymap, errors) = Keymap::from_overrides(&overrides);
+
+        assert_eq!(
+            keymap.chord(actions::GIZMO_TRANSLATE).unwrap().to_string(),
+            "T"
+        );
+        assert_eq!(keymap.chord(actions::GIZMO_SCALE).unwrap().to_string(), "S");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            keymap.overrides(),
+            BTreeMap::from([(actions::GIZMO_TRANSLATE.to_string(), "T".to_string())])
+        );
+    }
+}
@@ -2,10 +2,23 @@
 //!
 //! This crate provides the single source of truth for window dimensions,
 //! display settings, and other configuration shared across all build modes
-//! (native Bevy, Tauri/WASM).
+//! (native Bevy, Tauri/WASM), plus the user's hotkey [`Keymap`] and the
+//! autosave file layout.
 
 use serde::{Deserialize, Serialize};
 
+mod autosave;
+mod keymap;
+mod settings_file;
+
+pub use autosave::{
+    AUTOSAVE_DIR_NAME, AutosaveSession, DEFAULT_AUTOSAVE_INTERVAL_SECS, KEPT_SESSIONS,
+    RecoveryCandidate, SNAPSHOT_SLOTS, STROKE_JOURNAL_NAME, default_autosave_root, find_recovery,
+    latest_snapshot, prune_sessions, write_atomic,
+};
+pub use keymap::{KeyChord, KeyContext, Keymap, KeymapError, actions};
+pub use settings_file::{SettingsError, SettingsFile, WindowGeometry};
+
 #[cfg(feature = "bevy")]
 use bevy::prelude::Resource;
 
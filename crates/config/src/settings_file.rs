@@ -0,0 +1,225 @@
+//! Persistent user settings file
+//!
+//! Stores state that should survive restarts (window geometry, keymap,
+//! autosave interval, subdivision face budget) as TOML in the platform config
+//! directory, so it can be edited by hand. Missing or unreadable files fall
+//! back to defaults so a corrupt settings file can never prevent startup.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// File name of the settings file inside the config directory
+pub const SETTINGS_FILE_NAME: &str = "settings.toml";
+
+/// Environment variable that overrides the config directory
+pub const CONFIG_DIR_ENV: &str = "PENTIMENTO_CONFIG_DIR";
+
+/// Errors that can occur while reading or writing the settings file
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("No config directory available (set {CONFIG_DIR_ENV} or HOME)")]
+    NoConfigDir,
+
+    #[error("Settings file I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Settings file is malformed: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Settings could not be serialized: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Last known window geometry, in physical pixels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// Window width in physical pixels (size before maximizing)
+    pub width: u32,
+    /// Window height in physical pixels (size before maximizing)
+    pub height: u32,
+    /// Top-left window position in desktop coordinates
+    pub position: Option<[i32; 2]>,
+    /// Whether the window was maximized
+    pub maximized: bool,
+    /// Name of the monitor the window was on
+    pub monitor: Option<String>,
+}
+
+impl WindowGeometry {
+    /// Minimum number of pixels of the window that must overlap a monitor
+    /// for the saved position to be considered reachable.
+    pub const MIN_VISIBLE_PX: i32 = 64;
+
+    /// Check whether the saved position places enough of the window on a
+    /// monitor with the given physical position and size to be grabbed.
+    pub fn is_visible_on(&self, monitor_position: [i32; 2], monitor_size: [u32; 2]) -> bool {
+        let Some([x, y]) = self.position else {
+            return false;
+        };
+
+        let min_x = monitor_position[0];
+        let min_y = monitor_position[1];
+        let max_x = min_x + monitor_size[0] as i32;
+        let max_y = min_y + monitor_size[1] as i32;
+
+        // The title bar (top edge) must land on the monitor, otherwise the
+        // user has nothing to drag the window back with.
+        let overlap_x = (x + self.width as i32).min(max_x) - x.max(min_x);
+        overlap_x >= Self::MIN_VISIBLE_PX.min(self.width as i32) && y >= min_y && y < max_y
+    }
+}
+
+/// Contents of the persistent settings file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsFile {
+    /// Last window geometry (None until the window has been moved or resized)
+    pub window: Option<WindowGeometry>,
+    /// Hotkey overrides, action name -> chord (e.g. `"gizmo.translate" = "T"`
+    /// under `[keymap]`).
+    /// Actions not listed keep their default binding; see [`crate::Keymap`].
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub keymap: BTreeMap<String, String>,
+    /// Seconds between autosaves (None uses the 5 minute default, 0 disables autosave)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autosave_interval_secs: Option<u64>,
+    /// Most faces a subdivision preview may produce (None uses the scene default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subdivision_face_budget: Option<usize>,
+    /// Megabytes canvas paint history may hold before old strokes are
+    /// checkpointed (None uses the scene default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paint_history_budget_mb: Option<u64>,
+}
+
+impl SettingsFile {
+    /// Resolve the settings file path.
+    ///
+    /// Uses `$PENTIMENTO_CONFIG_DIR`, then `$XDG_CONFIG_HOME/pentimento`,
+    /// then `$HOME/.config/pentimento` (`%APPDATA%\pentimento` on Windows).
+    pub fn default_path() -> Result<PathBuf, SettingsError> {
+        if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV) {
+            return Ok(PathBuf::from(dir).join(SETTINGS_FILE_NAME));
+        }
+
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .ok_or(SettingsError::NoConfigDir)?;
+
+        Ok(base.join("pentimento").join(SETTINGS_FILE_NAME))
+    }
+
+    /// Load settings from the default path, falling back to defaults on any error
+    pub fn load_or_default() -> Self {
+        Self::default_path()
+            .and_then(|path| Self::load_from(&path))
+            .unwrap_or_default()
+    }
+
+    /// Load settings from a specific path.
+    ///
+    /// A missing file is not an error and yields the default settings.
+    pub fn load_from(path: &Path) -> Result<Self, SettingsError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(toml::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save settings to the default path
+    pub fn save(&self) -> Result<(), SettingsError> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save settings to a specific path, creating parent directories as needed.
+    ///
+    /// Writes to a temporary file first and renames it into place so a crash
+    /// mid-write never leaves a truncated settings file behind.
+    pub fn save_to(&self, path: &Path) -> Result<(), SettingsError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = path.with_extension("toml.tmp");
+        std::fs::write(&tmp_path, toml::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geometry(position: Option<[i32; 2]>) -> WindowGeometry {
+        WindowGeometry {
+            width: 1280,
+            height: 720,
+            position,
+            maximized: false,
+            monitor: Some("DP-1".into()),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("pentimento-settings-{}", std::process::id()));
+        let path = dir.join(SETTINGS_FILE_NAME);
+
+        let settings = SettingsFile {
+            window: Some(geometry(Some([100, 200]))),
+            keymap: BTreeMap::from([("gizmo.translate".into(), "T".into())]),
+            autosave_interval_secs: Some(60),
+            subdivision_face_budget: Some(250_000),
+            paint_history_budget_mb: Some(512),
+        };
+        settings.save_to(&path).unwrap();
+        assert_eq!(SettingsFile::load_from(&path).unwrap(), settings);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_hand_edited_keymap() {
+        let settings: SettingsFile = toml::from_str(
+            r#"
+            autosave_interval_secs = 120
+
+            [keymap]
+            "gizmo.translate" = "T"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(settings.keymap["gizmo.translate"], "T");
+        assert_eq!(settings.autosave_interval_secs, Some(120));
+        assert_eq!(settings.window, None);
+    }
+
+    #[test]
+    fn test_missing_file_is_default() {
+        let path = std::env::temp_dir().join("pentimento-settings-does-not-exist.toml");
+        assert_eq!(
+            SettingsFile::load_from(&path).unwrap(),
+            SettingsFile::default()
+        );
+    }
+
+    #[test]
+    fn test_visible_on_monitor() {
+        let monitor = ([0, 0], [1920, 1080]);
+        assert!(geometry(Some([100, 100])).is_visible_on(monitor.0, monitor.1));
+        // Partially off the right edge but title bar still reachable
+        assert!(geometry(Some([1800, 100])).is_visible_on(monitor.0, monitor.1));
+        // Entirely on a monitor that no longer exists
+        assert!(!geometry(Some([2500, 100])).is_visible_on(monitor.0, monitor.1));
+        // Title bar above the top edge
+        assert!(!geometry(Some([100, -50])).is_visible_on(monitor.0, monitor.1));
+        // No saved position
+        assert!(!geometry(None).is_visible_on(monitor.0, monitor.1));
+    }
+}
@@ -12,7 +12,9 @@ pub use remote::RemoteDiffusion;
 #[cfg(feature = "local")]
 pub use local::LocalDiffusion;
 
-use pentimento_ipc::DiffusionRequest;
+use std::sync::Arc;
+
+use pentimento_ipc::{BlobKind, DiffusionRequest};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -33,6 +35,19 @@ pub enum DiffusionError {
 /// Progress callback type
 pub type ProgressCallback = Box<dyn Fn(f32, Option<&image::RgbaImage>) + Send + Sync>;
 
+/// A preview frame from a `ProgressCallback` as a UI binary blob
+///
+/// Sent through the blob side channel (`BevyToUi::BinaryBlob`) instead of
+/// base64 in JSON; the pixels are copied once out of the callback's image.
+pub fn preview_blob(task_id: &str, preview: &image::RgbaImage) -> (BlobKind, Arc<[u8]>) {
+    let kind = BlobKind::DiffusionPreview {
+        task_id: task_id.to_string(),
+        width: preview.width(),
+        height: preview.height(),
+    };
+    (kind, Arc::from(preview.as_raw().as_slice()))
+}
+
 /// Trait for diffusion backends
 #[allow(async_fn_in_trait)]
 pub trait DiffusionBackend {
@@ -2,10 +2,13 @@
 
 use dioxus::prelude::*;
 
-use pentimento_ipc::{BevyToUi, EditMode, LayerInfo};
+use pentimento_ipc::{BevyToUi, CloseDecision, EditMode, LayerInfo};
 
 use crate::bridge::DioxusBridge;
-use crate::components::{AddObjectMenu, PaintSidePanel, PaintToolbar, SidePanel, Toolbar};
+use crate::components::{
+    AddObjectMenu, AssetPreview, PaintSidePanel, PaintToolbar, SidePanel, Toolbar,
+    asset_preview_url,
+};
 use crate::state::RenderStats;
 
 const APP_CSS: &str = r#"
@@ -97,7 +100,7 @@ pub fn PentimentoApp(props: PentimentoAppProps) -> Element {
     tracing::info!("PentimentoApp component rendering");
 
     // Reactive state
-    let render_stats = use_signal(|| RenderStats::default());
+    let mut render_stats = use_signal(|| RenderStats::default());
     let selected_objects = use_signal(|| Vec::<String>::new());
 
     // Add object menu state - uses Dioxus signals for reactivity
@@ -117,6 +120,9 @@ pub fn PentimentoApp(props: PentimentoAppProps) -> Element {
     // Layer state
     let mut paint_layers = use_signal(|| Vec::<LayerInfo>::new());
 
+    // Image loading check, enabled by PENTIMENTO_ASSET_PREVIEW
+    let asset_preview = use_hook(asset_preview_url);
+
     // Sync edit mode from shared state (updated immediately by bridge handle)
     // This is more reliable than depending solely on channel messages
     {
@@ -155,6 +161,22 @@ pub fn PentimentoApp(props: PentimentoAppProps) -> Element {
             BevyToUi::LayerStateChanged { layers } => {
                 paint_layers.set(layers);
             }
+            BevyToUi::CloseRequested => {
+                // Nothing in the Dioxus UI holds unsaved state of its own
+                props.bridge.respond_to_close(CloseDecision::Proceed);
+            }
+            BevyToUi::RenderStats {
+                fps,
+                frame_time_ms,
+                render_scale,
+                ..
+            } => {
+                render_stats.set(RenderStats {
+                    fps,
+                    frame_time: frame_time_ms,
+                    render_scale,
+                });
+            }
             _ => {
                 // Other messages not yet handled
             }
@@ -256,13 +278,13 @@ pub fn PentimentoApp(props: PentimentoAppProps) -> Element {
                 }
                 // Paint toolbar - position: absolute within main-content
                 PaintToolbar {
-                    visible: edit_mode() == EditMode::Paint,
+                    visible: matches!(edit_mode(), EditMode::Paint | EditMode::MeshPaint),
                     bridge: props.bridge.clone()
                 }
                 // Spacer fills remaining space, pushes side panel to right
                 div { class: "content-spacer" }
                 // Side panel in normal flow (no position:absolute)
-                if edit_mode() == EditMode::Paint {
+                if matches!(edit_mode(), EditMode::Paint | EditMode::MeshPaint) {
                     PaintSidePanel {
                         bridge: props.bridge.clone(),
                         layers: paint_layers(),
@@ -273,6 +295,9 @@ pub fn PentimentoApp(props: PentimentoAppProps) -> Element {
                         bridge: props.bridge.clone(),
                     }
                 }
+                if let Some(url) = asset_preview.clone() {
+                    AssetPreview { remote_url: url }
+                }
             }
         }
     }
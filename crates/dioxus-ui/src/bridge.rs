@@ -4,8 +4,13 @@
 
 use pentimento_ipc::{
     AddObjectRequest, AddPaintCanvasRequest, AmbientOcclusionSettings, BevyToUi, BlendMode,
-    CameraCommand, DiffusionRequest, EditMode, LightingSettings, MaterialCommand, MeshEditCommand,
-    MeshEditTool, MeshSelectionMode, ObjectCommand, PaintCommand, PrimitiveType, UiToBevy,
+    BlobKind, BrushTipSource, CameraCommand, CanvasAnchor, CanvasOrientation, CanvasTool,
+    CloseDecision, DiffusionRequest, EditMode, GizmoCommand, GradientKind, KeyBinding,
+    LightCommand, LightInfo, LightType, LightingSettings, MaterialCommand, MeshEditCommand,
+    MeshEditTool, MeshPaintChannel, MeshPaintTarget, MeshSelectionMode, ObjectCommand,
+    OrbitOptions, PaintCommand, PivotPoint, PixelSelectionMode, PrimitiveType, ProjectionOptions,
+    ReferenceImageMode, SculptCommand, SculptDetailMode, SnapTarget, TipRotationMode, UiToBevy,
+    ViewMode, WireframeInfo, WireframeTarget,
 };
 use std::sync::{
     Arc, Mutex,
@@ -13,6 +18,16 @@ use std::sync::{
     mpsc,
 };
 
+/// Latest preview frame of a running diffusion task
+#[derive(Clone, PartialEq)]
+pub struct DiffusionPreviewImage {
+    pub task_id: String,
+    pub width: u32,
+    pub height: u32,
+    /// Row-major RGBA8 pixels, shared with Bevy's blob registry (not copied)
+    pub rgba: Arc<[u8]>,
+}
+
 /// Shared state that persists between renders.
 /// Used for state that needs to be set from Bevy and read by the component.
 #[derive(Clone, PartialEq)]
@@ -30,8 +45,39 @@ pub struct SharedUiState {
     pub selected_vertex_count: usize,
     pub selected_edge_count: usize,
     pub selected_face_count: usize,
-    /// Depth view mode
-    pub depth_view_enabled: bool,
+    /// Viewport debug view
+    pub view_mode: ViewMode,
+    /// Gizmo snap target, as last reported by Bevy
+    pub gizmo_snap: SnapTarget,
+    /// Gizmo rotation/scale pivot, as last reported by Bevy
+    pub gizmo_pivot: PivotPoint,
+    /// Whether a gizmo transform operation is in progress
+    pub gizmo_active: bool,
+    /// Sculpt dynamic topology settings (reported on entering sculpt mode)
+    pub sculpt_dynamic_topology: bool,
+    pub sculpt_detail_mode: SculptDetailMode,
+    pub sculpt_detail_size: f32,
+    pub sculpt_max_vertices: Option<u32>,
+    /// Progress of the running sculpt remesh (None = not remeshing)
+    pub sculpt_remesh_progress: Option<f32>,
+    /// Last sculpt mesh health report: (issues found, issues repaired)
+    pub sculpt_mesh_health: Option<(usize, u32)>,
+    /// Hotkey bindings, as last reported by Bevy
+    pub keymap: Vec<KeyBinding>,
+    /// Scene lights, as last reported by Bevy
+    pub lights: Vec<LightInfo>,
+    /// Global wireframe overlay, as last reported by Bevy (None = unavailable)
+    pub wireframe: Option<WireframeInfo>,
+    /// Autosave offered for recovery at startup: (path, Unix ms timestamp)
+    pub recovery_available: Option<(String, u64)>,
+    /// Path of the last finished canvas export
+    pub last_canvas_export: Option<String>,
+    /// Progress of the running turntable render: (frame, total) (None = not rendering)
+    pub render_progress: Option<(u32, u32)>,
+    /// Output path of the last finished turntable render
+    pub last_render: Option<String>,
+    /// Latest diffusion preview frame
+    pub diffusion_preview: Option<DiffusionPreviewImage>,
 }
 
 impl Default for SharedUiState {
@@ -46,7 +92,24 @@ impl Default for SharedUiState {
             selected_vertex_count: 0,
             selected_edge_count: 0,
             selected_face_count: 0,
-            depth_view_enabled: false,
+            view_mode: ViewMode::Shaded,
+            gizmo_snap: SnapTarget::None,
+            gizmo_pivot: PivotPoint::Median,
+            gizmo_active: false,
+            sculpt_dynamic_topology: true,
+            sculpt_detail_mode: SculptDetailMode::ScreenSpace,
+            sculpt_detail_size: 6.0,
+            sculpt_max_vertices: None,
+            sculpt_remesh_progress: None,
+            sculpt_mesh_health: None,
+            keymap: Vec::new(),
+            lights: Vec::new(),
+            wireframe: None,
+            recovery_available: None,
+            last_canvas_export: None,
+            render_progress: None,
+            last_render: None,
+            diffusion_preview: None,
         }
     }
 }
@@ -172,6 +235,12 @@ impl DioxusBridge {
         self.send(UiToBevy::CameraCommand(CameraCommand::Zoom { delta }));
     }
 
+    pub fn set_orbit_options(&self, options: OrbitOptions) {
+        self.send(UiToBevy::CameraCommand(CameraCommand::SetOrbitOptions(
+            options,
+        )));
+    }
+
     // ========================================================================
     // Object commands
     // ========================================================================
@@ -180,14 +249,72 @@ impl DioxusBridge {
         self.send(UiToBevy::ObjectCommand(ObjectCommand::Select { ids }));
     }
 
-    pub fn delete_objects(&self, ids: Vec<String>) {
-        self.send(UiToBevy::ObjectCommand(ObjectCommand::Delete { ids }));
+    /// Delete objects; with `recursive`, their children too
+    pub fn delete_objects(&self, ids: Vec<String>, recursive: bool) {
+        self.send(UiToBevy::ObjectCommand(ObjectCommand::Delete {
+            ids,
+            recursive,
+        }));
+    }
+
+    /// Parent an object to another, or to the scene root with `None`
+    pub fn set_object_parent(&self, id: String, parent_id: Option<String>) {
+        self.send(UiToBevy::ObjectCommand(ObjectCommand::SetParent {
+            id,
+            parent_id,
+        }));
+    }
+
+    /// Group objects under a new, empty transform node
+    pub fn group_objects(&self, ids: Vec<String>, name: String) {
+        self.send(UiToBevy::ObjectCommand(ObjectCommand::Group { ids, name }));
+    }
+
+    /// Preview `levels` (0-4) of smooth subdivision; 0 shows the base mesh
+    pub fn set_object_subdivision(&self, id: String, levels: u8) {
+        self.send(UiToBevy::ObjectCommand(ObjectCommand::SetSubdivision {
+            id,
+            levels,
+        }));
+    }
+
+    /// Give an instanced object its own copies of its shared mesh and material
+    pub fn make_object_unique(&self, id: String) {
+        self.send(UiToBevy::ObjectCommand(ObjectCommand::MakeUnique { id }));
     }
 
     pub fn duplicate_objects(&self, ids: Vec<String>) {
         self.send(UiToBevy::ObjectCommand(ObjectCommand::Duplicate { ids }));
     }
 
+    pub fn set_object_visibility(&self, id: String, visible: bool) {
+        self.send(UiToBevy::ObjectCommand(ObjectCommand::SetVisibility {
+            id,
+            visible,
+        }));
+    }
+
+    /// Hide every object but `ids`; empty `ids` ends isolation
+    pub fn isolate_objects(&self, ids: Vec<String>) {
+        self.send(UiToBevy::ObjectCommand(ObjectCommand::Isolate { ids }));
+    }
+
+    pub fn set_object_locked(&self, id: String, locked: bool) {
+        self.send(UiToBevy::ObjectCommand(ObjectCommand::SetLocked {
+            id,
+            locked,
+        }));
+    }
+
+    /// Load a reference image (Bevy answers with `ObjectAdded`)
+    pub fn add_reference_image(&self, path: String, mode: ReferenceImageMode) {
+        self.send(UiToBevy::AddReferenceImage { path, mode });
+    }
+
+    pub fn set_reference_image_opacity(&self, id: String, opacity: f32) {
+        self.send(UiToBevy::SetReferenceImageOpacity { id, opacity });
+    }
+
     // ========================================================================
     // Material commands
     // ========================================================================
@@ -217,6 +344,11 @@ impl DioxusBridge {
         self.send(UiToBevy::CancelDiffusion { task_id });
     }
 
+    /// Free a registered texture; material slots using it are cleared
+    pub fn delete_texture(&self, texture_id: String) {
+        self.send(UiToBevy::DeleteTexture { texture_id });
+    }
+
     // ========================================================================
     // Settings commands
     // ========================================================================
@@ -225,17 +357,127 @@ impl DioxusBridge {
         self.send(UiToBevy::UpdateLighting(settings));
     }
 
+    /// Add a light (it becomes the selection)
+    pub fn add_light(&self, light_type: LightType, position: [f32; 3]) {
+        self.send(UiToBevy::LightCommand(LightCommand::Add {
+            light_type,
+            position,
+        }));
+    }
+
+    /// Change light properties; `None` fields are left as they are
+    pub fn update_light(
+        &self,
+        id: String,
+        color: Option<[f32; 3]>,
+        intensity: Option<f32>,
+        range: Option<f32>,
+        angles: Option<[f32; 2]>,
+    ) {
+        self.send(UiToBevy::LightCommand(LightCommand::Update {
+            id,
+            color,
+            intensity,
+            range,
+            angles,
+        }));
+    }
+
+    pub fn delete_light(&self, id: String) {
+        self.send(UiToBevy::LightCommand(LightCommand::Delete { id }));
+    }
+
+    pub fn set_light_shadows(&self, id: String, enabled: bool) {
+        self.send(UiToBevy::LightCommand(LightCommand::SetShadows {
+            id,
+            enabled,
+        }));
+    }
+
     pub fn update_ambient_occlusion(&self, settings: AmbientOcclusionSettings) {
         self.send(UiToBevy::UpdateAmbientOcclusion(settings));
     }
 
-    /// Toggle depth view mode on/off
-    pub fn set_depth_view(&self, enabled: bool) {
+    /// Set what translated objects snap to; Bevy answers with `GizmoStatus`
+    pub fn set_snap_target(&self, mode: SnapTarget) {
+        self.send(UiToBevy::GizmoCommand(GizmoCommand::SetSnapTarget { mode }));
+    }
+
+    /// Set what rotation and scaling pivot around; Bevy answers with `GizmoStatus`
+    pub fn set_pivot(&self, pivot: PivotPoint) {
+        self.send(UiToBevy::GizmoCommand(GizmoCommand::SetPivot { pivot }));
+    }
+
+    /// Switch the viewport between shaded rendering and a debug view
+    pub fn set_view_mode(&self, mode: ViewMode) {
+        {
+            let mut state = self.shared_state.lock().unwrap();
+            state.view_mode = mode;
+        }
+        self.send(UiToBevy::SetViewMode { mode });
+    }
+
+    /// Show or hide the wireframe overlay for the scene or one object; the
+    /// result comes back in `SceneUpdated`
+    pub fn set_wireframe(
+        &self,
+        target: WireframeTarget,
+        enabled: bool,
+        color: Option<[f32; 4]>,
+        depth_test: bool,
+    ) {
+        self.send(UiToBevy::SetWireframe {
+            target,
+            enabled,
+            color,
+            depth_test,
+        });
+    }
+
+    /// Rebind a hotkey action to a chord like "Shift+A"
+    pub fn set_keybinding(&self, action: String, chord: String) {
+        self.send(UiToBevy::SetKeybinding { action, chord });
+    }
+
+    /// Restore the autosave offered by `RecoveryAvailable`
+    pub fn restore_autosave(&self, path: String) {
+        self.send(UiToBevy::RestoreAutosave { path });
+    }
+
+    /// Answer Bevy's `CloseRequested`
+    pub fn respond_to_close(&self, decision: CloseDecision) {
+        self.send(UiToBevy::CloseResponse { decision });
+    }
+
+    /// Render a 360° turntable to a PNG sequence directory or an MP4 file
+    pub fn render_turntable(
+        &self,
+        frames: u32,
+        seconds: f32,
+        width: u32,
+        height: u32,
+        path: String,
+    ) {
+        {
+            let mut state = self.shared_state.lock().unwrap();
+            state.render_progress = Some((0, frames));
+        }
+        self.send(UiToBevy::RenderTurntable {
+            frames,
+            seconds,
+            width,
+            height,
+            path,
+        });
+    }
+
+    /// Cancel the running turntable render
+    pub fn cancel_render(&self) {
         {
             let mut state = self.shared_state.lock().unwrap();
-            state.depth_view_enabled = enabled;
+            state.render_progress = None;
         }
-        self.send(UiToBevy::SetDepthView { enabled });
+        self.send(UiToBevy::CancelRender);
     }
 
     // ========================================================================
@@ -252,6 +494,7 @@ impl DioxusBridge {
             primitive_type,
             position,
             name,
+            subdivisions: None,
         }));
     }
 
@@ -261,9 +504,24 @@ impl DioxusBridge {
 
     /// Add a paint canvas in front of the camera and enter paint mode
     pub fn add_paint_canvas(&self, width: Option<u32>, height: Option<u32>) {
-        self.send(UiToBevy::AddPaintCanvas(AddPaintCanvasRequest {
+        self.add_placed_paint_canvas(AddPaintCanvasRequest {
             width,
             height,
+            world_size: None,
+            distance: None,
+            orientation: CanvasOrientation::FacingCamera,
+        });
+    }
+
+    /// Add a paint canvas with its size, distance and orientation set
+    pub fn add_placed_paint_canvas(&self, request: AddPaintCanvasRequest) {
+        self.send(UiToBevy::AddPaintCanvas(request));
+    }
+
+    /// Move a canvas (the active one without `plane_id`) back in front of the camera
+    pub fn reanchor_canvas(&self, plane_id: Option<u32>) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::ReanchorCanvas {
+            plane_id,
         }));
     }
 
@@ -297,6 +555,23 @@ impl DioxusBridge {
         }));
     }
 
+    /// Set brush flow (0.0-1.0)
+    pub fn set_brush_flow(&self, flow: f32) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::SetBrushFlow { flow }));
+    }
+
+    /// Use a stamp brush tip, or `BrushTipSource::Round` for round dabs
+    pub fn set_brush_tip(&self, source: BrushTipSource) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::SetBrushTip { source }));
+    }
+
+    /// Set how the stamp brush tip rotates per dab
+    pub fn set_brush_tip_rotation(&self, mode: TipRotationMode) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::SetBrushTipRotation {
+            mode,
+        }));
+    }
+
     /// Set brush hardness (0.0-1.0)
     pub fn set_brush_hardness(&self, hardness: f32) {
         self.send(UiToBevy::PaintCommand(PaintCommand::SetBrushHardness {
@@ -309,6 +584,34 @@ impl DioxusBridge {
         self.send(UiToBevy::PaintCommand(PaintCommand::SetBlendMode { mode }));
     }
 
+    /// Paint meshes into a material channel
+    pub fn set_mesh_paint_channel(&self, channel: MeshPaintChannel) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::SetMeshPaintChannel {
+            channel,
+        }));
+    }
+
+    /// Paint meshes into their textures or their vertex colors
+    pub fn set_mesh_paint_target(&self, target: MeshPaintTarget) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::SetMeshPaintTarget {
+            target,
+        }));
+    }
+
+    /// Set the value roughness and metallic strokes paint (0.0-1.0)
+    pub fn set_mesh_paint_value(&self, value: f32) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::SetMeshPaintValue {
+            value,
+        }));
+    }
+
+    /// Set how far normal strokes tilt the surface (0.0-1.0)
+    pub fn set_normal_strength(&self, strength: f32) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::SetNormalStrength {
+            strength,
+        }));
+    }
+
     /// Undo last paint stroke
     pub fn paint_undo(&self) {
         self.send(UiToBevy::PaintCommand(PaintCommand::Undo));
@@ -322,8 +625,22 @@ impl DioxusBridge {
     }
 
     /// Project current canvas contents to all visible meshes (one-shot)
-    pub fn project_to_scene(&self) {
-        self.send(UiToBevy::PaintCommand(PaintCommand::ProjectToScene));
+    pub fn project_to_scene(&self, options: ProjectionOptions) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::ProjectToScene {
+            options,
+        }));
+    }
+
+    /// Project current canvas contents to the selected meshes only (one-shot)
+    pub fn project_to_selection(&self, options: ProjectionOptions) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::ProjectToSelection {
+            options,
+        }));
+    }
+
+    /// Redo the last projection onto the meshes where they are now
+    pub fn reproject_last(&self) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::ReprojectLast));
     }
 
     // ========================================================================
@@ -381,6 +698,92 @@ impl DioxusBridge {
         }));
     }
 
+    /// Export the active canvas as PNG (default path when `None`)
+    pub fn export_canvas(&self, path: Option<String>) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::ExportCanvas { path }));
+    }
+
+    /// Replace the active canvas layer with an image
+    pub fn import_canvas(&self, path: String) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::ImportCanvas { path }));
+    }
+
+    /// Resize the active canvas, keeping content pinned to `anchor`
+    pub fn resize_canvas(&self, width: u32, height: u32, anchor: CanvasAnchor) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::ResizeCanvas {
+            width,
+            height,
+            anchor,
+        }));
+    }
+
+    /// Crop the active canvas to a pixel rectangle
+    pub fn crop_canvas(&self, x: u32, y: u32, width: u32, height: u32) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::CropCanvas {
+            x,
+            y,
+            width,
+            height,
+        }));
+    }
+
+    /// Arm the pixel selection tool (the next canvas drag lifts pixels)
+    pub fn begin_pixel_selection(&self, mode: PixelSelectionMode) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::BeginPixelSelection {
+            mode,
+        }));
+    }
+
+    /// Move, rotate, and scale the floating selection
+    pub fn transform_selection(&self, translate: [f32; 2], rotate_deg: f32, scale: [f32; 2]) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::TransformSelection {
+            translate,
+            rotate_deg,
+            scale,
+        }));
+    }
+
+    /// Resample the floating selection onto its layer
+    pub fn commit_selection(&self) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::CommitSelection));
+    }
+
+    /// Put the floating selection back where it was
+    pub fn cancel_selection(&self) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::CancelSelection));
+    }
+
+    /// Bucket fill the active layer from a canvas pixel
+    pub fn fill(&self, x: f32, y: f32, tolerance: f32, contiguous: bool) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::Fill {
+            x,
+            y,
+            tolerance,
+            contiguous,
+        }));
+    }
+
+    /// Render a gradient over the active layer
+    pub fn gradient(
+        &self,
+        start: [f32; 2],
+        end: [f32; 2],
+        kind: GradientKind,
+        colors: Vec<[f32; 4]>,
+    ) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::Gradient {
+            start,
+            end,
+            kind,
+            colors,
+        }));
+    }
+
+    /// Arm a fill or gradient click tool on the canvas (`None` for the brush)
+    pub fn arm_canvas_tool(&self, tool: Option<CanvasTool>) {
+        self.send(UiToBevy::PaintCommand(PaintCommand::ArmCanvasTool { tool }));
+    }
+
     // ========================================================================
     // Mesh edit commands
     // ========================================================================
@@ -407,6 +810,91 @@ impl DioxusBridge {
         self.send(UiToBevy::MeshEditCommand(MeshEditCommand::DeselectAll));
     }
 
+    // ========================================================================
+    // Sculpt commands
+    // ========================================================================
+
+    /// Set sculpt dab spacing as a fraction of the brush radius
+    pub fn set_sculpt_brush_spacing(&self, spacing: f32) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::SetBrushSpacing {
+            spacing,
+        }));
+    }
+
+    /// Set sculpt brush flow (0.0-1.0)
+    pub fn set_sculpt_brush_flow(&self, flow: f32) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::SetBrushFlow {
+            flow,
+        }));
+    }
+
+    /// Select a built-in sculpt brush preset by ID
+    pub fn select_sculpt_brush_preset(&self, preset_id: u32) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::SelectBrushPreset {
+            preset_id,
+        }));
+    }
+
+    /// Enable or disable sculpt dynamic topology
+    pub fn set_sculpt_dynamic_topology(&self, enabled: bool) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::SetDynamicTopology {
+            enabled,
+        }));
+    }
+
+    /// Set the sculpt detail mode
+    pub fn set_sculpt_detail_mode(&self, mode: SculptDetailMode) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::SetDetailMode(mode)));
+    }
+
+    /// Set the sculpt detail size (pixels or local units, depending on mode)
+    pub fn set_sculpt_detail_size(&self, pixels_or_world: f32) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::SetDetailSize {
+            pixels_or_world,
+        }));
+    }
+
+    /// Cap the sculpt vertex count (None = pixel coverage only)
+    pub fn set_sculpt_vertex_budget(&self, max_vertices: Option<u32>) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::SetVertexBudget {
+            max_vertices,
+        }));
+    }
+
+    /// Uniformly remesh the sculpt to a target edge length (local units)
+    pub fn remesh_sculpt(&self, target_edge_length: f32) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::Remesh {
+            target_edge_length,
+        }));
+    }
+
+    /// Cancel a running sculpt remesh
+    pub fn cancel_sculpt_remesh(&self) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::CancelRemesh));
+    }
+
+    /// Clear the sculpt mask
+    pub fn clear_sculpt_mask(&self) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::ClearMask));
+    }
+
+    /// Invert the sculpt mask
+    pub fn invert_sculpt_mask(&self) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::InvertMask));
+    }
+
+    /// Audit and repair the sculpt mesh
+    pub fn validate_sculpt_mesh(&self) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::ValidateMesh));
+    }
+
+    /// Validate the sculpt mesh every N strokes (0 = never)
+    pub fn set_sculpt_auto_validate(&self, every_strokes: u32) {
+        self.send(UiToBevy::SculptCommand(SculptCommand::SetAutoValidate {
+            every_strokes,
+        }));
+    }
+
     // ========================================================================
     // UI dirty notification
     // ========================================================================
@@ -450,6 +938,16 @@ impl DioxusBridgeHandle {
                 BevyToUi::EditModeChanged { mode } => {
                     state.edit_mode = *mode;
                 }
+                BevyToUi::GizmoStatus {
+                    active,
+                    snap,
+                    pivot,
+                    ..
+                } => {
+                    state.gizmo_active = *active;
+                    state.gizmo_snap = *snap;
+                    state.gizmo_pivot = *pivot;
+                }
                 BevyToUi::MeshEditModeChanged {
                     active,
                     selection_mode,
@@ -468,6 +966,51 @@ impl DioxusBridgeHandle {
                     state.selected_edge_count = *edge_count;
                     state.selected_face_count = *face_count;
                 }
+                BevyToUi::SculptSettingsChanged {
+                    dynamic_topology,
+                    detail_mode,
+                    detail_size,
+                    max_vertices,
+                } => {
+                    state.sculpt_dynamic_topology = *dynamic_topology;
+                    state.sculpt_detail_mode = *detail_mode;
+                    state.sculpt_detail_size = *detail_size;
+                    state.sculpt_max_vertices = *max_vertices;
+                }
+                BevyToUi::SculptRemeshProgress { progress } => {
+                    state.sculpt_remesh_progress = Some(*progress);
+                }
+                BevyToUi::SculptRemeshFinished { .. } => {
+                    state.sculpt_remesh_progress = None;
+                }
+                BevyToUi::MeshHealthReport {
+                    errors, repaired, ..
+                } => {
+                    state.sculpt_mesh_health = Some((errors.len(), *repaired));
+                }
+                BevyToUi::KeymapChanged { bindings } => {
+                    state.keymap = bindings.clone();
+                }
+                BevyToUi::Initialize { scene_info, .. } | BevyToUi::SceneUpdated(scene_info) => {
+                    state.lights = scene_info.lights.clone();
+                    state.wireframe = scene_info.wireframe;
+                }
+                BevyToUi::LightsChanged { lights } => {
+                    state.lights = lights.clone();
+                }
+                BevyToUi::RecoveryAvailable { path, timestamp } => {
+                    state.recovery_available = Some((path.clone(), *timestamp));
+                }
+                BevyToUi::CanvasExported { path } => {
+                    state.last_canvas_export = Some(path.clone());
+                }
+                BevyToUi::RenderProgress { frame, total } => {
+                    state.render_progress = Some((*frame, *total));
+                }
+                BevyToUi::RenderFinished { path } => {
+                    state.render_progress = None;
+                    state.last_render = Some(path.clone());
+                }
                 _ => {}
             }
         }
@@ -480,4 +1023,29 @@ impl DioxusBridgeHandle {
             Err(e) => tracing::error!("DioxusBridgeHandle::send() failed: {:?}", e),
         }
     }
+
+    /// Send a `BinaryBlob` announcement along with its bytes
+    ///
+    /// Webview UIs fetch blob bytes by URL; here the `Arc` is handed over
+    /// directly, so the blob needs no `BlobConsumed` acknowledgement.
+    pub fn send_blob(&self, msg: BevyToUi, bytes: Arc<[u8]>) {
+        if let BevyToUi::BinaryBlob {
+            kind:
+                BlobKind::DiffusionPreview {
+                    task_id,
+                    width,
+                    height,
+                },
+            ..
+        } = &msg
+        {
+            self.shared_state.lock().unwrap().diffusion_preview = Some(DiffusionPreviewImage {
+                task_id: task_id.clone(),
+                width: *width,
+                height: *height,
+                rgba: bytes,
+            });
+        }
+        self.send(msg);
+    }
 }
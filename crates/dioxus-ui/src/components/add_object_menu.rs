@@ -91,9 +91,11 @@ pub fn AddObjectMenu(props: AddObjectMenuProps) -> Element {
         (PrimitiveType::Sphere, "Sphere"),
         (PrimitiveType::Cylinder, "Cylinder"),
         (PrimitiveType::Plane, "Plane"),
+        (PrimitiveType::Grid { subdivisions: 10 }, "Grid"),
         (PrimitiveType::Torus, "Torus"),
         (PrimitiveType::Cone, "Cone"),
         (PrimitiveType::Capsule, "Capsule"),
+        (PrimitiveType::Monkey, "Monkey"),
     ];
 
     let on_close = props.on_close.clone();
@@ -0,0 +1,63 @@
+//! Asset preview - checks image loading through the net provider
+//!
+//! Shown when `PENTIMENTO_ASSET_PREVIEW` is set: a bundled SVG icon and a
+//! remote image (the variable's value, or the Rust logo when it is empty).
+//! Either one failing shows the placeholder instead.
+
+use dioxus::prelude::*;
+
+/// Remote image shown when `PENTIMENTO_ASSET_PREVIEW` has no URL
+const DEFAULT_REMOTE_IMAGE: &str = "https://www.rust-lang.org/logos/rust-logo-128x128.png";
+
+const ASSET_PREVIEW_CSS: &str = r#"
+.asset-preview {
+    position: absolute;
+    left: 12px;
+    bottom: 12px;
+    display: flex;
+    align-items: center;
+    gap: 12px;
+    padding: 8px 12px;
+    border-radius: 6px;
+    background: rgba(20, 20, 24, 0.85);
+    pointer-events: auto;
+}
+
+.asset-preview img {
+    width: 48px;
+    height: 48px;
+}
+
+.asset-preview-label {
+    font-size: 10px;
+    color: rgba(255, 255, 255, 0.5);
+}
+"#;
+
+/// URL to preview if `PENTIMENTO_ASSET_PREVIEW` is set
+pub fn asset_preview_url() -> Option<String> {
+    let url = std::env::var("PENTIMENTO_ASSET_PREVIEW").ok()?;
+    Some(if url.is_empty() {
+        DEFAULT_REMOTE_IMAGE.to_string()
+    } else {
+        url
+    })
+}
+
+#[derive(Props, Clone, PartialEq)]
+pub struct AssetPreviewProps {
+    pub remote_url: String,
+}
+
+#[component]
+pub fn AssetPreview(props: AssetPreviewProps) -> Element {
+    rsx! {
+        style { {ASSET_PREVIEW_CSS} }
+        div { class: "asset-preview",
+            img { src: "asset:///icons/pentimento.svg", alt: "Bundled icon" }
+            span { class: "asset-preview-label", "bundled" }
+            img { src: "{props.remote_url}", alt: "Remote image" }
+            span { class: "asset-preview-label", "remote" }
+        }
+    }
+}
@@ -103,6 +103,17 @@ const LAYERS_PANEL_CSS: &str = r#"
     white-space: nowrap;
 }
 
+.layer-name-input {
+    flex: 1;
+    min-width: 0;
+    font-size: 12px;
+    color: white;
+    background: rgba(0, 0, 0, 0.3);
+    border: 1px solid rgba(100, 150, 255, 0.6);
+    border-radius: 3px;
+    padding: 1px 4px;
+}
+
 .layer-opacity {
     font-size: 10px;
     font-family: monospace;
@@ -131,6 +142,18 @@ pub fn LayersPanel(props: LayersPanelProps) -> Element {
         bridge_add.add_layer(String::new());
     };
 
+    // Layer whose name is being edited, and the text typed so far
+    let mut renaming = use_signal(|| None::<u32>);
+    let mut draft = use_signal(String::new);
+
+    let layers_for_rename = props.layers.clone();
+    let handle_rename = move |_| {
+        if let Some(active) = layers_for_rename.iter().find(|l| l.is_active) {
+            draft.set(active.name.clone());
+            renaming.set(Some(active.id));
+        }
+    };
+
     let layers_for_delete = props.layers.clone();
     let bridge_delete = props.bridge.clone();
     let handle_delete = move |_| {
@@ -151,6 +174,12 @@ pub fn LayersPanel(props: LayersPanelProps) -> Element {
                         onclick: handle_add,
                         "+"
                     }
+                    button {
+                        class: "layer-action-btn",
+                        title: "Rename active layer",
+                        onclick: handle_rename,
+                        "R"
+                    }
                     button {
                         class: "layer-action-btn",
                         title: "Remove active layer",
@@ -175,6 +204,8 @@ pub fn LayersPanel(props: LayersPanelProps) -> Element {
 
                             let bridge_select = props.bridge.clone();
                             let bridge_vis = props.bridge.clone();
+                            let bridge_rename = props.bridge.clone();
+                            let is_renaming = renaming() == Some(id);
 
                             let row_class = if is_active {
                                 "layer-row layer-row-active"
@@ -205,7 +236,32 @@ pub fn LayersPanel(props: LayersPanelProps) -> Element {
                                         },
                                         "{vis_text}"
                                     }
-                                    span { class: "layer-name", "{name}" }
+                                    if is_renaming {
+                                        input {
+                                            class: "layer-name-input",
+                                            r#type: "text",
+                                            initial_value: "{name}",
+                                            onclick: move |evt| evt.stop_propagation(),
+                                            oninput: move |evt| draft.set(evt.value()),
+                                            onkeydown: move |evt: Event<KeyboardData>| {
+                                                // Keep typing away from the shortcuts on the app root
+                                                evt.stop_propagation();
+                                                match evt.key() {
+                                                    Key::Enter => {
+                                                        let name = draft().trim().to_string();
+                                                        if !name.is_empty() {
+                                                            bridge_rename.rename_layer(id, name);
+                                                        }
+                                                        renaming.set(None);
+                                                    }
+                                                    Key::Escape => renaming.set(None),
+                                                    _ => {}
+                                                }
+                                            },
+                                        }
+                                    } else {
+                                        span { class: "layer-name", "{name}" }
+                                    }
                                     span { class: "layer-opacity", "{(opacity * 100.0) as i32}%" }
                                 }
                             }
@@ -1,6 +1,7 @@
 //! UI Components
 
 mod add_object_menu;
+mod asset_preview;
 mod brush_palette;
 mod color_picker;
 pub(crate) mod layers_panel;
@@ -11,6 +12,7 @@ mod slider;
 mod toolbar;
 
 pub use add_object_menu::AddObjectMenu;
+pub use asset_preview::{AssetPreview, asset_preview_url};
 pub use paint_side_panel::PaintSidePanel;
 pub use paint_toolbar::PaintToolbar;
 pub use side_panel::SidePanel;
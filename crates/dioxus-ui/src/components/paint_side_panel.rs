@@ -1,6 +1,7 @@
 //! Paint side panel component - shows painting controls when in paint mode
 
 use dioxus::prelude::*;
+use pentimento_ipc::{BrushTipSource, CanvasTool, GradientKind, MeshPaintChannel, MeshPaintTarget};
 
 use crate::bridge::DioxusBridge;
 use crate::components::Slider;
@@ -117,6 +118,30 @@ const PRESET_SWATCHES: [[f32; 4]; 8] = [
 /// Maximum number of recent colors to track
 const MAX_RECENT_COLORS: usize = 8;
 
+/// Stamp tips offered in the panel ("round" is the procedural dab)
+const BRUSH_TIPS: [&str; 5] = ["round", "chalk", "bristles", "square", "splatter"];
+
+/// What a left click on the canvas does
+const CANVAS_TOOLS: [&str; 3] = ["brush", "fill", "gradient"];
+
+/// Color tolerance of the fill tool (perceptual distance)
+const FILL_TOLERANCE: f32 = 0.1;
+
+/// Material channels mesh strokes can paint, with their button labels
+const MESH_PAINT_CHANNELS: [(MeshPaintChannel, &str); 5] = [
+    (MeshPaintChannel::BaseColor, "color"),
+    (MeshPaintChannel::Roughness, "rough"),
+    (MeshPaintChannel::Metallic, "metal"),
+    (MeshPaintChannel::Normal, "normal"),
+    (MeshPaintChannel::Emissive, "emit"),
+];
+
+/// Where mesh strokes are stored, with their button labels
+const MESH_PAINT_TARGETS: [(MeshPaintTarget, &str); 2] = [
+    (MeshPaintTarget::Texture, "texture"),
+    (MeshPaintTarget::VertexColors, "vertex"),
+];
+
 /// Format a color as a CSS hex string
 fn color_to_hex(color: &[f32; 4]) -> String {
     format!(
@@ -140,8 +165,15 @@ pub fn PaintSidePanel(props: PaintSidePanelProps) -> Element {
     let mut brush_color = use_signal(|| [0.0f32, 0.0, 0.0, 1.0]); // Black default
     let mut brush_size = use_signal(|| 20.0f32);
     let mut brush_opacity = use_signal(|| 1.0f32);
+    let mut brush_flow = use_signal(|| 1.0f32);
     let mut brush_hardness = use_signal(|| 0.8f32);
     let mut active_preset_id = use_signal(|| 0u32);
+    let mut active_tip = use_signal(|| "round");
+    let mut active_tool = use_signal(|| "brush");
+    let mut mesh_target = use_signal(|| MeshPaintTarget::Texture);
+    let mut mesh_channel = use_signal(|| MeshPaintChannel::BaseColor);
+    let mut mesh_value = use_signal(|| 0.5f32);
+    let mut normal_strength = use_signal(|| 0.5f32);
 
     // Color history state
     let mut color_history = use_signal(|| Vec::<[f32; 4]>::new());
@@ -180,6 +212,8 @@ pub fn PaintSidePanel(props: PaintSidePanelProps) -> Element {
         active_preset_id.set(id);
         brush_size.set(size);
         brush_opacity.set(opacity);
+        // Built-in presets paint at full flow
+        brush_flow.set(1.0);
         brush_hardness.set(hardness);
     };
 
@@ -198,6 +232,14 @@ pub fn PaintSidePanel(props: PaintSidePanelProps) -> Element {
         bridge_opacity.set_brush_opacity(normalized);
     };
 
+    // Brush flow handler
+    let bridge_flow = props.bridge.clone();
+    let handle_flow_change = move |value: f32| {
+        let normalized = value / 100.0;
+        brush_flow.set(normalized);
+        bridge_flow.set_brush_flow(normalized);
+    };
+
     // Brush hardness handler
     let bridge_hardness = props.bridge.clone();
     let handle_hardness_change = move |value: f32| {
@@ -206,41 +248,120 @@ pub fn PaintSidePanel(props: PaintSidePanelProps) -> Element {
         bridge_hardness.set_brush_hardness(normalized);
     };
 
+    // Roughness/metallic value handler
+    let bridge_value = props.bridge.clone();
+    let handle_value_change = move |value: f32| {
+        let normalized = value / 100.0;
+        mesh_value.set(normalized);
+        bridge_value.set_mesh_paint_value(normalized);
+    };
+
+    // Normal strength handler
+    let bridge_strength = props.bridge.clone();
+    let handle_strength_change = move |value: f32| {
+        let normalized = value / 100.0;
+        normal_strength.set(normalized);
+        bridge_strength.set_normal_strength(normalized);
+    };
+
     rsx! {
         style { {PAINT_SIDE_PANEL_CSS} }
         aside { class: "paint-side-panel panel",
-            // Color section
+            // Mesh paint target and channel section
             section { class: "section",
-                h2 { class: "section-title", "Color" }
-                ColorPicker {
-                    color: brush_color(),
-                    on_change: handle_color_change
-                }
-
-                // Preset swatches
-                div { class: "swatches-label", "Swatches" }
-                div { class: "color-swatches-row",
-                    for color in PRESET_SWATCHES.iter() {
+                h2 { class: "section-title", "Mesh Target" }
+                div { class: "brush-palette-grid",
+                    for (target, label) in MESH_PAINT_TARGETS {
                         {
-                            let c = *color;
-                            let hex = color_to_hex(&c);
-                            let mut on_click = handle_swatch_click.clone();
+                            let bridge = props.bridge.clone();
+                            let class = if mesh_target() == target {
+                                "brush-preset-btn brush-preset-active"
+                            } else {
+                                "brush-preset-btn"
+                            };
                             rsx! {
                                 button {
-                                    class: "color-swatch-btn",
-                                    style: "background-color: {hex};",
-                                    onclick: move |_| on_click(c),
+                                    class: class,
+                                    onclick: move |_| {
+                                        mesh_target.set(target);
+                                        bridge.set_mesh_paint_target(target);
+                                    },
+                                    "{label}"
                                 }
                             }
                         }
                     }
                 }
+            }
 
-                // Recent colors
-                if !color_history().is_empty() {
-                    div { class: "swatches-label", "Recent" }
+            // Mesh paint channel section (vertex colors only take base color)
+            if mesh_target() == MeshPaintTarget::Texture {
+                section { class: "section",
+                    h2 { class: "section-title", "Mesh Channel" }
+                    div { class: "brush-palette-grid",
+                        for (channel, label) in MESH_PAINT_CHANNELS {
+                            {
+                                let bridge = props.bridge.clone();
+                                let class = if mesh_channel() == channel {
+                                    "brush-preset-btn brush-preset-active"
+                                } else {
+                                    "brush-preset-btn"
+                                };
+                                rsx! {
+                                    button {
+                                        class: class,
+                                        onclick: move |_| {
+                                            mesh_channel.set(channel);
+                                            bridge.set_mesh_paint_channel(channel);
+                                        },
+                                        "{label}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    if mesh_channel() == MeshPaintChannel::Normal {
+                        div { class: "property", style: "margin-top: 12px;",
+                            label { class: "property-label", "Strength" }
+                            Slider {
+                                value: normal_strength() * 100.0,
+                                min: 0.0,
+                                max: 100.0,
+                                step: 1.0,
+                                on_change: handle_strength_change
+                            }
+                            span { class: "property-value", "{(normal_strength() * 100.0) as i32}%" }
+                        }
+                    }
+                }
+            }
+
+            // Color section (a value for scalar channels)
+            section { class: "section",
+                if mesh_target() == MeshPaintTarget::Texture && mesh_channel().is_scalar() {
+                    h2 { class: "section-title", "Value" }
+                    div { class: "property",
+                        label { class: "property-label", "Value" }
+                        Slider {
+                            value: mesh_value() * 100.0,
+                            min: 0.0,
+                            max: 100.0,
+                            step: 1.0,
+                            on_change: handle_value_change
+                        }
+                        span { class: "property-value", "{mesh_value:.2}" }
+                    }
+                } else {
+                    h2 { class: "section-title", "Color" }
+                    ColorPicker {
+                        color: brush_color(),
+                        on_change: handle_color_change
+                    }
+
+                    // Preset swatches
+                    div { class: "swatches-label", "Swatches" }
                     div { class: "color-swatches-row",
-                        for color in color_history().iter() {
+                        for color in PRESET_SWATCHES.iter() {
                             {
                                 let c = *color;
                                 let hex = color_to_hex(&c);
@@ -255,6 +376,67 @@ pub fn PaintSidePanel(props: PaintSidePanelProps) -> Element {
                             }
                         }
                     }
+
+                    // Recent colors
+                    if !color_history().is_empty() {
+                        div { class: "swatches-label", "Recent" }
+                        div { class: "color-swatches-row",
+                            for color in color_history().iter() {
+                                {
+                                    let c = *color;
+                                    let hex = color_to_hex(&c);
+                                    let mut on_click = handle_swatch_click.clone();
+                                    rsx! {
+                                        button {
+                                            class: "color-swatch-btn",
+                                            style: "background-color: {hex};",
+                                            onclick: move |_| on_click(c),
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Canvas tool section
+            section { class: "section",
+                h2 { class: "section-title", "Tool" }
+                div { class: "brush-palette-grid",
+                    for tool in CANVAS_TOOLS {
+                        {
+                            let bridge = props.bridge.clone();
+                            let class = if active_tool() == tool {
+                                "brush-preset-btn brush-preset-active"
+                            } else {
+                                "brush-preset-btn"
+                            };
+                            rsx! {
+                                button {
+                                    class: class,
+                                    onclick: move |_| {
+                                        active_tool.set(tool);
+                                        // Gradients fade from the brush color to transparent
+                                        let color = brush_color();
+                                        let clear = [color[0], color[1], color[2], 0.0];
+                                        bridge.arm_canvas_tool(match tool {
+                                            "fill" => Some(CanvasTool::Fill {
+                                                tolerance: FILL_TOLERANCE,
+                                                contiguous: true,
+                                            }),
+                                            "gradient" => Some(CanvasTool::Gradient {
+                                                kind: GradientKind::Linear,
+                                                colors: vec![color, clear],
+                                            }),
+                                            _ => None,
+                                        });
+                                    },
+                                    "{tool}"
+                                }
+                            }
+                        }
+                    }
                 }
             }
 
@@ -266,6 +448,34 @@ pub fn PaintSidePanel(props: PaintSidePanelProps) -> Element {
                     active_preset_id: active_preset_id(),
                     on_select: handle_preset_select,
                 }
+
+                div { class: "swatches-label", "Tip" }
+                div { class: "brush-palette-grid",
+                    for tip in BRUSH_TIPS {
+                        {
+                            let bridge = props.bridge.clone();
+                            let class = if active_tip() == tip {
+                                "brush-preset-btn brush-preset-active"
+                            } else {
+                                "brush-preset-btn"
+                            };
+                            rsx! {
+                                button {
+                                    class: class,
+                                    onclick: move |_| {
+                                        active_tip.set(tip);
+                                        bridge.set_brush_tip(if tip == "round" {
+                                            BrushTipSource::Round
+                                        } else {
+                                            BrushTipSource::BuiltIn(tip.to_string())
+                                        });
+                                    },
+                                    "{tip}"
+                                }
+                            }
+                        }
+                    }
+                }
             }
 
             // Brush settings section
@@ -297,6 +507,18 @@ pub fn PaintSidePanel(props: PaintSidePanelProps) -> Element {
                         span { class: "property-value", "{(brush_opacity() * 100.0) as i32}%" }
                     }
 
+                    div { class: "property",
+                        label { class: "property-label", "Flow" }
+                        Slider {
+                            value: brush_flow() * 100.0,
+                            min: 0.0,
+                            max: 100.0,
+                            step: 1.0,
+                            on_change: handle_flow_change
+                        }
+                        span { class: "property-value", "{(brush_flow() * 100.0) as i32}%" }
+                    }
+
                     div { class: "property",
                         label { class: "property-label", "Hardness" }
                         Slider {
@@ -1,7 +1,7 @@
 //! Paint toolbar component - shows brush tools when in paint mode
 
 use dioxus::prelude::*;
-use pentimento_ipc::BlendMode;
+use pentimento_ipc::{BlendMode, ProjectionOptions};
 
 use crate::bridge::DioxusBridge;
 
@@ -141,7 +141,19 @@ pub fn PaintToolbar(props: PaintToolbarProps) -> Element {
     // Project to scene handler
     let bridge = props.bridge.clone();
     let handle_project_to_scene = move |_| {
-        bridge.project_to_scene();
+        bridge.project_to_scene(ProjectionOptions::default());
+    };
+
+    // Project to selection handler
+    let bridge = props.bridge.clone();
+    let handle_project_to_selection = move |_| {
+        bridge.project_to_selection(ProjectionOptions::default());
+    };
+
+    // Reproject handler
+    let bridge = props.bridge.clone();
+    let handle_reproject = move |_| {
+        bridge.reproject_last();
     };
 
     rsx! {
@@ -214,6 +226,18 @@ pub fn PaintToolbar(props: PaintToolbarProps) -> Element {
                     onclick: handle_project_to_scene,
                     "P"
                 }
+                button {
+                    class: "paint-tool",
+                    title: "Project to Selection (apply canvas paint to selected meshes)",
+                    onclick: handle_project_to_selection,
+                    "PS"
+                }
+                button {
+                    class: "paint-tool",
+                    title: "Reproject (redo the last projection onto meshes where they are now)",
+                    onclick: handle_reproject,
+                    "RP"
+                }
             }
 
             span { class: "toolbar-hint", "Tab to exit" }
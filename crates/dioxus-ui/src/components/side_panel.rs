@@ -192,6 +192,8 @@ pub fn SidePanel(props: SidePanelProps) -> Element {
     let mut moon_phase = use_signal(|| 50.0f32); // 0-100%
     let mut azimuth_angle = use_signal(|| 0.0f32); // 0-360 degrees
     let mut pollution = use_signal(|| 0.0f32); // 0-100%
+    let mut latitude = use_signal(|| 45.0f32); // -90-90 degrees
+    let mut animate_speed = use_signal(|| 0.0f32); // hours per second, 0 = paused
 
     // Ambient occlusion settings
     let mut ao_enabled = use_signal(|| false);
@@ -244,6 +246,8 @@ pub fn SidePanel(props: SidePanelProps) -> Element {
                 moon_phase: moon_phase() / 100.0,
                 azimuth_angle: azimuth_angle(),
                 pollution: pollution() / 100.0,
+                latitude: latitude(),
+                animate_speed: animate_speed(),
             });
         }
     };
@@ -275,6 +279,18 @@ pub fn SidePanel(props: SidePanelProps) -> Element {
         send_lighting();
     };
 
+    let send_lighting = send_lighting_update.clone();
+    let handle_latitude_change = move |value: f32| {
+        latitude.set(value);
+        send_lighting();
+    };
+
+    let send_lighting = send_lighting_update.clone();
+    let handle_day_cycle_change = move |value: f32| {
+        animate_speed.set(value);
+        send_lighting();
+    };
+
     let send_lighting = send_lighting_update.clone();
     let handle_pollution_change = move |value: f32| {
         pollution.set(value);
@@ -334,9 +350,11 @@ pub fn SidePanel(props: SidePanelProps) -> Element {
                                 (PrimitiveType::Sphere, "Sphere"),
                                 (PrimitiveType::Cylinder, "Cylinder"),
                                 (PrimitiveType::Plane, "Plane"),
+                                (PrimitiveType::Grid { subdivisions: 10 }, "Grid"),
                                 (PrimitiveType::Torus, "Torus"),
                                 (PrimitiveType::Cone, "Cone"),
                                 (PrimitiveType::Capsule, "Capsule"),
+                                (PrimitiveType::Monkey, "Monkey"),
                             ];
                             rsx! {
                                 for (prim_type, name) in primitives.iter() {
@@ -439,6 +457,30 @@ pub fn SidePanel(props: SidePanelProps) -> Element {
                         span { class: "property-value", "{format_time(time_of_day())}" }
                     }
 
+                    div { class: "property",
+                        label { class: "property-label", "Day Cycle" }
+                        Slider {
+                            value: animate_speed(),
+                            min: 0.0,
+                            max: 4.0,
+                            step: 0.1,
+                            on_change: handle_day_cycle_change
+                        }
+                        span { class: "property-value", "{animate_speed():.1} h/s" }
+                    }
+
+                    div { class: "property",
+                        label { class: "property-label", "Latitude" }
+                        Slider {
+                            value: latitude(),
+                            min: -90.0,
+                            max: 90.0,
+                            step: 1.0,
+                            on_change: handle_latitude_change
+                        }
+                        span { class: "property-value", "{latitude() as i32}°" }
+                    }
+
                     div { class: "property",
                         label { class: "property-label", "Cloudiness" }
                         Slider {
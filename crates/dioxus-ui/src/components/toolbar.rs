@@ -1,7 +1,7 @@
 //! Toolbar component - replicates the Svelte Toolbar.svelte
 
 use dioxus::prelude::*;
-use pentimento_ipc::{EditMode, MeshSelectionMode};
+use pentimento_ipc::{EditMode, MeshSelectionMode, ViewMode};
 
 use crate::bridge::{DioxusBridge, SharedUiState};
 use crate::state::RenderStats;
@@ -51,6 +51,20 @@ const TOOLBAR_CSS: &str = r#"
     transition: background 0.15s;
 }
 
+.view-mode-select {
+    background: rgba(255, 255, 255, 0.05);
+    border: 1px solid rgba(255, 255, 255, 0.15);
+    color: rgba(255, 255, 255, 0.8);
+    padding: 6px 8px;
+    border-radius: 4px;
+    font-size: 13px;
+    cursor: pointer;
+}
+
+.view-mode-select option {
+    background: #2a2a2a;
+}
+
 .nav-button:hover,
 .nav-button.active {
     background: rgba(255, 255, 255, 0.1);
@@ -223,6 +237,15 @@ const TOOLBAR_CSS: &str = r#"
 }
 "#;
 
+/// Viewport debug views offered by the view mode dropdown
+const VIEW_MODES: [(ViewMode, &str); 5] = [
+    (ViewMode::Shaded, "Shaded"),
+    (ViewMode::Depth, "Depth"),
+    (ViewMode::Normals, "Normals"),
+    (ViewMode::AmbientOcclusion, "Ambient Occlusion"),
+    (ViewMode::UvChecker, "UV Checker"),
+];
+
 #[derive(Props, Clone, PartialEq)]
 pub struct ToolbarProps {
     pub render_stats: RenderStats,
@@ -249,6 +272,7 @@ pub fn Toolbar(props: ToolbarProps) -> Element {
     let edit_mode_text = match shared_state.edit_mode {
         EditMode::None => "Object Mode",
         EditMode::Paint => "Paint Mode",
+        EditMode::MeshPaint => "Mesh Paint Mode",
         EditMode::MeshEdit => "Edit Mode",
         EditMode::Sculpt => "Sculpt Mode",
     };
@@ -269,6 +293,16 @@ pub fn Toolbar(props: ToolbarProps) -> Element {
         String::new()
     };
 
+    // Shown while the scene renders below full resolution
+    let render_scale_text = if props.render_stats.render_scale < 1.0 {
+        format!(
+            "rendering at {:.0}%",
+            props.render_stats.render_scale * 100.0
+        )
+    } else {
+        String::new()
+    };
+
     rsx! {
         style { {TOOLBAR_CSS} }
         // Backdrop to catch clicks outside menu
@@ -520,26 +554,27 @@ pub fn Toolbar(props: ToolbarProps) -> Element {
             }
 
             div { class: "toolbar-right",
-                // Depth view toggle
-                if shared_state.depth_view_enabled {
-                    button {
-                        class: "tool-button-selected",
-                        title: "Depth View (active)",
-                        onclick: {
-                            let bridge = bridge.clone();
-                            move |_| bridge.set_depth_view(false)
-                        },
-                        "D"
-                    }
-                } else {
-                    button {
-                        class: "tool-button",
-                        title: "Depth View",
-                        onclick: {
-                            let bridge = bridge.clone();
-                            move |_| bridge.set_depth_view(true)
-                        },
-                        "D"
+                // Viewport debug view
+                select {
+                    class: "view-mode-select",
+                    title: "View Mode",
+                    onchange: {
+                        let bridge = bridge.clone();
+                        move |evt: Event<FormData>| {
+                            if let Some((mode, _)) = VIEW_MODES
+                                .into_iter()
+                                .find(|(mode, _)| format!("{:?}", mode) == evt.value())
+                            {
+                                bridge.set_view_mode(mode);
+                            }
+                        }
+                    },
+                    for (mode, label) in VIEW_MODES {
+                        option {
+                            value: "{mode:?}",
+                            selected: shared_state.view_mode == mode,
+                            "{label}"
+                        }
                     }
                 }
                 button {
@@ -550,6 +585,9 @@ pub fn Toolbar(props: ToolbarProps) -> Element {
                 div { class: "stats",
                     span { class: "stat", "{props.render_stats.fps:.0} FPS" }
                     span { class: "stat", "{props.render_stats.frame_time:.1}ms" }
+                    if !render_scale_text.is_empty() {
+                        span { class: "stat", "{render_scale_text}" }
+                    }
                 }
             }
         }
@@ -0,0 +1,364 @@
+//! Conversion of IPC input events into Blitz UI events
+//!
+//! The app forwards input as `pentimento_ipc::MouseEvent`/`KeyboardEvent`,
+//! with the web key strings every frontend receives. Blitz needs more than a
+//! single IPC event carries: pointer events hold the full set of pressed
+//! buttons, and key events say whether they are auto-repeats. `InputConverter`
+//! keeps that state between events.
+
+use std::collections::HashSet;
+
+use blitz_traits::events::{
+    BlitzKeyEvent, BlitzPointerEvent, BlitzPointerId, BlitzWheelDelta, BlitzWheelEvent, KeyState,
+    MouseEventButton, MouseEventButtons, PointerCoords, PointerDetails, UiEvent,
+};
+use keyboard_types::{Code, Key, Location, Modifiers};
+use pentimento_ipc::{KeyboardEvent, MouseButton, MouseEvent};
+
+/// Click tolerance in logical pixels. Movement within this distance from mousedown
+/// won't trigger drag mode, making clicks more reliable on sensitive input devices.
+pub const CLICK_TOLERANCE: f32 = 8.0;
+
+/// Stateful converter from IPC input events to Blitz `UiEvent`s
+pub struct InputConverter {
+    /// Buttons currently held down
+    buttons: MouseEventButtons,
+    /// Where the last button went down (for click vs drag detection)
+    press_origin: (f32, f32),
+    /// Modifiers from the last keyboard event, applied to pointer events too
+    mods: Modifiers,
+    /// Physical codes currently held down, for repeat detection
+    pressed_codes: HashSet<Code>,
+}
+
+impl Default for InputConverter {
+    fn default() -> Self {
+        Self {
+            buttons: MouseEventButtons::empty(),
+            press_origin: (0.0, 0.0),
+            mods: Modifiers::empty(),
+            pressed_codes: HashSet::new(),
+        }
+    }
+}
+
+impl InputConverter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert a mouse event
+    ///
+    /// `PointerDown` includes the pressed button in `buttons` and `PointerUp`
+    /// no longer does, as in the DOM. Moves report no buttons until the
+    /// pointer leaves `CLICK_TOLERANCE` of the press, so that jitter doesn't
+    /// start a drag and swallow the click.
+    pub fn mouse(&mut self, event: &MouseEvent) -> UiEvent {
+        match *event {
+            MouseEvent::Move { x, y } => {
+                let (down_x, down_y) = self.press_origin;
+                let dragging =
+                    (x - down_x).abs() > CLICK_TOLERANCE || (y - down_y).abs() > CLICK_TOLERANCE;
+                let buttons = if dragging {
+                    self.buttons
+                } else {
+                    MouseEventButtons::empty()
+                };
+                UiEvent::PointerMove(self.pointer_event(x, y, MouseEventButton::Main, buttons))
+            }
+            MouseEvent::ButtonDown { x, y, button } => {
+                let button = convert_button(button);
+                self.press_origin = (x, y);
+                self.buttons.insert(button.into());
+                UiEvent::PointerDown(self.pointer_event(x, y, button, self.buttons))
+            }
+            MouseEvent::ButtonUp { x, y, button } => {
+                let button = convert_button(button);
+                self.buttons.remove(button.into());
+                UiEvent::PointerUp(self.pointer_event(x, y, button, self.buttons))
+            }
+            MouseEvent::Scroll {
+                delta_x,
+                delta_y,
+                x,
+                y,
+            } => UiEvent::Wheel(BlitzWheelEvent {
+                delta: BlitzWheelDelta::Pixels(delta_x as f64, delta_y as f64),
+                coords: coords(x, y),
+                buttons: self.buttons,
+                mods: self.mods,
+            }),
+        }
+    }
+
+    /// Convert a keyboard event
+    ///
+    /// A press of a code that is already held down is reported as an
+    /// auto-repeat. Text is only attached to presses of character keys
+    /// without Ctrl or Meta held, so shortcuts don't type into inputs.
+    pub fn keyboard(&mut self, event: &KeyboardEvent) -> UiEvent {
+        let key = convert_key(&event.key);
+        let code = convert_code(&event.code);
+        self.mods = convert_modifiers(&event.modifiers);
+
+        let is_auto_repeating = if event.pressed {
+            code != Code::Unidentified && !self.pressed_codes.insert(code)
+        } else {
+            self.pressed_codes.remove(&code);
+            false
+        };
+
+        let text = match &key {
+            Key::Character(text)
+                if event.pressed && !self.mods.intersects(Modifiers::CONTROL | Modifiers::META) =>
+            {
+                Some(text.as_str().into())
+            }
+            _ => None,
+        };
+
+        let key_event = BlitzKeyEvent {
+            key,
+            code,
+            modifiers: self.mods,
+            location: code_location(&event.code),
+            is_auto_repeating,
+            is_composing: false,
+            state: if event.pressed {
+                KeyState::Pressed
+            } else {
+                KeyState::Released
+            },
+            text,
+        };
+
+        if event.pressed {
+            UiEvent::KeyDown(key_event)
+        } else {
+            UiEvent::KeyUp(key_event)
+        }
+    }
+
+    fn pointer_event(
+        &self,
+        x: f32,
+        y: f32,
+        button: MouseEventButton,
+        buttons: MouseEventButtons,
+    ) -> BlitzPointerEvent {
+        BlitzPointerEvent {
+            id: BlitzPointerId::Mouse,
+            is_primary: true,
+            coords: coords(x, y),
+            button,
+            buttons,
+            mods: self.mods,
+            details: PointerDetails::default(),
+        }
+    }
+}
+
+/// Map a web `KeyboardEvent.key` string to a logical key
+///
+/// Unknown names become `Key::Unidentified`.
+pub fn convert_key(key: &str) -> Key {
+    key.parse().unwrap_or(Key::Unidentified)
+}
+
+/// Map a web `KeyboardEvent.code` string to a physical key
+///
+/// Unknown names become `Code::Unidentified`.
+pub fn convert_code(code: &str) -> Code {
+    code.parse().unwrap_or(Code::Unidentified)
+}
+
+/// Map IPC modifier flags to keyboard-types modifiers
+pub fn convert_modifiers(modifiers: &pentimento_ipc::Modifiers) -> Modifiers {
+    let mut mods = Modifiers::empty();
+    mods.set(Modifiers::SHIFT, modifiers.shift);
+    mods.set(Modifiers::CONTROL, modifiers.ctrl);
+    mods.set(Modifiers::ALT, modifiers.alt);
+    mods.set(Modifiers::META, modifiers.meta);
+    mods
+}
+
+/// Map an IPC mouse button to a DOM button
+pub fn convert_button(button: MouseButton) -> MouseEventButton {
+    match button {
+        MouseButton::Left => MouseEventButton::Main,
+        MouseButton::Right => MouseEventButton::Secondary,
+        MouseButton::Middle => MouseEventButton::Auxiliary,
+    }
+}
+
+/// Key location implied by a web code string (`ShiftLeft`, `Numpad4`, ...)
+fn code_location(code: &str) -> Location {
+    if code.starts_with("Numpad") {
+        Location::Numpad
+    } else if code.ends_with("Left") && code != "ArrowLeft" {
+        Location::Left
+    } else if code.ends_with("Right") && code != "ArrowRight" {
+        Location::Right
+    } else {
+        Location::Standard
+    }
+}
+
+/// The UI texture covers the window, so page, client and screen coordinates agree
+fn coords(x: f32, y: f32) -> PointerCoords {
+    PointerCoords {
+        page_x: x,
+        page_y: y,
+        screen_x: x,
+        screen_y: y,
+        client_x: x,
+        client_y: y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pointer(event: UiEvent) -> BlitzPointerEvent {
+        match event {
+            UiEvent::PointerDown(e) | UiEvent::PointerMove(e) | UiEvent::PointerUp(e) => e,
+            other => panic!("expected a pointer event, got {:?}", other),
+        }
+    }
+
+    fn key(event: UiEvent) -> BlitzKeyEvent {
+        match event {
+            UiEvent::KeyDown(e) | UiEvent::KeyUp(e) => e,
+            other => panic!("expected a key event, got {:?}", other),
+        }
+    }
+
+    fn key_event(key: &str, code: &str, pressed: bool) -> KeyboardEvent {
+        KeyboardEvent {
+            key: key.to_string(),
+            code: code.to_string(),
+            pressed,
+            modifiers: pentimento_ipc::Modifiers::default(),
+        }
+    }
+
+    #[test]
+    fn test_buttons_across_press_drag_release() {
+        let mut converter = InputConverter::new();
+        let left = MouseEventButtons::from(MouseEventButton::Main);
+        let right = MouseEventButtons::from(MouseEventButton::Secondary);
+
+        let down = pointer(converter.mouse(&MouseEvent::ButtonDown {
+            x: 10.0,
+            y: 10.0,
+            button: MouseButton::Left,
+        }));
+        assert_eq!(down.button, MouseEventButton::Main);
+        assert_eq!(down.buttons, left);
+
+        // Within click tolerance the press doesn't count as a drag
+        let jitter = pointer(converter.mouse(&MouseEvent::Move { x: 13.0, y: 12.0 }));
+        assert_eq!(jitter.buttons, MouseEventButtons::empty());
+
+        let drag = pointer(converter.mouse(&MouseEvent::Move { x: 40.0, y: 10.0 }));
+        assert_eq!(drag.buttons, left);
+
+        let both = pointer(converter.mouse(&MouseEvent::ButtonDown {
+            x: 40.0,
+            y: 10.0,
+            button: MouseButton::Right,
+        }));
+        assert_eq!(both.buttons, left | right);
+
+        let up = pointer(converter.mouse(&MouseEvent::ButtonUp {
+            x: 40.0,
+            y: 10.0,
+            button: MouseButton::Left,
+        }));
+        assert_eq!(up.button, MouseEventButton::Main);
+        assert_eq!(up.buttons, right);
+
+        converter.mouse(&MouseEvent::ButtonUp {
+            x: 40.0,
+            y: 10.0,
+            button: MouseButton::Right,
+        });
+        let hover = pointer(converter.mouse(&MouseEvent::Move { x: 90.0, y: 90.0 }));
+        assert_eq!(hover.buttons, MouseEventButtons::empty());
+        assert_eq!(hover.coords.client_x, 90.0);
+    }
+
+    #[test]
+    fn test_wheel_uses_pixel_delta() {
+        let mut converter = InputConverter::new();
+        let UiEvent::Wheel(wheel) = converter.mouse(&MouseEvent::Scroll {
+            delta_x: 0.0,
+            delta_y: -120.0,
+            x: 5.0,
+            y: 6.0,
+        }) else {
+            panic!("expected a wheel event");
+        };
+        assert!(matches!(wheel.delta, BlitzWheelDelta::Pixels(x, y) if x == 0.0 && y == -120.0));
+        assert_eq!(wheel.coords.page_y, 6.0);
+    }
+
+    #[test]
+    fn test_web_key_strings() {
+        assert_eq!(convert_key("a"), Key::Character("a".into()));
+        assert_eq!(convert_key(" "), Key::Character(" ".into()));
+        assert_eq!(convert_key("Enter"), Key::Enter);
+        assert_eq!(convert_key("F5"), Key::F5);
+        assert_eq!(convert_key("Shift"), Key::Shift);
+        assert_eq!(convert_key("Dead"), Key::Dead);
+        assert_eq!(convert_key("NotAKey"), Key::Unidentified);
+
+        assert_eq!(convert_code("KeyA"), Code::KeyA);
+        assert_eq!(convert_code("Space"), Code::Space);
+        assert_eq!(convert_code("MetaLeft"), Code::MetaLeft);
+        assert_eq!(convert_code("Numpad7"), Code::Numpad7);
+        assert_eq!(convert_code("NotACode"), Code::Unidentified);
+
+        assert_eq!(code_location("ShiftRight"), Location::Right);
+        assert_eq!(code_location("NumpadEnter"), Location::Numpad);
+        assert_eq!(code_location("ArrowLeft"), Location::Standard);
+    }
+
+    #[test]
+    fn test_key_repeat_and_text() {
+        let mut converter = InputConverter::new();
+
+        let first = key(converter.keyboard(&key_event("a", "KeyA", true)));
+        assert!(!first.is_auto_repeating);
+        assert_eq!(first.text.as_deref(), Some("a"));
+
+        let repeat = key(converter.keyboard(&key_event("a", "KeyA", true)));
+        assert!(repeat.is_auto_repeating);
+
+        let up = key(converter.keyboard(&key_event("a", "KeyA", false)));
+        assert_eq!(up.state, KeyState::Released);
+        assert!(up.text.is_none());
+
+        let again = key(converter.keyboard(&key_event("a", "KeyA", true)));
+        assert!(!again.is_auto_repeating);
+    }
+
+    #[test]
+    fn test_modifiers_carry_to_pointer_and_suppress_text() {
+        let mut converter = InputConverter::new();
+        let mut ctrl_a = key_event("a", "KeyA", true);
+        ctrl_a.modifiers.ctrl = true;
+
+        let shortcut = key(converter.keyboard(&ctrl_a));
+        assert_eq!(shortcut.modifiers, Modifiers::CONTROL);
+        assert!(shortcut.text.is_none());
+
+        let click = pointer(converter.mouse(&MouseEvent::ButtonDown {
+            x: 0.0,
+            y: 0.0,
+            button: MouseButton::Left,
+        }));
+        assert_eq!(click.mods, Modifiers::CONTROL);
+    }
+}
@@ -10,28 +10,56 @@
 //! 3. `resolve()` computes CSS styles and Taffy layout
 //! 4. `paint_to_scene()` renders to a Vello Scene via anyrender
 //!
+//! # Dirty Tracking
+//!
+//! Anything that can change what the document paints (VirtualDom updates,
+//! input events, head elements, resizes) marks it dirty. `take_damage()`
+//! tells the host whether to repaint and roughly where, so an idle UI
+//! costs nothing on the GPU.
+//!
 //! # Providers (matching official Dioxus Bevy example)
 //!
 //! - `BevyNetProvider`: Handles asset loading (CSS, images, fonts)
 //! - `DioxusDocumentProxy`: Handles head elements (Title, Meta, Style, etc.)
+//! - `DocumentShell`: Clipboard and redraw requests from text inputs
+//!
+//! # Text Inputs
+//!
+//! Blitz edits, and paints the caret and selection of, the focused text
+//! input itself: arrows, Home/End, Shift-selection, Ctrl+A, and cut, copy,
+//! and paste arrive as key events. This wrapper adds what Blitz leaves to
+//! the host: focusing an input when it is clicked (and giving focus back to
+//! the app root when something else is), and selecting a word on double-click.
 
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyrender_vello::VelloScenePainter;
 use blitz_dom::{Document, DocumentConfig};
+use blitz_traits::events::{BlitzKeyEvent, KeyState, MouseEventButton, UiEvent};
 use blitz_traits::shell::{ColorScheme, Viewport};
 use crossbeam_channel::Receiver;
 use dioxus::prelude::*;
 use dioxus_native_dom::DioxusDocument;
+use keyboard_types::{Code, Key, Location, Modifiers};
 use tracing::{debug, info};
 use vello::Scene;
-use vello::kurbo::{Affine, Circle};
+use vello::kurbo::{Affine, Circle, Rect};
 use vello::peniko::{Color, Fill};
 
 use crate::PentimentoApp;
 use crate::bridge::DioxusBridge;
 use crate::document_proxy::{DioxusDocumentProxy, DocumentMessage};
-use crate::net_provider::BevyNetProvider;
+use crate::net_provider::{AssetSource, BevyNetProvider};
+use crate::shell::DocumentShell;
+
+/// Longest gap between two presses that still counts as a double-click
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+
+/// Farthest (in logical pixels) the second press of a double-click may land from the first
+const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
 
 /// Wrapper around DioxusDocument that provides Vello rendering integration.
 ///
@@ -54,6 +82,18 @@ pub struct BlitzDocument {
     doc_receiver: Receiver<DocumentMessage>,
     /// Flag: a click occurred outside UI elements (viewport click)
     viewport_clicked: bool,
+    /// Something changed since the last `take_damage()`
+    dirty: bool,
+    /// Absolute layout rect of every node at the last `take_damage()`
+    layout_rects: HashMap<usize, Rect>,
+    /// Clipboard and redraw requests from Blitz
+    shell: Arc<DocumentShell>,
+    /// Asset loader, checked for finished background loads
+    net_provider: Arc<BevyNetProvider>,
+    /// Text input given focus by a click, until something else is clicked
+    focused_input: Option<usize>,
+    /// Time and position of the last main-button press, for double-clicks
+    last_press: Option<(Instant, f32, f32)>,
 }
 
 impl BlitzDocument {
@@ -62,16 +102,22 @@ impl BlitzDocument {
     /// # Arguments
     /// * `width` - Viewport width in logical pixels
     /// * `height` - Viewport height in logical pixels
-    /// * `scale` - Device pixel ratio (should be 1.0 for logical coordinate mode)
+    /// * `scale` - Device pixel ratio the document is painted at; layout and
+    ///   input stay in logical pixels
     /// * `bridge` - The IPC bridge for communication with Bevy
-    pub fn new(width: u32, height: u32, scale: f64, bridge: DioxusBridge) -> Self {
-        info!(
-            "BlitzDocument::new({}x{}, scale={}) - SHOULD BE 1.0",
-            width, height, scale
-        );
+    /// * `assets` - Host lookup for `asset:///` URLs this crate doesn't bundle
+    pub fn new(
+        width: u32,
+        height: u32,
+        scale: f64,
+        bridge: DioxusBridge,
+        assets: Option<AssetSource>,
+    ) -> Self {
+        info!("BlitzDocument::new({}x{}, scale={})", width, height, scale);
 
         // Create channel for document proxy communication
         let (doc_sender, doc_receiver) = crossbeam_channel::unbounded();
+        let shell = Arc::new(DocumentShell::default());
 
         // Create the Dioxus VirtualDom with our app component
         let vdom =
@@ -79,12 +125,8 @@ impl BlitzDocument {
 
         // Configure the document with viewport settings
         let config = DocumentConfig {
-            viewport: Some(Viewport::new(
-                width,
-                height,
-                scale as f32,
-                ColorScheme::Dark,
-            )),
+            viewport: Some(Self::viewport(width, height, scale)),
+            shell_provider: Some(shell.clone()),
             ..Default::default()
         };
 
@@ -93,8 +135,10 @@ impl BlitzDocument {
 
         // Set up NetProvider for asset loading (CSS, images, fonts)
         // The new blitz API handles resource loading internally via the NetHandler
-        let net_provider = BevyNetProvider::shared();
-        doc.inner.borrow_mut().set_net_provider(net_provider);
+        let net_provider = Arc::new(BevyNetProvider::new(assets));
+        doc.inner
+            .borrow_mut()
+            .set_net_provider(net_provider.clone());
         info!("BevyNetProvider configured for asset loading");
 
         // Set up DocumentProxy for head elements (Title, Meta, Style, etc.)
@@ -132,6 +176,12 @@ impl BlitzDocument {
             click_dots: Vec::new(),
             doc_receiver,
             viewport_clicked: false,
+            dirty: true,
+            layout_rects: HashMap::new(),
+            shell,
+            net_provider,
+            focused_input: None,
+            last_press: None,
         }
     }
 
@@ -149,27 +199,59 @@ impl BlitzDocument {
         debug!("BlitzDocument resize: {}x{}", width, height);
         self.width = width;
         self.height = height;
+        self.update_viewport();
+    }
 
-        // Update the viewport in the document
-        self.doc.inner.borrow_mut().set_viewport(Viewport::new(
-            width,
-            height,
-            self.scale as f32,
+    /// Update the device pixel ratio.
+    ///
+    /// Call this when the window moves to a monitor with a different scale.
+    /// The logical size is unchanged; the document is repainted sharp at the
+    /// new `physical_size()`.
+    pub fn set_scale(&mut self, scale: f64) {
+        if scale <= 0.0 || (scale - self.scale).abs() <= f64::EPSILON {
+            return;
+        }
+
+        debug!("BlitzDocument scale: {}", scale);
+        self.scale = scale;
+        self.update_viewport();
+    }
+
+    /// Viewport for a logical size painted at `scale`
+    ///
+    /// Blitz takes the window size in physical pixels and lays out in
+    /// `size / scale` CSS pixels.
+    fn viewport(width: u32, height: u32, scale: f64) -> Viewport {
+        let (physical_width, physical_height) = physical_size(width, height, scale);
+        Viewport::new(
+            physical_width,
+            physical_height,
+            scale as f32,
             ColorScheme::Dark,
-        ));
+        )
+    }
 
-        // Re-resolve layout with new dimensions
+    /// Apply the current size and scale to the document and re-resolve layout
+    fn update_viewport(&mut self) {
+        self.doc.inner.borrow_mut().set_viewport(Self::viewport(
+            self.width,
+            self.height,
+            self.scale,
+        ));
         self.doc.inner.borrow_mut().resolve(0.0);
+        self.dirty = true;
     }
 
     /// Process pending VirtualDom updates and re-resolve layout.
     ///
     /// Call this each frame to process any Dioxus state changes.
-    /// Returns true if any changes were processed.
+    /// Returns true if any changes were processed (which marks the document dirty).
     pub fn poll(&mut self) -> bool {
         // Poll returns true if there were pending updates
         let had_updates = self.doc.poll(None);
         debug!("poll: doc.poll() returned {}", had_updates);
+        self.dirty |=
+            had_updates || self.shell.take_redraw_request() || self.net_provider.take_completed();
 
         // Always resolve - like the official Dioxus Bevy example.
         // External state changes (e.g., SharedUiState from IPC) don't trigger
@@ -190,6 +272,7 @@ impl BlitzDocument {
 
         // Mark root scope dirty so poll() sees work to do
         self.doc.vdom.mark_dirty(dioxus_core::ScopeId::ROOT);
+        self.dirty = true;
 
         // Poll will now call render_immediate() for incremental update
         self.poll();
@@ -226,6 +309,7 @@ impl BlitzDocument {
 
         // Re-resolve layout with potentially updated DOM
         self.doc.inner.borrow_mut().resolve(0.0);
+        self.dirty = true;
 
         info!("force_render: rebuild completed");
     }
@@ -276,6 +360,100 @@ impl BlitzDocument {
         if had_messages {
             debug!("Processed provider messages, re-resolving layout");
             self.doc.inner.borrow_mut().resolve(0.0);
+            self.dirty = true;
+        }
+    }
+
+    /// Mark the document as needing a repaint.
+    ///
+    /// For changes the document can't see itself, e.g. the render target
+    /// being recreated.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether anything changed since the last `take_damage()`.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Check and clear the dirty flag, returning the area that needs repainting.
+    ///
+    /// Returns `None` when nothing changed, so the previous frame can be kept.
+    /// Otherwise returns a coarse damage rect in logical pixels: the bounding
+    /// box of nodes whose layout changed. Changes that don't move anything
+    /// (hover colors, text edits in place) can't be located from layout, so
+    /// they damage the whole viewport.
+    pub fn take_damage(&mut self) -> Option<Rect> {
+        if !std::mem::take(&mut self.dirty) {
+            return None;
+        }
+
+        let viewport = Rect::new(0.0, 0.0, self.width as f64, self.height as f64);
+        let rects = {
+            let doc = self.doc.inner.borrow();
+            let mut rects = HashMap::with_capacity(self.layout_rects.len());
+            Self::collect_layout_rects(&doc, doc.root_node().id, 0.0, 0.0, &mut rects);
+            rects
+        };
+
+        let mut damage: Option<Rect> = None;
+        let mut add = |rect: Rect| {
+            damage = Some(damage.map_or(rect, |damage| damage.union(rect)));
+        };
+        for (id, rect) in &rects {
+            match self.layout_rects.get(id) {
+                Some(old) if old == rect => {}
+                Some(old) => {
+                    add(*old);
+                    add(*rect);
+                }
+                None => add(*rect),
+            }
+        }
+        for (id, old) in &self.layout_rects {
+            if !rects.contains_key(id) {
+                add(*old);
+            }
+        }
+        self.layout_rects = rects;
+
+        let damage = damage
+            .map(|damage| damage.intersect(viewport))
+            .filter(|damage| damage.area() > 0.0)
+            .unwrap_or(viewport);
+        debug!("take_damage: {:?}", damage);
+        Some(damage)
+    }
+
+    /// Record the absolute layout rect of a node and its descendants.
+    ///
+    /// Coarse like `deepest_hit`: positions are summed down the tree, ignoring
+    /// scrolling and fixed positioning.
+    fn collect_layout_rects(
+        doc: &blitz_dom::BaseDocument,
+        node_id: usize,
+        parent_x: f64,
+        parent_y: f64,
+        rects: &mut HashMap<usize, Rect>,
+    ) {
+        let Some(node) = doc.get_node(node_id) else {
+            return;
+        };
+        let layout = node.final_layout;
+        let x = parent_x + layout.location.x as f64;
+        let y = parent_y + layout.location.y as f64;
+        rects.insert(
+            node_id,
+            Rect::new(
+                x,
+                y,
+                x + layout.size.width as f64,
+                y + layout.size.height as f64,
+            ),
+        );
+        for &child_id in &node.children {
+            Self::collect_layout_rects(doc, child_id, x, y, rects);
         }
     }
 
@@ -292,27 +470,28 @@ impl BlitzDocument {
 
         // Paint the document using blitz-paint
         // This converts the styled/laid-out DOM tree to draw commands
+        let (width, height) = self.physical_size();
         blitz_paint::paint_scene(
             &mut painter,
             &*self.doc.inner.borrow(),
             self.scale,
-            self.width,
-            self.height,
+            width,
+            height,
             0, // x_offset
             0, // y_offset
         );
 
         // Debug: render click dot at the most recent click position
-        // Using coordinates directly since we operate in logical pixels (scale=1.0)
+        // Click positions are logical pixels, painted at the document scale
         if let Some((x, y)) = self.click_dots.last() {
             debug!("Rendering dot at ({}, {}), doc scale={}", x, y, self.scale);
             let red = Color::from_rgba8(255, 0, 0, 255);
             let white = Color::from_rgba8(255, 255, 255, 255);
-            // No scaling needed - we're in logical pixel space
+            let transform = Affine::scale(self.scale);
             let circle = Circle::new((*x as f64, *y as f64), 12.0);
-            scene.fill(Fill::NonZero, Affine::IDENTITY, red, None, &circle);
+            scene.fill(Fill::NonZero, transform, red, None, &circle);
             let inner = Circle::new((*x as f64, *y as f64), 4.0);
-            scene.fill(Fill::NonZero, Affine::IDENTITY, white, None, &inner);
+            scene.fill(Fill::NonZero, transform, white, None, &inner);
         }
     }
 
@@ -321,6 +500,11 @@ impl BlitzDocument {
         (self.width, self.height)
     }
 
+    /// Size of the painted scene in physical pixels
+    pub fn physical_size(&self) -> (u32, u32) {
+        physical_size(self.width, self.height, self.scale)
+    }
+
     /// Get access to the underlying DioxusDocument for event handling.
     pub fn document(&mut self) -> &mut DioxusDocument {
         &mut self.doc
@@ -402,8 +586,93 @@ impl BlitzDocument {
             }
         }
 
-        // Let Blitz handle the event
+        // Focus text inputs on press, before Blitz places the caret
+        let double_click = match &event {
+            UiEvent::PointerDown(e) if e.button == MouseEventButton::Main => {
+                self.focus_on_press(e.coords.page_x, e.coords.page_y)
+            }
+            _ => false,
+        };
+
+        // Let Blitz handle the event (hover, focus, and handlers can all change the page)
         self.doc.handle_ui_event(event);
+        self.dirty = true;
+
+        if double_click && self.focused_input.is_some() {
+            self.select_word_at_caret();
+        }
+    }
+
+    /// Move focus for a main-button press at the given position.
+    ///
+    /// Blitz doesn't focus on click, so a pressed text input is focused here.
+    /// Pressing anything else after editing gives focus back to the app root,
+    /// where the keyboard shortcuts are handled. Returns whether the press
+    /// completes a double-click.
+    fn focus_on_press(&mut self, x: f32, y: f32) -> bool {
+        let now = Instant::now();
+        let double_click = self.last_press.is_some_and(|(time, last_x, last_y)| {
+            now.duration_since(time) <= DOUBLE_CLICK_TIME
+                && (x - last_x).abs() <= DOUBLE_CLICK_DISTANCE
+                && (y - last_y).abs() <= DOUBLE_CLICK_DISTANCE
+        });
+        // A third press starts over rather than counting as another double-click
+        self.last_press = if double_click {
+            None
+        } else {
+            Some((now, x, y))
+        };
+
+        let mut inner = self.doc.inner.borrow_mut();
+        let pressed_input = inner
+            .hit(x, y)
+            .map(|hit| hit.node_id)
+            .filter(|&id| inner.get_node(id).is_some_and(is_text_input));
+
+        match (pressed_input, self.focused_input) {
+            (Some(id), focused) if focused != Some(id) => {
+                debug!("Focusing text input node {}", id);
+                inner.set_focus_to(id);
+                self.focused_input = Some(id);
+            }
+            (None, Some(_)) => {
+                if let Some(root_id) = Self::find_first_focusable(&inner) {
+                    inner.set_focus_to(root_id);
+                }
+                self.focused_input = None;
+            }
+            _ => {}
+        }
+
+        double_click
+    }
+
+    /// Select the word around the caret of the focused input.
+    ///
+    /// Goes through Blitz's own key handling: jump to the end of the word,
+    /// then select back to its start.
+    fn select_word_at_caret(&mut self) {
+        use blitz_dom::Document;
+
+        for (key, code, modifiers) in [
+            (Key::ArrowRight, Code::ArrowRight, Modifiers::CONTROL),
+            (
+                Key::ArrowLeft,
+                Code::ArrowLeft,
+                Modifiers::CONTROL | Modifiers::SHIFT,
+            ),
+        ] {
+            self.doc.handle_ui_event(UiEvent::KeyDown(BlitzKeyEvent {
+                key,
+                code,
+                modifiers,
+                location: Location::Standard,
+                is_auto_repeating: false,
+                is_composing: false,
+                state: KeyState::Pressed,
+                text: None,
+            }));
+        }
     }
 
     /// Custom hit testing that finds the deepest element at the given position.
@@ -508,3 +777,24 @@ impl BlitzDocument {
         None
     }
 }
+
+/// Whether a node is a text field (`<textarea>`, or an `<input>` that edits text)
+fn is_text_input(node: &blitz_dom::Node) -> bool {
+    let Some(el) = node.element_data() else {
+        return false;
+    };
+    match el.name.local.as_ref() {
+        "textarea" => true,
+        "input" => matches!(
+            el.attr(blitz_dom::local_name!("type")).unwrap_or("text"),
+            "text" | "search" | "email" | "url" | "tel" | "password"
+        ),
+        _ => false,
+    }
+}
+
+/// A logical size in physical pixels at `scale` (at least 1x1)
+fn physical_size(width: u32, height: u32, scale: f64) -> (u32, u32) {
+    let scaled = |pixels: u32| ((f64::from(pixels) * scale).round() as u32).max(1);
+    (scaled(width), scaled(height))
+}
@@ -22,15 +22,18 @@
 mod app;
 mod bridge;
 mod components;
+pub mod convert;
 mod document;
 mod document_proxy;
 mod net_provider;
 mod renderer;
+mod shell;
 mod state;
 
 pub use app::PentimentoApp;
-pub use bridge::{DioxusBridge, DioxusBridgeHandle};
+pub use bridge::{DiffusionPreviewImage, DioxusBridge, DioxusBridgeHandle};
 pub use document::BlitzDocument;
+pub use net_provider::AssetSource;
 pub use renderer::{SharedVelloRenderer, UiRenderState, VelloRenderer, VelloRendererError};
 pub use state::UiState;
 
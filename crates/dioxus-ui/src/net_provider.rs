@@ -4,25 +4,156 @@
 //! (images, stylesheets, fonts) for the Dioxus document when running in
 //! headless mode within Bevy.
 //!
+//! Local URLs are answered synchronously inside `fetch`. Remote URLs are
+//! downloaded on a small tokio runtime and answered from its worker thread;
+//! Blitz picks the bytes up on its next `resolve`, and `take_completed` tells
+//! `BlitzDocument` to repaint. Fonts need nothing special: Blitz fetches
+//! `@font-face` sources through this provider and registers them with its
+//! font context itself.
+//!
 //! Based on the official Dioxus native asset provider.
 
-use std::sync::Arc;
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
 use blitz_traits::net::{Bytes, NetHandler, NetProvider, Request};
 use data_url::DataUrl;
 use tracing::{debug, warn};
 
+/// Host lookup for bundled UI files, by path relative to the bundle root
+/// (e.g. `UiAssets` in the app)
+pub type AssetSource = Arc<dyn Fn(&str) -> Option<Vec<u8>> + Send + Sync>;
+
+/// Files built into this crate, served under `asset:///`
+const BUNDLED_ASSETS: &[(&str, &[u8])] = &[(
+    "icons/pentimento.svg",
+    include_bytes!("../assets/icons/pentimento.svg"),
+)];
+
+/// Drawn in place of an image that failed to load
+const PLACEHOLDER_IMAGE: &[u8] = br##"<svg xmlns="http://www.w3.org/2000/svg" width="32" height="32" viewBox="0 0 32 32"><rect x="1" y="1" width="30" height="30" fill="none" stroke="#888" stroke-width="2" stroke-dasharray="4 3"/><path d="M9 9l14 14M23 9L9 23" stroke="#888" stroke-width="2"/></svg>"##;
+
+/// File extensions treated as images when deciding whether to send the placeholder
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "ico"];
+
+/// Bytes of downloaded responses kept for reuse
+const REMOTE_CACHE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Bytes downloaded per session before further remote loads are refused
+const REMOTE_SESSION_BYTES: usize = 64 * 1024 * 1024;
+
+/// Largest single response accepted
+const REMOTE_RESPONSE_BYTES: usize = 8 * 1024 * 1024;
+
 /// Network provider for loading assets in Bevy-hosted Dioxus.
 ///
 /// Supports:
 /// - `dioxus://` scheme for bundled assets (via `dioxus-asset-resolver`)
+/// - `asset:///` for files built into this crate, then the host's `AssetSource`
+/// - `file://` read from disk
 /// - `data:` URIs for inline base64 encoded content
-pub struct BevyNetProvider;
+/// - `http://` and `https://`, downloaded in the background and cached
+pub struct BevyNetProvider {
+    assets: Option<AssetSource>,
+    /// Created on the first remote load (`None` if it couldn't be)
+    runtime: OnceLock<Option<tokio::runtime::Runtime>>,
+    client: reqwest::Client,
+    remote: Arc<Mutex<RemoteCache>>,
+    state: Arc<LoadState>,
+}
 
 impl BevyNetProvider {
-    /// Create a shared network provider.
-    pub fn shared() -> Arc<dyn NetProvider> {
-        Arc::new(Self)
+    /// Create a network provider, resolving `asset:///` URLs the crate
+    /// doesn't bundle through `assets`.
+    pub fn new(assets: Option<AssetSource>) -> Self {
+        Self {
+            assets,
+            runtime: OnceLock::new(),
+            client: reqwest::Client::new(),
+            remote: Arc::new(Mutex::new(RemoteCache::new(
+                REMOTE_CACHE_BYTES,
+                REMOTE_SESSION_BYTES,
+            ))),
+            state: Arc::new(LoadState::default()),
+        }
+    }
+
+    /// Check and clear whether a background load finished since the last call.
+    pub fn take_completed(&self) -> bool {
+        self.state.completed.swap(false, Ordering::Relaxed)
+    }
+
+    /// Look up a bundled file by path relative to the bundle root.
+    fn bundled(&self, path: &str) -> Option<Vec<u8>> {
+        let path = path.trim_start_matches('/');
+        BUNDLED_ASSETS
+            .iter()
+            .find(|(name, _)| *name == path)
+            .map(|(_, bytes)| bytes.to_vec())
+            .or_else(|| self.assets.as_ref().and_then(|assets| assets(path)))
+    }
+
+    fn runtime(&self) -> Option<&tokio::runtime::Runtime> {
+        self.runtime
+            .get_or_init(|| {
+                tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(1)
+                    .thread_name("ui-net")
+                    .enable_all()
+                    .build()
+                    .inspect_err(|e| warn!("Failed to start UI network runtime: {}", e))
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    /// Answer from the cache, or download in the background.
+    fn fetch_remote(&self, url: String, handler: Box<dyn NetHandler>) {
+        let cached = {
+            let mut remote = self.remote.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(bytes) = remote.get(&url) {
+                Ok(bytes)
+            } else if remote.session_budget_left() {
+                Err(None)
+            } else {
+                Err(Some("session download limit reached"))
+            }
+        };
+        match cached {
+            Ok(bytes) => {
+                debug!("Remote asset cache hit: {}", url);
+                handler.bytes(url, bytes);
+                return;
+            }
+            Err(Some(reason)) => {
+                self.state.fail(handler, url, reason);
+                return;
+            }
+            Err(None) => {}
+        }
+
+        let Some(runtime) = self.runtime() else {
+            self.state.fail(handler, url, "no network runtime");
+            return;
+        };
+
+        let client = self.client.clone();
+        let remote = self.remote.clone();
+        let state = self.state.clone();
+        runtime.spawn(async move {
+            match download(&client, &url).await {
+                Ok(bytes) => {
+                    debug!("Downloaded remote asset: {} ({} bytes)", url, bytes.len());
+                    remote
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(url.clone(), bytes.clone());
+                    state.complete(handler, url, bytes);
+                }
+                Err(e) => state.fail(handler, url, &e),
+            }
+        });
     }
 }
 
@@ -42,13 +173,28 @@ impl NetProvider for BevyNetProvider {
                     handler.bytes(request.url.to_string(), res.into_body().into());
                 }
                 Err(e) => {
-                    warn!(
-                        "Failed to load dioxus asset {}: {:?}",
-                        request.url.path(),
-                        e
-                    );
+                    let reason = format!("{:?}", e);
+                    self.state.fail(handler, request.url.to_string(), &reason);
                 }
             },
+            // Files built into this crate or the host's UI bundle
+            "asset" => match self.bundled(request.url.path()) {
+                Some(bytes) => handler.bytes(request.url.to_string(), Bytes::from(bytes)),
+                None => self
+                    .state
+                    .fail(handler, request.url.to_string(), "not bundled"),
+            },
+            "file" => {
+                let bytes = request
+                    .url
+                    .to_file_path()
+                    .map_err(|()| "not a local path".to_string())
+                    .and_then(|path| std::fs::read(path).map_err(|e| e.to_string()));
+                match bytes {
+                    Ok(bytes) => handler.bytes(request.url.to_string(), Bytes::from(bytes)),
+                    Err(reason) => self.state.fail(handler, request.url.to_string(), &reason),
+                }
+            }
             // Decode data URIs (inline base64 images, etc.)
             "data" => {
                 let Ok(data_url) = DataUrl::process(request.url.as_str()) else {
@@ -63,10 +209,174 @@ impl NetProvider for BevyNetProvider {
                 debug!("Decoded data URI: {} bytes", bytes.len());
                 handler.bytes(request.url.to_string(), bytes);
             }
-            // Unsupported schemes (http, https, etc.)
+            "http" | "https" => self.fetch_remote(request.url.to_string(), handler),
+            // Unsupported schemes
             _ => {
                 warn!("Unsupported URL scheme: {}", scheme);
             }
         }
     }
 }
+
+/// Download one response, refusing anything over `REMOTE_RESPONSE_BYTES`.
+async fn download(client: &reqwest::Client, url: &str) -> Result<Bytes, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > REMOTE_RESPONSE_BYTES)
+    {
+        return Err("response too large".to_string());
+    }
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+    if bytes.len() > REMOTE_RESPONSE_BYTES {
+        return Err("response too large".to_string());
+    }
+    Ok(bytes)
+}
+
+/// Completion and failure bookkeeping shared with background loads
+#[derive(Default)]
+struct LoadState {
+    /// A background load finished since the last `take_completed`
+    completed: AtomicBool,
+    /// URLs whose failure was already logged
+    failed: Mutex<HashSet<String>>,
+}
+
+impl LoadState {
+    fn complete(&self, handler: Box<dyn NetHandler>, url: String, bytes: Bytes) {
+        handler.bytes(url, bytes);
+        self.completed.store(true, Ordering::Relaxed);
+    }
+
+    /// Log the failure once per URL and answer images with the placeholder.
+    fn fail(&self, handler: Box<dyn NetHandler>, url: String, reason: &str) {
+        let first = self
+            .failed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(url.clone());
+        if first {
+            warn!("Failed to load UI asset {}: {}", url, reason);
+        }
+        if is_image_url(&url) {
+            self.complete(handler, url, Bytes::from_static(PLACEHOLDER_IMAGE));
+        }
+    }
+}
+
+fn is_image_url(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit_once('.').is_some_and(|(_, ext)| {
+        IMAGE_EXTENSIONS
+            .iter()
+            .any(|image| ext.eq_ignore_ascii_case(image))
+    })
+}
+
+/// Least-recently-used cache of downloaded responses, with a session download budget
+struct RemoteCache {
+    /// Oldest first
+    entries: VecDeque<(String, Bytes)>,
+    cached_bytes: usize,
+    capacity: usize,
+    downloaded_bytes: usize,
+    session_limit: usize,
+}
+
+impl RemoteCache {
+    fn new(capacity: usize, session_limit: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            cached_bytes: 0,
+            capacity,
+            downloaded_bytes: 0,
+            session_limit,
+        }
+    }
+
+    fn get(&mut self, url: &str) -> Option<Bytes> {
+        let index = self.entries.iter().position(|(entry, _)| entry == url)?;
+        let entry = self.entries.remove(index)?;
+        let bytes = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(bytes)
+    }
+
+    /// Store a fresh download, counting it against the session budget
+    fn insert(&mut self, url: String, bytes: Bytes) {
+        self.downloaded_bytes += bytes.len();
+        if let Some(index) = self.entries.iter().position(|(entry, _)| *entry == url) {
+            if let Some((_, old)) = self.entries.remove(index) {
+                self.cached_bytes -= old.len();
+            }
+        }
+        if bytes.len() > self.capacity {
+            return;
+        }
+        self.cached_bytes += bytes.len();
+        self.entries.push_back((url, bytes));
+        while self.cached_bytes > self.capacity {
+            let Some((_, evicted)) = self.entries.pop_front() else {
+                break;
+            };
+            self.cached_bytes -= evicted.len();
+        }
+    }
+
+    fn session_budget_left(&self) -> bool {
+        self.downloaded_bytes < self.session_limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let mut cache = RemoteCache::new(10, 100);
+        cache.insert("a".into(), Bytes::from_static(b"aaaa"));
+        cache.insert("b".into(), Bytes::from_static(b"bbbb"));
+        // Touch "a" so "b" is the oldest
+        assert!(cache.get("a").is_some());
+        cache.insert("c".into(), Bytes::from_static(b"cccc"));
+
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.cached_bytes, 8);
+    }
+
+    #[test]
+    fn test_session_budget_counts_every_download() {
+        let mut cache = RemoteCache::new(4, 10);
+        // Too big to cache, but still downloaded
+        cache.insert("big".into(), Bytes::from_static(b"0123456789"));
+        assert!(cache.get("big").is_none());
+        assert!(!cache.session_budget_left());
+    }
+
+    #[test]
+    fn test_bundled_assets_before_host_source() {
+        let provider = BevyNetProvider::new(Some(Arc::new(|path: &str| {
+            (path == "fonts/ui.woff2").then(|| b"font".to_vec())
+        })));
+        assert!(provider.bundled("/icons/pentimento.svg").is_some());
+        assert_eq!(provider.bundled("fonts/ui.woff2"), Some(b"font".to_vec()));
+        assert!(provider.bundled("missing.png").is_none());
+    }
+
+    #[test]
+    fn test_image_urls() {
+        assert!(is_image_url("https://example.com/logo.PNG?size=2"));
+        assert!(is_image_url("asset:///icons/pentimento.svg"));
+        assert!(!is_image_url("https://example.com/style.css"));
+        assert!(!is_image_url("https://example.com/"));
+    }
+}
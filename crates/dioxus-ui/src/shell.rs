@@ -0,0 +1,65 @@
+//! Shell services Blitz asks of its host
+//!
+//! Blitz's text inputs copy and paste through the shell provider and ask it
+//! for a redraw when their caret or selection changes. Pentimento has no
+//! windowing shell around the document, so `DocumentShell` keeps the
+//! clipboard in process and turns redraw requests into a flag that
+//! `BlitzDocument::poll` folds into its dirty state.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use blitz_traits::shell::{ClipboardError, ShellProvider};
+
+/// Clipboard and redraw requests for one `BlitzDocument`
+#[derive(Default)]
+pub(crate) struct DocumentShell {
+    /// Text cut or copied from an input (in-process only)
+    clipboard: Mutex<String>,
+    /// Blitz asked for a repaint since the last `take_redraw_request`
+    redraw_requested: AtomicBool,
+}
+
+impl DocumentShell {
+    /// Check and clear the redraw request
+    pub(crate) fn take_redraw_request(&self) -> bool {
+        self.redraw_requested.swap(false, Ordering::Relaxed)
+    }
+}
+
+impl ShellProvider for DocumentShell {
+    fn request_redraw(&self) {
+        self.redraw_requested.store(true, Ordering::Relaxed);
+    }
+
+    fn get_clipboard_text(&self) -> Result<String, ClipboardError> {
+        Ok(self.clipboard.lock().map_err(|_| ClipboardError)?.clone())
+    }
+
+    fn set_clipboard_text(&self, text: String) -> Result<(), ClipboardError> {
+        *self.clipboard.lock().map_err(|_| ClipboardError)? = text;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_round_trip() {
+        let shell = DocumentShell::default();
+        assert_eq!(shell.get_clipboard_text().ok().as_deref(), Some(""));
+        shell.set_clipboard_text("Layer 2".to_string()).ok();
+        assert_eq!(shell.get_clipboard_text().ok().as_deref(), Some("Layer 2"));
+    }
+
+    #[test]
+    fn test_redraw_request_is_taken_once() {
+        let shell = DocumentShell::default();
+        assert!(!shell.take_redraw_request());
+        shell.request_redraw();
+        assert!(shell.take_redraw_request());
+        assert!(!shell.take_redraw_request());
+    }
+}
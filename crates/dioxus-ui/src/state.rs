@@ -4,10 +4,22 @@ use pentimento_ipc::{AppSettings, MaterialProperties, SceneInfo};
 use serde::{Deserialize, Serialize};
 
 /// Render statistics displayed in the toolbar
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RenderStats {
     pub fps: f32,
     pub frame_time: f32,
+    /// Scene resolution relative to the window
+    pub render_scale: f32,
+}
+
+impl Default for RenderStats {
+    fn default() -> Self {
+        Self {
+            fps: 0.0,
+            frame_time: 0.0,
+            render_scale: 1.0,
+        }
+    }
 }
 
 /// Main UI state synchronized with Bevy
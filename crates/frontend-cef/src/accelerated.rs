@@ -0,0 +1,169 @@
+//! Shared-texture paint capture (`cef-gpu` feature, Linux)
+//!
+//! When the browser is created with `shared_texture_enabled`, CEF calls
+//! `OnAcceleratedPaint` with a DMA-BUF instead of `OnPaint` with a BGRA buffer,
+//! so no pixels are copied on the CPU. The plane fds are only valid during the
+//! callback, so they are duplicated and handed to the renderer as
+//! `CaptureResult::GpuExternal`.
+//!
+//! Chromium recycles its shared textures, so the renderer copies each frame on
+//! the GPU as soon as it has imported it. If the import isn't supported, the
+//! renderer calls `disable_gpu_capture` and the browser is recreated with CPU
+//! painting.
+
+use cef::{AcceleratedPaintInfo, ColorType};
+use pentimento_frontend_core::{DmabufPlane, DmabufTexture, ExternalTextureFormat};
+use std::os::fd::BorrowedFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Latest shared-texture frame, written by the paint callback and read by capture
+pub(crate) struct GpuFrameSlot {
+    frame: Mutex<Option<DmabufTexture>>,
+    /// Cleared once the renderer falls back to CPU captures
+    enabled: AtomicBool,
+}
+
+impl GpuFrameSlot {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            frame: Mutex::new(None),
+            enabled: AtomicBool::new(enabled),
+        }
+    }
+
+    /// Whether the browser should be (or was) created with shared textures
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Stop accepting shared-texture frames and close the stored one
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::SeqCst);
+        self.frame.lock().unwrap().take();
+    }
+
+    /// Close the stored frame, so `has_frame` waits for the next paint
+    pub fn clear(&self) {
+        self.frame.lock().unwrap().take();
+    }
+
+    /// Store a painted frame, replacing (and closing) the previous one
+    pub fn publish(&self, frame: DmabufTexture) {
+        if !self.is_enabled() {
+            return;
+        }
+        *self.frame.lock().unwrap() = Some(frame);
+    }
+
+    /// The latest frame (fds are shared, not duplicated again)
+    pub fn latest(&self) -> Option<DmabufTexture> {
+        self.frame.lock().unwrap().clone()
+    }
+
+    /// Whether any shared-texture frame has been painted yet
+    pub fn has_frame(&self) -> bool {
+        self.frame.lock().unwrap().is_some()
+    }
+}
+
+/// Convert CEF's paint info into an owned DMA-BUF description
+///
+/// Returns `None` for pixel formats the renderer can't use or if duplicating
+/// a plane fd fails.
+pub(crate) fn dmabuf_from_paint_info(info: &AcceleratedPaintInfo) -> Option<DmabufTexture> {
+    let format = if info.format == ColorType::BGRA_8888 {
+        ExternalTextureFormat::Bgra8
+    } else if info.format == ColorType::RGBA_8888 {
+        ExternalTextureFormat::Rgba8
+    } else {
+        tracing::warn!("Unsupported CEF shared texture format");
+        return None;
+    };
+
+    let width = info.extra.coded_size.width;
+    let height = info.extra.coded_size.height;
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+
+    let plane_count = (info.plane_count.max(0) as usize).min(info.planes.len());
+    let mut planes = Vec::with_capacity(plane_count);
+    for plane in &info.planes[..plane_count] {
+        // Safety: CEF guarantees the fd is open for the duration of the callback
+        let fd = unsafe { BorrowedFd::borrow_raw(plane.fd) };
+        let fd = match fd.try_clone_to_owned() {
+            Ok(fd) => fd,
+            Err(e) => {
+                tracing::warn!("Failed to duplicate CEF shared texture fd: {}", e);
+                return None;
+            }
+        };
+        planes.push(DmabufPlane {
+            fd: Arc::new(fd),
+            offset: plane.offset,
+            stride: plane.stride,
+            size: plane.size,
+        });
+    }
+
+    if planes.is_empty() {
+        return None;
+    }
+
+    Some(DmabufTexture {
+        width: width as u32,
+        height: height as u32,
+        format,
+        modifier: info.modifier,
+        planes,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::os::fd::OwnedFd;
+
+    /// A single-plane frame backed by /dev/null (never imported in tests)
+    pub(crate) fn test_dmabuf(width: u32, height: u32) -> DmabufTexture {
+        let fd = OwnedFd::from(File::open("/dev/null").unwrap());
+        DmabufTexture {
+            width,
+            height,
+            format: ExternalTextureFormat::Bgra8,
+            modifier: DmabufTexture::MODIFIER_LINEAR,
+            planes: vec![DmabufPlane {
+                fd: Arc::new(fd),
+                offset: 0,
+                stride: width * 4,
+                size: u64::from(width * height * 4),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_publish_replaces_previous_frame() {
+        let slot = GpuFrameSlot::new(true);
+        assert!(!slot.has_frame());
+
+        slot.publish(test_dmabuf(4, 4));
+        slot.publish(test_dmabuf(8, 2));
+        let frame = slot.latest().unwrap();
+        assert_eq!((frame.width, frame.height), (8, 2));
+    }
+
+    #[test]
+    fn test_disable_drops_frame_and_ignores_later_paints() {
+        let slot = GpuFrameSlot::new(true);
+        slot.publish(test_dmabuf(4, 4));
+
+        slot.disable();
+        assert!(!slot.is_enabled());
+        assert!(!slot.has_frame());
+
+        slot.publish(test_dmabuf(4, 4));
+        assert!(slot.latest().is_none());
+    }
+}
@@ -4,42 +4,104 @@
 //! - CEF initialization (once per process)
 //! - Helper binary discovery for subprocess architecture
 //! - Browser instance creation with offscreen rendering
+//! - Render process crash detection (`OnRenderProcessTerminated`)
+//! - Open browser counting (`OnBeforeClose`), so shutdown can wait for the close
+//! - UI→Bevy IPC (`OnProcessMessageReceived`), fed by the helper's render process
+//! - Console forwarding (`OnConsoleMessage`)
+//! - Message pump scheduling (`OnScheduleMessagePumpWork`), see `message_pump`
+//!
+//! With the `console-ipc` feature, IPC goes through prefixed console.log
+//! messages instead of process messages, which is handy for debugging.
 
+#[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+use crate::accelerated::{self, GpuFrameSlot};
+use crate::capture::FrameBuffers;
+use crate::message_pump;
 use cef::args::Args;
 use cef::rc::Rc as _;
 use cef::{
-    api_hash, sys, wrap_app, wrap_client, wrap_display_handler, wrap_render_handler, App, Browser,
-    BrowserSettings, CefString, CefStringUtf16, Client, DisplayHandler, ImplApp, ImplClient,
-    ImplDisplayHandler, ImplRenderHandler, LogSeverity, PaintElementType, Rect, RenderHandler,
-    Settings, WindowInfo, WrapApp, WrapClient, WrapDisplayHandler, WrapRenderHandler,
+    api_hash, sys, wrap_app, wrap_browser_process_handler, wrap_client, wrap_display_handler,
+    wrap_life_span_handler, wrap_render_handler, wrap_request_handler, AcceleratedPaintInfo, App,
+    Browser, BrowserProcessHandler, BrowserSettings, CefString, CefStringUtf16, Client,
+    DisplayHandler, Frame, ImplApp, ImplBrowserProcessHandler, ImplClient, ImplDisplayHandler,
+    ImplLifeSpanHandler, ImplListValue, ImplProcessMessage, ImplRenderHandler, ImplRequestHandler,
+    ImplSchemeRegistrar, LifeSpanHandler, LogSeverity, PaintElementType, ProcessId, ProcessMessage,
+    Rect, RenderHandler, RequestHandler, SchemeRegistrar, ScreenInfo, Settings, TerminationStatus,
+    WindowInfo, WrapApp, WrapBrowserProcessHandler, WrapClient, WrapDisplayHandler,
+    WrapLifeSpanHandler, WrapRenderHandler, WrapRequestHandler,
 };
-use pentimento_frontend_core::FrontendError;
-use pentimento_ipc::UiToBevy;
+use pentimento_frontend_core::blob::BLOB_SCHEME;
+use pentimento_frontend_core::console::{ConsoleForwarder, ConsoleMessage};
+use pentimento_frontend_core::{parse_ui_messages, FrontendError};
+use pentimento_ipc::{BevyToUi, UiLogLevel, UiToBevy};
 use std::ffi::c_int;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
 use tokio::sync::mpsc;
 
 /// Global flag indicating whether CEF has been initialized
 static CEF_INITIALIZED: OnceLock<bool> = OnceLock::new();
 
 /// IPC message prefix used in console.log messages from JavaScript
+#[cfg(feature = "console-ipc")]
 pub(crate) const IPC_PREFIX: &str = "__PENTIMENTO_IPC__:";
 
+/// Name of the process message carrying one UI→Bevy message.
+/// Must match `IPC_PROCESS_MESSAGE` in `pentimento-cef-helper`.
+pub(crate) const IPC_PROCESS_MESSAGE: &str = "pentimento_ipc";
+
+/// Global function the helper installs in every page to send a message.
+/// Must match `IPC_SEND_FUNCTION` in `pentimento-cef-helper`.
+#[cfg(not(feature = "console-ipc"))]
+pub(crate) const IPC_SEND_FUNCTION: &str = "__pentimentoIpcSend";
+
 /// Shared state between RenderHandler, DisplayHandler, and CefBackend
 pub(crate) struct SharedState {
-    /// Raw BGRA pixel data wrapped in Arc for zero-copy sharing between threads.
-    /// The Arc allows capture() to clone just the pointer (~20ns) instead of
-    /// copying the entire 18MB buffer (~6-12ms at HiDPI).
-    pub framebuffer: Mutex<Option<Arc<Vec<u8>>>>,
-    /// Dimensions of the framebuffer
-    pub framebuffer_size: Mutex<(u32, u32)>,
+    /// Double-buffered BGRA frames. Capture clones just the front Arc (~20ns)
+    /// instead of copying the entire 18MB buffer (~6-12ms at HiDPI).
+    pub frames: FrameBuffers,
+    /// Shared-texture frames from `OnAcceleratedPaint` (`cef-gpu` feature)
+    #[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+    pub gpu_frames: GpuFrameSlot,
     /// Flag indicating the framebuffer has been updated
     pub dirty: Arc<AtomicBool>,
-    /// Current viewport size
+    /// Current viewport size in physical pixels
     pub size: Mutex<(u32, u32)>,
-    /// Channel for sending UI messages to Bevy (for IPC via console messages)
+    /// Device scale factor reported to CEF (`ScreenInfo`)
+    pub scale_factor: Mutex<f64>,
+    /// Channel for sending UI messages to Bevy
     pub from_ui_tx: mpsc::UnboundedSender<UiToBevy>,
+    /// Reason the render process died, taken by `CefBackend::poll`
+    pub terminated: Mutex<Option<String>>,
+    /// Logs page console messages and rate limits the ones sent to the UI
+    pub console: Mutex<ConsoleForwarder>,
+    /// `UiLog` messages and IPC error replies waiting for `CefBackend::poll`
+    /// to queue them
+    pub console_logs: Mutex<Vec<BevyToUi>>,
+    /// Browsers created and not yet closed (`OnBeforeClose`)
+    pub open_browsers: AtomicUsize,
+}
+
+/// Parse a serialized UI message or batch and send it to Bevy
+fn receive_ui_message(shared: &SharedState, json_str: &str) {
+    tracing::trace!("CEF IPC received: {} bytes", json_str.len());
+    for result in parse_ui_messages(json_str) {
+        match result {
+            Ok(ui_msg) => {
+                // Mark dirty when UI sends UiDirty message
+                if matches!(ui_msg, UiToBevy::UiDirty) {
+                    shared.dirty.store(true, Ordering::SeqCst);
+                }
+                let _ = shared.from_ui_tx.send(ui_msg);
+            }
+            Err(e) => {
+                if let Some(reply) = e.reply() {
+                    shared.console_logs.lock().unwrap().push(reply);
+                }
+            }
+        }
+    }
 }
 
 /// Custom render handler for offscreen rendering
@@ -52,6 +114,31 @@ impl OsrRenderHandler {
     pub fn new(shared: Arc<SharedState>) -> Self {
         Self { shared }
     }
+
+    /// Store a shared-texture frame (only called when shared textures are enabled)
+    #[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+    fn on_accelerated_paint(&self, info: &AcceleratedPaintInfo) {
+        if let Some(frame) = accelerated::dmabuf_from_paint_info(info) {
+            self.shared.gpu_frames.publish(frame);
+            self.shared.dirty.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[cfg(not(all(feature = "cef-gpu", target_os = "linux")))]
+    fn on_accelerated_paint(&self, _info: &AcceleratedPaintInfo) {
+        // Shared textures are never requested without the cef-gpu feature
+    }
+}
+
+/// View size in DIPs for a physical size at `scale_factor`
+fn view_size(size: (u32, u32), scale_factor: f64) -> (c_int, c_int) {
+    let scale_factor = if scale_factor > 0.0 {
+        scale_factor
+    } else {
+        1.0
+    };
+    let dips = |physical: u32| ((f64::from(physical) / scale_factor).round() as c_int).max(1);
+    (dips(size.0), dips(size.1))
 }
 
 // Macro generates RenderHandlerBuilder which wraps OsrRenderHandler
@@ -63,14 +150,30 @@ wrap_render_handler! {
     impl RenderHandler {
         fn view_rect(&self, _browser: Option<&mut Browser>, rect: Option<&mut Rect>) {
             if let Some(rect) = rect {
-                let size = self.handler.shared.size.lock().unwrap();
+                // CEF wants the view in DIPs and paints it at `screen_info`'s scale
+                let size = *self.handler.shared.size.lock().unwrap();
+                let scale_factor = *self.handler.shared.scale_factor.lock().unwrap();
+                let (width, height) = view_size(size, scale_factor);
                 rect.x = 0;
                 rect.y = 0;
-                rect.width = size.0 as c_int;
-                rect.height = size.1 as c_int;
+                rect.width = width;
+                rect.height = height;
             }
         }
 
+        fn screen_info(
+            &self,
+            _browser: Option<&mut Browser>,
+            screen_info: Option<&mut ScreenInfo>,
+        ) -> c_int {
+            let Some(screen_info) = screen_info else {
+                return 0;
+            };
+            let scale_factor = *self.handler.shared.scale_factor.lock().unwrap();
+            screen_info.device_scale_factor = scale_factor as f32;
+            1
+        }
+
         fn on_paint(
             &self,
             _browser: Option<&mut Browser>,
@@ -96,16 +199,29 @@ wrap_render_handler! {
             // Safety: CEF guarantees the buffer is valid for the duration of on_paint
             let bgra = unsafe { std::slice::from_raw_parts(buffer, buffer_size) };
 
-            // Copy the BGRA buffer and wrap in Arc for zero-copy sharing.
-            // The to_vec() copy is unavoidable (CEF owns the buffer), but wrapping
-            // in Arc allows capture() to share without another copy.
-            let buffer_copy = Arc::new(bgra.to_vec());
-
-            // Store the Arc-wrapped buffer
-            *self.handler.shared.framebuffer.lock().unwrap() = Some(buffer_copy);
-            *self.handler.shared.framebuffer_size.lock().unwrap() = (width, height);
+            // Copy into the back buffer and swap it to the front. The copy is
+            // unavoidable (CEF owns the buffer), but the back buffer is reused
+            // so no allocation happens after the first frames at a given size.
+            self.handler.shared.frames.publish(bgra, width, height);
             self.handler.shared.dirty.store(true, Ordering::SeqCst);
         }
+
+        fn on_accelerated_paint(
+            &self,
+            _browser: Option<&mut Browser>,
+            type_: PaintElementType,
+            _dirty_rects: Option<&[Rect]>,
+            info: Option<&AcceleratedPaintInfo>,
+        ) {
+            // Only handle VIEW (main view), not POPUP
+            if type_ != PaintElementType::VIEW {
+                return;
+            }
+
+            if let Some(info) = info {
+                self.handler.on_accelerated_paint(info);
+            }
+        }
     }
 }
 
@@ -115,7 +231,7 @@ impl RenderHandlerBuilder {
     }
 }
 
-/// Display handler for intercepting console messages (used for IPC)
+/// Display handler for forwarding console messages (and console IPC)
 #[derive(Clone)]
 pub(crate) struct OsrDisplayHandler {
     pub shared: Arc<SharedState>,
@@ -125,6 +241,57 @@ impl OsrDisplayHandler {
     pub fn new(shared: Arc<SharedState>) -> Self {
         Self { shared }
     }
+
+    /// Handle a console message carrying IPC; false for regular output
+    #[cfg(feature = "console-ipc")]
+    fn receive_console_ipc(&self, message: &str) -> bool {
+        match message.strip_prefix(IPC_PREFIX) {
+            Some(json_str) => {
+                receive_ui_message(&self.shared, json_str);
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[cfg(not(feature = "console-ipc"))]
+    fn receive_console_ipc(&self, _message: &str) -> bool {
+        // IPC arrives as process messages instead
+        false
+    }
+
+    /// Forward a regular (non-IPC) console message to tracing and the UI
+    fn forward_console_message(
+        &self,
+        level: LogSeverity,
+        message: String,
+        source: String,
+        line: c_int,
+    ) {
+        let msg = ConsoleMessage {
+            level: console_level(level),
+            message,
+            source,
+            line: line.max(0) as u32,
+        };
+        let mut console = self.shared.console.lock().unwrap();
+        if let Some(ui_log) = console.forward(msg, Instant::now()) {
+            self.shared.console_logs.lock().unwrap().push(ui_log);
+        }
+    }
+}
+
+/// Map CEF's console severity onto the UI log levels
+fn console_level(level: LogSeverity) -> UiLogLevel {
+    if level == LogSeverity::ERROR || level == LogSeverity::FATAL {
+        UiLogLevel::Error
+    } else if level == LogSeverity::WARNING {
+        UiLogLevel::Warn
+    } else if level == LogSeverity::VERBOSE {
+        UiLogLevel::Debug
+    } else {
+        UiLogLevel::Info
+    }
 }
 
 // Macro generates DisplayHandlerBuilder which wraps OsrDisplayHandler
@@ -137,32 +304,23 @@ wrap_display_handler! {
         fn on_console_message(
             &self,
             _browser: Option<&mut Browser>,
-            _level: LogSeverity,
+            level: LogSeverity,
             message: Option<&CefString>,
-            _source: Option<&CefString>,
-            _line: c_int,
+            source: Option<&CefString>,
+            line: c_int,
         ) -> c_int {
             // Check if this is an IPC message
             if let Some(msg) = message {
                 let msg_str = msg.to_string();
-                if let Some(json_str) = msg_str.strip_prefix(IPC_PREFIX) {
-                    // Parse the JSON message and send to Bevy
-                    match serde_json::from_str::<UiToBevy>(json_str) {
-                        Ok(ui_msg) => {
-                            // Mark dirty when UI sends UiDirty message
-                            if matches!(ui_msg, UiToBevy::UiDirty) {
-                                self.handler.shared.dirty.store(true, Ordering::SeqCst);
-                            }
-                            let _ = self.handler.shared.from_ui_tx.send(ui_msg);
-                            tracing::trace!("CEF IPC received: {}", json_str);
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to parse CEF IPC message: {} - {}", json_str, e);
-                        }
-                    }
+                if self.handler.receive_console_ipc(&msg_str) {
                     // Return 1 to suppress the console message (we handled it)
                     return 1;
                 }
+
+                // Regular console output goes to tracing instead of CEF's log
+                let source = source.map(|s| s.to_string()).unwrap_or_default();
+                self.handler.forward_console_message(level, msg_str, source, line);
+                return 1;
             }
             // Return 0 to allow normal console message handling
             0
@@ -176,14 +334,136 @@ impl DisplayHandlerBuilder {
     }
 }
 
-// Macro generates ClientBuilder which wraps RenderHandler and DisplayHandler
+/// Request handler for detecting render process crashes
+#[derive(Clone)]
+pub(crate) struct OsrRequestHandler {
+    pub shared: Arc<SharedState>,
+}
+
+impl OsrRequestHandler {
+    pub fn new(shared: Arc<SharedState>) -> Self {
+        Self { shared }
+    }
+}
+
+// Macro generates RequestHandlerBuilder which wraps OsrRequestHandler
+wrap_request_handler! {
+    pub(crate) struct RequestHandlerBuilder {
+        handler: OsrRequestHandler,
+    }
+
+    impl RequestHandler {
+        fn on_render_process_terminated(
+            &self,
+            _browser: Option<&mut Browser>,
+            status: TerminationStatus,
+            error_code: c_int,
+            error_string: Option<&CefString>,
+        ) {
+            // The page is gone and no more paints will come; the last frame
+            // stays on screen until CefBackend::poll reloads the browser
+            let reason = match error_string {
+                Some(error) => format!("{:?}, code {}: {}", status, error_code, error),
+                None => format!("{:?}, code {}", status, error_code),
+            };
+            tracing::error!("CEF render process terminated: {}", reason);
+            *self.handler.shared.terminated.lock().unwrap() = Some(reason);
+        }
+    }
+}
+
+impl RequestHandlerBuilder {
+    pub fn build(handler: OsrRequestHandler) -> RequestHandler {
+        Self::new(handler)
+    }
+}
+
+/// Life span handler counting closed browsers
+#[derive(Clone)]
+pub(crate) struct OsrLifeSpanHandler {
+    pub shared: Arc<SharedState>,
+}
+
+impl OsrLifeSpanHandler {
+    pub fn new(shared: Arc<SharedState>) -> Self {
+        Self { shared }
+    }
+}
+
+// Macro generates LifeSpanHandlerBuilder which wraps OsrLifeSpanHandler
+wrap_life_span_handler! {
+    pub(crate) struct LifeSpanHandlerBuilder {
+        handler: OsrLifeSpanHandler,
+    }
+
+    impl LifeSpanHandler {
+        fn on_before_close(&self, _browser: Option<&mut Browser>) {
+            // Last callback for a browser; CefBackend::shutdown waits for it
+            let open = &self.handler.shared.open_browsers;
+            let _ = open.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+            tracing::debug!("CEF browser closed");
+        }
+    }
+}
+
+impl LifeSpanHandlerBuilder {
+    pub fn build(handler: OsrLifeSpanHandler) -> LifeSpanHandler {
+        Self::new(handler)
+    }
+}
+
+/// Receiver for IPC process messages sent by the helper's render process
+#[derive(Clone)]
+pub(crate) struct OsrIpcReceiver {
+    pub shared: Arc<SharedState>,
+}
+
+impl OsrIpcReceiver {
+    pub fn new(shared: Arc<SharedState>) -> Self {
+        Self { shared }
+    }
+
+    /// Deliver an IPC process message; false for other messages
+    fn receive(&self, message: &ProcessMessage) -> bool {
+        if CefString::from(&message.name()).to_string() != IPC_PROCESS_MESSAGE {
+            return false;
+        }
+        match message.argument_list() {
+            Some(arguments) => {
+                let json_str = CefString::from(&arguments.string(0)).to_string();
+                receive_ui_message(&self.shared, &json_str);
+            }
+            None => tracing::warn!("CEF IPC process message without arguments"),
+        }
+        true
+    }
+}
+
+// Macro generates ClientBuilder which wraps the handlers and the IPC receiver
 wrap_client! {
     pub(crate) struct ClientBuilder {
         render_handler: RenderHandler,
         display_handler: DisplayHandler,
+        request_handler: RequestHandler,
+        life_span_handler: LifeSpanHandler,
+        ipc_receiver: OsrIpcReceiver,
     }
 
     impl Client {
+        fn on_process_message_received(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            _source_process: ProcessId,
+            message: Option<&mut ProcessMessage>,
+        ) -> c_int {
+            // Process messages from one frame arrive in the order they were sent
+            match message {
+                Some(message) => self.ipc_receiver.receive(message) as c_int,
+                None => 0,
+            }
+        }
+
         fn render_handler(&self) -> Option<cef::RenderHandler> {
             Some(self.render_handler.clone())
         }
@@ -191,14 +471,57 @@ wrap_client! {
         fn display_handler(&self) -> Option<cef::DisplayHandler> {
             Some(self.display_handler.clone())
         }
+
+        fn request_handler(&self) -> Option<cef::RequestHandler> {
+            Some(self.request_handler.clone())
+        }
+
+        fn life_span_handler(&self) -> Option<cef::LifeSpanHandler> {
+            Some(self.life_span_handler.clone())
+        }
     }
 }
 
 impl ClientBuilder {
     pub fn build(shared: Arc<SharedState>) -> Client {
         let render_handler = RenderHandlerBuilder::build(OsrRenderHandler::new(Arc::clone(&shared)));
-        let display_handler = DisplayHandlerBuilder::build(OsrDisplayHandler::new(shared));
-        Self::new(render_handler, display_handler)
+        let display_handler =
+            DisplayHandlerBuilder::build(OsrDisplayHandler::new(Arc::clone(&shared)));
+        let request_handler =
+            RequestHandlerBuilder::build(OsrRequestHandler::new(Arc::clone(&shared)));
+        let life_span_handler =
+            LifeSpanHandlerBuilder::build(OsrLifeSpanHandler::new(Arc::clone(&shared)));
+        let ipc_receiver = OsrIpcReceiver::new(shared);
+        Self::new(
+            render_handler,
+            display_handler,
+            request_handler,
+            life_span_handler,
+            ipc_receiver,
+        )
+    }
+}
+
+/// Browser process handler forwarding pump requests to `message_pump`
+#[derive(Clone)]
+pub(crate) struct OsrBrowserProcessHandler;
+
+wrap_browser_process_handler! {
+    pub(crate) struct BrowserProcessHandlerBuilder {
+        handler: OsrBrowserProcessHandler,
+    }
+
+    impl BrowserProcessHandler {
+        // Called on any thread; the work itself runs in CefBackend::poll
+        fn on_schedule_message_pump_work(&self, delay_ms: i64) {
+            message_pump::schedule_work(delay_ms);
+        }
+    }
+}
+
+impl BrowserProcessHandlerBuilder {
+    pub fn build(handler: OsrBrowserProcessHandler) -> BrowserProcessHandler {
+        Self::new(handler)
     }
 }
 
@@ -213,7 +536,19 @@ wrap_app! {
     }
 
     impl App {
-        // We don't need any custom app behavior for OSR
+        // Every CEF process must register the same schemes as the shared
+        // helper, even though this backend doesn't serve blobs itself
+        fn on_register_custom_schemes(&self, registrar: Option<&mut SchemeRegistrar>) {
+            let Some(registrar) = registrar else { return };
+            let options = sys::cef_scheme_options_t::CEF_SCHEME_OPTION_STANDARD as c_int
+                | sys::cef_scheme_options_t::CEF_SCHEME_OPTION_CORS_ENABLED as c_int
+                | sys::cef_scheme_options_t::CEF_SCHEME_OPTION_FETCH_ENABLED as c_int;
+            registrar.add_custom_scheme(Some(&CefString::from(BLOB_SCHEME)), options);
+        }
+
+        fn browser_process_handler(&self) -> Option<BrowserProcessHandler> {
+            Some(BrowserProcessHandlerBuilder::build(OsrBrowserProcessHandler))
+        }
     }
 }
 
@@ -273,7 +608,7 @@ pub fn ensure_cef_initialized() -> Result<(), FrontendError> {
         let mut settings = Settings::default();
         settings.windowless_rendering_enabled = 1;
         settings.no_sandbox = 1; // Disable sandbox for simpler setup
-        settings.external_message_pump = 1; // We'll pump messages ourselves
+        settings.external_message_pump = 1; // Pumped by message_pump::pump
         settings.multi_threaded_message_loop = 0;
 
         // CRITICAL: Set the subprocess helper path to prevent runaway process spawning
@@ -336,10 +671,14 @@ pub fn ensure_cef_initialized() -> Result<(), FrontendError> {
 }
 
 /// Create a new CEF browser with offscreen rendering
+///
+/// With `shared_textures`, CEF paints into GPU textures (`OnAcceleratedPaint`)
+/// instead of CPU buffers (`OnPaint`).
 pub fn create_browser(
     html_content: &str,
     size: (u32, u32),
     shared: &Arc<SharedState>,
+    shared_textures: bool,
 ) -> Result<Browser, FrontendError> {
     // Create the client with render handler and display handler (for IPC)
     let mut client = ClientBuilder::build(Arc::clone(shared));
@@ -349,6 +688,7 @@ pub fn create_browser(
     window_info.windowless_rendering_enabled = 1;
     window_info.bounds.width = size.0 as c_int;
     window_info.bounds.height = size.1 as c_int;
+    window_info.shared_texture_enabled = shared_textures as c_int;
 
     // Browser settings
     let mut browser_settings = BrowserSettings::default();
@@ -358,7 +698,12 @@ pub fn create_browser(
     let data_url = format!("data:text/html,{}", encoded_html);
     let mut url: CefStringUtf16 = data_url.as_str().into();
 
-    tracing::info!("Creating CEF browser with size {}x{}", size.0, size.1);
+    tracing::info!(
+        "Creating CEF browser with size {}x{} (shared textures: {})",
+        size.0,
+        size.1,
+        shared_textures
+    );
 
     // Create the browser
     let browser = cef::browser_host_create_browser_sync(
@@ -373,6 +718,7 @@ pub fn create_browser(
     match browser {
         Some(browser) => {
             tracing::info!("CEF browser created successfully");
+            shared.open_browsers.fetch_add(1, Ordering::SeqCst);
             Ok(browser)
         }
         None => {
@@ -381,3 +727,17 @@ pub fn create_browser(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_size_is_in_dips() {
+        assert_eq!(view_size((1920, 1080), 1.0), (1920, 1080));
+        assert_eq!(view_size((2880, 1620), 1.5), (1920, 1080));
+        assert_eq!(view_size((1, 1), 2.0), (1, 1));
+        // An unset scale factor leaves the size alone
+        assert_eq!(view_size((800, 600), 0.0), (800, 600));
+    }
+}
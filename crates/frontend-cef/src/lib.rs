@@ -22,25 +22,65 @@
 //! 2. Implement a RenderHandler that receives paint callbacks
 //! 3. Store the BGRA pixel buffer for zero-copy sharing via Arc
 //!
+//! UI→Bevy messages travel as CEF process messages: the helper's render
+//! process installs a native send function that `window.ipc.postMessage`
+//! calls, and the client's `OnProcessMessageReceived` forwards them to Bevy.
+//! The `console-ipc` feature switches back to prefixed console.log messages.
+//!
+//! With the `cef-gpu` feature (Linux), the browser is created with shared
+//! textures instead: CEF hands over a DMA-BUF per frame (`OnAcceleratedPaint`)
+//! which is returned as `CaptureResult::GpuExternal`. If the renderer can't
+//! import it, `disable_gpu_capture` recreates the browser with CPU painting.
+//!
+//! If the render process dies (`OnRenderProcessTerminated`), the backend goes
+//! to `CefState::Error` and recreates the browser after a backoff, replaying
+//! the latest state messages into the new page. After
+//! `MAX_RECOVERY_ATTEMPTS` crashes it stays in the error state.
+//!
+//! CEF runs with an external message pump: `OnScheduleMessagePumpWork`
+//! records when work is wanted, and `poll` runs `do_message_loop_work` as many
+//! times as is due (see `message_pump`), so the UI's pacing doesn't follow
+//! Bevy's frame rate.
+//!
+//! `shutdown` closes the browser and pumps the CEF message loop until its
+//! `OnBeforeClose` arrives (or `SHUTDOWN_TIMEOUT` passes), so the render and
+//! GPU processes are gone before the app exits.
+//!
 //! # References
 //!
 //! - CEF C API: https://bitbucket.org/chromiumembedded/cef/wiki/GeneralUsage
 //! - Offscreen rendering: https://bitbucket.org/chromiumembedded/cef/wiki/GeneralUsage#markdown-header-off-screen-rendering
 
+#[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+pub mod accelerated;
 pub mod browser;
 pub mod capture;
 pub mod devtools;
+pub mod message_pump;
 
-use browser::{SharedState, IPC_PREFIX};
+use browser::SharedState;
+use capture::FrameBuffers;
 use cef::{Browser, CefStringUtf16, ImplBrowser, ImplBrowserHost, ImplFrame, KeyEvent, KeyEventType, MouseButtonType};
-use pentimento_frontend_core::{CaptureResult, CompositeBackend, FrontendError};
+use pentimento_frontend_core::batch::batch_script;
+use pentimento_frontend_core::console::ConsoleForwarder;
+use pentimento_frontend_core::keyboard::{key_character, windows_key_code};
+use pentimento_frontend_core::recovery::{
+    is_state_message, CrashRecovery, StateReplayCache, MAX_RECOVERY_ATTEMPTS,
+};
+use pentimento_frontend_core::{
+    BackendLifecycle, CaptureResult, CompositeBackend, FrontendError,
+};
 use pentimento_ipc::{BevyToUi, KeyboardEvent, MouseButton, MouseEvent, UiToBevy};
 use std::ffi::c_int;
 use std::mem::size_of;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+/// How long `shutdown` pumps the message loop waiting for the browser to close
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// CEF webview state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CefState {
@@ -50,7 +90,7 @@ pub enum CefState {
     Loading,
     /// Ready for capture
     Ready,
-    /// CEF encountered an error
+    /// CEF encountered an error (or the render process crashed)
     Error,
 }
 
@@ -62,6 +102,8 @@ pub enum CefState {
 /// - More consistent rendering across platforms
 /// - GPU-accelerated compositing options
 pub struct CefBackend {
+    /// Page content, kept to recreate the browser on GPU capture fallback
+    html_content: String,
     size: (u32, u32),
     state: CefState,
     shared: Arc<SharedState>,
@@ -69,6 +111,13 @@ pub struct CefBackend {
     from_ui_tx: mpsc::UnboundedSender<UiToBevy>,
     from_ui_rx: mpsc::UnboundedReceiver<UiToBevy>,
     to_ui_messages: Vec<BevyToUi>,
+    /// Latest state messages, replayed after a crash
+    replay_cache: StateReplayCache,
+    recovery: CrashRecovery,
+    /// Why the render process died, while a reload is pending
+    crash_reason: Option<String>,
+    /// Set by `shutdown`; the browser is closed and never recreated
+    shut_down: bool,
 }
 
 impl CefBackend {
@@ -86,17 +135,25 @@ impl CefBackend {
 
         // Create shared state for communication between handlers and this struct
         let shared = Arc::new(SharedState {
-            framebuffer: Mutex::new(None),
-            framebuffer_size: Mutex::new((0, 0)),
+            frames: FrameBuffers::new(),
+            #[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+            gpu_frames: accelerated::GpuFrameSlot::new(true),
             dirty: Arc::new(AtomicBool::new(false)),
             size: Mutex::new(size),
+            scale_factor: Mutex::new(1.0),
             from_ui_tx: from_ui_tx.clone(),
+            terminated: Mutex::new(None),
+            console: Mutex::new(ConsoleForwarder::new()),
+            console_logs: Mutex::new(Vec::new()),
+            open_browsers: AtomicUsize::new(0),
         });
 
         // Create the browser
-        let browser = browser::create_browser(html_content, size, &shared)?;
+        let browser =
+            browser::create_browser(html_content, size, &shared, Self::shared_textures(&shared))?;
 
         Ok(Self {
+            html_content: html_content.to_string(),
             size,
             state: CefState::Loading,
             shared,
@@ -104,6 +161,10 @@ impl CefBackend {
             from_ui_tx,
             from_ui_rx,
             to_ui_messages: Vec::new(),
+            replay_cache: StateReplayCache::new(),
+            recovery: CrashRecovery::new(),
+            crash_reason: None,
+            shut_down: false,
         })
     }
 
@@ -112,6 +173,108 @@ impl CefBackend {
         self.state
     }
 
+    /// Whether browsers should be created with shared textures
+    #[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+    fn shared_textures(shared: &SharedState) -> bool {
+        shared.gpu_frames.is_enabled()
+    }
+
+    #[cfg(not(all(feature = "cef-gpu", target_os = "linux")))]
+    fn shared_textures(_shared: &SharedState) -> bool {
+        false
+    }
+
+    /// Close the current browser and create a new one with the same content
+    ///
+    /// The page reloads, so the backend goes back to `Loading` until the first
+    /// paint of the new browser.
+    fn recreate_browser(&mut self) {
+        if let Some(browser) = self.browser.take() {
+            if let Some(host) = browser.host() {
+                host.close_browser(1); // force_close = true (as c_int)
+            }
+        }
+        capture::clear_framebuffer(&self.shared);
+
+        let shared_textures = Self::shared_textures(&self.shared);
+        match browser::create_browser(&self.html_content, self.size, &self.shared, shared_textures)
+        {
+            Ok(browser) => {
+                self.browser = Some(browser);
+                self.state = CefState::Loading;
+            }
+            Err(e) => {
+                tracing::error!("Failed to recreate CEF browser: {}", e);
+                self.state = CefState::Error;
+                // No browser left to reload into
+                self.crash_reason = None;
+            }
+        }
+    }
+
+    /// Stop using the dead page and schedule a reload, unless out of attempts
+    fn handle_render_process_crash(&mut self, reason: String) {
+        self.state = CefState::Error;
+        // Queued messages were meant for the dead page; state is replayed
+        // from the cache once the new page is up
+        self.to_ui_messages.clear();
+
+        if self.recovery.crashed(Instant::now()) {
+            tracing::warn!(
+                "Reloading UI after render process crash (attempt {} of {})",
+                self.recovery.attempts(),
+                MAX_RECOVERY_ATTEMPTS
+            );
+            self.crash_reason = Some(reason);
+        } else {
+            tracing::error!(
+                "UI render process crashed more than {} times, not reloading again",
+                MAX_RECOVERY_ATTEMPTS
+            );
+            self.crash_reason = None;
+        }
+    }
+
+    /// Queue the cached state for the reloaded page, followed by the crash
+    /// error and recovery notice
+    fn queue_replay(&mut self, reason: &str) {
+        let mut messages = self.replay_cache.messages().to_vec();
+        messages.push(self.recovery.crash_error(reason));
+        messages.push(self.recovery.recovered_notice());
+        // State sent during the reload is already in the cache
+        messages.extend(
+            self.to_ui_messages
+                .drain(..)
+                .filter(|msg| !is_state_message(msg)),
+        );
+        self.to_ui_messages = messages;
+    }
+
+    /// Body of `window.ipc.postMessage`: hand the message to the native
+    /// function the helper's render process installed
+    #[cfg(not(feature = "console-ipc"))]
+    fn post_message_js() -> String {
+        format!(
+            r#"
+                        // Sent to the browser process as a CEF process message
+                        window.{}(String(message));
+            "#,
+            browser::IPC_SEND_FUNCTION
+        )
+    }
+
+    /// Body of `window.ipc.postMessage` for the console.log fallback
+    #[cfg(feature = "console-ipc")]
+    fn post_message_js() -> String {
+        format!(
+            r#"
+                        // Send IPC messages via console.log with our special prefix
+                        console.log('{}' + message);
+            "#,
+            browser::IPC_PREFIX
+        )
+    }
+
     /// Inject the JavaScript IPC bridge that mimics wry's window.ipc.postMessage()
     fn inject_ipc_bridge(&self) {
         let ipc_bridge_js = format!(
@@ -120,10 +283,7 @@ impl CefBackend {
                 if (window.ipc) return; // Already injected
 
                 window.ipc = {{
-                    postMessage: function(message) {{
-                        // Send IPC messages via console.log with our special prefix
-                        console.log('{}' + message);
-                    }}
+                    postMessage: function(message) {{{}}}
                 }};
 
                 // Also trigger initial UiDirty to signal that IPC is ready
@@ -132,7 +292,7 @@ impl CefBackend {
                 console.log('Pentimento IPC bridge initialized');
             }})();
             "#,
-            IPC_PREFIX
+            Self::post_message_js()
         );
 
         if let Err(e) = self.eval(&ipc_bridge_js) {
@@ -189,37 +349,46 @@ impl CefBackend {
             .unwrap_or(false)
     }
 
-    /// Flush pending messages to the UI by evaluating JavaScript
+    /// Flush pending messages to the UI with one JavaScript evaluation
     fn flush_to_ui_messages(&mut self) {
         if self.to_ui_messages.is_empty() || self.state != CefState::Ready {
             return;
         }
 
         let messages = std::mem::take(&mut self.to_ui_messages);
-        for msg in messages {
-            match serde_json::to_string(&msg) {
-                Ok(json) => {
-                    // Call the global message handler if it exists
-                    let js = format!(
-                        r#"if (window.__PENTIMENTO_RECV__) {{ window.__PENTIMENTO_RECV__('{}'); }}"#,
-                        json.replace('\\', "\\\\").replace('\'', "\\'")
-                    );
-                    if let Err(e) = self.eval(&js) {
-                        tracing::warn!("Failed to send message to UI: {}", e);
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to serialize message for UI: {}", e);
+        match batch_script(&messages) {
+            Ok(js) => {
+                tracing::trace!("Sending {} messages to the UI in one eval", messages.len());
+                if let Err(e) = self.eval(&js) {
+                    tracing::warn!("Failed to send messages to UI: {}", e);
                 }
             }
+            Err(e) => {
+                tracing::warn!("Failed to serialize messages for UI: {}", e);
+            }
         }
     }
 }
 
 impl CompositeBackend for CefBackend {
     fn poll(&mut self) {
-        // Process CEF message loop work
-        cef::do_message_loop_work();
+        if self.shut_down {
+            return;
+        }
+
+        // Run the message loop work CEF has scheduled, which may be none this
+        // frame or several calls when Bevy runs slower than the UI
+        message_pump::pump();
+
+        // A dead render process leaves the last frame frozen on screen
+        let terminated = self.shared.terminated.lock().unwrap().take();
+        if let Some(reason) = terminated {
+            self.handle_render_process_crash(reason);
+        }
+
+        if self.state == CefState::Error && self.recovery.reload_due(Instant::now()) {
+            self.recreate_browser();
+        }
 
         // Check if we've received our first paint (framebuffer has data)
         if self.state == CefState::Loading {
@@ -229,9 +398,19 @@ impl CompositeBackend for CefBackend {
 
                 // Inject the IPC bridge into the page
                 self.inject_ipc_bridge();
+
+                if let Some(reason) = self.crash_reason.take() {
+                    self.queue_replay(&reason);
+                }
             }
         }
 
+        // Queue page console output for the UI log panel
+        let console_logs = std::mem::take(&mut *self.shared.console_logs.lock().unwrap());
+        for ui_log in console_logs {
+            let _ = self.send_to_ui(ui_log);
+        }
+
         // Flush any pending messages to the UI
         self.flush_to_ui_messages();
     }
@@ -240,6 +419,21 @@ impl CompositeBackend for CefBackend {
         self.state == CefState::Ready
     }
 
+    fn lifecycle(&self) -> BackendLifecycle {
+        match self.state {
+            CefState::Initializing => BackendLifecycle::Initializing,
+            CefState::Loading => BackendLifecycle::Loading {
+                frames_remaining: None,
+            },
+            CefState::Ready => BackendLifecycle::Ready,
+            // A crashed page is reloaded after a backoff
+            CefState::Error if self.crash_reason.is_some() => BackendLifecycle::Loading {
+                frames_remaining: None,
+            },
+            CefState::Error => BackendLifecycle::Error,
+        }
+    }
+
     fn capture_if_dirty(&mut self) -> Option<CaptureResult> {
         capture::capture_if_dirty(&self.shared)
     }
@@ -265,6 +459,43 @@ impl CompositeBackend for CefBackend {
         }
     }
 
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        {
+            let mut current = self.shared.scale_factor.lock().unwrap();
+            if scale_factor <= 0.0 || (*current - scale_factor).abs() <= f64::EPSILON {
+                return;
+            }
+            *current = scale_factor;
+        }
+        tracing::info!("CEF device scale factor changed to {:.2}", scale_factor);
+
+        // CEF re-reads `ScreenInfo` and, with the new scale, the view size in DIPs
+        if let Some(browser) = &self.browser {
+            if let Some(host) = browser.host() {
+                host.notify_screen_info_changed();
+                host.was_resized();
+            }
+        }
+    }
+
+    fn set_background(&mut self, background: bool) {
+        // Hidden browsers stop painting; CEF repaints the view when shown again
+        if let Some(browser) = &self.browser {
+            if let Some(host) = browser.host() {
+                host.was_hidden(background as c_int);
+            }
+        }
+    }
+
+    fn disable_gpu_capture(&mut self) {
+        #[cfg(all(feature = "cef-gpu", target_os = "linux"))]
+        if self.shared.gpu_frames.is_enabled() {
+            tracing::warn!("Renderer can't import CEF shared textures, falling back to CPU paint");
+            self.shared.gpu_frames.disable();
+            self.recreate_browser();
+        }
+    }
+
     fn send_mouse_event(&mut self, event: MouseEvent) {
         let Some(browser) = &self.browser else { return };
         let Some(host) = browser.host() else { return };
@@ -324,23 +555,14 @@ impl CompositeBackend for CefBackend {
         let Some(browser) = &self.browser else { return };
         let Some(host) = browser.host() else { return };
 
-        // Get the character from the key string
-        let char_code = event.key.chars().next().unwrap_or('\0');
-
-        // For Windows virtual key codes, letter keys must use uppercase (VK_KEY_A = 65, not 97)
-        // This is how Windows virtual key codes work - they're always uppercase for letters
-        let vk_code = if char_code.is_ascii_lowercase() {
-            char_code.to_ascii_uppercase() as c_int
-        } else {
-            char_code as c_int
-        };
+        // Virtual key code from the physical key, so it doesn't depend on the layout
+        let vk_code = windows_key_code(&event.code) as c_int;
 
-        // The actual character that would be typed (depends on shift state)
-        let typed_char = if event.modifiers.shift && char_code.is_ascii_lowercase() {
-            char_code.to_ascii_uppercase()
-        } else {
-            char_code
-        };
+        // The logical key is already layout- and shift-aware ("a" or "A" on the
+        // same key), and is None for named keys and dead keys
+        let character = key_character(&event.key);
+        let typed_char = character.map_or(0, |c| c as u16);
+        let unmodified_char = character.map_or(0, |c| c.to_lowercase().next().unwrap_or(c) as u16);
 
         // Build modifiers from the event
         let mut modifiers: u32 = 0;
@@ -365,14 +587,15 @@ impl CompositeBackend for CefBackend {
             windows_key_code: vk_code,
             native_key_code: 0, // Platform-specific, not needed for basic input
             is_system_key: 0,
-            character: typed_char as u16,
-            unmodified_character: char_code as u16,
+            character: typed_char,
+            unmodified_character: unmodified_char,
             focus_on_editable_field: 0,
         };
         host.send_key_event(Some(&key_event));
 
-        // Also send char event for key presses (for text input)
-        if event.pressed && !event.key.is_empty() && char_code != '\0' {
+        // Also send char event for key presses (for text input). Dead keys
+        // only type once composed, which is left to IME handling.
+        if event.pressed && character.is_some() {
             let char_event = KeyEvent {
                 size: size_of::<KeyEvent>(),
                 type_: KeyEventType::CHAR,
@@ -380,8 +603,8 @@ impl CompositeBackend for CefBackend {
                 windows_key_code: vk_code,
                 native_key_code: 0,
                 is_system_key: 0,
-                character: typed_char as u16,
-                unmodified_character: char_code as u16,
+                character: typed_char,
+                unmodified_character: unmodified_char,
                 focus_on_editable_field: 0,
             };
             host.send_key_event(Some(&char_event));
@@ -389,6 +612,11 @@ impl CompositeBackend for CefBackend {
     }
 
     fn send_to_ui(&mut self, msg: BevyToUi) -> Result<(), FrontendError> {
+        self.replay_cache.record(&msg);
+        if self.recovery.gave_up() {
+            return Err(FrontendError::NotReady);
+        }
+
         // Queue the message for sending during poll()
         self.to_ui_messages.push(msg);
         Ok(())
@@ -397,6 +625,37 @@ impl CompositeBackend for CefBackend {
     fn try_recv_from_ui(&mut self) -> Option<UiToBevy> {
         self.from_ui_rx.try_recv().ok()
     }
+
+    fn shutdown(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+        self.state = CefState::Error;
+        self.crash_reason = None;
+        self.to_ui_messages.clear();
+
+        if let Some(browser) = self.browser.take() {
+            tracing::info!("Closing CEF browser for shutdown");
+            if let Some(host) = browser.host() {
+                host.close_browser(1); // force_close = true (as c_int)
+            }
+        }
+
+        // Browsers closed by a recreate may still be closing too
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while self.shared.open_browsers.load(Ordering::SeqCst) > 0 {
+            if Instant::now() >= deadline {
+                tracing::warn!(
+                    "CEF browser did not close within {:?}, exiting anyway",
+                    SHUTDOWN_TIMEOUT
+                );
+                break;
+            }
+            cef::do_message_loop_work();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
 }
 
 impl Drop for CefBackend {
@@ -0,0 +1,170 @@
+//! External message pump scheduling
+//!
+//! CEF runs with `external_message_pump`, so it asks for
+//! `do_message_loop_work()` calls through `OnScheduleMessagePumpWork(delay_ms)`
+//! instead of running its own loop. The request can arrive on any thread; the
+//! work call itself must happen on the thread that initialized CEF, so
+//! `CefBackend::poll` drains the due work on the main thread within a small
+//! time budget rather than handing it to a timer thread.
+//!
+//! CEF registers one browser process handler for the whole process, so the
+//! schedule is a process global rather than part of a backend's `SharedState`.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Longest time between work calls even if CEF never asks, matching the
+/// fallback timer in CEF's own external pump sample
+pub const MAX_PUMP_INTERVAL: Duration = Duration::from_millis(1000 / 30);
+
+/// Time `pump` may spend running back-to-back work calls in one frame
+pub const PUMP_BUDGET: Duration = Duration::from_millis(4);
+
+/// Upper bound on work calls per `pump`, in case each call is very cheap but
+/// CEF keeps rescheduling immediately
+pub const MAX_WORK_CALLS: usize = 16;
+
+/// When CEF next wants `do_message_loop_work()` to run
+#[derive(Debug, Default)]
+pub struct PumpSchedule {
+    /// Requested deadline, replaced by every `OnScheduleMessagePumpWork`
+    deadline: Option<Instant>,
+    /// When the last work call finished
+    last_work: Option<Instant>,
+}
+
+impl PumpSchedule {
+    /// Record a `OnScheduleMessagePumpWork(delay_ms)` request. A delay of zero
+    /// or less means as soon as possible.
+    pub fn schedule(&mut self, now: Instant, delay_ms: i64) {
+        let delay = Duration::from_millis(delay_ms.max(0) as u64).min(MAX_PUMP_INTERVAL);
+        self.deadline = Some(now + delay);
+    }
+
+    /// Whether a work call is due, either requested or from the fallback interval
+    pub fn is_due(&self, now: Instant) -> bool {
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            return true;
+        }
+        match self.last_work {
+            Some(last) => now.duration_since(last) >= MAX_PUMP_INTERVAL,
+            None => true,
+        }
+    }
+
+    /// Clear the deadline before a work call. Requests made during the call
+    /// set a new one.
+    pub fn start_work(&mut self) {
+        self.deadline = None;
+    }
+
+    /// Record that a work call finished at `now`
+    pub fn finish_work(&mut self, now: Instant) {
+        self.last_work = Some(now);
+    }
+}
+
+static SCHEDULE: Mutex<PumpSchedule> = Mutex::new(PumpSchedule {
+    deadline: None,
+    last_work: None,
+});
+
+/// Called from `OnScheduleMessagePumpWork`, on any thread
+pub fn schedule_work(delay_ms: i64) {
+    SCHEDULE.lock().unwrap().schedule(Instant::now(), delay_ms);
+}
+
+/// Run the message loop work that is due, zero or more times, and return how
+/// many work calls were made
+///
+/// Must be called on the thread that initialized CEF.
+pub fn pump() -> usize {
+    let start = Instant::now();
+    let mut calls = 0;
+    while calls < MAX_WORK_CALLS {
+        let now = Instant::now();
+        if calls > 0 && now.duration_since(start) >= PUMP_BUDGET {
+            break;
+        }
+        {
+            let mut schedule = SCHEDULE.lock().unwrap();
+            if !schedule.is_due(now) {
+                break;
+            }
+            schedule.start_work();
+        }
+        // The lock is released here; CEF may call `schedule_work` re-entrantly
+        cef::do_message_loop_work();
+        SCHEDULE.lock().unwrap().finish_work(Instant::now());
+        calls += 1;
+    }
+    if calls > 1 {
+        tracing::trace!("Ran {} CEF work calls in {:?}", calls, start.elapsed());
+    }
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_pump_is_due() {
+        let schedule = PumpSchedule::default();
+        assert!(schedule.is_due(Instant::now()));
+    }
+
+    #[test]
+    fn test_delayed_request_waits_for_deadline() {
+        let now = Instant::now();
+        let mut schedule = PumpSchedule::default();
+        schedule.start_work();
+        schedule.finish_work(now);
+        schedule.schedule(now, 10);
+
+        assert!(!schedule.is_due(now + Duration::from_millis(5)));
+        assert!(schedule.is_due(now + Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_zero_delay_is_due_immediately() {
+        let now = Instant::now();
+        let mut schedule = PumpSchedule::default();
+        schedule.finish_work(now);
+        schedule.schedule(now, 0);
+        assert!(schedule.is_due(now));
+
+        schedule.schedule(now, -1);
+        assert!(schedule.is_due(now));
+    }
+
+    #[test]
+    fn test_no_request_is_not_due_until_fallback_interval() {
+        let now = Instant::now();
+        let mut schedule = PumpSchedule::default();
+        schedule.start_work();
+        schedule.finish_work(now);
+
+        assert!(!schedule.is_due(now + Duration::from_millis(1)));
+        assert!(schedule.is_due(now + MAX_PUMP_INTERVAL));
+    }
+
+    #[test]
+    fn test_long_delay_is_capped_at_fallback_interval() {
+        let now = Instant::now();
+        let mut schedule = PumpSchedule::default();
+        schedule.finish_work(now);
+        schedule.schedule(now, 10_000);
+        assert!(schedule.is_due(now + MAX_PUMP_INTERVAL));
+    }
+
+    #[test]
+    fn test_start_work_clears_request() {
+        let now = Instant::now();
+        let mut schedule = PumpSchedule::default();
+        schedule.schedule(now, 0);
+        schedule.start_work();
+        schedule.finish_work(now);
+        assert!(!schedule.is_due(now));
+    }
+}
@@ -0,0 +1,58 @@
+//! Delivering a frame's messages to the UI in one script
+//!
+//! Every `evaluate_javascript` round trip costs more than the message it
+//! carries, so backends queue `BevyToUi` messages and flush them once per
+//! poll with `batch_script`. The page's bridge registers
+//! `__PENTIMENTO_RECV_BATCH__`; a page built before batching only has
+//! `__PENTIMENTO_RECEIVE__`, which the script falls back to per message.
+
+use pentimento_ipc::BevyToUi;
+
+/// Global function the UI bridge registers to receive a batch
+pub const RECV_BATCH_FUNCTION: &str = "__PENTIMENTO_RECV_BATCH__";
+
+/// Script handing `messages` to the page as one JSON array string
+pub fn batch_script(messages: &[BevyToUi]) -> Result<String, serde_json::Error> {
+    let batch = serde_json::to_string(messages)?;
+    // A JSON string is also a valid JavaScript string literal
+    let literal = serde_json::to_string(&batch)?;
+    Ok(format!(
+        "(function (batch) {{ \
+           if (window.{batch_fn}) {{ window.{batch_fn}(batch); }} \
+           else if (window.__PENTIMENTO_RECEIVE__) {{ \
+             JSON.parse(batch).forEach(function (msg) {{ \
+               window.__PENTIMENTO_RECEIVE__(JSON.stringify(msg)); \
+             }}); \
+           }} \
+           else {{ console.warn('{batch_fn} not defined, UI messages dropped'); }} \
+         }})({literal});",
+        batch_fn = RECV_BATCH_FUNCTION,
+        literal = literal,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_script_embeds_messages_once() {
+        let messages = [
+            BevyToUi::CloseMenus,
+            BevyToUi::Warning {
+                code: "quote".to_string(),
+                message: "it's \"quoted\"\n</script>".to_string(),
+            },
+        ];
+        let script = batch_script(&messages).unwrap();
+
+        // The argument is the string literal of the serialized array
+        let literal = script
+            .rsplit_once("})(")
+            .and_then(|(_, rest)| rest.strip_suffix(");"))
+            .unwrap();
+        let batch: String = serde_json::from_str(literal).unwrap();
+        assert_eq!(batch, serde_json::to_string(&messages).unwrap());
+        assert!(script.contains("window.__PENTIMENTO_RECV_BATCH__(batch)"));
+    }
+}
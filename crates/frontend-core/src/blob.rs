@@ -0,0 +1,196 @@
+//! Binary side channel for large UI payloads
+//!
+//! Image data is too slow to push through JSON (base64 alone inflates it by a
+//! third), so Bevy stores the bytes in a `BlobRegistry` and only announces
+//! them with `BevyToUi::BinaryBlob`. Webview backends serve registered blobs
+//! at `pentimento-blob://<id>` for the page to `fetch()`; Dioxus reads the
+//! `Arc` straight from the registry. The UI acknowledges with
+//! `UiToBevy::BlobConsumed`, and blobs nobody claims expire after the TTL.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use pentimento_ipc::{BevyToUi, BlobKind};
+
+/// URL scheme webview backends serve blobs under
+pub const BLOB_SCHEME: &str = "pentimento-blob";
+
+/// How long an unconsumed blob stays available
+pub const DEFAULT_BLOB_TTL: Duration = Duration::from_secs(10);
+
+/// `pentimento-blob://<id>`
+pub fn blob_url(id: u64) -> String {
+    format!("{}://{}", BLOB_SCHEME, id)
+}
+
+/// Blob ID of a `pentimento-blob://` URL (trailing slash and query allowed)
+pub fn blob_id_from_url(url: &str) -> Option<u64> {
+    let rest = url.strip_prefix(BLOB_SCHEME)?.strip_prefix("://")?;
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+struct StoredBlob {
+    bytes: Arc<[u8]>,
+    kind: BlobKind,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct BlobStore {
+    next_id: u64,
+    blobs: HashMap<u64, StoredBlob>,
+}
+
+/// Blobs waiting to be read by the UI
+///
+/// Cloning is cheap and every clone shares the same blobs, so the app and
+/// the backend's scheme handler can each hold one.
+#[derive(Clone)]
+pub struct BlobRegistry {
+    store: Arc<Mutex<BlobStore>>,
+    ttl: Duration,
+}
+
+impl Default for BlobRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_BLOB_TTL)
+    }
+}
+
+impl BlobRegistry {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(BlobStore::default())),
+            ttl,
+        }
+    }
+
+    /// Store `bytes` until `now + ttl` and return the `BinaryBlob`
+    /// announcement to send to the UI
+    pub fn insert(&self, kind: BlobKind, bytes: Arc<[u8]>, now: Instant) -> BevyToUi {
+        let mut store = self.store.lock().unwrap();
+        store.next_id += 1;
+        let id = store.next_id;
+        let byte_length = bytes.len() as u64;
+        store.blobs.insert(
+            id,
+            StoredBlob {
+                bytes,
+                kind: kind.clone(),
+                expires_at: now + self.ttl,
+            },
+        );
+        BevyToUi::BinaryBlob {
+            id,
+            kind,
+            byte_length,
+        }
+    }
+
+    /// Bytes of blob `id`, shared rather than copied
+    pub fn get(&self, id: u64) -> Option<Arc<[u8]>> {
+        let store = self.store.lock().unwrap();
+        store.blobs.get(&id).map(|blob| Arc::clone(&blob.bytes))
+    }
+
+    /// What blob `id` contains
+    pub fn kind(&self, id: u64) -> Option<BlobKind> {
+        let store = self.store.lock().unwrap();
+        store.blobs.get(&id).map(|blob| blob.kind.clone())
+    }
+
+    /// Bytes of the blob a `pentimento-blob://` URL points at
+    pub fn get_url(&self, url: &str) -> Option<Arc<[u8]>> {
+        blob_id_from_url(url).and_then(|id| self.get(id))
+    }
+
+    /// Drop blob `id` after the UI acknowledged it; false if already gone
+    pub fn consume(&self, id: u64) -> bool {
+        self.store.lock().unwrap().blobs.remove(&id).is_some()
+    }
+
+    /// Drop blobs whose TTL ran out by `now`, returning how many
+    pub fn evict_expired(&self, now: Instant) -> usize {
+        let mut store = self.store.lock().unwrap();
+        let before = store.blobs.len();
+        store.blobs.retain(|_, blob| blob.expires_at > now);
+        before - store.blobs.len()
+    }
+
+    /// Blobs currently stored
+    pub fn len(&self) -> usize {
+        self.store.lock().unwrap().blobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview() -> BlobKind {
+        BlobKind::DiffusionPreview {
+            task_id: "task".to_string(),
+            width: 2,
+            height: 1,
+        }
+    }
+
+    #[test]
+    fn test_blob_url_round_trip() {
+        assert_eq!(blob_url(42), "pentimento-blob://42");
+        assert_eq!(blob_id_from_url("pentimento-blob://42"), Some(42));
+        assert_eq!(blob_id_from_url("pentimento-blob://42/"), Some(42));
+        assert_eq!(blob_id_from_url("pentimento-blob://42?t=1"), Some(42));
+        assert_eq!(blob_id_from_url("https://42"), None);
+        assert_eq!(blob_id_from_url("pentimento-blob://preview"), None);
+    }
+
+    #[test]
+    fn test_insert_shares_bytes_until_consumed() {
+        let registry = BlobRegistry::default();
+        let bytes: Arc<[u8]> = Arc::from(vec![0u8; 8]);
+        let announcement = registry.insert(preview(), Arc::clone(&bytes), Instant::now());
+
+        let BevyToUi::BinaryBlob {
+            id, byte_length, ..
+        } = announcement
+        else {
+            panic!("expected BinaryBlob, got {:?}", announcement);
+        };
+        assert_eq!(byte_length, 8);
+        assert!(Arc::ptr_eq(&registry.get(id).unwrap(), &bytes));
+        assert!(registry.get_url(&blob_url(id)).is_some());
+        assert_eq!(registry.kind(id), Some(preview()));
+
+        assert!(registry.consume(id));
+        assert!(!registry.consume(id));
+        assert!(registry.get(id).is_none());
+    }
+
+    #[test]
+    fn test_evict_expired_keeps_fresh_blobs() {
+        let registry = BlobRegistry::new(Duration::from_secs(1));
+        let start = Instant::now();
+        registry.insert(preview(), Arc::from(vec![1u8]), start);
+        registry.insert(
+            preview(),
+            Arc::from(vec![2u8]),
+            start + Duration::from_millis(600),
+        );
+
+        assert_eq!(
+            registry.evict_expired(start + Duration::from_millis(999)),
+            0
+        );
+        assert_eq!(registry.evict_expired(start + Duration::from_secs(1)), 1);
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.evict_expired(start + Duration::from_secs(2)), 1);
+        assert!(registry.is_empty());
+    }
+}
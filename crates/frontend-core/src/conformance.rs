@@ -0,0 +1,517 @@
+//! Conformance script for `CompositeBackend` implementations (`test-util` feature)
+//!
+//! Backends differ in subtle ways: when they report dirty, how long a resize
+//! takes to settle, which coordinate space input arrives in. `run_conformance`
+//! drives a backend through the script every backend must pass: load
+//! `TEST_PAGE_HTML`, wait for ready, check the captured color at several
+//! points, resize twice, click, round-trip a message through the page, and
+//! shut down (twice, then keep polling and sending input). Backends that never capture pixels (`CompositorManaged`) skip the pixel
+//! checks through `BackendCaps`.
+//!
+//! `run_color_check` loads `COLOR_PATTERN_HTML` instead and checks that each
+//! swatch is captured as its exact sRGB value: capture backends must deliver
+//! sRGB-encoded bytes with no color management applied.
+//!
+//! Failures panic with the step that failed, so the harness is meant to be
+//! called from a test. Real backends need a display, so their runs are
+//! `#[ignore]`d tests in their own crates; the harness itself is tested
+//! against `MockBackend`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use pentimento_ipc::{BevyToUi, MouseButton, MouseEvent, QueryKind, UiToBevy};
+
+use crate::{CaptureResult, CompositeBackend};
+
+/// Size the test page should be loaded at
+pub const TEST_PAGE_SIZE: (u32, u32) = (320, 240);
+
+/// Background color of the test page (RGBA)
+pub const TEST_PAGE_COLOR: [u8; 4] = [32, 160, 96, 255];
+
+/// `request_id` of the `Query` the test page posts when clicked
+pub const CLICK_REQUEST_ID: u64 = 4242;
+
+/// Protocol version sent to the test page, which echoes it back
+pub const ECHO_VERSION: u32 = 9999;
+
+/// Test page: a solid background that reports clicks as an `ObjectAtPoint`
+/// query (at the click's CSS position) and echoes `ProtocolVersion`
+pub const TEST_PAGE_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <style>
+        html, body {
+            margin: 0;
+            width: 100%;
+            height: 100%;
+            background: rgb(32, 160, 96);
+        }
+    </style>
+</head>
+<body>
+    <script>
+        function post(msg) {
+            if (window.ipc) window.ipc.postMessage(JSON.stringify(msg));
+        }
+        document.addEventListener('click', function (e) {
+            post({
+                type: 'Query',
+                data: { request_id: 4242, query: { ObjectAtPoint: { x: e.clientX, y: e.clientY } } }
+            });
+        });
+        window.__PENTIMENTO_RECV_BATCH__ = function (batch) {
+            JSON.parse(batch).forEach(function (msg) {
+                if (msg.type === 'ProtocolVersion') post({ type: 'ProtocolVersion', data: msg.data });
+            });
+        };
+    </script>
+</body>
+</html>"#;
+
+/// Colors of the `COLOR_PATTERN_HTML` stripes, left to right (sRGB)
+pub const COLOR_PATTERN_SWATCHES: [[u8; 3]; 6] = [
+    [0xff, 0x00, 0x00],
+    [0x00, 0xff, 0x00],
+    [0x00, 0x00, 0xff],
+    [0x80, 0x80, 0x80],
+    [0x33, 0x66, 0xcc],
+    [0xf5, 0xde, 0xb3],
+];
+
+/// Test pattern: one full-height stripe of equal width per swatch
+pub const COLOR_PATTERN_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <style>
+        html, body {
+            margin: 0;
+            width: 100%;
+            height: 100%;
+        }
+        body {
+            display: flex;
+        }
+        div {
+            flex: 1;
+        }
+    </style>
+</head>
+<body>
+    <div style="background: #ff0000"></div>
+    <div style="background: #00ff00"></div>
+    <div style="background: #0000ff"></div>
+    <div style="background: #808080"></div>
+    <div style="background: #3366cc"></div>
+    <div style="background: #f5deb3"></div>
+</body>
+</html>"#;
+
+/// Per-channel difference allowed for a swatch (one 8-bit step)
+const SWATCH_TOLERANCE: u8 = 1;
+
+/// Sizes the backend is resized to, in order
+const RESIZES: [(u32, u32); 2] = [(400, 300), (200, 150)];
+
+/// Points whose color is checked, as fractions of the capture size
+const SAMPLE_POINTS: [(f32, f32); 5] = [(0.5, 0.5), (0.1, 0.1), (0.9, 0.1), (0.1, 0.9), (0.9, 0.9)];
+
+/// Per-channel difference allowed for color management and dithering
+const COLOR_TOLERANCE: u8 = 2;
+
+/// Where the page is clicked, in CSS pixels (physical at scale factor 1)
+const CLICK_POINT: (f32, f32) = (40.0, 30.0);
+
+/// What a backend can do, for steps that don't apply to every backend
+#[derive(Debug, Clone)]
+pub struct BackendCaps {
+    /// Captures return pixels; false for `CompositorManaged` backends
+    pub pixel_capture: bool,
+    /// Polls to wait for each step before failing
+    pub max_polls: u32,
+    /// Sleep between polls, for backends whose page runs on another thread
+    /// or process
+    pub poll_interval: Duration,
+}
+
+impl Default for BackendCaps {
+    fn default() -> Self {
+        Self {
+            pixel_capture: true,
+            max_polls: 500,
+            poll_interval: Duration::from_millis(10),
+        }
+    }
+}
+
+impl BackendCaps {
+    /// Caps of a backend the compositor draws (no pixel capture)
+    pub fn compositor_managed() -> Self {
+        Self {
+            pixel_capture: false,
+            ..Self::default()
+        }
+    }
+}
+
+/// Run the conformance script on a backend made by `factory`
+///
+/// `factory` must load `TEST_PAGE_HTML` at `TEST_PAGE_SIZE`. Panics at the
+/// first step the backend fails.
+pub fn run_conformance<B: CompositeBackend>(factory: impl Fn() -> B, caps: BackendCaps) {
+    let mut run = Run {
+        backend: factory(),
+        caps,
+    };
+    // The script compares CPU pixels and expects CSS and physical pixels to match
+    run.backend.disable_gpu_capture();
+    run.backend.set_scale_factor(1.0);
+
+    run.wait_ready("the test page to load");
+    run.check_capture(TEST_PAGE_SIZE);
+
+    for (width, height) in RESIZES {
+        run.backend.resize(width, height);
+        assert_eq!(
+            run.backend.size(),
+            (width, height),
+            "size() should follow resize()"
+        );
+        run.wait_ready("the resize to settle");
+        run.check_capture((width, height));
+    }
+
+    run.check_click();
+    run.check_round_trip();
+    run.check_shutdown();
+}
+
+/// Check the colors a backend made by `factory` captures
+///
+/// `factory` must load `COLOR_PATTERN_HTML` at `TEST_PAGE_SIZE`. Samples the
+/// middle of each stripe and panics if any channel is off by more than one
+/// step. Backends without pixel capture only have to load the page.
+pub fn run_color_check<B: CompositeBackend>(factory: impl Fn() -> B, caps: BackendCaps) {
+    let mut run = Run {
+        backend: factory(),
+        caps,
+    };
+    run.backend.disable_gpu_capture();
+    run.backend.set_scale_factor(1.0);
+
+    run.wait_ready("the color pattern to load");
+    if run.caps.pixel_capture {
+        let (pixels, width, height) = run.capture_rgba(TEST_PAGE_SIZE);
+        let stripe_width = width as f32 / COLOR_PATTERN_SWATCHES.len() as f32;
+        for (index, swatch) in COLOR_PATTERN_SWATCHES.iter().enumerate() {
+            let x = ((index as f32 + 0.5) * stripe_width) as u32;
+            let y = height / 2;
+            let i = (y as usize * width as usize + x as usize) * 4;
+            let pixel = &pixels[i..i + 4];
+            let matches = pixel[3] == 255
+                && pixel
+                    .iter()
+                    .zip(swatch)
+                    .all(|(&got, &want)| got.abs_diff(want) <= SWATCH_TOLERANCE);
+            assert!(
+                matches,
+                "swatch {} at ({}, {}) is {:?}, expected #{:02x}{:02x}{:02x}",
+                index, x, y, pixel, swatch[0], swatch[1], swatch[2]
+            );
+        }
+    }
+    run.backend.shutdown();
+}
+
+struct Run<B> {
+    backend: B,
+    caps: BackendCaps,
+}
+
+impl<B: CompositeBackend> Run<B> {
+    /// Poll until `step` returns something, panicking after `max_polls` polls
+    fn poll_until<T>(&mut self, what: &str, mut step: impl FnMut(&mut B) -> Option<T>) -> T {
+        for _ in 0..self.caps.max_polls {
+            self.backend.poll();
+            if let Some(value) = step(&mut self.backend) {
+                return value;
+            }
+            std::thread::sleep(self.caps.poll_interval);
+        }
+        panic!(
+            "gave up waiting for {} after {} polls",
+            what, self.caps.max_polls
+        );
+    }
+
+    fn wait_ready(&mut self, what: &str) {
+        self.poll_until(what, |backend| backend.is_ready().then_some(()));
+    }
+
+    /// Capture a frame of `size` and check its color
+    fn check_capture(&mut self, size: (u32, u32)) {
+        if !self.caps.pixel_capture {
+            self.backend.mark_dirty();
+            self.backend.poll();
+            if let Some(capture) = self.backend.capture_if_dirty() {
+                assert!(
+                    matches!(capture, CaptureResult::CompositorManaged),
+                    "backend without pixel capture returned {:?}",
+                    capture
+                );
+            }
+            return;
+        }
+
+        let (pixels, width, height) = self.capture_rgba(size);
+
+        for (fx, fy) in SAMPLE_POINTS {
+            let x = ((width as f32 * fx) as u32).min(width - 1);
+            let y = ((height as f32 * fy) as u32).min(height - 1);
+            let i = (y as usize * width as usize + x as usize) * 4;
+            let pixel = &pixels[i..i + 4];
+            let matches = pixel
+                .iter()
+                .zip(TEST_PAGE_COLOR)
+                .all(|(&got, want)| got.abs_diff(want) <= COLOR_TOLERANCE);
+            assert!(
+                matches,
+                "pixel ({}, {}) of the {}x{} capture is {:?}, expected {:?}",
+                x, y, width, height, pixel, TEST_PAGE_COLOR
+            );
+        }
+    }
+
+    /// Capture a CPU frame of `size` as RGBA
+    fn capture_rgba(&mut self, size: (u32, u32)) -> (Vec<u8>, u32, u32) {
+        let what = format!("a {}x{} capture", size.0, size.1);
+        self.poll_until(&what, |backend| {
+            backend.mark_dirty();
+            let (pixels, width, height) = rgba_pixels(backend.capture_if_dirty()?);
+            // Frames rendered before the resize settled may still arrive
+            ((width, height) == size).then_some((pixels, width, height))
+        })
+    }
+
+    /// Click the page and wait for its click handler's query
+    fn check_click(&mut self) {
+        let (x, y) = CLICK_POINT;
+        let button = MouseButton::Left;
+        self.backend.send_mouse_event(MouseEvent::Move { x, y });
+        self.backend
+            .send_mouse_event(MouseEvent::ButtonDown { button, x, y });
+        self.backend
+            .send_mouse_event(MouseEvent::ButtonUp { button, x, y });
+
+        let query = self.poll_until("the page to report the click", |backend| {
+            std::iter::from_fn(|| backend.try_recv_from_ui()).find_map(|msg| match msg {
+                UiToBevy::Query { request_id, query } if request_id == CLICK_REQUEST_ID => {
+                    Some(query)
+                }
+                _ => None,
+            })
+        });
+        match query {
+            QueryKind::ObjectAtPoint {
+                x: page_x,
+                y: page_y,
+            } => assert!(
+                (page_x - x).abs() <= 1.0 && (page_y - y).abs() <= 1.0,
+                "page saw the click at ({}, {}), sent at ({}, {})",
+                page_x,
+                page_y,
+                x,
+                y
+            ),
+            other => panic!("page reported the click as {:?}", other),
+        }
+    }
+
+    /// Send the page a message and wait for its echo
+    fn check_round_trip(&mut self) {
+        self.backend
+            .send_to_ui(BevyToUi::ProtocolVersion {
+                version: ECHO_VERSION,
+            })
+            .expect("send_to_ui failed");
+        self.poll_until("the page to echo ProtocolVersion", |backend| {
+            std::iter::from_fn(|| backend.try_recv_from_ui()).find_map(|msg| match msg {
+                UiToBevy::ProtocolVersion {
+                    version: ECHO_VERSION,
+                } => Some(()),
+                _ => None,
+            })
+        });
+    }
+
+    /// Shut down twice, then check the backend tolerates the calls the app
+    /// may still make before it is dropped
+    fn check_shutdown(&mut self) {
+        self.backend.shutdown();
+        self.backend.shutdown();
+        for _ in 0..3 {
+            self.backend.poll();
+        }
+        let (x, y) = CLICK_POINT;
+        self.backend.send_mouse_event(MouseEvent::Move { x, y });
+        self.backend.resize(TEST_PAGE_SIZE.0, TEST_PAGE_SIZE.1);
+        let _ = self.backend.send_to_ui(BevyToUi::CloseMenus);
+        let _ = self.backend.capture_if_dirty();
+    }
+}
+
+/// RGBA pixels of a CPU capture
+fn rgba_pixels(capture: CaptureResult) -> (Vec<u8>, u32, u32) {
+    match capture {
+        CaptureResult::Rgba(pixels, width, height, _) => (pixels, width, height),
+        CaptureResult::Bgra(pixels, width, height, _) => {
+            let mut pixels = Arc::unwrap_or_clone(pixels);
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+            (pixels, width, height)
+        }
+        other => panic!(
+            "expected a CPU capture, got {:?}; use BackendCaps::compositor_managed()",
+            other
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockBackend, MockUi};
+
+    fn fast_caps() -> BackendCaps {
+        BackendCaps {
+            max_polls: 20,
+            poll_interval: Duration::ZERO,
+            ..BackendCaps::default()
+        }
+    }
+
+    /// Mock UI scripted like `TEST_PAGE_HTML`, without the painting
+    fn page_scripts(ui: &MockUi) {
+        ui.on_mouse(|event| match *event {
+            MouseEvent::ButtonUp { x, y, .. } => Some(UiToBevy::Query {
+                request_id: CLICK_REQUEST_ID,
+                query: QueryKind::ObjectAtPoint { x, y },
+            }),
+            _ => None,
+        });
+        ui.on_message(|msg| match *msg {
+            BevyToUi::ProtocolVersion { version } => Some(UiToBevy::ProtocolVersion { version }),
+            _ => None,
+        });
+    }
+
+    fn factory(ui: &MockUi) -> impl Fn() -> MockBackend + '_ {
+        move || ui.backend(TEST_PAGE_SIZE)
+    }
+
+    #[test]
+    fn test_page_constants_match_the_html() {
+        assert!(TEST_PAGE_HTML.contains(&format!("request_id: {}", CLICK_REQUEST_ID)));
+        let [r, g, b, _] = TEST_PAGE_COLOR;
+        assert!(TEST_PAGE_HTML.contains(&format!("rgb({}, {}, {})", r, g, b)));
+    }
+
+    /// Paint `ui` with the color pattern's stripes, each color passed through `color`
+    fn paint_color_pattern(ui: &MockUi, color: impl Fn(u8) -> u8) {
+        let (width, height) = TEST_PAGE_SIZE;
+        let stripes = COLOR_PATTERN_SWATCHES.len();
+        let row: Vec<u8> = (0..width as usize)
+            .flat_map(|x| {
+                let [r, g, b] = COLOR_PATTERN_SWATCHES[x * stripes / width as usize];
+                [color(r), color(g), color(b), 255]
+            })
+            .collect();
+        ui.paint_pixels(width, height, row.repeat(height as usize));
+    }
+
+    #[test]
+    fn test_color_pattern_matches_the_html() {
+        for [r, g, b] in COLOR_PATTERN_SWATCHES {
+            let css = format!("background: #{:02x}{:02x}{:02x}", r, g, b);
+            assert!(COLOR_PATTERN_HTML.contains(&css), "missing {css}");
+        }
+        assert_eq!(
+            COLOR_PATTERN_HTML.matches("<div").count(),
+            COLOR_PATTERN_SWATCHES.len()
+        );
+    }
+
+    #[test]
+    fn test_exact_color_pattern_passes() {
+        let ui = MockUi::new(1, 0);
+        paint_color_pattern(&ui, |c| c);
+        run_color_check(factory(&ui), fast_caps());
+        assert_eq!(ui.shutdowns(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "swatch 3")]
+    fn test_washed_out_color_pattern_fails() {
+        let ui = MockUi::new(1, 0);
+        // Encoded twice: full and zero channels survive, mid grey doesn't
+        paint_color_pattern(&ui, |c| {
+            (255.0 * (f32::from(c) / 255.0).powf(1.0 / 2.2)).round() as u8
+        });
+        run_color_check(factory(&ui), fast_caps());
+    }
+
+    #[test]
+    fn test_conforming_mock_passes() {
+        let ui = MockUi::new(3, 2);
+        page_scripts(&ui);
+        ui.fill(TEST_PAGE_COLOR);
+        run_conformance(factory(&ui), fast_caps());
+
+        assert_eq!(ui.size(), RESIZES[1]);
+        assert_eq!(ui.mouse_events().len(), 3);
+    }
+
+    #[test]
+    fn test_compositor_managed_skips_pixel_checks() {
+        // Nothing is painted, so nothing is ever captured
+        let ui = MockUi::default();
+        page_scripts(&ui);
+        let caps = BackendCaps {
+            pixel_capture: false,
+            ..fast_caps()
+        };
+        run_conformance(factory(&ui), caps);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected [32, 160, 96, 255]")]
+    fn test_wrong_color_fails() {
+        let ui = MockUi::default();
+        page_scripts(&ui);
+        ui.fill([255, 0, 0, 255]);
+        run_conformance(factory(&ui), fast_caps());
+    }
+
+    #[test]
+    #[should_panic(expected = "a 400x300 capture")]
+    fn test_capture_that_ignores_resize_fails() {
+        let ui = MockUi::default();
+        page_scripts(&ui);
+        // A fixed-size frame instead of one that follows the backend
+        let (width, height) = TEST_PAGE_SIZE;
+        ui.paint(width, height, TEST_PAGE_COLOR);
+        run_conformance(factory(&ui), fast_caps());
+    }
+
+    #[test]
+    #[should_panic(expected = "the page to report the click")]
+    fn test_dropped_click_fails() {
+        let ui = MockUi::default();
+        page_scripts(&ui);
+        ui.fill(TEST_PAGE_COLOR);
+        ui.on_mouse(|_| None);
+        run_conformance(factory(&ui), fast_caps());
+    }
+}
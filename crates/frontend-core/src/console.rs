@@ -0,0 +1,207 @@
+//! Webview console forwarding
+//!
+//! Console output from the UI page is invisible unless DevTools is open, so
+//! backends hand each message to a `ConsoleForwarder`. It logs the message
+//! through `tracing` and returns a `BevyToUi::UiLog` for a debug log panel.
+//! A page stuck in a logging loop is rate limited so it can't flood the IPC
+//! channel.
+
+use std::time::{Duration, Instant};
+
+use pentimento_ipc::{BevyToUi, UiLogLevel};
+use serde::Deserialize;
+
+/// Prefix of console messages posted through the IPC handler by the shim
+pub const CONSOLE_PREFIX: &str = "__console__:";
+
+/// Messages forwarded per second; the rest are dropped and counted
+pub const MAX_MESSAGES_PER_SECOND: u32 = 200;
+
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// A console message from the UI page
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConsoleMessage {
+    pub level: UiLogLevel,
+    pub message: String,
+    /// Script URL (empty if unknown)
+    #[serde(default)]
+    pub source: String,
+    /// Line in `source` (0 if unknown)
+    #[serde(default)]
+    pub line: u32,
+}
+
+impl ConsoleMessage {
+    /// Parse an IPC body posted by the console shim; `None` for other messages
+    pub fn from_ipc(body: &str) -> Option<Self> {
+        let json = body.strip_prefix(CONSOLE_PREFIX)?;
+        match serde_json::from_str(json) {
+            Ok(msg) => Some(msg),
+            Err(e) => {
+                tracing::warn!("Failed to parse UI console message: {} - {}", json, e);
+                None
+            }
+        }
+    }
+}
+
+/// Script that wraps `console.*` to also post each message through
+/// `window.ipc` with `CONSOLE_PREFIX`, for backends that can't see the
+/// page's console themselves (WebKit)
+pub fn console_shim_js() -> String {
+    format!(
+        r#"
+        (function() {{
+            if (window.__PENTIMENTO_CONSOLE_SHIM__) return; // Already injected
+            window.__PENTIMENTO_CONSOLE_SHIM__ = true;
+
+            var levels = {{
+                debug: 'Debug', log: 'Info', info: 'Info', warn: 'Warn', error: 'Error'
+            }};
+            Object.keys(levels).forEach(function(method) {{
+                var original = console[method].bind(console);
+                console[method] = function() {{
+                    original.apply(null, arguments);
+                    if (!window.ipc) return;
+
+                    var message = Array.prototype.map.call(arguments, function(arg) {{
+                        if (typeof arg === 'string') return arg;
+                        try {{ return JSON.stringify(arg); }} catch (e) {{ return String(arg); }}
+                    }}).join(' ');
+
+                    // JavaScriptCore stack frames look like "fn@url:line:column";
+                    // frame 1 is the caller of this wrapper
+                    var frame = (new Error().stack || '').split('\n')[1] || '';
+                    var location = frame.match(/([^@\s(]+):(\d+):\d+\)?$/);
+
+                    window.ipc.postMessage('{}' + JSON.stringify({{
+                        level: levels[method],
+                        message: message,
+                        source: location ? location[1] : '',
+                        line: location ? parseInt(location[2], 10) : 0
+                    }}));
+                }};
+            }});
+        }})();
+        "#,
+        CONSOLE_PREFIX
+    )
+}
+
+/// Logs console messages and rate limits what is sent back to the UI
+#[derive(Debug, Default)]
+pub struct ConsoleForwarder {
+    window_start: Option<Instant>,
+    forwarded: u32,
+    dropped: u32,
+}
+
+impl ConsoleForwarder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log `msg` and return the `UiLog` to queue for the UI, or `None` if
+    /// more than `MAX_MESSAGES_PER_SECOND` arrived in the current second
+    pub fn forward(&mut self, msg: ConsoleMessage, now: Instant) -> Option<BevyToUi> {
+        let window_over = self
+            .window_start
+            .is_none_or(|start| now.duration_since(start) >= RATE_WINDOW);
+        if window_over {
+            if self.dropped > 0 {
+                tracing::warn!(
+                    "Dropped {} UI console messages (over {} per second)",
+                    self.dropped,
+                    MAX_MESSAGES_PER_SECOND
+                );
+            }
+            self.window_start = Some(now);
+            self.forwarded = 0;
+            self.dropped = 0;
+        }
+
+        if self.forwarded >= MAX_MESSAGES_PER_SECOND {
+            self.dropped += 1;
+            return None;
+        }
+        self.forwarded += 1;
+
+        log_console_message(&msg);
+        Some(BevyToUi::UiLog {
+            level: msg.level,
+            message: msg.message,
+            source: msg.source,
+            line: msg.line,
+        })
+    }
+
+    /// Messages dropped so far in the current second
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+fn log_console_message(msg: &ConsoleMessage) {
+    let (source, line, message) = (&msg.source, msg.line, &msg.message);
+    match msg.level {
+        UiLogLevel::Debug => {
+            tracing::debug!(target: "pentimento::ui_console", %source, line, "{}", message)
+        }
+        UiLogLevel::Info => {
+            tracing::info!(target: "pentimento::ui_console", %source, line, "{}", message)
+        }
+        UiLogLevel::Warn => {
+            tracing::warn!(target: "pentimento::ui_console", %source, line, "{}", message)
+        }
+        UiLogLevel::Error => {
+            tracing::error!(target: "pentimento::ui_console", %source, line, "{}", message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(message: &str) -> ConsoleMessage {
+        ConsoleMessage {
+            level: UiLogLevel::Info,
+            message: message.to_string(),
+            source: String::new(),
+            line: 0,
+        }
+    }
+
+    #[test]
+    fn test_from_ipc_requires_prefix() {
+        let body = r#"{"level":"Warn","message":"slow frame","source":"app.js","line":7}"#;
+        assert_eq!(ConsoleMessage::from_ipc(body), None);
+
+        let msg = ConsoleMessage::from_ipc(&format!("{}{}", CONSOLE_PREFIX, body)).unwrap();
+        assert_eq!(msg.level, UiLogLevel::Warn);
+        assert_eq!(msg.message, "slow frame");
+        assert_eq!((msg.source.as_str(), msg.line), ("app.js", 7));
+    }
+
+    #[test]
+    fn test_forward_drops_over_rate_limit() {
+        let mut forwarder = ConsoleForwarder::new();
+        let start = Instant::now();
+
+        for _ in 0..MAX_MESSAGES_PER_SECOND {
+            assert!(forwarder.forward(info("tick"), start).is_some());
+        }
+        assert!(forwarder.forward(info("tick"), start).is_none());
+        assert!(forwarder.forward(info("tick"), start).is_none());
+        assert_eq!(forwarder.dropped(), 2);
+
+        // A new second starts a new budget
+        let later = start + RATE_WINDOW;
+        assert!(matches!(
+            forwarder.forward(info("tock"), later),
+            Some(BevyToUi::UiLog { message, .. }) if message == "tock"
+        ));
+        assert_eq!(forwarder.dropped(), 0);
+    }
+}
@@ -0,0 +1,118 @@
+//! Keyboard helpers shared by the webview backends
+//!
+//! [`KeyboardEvent`](pentimento_ipc::KeyboardEvent) carries the logical key
+//! (layout-aware) and the physical code separately. Backends that need a
+//! Windows virtual key code, like CEF, derive it from the physical code so a
+//! key reports the same code on every layout, while the typed character
+//! comes from the logical key.
+
+/// Character typed by a logical key value, or `None` for named keys
+/// ("Enter", "Shift") and dead keys ("Dead"), which don't type anything.
+pub fn key_character(key: &str) -> Option<char> {
+    single_char(key, |c| !c.is_control())
+}
+
+/// Windows virtual key code for a web `KeyboardEvent.code` value (0 if unknown)
+pub fn windows_key_code(code: &str) -> i32 {
+    if let Some(letter) = code.strip_prefix("Key") {
+        if let Some(c) = single_char(letter, |c| c.is_ascii_uppercase()) {
+            return c as i32;
+        }
+    }
+    if let Some(digit) = code.strip_prefix("Digit") {
+        if let Some(c) = single_char(digit, |c| c.is_ascii_digit()) {
+            return c as i32;
+        }
+    }
+    if let Some(digit) = code.strip_prefix("Numpad") {
+        if let Some(c) = single_char(digit, |c| c.is_ascii_digit()) {
+            // VK_NUMPAD0..VK_NUMPAD9
+            return 0x60 + (c as i32 - '0' as i32);
+        }
+    }
+    if let Some(n) = code.strip_prefix('F').and_then(|n| n.parse::<i32>().ok()) {
+        if (1..=24).contains(&n) {
+            // VK_F1..VK_F24
+            return 0x6F + n;
+        }
+    }
+
+    match code {
+        "Backspace" => 0x08,
+        "Tab" => 0x09,
+        "Enter" | "NumpadEnter" => 0x0D,
+        "ShiftLeft" | "ShiftRight" => 0x10,
+        "ControlLeft" | "ControlRight" => 0x11,
+        "AltLeft" | "AltRight" => 0x12,
+        "CapsLock" => 0x14,
+        "Escape" => 0x1B,
+        "Space" => 0x20,
+        "PageUp" => 0x21,
+        "PageDown" => 0x22,
+        "End" => 0x23,
+        "Home" => 0x24,
+        "ArrowLeft" => 0x25,
+        "ArrowUp" => 0x26,
+        "ArrowRight" => 0x27,
+        "ArrowDown" => 0x28,
+        "Insert" => 0x2D,
+        "Delete" => 0x2E,
+        "MetaLeft" => 0x5B,
+        "MetaRight" => 0x5C,
+        "NumpadMultiply" => 0x6A,
+        "NumpadAdd" => 0x6B,
+        "NumpadSubtract" => 0x6D,
+        "NumpadDecimal" => 0x6E,
+        "NumpadDivide" => 0x6F,
+        "Semicolon" => 0xBA,
+        "Equal" => 0xBB,
+        "Comma" => 0xBC,
+        "Minus" => 0xBD,
+        "Period" => 0xBE,
+        "Slash" => 0xBF,
+        "Backquote" => 0xC0,
+        "BracketLeft" => 0xDB,
+        "Backslash" => 0xDC,
+        "BracketRight" => 0xDD,
+        "Quote" => 0xDE,
+        "IntlBackslash" => 0xE2,
+        _ => 0,
+    }
+}
+
+/// The only character of `s`, if it has exactly one and it passes `accept`
+fn single_char(s: &str, accept: impl Fn(char) -> bool) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if accept(c) => Some(c),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_character() {
+        assert_eq!(key_character("a"), Some('a'));
+        assert_eq!(key_character("É"), Some('É'));
+        assert_eq!(key_character(" "), Some(' '));
+        assert_eq!(key_character("Enter"), None);
+        assert_eq!(key_character("Dead"), None);
+        assert_eq!(key_character(""), None);
+    }
+
+    #[test]
+    fn test_windows_key_code_uses_physical_position() {
+        // The AZERTY "a" key sits where QWERTY has Q; CEF gets VK_Q either way
+        assert_eq!(windows_key_code("KeyQ"), 'Q' as i32);
+        assert_eq!(windows_key_code("Digit1"), '1' as i32);
+        assert_eq!(windows_key_code("Numpad7"), 0x67);
+        assert_eq!(windows_key_code("F12"), 0x7B);
+        assert_eq!(windows_key_code("Enter"), 0x0D);
+        assert_eq!(windows_key_code("BracketLeft"), 0xDB);
+        assert_eq!(windows_key_code("Keyboard"), 0);
+        assert_eq!(windows_key_code("F99"), 0);
+    }
+}
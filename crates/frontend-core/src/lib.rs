@@ -2,21 +2,95 @@
 //!
 //! Defines the `CompositeBackend` trait that abstracts over different UI rendering backends.
 
+#[cfg(target_os = "linux")]
+use std::os::fd::OwnedFd;
 use std::sync::Arc;
 
-use pentimento_ipc::{BevyToUi, KeyboardEvent, MouseEvent, UiToBevy};
+use pentimento_ipc::{
+    BevyToUi, FrontendLifecycle, IpcError, KeyboardEvent, MouseEvent, PROTOCOL_VERSION, UiToBevy,
+};
+
+pub mod batch;
+pub mod blob;
+#[cfg(feature = "test-util")]
+pub mod conformance;
+pub mod console;
+pub mod keyboard;
+#[cfg(feature = "test-util")]
+pub mod mock;
+pub mod recovery;
+pub mod script;
+
+/// How the color channels of captured 8-bit pixels relate to alpha
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlphaMode8 {
+    /// Color is independent of alpha (WebKit snapshots are un-premultiplied)
+    Straight,
+    /// Color is already multiplied by alpha (CEF paint buffers)
+    Premultiplied,
+}
 
 /// Result of capturing the UI framebuffer
 #[derive(Debug, Clone)]
 pub enum CaptureResult {
     /// RGBA pixel data with dimensions
-    Rgba(Vec<u8>, u32, u32),
+    Rgba(Vec<u8>, u32, u32, AlphaMode8),
     /// BGRA pixel data (shared) with dimensions
-    Bgra(Arc<Vec<u8>>, u32, u32),
+    Bgra(Arc<Vec<u8>>, u32, u32, AlphaMode8),
+    /// GPU texture shared by the backend (no CPU pixel data)
+    GpuExternal(ExternalTextureHandle),
     /// Compositor-managed rendering (no capture needed)
     CompositorManaged,
 }
 
+/// Handle to a texture that lives in another process's GPU memory
+///
+/// Importing it is up to the renderer. If the import fails, the renderer
+/// should call `CompositeBackend::disable_gpu_capture` to switch back to
+/// CPU captures.
+#[derive(Debug, Clone)]
+pub enum ExternalTextureHandle {
+    /// Linux DMA-BUF (e.g. CEF `OnAcceleratedPaint`)
+    #[cfg(target_os = "linux")]
+    Dmabuf(DmabufTexture),
+}
+
+/// Pixel layout of an external texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalTextureFormat {
+    Rgba8,
+    Bgra8,
+}
+
+/// A DMA-BUF backed texture
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
+pub struct DmabufTexture {
+    pub width: u32,
+    pub height: u32,
+    pub format: ExternalTextureFormat,
+    /// DRM format modifier (`0` is `DRM_FORMAT_MOD_LINEAR`)
+    pub modifier: u64,
+    pub planes: Vec<DmabufPlane>,
+}
+
+/// One memory plane of a DMA-BUF
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
+pub struct DmabufPlane {
+    /// Duplicated file descriptor, closed when the last clone is dropped
+    pub fd: Arc<OwnedFd>,
+    pub offset: u64,
+    pub stride: u32,
+    pub size: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl DmabufTexture {
+    /// `DRM_FORMAT_MOD_LINEAR`
+    pub const MODIFIER_LINEAR: u64 = 0;
+}
+
 /// Lifecycle state of a backend
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BackendLifecycle {
@@ -38,6 +112,18 @@ pub enum BackendLifecycle {
     Error,
 }
 
+impl From<&BackendLifecycle> for FrontendLifecycle {
+    fn from(lifecycle: &BackendLifecycle) -> Self {
+        match lifecycle {
+            BackendLifecycle::Initializing => FrontendLifecycle::Initializing,
+            BackendLifecycle::Loading { .. } => FrontendLifecycle::Loading,
+            BackendLifecycle::Ready => FrontendLifecycle::Ready,
+            BackendLifecycle::Resizing { .. } => FrontendLifecycle::Resizing,
+            BackendLifecycle::Error => FrontendLifecycle::Error,
+        }
+    }
+}
+
 /// Errors that can occur in frontend operations
 #[derive(Debug, thiserror::Error)]
 pub enum FrontendError {
@@ -66,6 +152,27 @@ pub enum FrontendError {
     Backend(String),
 }
 
+/// Parse what the UI page posted (one message or a batch), logging why any
+/// message was rejected
+///
+/// Backends that can queue messages for the UI should also send
+/// `IpcError::reply` so the page learns its message was not understood.
+pub fn parse_ui_messages(body: &str) -> Vec<Result<UiToBevy, IpcError>> {
+    let results = pentimento_ipc::parse_ui_batch(body);
+    for result in &results {
+        match result {
+            Ok(_) => {}
+            Err(IpcError::UnknownMessage(type_name)) => tracing::warn!(
+                "UI sent message type {} unknown to IPC protocol {}; is the UI newer than the app?",
+                type_name,
+                PROTOCOL_VERSION
+            ),
+            Err(e) => tracing::warn!("Failed to parse UI IPC message: {} - {}", body, e),
+        }
+    }
+    results
+}
+
 /// Trait for UI rendering backends that can be composited into the scene
 pub trait CompositeBackend {
     /// Poll the backend for events and updates
@@ -74,6 +181,19 @@ pub trait CompositeBackend {
     /// Check if the backend is ready for rendering
     fn is_ready(&self) -> bool;
 
+    /// Current lifecycle state
+    ///
+    /// Default implementation only tells `Ready` from `Initializing`.
+    /// Backends with a loading, resize, or crash state should report it, so
+    /// the app can hold input during resizes and recover from errors.
+    fn lifecycle(&self) -> BackendLifecycle {
+        if self.is_ready() {
+            BackendLifecycle::Ready
+        } else {
+            BackendLifecycle::Initializing
+        }
+    }
+
     /// Capture the current framebuffer if it has changed
     ///
     /// Returns `Some(CaptureResult)` if the framebuffer has been updated,
@@ -86,6 +206,60 @@ pub trait CompositeBackend {
     /// Resize the backend surface
     fn resize(&mut self, width: u32, height: u32);
 
+    /// Move the backend surface to follow the parent window (Overlay only)
+    ///
+    /// Default implementation does nothing. Override in backends that own a
+    /// separate top-level window which must track the Bevy window position.
+    fn set_position(&mut self, _x: i32, _y: i32) {
+        // Default: no-op for offscreen backends
+    }
+
+    /// Show or hide the backend surface along with the parent window (Overlay only)
+    ///
+    /// Driven by the parent's own window events, which also works where the
+    /// parent's state can't be polled (Wayland). Default implementation does
+    /// nothing for backends drawn inside the Bevy window.
+    fn set_parent_visible(&mut self, _visible: bool) {
+        // Default: no-op for offscreen backends
+    }
+
+    /// Enter or leave power saving while the app is in the background
+    ///
+    /// While in the background the app stops calling `capture_if_dirty`, so a
+    /// backend must keep its dirty flag for the first capture after `false`.
+    /// Backends that can stop painting altogether should. Default
+    /// implementation does nothing.
+    fn set_background(&mut self, _background: bool) {
+        // Default: no-op, captures are simply not taken
+    }
+
+    /// Request a capture on the next `capture_if_dirty` even if nothing changed
+    ///
+    /// Used as a slow safety-net heartbeat. Default implementation does nothing
+    /// for backends that are notified of every paint (CEF) or don't capture.
+    fn mark_dirty(&self) {
+        // Default: no-op
+    }
+
+    /// Set the device scale factor (physical pixels per CSS pixel)
+    ///
+    /// Called before `resize` whenever the window's scale changes, e.g. when it
+    /// moves to a monitor with a different DPI, so the size passed to that
+    /// `resize` may be unchanged. Default implementation does nothing for
+    /// backends that always render at a fixed scale.
+    fn set_scale_factor(&mut self, _scale_factor: f64) {
+        // Default: no-op
+    }
+
+    /// Stop producing `CaptureResult::GpuExternal` and use CPU captures instead
+    ///
+    /// Called by the renderer when it can't import the shared texture (driver
+    /// or format unsupported). Default implementation does nothing for
+    /// backends that only capture on the CPU.
+    fn disable_gpu_capture(&mut self) {
+        // Default: no-op
+    }
+
     /// Send a mouse event to the backend
     fn send_mouse_event(&mut self, event: MouseEvent);
 
@@ -104,4 +278,14 @@ pub trait CompositeBackend {
     fn show_dev_tools(&self) {
         // Default: no-op for backends that don't support DevTools
     }
+
+    /// Release the webview, browser, or window before the app exits
+    ///
+    /// Called once on `AppExit`, while the world (and the GTK/CEF loop) is
+    /// still alive. Must be safe to call twice; afterwards the backend only
+    /// has to tolerate further calls, not render. Default implementation
+    /// does nothing and leaves cleanup to `Drop`.
+    fn shutdown(&mut self) {
+        // Default: no-op, dropping the backend is enough
+    }
 }
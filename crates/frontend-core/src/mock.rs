@@ -0,0 +1,474 @@
+//! Scriptable in-memory backend for tests (`test-util` feature)
+//!
+//! `MockBackend` implements `CompositeBackend` without a webview or display,
+//! so the compositing and IPC paths can run in CI. Tests drive it through a
+//! `MockUi` handle that shares the backend's state: they inject `UiToBevy`
+//! messages and dirty framebuffers, and read back every `BevyToUi` message
+//! and input event the app sent.
+//!
+//! The lifecycle is simulated by counting polls: the backend is
+//! `Initializing` until `ready_after_polls` polls have passed, and a resize
+//! keeps it `Resizing` for `resize_settle_polls` polls. `MockUi::crash` and
+//! `shutdown` put it in `Error` until a new backend is made, while
+//! `MockUi::reload` starts the same backend's lifecycle over like a page
+//! reload. Hooks can stand in for page scripts, answering input and
+//! messages with `UiToBevy` messages.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use pentimento_ipc::{BevyToUi, KeyboardEvent, MouseEvent, UiToBevy};
+
+use crate::{AlphaMode8, BackendLifecycle, CaptureResult, CompositeBackend, FrontendError};
+
+/// Page script answering an input event or message, if it has anything to say
+type Hook<T> = Box<dyn FnMut(&T) -> Option<UiToBevy> + Send>;
+
+/// Shared state of a mock UI and its backend
+struct MockState {
+    ready_after_polls: u32,
+    resize_settle_polls: u32,
+    lifecycle: BackendLifecycle,
+    polls: u32,
+    size: (u32, u32),
+    scale_factor: f64,
+    /// Set by `set_background`
+    background: bool,
+    /// Last painted framebuffer (RGBA) and whether it is waiting to be captured
+    frame: Option<(Vec<u8>, u32, u32)>,
+    dirty: bool,
+    /// Color repainted at the backend size after every resize
+    fill: Option<[u8; 4]>,
+    on_mouse: Option<Hook<MouseEvent>>,
+    on_message: Option<Hook<BevyToUi>>,
+    sent: Vec<BevyToUi>,
+    inbox: VecDeque<UiToBevy>,
+    mouse_events: Vec<MouseEvent>,
+    keyboard_events: Vec<KeyboardEvent>,
+    /// Times `shutdown` was called
+    shutdowns: u32,
+}
+
+impl MockState {
+    fn paint(&mut self, width: u32, height: u32, rgba: [u8; 4]) {
+        let pixels = rgba.repeat(width as usize * height as usize);
+        self.frame = Some((pixels, width, height));
+        self.dirty = true;
+    }
+
+    fn repaint_fill(&mut self) {
+        if let Some(rgba) = self.fill {
+            let (width, height) = self.size;
+            self.paint(width, height, rgba);
+        }
+    }
+}
+
+/// Test-side handle to a mock UI
+///
+/// Clones share the same state, so a test can keep one while the app owns
+/// the backend made from it.
+#[derive(Clone)]
+pub struct MockUi {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl Default for MockUi {
+    /// Ready on the first poll, resizes settle after one more
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
+impl MockUi {
+    /// Mock UI that becomes ready after `ready_after_polls` polls and takes
+    /// `resize_settle_polls` polls to settle after a resize
+    pub fn new(ready_after_polls: u32, resize_settle_polls: u32) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState {
+                ready_after_polls,
+                resize_settle_polls,
+                lifecycle: BackendLifecycle::Initializing,
+                polls: 0,
+                size: (0, 0),
+                scale_factor: 1.0,
+                background: false,
+                frame: None,
+                dirty: false,
+                fill: None,
+                on_mouse: None,
+                on_message: None,
+                sent: Vec::new(),
+                inbox: VecDeque::new(),
+                mouse_events: Vec::new(),
+                keyboard_events: Vec::new(),
+                shutdowns: 0,
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // A test that panicked while holding the lock already failed
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Create a backend for this UI, starting its lifecycle over
+    pub fn backend(&self, size: (u32, u32)) -> MockBackend {
+        let mut state = self.state();
+        state.lifecycle = BackendLifecycle::Initializing;
+        state.polls = 0;
+        state.size = size;
+        state.repaint_fill();
+        MockBackend { ui: self.clone() }
+    }
+
+    /// Fail the backend as if its page crashed; it stays in `Error`
+    pub fn crash(&self) {
+        self.state().lifecycle = BackendLifecycle::Error;
+    }
+
+    /// Reload the page in the same backend: `Initializing` again until
+    /// `ready_after_polls` more polls have passed
+    pub fn reload(&self) {
+        let mut state = self.state();
+        state.lifecycle = BackendLifecycle::Initializing;
+        state.polls = 0;
+    }
+
+    /// Queue a message as if the UI had posted it
+    pub fn inject(&self, msg: UiToBevy) {
+        self.state().inbox.push_back(msg);
+    }
+
+    /// Paint the whole framebuffer one color and mark it dirty
+    pub fn paint(&self, width: u32, height: u32, rgba: [u8; 4]) {
+        self.state().paint(width, height, rgba);
+    }
+
+    /// Set the framebuffer to `rgba` (tightly packed RGBA rows) and mark it dirty
+    pub fn paint_pixels(&self, width: u32, height: u32, rgba: Vec<u8>) {
+        assert_eq!(
+            rgba.len(),
+            width as usize * height as usize * 4,
+            "pixel buffer doesn't match {width}x{height}"
+        );
+        let mut state = self.state();
+        state.frame = Some((rgba, width, height));
+        state.dirty = true;
+    }
+
+    /// Keep the framebuffer one color at the backend's size, like a page
+    /// with a solid background: it is repainted after every resize
+    pub fn fill(&self, rgba: [u8; 4]) {
+        let mut state = self.state();
+        state.fill = Some(rgba);
+        state.repaint_fill();
+    }
+
+    /// Answer mouse events the way a page script would
+    ///
+    /// The hook runs while the UI is locked, so it must not use this handle.
+    pub fn on_mouse(&self, hook: impl FnMut(&MouseEvent) -> Option<UiToBevy> + Send + 'static) {
+        self.state().on_mouse = Some(Box::new(hook));
+    }
+
+    /// Answer messages sent to the UI the way a page script would
+    ///
+    /// The hook runs while the UI is locked, so it must not use this handle.
+    pub fn on_message(&self, hook: impl FnMut(&BevyToUi) -> Option<UiToBevy> + Send + 'static) {
+        self.state().on_message = Some(Box::new(hook));
+    }
+
+    /// Whether a painted framebuffer is still waiting to be captured
+    pub fn is_dirty(&self) -> bool {
+        self.state().dirty
+    }
+
+    /// Every message sent to the UI so far
+    pub fn sent(&self) -> Vec<BevyToUi> {
+        self.state().sent.clone()
+    }
+
+    /// Take the messages sent to the UI since the last call
+    pub fn take_sent(&self) -> Vec<BevyToUi> {
+        std::mem::take(&mut self.state().sent)
+    }
+
+    pub fn mouse_events(&self) -> Vec<MouseEvent> {
+        self.state().mouse_events.clone()
+    }
+
+    pub fn keyboard_events(&self) -> Vec<KeyboardEvent> {
+        self.state().keyboard_events.clone()
+    }
+
+    pub fn lifecycle(&self) -> BackendLifecycle {
+        self.state().lifecycle.clone()
+    }
+
+    /// Number of times the backend has been polled
+    pub fn polls(&self) -> u32 {
+        self.state().polls
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.state().size
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.state().scale_factor
+    }
+
+    /// Whether the app put the backend into power saving
+    pub fn is_background(&self) -> bool {
+        self.state().background
+    }
+
+    /// Number of times the backend has been shut down
+    pub fn shutdowns(&self) -> u32 {
+        self.state().shutdowns
+    }
+}
+
+/// `CompositeBackend` backed by a `MockUi`
+pub struct MockBackend {
+    ui: MockUi,
+}
+
+impl MockBackend {
+    /// The handle this backend was made from
+    pub fn ui(&self) -> &MockUi {
+        &self.ui
+    }
+}
+
+impl CompositeBackend for MockBackend {
+    fn poll(&mut self) {
+        let mut state = self.ui.state();
+        state.polls += 1;
+        let next = match state.lifecycle {
+            BackendLifecycle::Initializing if state.polls >= state.ready_after_polls => {
+                BackendLifecycle::Ready
+            }
+            BackendLifecycle::Resizing { frames_remaining } if frames_remaining <= 1 => {
+                BackendLifecycle::Ready
+            }
+            BackendLifecycle::Resizing { frames_remaining } => BackendLifecycle::Resizing {
+                frames_remaining: frames_remaining - 1,
+            },
+            _ => return,
+        };
+        state.lifecycle = next;
+    }
+
+    fn is_ready(&self) -> bool {
+        self.ui.state().lifecycle == BackendLifecycle::Ready
+    }
+
+    fn lifecycle(&self) -> BackendLifecycle {
+        self.ui.state().lifecycle.clone()
+    }
+
+    fn capture_if_dirty(&mut self) -> Option<CaptureResult> {
+        let mut state = self.ui.state();
+        if state.lifecycle != BackendLifecycle::Ready || !state.dirty {
+            return None;
+        }
+        let (pixels, width, height) = state.frame.clone()?;
+        state.dirty = false;
+        Some(CaptureResult::Rgba(
+            pixels,
+            width,
+            height,
+            AlphaMode8::Straight,
+        ))
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.ui.state().size
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        let mut state = self.ui.state();
+        if state.shutdowns > 0 {
+            return;
+        }
+        state.size = (width, height);
+        state.repaint_fill();
+        if state.resize_settle_polls > 0 {
+            state.lifecycle = BackendLifecycle::Resizing {
+                frames_remaining: state.resize_settle_polls,
+            };
+        }
+    }
+
+    fn mark_dirty(&self) {
+        let mut state = self.ui.state();
+        state.dirty = state.frame.is_some();
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.ui.state().scale_factor = scale_factor;
+    }
+
+    fn set_background(&mut self, background: bool) {
+        self.ui.state().background = background;
+    }
+
+    fn send_mouse_event(&mut self, event: MouseEvent) {
+        let mut state = self.ui.state();
+        if state.shutdowns > 0 {
+            return;
+        }
+        if let Some(reply) = state.on_mouse.as_mut().and_then(|hook| hook(&event)) {
+            state.inbox.push_back(reply);
+        }
+        state.mouse_events.push(event);
+    }
+
+    fn send_keyboard_event(&mut self, event: KeyboardEvent) {
+        let mut state = self.ui.state();
+        if state.shutdowns > 0 {
+            return;
+        }
+        state.keyboard_events.push(event);
+    }
+
+    fn send_to_ui(&mut self, msg: BevyToUi) -> Result<(), FrontendError> {
+        let mut state = self.ui.state();
+        if state.shutdowns > 0 {
+            return Err(FrontendError::NotReady);
+        }
+        if let Some(reply) = state.on_message.as_mut().and_then(|hook| hook(&msg)) {
+            state.inbox.push_back(reply);
+        }
+        state.sent.push(msg);
+        Ok(())
+    }
+
+    fn try_recv_from_ui(&mut self) -> Option<UiToBevy> {
+        self.ui.state().inbox.pop_front()
+    }
+
+    fn shutdown(&mut self) {
+        let mut state = self.ui.state();
+        state.shutdowns += 1;
+        state.lifecycle = BackendLifecycle::Error;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifecycle_follows_polls() {
+        let ui = MockUi::new(3, 2);
+        let mut backend = ui.backend((64, 32));
+        backend.poll();
+        backend.poll();
+        assert_eq!(ui.lifecycle(), BackendLifecycle::Initializing);
+        backend.poll();
+        assert!(backend.is_ready());
+
+        backend.resize(128, 64);
+        assert_eq!(ui.size(), (128, 64));
+        backend.poll();
+        assert_eq!(
+            ui.lifecycle(),
+            BackendLifecycle::Resizing {
+                frames_remaining: 1
+            }
+        );
+        backend.poll();
+        assert!(backend.is_ready());
+    }
+
+    #[test]
+    fn test_crash_lasts_until_a_new_backend() {
+        let ui = MockUi::default();
+        let mut backend = ui.backend((8, 8));
+        backend.poll();
+        ui.crash();
+        backend.poll();
+        assert_eq!(backend.lifecycle(), BackendLifecycle::Error);
+        assert!(!backend.is_ready());
+
+        let mut backend = ui.backend((8, 8));
+        assert_eq!(backend.lifecycle(), BackendLifecycle::Initializing);
+        backend.poll();
+        assert_eq!(backend.lifecycle(), BackendLifecycle::Ready);
+    }
+
+    #[test]
+    fn test_reload_starts_the_lifecycle_over() {
+        let ui = MockUi::new(2, 1);
+        let mut backend = ui.backend((8, 8));
+        backend.poll();
+        backend.poll();
+        assert!(backend.is_ready());
+
+        ui.reload();
+        assert_eq!(backend.lifecycle(), BackendLifecycle::Initializing);
+        backend.poll();
+        assert!(!backend.is_ready());
+        backend.poll();
+        assert!(backend.is_ready());
+    }
+
+    #[test]
+    fn test_painted_frame_is_captured_once() {
+        let ui = MockUi::new(1, 0);
+        let mut backend = ui.backend((2, 2));
+        ui.paint(2, 2, [255, 0, 0, 255]);
+        assert!(
+            backend.capture_if_dirty().is_none(),
+            "nothing is captured before the backend is ready"
+        );
+
+        backend.poll();
+        match backend.capture_if_dirty() {
+            Some(CaptureResult::Rgba(pixels, 2, 2, AlphaMode8::Straight)) => {
+                assert_eq!(pixels, [255, 0, 0, 255].repeat(4));
+            }
+            other => panic!("unexpected capture {:?}", other),
+        }
+        assert!(backend.capture_if_dirty().is_none());
+
+        backend.mark_dirty();
+        assert!(backend.capture_if_dirty().is_some());
+    }
+
+    #[test]
+    fn test_messages_pass_through_the_handle() {
+        let ui = MockUi::default();
+        let mut backend = ui.backend((8, 8));
+        ui.inject(UiToBevy::UiDirty);
+        assert!(matches!(
+            backend.try_recv_from_ui(),
+            Some(UiToBevy::UiDirty)
+        ));
+        assert!(backend.try_recv_from_ui().is_none());
+
+        backend.send_to_ui(BevyToUi::CloseMenus).unwrap();
+        assert!(matches!(ui.take_sent()[..], [BevyToUi::CloseMenus]));
+        assert!(ui.sent().is_empty());
+    }
+
+    #[test]
+    fn test_shutdown_stops_the_backend() {
+        let ui = MockUi::default();
+        let mut backend = ui.backend((8, 8));
+        backend.poll();
+        assert!(backend.is_ready());
+
+        backend.shutdown();
+        backend.poll();
+        assert_eq!(ui.shutdowns(), 1);
+        assert_eq!(backend.lifecycle(), BackendLifecycle::Error);
+
+        backend.resize(16, 16);
+        assert_eq!(ui.size(), (8, 8));
+        assert!(backend.send_to_ui(BevyToUi::CloseMenus).is_err());
+    }
+}
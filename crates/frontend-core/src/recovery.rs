@@ -0,0 +1,204 @@
+//! Crash recovery for webview backends
+//!
+//! When the browser's render process dies, the page is gone but the backend
+//! is not: it reloads the page after a backoff and replays the latest state
+//! messages so the fresh page matches the app again. After
+//! `MAX_RECOVERY_ATTEMPTS` crashes the backend stays in its error state.
+
+use std::mem::discriminant;
+use std::time::{Duration, Instant};
+
+use pentimento_ipc::BevyToUi;
+
+/// Crashes recovered from before giving up
+pub const MAX_RECOVERY_ATTEMPTS: u32 = 3;
+
+/// Delay before the first reload, doubled for each further attempt
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Error code sent to the UI when its process crashed
+pub const UI_CRASHED_CODE: &str = "ui_crashed";
+
+/// Warning code sent to the UI once it has been reloaded
+pub const UI_RECOVERED_CODE: &str = "ui_recovered";
+
+/// Whether a message carries state a freshly loaded page needs, as opposed
+/// to a one-off event (progress, errors, menu toggles)
+pub fn is_state_message(msg: &BevyToUi) -> bool {
+    matches!(
+        msg,
+        BevyToUi::Initialize { .. }
+            | BevyToUi::SceneUpdated(_)
+            | BevyToUi::SelectionChanged { .. }
+            | BevyToUi::GizmoModeChanged { .. }
+            | BevyToUi::AmbientOcclusionChanged { .. }
+            | BevyToUi::LightingChanged { .. }
+            | BevyToUi::EditModeChanged { .. }
+            | BevyToUi::ProjectionModeChanged { .. }
+            | BevyToUi::MeshEditModeChanged { .. }
+            | BevyToUi::MeshEditSelectionChanged { .. }
+            | BevyToUi::LayerStateChanged { .. }
+            | BevyToUi::SculptSettingsChanged { .. }
+            | BevyToUi::KeymapChanged { .. }
+            | BevyToUi::LightsChanged { .. }
+            | BevyToUi::ProtocolVersion { .. }
+    )
+}
+
+/// Latest state messages sent to the UI, replayed after a reload
+///
+/// Keeps the most recent message of each kind in send order. `Initialize`
+/// is a full sync, so it drops everything sent before it.
+#[derive(Debug, Default)]
+pub struct StateReplayCache {
+    messages: Vec<BevyToUi>,
+}
+
+impl StateReplayCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember `msg` if it is a state message
+    pub fn record(&mut self, msg: &BevyToUi) {
+        if !is_state_message(msg) {
+            return;
+        }
+
+        if matches!(msg, BevyToUi::Initialize { .. }) {
+            self.messages.clear();
+        } else {
+            let kind = discriminant(msg);
+            self.messages.retain(|cached| discriminant(cached) != kind);
+        }
+        self.messages.push(msg.clone());
+    }
+
+    /// Messages to send to a reloaded page, oldest first
+    pub fn messages(&self) -> &[BevyToUi] {
+        &self.messages
+    }
+}
+
+/// Crash counter and reload backoff
+#[derive(Debug, Default)]
+pub struct CrashRecovery {
+    attempts: u32,
+    reload_at: Option<Instant>,
+}
+
+impl CrashRecovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a crash at `now` and schedule a reload
+    ///
+    /// Returns false once the attempts are used up; no reload is scheduled
+    /// then and the backend should stay in its error state.
+    pub fn crashed(&mut self, now: Instant) -> bool {
+        if self.gave_up() {
+            return false;
+        }
+        self.attempts += 1;
+        if self.gave_up() {
+            self.reload_at = None;
+            return false;
+        }
+        self.reload_at = Some(now + BASE_BACKOFF * 2u32.pow(self.attempts - 1));
+        true
+    }
+
+    /// Whether the scheduled reload is due at `now` (true once per crash)
+    pub fn reload_due(&mut self, now: Instant) -> bool {
+        match self.reload_at {
+            Some(at) if now >= at => {
+                self.reload_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Crashes recorded so far
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Whether the UI crashed more often than it may be reloaded
+    pub fn gave_up(&self) -> bool {
+        self.attempts > MAX_RECOVERY_ATTEMPTS
+    }
+
+    /// Error shown on the reloaded page about the crash
+    pub fn crash_error(&self, reason: &str) -> BevyToUi {
+        BevyToUi::Error {
+            code: UI_CRASHED_CODE.to_string(),
+            message: format!(
+                "The UI process crashed ({}) and was reloaded (attempt {} of {})",
+                reason, self.attempts, MAX_RECOVERY_ATTEMPTS
+            ),
+        }
+    }
+
+    /// Notice that the reloaded page has its state back
+    pub fn recovered_notice(&self) -> BevyToUi {
+        BevyToUi::Warning {
+            code: UI_RECOVERED_CODE.to_string(),
+            message: "UI recovered after a crash; unsaved UI-only state was reset".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(id: &str) -> BevyToUi {
+        BevyToUi::SelectionChanged {
+            selected_ids: vec![id.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_cache_keeps_latest_of_each_kind() {
+        let mut cache = StateReplayCache::new();
+        cache.record(&selection("a"));
+        cache.record(&BevyToUi::CloseMenus);
+        cache.record(&BevyToUi::SceneUpdated(Default::default()));
+        cache.record(&selection("b"));
+
+        let messages = cache.messages();
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(messages[0], BevyToUi::SceneUpdated(_)));
+        assert!(matches!(
+            &messages[1],
+            BevyToUi::SelectionChanged { selected_ids } if selected_ids == &["b"]
+        ));
+    }
+
+    #[test]
+    fn test_backoff_doubles_until_giving_up() {
+        let mut recovery = CrashRecovery::new();
+        let start = Instant::now();
+
+        assert!(recovery.crashed(start));
+        assert!(!recovery.reload_due(start));
+        assert!(recovery.reload_due(start + BASE_BACKOFF));
+        assert!(!recovery.reload_due(start + BASE_BACKOFF));
+
+        assert!(recovery.crashed(start));
+        assert!(!recovery.reload_due(start + BASE_BACKOFF));
+        assert!(recovery.reload_due(start + BASE_BACKOFF * 2));
+
+        assert!(recovery.crashed(start));
+        assert!(recovery.reload_due(start + BASE_BACKOFF * 4));
+        assert!(!recovery.gave_up());
+
+        assert!(!recovery.crashed(start));
+        assert!(recovery.gave_up());
+        assert!(!recovery.reload_due(start + Duration::from_secs(60)));
+        assert!(!recovery.crashed(start));
+        assert_eq!(recovery.attempts(), MAX_RECOVERY_ATTEMPTS + 1);
+    }
+}
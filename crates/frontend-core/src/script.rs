@@ -0,0 +1,86 @@
+//! Building injected JavaScript without per-event allocations
+//!
+//! Backends that inject input as script (WebKit, overlay) format a few hundred
+//! bytes of JavaScript per event. A [`ScriptBuffer`] kept in the backend is
+//! cleared and rewritten for each event, so once it has grown to fit the
+//! largest script, mouse moves no longer allocate.
+
+use std::fmt::{self, Write};
+
+/// Reusable string buffer for one script at a time
+#[derive(Debug, Default)]
+pub struct ScriptBuffer {
+    buf: String,
+}
+
+impl ScriptBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the buffer contents with `args` and return the script.
+    /// Use with `format_args!` to get exactly what `format!` would produce.
+    pub fn write(&mut self, args: fmt::Arguments<'_>) -> &str {
+        self.buf.clear();
+        // Writing into a String can't fail
+        let _ = self.buf.write_fmt(args);
+        &self.buf
+    }
+
+    /// Bytes currently reserved by the buffer
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+}
+
+/// Escapes a string for a single-quoted JavaScript string literal, the same as
+/// `replace('\\', "\\\\").replace('\'', "\\'")` but written straight into the
+/// formatter
+pub struct SingleQuoted<'a>(pub &'a str);
+
+impl fmt::Display for SingleQuoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.0;
+        while let Some(i) = rest.find(['\\', '\'']) {
+            f.write_str(&rest[..i])?;
+            f.write_char('\\')?;
+            f.write_str(&rest[i..i + 1])?;
+            rest = &rest[i + 1..];
+        }
+        f.write_str(rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_matches_format() {
+        let mut script = ScriptBuffer::new();
+        let (x, y) = (12.5_f32, 40.0_f32);
+        assert_eq!(
+            script.write(format_args!("move({x}, {y})")),
+            format!("move({x}, {y})")
+        );
+        // The previous script is replaced, not appended to
+        assert_eq!(script.write(format_args!("up({})", 0)), "up(0)");
+    }
+
+    #[test]
+    fn test_buffer_is_reused() {
+        let mut script = ScriptBuffer::new();
+        script.write(format_args!("{:>200}", "first"));
+        let capacity = script.capacity();
+        script.write(format_args!("{:>100}", "second"));
+        assert_eq!(script.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_single_quoted_matches_replace() {
+        for key in ["a", "'", "\\", "\\'", "it's", "é", "Enter", ""] {
+            let expected = key.replace('\\', "\\\\").replace('\'', "\\'");
+            assert_eq!(SingleQuoted(key).to_string(), expected);
+        }
+    }
+}
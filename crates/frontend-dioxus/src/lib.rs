@@ -6,8 +6,12 @@
 
 mod bridge;
 
+use std::cell::Cell;
+
 pub use bridge::{DioxusBridge, DioxusBridgeHandle, DioxusBridgeHandleExt};
-use pentimento_frontend_core::{CaptureResult, CompositeBackend, FrontendError};
+use pentimento_dioxus_ui::UiEvent;
+use pentimento_dioxus_ui::convert::InputConverter;
+use pentimento_frontend_core::{BackendLifecycle, CaptureResult, CompositeBackend, FrontendError};
 use pentimento_ipc::{BevyToUi, KeyboardEvent, MouseEvent, UiToBevy};
 
 /// Backend implementation for Dioxus-based UI rendering.
@@ -25,10 +29,15 @@ pub struct DioxusBackend {
     height: u32,
     /// Whether the backend is ready for rendering
     ready: bool,
-    /// Queued mouse events to be processed
-    mouse_events: Vec<MouseEvent>,
-    /// Queued keyboard events to be processed
-    keyboard_events: Vec<KeyboardEvent>,
+    /// Converts input to Blitz events, tracking buttons and held keys
+    converter: InputConverter,
+    /// Queued input events, in arrival order, for the Blitz document
+    ui_events: Vec<UiEvent>,
+    /// Whether input, a resize, or a message may have changed the UI since
+    /// the last `capture_if_dirty`
+    dirty: Cell<bool>,
+    /// Set by `shutdown`; input and messages are dropped afterwards
+    shut_down: bool,
 }
 
 impl DioxusBackend {
@@ -39,8 +48,10 @@ impl DioxusBackend {
             width: 800,
             height: 600,
             ready: false,
-            mouse_events: Vec::new(),
-            keyboard_events: Vec::new(),
+            converter: InputConverter::new(),
+            ui_events: Vec::new(),
+            dirty: Cell::new(true),
+            shut_down: false,
         }
     }
 
@@ -51,8 +62,10 @@ impl DioxusBackend {
             width,
             height,
             ready: false,
-            mouse_events: Vec::new(),
-            keyboard_events: Vec::new(),
+            converter: InputConverter::new(),
+            ui_events: Vec::new(),
+            dirty: Cell::new(true),
+            shut_down: false,
         }
     }
 
@@ -71,14 +84,11 @@ impl DioxusBackend {
         &mut self.bridge_handle
     }
 
-    /// Take queued mouse events (drains the queue)
-    pub fn take_mouse_events(&mut self) -> Vec<MouseEvent> {
-        std::mem::take(&mut self.mouse_events)
-    }
-
-    /// Take queued keyboard events (drains the queue)
-    pub fn take_keyboard_events(&mut self) -> Vec<KeyboardEvent> {
-        std::mem::take(&mut self.keyboard_events)
+    /// Take queued input events (drains the queue)
+    ///
+    /// Feed them to `BlitzDocument::handle_event` in order.
+    pub fn take_ui_events(&mut self) -> Vec<UiEvent> {
+        std::mem::take(&mut self.ui_events)
     }
 }
 
@@ -92,10 +102,26 @@ impl CompositeBackend for DioxusBackend {
         self.ready
     }
 
+    fn lifecycle(&self) -> BackendLifecycle {
+        if self.shut_down {
+            return BackendLifecycle::Error;
+        }
+        // Vello repaints at the current size, so resizes don't settle
+        if self.ready {
+            BackendLifecycle::Ready
+        } else {
+            BackendLifecycle::Loading {
+                frames_remaining: None,
+            }
+        }
+    }
+
     fn capture_if_dirty(&mut self) -> Option<CaptureResult> {
         // Dioxus uses Vello which renders directly to GPU textures.
-        // No CPU-side framebuffer capture is needed.
-        Some(CaptureResult::CompositorManaged)
+        // No CPU-side framebuffer capture is needed, only a repaint when dirty.
+        self.dirty
+            .replace(false)
+            .then_some(CaptureResult::CompositorManaged)
     }
 
     fn size(&self) -> (u32, u32) {
@@ -106,26 +132,53 @@ impl CompositeBackend for DioxusBackend {
         if width > 0 && height > 0 {
             self.width = width;
             self.height = height;
+            self.dirty.set(true);
             tracing::debug!("DioxusBackend resized to {}x{}", width, height);
         }
     }
 
+    fn mark_dirty(&self) {
+        self.dirty.set(true);
+    }
+
     fn send_mouse_event(&mut self, event: MouseEvent) {
+        if self.shut_down {
+            return;
+        }
         // Queue the event for processing by the Bevy systems
-        self.mouse_events.push(event);
+        let ui_event = self.converter.mouse(&event);
+        self.ui_events.push(ui_event);
+        self.dirty.set(true);
     }
 
     fn send_keyboard_event(&mut self, event: KeyboardEvent) {
+        if self.shut_down {
+            return;
+        }
         // Queue the event for processing by the Bevy systems
-        self.keyboard_events.push(event);
+        let ui_event = self.converter.keyboard(&event);
+        self.ui_events.push(ui_event);
+        self.dirty.set(true);
     }
 
     fn send_to_ui(&mut self, msg: BevyToUi) -> Result<(), FrontendError> {
+        if self.shut_down {
+            return Err(FrontendError::NotReady);
+        }
         self.bridge_handle.send(msg);
+        self.dirty.set(true);
         Ok(())
     }
 
     fn try_recv_from_ui(&mut self) -> Option<UiToBevy> {
         self.bridge_handle.try_recv()
     }
+
+    fn shutdown(&mut self) {
+        // The VirtualDom itself is dropped by the app along with the
+        // BlitzDocument; only stop feeding it here
+        self.shut_down = true;
+        self.ready = false;
+        self.ui_events.clear();
+    }
 }
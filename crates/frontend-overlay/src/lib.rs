@@ -4,8 +4,17 @@
 //! The desktop compositor handles the actual blending, avoiding the need for framebuffer capture.
 //!
 //! Input handling uses a selective passthrough approach:
-//! - UI regions (toolbar, sidebar) receive native input for proper Svelte interaction
+//! - UI regions receive native input for proper Svelte interaction; the regions
+//!   follow the `LayoutUpdate` messages the UI sends, so collapsed panels and
+//!   floating dialogs get the right shape
 //! - The 3D viewport area is click-through, passing events to Bevy underneath
+//!
+//! On Wayland the overlay is a layer-shell surface, which needs the `layer-shell`
+//! feature and a compositor implementing wlr-layer-shell; otherwise creation
+//! fails with [`OverlayError::WaylandUnsupported`].
+//!
+//! `shutdown` destroys the overlay window (and the webview in it) before the
+//! app exits, so it doesn't linger on screen while Bevy tears down.
 
 pub mod sync;
 pub mod window;
@@ -15,7 +24,11 @@ use std::rc::Rc;
 
 use gio::Cancellable;
 use gtk::prelude::*;
-use pentimento_frontend_core::{CaptureResult, CompositeBackend, FrontendError};
+use pentimento_frontend_core::script::{ScriptBuffer, SingleQuoted};
+use pentimento_frontend_core::{
+    batch::batch_script, parse_ui_messages, BackendLifecycle, CaptureResult, CompositeBackend,
+    FrontendError,
+};
 use pentimento_ipc::{BevyToUi, KeyboardEvent, MouseButton, MouseEvent, UiToBevy};
 use raw_window_handle::RawWindowHandle;
 use tokio::sync::mpsc;
@@ -40,6 +53,10 @@ pub enum OverlayError {
     /// JavaScript evaluation failed
     #[error("Failed to evaluate script: {0}")]
     EvalScript(String),
+
+    /// The overlay cannot be attached to a Wayland parent window
+    #[error("Overlay mode is not supported on this Wayland session: {0}")]
+    WaylandUnsupported(String),
 }
 
 /// Overlay webview state
@@ -49,6 +66,8 @@ pub enum OverlayState {
     Initializing,
     /// Content loaded, ready for use
     Ready,
+    /// Window destroyed by `shutdown`
+    ShutDown,
 }
 
 /// Overlay backend using transparent GTK window
@@ -67,6 +86,16 @@ pub struct OverlayBackend {
     parent_xid: Option<u64>,
     /// Channel receiver for UI messages
     from_ui_rx: mpsc::UnboundedReceiver<UiToBevy>,
+    /// Messages for the UI, sent in one batch on poll
+    to_ui_messages: Vec<BevyToUi>,
+    /// Input shape following the UI layout
+    input_shape: window::InputShape,
+    /// Device scale factor (physical pixels per CSS pixel)
+    scale_factor: f64,
+    /// The scale factor changed since the last `resize`
+    rescale_pending: bool,
+    /// Reused for the scripts that inject input events
+    script: ScriptBuffer,
 }
 
 impl OverlayBackend {
@@ -104,7 +133,7 @@ impl OverlayBackend {
             })
             .with_ipc_handler(move |msg: wry::http::Request<String>| {
                 let body = msg.body();
-                if let Ok(ui_msg) = serde_json::from_str::<UiToBevy>(body) {
+                for ui_msg in parse_ui_messages(body).into_iter().flatten() {
                     let _ = from_ui_tx.send(ui_msg);
                 }
             })
@@ -128,7 +157,7 @@ impl OverlayBackend {
         });
 
         // Position the overlay window and set up window grouping
-        let parent_xid = sync::setup_window_relationship(&window, parent_handle);
+        let parent_xid = sync::setup_window_relationship(&window, parent_handle)?;
 
         // Show the window
         window.show_all();
@@ -155,9 +184,39 @@ impl OverlayBackend {
             load_finished,
             parent_xid,
             from_ui_rx,
+            to_ui_messages: Vec::new(),
+            input_shape: window::InputShape::default(),
+            scale_factor: 1.0,
+            rescale_pending: false,
+            script: ScriptBuffer::new(),
         })
     }
 
+    /// Current size in GTK units and CSS pixels
+    fn geometry(&self) -> window::OverlayGeometry {
+        window::OverlayGeometry::new(self.size, self.scale_factor, self.window.scale_factor())
+    }
+
+    /// Flush pending messages to the UI with one JavaScript evaluation
+    fn flush_to_ui_messages(&mut self) {
+        if self.to_ui_messages.is_empty() || self.state != OverlayState::Ready {
+            return;
+        }
+
+        let messages = std::mem::take(&mut self.to_ui_messages);
+        match batch_script(&messages) {
+            Ok(js) => {
+                tracing::trace!("Sending {} messages to the UI in one eval", messages.len());
+                if let Err(e) = self.webview.evaluate_script(&js) {
+                    tracing::warn!("Failed to send messages to UI: {}", e);
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to serialize messages for UI: {}", e);
+            }
+        }
+    }
+
     /// Find the WebKitWebView widget within a GTK container
     fn find_webkit_webview(container: &gtk::Fixed) -> Option<webkit2gtk::WebView> {
         for child in container.children() {
@@ -195,10 +254,13 @@ impl OverlayBackend {
     }
 
     /// Inject a mouse event into the webview via JavaScript
+    ///
+    /// Coordinates are in physical pixels and are converted to CSS pixels.
     pub fn inject_mouse(&mut self, event: MouseEvent) {
+        let event = css_mouse_event(event, self.scale_factor);
         let js = match event {
             MouseEvent::Move { x, y } => {
-                format!(
+                self.script.write(format_args!(
                     r#"(function() {{
                         const target = document.elementFromPoint({x}, {y}) || document.body;
                         target.dispatchEvent(new MouseEvent('mousemove', {{
@@ -208,7 +270,7 @@ impl OverlayBackend {
                     }})()"#,
                     x = x,
                     y = y
-                )
+                ))
             }
             MouseEvent::ButtonDown { button, x, y } => {
                 let button_num = match button {
@@ -216,7 +278,7 @@ impl OverlayBackend {
                     MouseButton::Middle => 1,
                     MouseButton::Right => 2,
                 };
-                format!(
+                self.script.write(format_args!(
                     r#"(function() {{
                         const target = document.elementFromPoint({x}, {y}) || document.body;
                         target.dispatchEvent(new MouseEvent('mousedown', {{
@@ -227,7 +289,7 @@ impl OverlayBackend {
                     x = x,
                     y = y,
                     button = button_num
-                )
+                ))
             }
             MouseEvent::ButtonUp { button, x, y } => {
                 let button_num = match button {
@@ -235,7 +297,7 @@ impl OverlayBackend {
                     MouseButton::Middle => 1,
                     MouseButton::Right => 2,
                 };
-                format!(
+                self.script.write(format_args!(
                     r#"(function() {{
                         const target = document.elementFromPoint({x}, {y}) || document.body;
                         target.dispatchEvent(new MouseEvent('mouseup', {{
@@ -252,7 +314,7 @@ impl OverlayBackend {
                     x = x,
                     y = y,
                     button = button_num
-                )
+                ))
             }
             MouseEvent::Scroll {
                 delta_x,
@@ -260,7 +322,7 @@ impl OverlayBackend {
                 x,
                 y,
             } => {
-                format!(
+                self.script.write(format_args!(
                     r#"(function() {{
                         const target = document.elementFromPoint({x}, {y}) || document.body;
                         target.dispatchEvent(new WheelEvent('wheel', {{
@@ -274,38 +336,39 @@ impl OverlayBackend {
                     y = y,
                     delta_x = delta_x,
                     delta_y = delta_y
-                )
+                ))
             }
         };
 
-        let _ = self.webview.evaluate_script(&js);
+        let _ = self.webview.evaluate_script(js);
     }
 
     /// Inject a keyboard event into the webview via JavaScript
     pub fn inject_keyboard(&mut self, event: KeyboardEvent) {
         let event_type = if event.pressed { "keydown" } else { "keyup" };
-        let key_escaped = event.key.replace('\\', "\\\\").replace('\'', "\\'");
-
-        let js = format!(
+        let js = self.script.write(format_args!(
             r#"(function() {{
                 const target = document.activeElement || document.body;
                 target.dispatchEvent(new KeyboardEvent('{event_type}', {{
                     bubbles: true, cancelable: true,
                     key: '{key}',
+                    code: '{code}',
                     shiftKey: {shift}, ctrlKey: {ctrl},
                     altKey: {alt}, metaKey: {meta},
                     view: window
                 }}));
             }})()"#,
             event_type = event_type,
-            key = key_escaped,
+            key = SingleQuoted(&event.key),
+            // Physical codes are plain identifiers like "KeyA", nothing to escape
+            code = event.code,
             shift = event.modifiers.shift,
             ctrl = event.modifiers.ctrl,
             alt = event.modifiers.alt,
             meta = event.modifiers.meta
-        );
+        ));
 
-        let _ = self.webview.evaluate_script(&js);
+        let _ = self.webview.evaluate_script(js);
     }
 
     /// Evaluate JavaScript in the webview
@@ -318,6 +381,10 @@ impl OverlayBackend {
 
 impl CompositeBackend for OverlayBackend {
     fn poll(&mut self) {
+        if self.state == OverlayState::ShutDown {
+            return;
+        }
+
         // Pump GTK events
         for _ in 0..10 {
             if gtk::events_pending() {
@@ -328,10 +395,13 @@ impl CompositeBackend for OverlayBackend {
         // Sync visibility with parent window state
         sync::check_parent_visibility(&self.window, self.parent_xid);
 
+        // Apply layout updates held back by the debounce
+        self.input_shape.flush(&self.window, &self.geometry());
+
         // Update state
         if self.state == OverlayState::Initializing && *self.load_finished.borrow() {
             // Set viewport dimensions via JavaScript
-            let (width, height) = self.size;
+            let (width, height) = self.geometry().css_size;
             self.webkit_webview.run_javascript(
                 &format!(
                     "document.body.style.width = '{}px'; \
@@ -347,12 +417,23 @@ impl CompositeBackend for OverlayBackend {
             self.state = OverlayState::Ready;
             tracing::info!("Overlay backend ready");
         }
+
+        self.flush_to_ui_messages();
     }
 
     fn is_ready(&self) -> bool {
         self.state == OverlayState::Ready
     }
 
+    fn lifecycle(&self) -> BackendLifecycle {
+        // The window resizes in place; there is nothing to wait for
+        match self.state {
+            OverlayState::Initializing => BackendLifecycle::Initializing,
+            OverlayState::Ready => BackendLifecycle::Ready,
+            OverlayState::ShutDown => BackendLifecycle::Error,
+        }
+    }
+
     fn capture_if_dirty(&mut self) -> Option<CaptureResult> {
         // Overlay mode uses compositor-managed rendering
         // No framebuffer capture needed - compositor handles blending
@@ -364,17 +445,26 @@ impl CompositeBackend for OverlayBackend {
     }
 
     fn resize(&mut self, width: u32, height: u32) {
-        if self.size == (width, height) {
+        if self.state == OverlayState::ShutDown
+            || (self.size == (width, height) && !self.rescale_pending)
+        {
             return;
         }
 
         self.size = (width, height);
-
-        // Resize all components
-        window::resize_window(&self.window, width, height);
-        self.container.set_size_request(width as i32, height as i32);
+        self.rescale_pending = false;
+        let geometry = self.geometry();
+        let (gtk_width, gtk_height) = geometry.size;
+        let (css_width, css_height) = geometry.css_size;
+
+        // Resize all components (GTK sizes are in GTK units, not physical pixels)
+        window::resize_window(&self.window, gtk_width, gtk_height);
+        self.container
+            .set_size_request(gtk_width as i32, gtk_height as i32);
         self.webkit_webview
-            .set_size_request(width as i32, height as i32);
+            .set_size_request(gtk_width as i32, gtk_height as i32);
+        // The zoom covers the part of the device scale GTK doesn't
+        self.webkit_webview.set_zoom_level(geometry.zoom);
 
         // Update wry webview bounds
         self.webview
@@ -392,14 +482,14 @@ impl CompositeBackend for OverlayBackend {
                  document.body.style.height = '{}px'; \
                  document.documentElement.style.width = '{}px'; \
                  document.documentElement.style.height = '{}px';",
-                width, height, width, height
+                css_width, css_height, css_width, css_height
             ),
             Cancellable::NONE,
             |_| {},
         );
 
         // Update input regions for the new size
-        window::update_input_regions(&self.window, width, height);
+        self.input_shape.apply(&self.window, &geometry);
 
         // Pump GTK events to help the resize propagate
         for _ in 0..30 {
@@ -411,25 +501,115 @@ impl CompositeBackend for OverlayBackend {
         tracing::info!("Overlay backend resized to ({}, {})", width, height);
     }
 
+    fn set_position(&mut self, x: i32, y: i32) {
+        if self.state != OverlayState::ShutDown {
+            OverlayBackend::set_position(self, x, y);
+        }
+    }
+
+    fn set_parent_visible(&mut self, visible: bool) {
+        if self.state != OverlayState::ShutDown {
+            self.sync_visibility(visible);
+        }
+    }
+
     fn send_mouse_event(&mut self, event: MouseEvent) {
-        self.inject_mouse(event);
+        if self.state != OverlayState::ShutDown {
+            self.inject_mouse(event);
+        }
     }
 
     fn send_keyboard_event(&mut self, event: KeyboardEvent) {
-        self.inject_keyboard(event);
+        if self.state != OverlayState::ShutDown {
+            self.inject_keyboard(event);
+        }
     }
 
     fn send_to_ui(&mut self, msg: BevyToUi) -> Result<(), FrontendError> {
-        let json = serde_json::to_string(&msg)
-            .map_err(|e| FrontendError::SendFailed(e.to_string()))?;
-
-        let js = format!("window.dispatchEvent(new CustomEvent('bevy-message', {{ detail: {} }}));", json);
-        self.webview
-            .evaluate_script(&js)
-            .map_err(|e| FrontendError::SendFailed(e.to_string()))
+        // Queue the message for sending during poll()
+        self.to_ui_messages.push(msg);
+        Ok(())
     }
 
     fn try_recv_from_ui(&mut self) -> Option<UiToBevy> {
-        self.from_ui_rx.try_recv().ok()
+        let msg = self.from_ui_rx.try_recv().ok()?;
+        if self.state == OverlayState::ShutDown {
+            return Some(msg);
+        }
+        // The layout drives the input shape here as well as in Bevy
+        if let UiToBevy::LayoutUpdate(layout) = &msg {
+            self.input_shape.set_layout(layout.clone());
+            self.input_shape.flush(&self.window, &self.geometry());
+        }
+        Some(msg)
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        if scale_factor > 0.0 && (scale_factor - self.scale_factor).abs() > f64::EPSILON {
+            self.scale_factor = scale_factor;
+            // Re-apply the GTK size and zoom even if the physical size is unchanged
+            self.rescale_pending = true;
+        }
+    }
+
+    fn shutdown(&mut self) {
+        if self.state == OverlayState::ShutDown {
+            return;
+        }
+        tracing::info!("Shutting down overlay backend");
+        self.state = OverlayState::ShutDown;
+        self.to_ui_messages.clear();
+
+        self.webkit_webview.stop_loading();
+        self.window.hide();
+        // SAFETY: the window is only used from this thread and every later
+        // call on it is skipped in the `ShutDown` state. wry destroys the
+        // webview again on drop, which GTK ignores for destroyed widgets.
+        unsafe {
+            self.window.destroy();
+        }
+
+        for _ in 0..200 {
+            if !gtk::events_pending() {
+                break;
+            }
+            gtk::main_iteration_do(false);
+        }
+    }
+}
+
+/// A mouse event in physical pixels moved to CSS pixels at `scale_factor`
+fn css_mouse_event(event: MouseEvent, scale_factor: f64) -> MouseEvent {
+    let scale = if scale_factor > 0.0 {
+        scale_factor as f32
+    } else {
+        1.0
+    };
+    match event {
+        MouseEvent::Move { x, y } => MouseEvent::Move {
+            x: x / scale,
+            y: y / scale,
+        },
+        MouseEvent::ButtonDown { button, x, y } => MouseEvent::ButtonDown {
+            button,
+            x: x / scale,
+            y: y / scale,
+        },
+        MouseEvent::ButtonUp { button, x, y } => MouseEvent::ButtonUp {
+            button,
+            x: x / scale,
+            y: y / scale,
+        },
+        MouseEvent::Scroll {
+            delta_x,
+            delta_y,
+            x,
+            y,
+        } => MouseEvent::Scroll {
+            delta_x,
+            delta_y,
+            x: x / scale,
+            y: y / scale,
+        },
     }
 }
@@ -2,18 +2,26 @@
 //!
 //! Handles the relationship between the overlay window and its parent (Bevy) window,
 //! including transient-for hints and visibility synchronization.
+//!
+//! On X11 the overlay is a transient toplevel whose visibility follows the
+//! parent's polled XID state. On Wayland it is a layer-shell surface, and
+//! visibility follows the parent's window events through `sync_visibility`.
 
 use gdk::prelude::*;
 use gdkx11::{X11Display, X11Window};
 use gtk::prelude::*;
 use raw_window_handle::RawWindowHandle;
 
+use crate::OverlayError;
+
 /// Set up the window relationship with the parent (transient, grouping) and position
 /// Returns the parent window XID for state tracking (X11 only)
+///
+/// Must run before the overlay window is shown.
 pub fn setup_window_relationship(
     window: &gtk::Window,
     parent_handle: RawWindowHandle,
-) -> Option<u64> {
+) -> Result<Option<u64>, OverlayError> {
     let mut parent_xid: Option<u64> = None;
 
     match parent_handle {
@@ -32,17 +40,67 @@ pub fn setup_window_relationship(
             set_transient_for_x11(window, xid);
         }
         RawWindowHandle::Wayland(_handle) => {
-            // Wayland positioning is more complex due to security model
-            // The compositor controls window positioning
-            // Transient relationships work differently on Wayland
-            tracing::info!("Wayland parent window - positioning controlled by compositor");
+            // GTK and winit hold separate Wayland connections, so the parent's
+            // wl_surface can't host a subsurface or popup of ours
+            tracing::info!("Wayland parent window");
+            setup_wayland_surface(window)?;
         }
         _ => {
             tracing::warn!("Unknown window handle type for parent positioning");
         }
     }
 
-    parent_xid
+    Ok(parent_xid)
+}
+
+/// Whether GTK itself uses the Wayland backend (not XWayland via `GDK_BACKEND=x11`)
+#[cfg(feature = "layer-shell")]
+fn gdk_uses_wayland(window: &gtk::Window) -> bool {
+    gtk::prelude::WidgetExt::display(window).type_().name() == "GdkWaylandDisplay"
+}
+
+/// Turn the overlay into a layer-shell surface above the parent
+///
+/// Wayland clients can't read or set global window positions, so the surface
+/// is anchored to the top-left of the output; it lines up with fullscreen and
+/// maximized parents and the compositor keeps it there without us tracking
+/// moves. Input shapes apply to the surface as they do on X11.
+#[cfg(feature = "layer-shell")]
+fn setup_wayland_surface(window: &gtk::Window) -> Result<(), OverlayError> {
+    use gtk_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+
+    if !gdk_uses_wayland(window) {
+        return Err(OverlayError::WaylandUnsupported(
+            "GTK is running through XWayland and cannot follow a Wayland parent".into(),
+        ));
+    }
+    if !gtk_layer_shell::is_supported() {
+        return Err(OverlayError::WaylandUnsupported(
+            "the compositor does not implement wlr-layer-shell".into(),
+        ));
+    }
+
+    // Layer-shell setup has to happen before the window is realized
+    window.init_layer_shell();
+    window.set_namespace("pentimento-overlay");
+    // Overlay stays above fullscreen parents; hidden with the parent instead
+    window.set_layer(Layer::Overlay);
+    window.set_anchor(Edge::Top, true);
+    window.set_anchor(Edge::Left, true);
+    // Ignore other surfaces' exclusive zones so we stay aligned with the parent
+    window.set_exclusive_zone(-1);
+    // Text fields in the UI still get keyboard focus when clicked
+    window.set_keyboard_mode(KeyboardMode::OnDemand);
+
+    tracing::info!("Overlay attached as a layer-shell surface");
+    Ok(())
+}
+
+#[cfg(not(feature = "layer-shell"))]
+fn setup_wayland_surface(_window: &gtk::Window) -> Result<(), OverlayError> {
+    Err(OverlayError::WaylandUnsupported(
+        "built without the `layer-shell` feature".into(),
+    ))
 }
 
 /// Set the overlay window as transient for the parent X11 window
@@ -124,7 +182,8 @@ pub fn check_parent_visibility(
 }
 
 /// Sync overlay visibility with the given parent window visibility state
-/// Called externally when the parent window state changes
+/// Called externally when the parent window state changes (the only visibility
+/// source on Wayland, where the parent can't be polled)
 pub fn sync_visibility(window: &gtk::Window, parent_visible: bool) {
     let currently_visible = window.is_visible();
     if !parent_visible && currently_visible {
@@ -3,9 +3,12 @@
 //! Provides functionality for creating transparent GTK windows that can overlay
 //! the Bevy rendering window. The compositor handles the actual blending.
 
+use std::time::{Duration, Instant};
+
 use gdk::cairo;
 use gdk::prelude::*;
 use gtk::prelude::*;
+use pentimento_ipc::LayoutInfo;
 
 use crate::OverlayError;
 
@@ -15,6 +18,10 @@ pub const SIDEBAR_WIDTH: i32 = 316; // 300px + margins
 pub const SIDEBAR_TOP: i32 = 56;
 pub const SIDEBAR_MARGIN: i32 = 8;
 
+/// Minimum time between input shape updates (layout updates stream in
+/// during panel drags)
+pub const INPUT_SHAPE_DEBOUNCE: Duration = Duration::from_millis(50);
+
 /// Create a transparent GTK toplevel window configured for overlay use
 pub fn create_transparent_window(size: (u32, u32)) -> Result<gtk::Window, OverlayError> {
     let window = gtk::Window::new(gtk::WindowType::Toplevel);
@@ -58,7 +65,8 @@ pub fn create_transparent_window(size: (u32, u32)) -> Result<gtk::Window, Overla
 /// Update input regions to allow selective passthrough
 ///
 /// Creates an input shape that covers only UI elements (toolbar, sidebar),
-/// making the 3D viewport area click-through to Bevy underneath.
+/// making the 3D viewport area click-through to Bevy underneath. Used until
+/// the UI reports its first layout; see [`InputShape`].
 pub fn update_input_regions(window: &gtk::Window, width: u32, height: u32) {
     let Some(gdk_window) = window.window() else {
         tracing::warn!("Could not get GDK window for input region setup");
@@ -99,6 +107,190 @@ pub fn update_input_regions(window: &gtk::Window, width: u32, height: u32) {
     );
 }
 
+/// Input shape driven by the `LayoutInfo` the UI reports
+///
+/// Layout updates are coalesced so the X11 shape is changed at most once per
+/// [`INPUT_SHAPE_DEBOUNCE`]; the latest layout is applied by `flush` once the
+/// interval has passed.
+#[derive(Debug, Default)]
+pub struct InputShape {
+    /// Latest layout from the UI, `None` until the first `LayoutUpdate`
+    layout: Option<LayoutInfo>,
+    /// Whether `layout` changed since the shape was last set
+    pending: bool,
+    last_update: Option<Instant>,
+}
+
+impl InputShape {
+    /// Record a new layout; applied on the next `flush` outside the debounce window
+    pub fn set_layout(&mut self, layout: LayoutInfo) {
+        self.layout = Some(layout);
+        self.pending = true;
+    }
+
+    /// Apply a pending layout if the last shape update is old enough
+    pub fn flush(&mut self, window: &gtk::Window, geometry: &OverlayGeometry) {
+        let debounced = self
+            .last_update
+            .is_some_and(|last| last.elapsed() < INPUT_SHAPE_DEBOUNCE);
+        if self.pending && !debounced {
+            self.apply(window, geometry);
+        }
+    }
+
+    /// Set the input shape now, from the layout or the fixed fallback regions
+    pub fn apply(&mut self, window: &gtk::Window, geometry: &OverlayGeometry) {
+        self.pending = false;
+        let (width, height) = geometry.size;
+        let Some(layout) = &self.layout else {
+            update_input_regions(window, width, height);
+            return;
+        };
+        let Some(gdk_window) = window.window() else {
+            tracing::warn!("Could not get GDK window for input region setup");
+            return;
+        };
+        self.last_update = Some(Instant::now());
+
+        let rects = layout_input_rects(layout, geometry.zoom, geometry.size);
+        let region = cairo::Region::create();
+        for rect in &rects {
+            let _ = region.union_rectangle(&cairo::RectangleInt::new(
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+            ));
+        }
+        // Everything outside the UI regions is click-through to Bevy
+        gdk_window.input_shape_combine_region(&region, 0, 0);
+
+        tracing::debug!(
+            "Input shape set from {} layout regions ({} rects)",
+            layout.regions.len(),
+            rects.len()
+        );
+    }
+}
+
+/// Edges of an input rectangle in GTK units (right/bottom exclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PixelRect {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+impl PixelRect {
+    fn contains(&self, other: &PixelRect) -> bool {
+        self.left <= other.left
+            && self.top <= other.top
+            && self.right >= other.right
+            && self.bottom >= other.bottom
+    }
+
+    /// The union of two rects if it is itself a rectangle
+    fn merge(&self, other: &PixelRect) -> Option<PixelRect> {
+        if self.contains(other) {
+            return Some(*self);
+        }
+        if other.contains(self) {
+            return Some(*other);
+        }
+        let same_columns = self.left == other.left && self.right == other.right;
+        let same_rows = self.top == other.top && self.bottom == other.bottom;
+        let touch_vertically = self.top <= other.bottom && other.top <= self.bottom;
+        let touch_horizontally = self.left <= other.right && other.left <= self.right;
+        if (same_columns && touch_vertically) || (same_rows && touch_horizontally) {
+            Some(PixelRect {
+                left: self.left.min(other.left),
+                top: self.top.min(other.top),
+                right: self.right.max(other.right),
+                bottom: self.bottom.max(other.bottom),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Input rectangles for a layout, scaled from CSS pixels to GTK units
+///
+/// Regions with `z_index < 0` are click-through. Rects are clipped to the
+/// window and overlapping ones coalesced where their union stays a rectangle;
+/// any remaining overlap is resolved by the region union.
+fn layout_input_rects(layout: &LayoutInfo, zoom: f64, size: (u32, u32)) -> Vec<PixelRect> {
+    let scale = if zoom > 0.0 { zoom } else { 1.0 };
+    let (width, height) = (size.0 as i32, size.1 as i32);
+
+    let mut rects: Vec<PixelRect> = layout
+        .regions
+        .iter()
+        .filter(|region| region.z_index >= 0)
+        .map(|region| PixelRect {
+            // Round outwards so region edges stay clickable
+            left: ((region.x as f64 * scale).floor() as i32).max(0),
+            top: ((region.y as f64 * scale).floor() as i32).max(0),
+            right: (((region.x + region.width) as f64 * scale).ceil() as i32).min(width),
+            bottom: (((region.y + region.height) as f64 * scale).ceil() as i32).min(height),
+        })
+        .filter(|rect| rect.right > rect.left && rect.bottom > rect.top)
+        .collect();
+
+    let mut merged = true;
+    while merged {
+        merged = false;
+        'outer: for i in 0..rects.len() {
+            for j in (i + 1)..rects.len() {
+                if let Some(union) = rects[i].merge(&rects[j]) {
+                    rects[i] = union;
+                    rects.swap_remove(j);
+                    merged = true;
+                    break 'outer;
+                }
+            }
+        }
+    }
+    rects
+}
+
+/// How the overlay's physical size maps onto GTK and WebKit
+///
+/// GTK sizes windows in units of its whole-number monitor scale; WebKit's zoom
+/// makes up the rest of a fractional device scale, so the page always lays out
+/// in the same CSS pixels as the Bevy window's logical size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OverlayGeometry {
+    /// Window size in GTK units
+    pub size: (u32, u32),
+    /// Page size in CSS pixels
+    pub css_size: (u32, u32),
+    /// WebKit zoom level, GTK units per CSS pixel
+    pub zoom: f64,
+}
+
+impl OverlayGeometry {
+    /// Geometry for a `physical` size at `scale_factor`, with GTK scaling by `gtk_scale`
+    pub fn new(physical: (u32, u32), scale_factor: f64, gtk_scale: i32) -> Self {
+        let scale_factor = if scale_factor > 0.0 {
+            scale_factor
+        } else {
+            1.0
+        };
+        let gtk_scale = f64::from(gtk_scale.max(1));
+        let scaled = |pixels: u32, scale: f64| ((f64::from(pixels) / scale).round() as u32).max(1);
+        Self {
+            size: (scaled(physical.0, gtk_scale), scaled(physical.1, gtk_scale)),
+            css_size: (
+                scaled(physical.0, scale_factor),
+                scaled(physical.1, scale_factor),
+            ),
+            zoom: scale_factor / gtk_scale,
+        }
+    }
+}
+
 /// Position the overlay window at the given coordinates
 pub fn set_position(window: &gtk::Window, x: i32, y: i32) {
     window.move_(x, y);
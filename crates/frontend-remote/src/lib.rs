@@ -0,0 +1,270 @@
+//! Remote frontend backend for Pentimento
+//!
+//! Serves the UI over HTTP so it can be opened in an ordinary desktop
+//! browser, e.g. to debug it with the browser's own developer tools. IPC
+//! messages travel as JSON text frames over a WebSocket, in the same batches
+//! the embedded backends evaluate.
+//!
+//! The browser draws the UI and handles its own input, so nothing is
+//! captured (`CaptureResult::CompositorManaged`) and mouse and keyboard
+//! events from Bevy are not forwarded. Bevy shows the 3D scene full-window.
+//!
+//! One browser tab is served at a time: a new connection replaces the
+//! previous one and is sent the latest state, so reloading the page or
+//! reconnecting after a dropped socket picks up where the app is. The server
+//! only listens on the loopback interface and refuses WebSocket connections
+//! from pages served elsewhere.
+
+mod server;
+pub mod shim;
+
+use std::collections::VecDeque;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+
+use pentimento_frontend_core::blob::BlobRegistry;
+use pentimento_frontend_core::recovery::StateReplayCache;
+use pentimento_frontend_core::{CaptureResult, CompositeBackend, FrontendError, parse_ui_messages};
+use pentimento_ipc::{BevyToUi, KeyboardEvent, MouseEvent, UiToBevy};
+use tokio::sync::mpsc;
+
+use server::{ClientSlot, ServerEvent, ServerHandle};
+
+/// Port the UI is served on unless configured otherwise
+pub const DEFAULT_PORT: u16 = 7777;
+
+/// Errors specific to the remote backend
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteError {
+    /// The port is taken or can't be listened on
+    #[error("Failed to listen on {addr}: {source}")]
+    Bind {
+        addr: SocketAddr,
+        source: std::io::Error,
+    },
+
+    /// The server thread or its runtime could not be started
+    #[error("Failed to start remote UI server: {0}")]
+    Server(std::io::Error),
+}
+
+/// UI served to a browser over HTTP and WebSocket
+pub struct RemoteBackend {
+    addr: SocketAddr,
+    size: (u32, u32),
+    client: ClientSlot,
+    /// ID of the connected client, if any
+    client_id: Option<u64>,
+    events: mpsc::UnboundedReceiver<ServerEvent>,
+    /// Messages for the UI, sent as one batch per poll
+    to_ui_messages: Vec<BevyToUi>,
+    from_ui_messages: VecDeque<UiToBevy>,
+    /// State sent to a newly connected client
+    replay: StateReplayCache,
+    // Dropped last, after the channels the server thread uses
+    _server: ServerHandle,
+}
+
+impl RemoteBackend {
+    /// Serve `html` at `http://localhost:<port>`
+    ///
+    /// The port is bound before returning, so a port that is already in use
+    /// is reported here rather than from the server thread.
+    pub fn new(
+        html: &str,
+        size: (u32, u32),
+        blobs: BlobRegistry,
+        port: u16,
+    ) -> Result<Self, RemoteError> {
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let listener =
+            TcpListener::bind(addr).map_err(|source| RemoteError::Bind { addr, source })?;
+        // Port 0 picks a free port
+        let addr = listener.local_addr().unwrap_or(addr);
+
+        let client = ClientSlot::default();
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let server = server::spawn(
+            listener,
+            shim::inject_shim(html),
+            blobs,
+            client.clone(),
+            events_tx,
+        )?;
+
+        tracing::info!("Remote UI served at http://localhost:{}", addr.port());
+
+        Ok(Self {
+            addr,
+            size,
+            client,
+            client_id: None,
+            events,
+            to_ui_messages: Vec::new(),
+            from_ui_messages: VecDeque::new(),
+            replay: StateReplayCache::new(),
+            _server: server,
+        })
+    }
+
+    /// Address the server listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn handle_event(&mut self, event: ServerEvent) {
+        match event {
+            ServerEvent::Connected(id) => {
+                self.client_id = Some(id);
+                // Bring the new page up to date before anything newer
+                let mut batch = self.replay.messages().to_vec();
+                batch.append(&mut self.to_ui_messages);
+                self.to_ui_messages = batch;
+            }
+            ServerEvent::Disconnected(id) => {
+                if self.client_id == Some(id) {
+                    self.client_id = None;
+                }
+            }
+            ServerEvent::Message { client, body } => {
+                // Anything a replaced tab sent on its way out is stale
+                if self.client_id != Some(client) {
+                    return;
+                }
+                for result in parse_ui_messages(&body) {
+                    match result {
+                        Ok(msg) => self.from_ui_messages.push_back(msg),
+                        Err(e) => {
+                            if let Some(reply) = e.reply() {
+                                self.to_ui_messages.push(reply);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send this poll's messages as one batch
+    ///
+    /// With no client connected they are dropped; the state among them is
+    /// replayed when a client connects.
+    fn flush_to_ui_messages(&mut self) {
+        if self.to_ui_messages.is_empty() {
+            return;
+        }
+        let messages = std::mem::take(&mut self.to_ui_messages);
+        if self.client_id.is_none() {
+            return;
+        }
+
+        match serde_json::to_string(&messages) {
+            Ok(batch) => {
+                tracing::trace!("Sending {} UI message(s) to remote client", messages.len());
+                if !self.client.send(batch) {
+                    tracing::debug!("Remote UI client went away; messages dropped");
+                }
+            }
+            Err(e) => tracing::error!("Failed to serialize UI messages: {}", e),
+        }
+    }
+}
+
+impl CompositeBackend for RemoteBackend {
+    fn poll(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            self.handle_event(event);
+        }
+        self.flush_to_ui_messages();
+    }
+
+    fn is_ready(&self) -> bool {
+        // Messages sent before a browser connects are replayed to it
+        true
+    }
+
+    fn capture_if_dirty(&mut self) -> Option<CaptureResult> {
+        // The browser renders the UI itself
+        Some(CaptureResult::CompositorManaged)
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        // The browser window has its own size; only remembered for `size`
+        self.size = (width, height);
+    }
+
+    fn send_mouse_event(&mut self, _event: MouseEvent) {
+        // The browser handles its own input
+    }
+
+    fn send_keyboard_event(&mut self, _event: KeyboardEvent) {
+        // The browser handles its own input
+    }
+
+    fn send_to_ui(&mut self, msg: BevyToUi) -> Result<(), FrontendError> {
+        self.replay.record(&msg);
+        self.to_ui_messages.push(msg);
+        Ok(())
+    }
+
+    fn try_recv_from_ui(&mut self) -> Option<UiToBevy> {
+        self.from_ui_messages.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pentimento_ipc::SceneInfo;
+
+    fn backend() -> RemoteBackend {
+        RemoteBackend::new("<html></html>", (800, 600), BlobRegistry::default(), 0).unwrap()
+    }
+
+    #[test]
+    fn test_new_client_gets_state_replayed() {
+        let mut remote = backend();
+        remote
+            .send_to_ui(BevyToUi::SceneUpdated(SceneInfo::default()))
+            .unwrap();
+        remote.send_to_ui(BevyToUi::CloseMenus).unwrap();
+        remote.poll();
+        assert!(remote.to_ui_messages.is_empty());
+
+        remote.send_to_ui(BevyToUi::CloseMenus).unwrap();
+        remote.handle_event(ServerEvent::Connected(1));
+        assert!(matches!(
+            remote.to_ui_messages.as_slice(),
+            [BevyToUi::SceneUpdated(_), BevyToUi::CloseMenus]
+        ));
+    }
+
+    #[test]
+    fn test_messages_from_replaced_client_are_ignored() {
+        let mut remote = backend();
+        remote.handle_event(ServerEvent::Connected(1));
+        remote.handle_event(ServerEvent::Connected(2));
+        remote.handle_event(ServerEvent::Message {
+            client: 1,
+            body: r#"{"type":"UiDirty"}"#.to_string(),
+        });
+        assert!(remote.try_recv_from_ui().is_none());
+
+        remote.handle_event(ServerEvent::Message {
+            client: 2,
+            body: r#"[{"type":"UiDirty"},{"type":"RequestTelepathy"}]"#.to_string(),
+        });
+        assert!(matches!(remote.try_recv_from_ui(), Some(UiToBevy::UiDirty)));
+        assert!(matches!(
+            remote.to_ui_messages.as_slice(),
+            [BevyToUi::Error { .. }]
+        ));
+
+        // A late disconnect of client 1 doesn't disconnect client 2
+        remote.handle_event(ServerEvent::Disconnected(1));
+        assert_eq!(remote.client_id, Some(2));
+    }
+}
@@ -0,0 +1,301 @@
+//! HTTP and WebSocket server for the remote backend
+//!
+//! Runs on its own thread with a single-threaded tokio runtime, so the app
+//! doesn't need one. The backend and the server share the current client's
+//! sender; everything the server hears about is reported as a `ServerEvent`.
+
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use futures_util::{SinkExt, StreamExt};
+use pentimento_frontend_core::blob::BlobRegistry;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::RemoteError;
+use crate::shim::{BLOB_PATH, REPLACED_CLOSE_CODE, SOCKET_PATH};
+
+/// What happened on the server since the backend last polled
+#[derive(Debug)]
+pub(crate) enum ServerEvent {
+    /// A browser connected and replaced any previous client
+    Connected(u64),
+    /// The current client went away
+    Disconnected(u64),
+    /// Text frame (one message or a batch) from a client
+    Message { client: u64, body: String },
+}
+
+/// The one connected browser tab
+struct Client {
+    id: u64,
+    tx: mpsc::UnboundedSender<String>,
+}
+
+/// Sender to whichever client is connected, shared with the server
+#[derive(Clone, Default)]
+pub(crate) struct ClientSlot(Arc<Mutex<Option<Client>>>);
+
+impl ClientSlot {
+    /// Send a text frame to the current client; false if there is none
+    pub(crate) fn send(&self, text: String) -> bool {
+        match self.0.lock().unwrap().as_ref() {
+            Some(client) => client.tx.send(text).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Make `client` the current client, returning the ID it replaced
+    fn replace(&self, client: Client) -> Option<u64> {
+        self.0.lock().unwrap().replace(client).map(|old| old.id)
+    }
+
+    /// Forget client `id` unless it was already replaced
+    fn remove(&self, id: u64) -> bool {
+        let mut slot = self.0.lock().unwrap();
+        if slot.as_ref().is_some_and(|client| client.id == id) {
+            *slot = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ServerState {
+    /// Page with the shim already injected
+    html: Bytes,
+    blobs: BlobRegistry,
+    port: u16,
+    client: ClientSlot,
+    next_client_id: Arc<AtomicU64>,
+    events: mpsc::UnboundedSender<ServerEvent>,
+}
+
+/// Running server thread, stopped on drop
+pub(crate) struct ServerHandle {
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for ServerHandle {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Serve `html` on `listener` until the returned handle is dropped
+pub(crate) fn spawn(
+    listener: TcpListener,
+    html: String,
+    blobs: BlobRegistry,
+    client: ClientSlot,
+    events: mpsc::UnboundedSender<ServerEvent>,
+) -> Result<ServerHandle, RemoteError> {
+    let port = listener.local_addr().map_err(RemoteError::Server)?.port();
+    listener
+        .set_nonblocking(true)
+        .map_err(RemoteError::Server)?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .map_err(RemoteError::Server)?;
+
+    let state = ServerState {
+        html: Bytes::from(html),
+        blobs,
+        port,
+        client,
+        next_client_id: Arc::new(AtomicU64::new(1)),
+        events,
+    };
+    let (shutdown, shutdown_rx) = oneshot::channel();
+
+    let thread = std::thread::Builder::new()
+        .name("pentimento-remote-ui".to_string())
+        .spawn(move || {
+            runtime.block_on(async move {
+                let listener = match tokio::net::TcpListener::from_std(listener) {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        tracing::error!("Remote UI server failed to start: {}", e);
+                        return;
+                    }
+                };
+                let serve = axum::serve(listener, router(state));
+                tokio::select! {
+                    result = serve => {
+                        if let Err(e) = result {
+                            tracing::error!("Remote UI server stopped: {}", e);
+                        }
+                    }
+                    _ = shutdown_rx => {}
+                }
+            });
+            // Dropping the runtime closes any open connections
+        })
+        .map_err(RemoteError::Server)?;
+
+    Ok(ServerHandle {
+        shutdown: Some(shutdown),
+        thread: Some(thread),
+    })
+}
+
+fn router(state: ServerState) -> Router {
+    Router::new()
+        .route("/", get(index))
+        .route(SOCKET_PATH, get(websocket))
+        .route(&format!("{}{{id}}", BLOB_PATH), get(blob))
+        .with_state(state)
+}
+
+async fn index(State(state): State<ServerState>) -> Html<Bytes> {
+    Html(state.html)
+}
+
+async fn blob(State(state): State<ServerState>, Path(id): Path<u64>) -> Response {
+    match state.blobs.get(id) {
+        Some(bytes) => (
+            [(header::CONTENT_TYPE, "application/octet-stream")],
+            Bytes::from_owner(bytes),
+        )
+            .into_response(),
+        None => {
+            tracing::warn!("Unknown or expired blob requested: {}", id);
+            StatusCode::NOT_FOUND.into_response()
+        }
+    }
+}
+
+async fn websocket(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    // Any page the browser has open could otherwise drive the app
+    let origin = headers
+        .get(header::ORIGIN)
+        .map(|origin| origin.to_str().unwrap_or_default());
+    if let Some(origin) = origin
+        && !is_local_origin(origin, state.port)
+    {
+        tracing::warn!("Rejected remote UI connection from origin {}", origin);
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    ws.on_upgrade(move |socket| serve_client(socket, state))
+}
+
+/// Whether `origin` is this server as reached through the loopback interface
+fn is_local_origin(origin: &str, port: u16) -> bool {
+    let Some(host) = origin.strip_prefix("http://") else {
+        return false;
+    };
+    ["localhost", "127.0.0.1"]
+        .iter()
+        .any(|name| host == format!("{}:{}", name, port))
+}
+
+/// Relay frames between a browser tab and the backend until either side is done
+async fn serve_client(socket: WebSocket, state: ServerState) {
+    let id = state.next_client_id.fetch_add(1, Ordering::Relaxed);
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    match state.client.replace(Client { id, tx }) {
+        Some(previous) => tracing::info!("Remote UI client {} replaced by {}", previous, id),
+        None => tracing::info!("Remote UI client {} connected", id),
+    }
+    let _ = state.events.send(ServerEvent::Connected(id));
+
+    let (mut sink, mut stream) = socket.split();
+
+    // The sender is dropped when a newer client takes the slot
+    let send = async {
+        while let Some(text) = rx.recv().await {
+            if sink.send(Message::Text(text.into())).await.is_err() {
+                return;
+            }
+        }
+        let _ = sink
+            .send(Message::Close(Some(CloseFrame {
+                code: REPLACED_CLOSE_CODE,
+                reason: "replaced by a newer connection".into(),
+            })))
+            .await;
+    };
+
+    let receive = async {
+        while let Some(Ok(message)) = stream.next().await {
+            match message {
+                Message::Text(body) => {
+                    let _ = state.events.send(ServerEvent::Message {
+                        client: id,
+                        body: body.to_string(),
+                    });
+                }
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = send => {}
+        _ = receive => {}
+    }
+
+    if state.client.remove(id) {
+        tracing::info!("Remote UI client {} disconnected", id);
+        let _ = state.events.send(ServerEvent::Disconnected(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_local_origins_may_connect() {
+        assert!(is_local_origin("http://localhost:7777", 7777));
+        assert!(is_local_origin("http://127.0.0.1:7777", 7777));
+        assert!(!is_local_origin("http://localhost:8080", 7777));
+        assert!(!is_local_origin("http://localhost:7777.evil.example", 7777));
+        assert!(!is_local_origin("https://example.com", 7777));
+        assert!(!is_local_origin("null", 7777));
+    }
+
+    #[test]
+    fn test_client_slot_keeps_newest_client() {
+        let slot = ClientSlot::default();
+        assert!(!slot.send("dropped".to_string()));
+
+        let (tx, mut first_rx) = mpsc::unbounded_channel();
+        assert_eq!(slot.replace(Client { id: 1, tx }), None);
+        let (tx, mut second_rx) = mpsc::unbounded_channel();
+        assert_eq!(slot.replace(Client { id: 2, tx }), Some(1));
+
+        // The replaced client's channel closes, which closes its socket
+        assert!(first_rx.try_recv().is_err());
+        assert!(slot.send("batch".to_string()));
+        assert_eq!(second_rx.try_recv().unwrap(), "batch");
+
+        // A late disconnect of the old client leaves the new one in place
+        assert!(!slot.remove(1));
+        assert!(slot.remove(2));
+        assert!(!slot.send("dropped".to_string()));
+    }
+}
@@ -0,0 +1,124 @@
+//! Script that connects a browser page to the remote backend
+//!
+//! Embedded backends inject `__PENTIMENTO_IPC__` and call the page's receive
+//! functions directly. In a browser the same bridge is backed by the
+//! WebSocket instead: posted messages are sent as text frames, and every
+//! frame received is a batch for `__PENTIMENTO_RECV_BATCH__`.
+
+use pentimento_frontend_core::batch::RECV_BATCH_FUNCTION;
+use pentimento_frontend_core::blob::BLOB_SCHEME;
+
+/// Path of the WebSocket endpoint
+pub const SOCKET_PATH: &str = "/ws";
+
+/// Path prefix blobs are served under (`/blob/<id>`)
+pub const BLOB_PATH: &str = "/blob/";
+
+/// Close code sent to a client replaced by a newer connection. The page
+/// doesn't reconnect after it, so two tabs don't keep taking turns.
+pub const REPLACED_CLOSE_CODE: u16 = 4000;
+
+/// Longest wait between reconnect attempts, in milliseconds
+const MAX_RECONNECT_DELAY_MS: u32 = 5000;
+
+fn shim_js() -> String {
+    format!(
+        r#"
+        (function() {{
+            if (window.__PENTIMENTO_IPC__) return; // Already injected
+
+            var socket = null;
+            var outbox = [];
+            var delay = 250;
+
+            function connect() {{
+                var scheme = location.protocol === 'https:' ? 'wss://' : 'ws://';
+                socket = new WebSocket(scheme + location.host + '{socket_path}');
+                socket.onopen = function() {{
+                    delay = 250;
+                    outbox.splice(0).forEach(function(msg) {{ socket.send(msg); }});
+                }};
+                socket.onmessage = function(event) {{
+                    if (window.{batch_fn}) {{
+                        window.{batch_fn}(event.data);
+                    }} else {{
+                        console.warn('{batch_fn} not defined, UI messages dropped');
+                    }}
+                }};
+                socket.onclose = function(event) {{
+                    socket = null;
+                    if (event.code === {replaced_code}) {{
+                        console.warn('Pentimento UI was opened in another tab; this tab is disconnected');
+                        return;
+                    }}
+                    setTimeout(connect, delay);
+                    delay = Math.min(delay * 2, {max_delay});
+                }};
+            }}
+
+            // Messages posted before the socket opens are sent once it does
+            window.__PENTIMENTO_IPC__ = {{
+                postMessage: function(msg) {{
+                    if (socket && socket.readyState === WebSocket.OPEN) {{
+                        socket.send(msg);
+                    }} else {{
+                        outbox.push(msg);
+                    }}
+                }}
+            }};
+
+            // Blobs are announced as {blob_scheme}://<id> URLs
+            var fetch = window.fetch.bind(window);
+            window.fetch = function(input, init) {{
+                var prefix = '{blob_scheme}://';
+                if (typeof input === 'string' && input.indexOf(prefix) === 0) {{
+                    input = '{blob_path}' + input.slice(prefix.length);
+                }}
+                return fetch(input, init);
+            }};
+
+            // Connect once the bridge has registered its receive functions
+            if (document.readyState === 'loading') {{
+                document.addEventListener('DOMContentLoaded', connect);
+            }} else {{
+                connect();
+            }}
+        }})();
+        "#,
+        socket_path = SOCKET_PATH,
+        batch_fn = RECV_BATCH_FUNCTION,
+        replaced_code = REPLACED_CLOSE_CODE,
+        max_delay = MAX_RECONNECT_DELAY_MS,
+        blob_scheme = BLOB_SCHEME,
+        blob_path = BLOB_PATH,
+    )
+}
+
+/// `html` with the shim as the first script in `<head>`, so it runs before
+/// the UI bundle looks for `__PENTIMENTO_IPC__`
+pub fn inject_shim(html: &str) -> String {
+    let script = format!("<script>{}</script>", shim_js());
+    let head_end = html
+        .find("<head")
+        .and_then(|start| html[start..].find('>').map(|end| start + end + 1));
+    match head_end {
+        Some(at) => format!("{}{}{}", &html[..at], script, &html[at..]),
+        None => format!("{}{}", script, html),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inject_shim_runs_before_page_scripts() {
+        let html = r#"<!DOCTYPE html><html><head lang="en"><script type="module">app()</script></head></html>"#;
+        let injected = inject_shim(html);
+        let shim = injected.find("__PENTIMENTO_IPC__").unwrap();
+        assert!(injected.starts_with(r#"<!DOCTYPE html><html><head lang="en"><script>"#));
+        assert!(shim < injected.find("app()").unwrap());
+
+        assert!(inject_shim("<body></body>").starts_with("<script>"));
+    }
+}
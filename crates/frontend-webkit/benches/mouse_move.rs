@@ -0,0 +1,24 @@
+//! Mouse-move hot path: building the injected script for each move event
+//!
+//! Run with `cargo bench --bench mouse_move`. Once the buffer has grown, each
+//! iteration should format in place without allocating.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pentimento_frontend_core::script::ScriptBuffer;
+use pentimento_frontend_webkit::input_mouse::generate_mouse_move_js;
+
+fn mouse_move(c: &mut Criterion) {
+    let mut script = ScriptBuffer::new();
+    let mut x = 0.0_f32;
+    c.bench_function("generate_mouse_move_js", |b| {
+        b.iter(|| {
+            x = (x + 1.5) % 1920.0;
+            let js =
+                generate_mouse_move_js(&mut script, black_box(x), black_box(540.25), 1920, 1080);
+            black_box(js.len())
+        })
+    });
+}
+
+criterion_group!(benches, mouse_move);
+criterion_main!(benches);
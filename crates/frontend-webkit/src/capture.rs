@@ -1,6 +1,13 @@
 //! WebKit capture functionality using Cairo snapshots
+//!
+//! The snapshot callback runs on the GTK thread, so it only copies the raw
+//! Cairo pixels there. Un-premultiplying and repacking into RGBA happens on a
+//! worker thread, and `capture` picks up the finished image on a later frame.
 
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
 
 use cairo::{Format, ImageSurface};
 use gio::Cancellable;
@@ -9,11 +16,84 @@ use webkit2gtk::{SnapshotOptions, SnapshotRegion, WebViewExt};
 use crate::state::WebviewState;
 use crate::WebKitBackend;
 
+/// Latest decoded snapshot, written by the decode worker
+pub type SnapshotCache = Arc<Mutex<Option<image::RgbaImage>>>;
+
+/// Raw premultiplied BGRA pixels copied out of a Cairo image surface
+pub struct RawSnapshot {
+    pub data: Vec<u8>,
+    /// Bytes per row, which Cairo may pad beyond `width * 4`
+    pub stride: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Worker thread that turns raw snapshots into RGBA images
+///
+/// `pending` stays set from the start of a snapshot until its image lands in
+/// the cache (or the snapshot fails), so only one snapshot is in flight.
+pub struct SnapshotDecoder {
+    jobs: Option<Sender<RawSnapshot>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SnapshotDecoder {
+    pub fn spawn(cache: SnapshotCache, pending: Arc<AtomicBool>) -> Self {
+        let (jobs, rx) = mpsc::channel::<RawSnapshot>();
+        let worker = std::thread::Builder::new()
+            .name("webkit-snapshot-decode".to_string())
+            .spawn(move || {
+                for raw in rx {
+                    let _span = tracing::debug_span!(
+                        "webkit_snapshot_decode",
+                        width = raw.width,
+                        height = raw.height
+                    )
+                    .entered();
+                    match bgra_premultiplied_to_rgba(&raw) {
+                        Ok(img) => {
+                            *cache.lock().unwrap_or_else(|e| e.into_inner()) = Some(img);
+                            tracing::debug!("Snapshot decoded at {}x{}", raw.width, raw.height);
+                        }
+                        Err(e) => tracing::error!("Failed to decode snapshot: {}", e),
+                    }
+                    pending.store(false, Ordering::SeqCst);
+                }
+            });
+        let worker = match worker {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                tracing::error!("Failed to spawn snapshot decode thread: {}", e);
+                None
+            }
+        };
+        Self {
+            jobs: worker.is_some().then_some(jobs),
+            worker,
+        }
+    }
+
+    /// Sender handle for use inside the snapshot callback
+    fn sender(&self) -> Option<Sender<RawSnapshot>> {
+        self.jobs.clone()
+    }
+}
+
+impl Drop for SnapshotDecoder {
+    fn drop(&mut self) {
+        // Closing the channel ends the worker loop.
+        self.jobs = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
 impl WebKitBackend {
     /// Capture the current webview content as an RGBA image
     pub fn capture(&mut self) -> Option<image::RgbaImage> {
         // Check if we have a cached snapshot ready
-        if let Some(img) = self.snapshot_cache.borrow_mut().take() {
+        if let Some(img) = self.take_decoded_snapshot() {
             return Some(img);
         }
 
@@ -36,17 +116,18 @@ impl WebKitBackend {
         }
 
         // If a snapshot is already pending, don't start another one
-        if *self.snapshot_pending.borrow() {
+        if self.snapshot_pending.load(Ordering::SeqCst) {
             // Preserve dirty so we pull the pending snapshot on a later frame.
             self.dirty.store(true, Ordering::SeqCst);
             return None;
         }
 
         // Start async snapshot capture
-        *self.snapshot_pending.borrow_mut() = true;
+        self.snapshot_pending.store(true, Ordering::SeqCst);
 
-        let cache = self.snapshot_cache.clone();
         let pending = self.snapshot_pending.clone();
+        let fallback_cache = self.snapshot_cache.clone();
+        let jobs = self.snapshot_decoder.sender();
         // Capture the known viewport size for the async closure
         let (width, height) = self.size;
 
@@ -58,55 +139,77 @@ impl WebKitBackend {
             SnapshotOptions::TRANSPARENT_BACKGROUND,
             Cancellable::NONE,
             move |result| {
-                *pending.borrow_mut() = false;
-
-                match result {
-                    Ok(surface) => {
-                        // Pass explicit dimensions to ensure correct output size
-                        match cairo_surface_to_rgba(&surface, width as i32, height as i32) {
-                            Ok(img) => {
-                                *cache.borrow_mut() = Some(img);
-                                tracing::debug!(
-                                    "Snapshot captured successfully at {}x{}",
-                                    width,
-                                    height
-                                );
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to convert Cairo surface: {}", e);
-                            }
-                        }
-                    }
+                let surface = match result {
+                    Ok(surface) => surface,
                     Err(e) => {
                         tracing::warn!("WebKitGTK snapshot failed: {}", e);
+                        pending.store(false, Ordering::SeqCst);
+                        return;
+                    }
+                };
+
+                // Only the copy out of Cairo happens on the GTK thread.
+                let raw = {
+                    let _span =
+                        tracing::debug_span!("webkit_snapshot_copy", width, height).entered();
+                    copy_cairo_surface(&surface, width as i32, height as i32)
+                };
+                let raw = match raw {
+                    Ok(raw) => raw,
+                    Err(e) => {
+                        tracing::error!("Failed to copy Cairo surface: {}", e);
+                        pending.store(false, Ordering::SeqCst);
+                        return;
                     }
+                };
+
+                let unsent = match &jobs {
+                    Some(jobs) => jobs.send(raw).err().map(|e| e.0),
+                    None => Some(raw),
+                };
+                if let Some(raw) = unsent {
+                    // No worker to hand off to; decode here instead.
+                    match bgra_premultiplied_to_rgba(&raw) {
+                        Ok(img) => {
+                            *fallback_cache.lock().unwrap_or_else(|e| e.into_inner()) = Some(img);
+                        }
+                        Err(e) => tracing::error!("Failed to decode snapshot: {}", e),
+                    }
+                    pending.store(false, Ordering::SeqCst);
                 }
             },
         );
 
-        // Check if the snapshot completed immediately.
-        let captured = self.snapshot_cache.borrow_mut().take();
+        // Check if the snapshot already finished decoding.
+        let captured = self.take_decoded_snapshot();
         if captured.is_none() {
-            // Keep dirty so we keep polling until the async snapshot finishes.
+            // Keep dirty so we keep polling until the decode finishes.
             self.dirty.store(true, Ordering::SeqCst);
         }
         captured
     }
+
+    fn take_decoded_snapshot(&self) -> Option<image::RgbaImage> {
+        self.snapshot_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
 }
 
-/// Convert a Cairo surface to an RGBA image at the specified dimensions
+/// Paint a Cairo surface into an ARGB32 image surface of the given size and
+/// copy out its bytes
+///
 /// Uses explicit width/height instead of extracting from surface for reliable sizing
-pub fn cairo_surface_to_rgba(
+pub fn copy_cairo_surface(
     surface: &cairo::Surface,
     width: i32,
     height: i32,
-) -> Result<image::RgbaImage, String> {
+) -> Result<RawSnapshot, String> {
     if width <= 0 || height <= 0 {
         return Err(format!("Invalid dimensions: {}x{}", width, height));
     }
 
-    tracing::trace!("Converting Cairo surface to RGBA at {}x{}", width, height);
-
     let mut img_surface = ImageSurface::create(Format::ARgb32, width, height)
         .map_err(|e| format!("Failed to create image surface: {}", e))?;
 
@@ -121,40 +224,74 @@ pub fn cairo_surface_to_rgba(
     drop(ctx);
     img_surface.flush();
 
-    // Get the raw pixel data
+    let stride = img_surface.stride() as usize;
     let data = img_surface
         .data()
         .map_err(|e| format!("Failed to get surface data: {}", e))?;
 
-    // Convert from Cairo's BGRA/ARGB format to RGBA
-    // Cairo uses pre-multiplied alpha in native byte order
-    let mut rgba_data = Vec::with_capacity((width * height * 4) as usize);
-
-    for chunk in data.chunks_exact(4) {
-        // Cairo on Linux (little-endian) stores as BGRA
-        let b = chunk[0];
-        let g = chunk[1];
-        let r = chunk[2];
-        let a = chunk[3];
-
-        // Un-premultiply alpha if needed
-        let (r, g, b) = if a > 0 && a < 255 {
-            let alpha = a as f32 / 255.0;
-            (
-                (r as f32 / alpha).min(255.0) as u8,
-                (g as f32 / alpha).min(255.0) as u8,
-                (b as f32 / alpha).min(255.0) as u8,
-            )
-        } else {
-            (r, g, b)
-        };
+    Ok(RawSnapshot {
+        data: data.to_vec(),
+        stride,
+        width: width as u32,
+        height: height as u32,
+    })
+}
 
-        rgba_data.push(r);
-        rgba_data.push(g);
-        rgba_data.push(b);
-        rgba_data.push(a);
+/// Convert a Cairo surface to an RGBA image at the specified dimensions
+///
+/// Does the whole conversion on the calling thread; `capture` splits it
+/// between `copy_cairo_surface` and the decode worker instead.
+pub fn cairo_surface_to_rgba(
+    surface: &cairo::Surface,
+    width: i32,
+    height: i32,
+) -> Result<image::RgbaImage, String> {
+    tracing::trace!("Converting Cairo surface to RGBA at {}x{}", width, height);
+    let raw = copy_cairo_surface(surface, width, height)?;
+    bgra_premultiplied_to_rgba(&raw)
+}
+
+/// Convert Cairo's premultiplied BGRA rows to straight-alpha RGBA
+pub fn bgra_premultiplied_to_rgba(raw: &RawSnapshot) -> Result<image::RgbaImage, String> {
+    let row_bytes = raw.width as usize * 4;
+    let height = raw.height as usize;
+    if raw.stride < row_bytes || raw.data.len() < raw.stride * height.saturating_sub(1) + row_bytes
+    {
+        return Err(format!(
+            "Snapshot buffer too small: {} bytes, stride {} for {}x{}",
+            raw.data.len(),
+            raw.stride,
+            raw.width,
+            raw.height
+        ));
+    }
+
+    let mut rgba_data = Vec::with_capacity(row_bytes * height);
+
+    for row in raw.data.chunks(raw.stride).take(height) {
+        for chunk in row[..row_bytes].chunks_exact(4) {
+            // Cairo on Linux (little-endian) stores as BGRA
+            let b = chunk[0];
+            let g = chunk[1];
+            let r = chunk[2];
+            let a = chunk[3];
+
+            // Un-premultiply alpha if needed
+            let (r, g, b) = if a > 0 && a < 255 {
+                let alpha = a as f32 / 255.0;
+                (
+                    (r as f32 / alpha).min(255.0) as u8,
+                    (g as f32 / alpha).min(255.0) as u8,
+                    (b as f32 / alpha).min(255.0) as u8,
+                )
+            } else {
+                (r, g, b)
+            };
+
+            rgba_data.extend_from_slice(&[r, g, b, a]);
+        }
     }
 
-    image::RgbaImage::from_raw(width as u32, height as u32, rgba_data)
+    image::RgbaImage::from_raw(raw.width, raw.height, rgba_data)
         .ok_or_else(|| "Failed to create image from raw data".to_string())
 }
@@ -4,15 +4,20 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use gio::prelude::*;
 use gtk::prelude::*;
-use pentimento_frontend_core::FrontendError;
+use pentimento_frontend_core::console::{console_shim_js, ConsoleForwarder, ConsoleMessage};
+use pentimento_frontend_core::recovery::{CrashRecovery, StateReplayCache};
+use pentimento_frontend_core::script::ScriptBuffer;
+use pentimento_frontend_core::{parse_ui_messages, FrontendError};
 use pentimento_ipc::UiToBevy;
 use tokio::sync::mpsc;
-use webkit2gtk::{LoadEvent, WebView as WebKitWebView, WebViewExt};
+use webkit2gtk::{LoadEvent, WebProcessTerminationReason, WebView as WebKitWebView, WebViewExt};
 use wry::WebViewBuilderExtUnix;
 
+use crate::capture::{SnapshotCache, SnapshotDecoder};
 use crate::state::WebviewState;
 use crate::WebKitBackend;
 
@@ -42,6 +47,11 @@ impl WebKitBackend {
         // Clone for IPC handler
         let dirty_clone = dirty.clone();
 
+        // Console messages posted by the shim (rate limited) and IPC error replies
+        let console_logs = Rc::new(RefCell::new(Vec::new()));
+        let console_logs_clone = console_logs.clone();
+        let console = RefCell::new(ConsoleForwarder::new());
+
         // Create WebView using wry's GTK extension
         // CRITICAL: Set explicit bounds - without this, wry defaults to a small size
         // and the webview renders fuzzy. This matches overlay mode which works perfectly.
@@ -52,14 +62,31 @@ impl WebKitBackend {
                 position: wry::dpi::PhysicalPosition::new(0, 0).into(),
                 size: wry::dpi::PhysicalSize::new(size.0, size.1).into(),
             })
+            .with_initialization_script(console_shim_js())
             .with_ipc_handler(move |msg: wry::http::Request<String>| {
                 let body = msg.body();
-                if let Ok(ui_msg) = serde_json::from_str::<UiToBevy>(body) {
-                    // Mark dirty when UI sends UiDirty message
-                    if matches!(ui_msg, UiToBevy::UiDirty) {
-                        dirty_clone.store(true, Ordering::SeqCst);
+                if let Some(console_msg) = ConsoleMessage::from_ipc(body) {
+                    let ui_log = console.borrow_mut().forward(console_msg, Instant::now());
+                    if let Some(ui_log) = ui_log {
+                        console_logs_clone.borrow_mut().push(ui_log);
+                    }
+                    return;
+                }
+                for result in parse_ui_messages(body) {
+                    match result {
+                        Ok(ui_msg) => {
+                            // Mark dirty when UI sends UiDirty message
+                            if matches!(ui_msg, UiToBevy::UiDirty) {
+                                dirty_clone.store(true, Ordering::SeqCst);
+                            }
+                            let _ = from_ui_tx.send(ui_msg);
+                        }
+                        Err(e) => {
+                            if let Some(reply) = e.reply() {
+                                console_logs_clone.borrow_mut().push(reply);
+                            }
+                        }
                     }
-                    let _ = from_ui_tx.send(ui_msg);
                 }
             })
             .build_gtk(&container)
@@ -87,6 +114,21 @@ impl WebKitBackend {
             }
         });
 
+        // Detect web process crashes; the page is gone until it's reloaded
+        let terminated = Rc::new(RefCell::new(None));
+        let terminated_clone = terminated.clone();
+        webkit_webview.connect_web_process_terminated(move |_webview, reason| {
+            let reason = match reason {
+                WebProcessTerminationReason::Crashed => "crashed".to_string(),
+                WebProcessTerminationReason::ExceededMemoryLimit => {
+                    "exceeded memory limit".to_string()
+                }
+                other => format!("{:?}", other),
+            };
+            tracing::error!("WebKit web process terminated: {}", reason);
+            *terminated_clone.borrow_mut() = Some(reason);
+        });
+
         // Show the offscreen window to realize all widgets and set up GL contexts
         // This is needed for WebKit to properly render content
         offscreen_window.show_all();
@@ -106,6 +148,11 @@ impl WebKitBackend {
             tracing::debug!("Container widget realized successfully");
         }
 
+        let snapshot_cache = SnapshotCache::default();
+        let snapshot_pending = Arc::new(AtomicBool::new(false));
+        let snapshot_decoder =
+            SnapshotDecoder::spawn(snapshot_cache.clone(), snapshot_pending.clone());
+
         Ok(Self {
             webview,
             webkit_webview,
@@ -113,12 +160,19 @@ impl WebKitBackend {
             offscreen_window,
             size,
             dirty,
-            snapshot_cache: Rc::new(RefCell::new(None)),
-            snapshot_pending: Rc::new(RefCell::new(false)),
+            snapshot_cache,
+            snapshot_pending,
+            snapshot_decoder,
             state: WebviewState::Initializing,
             load_finished,
+            terminated,
+            console_logs,
+            recovery: CrashRecovery::new(),
+            replay_cache: StateReplayCache::new(),
+            crash_reason: None,
             frames_until_capture_allowed: 0,
             scale_factor: 1.0,
+            script: ScriptBuffer::new(),
             to_ui_tx: None,
             from_ui_rx: None,
         })
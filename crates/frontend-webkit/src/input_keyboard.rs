@@ -3,6 +3,7 @@
 use std::sync::atomic::Ordering;
 
 use gio::Cancellable;
+use pentimento_frontend_core::script::SingleQuoted;
 use pentimento_ipc::KeyboardEvent;
 use webkit2gtk::WebViewExt;
 
@@ -14,16 +15,14 @@ impl WebKitBackend {
         // Use JavaScript to dispatch DOM keyboard events
         let event_type = if event.pressed { "keydown" } else { "keyup" };
 
-        // Escape the key for JavaScript string
-        let key_escaped = event.key.replace('\\', "\\\\").replace('\'', "\\'");
-
-        let js = format!(
+        let js = self.script.write(format_args!(
             r#"(function() {{
                 const target = document.activeElement || document.body;
                 target.dispatchEvent(new KeyboardEvent('{event_type}', {{
                     bubbles: true,
                     cancelable: true,
                     key: '{key}',
+                    code: '{code}',
                     shiftKey: {shift},
                     ctrlKey: {ctrl},
                     altKey: {alt},
@@ -38,15 +37,18 @@ impl WebKitBackend {
                 }}
             }})()"#,
             event_type = event_type,
-            key = key_escaped,
+            // Escaped for the single-quoted JavaScript string
+            key = SingleQuoted(&event.key),
+            // Physical codes are plain identifiers like "KeyA", nothing to escape
+            code = event.code,
             shift = event.modifiers.shift,
             ctrl = event.modifiers.ctrl,
             alt = event.modifiers.alt,
             meta = event.modifiers.meta
-        );
+        ));
 
         self.webkit_webview
-            .run_javascript(&js, Cancellable::NONE, |_| {});
+            .run_javascript(js, Cancellable::NONE, |_| {});
 
         // Only mark dirty for key presses (not releases) that might change UI
         // Modifier keys alone don't typically change visible UI
@@ -1,8 +1,10 @@
 //! Mouse input injection for WebKit backend
 
+use std::fmt;
 use std::sync::atomic::Ordering;
 
 use gio::Cancellable;
+use pentimento_frontend_core::script::ScriptBuffer;
 use pentimento_ipc::{MouseButton, MouseEvent};
 use webkit2gtk::WebViewExt;
 
@@ -35,11 +37,12 @@ impl WebKitBackend {
         // 1. Dispatch the DOM event
         // 2. Use requestAnimationFrame to wait for Svelte to re-render
         // 3. Send IPC message to mark dirty AFTER the DOM has updated
+        let (view_width, view_height) = self.size;
         let (js, needs_raf_dirty) = match event {
             MouseEvent::Move { x, y } => {
                 // Mouse move doesn't need dirty update
                 (
-                    generate_mouse_move_js(x, y, self.size.0, self.size.1),
+                    generate_mouse_move_js(&mut self.script, x, y, view_width, view_height),
                     false,
                 )
             }
@@ -51,7 +54,14 @@ impl WebKitBackend {
                 };
                 // mousedown alone typically doesn't change visible UI state much
                 (
-                    generate_mouse_down_js(x, y, button_num, self.size.0, self.size.1),
+                    generate_mouse_down_js(
+                        &mut self.script,
+                        x,
+                        y,
+                        button_num,
+                        view_width,
+                        view_height,
+                    ),
                     false,
                 )
             }
@@ -63,7 +73,14 @@ impl WebKitBackend {
                 };
                 // Click is where state changes happen - use RAF to wait for DOM update
                 (
-                    generate_mouse_up_js(x, y, button_num, self.size.0, self.size.1),
+                    generate_mouse_up_js(
+                        &mut self.script,
+                        x,
+                        y,
+                        button_num,
+                        view_width,
+                        view_height,
+                    ),
                     true,
                 )
             }
@@ -73,14 +90,22 @@ impl WebKitBackend {
                 x,
                 y,
             } => (
-                generate_scroll_js(x, y, delta_x, delta_y, self.size.0, self.size.1),
+                generate_scroll_js(
+                    &mut self.script,
+                    x,
+                    y,
+                    delta_x,
+                    delta_y,
+                    view_width,
+                    view_height,
+                ),
                 true,
             ),
         };
 
         // Execute the JavaScript to dispatch the event
         self.webkit_webview
-            .run_javascript(&js, Cancellable::NONE, |_| {});
+            .run_javascript(js, Cancellable::NONE, |_| {});
 
         // Delay capture after mouse events to allow RAF callbacks and layout/paint to complete
         // This prevents capturing WebKit in an intermediate render state (which causes fuzziness)
@@ -137,24 +162,47 @@ fn hover_update_js() -> &'static str {
                         window.__PENTIMENTO_UPDATE_HOVER(hoverTarget);"#
 }
 
-/// Generate coordinate scaling JavaScript
-fn coordinate_scaling_js(x: f32, y: f32, view_width: u32, view_height: u32) -> String {
-    format!(
-        r#"const viewWidth = {view_width};
+/// Coordinate scaling JavaScript, written in place when formatted
+struct CoordinateScaling {
+    x: f32,
+    y: f32,
+    view_width: u32,
+    view_height: u32,
+}
+
+impl fmt::Display for CoordinateScaling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let CoordinateScaling {
+            x,
+            y,
+            view_width,
+            view_height,
+        } = *self;
+        write!(
+            f,
+            r#"const viewWidth = {view_width};
                         const viewHeight = {view_height};
                         const scaleX = viewWidth > 0 ? window.innerWidth / viewWidth : 1;
                         const scaleY = viewHeight > 0 ? window.innerHeight / viewHeight : 1;
                         const cx = {x} * (Number.isFinite(scaleX) && scaleX > 0 ? scaleX : 1);
                         const cy = {y} * (Number.isFinite(scaleY) && scaleY > 0 ? scaleY : 1);"#,
-        x = x,
-        y = y,
-        view_width = view_width,
-        view_height = view_height
-    )
+            x = x,
+            y = y,
+            view_width = view_width,
+            view_height = view_height
+        )
+    }
 }
 
-fn generate_mouse_move_js(x: f32, y: f32, view_width: u32, view_height: u32) -> String {
-    format!(
+/// Script dispatching a mousemove at view coordinates (x, y), written into `script`
+pub fn generate_mouse_move_js(
+    script: &mut ScriptBuffer,
+    x: f32,
+    y: f32,
+    view_width: u32,
+    view_height: u32,
+) -> &str {
+    script.write(format_args!(
         r#"(function() {{
                         {coords}
                         {target_finding}
@@ -167,14 +215,26 @@ fn generate_mouse_move_js(x: f32, y: f32, view_width: u32, view_height: u32) ->
                             view: window
                         }}));
                     }})()"#,
-        coords = coordinate_scaling_js(x, y, view_width, view_height),
+        coords = CoordinateScaling {
+            x,
+            y,
+            view_width,
+            view_height
+        },
         target_finding = target_finding_js(),
         hover_update = hover_update_js()
-    )
+    ))
 }
 
-fn generate_mouse_down_js(x: f32, y: f32, button: i32, view_width: u32, view_height: u32) -> String {
-    format!(
+fn generate_mouse_down_js(
+    script: &mut ScriptBuffer,
+    x: f32,
+    y: f32,
+    button: i32,
+    view_width: u32,
+    view_height: u32,
+) -> &str {
+    script.write(format_args!(
         r#"(function() {{
                         {coords}
                         {target_finding}
@@ -191,15 +251,27 @@ fn generate_mouse_down_js(x: f32, y: f32, button: i32, view_width: u32, view_hei
                             view: window
                         }}));
                     }})()"#,
-        coords = coordinate_scaling_js(x, y, view_width, view_height),
+        coords = CoordinateScaling {
+            x,
+            y,
+            view_width,
+            view_height
+        },
         target_finding = target_finding_js(),
         hover_update = hover_update_js(),
         button = button
-    )
+    ))
 }
 
-fn generate_mouse_up_js(x: f32, y: f32, button: i32, view_width: u32, view_height: u32) -> String {
-    format!(
+fn generate_mouse_up_js(
+    script: &mut ScriptBuffer,
+    x: f32,
+    y: f32,
+    button: i32,
+    view_width: u32,
+    view_height: u32,
+) -> &str {
+    script.write(format_args!(
         r#"(function() {{
                         {coords}
                         {target_finding}
@@ -232,22 +304,28 @@ fn generate_mouse_up_js(x: f32, y: f32, button: i32, view_width: u32, view_heigh
                             }});
                         }}
                     }})()"#,
-        coords = coordinate_scaling_js(x, y, view_width, view_height),
+        coords = CoordinateScaling {
+            x,
+            y,
+            view_width,
+            view_height
+        },
         target_finding = target_finding_js(),
         hover_update = hover_update_js(),
         button = button
-    )
+    ))
 }
 
 fn generate_scroll_js(
+    script: &mut ScriptBuffer,
     x: f32,
     y: f32,
     delta_x: f32,
     delta_y: f32,
     view_width: u32,
     view_height: u32,
-) -> String {
-    format!(
+) -> &str {
+    script.write(format_args!(
         r#"(function() {{
                         {coords}
                         {target_finding}
@@ -269,10 +347,15 @@ fn generate_scroll_js(
                             }}
                         }});
                     }})()"#,
-        coords = coordinate_scaling_js(x, y, view_width, view_height),
+        coords = CoordinateScaling {
+            x,
+            y,
+            view_width,
+            view_height
+        },
         target_finding = target_finding_js(),
         hover_update = hover_update_js(),
         delta_x = delta_x,
         delta_y = delta_y
-    )
+    ))
 }
@@ -2,14 +2,28 @@
 //!
 //! This crate provides a WebKitGTK-based backend for the Pentimento UI,
 //! implementing the `CompositeBackend` trait for integration with Bevy rendering.
+//!
+//! Page console output is forwarded to `tracing` and to the UI as
+//! `BevyToUi::UiLog` through an injected console shim.
+//!
+//! If the WebKit web process dies (`web-process-terminated`), the page is
+//! reloaded after a backoff and the latest state messages are sent again.
 
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use gio::Cancellable;
-use pentimento_frontend_core::{CaptureResult, CompositeBackend, FrontendError};
+use gtk::prelude::*;
+use pentimento_frontend_core::recovery::{
+    is_state_message, CrashRecovery, StateReplayCache, MAX_RECOVERY_ATTEMPTS,
+};
+use pentimento_frontend_core::script::ScriptBuffer;
+use pentimento_frontend_core::{
+    AlphaMode8, BackendLifecycle, CaptureResult, CompositeBackend, FrontendError,
+};
 use pentimento_ipc::{BevyToUi, KeyboardEvent, MouseEvent, UiToBevy};
 use tokio::sync::mpsc;
 use webkit2gtk::{WebView as WebKitWebView, WebViewExt};
@@ -22,7 +36,10 @@ pub mod resize;
 pub mod state;
 pub mod utils;
 
-use state::{WebviewState, READY_GTK_ITERATIONS, WARMUP_FRAMES, WARMUP_GTK_ITERATIONS};
+use state::{
+    WebviewState, READY_GTK_ITERATIONS, SHUTDOWN_GTK_ITERATIONS, WARMUP_FRAMES,
+    WARMUP_GTK_ITERATIONS,
+};
 
 /// WebKit backend for Pentimento capture mode
 ///
@@ -34,23 +51,36 @@ pub struct WebKitBackend {
     #[allow(dead_code)]
     container: gtk::Fixed,
     /// Offscreen window to host the container (needed for widget realization)
-    #[allow(dead_code)]
     offscreen_window: gtk::OffscreenWindow,
     size: (u32, u32),
     dirty: Arc<AtomicBool>,
-    /// Cached snapshot result from async capture
-    snapshot_cache: Rc<RefCell<Option<image::RgbaImage>>>,
-    /// Flag indicating snapshot is in progress
-    snapshot_pending: Rc<RefCell<bool>>,
+    /// Latest decoded snapshot, filled by the decode worker
+    snapshot_cache: capture::SnapshotCache,
+    /// Set while a snapshot is being taken or decoded
+    snapshot_pending: Arc<AtomicBool>,
+    /// Worker thread that converts raw snapshots off the GTK thread
+    snapshot_decoder: capture::SnapshotDecoder,
     /// Current lifecycle state
     state: WebviewState,
     /// Flag set when WebKit reports load finished
     load_finished: Rc<RefCell<bool>>,
+    /// Reason the web process died, set by `web-process-terminated`
+    terminated: Rc<RefCell<Option<String>>>,
+    /// `UiLog` messages from the console shim and IPC error replies, sent to
+    /// the UI on poll
+    console_logs: Rc<RefCell<Vec<BevyToUi>>>,
+    recovery: CrashRecovery,
+    /// Latest state messages, replayed after a crash
+    replay_cache: StateReplayCache,
+    /// Why the web process died, while a reload is pending
+    crash_reason: Option<String>,
     /// Frames to wait after mouse event before allowing capture
     /// Prevents capturing intermediate render state during RAF callback processing
     frames_until_capture_allowed: u32,
     /// Current device scale factor for HiDPI rendering
     scale_factor: f64,
+    /// Reused for the scripts that inject input events
+    script: ScriptBuffer,
     /// Channel for sending messages to the UI
     to_ui_tx: Option<mpsc::UnboundedSender<BevyToUi>>,
     /// Channel for receiving messages from the UI
@@ -73,7 +103,11 @@ impl WebKitBackend {
         // Determine how many GTK iterations based on current state
         let iterations = match self.state {
             WebviewState::Initializing | WebviewState::WarmingUp { .. } => WARMUP_GTK_ITERATIONS,
-            WebviewState::Ready | WebviewState::Resizing { .. } => READY_GTK_ITERATIONS,
+            WebviewState::Ready | WebviewState::Resizing { .. } | WebviewState::Crashed => {
+                READY_GTK_ITERATIONS
+            }
+            // The widgets are gone; nothing left to pump for
+            WebviewState::ShutDown => return,
         };
 
         // Pump GTK events
@@ -85,10 +119,97 @@ impl WebKitBackend {
 
         // Handle state transitions
         self.update_state();
+
+        // Send page console output to the UI log panel
+        let console_logs = std::mem::take(&mut *self.console_logs.borrow_mut());
+        for ui_log in console_logs {
+            let _ = self.send_to_ui(ui_log);
+        }
+    }
+
+    /// Stop using the dead page and schedule a reload, unless out of attempts
+    fn handle_web_process_crash(&mut self, reason: String) {
+        self.state = WebviewState::Crashed;
+
+        if self.recovery.crashed(Instant::now()) {
+            tracing::warn!(
+                "Reloading UI after web process crash (attempt {} of {})",
+                self.recovery.attempts(),
+                MAX_RECOVERY_ATTEMPTS
+            );
+            self.crash_reason = Some(reason);
+        } else {
+            tracing::error!(
+                "UI web process crashed more than {} times, not reloading again",
+                MAX_RECOVERY_ATTEMPTS
+            );
+            self.crash_reason = None;
+        }
+    }
+
+    /// Send the cached state to the reloaded page, followed by the crash
+    /// error and recovery notice
+    fn replay_state(&mut self, reason: &str) {
+        let mut messages = self.replay_cache.messages().to_vec();
+        messages.push(self.recovery.crash_error(reason));
+        messages.push(self.recovery.recovered_notice());
+        for msg in messages {
+            if let Err(e) = self.deliver(msg) {
+                tracing::warn!("Failed to replay message to UI: {}", e);
+            }
+        }
+    }
+
+    /// Hand a message to the page, through the channel if one is set
+    fn deliver(&self, msg: BevyToUi) -> Result<(), FrontendError> {
+        if let Some(tx) = &self.to_ui_tx {
+            tx.send(msg)
+                .map_err(|e| FrontendError::SendFailed(e.to_string()))
+        } else {
+            // Fall back to JavaScript evaluation for messages without channel
+            let js = format!(
+                "window.postMessage({}, '*');",
+                serde_json::to_string(&msg).unwrap_or_default()
+            );
+            self.eval(&js)
+        }
+    }
+
+    /// Destroy the webview and its offscreen window, then let GTK process the
+    /// resulting events so the web process exits before the app does
+    fn shutdown_webview(&mut self) {
+        if self.state == WebviewState::ShutDown {
+            return;
+        }
+        tracing::info!("Shutting down WebKit webview");
+        self.state = WebviewState::ShutDown;
+        self.to_ui_tx = None;
+        self.from_ui_rx = None;
+
+        self.webkit_webview.stop_loading();
+        // SAFETY: the widgets are only used from this thread and every later
+        // call on them is skipped in the `ShutDown` state. wry destroys the
+        // webview again on drop, which GTK ignores for destroyed widgets.
+        unsafe {
+            self.webkit_webview.destroy();
+            self.offscreen_window.destroy();
+        }
+
+        for _ in 0..SHUTDOWN_GTK_ITERATIONS {
+            if !gtk::events_pending() {
+                break;
+            }
+            gtk::main_iteration_do(false);
+        }
     }
 
     /// Update the webview state machine
     fn update_state(&mut self) {
+        let terminated = self.terminated.borrow_mut().take();
+        if let Some(reason) = terminated {
+            self.handle_web_process_crash(reason);
+        }
+
         match self.state {
             WebviewState::Initializing => {
                 if *self.load_finished.borrow() {
@@ -163,6 +284,10 @@ impl WebKitBackend {
                     self.state = WebviewState::Ready;
                     // Now safe to mark dirty for first capture
                     self.dirty.store(true, Ordering::SeqCst);
+
+                    if let Some(reason) = self.crash_reason.take() {
+                        self.replay_state(&reason);
+                    }
                 } else {
                     self.state = WebviewState::WarmingUp {
                         frames_remaining: frames_remaining - 1,
@@ -202,8 +327,17 @@ impl WebKitBackend {
                     };
                 }
             }
-            WebviewState::Ready => {
-                // Normal operation, no transition needed
+            WebviewState::Ready | WebviewState::ShutDown => {
+                // Normal operation (or torn down), no transition needed
+            }
+            WebviewState::Crashed => {
+                if self.recovery.reload_due(Instant::now()) {
+                    tracing::info!("Reloading crashed WebView");
+                    // Go through load and warmup again, like a fresh webview
+                    *self.load_finished.borrow_mut() = false;
+                    self.webkit_webview.reload();
+                    self.state = WebviewState::Initializing;
+                }
             }
         }
     }
@@ -218,6 +352,24 @@ impl CompositeBackend for WebKitBackend {
         self.state == WebviewState::Ready
     }
 
+    fn lifecycle(&self) -> BackendLifecycle {
+        match self.state {
+            WebviewState::Initializing => BackendLifecycle::Initializing,
+            WebviewState::WarmingUp { frames_remaining } => BackendLifecycle::Loading {
+                frames_remaining: Some(frames_remaining),
+            },
+            WebviewState::Ready => BackendLifecycle::Ready,
+            WebviewState::Resizing { frames_remaining } => {
+                BackendLifecycle::Resizing { frames_remaining }
+            }
+            // Waiting out the backoff before the reload
+            WebviewState::Crashed if self.crash_reason.is_some() => BackendLifecycle::Loading {
+                frames_remaining: None,
+            },
+            WebviewState::Crashed | WebviewState::ShutDown => BackendLifecycle::Error,
+        }
+    }
+
     fn capture_if_dirty(&mut self) -> Option<CaptureResult> {
         if !self.dirty.swap(false, Ordering::SeqCst) {
             return None;
@@ -225,7 +377,7 @@ impl CompositeBackend for WebKitBackend {
 
         self.capture().map(|img| {
             let (width, height) = (img.width(), img.height());
-            CaptureResult::Rgba(img.into_raw(), width, height)
+            CaptureResult::Rgba(img.into_raw(), width, height, AlphaMode8::Straight)
         })
     }
 
@@ -234,29 +386,45 @@ impl CompositeBackend for WebKitBackend {
     }
 
     fn resize(&mut self, width: u32, height: u32) {
-        self.resize_webview(width, height);
+        if self.state != WebviewState::ShutDown {
+            self.resize_webview(width, height);
+        }
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        WebKitBackend::set_scale_factor(self, scale_factor);
+    }
+
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::SeqCst);
     }
 
     fn send_mouse_event(&mut self, event: MouseEvent) {
-        self.inject_mouse(event);
+        if self.state != WebviewState::ShutDown {
+            self.inject_mouse(event);
+        }
     }
 
     fn send_keyboard_event(&mut self, event: KeyboardEvent) {
-        self.inject_keyboard(event);
+        if self.state != WebviewState::ShutDown {
+            self.inject_keyboard(event);
+        }
     }
 
     fn send_to_ui(&mut self, msg: BevyToUi) -> Result<(), FrontendError> {
-        if let Some(tx) = &self.to_ui_tx {
-            tx.send(msg)
-                .map_err(|e| FrontendError::SendFailed(e.to_string()))
-        } else {
-            // Fall back to JavaScript evaluation for messages without channel
-            let js = format!(
-                "window.postMessage({}, '*');",
-                serde_json::to_string(&msg).unwrap_or_default()
-            );
-            self.eval(&js)
+        if self.state == WebviewState::ShutDown {
+            return Err(FrontendError::NotReady);
+        }
+        self.replay_cache.record(&msg);
+        if self.state == WebviewState::Crashed || self.crash_reason.is_some() {
+            // The dead page can't take it; state is replayed after the reload
+            return if is_state_message(&msg) && !self.recovery.gave_up() {
+                Ok(())
+            } else {
+                Err(FrontendError::NotReady)
+            };
         }
+        self.deliver(msg)
     }
 
     fn try_recv_from_ui(&mut self) -> Option<UiToBevy> {
@@ -266,4 +434,8 @@ impl CompositeBackend for WebKitBackend {
             None
         }
     }
+
+    fn shutdown(&mut self) {
+        self.shutdown_webview();
+    }
 }
@@ -1,5 +1,7 @@
 //! WebKit resize handling
 
+use std::sync::atomic::Ordering;
+
 use gio::Cancellable;
 use gtk::prelude::*;
 use webkit2gtk::WebViewExt;
@@ -81,8 +83,8 @@ impl WebKitBackend {
         }
 
         // Clear any cached snapshot since it's now the wrong size
-        *self.snapshot_cache.borrow_mut() = None;
-        *self.snapshot_pending.borrow_mut() = false;
+        *self.snapshot_cache.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.snapshot_pending.store(false, Ordering::SeqCst);
 
         // Only transition to Resizing state if we're already Ready
         // Don't interrupt Initializing or WarmingUp states
@@ -11,6 +11,10 @@ pub enum WebviewState {
     Ready,
     /// Resize in progress, waiting for stabilization
     Resizing { frames_remaining: u32 },
+    /// Web process terminated; waiting to reload, or out of reload attempts
+    Crashed,
+    /// Webview and offscreen window destroyed by `shutdown`
+    ShutDown,
 }
 
 /// Number of frames to wait during warmup before first capture (~1 second at 60fps)
@@ -29,3 +33,6 @@ pub const READY_GTK_ITERATIONS: u32 = 30;
 /// Number of frames to wait after mouse event before allowing capture
 /// This allows RAF callbacks and WebKit layout/paint to complete
 pub const MOUSE_EVENT_SETTLE_FRAMES: u32 = 3;
+
+/// Maximum GTK iterations to drain after destroying the webview on shutdown
+pub const SHUTDOWN_GTK_ITERATIONS: u32 = 200;
@@ -1,33 +1,108 @@
 use pentimento_ipc::{
-    AddObjectRequest, AddPaintCanvasRequest, AmbientOcclusionSettings, AppSettings, BevyToUi,
-    DiffusionRequest, EditMode, GizmoCommand, GizmoMode, LayerInfo, LightingSettings,
-    MeshEditCommand, MeshEditTool, MeshSelectionMode, PaintCommand, PrimitiveType, SceneInfo,
-    SceneObject, Transform3D, UiToBevy,
+    AddObjectRequest, AddPaintCanvasRequest, AmbientOcclusionSettings, AnnotationInfo, AppSettings,
+    BevyToUi, BlobKind, BrushTipSource, CameraCommand, CanvasAnchor, CanvasOrientation, CanvasTool,
+    CloseDecision, CollabCommand, CollabState, CompositeMode, CoordinateSpace, DiffusionRequest,
+    EditMode, FrontendLifecycle, GizmoAxis, GizmoCommand, GizmoMode, GradientKind, KeyBinding,
+    LayerInfo, LightCommand, LightInfo, LightType, LightingSettings, MeasureCommand,
+    MeshEditCommand, MeshEditTool, MeshPaintChannel, MeshPaintTarget, MeshSelectionMode,
+    NodeConnection, NodeGraphState, NodeInfo, ObjectCommand, OrbitOptions, PROTOCOL_VERSION,
+    PaintCommand, PivotPoint, PixelSelectionMode, PrimitiveType, ProjectionOptions, QueryKind,
+    ReferenceImageMode, SceneInfo, SceneObject, ScreenCorner, SculptChunkStats, SculptCommand,
+    SculptDetailMode, SnapTarget, TextureRegistryStats, TipRotationMode, Transform3D, UiLogLevel,
+    UiToBevy, ViewMode, WireframeInfo, WireframeTarget,
 };
 use serde::Serialize;
 
 #[derive(Serialize)]
 struct ContractSamples {
+    protocol_version: u32,
     bevy_to_ui: Vec<BevyToUi>,
     ui_to_bevy: Vec<UiToBevy>,
 }
 
 fn main() {
     let samples = ContractSamples {
+        protocol_version: PROTOCOL_VERSION,
         bevy_to_ui: vec![
             BevyToUi::Initialize {
                 scene_info: SceneInfo {
-                    objects: vec![SceneObject {
-                        id: "object-1".into(),
-                        name: "Paint Canvas".into(),
-                        transform: Transform3D::default(),
-                        material_id: Some("material-1".into()),
-                        visible: true,
-                    }],
+                    objects: vec![
+                        SceneObject {
+                            id: "object-1".into(),
+                            name: "Paint Canvas".into(),
+                            transform: Transform3D::default(),
+                            material_id: Some("material-1".into()),
+                            visible: true,
+                            locked: false,
+                            parent_id: None,
+                            subdivision_levels: 0,
+                            wireframe: None,
+                            instanced: false,
+                        },
+                        SceneObject {
+                            id: "object-2".into(),
+                            name: "Cube".into(),
+                            transform: Transform3D::default(),
+                            material_id: None,
+                            visible: true,
+                            locked: false,
+                            parent_id: Some("object-1".into()),
+                            subdivision_levels: 0,
+                            wireframe: None,
+                            instanced: false,
+                        },
+                    ],
                     ..SceneInfo::default()
                 },
                 settings: AppSettings::default(),
             },
+            BevyToUi::SceneUpdated(SceneInfo {
+                objects: vec![SceneObject {
+                    id: "object-2".into(),
+                    name: "Cube".into(),
+                    transform: Transform3D::default(),
+                    material_id: None,
+                    visible: true,
+                    locked: true,
+                    parent_id: None,
+                    subdivision_levels: 3,
+                    wireframe: Some(WireframeInfo {
+                        enabled: true,
+                        color: [1.0, 0.5, 0.0, 1.0],
+                        depth_test: false,
+                    }),
+                    instanced: true,
+                }],
+                wireframe: Some(WireframeInfo {
+                    enabled: false,
+                    color: [0.8, 0.8, 0.8, 0.5],
+                    depth_test: true,
+                }),
+                isolated: true,
+                annotations: vec![AnnotationInfo {
+                    id: "0190f3a2-7c1e-7b40-9d2a-5e8f1c3b6a71".into(),
+                    name: "Measurement 1".into(),
+                    start: [0.0, 0.0, 0.0],
+                    end: [1.0, 0.5, 0.0],
+                    distance: 1.118_034,
+                }],
+                ..SceneInfo::default()
+            }),
+            BevyToUi::ObjectAdded {
+                object: SceneObject {
+                    id: "0190f3a2-7c1e-7b40-9d2a-5e8f1c3b6a70".into(),
+                    name: "Cube copy".into(),
+                    transform: Transform3D::default(),
+                    material_id: Some("0190f3a2-7c1e-7b40-9d2a-5e8f1c3b6a70".into()),
+                    visible: true,
+                    locked: false,
+                    parent_id: None,
+                    subdivision_levels: 0,
+                    wireframe: None,
+                    instanced: false,
+                },
+                duplicated_from: Some("object-2".into()),
+            },
             BevyToUi::ShowAddObjectMenu {
                 show: true,
                 position: Some([128.0, 256.0]),
@@ -35,6 +110,19 @@ fn main() {
             BevyToUi::AmbientOcclusionChanged {
                 settings: AmbientOcclusionSettings::default(),
             },
+            BevyToUi::LightingChanged {
+                settings: LightingSettings::default(),
+            },
+            BevyToUi::GizmoStatus {
+                mode: GizmoMode::Translate,
+                axis: GizmoAxis::XZ,
+                coordinate_space: CoordinateSpace::Local,
+                active: true,
+                snap: SnapTarget::Surface {
+                    align_to_normal: true,
+                },
+                pivot: PivotPoint::Cursor3D,
+            },
             BevyToUi::EditModeChanged {
                 mode: EditMode::Paint,
             },
@@ -61,20 +149,301 @@ fn main() {
                     },
                 ],
             },
+            BevyToUi::RenderStats {
+                fps: 60.0,
+                frame_time_ms: 16.6,
+                draw_calls: 0,
+                triangles: 0,
+                captures_per_second: 2.0,
+                skipped_captures: 58,
+                projected_texels_per_frame: 1250.0,
+                tiles_uploaded_per_frame: 4.0,
+                ui_renders_skipped: 0,
+                render_scale: 0.75,
+                scale_factor: 1.5,
+                history_bytes: 48_234_496,
+                history_budget_bytes: 1_073_741_824,
+            },
+            BevyToUi::TextureRegistryStats(TextureRegistryStats {
+                texture_count: 3,
+                referenced_count: 2,
+                total_bytes: 12_582_912,
+                budget_bytes: 536_870_912,
+                evicted_count: 1,
+            }),
+            BevyToUi::SculptSettingsChanged {
+                dynamic_topology: true,
+                detail_mode: SculptDetailMode::ScreenSpace,
+                detail_size: 6.0,
+                max_vertices: None,
+            },
+            BevyToUi::SculptRemeshProgress { progress: 0.4 },
+            BevyToUi::SculptRemeshFinished {
+                cancelled: false,
+                vertex_count: 48_210,
+                face_count: 96_416,
+            },
+            BevyToUi::MeshHealthReport {
+                errors: vec!["chunk 3: vertex 118 has no original vertex mapping".into()],
+                repaired: 1,
+                chunk_stats: vec![SculptChunkStats {
+                    chunk_id: 3,
+                    vertex_count: 5_012,
+                    face_count: 9_980,
+                    issue_count: 0,
+                }],
+            },
+            BevyToUi::KeymapChanged {
+                bindings: vec![KeyBinding {
+                    action: "gizmo.translate".into(),
+                    chord: "T".into(),
+                }],
+            },
+            BevyToUi::LightsChanged {
+                lights: vec![
+                    LightInfo {
+                        id: "sun".into(),
+                        name: "Sun".into(),
+                        light_type: LightType::Directional,
+                        color: [1.0, 0.98, 0.95],
+                        intensity: 10_000.0,
+                        transform: Transform3D::default(),
+                        shadows: true,
+                    },
+                    LightInfo {
+                        id: "light_1".into(),
+                        name: "Point Light 1".into(),
+                        light_type: LightType::Point { range: 20.0 },
+                        color: [1.0, 0.0, 0.0],
+                        intensity: 800_000.0,
+                        transform: Transform3D {
+                            position: [0.0, 2.0, 0.0],
+                            rotation: [0.0, 0.0, 0.0, 1.0],
+                            scale: [1.0, 1.0, 1.0],
+                        },
+                        shadows: false,
+                    },
+                ],
+            },
+            BevyToUi::MeasureResult {
+                id: "0190f3a2-7c1e-7b40-9d2a-5e8f1c3b6a71".into(),
+                start: [0.0, 0.0, 0.0],
+                end: [1.0, 0.5, 0.0],
+                distance: 1.118_034,
+            },
+            BevyToUi::RecoveryAvailable {
+                path: "/home/user/.config/pentimento/autosave/1760000000000".into(),
+                timestamp: 1_760_000_300_000,
+            },
+            BevyToUi::CanvasExported {
+                path: "/home/user/Pictures/canvas-1.png".into(),
+            },
+            BevyToUi::RenderProgress {
+                frame: 12,
+                total: 120,
+            },
+            BevyToUi::RenderFinished {
+                path: "/home/user/Videos/turntable.mp4".into(),
+            },
+            BevyToUi::MeshStorageConversionProgress {
+                mesh_id: 1,
+                progress: 0.25,
+            },
+            BevyToUi::MeshStorageConversionFinished {
+                mesh_id: 1,
+                cancelled: false,
+            },
+            BevyToUi::UiLog {
+                level: UiLogLevel::Warn,
+                message: "Layout region has no size".into(),
+                source: "app.js".into(),
+                line: 42,
+            },
+            BevyToUi::BinaryBlob {
+                id: 7,
+                kind: BlobKind::DiffusionPreview {
+                    task_id: "task-1".into(),
+                    width: 1024,
+                    height: 1024,
+                },
+                byte_length: 1024 * 1024 * 4,
+            },
+            BevyToUi::ProtocolVersion {
+                version: PROTOCOL_VERSION,
+            },
+            BevyToUi::QueryResult {
+                request_id: 3,
+                result: Ok(serde_json::json!({
+                    "object_id": "object-2",
+                    "position": [0.0, 0.5, 0.0],
+                    "distance": 4.2,
+                })),
+            },
+            BevyToUi::QueryResult {
+                request_id: 4,
+                result: Err("Unknown material material-9".into()),
+            },
+            BevyToUi::CollabStatus {
+                state: CollabState::Hosting,
+                ticket: Some("tcp://127.0.0.1:47011".into()),
+                peers: 1,
+                error: None,
+            },
+            BevyToUi::CollabStatus {
+                state: CollabState::Offline,
+                ticket: None,
+                peers: 0,
+                error: Some("Invalid session ticket: abc".into()),
+            },
+            BevyToUi::BackendLifecycleChanged {
+                state: FrontendLifecycle::Resizing,
+            },
+            BevyToUi::BackendLifecycleChanged {
+                state: FrontendLifecycle::Ready,
+            },
+            BevyToUi::Warning {
+                code: "sculpt_remesh_paint".into(),
+                message: "Remesh changed the mesh layout; paint needs reprojection".into(),
+            },
             BevyToUi::CloseMenus,
+            BevyToUi::CloseRequested,
         ],
         ui_to_bevy: vec![
             UiToBevy::AddObject(AddObjectRequest {
                 primitive_type: PrimitiveType::Cube,
                 position: Some([0.0, 1.0, 0.0]),
                 name: Some("Blockout".into()),
+                subdivisions: None,
+            }),
+            UiToBevy::AddObject(AddObjectRequest {
+                primitive_type: PrimitiveType::Grid { subdivisions: 8 },
+                position: None,
+                name: None,
+                subdivisions: Some(16),
+            }),
+            UiToBevy::UpdateLighting(LightingSettings {
+                animate_speed: 0.5,
+                ..LightingSettings::default()
+            }),
+            UiToBevy::NodeGraphUpdate(NodeGraphState {
+                material_id: "object-1".into(),
+                nodes: vec![
+                    NodeInfo {
+                        id: "roughness".into(),
+                        node_type: "ScalarConstant".into(),
+                        position: [0.0, 0.0],
+                        data: serde_json::json!({ "value": 0.3 }),
+                    },
+                    NodeInfo {
+                        id: "output".into(),
+                        node_type: "Output".into(),
+                        position: [200.0, 0.0],
+                        data: serde_json::Value::Null,
+                    },
+                ],
+                connections: vec![NodeConnection {
+                    from_node: "roughness".into(),
+                    from_output: "value".into(),
+                    to_node: "output".into(),
+                    to_input: "roughness".into(),
+                }],
             }),
-            UiToBevy::UpdateLighting(LightingSettings::default()),
             UiToBevy::SetDepthView { enabled: true },
+            UiToBevy::SetViewMode {
+                mode: ViewMode::Normals,
+            },
+            UiToBevy::SetCompositeMode {
+                mode: CompositeMode::Cef,
+            },
+            UiToBevy::SetKeybinding {
+                action: "gizmo.translate".into(),
+                chord: "T".into(),
+            },
+            UiToBevy::RestoreAutosave {
+                path: "/home/user/.config/pentimento/autosave/1760000000000".into(),
+            },
+            UiToBevy::AddReferenceImage {
+                path: "/home/user/Pictures/turnaround.png".into(),
+                mode: ReferenceImageMode::WorldPlane,
+            },
+            UiToBevy::AddReferenceImage {
+                path: "/home/user/Pictures/palette.jpg".into(),
+                mode: ReferenceImageMode::CameraPinned {
+                    corner: ScreenCorner::BottomRight,
+                    size: 0.3,
+                    opacity: 0.8,
+                },
+            },
+            UiToBevy::SetReferenceImageOpacity {
+                id: "reference_1".into(),
+                opacity: 0.5,
+            },
+            UiToBevy::RenderTurntable {
+                frames: 120,
+                seconds: 4.0,
+                width: 1920,
+                height: 1080,
+                path: "/home/user/Videos/turntable.mp4".into(),
+            },
+            UiToBevy::CancelRender,
+            UiToBevy::DeleteTexture {
+                texture_id: "diffusion_1".into(),
+            },
+            UiToBevy::SetWireframe {
+                target: WireframeTarget::Global,
+                enabled: true,
+                color: None,
+                depth_test: true,
+            },
+            UiToBevy::SetWireframe {
+                target: WireframeTarget::Object("object-2".into()),
+                enabled: true,
+                color: Some([1.0, 0.5, 0.0, 1.0]),
+                depth_test: false,
+            },
+            UiToBevy::BlobConsumed { id: 7 },
+            UiToBevy::ProtocolVersion {
+                version: PROTOCOL_VERSION,
+            },
+            UiToBevy::Query {
+                request_id: 3,
+                query: QueryKind::ObjectAtPoint { x: 320.0, y: 240.0 },
+            },
+            UiToBevy::Query {
+                request_id: 4,
+                query: QueryKind::MaterialProperties {
+                    material_id: "material-9".into(),
+                },
+            },
+            UiToBevy::Query {
+                request_id: 5,
+                query: QueryKind::SceneInfo,
+            },
+            UiToBevy::Collab(CollabCommand::Host),
+            UiToBevy::Collab(CollabCommand::Join {
+                ticket: "tcp://127.0.0.1:47011".into(),
+            }),
+            UiToBevy::Collab(CollabCommand::Leave),
+            UiToBevy::CloseResponse {
+                decision: CloseDecision::Asking,
+            },
+            UiToBevy::RequestInitialize,
+            UiToBevy::CompactHistory,
             UiToBevy::AddPaintCanvas(AddPaintCanvasRequest {
                 width: Some(1024),
                 height: Some(1024),
+                world_size: None,
+                distance: None,
+                orientation: CanvasOrientation::FacingCamera,
+            }),
+            UiToBevy::AddPaintCanvas(AddPaintCanvasRequest {
+                width: Some(2048),
+                height: Some(1024),
+                world_size: Some([2.0, 1.0]),
+                distance: Some(3.0),
+                orientation: CanvasOrientation::Vertical,
             }),
+            UiToBevy::PaintCommand(PaintCommand::SetBrushFlow { flow: 0.35 }),
             UiToBevy::PaintCommand(PaintCommand::AddLayer {
                 name: "Details".into(),
             }),
@@ -82,8 +451,171 @@ fn main() {
                 layer_id: 2,
                 opacity: 0.45,
             }),
+            UiToBevy::PaintCommand(PaintCommand::ProjectToSelection {
+                options: ProjectionOptions {
+                    occlusion: true,
+                    backface_cull: false,
+                    falloff_angle_deg: 60.0,
+                },
+            }),
+            UiToBevy::PaintCommand(PaintCommand::ReprojectLast),
+            UiToBevy::PaintCommand(PaintCommand::ExportCanvas { path: None }),
+            UiToBevy::PaintCommand(PaintCommand::ImportCanvas {
+                path: "/home/user/Pictures/canvas-1.16.png".into(),
+            }),
+            UiToBevy::PaintCommand(PaintCommand::ResizeCanvas {
+                width: 2048,
+                height: 1024,
+                anchor: CanvasAnchor::TopLeft,
+            }),
+            UiToBevy::PaintCommand(PaintCommand::CropCanvas {
+                x: 64,
+                y: 32,
+                width: 512,
+                height: 256,
+            }),
+            UiToBevy::PaintCommand(PaintCommand::BeginPixelSelection {
+                mode: PixelSelectionMode::Lasso,
+            }),
+            UiToBevy::PaintCommand(PaintCommand::TransformSelection {
+                translate: [12.0, -4.5],
+                rotate_deg: 15.0,
+                scale: [1.0, 1.0],
+            }),
+            UiToBevy::PaintCommand(PaintCommand::SetBrushTip {
+                source: BrushTipSource::BuiltIn("chalk".into()),
+            }),
+            UiToBevy::PaintCommand(PaintCommand::SetBrushTip {
+                source: BrushTipSource::Round,
+            }),
+            UiToBevy::PaintCommand(PaintCommand::SetBrushTipRotation {
+                mode: TipRotationMode::Fixed(30.0),
+            }),
+            UiToBevy::PaintCommand(PaintCommand::SetBrushTipRotation {
+                mode: TipRotationMode::Random,
+            }),
+            UiToBevy::PaintCommand(PaintCommand::Fill {
+                x: 120.5,
+                y: 64.0,
+                tolerance: 0.1,
+                contiguous: true,
+            }),
+            UiToBevy::PaintCommand(PaintCommand::Gradient {
+                start: [0.0, 0.0],
+                end: [512.0, 256.0],
+                kind: GradientKind::Radial,
+                colors: vec![[1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 0.0]],
+            }),
+            UiToBevy::PaintCommand(PaintCommand::ArmCanvasTool {
+                tool: Some(CanvasTool::Fill {
+                    tolerance: 0.1,
+                    contiguous: false,
+                }),
+            }),
+            UiToBevy::PaintCommand(PaintCommand::ArmCanvasTool {
+                tool: Some(CanvasTool::Gradient {
+                    kind: GradientKind::Linear,
+                    colors: vec![[0.0, 0.0, 0.0, 1.0], [1.0, 1.0, 1.0, 1.0]],
+                }),
+            }),
+            UiToBevy::PaintCommand(PaintCommand::ArmCanvasTool { tool: None }),
+            UiToBevy::PaintCommand(PaintCommand::SetMeshPaintChannel {
+                channel: MeshPaintChannel::Roughness,
+            }),
+            UiToBevy::PaintCommand(PaintCommand::SetMeshPaintValue { value: 0.65 }),
+            UiToBevy::PaintCommand(PaintCommand::SetNormalStrength { strength: 0.4 }),
+            UiToBevy::PaintCommand(PaintCommand::SetMeshPaintTarget {
+                target: MeshPaintTarget::VertexColors,
+            }),
+            UiToBevy::PaintCommand(PaintCommand::ReanchorCanvas { plane_id: None }),
+            UiToBevy::PaintCommand(PaintCommand::ReanchorCanvas { plane_id: Some(2) }),
+            UiToBevy::ObjectCommand(ObjectCommand::SetParent {
+                id: "object-2".into(),
+                parent_id: Some("object-1".into()),
+            }),
+            UiToBevy::ObjectCommand(ObjectCommand::SetParent {
+                id: "object-2".into(),
+                parent_id: None,
+            }),
+            UiToBevy::ObjectCommand(ObjectCommand::Group {
+                ids: vec!["object-1".into(), "object-2".into()],
+                name: "Group".into(),
+            }),
+            UiToBevy::ObjectCommand(ObjectCommand::Delete {
+                ids: vec!["group_1".into()],
+                recursive: false,
+            }),
+            UiToBevy::ObjectCommand(ObjectCommand::MakeUnique {
+                id: "object-3".into(),
+            }),
+            UiToBevy::ObjectCommand(ObjectCommand::SetSubdivision {
+                id: "object-2".into(),
+                levels: 2,
+            }),
+            UiToBevy::ObjectCommand(ObjectCommand::Isolate {
+                ids: vec!["object-2".into()],
+            }),
+            UiToBevy::ObjectCommand(ObjectCommand::Isolate { ids: Vec::new() }),
+            UiToBevy::ObjectCommand(ObjectCommand::SetLocked {
+                id: "object-1".into(),
+                locked: true,
+            }),
+            UiToBevy::CameraCommand(CameraCommand::SetOrbitOptions(OrbitOptions {
+                orbit_around_depth: false,
+                zoom_to_cursor: true,
+            })),
             UiToBevy::GizmoCommand(GizmoCommand::SetMode(GizmoMode::Translate)),
+            UiToBevy::GizmoCommand(GizmoCommand::SetSnapTarget {
+                mode: SnapTarget::Grid { size: Some(0.25) },
+            }),
+            UiToBevy::GizmoCommand(GizmoCommand::SetSnapTarget {
+                mode: SnapTarget::Grid { size: None },
+            }),
+            UiToBevy::GizmoCommand(GizmoCommand::SetPivot {
+                pivot: PivotPoint::IndividualOrigins,
+            }),
+            UiToBevy::LightCommand(LightCommand::Add {
+                light_type: LightType::Spot {
+                    range: 20.0,
+                    inner_angle: 0.3,
+                    outer_angle: 0.6,
+                },
+                position: [0.0, 3.0, 0.0],
+            }),
+            UiToBevy::LightCommand(LightCommand::Update {
+                id: "light_1".into(),
+                color: Some([1.0, 0.0, 0.0]),
+                intensity: None,
+                range: Some(10.0),
+                angles: None,
+            }),
+            UiToBevy::LightCommand(LightCommand::SetShadows {
+                id: "light_1".into(),
+                enabled: true,
+            }),
+            UiToBevy::LightCommand(LightCommand::Delete {
+                id: "light_1".into(),
+            }),
             UiToBevy::MeshEditCommand(MeshEditCommand::SetTool(MeshEditTool::Inset)),
+            UiToBevy::MeasureCommand(MeasureCommand::Start),
+            UiToBevy::MeasureCommand(MeasureCommand::Cancel),
+            UiToBevy::SetEditMode {
+                mode: EditMode::MeshPaint,
+            },
+            UiToBevy::SculptCommand(SculptCommand::SetBrushSpacing { spacing: 0.25 }),
+            UiToBevy::SculptCommand(SculptCommand::SetBrushFlow { flow: 0.6 }),
+            UiToBevy::SculptCommand(SculptCommand::SelectBrushPreset { preset_id: 8 }),
+            UiToBevy::SculptCommand(SculptCommand::SetDetailMode(SculptDetailMode::Constant)),
+            UiToBevy::SculptCommand(SculptCommand::SetVertexBudget {
+                max_vertices: Some(200_000),
+            }),
+            UiToBevy::SculptCommand(SculptCommand::Remesh {
+                target_edge_length: 0.02,
+            }),
+            UiToBevy::SculptCommand(SculptCommand::CancelRemesh),
+            UiToBevy::SculptCommand(SculptCommand::InvertMask),
+            UiToBevy::SculptCommand(SculptCommand::ValidateMesh),
+            UiToBevy::SculptCommand(SculptCommand::SetAutoValidate { every_strokes: 20 }),
             UiToBevy::StartDiffusion(DiffusionRequest {
                 task_id: "task-1".into(),
                 prompt: "weathered brass".into(),
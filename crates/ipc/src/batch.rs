@@ -0,0 +1,222 @@
+//! Per-frame batching of IPC messages
+//!
+//! Messages are delivered in batches, one per frame in each direction: the
+//! backends hand Bevy's messages to `__PENTIMENTO_RECV_BATCH__` in a single
+//! script, and the UI bridge posts everything it sent during an animation
+//! frame as one JSON array.
+//!
+//! Within a batch, messages that only report the latest state (a drag can
+//! produce dozens of `SceneUpdated`/`GizmoStatus` a frame) are coalesced:
+//! only the newest message of each `CoalesceKey` is kept. Messages without a
+//! key (objects added, errors, commands) are never dropped.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::error::IpcError;
+use crate::messages::{BevyToUi, UiToBevy};
+use crate::protocol::parse_ui_value;
+
+/// Identifies messages where a newer one replaces an older one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CoalesceKey<'a> {
+    /// Message type
+    pub kind: &'static str,
+    /// Narrows `kind`, e.g. the diffusion task a progress update is for
+    pub scope: Option<&'a str>,
+}
+
+impl CoalesceKey<'_> {
+    fn kind(kind: &'static str) -> Self {
+        Self { kind, scope: None }
+    }
+}
+
+/// Messages that can be coalesced within a batch
+pub trait Coalesce {
+    /// Key shared by messages where only the newest matters, or `None` if
+    /// every message of this kind must be delivered
+    fn coalesce_key(&self) -> Option<CoalesceKey<'_>>;
+}
+
+impl Coalesce for BevyToUi {
+    fn coalesce_key(&self) -> Option<CoalesceKey<'_>> {
+        match self {
+            BevyToUi::SceneUpdated(_) => Some(CoalesceKey::kind("SceneUpdated")),
+            BevyToUi::RenderStats { .. } => Some(CoalesceKey::kind("RenderStats")),
+            BevyToUi::GizmoStatus { .. } => Some(CoalesceKey::kind("GizmoStatus")),
+            BevyToUi::TextureRegistryStats(_) => Some(CoalesceKey::kind("TextureRegistryStats")),
+            BevyToUi::BackendLifecycleChanged { .. } => {
+                Some(CoalesceKey::kind("BackendLifecycleChanged"))
+            }
+            BevyToUi::DiffusionProgress { task_id, .. } => Some(CoalesceKey {
+                kind: "DiffusionProgress",
+                scope: Some(task_id),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Coalesce for UiToBevy {
+    fn coalesce_key(&self) -> Option<CoalesceKey<'_>> {
+        match self {
+            UiToBevy::UiDirty => Some(CoalesceKey::kind("UiDirty")),
+            UiToBevy::LayoutUpdate(_) => Some(CoalesceKey::kind("LayoutUpdate")),
+            _ => None,
+        }
+    }
+}
+
+/// Drop every message that a later message in `messages` replaces
+///
+/// The remaining messages keep their order.
+pub fn coalesce<T: Coalesce>(messages: Vec<T>) -> Vec<T> {
+    let mut newest = HashMap::new();
+    for (index, msg) in messages.iter().enumerate() {
+        if let Some(key) = msg.coalesce_key() {
+            newest.insert(key, index);
+        }
+    }
+    if newest.is_empty() {
+        return messages;
+    }
+
+    let keep: Vec<bool> = messages
+        .iter()
+        .enumerate()
+        .map(|(index, msg)| {
+            msg.coalesce_key()
+                .is_none_or(|key| newest.get(&key) == Some(&index))
+        })
+        .collect();
+    messages
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(msg, keep)| keep.then_some(msg))
+        .collect()
+}
+
+/// Parse what the UI posted: one message, or a JSON array of them
+///
+/// Each message in a batch is parsed on its own, so one the app doesn't
+/// understand doesn't cost the rest of the batch.
+pub fn parse_ui_batch(json: &str) -> Vec<Result<UiToBevy, IpcError>> {
+    if !json.trim_start().starts_with('[') {
+        return vec![crate::protocol::parse_ui_to_bevy(json)];
+    }
+    match serde_json::from_str::<Vec<Value>>(json) {
+        Ok(values) => values.into_iter().map(parse_ui_value).collect(),
+        Err(e) => vec![Err(IpcError::Malformed(e))],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{
+        CameraCommand, CoordinateSpace, GizmoAxis, GizmoMode, PivotPoint, SnapTarget,
+    };
+    use crate::types::{SceneInfo, SceneObject, Transform3D};
+
+    fn gizmo_status(active: bool) -> BevyToUi {
+        BevyToUi::GizmoStatus {
+            mode: GizmoMode::Translate,
+            axis: GizmoAxis::None,
+            coordinate_space: CoordinateSpace::Global,
+            active,
+            snap: SnapTarget::None,
+            pivot: PivotPoint::Median,
+        }
+    }
+
+    fn progress(task_id: &str, progress: f32) -> BevyToUi {
+        BevyToUi::DiffusionProgress {
+            task_id: task_id.to_string(),
+            progress,
+            preview_available: false,
+        }
+    }
+
+    #[test]
+    fn test_coalesce_keeps_newest() {
+        let messages = vec![
+            BevyToUi::SceneUpdated(SceneInfo::default()),
+            gizmo_status(true),
+            BevyToUi::ObjectAdded {
+                object: SceneObject {
+                    id: "cube".to_string(),
+                    name: "Cube".to_string(),
+                    transform: Transform3D::default(),
+                    material_id: None,
+                    visible: true,
+                    locked: false,
+                    parent_id: None,
+                    subdivision_levels: 0,
+                    wireframe: None,
+                    instanced: false,
+                },
+                duplicated_from: None,
+            },
+            progress("a", 0.1),
+            progress("b", 0.5),
+            BevyToUi::SceneUpdated(SceneInfo::default()),
+            progress("a", 0.2),
+            gizmo_status(false),
+        ];
+
+        let kept: Vec<String> = coalesce(messages)
+            .iter()
+            .map(|msg| serde_json::to_string(msg).unwrap())
+            .collect();
+        assert_eq!(kept.len(), 5);
+        assert!(kept[0].contains("ObjectAdded"));
+        assert!(kept[1].contains(r#""task_id":"b""#));
+        assert!(kept[2].contains("SceneUpdated"));
+        assert!(kept[3].contains(r#""task_id":"a","progress":0.2"#));
+        assert!(kept[4].contains(r#""active":false"#));
+    }
+
+    #[test]
+    fn test_coalesce_never_drops_critical_messages() {
+        let error = || BevyToUi::Error {
+            code: "e".to_string(),
+            message: "failed".to_string(),
+        };
+        let messages = vec![error(), error(), error()];
+        assert_eq!(coalesce(messages).len(), 3);
+
+        let orbit = || {
+            UiToBevy::CameraCommand(CameraCommand::Orbit {
+                delta_x: 1.0,
+                delta_y: 0.0,
+            })
+        };
+        let messages = vec![UiToBevy::UiDirty, orbit(), UiToBevy::UiDirty, orbit()];
+        let kept = coalesce(messages);
+        assert_eq!(kept.len(), 3);
+        assert!(matches!(kept[1], UiToBevy::UiDirty));
+    }
+
+    #[test]
+    fn test_parse_ui_batch() {
+        let results = parse_ui_batch(r#"{"type":"UiDirty"}"#);
+        assert!(matches!(results.as_slice(), [Ok(UiToBevy::UiDirty)]));
+
+        let results = parse_ui_batch(
+            r#"[{"type":"UiDirty"},{"type":"RequestTelepathy"},{"type":"CancelRender"}]"#,
+        );
+        assert!(matches!(
+            results.as_slice(),
+            [
+                Ok(UiToBevy::UiDirty),
+                Err(IpcError::UnknownMessage(_)),
+                Ok(UiToBevy::CancelRender)
+            ]
+        ));
+
+        let results = parse_ui_batch(r#"[{"type":"UiDirty"}"#);
+        assert!(matches!(results.as_slice(), [Err(IpcError::Malformed(_))]));
+    }
+}
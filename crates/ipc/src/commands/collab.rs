@@ -0,0 +1,24 @@
+//! Collaboration session commands and status.
+
+use serde::{Deserialize, Serialize};
+
+/// Commands for sharing the session with other peers. Bevy answers each with
+/// `CollabStatus`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CollabCommand {
+    /// Start a session others can join with the ticket in `CollabStatus`
+    Host,
+    /// Join the session behind a ticket from its host
+    Join { ticket: String },
+    /// Leave the current session (strokes painted so far are kept)
+    Leave,
+}
+
+/// Whether this instance is in a collaboration session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CollabState {
+    #[default]
+    Offline,
+    Hosting,
+    Joined,
+}
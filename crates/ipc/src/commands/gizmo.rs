@@ -40,6 +40,36 @@ pub enum CoordinateSpace {
     Local,
 }
 
+/// What translated objects snap to; kept across transform operations.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SnapTarget {
+    #[default]
+    None,
+    /// Quantize the translation to steps of `size` world units; without a
+    /// size, steps follow the finest visible subdivision of the ground grid
+    Grid {
+        #[serde(default)]
+        size: Option<f32>,
+    },
+    /// Place objects on the surface under the cursor (unconstrained moves only)
+    Surface { align_to_normal: bool },
+}
+
+/// Point that rotation and scaling happen around; kept across transform
+/// operations. The gizmo is drawn at the pivot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PivotPoint {
+    /// Average of the selected objects' origins
+    #[default]
+    Median,
+    /// Each object turns and scales about its own origin
+    IndividualOrigins,
+    /// Origin of the active (last selected) object
+    ActiveObject,
+    /// The 3D cursor (placed with Shift+right click)
+    Cursor3D,
+}
+
 /// Commands for controlling the transform gizmo.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GizmoCommand {
@@ -51,4 +81,8 @@ pub enum GizmoCommand {
     Cancel,
     /// Confirm current transform operation (Enter/LMB)
     Confirm,
+    /// Set what translated objects snap to
+    SetSnapTarget { mode: SnapTarget },
+    /// Set what rotation and scaling pivot around
+    SetPivot { pivot: PivotPoint },
 }
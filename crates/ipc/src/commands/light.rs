@@ -0,0 +1,32 @@
+//! Light command types for adding and editing scene lights.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::LightType;
+
+/// Commands for scene lights. Bevy answers each change with `LightsChanged`.
+///
+/// Intensity is in lumens for point and spot lights and lux for directional
+/// lights; angles are in radians.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LightCommand {
+    /// Add a light at a world position (it becomes the selection)
+    Add {
+        light_type: LightType,
+        position: [f32; 3],
+    },
+    /// Change light properties; `None` fields are left as they are, and
+    /// `range`/`angles` only apply to the light types that have them
+    Update {
+        id: String,
+        color: Option<[f32; 3]>,
+        intensity: Option<f32>,
+        range: Option<f32>,
+        /// Spot cone `[inner, outer]` half-angles
+        angles: Option<[f32; 2]>,
+    },
+    /// Remove a light (the sun can't be deleted)
+    Delete { id: String },
+    /// Toggle shadow casting
+    SetShadows { id: String, enabled: bool },
+}
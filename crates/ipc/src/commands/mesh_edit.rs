@@ -10,6 +10,8 @@ pub enum EditMode {
     None,
     /// Paint mode - painting on a canvas plane
     Paint,
+    /// Mesh paint mode - painting on mesh surfaces
+    MeshPaint,
     /// Mesh edit mode - editing vertices, edges, and faces
     MeshEdit,
     /// Sculpt mode - 3D sculpting with dynamic tessellation
@@ -1,14 +1,20 @@
 //! Command types for IPC messages.
 
+mod collab;
 mod gizmo;
+mod light;
 mod mesh_edit;
 mod paint;
+mod sculpt;
 
+pub use collab::*;
 pub use gizmo::*;
+pub use light::*;
 pub use mesh_edit::*;
 pub use paint::*;
+pub use sculpt::*;
 
-use crate::types::Transform3D;
+use crate::types::{OrbitOptions, Transform3D};
 use serde::{Deserialize, Serialize};
 
 /// Camera control commands.
@@ -20,18 +26,81 @@ pub enum CameraCommand {
     SetPosition { position: [f32; 3] },
     SetTarget { target: [f32; 3] },
     Reset,
+    SetOrbitOptions(OrbitOptions),
+}
+
+/// Measure tool commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MeasureCommand {
+    /// Let viewport clicks place measurements, two clicks each, until
+    /// cancelled
+    Start,
+    /// Leave the measure tool, dropping a half-placed measurement
+    Cancel,
 }
 
 /// Object manipulation commands.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ObjectCommand {
-    Select { ids: Vec<String> },
-    Deselect { ids: Vec<String> },
-    Delete { ids: Vec<String> },
-    Duplicate { ids: Vec<String> },
-    Transform { id: String, transform: Transform3D },
-    SetVisibility { id: String, visible: bool },
-    Rename { id: String, name: String },
+    Select {
+        ids: Vec<String>,
+    },
+    Deselect {
+        ids: Vec<String>,
+    },
+    /// Delete objects; children of a deleted object are deleted with it
+    /// when `recursive`, otherwise moved up to its parent
+    Delete {
+        ids: Vec<String>,
+        recursive: bool,
+    },
+    Duplicate {
+        ids: Vec<String>,
+    },
+    Transform {
+        id: String,
+        transform: Transform3D,
+    },
+    SetVisibility {
+        id: String,
+        visible: bool,
+    },
+    /// Hide every object but `ids` (with their ancestors and descendants);
+    /// empty `ids` ends isolation, restoring the visibility from before it
+    Isolate {
+        ids: Vec<String>,
+    },
+    /// Locked objects can't be selected, transformed, painted, or sculpted
+    SetLocked {
+        id: String,
+        locked: bool,
+    },
+    Rename {
+        id: String,
+        name: String,
+    },
+    /// Parent an object to another (`None` moves it to the scene root),
+    /// keeping its world transform
+    SetParent {
+        id: String,
+        parent_id: Option<String>,
+    },
+    /// Parent objects to a new, empty transform node
+    Group {
+        ids: Vec<String>,
+        name: String,
+    },
+    /// Show a smooth (Loop) subdivision preview of a mesh object; `levels`
+    /// runs 0-4 and 0 shows the base mesh
+    SetSubdivision {
+        id: String,
+        levels: u8,
+    },
+    /// Give an instanced object its own copies of the mesh and material it
+    /// shares with other objects
+    MakeUnique {
+        id: String,
+    },
 }
 
 /// Material editing commands.
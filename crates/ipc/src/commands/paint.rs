@@ -17,8 +17,10 @@ pub enum PaintCommand {
     SetBrushColor { color: [f32; 4] },
     /// Set brush size in pixels
     SetBrushSize { size: f32 },
-    /// Set brush opacity (0.0-1.0)
+    /// Set brush opacity (0.0-1.0), the cap for a whole stroke
     SetBrushOpacity { opacity: f32 },
+    /// Set brush flow (0.0-1.0), the opacity of each dab within a stroke
+    SetBrushFlow { flow: f32 },
     /// Set brush hardness (0.0-1.0)
     SetBrushHardness { hardness: f32 },
     /// Set blend mode (Normal or Erase)
@@ -30,7 +32,12 @@ pub enum PaintCommand {
     /// Enable/disable live projection mode (paint-as-project)
     SetLiveProjection { enabled: bool },
     /// Project current canvas contents to all visible meshes (one-shot)
-    ProjectToScene,
+    ProjectToScene { options: ProjectionOptions },
+    /// Project current canvas contents to the selected meshes only (one-shot)
+    ProjectToSelection { options: ProjectionOptions },
+    /// Redo the last one-shot projection onto the meshes where they are now,
+    /// replacing the paint it left
+    ReprojectLast,
     /// Add a new layer (empty name for auto-generated)
     AddLayer { name: String },
     /// Remove a layer by ID
@@ -45,6 +52,207 @@ pub enum PaintCommand {
     ReorderLayer { layer_id: u32, new_index: u32 },
     /// Rename a layer
     RenameLayer { layer_id: u32, name: String },
+    /// Export the active canvas as a PNG with alpha (16-bit for `*.16.png`).
+    /// Without a path the image goes next to the working directory as
+    /// `canvas-<plane id>.png`.
+    ExportCanvas { path: Option<String> },
+    /// Replace the active layer with an image, fitted to the canvas (undoable)
+    ImportCanvas { path: String },
+    /// Resize the active canvas, keeping its content pinned to `anchor` (undoable)
+    ResizeCanvas {
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        anchor: CanvasAnchor,
+    },
+    /// Crop the active canvas to a pixel rectangle (undoable)
+    CropCanvas {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Arm the pixel selection tool; the next drag on the canvas lifts the
+    /// selected pixels of the active layer into a floating selection
+    BeginPixelSelection { mode: PixelSelectionMode },
+    /// Move (canvas pixels), rotate, and scale the floating selection about
+    /// its center, on top of its current transform
+    TransformSelection {
+        translate: [f32; 2],
+        rotate_deg: f32,
+        scale: [f32; 2],
+    },
+    /// Resample the floating selection onto its layer (undoable)
+    CommitSelection,
+    /// Put the floating selection back where it was
+    CancelSelection,
+    /// Use a stamp tip (greyscale mask) for each dab, or go back to round
+    SetBrushTip { source: BrushTipSource },
+    /// Set how the stamp tip rotates from dab to dab
+    SetBrushTipRotation { mode: TipRotationMode },
+    /// Bucket fill the active layer with the brush color from a canvas pixel
+    /// (undoable). Pixels within `tolerance` (perceptual distance, 0-1) of
+    /// the clicked color are filled: only connected ones when `contiguous`,
+    /// otherwise everywhere. Positions outside the canvas are ignored.
+    Fill {
+        x: f32,
+        y: f32,
+        tolerance: f32,
+        contiguous: bool,
+    },
+    /// Render a gradient of evenly spaced `colors` from `start` to `end`
+    /// (canvas pixels) over the active layer, with the current blend mode
+    /// (undoable)
+    Gradient {
+        start: [f32; 2],
+        end: [f32; 2],
+        kind: GradientKind,
+        colors: Vec<[f32; 4]>,
+    },
+    /// Arm a click tool on the active canvas instead of the brush, or disarm
+    /// it with `None`. A click fills, a drag draws a gradient.
+    ArmCanvasTool { tool: Option<CanvasTool> },
+    /// Paint meshes into a material channel instead of base color
+    SetMeshPaintChannel { channel: MeshPaintChannel },
+    /// Set the value painted into the roughness and metallic channels (0.0-1.0)
+    SetMeshPaintValue { value: f32 },
+    /// Set how far normal strokes tilt the surface (0.0-1.0)
+    SetNormalStrength { strength: f32 },
+    /// Paint meshes into their textures or their vertex colors
+    SetMeshPaintTarget { target: MeshPaintTarget },
+    /// Move a canvas (the active one without `plane_id`) back in front of
+    /// the camera, keeping its size and pixels
+    ReanchorCanvas {
+        #[serde(default)]
+        plane_id: Option<u32>,
+    },
+}
+
+/// Material channel that mesh paint strokes write to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum MeshPaintChannel {
+    /// Albedo, painted with the brush color
+    #[default]
+    BaseColor,
+    /// Perceptual roughness, painted with the brush value
+    Roughness,
+    /// Metalness, painted with the brush value
+    Metallic,
+    /// Tangent-space normals, tilted along the stroke direction
+    Normal,
+    /// Emitted light, painted with the brush color
+    Emissive,
+}
+
+impl MeshPaintChannel {
+    /// Whether strokes paint a single value rather than a color
+    pub fn is_scalar(self) -> bool {
+        matches!(self, Self::Roughness | Self::Metallic)
+    }
+}
+
+/// Where mesh paint strokes are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum MeshPaintTarget {
+    /// The mesh's paint texture (UV atlas or Ptex)
+    #[default]
+    Texture,
+    /// The mesh's vertex colors; needs no UVs, best on dense meshes
+    VertexColors,
+}
+
+/// Shape of a gradient.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GradientKind {
+    /// Bands perpendicular to the start-end line
+    #[default]
+    Linear,
+    /// Rings around the start point
+    Radial,
+}
+
+/// Click tools for the canvas, with the settings they apply on use.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CanvasTool {
+    /// Bucket fill at the clicked pixel
+    Fill { tolerance: f32, contiguous: bool },
+    /// Gradient from the drag start to the drag end
+    Gradient {
+        kind: GradientKind,
+        colors: Vec<[f32; 4]>,
+    },
+}
+
+/// Where a stamp brush tip comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BrushTipSource {
+    /// Procedural round dab (no stamp)
+    #[default]
+    Round,
+    /// A tip embedded in the app, by name (chalk, bristles, square, splatter)
+    BuiltIn(String),
+    /// A greyscale image file; brighter pixels paint more
+    File(String),
+}
+
+/// How a stamp brush tip rotates from dab to dab.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum TipRotationMode {
+    /// Always the same angle (degrees, counter-clockwise)
+    Fixed(f32),
+    /// Aligned with the stroke direction
+    #[default]
+    FollowStroke,
+    /// A new random angle per dab
+    Random,
+}
+
+/// Shape drawn by the pixel selection tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PixelSelectionMode {
+    /// Rectangle between the drag start and end
+    #[default]
+    Rect,
+    /// Freehand polygon following the drag
+    Lasso,
+}
+
+/// Where existing content stays when a canvas is resized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CanvasAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// Options shared by the one-shot canvas projection commands.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectionOptions {
+    /// Only paint texels visible from the projection camera
+    pub occlusion: bool,
+    /// Skip surfaces facing away from the projection camera
+    pub backface_cull: bool,
+    /// Angle (degrees) between surface normal and view direction where the
+    /// projection starts fading out; it reaches zero at 90 degrees
+    pub falloff_angle_deg: f32,
+}
+
+impl Default for ProjectionOptions {
+    fn default() -> Self {
+        Self {
+            occlusion: true,
+            backface_cull: true,
+            falloff_angle_deg: 90.0,
+        }
+    }
 }
 
 /// Layer metadata for UI synchronization.
@@ -62,6 +270,21 @@ pub struct LayerInfo {
     pub is_active: bool,
 }
 
+/// How a new paint canvas is turned relative to the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CanvasOrientation {
+    /// Square on to the view
+    #[default]
+    FacingCamera,
+    /// Lying flat, its top edge pointing away from the camera
+    Horizontal,
+    /// Upright, turned toward the camera about the vertical axis only
+    Vertical,
+    /// On the surface at the center of the view, like a decal (facing the
+    /// camera when nothing is there)
+    SurfaceAligned,
+}
+
 /// Request to add a paint canvas and enter paint mode.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddPaintCanvasRequest {
@@ -69,4 +292,13 @@ pub struct AddPaintCanvasRequest {
     pub width: Option<u32>,
     /// Canvas height in pixels (defaults to 1024)
     pub height: Option<u32>,
+    /// Width and height in world units (defaults to filling the view)
+    #[serde(default)]
+    pub world_size: Option<[f32; 2]>,
+    /// Distance from the camera in world units (defaults to 2)
+    #[serde(default)]
+    pub distance: Option<f32>,
+    /// How the canvas is turned (defaults to facing the camera)
+    #[serde(default)]
+    pub orientation: CanvasOrientation,
 }
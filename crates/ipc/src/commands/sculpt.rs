@@ -0,0 +1,59 @@
+//! Sculpt command types for the sculpting brush.
+
+use serde::{Deserialize, Serialize};
+
+/// Commands for controlling the sculpt brush.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SculptCommand {
+    /// Set dab spacing as a fraction of the brush radius (0.0 = one dab per input sample)
+    SetBrushSpacing { spacing: f32 },
+    /// Set brush flow, the per-dab strength multiplier (0.0-1.0)
+    SetBrushFlow { flow: f32 },
+    /// Select a built-in sculpt brush preset (Push, Clay Strips, Scrape, Mask, ...)
+    SelectBrushPreset { preset_id: u32 },
+    /// Enable or disable dynamic topology (tessellation while sculpting)
+    SetDynamicTopology { enabled: bool },
+    /// Set how dynamic topology decides the detail level
+    SetDetailMode(SculptDetailMode),
+    /// Set the detail size: target edge length in pixels (ScreenSpace) or
+    /// local mesh units (Constant), minimum edge length (Budget)
+    SetDetailSize { pixels_or_world: f32 },
+    /// Cap the total vertex count (None = pixel coverage only)
+    SetVertexBudget { max_vertices: Option<u32> },
+    /// Uniformly remesh the whole sculpt to a target edge length (local mesh
+    /// units). Runs in the background; paint data needs reprojection after.
+    Remesh { target_edge_length: f32 },
+    /// Cancel a running remesh, keeping the mesh as it was
+    CancelRemesh,
+    /// Clear the sculpt mask on the whole mesh
+    ClearMask,
+    /// Invert the sculpt mask on the whole mesh
+    InvertMask,
+    /// Audit the sculpt mesh for topology problems and repair what can be
+    /// repaired. Replies with `BevyToUi::MeshHealthReport`.
+    ValidateMesh,
+    /// Validate automatically after every N strokes (0 = never)
+    SetAutoValidate { every_strokes: u32 },
+}
+
+/// How dynamic topology decides the detail level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SculptDetailMode {
+    /// Constant edge length on screen, in pixels
+    #[default]
+    ScreenSpace,
+    /// Constant edge length on the mesh, in local units
+    Constant,
+    /// Vertex budget from render coverage, refined by curvature
+    Budget,
+}
+
+/// Per-chunk summary in a mesh health report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SculptChunkStats {
+    pub chunk_id: u32,
+    pub vertex_count: u32,
+    pub face_count: u32,
+    /// Issues left unrepaired in this chunk
+    pub issue_count: u32,
+}
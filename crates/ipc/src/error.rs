@@ -1,5 +1,8 @@
 //! Error types for IPC operations.
 
+use crate::messages::BevyToUi;
+use crate::protocol::{PROTOCOL_VERSION, UNSUPPORTED_MESSAGE_CODE};
+
 /// Errors that can occur during IPC operations.
 #[derive(Debug, thiserror::Error)]
 pub enum IpcError {
@@ -8,4 +11,29 @@ pub enum IpcError {
 
     #[error("Invalid message format: {0}")]
     InvalidFormat(String),
+
+    /// Well-formed message whose `type` this build doesn't know, usually
+    /// sent by a newer UI
+    #[error("Unknown message type: {0}")]
+    UnknownMessage(String),
+
+    /// Not valid JSON, or a known `type` with the wrong payload
+    #[error("Malformed message: {0}")]
+    Malformed(serde_json::Error),
+}
+
+impl IpcError {
+    /// `BevyToUi::Error` to send back to the UI, for errors the UI can act on
+    pub fn reply(&self) -> Option<BevyToUi> {
+        match self {
+            IpcError::UnknownMessage(type_name) => Some(BevyToUi::Error {
+                code: UNSUPPORTED_MESSAGE_CODE.to_string(),
+                message: format!(
+                    "Message type {} is not supported (IPC protocol {})",
+                    type_name, PROTOCOL_VERSION
+                ),
+            }),
+            _ => None,
+        }
+    }
 }
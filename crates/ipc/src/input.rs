@@ -38,7 +38,13 @@ pub enum MouseButton {
 /// Keyboard input event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyboardEvent {
+    /// Logical key as a web `KeyboardEvent.key` value, following the user's
+    /// layout ("a" or "A" for a letter, "Enter", "Dead" for a dead key)
     pub key: String,
+    /// Physical key as a web `KeyboardEvent.code` value ("KeyA", "Digit1"),
+    /// independent of the layout. Empty when the sender doesn't know it.
+    #[serde(default)]
+    pub code: String,
     pub pressed: bool,
     pub modifiers: Modifiers,
 }
@@ -2,10 +2,12 @@
 //!
 //! Defines all message types exchanged between the Bevy backend and Svelte UI.
 
+pub mod batch;
 pub mod commands;
 pub mod error;
 pub mod input;
 pub mod messages;
+pub mod protocol;
 pub mod types;
 
 // Re-export all public types at the crate root for API compatibility.
@@ -14,19 +16,35 @@ pub mod types;
 // Main message enums
 pub use messages::{BevyToUi, UiToBevy};
 
+// Per-frame batching
+pub use batch::{Coalesce, CoalesceKey, coalesce, parse_ui_batch};
+
+// Protocol versioning and parsing
+pub use protocol::{
+    PROTOCOL_MISMATCH_CODE, PROTOCOL_VERSION, UNSUPPORTED_MESSAGE_CODE, parse_bevy_to_ui,
+    parse_ui_to_bevy, protocol_mismatch_warning,
+};
+
 // Types
 pub use types::{
-    AddObjectRequest, AmbientOcclusionSettings, AppSettings, CameraInfo, DiffusionRequest,
+    AddObjectRequest, AmbientOcclusionSettings, AnnotationInfo, AppSettings, BlobKind, CameraInfo,
+    CloseDecision, CompositeMode, DiffusionRequest, FrontendLifecycle, GamepadStick, KeyBinding,
     LayoutInfo, LayoutRegion, LightInfo, LightType, LightingSettings, MaterialProperties,
-    NodeConnection, NodeGraphState, NodeInfo, PrimitiveType, SceneInfo, SceneObject, TextureSlot,
-    Transform3D,
+    NavigationDeviceSettings, NodeConnection, NodeGraphState, NodeInfo, OrbitOptions,
+    PrimitiveType, QueryKind, ReferenceImageMode, SceneInfo, SceneObject, ScreenCorner,
+    SelectionOutlineSettings, TextureRegistryStats, TextureSlot, Transform3D, UiLogLevel, ViewMode,
+    WireframeInfo, WireframeTarget,
 };
 
 // Commands
 pub use commands::{
-    AddPaintCanvasRequest, BlendMode, CameraCommand, CoordinateSpace, EditMode, GizmoAxis,
-    GizmoCommand, GizmoMode, LayerInfo, MaterialCommand, MeshEditCommand, MeshEditTool,
-    MeshSelectionMode, ObjectCommand, PaintCommand,
+    AddPaintCanvasRequest, BlendMode, BrushTipSource, CameraCommand, CanvasAnchor,
+    CanvasOrientation, CanvasTool, CollabCommand, CollabState, CoordinateSpace, EditMode,
+    GizmoAxis, GizmoCommand, GizmoMode, GradientKind, LayerInfo, LightCommand, MaterialCommand,
+    MeasureCommand, MeshEditCommand, MeshEditTool, MeshPaintChannel, MeshPaintTarget,
+    MeshSelectionMode, ObjectCommand, PaintCommand, PivotPoint, PixelSelectionMode,
+    ProjectionOptions, SculptChunkStats, SculptCommand, SculptDetailMode, SnapTarget,
+    TipRotationMode,
 };
 
 // Input types
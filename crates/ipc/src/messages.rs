@@ -3,19 +3,24 @@
 use serde::{Deserialize, Serialize};
 
 use crate::commands::{
-    AddPaintCanvasRequest, CameraCommand, EditMode, GizmoCommand, GizmoMode, LayerInfo,
-    MaterialCommand, MeshEditCommand, MeshEditTool, MeshSelectionMode, ObjectCommand, PaintCommand,
+    AddPaintCanvasRequest, CameraCommand, CollabCommand, CollabState, CoordinateSpace, EditMode,
+    GizmoAxis, GizmoCommand, GizmoMode, LayerInfo, LightCommand, MaterialCommand, MeasureCommand,
+    MeshEditCommand, MeshEditTool, MeshSelectionMode, ObjectCommand, PaintCommand, PivotPoint,
+    SculptChunkStats, SculptCommand, SculptDetailMode, SnapTarget,
 };
 use crate::types::{
-    AddObjectRequest, AmbientOcclusionSettings, AppSettings, DiffusionRequest, LayoutInfo,
-    LightingSettings, MaterialProperties, NodeGraphState, SceneInfo, SceneObject,
+    AddObjectRequest, AmbientOcclusionSettings, AppSettings, BlobKind, CloseDecision,
+    CompositeMode, DiffusionRequest, FrontendLifecycle, KeyBinding, LayoutInfo, LightInfo,
+    LightingSettings, MaterialProperties, NodeGraphState, QueryKind, ReferenceImageMode, SceneInfo,
+    SceneObject, TextureRegistryStats, UiLogLevel, ViewMode, WireframeTarget,
 };
 
 /// Messages from Bevy to the Svelte UI.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum BevyToUi {
-    /// Initial state sync when UI loads
+    /// Initial state sync when UI loads, first in the initialization
+    /// sequence (see [`crate::protocol`] for what follows it)
     Initialize {
         scene_info: SceneInfo,
         settings: AppSettings,
@@ -43,12 +48,37 @@ pub enum BevyToUi {
     /// Diffusion generation complete
     DiffusionComplete { task_id: String, texture_id: String },
 
+    /// Texture registry totals changed (textures added, assigned, freed)
+    TextureRegistryStats(TextureRegistryStats),
+
     /// Render statistics
     RenderStats {
         fps: f32,
         frame_time_ms: f32,
         draw_calls: u32,
+        /// Triangles in the meshes visible when the stats were sent
         triangles: u32,
+        /// UI framebuffer captures in the last second
+        captures_per_second: f32,
+        /// Frames in the last second where the UI was clean and no capture was taken
+        skipped_captures: u32,
+        /// Mesh texels reprojected per frame by live projection painting
+        projected_texels_per_frame: f32,
+        /// Canvas and mesh paint tiles written to GPU textures per frame
+        tiles_uploaded_per_frame: f32,
+        /// Frames in the last second where the Dioxus UI was clean and its
+        /// Vello render was skipped (always 0 for captured frontends)
+        ui_renders_skipped: u32,
+        /// Resolution the scene is rendered at relative to the window
+        /// (below 1.0 while the performance governor or the settings lower it)
+        render_scale: f32,
+        /// Window scale factor (physical pixels per logical pixel), which
+        /// changes when the window moves to a monitor with a different DPI
+        scale_factor: f32,
+        /// Bytes held by canvas stroke logs and undo stacks
+        history_bytes: u64,
+        /// Bytes paint history may hold before old strokes are checkpointed
+        history_budget_bytes: u64,
     },
 
     /// Mouse entered a UI region
@@ -60,6 +90,9 @@ pub enum BevyToUi {
     /// Error notification
     Error { code: String, message: String },
 
+    /// Warning notification (operation succeeded with caveats)
+    Warning { code: String, message: String },
+
     /// Show/hide add object menu (triggered by Shift+A)
     ShowAddObjectMenu {
         show: bool,
@@ -68,14 +101,33 @@ pub enum BevyToUi {
     },
 
     /// Object was added to scene
-    ObjectAdded { object: SceneObject },
+    ObjectAdded {
+        object: SceneObject,
+        /// ID of the object this one is a duplicate of
+        #[serde(default)]
+        duplicated_from: Option<String>,
+    },
 
     /// Gizmo mode changed (for UI sync)
     GizmoModeChanged { mode: GizmoMode },
 
+    /// Gizmo operation, constraint, snap target, or pivot changed
+    GizmoStatus {
+        mode: GizmoMode,
+        axis: GizmoAxis,
+        coordinate_space: CoordinateSpace,
+        /// Whether a transform operation is in progress
+        active: bool,
+        snap: SnapTarget,
+        pivot: PivotPoint,
+    },
+
     /// Ambient occlusion settings changed
     AmbientOcclusionChanged { settings: AmbientOcclusionSettings },
 
+    /// Lighting (sun, sky, time of day) settings changed
+    LightingChanged { settings: LightingSettings },
+
     /// Edit mode changed (paint mode, etc.)
     EditModeChanged { mode: EditMode },
 
@@ -107,6 +159,139 @@ pub enum BevyToUi {
 
     /// Layer state changed (full layer stack info for UI sync)
     LayerStateChanged { layers: Vec<LayerInfo> },
+
+    /// Effective sculpt dynamic topology settings (sent on entering sculpt mode)
+    SculptSettingsChanged {
+        /// Whether dynamic topology is enabled
+        dynamic_topology: bool,
+        /// Current detail mode
+        detail_mode: SculptDetailMode,
+        /// Detail size for the current mode (see `SculptCommand::SetDetailSize`)
+        detail_size: f32,
+        /// Vertex budget cap (None = pixel coverage only)
+        max_vertices: Option<u32>,
+    },
+
+    /// Progress of a running sculpt remesh
+    SculptRemeshProgress {
+        /// Completed fraction (0.0-1.0)
+        progress: f32,
+    },
+
+    /// A sculpt remesh finished or was cancelled
+    SculptRemeshFinished {
+        /// Whether the remesh was cancelled or failed (mesh unchanged)
+        cancelled: bool,
+        /// Vertex count of the sculpt afterwards
+        vertex_count: u32,
+        /// Face count of the sculpt afterwards
+        face_count: u32,
+    },
+
+    /// Result of a sculpt mesh integrity audit
+    MeshHealthReport {
+        /// Description of every issue found, repaired or not
+        errors: Vec<String>,
+        /// How many of the issues were repaired
+        repaired: u32,
+        /// Per-chunk summary
+        chunk_stats: Vec<SculptChunkStats>,
+    },
+
+    /// Full keymap after a startup load or a rebinding
+    KeymapChanged { bindings: Vec<KeyBinding> },
+
+    /// Full light list after a light was added, edited, moved, or deleted
+    LightsChanged { lights: Vec<LightInfo> },
+
+    /// The measure tool placed a measurement (endpoints in world space);
+    /// it is listed in `SceneInfo::annotations` under `id`
+    MeasureResult {
+        id: String,
+        start: [f32; 3],
+        end: [f32; 3],
+        distance: f32,
+    },
+
+    /// An autosave newer than the last explicit save was found at startup.
+    /// `timestamp` is the autosave time in Unix milliseconds.
+    RecoveryAvailable { path: String, timestamp: u64 },
+
+    /// A canvas export requested with `PaintCommand::ExportCanvas` was written
+    CanvasExported { path: String },
+
+    /// A turntable render captured `frame` of `total` frames
+    RenderProgress { frame: u32, total: u32 },
+
+    /// A turntable render finished; `path` is the video file or frame directory
+    RenderFinished { path: String },
+
+    /// Progress of a running paint storage conversion (Ptex / UV atlas)
+    MeshStorageConversionProgress {
+        /// Paintable mesh being converted
+        mesh_id: u32,
+        /// Completed fraction (0.0-1.0)
+        progress: f32,
+    },
+
+    /// A paint storage conversion finished or was cancelled
+    MeshStorageConversionFinished {
+        /// Paintable mesh that was converted
+        mesh_id: u32,
+        /// Whether the conversion was dropped (storage unchanged)
+        cancelled: bool,
+    },
+
+    /// A console message from the UI webview, for a debug log panel
+    UiLog {
+        level: UiLogLevel,
+        message: String,
+        /// Script URL the message came from (empty if unknown)
+        source: String,
+        /// Line in `source` (0 if unknown)
+        line: u32,
+    },
+
+    /// Binary data too large for JSON is ready to fetch. The UI reads it
+    /// from `pentimento-blob://<id>` (Dioxus receives it directly) and
+    /// answers with `BlobConsumed`; unclaimed blobs expire after a few seconds.
+    BinaryBlob {
+        id: u64,
+        kind: BlobKind,
+        byte_length: u64,
+    },
+
+    /// IPC protocol version of this build, sent right after `Initialize`.
+    /// The UI answers with its own `UiToBevy::ProtocolVersion`.
+    ProtocolVersion { version: u32 },
+
+    /// Answer to `UiToBevy::Query` with the same `request_id`. Every query
+    /// gets exactly one; `Err` carries a message for the user.
+    QueryResult {
+        request_id: u64,
+        result: Result<serde_json::Value, String>,
+    },
+
+    /// Collaboration session changed: after `UiToBevy::Collab`, when a peer
+    /// joins or leaves, or when the host goes away
+    CollabStatus {
+        state: CollabState,
+        /// Ticket other peers join with (while hosting)
+        ticket: Option<String>,
+        /// Peers connected to this instance
+        peers: u32,
+        /// Why the last command failed or the session ended
+        error: Option<String>,
+    },
+
+    /// UI backend changed lifecycle state (sent once the UI is up, e.g.
+    /// around window resizes; the UI greys itself out while `Resizing`)
+    BackendLifecycleChanged { state: FrontendLifecycle },
+
+    /// The user asked to close the window. The UI answers with
+    /// `UiToBevy::CloseResponse`; without an answer the app closes after a
+    /// few seconds, so a dead page can't keep it open.
+    CloseRequested,
 }
 
 /// Messages from Svelte UI to Bevy.
@@ -134,6 +319,9 @@ pub enum UiToBevy {
     /// Cancel diffusion generation
     CancelDiffusion { task_id: String },
 
+    /// Free a registered texture and clear the material slots using it
+    DeleteTexture { texture_id: String },
+
     /// Settings changed
     UpdateSettings(AppSettings),
 
@@ -152,6 +340,9 @@ pub enum UiToBevy {
     /// Gizmo command (from keyboard hotkeys)
     GizmoCommand(GizmoCommand),
 
+    /// Add, edit, or delete scene lights
+    LightCommand(LightCommand),
+
     /// Add a paint canvas and enter paint mode
     AddPaintCanvas(AddPaintCanvasRequest),
 
@@ -161,6 +352,90 @@ pub enum UiToBevy {
     /// Mesh edit mode commands
     MeshEditCommand(MeshEditCommand),
 
-    /// Toggle depth view mode
+    /// Sculpt brush commands (spacing, flow)
+    SculptCommand(SculptCommand),
+
+    /// Start or cancel the measure tool; each measurement is answered with
+    /// `MeasureResult`
+    MeasureCommand(MeasureCommand),
+
+    /// Request an edit mode; the target (canvas plane or mesh) comes from the
+    /// selection. Answered with `EditModeChanged`, or `Error` when the mode
+    /// can't be entered
+    SetEditMode { mode: EditMode },
+
+    /// Toggle depth view mode (shorthand for `SetViewMode` with `Depth` or
+    /// `Shaded`)
     SetDepthView { enabled: bool },
+
+    /// Switch the viewport between shaded rendering and a debug view
+    SetViewMode { mode: ViewMode },
+
+    /// Switch the UI compositing backend without restarting
+    SetCompositeMode { mode: CompositeMode },
+
+    /// Rebind a hotkey action to a chord like "Shift+A"
+    SetKeybinding { action: String, chord: String },
+
+    /// Restore the autosave offered by `RecoveryAvailable`
+    RestoreAutosave { path: String },
+
+    /// Load an image file as a reference image. Bevy answers with
+    /// `ObjectAdded`; visibility and deletion go through `ObjectCommand`.
+    AddReferenceImage {
+        path: String,
+        mode: ReferenceImageMode,
+    },
+
+    /// Set a reference image's opacity (0.0-1.0)
+    SetReferenceImageOpacity { id: String, opacity: f32 },
+
+    /// Render a 360° orbit around the camera target to a PNG sequence
+    /// (`path` is a directory) or, with the `ffmpeg` feature, an MP4 file
+    RenderTurntable {
+        frames: u32,
+        seconds: f32,
+        width: u32,
+        height: u32,
+        path: String,
+    },
+
+    /// Cancel the running turntable render
+    CancelRender,
+
+    /// Show or hide the wireframe overlay for the whole scene or one object.
+    /// `color` keeps the current color when `None`. Reflected in
+    /// `SceneUpdated`; answered with `Error` when the `wireframe` feature is off.
+    SetWireframe {
+        target: WireframeTarget,
+        enabled: bool,
+        color: Option<[f32; 4]>,
+        depth_test: bool,
+    },
+
+    /// The UI has read the blob announced by `BinaryBlob`, so Bevy can free it
+    BlobConsumed { id: u64 },
+
+    /// IPC protocol version the UI was built against, in reply to
+    /// `BevyToUi::ProtocolVersion`; a mismatch is answered with `Warning`
+    ProtocolVersion { version: u32 },
+
+    /// Ask Bevy a question; answered by one `BevyToUi::QueryResult` with the
+    /// same `request_id` (chosen by the UI, unique per page)
+    Query { request_id: u64, query: QueryKind },
+
+    /// Host, join, or leave a collaboration session; answered with
+    /// `CollabStatus` (an `Error` when the `collab` feature is off)
+    Collab(CollabCommand),
+
+    /// Answer to `BevyToUi::CloseRequested`
+    CloseResponse { decision: CloseDecision },
+
+    /// The page lost its state (reloaded, hot-reloaded): send the
+    /// initialization sequence again
+    RequestInitialize,
+
+    /// Checkpoint every canvas's stroke log behind its undo stack now,
+    /// instead of waiting for the paint history budget to run out
+    CompactHistory,
 }
@@ -0,0 +1,200 @@
+//! IPC protocol versioning and message parsing
+//!
+//! Bevy sends `BevyToUi::ProtocolVersion` right after `Initialize` and the UI
+//! answers with `UiToBevy::ProtocolVersion`, so a UI built from a different
+//! contract is reported instead of misbehaving quietly.
+//!
+//! # Initialization sequence
+//!
+//! Whenever a backend becomes ready (startup, mode switch, crash restart, or
+//! a page reload the backend notices) and whenever the UI sends
+//! `UiToBevy::RequestInitialize`, Bevy sends the full app state ahead of
+//! anything else queued that frame, in this order:
+//!
+//! 1. `Initialize` (scene and app settings)
+//! 2. `ProtocolVersion`
+//! 3. `LightingChanged`
+//! 4. `AmbientOcclusionChanged`
+//! 5. `EditModeChanged`
+//! 6. `GizmoModeChanged`
+//! 7. `SelectionChanged` (empty without the `selection` feature)
+//! 8. In paint mode only: `LayerStateChanged` for the active canvas, then
+//!    `ProjectionModeChanged`
+//!
+//! Later messages may rely on the state set by earlier ones, and the UI can
+//! treat everything up to and including the last of them as one sync. Brush
+//! settings are owned by the UI and sent to Bevy, so they aren't part of it.
+//!
+//! Backends parse incoming messages with `parse_ui_to_bevy`, which tells a
+//! message type this build doesn't know (a newer UI) apart from broken JSON.
+//! Unknown fields in known messages are ignored by serde, so adding a field
+//! stays compatible; adding a message type needs a version bump. Batches of
+//! messages are parsed with `parse_ui_batch` (see `batch`).
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::IpcError;
+use crate::messages::{BevyToUi, UiToBevy};
+
+/// Version of the message contract in this crate. Bump it when a message is
+/// added or changed; `PROTOCOL_VERSION` in `ui/src/lib/types.ts` must match.
+pub const PROTOCOL_VERSION: u32 = 26;
+
+/// `BevyToUi::Error` code answering a message type Bevy doesn't know
+pub const UNSUPPORTED_MESSAGE_CODE: &str = "unsupported_message";
+
+/// `BevyToUi::Warning` code sent when the UI reports another protocol version
+pub const PROTOCOL_MISMATCH_CODE: &str = "protocol_version_mismatch";
+
+/// Parse a message sent by the UI
+pub fn parse_ui_to_bevy(json: &str) -> Result<UiToBevy, IpcError> {
+    parse_message(json)
+}
+
+/// Parse a message sent by Bevy
+pub fn parse_bevy_to_ui(json: &str) -> Result<BevyToUi, IpcError> {
+    parse_message(json)
+}
+
+/// Warning for the UI if it was built against another protocol version
+pub fn protocol_mismatch_warning(ui_version: u32) -> Option<BevyToUi> {
+    (ui_version != PROTOCOL_VERSION).then(|| BevyToUi::Warning {
+        code: PROTOCOL_MISMATCH_CODE.to_string(),
+        message: format!(
+            "UI speaks IPC protocol {} but the app speaks {}; some actions may be ignored",
+            ui_version, PROTOCOL_VERSION
+        ),
+    })
+}
+
+fn parse_message<T: DeserializeOwned>(json: &str) -> Result<T, IpcError> {
+    serde_json::from_str(json).map_err(|e| {
+        let value = serde_json::from_str::<Value>(json).ok();
+        classify_error(value.as_ref().and_then(message_type), e)
+    })
+}
+
+/// Parse one message of a batch the UI sent as a JSON array
+pub(crate) fn parse_ui_value(value: Value) -> Result<UiToBevy, IpcError> {
+    let type_name = message_type(&value);
+    serde_json::from_value(value).map_err(|e| classify_error(type_name, e))
+}
+
+fn message_type(value: &Value) -> Option<String> {
+    value.get("type")?.as_str().map(str::to_owned)
+}
+
+/// Tell an unknown `type` apart from any other parse failure
+///
+/// serde reports an unknown tag as "unknown variant `X`, expected ...". The
+/// variant name is compared with the message's own `type` so an unknown
+/// value in a nested enum still counts as malformed.
+fn classify_error(type_name: Option<String>, error: serde_json::Error) -> IpcError {
+    if !error.is_data() {
+        return IpcError::Malformed(error);
+    }
+
+    match type_name {
+        Some(type_name)
+            if error
+                .to_string()
+                .starts_with(&format!("unknown variant `{}`", type_name)) =>
+        {
+            IpcError::UnknownMessage(type_name)
+        }
+        _ => IpcError::Malformed(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CameraCommand;
+    use crate::types::{CompositeMode, QueryKind};
+
+    #[test]
+    fn test_round_trip() {
+        let messages = [
+            UiToBevy::UiDirty,
+            UiToBevy::ProtocolVersion {
+                version: PROTOCOL_VERSION,
+            },
+            UiToBevy::CameraCommand(CameraCommand::Orbit {
+                delta_x: 1.0,
+                delta_y: -2.0,
+            }),
+            UiToBevy::SetCompositeMode {
+                mode: CompositeMode::Cef,
+            },
+            UiToBevy::Query {
+                request_id: 9,
+                query: QueryKind::ObjectAtPoint { x: 10.0, y: 20.5 },
+            },
+        ];
+        for msg in messages {
+            let json = serde_json::to_string(&msg).unwrap();
+            let parsed = parse_ui_to_bevy(&json).unwrap();
+            assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        }
+
+        let json = serde_json::to_string(&BevyToUi::ProtocolVersion { version: 3 }).unwrap();
+        assert!(matches!(
+            parse_bevy_to_ui(&json),
+            Ok(BevyToUi::ProtocolVersion { version: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_message_type() {
+        let result = parse_ui_to_bevy(r#"{"type":"SetHologramMode","data":{"enabled":true}}"#);
+        assert!(matches!(result, Err(IpcError::UnknownMessage(name)) if name == "SetHologramMode"));
+
+        let result = parse_ui_to_bevy(r#"{"type":"RequestTelepathy"}"#);
+        assert!(
+            matches!(result, Err(IpcError::UnknownMessage(name)) if name == "RequestTelepathy")
+        );
+
+        let result = parse_bevy_to_ui(r#"{"type":"ShowHologram","data":null}"#);
+        assert!(matches!(result, Err(IpcError::UnknownMessage(name)) if name == "ShowHologram"));
+    }
+
+    #[test]
+    fn test_malformed_messages() {
+        for json in [
+            "not json",
+            r#"{"type":"UiDirty""#,
+            r#"{"data":{}}"#,
+            r#"{"type":42}"#,
+            // Known type with the wrong payload
+            r#"{"type":"SetDepthView","data":{"enabled":"yes"}}"#,
+            // Unknown value in a nested enum is not an unknown message
+            r#"{"type":"SetCompositeMode","data":{"mode":"Hologram"}}"#,
+        ] {
+            assert!(
+                matches!(parse_ui_to_bevy(json), Err(IpcError::Malformed(_))),
+                "{} should be malformed",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_fields_are_ignored() {
+        // A newer UI adding a field to an existing message still parses
+        let msg = parse_ui_to_bevy(
+            r#"{"type":"SetDepthView","data":{"enabled":true,"fade":0.5},"trace_id":7}"#,
+        )
+        .unwrap();
+        assert!(matches!(msg, UiToBevy::SetDepthView { enabled: true }));
+    }
+
+    #[test]
+    fn test_protocol_mismatch_warning() {
+        assert!(protocol_mismatch_warning(PROTOCOL_VERSION).is_none());
+        assert!(matches!(
+            protocol_mismatch_warning(PROTOCOL_VERSION + 1),
+            Some(BevyToUi::Warning { code, .. }) if code == PROTOCOL_MISMATCH_CODE
+        ));
+    }
+}
@@ -18,3 +18,18 @@ pub struct TextureSlot {
     pub slot_name: String,
     pub texture_id: Option<String>,
 }
+
+/// Texture registry totals, for a debug panel.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextureRegistryStats {
+    /// Textures the registry holds
+    pub texture_count: u32,
+    /// Textures assigned to at least one material slot
+    pub referenced_count: u32,
+    /// Pixel bytes of all registered textures
+    pub total_bytes: u64,
+    /// Bytes above which unreferenced textures are evicted
+    pub budget_bytes: u64,
+    /// Unreferenced textures evicted over budget this session
+    pub evicted_count: u32,
+}
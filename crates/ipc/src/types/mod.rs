@@ -1,9 +1,11 @@
 //! Type definitions for IPC messages.
 
 mod material;
+mod query;
 mod scene;
 mod settings;
 
 pub use material::*;
+pub use query::*;
 pub use scene::*;
 pub use settings::*;
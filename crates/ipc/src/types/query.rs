@@ -0,0 +1,18 @@
+//! Query payloads for request/response IPC.
+
+use serde::{Deserialize, Serialize};
+
+/// A question the UI asks Bevy with `UiToBevy::Query`.
+///
+/// Each variant documents the JSON its `BevyToUi::QueryResult` carries on
+/// success. Add new questions here instead of a new message pair.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum QueryKind {
+    /// Object under a point in the viewport, in logical window pixels.
+    /// Answers `{ object_id, position, distance }`, or `null` over empty space.
+    ObjectAtPoint { x: f32, y: f32 },
+    /// Properties of a material. Answers a `MaterialProperties`.
+    MaterialProperties { material_id: String },
+    /// Current scene. Answers a `SceneInfo`.
+    SceneInfo,
+}
@@ -8,6 +8,27 @@ pub struct SceneInfo {
     pub objects: Vec<SceneObject>,
     pub cameras: Vec<CameraInfo>,
     pub lights: Vec<LightInfo>,
+    /// Global wireframe overlay; `None` when the `wireframe` feature is off
+    #[serde(default)]
+    pub wireframe: Option<WireframeInfo>,
+    /// Whether `ObjectCommand::Isolate` is hiding the other objects
+    #[serde(default)]
+    pub isolated: bool,
+    /// Viewport annotations (measurements), deleted with `ObjectCommand::Delete`
+    #[serde(default)]
+    pub annotations: Vec<AnnotationInfo>,
+}
+
+/// A measurement between two points in the scene.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationInfo {
+    pub id: String,
+    pub name: String,
+    /// World-space endpoints
+    pub start: [f32; 3],
+    pub end: [f32; 3],
+    /// Distance between the endpoints in world units
+    pub distance: f32,
 }
 
 /// A scene object with its properties.
@@ -18,6 +39,41 @@ pub struct SceneObject {
     pub transform: Transform3D,
     pub material_id: Option<String>,
     pub visible: bool,
+    /// Locked against selection, transforms, painting, and sculpting
+    #[serde(default)]
+    pub locked: bool,
+    /// Parent object ID; `None` for objects at the scene root
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// Subdivision preview levels shown in place of the base mesh (0 = off)
+    #[serde(default)]
+    pub subdivision_levels: u8,
+    /// Wireframe set on this object; `None` follows the global overlay
+    #[serde(default)]
+    pub wireframe: Option<WireframeInfo>,
+    /// Shares its mesh with another object (a duplicate not edited yet)
+    #[serde(default)]
+    pub instanced: bool,
+}
+
+/// Wireframe overlay state, global or for one object.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WireframeInfo {
+    pub enabled: bool,
+    /// RGBA (0.0-1.0)
+    pub color: [f32; 4],
+    /// Whether the wireframe is hidden behind nearer surfaces; `false` shows
+    /// it through them
+    pub depth_test: bool,
+}
+
+/// What a `SetWireframe` message applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WireframeTarget {
+    /// The overlay on every object without its own setting
+    Global,
+    /// One object by ID
+    Object(String),
 }
 
 /// 3D transform with position, rotation, and scale.
@@ -46,8 +102,10 @@ pub struct LightInfo {
     pub name: String,
     pub light_type: LightType,
     pub color: [f32; 3],
+    /// Lumens for point and spot lights, lux for directional lights
     pub intensity: f32,
     pub transform: Transform3D,
+    pub shadows: bool,
 }
 
 /// Type of light source.
@@ -74,16 +132,49 @@ pub enum PrimitiveType {
     Torus,
     Cone,
     Capsule,
+    /// Stylized monkey head, for testing shading and painting
+    Monkey,
+    /// Flat 2x2 grid with `subdivisions` cuts along each side
+    Grid {
+        subdivisions: u32,
+    },
 }
 
 /// Request to add a new object to the scene.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddObjectRequest {
     pub primitive_type: PrimitiveType,
-    /// Optional world position (defaults to origin)
+    /// Optional world position (defaults to where the Shift+A menu was
+    /// opened, or the origin)
     pub position: Option<[f32; 3]>,
     /// Optional custom name
     pub name: Option<String>,
+    /// Segments around spheres and cylinders (defaults to 32)
+    pub subdivisions: Option<u32>,
+}
+
+/// How a reference image is shown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReferenceImageMode {
+    /// Textured quad in the scene (selectable, transformable with the gizmo)
+    WorldPlane,
+    /// Pinned to a viewport corner, drawn over the 3D scene and under the UI
+    CameraPinned {
+        corner: ScreenCorner,
+        /// Image height as a fraction of the viewport height (0.0-1.0)
+        size: f32,
+        /// Opacity (0.0-1.0)
+        opacity: f32,
+    },
+}
+
+/// Corner of the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScreenCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
 }
 
 /// Layout information for UI regions.
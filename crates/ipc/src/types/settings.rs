@@ -5,27 +5,287 @@ use serde::{Deserialize, Serialize};
 /// Application-wide settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
+    /// Resolution of the scene relative to the window (0.5-1.0), used while
+    /// `auto_performance` is off
     pub render_scale: f32,
+    /// Resolution of the UI texture relative to the window (0.5-1.0).
+    /// Lower values make UI capture cheaper at the cost of softer text.
+    #[serde(default = "default_ui_render_scale")]
+    pub ui_render_scale: f32,
     pub vsync: bool,
     pub msaa_samples: u32,
+    /// Lower the scene's render scale and MSAA when frames take too long.
+    /// Changing `render_scale` or `msaa_samples` turns it off until it is
+    /// switched on again.
+    #[serde(default = "default_auto_performance")]
+    pub auto_performance: bool,
     pub show_wireframe: bool,
     pub show_grid: bool,
     pub diffusion_server_url: Option<String>,
+    /// Gamepad / SpaceMouse camera navigation
+    #[serde(default)]
+    pub navigation: NavigationDeviceSettings,
+    /// Selection outline colors, width, and see-through style
+    #[serde(default)]
+    pub outline: SelectionOutlineSettings,
+    /// Orbit pivot and zoom behavior of the viewport camera
+    #[serde(default)]
+    pub orbit: OrbitOptions,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
             render_scale: 1.0,
+            ui_render_scale: default_ui_render_scale(),
             vsync: true,
             msaa_samples: 4,
+            auto_performance: default_auto_performance(),
             show_wireframe: false,
             show_grid: true,
             diffusion_server_url: None,
+            navigation: NavigationDeviceSettings::default(),
+            outline: SelectionOutlineSettings::default(),
+            orbit: OrbitOptions::default(),
         }
     }
 }
 
+impl AppSettings {
+    /// Allowed range for `render_scale`
+    pub const RENDER_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=1.0;
+
+    /// Allowed range for `ui_render_scale`
+    pub const UI_RENDER_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=1.0;
+
+    /// `render_scale` clamped to `RENDER_SCALE_RANGE` (1.0 if not finite)
+    pub fn clamped_render_scale(&self) -> f32 {
+        clamp_scale(self.render_scale, &Self::RENDER_SCALE_RANGE)
+    }
+
+    /// `ui_render_scale` clamped to `UI_RENDER_SCALE_RANGE` (1.0 if not finite)
+    pub fn clamped_ui_render_scale(&self) -> f32 {
+        clamp_scale(self.ui_render_scale, &Self::UI_RENDER_SCALE_RANGE)
+    }
+
+    /// `msaa_samples` rounded down to a supported sample count (1, 2, 4, or 8)
+    pub fn supported_msaa_samples(&self) -> u32 {
+        match self.msaa_samples {
+            0..=1 => 1,
+            2..=3 => 2,
+            4..=7 => 4,
+            _ => 8,
+        }
+    }
+}
+
+fn clamp_scale(scale: f32, range: &std::ops::RangeInclusive<f32>) -> f32 {
+    if scale.is_finite() {
+        scale.clamp(*range.start(), *range.end())
+    } else {
+        1.0
+    }
+}
+
+fn default_ui_render_scale() -> f32 {
+    1.0
+}
+
+fn default_auto_performance() -> bool {
+    true
+}
+
+/// Gamepad stick selection for navigation axis mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GamepadStick {
+    Left,
+    Right,
+}
+
+/// Navigation device (gamepad, SpaceMouse) axis mapping and response.
+///
+/// Speeds are in mouse pixels (orbit, pan) or scroll lines (zoom) per second
+/// at full deflection, so they stack with the camera's own sensitivities.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavigationDeviceSettings {
+    pub enabled: bool,
+    /// Stick that orbits the camera
+    pub orbit_stick: GamepadStick,
+    /// Stick that pans the camera (and nudges the selection while the modifier is held)
+    pub pan_stick: GamepadStick,
+    /// Invert the vertical axis of both sticks
+    pub invert_y: bool,
+    /// Axis magnitude below which input is ignored (0.0-1.0)
+    pub dead_zone: f32,
+    pub orbit_speed: f32,
+    pub pan_speed: f32,
+    /// Triggers: right zooms in, left zooms out
+    pub zoom_speed: f32,
+}
+
+impl Default for NavigationDeviceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            orbit_stick: GamepadStick::Right,
+            pan_stick: GamepadStick::Left,
+            invert_y: false,
+            dead_zone: 0.15,
+            orbit_speed: 400.0,
+            pan_speed: 400.0,
+            zoom_speed: 6.0,
+        }
+    }
+}
+
+/// Viewport camera orbit and zoom behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrbitOptions {
+    /// Orbit around the surface under the cursor when an orbit starts,
+    /// easing the orbit target over to it
+    pub orbit_around_depth: bool,
+    /// Keep the point under the cursor in place while scrolling to zoom
+    pub zoom_to_cursor: bool,
+}
+
+impl Default for OrbitOptions {
+    fn default() -> Self {
+        Self {
+            orbit_around_depth: true,
+            zoom_to_cursor: true,
+        }
+    }
+}
+
+/// Selection outline style.
+///
+/// The active object is the last one selected. Where other geometry hides
+/// part of a selected object, its outline is drawn at `occluded_opacity`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelectionOutlineSettings {
+    pub enabled: bool,
+    /// Outline of the active object as sRGB (0.0-1.0)
+    pub active_color: [f32; 3],
+    /// Outline of the other selected objects as sRGB (0.0-1.0)
+    pub selected_color: [f32; 3],
+    /// Width in physical pixels, the same at any render scale
+    pub thickness: f32,
+    /// Opacity of hidden outline portions (0.0 = not drawn, 1.0 = like visible ones)
+    pub occluded_opacity: f32,
+}
+
+impl Default for SelectionOutlineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            // Blender's light orange for the active object, orange-red for the rest
+            active_color: [1.0, 0.667, 0.251],
+            selected_color: [0.929, 0.341, 0.0],
+            thickness: 2.0,
+            occluded_opacity: 0.35,
+        }
+    }
+}
+
+impl SelectionOutlineSettings {
+    /// Allowed range for `thickness`
+    pub const THICKNESS_RANGE: std::ops::RangeInclusive<f32> = 1.0..=8.0;
+
+    /// `thickness` clamped to `THICKNESS_RANGE` (the default if not finite)
+    pub fn clamped_thickness(&self) -> f32 {
+        if self.thickness.is_finite() {
+            self.thickness
+                .clamp(*Self::THICKNESS_RANGE.start(), *Self::THICKNESS_RANGE.end())
+        } else {
+            Self::default().thickness
+        }
+    }
+}
+
+/// UI compositing backend, as requested by the UI for a runtime switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompositeMode {
+    /// Offscreen WebKitGTK capture with texture upload
+    Capture,
+    /// Transparent child window composited by the desktop compositor
+    Overlay,
+    /// CEF (Chromium) offscreen rendering
+    Cef,
+    /// Native Dioxus UI rendered with Vello
+    Dioxus,
+    /// Bevy WASM inside a Tauri webview
+    Tauri,
+}
+
+/// Lifecycle of the UI backend, reported with `BackendLifecycleChanged`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrontendLifecycle {
+    /// Backend is starting up
+    Initializing,
+    /// Page is loading or warming up
+    Loading,
+    /// Normal operation
+    Ready,
+    /// Surface is being resized; input is not forwarded until it settles
+    Resizing,
+    /// Backend failed (e.g. the page crashed) and is being recovered
+    Error,
+}
+
+/// UI's answer to `BevyToUi::CloseRequested`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CloseDecision {
+    /// Nothing unsaved (or the user chose to discard it); close the window
+    Proceed,
+    /// Keep the window open
+    Cancel,
+    /// The user is being asked; wait for `Proceed` or `Cancel` without a timeout
+    Asking,
+}
+
+/// Viewport debug view that replaces the shaded scene.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ViewMode {
+    /// Normal lit rendering
+    #[default]
+    Shaded,
+    /// Linearized greyscale depth (white = near)
+    Depth,
+    /// World-space normals as RGB
+    Normals,
+    /// Raw screen-space ambient occlusion term
+    AmbientOcclusion,
+    /// Procedural checker on the first UV set
+    UvChecker,
+}
+
+/// Severity of a UI console message, mapped from `console.*` calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UiLogLevel {
+    /// `console.debug`
+    Debug,
+    /// `console.log` and `console.info`
+    Info,
+    /// `console.warn`
+    Warn,
+    /// `console.error`
+    Error,
+}
+
+/// Contents of a binary blob announced with `BevyToUi::BinaryBlob`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlobKind {
+    /// RGBA8 preview image of a running diffusion task, row-major
+    DiffusionPreview {
+        task_id: String,
+        width: u32,
+        height: u32,
+    },
+}
+
 /// Configurable lighting settings for the scene.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LightingSettings {
@@ -54,6 +314,17 @@ pub struct LightingSettings {
     /// Atmospheric pollution level (0.0 = clear, 1.0 = heavy pollution)
     /// Affects sky color, haze, and light intensity
     pub pollution: f32,
+    /// Latitude in degrees (-90 to 90) for the sun's path; the noon sun is
+    /// `90 - |latitude|` degrees above the horizon
+    #[serde(default = "default_latitude")]
+    pub latitude: f32,
+    /// Hours of `time_of_day` that pass per real second (0.0 = paused)
+    #[serde(default)]
+    pub animate_speed: f32,
+}
+
+fn default_latitude() -> f32 {
+    45.0
 }
 
 impl Default for LightingSettings {
@@ -81,6 +352,10 @@ impl Default for LightingSettings {
             azimuth_angle: 0.0,
             // Clear atmosphere
             pollution: 0.0,
+            // Mid-latitude sun path
+            latitude: default_latitude(),
+            // Time stands still
+            animate_speed: 0.0,
         }
     }
 }
@@ -122,8 +397,17 @@ pub struct DiffusionRequest {
 }
 
 /// Node graph state for material editing.
+///
+/// Node types: `TextureSample` (data `{ "texture_id" }`, output `color`),
+/// `ColorConstant` (data `{ "color": [r, g, b, a] }`, output `color`),
+/// `ScalarConstant` (data `{ "value" }`, output `value`), `Multiply` (inputs
+/// `a`, `b`, output `result`), `Mix` (inputs `a`, `b`, `factor`, output
+/// `result`), and `Output` (inputs `base_color`, `roughness`, `metallic`,
+/// `emissive`).
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NodeGraphState {
+    /// Material the graph drives (its object's ID)
+    pub material_id: String,
     pub nodes: Vec<NodeInfo>,
     pub connections: Vec<NodeConnection>,
 }
@@ -145,3 +429,10 @@ pub struct NodeConnection {
     pub to_node: String,
     pub to_input: String,
 }
+
+/// One hotkey action and the chord bound to it, e.g. `gizmo.translate` -> `"G"`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub action: String,
+    pub chord: String,
+}
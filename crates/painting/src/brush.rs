@@ -4,8 +4,24 @@
 //! points and generates dabs for painting. This is a placeholder for
 //! future libmypaint FFI integration.
 
+use std::sync::Arc;
+
 use tracing::debug;
 
+use crate::brush_tip::BrushTip;
+
+/// How a stamp tip is rotated for each dab
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TipRotation {
+    /// Always the same angle (degrees, counter-clockwise)
+    Fixed(f32),
+    /// Aligned with the stroke direction
+    #[default]
+    FollowStroke,
+    /// A new random angle per dab
+    Random,
+}
+
 /// Brush preset configuration
 #[derive(Debug, Clone)]
 pub struct BrushPreset {
@@ -21,10 +37,16 @@ pub struct BrushPreset {
     pub max_size: f32,
     /// Hardness: 0.0 = soft, 1.0 = hard
     pub hardness: f32,
-    /// Base opacity 0.0-1.0
+    /// Stroke opacity 0.0-1.0 (cap for the whole stroke, however dabs overlap)
     pub opacity: f32,
+    /// Flow 0.0-1.0 (per-dab opacity; overlapping dabs build up to `opacity`)
+    pub flow: f32,
     /// Spacing as fraction of size (e.g., 0.25 = 25% of diameter)
     pub spacing: f32,
+    /// Stamp tip multiplied into each dab (None = procedural round dab)
+    pub tip: Option<Arc<BrushTip>>,
+    /// Per-dab rotation of the stamp tip
+    pub tip_rotation: TipRotation,
 }
 
 impl Default for BrushPreset {
@@ -37,7 +59,10 @@ impl Default for BrushPreset {
             max_size: 50.0,
             hardness: 0.8,
             opacity: 1.0,
+            flow: 1.0,
             spacing: 0.25,
+            tip: None,
+            tip_rotation: TipRotation::default(),
         }
     }
 }
@@ -74,7 +99,10 @@ impl BrushPreset {
             max_size,
             hardness: hardness.clamp(0.0, 1.0),
             opacity: opacity.clamp(0.0, 1.0),
+            flow: 1.0,
             spacing: spacing.max(0.01), // Prevent zero spacing
+            tip: None,
+            tip_rotation: TipRotation::default(),
         }
     }
 
@@ -96,8 +124,10 @@ pub struct DabOutput {
     pub size: f32,
     /// Hardness 0.0-1.0
     pub hardness: f32,
-    /// Opacity 0.0-1.0
+    /// Dab opacity 0.0-1.0 (the preset's flow)
     pub opacity: f32,
+    /// Stamp tip rotation in radians (counter-clockwise, 0 for round dabs)
+    pub angle: f32,
 }
 
 /// Brush engine that generates dabs from input
@@ -113,6 +143,8 @@ pub struct BrushEngine {
     last_pressure: f32,
     /// Accumulated distance since last dab
     distance_accumulator: f32,
+    /// Xorshift state for random tip rotation
+    rng_state: u32,
 }
 
 impl BrushEngine {
@@ -123,6 +155,7 @@ impl BrushEngine {
             last_pos: None,
             last_pressure: 0.0,
             distance_accumulator: 0.0,
+            rng_state: 0x9e37_79b9,
         }
     }
 
@@ -174,7 +207,8 @@ impl BrushEngine {
                 y,
                 size,
                 hardness: self.preset.hardness,
-                opacity: self.preset.opacity,
+                opacity: self.preset.flow,
+                angle: self.tip_angle(0.0),
             });
 
             return dabs;
@@ -190,9 +224,8 @@ impl BrushEngine {
             return dabs;
         }
 
-        // Direction vector (reserved for future use with angle calculations)
-        let _dir_x = dx / distance;
-        let _dir_y = dy / distance;
+        // Stroke direction, counter-clockwise on screen (surface Y points down)
+        let direction = (-dy).atan2(dx);
 
         // Add distance to accumulator
         self.distance_accumulator += distance;
@@ -235,7 +268,8 @@ impl BrushEngine {
                 y: dab_y,
                 size,
                 hardness: self.preset.hardness,
-                opacity: self.preset.opacity,
+                opacity: self.preset.flow,
+                angle: self.tip_angle(direction),
             });
 
             current_distance = dab_start;
@@ -266,6 +300,23 @@ impl BrushEngine {
         dabs
     }
 
+    /// Rotation for the next dab's stamp tip, given the stroke direction
+    fn tip_angle(&mut self, direction: f32) -> f32 {
+        if self.preset.tip.is_none() {
+            return 0.0;
+        }
+        match self.preset.tip_rotation {
+            TipRotation::Fixed(degrees) => degrees.to_radians(),
+            TipRotation::FollowStroke => direction,
+            TipRotation::Random => {
+                self.rng_state ^= self.rng_state << 13;
+                self.rng_state ^= self.rng_state >> 17;
+                self.rng_state ^= self.rng_state << 5;
+                self.rng_state as f32 / u32::MAX as f32 * std::f32::consts::TAU
+            }
+        }
+    }
+
     /// End the current stroke
     pub fn end_stroke(&mut self) {
         self.last_pos = None;
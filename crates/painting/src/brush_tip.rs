@@ -0,0 +1,370 @@
+//! Stamp brush tips (greyscale alpha masks)
+//!
+//! A [`BrushTip`] replaces the procedural round falloff of a dab with an image:
+//! each dab's footprint is multiplied by the mask, rotated and scaled to the
+//! dab. The mask keeps a mip chain so small dabs sample a pre-filtered level
+//! instead of aliasing. Tips are identified in the stroke log by a content
+//! hash; a replay that cannot find the tip falls back to the round brush.
+//!
+//! Sampling works in tip space (`u`, `v` in -1..1), so the same type can drive
+//! other brushes (e.g. as a displacement alpha for sculpting).
+
+use thiserror::Error;
+use tracing::warn;
+
+use crate::types::{StrokeHeader, content_hash};
+
+/// Names of the tips embedded in the crate, for [`BrushTip::builtin`]
+pub const BUILTIN_TIP_NAMES: &[&str] = &["chalk", "bristles", "square", "splatter"];
+
+/// Largest mask side kept at full resolution; bigger images are box-filtered
+/// down to this first
+const MAX_TIP_SIZE: u32 = 512;
+
+#[derive(Debug, Error)]
+pub enum BrushTipError {
+    #[error("Brush tip is empty ({0}x{1})")]
+    Empty(u32, u32),
+    #[error("Brush tip data has {actual} values, expected {expected}")]
+    DataLength { expected: usize, actual: usize },
+    #[error("Invalid PGM brush tip: {0}")]
+    InvalidPgm(&'static str),
+}
+
+/// One mip level: a square greyscale mask
+#[derive(Clone)]
+struct TipLevel {
+    size: u32,
+    alpha: Vec<f32>,
+}
+
+impl TipLevel {
+    /// Bilinear sample at texel coordinates, transparent outside the mask
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        let x = x - 0.5;
+        let y = y - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+        let texel = |tx: i64, ty: i64| -> f32 {
+            if tx < 0 || ty < 0 || tx >= self.size as i64 || ty >= self.size as i64 {
+                0.0
+            } else {
+                self.alpha[(ty * self.size as i64 + tx) as usize]
+            }
+        };
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1, y0) * fx;
+        let bottom = texel(x0, y0 + 1) * (1.0 - fx) + texel(x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    /// Next smaller level (2x2 box filter)
+    fn downsample(&self) -> TipLevel {
+        let size = (self.size / 2).max(1);
+        let mut alpha = Vec::with_capacity((size * size) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let mut sum = 0.0;
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (x * 2 + dx).min(self.size - 1);
+                    let sy = (y * 2 + dy).min(self.size - 1);
+                    sum += self.alpha[(sy * self.size + sx) as usize];
+                }
+                alpha.push(sum / 4.0);
+            }
+        }
+        TipLevel { size, alpha }
+    }
+}
+
+/// A greyscale stamp mask with its mip chain
+#[derive(Clone)]
+pub struct BrushTip {
+    name: String,
+    hash: u64,
+    /// Mip chain, largest first, down to 1x1
+    levels: Vec<TipLevel>,
+}
+
+impl std::fmt::Debug for BrushTip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BrushTip")
+            .field("name", &self.name)
+            .field("hash", &format_args!("{:016x}", self.hash))
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+impl BrushTip {
+    /// Build a tip from 8-bit alpha values in row-major order (255 = full paint)
+    ///
+    /// Non-square masks are centered in a square so the tip keeps its aspect
+    /// ratio. The hash covers the dimensions and the original values, so the
+    /// same image file hashes the same on every machine.
+    pub fn from_alpha(
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        alpha: &[u8],
+    ) -> Result<Self, BrushTipError> {
+        if width == 0 || height == 0 {
+            return Err(BrushTipError::Empty(width, height));
+        }
+        let expected = width as usize * height as usize;
+        if alpha.len() != expected {
+            return Err(BrushTipError::DataLength {
+                expected,
+                actual: alpha.len(),
+            });
+        }
+
+        let mut hashed = Vec::with_capacity(8 + alpha.len());
+        hashed.extend_from_slice(&width.to_le_bytes());
+        hashed.extend_from_slice(&height.to_le_bytes());
+        hashed.extend_from_slice(alpha);
+        let hash = content_hash(&hashed);
+
+        let size = width.max(height);
+        let mut level = TipLevel {
+            size,
+            alpha: vec![0.0; (size * size) as usize],
+        };
+        let (off_x, off_y) = ((size - width) / 2, (size - height) / 2);
+        for y in 0..height {
+            for x in 0..width {
+                level.alpha[((y + off_y) * size + x + off_x) as usize] =
+                    alpha[(y * width + x) as usize] as f32 / 255.0;
+            }
+        }
+        while level.size > MAX_TIP_SIZE {
+            level = level.downsample();
+        }
+
+        let mut levels = vec![level];
+        while let Some(last) = levels.last().filter(|level| level.size > 1) {
+            let next = last.downsample();
+            levels.push(next);
+        }
+
+        Ok(Self {
+            name: name.into(),
+            hash,
+            levels,
+        })
+    }
+
+    /// Build a tip from a binary greyscale PGM (`P5`, maxval up to 255)
+    pub fn from_pgm(name: impl Into<String>, bytes: &[u8]) -> Result<Self, BrushTipError> {
+        let mut fields = Vec::with_capacity(4);
+        let mut pos = 0;
+        while fields.len() < 4 {
+            while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if bytes.get(pos) == Some(&b'#') {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+                continue;
+            }
+            let start = pos;
+            while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if start == pos {
+                return Err(BrushTipError::InvalidPgm("truncated header"));
+            }
+            fields.push(&bytes[start..pos]);
+        }
+        // Exactly one whitespace byte separates the header from the pixels
+        pos += 1;
+
+        if fields[0] != b"P5" {
+            return Err(BrushTipError::InvalidPgm("not a binary greyscale PGM"));
+        }
+        let number = |field: &[u8]| -> Result<u32, BrushTipError> {
+            std::str::from_utf8(field)
+                .ok()
+                .and_then(|text| text.parse().ok())
+                .ok_or(BrushTipError::InvalidPgm("bad header number"))
+        };
+        let (width, height, maxval) = (number(fields[1])?, number(fields[2])?, number(fields[3])?);
+        if maxval == 0 || maxval > 255 {
+            return Err(BrushTipError::InvalidPgm("only 8-bit PGM is supported"));
+        }
+
+        let pixels = bytes.get(pos..).unwrap_or_default();
+        let alpha: Vec<u8> = pixels
+            .iter()
+            .map(|&value| ((value as u32 * 255) / maxval).min(255) as u8)
+            .collect();
+        Self::from_alpha(name, width, height, &alpha)
+    }
+
+    /// One of the tips embedded in the crate (see [`BUILTIN_TIP_NAMES`])
+    pub fn builtin(name: &str) -> Option<Self> {
+        let bytes: &[u8] = match name {
+            "chalk" => include_bytes!("../assets/tips/chalk.pgm"),
+            "bristles" => include_bytes!("../assets/tips/bristles.pgm"),
+            "square" => include_bytes!("../assets/tips/square.pgm"),
+            "splatter" => include_bytes!("../assets/tips/splatter.pgm"),
+            _ => return None,
+        };
+        Self::from_pgm(name, bytes).ok()
+    }
+
+    /// Tip name (built-in name or file name)
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Content hash referenced by the stroke log (never 0)
+    pub fn hash(&self) -> u64 {
+        self.hash.max(1)
+    }
+
+    /// Side of the full-resolution mask in texels
+    pub fn size(&self) -> u32 {
+        self.levels[0].size
+    }
+
+    /// Mask value at tip-space `(u, v)` (-1..1, +v down) for a dab
+    /// `diameter` pixels across
+    ///
+    /// Samples the smallest mip level that still has a texel per pixel, so
+    /// small dabs are filtered rather than aliased. Outside the tip is 0.
+    pub fn sample(&self, u: f32, v: f32, diameter: f32) -> f32 {
+        if !(-1.0..=1.0).contains(&u) || !(-1.0..=1.0).contains(&v) {
+            return 0.0;
+        }
+        let level = self
+            .levels
+            .iter()
+            .rev()
+            .find(|level| level.size as f32 >= diameter)
+            .unwrap_or(&self.levels[0]);
+        let size = level.size as f32;
+        level.sample((u + 1.0) * 0.5 * size, (v + 1.0) * 0.5 * size)
+    }
+}
+
+/// All tips embedded in the crate
+pub fn builtin_tips() -> Vec<BrushTip> {
+    BUILTIN_TIP_NAMES
+        .iter()
+        .filter_map(|name| BrushTip::builtin(name))
+        .collect()
+}
+
+/// Find the tip a logged stroke was painted with among `available`
+///
+/// Returns None for round strokes and, with a warning, when the tip is not
+/// available on this machine; the stroke then replays with the round brush.
+pub fn tip_for_header<'a>(
+    header: &StrokeHeader,
+    available: &'a [BrushTip],
+) -> Option<&'a BrushTip> {
+    if header.tip_hash == 0 {
+        return None;
+    }
+    let tip = available.iter().find(|tip| tip.hash() == header.tip_hash);
+    if tip.is_none() {
+        warn!(
+            "Stroke {} uses missing brush tip {:016x}; replaying with the round brush",
+            header.stroke_id, header.tip_hash
+        );
+    }
+    tip
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_tips_load_with_mips() {
+        let tips = builtin_tips();
+        assert_eq!(tips.len(), BUILTIN_TIP_NAMES.len());
+        for tip in &tips {
+            assert_eq!(tip.size(), 64);
+            assert_eq!(tip.levels.last().map(|level| level.size), Some(1));
+        }
+        assert!(BrushTip::builtin("missing").is_none());
+    }
+
+    #[test]
+    fn test_tip_sampling_and_hash() {
+        // 4x2 mask: left half opaque, right half clear
+        let alpha = [255, 255, 0, 0, 255, 255, 0, 0];
+        let tip = BrushTip::from_alpha("half", 4, 2, &alpha).unwrap();
+        assert_eq!(tip.size(), 4);
+        assert!(tip.sample(-0.5, 0.0, 4.0) > 0.9);
+        assert!(tip.sample(0.75, 0.0, 4.0) < 0.1);
+        assert_eq!(tip.sample(1.5, 0.0, 4.0), 0.0);
+        // Small dabs read the averaged mip level
+        let small = tip.sample(0.0, 0.0, 1.0);
+        assert!(small > 0.1 && small < 0.9);
+
+        let same = BrushTip::from_alpha("other name", 4, 2, &alpha).unwrap();
+        assert_eq!(tip.hash(), same.hash());
+        assert!(BrushTip::from_alpha("bad", 4, 2, &alpha[..4]).is_err());
+    }
+
+    #[test]
+    fn test_square_tip_stamp_covers_corners() {
+        use crate::tiles::TiledSurface;
+        use crate::types::BlendMode;
+
+        let tip = BrushTip::builtin("square").unwrap();
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let mut surface = TiledSurface::with_default_tile_size(64, 64);
+        surface.apply_dab_stamp(32.0, 32.0, 16.0, red, 1.0, BlendMode::Normal, &tip, 0.0);
+        // The corner of the square lies outside a round dab of the same radius
+        let corner = surface.surface().get_pixel(20, 20).unwrap();
+        assert!(corner[3] > 0.9);
+        assert_eq!(surface.surface().get_pixel(14, 32).unwrap()[3], 0.0);
+
+        // Rotated 45 degrees, the same corner falls outside the diamond
+        let mut rotated = TiledSurface::with_default_tile_size(64, 64);
+        let angle = std::f32::consts::FRAC_PI_4;
+        rotated.apply_dab_stamp(32.0, 32.0, 16.0, red, 1.0, BlendMode::Normal, &tip, angle);
+        assert_eq!(rotated.surface().get_pixel(20, 20).unwrap()[3], 0.0);
+        assert!(rotated.surface().get_pixel(14, 32).unwrap()[3] > 0.9);
+    }
+
+    #[test]
+    fn test_missing_tip_falls_back_to_round() {
+        let tips = builtin_tips();
+        let mut header = StrokeHeader {
+            version: 1,
+            space_kind: crate::types::SpaceKind::CanvasPlane,
+            space_id: 0,
+            stroke_id: 1,
+            timestamp_ms: 0,
+            tool_id: 0,
+            blend_mode: crate::types::BlendMode::Normal,
+            color: [0.0, 0.0, 0.0, 1.0],
+            flags: 0,
+            base_x: 0,
+            base_y: 0,
+            face_id: 0,
+            ptex_tile: 0,
+            pressure_quant: crate::types::Quantization::U8,
+            speed_quant: crate::types::Quantization::U8,
+            tip_hash: 0,
+            opacity: 255,
+            lamport: 0,
+        };
+        assert!(tip_for_header(&header, &tips).is_none());
+        header.tip_hash = tips[0].hash();
+        assert_eq!(
+            tip_for_header(&header, &tips).map(BrushTip::name),
+            Some("chalk")
+        );
+        header.tip_hash = 42;
+        assert!(tip_for_header(&header, &tips).is_none());
+    }
+}
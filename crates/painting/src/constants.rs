@@ -12,3 +12,6 @@ pub const MAX_XY_DELTA: i8 = 127;
 
 /// Default tile size for CPU surface.
 pub const DEFAULT_TILE_SIZE: u32 = 128;
+
+/// Default pixels that UV-atlas mesh paint bleeds into chart margins.
+pub const DEFAULT_SEAM_PADDING: u32 = 4;
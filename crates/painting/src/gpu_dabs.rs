@@ -0,0 +1,623 @@
+//! GPU dab splatting for large canvases
+//!
+//! On a 4096² canvas a big soft brush makes every dab touch hundreds of
+//! thousands of texels, and splatting them into the CPU stroke buffer becomes
+//! the bottleneck. [`GpuDabCompositor`] splats batches of round dabs with a
+//! compute shader instead, into a single-channel coverage buffer that mirrors
+//! the stroke buffer on the GPU.
+//!
+//! With one brush color per stroke the stroke buffer is fully described by
+//! its alpha (color channels are `color * alpha`), so the GPU mirror is a
+//! quarter the size of the CPU surface and a 4096² canvas fits in the default
+//! 128 MiB storage binding limit. The pipeline reads coverage back only for
+//! regions that changed, when the CPU side needs them, and composites it onto
+//! the layer with the same code as the CPU path (see `PaintingPipeline::sync_gpu_stroke`).
+//!
+//! Stamp tips are not splatted on the GPU; strokes with a tip stay on the CPU.
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::mpsc;
+use std::task::{Context, Poll, Waker};
+
+use bytemuck::{Pod, Zeroable};
+use thiserror::Error;
+use tracing::debug;
+
+/// Dabs splatted by one dispatch, at most
+pub const MAX_BATCH_DABS: usize = 256;
+
+/// A batch is flushed before its bounding region grows past this many times
+/// the area of its dabs, so dabs far apart don't make every pixel in between
+/// loop over the whole batch
+const MAX_BATCH_AREA_RATIO: u64 = 4;
+
+/// Shader workgroup size along each axis
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Errors from the GPU dab path. Any of them means painting falls back to the CPU.
+#[derive(Debug, Error)]
+pub enum GpuPaintError {
+    #[error("no GPU adapter available: {0}")]
+    NoAdapter(String),
+
+    #[error("GPU device request failed: {0}")]
+    Device(String),
+
+    #[error("GPU lacks compute support needed for dab splatting ({0})")]
+    Unsupported(&'static str),
+
+    #[error(
+        "canvas {width}x{height} needs {bytes} bytes of coverage, over the device limit of {limit}"
+    )]
+    CanvasTooLarge {
+        width: u32,
+        height: u32,
+        bytes: u64,
+        limit: u64,
+    },
+
+    #[error("GPU dab pipeline setup failed: {0}")]
+    Pipeline(String),
+
+    #[error("reading back stroke coverage failed: {0}")]
+    Readback(String),
+}
+
+/// One dab as the compute shader reads it
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct GpuDab {
+    pub center: [f32; 2],
+    pub radius_major: f32,
+    pub radius_minor: f32,
+    pub cos_a: f32,
+    pub sin_a: f32,
+    pub opacity: f32,
+    pub hardness: f32,
+    /// x_min, y_min, x_max, y_max (exclusive), clamped to the canvas
+    pub bounds: [u32; 4],
+}
+
+impl GpuDab {
+    /// A dab with the same shape and bounding box `TiledSurface::apply_dab_ellipse`
+    /// paints, or `None` where that paints nothing
+    #[allow(clippy::too_many_arguments)]
+    pub fn ellipse(
+        center_x: f32,
+        center_y: f32,
+        radius: f32,
+        opacity: f32,
+        hardness: f32,
+        angle: f32,
+        aspect_ratio: f32,
+        canvas_width: u32,
+        canvas_height: u32,
+    ) -> Option<Self> {
+        if radius <= 0.0 || opacity <= 0.0 || aspect_ratio <= 0.0 {
+            return None;
+        }
+        let aspect_ratio = aspect_ratio.clamp(0.01, 1.0);
+
+        // Same bounding box math as the CPU path, so both touch the same pixels
+        let cos_a = angle.cos();
+        let sin_a = angle.sin();
+        let cos_sq = cos_a * cos_a;
+        let sin_sq = sin_a * sin_a;
+        let radius_major = radius;
+        let radius_minor = radius * aspect_ratio;
+        let r_major_sq = radius_major * radius_major;
+        let r_minor_sq = radius_minor * radius_minor;
+        let half_w = (r_major_sq * cos_sq + r_minor_sq * sin_sq).sqrt();
+        let half_h = (r_major_sq * sin_sq + r_minor_sq * cos_sq).sqrt();
+
+        let x_min = (((center_x - half_w).floor()).max(0.0) as u32).min(canvas_width);
+        let y_min = (((center_y - half_h).floor()).max(0.0) as u32).min(canvas_height);
+        let x_max = (((center_x + half_w).ceil()).max(0.0) as u32).min(canvas_width);
+        let y_max = (((center_y + half_h).ceil()).max(0.0) as u32).min(canvas_height);
+        if x_min >= x_max || y_min >= y_max {
+            return None;
+        }
+
+        Some(Self {
+            center: [center_x, center_y],
+            radius_major,
+            radius_minor,
+            cos_a,
+            sin_a,
+            opacity,
+            hardness,
+            bounds: [x_min, y_min, x_max, y_max],
+        })
+    }
+
+    /// Affected region as (x, y, width, height)
+    pub fn region(&self) -> (u32, u32, u32, u32) {
+        let [x_min, y_min, x_max, y_max] = self.bounds;
+        (x_min, y_min, x_max - x_min, y_max - y_min)
+    }
+
+    fn area(&self) -> u64 {
+        let (_, _, width, height) = self.region();
+        width as u64 * height as u64
+    }
+}
+
+/// Uniforms for one dispatch, matching `Params` in gpu_dabs.wgsl
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct Params {
+    region_x: u32,
+    region_y: u32,
+    region_width: u32,
+    region_height: u32,
+    canvas_width: u32,
+    dab_count: u32,
+    color_alpha: f32,
+    _padding: u32,
+}
+
+/// Dabs waiting to be dispatched together
+#[derive(Default)]
+struct Batch {
+    dabs: Vec<GpuDab>,
+    /// Union of the dab bounds
+    bounds: Option<[u32; 4]>,
+    /// Sum of the dab areas
+    area: u64,
+}
+
+impl Batch {
+    /// Whether adding `dab` would make the batch too large or too sparse
+    fn would_overflow(&self, dab: &GpuDab) -> bool {
+        let Some(bounds) = self.bounds else {
+            return false;
+        };
+        if self.dabs.len() >= MAX_BATCH_DABS {
+            return true;
+        }
+        let union = union_bounds(bounds, dab.bounds);
+        let union_area = (union[2] - union[0]) as u64 * (union[3] - union[1]) as u64;
+        union_area > MAX_BATCH_AREA_RATIO * (self.area + dab.area())
+    }
+
+    fn push(&mut self, dab: GpuDab) {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => union_bounds(bounds, dab.bounds),
+            None => dab.bounds,
+        });
+        self.area += dab.area();
+        self.dabs.push(dab);
+    }
+}
+
+fn union_bounds(a: [u32; 4], b: [u32; 4]) -> [u32; 4] {
+    [
+        a[0].min(b[0]),
+        a[1].min(b[1]),
+        a[2].max(b[2]),
+        a[3].max(b[3]),
+    ]
+}
+
+/// Compute pipeline splatting dabs into a GPU stroke coverage buffer
+pub struct GpuDabCompositor {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    layout: wgpu::BindGroupLayout,
+    splat_pipeline: wgpu::ComputePipeline,
+    gather_pipeline: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    dabs: wgpu::Buffer,
+    /// Stroke coverage, one f32 per canvas pixel
+    coverage: Option<wgpu::Buffer>,
+    canvas_size: (u32, u32),
+    /// Compact copy of a region for readback, and its staging buffer
+    region: wgpu::Buffer,
+    staging: wgpu::Buffer,
+    /// Brush color alpha for this stroke (1.0 when erasing)
+    color_alpha: f32,
+    batch: Batch,
+}
+
+impl GpuDabCompositor {
+    /// Build the dab pipelines on an existing device (Bevy's render device,
+    /// so the canvas shares the GPU with the rest of the scene)
+    pub fn new(device: wgpu::Device, queue: wgpu::Queue) -> Result<Self, GpuPaintError> {
+        let limits = device.limits();
+        if limits.max_storage_buffers_per_shader_stage < 3 {
+            return Err(GpuPaintError::Unsupported("storage buffers"));
+        }
+        if limits.max_compute_workgroup_size_x < WORKGROUP_SIZE
+            || limits.max_compute_workgroup_size_y < WORKGROUP_SIZE
+            || limits.max_compute_invocations_per_workgroup < WORKGROUP_SIZE * WORKGROUP_SIZE
+        {
+            return Err(GpuPaintError::Unsupported("compute workgroups"));
+        }
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("gpu_dabs"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("gpu_dabs.wgsl").into()),
+        });
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("gpu_dabs"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, false),
+                storage(2, true),
+                storage(3, false),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("gpu_dabs"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let splat_pipeline = pipeline("splat");
+        let gather_pipeline = pipeline("gather");
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gpu_dabs_params"),
+            size: std::mem::size_of::<Params>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let dabs = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gpu_dabs_batch"),
+            size: (MAX_BATCH_DABS * std::mem::size_of::<GpuDab>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let (region, staging) = region_buffers(&device, 4);
+
+        if let Some(error) = block_on_ready(device.pop_error_scope()).flatten() {
+            return Err(GpuPaintError::Pipeline(error.to_string()));
+        }
+
+        Ok(Self {
+            device,
+            queue,
+            layout,
+            splat_pipeline,
+            gather_pipeline,
+            params,
+            dabs,
+            coverage: None,
+            canvas_size: (0, 0),
+            region,
+            staging,
+            color_alpha: 1.0,
+            batch: Batch::default(),
+        })
+    }
+
+    /// Create a standalone device for dab splatting (tools and tests without Bevy)
+    pub fn request() -> Result<Self, GpuPaintError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::from_env_or_default());
+        let adapter = block_on_ready(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok_or_else(|| GpuPaintError::NoAdapter("adapter request did not complete".into()))?
+        .map_err(|error| GpuPaintError::NoAdapter(error.to_string()))?;
+
+        let (device, queue) = block_on_ready(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("gpu_dabs"),
+            required_limits: adapter.limits(),
+            ..Default::default()
+        }))
+        .ok_or_else(|| GpuPaintError::Device("device request did not complete".into()))?
+        .map_err(|error| GpuPaintError::Device(error.to_string()))?;
+
+        Self::new(device, queue)
+    }
+
+    /// Start a stroke on a canvas of the given size with empty coverage
+    ///
+    /// `color_alpha` is the brush color's alpha (1.0 when erasing). Fails if
+    /// the canvas doesn't fit the device's storage buffer limits.
+    pub fn begin_stroke(
+        &mut self,
+        width: u32,
+        height: u32,
+        color_alpha: f32,
+    ) -> Result<(), GpuPaintError> {
+        self.batch = Batch::default();
+        self.color_alpha = color_alpha;
+
+        if self.coverage.is_none() || self.canvas_size != (width, height) {
+            let bytes = width as u64 * height as u64 * 4;
+            let limits = self.device.limits();
+            let limit = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+            if bytes == 0 || bytes > limit {
+                self.coverage = None;
+                return Err(GpuPaintError::CanvasTooLarge {
+                    width,
+                    height,
+                    bytes,
+                    limit,
+                });
+            }
+            debug!("Allocating {}x{} GPU stroke coverage", width, height);
+            self.coverage = Some(self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("gpu_dabs_coverage"),
+                size: bytes,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+            self.canvas_size = (width, height);
+        }
+
+        let Some(coverage) = &self.coverage else {
+            return Ok(());
+        };
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("gpu_dabs_clear"),
+            });
+        encoder.clear_buffer(coverage, 0, None);
+        self.queue.submit([encoder.finish()]);
+        Ok(())
+    }
+
+    /// Queue a dab, dispatching the pending batch first if it is full
+    pub fn push(&mut self, dab: GpuDab) {
+        if self.batch.would_overflow(&dab) {
+            self.flush();
+        }
+        self.batch.push(dab);
+    }
+
+    /// Dispatch the pending batch
+    pub fn flush(&mut self) {
+        let Some([x_min, y_min, x_max, y_max]) = self.batch.bounds else {
+            return;
+        };
+        let dab_count = self.batch.dabs.len();
+        self.queue
+            .write_buffer(&self.dabs, 0, bytemuck::cast_slice(&self.batch.dabs));
+        let (width, height) = (x_max - x_min, y_max - y_min);
+        self.dispatch(
+            &self.splat_pipeline,
+            x_min,
+            y_min,
+            width,
+            height,
+            dab_count as u32,
+        );
+        self.batch = Batch::default();
+    }
+
+    /// Stroke coverage of a region, row by row
+    ///
+    /// Dispatches pending dabs first and blocks until the GPU has finished them.
+    pub fn read_region(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<f32>, GpuPaintError> {
+        self.flush();
+        if self.coverage.is_none() {
+            return Err(GpuPaintError::Readback("no stroke in progress".into()));
+        }
+        let bytes = width as u64 * height as u64 * 4;
+        if bytes == 0 {
+            return Ok(Vec::new());
+        }
+        if self.region.size() < bytes {
+            (self.region, self.staging) = region_buffers(&self.device, bytes);
+        }
+
+        self.dispatch(&self.gather_pipeline, x, y, width, height, 0);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("gpu_dabs_readback"),
+            });
+        encoder.copy_buffer_to_buffer(&self.region, 0, &self.staging, 0, bytes);
+        self.queue.submit([encoder.finish()]);
+
+        let slice = self.staging.slice(..bytes);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|error| GpuPaintError::Readback(error.to_string()))?;
+        receiver
+            .recv()
+            .map_err(|error| GpuPaintError::Readback(error.to_string()))?
+            .map_err(|error| GpuPaintError::Readback(error.to_string()))?;
+
+        let coverage = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        self.staging.unmap();
+        Ok(coverage)
+    }
+
+    /// Run one entry point over a region of the canvas in its own submission
+    /// (the uniforms are rewritten for every dispatch)
+    fn dispatch(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        dab_count: u32,
+    ) {
+        let Some(coverage) = &self.coverage else {
+            return;
+        };
+        let params = Params {
+            region_x: x,
+            region_y: y,
+            region_width: width,
+            region_height: height,
+            canvas_width: self.canvas_size.0,
+            dab_count,
+            color_alpha: self.color_alpha,
+            _padding: 0,
+        };
+        self.queue
+            .write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("gpu_dabs"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: coverage.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.dabs.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.region.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("gpu_dabs"),
+            });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("gpu_dabs"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(
+                width.div_ceil(WORKGROUP_SIZE),
+                height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+        self.queue.submit([encoder.finish()]);
+    }
+}
+
+/// Region copy buffer and its mappable staging buffer
+fn region_buffers(device: &wgpu::Device, bytes: u64) -> (wgpu::Buffer, wgpu::Buffer) {
+    let region = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("gpu_dabs_region"),
+        size: bytes,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("gpu_dabs_staging"),
+        size: bytes,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    (region, staging)
+}
+
+/// Poll a future once. wgpu's adapter, device and error scope futures are
+/// ready immediately on native backends.
+fn block_on_ready<F: Future>(future: F) -> Option<F::Output> {
+    let mut future = pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::TiledSurface;
+    use crate::types::BlendMode;
+
+    #[test]
+    fn test_dab_bounds_match_cpu() {
+        let cases = [
+            (100.0, 100.0, 30.0, 0.0, 1.0),
+            (3.5, 7.25, 12.0, 0.0, 1.0),
+            (250.0, 10.0, 40.0, 0.7, 0.4),
+            (-5.0, 128.0, 9.0, 2.0, 0.01),
+        ];
+        for (x, y, radius, angle, aspect) in cases {
+            let mut surface = TiledSurface::with_default_tile_size(256, 256);
+            let cpu = surface.apply_dab_ellipse(
+                x,
+                y,
+                radius,
+                [1.0; 4],
+                1.0,
+                0.5,
+                BlendMode::Normal,
+                angle,
+                aspect,
+            );
+            let gpu = GpuDab::ellipse(x, y, radius, 1.0, 0.5, angle, aspect, 256, 256);
+            assert_eq!(gpu.map(|dab| dab.region()), cpu, "dab at ({x}, {y})");
+        }
+        assert!(GpuDab::ellipse(-100.0, -100.0, 10.0, 1.0, 0.5, 0.0, 1.0, 256, 256).is_none());
+    }
+
+    #[test]
+    fn test_batch_splits_sparse_dabs() {
+        let dab = |x| GpuDab::ellipse(x, 50.0, 10.0, 1.0, 0.5, 0.0, 1.0, 4096, 4096).unwrap();
+        let mut batch = Batch::default();
+        batch.push(dab(50.0));
+        // Overlapping neighbours share a dispatch, a far away dab doesn't
+        assert!(!batch.would_overflow(&dab(60.0)));
+        assert!(batch.would_overflow(&dab(3000.0)));
+    }
+
+    #[test]
+    fn test_dab_layout_matches_shader() {
+        // `Dab` in gpu_dabs.wgsl: eight f32s, then a 16-byte aligned vec4<u32>
+        assert_eq!(std::mem::size_of::<GpuDab>(), 48);
+        assert_eq!(std::mem::size_of::<Params>(), 32);
+    }
+}
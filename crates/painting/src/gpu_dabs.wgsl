@@ -0,0 +1,90 @@
+// Dab splatting into a stroke coverage buffer (see gpu_dabs.rs)
+//
+// `splat` runs one invocation per pixel of the batch's bounding region and
+// applies every dab of the batch to it in order, with the same math as
+// `TiledSurface::apply_dab_ellipse` and `CpuSurface::blend_pixel` alpha.
+// `gather` copies a region out into a compact buffer for readback.
+
+struct Params {
+    region_x: u32,
+    region_y: u32,
+    region_width: u32,
+    region_height: u32,
+    canvas_width: u32,
+    dab_count: u32,
+    // Brush color alpha (1.0 when erasing)
+    color_alpha: f32,
+    _padding: u32,
+}
+
+struct Dab {
+    center: vec2<f32>,
+    radius_major: f32,
+    radius_minor: f32,
+    cos_a: f32,
+    sin_a: f32,
+    opacity: f32,
+    hardness: f32,
+    // x_min, y_min, x_max, y_max (exclusive), clamped to the canvas
+    bounds: vec4<u32>,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> coverage: array<f32>;
+@group(0) @binding(2) var<storage, read> dabs: array<Dab>;
+@group(0) @binding(3) var<storage, read_write> region_out: array<f32>;
+
+fn hardness_falloff(distance_normalized: f32, hardness: f32) -> f32 {
+    if (hardness >= 1.0) {
+        return select(0.0, 1.0, distance_normalized <= 1.0);
+    }
+    let t = clamp(distance_normalized, 0.0, 1.0);
+    let soft = 1.0 - t;
+    let hard = select(0.0, 1.0, t <= 1.0);
+    return soft * (1.0 - hardness) + hard * hardness;
+}
+
+@compute @workgroup_size(8, 8)
+fn splat(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.region_width || id.y >= params.region_height) {
+        return;
+    }
+    let px = params.region_x + id.x;
+    let py = params.region_y + id.y;
+    let index = py * params.canvas_width + px;
+    let x = f32(px) + 0.5;
+    let y = f32(py) + 0.5;
+
+    var alpha = coverage[index];
+    for (var i = 0u; i < params.dab_count; i++) {
+        let dab = dabs[i];
+        if (px < dab.bounds.x || py < dab.bounds.y || px >= dab.bounds.z || py >= dab.bounds.w) {
+            continue;
+        }
+        let dx = x - dab.center.x;
+        let dy = y - dab.center.y;
+        let rotated_x = dx * dab.cos_a + dy * dab.sin_a;
+        let rotated_y = -dx * dab.sin_a + dy * dab.cos_a;
+        let normalized_x = rotated_x / dab.radius_major;
+        let normalized_y = rotated_y / dab.radius_minor;
+        let normalized_dist_sq = normalized_x * normalized_x + normalized_y * normalized_y;
+        if (normalized_dist_sq > 1.0) {
+            continue;
+        }
+        let falloff = hardness_falloff(sqrt(normalized_dist_sq), dab.hardness);
+        if (falloff > 0.0) {
+            let src_alpha = params.color_alpha * (dab.opacity * falloff);
+            alpha = src_alpha + alpha * (1.0 - src_alpha);
+        }
+    }
+    coverage[index] = alpha;
+}
+
+@compute @workgroup_size(8, 8)
+fn gather(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.region_width || id.y >= params.region_height) {
+        return;
+    }
+    let source = (params.region_y + id.y) * params.canvas_width + params.region_x + id.x;
+    region_out[id.y * params.region_width + id.x] = coverage[source];
+}
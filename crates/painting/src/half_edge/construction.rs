@@ -3,7 +3,7 @@
 use bevy::asset::RenderAssetUsages;
 use bevy::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
 use bevy::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use super::HalfEdgeMesh;
 use super::types::{Face, FaceId, HalfEdge, HalfEdgeError, HalfEdgeId, Vertex, VertexId};
@@ -13,6 +13,17 @@ impl HalfEdgeMesh {
     ///
     /// The mesh must have position attributes and triangle indices.
     pub fn from_bevy_mesh(mesh: &Mesh) -> Result<Self, HalfEdgeError> {
+        Self::from_bevy_mesh_with_source_faces(mesh).map(|(mesh, _)| mesh)
+    }
+
+    /// Build a half-edge mesh from a Bevy mesh, also returning the source
+    /// triangle index of every face.
+    ///
+    /// Triangles that collapse when duplicate vertices are welded get no face,
+    /// so face ids and triangle indices of the Bevy mesh can differ.
+    pub fn from_bevy_mesh_with_source_faces(
+        mesh: &Mesh,
+    ) -> Result<(Self, Vec<u32>), HalfEdgeError> {
         // Extract positions
         let positions = mesh
             .attribute(Mesh::ATTRIBUTE_POSITION)
@@ -34,6 +45,14 @@ impl HalfEdgeMesh {
                     _ => None,
                 });
 
+        // Extract vertex colors (optional, white when missing)
+        let colors: Option<Vec<[f32; 4]>> =
+            mesh.attribute(Mesh::ATTRIBUTE_COLOR)
+                .and_then(|attr| match attr {
+                    VertexAttributeValues::Float32x4(v) => Some(v.clone()),
+                    _ => None,
+                });
+
         // Extract indices
         let indices: Vec<u32> = match mesh.indices() {
             Some(Indices::U16(idx)) => idx.iter().map(|&i| i as u32).collect(),
@@ -88,19 +107,26 @@ impl HalfEdgeMesh {
             );
         }
 
+        let seams = uvs
+            .as_ref()
+            .map(|uvs| find_uv_seams(&indices, &canonical_map, uvs))
+            .unwrap_or_default();
+
         let indices: Vec<u32> = indices
             .iter()
             .map(|&i| canonical_map[i as usize] as u32)
             .collect();
 
         // Remove degenerate triangles (two or more identical vertices after welding)
-        let indices: Vec<u32> = indices
-            .chunks(3)
-            .filter(|tri| {
-                tri.len() == 3 && tri[0] != tri[1] && tri[1] != tri[2] && tri[0] != tri[2]
-            })
-            .flat_map(|tri| tri.iter().copied())
-            .collect();
+        let mut kept = Vec::with_capacity(indices.len());
+        let mut source_faces = Vec::with_capacity(indices.len() / 3);
+        for (source_face, tri) in indices.chunks(3).enumerate() {
+            if tri.len() == 3 && tri[0] != tri[1] && tri[1] != tri[2] && tri[0] != tri[2] {
+                kept.extend_from_slice(tri);
+                source_faces.push(source_face as u32);
+            }
+        }
+        let indices = kept;
 
         if indices.len() % 3 != 0 {
             return Err(HalfEdgeError::InvalidTopology(
@@ -109,7 +135,7 @@ impl HalfEdgeMesh {
         }
 
         // Create vertices
-        let mut vertices: Vec<Vertex> = positions
+        let vertices: Vec<Vertex> = positions
             .iter()
             .enumerate()
             .map(|(i, pos)| Vertex {
@@ -119,9 +145,28 @@ impl HalfEdgeMesh {
                 uv: uvs.as_ref().map(|u| Vec2::from_array(u[i])),
                 outgoing_half_edge: None,
                 source_index: i as u32,
+                mask: 0.0,
+                color: colors
+                    .as_ref()
+                    .map_or(Vec4::ONE, |c| Vec4::from_array(c[i])),
             })
             .collect();
 
+        Ok((
+            Self::from_triangles(vertices, &indices, seams),
+            source_faces,
+        ))
+    }
+
+    /// Build the half-edge structure for an indexed triangle list.
+    ///
+    /// `vertices` must be in id order with no outgoing half-edges set yet;
+    /// indices refer to positions in that list.
+    pub(crate) fn from_triangles(
+        mut vertices: Vec<Vertex>,
+        indices: &[u32],
+        seams: HashSet<(VertexId, VertexId)>,
+    ) -> Self {
         let mut half_edges: Vec<HalfEdge> = Vec::new();
         let mut faces: Vec<Face> = Vec::new();
         let mut edge_map: HashMap<(VertexId, VertexId), HalfEdgeId> = HashMap::new();
@@ -211,12 +256,13 @@ impl HalfEdgeMesh {
             });
         }
 
-        Ok(Self {
+        Self {
             vertices,
             half_edges,
             faces,
             edge_map,
-        })
+            seams,
+        }
     }
 
     /// Create a HalfEdgeMesh from raw components.
@@ -234,6 +280,7 @@ impl HalfEdgeMesh {
             half_edges,
             faces,
             edge_map,
+            seams: HashSet::new(),
         }
     }
 
@@ -242,6 +289,7 @@ impl HalfEdgeMesh {
         let mut positions: Vec<[f32; 3]> = Vec::new();
         let mut normals: Vec<[f32; 3]> = Vec::new();
         let mut uvs: Vec<[f32; 2]> = Vec::new();
+        let mut colors: Vec<[f32; 4]> = Vec::new();
         let mut indices: Vec<u32> = Vec::new();
 
         // For each face, emit triangle vertices
@@ -260,6 +308,7 @@ impl HalfEdgeMesh {
                 positions.push(v.position.to_array());
                 normals.push(v.normal.to_array());
                 uvs.push(v.uv.unwrap_or(Vec2::ZERO).to_array());
+                colors.push(v.color.to_array());
             }
 
             // Fan triangulation
@@ -277,7 +326,48 @@ impl HalfEdgeMesh {
         mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
         mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        // Unpainted meshes keep the vertex layout without colors
+        if self.vertices.iter().any(|v| v.color != Vec4::ONE) {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
         mesh.insert_indices(Indices::U32(indices));
         mesh
     }
 }
+
+/// Find welded edges whose triangles disagree on UVs (texture seams).
+///
+/// Returns (lower, higher) canonical vertex id pairs.
+fn find_uv_seams(
+    indices: &[u32],
+    canonical_map: &[usize],
+    uvs: &[[f32; 2]],
+) -> HashSet<(VertexId, VertexId)> {
+    const UV_EPSILON: f32 = 1e-5;
+    let same_uv = |a: [f32; 2], b: [f32; 2]| {
+        (a[0] - b[0]).abs() < UV_EPSILON && (a[1] - b[1]).abs() < UV_EPSILON
+    };
+
+    // UVs at the (lower, higher) end of each edge, as first seen
+    let mut edge_uvs: HashMap<(VertexId, VertexId), ([f32; 2], [f32; 2])> = HashMap::new();
+    let mut seams = HashSet::new();
+    for tri in indices.chunks_exact(3) {
+        for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+            let (a, b) = (a as usize, b as usize);
+            let (ca, cb) = (canonical_map[a], canonical_map[b]);
+            if ca == cb {
+                continue;
+            }
+            let (key, corner_uvs) = if ca < cb {
+                ((VertexId(ca as u32), VertexId(cb as u32)), (uvs[a], uvs[b]))
+            } else {
+                ((VertexId(cb as u32), VertexId(ca as u32)), (uvs[b], uvs[a]))
+            };
+            let first = *edge_uvs.entry(key).or_insert(corner_uvs);
+            if !same_uv(first.0, corner_uvs.0) || !same_uv(first.1, corner_uvs.1) {
+                seams.insert(key);
+            }
+        }
+    }
+    seams
+}
@@ -0,0 +1,331 @@
+//! Edge loop, edge ring, and face region walks for mesh edit selection.
+//!
+//! Meshes are stored as triangles, so the loop and ring walks look at a quad
+//! view of the mesh: two triangles whose shared edge is the longest edge of
+//! both, and whose normals nearly agree, are treated as one quad and their
+//! shared diagonal is skipped.
+
+use bevy::math::Vec3;
+use std::collections::{HashSet, VecDeque};
+
+use super::HalfEdgeMesh;
+use super::types::{FaceId, HalfEdgeId, VertexId};
+
+/// Largest angle between two triangles (degrees) for them to form one quad
+const QUAD_MAX_FOLD_DEGREES: f32 = 20.0;
+
+/// Relative margin by which a diagonal must be longer than the quad sides
+const DIAGONAL_LENGTH_MARGIN: f32 = 1e-4;
+
+/// Maximum sides of a quad-view face or edges around a vertex
+const MAX_FAN: usize = 100;
+
+impl HalfEdgeMesh {
+    /// Select the edge loop through `start`.
+    ///
+    /// Walks straight across vertices with four quad edges in both directions,
+    /// stopping at poles and boundaries. Returns one half-edge per edge (the
+    /// lower id of each twin pair), starting with `start`'s edge.
+    pub fn edge_loop(&self, start: HalfEdgeId) -> Vec<HalfEdgeId> {
+        self.walk_both_ways(start, |mesh, he| mesh.loop_step(he), false)
+    }
+
+    /// Select the edge ring through `start`.
+    ///
+    /// Walks across opposite sides of quads in both directions, stopping at
+    /// boundaries and non-quad faces. Returns one half-edge per edge, like
+    /// [`edge_loop`](Self::edge_loop).
+    pub fn edge_ring(&self, start: HalfEdgeId) -> Vec<HalfEdgeId> {
+        self.walk_both_ways(start, |mesh, he| mesh.ring_step(he), true)
+    }
+
+    /// Select the faces connected to `start` without crossing a boundary or
+    /// a seam edge.
+    pub fn face_region(&self, start: FaceId) -> Vec<FaceId> {
+        if !self.is_face_valid(start) {
+            return Vec::new();
+        }
+
+        let mut region = vec![start];
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(face_id) = queue.pop_front() {
+            for he_id in self.get_face_half_edges(face_id) {
+                if self.is_seam_edge(he_id) {
+                    continue;
+                }
+                let (_, Some(neighbor)) = self.get_edge_faces(he_id) else {
+                    continue;
+                };
+                if self.is_face_valid(neighbor) && visited.insert(neighbor) {
+                    region.push(neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        region
+    }
+
+    /// Check if a half-edge lies on a UV seam of the source mesh
+    pub fn is_seam_edge(&self, he_id: HalfEdgeId) -> bool {
+        self.edge_key(he_id)
+            .is_some_and(|key| self.seams.contains(&key))
+    }
+
+    /// Check if a half-edge is the diagonal of a triangulated quad
+    pub fn is_quad_diagonal(&self, he_id: HalfEdgeId) -> bool {
+        let Some(he) = self.half_edge(he_id) else {
+            return false;
+        };
+        let (Some(face), Some(twin)) = (he.face, he.twin) else {
+            return false;
+        };
+        let Some(twin_face) = self.half_edge(twin).and_then(|t| t.face) else {
+            return false;
+        };
+
+        let (Some(normal), Some(twin_normal)) = (
+            self.longest_edge_normal(face, he_id),
+            self.longest_edge_normal(twin_face, twin),
+        ) else {
+            return false;
+        };
+        normal.dot(twin_normal) >= QUAD_MAX_FOLD_DEGREES.to_radians().cos()
+    }
+
+    /// Normal of a triangle whose strictly longest edge is `he_id`
+    fn longest_edge_normal(&self, face_id: FaceId, he_id: HalfEdgeId) -> Option<Vec3> {
+        let edges = self.get_face_half_edges(face_id);
+        if edges.len() != 3 {
+            return None;
+        }
+        let length = self.edge_length(he_id)?;
+        for other in edges.iter().filter(|e| **e != he_id) {
+            if length <= self.edge_length(*other)? * (1.0 + DIAGONAL_LENGTH_MARGIN) {
+                return None;
+            }
+        }
+
+        let verts = self.get_face_vertices(face_id);
+        let p0 = self.vertex(verts[0])?.position;
+        let p1 = self.vertex(verts[1])?.position;
+        let p2 = self.vertex(verts[2])?.position;
+        Some((p1 - p0).cross(p2 - p0).normalize_or_zero())
+    }
+
+    fn edge_length(&self, he_id: HalfEdgeId) -> Option<f32> {
+        let origin = self.vertex(self.half_edge(he_id)?.origin)?.position;
+        let dest = self.vertex(self.get_half_edge_dest(he_id)?)?.position;
+        Some(origin.distance(dest))
+    }
+
+    /// Undirected key of an edge: its endpoints in ascending order
+    pub(crate) fn edge_key(&self, he_id: HalfEdgeId) -> Option<(VertexId, VertexId)> {
+        let origin = self.half_edge(he_id)?.origin;
+        let dest = self.get_half_edge_dest(he_id)?;
+        Some(if origin.0 <= dest.0 {
+            (origin, dest)
+        } else {
+            (dest, origin)
+        })
+    }
+
+    /// The lower id of a half-edge and its twin
+    fn canonical_half_edge(&self, he_id: HalfEdgeId) -> HalfEdgeId {
+        match self.half_edge(he_id).and_then(|he| he.twin) {
+            Some(twin) if twin.0 < he_id.0 => twin,
+            _ => he_id,
+        }
+    }
+
+    /// Next half-edge around the quad-view face, stepping over diagonals
+    fn quad_next(&self, he_id: HalfEdgeId) -> Option<HalfEdgeId> {
+        let mut next = self.half_edge(he_id)?.next;
+        for _ in 0..MAX_FAN {
+            if !self.is_quad_diagonal(next) {
+                return Some(next);
+            }
+            let twin = self.half_edge(next)?.twin?;
+            next = self.half_edge(twin)?.next;
+        }
+        None
+    }
+
+    /// Number of sides of the quad-view face containing `he_id`
+    fn quad_face_sides(&self, he_id: HalfEdgeId) -> Option<usize> {
+        let mut current = he_id;
+        for sides in 1..=MAX_FAN {
+            current = self.quad_next(current)?;
+            if current == he_id {
+                return Some(sides);
+            }
+        }
+        None
+    }
+
+    /// Number of quad-view edges at an interior vertex (`None` on a boundary)
+    fn quad_valence(&self, vertex_id: VertexId) -> Option<usize> {
+        let start = self.vertex(vertex_id)?.outgoing_half_edge?;
+        let mut current = start;
+        let mut valence = 0;
+        for _ in 0..MAX_FAN {
+            let he = self.half_edge(current)?;
+            if !self.is_quad_diagonal(current) {
+                valence += 1;
+            }
+            current = self.half_edge(he.prev)?.twin?;
+            if current == start {
+                return Some(valence);
+            }
+        }
+        None
+    }
+
+    /// Continue an edge loop straight across the destination vertex
+    fn loop_step(&self, he_id: HalfEdgeId) -> Option<HalfEdgeId> {
+        let dest = self.get_half_edge_dest(he_id)?;
+        if self.quad_valence(dest)? != 4 {
+            return None;
+        }
+        let side = self.quad_next(he_id)?;
+        let across = self.half_edge(side)?.twin?;
+        self.quad_next(across)
+    }
+
+    /// Continue an edge ring to the opposite side of the quad
+    fn ring_step(&self, he_id: HalfEdgeId) -> Option<HalfEdgeId> {
+        if self.quad_face_sides(he_id)? != 4 {
+            return None;
+        }
+        self.quad_next(self.quad_next(he_id)?)
+    }
+
+    /// Walk `step` forward from `start`, then backward from its twin unless
+    /// the walk closed on itself. With `cross_faces` each step continues from
+    /// the twin of the edge it found (ring walks hop from face to face).
+    fn walk_both_ways(
+        &self,
+        start: HalfEdgeId,
+        step: impl Fn(&Self, HalfEdgeId) -> Option<HalfEdgeId>,
+        cross_faces: bool,
+    ) -> Vec<HalfEdgeId> {
+        let Some(start_key) = self.edge_key(start) else {
+            return Vec::new();
+        };
+        let mut edges = vec![self.canonical_half_edge(start)];
+        // A quad diagonal is not part of any loop or ring
+        if self.is_quad_diagonal(start) {
+            return edges;
+        }
+        let mut visited = HashSet::from([start_key]);
+
+        let mut walk = |from: HalfEdgeId, edges: &mut Vec<HalfEdgeId>| -> bool {
+            let mut current = from;
+            while let Some(next) = step(self, current) {
+                let Some(key) = self.edge_key(next) else {
+                    break;
+                };
+                if key == start_key {
+                    return true;
+                }
+                if !visited.insert(key) {
+                    break;
+                }
+                edges.push(self.canonical_half_edge(next));
+                current = if cross_faces {
+                    match self.half_edge(next).and_then(|he| he.twin) {
+                        Some(twin) => twin,
+                        None => break,
+                    }
+                } else {
+                    next
+                };
+            }
+            false
+        };
+
+        let closed = walk(start, &mut edges);
+        if !closed && let Some(twin) = self.half_edge(start).and_then(|he| he.twin) {
+            walk(twin, &mut edges);
+        }
+        edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::*;
+
+    /// Find the half-edge between the vertices closest to two positions
+    fn edge_between(mesh: &HalfEdgeMesh, a: Vec3, b: Vec3) -> HalfEdgeId {
+        let closest = |p: Vec3| {
+            mesh.half_edges()
+                .iter()
+                .map(|he| he.origin)
+                .min_by(|x, y| {
+                    let dx = mesh.vertex(*x).unwrap().position.distance(p);
+                    let dy = mesh.vertex(*y).unwrap().position.distance(p);
+                    dx.total_cmp(&dy)
+                })
+                .unwrap()
+        };
+        mesh.find_half_edge(closest(a), closest(b))
+            .or_else(|| mesh.find_half_edge(closest(b), closest(a)))
+            .expect("edge exists")
+    }
+
+    #[test]
+    fn test_grid_loops_and_rings() {
+        // 4x4 quads on a 2x2 plane, vertices every 0.5
+        let plane = Plane3d::default().mesh().size(2.0, 2.0).subdivisions(3);
+        let mesh = HalfEdgeMesh::from_bevy_mesh(&Mesh::from(plane)).unwrap();
+
+        // Interior edge along X: the loop runs boundary to boundary
+        let edge = edge_between(&mesh, Vec3::new(-0.5, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(mesh.edge_loop(edge).len(), 4);
+        // Its ring crosses the four quads along Z
+        assert_eq!(mesh.edge_ring(edge).len(), 5);
+
+        // A boundary edge stops at its boundary vertices
+        let border = edge_between(
+            &mesh,
+            Vec3::new(-1.0, 0.0, -1.0),
+            Vec3::new(-0.5, 0.0, -1.0),
+        );
+        assert_eq!(
+            mesh.edge_loop(border),
+            vec![mesh.canonical_half_edge(border)]
+        );
+
+        // The whole grid is one region
+        assert_eq!(mesh.face_region(FaceId(0)).len(), mesh.face_count());
+    }
+
+    #[test]
+    fn test_cylinder_loops_rings_and_regions() {
+        // 16 sides, 3 segments, triangle fan caps
+        let cylinder = Cylinder::new(1.0, 3.0).mesh().resolution(16).segments(3);
+        let mesh = HalfEdgeMesh::from_bevy_mesh(&Mesh::from(cylinder)).unwrap();
+        let angle = std::f32::consts::TAU / 16.0;
+        let around = |i: f32, y: f32| Vec3::new((angle * i).cos(), y, (angle * i).sin());
+
+        // Horizontal loops close around the side
+        let middle = edge_between(&mesh, around(2.0, 0.5), around(3.0, 0.5));
+        let middle_loop = mesh.edge_loop(middle);
+        assert_eq!(middle_loop.len(), 16);
+        assert_eq!(mesh.edge_loop(middle_loop[7]).len(), 16);
+
+        // A vertical loop crosses the rims and stops at the cap centers (poles)
+        let vertical = edge_between(&mesh, around(2.0, -0.5), around(2.0, 0.5));
+        assert_eq!(mesh.edge_loop(vertical).len(), 5);
+
+        // A vertical edge's ring goes around; a horizontal one stops at the caps
+        assert_eq!(mesh.edge_ring(vertical).len(), 16);
+        assert_eq!(mesh.edge_ring(middle).len(), 4);
+
+        // UV seams split the side from the caps
+        let side_face = mesh.get_edge_faces(vertical).0.unwrap();
+        assert_eq!(mesh.face_region(side_face).len(), 16 * 3 * 2);
+    }
+}
@@ -4,14 +4,19 @@
 //! that is not available in a simple triangle soup representation.
 
 mod construction;
+mod loops;
 mod modification;
+mod ops;
+mod subdivision;
 mod topology;
 mod types;
 mod validation;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub use modification::CompactionMap;
+pub use ops::FaceOffset;
+pub use subdivision::{loop_face_count, subdivide_loop};
 pub use types::{Face, FaceId, HalfEdge, HalfEdgeError, HalfEdgeId, Vertex, VertexId};
 pub use validation::ManifoldError;
 
@@ -29,6 +34,8 @@ pub struct HalfEdgeMesh {
     pub(crate) faces: Vec<FaceInternal>,
     /// Map from (origin, destination) vertex pair to half-edge
     pub(crate) edge_map: HashMap<(VertexId, VertexId), HalfEdgeId>,
+    /// UV seam edges of the source mesh, keyed by (lower, higher) vertex id
+    pub(crate) seams: HashSet<(VertexId, VertexId)>,
 }
 
 #[cfg(test)]
@@ -83,4 +90,39 @@ mod tests {
         assert_eq!(faces.len(), 1);
         assert_eq!(faces[0], FaceId(0));
     }
+
+    #[test]
+    fn test_vertex_colors_round_trip_and_interpolate() {
+        let mut mesh = create_test_triangle_mesh();
+        let plain = HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap();
+        assert!(plain.vertices().iter().all(|v| v.color == Vec4::ONE));
+        assert!(
+            plain
+                .to_bevy_mesh()
+                .attribute(Mesh::ATTRIBUTE_COLOR)
+                .is_none()
+        );
+
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_COLOR,
+            vec![
+                [1.0, 0.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0, 1.0],
+                [1.0, 1.0, 1.0, 1.0],
+            ],
+        );
+        let mut he_mesh = HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap();
+        assert!(
+            he_mesh
+                .to_bevy_mesh()
+                .attribute(Mesh::ATTRIBUTE_COLOR)
+                .is_some()
+        );
+
+        // A split vertex blends the colors of the edge's ends
+        let edge = he_mesh.find_half_edge(VertexId(0), VertexId(1)).unwrap();
+        let (mid, _) = he_mesh.split_edge_topology(edge).unwrap();
+        let color = he_mesh.vertex(mid).unwrap().color;
+        assert_eq!(color, Vec4::new(0.5, 0.0, 0.5, 1.0));
+    }
 }
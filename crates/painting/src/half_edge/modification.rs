@@ -30,6 +30,42 @@ impl HalfEdgeMesh {
         }
     }
 
+    /// Set the sculpt mask of a vertex (clamped to 0.0-1.0)
+    pub fn set_vertex_mask(&mut self, vertex_id: VertexId, mask: f32) {
+        if let Some(v) = self.vertex_mut(vertex_id) {
+            v.mask = mask.clamp(0.0, 1.0);
+        }
+    }
+
+    /// Set the painted color of a vertex
+    pub fn set_vertex_color(&mut self, vertex_id: VertexId, color: Vec4) {
+        if let Some(v) = self.vertex_mut(vertex_id) {
+            v.color = color;
+        }
+    }
+
+    /// Remove a face, leaving it for the next `compact()` to drop.
+    ///
+    /// Disconnects the face's half-edges the same way edge collapse removes
+    /// degenerate faces. Vertices only this face used become orphans.
+    pub fn detach_face(&mut self, face_id: FaceId) {
+        for he_id in self.get_face_half_edges(face_id) {
+            if let Some(twin) = self.half_edges[he_id.0 as usize].twin
+                && let Some(twin_he) = self.half_edges.get_mut(twin.0 as usize)
+            {
+                twin_he.twin = None;
+            }
+            if let Some(dest) = self.get_half_edge_dest(he_id) {
+                let origin = self.half_edges[he_id.0 as usize].origin;
+                if self.edge_map.get(&(origin, dest)) == Some(&he_id) {
+                    self.edge_map.remove(&(origin, dest));
+                }
+            }
+            self.half_edges[he_id.0 as usize].face = None;
+            self.half_edges[he_id.0 as usize].twin = None;
+        }
+    }
+
     /// Flip an edge by swapping the diagonal of the two adjacent triangles.
     ///
     /// For an edge AB shared by triangles ABC and ABD, flipping creates
@@ -291,6 +327,8 @@ impl HalfEdgeMesh {
             uv,
             outgoing_half_edge: None,
             source_index: u32::MAX, // New vertex has no source
+            mask: 0.0,
+            color: Vec4::ONE,
         });
         id
     }
@@ -350,6 +388,8 @@ impl HalfEdgeMesh {
             (Some(uv0), Some(uv1)) => Some((uv0 + uv1) * 0.5),
             _ => None,
         };
+        let mid_mask = (v0.mask + v1.mask) * 0.5;
+        let mid_color = (v0.color + v1.color) * 0.5;
 
         let face_normal = self.faces[face_id.0 as usize].normal;
 
@@ -357,6 +397,8 @@ impl HalfEdgeMesh {
 
         // Create the new midpoint vertex
         let mid_id = self.add_vertex(mid_pos, mid_normal, mid_uv);
+        self.vertices[mid_id.0 as usize].mask = mid_mask;
+        self.vertices[mid_id.0 as usize].color = mid_color;
 
         // Pre-calculate all new IDs before pushing anything
         let base_he_id = self.half_edges.len() as u32;
@@ -1107,6 +1149,8 @@ impl HalfEdgeMesh {
                         .outgoing_half_edge
                         .and_then(|he| half_edge_map.get(&he).copied()),
                     source_index: v.source_index,
+                    mask: v.mask,
+                    color: v.color,
                 });
             }
         }
@@ -1175,6 +1219,14 @@ impl HalfEdgeMesh {
         self.half_edges = new_half_edges;
         self.faces = new_faces;
         self.edge_map = new_edge_map;
+        self.seams = self
+            .seams
+            .iter()
+            .filter_map(|(a, b)| {
+                let (a, b) = (*vertex_map.get(a)?, *vertex_map.get(b)?);
+                Some(if a.0 <= b.0 { (a, b) } else { (b, a) })
+            })
+            .collect();
 
         // Phase 5: Fix outgoing_half_edge for all vertices.
         // With reachability-based liveness, a vertex may be live (referenced by live
@@ -1271,6 +1323,8 @@ mod tests {
                 uv: None,
                 outgoing_half_edge: Some(HalfEdgeId(0)),
                 source_index: 0,
+                mask: 0.0,
+                color: Vec4::ONE,
             },
             Vertex {
                 id: VertexId(1),
@@ -1279,6 +1333,8 @@ mod tests {
                 uv: None,
                 outgoing_half_edge: Some(HalfEdgeId(1)),
                 source_index: 1,
+                mask: 0.0,
+                color: Vec4::ONE,
             },
             Vertex {
                 id: VertexId(2),
@@ -1287,6 +1343,8 @@ mod tests {
                 uv: None,
                 outgoing_half_edge: Some(HalfEdgeId(2)),
                 source_index: 2,
+                mask: 0.0,
+                color: Vec4::ONE,
             },
             Vertex {
                 id: VertexId(3),
@@ -1295,6 +1353,8 @@ mod tests {
                 uv: None,
                 outgoing_half_edge: Some(HalfEdgeId(5)),
                 source_index: 3,
+                mask: 0.0,
+                color: Vec4::ONE,
             },
         ];
 
@@ -1562,4 +1622,21 @@ mod tests {
         // Vertex map should only contain live vertices
         assert_eq!(compaction.vertex_map.len(), mesh.vertex_count());
     }
+
+    #[test]
+    fn test_detach_face_removed_by_compact() {
+        let mut mesh = create_bowtie_mesh();
+
+        mesh.detach_face(FaceId(1));
+        assert!(!mesh.is_face_valid(FaceId(1)));
+        // The shared edge lost its twin
+        assert!(mesh.half_edges().iter().all(|he| he.twin.is_none()));
+
+        mesh.compact();
+
+        // Only (v0, v1, v2) is left; v3 was used by the removed face alone
+        assert_eq!(mesh.face_count(), 1);
+        assert_eq!(mesh.vertex_count(), 3);
+        assert_eq!(mesh.get_face_vertices(FaceId(0)).len(), 3);
+    }
 }
@@ -0,0 +1,635 @@
+//! Modeling operations on face selections: extrude and inset.
+//!
+//! Both operations share the same topology change. The boundary vertices of
+//! the selected region are duplicated, the region's faces move onto the
+//! copies, and a strip of quads (two triangles each) is built between the
+//! old and new boundary. They differ only in where the new vertices go:
+//! extrude pushes the region along its average normal, and inset pulls the
+//! copied boundary inward in the plane of the faces.
+//!
+//! Existing face IDs keep pointing at the selected faces, so a face
+//! selection stays valid across either operation.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use super::HalfEdgeMesh;
+use super::types::{Face, FaceId, HalfEdge, HalfEdgeError, HalfEdgeId, VertexId};
+
+/// Smallest cosine between a boundary corner's miter and its edge normal,
+/// so sharp corners don't shoot vertices far away when insetting
+const MIN_MITER_COS: f32 = 0.25;
+
+/// Vertices moved by an extrude or inset, and how they follow the amount.
+///
+/// Returned by [`HalfEdgeMesh::extrude_faces`] and
+/// [`HalfEdgeMesh::inset_faces`] so the amount can be changed interactively
+/// without redoing the topology.
+#[derive(Debug, Clone, Default)]
+pub struct FaceOffset {
+    /// (vertex, position at amount zero, movement per unit of amount)
+    moves: Vec<(VertexId, Vec3, Vec3)>,
+    /// Side wall or inset border faces the operation created
+    pub new_faces: Vec<FaceId>,
+}
+
+impl FaceOffset {
+    /// Move the operation's vertices to `amount` and refresh normals
+    pub fn apply(&self, mesh: &mut HalfEdgeMesh, amount: f32) {
+        for (vertex, base, direction) in &self.moves {
+            mesh.set_vertex_position(*vertex, *base + *direction * amount);
+        }
+        mesh.recalculate_face_normals();
+        mesh.recalculate_vertex_normals();
+    }
+
+    /// Average movement direction (the extrusion axis for an extrude)
+    pub fn direction(&self) -> Vec3 {
+        self.moves
+            .iter()
+            .map(|(_, _, direction)| *direction)
+            .sum::<Vec3>()
+            .normalize_or_zero()
+    }
+}
+
+/// New boundary created by splitting a region off its surroundings
+struct RegionSplit {
+    /// Region boundary half-edges, now on the duplicated vertices
+    boundary: Vec<HalfEdgeId>,
+    /// Original vertex -> duplicate used by the region
+    duplicates: HashMap<VertexId, VertexId>,
+    /// Wall triangles between the old and new boundary
+    walls: Vec<FaceId>,
+}
+
+impl HalfEdgeMesh {
+    /// Extrude the selected faces as one region, moving them `offset` along
+    /// their average normal.
+    ///
+    /// Side walls are built along every edge of the region's boundary,
+    /// including edges on the mesh boundary. The mesh is left unchanged when
+    /// the operation fails.
+    pub fn extrude_faces(
+        &mut self,
+        faces: &[FaceId],
+        offset: f32,
+    ) -> Result<FaceOffset, HalfEdgeError> {
+        let region = self.live_region(faces)?;
+        let normal = region
+            .iter()
+            .map(|face| self.unnormalized_face_normal(*face))
+            .sum::<Vec3>()
+            .normalize_or_zero();
+        if normal == Vec3::ZERO {
+            return Err(HalfEdgeError::InvalidTopology(
+                "Selected faces have no average normal".into(),
+            ));
+        }
+
+        let mut mesh = self.clone();
+        let split = mesh.split_region(&region.iter().copied().collect())?;
+
+        // Every vertex of the region moves: the copies on its boundary and
+        // the interior vertices only the region uses
+        let mut moved: HashSet<VertexId> = split.duplicates.values().copied().collect();
+        for face in &region {
+            moved.extend(mesh.get_face_vertices(*face));
+        }
+        let mut moves: Vec<(VertexId, Vec3, Vec3)> = moved
+            .into_iter()
+            .map(|v| (v, mesh.vertices[v.0 as usize].position, normal))
+            .collect();
+        moves.sort_by_key(|(v, ..)| v.0);
+
+        let result = FaceOffset {
+            moves,
+            new_faces: split.walls,
+        };
+        result.apply(&mut mesh, offset);
+        mesh.validate()?;
+        mesh.validate_connectivity()
+            .map_err(HalfEdgeError::InvalidTopology)?;
+        *self = mesh;
+        Ok(result)
+    }
+
+    /// Inset the selected faces by `thickness`, leaving a border of new
+    /// faces around a smaller copy.
+    ///
+    /// With `individual` each face is inset on its own; otherwise connected
+    /// faces are inset together as one region. The mesh is left unchanged
+    /// when the operation fails.
+    pub fn inset_faces(
+        &mut self,
+        faces: &[FaceId],
+        thickness: f32,
+        individual: bool,
+    ) -> Result<FaceOffset, HalfEdgeError> {
+        let region = self.live_region(faces)?;
+        let regions: Vec<Vec<FaceId>> = if individual {
+            region.iter().map(|face| vec![*face]).collect()
+        } else {
+            self.connected_regions(&region)
+        };
+
+        let mut mesh = self.clone();
+        let mut result = FaceOffset::default();
+        for faces in regions {
+            let split = mesh.split_region(&faces.iter().copied().collect())?;
+
+            // Inward direction in the face plane of each boundary edge, keyed
+            // by the (duplicated) vertices at its ends
+            let mut incoming: HashMap<VertexId, Vec3> = HashMap::new();
+            let mut outgoing: HashMap<VertexId, Vec3> = HashMap::new();
+            for he_id in &split.boundary {
+                let he = &mesh.half_edges[he_id.0 as usize];
+                let (origin, dest) = (he.origin, mesh.half_edges[he.next.0 as usize].origin);
+                let normal = he
+                    .face
+                    .map(|face| mesh.unnormalized_face_normal(face))
+                    .unwrap_or_default();
+                let along = mesh.vertices[dest.0 as usize].position
+                    - mesh.vertices[origin.0 as usize].position;
+                let inward = normal.cross(along).normalize_or_zero();
+                outgoing.insert(origin, inward);
+                incoming.insert(dest, inward);
+            }
+
+            for duplicate in split.duplicates.values() {
+                let (Some(after), Some(before)) =
+                    (outgoing.get(duplicate), incoming.get(duplicate))
+                else {
+                    continue;
+                };
+                let miter = (*after + *before).normalize_or(*after);
+                let scale = 1.0 / miter.dot(*after).max(MIN_MITER_COS);
+                let base = mesh.vertices[duplicate.0 as usize].position;
+                result.moves.push((*duplicate, base, miter * scale));
+            }
+            result.new_faces.extend(split.walls);
+        }
+
+        result.moves.sort_by_key(|(v, ..)| v.0);
+        result.apply(&mut mesh, thickness);
+        mesh.validate()?;
+        mesh.validate_connectivity()
+            .map_err(HalfEdgeError::InvalidTopology)?;
+        *self = mesh;
+        Ok(result)
+    }
+
+    /// Deduplicated live faces of a selection, or an error if it is empty
+    fn live_region(&self, faces: &[FaceId]) -> Result<Vec<FaceId>, HalfEdgeError> {
+        let mut seen = HashSet::new();
+        let region: Vec<FaceId> = faces
+            .iter()
+            .copied()
+            .filter(|face| self.is_face_valid(*face) && seen.insert(*face))
+            .collect();
+        if region.is_empty() {
+            return Err(HalfEdgeError::InvalidTopology("No faces selected".into()));
+        }
+        Ok(region)
+    }
+
+    /// Split faces into groups connected across shared edges
+    fn connected_regions(&self, faces: &[FaceId]) -> Vec<Vec<FaceId>> {
+        let selected: HashSet<FaceId> = faces.iter().copied().collect();
+        let mut visited = HashSet::new();
+        let mut regions = Vec::new();
+        for start in faces {
+            if !visited.insert(*start) {
+                continue;
+            }
+            let mut region = vec![*start];
+            let mut i = 0;
+            while i < region.len() {
+                for he_id in self.get_face_half_edges(region[i]) {
+                    let neighbor = self.half_edges[he_id.0 as usize]
+                        .twin
+                        .and_then(|twin| self.half_edges[twin.0 as usize].face);
+                    if let Some(neighbor) = neighbor
+                        && selected.contains(&neighbor)
+                        && visited.insert(neighbor)
+                    {
+                        region.push(neighbor);
+                    }
+                }
+                i += 1;
+            }
+            regions.push(region);
+        }
+        regions
+    }
+
+    /// Area-weighted normal of a face (cross product of its first corner)
+    fn unnormalized_face_normal(&self, face: FaceId) -> Vec3 {
+        let verts = self.get_face_vertices(face);
+        if verts.len() < 3 {
+            return Vec3::ZERO;
+        }
+        let p0 = self.vertices[verts[0].0 as usize].position;
+        let p1 = self.vertices[verts[1].0 as usize].position;
+        let p2 = self.vertices[verts[2].0 as usize].position;
+        (p1 - p0).cross(p2 - p0)
+    }
+
+    /// Detach a face region along its boundary and join it back with walls.
+    ///
+    /// ```text
+    ///   a'------b'     region side (duplicated vertices)
+    ///   | \     |
+    ///   |   \   |      wall: (a, b, b') and (a, b', a')
+    ///   |     \ |
+    ///   a------b       outside (original vertices)
+    /// ```
+    ///
+    /// Each boundary edge a->b of the region becomes a'->b' and gets a wall
+    /// quad. Positions are unchanged, so the walls start out flat.
+    fn split_region(&mut self, region: &HashSet<FaceId>) -> Result<RegionSplit, HalfEdgeError> {
+        // ===== PHASE 1: GATHER (read-only, fail early) =====
+        let region_hes: Vec<HalfEdgeId> = region
+            .iter()
+            .flat_map(|face| self.get_face_half_edges(*face))
+            .collect();
+        let boundary: Vec<HalfEdgeId> = region_hes
+            .iter()
+            .copied()
+            .filter(|he_id| {
+                self.half_edges[he_id.0 as usize]
+                    .twin
+                    .and_then(|twin| self.half_edges[twin.0 as usize].face)
+                    .is_none_or(|face| !region.contains(&face))
+            })
+            .collect();
+        if boundary.is_empty() {
+            return Err(HalfEdgeError::InvalidTopology(
+                "Selected faces have no boundary".into(),
+            ));
+        }
+
+        // A vertex starting two boundary edges is a pinch point; one copy
+        // would join both sides into a non-manifold vertex
+        let mut boundary_by_origin: HashMap<VertexId, HalfEdgeId> = HashMap::new();
+        for he_id in &boundary {
+            let origin = self.half_edges[he_id.0 as usize].origin;
+            if boundary_by_origin.insert(origin, *he_id).is_some() {
+                return Err(HalfEdgeError::NonManifoldEdge);
+            }
+        }
+
+        let boundary_ends: Vec<(VertexId, VertexId)> = boundary
+            .iter()
+            .map(|he_id| self.half_edge_ends(*he_id))
+            .collect();
+        let ends: Vec<(VertexId, VertexId)> = region_hes
+            .iter()
+            .map(|he_id| self.half_edge_ends(*he_id))
+            .collect();
+
+        // ===== PHASE 2: DUPLICATE BOUNDARY VERTICES =====
+        let mut duplicates: HashMap<VertexId, VertexId> = HashMap::new();
+        let mut origins: Vec<VertexId> = boundary_by_origin.keys().copied().collect();
+        origins.sort_by_key(|v| v.0);
+        for v in origins {
+            let source = &self.vertices[v.0 as usize];
+            let (position, normal, uv, mask, color) = (
+                source.position,
+                source.normal,
+                source.uv,
+                source.mask,
+                source.color,
+            );
+            let copy = self.add_vertex(position, normal, uv);
+            self.vertices[copy.0 as usize].mask = mask;
+            self.vertices[copy.0 as usize].color = color;
+            duplicates.insert(v, copy);
+        }
+        let remap = |v: VertexId| duplicates.get(&v).copied().unwrap_or(v);
+
+        // ===== PHASE 3: MOVE REGION HALF-EDGES ONTO THE COPIES =====
+        for (he_id, (origin, dest)) in region_hes.iter().zip(&ends) {
+            if self.edge_map.get(&(*origin, *dest)) == Some(he_id) {
+                self.edge_map.remove(&(*origin, *dest));
+            }
+            self.half_edges[he_id.0 as usize].origin = remap(*origin);
+        }
+        for (he_id, (origin, dest)) in region_hes.iter().zip(&ends) {
+            self.edge_map.insert((remap(*origin), remap(*dest)), *he_id);
+        }
+
+        // ===== PHASE 4: BUILD WALLS =====
+        // Per boundary origin a: (wall a->b, wall b->b', wall a'->a, b)
+        let mut wall_edges: HashMap<VertexId, (HalfEdgeId, HalfEdgeId, HalfEdgeId, VertexId)> =
+            HashMap::new();
+        let mut walls = Vec::with_capacity(boundary.len() * 2);
+        for (he_id, (a, b)) in boundary.iter().zip(&boundary_ends) {
+            let (a, b) = (*a, *b);
+            let (a2, b2) = (remap(a), remap(b));
+            let outside_twin = self.half_edges[he_id.0 as usize].twin;
+
+            let base = self.half_edges.len() as u32;
+            let ids: [HalfEdgeId; 6] = std::array::from_fn(|i| HalfEdgeId(base + i as u32));
+            let [ab, bb2, b2a, ab2, b2a2, a2a] = ids;
+            let tri0 = FaceId(self.faces.len() as u32);
+            let tri1 = FaceId(tri0.0 + 1);
+
+            // (a, b, b') then (a, b', a'); twins inside the wall are known now
+            for (id, origin, twin, next, prev, face) in [
+                (ab, a, outside_twin, bb2, b2a, tri0),
+                (bb2, b, None, b2a, ab, tri0),
+                (b2a, b2, Some(ab2), ab, bb2, tri0),
+                (ab2, a, Some(b2a), b2a2, a2a, tri1),
+                (b2a2, b2, Some(*he_id), a2a, ab2, tri1),
+                (a2a, a2, None, ab2, b2a2, tri1),
+            ] {
+                self.half_edges.push(HalfEdge {
+                    id,
+                    origin,
+                    twin,
+                    next,
+                    prev,
+                    face: Some(face),
+                });
+            }
+            for (id, half_edge) in [(tri0, ab), (tri1, ab2)] {
+                self.faces.push(Face {
+                    id,
+                    half_edge,
+                    normal: Vec3::ZERO,
+                });
+            }
+
+            if let Some(twin) = outside_twin {
+                self.half_edges[twin.0 as usize].twin = Some(ab);
+            }
+            self.half_edges[he_id.0 as usize].twin = Some(b2a2);
+            for (key, id) in [
+                ((a, b), ab),
+                ((b, b2), bb2),
+                ((b2, a), b2a),
+                ((a, b2), ab2),
+                ((b2, a2), b2a2),
+                ((a2, a), a2a),
+            ] {
+                self.edge_map.insert(key, id);
+            }
+
+            wall_edges.insert(a, (ab, bb2, a2a, b));
+            walls.extend([tri0, tri1]);
+        }
+
+        // Vertical edges pair up with the neighboring wall: b->b' of this
+        // wall against b'->b of the wall starting at b
+        for (_, bb2, _, b) in wall_edges.values() {
+            let Some((_, _, b2b, _)) = wall_edges.get(b) else {
+                return Err(HalfEdgeError::InvalidTopology(
+                    "Region boundary is not closed".into(),
+                ));
+            };
+            self.half_edges[bb2.0 as usize].twin = Some(*b2b);
+            self.half_edges[b2b.0 as usize].twin = Some(*bb2);
+        }
+
+        // ===== PHASE 5: UPDATE AUXILIARY STRUCTURES =====
+        for (original, copy) in &duplicates {
+            let (wall_ab, ..) = wall_edges[original];
+            self.vertices[original.0 as usize].outgoing_half_edge = Some(wall_ab);
+            self.vertices[copy.0 as usize].outgoing_half_edge = Some(boundary_by_origin[original]);
+        }
+        self.recalculate_face_normals();
+
+        Ok(RegionSplit {
+            boundary,
+            duplicates,
+            walls,
+        })
+    }
+
+    /// (origin, destination) of a half-edge
+    fn half_edge_ends(&self, he_id: HalfEdgeId) -> (VertexId, VertexId) {
+        let he = &self.half_edges[he_id.0 as usize];
+        (he.origin, self.half_edges[he.next.0 as usize].origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::primitives::Plane3d;
+
+    const EPSILON: f32 = 1e-5;
+
+    /// Plane of `cells` x `cells` unit quads on XZ, centered at the origin
+    fn grid(cells: u32) -> HalfEdgeMesh {
+        let mesh = Plane3d::default()
+            .mesh()
+            .size(cells as f32, cells as f32)
+            .subdivisions(cells - 1)
+            .build();
+        HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap()
+    }
+
+    fn centroid(mesh: &HalfEdgeMesh, face: FaceId) -> Vec3 {
+        let verts = mesh.get_face_vertices(face);
+        verts
+            .iter()
+            .map(|v| mesh.vertices[v.0 as usize].position)
+            .sum::<Vec3>()
+            / verts.len() as f32
+    }
+
+    /// Triangles of the grid cells at (column, row), counted from -X/-Z
+    fn faces_in_cells(mesh: &HalfEdgeMesh, cells: u32, picked: &[(i32, i32)]) -> Vec<FaceId> {
+        let half = cells as f32 / 2.0;
+        mesh.faces()
+            .iter()
+            .map(|face| face.id)
+            .filter(|face| {
+                let c = centroid(mesh, *face);
+                let cell = ((c.x + half).floor() as i32, (c.z + half).floor() as i32);
+                picked.contains(&cell)
+            })
+            .collect()
+    }
+
+    fn area(mesh: &HalfEdgeMesh, faces: &[FaceId]) -> f32 {
+        faces
+            .iter()
+            .map(|face| mesh.unnormalized_face_normal(*face).length() / 2.0)
+            .sum()
+    }
+
+    /// Connectivity checks plus twins running in opposite directions
+    fn assert_valid(mesh: &HalfEdgeMesh) {
+        mesh.validate().unwrap();
+        mesh.validate_connectivity().unwrap();
+        for he in mesh.half_edges() {
+            if let Some(twin) = he.twin {
+                let (origin, dest) = mesh.half_edge_ends(he.id);
+                assert_eq!(mesh.half_edge_ends(twin), (dest, origin));
+            }
+        }
+    }
+
+    fn boundary_count(mesh: &HalfEdgeMesh) -> usize {
+        mesh.half_edges()
+            .iter()
+            .filter(|he| he.twin.is_none())
+            .count()
+    }
+
+    #[test]
+    fn test_extrude_single_quad() {
+        let mut mesh = grid(1);
+        let quad: Vec<FaceId> = mesh.faces().iter().map(|f| f.id).collect();
+        assert_eq!(quad.len(), 2);
+
+        let op = mesh.extrude_faces(&quad, 0.5).unwrap();
+        assert_valid(&mesh);
+        assert_eq!(op.new_faces.len(), 8);
+        assert_eq!(mesh.face_count(), 10);
+        assert!(op.direction().abs_diff_eq(Vec3::Y, EPSILON));
+
+        // The quad is lifted and still faces up
+        for face in &quad {
+            assert!(
+                mesh.faces[face.0 as usize]
+                    .normal
+                    .abs_diff_eq(Vec3::Y, EPSILON)
+            );
+            for v in mesh.get_face_vertices(*face) {
+                assert!((mesh.vertices[v.0 as usize].position.y - 0.5).abs() < EPSILON);
+            }
+        }
+
+        // Walls stand upright and face away from the middle
+        for face in &op.new_faces {
+            let normal = mesh.faces[face.0 as usize].normal;
+            let outward = centroid(&mesh, *face) * Vec3::new(1.0, 0.0, 1.0);
+            assert!(normal.y.abs() < EPSILON);
+            assert!(normal.dot(outward) > 0.0);
+        }
+
+        // The open bottom keeps the quad's four boundary edges
+        assert_eq!(boundary_count(&mesh), 4);
+
+        // Changing the amount only moves the extruded vertices
+        op.apply(&mut mesh, 1.0);
+        for v in mesh.get_face_vertices(quad[0]) {
+            assert!((mesh.vertices[v.0 as usize].position.y - 1.0).abs() < EPSILON);
+        }
+        assert_valid(&mesh);
+    }
+
+    #[test]
+    fn test_extrude_l_region() {
+        let mut mesh = grid(3);
+        let l_shape = faces_in_cells(&mesh, 3, &[(0, 0), (1, 0), (0, 1)]);
+        let others: Vec<FaceId> = mesh
+            .faces()
+            .iter()
+            .map(|f| f.id)
+            .filter(|f| !l_shape.contains(f))
+            .collect();
+        assert_eq!(l_shape.len(), 6);
+
+        let op = mesh.extrude_faces(&l_shape, 0.25).unwrap();
+        assert_valid(&mesh);
+
+        // One wall quad per edge of the L's perimeter
+        assert_eq!(op.new_faces.len(), 16);
+        assert_eq!(mesh.face_count(), 18 + 16);
+        assert_eq!(boundary_count(&mesh), 12);
+
+        for face in &l_shape {
+            for v in mesh.get_face_vertices(*face) {
+                assert!((mesh.vertices[v.0 as usize].position.y - 0.25).abs() < EPSILON);
+            }
+        }
+        for face in &others {
+            for v in mesh.get_face_vertices(*face) {
+                assert!(mesh.vertices[v.0 as usize].position.y.abs() < EPSILON);
+            }
+        }
+        for face in &op.new_faces {
+            assert!(mesh.faces[face.0 as usize].normal.y.abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_inset_single_quad() {
+        let mut mesh = grid(1);
+        let quad: Vec<FaceId> = mesh.faces().iter().map(|f| f.id).collect();
+
+        let mut region = mesh.clone();
+        let op = region.inset_faces(&quad, 0.1, false).unwrap();
+        assert_valid(&region);
+        assert_eq!(op.new_faces.len(), 8);
+        assert!((area(&region, &quad) - 0.64).abs() < EPSILON);
+        for face in &quad {
+            for v in region.get_face_vertices(*face) {
+                let p = region.vertices[v.0 as usize].position;
+                assert!((p.x.abs() - 0.4).abs() < EPSILON);
+                assert!((p.z.abs() - 0.4).abs() < EPSILON);
+                assert!(p.y.abs() < EPSILON);
+            }
+        }
+
+        // Individually, each triangle gets its own border
+        let op = mesh.inset_faces(&quad, 0.1, true).unwrap();
+        assert_valid(&mesh);
+        assert_eq!(op.new_faces.len(), 12);
+        assert_eq!(mesh.face_count(), 14);
+        for face in &quad {
+            assert!(
+                mesh.faces[face.0 as usize]
+                    .normal
+                    .abs_diff_eq(Vec3::Y, EPSILON)
+            );
+            assert!(area(&mesh, &[*face]) < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_inset_l_region() {
+        let mut mesh = grid(3);
+        let l_shape = faces_in_cells(&mesh, 3, &[(0, 0), (1, 0), (0, 1)]);
+
+        let op = mesh.inset_faces(&l_shape, 0.1, false).unwrap();
+        assert_valid(&mesh);
+        assert_eq!(op.new_faces.len(), 16);
+
+        // Area shrinks by perimeter * t, plus t^2 per convex corner (5)
+        // minus t^2 per concave corner (1)
+        assert!((area(&mesh, &l_shape) - (3.0 - 0.8 + 0.04)).abs() < 1e-4);
+
+        // The border fills the rest of the L
+        let border = area(&mesh, &op.new_faces);
+        assert!((border - (0.8 - 0.04)).abs() < 1e-4);
+        for face in &op.new_faces {
+            assert!(
+                mesh.faces[face.0 as usize]
+                    .normal
+                    .abs_diff_eq(Vec3::Y, EPSILON)
+            );
+        }
+    }
+
+    #[test]
+    fn test_failed_operation_leaves_mesh_unchanged() {
+        let mut mesh = grid(3);
+        // Two cells touching only at a corner pinch the boundary
+        let diagonal = faces_in_cells(&mesh, 3, &[(0, 0), (1, 1)]);
+        let before = mesh.half_edges().len();
+
+        assert!(mesh.extrude_faces(&diagonal, 1.0).is_err());
+        assert!(mesh.extrude_faces(&[], 1.0).is_err());
+        assert_eq!(mesh.half_edges().len(), before);
+
+        // Individually, the same faces are fine
+        assert!(mesh.inset_faces(&diagonal, 0.1, true).is_ok());
+        assert_valid(&mesh);
+    }
+}
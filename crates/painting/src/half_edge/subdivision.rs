@@ -0,0 +1,266 @@
+//! Loop subdivision for triangle meshes.
+//!
+//! Each level splits every triangle into four. Existing vertices are smoothed
+//! toward their neighbours and a new vertex is placed on every edge, using
+//! Loop's weights in the interior and the cubic B-spline rules on boundary
+//! edges so open borders stay smooth without being pulled by interior
+//! geometry.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use super::HalfEdgeMesh;
+use super::types::{Vertex, VertexId};
+
+/// Number of faces after `levels` rounds of Loop subdivision
+pub fn loop_face_count(face_count: usize, levels: u8) -> usize {
+    face_count.saturating_mul(4usize.saturating_pow(levels as u32))
+}
+
+/// Loop-subdivide `mesh` `levels` times into a new triangle mesh.
+///
+/// Faces removed by earlier topology edits are skipped and vertices that no
+/// longer belong to a face are dropped. UVs and mask values are interpolated
+/// linearly; UV seams are carried onto the split edges.
+pub fn subdivide_loop(mesh: &HalfEdgeMesh, levels: u8) -> HalfEdgeMesh {
+    let mut result = mesh.clone();
+    for _ in 0..levels {
+        result = subdivide_once(&result);
+    }
+    result
+}
+
+/// (lower, higher) vertex pair identifying an undirected edge
+fn edge_key(a: VertexId, b: VertexId) -> (VertexId, VertexId) {
+    if a.0 < b.0 { (a, b) } else { (b, a) }
+}
+
+/// Loop's weight for each neighbour of an interior vertex of `valence`
+fn loop_beta(valence: usize) -> f32 {
+    if valence == 3 {
+        3.0 / 16.0
+    } else {
+        3.0 / (8.0 * valence as f32)
+    }
+}
+
+fn subdivide_once(mesh: &HalfEdgeMesh) -> HalfEdgeMesh {
+    let position = |v: VertexId| mesh.vertices[v.0 as usize].position;
+
+    // Live triangles (fan-split anything larger)
+    let mut triangles: Vec<[VertexId; 3]> = Vec::new();
+    for face in mesh.faces() {
+        if !mesh.is_face_valid(face.id) {
+            continue;
+        }
+        let verts = mesh.get_face_vertices(face.id);
+        for i in 1..verts.len().saturating_sub(1) {
+            triangles.push([verts[0], verts[i], verts[i + 1]]);
+        }
+    }
+
+    // Keep the vertices still in use, in id order
+    let mut used = vec![false; mesh.vertices.len()];
+    for tri in &triangles {
+        for v in tri {
+            used[v.0 as usize] = true;
+        }
+    }
+    let mut remap: Vec<Option<u32>> = vec![None; mesh.vertices.len()];
+    let mut kept: Vec<VertexId> = Vec::new();
+    for (i, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        remap[i] = Some(kept.len() as u32);
+        kept.push(VertexId(i as u32));
+    }
+
+    // Edges in first-seen order, with the vertex opposite them in each face
+    let mut edge_index: HashMap<(VertexId, VertexId), u32> = HashMap::new();
+    let mut edges: Vec<(VertexId, VertexId)> = Vec::new();
+    let mut opposite: Vec<Vec<VertexId>> = Vec::new();
+    for tri in &triangles {
+        for i in 0..3 {
+            let key = edge_key(tri[i], tri[(i + 1) % 3]);
+            let index = *edge_index.entry(key).or_insert_with(|| {
+                edges.push(key);
+                opposite.push(Vec::new());
+                edges.len() as u32 - 1
+            });
+            opposite[index as usize].push(tri[(i + 2) % 3]);
+        }
+    }
+    // Anything other than two faces is treated as a border (crease)
+    let is_boundary = |index: usize| opposite[index].len() != 2;
+
+    let mut neighbours: Vec<Vec<VertexId>> = vec![Vec::new(); mesh.vertices.len()];
+    let mut boundary_neighbours: Vec<Vec<VertexId>> = vec![Vec::new(); mesh.vertices.len()];
+    for (index, &(a, b)) in edges.iter().enumerate() {
+        neighbours[a.0 as usize].push(b);
+        neighbours[b.0 as usize].push(a);
+        if is_boundary(index) {
+            boundary_neighbours[a.0 as usize].push(b);
+            boundary_neighbours[b.0 as usize].push(a);
+        }
+    }
+
+    let mut vertices: Vec<Vertex> = Vec::with_capacity(kept.len() + edges.len());
+
+    // Smoothed original vertices
+    for &v in &kept {
+        let source = &mesh.vertices[v.0 as usize];
+        let p = source.position;
+        let border = &boundary_neighbours[v.0 as usize];
+        let new_position = if border.is_empty() {
+            let ring = &neighbours[v.0 as usize];
+            let beta = loop_beta(ring.len());
+            let sum: Vec3 = ring.iter().map(|n| position(*n)).sum();
+            p * (1.0 - ring.len() as f32 * beta) + sum * beta
+        } else if border.len() == 2 {
+            p * 0.75 + (position(border[0]) + position(border[1])) * 0.125
+        } else {
+            // Corner where several borders meet: keep it pinned
+            p
+        };
+        let id = VertexId(vertices.len() as u32);
+        vertices.push(Vertex {
+            id,
+            position: new_position,
+            normal: source.normal,
+            uv: source.uv,
+            outgoing_half_edge: None,
+            source_index: id.0,
+            mask: source.mask,
+            color: source.color,
+        });
+    }
+
+    // One new vertex per edge
+    for (index, &(a, b)) in edges.iter().enumerate() {
+        let va = &mesh.vertices[a.0 as usize];
+        let vb = &mesh.vertices[b.0 as usize];
+        let new_position = if is_boundary(index) {
+            (va.position + vb.position) * 0.5
+        } else {
+            let [c, d] = [opposite[index][0], opposite[index][1]];
+            (va.position + vb.position) * 0.375 + (position(c) + position(d)) * 0.125
+        };
+        let id = VertexId(vertices.len() as u32);
+        vertices.push(Vertex {
+            id,
+            position: new_position,
+            normal: (va.normal + vb.normal).normalize_or_zero(),
+            uv: va.uv.zip(vb.uv).map(|(ua, ub)| (ua + ub) * 0.5),
+            outgoing_half_edge: None,
+            source_index: id.0,
+            mask: (va.mask + vb.mask) * 0.5,
+            color: (va.color + vb.color) * 0.5,
+        });
+    }
+
+    let corner = |v: VertexId| remap[v.0 as usize].expect("triangle vertex is kept");
+    let midpoint = |a: VertexId, b: VertexId| kept.len() as u32 + edge_index[&edge_key(a, b)];
+
+    let mut indices: Vec<u32> = Vec::with_capacity(triangles.len() * 12);
+    for &[a, b, c] in &triangles {
+        let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+        let (a, b, c) = (corner(a), corner(b), corner(c));
+        indices.extend_from_slice(&[a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+    }
+
+    let mut seams = HashSet::new();
+    for &(a, b) in &mesh.seams {
+        if let Some(&index) = edge_index.get(&(a, b)) {
+            let mid = VertexId(kept.len() as u32 + index);
+            seams.insert(edge_key(VertexId(corner(a)), mid));
+            seams.insert(edge_key(mid, VertexId(corner(b))));
+        }
+    }
+
+    let mut result = HalfEdgeMesh::from_triangles(vertices, &indices, seams);
+    result.recalculate_vertex_normals();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::primitives::{Cuboid, Plane3d};
+
+    fn boundary_half_edges(mesh: &HalfEdgeMesh) -> usize {
+        mesh.half_edges()
+            .iter()
+            .filter(|he| he.twin.is_none())
+            .count()
+    }
+
+    #[test]
+    fn test_subdivide_closed_mesh() {
+        let cube = HalfEdgeMesh::from_bevy_mesh(&Cuboid::default().mesh().build()).unwrap();
+        assert_eq!(cube.face_count(), 12);
+
+        let smooth = subdivide_loop(&cube, 2);
+        assert_eq!(smooth.face_count(), loop_face_count(12, 2));
+        assert_eq!(smooth.face_count(), 192);
+        smooth.validate().unwrap();
+        smooth.validate_connectivity().unwrap();
+        assert_eq!(boundary_half_edges(&smooth), 0);
+
+        // Euler characteristic of a sphere survives
+        let v = smooth.vertex_count() as i64;
+        let e = smooth.half_edges().len() as i64 / 2;
+        let f = smooth.face_count() as i64;
+        assert_eq!(v - e + f, 2);
+
+        // Smoothing pulls the corners in; nothing leaves the original box
+        let corner = Vec3::splat(0.5).length();
+        for vertex in smooth.vertices() {
+            assert!(vertex.position.abs().max_element() <= 0.5 + 1e-5);
+            assert!(vertex.position.length() < corner);
+        }
+    }
+
+    #[test]
+    fn test_subdivide_boundary_ignores_interior() {
+        let plane = Plane3d::default()
+            .mesh()
+            .size(2.0, 2.0)
+            .subdivisions(1)
+            .build();
+        let mut mesh = HalfEdgeMesh::from_bevy_mesh(&plane).unwrap();
+        // Lift the single interior vertex
+        let center = mesh
+            .vertices()
+            .iter()
+            .find(|v| v.position.length() < 1e-5)
+            .map(|v| v.id)
+            .unwrap();
+        mesh.set_vertex_position(center, Vec3::Y);
+        let border = boundary_half_edges(&mesh);
+
+        let smooth = subdivide_loop(&mesh, 1);
+        smooth.validate().unwrap();
+        assert_eq!(smooth.face_count(), mesh.face_count() * 4);
+        assert_eq!(boundary_half_edges(&smooth), border * 2);
+
+        let mut lifted = false;
+        for vertex in smooth.vertices() {
+            if smooth.is_boundary_vertex(vertex.id) {
+                assert_eq!(vertex.position.y, 0.0);
+            } else {
+                lifted |= vertex.position.y > 0.0;
+            }
+        }
+        assert!(lifted);
+    }
+
+    #[test]
+    fn test_subdivide_zero_levels_and_face_budget() {
+        let plane = Plane3d::default().mesh().build();
+        let mesh = HalfEdgeMesh::from_bevy_mesh(&plane).unwrap();
+        let same = subdivide_loop(&mesh, 0);
+        assert_eq!(same.face_count(), mesh.face_count());
+        assert_eq!(same.vertex_count(), mesh.vertex_count());
+
+        assert_eq!(loop_face_count(3, 4), 768);
+        assert_eq!(loop_face_count(usize::MAX / 2, 1), usize::MAX);
+    }
+}
@@ -25,6 +25,10 @@ pub struct Vertex {
     pub outgoing_half_edge: Option<HalfEdgeId>,
     /// Original index in the source Bevy mesh (for attribute mapping)
     pub source_index: u32,
+    /// Sculpt mask (0.0 = unmasked, 1.0 = fully protected from deformation)
+    pub mask: f32,
+    /// Painted vertex color, linear RGBA (white when unpainted)
+    pub color: Vec4,
 }
 
 /// A half-edge in the mesh
@@ -5,6 +5,7 @@
 //! flattened output surface for GPU upload.
 
 use crate::constants::DEFAULT_TILE_SIZE;
+use crate::surface::CpuSurface;
 use crate::tiles::TiledSurface;
 
 /// A single painting layer
@@ -232,6 +233,24 @@ impl LayerStack {
         }
     }
 
+    /// Resize every layer, placing the old content's top-left corner at `offset`
+    ///
+    /// Content pushed outside the new bounds is dropped and uncovered area is
+    /// transparent. The composite is rebuilt at the new size with every tile
+    /// marked dirty.
+    pub fn resize(&mut self, width: u32, height: u32, offset: (i32, i32)) {
+        for layer in &mut self.layers {
+            let mut resized = TiledSurface::with_default_tile_size(width, height);
+            copy_offset(layer.surface.surface(), resized.surface_mut(), offset);
+            resized.mark_region_dirty(0, 0, width, height);
+            layer.surface = resized;
+        }
+        self.composite = TiledSurface::with_default_tile_size(width, height);
+        self.width = width;
+        self.height = height;
+        self.composite();
+    }
+
     /// Get the composited output surface (after calling composite())
     pub fn composited_surface(&self) -> &TiledSurface {
         &self.composite
@@ -275,6 +294,33 @@ impl LayerStack {
     }
 }
 
+/// Copy the pixels of `src` into `dst` with `src`'s origin at `offset`,
+/// skipping whatever lands outside `dst`
+fn copy_offset(src: &CpuSurface, dst: &mut CpuSurface, offset: (i32, i32)) {
+    let (dst_width, dst_height) = (dst.width as i64, dst.height as i64);
+    let x_start = (-(offset.0 as i64)).max(0);
+    let x_end = (src.width as i64).min(dst_width - offset.0 as i64);
+    if x_start >= x_end {
+        return;
+    }
+
+    let src_pixels = src.pixels();
+    let dst_pixels = dst.pixels_mut();
+    for sy in 0..src.height as i64 {
+        let dy = sy + offset.1 as i64;
+        if dy < 0 || dy >= dst_height {
+            continue;
+        }
+        let src_row = (sy * src.width as i64) as usize;
+        let dst_row = (dy * dst_width) as usize;
+        let dx_start = (x_start + offset.0 as i64) as usize;
+        let len = (x_end - x_start) as usize;
+        dst_pixels[dst_row + dx_start..dst_row + dx_start + len].copy_from_slice(
+            &src_pixels[src_row + x_start as usize..src_row + x_start as usize + len],
+        );
+    }
+}
+
 /// Layer metadata for UI synchronization (mirroring pentimento_ipc::LayerInfo)
 #[derive(Debug, Clone)]
 pub struct LayerInfo {
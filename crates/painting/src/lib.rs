@@ -6,18 +6,28 @@
 //! - [`validation`] - Helpers for coordinate conversion and validation
 //! - [`surface`] - CPU 16-bit RGBA surface for painting
 //! - [`tiles`] - Tile management with dirty tracking
-//! - [`log`] - Stroke log storage and Iroh-ready hooks
+//! - [`log`] - Stroke log storage, checkpoints, and Iroh-ready hooks
 //! - [`brush`] - Brush engine for dab generation
+//! - [`brush_tip`] - Stamp brush tips (greyscale masks with mipmaps)
 //! - [`pipeline`] - Complete painting pipeline
+//! - [`gpu_dabs`] - Compute shader dab splatting (`gpu-paint` feature)
 //! - [`projection`] - Brush projection math for 3D mesh painting
 //! - [`half_edge`] - Half-edge mesh data structure for mesh editing
+//! - [`uv_unwrap`] - Automatic UV atlas generation for UV-less meshes
+//! - [`mesh_storage_conversion`] - Ptex/UV atlas conversion of mesh paint
+//! - [`uv_seams`] - Seam-aware bleeding and padding for UV-atlas mesh paint
+//! - [`vertex_color`] - Vertex color painting for meshes without UVs or Ptex
 
 pub mod brush;
+pub mod brush_tip;
 pub mod constants;
+#[cfg(feature = "gpu-paint")]
+pub mod gpu_dabs;
 #[cfg(feature = "bevy")]
 pub mod half_edge;
 pub mod layer;
 pub mod log;
+pub mod mesh_storage_conversion;
 pub mod mesh_surface;
 pub mod pipeline;
 pub mod projection;
@@ -26,14 +36,23 @@ pub mod raycast;
 pub mod surface;
 pub mod tiles;
 pub mod types;
+#[cfg(feature = "bevy")]
+pub mod uv_seams;
+#[cfg(feature = "bevy")]
+pub mod uv_unwrap;
 pub mod validation;
+pub mod vertex_color;
 
 pub use brush::*;
+pub use brush_tip::*;
 pub use constants::*;
+#[cfg(feature = "gpu-paint")]
+pub use gpu_dabs::*;
 #[cfg(feature = "bevy")]
 pub use half_edge::*;
 pub use layer::{Layer, LayerStack};
 pub use log::*;
+pub use mesh_storage_conversion::*;
 pub use mesh_surface::*;
 pub use pipeline::*;
 pub use projection::*;
@@ -42,4 +61,9 @@ pub use raycast::*;
 pub use surface::*;
 pub use tiles::*;
 pub use types::*;
+#[cfg(feature = "bevy")]
+pub use uv_seams::*;
+#[cfg(feature = "bevy")]
+pub use uv_unwrap::*;
 pub use validation::*;
+pub use vertex_color::*;
@@ -0,0 +1,175 @@
+//! Baked canvas snapshots standing in for old log entries.
+
+use std::io::Read;
+
+use ruzstd::decoding::StreamingDecoder;
+use ruzstd::encoding::{CompressionLevel, compress_to_vec};
+
+use crate::pipeline::CanvasSnapshot;
+
+/// Uncompressed bytes per compressed band (a band is whole rows of a layer).
+const BAND_BYTES: usize = 256 * 1024;
+
+/// Error type for reading a checkpoint back.
+#[derive(Debug, thiserror::Error)]
+pub enum CheckpointError {
+    #[error("Checkpoint layer {layer_id} is corrupt: {reason}")]
+    Corrupt { layer_id: u32, reason: String },
+}
+
+/// Canvas contents after every logged operation before `next_stroke`.
+///
+/// Replaces the packets and records it was baked from: a replay restores the
+/// checkpoint, then replays what the log still holds. Layer pixels are kept
+/// zstd-compressed, in bands of rows so they can be compressed a little at a
+/// time (see [`CheckpointBuilder`]).
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    /// Target space (canvas plane ID)
+    pub space_id: u32,
+    /// First stroke ID not baked in (shares the stroke ID sequence)
+    pub next_stroke: u64,
+    /// Canvas dimensions
+    pub width: u32,
+    pub height: u32,
+    /// Content origin of the baked canvas
+    pub origin: (i32, i32),
+    /// Compressed bands per layer ID, top to bottom
+    layers: Vec<(u32, Vec<Vec<u8>>)>,
+}
+
+impl Checkpoint {
+    /// Compress a baked canvas in one go.
+    pub fn new(space_id: u32, next_stroke: u64, canvas: CanvasSnapshot) -> Self {
+        let mut builder = CheckpointBuilder::new(space_id, next_stroke, canvas);
+        while !builder.step() {}
+        builder.finish()
+    }
+
+    /// Decompress the baked canvas.
+    pub fn canvas(&self) -> Result<CanvasSnapshot, CheckpointError> {
+        let layer_bytes = self.width as usize * self.height as usize * size_of::<[f32; 4]>();
+        let layers = self
+            .layers
+            .iter()
+            .map(|(layer_id, bands)| {
+                let corrupt = |reason: String| CheckpointError::Corrupt {
+                    layer_id: *layer_id,
+                    reason,
+                };
+                let mut bytes = Vec::with_capacity(layer_bytes);
+                for band in bands {
+                    StreamingDecoder::new(band.as_slice())
+                        .map_err(|e| corrupt(e.to_string()))?
+                        .read_to_end(&mut bytes)
+                        .map_err(|e| corrupt(e.to_string()))?;
+                }
+                if bytes.len() != layer_bytes {
+                    return Err(corrupt(format!(
+                        "{} bytes for a {}x{} canvas",
+                        bytes.len(),
+                        self.width,
+                        self.height
+                    )));
+                }
+                let pixels: Vec<[f32; 4]> = bytes
+                    .chunks_exact(size_of::<[f32; 4]>())
+                    .map(bytemuck::pod_read_unaligned)
+                    .collect();
+                Ok((*layer_id, pixels))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(CanvasSnapshot {
+            width: self.width,
+            height: self.height,
+            origin: self.origin,
+            layers,
+        })
+    }
+
+    /// Bytes held by the compressed layers.
+    pub fn compressed_bytes(&self) -> usize {
+        self.layers
+            .iter()
+            .flat_map(|(_, bands)| bands)
+            .map(Vec::len)
+            .sum()
+    }
+}
+
+/// A checkpoint being compressed one band of rows per [`step`](Self::step).
+///
+/// The baked canvas stays valid while it is compressed: later operations
+/// have stroke IDs from `next_stroke` on, so they are not part of it.
+pub struct CheckpointBuilder {
+    space_id: u32,
+    next_stroke: u64,
+    canvas: CanvasSnapshot,
+    /// Rows per band
+    band_rows: usize,
+    /// Bands compressed so far, over all layers in order
+    bands_done: usize,
+    layers: Vec<(u32, Vec<Vec<u8>>)>,
+}
+
+impl CheckpointBuilder {
+    /// Start compressing a baked canvas.
+    pub fn new(space_id: u32, next_stroke: u64, canvas: CanvasSnapshot) -> Self {
+        let row_bytes = canvas.width as usize * size_of::<[f32; 4]>();
+        let band_rows = (BAND_BYTES / row_bytes.max(1)).max(1);
+        let layers = canvas
+            .layers
+            .iter()
+            .map(|(layer_id, _)| (*layer_id, Vec::new()))
+            .collect();
+        Self {
+            space_id,
+            next_stroke,
+            canvas,
+            band_rows,
+            bands_done: 0,
+            layers,
+        }
+    }
+
+    /// Canvas plane the checkpoint is for.
+    pub fn space_id(&self) -> u32 {
+        self.space_id
+    }
+
+    /// Compress the next band. Returns true once every band is compressed.
+    pub fn step(&mut self) -> bool {
+        let bands_per_layer = (self.canvas.height as usize).div_ceil(self.band_rows);
+        let total = bands_per_layer * self.canvas.layers.len();
+        if self.bands_done >= total {
+            return true;
+        }
+
+        let layer = self.bands_done / bands_per_layer;
+        let band = self.bands_done % bands_per_layer;
+        let width = self.canvas.width as usize;
+        let pixels = &self.canvas.layers[layer].1;
+        let start = band * self.band_rows * width;
+        let end = (start + self.band_rows * width).min(pixels.len());
+        let bytes: &[u8] = bytemuck::cast_slice(&pixels[start..end]);
+        self.layers[layer]
+            .1
+            .push(compress_to_vec(bytes, CompressionLevel::Fastest));
+
+        self.bands_done += 1;
+        self.bands_done >= total
+    }
+
+    /// The finished checkpoint (call once [`step`](Self::step) returned true).
+    pub fn finish(self) -> Checkpoint {
+        Checkpoint {
+            space_id: self.space_id,
+            next_stroke: self.next_stroke,
+            width: self.canvas.width,
+            height: self.canvas.height,
+            origin: self.canvas.origin,
+            layers: self.layers,
+        }
+    }
+}
@@ -1,6 +1,8 @@
 //! Events emitted during stroke recording for Iroh integration hooks.
 
-use crate::types::StrokePacket;
+use crate::types::{
+    FillRecord, GradientRecord, ImportRecord, ResizeRecord, SelectionTransformRecord, StrokePacket,
+};
 
 /// Events emitted during stroke recording for Iroh integration hooks.
 ///
@@ -19,4 +21,16 @@ pub enum StrokeLogEvent {
     StrokeCompleted { packet: StrokePacket },
     /// A stroke was aborted before completion.
     StrokeAborted { stroke_id: u64, reason: String },
+    /// An image replaced a layer's contents and the import was stored.
+    ImageImported { record: ImportRecord },
+    /// The canvas was resized or cropped and the resize was stored.
+    CanvasResized { record: ResizeRecord },
+    /// A floating pixel selection was committed and the transform was stored.
+    SelectionTransformed { record: SelectionTransformRecord },
+    /// A bucket fill was applied and stored.
+    Filled { record: FillRecord },
+    /// A gradient was applied and stored.
+    GradientApplied { record: GradientRecord },
+    /// Entries of a space before `next_stroke` were baked into a checkpoint.
+    Checkpointed { space_id: u32, next_stroke: u64 },
 }
@@ -4,6 +4,7 @@
 //! - [`StrokeLog`] - Thread-safe append-only storage for stroke packets
 //! - [`StrokeLogEvent`] - Events for Iroh integration hooks
 //! - [`StrokeRecorder`] - Helper for building strokes with delta overflow handling
+//! - [`Checkpoint`] - Compressed canvas snapshot replacing older entries
 //!
 //! ## Iroh Key Format
 //!
@@ -22,12 +23,21 @@
 //! When a stroke overflows delta compression limits, multiple packets share the same
 //! stroke_id but have different base_x/base_y values. These are stored sequentially
 //! and must be replayed in order.
+//!
+//! ## Checkpoints
+//!
+//! The log would otherwise grow for the whole session. A checkpoint bakes the
+//! canvas as it was before some stroke and drops every entry of that space
+//! older than it, so a replay starts from the latest checkpoint and replays
+//! the entries that are left.
 
+mod checkpoint;
 mod dab_params;
 mod events;
 mod recorder;
 mod storage;
 
+pub use checkpoint::{Checkpoint, CheckpointBuilder, CheckpointError};
 pub use dab_params::DabParams;
 pub use events::StrokeLogEvent;
 pub use recorder::{RecorderError, StrokeConfig, StrokeRecorder};
@@ -36,6 +46,7 @@ pub use storage::StrokeLog;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pipeline::CanvasSnapshot;
     use crate::types::{BlendMode, Dab, Quantization, SpaceKind, StrokeHeader, StrokePacket};
     use crate::validation::to_fixed_point;
 
@@ -60,6 +71,9 @@ mod tests {
                 ptex_tile: 0,
                 pressure_quant: Quantization::U8,
                 speed_quant: Quantization::U8,
+                tip_hash: 0,
+                opacity: 255,
+                lamport: 0,
             },
             dabs: vec![Dab {
                 dx: 0,
@@ -179,6 +193,103 @@ mod tests {
         assert!(!recorder.is_recording());
     }
 
+    fn packet_with_dabs(space_id: u32, stroke_id: u64, dab_count: usize) -> StrokePacket {
+        StrokePacket {
+            header: StrokeHeader {
+                version: 1,
+                space_kind: SpaceKind::CanvasPlane,
+                space_id,
+                stroke_id,
+                timestamp_ms: 1000,
+                tool_id: 0,
+                blend_mode: BlendMode::Normal,
+                color: [0.0, 0.0, 0.0, 1.0],
+                flags: 0,
+                base_x: 0,
+                base_y: 0,
+                face_id: 0,
+                ptex_tile: 0,
+                pressure_quant: Quantization::U8,
+                speed_quant: Quantization::U8,
+                tip_hash: 0,
+                opacity: 255,
+                lamport: 0,
+            },
+            dabs: vec![
+                Dab {
+                    dx: 1,
+                    dy: 0,
+                    size: 256,
+                    pressure: 255,
+                    speed: 0,
+                    hardness: 128,
+                    opacity: 255,
+                    angle: 0,
+                    aspect_ratio: 255,
+                    _padding: [0, 0],
+                };
+                dab_count
+            ],
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_drops_older_entries() {
+        let log = StrokeLog::new();
+        for stroke_id in 1..=3 {
+            log.append(packet_with_dabs(7, stroke_id, 100));
+        }
+        log.append(packet_with_dabs(8, 1, 100));
+        let full = log.memory_bytes();
+        assert!(full >= 4 * 100 * size_of::<Dab>());
+        assert_eq!(log.stroke_id_range(7), Some((1, 3)));
+
+        let canvas = CanvasSnapshot {
+            width: 4,
+            height: 4,
+            origin: (0, 0),
+            layers: vec![(0, vec![[0.0; 4]; 16])],
+        };
+        log.apply_checkpoint(Checkpoint::new(7, 3, canvas));
+
+        assert_eq!(log.query_by_space(7).len(), 1);
+        assert_eq!(log.stroke_id_range(7), Some((3, 3)));
+        // Other spaces keep their entries
+        assert_eq!(log.query_by_space(8).len(), 1);
+        assert_eq!(log.checkpoint(7).unwrap().next_stroke, 3);
+        assert!(log.checkpoint(8).is_none());
+        assert!(log.memory_bytes() < full);
+    }
+
+    #[test]
+    fn test_checkpoint_round_trips_in_bands() {
+        // Rows this wide make bands of a few rows each
+        let (width, height) = (4096, 10);
+        let pixels: Vec<[f32; 4]> = (0..width * height)
+            .map(|i| [i as f32, 0.5, 0.25, 1.0])
+            .collect();
+        let canvas = CanvasSnapshot {
+            width,
+            height,
+            origin: (-3, 2),
+            layers: vec![
+                (1, pixels.clone()),
+                (2, vec![[0.0; 4]; (width * height) as usize]),
+            ],
+        };
+        let checkpoint = Checkpoint::new(0, 5, canvas);
+        assert!(checkpoint.compressed_bytes() < pixels.len() * size_of::<[f32; 4]>());
+
+        let restored = checkpoint.canvas().unwrap();
+        assert_eq!(
+            (restored.width, restored.height, restored.origin),
+            (width, height, (-3, 2))
+        );
+        assert_eq!(restored.layers[0], (1, pixels));
+        assert_eq!(restored.layers[1].0, 2);
+        assert!(restored.layers[1].1.iter().all(|pixel| *pixel == [0.0; 4]));
+    }
+
     #[test]
     fn test_iroh_key_format() {
         let key = StrokeLog::iroh_key(42, 1705847123456789);
@@ -215,6 +326,9 @@ mod tests {
                 ptex_tile: 0,
                 pressure_quant: Quantization::U8,
                 speed_quant: Quantization::U8,
+                tip_hash: 0,
+                opacity: 255,
+                lamport: 0,
             },
             dabs: vec![],
         };
@@ -34,6 +34,10 @@ pub struct StrokeConfig {
     pub ptex_tile: u16,
     pub pressure_quant: Quantization,
     pub speed_quant: Quantization,
+    /// Stamp brush tip hash (0 = round brush)
+    pub tip_hash: u64,
+    /// Brush opacity 0..255
+    pub opacity: u8,
 }
 
 impl Default for StrokeConfig {
@@ -51,6 +55,8 @@ impl Default for StrokeConfig {
             ptex_tile: 0,
             pressure_quant: Quantization::default(),
             speed_quant: Quantization::default(),
+            tip_hash: 0,
+            opacity: u8::MAX,
         }
     }
 }
@@ -248,6 +254,9 @@ impl StrokeRecorder {
             ptex_tile: config.ptex_tile,
             pressure_quant: config.pressure_quant,
             speed_quant: config.speed_quant,
+            tip_hash: config.tip_hash,
+            opacity: config.opacity,
+            lamport: 0,
         };
 
         let packet = StrokePacket {
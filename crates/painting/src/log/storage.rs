@@ -2,9 +2,14 @@
 
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use crate::types::StrokePacket;
+use crate::types::{
+    Dab, FillRecord, GradientRecord, ImportRecord, ResizeRecord, SelectionTransformRecord,
+    StrokePacket,
+};
 
+use super::checkpoint::Checkpoint;
 use super::events::StrokeLogEvent;
 
 /// Thread-safe append-only storage for stroke packets.
@@ -18,6 +23,20 @@ pub struct StrokeLog {
     /// Strokes indexed by space_id for efficient queries.
     /// Uses std::sync::RwLock for thread safety.
     strokes: RwLock<HashMap<u32, Vec<StrokePacket>>>,
+    /// Image imports indexed by space_id (replayed in order with the strokes).
+    imports: RwLock<HashMap<u32, Vec<ImportRecord>>>,
+    /// Canvas resizes indexed by space_id (replayed in order with the strokes).
+    resizes: RwLock<HashMap<u32, Vec<ResizeRecord>>>,
+    /// Selection transforms indexed by space_id (replayed in order with the strokes).
+    selection_transforms: RwLock<HashMap<u32, Vec<SelectionTransformRecord>>>,
+    /// Bucket fills indexed by space_id (replayed in order with the strokes).
+    fills: RwLock<HashMap<u32, Vec<FillRecord>>>,
+    /// Gradients indexed by space_id (replayed in order with the strokes).
+    gradients: RwLock<HashMap<u32, Vec<GradientRecord>>>,
+    /// Latest checkpoint per space_id (replaces the entries baked into it).
+    checkpoints: RwLock<HashMap<u32, Checkpoint>>,
+    /// Estimated bytes held by the entries and checkpoints.
+    bytes: AtomicUsize,
     /// Event listeners for Iroh integration hooks.
     /// Each listener receives cloned events.
     #[allow(clippy::type_complexity)]
@@ -43,6 +62,13 @@ impl Default for StrokeLog {
     fn default() -> Self {
         Self {
             strokes: RwLock::new(HashMap::new()),
+            imports: RwLock::new(HashMap::new()),
+            resizes: RwLock::new(HashMap::new()),
+            selection_transforms: RwLock::new(HashMap::new()),
+            fills: RwLock::new(HashMap::new()),
+            gradients: RwLock::new(HashMap::new()),
+            checkpoints: RwLock::new(HashMap::new()),
+            bytes: AtomicUsize::new(0),
             event_listeners: RwLock::new(Vec::new()),
         }
     }
@@ -60,6 +86,8 @@ impl StrokeLog {
     /// Emits a `StrokeCompleted` event to all registered listeners.
     pub fn append(&self, packet: StrokePacket) {
         let space_id = packet.header.space_id;
+        self.bytes
+            .fetch_add(packet_bytes(&packet), Ordering::Relaxed);
 
         // Store the packet
         {
@@ -79,6 +107,130 @@ impl StrokeLog {
         strokes.get(&space_id).cloned().unwrap_or_default()
     }
 
+    /// Append an image import record to the log.
+    ///
+    /// Emits an `ImageImported` event to all registered listeners.
+    pub fn append_import(&self, record: ImportRecord) {
+        self.bytes
+            .fetch_add(size_of::<ImportRecord>(), Ordering::Relaxed);
+        {
+            let mut imports = self.imports.write().expect("StrokeLog lock poisoned");
+            imports
+                .entry(record.space_id)
+                .or_default()
+                .push(record.clone());
+        }
+
+        self.emit_event(StrokeLogEvent::ImageImported { record });
+    }
+
+    /// Query all image imports for a given space_id.
+    pub fn query_imports_by_space(&self, space_id: u32) -> Vec<ImportRecord> {
+        let imports = self.imports.read().expect("StrokeLog lock poisoned");
+        imports.get(&space_id).cloned().unwrap_or_default()
+    }
+
+    /// Append a canvas resize record to the log.
+    ///
+    /// Emits a `CanvasResized` event to all registered listeners.
+    pub fn append_resize(&self, record: ResizeRecord) {
+        self.bytes
+            .fetch_add(size_of::<ResizeRecord>(), Ordering::Relaxed);
+        {
+            let mut resizes = self.resizes.write().expect("StrokeLog lock poisoned");
+            resizes
+                .entry(record.space_id)
+                .or_default()
+                .push(record.clone());
+        }
+
+        self.emit_event(StrokeLogEvent::CanvasResized { record });
+    }
+
+    /// Query all canvas resizes for a given space_id.
+    pub fn query_resizes_by_space(&self, space_id: u32) -> Vec<ResizeRecord> {
+        let resizes = self.resizes.read().expect("StrokeLog lock poisoned");
+        resizes.get(&space_id).cloned().unwrap_or_default()
+    }
+
+    /// Append a selection transform record to the log.
+    ///
+    /// Emits a `SelectionTransformed` event to all registered listeners.
+    pub fn append_selection_transform(&self, record: SelectionTransformRecord) {
+        self.bytes
+            .fetch_add(size_of::<SelectionTransformRecord>(), Ordering::Relaxed);
+        {
+            let mut transforms = self
+                .selection_transforms
+                .write()
+                .expect("StrokeLog lock poisoned");
+            transforms
+                .entry(record.space_id)
+                .or_default()
+                .push(record.clone());
+        }
+
+        self.emit_event(StrokeLogEvent::SelectionTransformed { record });
+    }
+
+    /// Query all selection transforms for a given space_id.
+    pub fn query_selection_transforms_by_space(
+        &self,
+        space_id: u32,
+    ) -> Vec<SelectionTransformRecord> {
+        let transforms = self
+            .selection_transforms
+            .read()
+            .expect("StrokeLog lock poisoned");
+        transforms.get(&space_id).cloned().unwrap_or_default()
+    }
+
+    /// Append a bucket fill record to the log.
+    ///
+    /// Emits a `Filled` event to all registered listeners.
+    pub fn append_fill(&self, record: FillRecord) {
+        self.bytes
+            .fetch_add(size_of::<FillRecord>(), Ordering::Relaxed);
+        {
+            let mut fills = self.fills.write().expect("StrokeLog lock poisoned");
+            fills
+                .entry(record.space_id)
+                .or_default()
+                .push(record.clone());
+        }
+
+        self.emit_event(StrokeLogEvent::Filled { record });
+    }
+
+    /// Query all bucket fills for a given space_id.
+    pub fn query_fills_by_space(&self, space_id: u32) -> Vec<FillRecord> {
+        let fills = self.fills.read().expect("StrokeLog lock poisoned");
+        fills.get(&space_id).cloned().unwrap_or_default()
+    }
+
+    /// Append a gradient record to the log.
+    ///
+    /// Emits a `GradientApplied` event to all registered listeners.
+    pub fn append_gradient(&self, record: GradientRecord) {
+        self.bytes
+            .fetch_add(gradient_bytes(&record), Ordering::Relaxed);
+        {
+            let mut gradients = self.gradients.write().expect("StrokeLog lock poisoned");
+            gradients
+                .entry(record.space_id)
+                .or_default()
+                .push(record.clone());
+        }
+
+        self.emit_event(StrokeLogEvent::GradientApplied { record });
+    }
+
+    /// Query all gradients for a given space_id.
+    pub fn query_gradients_by_space(&self, space_id: u32) -> Vec<GradientRecord> {
+        let gradients = self.gradients.read().expect("StrokeLog lock poisoned");
+        gradients.get(&space_id).cloned().unwrap_or_default()
+    }
+
     /// Get the total number of stroke packets across all spaces.
     pub fn total_packet_count(&self) -> usize {
         let strokes = self.strokes.read().expect("StrokeLog lock poisoned");
@@ -91,12 +243,122 @@ impl StrokeLog {
         strokes.keys().copied().collect()
     }
 
+    /// Estimated bytes held by the log's entries and checkpoints.
+    pub fn memory_bytes(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Lowest and highest stroke ID logged for a space, over every kind of
+    /// entry (None when the space has no entries left).
+    pub fn stroke_id_range(&self, space_id: u32) -> Option<(u64, u64)> {
+        fn ids<T>(
+            map: &RwLock<HashMap<u32, Vec<T>>>,
+            space_id: u32,
+            stroke_id: impl Fn(&T) -> u64,
+        ) -> Vec<u64> {
+            let map = map.read().expect("StrokeLog lock poisoned");
+            map.get(&space_id)
+                .map(|entries| entries.iter().map(stroke_id).collect())
+                .unwrap_or_default()
+        }
+
+        [
+            ids(&self.strokes, space_id, |p| p.header.stroke_id),
+            ids(&self.imports, space_id, |r| r.stroke_id),
+            ids(&self.resizes, space_id, |r| r.stroke_id),
+            ids(&self.selection_transforms, space_id, |r| r.stroke_id),
+            ids(&self.fills, space_id, |r| r.stroke_id),
+            ids(&self.gradients, space_id, |r| r.stroke_id),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(None, |range, id| match range {
+            None => Some((id, id)),
+            Some((low, high)) => Some((low.min(id), high.max(id))),
+        })
+    }
+
+    /// Store a checkpoint, dropping the entries of its space it was baked from.
+    ///
+    /// Entries with stroke IDs below `next_stroke` are removed, and the
+    /// checkpoint replaces any earlier one for the space. Emits a
+    /// `Checkpointed` event to all registered listeners.
+    pub fn apply_checkpoint(&self, checkpoint: Checkpoint) {
+        fn drop_before<T>(
+            map: &RwLock<HashMap<u32, Vec<T>>>,
+            space_id: u32,
+            next_stroke: u64,
+            stroke_id: impl Fn(&T) -> u64,
+        ) {
+            let mut map = map.write().expect("StrokeLog lock poisoned");
+            if let Some(entries) = map.get_mut(&space_id) {
+                entries.retain(|entry| stroke_id(entry) >= next_stroke);
+                entries.shrink_to_fit();
+            }
+        }
+
+        let space_id = checkpoint.space_id;
+        let next_stroke = checkpoint.next_stroke;
+        drop_before(&self.strokes, space_id, next_stroke, |p| p.header.stroke_id);
+        drop_before(&self.imports, space_id, next_stroke, |r| r.stroke_id);
+        drop_before(&self.resizes, space_id, next_stroke, |r| r.stroke_id);
+        drop_before(&self.selection_transforms, space_id, next_stroke, |r| {
+            r.stroke_id
+        });
+        drop_before(&self.fills, space_id, next_stroke, |r| r.stroke_id);
+        drop_before(&self.gradients, space_id, next_stroke, |r| r.stroke_id);
+        {
+            let mut checkpoints = self.checkpoints.write().expect("StrokeLog lock poisoned");
+            checkpoints.insert(space_id, checkpoint);
+        }
+        self.bytes.store(self.count_bytes(), Ordering::Relaxed);
+
+        self.emit_event(StrokeLogEvent::Checkpointed {
+            space_id,
+            next_stroke,
+        });
+    }
+
+    /// Latest checkpoint of a space, where a replay of its entries starts.
+    pub fn checkpoint(&self, space_id: u32) -> Option<Checkpoint> {
+        let checkpoints = self.checkpoints.read().expect("StrokeLog lock poisoned");
+        checkpoints.get(&space_id).cloned()
+    }
+
+    /// Add up the bytes held by every entry and checkpoint.
+    fn count_bytes(&self) -> usize {
+        fn sum<T>(map: &RwLock<HashMap<u32, Vec<T>>>, bytes: impl Fn(&T) -> usize) -> usize {
+            let map = map.read().expect("StrokeLog lock poisoned");
+            map.values().flatten().map(bytes).sum()
+        }
+
+        let checkpoints = self.checkpoints.read().expect("StrokeLog lock poisoned");
+        sum(&self.strokes, packet_bytes)
+            + sum(&self.imports, |_| size_of::<ImportRecord>())
+            + sum(&self.resizes, |_| size_of::<ResizeRecord>())
+            + sum(&self.selection_transforms, |_| {
+                size_of::<SelectionTransformRecord>()
+            })
+            + sum(&self.fills, |_| size_of::<FillRecord>())
+            + sum(&self.gradients, gradient_bytes)
+            + checkpoints
+                .values()
+                .map(Checkpoint::compressed_bytes)
+                .sum::<usize>()
+    }
+
     /// Register an event listener for Iroh integration hooks.
     ///
     /// The listener will receive cloned events for:
     /// - `StrokeStarted` - when a stroke begins recording
     /// - `StrokeCompleted` - when a stroke packet is stored
     /// - `StrokeAborted` - when a stroke is cancelled
+    /// - `ImageImported` - when an image import is stored
+    /// - `CanvasResized` - when a canvas resize is stored
+    /// - `SelectionTransformed` - when a selection transform is stored
+    /// - `Filled` - when a bucket fill is stored
+    /// - `GradientApplied` - when a gradient is stored
+    /// - `Checkpointed` - when old entries are baked into a checkpoint
     pub fn add_event_listener<F>(&self, listener: F)
     where
         F: Fn(StrokeLogEvent) + Send + Sync + 'static,
@@ -126,3 +388,13 @@ impl StrokeLog {
         format!("strokes/{}/{}", space_id, stroke_id)
     }
 }
+
+/// Estimated bytes held by a logged stroke packet.
+fn packet_bytes(packet: &StrokePacket) -> usize {
+    size_of::<StrokePacket>() + packet.dabs.len() * size_of::<Dab>()
+}
+
+/// Estimated bytes held by a logged gradient.
+fn gradient_bytes(record: &GradientRecord) -> usize {
+    size_of::<GradientRecord>() + record.colors.len() * size_of::<[f32; 4]>()
+}
@@ -0,0 +1,583 @@
+//! Conversion of mesh paint between UV atlas and Ptex storage
+//!
+//! [`StorageConversion`] moves the paint of one mesh from a [`MeshPtexSurface`]
+//! into a [`MeshUvSurface`] or back:
+//!
+//! - **Ptex to UV** rasterizes every face's UV triangle into the atlas and
+//!   fills each covered pixel with a bilinear sample of the face's ptex
+//!   texels. Pixels within half a pixel of a triangle are included so
+//!   sampling the atlas along chart borders never reads unpainted texels,
+//!   and the charts are then grown by the atlas' seam padding.
+//! - **UV to Ptex** gives each face a tile of the requested resolution and
+//!   fills it with bilinear samples of the atlas.
+//!
+//! Ptex tiles are addressed with the first two barycentric weights of a
+//! face, the same way brush hits address them: texel `(x, y)` of a tile at
+//! resolution `r` covers the weights `((x + 0.5) / r, (y + 0.5) / r)` of the
+//! face's first and second vertex. Texels past the diagonal lie outside the
+//! triangle; they are filled from the nearest point on its long edge so
+//! filtering near that edge stays clean.
+//!
+//! Both surfaces store `f32` channels and samples are never quantized, so
+//! 16-bit paint keeps its precision. Work is time-sliced like the sculpt
+//! mesh audit: each [`StorageConversion::step`] converts faces until its
+//! frame budget is spent.
+
+use std::time::{Duration, Instant};
+
+use glam::Vec2;
+
+use crate::constants::DEFAULT_SEAM_PADDING;
+use crate::mesh_surface::{MeshPtexSurface, MeshUvSurface, PtexFace};
+
+/// Distance in pixels from a UV triangle within which atlas pixels are
+/// still written by the face (half a pixel diagonal)
+pub(crate) const CONSERVATIVE_RASTER_PX: f32 = 0.71;
+
+/// Paint storage of one mesh in either mode
+pub enum MeshPaintSurface {
+    /// UV texture atlas
+    Uv(MeshUvSurface),
+    /// Per-face textures
+    Ptex(MeshPtexSurface),
+}
+
+/// How an atlas pixel was last written during a ptex to UV bake
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Coverage {
+    Empty,
+    /// Grown from neighbouring pixels
+    Dilated,
+    /// Near a triangle, outside it
+    Edge,
+    /// Pixel center inside a triangle
+    Inside,
+}
+
+/// A time-sliced conversion of one mesh's paint storage
+pub struct StorageConversion {
+    /// UVs of each face's corners, indexed by face id
+    face_uvs: Vec<[Vec2; 3]>,
+    source: MeshPaintSurface,
+    target: MeshPaintSurface,
+    /// Per-pixel coverage of the atlas being baked (ptex to UV only)
+    coverage: Vec<Coverage>,
+    next_face: usize,
+    dilation_passes_left: u32,
+    dilation_passes: u32,
+    /// Time one [`StorageConversion::step`] may spend before yielding
+    pub frame_budget: Duration,
+}
+
+impl StorageConversion {
+    /// Bake ptex paint into a new UV atlas of `resolution`.
+    ///
+    /// `face_uvs` holds the UVs of every face of the mesh, indexed by the
+    /// face ids the ptex surface uses.
+    pub fn ptex_to_uv(
+        source: MeshPtexSurface,
+        face_uvs: Vec<[Vec2; 3]>,
+        resolution: (u32, u32),
+    ) -> Self {
+        let target = MeshUvSurface::new(
+            source.mesh_id,
+            resolution.0,
+            resolution.1,
+            DEFAULT_SEAM_PADDING,
+        );
+        let dilation_passes = target.seam_padding;
+        Self {
+            face_uvs,
+            coverage: vec![Coverage::Empty; resolution.0 as usize * resolution.1 as usize],
+            source: MeshPaintSurface::Ptex(source),
+            target: MeshPaintSurface::Uv(target),
+            next_face: 0,
+            dilation_passes_left: dilation_passes,
+            dilation_passes,
+            frame_budget: Duration::from_millis(4),
+        }
+    }
+
+    /// Sample UV atlas paint into ptex tiles of `face_resolution`.
+    ///
+    /// Faces whose samples are all transparent get no tile.
+    pub fn uv_to_ptex(
+        source: MeshUvSurface,
+        face_uvs: Vec<[Vec2; 3]>,
+        face_resolution: u32,
+    ) -> Self {
+        let target = MeshPtexSurface::new(source.mesh_id, face_resolution.max(1));
+        Self {
+            face_uvs,
+            coverage: Vec::new(),
+            source: MeshPaintSurface::Uv(source),
+            target: MeshPaintSurface::Ptex(target),
+            next_face: 0,
+            dilation_passes_left: 0,
+            dilation_passes: 0,
+            frame_budget: Duration::from_millis(4),
+        }
+    }
+
+    /// Completed fraction (0.0-1.0)
+    pub fn progress(&self) -> f32 {
+        let total = self.face_uvs.len() + self.dilation_passes as usize;
+        if total == 0 {
+            return 1.0;
+        }
+        let done = self.next_face + (self.dilation_passes - self.dilation_passes_left) as usize;
+        done as f32 / total as f32
+    }
+
+    /// Whether every face (and dilation pass) has been converted
+    pub fn is_finished(&self) -> bool {
+        self.next_face >= self.face_uvs.len() && self.dilation_passes_left == 0
+    }
+
+    /// Convert faces until the frame budget runs out.
+    ///
+    /// Always converts at least one face or dilation pass. Returns true once
+    /// the conversion is finished.
+    pub fn step(&mut self) -> bool {
+        let start = Instant::now();
+        while self.next_face < self.face_uvs.len() {
+            let face_id = self.next_face;
+            self.next_face += 1;
+            self.convert_face(face_id);
+            if start.elapsed() >= self.frame_budget {
+                return self.is_finished();
+            }
+        }
+        while self.dilation_passes_left > 0 {
+            self.dilation_passes_left -= 1;
+            if let MeshPaintSurface::Uv(target) = &mut self.target {
+                dilate(target, &mut self.coverage);
+            }
+            if start.elapsed() >= self.frame_budget {
+                break;
+            }
+        }
+        self.is_finished()
+    }
+
+    /// Run the remaining conversion without a frame budget
+    pub fn run_to_completion(mut self) -> MeshPaintSurface {
+        while !self.step() {}
+        self.finish()
+    }
+
+    /// The converted surface, with every tile or face marked dirty for upload.
+    ///
+    /// Faces not yet converted stay unpainted; check [`Self::is_finished`]
+    /// first.
+    pub fn finish(self) -> MeshPaintSurface {
+        let mut target = self.target;
+        match &mut target {
+            MeshPaintSurface::Uv(surface) => {
+                let (width, height) = surface.dimensions();
+                surface.surface_mut().mark_region_dirty(0, 0, width, height);
+            }
+            MeshPaintSurface::Ptex(surface) => {
+                for face in surface.faces.values_mut() {
+                    face.dirty = true;
+                }
+            }
+        }
+        target
+    }
+
+    /// Take back the unchanged source surface, dropping the conversion
+    pub fn cancel(self) -> MeshPaintSurface {
+        self.source
+    }
+
+    fn convert_face(&mut self, face_id: usize) {
+        let uvs = self.face_uvs[face_id];
+        match (&self.source, &mut self.target) {
+            (MeshPaintSurface::Ptex(source), MeshPaintSurface::Uv(target)) => {
+                bake_ptex_face(
+                    source.faces.get(&(face_id as u32)),
+                    uvs,
+                    target,
+                    &mut self.coverage,
+                );
+            }
+            (MeshPaintSurface::Uv(source), MeshPaintSurface::Ptex(target)) => {
+                let face = sample_ptex_face(source, uvs, face_id as u32, target.default_resolution);
+                if face.pixels.iter().any(|pixel| pixel[3] > 0.0) {
+                    target.faces.insert(face.face_id, face);
+                }
+            }
+            _ => unreachable!("conversions always change the storage mode"),
+        }
+    }
+}
+
+/// Atlas pixel position of a UV (V points up, rows run down)
+pub(crate) fn uv_to_pixel(uv: Vec2, width: u32, height: u32) -> Vec2 {
+    Vec2::new(uv.x * width as f32, (1.0 - uv.y) * height as f32)
+}
+
+/// Barycentric weights of `p` in triangle `[a, b, c]`, or None if degenerate
+pub(crate) fn barycentric(p: Vec2, [a, b, c]: [Vec2; 3]) -> Option<[f32; 3]> {
+    let (v0, v1, v2) = (b - a, c - a, p - a);
+    let denom = v0.perp_dot(v1);
+    if denom.abs() < 1e-12 {
+        return None;
+    }
+    let w1 = v2.perp_dot(v1) / denom;
+    let w2 = v0.perp_dot(v2) / denom;
+    Some([1.0 - w1 - w2, w1, w2])
+}
+
+/// Closest point to `p` on segment `a`-`b`
+pub(crate) fn closest_on_segment(p: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_squared().max(1e-12)).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+/// Bilinear sample of a ptex tile at barycentric weights of its first two vertices
+fn sample_ptex(face: &PtexFace, w0: f32, w1: f32) -> [f32; 4] {
+    let res = face.resolution as f32;
+    let x = (w0 * res - 0.5).clamp(0.0, res - 1.0);
+    let y = (w1 * res - 0.5).clamp(0.0, res - 1.0);
+    bilinear(x, y, face.resolution, face.resolution, |x, y| {
+        face.pixels[(y * face.resolution + x) as usize]
+    })
+}
+
+/// Bilinear sample of the atlas at pixel position `p` (pixel centers at +0.5)
+fn sample_atlas(surface: &MeshUvSurface, p: Vec2) -> [f32; 4] {
+    let cpu = surface.surface().surface();
+    let (width, height) = (cpu.width, cpu.height);
+    let x = (p.x - 0.5).clamp(0.0, width as f32 - 1.0);
+    let y = (p.y - 0.5).clamp(0.0, height as f32 - 1.0);
+    let pixels = cpu.pixels();
+    bilinear(x, y, width, height, |x, y| {
+        pixels[(y as usize) * (width as usize) + x as usize]
+    })
+}
+
+/// Bilinear interpolation of texels around the clamped texel position `(x, y)`
+fn bilinear(
+    x: f32,
+    y: f32,
+    width: u32,
+    height: u32,
+    texel: impl Fn(u32, u32) -> [f32; 4],
+) -> [f32; 4] {
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let (a, b, c, d) = (texel(x0, y0), texel(x1, y0), texel(x0, y1), texel(x1, y1));
+    std::array::from_fn(|i| {
+        let top = a[i] + (b[i] - a[i]) * fx;
+        let bottom = c[i] + (d[i] - c[i]) * fx;
+        top + (bottom - top) * fy
+    })
+}
+
+/// Rasterize one face's ptex tile into the atlas.
+///
+/// Faces without a tile are unpainted and write transparent pixels, so
+/// paint of neighbouring faces doesn't grow into them.
+fn bake_ptex_face(
+    face: Option<&PtexFace>,
+    uvs: [Vec2; 3],
+    target: &mut MeshUvSurface,
+    coverage: &mut [Coverage],
+) {
+    let (width, height) = target.dimensions();
+    let corners = uvs.map(|uv| uv_to_pixel(uv, width, height));
+    if barycentric(corners[0], corners).is_none() {
+        return;
+    }
+
+    let min = corners[0].min(corners[1]).min(corners[2]) - CONSERVATIVE_RASTER_PX;
+    let max = corners[0].max(corners[1]).max(corners[2]) + CONSERVATIVE_RASTER_PX;
+    let x_range = (min.x.floor().max(0.0) as u32)..(max.x.ceil().max(0.0) as u32).min(width);
+    let y_range = (min.y.floor().max(0.0) as u32)..(max.y.ceil().max(0.0) as u32).min(height);
+
+    let cpu = target.surface_mut().surface_mut();
+    for y in y_range {
+        for x in x_range.clone() {
+            let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let Some(weights) = barycentric(p, corners) else {
+                continue;
+            };
+            let (weights, kind) = if weights.iter().all(|w| *w >= 0.0) {
+                (weights, Coverage::Inside)
+            } else {
+                let closest = [(0, 1), (1, 2), (2, 0)]
+                    .map(|(i, j)| closest_on_segment(p, corners[i], corners[j]))
+                    .into_iter()
+                    .min_by(|a, b| a.distance_squared(p).total_cmp(&b.distance_squared(p)))
+                    .expect("a triangle has edges");
+                if closest.distance(p) > CONSERVATIVE_RASTER_PX {
+                    continue;
+                }
+                let Some(weights) = barycentric(closest, corners) else {
+                    continue;
+                };
+                (weights.map(|w| w.max(0.0)), Coverage::Edge)
+            };
+
+            let index = y as usize * width as usize + x as usize;
+            if kind < coverage[index] {
+                continue;
+            }
+            coverage[index] = kind;
+            let color = face.map_or([0.0; 4], |face| sample_ptex(face, weights[0], weights[1]));
+            cpu.set_pixel(x, y, color);
+        }
+    }
+}
+
+/// Fill one ptex tile with samples of the atlas
+fn sample_ptex_face(
+    source: &MeshUvSurface,
+    uvs: [Vec2; 3],
+    face_id: u32,
+    resolution: u32,
+) -> PtexFace {
+    let (width, height) = source.dimensions();
+    let mut face = PtexFace::new(face_id, resolution);
+    let res = resolution as f32;
+    for y in 0..resolution {
+        for x in 0..resolution {
+            let mut w0 = (x as f32 + 0.5) / res;
+            let mut w1 = (y as f32 + 0.5) / res;
+            // Past the diagonal: use the nearest point on the w2 = 0 edge
+            let excess = w0 + w1 - 1.0;
+            if excess > 0.0 {
+                w0 = (w0 - excess * 0.5).clamp(0.0, 1.0);
+                w1 = (w1 - excess * 0.5).clamp(0.0, 1.0);
+            }
+            let w2 = (1.0 - w0 - w1).max(0.0);
+            let uv = uvs[0] * w0 + uvs[1] * w1 + uvs[2] * w2;
+            face.pixels[(y * resolution + x) as usize] =
+                sample_atlas(source, uv_to_pixel(uv, width, height));
+        }
+    }
+    face
+}
+
+/// Grow painted charts by one pixel into empty neighbours
+fn dilate(target: &mut MeshUvSurface, coverage: &mut [Coverage]) {
+    let (width, height) = target.dimensions();
+    let cpu = target.surface_mut().surface_mut();
+    let previous = coverage.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            let index = y as usize * width as usize + x as usize;
+            if previous[index] != Coverage::Empty {
+                continue;
+            }
+            let mut sum = [0.0f32; 4];
+            let mut count = 0;
+            for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)] {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32 {
+                    continue;
+                }
+                let neighbour = ny as usize * width as usize + nx as usize;
+                if previous[neighbour] == Coverage::Empty {
+                    continue;
+                }
+                let color = cpu.pixels()[neighbour];
+                for (total, channel) in sum.iter_mut().zip(color) {
+                    *total += channel;
+                }
+                count += 1;
+            }
+            if count > 0 {
+                cpu.set_pixel(x, y, sum.map(|total| total / count as f32));
+                coverage[index] = Coverage::Dilated;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    /// Latitude-longitude sphere: corner positions and UVs per triangle
+    fn uv_sphere(sectors: u32, stacks: u32) -> (Vec<[Vec3; 3]>, Vec<[Vec2; 3]>) {
+        let vertex = |i: u32, j: u32| {
+            let (u, v) = (i as f32 / sectors as f32, j as f32 / stacks as f32);
+            let (theta, phi) = (u * std::f32::consts::TAU, v * std::f32::consts::PI);
+            let position = Vec3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+            (position, Vec2::new(u, 1.0 - v))
+        };
+        let (mut positions, mut uvs) = (Vec::new(), Vec::new());
+        for j in 0..stacks {
+            for i in 0..sectors {
+                let (a, b, c, d) = (
+                    vertex(i, j),
+                    vertex(i + 1, j),
+                    vertex(i, j + 1),
+                    vertex(i + 1, j + 1),
+                );
+                if j != 0 {
+                    positions.push([a.0, b.0, c.0]);
+                    uvs.push([a.1, b.1, c.1]);
+                }
+                if j != stacks - 1 {
+                    positions.push([b.0, d.0, c.0]);
+                    uvs.push([b.1, d.1, c.1]);
+                }
+            }
+        }
+        (positions, uvs)
+    }
+
+    /// Whether ptex texel `(x, y)` lies inside its triangle
+    fn texel_inside(x: u32, y: u32, resolution: u32) -> bool {
+        (x as f32 + 0.5) + (y as f32 + 0.5) <= resolution as f32
+    }
+
+    #[test]
+    fn test_ptex_uv_round_trip_on_sphere() {
+        let resolution = 8;
+        let (positions, face_uvs) = uv_sphere(24, 12);
+
+        // Octant checkerboard painted into every face's tile
+        let mut ptex = MeshPtexSurface::new(7, resolution);
+        for (face_id, corners) in positions.iter().enumerate() {
+            let face = ptex.get_or_create_face(face_id as u32);
+            for y in 0..resolution {
+                for x in 0..resolution {
+                    let w0 = (x as f32 + 0.5) / resolution as f32;
+                    let w1 = (y as f32 + 0.5) / resolution as f32;
+                    let p = corners[0] * w0 + corners[1] * w1 + corners[2] * (1.0 - w0 - w1);
+                    let cell = p.floor();
+                    let value = if (cell.x + cell.y + cell.z) as i32 % 2 == 0 {
+                        0.9
+                    } else {
+                        0.1
+                    };
+                    face.set_pixel(x, y, [value, 1.0 - value, 0.5, 1.0]);
+                }
+            }
+        }
+        let original = ptex.faces.clone();
+
+        let MeshPaintSurface::Uv(atlas) =
+            StorageConversion::ptex_to_uv(ptex, face_uvs.clone(), (1024, 1024)).run_to_completion()
+        else {
+            panic!("ptex to UV produces an atlas");
+        };
+        assert_eq!(atlas.mesh_id, 7);
+        assert!(atlas.has_dirty_tiles());
+
+        let MeshPaintSurface::Ptex(round_trip) =
+            StorageConversion::uv_to_ptex(atlas, face_uvs, resolution).run_to_completion()
+        else {
+            panic!("UV to ptex produces tiles");
+        };
+        assert_eq!(round_trip.mesh_id, 7);
+        assert_eq!(round_trip.face_count(), original.len());
+
+        // Average difference per texel and channel, inside the triangles
+        let (mut error, mut samples) = (0.0f64, 0usize);
+        for (face_id, face) in &original {
+            let converted = &round_trip.faces[face_id];
+            assert!(converted.dirty);
+            for y in 0..resolution {
+                for x in 0..resolution {
+                    if !texel_inside(x, y, resolution) {
+                        continue;
+                    }
+                    let (a, b) = (
+                        face.get_pixel(x, y).unwrap(),
+                        converted.get_pixel(x, y).unwrap(),
+                    );
+                    for channel in 0..4 {
+                        error += (a[channel] - b[channel]).abs() as f64;
+                        samples += 1;
+                    }
+                }
+            }
+        }
+        let mean = error / samples as f64;
+        assert!(mean < 0.01, "mean per-texel difference {mean}");
+    }
+
+    #[test]
+    fn test_uv_to_ptex_skips_unpainted_faces() {
+        let face_uvs = vec![
+            [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(0.5, 0.0),
+                Vec2::new(0.0, 0.5),
+            ],
+            [
+                Vec2::new(1.0, 1.0),
+                Vec2::new(0.5, 1.0),
+                Vec2::new(1.0, 0.5),
+            ],
+        ];
+        let mut atlas = MeshUvSurface::new(1, 64, 64, 2);
+        // Paint only the top-right corner, where the second face lies
+        for y in 0..32 {
+            for x in 32..64 {
+                atlas
+                    .surface_mut()
+                    .surface_mut()
+                    .set_pixel(x, y, [0.25, 0.5, 0.75, 1.0]);
+            }
+        }
+
+        let mut conversion = StorageConversion::uv_to_ptex(atlas, face_uvs, 4);
+        conversion.frame_budget = Duration::ZERO;
+        assert!(!conversion.step());
+        assert_eq!(conversion.progress(), 0.5);
+        assert!(conversion.step());
+
+        let MeshPaintSurface::Ptex(ptex) = conversion.finish() else {
+            panic!("UV to ptex produces tiles");
+        };
+        assert_eq!(ptex.face_count(), 1);
+        let face = &ptex.faces[&1];
+        assert_eq!(face.resolution, 4);
+        assert_eq!(face.get_pixel(0, 0), Some([0.25, 0.5, 0.75, 1.0]));
+    }
+
+    #[test]
+    fn test_ptex_to_uv_keeps_precision_and_pads_charts() {
+        let face_uvs = vec![[
+            Vec2::new(0.25, 0.25),
+            Vec2::new(0.75, 0.25),
+            Vec2::new(0.25, 0.75),
+        ]];
+        let mut ptex = MeshPtexSurface::new(3, 4);
+        let color = [0.123_456, 0.654_321, 0.000_1, 1.0];
+        ptex.get_or_create_face(0).pixels.fill(color);
+
+        let conversion = StorageConversion::ptex_to_uv(ptex, face_uvs, (32, 32));
+        let MeshPaintSurface::Uv(atlas) = conversion.run_to_completion() else {
+            panic!("ptex to UV produces an atlas");
+        };
+        let cpu = atlas.surface().surface();
+        // Inside the triangle (values survive unquantized)
+        assert_eq!(cpu.get_pixel(10, 20), Some(color));
+        // Grown past the chart border by the seam padding
+        assert_eq!(cpu.get_pixel(6, 20), Some(color));
+        // Far from the chart
+        assert_eq!(cpu.get_pixel(30, 2), Some([0.0; 4]));
+    }
+
+    #[test]
+    fn test_cancel_returns_source() {
+        let ptex = MeshPtexSurface::new(5, 4);
+        let conversion = StorageConversion::ptex_to_uv(ptex, Vec::new(), (16, 16));
+        assert!(!conversion.is_finished());
+        let MeshPaintSurface::Ptex(source) = conversion.cancel() else {
+            panic!("cancel returns the ptex source");
+        };
+        assert_eq!(source.mesh_id, 5);
+    }
+}
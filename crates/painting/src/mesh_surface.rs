@@ -35,7 +35,8 @@ impl MeshUvSurface {
     /// * `mesh_id` - Unique identifier for the mesh
     /// * `width` - Texture atlas width in pixels
     /// * `height` - Texture atlas height in pixels
-    /// * `seam_padding` - Pixels of padding at UV seams (default: 2)
+    /// * `seam_padding` - Pixels of padding at UV seams (default:
+    ///   [`DEFAULT_SEAM_PADDING`](crate::constants::DEFAULT_SEAM_PADDING))
     pub fn new(mesh_id: u32, width: u32, height: u32, seam_padding: u32) -> Self {
         Self {
             atlas: TiledSurface::with_default_tile_size(width, height),
@@ -0,0 +1,668 @@
+//! Bucket fill and gradient tools for the painting pipeline
+//!
+//! Both operations rewrite the active layer in one step: the touched tiles
+//! become a single undo entry and only the parameters go into the stroke
+//! log, so a replay redoes them in stroke order.
+//!
+//! The fill matches pixels by perceptual distance (OKLab) to the seed color.
+//! It works tile by tile: a uniform tile is matched, flooded, and written as
+//! a whole, and only tiles with mixed content get a per-pixel mask and a
+//! scanline flood.
+
+use std::collections::HashMap;
+
+use glam::Vec2;
+use tracing::debug;
+
+use crate::tiles::{TileCoord, TiledSurface};
+use crate::types::{BlendMode, FillRecord, GradientKind, GradientRecord};
+
+use super::PaintingPipeline;
+use super::undo::UndoEntry;
+
+/// Fill mask states
+const NO_MATCH: u8 = 0;
+const MATCH: u8 = 1;
+const FILLED: u8 = 2;
+
+impl PaintingPipeline {
+    /// Flood fill the active layer with the brush color from pixel (x, y)
+    ///
+    /// Pixels within `tolerance` (perceptual distance, 0 = exact, 1 = black
+    /// versus white) of the seed color are filled; with `contiguous` only
+    /// those connected to the seed. The fill is blended with the current
+    /// blend mode.
+    ///
+    /// Returns false (and changes nothing) if the seed is outside the canvas
+    /// or a stroke is in progress.
+    pub fn fill(
+        &mut self,
+        space_id: u32,
+        stroke_id: u64,
+        x: u32,
+        y: u32,
+        tolerance: f32,
+        contiguous: bool,
+    ) -> bool {
+        let (width, height) = (self.width(), self.height());
+        if x >= width || y >= height {
+            debug!(
+                "fill: seed ({}, {}) outside {}x{} canvas",
+                x, y, width, height
+            );
+            return false;
+        }
+        if self.is_stroking() {
+            debug!("fill: stroke in progress, ignoring");
+            return false;
+        }
+
+        let color = self.color;
+        let blend_mode = self.blend_mode;
+        let layer_id = self.layers.active_layer_id();
+        let Some(layer) = self.layers.active_layer_mut() else {
+            return false;
+        };
+
+        // Classify tiles against the seed color, then fill at tile granularity
+        let surface = &layer.surface;
+        let seed_lab = oklab(surface.surface().pixels()[(y * width + x) as usize]);
+        let mut mask = FillMask::classify(surface, |pixel| {
+            perceptual_distance(seed_lab, pixel) <= tolerance
+        });
+        if contiguous {
+            mask.flood(x, y);
+        } else {
+            mask.fill_all_matches();
+        }
+
+        // Blend the filled pixels, capturing each touched tile for undo
+        let stride = width as usize;
+        let mut captured = HashMap::new();
+        for (index, tile) in mask.tiles.iter().enumerate() {
+            let coord = mask.coord(index);
+            let bounds = layer.surface.get_tile_bounds(coord);
+            match tile {
+                TileMask::Uniform {
+                    color: value,
+                    filled: true,
+                    ..
+                } => {
+                    captured.insert(coord, vec![*value]);
+                    let blended = blend(*value, color, blend_mode);
+                    let pixels = layer.surface.surface_mut().pixels_mut();
+                    for_each_row(stride, bounds, |row| pixels[row].fill(blended));
+                }
+                TileMask::Mixed(state) if state.contains(&FILLED) => {
+                    let pixels = layer.surface.surface_mut().pixels_mut();
+                    let mut original = Vec::with_capacity(state.len());
+                    for_each_row(stride, bounds, |row| {
+                        original.extend_from_slice(&pixels[row])
+                    });
+                    let mut states = state.iter();
+                    for_each_row(stride, bounds, |row| {
+                        for (pixel, state) in pixels[row].iter_mut().zip(&mut states) {
+                            if *state == FILLED {
+                                *pixel = blend(*pixel, color, blend_mode);
+                            }
+                        }
+                    });
+                    captured.insert(coord, original);
+                }
+                _ => continue,
+            }
+            layer
+                .surface
+                .mark_region_dirty(bounds.0, bounds.1, bounds.2, bounds.3);
+        }
+
+        debug!(
+            "Filled {} tiles of layer {} from ({}, {})",
+            captured.len(),
+            layer_id,
+            x,
+            y
+        );
+        self.push_undo(stroke_id, layer_id, captured);
+        self.log.append_fill(FillRecord {
+            space_id,
+            stroke_id,
+            layer_id,
+            timestamp_ms: now_ms(),
+            x,
+            y,
+            tolerance,
+            contiguous,
+            blend_mode,
+            color,
+        });
+        true
+    }
+
+    /// Render a gradient over the whole active layer
+    ///
+    /// `colors` are evenly spaced stops from `start` to `end` (canvas
+    /// pixels); a linear gradient runs along that line and a radial one
+    /// outwards from `start`. Each pixel is blended with the current blend
+    /// mode, so in Erase mode the stop alphas act as an erase mask.
+    ///
+    /// Returns false (and changes nothing) if there are no stops, start and
+    /// end coincide, or a stroke is in progress.
+    pub fn gradient(
+        &mut self,
+        space_id: u32,
+        stroke_id: u64,
+        start: Vec2,
+        end: Vec2,
+        kind: GradientKind,
+        colors: &[[f32; 4]],
+    ) -> bool {
+        let axis = end - start;
+        if colors.is_empty() || !axis.is_finite() || axis.length_squared() < 1e-6 {
+            debug!("gradient: needs color stops and distinct start/end points");
+            return false;
+        }
+        if self.is_stroking() {
+            debug!("gradient: stroke in progress, ignoring");
+            return false;
+        }
+
+        let blend_mode = self.blend_mode;
+        let layer_id = self.layers.active_layer_id();
+        let Some(layer) = self.layers.active_layer_mut() else {
+            return false;
+        };
+
+        // The whole layer changes, so every tile goes into the undo entry
+        let mut captured = HashMap::new();
+        for ty in 0..layer.surface.tiles_y() {
+            for tx in 0..layer.surface.tiles_x() {
+                let coord = TileCoord { x: tx, y: ty };
+                captured.insert(coord, layer.surface.get_tile_data(coord));
+            }
+        }
+
+        let surface = layer.surface.surface_mut();
+        let (width, height) = (surface.width, surface.height);
+        let inv_length_squared = 1.0 / axis.length_squared();
+        let inv_length = inv_length_squared.sqrt();
+        for (index, pixel) in surface.pixels_mut().iter_mut().enumerate() {
+            let p = Vec2::new(
+                (index % width as usize) as f32 + 0.5,
+                (index / width as usize) as f32 + 0.5,
+            ) - start;
+            let t = match kind {
+                GradientKind::Linear => p.dot(axis) * inv_length_squared,
+                GradientKind::Radial => p.length() * inv_length,
+            };
+            *pixel = blend(*pixel, sample_stops(colors, t), blend_mode);
+        }
+        layer.surface.mark_region_dirty(0, 0, width, height);
+
+        debug!(
+            "Applied {:?} gradient with {} stops to layer {}",
+            kind,
+            colors.len(),
+            layer_id
+        );
+        self.push_undo(stroke_id, layer_id, captured);
+        self.log.append_gradient(GradientRecord {
+            space_id,
+            stroke_id,
+            layer_id,
+            timestamp_ms: now_ms(),
+            start: start.to_array(),
+            end: end.to_array(),
+            kind,
+            colors: colors.to_vec(),
+            blend_mode,
+        });
+        true
+    }
+
+    fn push_undo(
+        &mut self,
+        stroke_id: u64,
+        layer_id: u32,
+        tiles: HashMap<TileCoord, Vec<[f32; 4]>>,
+    ) {
+        self.undo_stack.push(UndoEntry {
+            stroke_id,
+            layer_id,
+            tiles,
+            canvas: None,
+        });
+        while self.undo_stack.len() > self.max_undo_levels {
+            self.undo_stack.remove(0);
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Call `f` with the pixel index range of each row of a tile
+fn for_each_row(
+    stride: usize,
+    (bx, by, bw, bh): (u32, u32, u32, u32),
+    mut f: impl FnMut(std::ops::Range<usize>),
+) {
+    for y in by..by + bh {
+        let start = y as usize * stride + bx as usize;
+        f(start..start + bw as usize);
+    }
+}
+
+/// How the pixels of one tile take part in a fill
+enum TileMask {
+    /// Every pixel has `color`, so the whole tile matches or not and is
+    /// filled as one
+    Uniform {
+        color: [f32; 4],
+        matches: bool,
+        filled: bool,
+    },
+    /// Per-pixel `NO_MATCH`/`MATCH`/`FILLED` states, row-major in the tile
+    Mixed(Vec<u8>),
+}
+
+/// Fill states of a layer, tile by tile
+struct FillMask {
+    tiles: Vec<TileMask>,
+    tiles_x: u32,
+    tiles_y: u32,
+    tile_size: u32,
+    width: u32,
+    height: u32,
+}
+
+impl FillMask {
+    /// Classify every tile of `surface` with `matches`, one call per uniform tile
+    fn classify(surface: &TiledSurface, matches: impl Fn([f32; 4]) -> bool) -> Self {
+        let stride = surface.surface().width as usize;
+        let pixels = surface.surface().pixels();
+        let mut tiles = Vec::with_capacity((surface.tiles_x() * surface.tiles_y()) as usize);
+        for ty in 0..surface.tiles_y() {
+            for tx in 0..surface.tiles_x() {
+                let bounds = surface.get_tile_bounds(TileCoord { x: tx, y: ty });
+                let first = pixels[bounds.1 as usize * stride + bounds.0 as usize];
+                let mut uniform = true;
+                for_each_row(stride, bounds, |row| {
+                    uniform = uniform && pixels[row].iter().all(|pixel| *pixel == first);
+                });
+                if uniform {
+                    tiles.push(TileMask::Uniform {
+                        color: first,
+                        matches: matches(first),
+                        filled: false,
+                    });
+                    continue;
+                }
+
+                // Neighboring pixels often share a color, so cache the last result
+                let mut state = Vec::with_capacity((bounds.2 * bounds.3) as usize);
+                let mut last: Option<([f32; 4], u8)> = None;
+                for_each_row(stride, bounds, |row| {
+                    for &pixel in &pixels[row] {
+                        let value = match last {
+                            Some((previous, value)) if previous == pixel => value,
+                            _ => {
+                                let value = if matches(pixel) { MATCH } else { NO_MATCH };
+                                last = Some((pixel, value));
+                                value
+                            }
+                        };
+                        state.push(value);
+                    }
+                });
+                tiles.push(TileMask::Mixed(state));
+            }
+        }
+        Self {
+            tiles,
+            tiles_x: surface.tiles_x(),
+            tiles_y: surface.tiles_y(),
+            tile_size: surface.tile_size(),
+            width: surface.surface().width,
+            height: surface.surface().height,
+        }
+    }
+
+    fn coord(&self, index: usize) -> TileCoord {
+        TileCoord {
+            x: index as u32 % self.tiles_x,
+            y: index as u32 / self.tiles_x,
+        }
+    }
+
+    /// Width and height of a tile (edge tiles may be smaller)
+    fn tile_extent(&self, coord: TileCoord) -> (u32, u32) {
+        (
+            self.tile_size.min(self.width - coord.x * self.tile_size),
+            self.tile_size.min(self.height - coord.y * self.tile_size),
+        )
+    }
+
+    /// Mark every matching pixel as filled (non-contiguous fill)
+    fn fill_all_matches(&mut self) {
+        for tile in &mut self.tiles {
+            match tile {
+                TileMask::Uniform {
+                    matches, filled, ..
+                } => *filled = *matches,
+                TileMask::Mixed(state) => {
+                    for value in state.iter_mut().filter(|value| **value == MATCH) {
+                        *value = FILLED;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fill the matching pixels connected to canvas pixel (x, y)
+    ///
+    /// A matching uniform tile fills whole and passes the flood on along all
+    /// four edges; mixed tiles run a scanline flood and pass it on where a
+    /// filled span touches their edge.
+    fn flood(&mut self, x: u32, y: u32) {
+        let size = self.tile_size;
+        let mut seeds = vec![(
+            ((y / size) * self.tiles_x + x / size) as usize,
+            x % size,
+            y % size,
+        )];
+        let mut spans = Vec::new();
+        while let Some((index, lx, ly)) = seeds.pop() {
+            let coord = self.coord(index);
+            let (tile_width, tile_height) = self.tile_extent(coord);
+            spans.clear();
+            match &mut self.tiles[index] {
+                TileMask::Uniform {
+                    matches: true,
+                    filled,
+                    ..
+                } if !*filled => {
+                    *filled = true;
+                    spans.extend((0..tile_height).map(|row| (row, 0, tile_width - 1)));
+                }
+                TileMask::Mixed(state) => {
+                    flood_tile(state, tile_width, lx, ly, &mut spans);
+                }
+                TileMask::Uniform { .. } => continue,
+            }
+            self.pass_on(coord, (tile_width, tile_height), &spans, &mut seeds);
+        }
+    }
+
+    /// Seed the neighbors of a tile next to the filled spans touching its edges
+    fn pass_on(
+        &self,
+        coord: TileCoord,
+        (tile_width, tile_height): (u32, u32),
+        spans: &[(u32, u32, u32)],
+        seeds: &mut Vec<(usize, u32, u32)>,
+    ) {
+        let mut push = |tx: u32, ty: u32, lx: u32, ly: u32| {
+            let index = (ty * self.tiles_x + tx) as usize;
+            let open = match &self.tiles[index] {
+                TileMask::Uniform {
+                    matches, filled, ..
+                } => *matches && !*filled,
+                TileMask::Mixed(state) => {
+                    let (neighbor_width, _) = self.tile_extent(TileCoord { x: tx, y: ty });
+                    state[(ly * neighbor_width + lx) as usize] == MATCH
+                }
+            };
+            if open {
+                seeds.push((index, lx, ly));
+            }
+        };
+
+        for &(row, left, right) in spans {
+            if left == 0 && coord.x > 0 {
+                push(coord.x - 1, coord.y, self.tile_size - 1, row);
+            }
+            if right == tile_width - 1 && coord.x + 1 < self.tiles_x {
+                push(coord.x + 1, coord.y, 0, row);
+            }
+            if row == 0 && coord.y > 0 {
+                for lx in left..=right {
+                    push(coord.x, coord.y - 1, lx, self.tile_size - 1);
+                }
+            }
+            if row == tile_height - 1 && coord.y + 1 < self.tiles_y {
+                for lx in left..=right {
+                    push(coord.x, coord.y + 1, lx, 0);
+                }
+            }
+        }
+    }
+}
+
+/// Scanline flood fill inside one tile: mark the `MATCH` pixels connected to
+/// (x, y) as `FILLED`, collecting the filled spans as (row, left, right)
+fn flood_tile(state: &mut [u8], width: u32, x: u32, y: u32, spans: &mut Vec<(u32, u32, u32)>) {
+    let width = width as usize;
+    let height = state.len() / width;
+    let mut stack = vec![y as usize * width + x as usize];
+    while let Some(index) = stack.pop() {
+        if state[index] != MATCH {
+            continue;
+        }
+        let y = index / width;
+        let row = y * width;
+        let mut left = index;
+        while left > row && state[left - 1] == MATCH {
+            left -= 1;
+        }
+        let mut right = index;
+        while right + 1 < row + width && state[right + 1] == MATCH {
+            right += 1;
+        }
+        state[left..=right].fill(FILLED);
+        spans.push((y as u32, (left - row) as u32, (right - row) as u32));
+
+        // Seed one pixel per run of matches in the rows above and below
+        let neighbors = [y.checked_sub(1), (y + 1 < height).then_some(y + 1)];
+        for ny in neighbors.into_iter().flatten() {
+            let mut in_run = false;
+            for x in left - row..=right - row {
+                let neighbor = ny * width + x;
+                let hit = state[neighbor] == MATCH;
+                if hit && !in_run {
+                    stack.push(neighbor);
+                }
+                in_run = hit;
+            }
+        }
+    }
+}
+
+/// Composite `color` onto `dst` with the blend mode, like a full-strength dab
+fn blend(dst: [f32; 4], color: [f32; 4], blend_mode: BlendMode) -> [f32; 4] {
+    let coverage = color[3];
+    match blend_mode {
+        BlendMode::Normal => {
+            let inv_coverage = 1.0 - coverage;
+            [
+                color[0] * coverage + dst[0] * inv_coverage,
+                color[1] * coverage + dst[1] * inv_coverage,
+                color[2] * coverage + dst[2] * inv_coverage,
+                coverage + dst[3] * inv_coverage,
+            ]
+        }
+        BlendMode::Erase => dst.map(|channel| channel * (1.0 - coverage)),
+    }
+}
+
+/// Color at `t` (clamped to 0..1) along evenly spaced stops, interpolated
+/// with premultiplied alpha so fades to transparent don't darken
+fn sample_stops(colors: &[[f32; 4]], t: f32) -> [f32; 4] {
+    let Some(last) = colors.len().checked_sub(1).filter(|last| *last > 0) else {
+        return colors[0];
+    };
+    let position = t.clamp(0.0, 1.0) * last as f32;
+    let index = (position.floor() as usize).min(last - 1);
+    let frac = position - index as f32;
+    let (a, b) = (colors[index], colors[index + 1]);
+
+    let alpha = a[3] + (b[3] - a[3]) * frac;
+    if alpha <= 0.0 {
+        return [0.0; 4];
+    }
+    let channel = |c: usize| (a[c] * a[3] + (b[c] * b[3] - a[c] * a[3]) * frac) / alpha;
+    [channel(0), channel(1), channel(2), alpha]
+}
+
+/// Linear sRGB to OKLab
+fn oklab(pixel: [f32; 4]) -> [f32; 4] {
+    let [r, g, b, a] = pixel;
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        a,
+    ]
+}
+
+/// Distance between a seed color (already in OKLab) and a pixel
+///
+/// The color difference is weighted by the smaller alpha, so the hidden
+/// colors of transparent pixels don't matter; the alpha difference adds on.
+fn perceptual_distance(seed_lab: [f32; 4], pixel: [f32; 4]) -> f32 {
+    let lab = oklab(pixel);
+    let weight = seed_lab[3].min(lab[3]).clamp(0.0, 1.0);
+    let color = (0..3)
+        .map(|c| (seed_lab[c] - lab[c]).powi(2))
+        .sum::<f32>()
+        .sqrt();
+    ((color * weight).powi(2) + (seed_lab[3] - lab[3]).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    fn layer_pixel(pipeline: &PaintingPipeline, x: u32, y: u32) -> Option<[f32; 4]> {
+        let layer = pipeline.layers.active_layer().unwrap();
+        layer.surface.surface().get_pixel(x, y)
+    }
+
+    /// Canvas split by a vertical red wall at x = 100
+    fn walled_pipeline() -> PaintingPipeline {
+        let mut pipeline = PaintingPipeline::new(300, 200);
+        let surface = pipeline
+            .layers
+            .active_layer_mut()
+            .unwrap()
+            .surface
+            .surface_mut();
+        for y in 0..200 {
+            surface.set_pixel(100, y, RED);
+        }
+        pipeline.take_dirty_tiles();
+        pipeline
+    }
+
+    #[test]
+    fn test_fill_contiguous_stops_at_boundary_and_undoes() {
+        let mut pipeline = walled_pipeline();
+        pipeline.set_color(BLUE);
+
+        assert!(pipeline.fill(0, 1, 10, 10, 0.05, true));
+        assert_eq!(layer_pixel(&pipeline, 0, 199), Some(BLUE));
+        assert_eq!(layer_pixel(&pipeline, 99, 0), Some(BLUE));
+        assert_eq!(layer_pixel(&pipeline, 100, 50), Some(RED));
+        assert_eq!(layer_pixel(&pipeline, 250, 50), Some([0.0; 4]));
+        assert_eq!(pipeline.log().query_fills_by_space(0).len(), 1);
+
+        assert!(pipeline.undo());
+        assert_eq!(layer_pixel(&pipeline, 10, 10), Some([0.0; 4]));
+        assert_eq!(layer_pixel(&pipeline, 100, 50), Some(RED));
+    }
+
+    #[test]
+    fn test_fill_global_and_tolerance() {
+        let mut pipeline = walled_pipeline();
+        pipeline.set_color(BLUE);
+        // Slightly darker red still matches the wall at this tolerance
+        pipeline
+            .layers
+            .active_layer_mut()
+            .unwrap()
+            .surface
+            .surface_mut()
+            .set_pixel(250, 20, [0.95, 0.0, 0.0, 1.0]);
+
+        assert!(pipeline.fill(0, 1, 100, 0, 0.05, false));
+        assert_eq!(layer_pixel(&pipeline, 100, 150), Some(BLUE));
+        assert_eq!(layer_pixel(&pipeline, 250, 20), Some(BLUE));
+        assert_eq!(layer_pixel(&pipeline, 10, 10), Some([0.0; 4]));
+
+        // Outside the canvas nothing happens
+        assert!(!pipeline.fill(0, 2, 300, 0, 0.05, false));
+        assert_eq!(pipeline.undo_count(), 1);
+    }
+
+    #[test]
+    fn test_linear_gradient_interpolates_and_erases() {
+        let mut pipeline = PaintingPipeline::new(100, 10);
+        let stops = [RED, BLUE];
+        assert!(pipeline.gradient(
+            0,
+            1,
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            GradientKind::Linear,
+            &stops
+        ));
+        let left = layer_pixel(&pipeline, 0, 5).unwrap();
+        let middle = layer_pixel(&pipeline, 49, 5).unwrap();
+        assert!(left[0] > 0.98 && left[2] < 0.02);
+        assert!((middle[0] - 0.5).abs() < 0.02 && (middle[2] - 0.5).abs() < 0.02);
+        assert_eq!(pipeline.log().query_gradients_by_space(0).len(), 1);
+
+        // In Erase mode a radial fade clears the center and keeps the rim
+        pipeline.set_blend_mode(BlendMode::Erase);
+        let fade = [[0.0, 0.0, 0.0, 1.0], [0.0, 0.0, 0.0, 0.0]];
+        assert!(pipeline.gradient(
+            0,
+            2,
+            Vec2::new(50.0, 5.0),
+            Vec2::new(60.0, 5.0),
+            GradientKind::Radial,
+            &fade
+        ));
+        assert!(layer_pixel(&pipeline, 49, 4).unwrap()[3] < 0.1);
+        assert!(layer_pixel(&pipeline, 0, 5).unwrap()[3] > 0.99);
+    }
+
+    #[test]
+    fn test_fill_crosses_from_uniform_into_mixed_tiles() {
+        // The right half is uniform tiles except the one holding the wall
+        let mut pipeline = walled_pipeline();
+        pipeline.set_color(BLUE);
+        assert!(pipeline.fill(0, 1, 299, 199, 0.0, true));
+        assert_eq!(layer_pixel(&pipeline, 101, 0), Some(BLUE));
+        assert_eq!(layer_pixel(&pipeline, 127, 130), Some(BLUE));
+        assert_eq!(layer_pixel(&pipeline, 100, 130), Some(RED));
+        assert_eq!(layer_pixel(&pipeline, 99, 130), Some([0.0; 4]));
+        assert!(pipeline.has_dirty_tiles());
+
+        // Uniform tiles are captured as one pixel and restored whole
+        assert!(pipeline.undo());
+        assert_eq!(layer_pixel(&pipeline, 299, 199), Some([0.0; 4]));
+        assert_eq!(layer_pixel(&pipeline, 200, 0), Some([0.0; 4]));
+        assert_eq!(layer_pixel(&pipeline, 100, 130), Some(RED));
+    }
+}
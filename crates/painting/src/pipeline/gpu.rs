@@ -0,0 +1,260 @@
+//! GPU dab splatting for the painting pipeline (`gpu-paint` feature)
+//!
+//! While a round-brush stroke is painted on the GPU, dabs only capture their
+//! undo tiles on the CPU and are queued on the [`GpuDabCompositor`]. The
+//! stroke buffer and active layer catch up in [`PaintingPipeline::sync_gpu_stroke`],
+//! which reads back the coverage of the region painted since the last sync
+//! and composites it with the CPU code, so blend modes behave the same on
+//! both paths. The scene syncs once a frame before uploading dirty tiles, and
+//! `end_stroke` syncs before the layer is baked, logged, and snapshotted.
+
+use tracing::{info, warn};
+
+use crate::brush::DabOutput;
+use crate::gpu_dabs::{GpuDab, GpuDabCompositor};
+use crate::tiles::TiledSurface;
+use crate::types::BlendMode;
+
+use super::PaintingPipeline;
+
+/// Dabs painted on the GPU and not yet synced to the CPU
+#[derive(Default)]
+pub(crate) struct GpuStroke {
+    /// Kept so the CPU can repaint them if the readback fails
+    dabs: Vec<DabOutput>,
+    /// Union of their bounds: x_min, y_min, x_max, y_max (exclusive)
+    region: Option<[u32; 4]>,
+}
+
+impl PaintingPipeline {
+    /// Splat round-brush strokes on the GPU from now on
+    pub fn enable_gpu(&mut self, compositor: GpuDabCompositor) {
+        info!("GPU dab splatting enabled");
+        self.gpu = Some(compositor);
+    }
+
+    /// Whether strokes are splatted on the GPU
+    pub fn gpu_enabled(&self) -> bool {
+        self.gpu.is_some()
+    }
+
+    /// Bring the stroke buffer and active layer up to date with the dabs
+    /// splatted on the GPU since the last sync
+    ///
+    /// Blocks on the GPU readback. If it fails, GPU splatting is turned off
+    /// and the pending dabs are painted on the CPU instead.
+    pub fn sync_gpu_stroke(&mut self) {
+        let Some(stroke) = self.gpu_stroke.as_mut() else {
+            return;
+        };
+        let Some([x_min, y_min, x_max, y_max]) = stroke.region.take() else {
+            return;
+        };
+        let dabs = std::mem::take(&mut stroke.dabs);
+        let (width, height) = (x_max - x_min, y_max - y_min);
+
+        let coverage = match self.gpu.as_mut() {
+            Some(gpu) => gpu.read_region(x_min, y_min, width, height),
+            None => return,
+        };
+        let coverage = match coverage {
+            Ok(coverage) => coverage,
+            Err(error) => {
+                warn!("GPU dab splatting failed, painting on the CPU: {}", error);
+                self.gpu = None;
+                self.gpu_stroke = None;
+                for dab in &dabs {
+                    self.apply_dab(dab);
+                }
+                return;
+            }
+        };
+
+        // The stroke buffer is `color * alpha` in every channel but alpha
+        let color = self.stroke_color();
+        let (canvas_width, canvas_height) = (self.width(), self.height());
+        let buffer = self.stroke_buffer.get_or_insert_with(|| {
+            TiledSurface::with_default_tile_size(canvas_width, canvas_height)
+        });
+        let surface = buffer.surface_mut();
+        for (row, py) in (y_min..y_max).enumerate() {
+            let start = row * width as usize;
+            for (&alpha, px) in coverage[start..start + width as usize]
+                .iter()
+                .zip(x_min..x_max)
+            {
+                surface.set_pixel(
+                    px,
+                    py,
+                    [color[0] * alpha, color[1] * alpha, color[2] * alpha, alpha],
+                );
+            }
+        }
+        self.composite_stroke_region(x_min, y_min, width, height);
+    }
+
+    /// Start splatting the new stroke on the GPU if it can be
+    pub(crate) fn begin_gpu_stroke(&mut self) {
+        self.gpu_stroke = None;
+        // Stamp tips are sampled on the CPU only
+        if self.brush.preset().tip.is_some() {
+            return;
+        }
+        let color_alpha = self.stroke_color()[3];
+        let (width, height) = (self.width(), self.height());
+        let Some(gpu) = self.gpu.as_mut() else {
+            return;
+        };
+        match gpu.begin_stroke(width, height, color_alpha) {
+            Ok(()) => self.gpu_stroke = Some(GpuStroke::default()),
+            Err(error) => {
+                warn!(
+                    "GPU dab splatting unavailable, painting on the CPU: {}",
+                    error
+                );
+                self.gpu = None;
+            }
+        }
+    }
+
+    /// Queue a round dab on the GPU if the stroke is painted there. Returns
+    /// false when the caller should paint it on the CPU.
+    pub(crate) fn queue_gpu_dab(&mut self, dab: &DabOutput) -> bool {
+        let (width, height) = (self.width(), self.height());
+        let (Some(gpu), Some(stroke)) = (self.gpu.as_mut(), self.gpu_stroke.as_mut()) else {
+            return false;
+        };
+        // Same shape as the CPU's `apply_dab`: a circle
+        let Some(gpu_dab) = GpuDab::ellipse(
+            dab.x,
+            dab.y,
+            dab.size / 2.0,
+            dab.opacity,
+            dab.hardness,
+            0.0,
+            1.0,
+            width,
+            height,
+        ) else {
+            return true;
+        };
+        stroke.region = Some(match stroke.region {
+            Some(region) => [
+                region[0].min(gpu_dab.bounds[0]),
+                region[1].min(gpu_dab.bounds[1]),
+                region[2].max(gpu_dab.bounds[2]),
+                region[3].max(gpu_dab.bounds[3]),
+            ],
+            None => gpu_dab.bounds,
+        });
+        stroke.dabs.push(dab.clone());
+        gpu.push(gpu_dab);
+        true
+    }
+
+    /// Finish or drop the GPU side of the stroke, syncing it if `keep`
+    pub(crate) fn end_gpu_stroke(&mut self, keep: bool) {
+        if keep {
+            self.sync_gpu_stroke();
+        }
+        self.gpu_stroke = None;
+    }
+
+    /// Color stamped into the stroke buffer (erasing accumulates coverage only)
+    fn stroke_color(&self) -> [f32; 4] {
+        match self.blend_mode {
+            BlendMode::Normal => self.color,
+            BlendMode::Erase => [1.0; 4],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brush::BrushPreset;
+    use crate::gpu_dabs::GpuDabCompositor;
+    use crate::pipeline::PaintingPipeline;
+    use crate::types::BlendMode;
+
+    /// Largest difference allowed between the GPU and CPU paths, per channel
+    const TOLERANCE: f32 = 4.0 / 65535.0;
+
+    fn soft_brush() -> BrushPreset {
+        BrushPreset {
+            base_size: 120.0,
+            min_size: 120.0,
+            max_size: 120.0,
+            hardness: 0.0,
+            opacity: 0.8,
+            flow: 0.35,
+            spacing: 0.1,
+            ..Default::default()
+        }
+    }
+
+    fn paint(pipeline: &mut PaintingPipeline, mode: BlendMode, stroke_id: u64, sync_midway: bool) {
+        pipeline.set_blend_mode(mode);
+        pipeline.begin_stroke(0, stroke_id, 0);
+        for i in 0..40 {
+            let t = i as f32 / 39.0;
+            pipeline.stroke_to(
+                60.0 + t * 380.0,
+                80.0 + (t * 6.0).sin() * 60.0,
+                0.5 + t * 0.5,
+            );
+            if sync_midway && i % 7 == 0 {
+                pipeline.sync_gpu_stroke();
+            }
+        }
+        pipeline.end_stroke();
+    }
+
+    fn pipeline() -> PaintingPipeline {
+        let mut pipeline = PaintingPipeline::new(512, 256);
+        pipeline.set_brush(soft_brush());
+        pipeline.set_color([0.9, 0.3, 0.1, 0.85]);
+        pipeline
+    }
+
+    fn max_difference(a: &PaintingPipeline, b: &PaintingPipeline) -> f32 {
+        let a = a.layers.active_layer().unwrap().surface.surface().pixels();
+        let b = b.layers.active_layer().unwrap().surface.surface().pixels();
+        a.iter()
+            .zip(b)
+            .flat_map(|(a, b)| a.iter().zip(b).map(|(a, b)| (a - b).abs()))
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn test_gpu_stroke_matches_cpu() {
+        let compositor = match GpuDabCompositor::request() {
+            Ok(compositor) => compositor,
+            Err(error) => {
+                eprintln!("skipping GPU comparison: {}", error);
+                return;
+            }
+        };
+
+        let mut cpu = pipeline();
+        let mut gpu = pipeline();
+        gpu.enable_gpu(compositor);
+
+        // Normal, then Erase over it, with and without syncs mid-stroke
+        paint(&mut cpu, BlendMode::Normal, 1, false);
+        paint(&mut gpu, BlendMode::Normal, 1, true);
+        paint(&mut cpu, BlendMode::Erase, 2, false);
+        paint(&mut gpu, BlendMode::Erase, 2, false);
+        assert!(gpu.gpu_enabled());
+
+        let diff = max_difference(&cpu, &gpu);
+        assert!(
+            diff <= TOLERANCE,
+            "GPU and CPU strokes differ by {diff} (tolerance {TOLERANCE})"
+        );
+
+        // Undo restores the same pre-stroke tiles on both paths
+        assert!(cpu.undo());
+        assert!(gpu.undo());
+        assert!(max_difference(&cpu, &gpu) <= TOLERANCE);
+    }
+}
@@ -0,0 +1,231 @@
+//! Checkpointing the stroke log behind the undo stack
+//!
+//! Every logged operation gets an undo entry holding the pixels it replaced,
+//! until the undo stack drops it. Putting the retained entries back on a copy
+//! of the canvas, newest first, gives the canvas as it was before the oldest
+//! undoable operation, so everything logged before it can be baked into a
+//! checkpoint. Undo keeps working as before: its entries are untouched.
+
+use tracing::debug;
+
+use crate::log::{Checkpoint, CheckpointBuilder, CheckpointError};
+
+use super::PaintingPipeline;
+use super::undo::{CanvasSnapshot, restore_tile_pixels};
+
+impl PaintingPipeline {
+    /// Bytes held by the stroke log and the undo stack
+    pub fn history_bytes(&self) -> usize {
+        self.log.memory_bytes() + self.undo_bytes()
+    }
+
+    /// Bake the operations logged for `space_id` before the undo stack
+    ///
+    /// Returns a builder that compresses the baked canvas a band at a time;
+    /// hand the finished checkpoint to
+    /// [`StrokeLog::apply_checkpoint`](crate::log::StrokeLog::apply_checkpoint).
+    /// Returns None while a stroke or floating
+    /// selection is in progress, or when nothing logged is older than the
+    /// undo stack.
+    pub fn begin_checkpoint(&self, space_id: u32) -> Option<CheckpointBuilder> {
+        if self.is_stroking() || self.floating.is_some() {
+            return None;
+        }
+        let (oldest, newest) = self.log.stroke_id_range(space_id)?;
+        // With nothing to undo, the canvas already holds everything logged
+        let next_stroke = self
+            .undo_stack
+            .first()
+            .map_or(newest + 1, |entry| entry.stroke_id);
+        if oldest >= next_stroke {
+            return None;
+        }
+
+        debug!(
+            "Checkpointing space {} before stroke {}",
+            space_id, next_stroke
+        );
+        Some(CheckpointBuilder::new(
+            space_id,
+            next_stroke,
+            self.canvas_before_undo_stack(),
+        ))
+    }
+
+    /// Bake and store a checkpoint in one go (see [`Self::begin_checkpoint`])
+    ///
+    /// Returns false when there was nothing to checkpoint.
+    pub fn checkpoint_history(&mut self, space_id: u32) -> bool {
+        let Some(mut builder) = self.begin_checkpoint(space_id) else {
+            return false;
+        };
+        while !builder.step() {}
+        self.log.apply_checkpoint(builder.finish());
+        true
+    }
+
+    /// Replace the canvas with a checkpoint's, to replay the log from it
+    ///
+    /// Layers the checkpoint doesn't have are shifted to its size and keep
+    /// what fits, like after undoing a resize. Returns `Ok(false)` without
+    /// changing anything while a stroke is in progress.
+    pub fn restore_checkpoint(&mut self, checkpoint: &Checkpoint) -> Result<bool, CheckpointError> {
+        if self.is_stroking() {
+            return Ok(false);
+        }
+        self.restore_canvas(checkpoint.canvas()?);
+        Ok(true)
+    }
+
+    /// Size and layer contents of the canvas as it is now
+    pub(super) fn canvas_snapshot(&self) -> CanvasSnapshot {
+        CanvasSnapshot {
+            width: self.width(),
+            height: self.height(),
+            origin: self.origin,
+            layers: self
+                .layers
+                .layer_info()
+                .into_iter()
+                .filter_map(|info| self.layers.layer(info.id))
+                .map(|layer| (layer.id, layer.surface.surface().pixels().to_vec()))
+                .collect(),
+        }
+    }
+
+    /// The canvas as it was before the oldest entry on the undo stack
+    fn canvas_before_undo_stack(&self) -> CanvasSnapshot {
+        let tile_size = self.layers.composited_surface().tile_size();
+        let mut canvas = self.canvas_snapshot();
+        for entry in self.undo_stack.iter().rev() {
+            if let Some(snapshot) = &entry.canvas {
+                canvas = snapshot.clone();
+                continue;
+            }
+            let size = (canvas.width, canvas.height);
+            let Some((_, pixels)) = canvas
+                .layers
+                .iter_mut()
+                .find(|(layer_id, _)| *layer_id == entry.layer_id)
+            else {
+                continue;
+            };
+            for (coord, tile_data) in &entry.tiles {
+                restore_tile_pixels(pixels, size, tile_size, *coord, tile_data);
+            }
+        }
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paint(pipeline: &mut PaintingPipeline, stroke_id: u64) {
+        let y = 8.0 + stroke_id as f32 * 6.0;
+        pipeline.begin_stroke(0, stroke_id, 0);
+        for i in 0..12 {
+            pipeline.stroke_to(10.0 + i as f32 * 8.0, y, 1.0);
+        }
+        pipeline.end_stroke();
+    }
+
+    fn painted(strokes: std::ops::RangeInclusive<u64>, undo_levels: usize) -> PaintingPipeline {
+        let mut pipeline = PaintingPipeline::new(128, 128);
+        pipeline.set_color([0.2, 0.6, 1.0, 1.0]);
+        pipeline.max_undo_levels = undo_levels;
+        for stroke_id in strokes {
+            paint(&mut pipeline, stroke_id);
+        }
+        pipeline
+    }
+
+    #[test]
+    fn test_undo_across_checkpoint_boundary() {
+        let mut checkpointed = painted(1..=6, 3);
+        let mut reference = painted(1..=6, 3);
+
+        assert!(checkpointed.checkpoint_history(0));
+        let logged: Vec<u64> = checkpointed
+            .log()
+            .query_by_space(0)
+            .iter()
+            .map(|packet| packet.header.stroke_id)
+            .collect();
+        assert!(!logged.is_empty());
+        assert!(logged.iter().all(|&stroke_id| stroke_id >= 4));
+        assert_eq!(checkpointed.log().checkpoint(0).unwrap().next_stroke, 4);
+        // Nothing older than the undo stack is left
+        assert!(!checkpointed.checkpoint_history(0));
+
+        // Undo reaches exactly as far back, with the same results
+        for _ in 0..3 {
+            assert!(checkpointed.undo());
+            assert!(reference.undo());
+            assert_eq!(checkpointed.canvas_hash(), reference.canvas_hash());
+        }
+        assert!(!checkpointed.undo());
+        assert!(!reference.undo());
+        assert_eq!(checkpointed.canvas_hash(), painted(1..=3, 3).canvas_hash());
+    }
+
+    #[test]
+    fn test_replay_from_checkpoint_matches_live() {
+        let mut live = painted(1..=5, 2);
+        assert!(live.checkpoint_history(0));
+        paint(&mut live, 6);
+
+        let checkpoint = live.log().checkpoint(0).unwrap();
+        let mut replayed = PaintingPipeline::new(128, 128);
+        assert!(replayed.restore_checkpoint(&checkpoint).unwrap());
+        assert!(replayed.replay_packets(&live.log().query_by_space(0), &[]));
+        assert_eq!(replayed.canvas_hash(), live.canvas_hash());
+    }
+
+    #[test]
+    fn test_checkpoint_before_a_resize_keeps_the_old_size() {
+        let mut pipeline = painted(1..=2, 2);
+        let before_resize = pipeline.canvas_hash();
+        assert!(pipeline.resize_canvas(0, 3, 256, 128, (64, 0)));
+        paint(&mut pipeline, 4);
+
+        assert!(pipeline.checkpoint_history(0));
+        let canvas = pipeline.log().checkpoint(0).unwrap().canvas().unwrap();
+        assert_eq!((canvas.width, canvas.height), (128, 128));
+        let logged = pipeline.log().query_by_space(0);
+        assert!(logged.iter().all(|packet| packet.header.stroke_id == 4));
+        assert_eq!(pipeline.log().query_resizes_by_space(0).len(), 1);
+
+        assert!(pipeline.undo());
+        assert!(pipeline.undo());
+        assert_eq!(pipeline.canvas_hash(), before_resize);
+    }
+
+    #[test]
+    fn test_nothing_to_checkpoint() {
+        let mut pipeline = PaintingPipeline::new(64, 64);
+        assert!(!pipeline.checkpoint_history(0));
+
+        // Everything is still undoable
+        let mut pipeline = painted(1..=2, 20);
+        assert!(!pipeline.checkpoint_history(0));
+
+        // Not mid-stroke
+        let mut pipeline = painted(1..=3, 1);
+        pipeline.begin_stroke(0, 4, 0);
+        assert!(pipeline.begin_checkpoint(0).is_none());
+        pipeline.end_stroke();
+        assert!(pipeline.begin_checkpoint(0).is_some());
+    }
+
+    #[test]
+    fn test_history_bytes_count_log_and_undo() {
+        let pipeline = painted(1..=4, 2);
+        assert!(pipeline.undo_bytes() > 0);
+        assert_eq!(
+            pipeline.history_bytes(),
+            pipeline.log().memory_bytes() + pipeline.undo_bytes()
+        );
+    }
+}
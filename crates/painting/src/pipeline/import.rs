@@ -0,0 +1,94 @@
+//! Image import for the painting pipeline
+
+use std::collections::HashMap;
+use tracing::debug;
+
+use crate::tiles::TileCoord;
+use crate::types::ImportRecord;
+
+use super::PaintingPipeline;
+use super::undo::UndoEntry;
+
+impl PaintingPipeline {
+    /// Replace the active layer's contents with an image
+    ///
+    /// `pixels` holds `width * height` linear RGBA pixels in row-major order,
+    /// already fitted to the canvas. The previous layer contents become a
+    /// single undo entry, and the import is recorded in the stroke log under
+    /// `image_hash` so a replay can redo it.
+    ///
+    /// Returns false (and changes nothing) if the pixel count doesn't match
+    /// the canvas or a stroke is in progress.
+    pub fn import_image(
+        &mut self,
+        space_id: u32,
+        stroke_id: u64,
+        pixels: &[[f32; 4]],
+        image_hash: u64,
+    ) -> bool {
+        let (width, height) = (self.width(), self.height());
+        if pixels.len() != (width as usize) * (height as usize) {
+            debug!(
+                "import_image: got {} pixels for a {}x{} canvas",
+                pixels.len(),
+                width,
+                height
+            );
+            return false;
+        }
+        if self.is_stroking() {
+            debug!("import_image: stroke in progress, ignoring");
+            return false;
+        }
+
+        let layer_id = self.layers.active_layer_id();
+        let Some(layer) = self.layers.active_layer_mut() else {
+            return false;
+        };
+
+        // The whole layer changes, so every tile goes into the undo entry
+        let mut tiles = HashMap::new();
+        for ty in 0..layer.surface.tiles_y() {
+            for tx in 0..layer.surface.tiles_x() {
+                let coord = TileCoord { x: tx, y: ty };
+                tiles.insert(coord, layer.surface.get_tile_data(coord));
+            }
+        }
+
+        layer
+            .surface
+            .surface_mut()
+            .pixels_mut()
+            .copy_from_slice(pixels);
+        layer.surface.mark_region_dirty(0, 0, width, height);
+
+        self.undo_stack.push(UndoEntry {
+            stroke_id,
+            layer_id,
+            tiles,
+            canvas: None,
+        });
+        while self.undo_stack.len() > self.max_undo_levels {
+            self.undo_stack.remove(0);
+        }
+
+        self.log.append_import(ImportRecord {
+            space_id,
+            stroke_id,
+            layer_id,
+            timestamp_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            width,
+            height,
+            image_hash,
+        });
+
+        debug!(
+            "Imported image {:016x} into layer {} ({}x{})",
+            image_hash, layer_id, width, height
+        );
+        true
+    }
+}
@@ -9,6 +9,14 @@
 //! The pipeline is designed to be used from Bevy systems but does not
 //! depend on Bevy itself.
 
+mod fill;
+#[cfg(feature = "gpu-paint")]
+mod gpu;
+mod history;
+mod import;
+mod replay;
+mod resize;
+mod selection;
 mod stroke;
 mod surface_ops;
 mod undo;
@@ -18,18 +26,21 @@ use std::collections::{HashMap, HashSet};
 use crate::brush::{BrushEngine, BrushPreset};
 use crate::layer::LayerStack;
 use crate::log::{StrokeLog, StrokeRecorder};
-use crate::tiles::TileCoord;
+use crate::tiles::{TileCoord, TiledSurface};
 use crate::types::BlendMode;
 
-pub use undo::UndoEntry;
+pub use selection::{FloatingSelection, SelectionShape};
+pub use undo::{CanvasSnapshot, UndoEntry};
 
 /// Complete painting pipeline for a canvas
 ///
 /// This struct manages the full painting workflow:
 /// 1. Input comes in via `begin_stroke`, `stroke_to`, `end_stroke`
 /// 2. The brush engine generates dabs from input
-/// 3. Dabs are applied to the active layer's CPU surface
-/// 4. Dabs are recorded for storage/sync
+/// 3. Dabs build up in a stroke buffer at the brush flow, and the buffer is
+///    composited onto the active layer at the brush opacity
+/// 4. Dabs are recorded for storage/sync, and painted at the precision they
+///    are recorded at, so replaying the log reproduces the canvas exactly
 /// 5. Layers are composited and dirty tiles tracked for GPU upload
 pub struct PaintingPipeline {
     /// Layer stack (replaces single surface)
@@ -52,10 +63,24 @@ pub struct PaintingPipeline {
     pub(crate) pending_undo_captures: HashMap<TileCoord, Vec<[f32; 4]>>,
     /// Set of tiles already captured this stroke (to avoid re-capturing)
     pub(crate) captured_tiles: HashSet<TileCoord>,
+    /// Dabs of the current stroke at full opacity (premultiplied coverage)
+    pub(crate) stroke_buffer: Option<TiledSurface>,
+    /// Opacity the stroke buffer is composited at, as logged for the stroke
+    pub(crate) stroke_opacity: f32,
     /// Undo stack (most recent at end)
     pub(crate) undo_stack: Vec<UndoEntry>,
     /// Maximum undo levels
     pub(crate) max_undo_levels: usize,
+    /// Where the original top-left pixel sits after resizes and crops
+    pub(crate) origin: (i32, i32),
+    /// Pixels lifted off a layer and not yet committed
+    pub(crate) floating: Option<FloatingSelection>,
+    /// GPU dab splatting, when enabled and supported
+    #[cfg(feature = "gpu-paint")]
+    pub(crate) gpu: Option<crate::gpu_dabs::GpuDabCompositor>,
+    /// Current stroke's dabs on the GPU (None while painting on the CPU)
+    #[cfg(feature = "gpu-paint")]
+    pub(crate) gpu_stroke: Option<gpu::GpuStroke>,
 }
 
 impl PaintingPipeline {
@@ -72,8 +97,16 @@ impl PaintingPipeline {
             current_space_id: None,
             pending_undo_captures: HashMap::new(),
             captured_tiles: HashSet::new(),
+            stroke_buffer: None,
+            stroke_opacity: 1.0,
             undo_stack: Vec::new(),
             max_undo_levels: 20,
+            origin: (0, 0),
+            floating: None,
+            #[cfg(feature = "gpu-paint")]
+            gpu: None,
+            #[cfg(feature = "gpu-paint")]
+            gpu_stroke: None,
         }
     }
 
@@ -193,6 +226,72 @@ mod tests {
         assert!(!pipeline.has_dirty_tiles());
     }
 
+    #[test]
+    fn test_pipeline_import_image_undo() {
+        let mut pipeline = PaintingPipeline::new(64, 32);
+        let red = [1.0, 0.0, 0.0, 1.0];
+
+        // Wrong size is rejected
+        assert!(!pipeline.import_image(0, 1, &[red; 4], 7));
+        assert!(!pipeline.can_undo());
+
+        assert!(pipeline.import_image(0, 1, &vec![red; 64 * 32], 7));
+        assert!(pipeline.has_dirty_tiles());
+        pipeline.take_dirty_tiles();
+        assert_eq!(pipeline.get_pixel(63, 31), Some(red));
+
+        let imports = pipeline.log().query_imports_by_space(0);
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].image_hash, 7);
+        assert_eq!((imports[0].width, imports[0].height), (64, 32));
+
+        // Undo restores the empty layer
+        assert!(pipeline.undo());
+        pipeline.take_dirty_tiles();
+        assert_eq!(pipeline.get_pixel(63, 31), Some([0.0, 0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_pipeline_stroke_opacity_caps_overlapping_dabs() {
+        let mut pipeline = PaintingPipeline::new(128, 128);
+        pipeline.set_color([0.0, 0.0, 1.0, 1.0]);
+        pipeline.set_brush(BrushPreset {
+            opacity: 0.5,
+            flow: 0.4,
+            hardness: 1.0,
+            ..Default::default()
+        });
+
+        // 50 dabs scrubbing back and forth over the same spot
+        pipeline.begin_stroke(0, 1, 0);
+        for i in 0..50 {
+            let x = if i % 2 == 0 { 60.0 } else { 68.0 };
+            pipeline.stroke_to(x, 64.0, 1.0);
+        }
+        let max_alpha = |pipeline: &PaintingPipeline| {
+            let layer = pipeline.layers.active_layer().unwrap();
+            layer
+                .surface
+                .surface()
+                .pixels()
+                .iter()
+                .map(|pixel| pixel[3])
+                .fold(0.0f32, f32::max)
+        };
+        // Capped while the stroke is live...
+        assert!(max_alpha(&pipeline) <= 0.5 + 1e-5);
+        pipeline.end_stroke();
+
+        // ...and after it is baked, with the overlap reaching the cap
+        let max = max_alpha(&pipeline);
+        assert!(max <= 0.5 + 1e-5, "alpha {} exceeds stroke opacity", max);
+        assert!(max > 0.49);
+
+        // Undo restores the untouched layer
+        assert!(pipeline.undo());
+        assert_eq!(max_alpha(&pipeline), 0.0);
+    }
+
     #[test]
     fn test_pipeline_clear() {
         let mut pipeline = PaintingPipeline::new(256, 256);
@@ -0,0 +1,155 @@
+//! Replaying logged strokes onto the canvas
+//!
+//! A stroke packet records the color, blend mode and brush opacity, and each
+//! dab's position, size, hardness and flow. Stamp tips are looked up by hash
+//! among the tips passed in, falling back to the round brush
+//! (`tip_for_header`). Live strokes are painted at the precision they are
+//! logged at, so a replay reproduces them exactly.
+
+use std::sync::Arc;
+
+use crate::brush::{BrushPreset, DabOutput};
+use crate::brush_tip::{BrushTip, tip_for_header};
+use crate::types::{StrokePacket, content_hash};
+use crate::validation::{from_angle_field, from_fixed_point, from_size_field, from_unit_field};
+
+use super::PaintingPipeline;
+
+impl PaintingPipeline {
+    /// Paint logged strokes onto the active layer, in order
+    ///
+    /// Consecutive packets with the same stroke ID are painted as one stroke,
+    /// the way the recorder split them. Replayed strokes are not logged and
+    /// don't get undo entries; the caller owns their record. Returns false
+    /// without painting while a live stroke is in progress.
+    pub fn replay_packets(&mut self, packets: &[StrokePacket], tips: &[BrushTip]) -> bool {
+        if self.is_stroking() {
+            return false;
+        }
+
+        let preset = self.brush.preset().clone();
+        let color = self.color;
+        let blend_mode = self.blend_mode;
+
+        for stroke in packets.chunk_by(|a, b| a.header.stroke_id == b.header.stroke_id) {
+            self.replay_stroke(stroke, tips);
+        }
+
+        self.brush.set_preset(preset);
+        self.color = color;
+        self.blend_mode = blend_mode;
+        true
+    }
+
+    fn replay_stroke(&mut self, packets: &[StrokePacket], tips: &[BrushTip]) {
+        let header = &packets[0].header;
+        self.color = header.color;
+        self.blend_mode = header.blend_mode;
+        self.brush.set_preset(BrushPreset {
+            tip: tip_for_header(header, tips).map(|tip| Arc::new(tip.clone())),
+            ..Default::default()
+        });
+        self.stroke_opacity = from_unit_field(header.opacity);
+
+        self.pending_undo_captures.clear();
+        self.captured_tiles.clear();
+        self.stroke_buffer = None;
+
+        for packet in packets {
+            for dab in decode_dabs(packet) {
+                self.apply_dab(&dab);
+            }
+        }
+
+        self.pending_undo_captures.clear();
+        self.captured_tiles.clear();
+        self.stroke_buffer = None;
+    }
+
+    /// Hash of every layer's pixels in stack order
+    ///
+    /// Two canvases with the same layers and pixels hash the same, whatever
+    /// order their dirty tiles were composited in.
+    pub fn canvas_hash(&self) -> u64 {
+        let mut bytes = Vec::new();
+        for info in self.layers.layer_info() {
+            if let Some(layer) = self.layers.layer(info.id) {
+                bytes.extend_from_slice(&info.id.to_le_bytes());
+                bytes.extend_from_slice(bytemuck::cast_slice(layer.surface.surface().pixels()));
+            }
+        }
+        content_hash(&bytes)
+    }
+}
+
+/// Dabs of a packet in surface coordinates
+///
+/// The first dab is a delta from the header's base position, each later dab
+/// a delta from the one before.
+fn decode_dabs(packet: &StrokePacket) -> impl Iterator<Item = DabOutput> + '_ {
+    let (mut x, mut y) = (packet.header.base_x, packet.header.base_y);
+    packet.dabs.iter().map(move |dab| {
+        x += dab.dx as i32;
+        y += dab.dy as i32;
+        DabOutput {
+            x: from_fixed_point(x),
+            y: from_fixed_point(y),
+            size: from_size_field(dab.size),
+            hardness: from_unit_field(dab.hardness),
+            opacity: from_unit_field(dab.opacity),
+            angle: from_angle_field(dab.angle),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BlendMode;
+
+    fn paint(pipeline: &mut PaintingPipeline, stroke_id: u64, y: f32) {
+        pipeline.begin_stroke(0, stroke_id, 0);
+        for i in 0..20 {
+            pipeline.stroke_to(20.0 + i as f32 * 4.0, y, 1.0);
+        }
+        pipeline.end_stroke();
+    }
+
+    #[test]
+    fn test_replay_matches_live_strokes() {
+        let mut live = PaintingPipeline::new(128, 128);
+        live.set_color([1.0, 0.0, 0.0, 1.0]);
+        live.set_brush(BrushPreset {
+            opacity: 0.6,
+            ..Default::default()
+        });
+        paint(&mut live, 1, 40.0);
+        live.set_blend_mode(BlendMode::Erase);
+        paint(&mut live, 2, 44.0);
+
+        let packets = live.log().query_by_space(0);
+        let mut replayed = PaintingPipeline::new(128, 128);
+        assert!(replayed.replay_packets(&packets, &[]));
+
+        // The replay's own state is left alone
+        assert_eq!(replayed.color(), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(replayed.blend_mode(), BlendMode::Normal);
+        assert!(!replayed.can_undo());
+        assert_eq!(replayed.log().total_packet_count(), 0);
+
+        assert_eq!(replayed.canvas_hash(), live.canvas_hash());
+        assert_ne!(
+            replayed.canvas_hash(),
+            PaintingPipeline::new(128, 128).canvas_hash()
+        );
+    }
+
+    #[test]
+    fn test_replay_waits_for_live_stroke() {
+        let mut pipeline = PaintingPipeline::new(64, 64);
+        pipeline.begin_stroke(0, 1, 0);
+        assert!(!pipeline.replay_packets(&[], &[]));
+        pipeline.end_stroke();
+        assert!(pipeline.replay_packets(&[], &[]));
+    }
+}
@@ -0,0 +1,157 @@
+//! Canvas resize and crop for the painting pipeline
+
+use std::collections::HashMap;
+use tracing::debug;
+
+use crate::types::ResizeRecord;
+
+use super::PaintingPipeline;
+use super::undo::{CanvasSnapshot, UndoEntry};
+
+impl PaintingPipeline {
+    /// Resize the canvas to `width` x `height`, placing the old content's
+    /// top-left corner at `offset`
+    ///
+    /// A crop is a resize with a negative offset. Every layer is resized, the
+    /// previous size and contents become a single undo entry, and the resize
+    /// is recorded in the stroke log.
+    ///
+    /// Returns false (and changes nothing) for a zero dimension or while a
+    /// stroke or floating selection is in progress.
+    pub fn resize_canvas(
+        &mut self,
+        space_id: u32,
+        stroke_id: u64,
+        width: u32,
+        height: u32,
+        offset: (i32, i32),
+    ) -> bool {
+        if width == 0 || height == 0 {
+            debug!("resize_canvas: invalid size {}x{}", width, height);
+            return false;
+        }
+        if self.is_stroking() || self.floating.is_some() {
+            debug!("resize_canvas: stroke or selection in progress, ignoring");
+            return false;
+        }
+
+        let snapshot = self.canvas_snapshot();
+
+        self.layers.resize(width, height, offset);
+        self.origin = (self.origin.0 + offset.0, self.origin.1 + offset.1);
+
+        self.undo_stack.push(UndoEntry {
+            stroke_id,
+            layer_id: self.layers.active_layer_id(),
+            tiles: HashMap::new(),
+            canvas: Some(snapshot),
+        });
+        while self.undo_stack.len() > self.max_undo_levels {
+            self.undo_stack.remove(0);
+        }
+
+        self.log.append_resize(ResizeRecord {
+            space_id,
+            stroke_id,
+            timestamp_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            width,
+            height,
+            offset_x: offset.0,
+            offset_y: offset.1,
+        });
+
+        debug!(
+            "Resized canvas to {}x{} with content at ({}, {})",
+            width, height, offset.0, offset.1
+        );
+        true
+    }
+
+    /// Where the original canvas's top-left pixel sits after resizes and
+    /// crops (views use it to keep content in place as the canvas changes)
+    pub fn content_origin(&self) -> (i32, i32) {
+        self.origin
+    }
+
+    /// Put back the size and layer contents captured before a resize
+    ///
+    /// Layers added since the resize have no snapshot, so they are shifted
+    /// back with the rest of the canvas and keep what still fits.
+    pub(crate) fn restore_canvas(&mut self, snapshot: CanvasSnapshot) {
+        let shift = (
+            snapshot.origin.0 - self.origin.0,
+            snapshot.origin.1 - self.origin.1,
+        );
+        self.layers.resize(snapshot.width, snapshot.height, shift);
+        for (layer_id, pixels) in snapshot.layers {
+            if let Some(layer) = self.layers.layer_mut(layer_id) {
+                layer
+                    .surface
+                    .surface_mut()
+                    .pixels_mut()
+                    .copy_from_slice(&pixels);
+            }
+        }
+        self.layers.composite();
+        self.origin = snapshot.origin;
+
+        debug!("Restored canvas to {}x{}", snapshot.width, snapshot.height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paint_marker(pipeline: &mut PaintingPipeline, x: u32, y: u32, color: [f32; 4]) {
+        let layer = pipeline.layers.active_layer_mut().unwrap();
+        layer.surface.surface_mut().set_pixel(x, y, color);
+        pipeline.take_dirty_tiles();
+    }
+
+    #[test]
+    fn test_resize_anchors_content_and_undo_restores() {
+        let mut pipeline = PaintingPipeline::new(64, 32);
+        let red = [1.0, 0.0, 0.0, 1.0];
+        paint_marker(&mut pipeline, 10, 5, red);
+
+        assert!(pipeline.resize_canvas(0, 1, 128, 64, (32, 16)));
+        assert_eq!((pipeline.width(), pipeline.height()), (128, 64));
+        assert_eq!(pipeline.get_pixel(42, 21), Some(red));
+        assert_eq!(pipeline.get_pixel(10, 5), Some([0.0; 4]));
+        assert_eq!(pipeline.content_origin(), (32, 16));
+        assert_eq!(pipeline.log().query_resizes_by_space(0).len(), 1);
+
+        assert!(pipeline.undo());
+        assert_eq!((pipeline.width(), pipeline.height()), (64, 32));
+        assert_eq!(pipeline.get_pixel(10, 5), Some(red));
+        assert_eq!(pipeline.content_origin(), (0, 0));
+    }
+
+    #[test]
+    fn test_crop_drops_outside_content_until_undo() {
+        let mut pipeline = PaintingPipeline::new(64, 64);
+        let blue = [0.0, 0.0, 1.0, 1.0];
+        paint_marker(&mut pipeline, 2, 2, blue);
+        paint_marker(&mut pipeline, 40, 50, blue);
+
+        assert!(pipeline.resize_canvas(0, 1, 32, 32, (-20, -30)));
+        assert_eq!(pipeline.get_pixel(20, 20), Some(blue));
+        assert!(!pipeline.take_dirty_tiles().is_empty());
+
+        assert!(pipeline.undo());
+        assert_eq!(pipeline.get_pixel(2, 2), Some(blue));
+        assert_eq!(pipeline.get_pixel(40, 50), Some(blue));
+    }
+
+    #[test]
+    fn test_resize_rejects_zero_size() {
+        let mut pipeline = PaintingPipeline::new(64, 64);
+        assert!(!pipeline.resize_canvas(0, 1, 0, 64, (0, 0)));
+        assert_eq!(pipeline.width(), 64);
+        assert!(!pipeline.can_undo());
+    }
+}
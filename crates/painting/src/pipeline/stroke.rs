@@ -4,8 +4,12 @@ use tracing::debug;
 
 use crate::brush::DabOutput;
 use crate::log::{DabParams, StrokeConfig, StrokeRecorder};
-use crate::types::{Quantization, SpaceKind};
-use crate::validation::to_size_field;
+use crate::tiles::{TileCoord, TiledSurface};
+use crate::types::{BlendMode, Quantization, SpaceKind};
+use crate::validation::{
+    from_angle_field, from_fixed_point, from_size_field, from_unit_field, to_angle_field,
+    to_fixed_point, to_size_field, to_unit_field,
+};
 
 use super::PaintingPipeline;
 
@@ -29,6 +33,11 @@ impl PaintingPipeline {
         // Clear pending undo captures for new stroke
         self.pending_undo_captures.clear();
         self.captured_tiles.clear();
+        self.stroke_buffer = None;
+        self.stroke_opacity = from_unit_field(to_unit_field(self.brush.preset().opacity));
+
+        #[cfg(feature = "gpu-paint")]
+        self.begin_gpu_stroke();
     }
 
     /// Continue a stroke with new input
@@ -71,6 +80,8 @@ impl PaintingPipeline {
                 ptex_tile: 0,
                 pressure_quant: Quantization::U8,
                 speed_quant: Quantization::U8,
+                tip_hash: self.brush.preset().tip.as_ref().map_or(0, |tip| tip.hash()),
+                opacity: to_unit_field(self.stroke_opacity),
             };
 
             if recorder.start(config, first_dab.x, first_dab.y).is_ok() {
@@ -78,64 +89,152 @@ impl PaintingPipeline {
             }
         }
 
-        // Apply dabs to surface and record them
+        // Apply dabs as they are recorded, so a replay paints the same pixels
         for dab in dabs {
-            self.apply_dab(&dab);
-            self.record_dab(&dab, pressure);
+            let params = dab_params(&dab, pressure);
+            self.apply_dab(&DabOutput {
+                x: from_fixed_point(to_fixed_point(dab.x)),
+                y: from_fixed_point(to_fixed_point(dab.y)),
+                size: from_size_field(params.size),
+                hardness: from_unit_field(params.hardness),
+                opacity: from_unit_field(params.opacity),
+                angle: from_angle_field(params.angle),
+            });
+            if let Some(ref mut recorder) = self.recorder {
+                let _ = recorder.add_dab(dab.x, dab.y, params);
+            }
         }
     }
 
-    /// Apply a dab to the active layer's surface
+    /// Apply a dab to the stroke buffer and refresh the active layer under it
+    ///
+    /// Dabs build up in the stroke buffer at their own opacity (the brush
+    /// flow). The layer shows the pre-stroke pixels with the buffer composited
+    /// on top at the brush opacity, so overlapping dabs never exceed it and
+    /// the capped result is visible while painting.
     pub(crate) fn apply_dab(&mut self, dab: &DabOutput) {
         let radius = dab.size / 2.0;
         debug!(
-            "  apply_dab: pos=({:.1}, {:.1}), radius={:.1}, opacity={:.2}, hardness={:.2}, mode={:?}",
+            "  apply_dab: pos=({:.1}, {:.1}), radius={:.1}, flow={:.2}, hardness={:.2}, mode={:?}",
             dab.x, dab.y, radius, dab.opacity, dab.hardness, self.blend_mode
         );
 
-        // Capture tiles before modification for undo
-        self.capture_tiles_for_dab(dab.x, dab.y, radius);
+        // A rotated stamp tip reaches past the round dab's radius
+        let tip = self.brush.preset().tip.clone();
+        let extent = match tip {
+            Some(_) => radius * (dab.angle.cos().abs() + dab.angle.sin().abs()),
+            None => radius,
+        };
+
+        // Capture tiles before modification for undo (these are also the
+        // base the stroke buffer is composited over)
+        self.capture_tiles_for_dab(dab.x, dab.y, extent);
 
-        // Apply dab to the active layer's surface
-        let color = self.color;
-        let blend_mode = self.blend_mode;
-        if let Some(layer) = self.layers.active_layer_mut() {
-            let result = layer.surface.apply_dab(
+        #[cfg(feature = "gpu-paint")]
+        if tip.is_none() && self.queue_gpu_dab(dab) {
+            return;
+        }
+
+        let (width, height) = (self.width(), self.height());
+        let buffer = self
+            .stroke_buffer
+            .get_or_insert_with(|| TiledSurface::with_default_tile_size(width, height));
+
+        // Erasing accumulates coverage only, so stamp opaque white
+        let color = match self.blend_mode {
+            BlendMode::Normal => self.color,
+            BlendMode::Erase => [1.0; 4],
+        };
+        let result = match tip {
+            Some(tip) => buffer.apply_dab_stamp(
+                dab.x,
+                dab.y,
+                radius,
+                color,
+                dab.opacity,
+                BlendMode::Normal,
+                &tip,
+                dab.angle,
+            ),
+            None => buffer.apply_dab(
                 dab.x,
                 dab.y,
                 radius,
                 color,
                 dab.opacity,
                 dab.hardness,
-                blend_mode,
-            );
-            if let Some((x, y, w, h)) = result {
-                debug!("    -> affected region: ({}, {}) {}x{}", x, y, w, h);
-            } else {
-                debug!("    -> dab outside surface bounds");
-            }
+                BlendMode::Normal,
+            ),
+        };
+
+        if let Some((x, y, w, h)) = result {
+            debug!("    -> affected region: ({}, {}) {}x{}", x, y, w, h);
+            self.composite_stroke_region(x, y, w, h);
+        } else {
+            debug!("    -> dab outside surface bounds");
         }
     }
 
-    /// Record a dab to the stroke recorder
-    pub(crate) fn record_dab(&mut self, dab: &DabOutput, pressure: f32) {
-        if let Some(ref mut recorder) = self.recorder {
-            let params = DabParams {
-                size: to_size_field(dab.size),
-                pressure: (pressure.clamp(0.0, 1.0) * 65535.0) as u16,
-                speed: 0, // TODO: Calculate from input
-                hardness: (dab.hardness * 255.0) as u8,
-                opacity: (dab.opacity * 255.0) as u8,
-                angle: 0,
-                aspect_ratio: 255, // Circular
-            };
+    /// Rewrite a region of the active layer as its pre-stroke pixels with the
+    /// stroke buffer composited over them at the stroke opacity
+    pub(crate) fn composite_stroke_region(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        let opacity = self.stroke_opacity;
+        let blend_mode = self.blend_mode;
+        let Some(buffer) = self.stroke_buffer.as_ref() else {
+            return;
+        };
+        let Some(layer) = self.layers.active_layer_mut() else {
+            return;
+        };
+        let tile_size = layer.surface.tile_size();
+        let surface_width = layer.surface.surface().width;
+
+        for py in y..y + height {
+            for px in x..x + width {
+                let coord = TileCoord {
+                    x: px / tile_size,
+                    y: py / tile_size,
+                };
+                // Tiles are captured before any dab touches them
+                let Some(base_tile) = self.pending_undo_captures.get(&coord) else {
+                    continue;
+                };
+                let tile_width = tile_size.min(surface_width - coord.x * tile_size);
+                let index = ((py % tile_size) * tile_width + px % tile_size) as usize;
+                let (Some(&base), Some(stroke)) =
+                    (base_tile.get(index), buffer.surface().get_pixel(px, py))
+                else {
+                    continue;
+                };
 
-            let _ = recorder.add_dab(dab.x, dab.y, params);
+                let coverage = stroke[3] * opacity;
+                let pixel = match blend_mode {
+                    BlendMode::Normal => {
+                        let inv_coverage = 1.0 - coverage;
+                        [
+                            stroke[0] * opacity + base[0] * inv_coverage,
+                            stroke[1] * opacity + base[1] * inv_coverage,
+                            stroke[2] * opacity + base[2] * inv_coverage,
+                            coverage + base[3] * inv_coverage,
+                        ]
+                    }
+                    BlendMode::Erase => base.map(|channel| channel * (1.0 - coverage)),
+                };
+                layer.surface.surface_mut().set_pixel(px, py, pixel);
+            }
         }
+
+        layer.surface.mark_region_dirty(x, y, width, height);
     }
 
     /// End the current stroke
+    ///
+    /// The layer already holds the stroke composited at the brush opacity, so
+    /// finishing bakes it by dropping the stroke buffer.
     pub fn end_stroke(&mut self) {
+        #[cfg(feature = "gpu-paint")]
+        self.end_gpu_stroke(true);
+
         if let Some(mut recorder) = self.recorder.take() {
             if let Ok(packets) = recorder.finish() {
                 for packet in packets {
@@ -152,6 +251,7 @@ impl PaintingPipeline {
                 stroke_id,
                 layer_id,
                 tiles: std::mem::take(&mut self.pending_undo_captures),
+                canvas: None,
             };
             self.undo_stack.push(entry);
 
@@ -169,6 +269,7 @@ impl PaintingPipeline {
         }
 
         self.captured_tiles.clear();
+        self.stroke_buffer = None;
         self.brush.end_stroke();
         self.current_stroke_id = None;
         self.current_space_id = None;
@@ -180,6 +281,9 @@ impl PaintingPipeline {
     /// Note: The visual changes on the surface are NOT reverted.
     /// For proper undo, use the undo() method after canceling.
     pub fn cancel_stroke(&mut self) {
+        #[cfg(feature = "gpu-paint")]
+        self.end_gpu_stroke(false);
+
         if let Some(mut recorder) = self.recorder.take() {
             let _ = recorder.abort("Cancelled".to_string());
         }
@@ -187,6 +291,7 @@ impl PaintingPipeline {
         // Clear pending undo captures (don't save to undo stack)
         self.pending_undo_captures.clear();
         self.captured_tiles.clear();
+        self.stroke_buffer = None;
 
         self.brush.end_stroke();
         self.current_stroke_id = None;
@@ -198,3 +303,16 @@ impl PaintingPipeline {
         self.current_stroke_id.is_some()
     }
 }
+
+/// Recorded form of a dab
+fn dab_params(dab: &DabOutput, pressure: f32) -> DabParams {
+    DabParams {
+        size: to_size_field(dab.size),
+        pressure: (pressure.clamp(0.0, 1.0) * 65535.0) as u16,
+        speed: 0, // TODO: Calculate from input
+        hardness: to_unit_field(dab.hardness),
+        opacity: to_unit_field(dab.opacity),
+        angle: to_angle_field(dab.angle),
+        aspect_ratio: 255, // Circular
+    }
+}
@@ -66,6 +66,13 @@ impl PaintingPipeline {
             .compute_tiles_bounding_box(tiles)
     }
 
+    /// Merge dirty tiles into upload rects (see [`TiledSurface::coalesce_tile_rects`])
+    ///
+    /// Returns (x, y, width, height) rects in pixel coordinates.
+    pub fn coalesce_tile_rects(&self, tiles: &[TileCoord]) -> Vec<(u32, u32, u32, u32)> {
+        self.layers.composited_surface().coalesce_tile_rects(tiles)
+    }
+
     /// Clear the active layer's surface to a solid color
     pub fn clear(&mut self, color: [f32; 4]) {
         if let Some(layer) = self.layers.active_layer_mut() {
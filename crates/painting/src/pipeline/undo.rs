@@ -14,8 +14,36 @@ pub struct UndoEntry {
     pub stroke_id: u64,
     /// Layer ID this stroke was painted on
     pub layer_id: u32,
-    /// Captured tile data (tile coord -> pixel data)
+    /// Captured tile data (tile coord -> pixel data, or a single pixel for a
+    /// tile that was one uniform color)
     pub tiles: HashMap<TileCoord, Vec<[f32; 4]>>,
+    /// Whole canvas before a resize (restores size and every layer)
+    pub canvas: Option<CanvasSnapshot>,
+}
+
+impl UndoEntry {
+    /// Bytes of pixel data the entry holds
+    fn bytes(&self) -> usize {
+        let tiles: usize = self.tiles.values().map(Vec::len).sum();
+        let canvas: usize = self
+            .canvas
+            .iter()
+            .flat_map(|canvas| &canvas.layers)
+            .map(|(_, pixels)| pixels.len())
+            .sum();
+        (tiles + canvas) * size_of::<[f32; 4]>()
+    }
+}
+
+/// Canvas size and layer contents captured before a resize
+#[derive(Clone)]
+pub struct CanvasSnapshot {
+    pub width: u32,
+    pub height: u32,
+    /// Content origin before the resize
+    pub origin: (i32, i32),
+    /// Full pixel data per layer ID
+    pub layers: Vec<(u32, Vec<[f32; 4]>)>,
 }
 
 impl PaintingPipeline {
@@ -65,7 +93,7 @@ impl PaintingPipeline {
 
     /// Check if undo is available
     pub fn can_undo(&self) -> bool {
-        !self.undo_stack.is_empty()
+        !self.undo_stack.is_empty() || self.floating.is_some()
     }
 
     /// Get the number of undo levels available
@@ -73,10 +101,20 @@ impl PaintingPipeline {
         self.undo_stack.len()
     }
 
+    /// Bytes of pixel data held by the undo stack
+    pub fn undo_bytes(&self) -> usize {
+        self.undo_stack.iter().map(UndoEntry::bytes).sum()
+    }
+
     /// Undo the last stroke
     ///
+    /// A floating selection is cancelled first, counting as the undo.
     /// Returns true if an undo was performed, false if no undo available
     pub fn undo(&mut self) -> bool {
+        if self.cancel_selection() {
+            return true;
+        }
+
         let Some(entry) = self.undo_stack.pop() else {
             debug!("Undo: no entries available");
             return false;
@@ -89,6 +127,11 @@ impl PaintingPipeline {
             entry.tiles.len()
         );
 
+        if let Some(snapshot) = entry.canvas {
+            self.restore_canvas(snapshot);
+            return true;
+        }
+
         // Find the layer this stroke was on and restore tiles
         let layer_id = entry.layer_id;
         if let Some(layer) = self.layers.layer_mut(layer_id) {
@@ -104,31 +147,52 @@ impl PaintingPipeline {
 }
 
 /// Restore a tile's pixel data from an undo entry on a specific surface
-fn restore_tile(surface: &mut TiledSurface, coord: TileCoord, tile_data: &[[f32; 4]]) {
+pub(super) fn restore_tile(surface: &mut TiledSurface, coord: TileCoord, tile_data: &[[f32; 4]]) {
     let tile_size = surface.tile_size();
+    let cpu_surface = surface.surface_mut();
+    let (width, height) = (cpu_surface.width, cpu_surface.height);
+    restore_tile_pixels(
+        cpu_surface.pixels_mut(),
+        (width, height),
+        tile_size,
+        coord,
+        tile_data,
+    );
+
+    // Mark the tile as dirty for GPU upload
+    surface.mark_dirty(coord.x * tile_size, coord.y * tile_size);
+}
+
+/// Write a tile's pixel data from an undo entry into row-major pixels
+pub(super) fn restore_tile_pixels(
+    pixels: &mut [[f32; 4]],
+    (width, height): (u32, u32),
+    tile_size: u32,
+    coord: TileCoord,
+    tile_data: &[[f32; 4]],
+) {
     let tile_start_x = coord.x * tile_size;
     let tile_start_y = coord.y * tile_size;
 
-    let surface_width = surface.surface().width;
-    let surface_height = surface.surface().height;
-
     // Calculate actual tile dimensions (may be smaller at edges)
-    let tile_width = tile_size.min(surface_width.saturating_sub(tile_start_x));
-    let tile_height = tile_size.min(surface_height.saturating_sub(tile_start_y));
-
-    // Write pixels back
+    let tile_width = tile_size.min(width.saturating_sub(tile_start_x));
+    let tile_height = tile_size.min(height.saturating_sub(tile_start_y));
+
+    // Write pixels back (a single pixel stands for a uniform tile)
+    let uniform = match tile_data {
+        [color] => Some(*color),
+        _ => None,
+    };
     let mut idx = 0;
     for dy in 0..tile_height {
         for dx in 0..tile_width {
-            if idx < tile_data.len() {
-                let x = tile_start_x + dx;
-                let y = tile_start_y + dy;
-                surface.surface_mut().set_pixel(x, y, tile_data[idx]);
-                idx += 1;
-            }
+            let Some(pixel) = uniform.or_else(|| tile_data.get(idx).copied()) else {
+                continue;
+            };
+            let x = (tile_start_x + dx) as usize;
+            let y = (tile_start_y + dy) as usize;
+            pixels[y * width as usize + x] = pixel;
+            idx += 1;
         }
     }
-
-    // Mark the tile as dirty for GPU upload
-    surface.mark_dirty(tile_start_x, tile_start_y);
 }
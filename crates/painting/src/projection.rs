@@ -78,6 +78,10 @@ fn arbitrary_perpendicular(normal: Vec3) -> Vec3 {
 /// * `tex_pos` - Position in texture space (UV or Ptex local coordinates)
 /// * `surface_normal` - Surface normal at hit point
 /// * `world_to_texel_scale` - Conversion factor from world units to texture pixels
+///   at the mesh's average texel density
+/// * `face_density` - Texel density of the hit face relative to the mesh
+///   average (1.0 = average). UV charts packed at different densities need
+///   proportionally larger or smaller dabs to cover the same world area.
 ///
 /// # Returns
 /// A `ProjectedDab` with texture-space position, size, and shape parameters.
@@ -92,10 +96,11 @@ pub fn project_brush_to_surface(
     tex_pos: Vec2,
     _surface_normal: Vec3,
     world_to_texel_scale: f32,
+    face_density: f32,
 ) -> ProjectedDab {
     // For normal-based projection, the brush is always circular in tangent space.
-    // The size is converted from world units to texture pixels.
-    let texel_radius = brush_world_radius * world_to_texel_scale;
+    // The size is converted from world units to the hit face's texture pixels.
+    let texel_radius = brush_world_radius * world_to_texel_scale * face_density;
 
     ProjectedDab {
         tex_pos,
@@ -273,6 +278,47 @@ pub fn canvas_uv_to_ray(
     (ray_origin, ray_direction)
 }
 
+/// Find where the line of sight from the camera to a world point crosses the canvas.
+///
+/// This is the inverse of [`canvas_uv_to_ray`]: every point along a ray maps
+/// back to the canvas UV the ray was cast through.
+///
+/// # Returns
+/// Canvas UV in 0-1 range, or `None` if the point is not behind the canvas as
+/// seen from the camera or the crossing lies outside the canvas bounds.
+pub fn world_to_canvas_uv(
+    camera_pos: Vec3,
+    world_pos: Vec3,
+    canvas_center: Vec3,
+    canvas_right: Vec3,
+    canvas_up: Vec3,
+    canvas_params: &CanvasPlaneParams,
+) -> Option<Vec2> {
+    let normal = canvas_right.cross(canvas_up);
+    let dir = world_pos - camera_pos;
+    let denom = dir.dot(normal);
+    if denom.abs() < 1e-8 {
+        return None;
+    }
+
+    let t = (canvas_center - camera_pos).dot(normal) / denom;
+    if t <= 0.0 {
+        return None;
+    }
+
+    let offset = camera_pos + dir * t - canvas_center;
+    let uv = Vec2::new(
+        offset.dot(canvas_right) / canvas_params.world_size.0 + 0.5,
+        0.5 - offset.dot(canvas_up) / canvas_params.world_size.1,
+    );
+
+    if (0.0..1.0).contains(&uv.x) && (0.0..1.0).contains(&uv.y) {
+        Some(uv)
+    } else {
+        None
+    }
+}
+
 /// Convert a pixel coordinate on the canvas to UV.
 ///
 /// # Arguments
@@ -378,6 +424,7 @@ mod tests {
             Vec2::new(0.5, 0.5), // center of texture
             Vec3::Y,             // up normal
             100.0,               // 100 pixels per world unit
+            1.0,                 // average density face
         );
 
         // Brush should be circular (aspect ratio 1.0)
@@ -387,6 +434,16 @@ mod tests {
         assert!((dab.size - 200.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_project_brush_face_density() {
+        let sparse = project_brush_to_surface(0.5, Vec3::ZERO, Vec2::ZERO, Vec3::Y, 100.0, 0.5);
+        let dense = project_brush_to_surface(0.5, Vec3::ZERO, Vec2::ZERO, Vec3::Y, 100.0, 2.0);
+
+        // 4x the texels per world unit needs a 4x larger dab for the same world size
+        assert!((sparse.size - 50.0).abs() < 1e-6);
+        assert!((dense.size - 200.0).abs() < 1e-6);
+    }
+
     #[test]
     fn test_uv_scale_estimation() {
         // Unit triangle in world space
@@ -467,6 +524,46 @@ mod tests {
         assert!((dir - Vec3::Z).length() < 1e-6); // Points toward +Z
     }
 
+    #[test]
+    fn test_world_to_canvas_uv_inverts_ray() {
+        let camera_pos = Vec3::new(0.0, 0.0, 5.0);
+        let canvas_center = Vec3::new(0.0, 0.0, 3.0);
+        let right = Vec3::X;
+        let up = Vec3::Y;
+        let params = CanvasPlaneParams {
+            resolution: (256, 256),
+            world_size: (2.0, 2.0),
+        };
+
+        let canvas_uv = Vec2::new(0.3, 0.8);
+        let (origin, dir) =
+            canvas_uv_to_ray(camera_pos, canvas_uv, canvas_center, right, up, &params);
+
+        // Points in front of and behind the canvas map back to the same UV
+        for dist in [1.0, 4.0, 9.0] {
+            let uv = world_to_canvas_uv(
+                camera_pos,
+                origin + dir * dist,
+                canvas_center,
+                right,
+                up,
+                &params,
+            )
+            .unwrap();
+            assert!((uv - canvas_uv).length() < 1e-5);
+        }
+
+        // Behind the camera and outside the canvas bounds
+        let behind = camera_pos + Vec3::Z;
+        assert!(
+            world_to_canvas_uv(camera_pos, behind, canvas_center, right, up, &params).is_none()
+        );
+        let outside = Vec3::new(10.0, 0.0, 0.0);
+        assert!(
+            world_to_canvas_uv(camera_pos, outside, canvas_center, right, up, &params).is_none()
+        );
+    }
+
     #[test]
     fn test_pixel_to_canvas_uv() {
         let resolution = (256, 256);
@@ -3,6 +3,7 @@
 use tracing::debug;
 
 use super::TiledSurface;
+use crate::brush_tip::BrushTip;
 use crate::types::BlendMode;
 
 impl TiledSurface {
@@ -57,6 +58,36 @@ impl TiledSurface {
         blend_mode: BlendMode,
         angle: f32,
         aspect_ratio: f32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        self.apply_dab_ellipse_clipped(
+            center_x,
+            center_y,
+            radius,
+            color,
+            opacity,
+            hardness,
+            blend_mode,
+            angle,
+            aspect_ratio,
+            |_, _| true,
+        )
+    }
+
+    /// Apply an elliptical dab only to pixels whose center `(x, y)` passes
+    /// `clip`. Otherwise the same as [`apply_dab_ellipse`](Self::apply_dab_ellipse).
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn apply_dab_ellipse_clipped(
+        &mut self,
+        center_x: f32,
+        center_y: f32,
+        radius: f32,
+        color: [f32; 4],
+        opacity: f32,
+        hardness: f32,
+        blend_mode: BlendMode,
+        angle: f32,
+        aspect_ratio: f32,
+        clip: impl Fn(f32, f32) -> bool,
     ) -> Option<(u32, u32, u32, u32)> {
         debug!(
             "TiledSurface::apply_dab_ellipse: center=({:.1}, {:.1}), radius={:.1}, aspect={:.2}, angle={:.2}rad, opacity={:.2}, hardness={:.2}, mode={:?}",
@@ -109,6 +140,10 @@ impl TiledSurface {
         // Apply dab to each pixel in the bounding box
         for py in y_min..y_max {
             for px in x_min..x_max {
+                if !clip(px as f32 + 0.5, py as f32 + 0.5) {
+                    continue;
+                }
+
                 // Calculate distance from center (use pixel center)
                 let dx = (px as f32 + 0.5) - center_x;
                 let dy = (py as f32 + 0.5) - center_y;
@@ -156,6 +191,63 @@ impl TiledSurface {
 
         Some((x_min, y_min, width, height))
     }
+
+    /// Apply a stamp dab: the brush tip mask, rotated by `angle` (radians,
+    /// counter-clockwise) and scaled to `radius`, sets each pixel's coverage.
+    /// Returns the affected region like [`apply_dab`](Self::apply_dab).
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_dab_stamp(
+        &mut self,
+        center_x: f32,
+        center_y: f32,
+        radius: f32,
+        color: [f32; 4],
+        opacity: f32,
+        blend_mode: BlendMode,
+        tip: &BrushTip,
+        angle: f32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        if radius <= 0.0 || opacity <= 0.0 {
+            return None;
+        }
+
+        let (sin_a, cos_a) = angle.sin_cos();
+        // Half extent of the rotated square tip
+        let half = radius * (cos_a.abs() + sin_a.abs());
+
+        let x_min = ((center_x - half).floor().max(0.0) as u32).min(self.surface.width);
+        let y_min = ((center_y - half).floor().max(0.0) as u32).min(self.surface.height);
+        let x_max = ((center_x + half).ceil().max(0.0) as u32).min(self.surface.width);
+        let y_max = ((center_y + half).ceil().max(0.0) as u32).min(self.surface.height);
+        if x_min >= x_max || y_min >= y_max {
+            return None;
+        }
+
+        let diameter = radius * 2.0;
+        for py in y_min..y_max {
+            for px in x_min..x_max {
+                let dx = px as f32 + 0.5 - center_x;
+                let dy = py as f32 + 0.5 - center_y;
+                // Into tip space (surface Y points down, so CCW flips sin)
+                let u = (dx * cos_a - dy * sin_a) / radius;
+                let v = (dx * sin_a + dy * cos_a) / radius;
+                let mask = tip.sample(u, v, diameter);
+                if mask <= 0.0 {
+                    continue;
+                }
+                match blend_mode {
+                    BlendMode::Normal => self.surface.blend_pixel(px, py, color, opacity * mask),
+                    BlendMode::Erase => self.surface.erase_pixel(px, py, opacity * mask),
+                }
+            }
+        }
+
+        let width = x_max - x_min;
+        let height = y_max - y_min;
+        self.mark_region_dirty(x_min, y_min, width, height);
+
+        Some((x_min, y_min, width, height))
+    }
 }
 
 /// Calculate falloff based on hardness
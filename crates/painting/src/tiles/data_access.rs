@@ -1,7 +1,13 @@
 //! Tile data access and region queries
 
+use std::collections::{BTreeMap, HashMap};
+
 use super::{TileCoord, TiledSurface};
 
+/// Fraction of a tile row that must be dirty before the row's dirty span is
+/// uploaded as one rect, clean tiles inside it included
+pub const ROW_COALESCE_FRACTION: f32 = 0.3;
+
 impl TiledSurface {
     /// Get tile data for upload (returns pixel data for a tile)
     /// The returned Vec has tile_size * tile_size elements (or less for edge tiles)
@@ -103,4 +109,60 @@ impl TiledSurface {
             None
         }
     }
+
+    /// Rects (x, y, width, height) in pixels covering `tiles`, for uploading
+    /// dirty tiles in fewer texture writes
+    ///
+    /// A tile row with more than [`ROW_COALESCE_FRACTION`] of its tiles dirty
+    /// becomes one rect spanning its dirty tiles; otherwise each tile is its
+    /// own rect. Rects spanning the same tile columns in consecutive rows are
+    /// then stacked into one.
+    pub fn coalesce_tile_rects(&self, tiles: &[TileCoord]) -> Vec<(u32, u32, u32, u32)> {
+        let mut rows: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for tile in tiles {
+            rows.entry(tile.y).or_default().push(tile.x);
+        }
+
+        // Tile-space rects as (first column, last column, first row, last row)
+        let mut rects: Vec<(u32, u32, u32, u32)> = Vec::new();
+        // Rects ending on the previous row, by column span
+        let mut open: HashMap<(u32, u32), usize> = HashMap::new();
+        for (row, mut columns) in rows {
+            columns.sort_unstable();
+            columns.dedup();
+            let spans: Vec<(u32, u32)> =
+                if columns.len() as f32 > ROW_COALESCE_FRACTION * self.tiles_x as f32 {
+                    vec![(columns[0], columns[columns.len() - 1])]
+                } else {
+                    columns.iter().map(|&column| (column, column)).collect()
+                };
+
+            let mut next_open = HashMap::with_capacity(spans.len());
+            for span in spans {
+                let index = match open.get(&span) {
+                    Some(&index) if rects[index].3 + 1 == row => {
+                        rects[index].3 = row;
+                        index
+                    }
+                    _ => {
+                        rects.push((span.0, span.1, row, row));
+                        rects.len() - 1
+                    }
+                };
+                next_open.insert(span, index);
+            }
+            open = next_open;
+        }
+
+        rects
+            .into_iter()
+            .map(|(first_column, last_column, first_row, last_row)| {
+                let x = first_column * self.tile_size;
+                let y = first_row * self.tile_size;
+                let x_end = ((last_column + 1) * self.tile_size).min(self.surface.width);
+                let y_end = ((last_row + 1) * self.tile_size).min(self.surface.height);
+                (x, y, x_end - x, y_end - y)
+            })
+            .collect()
+    }
 }
@@ -10,6 +10,7 @@ use std::collections::HashSet;
 
 // Re-export the calculate_hardness_falloff function for tests
 pub use dab_application::calculate_hardness_falloff;
+pub use data_access::ROW_COALESCE_FRACTION;
 
 /// Tile coordinates
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -329,4 +330,47 @@ mod tests {
         // For a 45-degree rotated ellipse, width and height should be similar
         // (not as extreme as unrotated would be)
     }
+
+    fn tiles(coords: &[(u32, u32)]) -> Vec<TileCoord> {
+        coords.iter().map(|&(x, y)| TileCoord { x, y }).collect()
+    }
+
+    #[test]
+    fn test_small_dab_uploads_few_tiles() {
+        let mut surface = TiledSurface::with_default_tile_size(4096, 4096);
+        surface.apply_dab(1000.0, 1000.0, 10.0, [1.0; 4], 1.0, 1.0, BlendMode::Normal);
+        let dirty = surface.take_dirty_tiles();
+
+        let rects = surface.coalesce_tile_rects(&dirty);
+        let pixels: u32 = rects.iter().map(|&(_, _, w, h)| w * h).sum();
+        let tile_pixels = surface.tile_size() * surface.tile_size();
+        assert!(pixels <= 4 * tile_pixels, "uploads {pixels} pixels");
+    }
+
+    #[test]
+    fn test_coalesce_sparse_row_keeps_tiles() {
+        // 8 tiles per row; 2 dirty is under the threshold
+        let surface = TiledSurface::new(1024, 256, 128);
+        let rects = surface.coalesce_tile_rects(&tiles(&[(1, 0), (6, 0)]));
+        assert_eq!(rects, vec![(128, 0, 128, 128), (768, 0, 128, 128)]);
+    }
+
+    #[test]
+    fn test_coalesce_dense_rows_into_one_rect() {
+        // 3 of 8 tiles dirty in each of two rows: one span, stacked
+        let surface = TiledSurface::new(1024, 256, 128);
+        let rects =
+            surface.coalesce_tile_rects(&tiles(&[(2, 0), (3, 0), (5, 0), (5, 1), (2, 1), (4, 1)]));
+        assert_eq!(rects, vec![(256, 0, 512, 256)]);
+    }
+
+    #[test]
+    fn test_coalesce_stacks_columns_and_clips_edges() {
+        // 11 tiles per row; the last column is 20px wide and the last row 44px
+        let surface = TiledSurface::new(1300, 300, 128);
+        let rects = surface.coalesce_tile_rects(&tiles(&[(10, 1), (10, 2), (0, 2)]));
+        assert_eq!(rects.len(), 2);
+        assert!(rects.contains(&(1280, 128, 20, 172)));
+        assert!(rects.contains(&(0, 256, 128, 44)));
+    }
 }
@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 pub enum SpaceKind {
     CanvasPlane = 0,
     MeshPtex = 1,
+    /// Vertex colors of a mesh (see `vertex_color`)
+    MeshVertexColors = 2,
 }
 
 /// Blend modes for painting
@@ -62,6 +64,20 @@ pub struct StrokeHeader {
     pub pressure_quant: Quantization,
     /// Speed quantization level
     pub speed_quant: Quantization,
+    /// Content hash of the stamp brush tip (0 = round brush)
+    #[serde(default)]
+    pub tip_hash: u64,
+    /// Brush opacity 0..255, capping the stroke's overlapping dabs
+    #[serde(default = "full_opacity")]
+    pub opacity: u8,
+    /// Lamport clock of the stroke in a shared session (0 outside one)
+    #[serde(default)]
+    pub lamport: u64,
+}
+
+/// Opacity for packets recorded before stroke opacity was added.
+fn full_opacity() -> u8 {
+    u8::MAX
 }
 
 /// A single dab in a stroke
@@ -100,6 +116,137 @@ pub struct StrokePacket {
     pub dabs: Vec<Dab>,
 }
 
+/// An image import that replaced a layer's contents
+///
+/// The pixels themselves are not logged; `image_hash` identifies the source
+/// image so a replay can fetch it and redo the import in stroke order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportRecord {
+    /// Target space (canvas plane ID)
+    pub space_id: u32,
+    /// Stroke ID of the import (shares the stroke ID sequence)
+    pub stroke_id: u64,
+    /// Layer whose contents were replaced
+    pub layer_id: u32,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Canvas dimensions the image was fitted to
+    pub width: u32,
+    pub height: u32,
+    /// Content hash of the source image file (see [`content_hash`])
+    pub image_hash: u64,
+}
+
+/// A canvas resize or crop, replayed in stroke order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResizeRecord {
+    /// Target space (canvas plane ID)
+    pub space_id: u32,
+    /// Stroke ID of the resize (shares the stroke ID sequence)
+    pub stroke_id: u64,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// New canvas dimensions
+    pub width: u32,
+    pub height: u32,
+    /// Where the old content's top-left corner landed (negative when cropped)
+    pub offset_x: i32,
+    pub offset_y: i32,
+}
+
+/// A committed pixel selection transform, replayed in stroke order
+///
+/// Like imports, the lifted pixels are not logged; `region_hash` identifies
+/// them so a replay can check it lifted the same content before applying
+/// `matrix`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionTransformRecord {
+    /// Target space (canvas plane ID)
+    pub space_id: u32,
+    /// Stroke ID of the transform (shares the stroke ID sequence)
+    pub stroke_id: u64,
+    /// Layer the pixels were lifted from and committed to
+    pub layer_id: u32,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Bounding box of the lifted pixels (x, y, width, height)
+    pub source_bounds: [u32; 4],
+    /// Content hash of the lifted pixels (see [`content_hash`])
+    pub region_hash: u64,
+    /// Column-major 2x3 affine from source to destination canvas pixels
+    pub matrix: [f32; 6],
+}
+
+/// Shape of a gradient fill
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum GradientKind {
+    /// Bands perpendicular to the start-end line
+    #[default]
+    Linear = 0,
+    /// Rings around the start point, reaching the last color at the end point
+    Radial = 1,
+}
+
+/// A bucket fill, replayed in stroke order
+///
+/// Only the parameters are logged: replaying the fill on the same pixels
+/// reproduces the same region.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillRecord {
+    /// Target space (canvas plane ID)
+    pub space_id: u32,
+    /// Stroke ID of the fill (shares the stroke ID sequence)
+    pub stroke_id: u64,
+    /// Layer that was filled
+    pub layer_id: u32,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Seed pixel
+    pub x: u32,
+    pub y: u32,
+    /// Largest perceptual distance from the seed color that still fills
+    pub tolerance: f32,
+    /// Fill only pixels connected to the seed (false replaces matches everywhere)
+    pub contiguous: bool,
+    /// Blend mode
+    pub blend_mode: BlendMode,
+    /// Fill color in wgpu-native Rgba16Float compatible format [r, g, b, a]
+    pub color: [f32; 4],
+}
+
+/// A gradient rendered over a layer, replayed in stroke order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradientRecord {
+    /// Target space (canvas plane ID)
+    pub space_id: u32,
+    /// Stroke ID of the gradient (shares the stroke ID sequence)
+    pub stroke_id: u64,
+    /// Layer the gradient was rendered into
+    pub layer_id: u32,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Start and end points in canvas pixels
+    pub start: [f32; 2],
+    pub end: [f32; 2],
+    /// Gradient shape
+    pub kind: GradientKind,
+    /// Evenly spaced color stops [r, g, b, a]
+    pub colors: Vec<[f32; 4]>,
+    /// Blend mode
+    pub blend_mode: BlendMode,
+}
+
+/// 64-bit FNV-1a hash of `bytes`, stable across platforms and releases
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
 // ============================================================================
 // Mesh Painting Types
 // ============================================================================
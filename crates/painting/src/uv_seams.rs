@@ -0,0 +1,559 @@
+//! Seam-aware bleeding for UV-atlas mesh paint
+//!
+//! A mesh edge on a UV seam appears twice in the atlas, once in each chart,
+//! so a dab near it only paints the chart it landed in and strokes crossing
+//! the seam leave a gap. [`UvSeams`] finds these edges with the half-edge
+//! structure once per mesh and keeps both copies of each, in atlas pixels.
+//! Two passes close the gap:
+//!
+//! - [`UvSeams::apply_dab`] repeats a dab that reaches a seam edge in the
+//!   adjacent chart, moved by the similarity transform that takes one copy of
+//!   the edge onto the other. The repeat is clipped to the strip beside the
+//!   other copy, on its face's side, so it can't spill into unrelated charts.
+//! - [`UvSeams::pad_dirty_tiles`] grows the charts in the surface's dirty
+//!   tiles by its seam padding into the margins no face covers, so filtering
+//!   and mip levels near chart borders don't mix in background texels.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::half_edge::{FaceId, HalfEdgeMesh};
+use crate::mesh_storage_conversion::{
+    CONSERVATIVE_RASTER_PX, barycentric, closest_on_segment, uv_to_pixel,
+};
+use crate::mesh_surface::MeshUvSurface;
+use crate::tiles::TileCoord;
+use crate::types::BlendMode;
+
+/// Size in pixels of the grid cells seam edges are bucketed in
+const GRID_CELL_PX: f32 = 32.0;
+
+/// Seam edges shorter than this (in pixels) on either side are skipped
+const MIN_EDGE_PX: f32 = 1e-3;
+
+/// Chart id of triangles that have no half-edge face
+const NO_CHART: u32 = u32::MAX;
+
+/// One copy of a seam edge and where the other copy lies, in atlas pixels
+struct SeamSide {
+    /// Chart the copy belongs to
+    chart: u32,
+    /// Edge endpoints in this chart
+    from: [Vec2; 2],
+    /// The same endpoints in the adjacent chart
+    to: [Vec2; 2],
+    /// A point on the adjacent face's side of `to`
+    to_inside: Vec2,
+    /// The charts have opposite winding, so the transform mirrors
+    mirrored: bool,
+}
+
+impl SeamSide {
+    /// Map an atlas position from this copy of the edge to the other
+    fn transform(&self, p: Vec2) -> Vec2 {
+        let local = complex_div(p - self.from[0], self.from[1] - self.from[0]);
+        let local = if self.mirrored {
+            Vec2::new(local.x, -local.y)
+        } else {
+            local
+        };
+        self.to[0] + complex_mul(local, self.to[1] - self.to[0])
+    }
+
+    /// Scale and rotation of [`transform`](Self::transform)
+    fn scale_and_rotation(&self) -> (f32, f32) {
+        let (from, to) = (self.from[1] - self.from[0], self.to[1] - self.to[0]);
+        let scale = to.length() / from.length();
+        let rotation = if self.mirrored {
+            to.to_angle() + from.to_angle()
+        } else {
+            to.to_angle() - from.to_angle()
+        };
+        (scale, rotation)
+    }
+
+    /// Whether a pixel center lies beside the other copy on its face's side
+    fn in_target_strip(&self, x: f32, y: f32) -> bool {
+        let p = Vec2::new(x, y);
+        let edge = self.to[1] - self.to[0];
+        let along = (p - self.to[0]).dot(edge) / edge.length_squared();
+        let side = edge.perp_dot(p - self.to[0]);
+        let inside = edge.perp_dot(self.to_inside - self.to[0]);
+        (0.0..=1.0).contains(&along) && side * inside >= 0.0
+    }
+}
+
+/// Seam edge lookup and chart coverage of one mesh's UV atlas
+pub struct UvSeams {
+    sides: Vec<SeamSide>,
+    /// Indices into `sides` by grid cell
+    grid: HashMap<(i32, i32), Vec<usize>>,
+    /// Chart of each source triangle
+    face_charts: Vec<u32>,
+    /// Atlas pixels covered by a face (including its conservative border)
+    covered: Vec<bool>,
+    width: u32,
+    height: u32,
+}
+
+impl UvSeams {
+    /// Chart coverage of an atlas without seam edges: only padding applies.
+    ///
+    /// `face_uvs` holds the UVs of every triangle of the mesh.
+    pub fn new(face_uvs: &[[Vec2; 3]], resolution: (u32, u32)) -> Self {
+        let (width, height) = resolution;
+        let mut covered = vec![false; width as usize * height as usize];
+        for uvs in face_uvs {
+            rasterize_coverage(
+                uvs.map(|uv| uv_to_pixel(uv, width, height)),
+                width,
+                height,
+                &mut covered,
+            );
+        }
+        Self {
+            sides: Vec::new(),
+            grid: HashMap::new(),
+            face_charts: vec![NO_CHART; face_uvs.len()],
+            covered,
+            width,
+            height,
+        }
+    }
+
+    /// Chart coverage and seam edges of an atlas.
+    ///
+    /// `face_uvs` holds the UVs of every triangle of the source mesh, and
+    /// `source_faces` the source triangle of every face of `mesh` (see
+    /// [`HalfEdgeMesh::from_bevy_mesh_with_source_faces`]).
+    pub fn with_seam_edges(
+        face_uvs: &[[Vec2; 3]],
+        resolution: (u32, u32),
+        mesh: &HalfEdgeMesh,
+        source_faces: &[u32],
+    ) -> Self {
+        let mut seams = Self::new(face_uvs, resolution);
+        let (width, height) = resolution;
+        let source_face = |face: FaceId| {
+            source_faces
+                .get(face.0 as usize)
+                .map(|&t| t as usize)
+                .filter(|&t| t < face_uvs.len())
+        };
+
+        // Charts are the face regions bounded by seams
+        let mut face_chart: HashMap<FaceId, u32> = HashMap::new();
+        for face in mesh.faces() {
+            if face_chart.contains_key(&face.id) {
+                continue;
+            }
+            let chart = face_chart.len() as u32;
+            for member in mesh.face_region(face.id) {
+                face_chart.insert(member, chart);
+                if let Some(t) = source_face(member) {
+                    seams.face_charts[t] = chart;
+                }
+            }
+        }
+
+        for he in mesh.half_edges() {
+            if !mesh.is_seam_edge(he.id) {
+                continue;
+            }
+            let (Some(face), Some(twin)) = (he.face, he.twin) else {
+                continue;
+            };
+            let Some(other_face) = mesh.half_edge(twin).and_then(|twin| twin.face) else {
+                continue;
+            };
+            let (Some(t), Some(other_t)) = (source_face(face), source_face(other_face)) else {
+                continue;
+            };
+            let corner = |face, he_id| {
+                mesh.get_face_half_edges(face)
+                    .iter()
+                    .position(|&id| id == he_id)
+                    .filter(|&k| k < 3)
+            };
+            let (Some(k), Some(other_k)) = (corner(face, he.id), corner(other_face, twin)) else {
+                continue;
+            };
+
+            let pixels = |t: usize| face_uvs[t].map(|uv| uv_to_pixel(uv, width, height));
+            let (a, b) = (pixels(t), pixels(other_t));
+            let from = [a[k], a[(k + 1) % 3]];
+            // The twin runs the other way, so its corners swap
+            let to = [b[(other_k + 1) % 3], b[other_k]];
+            if from[0].distance(from[1]) < MIN_EDGE_PX || to[0].distance(to[1]) < MIN_EDGE_PX {
+                continue;
+            }
+            let (apex, other_apex) = (a[(k + 2) % 3], b[(other_k + 2) % 3]);
+            let apex_side = (from[1] - from[0]).perp_dot(apex - from[0]);
+            let other_side = (to[1] - to[0]).perp_dot(other_apex - to[0]);
+
+            seams.sides.push(SeamSide {
+                chart: face_chart[&face],
+                from,
+                to,
+                to_inside: other_apex,
+                // Without mirroring this face would land on the other face's side
+                mirrored: apex_side * other_side > 0.0,
+            });
+        }
+
+        for (index, side) in seams.sides.iter().enumerate() {
+            let (min, max) = (
+                side.from[0].min(side.from[1]),
+                side.from[0].max(side.from[1]),
+            );
+            for cell in grid_cells(min, max) {
+                seams.grid.entry(cell).or_default().push(index);
+            }
+        }
+        seams
+    }
+
+    /// Number of seam edge copies (two per seam edge)
+    pub fn seam_edge_count(&self) -> usize {
+        self.sides.len()
+    }
+
+    /// Atlas resolution the lookup was built for
+    pub fn resolution(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Repeat a dab across every seam edge within its radius.
+    ///
+    /// Takes the same arguments as [`MeshUvSurface::apply_dab`] plus the
+    /// triangle the dab landed on; only seams of that triangle's chart are
+    /// crossed. The dab itself must be applied separately. Returns the number
+    /// of repeats applied.
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_dab(
+        &self,
+        surface: &mut MeshUvSurface,
+        face_id: u32,
+        uv: Vec2,
+        radius: f32,
+        color: [f32; 4],
+        opacity: f32,
+        hardness: f32,
+        blend_mode: BlendMode,
+        angle: f32,
+        aspect_ratio: f32,
+    ) -> usize {
+        let Some(&chart) = self.face_charts.get(face_id as usize) else {
+            return 0;
+        };
+        if chart == NO_CHART || surface.dimensions() != (self.width, self.height) {
+            return 0;
+        }
+
+        let center = uv_to_pixel(uv, self.width, self.height);
+        let mut candidates: HashSet<usize> = HashSet::new();
+        for cell in grid_cells(center - radius, center + radius) {
+            if let Some(indices) = self.grid.get(&cell) {
+                candidates.extend(indices);
+            }
+        }
+
+        let mut repeats = 0;
+        for index in candidates {
+            let side = &self.sides[index];
+            if side.chart != chart
+                || closest_on_segment(center, side.from[0], side.from[1]).distance(center) > radius
+            {
+                continue;
+            }
+            let target = side.transform(center);
+            let (scale, rotation) = side.scale_and_rotation();
+            let angle = if side.mirrored {
+                rotation - angle
+            } else {
+                rotation + angle
+            };
+            let applied = surface.atlas.apply_dab_ellipse_clipped(
+                target.x,
+                target.y,
+                radius * scale,
+                color,
+                opacity,
+                hardness,
+                blend_mode,
+                angle,
+                aspect_ratio,
+                |x, y| side.in_target_strip(x, y),
+            );
+            if applied.is_some() {
+                repeats += 1;
+            }
+        }
+        repeats
+    }
+
+    /// Grow the charts of every dirty tile of `surface` into uncovered
+    /// pixels up to the surface's seam padding away.
+    ///
+    /// Only pixels inside dirty tiles are written; pixels no chart reaches
+    /// keep their paint.
+    pub fn pad_dirty_tiles(&self, surface: &mut MeshUvSurface) {
+        let padding = surface.seam_padding;
+        if padding == 0 || surface.dimensions() != (self.width, self.height) {
+            return;
+        }
+        let tiles: Vec<TileCoord> = surface.atlas.dirty_tiles.iter().copied().collect();
+        for tile in tiles {
+            self.pad_tile(surface, tile, padding);
+        }
+    }
+
+    /// Dilate the charts around one tile, writing only inside it
+    fn pad_tile(&self, surface: &mut MeshUvSurface, tile: TileCoord, padding: u32) {
+        let (tile_x, tile_y, tile_w, tile_h) = surface.atlas.get_tile_bounds(tile);
+        // Read `padding` pixels around the tile so charts just outside it grow in
+        let x0 = tile_x.saturating_sub(padding);
+        let y0 = tile_y.saturating_sub(padding);
+        let x1 = (tile_x + tile_w + padding).min(self.width);
+        let y1 = (tile_y + tile_h + padding).min(self.height);
+        let (w, h) = ((x1 - x0) as usize, (y1 - y0) as usize);
+
+        let cpu = surface.atlas.surface_mut();
+        let mut filled = vec![false; w * h];
+        let mut colors = vec![[0.0f32; 4]; w * h];
+        for y in 0..h {
+            for x in 0..w {
+                let (ax, ay) = (x0 as usize + x, y0 as usize + y);
+                if self.covered[ay * self.width as usize + ax] {
+                    filled[y * w + x] = true;
+                    colors[y * w + x] = cpu.pixels()[ay * self.width as usize + ax];
+                }
+            }
+        }
+
+        let mut grown = Vec::new();
+        for _ in 0..padding {
+            grown.clear();
+            for y in 0..h {
+                for x in 0..w {
+                    if filled[y * w + x] {
+                        continue;
+                    }
+                    let mut sum = [0.0f32; 4];
+                    let mut count = 0;
+                    for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)] {
+                        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                        if nx < 0 || ny < 0 || nx >= w as i32 || ny >= h as i32 {
+                            continue;
+                        }
+                        let neighbour = ny as usize * w + nx as usize;
+                        if !filled[neighbour] {
+                            continue;
+                        }
+                        for (total, channel) in sum.iter_mut().zip(colors[neighbour]) {
+                            *total += channel;
+                        }
+                        count += 1;
+                    }
+                    if count > 0 {
+                        grown.push((y * w + x, sum.map(|total| total / count as f32)));
+                    }
+                }
+            }
+            if grown.is_empty() {
+                break;
+            }
+            for &(index, color) in &grown {
+                filled[index] = true;
+                colors[index] = color;
+                let (ax, ay) = (x0 + (index % w) as u32, y0 + (index / w) as u32);
+                if (tile_x..tile_x + tile_w).contains(&ax)
+                    && (tile_y..tile_y + tile_h).contains(&ay)
+                {
+                    cpu.set_pixel(ax, ay, color);
+                }
+            }
+        }
+    }
+}
+
+/// Mark the pixels a triangle (in atlas pixels) covers conservatively
+fn rasterize_coverage(corners: [Vec2; 3], width: u32, height: u32, covered: &mut [bool]) {
+    if barycentric(corners[0], corners).is_none() {
+        return;
+    }
+    let min = corners[0].min(corners[1]).min(corners[2]) - CONSERVATIVE_RASTER_PX;
+    let max = corners[0].max(corners[1]).max(corners[2]) + CONSERVATIVE_RASTER_PX;
+    let x_range = (min.x.floor().max(0.0) as u32)..(max.x.ceil().max(0.0) as u32).min(width);
+    let y_range = (min.y.floor().max(0.0) as u32)..(max.y.ceil().max(0.0) as u32).min(height);
+    for y in y_range {
+        for x in x_range.clone() {
+            let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let inside = barycentric(p, corners).is_some_and(|w| w.iter().all(|w| *w >= 0.0));
+            let near = || {
+                [(0, 1), (1, 2), (2, 0)].iter().any(|&(i, j)| {
+                    closest_on_segment(p, corners[i], corners[j]).distance(p)
+                        <= CONSERVATIVE_RASTER_PX
+                })
+            };
+            if inside || near() {
+                covered[y as usize * width as usize + x as usize] = true;
+            }
+        }
+    }
+}
+
+/// Grid cells overlapping the pixel rectangle `min`-`max`
+fn grid_cells(min: Vec2, max: Vec2) -> impl Iterator<Item = (i32, i32)> {
+    let (x0, y0) = (
+        (min.x / GRID_CELL_PX).floor() as i32,
+        (min.y / GRID_CELL_PX).floor() as i32,
+    );
+    let (x1, y1) = (
+        (max.x / GRID_CELL_PX).floor() as i32,
+        (max.y / GRID_CELL_PX).floor() as i32,
+    );
+    (y0..=y1).flat_map(move |y| (x0..=x1).map(move |x| (x, y)))
+}
+
+/// Product of two vectors read as complex numbers
+fn complex_mul(a: Vec2, b: Vec2) -> Vec2 {
+    Vec2::new(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x)
+}
+
+/// Quotient of two vectors read as complex numbers
+fn complex_div(a: Vec2, b: Vec2) -> Vec2 {
+    let denom = b.length_squared();
+    Vec2::new(a.x * b.x + a.y * b.y, a.y * b.x - a.x * b.y) / denom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::mesh::VertexAttributeValues;
+
+    fn face_uvs(mesh: &Mesh) -> Vec<[Vec2; 3]> {
+        let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+        else {
+            panic!("mesh has no UVs");
+        };
+        let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+        indices
+            .chunks_exact(3)
+            .map(|tri| [tri[0], tri[1], tri[2]].map(|i| Vec2::from_array(uvs[i])))
+            .collect()
+    }
+
+    /// Triangle whose UVs contain `uv`
+    fn face_at(face_uvs: &[[Vec2; 3]], uv: Vec2) -> u32 {
+        face_uvs
+            .iter()
+            .position(|uvs| barycentric(uv, *uvs).is_some_and(|w| w.iter().all(|w| *w >= -1e-6)))
+            .unwrap() as u32
+    }
+
+    /// Alpha of pixel (x, y) of mip `level`, box-filtered from the atlas
+    fn mip_alpha(surface: &MeshUvSurface, level: u32, x: u32, y: u32) -> f32 {
+        let size = 1 << level;
+        let cpu = surface.surface().surface();
+        let mut sum = 0.0;
+        for dy in 0..size {
+            for dx in 0..size {
+                sum += cpu.get_pixel(x * size + dx, y * size + dy).unwrap()[3];
+            }
+        }
+        sum / (size * size) as f32
+    }
+
+    #[test]
+    fn test_stroke_across_sphere_seam_has_no_gap() {
+        let mesh = Sphere::new(1.0).mesh().uv(32, 18);
+        let face_uvs = face_uvs(&mesh);
+        let (he_mesh, source_faces) =
+            HalfEdgeMesh::from_bevy_mesh_with_source_faces(&mesh).unwrap();
+        let seams = UvSeams::with_seam_edges(&face_uvs, (256, 256), &he_mesh, &source_faces);
+        assert!(seams.seam_edge_count() > 0);
+
+        // One dab either side of the u = 0/1 meridian, each overhanging it
+        let paint = |surface: &mut MeshUvSurface, bleed: bool| {
+            for uv in [Vec2::new(0.985, 0.5), Vec2::new(0.05, 0.5)] {
+                let color = [1.0, 0.0, 0.0, 1.0];
+                surface.apply_dab(uv, 12.0, color, 1.0, 1.0, BlendMode::Normal, 0.0, 1.0);
+                if bleed {
+                    let face_id = face_at(&face_uvs, uv);
+                    seams.apply_dab(
+                        surface,
+                        face_id,
+                        uv,
+                        12.0,
+                        color,
+                        1.0,
+                        1.0,
+                        BlendMode::Normal,
+                        0.0,
+                        1.0,
+                    );
+                }
+            }
+            if bleed {
+                seams.pad_dirty_tiles(surface);
+            }
+        };
+        let mut plain = MeshUvSurface::new(0, 256, 256, 4);
+        paint(&mut plain, false);
+        let mut bled = MeshUvSurface::new(0, 256, 256, 4);
+        paint(&mut bled, true);
+
+        // Without bleeding the stroke leaves a bare line along u = 0
+        let row = 128;
+        assert!(plain.surface().surface().get_pixel(0, row).unwrap()[3] < 0.5);
+        for level in 0..4 {
+            let last = (256 >> level) - 1;
+            for x in [0, last] {
+                let alpha = mip_alpha(&bled, level, x, row >> level);
+                assert!(alpha > 0.9, "mip {} column {} alpha {}", level, x, alpha);
+            }
+        }
+    }
+
+    #[test]
+    fn test_padding_grows_charts_into_margins() {
+        // One quad chart over pixels 16-48 of a 64 pixel atlas
+        let chart = [
+            [
+                Vec2::new(0.25, 0.25),
+                Vec2::new(0.75, 0.25),
+                Vec2::new(0.75, 0.75),
+            ],
+            [
+                Vec2::new(0.25, 0.25),
+                Vec2::new(0.75, 0.75),
+                Vec2::new(0.25, 0.75),
+            ],
+        ];
+        let seams = UvSeams::new(&chart, (64, 64));
+        let mut surface = MeshUvSurface::new(0, 64, 64, 4);
+        let color = [0.0, 0.0, 1.0, 1.0];
+        for y in 0..64 {
+            for x in 0..64 {
+                if seams.covered[y * 64 + x] {
+                    surface
+                        .surface_mut()
+                        .surface_mut()
+                        .set_pixel(x as u32, y as u32, color);
+                }
+            }
+        }
+        surface.surface_mut().mark_region_dirty(0, 0, 64, 64);
+
+        seams.pad_dirty_tiles(&mut surface);
+
+        let alpha = |x, y| surface.surface().surface().get_pixel(x, y).unwrap()[3];
+        // Coverage reaches pixels 15 and 48; padding adds four more each way
+        assert_eq!(alpha(52, 32), 1.0);
+        assert_eq!(alpha(53, 32), 0.0);
+        assert_eq!(alpha(11, 32), 1.0);
+        assert_eq!(alpha(10, 32), 0.0);
+    }
+}
@@ -0,0 +1,528 @@
+//! Automatic UV atlas generation for meshes without usable UVs
+//!
+//! Faces are grouped into charts by normal direction: a chart grows from a
+//! seed face across shared edges while neighbours stay within 60 degrees of
+//! the seed normal and don't land on top of the chart so far. Each chart is
+//! projected onto the plane of its seed normal, so charts never flip, and
+//! the charts are shelf-packed into one atlas at a common texel density.
+//! Faces tilted away from their chart plane get stretched; the amount is
+//! reported in the log.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::prelude::*;
+
+use crate::half_edge::{FaceId, HalfEdgeMesh, VertexId};
+
+/// Cosine of the largest angle between a face normal and its chart's normal
+const MAX_CHART_ANGLE_COS: f32 = 0.5;
+
+/// Binary search steps when fitting the texel density to the atlas
+const SCALE_SEARCH_STEPS: usize = 32;
+
+/// Relative tolerance for triangles that only touch along an edge or corner
+const OVERLAP_TOLERANCE: f32 = 1e-4;
+
+/// Generate atlas UVs for every face corner of `mesh`.
+///
+/// UVs follow the vertex order of [`HalfEdgeMesh::to_bevy_mesh`] (faces in
+/// order, one vertex per face corner), so they can be inserted as
+/// `ATTRIBUTE_UV_0` of that mesh. Charts are separated by at least
+/// `padding_px` texels at `resolution`, and from the atlas border.
+pub fn generate_atlas_uvs(
+    mesh: &HalfEdgeMesh,
+    resolution: (u32, u32),
+    padding_px: u32,
+) -> Vec<Vec2> {
+    let atlas = unwrap(mesh, resolution, padding_px);
+    tracing::info!(
+        "Generated UV atlas: {} charts, {:.0}% coverage, stretch mean {:.3} max {:.3}",
+        atlas.charts.len(),
+        atlas.coverage * 100.0,
+        atlas.stretch_mean,
+        atlas.stretch_max
+    );
+    atlas.uvs
+}
+
+/// Result of [`unwrap`], with the quality metrics that get logged
+struct Atlas {
+    uvs: Vec<Vec2>,
+    charts: Vec<Chart>,
+    /// Fraction of the atlas covered by faces
+    coverage: f32,
+    /// Area-weighted mean of surface area over projected area (1 = no stretch)
+    stretch_mean: f32,
+    stretch_max: f32,
+}
+
+/// Faces projected onto one plane
+struct Chart {
+    faces: Vec<usize>,
+    /// Projection axes (the chart normal is their cross product)
+    axes: (Vec3, Vec3),
+    /// Lower corner of the projected faces
+    min: Vec2,
+    /// Extent of the projected faces, in world units
+    size: Vec2,
+    /// Placement in the atlas, in texels: (offset, size)
+    rect: (UVec2, UVec2),
+}
+
+impl Chart {
+    fn project(&self, p: Vec3) -> Vec2 {
+        Vec2::new(p.dot(self.axes.0), p.dot(self.axes.1))
+    }
+}
+
+/// Polygon of the mesh, in `to_bevy_mesh` order
+struct Polygon {
+    id: FaceId,
+    vertices: Vec<VertexId>,
+    /// Unit normal (zero for degenerate faces)
+    normal: Vec3,
+    area: f32,
+}
+
+fn unwrap(mesh: &HalfEdgeMesh, resolution: (u32, u32), padding_px: u32) -> Atlas {
+    let position = |v: VertexId| mesh.vertices()[v.0 as usize].position;
+
+    let mut polygons: Vec<Polygon> = Vec::new();
+    let mut polygon_of: HashMap<FaceId, usize> = HashMap::new();
+    for face in mesh.faces() {
+        let vertices = mesh.get_face_vertices(face.id);
+        if vertices.len() < 3 {
+            continue;
+        }
+        let p0 = position(vertices[0]);
+        let doubled: Vec3 = (1..vertices.len() - 1)
+            .map(|i| (position(vertices[i]) - p0).cross(position(vertices[i + 1]) - p0))
+            .sum();
+        polygon_of.insert(face.id, polygons.len());
+        polygons.push(Polygon {
+            id: face.id,
+            vertices,
+            normal: doubled.normalize_or_zero(),
+            area: doubled.length() / 2.0,
+        });
+    }
+
+    let edge_lengths: Vec<f32> = polygons
+        .iter()
+        .flat_map(|polygon| {
+            let n = polygon.vertices.len();
+            (0..n).map(move |i| (polygon.vertices[i], polygon.vertices[(i + 1) % n]))
+        })
+        .map(|(a, b)| position(a).distance(position(b)))
+        .collect();
+    let mean_edge = edge_lengths.iter().sum::<f32>() / edge_lengths.len().max(1) as f32;
+    let cell_size = if mean_edge > 0.0 { mean_edge } else { 1.0 };
+
+    // Grow charts from seeds in face order
+    let mut chart_of: Vec<Option<usize>> = vec![None; polygons.len()];
+    let mut charts: Vec<Chart> = Vec::new();
+    for seed in 0..polygons.len() {
+        if chart_of[seed].is_some() {
+            continue;
+        }
+        let normal = polygons[seed].normal;
+        let normal = if normal == Vec3::ZERO {
+            Vec3::Y
+        } else {
+            normal
+        };
+        let mut chart = Chart {
+            faces: Vec::new(),
+            axes: normal.any_orthonormal_pair(),
+            min: Vec2::ZERO,
+            size: Vec2::ZERO,
+            rect: (UVec2::ZERO, UVec2::ZERO),
+        };
+        let chart_id = charts.len();
+        let mut grid = TriangleGrid::new(cell_size);
+        let triangles = |chart: &Chart, polygon: &Polygon| -> Vec<[Vec2; 3]> {
+            let p: Vec<Vec2> = polygon
+                .vertices
+                .iter()
+                .map(|v| chart.project(position(*v)))
+                .collect();
+            (1..p.len() - 1).map(|i| [p[0], p[i], p[i + 1]]).collect()
+        };
+
+        for triangle in triangles(&chart, &polygons[seed]) {
+            grid.insert(triangle);
+        }
+        chart_of[seed] = Some(chart_id);
+        chart.faces.push(seed);
+
+        let mut queue = VecDeque::from([seed]);
+        while let Some(current) = queue.pop_front() {
+            for he_id in mesh.get_face_half_edges(polygons[current].id) {
+                let Some(twin) = mesh.half_edge(he_id).and_then(|he| he.twin) else {
+                    continue;
+                };
+                let Some(&neighbour) = mesh
+                    .half_edge(twin)
+                    .and_then(|he| he.face)
+                    .and_then(|face| polygon_of.get(&face))
+                else {
+                    continue;
+                };
+                if chart_of[neighbour].is_some()
+                    || polygons[neighbour].normal.dot(normal) < MAX_CHART_ANGLE_COS
+                {
+                    continue;
+                }
+                let candidate = triangles(&chart, &polygons[neighbour]);
+                if candidate.iter().any(|t| grid.overlaps(t)) {
+                    continue;
+                }
+                for triangle in candidate {
+                    grid.insert(triangle);
+                }
+                chart_of[neighbour] = Some(chart_id);
+                chart.faces.push(neighbour);
+                queue.push_back(neighbour);
+            }
+        }
+
+        let (min, max) = chart
+            .faces
+            .iter()
+            .flat_map(|&face| polygons[face].vertices.iter())
+            .map(|v| chart.project(position(*v)))
+            .fold((Vec2::MAX, Vec2::MIN), |(min, max), p| {
+                (min.min(p), max.max(p))
+            });
+        chart.min = min;
+        chart.size = max - min;
+        charts.push(chart);
+    }
+
+    let scale = pack_charts(&mut charts, resolution, padding_px);
+
+    // Corner UVs in to_bevy_mesh order
+    let atlas_size = UVec2::new(resolution.0, resolution.1)
+        .max(UVec2::ONE)
+        .as_vec2();
+    let mut uvs = Vec::new();
+    for (index, polygon) in polygons.iter().enumerate() {
+        let chart = &charts[chart_of[index].expect("every face is in a chart")];
+        for v in &polygon.vertices {
+            let local = (chart.project(position(*v)) - chart.min) * scale;
+            uvs.push((chart.rect.0.as_vec2() + local) / atlas_size);
+        }
+    }
+
+    // Stretch: surface area over the area of its projection
+    let mut projected_total = 0.0;
+    let mut stretch_sum = 0.0;
+    let mut stretch_max: f32 = 1.0;
+    let mut area_total = 0.0;
+    for (index, polygon) in polygons.iter().enumerate() {
+        let chart = &charts[chart_of[index].expect("every face is in a chart")];
+        let (u, v) = chart.axes;
+        let projected = polygon.area * polygon.normal.dot(u.cross(v)).abs();
+        projected_total += projected;
+        if projected > f32::EPSILON {
+            let stretch = polygon.area / projected;
+            stretch_sum += stretch * polygon.area;
+            stretch_max = stretch_max.max(stretch);
+            area_total += polygon.area;
+        }
+    }
+
+    Atlas {
+        uvs,
+        charts,
+        coverage: projected_total * scale * scale / (atlas_size.x * atlas_size.y),
+        stretch_mean: if area_total > 0.0 {
+            stretch_sum / area_total
+        } else {
+            1.0
+        },
+        stretch_max,
+    }
+}
+
+/// Place the charts at the largest texel density that fits the atlas and
+/// return that density (texels per world unit).
+///
+/// Charts too many for the padding to fit at any density are packed beyond
+/// the bottom edge; that is logged since the UVs then leave the atlas.
+fn pack_charts(charts: &mut [Chart], resolution: (u32, u32), padding_px: u32) -> f32 {
+    let bounds_area: f32 = charts.iter().map(|chart| chart.size.x * chart.size.y).sum();
+    let atlas_area = resolution.0 as f32 * resolution.1 as f32;
+    // Charts can't cover more than the whole atlas
+    let mut high = if bounds_area > 0.0 {
+        (atlas_area / bounds_area).sqrt()
+    } else {
+        1.0
+    };
+    let mut low = 0.0;
+
+    // Tallest charts first, so each shelf is as full as possible
+    let mut order: Vec<usize> = (0..charts.len()).collect();
+    order.sort_by(|a, b| charts[*b].size.y.total_cmp(&charts[*a].size.y));
+
+    if shelf_pack(charts, &order, 0.0, resolution, padding_px).is_none() {
+        tracing::warn!(
+            "UV atlas of {}x{} is too small for {} charts with {} px padding",
+            resolution.0,
+            resolution.1,
+            charts.len(),
+            padding_px
+        );
+        let rects = shelf_pack(charts, &order, 0.0, (resolution.0, u32::MAX), padding_px)
+            .unwrap_or_default();
+        for (chart, rect) in charts.iter_mut().zip(rects) {
+            chart.rect = rect;
+        }
+        return 0.0;
+    }
+
+    for _ in 0..SCALE_SEARCH_STEPS {
+        let mid = (low + high) / 2.0;
+        if shelf_pack(charts, &order, mid, resolution, padding_px).is_some() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    let rects = shelf_pack(charts, &order, low, resolution, padding_px)
+        .expect("the lower bound always fits");
+    for (chart, rect) in charts.iter_mut().zip(rects) {
+        chart.rect = rect;
+    }
+    low
+}
+
+/// Shelf packing at `scale` texels per world unit; rectangles per chart in
+/// chart order, or `None` when they don't fit
+fn shelf_pack(
+    charts: &[Chart],
+    order: &[usize],
+    scale: f32,
+    resolution: (u32, u32),
+    padding_px: u32,
+) -> Option<Vec<(UVec2, UVec2)>> {
+    let (width, height) = (resolution.0 as u64, resolution.1 as u64);
+    let padding = padding_px as u64;
+    let mut rects = vec![(UVec2::ZERO, UVec2::ZERO); charts.len()];
+    let (mut x, mut y, mut shelf) = (padding, padding, 0u64);
+
+    for &index in order {
+        let size = (charts[index].size * scale)
+            .ceil()
+            .as_uvec2()
+            .max(UVec2::ONE);
+        let (w, h) = (size.x as u64, size.y as u64);
+        if w + 2 * padding > width {
+            return None;
+        }
+        if x + w + padding > width {
+            x = padding;
+            y += shelf + padding;
+            shelf = 0;
+        }
+        if y + h + padding > height {
+            return None;
+        }
+        rects[index] = (UVec2::new(x as u32, y as u32), size);
+        x += w + padding;
+        shelf = shelf.max(h);
+    }
+    Some(rects)
+}
+
+/// Projected triangles of a chart in a uniform grid, for overlap tests
+struct TriangleGrid {
+    cell_size: f32,
+    triangles: Vec<[Vec2; 3]>,
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl TriangleGrid {
+    fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            triangles: Vec::new(),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell_range(&self, triangle: &[Vec2; 3]) -> (IVec2, IVec2) {
+        let min = triangle[0].min(triangle[1]).min(triangle[2]);
+        let max = triangle[0].max(triangle[1]).max(triangle[2]);
+        (
+            (min / self.cell_size).floor().as_ivec2(),
+            (max / self.cell_size).floor().as_ivec2(),
+        )
+    }
+
+    fn insert(&mut self, triangle: [Vec2; 3]) {
+        let index = self.triangles.len();
+        let (min, max) = self.cell_range(&triangle);
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(index);
+            }
+        }
+        self.triangles.push(triangle);
+    }
+
+    fn overlaps(&self, triangle: &[Vec2; 3]) -> bool {
+        let (min, max) = self.cell_range(triangle);
+        let tolerance = self.cell_size * OVERLAP_TOLERANCE;
+        let mut tested = HashSet::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                let Some(indices) = self.cells.get(&IVec2::new(x, y)) else {
+                    continue;
+                };
+                for &index in indices {
+                    if tested.insert(index)
+                        && triangles_overlap(&self.triangles[index], triangle, tolerance)
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Whether two triangles' interiors intersect by more than `tolerance`.
+///
+/// Separating axis test over the edge normals; triangles that only share an
+/// edge or a corner count as separate.
+fn triangles_overlap(a: &[Vec2; 3], b: &[Vec2; 3], tolerance: f32) -> bool {
+    let extent = |triangle: &[Vec2; 3], axis: Vec2| {
+        triangle
+            .iter()
+            .map(|p| p.dot(axis))
+            .fold((f32::MAX, f32::MIN), |(min, max), d| {
+                (min.min(d), max.max(d))
+            })
+    };
+    for triangle in [a, b] {
+        for i in 0..3 {
+            let edge = triangle[(i + 1) % 3] - triangle[i];
+            let axis = edge.perp().normalize_or_zero();
+            if axis == Vec2::ZERO {
+                continue;
+            }
+            let (a_min, a_max) = extent(a, axis);
+            let (b_min, b_max) = extent(b, axis);
+            if a_max <= b_min + tolerance || b_max <= a_min + tolerance {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::primitives::{Cuboid, Torus};
+
+    const RESOLUTION: (u32, u32) = (512, 512);
+    const PADDING: u32 = 4;
+
+    fn half_edge(mesh: Mesh) -> HalfEdgeMesh {
+        HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap()
+    }
+
+    /// UV triangles with the chart they belong to
+    fn uv_triangles(mesh: &HalfEdgeMesh, atlas: &Atlas) -> Vec<(usize, [Vec2; 3])> {
+        let mut chart_of = HashMap::new();
+        for (chart_id, chart) in atlas.charts.iter().enumerate() {
+            for face in &chart.faces {
+                chart_of.insert(*face, chart_id);
+            }
+        }
+        let mut triangles = Vec::new();
+        let mut corner = 0;
+        let mut polygon = 0;
+        for face in mesh.faces() {
+            let count = mesh.get_face_vertices(face.id).len();
+            if count < 3 {
+                continue;
+            }
+            let uvs = &atlas.uvs[corner..corner + count];
+            for i in 1..count - 1 {
+                triangles.push((chart_of[&polygon], [uvs[0], uvs[i], uvs[i + 1]]));
+            }
+            corner += count;
+            polygon += 1;
+        }
+        triangles
+    }
+
+    fn assert_no_overlaps(mesh: &HalfEdgeMesh, atlas: &Atlas) {
+        let triangles = uv_triangles(mesh, atlas);
+        for (i, (_, a)) in triangles.iter().enumerate() {
+            for p in a {
+                assert!(p.x >= 0.0 && p.x <= 1.0 && p.y >= 0.0 && p.y <= 1.0);
+            }
+            for (_, b) in &triangles[i + 1..] {
+                assert!(!triangles_overlap(a, b, 1e-6), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    fn assert_padded(atlas: &Atlas) {
+        let padding = PADDING as i64;
+        for (i, a) in atlas.charts.iter().enumerate() {
+            let (a_min, a_size) = (a.rect.0.as_i64vec2(), a.rect.1.as_i64vec2());
+            assert!(a_min.min_element() >= padding);
+            assert!(a_min.x + a_size.x + padding <= RESOLUTION.0 as i64);
+            assert!(a_min.y + a_size.y + padding <= RESOLUTION.1 as i64);
+            for b in &atlas.charts[i + 1..] {
+                let (b_min, b_size) = (b.rect.0.as_i64vec2(), b.rect.1.as_i64vec2());
+                let apart_x = a_min.x + a_size.x + padding <= b_min.x
+                    || b_min.x + b_size.x + padding <= a_min.x;
+                let apart_y = a_min.y + a_size.y + padding <= b_min.y
+                    || b_min.y + b_size.y + padding <= a_min.y;
+                assert!(apart_x || apart_y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cube_gets_one_flat_chart_per_side() {
+        let mesh = half_edge(Cuboid::default().mesh().build());
+        let atlas = unwrap(&mesh, RESOLUTION, PADDING);
+
+        assert_eq!(atlas.charts.len(), 6);
+        assert_eq!(atlas.uvs.len(), mesh.face_count() * 3);
+        assert!((atlas.stretch_mean - 1.0).abs() < 1e-4);
+        assert!((atlas.stretch_max - 1.0).abs() < 1e-4);
+        assert!(atlas.coverage > 0.3);
+        assert_no_overlaps(&mesh, &atlas);
+        assert_padded(&atlas);
+    }
+
+    #[test]
+    fn test_curved_mesh_charts_do_not_overlap() {
+        let mesh = half_edge(Torus::default().mesh().build());
+        let atlas = unwrap(&mesh, RESOLUTION, PADDING);
+
+        assert!(atlas.charts.len() > 1);
+        assert!(atlas.stretch_mean >= 1.0);
+        // Faces stay within 60 degrees of their chart plane
+        assert!(atlas.stretch_max <= 2.0 + 1e-3);
+        assert_no_overlaps(&mesh, &atlas);
+        assert_padded(&atlas);
+    }
+
+    #[test]
+    fn test_uvs_follow_to_bevy_mesh_vertex_order() {
+        let mesh = half_edge(Cuboid::default().mesh().build());
+        let uvs = generate_atlas_uvs(&mesh, RESOLUTION, PADDING);
+        let bevy_mesh = mesh.to_bevy_mesh();
+        assert_eq!(uvs.len(), bevy_mesh.count_vertices());
+    }
+}
@@ -60,3 +60,42 @@ pub fn to_size_field(diameter: f32) -> u32 {
 pub fn from_size_field(size: u32) -> f32 {
     size as f32 / crate::constants::SIZE_SCALE
 }
+
+/// Convert a 0.0-1.0 value (hardness, opacity) to a u8 field
+pub fn to_unit_field(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0) as u8
+}
+
+/// Convert a u8 field to a 0.0-1.0 value
+pub fn from_unit_field(field: u8) -> f32 {
+    field as f32 / 255.0
+}
+
+/// Convert an angle in radians to a u8 field (0..255 spans a full turn)
+pub fn to_angle_field(angle: f32) -> u8 {
+    (angle.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU * 255.0) as u8
+}
+
+/// Convert a u8 angle field to radians
+pub fn from_angle_field(field: u8) -> f32 {
+    field as f32 / 255.0 * std::f32::consts::TAU
+}
+
+/// Convert a canvas UV coordinate to canvas pixels
+/// Returns None if the UV lies outside the canvas
+pub fn uv_to_canvas(u: f32, v: f32, width: u32, height: u32) -> Option<(f32, f32)> {
+    if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+        return None;
+    }
+    Some((u * width as f32, v * height as f32))
+}
+
+/// Get the pixel containing a canvas position
+/// Returns None if the position lies outside the canvas
+pub fn canvas_pixel(x: f32, y: f32, width: u32, height: u32) -> Option<(u32, u32)> {
+    if x.is_nan() || y.is_nan() || x < 0.0 || y < 0.0 {
+        return None;
+    }
+    let (px, py) = (x.floor() as u32, y.floor() as u32);
+    (px < width && py < height).then_some((px, py))
+}
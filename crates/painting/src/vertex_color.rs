@@ -0,0 +1,198 @@
+//! Vertex color painting for meshes without UVs or Ptex
+//!
+//! A dab blends the brush color into the colors of the mesh vertices within
+//! its radius, weighted by the hardness falloff (see
+//! [`calculate_hardness_falloff`]) and opacity. Erasing blends back toward
+//! white, the color of unpainted vertices. Finding the vertices in the radius
+//! is left to the caller's spatial index, so a dab costs the vertices it
+//! covers, not the size of the mesh.
+//!
+//! Strokes are recorded as [`VertexColorStroke`] packets tagged
+//! [`SpaceKind::MeshVertexColors`]; dabs are in the mesh's local space, so
+//! replaying a packet on the same mesh repaints the stroke.
+
+use glam::{Vec3, Vec4};
+use serde::{Deserialize, Serialize};
+
+use crate::tiles::calculate_hardness_falloff;
+use crate::types::{BlendMode, SpaceKind};
+
+/// Color of vertices that were never painted
+pub const UNPAINTED_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// One vertex color dab, in the painted mesh's local space
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VertexColorDab {
+    /// Center as an array, so the packet serializes without glam's serde
+    pub center: [f32; 3],
+    pub radius: f32,
+    /// Opacity 0..1 (pressure already applied)
+    pub opacity: f32,
+    /// Edge hardness 0..1
+    pub hardness: f32,
+}
+
+/// A recorded vertex color stroke: its dabs in order, with the brush settings
+/// they share
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VertexColorStroke {
+    /// Always [`SpaceKind::MeshVertexColors`]
+    pub space_kind: SpaceKind,
+    /// Painted mesh
+    pub mesh_id: u32,
+    pub stroke_id: u64,
+    /// Linear RGBA brush color
+    pub color: [f32; 4],
+    pub blend_mode: BlendMode,
+    pub dabs: Vec<VertexColorDab>,
+}
+
+impl VertexColorStroke {
+    /// Empty stroke on `mesh_id`
+    pub fn new(mesh_id: u32, stroke_id: u64, color: [f32; 4], blend_mode: BlendMode) -> Self {
+        Self {
+            space_kind: SpaceKind::MeshVertexColors,
+            mesh_id,
+            stroke_id,
+            color,
+            blend_mode,
+            dabs: Vec::new(),
+        }
+    }
+
+    /// Replay every dab onto `colors`, finding the vertices in each dab's
+    /// radius with `query`
+    pub fn replay(
+        &self,
+        positions: &[[f32; 3]],
+        colors: &mut [[f32; 4]],
+        mut query: impl FnMut(Vec3, f32) -> Vec<u32>,
+    ) {
+        for dab in &self.dabs {
+            let candidates = query(Vec3::from_array(dab.center), dab.radius);
+            apply_vertex_color_dab(
+                positions,
+                colors,
+                &candidates,
+                dab,
+                self.color,
+                self.blend_mode,
+            );
+        }
+    }
+}
+
+/// Blend a dab into `colors` at the `candidates` vertex indices, skipping
+/// those outside its radius or out of range.
+///
+/// Returns `(index, color before the dab)` for every vertex that changed.
+pub fn apply_vertex_color_dab(
+    positions: &[[f32; 3]],
+    colors: &mut [[f32; 4]],
+    candidates: &[u32],
+    dab: &VertexColorDab,
+    color: [f32; 4],
+    blend_mode: BlendMode,
+) -> Vec<(u32, [f32; 4])> {
+    let target = match blend_mode {
+        BlendMode::Normal => Vec4::from_array(color),
+        BlendMode::Erase => Vec4::from_array(UNPAINTED_COLOR),
+    };
+    let mut changed = Vec::new();
+    if dab.radius <= 0.0 {
+        return changed;
+    }
+
+    for &index in candidates {
+        let (Some(position), Some(current)) = (
+            positions.get(index as usize),
+            colors.get_mut(index as usize),
+        ) else {
+            continue;
+        };
+        let distance =
+            Vec3::from_array(*position).distance(Vec3::from_array(dab.center)) / dab.radius;
+        if distance > 1.0 {
+            continue;
+        }
+        let weight = calculate_hardness_falloff(distance, dab.hardness) * dab.opacity;
+        if weight <= 0.0 {
+            continue;
+        }
+        let before = *current;
+        *current = Vec4::from_array(before)
+            .lerp(target, weight.min(1.0))
+            .to_array();
+        if *current != before {
+            changed.push((index, before));
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+    fn dab(center: Vec3, hardness: f32) -> VertexColorDab {
+        VertexColorDab {
+            center: center.to_array(),
+            radius: 1.0,
+            opacity: 1.0,
+            hardness,
+        }
+    }
+
+    #[test]
+    fn test_dab_paints_vertices_in_radius() {
+        let positions = [[0.0, 0.0, 0.0], [0.5, 0.0, 0.0], [2.0, 0.0, 0.0]];
+        let mut colors = [UNPAINTED_COLOR; 3];
+
+        let changed = apply_vertex_color_dab(
+            &positions,
+            &mut colors,
+            &[0, 1, 2],
+            &dab(Vec3::ZERO, 0.0),
+            RED,
+            BlendMode::Normal,
+        );
+
+        assert_eq!(colors[0], RED);
+        // Soft falloff: halfway out blends halfway
+        assert_eq!(colors[1], [1.0, 0.5, 0.5, 1.0]);
+        assert_eq!(colors[2], UNPAINTED_COLOR);
+        assert_eq!(changed, vec![(0, UNPAINTED_COLOR), (1, UNPAINTED_COLOR)]);
+    }
+
+    #[test]
+    fn test_erase_returns_to_unpainted() {
+        let positions = [[0.0, 0.0, 0.0]];
+        let mut colors = [RED];
+
+        apply_vertex_color_dab(
+            &positions,
+            &mut colors,
+            &[0],
+            &dab(Vec3::ZERO, 1.0),
+            RED,
+            BlendMode::Erase,
+        );
+        assert_eq!(colors[0], UNPAINTED_COLOR);
+    }
+
+    #[test]
+    fn test_replay_repaints_stroke() {
+        let positions = [[0.0, 0.0, 0.0], [3.0, 0.0, 0.0], [6.0, 0.0, 0.0]];
+        let mut stroke = VertexColorStroke::new(1, 7, RED, BlendMode::Normal);
+        stroke.dabs.push(dab(Vec3::ZERO, 1.0));
+        stroke.dabs.push(dab(Vec3::new(3.0, 0.0, 0.0), 1.0));
+        assert_eq!(stroke.space_kind, SpaceKind::MeshVertexColors);
+
+        let mut colors = [UNPAINTED_COLOR; 3];
+        // Brute-force query standing in for a spatial index
+        stroke.replay(&positions, &mut colors, |_, _| vec![0, 1, 2]);
+        assert_eq!(colors, [RED, RED, UNPAINTED_COLOR]);
+    }
+}
@@ -0,0 +1,151 @@
+//! Scene raycasts: the BVH against testing every triangle of every mesh
+//!
+//! Run with `cargo bench -p pentimento-scene --bench raycast`. The scene is
+//! the default one (plane, cube, sphere, torus) tessellated to about 100k
+//! triangles, and each iteration casts a 32x32 grid of camera rays.
+
+use bevy::asset::uuid::Uuid;
+use bevy::mesh::{Indices, VertexAttributeValues};
+use bevy::prelude::*;
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use painting::raycast::ray_triangle_intersection;
+use pentimento_scene::SceneBvh;
+
+/// Rays per side of the grid cast each iteration
+const GRID: usize = 32;
+
+/// Triangles of one scene mesh, with its inverse transform
+struct BruteForceMesh {
+    positions: Vec<Vec3>,
+    indices: Vec<u32>,
+    local_from_world: bevy::math::Affine3A,
+}
+
+fn default_scene() -> Vec<(Mesh, Transform)> {
+    vec![
+        (
+            Plane3d::default()
+                .mesh()
+                .size(10.0, 10.0)
+                .subdivisions(99)
+                .build(),
+            Transform::IDENTITY,
+        ),
+        (
+            Mesh::from(Cuboid::new(1.0, 1.0, 1.0)),
+            Transform::from_xyz(0.0, 0.5, 0.0),
+        ),
+        (
+            Sphere::new(0.5).mesh().uv(200, 120),
+            Transform::from_xyz(2.0, 0.5, 0.0),
+        ),
+        (
+            Torus::new(0.3, 0.5)
+                .mesh()
+                .minor_resolution(128)
+                .major_resolution(128)
+                .build(),
+            Transform::from_xyz(-2.0, 0.5, 0.0),
+        ),
+    ]
+}
+
+fn brute_force_mesh(mesh: &Mesh, transform: &Transform) -> BruteForceMesh {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("scene meshes have positions");
+    };
+    let indices = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.iter().map(|&i| i as u32).collect(),
+        Some(Indices::U32(indices)) => indices.clone(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    BruteForceMesh {
+        positions: positions.iter().map(|&p| Vec3::from(p)).collect(),
+        indices,
+        local_from_world: transform.compute_affine().inverse(),
+    }
+}
+
+/// The per-triangle loop the BVH replaces
+fn brute_force_raycast(meshes: &[BruteForceMesh], ray: Ray3d) -> Option<f32> {
+    let mut nearest: Option<f32> = None;
+    for mesh in meshes {
+        let origin = mesh.local_from_world.transform_point3(ray.origin);
+        let direction = mesh.local_from_world.transform_vector3(*ray.direction);
+        for tri in mesh.indices.chunks_exact(3) {
+            let [v0, v1, v2] = [tri[0], tri[1], tri[2]].map(|i| mesh.positions[i as usize]);
+            if let Some(hit) = ray_triangle_intersection(origin, direction, v0, v1, v2) {
+                nearest = Some(nearest.map_or(hit.t, |t| t.min(hit.t)));
+            }
+        }
+    }
+    nearest
+}
+
+/// A grid of rays from a camera above and in front of the scene
+fn camera_rays() -> Vec<Ray3d> {
+    let origin = Vec3::new(0.0, 5.0, 8.0);
+    (0..GRID * GRID)
+        .map(|i| {
+            let (x, y) = ((i % GRID) as f32, (i / GRID) as f32);
+            let target = Vec3::new(
+                -5.0 + 10.0 * x / (GRID - 1) as f32,
+                0.0,
+                -5.0 + 10.0 * y / (GRID - 1) as f32,
+            );
+            Ray3d::new(origin, Dir3::new(target - origin).unwrap())
+        })
+        .collect()
+}
+
+fn raycast(c: &mut Criterion) {
+    let scene = default_scene();
+    let triangles: usize = scene
+        .iter()
+        .map(|(mesh, _)| mesh.indices().map_or(0, |indices| indices.len() / 3))
+        .sum();
+    println!("default scene: {triangles} triangles");
+
+    let mut bvh = SceneBvh::default();
+    for (i, (mesh, transform)) in scene.iter().enumerate() {
+        let mesh_id = AssetId::Uuid {
+            uuid: Uuid::from_u128(i as u128 + 1),
+        };
+        let entity = Entity::from_raw_u32(i as u32).unwrap();
+        bvh.insert(
+            entity,
+            mesh_id,
+            mesh,
+            &GlobalTransform::from(*transform),
+            true,
+        );
+    }
+    bvh.update();
+    let brute_force: Vec<_> = scene
+        .iter()
+        .map(|(mesh, transform)| brute_force_mesh(mesh, transform))
+        .collect();
+    let rays = camera_rays();
+
+    let mut group = c.benchmark_group("raycast_grid");
+    group.bench_function("scene_bvh", |b| {
+        b.iter(|| {
+            rays.iter()
+                .filter_map(|&ray| bvh.raycast(black_box(ray), |_| true))
+                .count()
+        })
+    });
+    group.bench_function("per_triangle", |b| {
+        b.iter(|| {
+            rays.iter()
+                .filter_map(|&ray| brute_force_raycast(&brute_force, black_box(ray)))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, raycast);
+criterion_main!(benches);
@@ -1,70 +1,114 @@
 //! Add object system for creating new primitives in the scene
 //!
-//! Handles spawning new mesh objects via the AddObjectEvent.
+//! Handles spawning new mesh objects via the AddObjectEvent, and reports
+//! each one to the UI with `ObjectAdded`. Objects added without a position
+//! land under the cursor where the Shift+A menu was opened ([`AddMenuAnchor`]):
+//! on the scene surface there, or on the ground plane when nothing is hit,
+//! lifted so they rest on it. The last object added is selected with a
+//! translate operation running, so it can be placed right away.
+//!
+//! Every primitive comes with normals, UVs in [0, 1], and tangents, and is
+//! paintable straight away with the Ptex storage the built-in cube and torus
+//! use. Objects get their own gray material, addressed by object ID like the
+//! rest of the scene's (there is no separate material registry).
 
 use bevy::ecs::message::Message;
 use bevy::prelude::*;
-use pentimento_ipc::{AddObjectRequest, PrimitiveType};
+use pentimento_ipc::{AddObjectRequest, BevyToUi, PrimitiveType, SceneObject, Transform3D};
+
+use crate::OutboundUiMessages;
+use crate::camera::MainCamera;
+use crate::object_id::IdAllocator;
+use crate::scene_bvh::SceneBvh;
 
 #[cfg(feature = "selection")]
-use crate::selection::Selectable;
+use crate::gizmo::GizmoState;
+#[cfg(feature = "selection")]
+use crate::selection::{Selectable, Selected, SelectionState};
+#[cfg(feature = "selection")]
+use pentimento_ipc::GizmoMode;
+
+#[cfg(feature = "mesh_painting")]
+use crate::mesh_paint_mode::{MeshIdGenerator, PaintableMesh};
+
+/// Segments around round primitives when the request doesn't say
+const DEFAULT_SUBDIVISIONS: u32 = 32;
+/// Limits on requested segments around round primitives
+const SUBDIVISION_RANGE: std::ops::RangeInclusive<u32> = 3..=256;
 
 /// Event/Message for adding new objects to the scene
 #[derive(Message)]
 pub struct AddObjectEvent(pub AddObjectRequest);
 
-/// Counter for generating unique object IDs
+/// Window position (logical pixels) the Shift+A menu was opened at. The next
+/// object added without a position is placed under it; closing the menu
+/// clears it.
 #[derive(Resource, Default)]
-struct ObjectCounter(u32);
+pub struct AddMenuAnchor(pub Option<Vec2>);
 
 /// Plugin for adding objects to the scene
 pub struct AddObjectPlugin;
 
 impl Plugin for AddObjectPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<ObjectCounter>()
+        app.init_resource::<AddMenuAnchor>()
             .add_message::<AddObjectEvent>()
             .add_systems(Update, handle_add_object_event);
     }
 }
 
 /// Handle add object events by spawning appropriate meshes
+#[allow(clippy::too_many_arguments)]
 fn handle_add_object_event(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut events: MessageReader<AddObjectEvent>,
-    mut counter: ResMut<ObjectCounter>,
+    mut allocator: ResMut<IdAllocator>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    mut anchor: ResMut<AddMenuAnchor>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    bvh: Option<Res<SceneBvh>>,
+    #[cfg(feature = "selection")] mut selection: ResMut<SelectionState>,
+    #[cfg(feature = "selection")] selected_query: Query<Entity, With<Selected>>,
+    #[cfg(feature = "selection")] gizmo_state: Option<ResMut<GizmoState>>,
+    #[cfg(feature = "mesh_painting")] mut mesh_ids: Option<ResMut<MeshIdGenerator>>,
 ) {
+    #[cfg(feature = "selection")]
+    let mut last_added = None;
+
     for event in events.read() {
         let request = &event.0;
 
-        // Generate unique ID
-        counter.0 += 1;
-        let id = format!("object_{}", counter.0);
+        let id = allocator.allocate();
+
+        let (mesh, half_height) = primitive_mesh(request.primitive_type, request.subdivisions);
 
-        // Determine position
-        let position = request
-            .position
-            .map(Vec3::from_array)
-            .unwrap_or(Vec3::new(0.0, 0.5, 0.0)); // Slightly above ground by default
+        // Determine position: as requested, else under the add menu, resting
+        // on whatever is there
+        let position = match request.position {
+            Some(position) => Vec3::from_array(position),
+            None => {
+                let ground = anchor
+                    .0
+                    .take()
+                    .and_then(|cursor| {
+                        let (camera, camera_transform) = camera_query.single().ok()?;
+                        camera.viewport_to_world(camera_transform, cursor).ok()
+                    })
+                    .and_then(|ray| scene_point(ray, bvh.as_deref()))
+                    .unwrap_or(Vec3::ZERO);
+                ground + Vec3::Y * half_height
+            }
+        };
 
         // Generate name
         let name = request
             .name
             .clone()
-            .unwrap_or_else(|| format!("{:?}", request.primitive_type));
-
-        // Create mesh based on primitive type
-        let mesh = match request.primitive_type {
-            PrimitiveType::Cube => meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
-            PrimitiveType::Sphere => meshes.add(Sphere::new(0.5).mesh().uv(32, 18)),
-            PrimitiveType::Cylinder => meshes.add(Cylinder::new(0.5, 1.0)),
-            PrimitiveType::Plane => meshes.add(Plane3d::default().mesh().size(2.0, 2.0)),
-            PrimitiveType::Torus => meshes.add(Torus::new(0.3, 0.5)),
-            PrimitiveType::Cone => meshes.add(Cone::new(0.5, 1.0)),
-            PrimitiveType::Capsule => meshes.add(Capsule3d::new(0.25, 0.5)),
-        };
+            .unwrap_or_else(|| primitive_name(request.primitive_type).to_string());
+
+        let mesh = meshes.add(mesh);
 
         // Default gray material
         let material = materials.add(StandardMaterial {
@@ -75,12 +119,13 @@ fn handle_add_object_event(
         });
 
         // Spawn the entity
+        let transform = Transform::from_translation(position);
         #[allow(unused_variables)]
         let entity = commands
             .spawn((
                 Mesh3d(mesh),
                 MeshMaterial3d(material),
-                Transform::from_translation(position),
+                transform,
                 Name::new(name.clone()),
             ))
             .id();
@@ -91,6 +136,304 @@ fn handle_add_object_event(
             .entity(entity)
             .insert(Selectable { id: id.clone() });
 
+        // Paintable like the built-in cube and torus; Ptex needs no UV layout
+        #[cfg(feature = "mesh_painting")]
+        if let Some(mesh_ids) = mesh_ids.as_mut() {
+            commands.entity(entity).insert(PaintableMesh {
+                mesh_id: mesh_ids.next(),
+                storage_mode: painting::types::MeshStorageMode::Ptex {
+                    face_resolution: 32,
+                },
+            });
+        }
+
         info!("Added object '{}' (id: {}) at {:?}", name, id, position);
+        outbound.send(BevyToUi::ObjectAdded {
+            object: SceneObject {
+                id: id.clone(),
+                name,
+                transform: Transform3D {
+                    position: transform.translation.to_array(),
+                    rotation: transform.rotation.to_array(),
+                    scale: transform.scale.to_array(),
+                },
+                material_id: None,
+                visible: true,
+                locked: false,
+                parent_id: None,
+                subdivision_levels: 0,
+                wireframe: None,
+                instanced: false,
+            },
+            duplicated_from: None,
+        });
+        #[cfg(feature = "selection")]
+        last_added = Some((entity, id, transform));
+    }
+
+    // Select the new object and start moving it
+    #[cfg(feature = "selection")]
+    if let Some((entity, id, transform)) = last_added {
+        for selected in &selected_query {
+            commands.entity(selected).remove::<Selected>();
+        }
+        commands.entity(entity).insert(Selected);
+        selection.selected_ids = vec![id];
+
+        if let Some(mut gizmo_state) = gizmo_state
+            && !gizmo_state.is_active
+        {
+            gizmo_state.original_transforms = vec![(entity, transform)];
+            gizmo_state.start_operation(GizmoMode::Translate);
+        }
+    }
+}
+
+/// Scene point under a ray: the first scene hit along it, else where it
+/// crosses the ground plane (y = 0)
+pub(crate) fn scene_point(ray: Ray3d, bvh: Option<&SceneBvh>) -> Option<Vec3> {
+    if let Some(hit) = bvh.and_then(|bvh| bvh.raycast(ray, |_| true)) {
+        return Some(hit.position);
+    }
+    ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))
+        .map(|distance| ray.get_point(distance))
+}
+
+/// Default object name for a primitive
+fn primitive_name(primitive: PrimitiveType) -> &'static str {
+    match primitive {
+        PrimitiveType::Cube => "Cube",
+        PrimitiveType::Sphere => "Sphere",
+        PrimitiveType::Cylinder => "Cylinder",
+        PrimitiveType::Plane => "Plane",
+        PrimitiveType::Torus => "Torus",
+        PrimitiveType::Cone => "Cone",
+        PrimitiveType::Capsule => "Capsule",
+        PrimitiveType::Monkey => "Monkey",
+        PrimitiveType::Grid { .. } => "Grid",
+    }
+}
+
+/// Mesh for a primitive, and the distance from its origin down to its base
+///
+/// `subdivisions` sets the segments around spheres, cylinders, cones, tori
+/// (around the ring; half as many around the tube), and capsules. Tangents
+/// are generated for normal-map painting.
+fn primitive_mesh(primitive: PrimitiveType, subdivisions: Option<u32>) -> (Mesh, f32) {
+    let segments = subdivisions
+        .unwrap_or(DEFAULT_SUBDIVISIONS)
+        .clamp(*SUBDIVISION_RANGE.start(), *SUBDIVISION_RANGE.end());
+    let (mut mesh, half_height) = match primitive {
+        PrimitiveType::Cube => (Cuboid::new(1.0, 1.0, 1.0).into(), 0.5),
+        PrimitiveType::Sphere => (
+            Sphere::new(0.5)
+                .mesh()
+                .uv(segments as usize, (segments as usize / 2).max(2)),
+            0.5,
+        ),
+        PrimitiveType::Cylinder => (
+            Cylinder::new(0.5, 1.0).mesh().resolution(segments).build(),
+            0.5,
+        ),
+        PrimitiveType::Plane => (Plane3d::default().mesh().size(2.0, 2.0).build(), 0.0),
+        PrimitiveType::Torus => {
+            let torus = Torus::new(0.3, 0.5);
+            let mesh = torus
+                .mesh()
+                .major_resolution(segments as usize)
+                .minor_resolution((segments as usize / 2).max(3))
+                .build();
+            (mesh, torus.minor_radius)
+        }
+        PrimitiveType::Cone => (Cone::new(0.5, 1.0).mesh().resolution(segments).build(), 0.5),
+        PrimitiveType::Capsule => (
+            // Latitudes must be even
+            Capsule3d::new(0.25, 0.5)
+                .mesh()
+                .longitudes(segments as usize)
+                .latitudes((segments as usize / 4).max(2) * 2)
+                .build(),
+            0.5,
+        ),
+        PrimitiveType::Monkey => (monkey_mesh(), MONKEY_HALF_HEIGHT),
+        PrimitiveType::Grid { subdivisions } => (
+            Plane3d::default()
+                .mesh()
+                .size(2.0, 2.0)
+                .subdivisions(subdivisions.min(*SUBDIVISION_RANGE.end()))
+                .build(),
+            0.0,
+        ),
+    };
+    if let Err(e) = mesh.generate_tangents() {
+        warn!("Could not generate tangents for {:?}: {}", primitive, e);
+    }
+    (mesh, half_height)
+}
+
+/// Distance from the monkey head's origin down to its chin
+const MONKEY_HALF_HEIGHT: f32 = 0.4;
+
+/// Stylized monkey head built from squashed spheres: skull, ears, muzzle, and
+/// eyes, facing +Z
+fn monkey_mesh() -> Mesh {
+    // (center, radius, scale) of each part
+    let parts = [
+        (Vec3::ZERO, 0.4, Vec3::new(1.0, 1.0, 0.9)),
+        (
+            Vec3::new(-0.42, 0.12, -0.02),
+            0.17,
+            Vec3::new(1.0, 1.0, 0.45),
+        ),
+        (
+            Vec3::new(0.42, 0.12, -0.02),
+            0.17,
+            Vec3::new(1.0, 1.0, 0.45),
+        ),
+        (
+            Vec3::new(0.0, -0.14, 0.24),
+            0.26,
+            Vec3::new(1.25, 0.8, 0.85),
+        ),
+        (Vec3::new(-0.14, 0.1, 0.33), 0.075, Vec3::ONE),
+        (Vec3::new(0.14, 0.1, 0.33), 0.075, Vec3::ONE),
+    ];
+    let mut parts = parts.into_iter().map(|(center, radius, scale)| {
+        Sphere::new(radius)
+            .mesh()
+            .uv(24, 12)
+            .transformed_by(Transform::from_translation(center).with_scale(scale))
+    });
+    let mut head = parts.next().expect("the monkey has a skull");
+    for part in parts {
+        head.merge(&part)
+            .expect("sphere meshes share their attributes");
+    }
+    head
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::mesh::VertexAttributeValues;
+
+    use super::*;
+
+    fn lowest_y(mesh: &Mesh) -> f32 {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("mesh without positions");
+        };
+        positions.iter().map(|p| p[1]).fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn test_primitives_rest_on_their_base() {
+        let primitives = [
+            PrimitiveType::Cube,
+            PrimitiveType::Sphere,
+            PrimitiveType::Cylinder,
+            PrimitiveType::Plane,
+            PrimitiveType::Torus,
+            PrimitiveType::Cone,
+            PrimitiveType::Capsule,
+            PrimitiveType::Monkey,
+            PrimitiveType::Grid { subdivisions: 4 },
+        ];
+        for primitive in primitives {
+            let (mesh, half_height) = primitive_mesh(primitive, None);
+            assert!(
+                (lowest_y(&mesh) + half_height).abs() < 0.02,
+                "{primitive:?} base at {} with half height {half_height}",
+                lowest_y(&mesh)
+            );
+        }
+    }
+
+    #[test]
+    fn test_subdivisions_set_resolution() {
+        let vertices =
+            |primitive, subdivisions| primitive_mesh(primitive, subdivisions).0.count_vertices();
+        assert!(
+            vertices(PrimitiveType::Sphere, Some(64)) > vertices(PrimitiveType::Sphere, Some(8))
+        );
+        for primitive in [
+            PrimitiveType::Cylinder,
+            PrimitiveType::Torus,
+            PrimitiveType::Cone,
+            PrimitiveType::Capsule,
+        ] {
+            assert!(
+                vertices(primitive, Some(64)) > vertices(primitive, Some(8)),
+                "{primitive:?}"
+            );
+        }
+        // Out-of-range requests are clamped instead of building degenerate meshes
+        assert_eq!(
+            vertices(PrimitiveType::Sphere, Some(0)),
+            vertices(PrimitiveType::Sphere, Some(3))
+        );
+        // (n + 1)^2 vertices for n cuts
+        assert_eq!(vertices(PrimitiveType::Grid { subdivisions: 3 }, None), 25);
+    }
+
+    #[test]
+    fn test_primitive_meshes_are_paintable() {
+        // (primitive, vertices, indices) at the default 32 segments
+        let golden = [
+            (PrimitiveType::Cube, 24, 36),
+            (PrimitiveType::Sphere, 561, 2880),
+            (PrimitiveType::Cylinder, 130, 372),
+            (PrimitiveType::Plane, 4, 6),
+            (PrimitiveType::Torus, 561, 3072),
+            (PrimitiveType::Cone, 65, 186),
+            (PrimitiveType::Capsule, 592, 3072),
+            (PrimitiveType::Monkey, 1950, 9504),
+            (PrimitiveType::Grid { subdivisions: 3 }, 25, 96),
+        ];
+        for (primitive, vertices, indices) in golden {
+            let (mesh, _) = primitive_mesh(primitive, None);
+            assert_eq!(mesh.count_vertices(), vertices, "{primitive:?} vertices");
+            assert_eq!(
+                mesh.indices().map(|indices| indices.len()),
+                Some(indices),
+                "{primitive:?} indices"
+            );
+            assert!(
+                mesh.contains_attribute(Mesh::ATTRIBUTE_NORMAL),
+                "{primitive:?} normals"
+            );
+            assert!(
+                mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT),
+                "{primitive:?} tangents"
+            );
+
+            let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+            else {
+                panic!("{primitive:?} without UVs");
+            };
+            assert!(
+                uvs.iter().flatten().all(|c| c.is_finite()),
+                "{primitive:?} UVs"
+            );
+            for axis in 0..2 {
+                let min = uvs.iter().map(|uv| uv[axis]).fold(f32::MAX, f32::min);
+                let max = uvs.iter().map(|uv| uv[axis]).fold(f32::MIN, f32::max);
+                assert!(
+                    min.abs() < 1e-4 && (max - 1.0).abs() < 1e-4,
+                    "{primitive:?} UV axis {axis} spans {min}..{max}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_scene_point_falls_back_to_ground_plane() {
+        let ray = Ray3d::new(Vec3::new(1.0, 5.0, 2.0), Dir3::NEG_Y);
+        assert_eq!(scene_point(ray, None), Some(Vec3::new(1.0, 0.0, 2.0)));
+
+        // Looking away from the ground there's nothing to stand on
+        let ray = Ray3d::new(Vec3::new(0.0, 5.0, 0.0), Dir3::Y);
+        assert_eq!(scene_point(ray, None), None);
     }
 }
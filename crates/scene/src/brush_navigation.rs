@@ -0,0 +1,129 @@
+//! Camera navigation while painting or sculpting
+//!
+//! In the brush modes (canvas paint, mesh paint, sculpt) the left button
+//! drives the brush, and navigation passes through to the camera controller:
+//! - Middle mouse drag orbits (Shift pans) and the wheel zooms, even while
+//!   the camera is locked to a canvas plane
+//! - Space + left drag pans
+//! - Holding `paint.suspend` (Alt by default) holds back dabs
+//!
+//! Each mode's input system checks [`BrushNavigation`] first. Navigating ends
+//! a stroke in progress as if the button had been released, so the stroke log
+//! only ever records whole strokes, and hides the brush cursor. Strokes don't
+//! start over the UI (`PointerOverUi`).
+
+use bevy::input::InputSystems;
+use bevy::prelude::*;
+use pentimento_config::{Keymap, actions};
+
+use crate::edit_mode::{EditModeState, EditorMode};
+use crate::gizmo::PointerOverUi;
+
+/// How mouse input is shared between the brush and the camera this frame
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BrushNavigation {
+    /// A brush mode is active
+    pub brush_mode: bool,
+    /// The camera has the mouse: no dabs, no brush cursor
+    pub navigating: bool,
+    /// Space is held, so a left drag pans the camera
+    pub space_pan: bool,
+    /// The cursor is over the UI
+    pub over_ui: bool,
+}
+
+impl BrushNavigation {
+    /// Whether a left press starts a stroke
+    pub fn starts_stroke(&self) -> bool {
+        !self.navigating && !self.over_ui
+    }
+}
+
+/// Plugin routing brush-mode mouse input between the brush and the camera
+pub struct BrushNavigationPlugin;
+
+impl Plugin for BrushNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BrushNavigation>()
+            .add_systems(PreUpdate, route_brush_input.after(InputSystems));
+    }
+}
+
+/// Work out who gets the mouse, before the brush and camera systems run
+fn route_brush_input(
+    edit_mode: Res<EditModeState>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    pointer_over_ui: Res<PointerOverUi>,
+    mut navigation: ResMut<BrushNavigation>,
+) {
+    let routed = brush_routing(
+        edit_mode.mode,
+        mouse_button.pressed(MouseButton::Middle),
+        key_input.pressed(KeyCode::Space),
+        keymap.held(actions::PAINT_SUSPEND, &key_input),
+        pointer_over_ui.0,
+    );
+    if *navigation != routed {
+        *navigation = routed;
+    }
+}
+
+/// Routing for a mode and the navigation inputs held
+fn brush_routing(
+    mode: EditorMode,
+    middle: bool,
+    space: bool,
+    suspend: bool,
+    over_ui: bool,
+) -> BrushNavigation {
+    let brush_mode = matches!(
+        mode,
+        EditorMode::Paint | EditorMode::MeshPaint | EditorMode::Sculpt
+    );
+    if !brush_mode {
+        return BrushNavigation {
+            over_ui,
+            ..default()
+        };
+    }
+    BrushNavigation {
+        brush_mode,
+        navigating: middle || space || suspend,
+        space_pan: space,
+        over_ui,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navigation_holds_back_the_brush() {
+        let idle = brush_routing(EditorMode::Paint, false, false, false, false);
+        assert!(idle.brush_mode && !idle.navigating && idle.starts_stroke());
+
+        let orbiting = brush_routing(EditorMode::Sculpt, true, false, false, false);
+        assert!(orbiting.navigating && !orbiting.starts_stroke());
+
+        let panning = brush_routing(EditorMode::MeshPaint, false, true, false, false);
+        assert!(panning.navigating && panning.space_pan);
+
+        let suspended = brush_routing(EditorMode::Paint, false, false, true, false);
+        assert!(suspended.navigating && !suspended.space_pan);
+    }
+
+    #[test]
+    fn test_strokes_do_not_start_over_the_ui() {
+        let over_panel = brush_routing(EditorMode::Paint, false, false, false, true);
+        assert!(!over_panel.navigating && !over_panel.starts_stroke());
+    }
+
+    #[test]
+    fn test_other_modes_are_not_routed() {
+        let object = brush_routing(EditorMode::Object, true, true, true, false);
+        assert!(!object.brush_mode && !object.navigating && !object.space_pan);
+    }
+}
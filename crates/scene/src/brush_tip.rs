@@ -0,0 +1,111 @@
+//! Stamp brush tip selection
+//!
+//! Loads the tip named by `SetBrushTip` (built-in or image file) into the
+//! shared brush preset, so every canvas pipeline stamps it, and applies the
+//! tip rotation mode.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use bevy::ecs::message::Message;
+use bevy::prelude::*;
+use image::ImageReader;
+use painting::{BrushTip, TipRotation};
+use pentimento_ipc::{BrushTipSource, TipRotationMode};
+
+use crate::OutboundUiMessages;
+use crate::canvas_file::MAX_IMAGE_DIMENSION;
+use crate::painting_system::PaintingResource;
+
+/// Message for changing the stamp brush tip
+#[derive(Message, Debug, Clone)]
+pub enum BrushTipEvent {
+    /// Load a tip (or go back to the round brush)
+    SetTip { source: BrushTipSource },
+    /// Change how the tip rotates per dab
+    SetRotation { mode: TipRotationMode },
+}
+
+/// Apply tip changes to the brush preset of every pipeline
+pub(crate) fn handle_brush_tip_events(
+    mut events: MessageReader<BrushTipEvent>,
+    mut painting_res: ResMut<PaintingResource>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for event in events.read() {
+        let mut preset = painting_res.brush_preset.clone();
+        match event {
+            BrushTipEvent::SetTip { source } => {
+                let tip = match source {
+                    BrushTipSource::Round => None,
+                    BrushTipSource::BuiltIn(name) => match BrushTip::builtin(name) {
+                        Some(tip) => Some(tip),
+                        None => {
+                            outbound.send_error(
+                                "brush_tip_unknown",
+                                format!("There is no built-in brush tip named '{}'", name),
+                            );
+                            continue;
+                        }
+                    },
+                    BrushTipSource::File(path) => match load_tip(Path::new(path)) {
+                        Ok(tip) => Some(tip),
+                        Err(message) => {
+                            outbound.send_error("brush_tip_load_failed", message);
+                            continue;
+                        }
+                    },
+                };
+                match &tip {
+                    Some(tip) => info!("Brush tip set to {:?}", tip),
+                    None => info!("Brush tip set to round"),
+                }
+                preset.tip = tip.map(Arc::new);
+            }
+            BrushTipEvent::SetRotation { mode } => {
+                preset.tip_rotation = match *mode {
+                    TipRotationMode::Fixed(degrees) => TipRotation::Fixed(degrees),
+                    TipRotationMode::FollowStroke => TipRotation::FollowStroke,
+                    TipRotationMode::Random => TipRotation::Random,
+                };
+                debug!("Brush tip rotation set to {:?}", mode);
+            }
+        }
+        painting_res.set_brush_preset(preset);
+    }
+}
+
+/// Load an image file as a tip mask: brightness times alpha is coverage
+fn load_tip(path: &Path) -> Result<BrushTip, String> {
+    let reader = ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let (width, height) = reader
+        .into_dimensions()
+        .map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    if width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+        return Err(format!(
+            "{} is {}x{}; brush tips up to {}x{} can be loaded",
+            path.display(),
+            width,
+            height,
+            MAX_IMAGE_DIMENSION,
+            MAX_IMAGE_DIMENSION
+        ));
+    }
+
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?
+        .to_luma_alpha8();
+    let alpha: Vec<u8> = image
+        .pixels()
+        .map(|pixel| ((pixel.0[0] as u16 * pixel.0[1] as u16) / 255) as u8)
+        .collect();
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+
+    BrushTip::from_alpha(name, image.width(), image.height(), &alpha)
+        .map_err(|e| format!("Failed to load brush tip {}: {}", path.display(), e))
+}
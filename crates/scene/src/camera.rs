@@ -4,12 +4,36 @@
 //! - Middle mouse drag: Orbit around target
 //! - Shift + Middle mouse drag: Pan
 //! - Scroll wheel: Dolly (zoom)
+//!
+//! `CameraCommandEvent` drives the same controls from the UI or a navigation
+//! device (gamepad, SpaceMouse). Orbit and pan deltas are in mouse pixels and
+//! zoom deltas in scroll lines, so every source shares the camera sensitivities.
+//!
+//! With [`OrbitSettings`] (`AppSettings::orbit`), a mouse orbit that starts over
+//! the scene eases the target onto the surface under the cursor, and scrolling
+//! keeps the point under the cursor in place.
+//!
+//! In the brush modes the mouse still navigates while the camera is locked to
+//! a canvas plane, and Space + left drag pans (see `brush_navigation`).
 
+use bevy::ecs::system::SystemParam;
 use bevy::input::mouse::{MouseButton, MouseMotion, MouseWheel};
 use bevy::prelude::*;
+use bevy::render::render_resource::TextureUsages;
+use bevy::window::PrimaryWindow;
+use pentimento_ipc::{CameraCommand, OrbitOptions};
 
+use crate::brush_navigation::BrushNavigation;
 use crate::canvas_plane::ActiveCanvasPlane;
 use crate::gizmo::GizmoState;
+use crate::scene_bvh::SceneBvh;
+
+/// Farthest surface an orbit pivots around, in multiples of the orbit distance
+const DEPTH_PIVOT_RANGE: f32 = 4.0;
+
+/// How fast the orbit target eases onto a new pivot (per second); it covers
+/// about 95% of the way in 3 / rate seconds
+const PIVOT_EASE_RATE: f32 = 20.0;
 
 /// Marker component for the main camera
 #[derive(Component)]
@@ -36,6 +60,8 @@ pub struct OrbitCamera {
     pub min_distance: f32,
     /// Maximum distance from target
     pub max_distance: f32,
+    /// Surface point the target is easing toward (depth-aware orbit)
+    pub pivot_goal: Option<Vec3>,
 }
 
 impl Default for OrbitCamera {
@@ -54,6 +80,7 @@ impl Default for OrbitCamera {
             zoom_sensitivity: 1.0,
             min_distance: 0.5,
             max_distance: 200.0,
+            pivot_goal: None,
         }
     }
 }
@@ -75,13 +102,147 @@ impl OrbitCamera {
     pub fn reset(&mut self) {
         *self = Self::default();
     }
+
+    /// Move the camera to `position`, still looking at the target
+    pub fn look_from(&mut self, position: Vec3) {
+        let offset = position - self.target;
+        let distance = offset.length();
+        if distance > f32::EPSILON {
+            self.distance = distance.clamp(self.min_distance, self.max_distance);
+            self.yaw = offset.x.atan2(offset.z);
+            self.pitch = (offset.y / distance).asin().clamp(-1.5, 1.5);
+        }
+    }
+
+    /// Look at `target` from where the camera is now
+    pub fn retarget(&mut self, target: Vec3) {
+        let position = self.calculate_position();
+        self.target = target;
+        self.look_from(position);
+    }
+
+    /// Ease the target toward `pivot_goal` over `delta_secs`, keeping the
+    /// camera in place
+    pub fn ease_toward_pivot(&mut self, delta_secs: f32) {
+        let Some(goal) = self.pivot_goal else {
+            return;
+        };
+        let blend = 1.0 - (-PIVOT_EASE_RATE * delta_secs).exp();
+        let target = self.target.lerp(goal, blend);
+        if target.distance(goal) < 1e-3 {
+            self.retarget(goal);
+            self.pivot_goal = None;
+        } else {
+            self.retarget(target);
+        }
+    }
+
+    /// Orbit around the target by a mouse delta in pixels
+    pub fn orbit(&mut self, delta: Vec2) {
+        // Horizontal movement rotates around Y axis (yaw)
+        self.yaw -= delta.x * self.orbit_sensitivity;
+
+        // Vertical movement changes pitch (elevation)
+        self.pitch -= delta.y * self.orbit_sensitivity;
+
+        // Clamp pitch to prevent flipping (just below straight up/down)
+        self.pitch = self.pitch.clamp(-1.5, 1.5);
+    }
+
+    /// Pan the target in the camera's view plane by a mouse delta in pixels
+    pub fn pan(&mut self, delta: Vec2, camera_transform: &Transform) {
+        // Pan in camera's local XY plane
+        let right = camera_transform.rotation * Vec3::X;
+        let up = camera_transform.rotation * Vec3::Y;
+
+        // Scale pan by distance so it feels consistent at different zoom levels
+        let pan_scale = self.pan_sensitivity * self.distance;
+
+        // Move target (negative to feel like dragging the scene)
+        let pan_offset = (-right * delta.x + up * delta.y) * pan_scale;
+        self.target += pan_offset;
+        self.pivot_goal = None;
+    }
+
+    /// Dolly toward the target by a scroll amount in lines (positive zooms in)
+    pub fn zoom(&mut self, scroll_delta: f32) {
+        // Zoom by adjusting distance (scroll up = zoom in = decrease distance)
+        // Scale zoom speed by current distance for consistent feel
+        let zoom_amount = scroll_delta * self.zoom_sensitivity * (self.distance * 0.1);
+        self.distance -= zoom_amount;
+        self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+    }
+
+    /// Zoom like [`zoom`](Self::zoom), keeping what lies along `ray` (the
+    /// cursor's view ray direction) under the cursor. `forward` is the
+    /// camera's view direction.
+    ///
+    /// Dollying by `d` along `forward` and shifting camera and target by
+    /// `d * (ray / (ray · forward) - forward)` keeps every point on the ray
+    /// on it, whatever its depth.
+    pub fn zoom_toward(&mut self, scroll_delta: f32, ray: Vec3, forward: Vec3) {
+        let before = self.distance;
+        self.zoom(scroll_delta);
+        let along = ray.dot(forward);
+        if along > f32::EPSILON {
+            let dolly = before - self.distance;
+            self.target += dolly * (ray / along - forward);
+            self.pivot_goal = None;
+        }
+    }
 }
 
+/// Orbit pivot and zoom behavior (`AppSettings::orbit`), updated from the UI
+#[derive(Resource, Default)]
+pub struct OrbitSettings(pub OrbitOptions);
+
+/// View ray under the mouse cursor, for the main camera
+#[derive(SystemParam)]
+struct CursorRay<'w, 's> {
+    window: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    camera: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<MainCamera>>,
+}
+
+impl CursorRay<'_, '_> {
+    /// Ray through the cursor, or `None` when it is outside the window
+    fn ray(&self) -> Option<Ray3d> {
+        let cursor = self.window.single().ok()?.cursor_position()?;
+        let (camera, camera_transform) = self.camera.single().ok()?;
+        camera.viewport_to_world(camera_transform, cursor).ok()
+    }
+
+    /// Ray through the cursor, or through the middle of the viewport when
+    /// the cursor is outside the window
+    fn ray_or_center(&self) -> Option<Ray3d> {
+        self.ray().or_else(|| {
+            let (camera, camera_transform) = self.camera.single().ok()?;
+            let center = camera.logical_viewport_rect()?.center();
+            camera.viewport_to_world(camera_transform, center).ok()
+        })
+    }
+}
+
+/// Let post-process passes sample a camera's depth texture, keeping the
+/// usages it already has
+pub(crate) fn enable_depth_texture_binding(camera: &mut Camera3d) {
+    let usages = TextureUsages::from(camera.depth_texture_usages);
+    if !usages.contains(TextureUsages::TEXTURE_BINDING) {
+        camera.depth_texture_usages = (usages | TextureUsages::TEXTURE_BINDING).into();
+    }
+}
+
+/// Camera command from the UI or a navigation device
+#[derive(Message, Debug, Clone)]
+pub struct CameraCommandEvent(pub CameraCommand);
+
 /// Plugin for Blender-style camera controls
 pub struct CameraControllerPlugin;
 
 impl Plugin for CameraControllerPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<OrbitSettings>()
+            .add_message::<CameraCommandEvent>();
+
         // Order systems to avoid MessageReader conflicts:
         // orbit and pan both read MouseMotion, so they must run sequentially
         app.add_systems(
@@ -90,26 +251,38 @@ impl Plugin for CameraControllerPlugin {
                 camera_orbit_system,
                 camera_pan_system.after(camera_orbit_system),
                 camera_zoom_system,
-                update_camera_transform
+                handle_camera_commands,
+                ease_orbit_pivot
                     .after(camera_orbit_system)
                     .after(camera_pan_system)
-                    .after(camera_zoom_system),
+                    .after(camera_zoom_system)
+                    .after(handle_camera_commands),
+                update_camera_transform.after(ease_orbit_pivot),
             ),
         );
     }
 }
 
 /// Handle orbit (middle mouse drag without shift)
+///
+/// Pressing the button over the scene starts easing the target onto the
+/// surface under the cursor, when that is on by [`OrbitSettings`].
+#[allow(clippy::too_many_arguments)]
 fn camera_orbit_system(
     mouse_button: Res<ButtonInput<MouseButton>>,
     key_input: Res<ButtonInput<KeyCode>>,
     mut motion_events: MessageReader<MouseMotion>,
     mut camera_query: Query<&mut OrbitCamera>,
     active_plane: Res<ActiveCanvasPlane>,
+    navigation: Res<BrushNavigation>,
     gizmo_state: Res<GizmoState>,
+    settings: Res<OrbitSettings>,
+    cursor: CursorRay,
+    bvh: Option<Res<SceneBvh>>,
 ) {
-    // Don't allow camera movement when locked to a canvas plane
-    if active_plane.camera_locked {
+    // Don't allow camera movement when locked to a canvas plane, unless
+    // navigating from a brush mode
+    if active_plane.camera_locked && !navigation.brush_mode {
         motion_events.clear();
         return;
     }
@@ -134,6 +307,21 @@ fn camera_orbit_system(
         return; // Pan mode, not orbit
     }
 
+    if mouse_button.just_pressed(MouseButton::Middle) && settings.0.orbit_around_depth {
+        let hit = cursor
+            .ray_or_center()
+            .zip(bvh.as_deref())
+            .and_then(|(ray, bvh)| bvh.raycast(ray, |_| true));
+        if let Some(hit) = hit {
+            for mut orbit in camera_query.iter_mut() {
+                // Far-off surfaces (the background) would swing the view away
+                if hit.distance <= orbit.distance * DEPTH_PIVOT_RANGE {
+                    orbit.pivot_goal = Some(hit.position);
+                }
+            }
+        }
+    }
+
     let mut delta = Vec2::ZERO;
     for event in motion_events.read() {
         delta += event.delta;
@@ -144,28 +332,23 @@ fn camera_orbit_system(
     }
 
     for mut orbit in camera_query.iter_mut() {
-        // Horizontal movement rotates around Y axis (yaw)
-        orbit.yaw -= delta.x * orbit.orbit_sensitivity;
-
-        // Vertical movement changes pitch (elevation)
-        orbit.pitch -= delta.y * orbit.orbit_sensitivity;
-
-        // Clamp pitch to prevent flipping (just below straight up/down)
-        orbit.pitch = orbit.pitch.clamp(-1.5, 1.5);
+        orbit.orbit(delta);
     }
 }
 
-/// Handle pan (shift + middle mouse drag)
+/// Handle pan (shift + middle mouse drag, or space + left drag in a brush mode)
 fn camera_pan_system(
     mouse_button: Res<ButtonInput<MouseButton>>,
     key_input: Res<ButtonInput<KeyCode>>,
     mut motion_events: MessageReader<MouseMotion>,
     mut camera_query: Query<(&mut OrbitCamera, &Transform)>,
     active_plane: Res<ActiveCanvasPlane>,
+    navigation: Res<BrushNavigation>,
     gizmo_state: Res<GizmoState>,
 ) {
-    // Don't allow camera movement when locked to a canvas plane
-    if active_plane.camera_locked {
+    // Don't allow camera movement when locked to a canvas plane, unless
+    // navigating from a brush mode
+    if active_plane.camera_locked && !navigation.brush_mode {
         motion_events.clear();
         return;
     }
@@ -176,17 +359,14 @@ fn camera_pan_system(
         return;
     }
 
-    if !mouse_button.pressed(MouseButton::Middle) {
-        motion_events.clear();
-        return;
-    }
-
     let shift_held =
         key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
+    let middle_pan = mouse_button.pressed(MouseButton::Middle) && shift_held;
+    let space_pan = navigation.space_pan && mouse_button.pressed(MouseButton::Left);
 
-    if !shift_held {
+    if !middle_pan && !space_pan {
         motion_events.clear();
-        return; // Orbit mode, not pan
+        return; // Orbit mode (or no drag), not pan
     }
 
     let mut delta = Vec2::ZERO;
@@ -199,28 +379,24 @@ fn camera_pan_system(
     }
 
     for (mut orbit, transform) in camera_query.iter_mut() {
-        // Pan in camera's local XY plane
-        let right = transform.rotation * Vec3::X;
-        let up = transform.rotation * Vec3::Y;
-
-        // Scale pan by distance so it feels consistent at different zoom levels
-        let pan_scale = orbit.pan_sensitivity * orbit.distance;
-
-        // Move target (negative to feel like dragging the scene)
-        let pan_offset = (-right * delta.x + up * delta.y) * pan_scale;
-        orbit.target += pan_offset;
+        orbit.pan(delta, transform);
     }
 }
 
-/// Handle zoom (scroll wheel)
+/// Handle zoom (scroll wheel), toward the cursor when that is on by
+/// [`OrbitSettings`]
 fn camera_zoom_system(
     mut scroll_events: MessageReader<MouseWheel>,
     mut camera_query: Query<&mut OrbitCamera>,
     active_plane: Res<ActiveCanvasPlane>,
+    navigation: Res<BrushNavigation>,
     gizmo_state: Res<GizmoState>,
+    settings: Res<OrbitSettings>,
+    cursor: CursorRay,
 ) {
-    // Don't allow camera movement when locked to a canvas plane
-    if active_plane.camera_locked {
+    // Don't allow camera movement when locked to a canvas plane, unless
+    // navigating from a brush mode
+    if active_plane.camera_locked && !navigation.brush_mode {
         scroll_events.clear();
         return;
     }
@@ -240,12 +416,70 @@ fn camera_zoom_system(
         return;
     }
 
+    // The ray is cast with last frame's camera, which is what's on screen
+    let toward = settings
+        .0
+        .zoom_to_cursor
+        .then(|| cursor.ray())
+        .flatten()
+        .zip(cursor.camera.single().ok())
+        .map(|(ray, (_, camera_transform))| (*ray.direction, camera_transform.forward()));
     for mut orbit in camera_query.iter_mut() {
-        // Zoom by adjusting distance (scroll up = zoom in = decrease distance)
-        // Scale zoom speed by current distance for consistent feel
-        let zoom_amount = scroll_delta * orbit.zoom_sensitivity * (orbit.distance * 0.1);
-        orbit.distance -= zoom_amount;
-        orbit.distance = orbit.distance.clamp(orbit.min_distance, orbit.max_distance);
+        match toward {
+            Some((ray, forward)) => orbit.zoom_toward(scroll_delta, ray, *forward),
+            None => orbit.zoom(scroll_delta),
+        }
+    }
+}
+
+/// Apply camera commands from the UI and navigation devices
+fn handle_camera_commands(
+    mut events: MessageReader<CameraCommandEvent>,
+    mut camera_query: Query<(&mut OrbitCamera, &Transform), With<MainCamera>>,
+    active_plane: Res<ActiveCanvasPlane>,
+    gizmo_state: Res<GizmoState>,
+    mut settings: ResMut<OrbitSettings>,
+) {
+    // Same locks as the mouse controls
+    let locked = active_plane.camera_locked || gizmo_state.is_active;
+
+    for CameraCommandEvent(command) in events.read() {
+        if let CameraCommand::SetOrbitOptions(options) = command {
+            settings.0 = *options;
+            continue;
+        }
+        if locked {
+            continue;
+        }
+        for (mut orbit, transform) in camera_query.iter_mut() {
+            match *command {
+                CameraCommand::Orbit { delta_x, delta_y } => {
+                    orbit.orbit(Vec2::new(delta_x, delta_y));
+                }
+                CameraCommand::Pan { delta_x, delta_y } => {
+                    orbit.pan(Vec2::new(delta_x, delta_y), transform);
+                }
+                CameraCommand::Zoom { delta } => orbit.zoom(delta),
+                CameraCommand::SetPosition { position } => {
+                    orbit.look_from(Vec3::from_array(position));
+                }
+                CameraCommand::SetTarget { target } => {
+                    orbit.target = Vec3::from_array(target);
+                    orbit.pivot_goal = None;
+                }
+                CameraCommand::Reset => orbit.reset(),
+                CameraCommand::SetOrbitOptions(_) => {}
+            }
+        }
+    }
+}
+
+/// Ease orbit targets onto their depth pivots
+fn ease_orbit_pivot(time: Res<Time>, mut camera_query: Query<&mut OrbitCamera>) {
+    for mut orbit in camera_query.iter_mut() {
+        if orbit.pivot_goal.is_some() {
+            orbit.ease_toward_pivot(time.delta_secs());
+        }
     }
 }
 
@@ -258,3 +492,95 @@ fn update_camera_transform(
         *transform = Transform::from_translation(position).looking_at(orbit.target, Vec3::Y);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::camera::CameraProjection;
+
+    use super::*;
+
+    const VIEWPORT: Vec2 = Vec2::new(1280.0, 720.0);
+
+    fn camera_transform(orbit: &OrbitCamera) -> Transform {
+        Transform::from_translation(orbit.calculate_position()).looking_at(orbit.target, Vec3::Y)
+    }
+
+    fn projection() -> PerspectiveProjection {
+        PerspectiveProjection {
+            aspect_ratio: VIEWPORT.x / VIEWPORT.y,
+            ..default()
+        }
+    }
+
+    /// Window position of a world point
+    fn to_screen(point: Vec3, transform: &Transform) -> Vec2 {
+        let view = transform.compute_affine().inverse();
+        let clip = projection().get_clip_from_view() * view.transform_point3(point).extend(1.0);
+        let ndc = clip.truncate() / clip.w;
+        Vec2::new(
+            (ndc.x + 1.0) * 0.5 * VIEWPORT.x,
+            (1.0 - ndc.y) * 0.5 * VIEWPORT.y,
+        )
+    }
+
+    /// World direction of the view ray through a window position
+    fn ray_through(pixel: Vec2, transform: &Transform) -> Vec3 {
+        let ndc = Vec2::new(
+            pixel.x / VIEWPORT.x * 2.0 - 1.0,
+            1.0 - pixel.y / VIEWPORT.y * 2.0,
+        );
+        let view_from_clip = projection().get_clip_from_view().inverse();
+        let view_point = view_from_clip.project_point3(ndc.extend(0.5));
+        (transform.rotation * view_point).normalize()
+    }
+
+    #[test]
+    fn test_zoom_to_cursor_keeps_the_torus_under_the_cursor() {
+        // The default scene's torus, away from the middle of the default view
+        let torus = Vec3::new(-2.0, 0.5, 0.0);
+        let mut orbit = OrbitCamera::default();
+
+        for scroll in [3.0, 2.0, -4.0] {
+            let transform = camera_transform(&orbit);
+            let cursor = to_screen(torus, &transform);
+            let ray = ray_through(cursor, &transform);
+            orbit.zoom_toward(scroll, ray, *transform.forward());
+
+            let after = to_screen(torus, &camera_transform(&orbit));
+            assert!(
+                after.distance(cursor) < 2.0,
+                "torus moved from {cursor} to {after} zooming by {scroll}"
+            );
+        }
+        assert!(orbit.distance < OrbitCamera::default().distance);
+    }
+
+    #[test]
+    fn test_plain_zoom_moves_off_center_points() {
+        let torus = Vec3::new(-2.0, 0.5, 0.0);
+        let mut orbit = OrbitCamera::default();
+        let cursor = to_screen(torus, &camera_transform(&orbit));
+        orbit.zoom(3.0);
+        let after = to_screen(torus, &camera_transform(&orbit));
+        assert!(after.distance(cursor) > 10.0);
+    }
+
+    #[test]
+    fn test_easing_onto_a_pivot_keeps_the_camera_in_place() {
+        let mut orbit = OrbitCamera::default();
+        let position = orbit.calculate_position();
+        let pivot = Vec3::new(-2.0, 0.5, 0.0);
+        orbit.pivot_goal = Some(pivot);
+
+        orbit.ease_toward_pivot(1.0 / 60.0);
+        assert!(orbit.calculate_position().abs_diff_eq(position, 1e-3));
+        assert!(orbit.target.distance(pivot) < pivot.length());
+
+        for _ in 0..120 {
+            orbit.ease_toward_pivot(1.0 / 60.0);
+        }
+        assert_eq!(orbit.pivot_goal, None);
+        assert!(orbit.target.abs_diff_eq(pivot, 1e-5));
+        assert!(orbit.calculate_position().abs_diff_eq(position, 1e-3));
+    }
+}
@@ -0,0 +1,353 @@
+//! Canvas image export and import
+//!
+//! Export writes the active canvas as a PNG with alpha: 8 bits per channel,
+//! or 16 for a `*.16.png` file name. The composited pixels are copied on the
+//! main thread and encoded and written on a background thread; the UI gets
+//! `CanvasExported` once the file is on disk.
+//!
+//! Import reads any format the `image` crate decodes, scales it to fit the
+//! canvas keeping its aspect ratio (letterboxed with transparency), and
+//! replaces the active layer as one undo step. The stroke log records the
+//! file's content hash so a replay can redo the import. Every tile is marked
+//! dirty, so the whole `CanvasTexture` is re-uploaded.
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+use bevy::ecs::message::Message;
+use bevy::prelude::*;
+use image::imageops::{self, FilterType};
+use image::{ColorType, ImageBuffer, ImageError, ImageReader, Rgba, Rgba32FImage};
+use painting::content_hash;
+use pentimento_ipc::BevyToUi;
+
+use crate::OutboundUiMessages;
+use crate::canvas_plane::{ActiveCanvasPlane, CanvasPlane};
+use crate::paint_mode::StrokeIdGenerator;
+use crate::painting_system::{PaintingResource, linear_to_srgb};
+
+/// Largest image width or height accepted for import
+pub const MAX_IMAGE_DIMENSION: u32 = 16_384;
+
+/// Message for canvas image file operations
+#[derive(Message, Debug, Clone)]
+pub enum CanvasFileEvent {
+    /// Export the active canvas (to `canvas-<plane id>.png` without a path)
+    Export { path: Option<PathBuf> },
+    /// Replace the active canvas layer with an image
+    Import { path: PathBuf },
+}
+
+/// Failure reported to the UI as `BevyToUi::Error`
+#[derive(Debug)]
+struct CanvasFileError {
+    code: &'static str,
+    message: String,
+}
+
+impl CanvasFileError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Background exports in flight
+#[derive(Resource, Default)]
+pub(crate) struct CanvasExports {
+    pending: Vec<JoinHandle<Result<PathBuf, CanvasFileError>>>,
+}
+
+/// Handle export and import requests for the active canvas
+pub(crate) fn handle_canvas_file_events(
+    mut events: MessageReader<CanvasFileEvent>,
+    mut painting_res: ResMut<PaintingResource>,
+    mut exports: ResMut<CanvasExports>,
+    mut stroke_ids: ResMut<StrokeIdGenerator>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    active_plane: Res<ActiveCanvasPlane>,
+    canvas_query: Query<&CanvasPlane>,
+) {
+    for event in events.read() {
+        let Some(canvas_plane) = active_plane.entity.and_then(|e| canvas_query.get(e).ok()) else {
+            outbound.send_error("canvas_not_active", "No canvas plane is active");
+            continue;
+        };
+        let plane_id = canvas_plane.plane_id;
+        let Some(pipeline) = painting_res.get_pipeline_mut(plane_id) else {
+            continue;
+        };
+
+        match event {
+            CanvasFileEvent::Export { path } => {
+                let path = path
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(format!("canvas-{}.png", plane_id)));
+                let Some(bit_depth) = export_bit_depth(&path) else {
+                    outbound.send_error(
+                        "canvas_format_unsupported",
+                        format!("Canvas export needs a .png file name ({})", path.display()),
+                    );
+                    continue;
+                };
+
+                let (width, height) = (pipeline.width(), pipeline.height());
+                let pixels = pipeline.get_region_data(0, 0, width, height);
+                info!(
+                    "Exporting canvas plane {} to {} ({}-bit)",
+                    plane_id,
+                    path.display(),
+                    bit_depth
+                );
+                let spawned = std::thread::Builder::new()
+                    .name("canvas-export".into())
+                    .spawn(move || write_png(&path, width, height, &pixels, bit_depth));
+                match spawned {
+                    Ok(handle) => exports.pending.push(handle),
+                    Err(e) => outbound.send_error(
+                        "canvas_export_failed",
+                        format!("Failed to start canvas export: {}", e),
+                    ),
+                }
+            }
+            CanvasFileEvent::Import { path } => {
+                let (width, height) = (pipeline.width(), pipeline.height());
+                let imported = load_image(path, width, height).and_then(|(pixels, hash)| {
+                    if pipeline.import_image(plane_id, stroke_ids.next(), &pixels, hash) {
+                        Ok(())
+                    } else {
+                        Err(CanvasFileError::new(
+                            "canvas_import_failed",
+                            "Cannot import while a stroke is in progress",
+                        ))
+                    }
+                });
+                match imported {
+                    Ok(()) => info!("Imported {} into canvas plane {}", path.display(), plane_id),
+                    Err(e) => outbound.send_error(e.code, e.message),
+                }
+            }
+        }
+    }
+}
+
+/// Report finished background exports to the UI
+pub(crate) fn finish_canvas_exports(
+    mut exports: ResMut<CanvasExports>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let (finished, pending) = std::mem::take(&mut exports.pending)
+        .into_iter()
+        .partition::<Vec<_>, _>(|handle| handle.is_finished());
+    exports.pending = pending;
+
+    for handle in finished {
+        match handle.join() {
+            Ok(Ok(path)) => {
+                info!("Canvas exported to {}", path.display());
+                outbound.send(BevyToUi::CanvasExported {
+                    path: path.display().to_string(),
+                });
+            }
+            Ok(Err(e)) => outbound.send_error(e.code, e.message),
+            Err(_) => outbound.send_error("canvas_export_failed", "Canvas export thread panicked"),
+        }
+    }
+}
+
+/// Bits per channel for an export path: 16 for `*.16.png`, 8 for other
+/// `.png` names, None for anything else
+fn export_bit_depth(path: &Path) -> Option<u8> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+    if name.ends_with(".16.png") {
+        Some(16)
+    } else if name.ends_with(".png") {
+        Some(8)
+    } else {
+        None
+    }
+}
+
+/// Encode linear canvas pixels as an sRGB PNG with straight alpha
+fn write_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[[f32; 4]],
+    bit_depth: u8,
+) -> Result<PathBuf, CanvasFileError> {
+    let saved = if bit_depth == 16 {
+        let data = pixels
+            .iter()
+            .flat_map(|p| encode_pixel(*p).map(|c| (c * 65535.0).round() as u16))
+            .collect();
+        ImageBuffer::<Rgba<u16>, Vec<u16>>::from_raw(width, height, data)
+            .map(|image| image.save_with_format(path, image::ImageFormat::Png))
+    } else {
+        let data = pixels
+            .iter()
+            .flat_map(|p| encode_pixel(*p).map(|c| (c * 255.0).round() as u8))
+            .collect();
+        ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(width, height, data)
+            .map(|image| image.save_with_format(path, image::ImageFormat::Png))
+    };
+
+    match saved {
+        Some(Ok(())) => Ok(std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())),
+        Some(Err(e)) => Err(CanvasFileError::new(
+            "canvas_export_failed",
+            format!("Failed to write {}: {}", path.display(), e),
+        )),
+        None => Err(CanvasFileError::new(
+            "canvas_export_failed",
+            "Canvas pixel data does not match its size",
+        )),
+    }
+}
+
+/// Linear RGBA to sRGB-encoded RGBA, all 0.0-1.0 (alpha stays linear)
+fn encode_pixel(pixel: [f32; 4]) -> [f32; 4] {
+    [
+        linear_to_srgb(pixel[0]),
+        linear_to_srgb(pixel[1]),
+        linear_to_srgb(pixel[2]),
+        pixel[3].clamp(0.0, 1.0),
+    ]
+}
+
+fn srgb_to_linear(srgb: f32) -> f32 {
+    let srgb = srgb.clamp(0.0, 1.0);
+    if srgb <= 0.04045 {
+        srgb / 12.92
+    } else {
+        ((srgb + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Read an image file and fit it to a `width` x `height` canvas.
+/// Returns linear canvas pixels and the file's content hash.
+fn load_image(
+    path: &Path,
+    width: u32,
+    height: u32,
+) -> Result<(Vec<[f32; 4]>, u64), CanvasFileError> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        CanvasFileError::new(
+            "canvas_import_failed",
+            format!("Failed to read {}: {}", path.display(), e),
+        )
+    })?;
+    let unsupported = || {
+        CanvasFileError::new(
+            "canvas_format_unsupported",
+            format!("Unsupported image format: {}", path.display()),
+        )
+    };
+    let decode_error = |e: ImageError| match e {
+        ImageError::Unsupported(_) => unsupported(),
+        e => CanvasFileError::new(
+            "canvas_import_failed",
+            format!("Failed to decode {}: {}", path.display(), e),
+        ),
+    };
+    let reader = || -> Result<ImageReader<Cursor<&[u8]>>, CanvasFileError> {
+        let reader = ImageReader::new(Cursor::new(bytes.as_slice()))
+            .with_guessed_format()
+            .map_err(|_| unsupported())?;
+        match reader.format() {
+            Some(_) => Ok(reader),
+            None => Err(unsupported()),
+        }
+    };
+
+    // Check the size before decoding, so a huge image is never allocated
+    let (image_width, image_height) = reader()?.into_dimensions().map_err(decode_error)?;
+    if image_width > MAX_IMAGE_DIMENSION || image_height > MAX_IMAGE_DIMENSION {
+        return Err(CanvasFileError::new(
+            "canvas_image_too_large",
+            format!(
+                "{} is {}x{}; images up to {}x{} can be imported",
+                path.display(),
+                image_width,
+                image_height,
+                MAX_IMAGE_DIMENSION,
+                MAX_IMAGE_DIMENSION
+            ),
+        ));
+    }
+
+    let image = reader()?.decode().map_err(decode_error)?;
+    // Float formats are already linear; everything else is sRGB-encoded
+    let linear = matches!(image.color(), ColorType::Rgb32F | ColorType::Rgba32F);
+    let fitted = fit_to_canvas(&image.to_rgba32f(), width, height);
+
+    let pixels = fitted
+        .pixels()
+        .map(|&Rgba([r, g, b, a])| {
+            if linear {
+                [r, g, b, a]
+            } else {
+                [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+            }
+        })
+        .collect();
+    Ok((pixels, content_hash(&bytes)))
+}
+
+/// Scale `image` to fit inside `width` x `height` keeping its aspect ratio,
+/// centered on a transparent canvas
+fn fit_to_canvas(image: &Rgba32FImage, width: u32, height: u32) -> Rgba32FImage {
+    if image.dimensions() == (width, height) {
+        return image.clone();
+    }
+
+    let scale = (width as f32 / image.width() as f32).min(height as f32 / image.height() as f32);
+    let scaled_width = ((image.width() as f32 * scale).round() as u32).clamp(1, width);
+    let scaled_height = ((image.height() as f32 * scale).round() as u32).clamp(1, height);
+    let scaled = imageops::resize(image, scaled_width, scaled_height, FilterType::Lanczos3);
+
+    let mut canvas = Rgba32FImage::new(width, height);
+    imageops::replace(
+        &mut canvas,
+        &scaled,
+        ((width - scaled_width) / 2) as i64,
+        ((height - scaled_height) / 2) as i64,
+    );
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_bit_depth_from_name() {
+        assert_eq!(export_bit_depth(Path::new("canvas-1.png")), Some(8));
+        assert_eq!(export_bit_depth(Path::new("/tmp/Canvas.16.PNG")), Some(16));
+        assert_eq!(export_bit_depth(Path::new("canvas.jpg")), None);
+    }
+
+    #[test]
+    fn test_fit_to_canvas_letterboxes() {
+        let opaque = Rgba([1.0, 0.5, 0.25, 1.0]);
+        let wide = Rgba32FImage::from_pixel(200, 100, opaque);
+        let fitted = fit_to_canvas(&wide, 100, 100);
+
+        assert_eq!(fitted.dimensions(), (100, 100));
+        // Bars above and below, image in the middle
+        assert_eq!(fitted.get_pixel(50, 10)[3], 0.0);
+        assert_eq!(fitted.get_pixel(50, 90)[3], 0.0);
+        assert!((fitted.get_pixel(50, 50)[3] - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_srgb_round_trip() {
+        for value in [0.0, 0.002, 0.2, 0.5, 1.0] {
+            let encoded = encode_pixel([value, value, value, 1.0])[0];
+            assert!((srgb_to_linear(encoded) - value).abs() < 1e-4);
+        }
+    }
+}
@@ -2,17 +2,25 @@
 //!
 //! A CanvasPlane is a planar surface that can be painted on.
 //! It has a resolution (up to 1048x1048), a unique ID, and can be selected
-//! for painting. When a plane is active, the camera can be locked to it
-//! using the Tab key.
+//! for painting. Paint mode (see `edit_mode`) targets a plane and locks the
+//! camera to it; Tab toggles paint mode on the active plane.
+//!
+//! New planes are placed relative to the camera by a [`CanvasPlacement`]:
+//! facing it, flat, upright, or on the surface at the center of the view.
+//! Outside paint mode planes move with the gizmo like any object, and
+//! `CanvasPlaneEvent::Reanchor` brings one back in front of the camera.
 
 use bevy::ecs::message::Message;
 use bevy::prelude::*;
-use pentimento_ipc::{BevyToUi, EditMode};
+use pentimento_config::{Keymap, actions};
+use pentimento_ipc::{AddPaintCanvasRequest, CanvasOrientation};
 
-use crate::OutboundUiMessages;
 use crate::camera::{MainCamera, OrbitCamera};
-use crate::paint_mode::PaintMode;
+use crate::edit_mode::{EditModeAppExt, EditModeRequests, EditModeState, EditorMode};
+#[cfg(feature = "selection")]
+use crate::object_id::IdAllocator;
 use crate::painting_system::CanvasTexture;
+use crate::scene_bvh::SceneBvh;
 #[cfg(feature = "selection")]
 use crate::selection::{Selectable, Selected};
 
@@ -31,10 +39,9 @@ pub struct CanvasPlane {
     pub world_width: f32,
     /// World-space height of the plane (for UV calculation)
     pub world_height: f32,
-    /// Camera position when paint mode was entered (for returning to paint view)
-    pub paint_camera_pos: Option<Vec3>,
-    /// Camera target (plane center) when paint mode was entered
-    pub paint_camera_target: Option<Vec3>,
+    /// Camera position the plane was placed from, in the plane's frame, so
+    /// paint mode returns to the same view after the plane is moved
+    pub paint_view: Option<Vec3>,
 }
 
 impl CanvasPlane {
@@ -56,8 +63,7 @@ impl CanvasPlane {
             active: false,
             world_width,
             world_height,
-            paint_camera_pos: None,
-            paint_camera_target: None,
+            paint_view: None,
         }
     }
 }
@@ -67,7 +73,7 @@ impl CanvasPlane {
 pub struct ActiveCanvasPlane {
     /// The entity of the currently active canvas plane, if any
     pub entity: Option<Entity>,
-    /// Whether the camera is locked to the active plane
+    /// Whether the camera is locked to the active plane (while in paint mode)
     pub camera_locked: bool,
 }
 
@@ -86,6 +92,55 @@ impl CanvasPlaneIdGenerator {
     }
 }
 
+/// Default distance from the camera to a new canvas, in world units
+const DEFAULT_CANVAS_DISTANCE: f32 = 2.0;
+
+/// Vertical field of view a canvas fills when no world size is given
+/// (Bevy's default perspective)
+const CANVAS_FILL_FOV: f32 = std::f32::consts::FRAC_PI_4;
+
+/// Gap between a surface-aligned canvas and the surface, against z-fighting
+const SURFACE_OFFSET: f32 = 0.002;
+
+/// Where a new canvas plane goes relative to the camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanvasPlacement {
+    /// Width and height in world units; `None` fills the view at `distance`
+    /// with the canvas's aspect ratio
+    pub world_size: Option<Vec2>,
+    /// Distance from the camera along the view
+    pub distance: f32,
+    /// How the plane is turned
+    pub orientation: CanvasOrientation,
+}
+
+impl Default for CanvasPlacement {
+    fn default() -> Self {
+        Self {
+            world_size: None,
+            distance: DEFAULT_CANVAS_DISTANCE,
+            orientation: CanvasOrientation::FacingCamera,
+        }
+    }
+}
+
+impl CanvasPlacement {
+    /// Placement asked for by the UI, with defaults for what it left out
+    pub fn from_request(request: &AddPaintCanvasRequest) -> Self {
+        Self {
+            world_size: request
+                .world_size
+                .map(Vec2::from)
+                .filter(|size| size.x > 0.0 && size.y > 0.0),
+            distance: request
+                .distance
+                .filter(|distance| *distance > 0.0)
+                .unwrap_or(DEFAULT_CANVAS_DISTANCE),
+            orientation: request.orientation,
+        }
+    }
+}
+
 /// Message for canvas plane actions
 #[derive(Message, Debug, Clone)]
 pub enum CanvasPlaneEvent {
@@ -96,12 +151,20 @@ pub enum CanvasPlaneEvent {
         height: u32,
     },
     /// Create a canvas plane in front of the camera and enter paint mode
-    CreateInFrontOfCamera { width: u32, height: u32 },
+    CreateInFrontOfCamera {
+        width: u32,
+        height: u32,
+        placement: CanvasPlacement,
+    },
+    /// Move a canvas plane (the active one without `plane_id`) back in front
+    /// of the camera, keeping its size and pixels
+    Reanchor { plane_id: Option<u32> },
     /// Select a canvas plane for painting
     Select(Entity),
     /// Deselect the current canvas plane
     Deselect,
-    /// Toggle camera lock (Tab key)
+    /// Toggle paint mode, and with it the camera lock, on the active plane
+    /// (Tab key)
     ToggleCameraLock,
 }
 
@@ -120,7 +183,10 @@ impl Plugin for CanvasPlanePlugin {
                     handle_camera_lock_input.after(handle_canvas_plane_events),
                     update_canvas_materials,
                 ),
-            );
+            )
+            .set_edit_mode_guard(EditorMode::Paint, paint_mode_guard)
+            .on_enter_edit_mode(EditorMode::Paint, enter_canvas_paint_mode)
+            .on_exit_edit_mode(EditorMode::Paint, exit_canvas_paint_mode);
 
         #[cfg(feature = "selection")]
         app.add_systems(
@@ -131,6 +197,7 @@ impl Plugin for CanvasPlanePlugin {
 }
 
 /// Handle canvas plane events (create, select, deselect, toggle camera lock)
+#[allow(clippy::too_many_arguments)]
 fn handle_canvas_plane_events(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -138,11 +205,12 @@ fn handle_canvas_plane_events(
     mut events: MessageReader<CanvasPlaneEvent>,
     mut active_plane: ResMut<ActiveCanvasPlane>,
     mut id_generator: ResMut<CanvasPlaneIdGenerator>,
-    mut canvas_query: Query<&mut CanvasPlane>,
+    #[cfg(feature = "selection")] mut allocator: ResMut<IdAllocator>,
+    mut canvas_query: Query<(Entity, &mut CanvasPlane, &mut Transform)>,
     camera_query: Query<&GlobalTransform, With<MainCamera>>,
-    mut orbit_camera_query: Query<&mut OrbitCamera>,
-    mut paint_mode: ResMut<PaintMode>,
-    mut outbound: ResMut<OutboundUiMessages>,
+    bvh: Option<Res<SceneBvh>>,
+    edit_mode: Res<EditModeState>,
+    mut mode_requests: EditModeRequests,
 ) {
     for event in events.read() {
         match event {
@@ -185,7 +253,7 @@ fn handle_canvas_plane_events(
 
                 #[cfg(feature = "selection")]
                 commands.entity(entity).insert(Selectable {
-                    id: format!("canvas_{}", plane_id),
+                    id: allocator.allocate(),
                 });
 
                 info!(
@@ -195,38 +263,43 @@ fn handle_canvas_plane_events(
 
                 // Automatically select the newly created plane
                 active_plane.entity = Some(entity);
-                if let Ok(mut plane) = canvas_query.get_mut(entity) {
+                if let Ok((_, mut plane, _)) = canvas_query.get_mut(entity) {
                     plane.active = true;
                 }
             }
-            CanvasPlaneEvent::CreateInFrontOfCamera { width, height } => {
-                // Get camera position and forward direction
+            CanvasPlaneEvent::CreateInFrontOfCamera {
+                width,
+                height,
+                placement,
+            } => {
                 let Ok(camera_transform) = camera_query.single() else {
                     warn!("No main camera found for CreateInFrontOfCamera");
                     continue;
                 };
 
-                let camera_pos = camera_transform.translation();
-                let forward = camera_transform.forward();
-
-                // Position the plane in front of the camera
-                let distance = 2.0;
-                let plane_pos = camera_pos + *forward * distance;
-
-                let plane_id = id_generator.next();
+                // Surface-aligned canvases go where the center of the view
+                // meets the scene (other canvases don't count)
+                let surface = if placement.orientation == CanvasOrientation::SurfaceAligned {
+                    let ray =
+                        Ray3d::new(camera_transform.translation(), camera_transform.forward());
+                    let hit = bvh
+                        .as_deref()
+                        .and_then(|bvh| bvh.raycast(ray, |entity| !canvas_query.contains(entity)));
+                    if hit.is_none() {
+                        info!("No surface at the center of the view, canvas faces the camera");
+                    }
+                    hit.map(|hit| (hit.position, hit.normal))
+                } else {
+                    None
+                };
 
-                // Calculate plane size to fill camera's field of view
-                // Default Bevy FOV is 45 degrees (vertical)
-                let fov = std::f32::consts::FRAC_PI_4;
-                let half_height = (fov / 2.0).tan() * distance;
-                // Use canvas aspect ratio for the plane
                 let aspect_ratio = *width as f32 / *height as f32;
-                let half_width = half_height * aspect_ratio;
-                let plane_width = half_width * 2.0;
-                let plane_height = half_height * 2.0;
+                let (transform, size) =
+                    place_canvas(camera_transform, placement, aspect_ratio, surface);
+                let plane_id = id_generator.next();
 
-                // Create a vertical quad mesh (Rectangle is in XY plane)
-                let mesh = Rectangle::new(plane_width, plane_height);
+                // Create a quad mesh (Rectangle is in the XY plane, front face at +Z)
+                let mesh = Rectangle::new(size.x, size.y);
 
                 // Fully transparent material - only the painted texture will show
                 let material = StandardMaterial {
@@ -237,20 +310,13 @@ fn handle_canvas_plane_events(
                     ..default()
                 };
 
-                // Create transform facing the camera
-                // Rectangle mesh is in XY plane with front face at +Z
-                // looking_at makes -Z point toward target, so we rotate 180° around Y
-                // to make the +Z face (front) point toward the camera
-                let mut transform =
-                    Transform::from_translation(plane_pos).looking_at(camera_pos, Vec3::Y);
-                transform.rotate_local_y(std::f32::consts::PI);
-
                 // Create canvas plane with world dimensions
-                let mut canvas_plane =
-                    CanvasPlane::new(plane_id, *width, *height, plane_width, plane_height);
-                // Store camera position for returning to paint view
-                canvas_plane.paint_camera_pos = Some(camera_pos);
-                canvas_plane.paint_camera_target = Some(plane_pos);
+                let mut canvas_plane = CanvasPlane::new(plane_id, *width, *height, size.x, size.y);
+                // Store the camera position for returning to paint view
+                canvas_plane.paint_view = Some(view_in_plane_frame(
+                    &transform,
+                    camera_transform.translation(),
+                ));
 
                 let entity = commands
                     .spawn((
@@ -264,118 +330,277 @@ fn handle_canvas_plane_events(
 
                 #[cfg(feature = "selection")]
                 commands.entity(entity).insert(Selectable {
-                    id: format!("canvas_{}", plane_id),
+                    id: allocator.allocate(),
                 });
 
                 info!(
-                    "Created canvas plane {} in front of camera at {:?} with resolution {}x{}",
-                    plane_id, plane_pos, width, height
+                    "Created {:?} canvas plane {} at {:?}, {}x{} world units, resolution {}x{}",
+                    placement.orientation,
+                    plane_id,
+                    transform.translation,
+                    size.x,
+                    size.y,
+                    width,
+                    height
                 );
 
-                // Auto-select and lock camera
-                active_plane.entity = Some(entity);
-                active_plane.camera_locked = true;
+                // Paint on it right away, with the camera locked
+                mode_requests.request_mode_change_for(EditorMode::Paint, entity);
+            }
+            CanvasPlaneEvent::Reanchor { plane_id } => {
+                let Ok(camera_transform) = camera_query.single() else {
+                    warn!("No main camera found for Reanchor");
+                    continue;
+                };
+                let entity = match plane_id {
+                    Some(id) => canvas_query
+                        .iter()
+                        .find(|(_, plane, _)| plane.plane_id == *id)
+                        .map(|(entity, ..)| entity),
+                    None => active_plane.entity,
+                };
+                let Some((entity, mut plane, mut transform)) =
+                    entity.and_then(|entity| canvas_query.get_mut(entity).ok())
+                else {
+                    warn!("No canvas plane to reanchor");
+                    continue;
+                };
 
-                // Enable paint mode and notify UI
-                paint_mode.active = true;
-                outbound.send(BevyToUi::EditModeChanged {
-                    mode: EditMode::Paint,
-                });
-                info!("Entered paint mode with camera locked");
+                *transform = reanchor_transform(
+                    camera_transform,
+                    Vec2::new(plane.world_width, plane.world_height),
+                    transform.scale,
+                );
+                plane.paint_view = Some(view_in_plane_frame(
+                    &transform,
+                    camera_transform.translation(),
+                ));
+                info!(
+                    "Reanchored canvas plane {} at {:?}",
+                    plane.plane_id, transform.translation
+                );
+
+                // Paint mode on this plane looks at it from the new view
+                if edit_mode.mode == EditorMode::Paint && edit_mode.target_entity == Some(entity) {
+                    mode_requests.request_mode_change_for(EditorMode::Paint, entity);
+                }
             }
             CanvasPlaneEvent::Select(entity) => {
                 // Deactivate previous plane
                 if let Some(prev_entity) = active_plane.entity {
-                    if let Ok(mut prev_plane) = canvas_query.get_mut(prev_entity) {
+                    if let Ok((_, mut prev_plane, _)) = canvas_query.get_mut(prev_entity) {
                         prev_plane.active = false;
                     }
                 }
 
                 // Activate new plane
-                if let Ok(mut plane) = canvas_query.get_mut(*entity) {
+                if let Ok((_, mut plane, _)) = canvas_query.get_mut(*entity) {
                     plane.active = true;
                     active_plane.entity = Some(*entity);
                     info!("Selected canvas plane {}", plane.plane_id);
+
+                    // Keep painting, on the new plane
+                    if edit_mode.mode == EditorMode::Paint {
+                        mode_requests.request_mode_change_for(EditorMode::Paint, *entity);
+                    }
                 }
             }
             CanvasPlaneEvent::Deselect => {
                 // Deactivate current plane
                 if let Some(prev_entity) = active_plane.entity {
-                    if let Ok(mut prev_plane) = canvas_query.get_mut(prev_entity) {
+                    if let Ok((_, mut prev_plane, _)) = canvas_query.get_mut(prev_entity) {
                         prev_plane.active = false;
                     }
                 }
                 active_plane.entity = None;
-                active_plane.camera_locked = false;
                 info!("Deselected canvas plane");
+
+                if edit_mode.mode == EditorMode::Paint {
+                    mode_requests.request_mode_change(EditorMode::Object);
+                }
             }
             CanvasPlaneEvent::ToggleCameraLock => {
-                if let Some(plane_entity) = active_plane.entity {
-                    let was_locked = active_plane.camera_locked;
-                    active_plane.camera_locked = !was_locked;
-
-                    // When unlocking, deactivate paint mode and notify UI
-                    if was_locked && !active_plane.camera_locked {
-                        paint_mode.active = false;
-                        outbound.send(BevyToUi::EditModeChanged {
-                            mode: EditMode::None,
-                        });
-                        info!("Camera lock disabled, exiting paint mode");
-                    } else if !was_locked && active_plane.camera_locked {
-                        // When locking, restore camera to paint position and activate paint mode
-                        if let Ok(canvas_plane) = canvas_query.get(plane_entity) {
-                            if let (Some(cam_pos), Some(cam_target)) = (
-                                canvas_plane.paint_camera_pos,
-                                canvas_plane.paint_camera_target,
-                            ) {
-                                // Update OrbitCamera to match stored position
-                                for mut orbit in orbit_camera_query.iter_mut() {
-                                    orbit.target = cam_target;
-                                    // Calculate distance, yaw, pitch from position
-                                    let offset = cam_pos - cam_target;
-                                    orbit.distance = offset.length();
-                                    orbit.yaw = offset.x.atan2(offset.z);
-                                    orbit.pitch = (offset.y / orbit.distance).asin();
-                                    info!(
-                                        "Restored camera: distance={}, yaw={}, pitch={}",
-                                        orbit.distance, orbit.yaw, orbit.pitch
-                                    );
-                                }
-                            }
-                        }
-                        paint_mode.active = true;
-                        outbound.send(BevyToUi::EditModeChanged {
-                            mode: EditMode::Paint,
-                        });
-                        info!("Camera lock enabled, entering paint mode");
-                    }
+                if edit_mode.mode == EditorMode::Paint {
+                    mode_requests.request_mode_change(EditorMode::Object);
+                } else if let Some(plane_entity) = active_plane.entity {
+                    mode_requests.request_mode_change_for(EditorMode::Paint, plane_entity);
                 }
             }
         }
     }
 }
 
+/// Transform and world size of a new canvas seen from `camera`
+///
+/// `surface` is the hit (position, normal) at the center of the view; a
+/// surface-aligned canvas without one faces the camera instead. The canvas
+/// mesh is a `Rectangle`, so its front (+Z) is turned toward the camera and
+/// its top (+Y) kept as close to world up as the orientation allows.
+fn place_canvas(
+    camera: &GlobalTransform,
+    placement: CanvasPlacement,
+    aspect_ratio: f32,
+    surface: Option<(Vec3, Vec3)>,
+) -> (Transform, Vec2) {
+    let camera_pos = camera.translation();
+    let forward = camera.forward().as_vec3();
+    let camera_up = camera.up().as_vec3();
+    let in_view = camera_pos + forward * placement.distance;
+
+    let (center, normal, up_hints, distance) = match (placement.orientation, surface) {
+        (CanvasOrientation::SurfaceAligned, Some((position, normal))) => {
+            // Onto the side of the surface the camera sees
+            let normal = if normal.dot(camera_pos - position) < 0.0 {
+                -normal
+            } else {
+                normal
+            };
+            (
+                position + normal * SURFACE_OFFSET,
+                normal,
+                [Vec3::Y, camera_up],
+                camera_pos.distance(position),
+            )
+        }
+        (CanvasOrientation::Horizontal, _) => {
+            let normal = if camera_pos.y >= in_view.y {
+                Vec3::Y
+            } else {
+                Vec3::NEG_Y
+            };
+            // Top edge away from the camera, or the camera's up looking straight down
+            (in_view, normal, [forward, camera_up], placement.distance)
+        }
+        (CanvasOrientation::Vertical, _) => {
+            let normal = Vec3::new(-forward.x, 0.0, -forward.z)
+                .try_normalize()
+                .unwrap_or(Vec3::Z);
+            (in_view, normal, [Vec3::Y, camera_up], placement.distance)
+        }
+        _ => (in_view, -forward, [Vec3::Y, camera_up], placement.distance),
+    };
+
+    let size = placement.world_size.unwrap_or_else(|| {
+        let height = (CANVAS_FILL_FOV / 2.0).tan() * distance * 2.0;
+        Vec2::new(height * aspect_ratio, height)
+    });
+    let transform =
+        Transform::from_translation(center).with_rotation(canvas_rotation(normal, up_hints));
+    (transform, size)
+}
+
+/// Rotation turning a canvas's front (+Z) to `normal`, with its top (+Y) on
+/// the first of `up_hints` that isn't parallel to the normal
+fn canvas_rotation(normal: Vec3, up_hints: [Vec3; 2]) -> Quat {
+    let up = up_hints
+        .into_iter()
+        .find_map(|hint| (hint - normal * hint.dot(normal)).try_normalize())
+        .unwrap_or_else(|| normal.any_orthonormal_vector());
+    Quat::from_mat3(&Mat3::from_cols(up.cross(normal), up, normal))
+}
+
+/// Transform putting a canvas of `world_size` back in front of `camera`,
+/// at the distance where its height fills the view
+fn reanchor_transform(camera: &GlobalTransform, world_size: Vec2, scale: Vec3) -> Transform {
+    let height = world_size.y * scale.y;
+    let placement = CanvasPlacement {
+        world_size: Some(world_size),
+        distance: height / 2.0 / (CANVAS_FILL_FOV / 2.0).tan(),
+        orientation: CanvasOrientation::FacingCamera,
+    };
+    let (transform, _) = place_canvas(camera, placement, 1.0, None);
+    transform.with_scale(scale)
+}
+
+/// Camera position relative to a plane, in the plane's frame (without scale)
+fn view_in_plane_frame(plane: &Transform, camera_pos: Vec3) -> Vec3 {
+    plane.rotation.inverse() * (camera_pos - plane.translation)
+}
+
 /// Handle Tab key input to toggle camera lock when a plane is selected
 ///
 /// Tab behavior is context-aware:
-/// - In Paint mode or when no mesh is in edit mode: toggle camera lock for canvas
+/// - In Object or Paint mode with a plane selected: toggle paint mode (and
+///   camera lock) for the canvas
 /// - In MeshEdit mode: handled by mesh_edit_mode.rs
 fn handle_camera_lock_input(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     active_plane: Res<ActiveCanvasPlane>,
-    edit_mode: Res<crate::edit_mode::EditModeState>,
+    edit_mode: Res<EditModeState>,
     mut events: MessageWriter<CanvasPlaneEvent>,
 ) {
-    // Tab key toggles camera lock when a plane is selected AND we're not in mesh edit mode
-    // (mesh_edit_mode.rs handles Tab for entering/exiting mesh edit mode)
-    if key_input.just_pressed(KeyCode::Tab)
+    // Tab key toggles camera lock when a plane is selected AND no other mode
+    // owns Tab (mesh_edit_mode.rs handles Tab for entering/exiting mesh edit mode)
+    if keymap.just_pressed(actions::MODE_TOGGLE, &key_input)
         && active_plane.entity.is_some()
-        && edit_mode.mode != EditMode::MeshEdit
+        && matches!(edit_mode.mode, EditorMode::Object | EditorMode::Paint)
     {
         events.write(CanvasPlaneEvent::ToggleCameraLock);
     }
 }
 
+/// Paint mode needs a canvas plane: the requested one, or the active plane
+fn paint_mode_guard(
+    In(target): In<Option<Entity>>,
+    active_plane: Res<ActiveCanvasPlane>,
+    planes: Query<(), With<CanvasPlane>>,
+) -> Result<Option<Entity>, String> {
+    target
+        .or(active_plane.entity)
+        .filter(|&entity| planes.contains(entity))
+        .map(Some)
+        .ok_or_else(|| "Select a canvas plane to paint on".to_string())
+}
+
+/// Make the painted plane active and lock the camera to it, restoring the
+/// view the plane was placed from (wherever the plane has moved since)
+fn enter_canvas_paint_mode(
+    In(target): In<Option<Entity>>,
+    mut active_plane: ResMut<ActiveCanvasPlane>,
+    mut canvas_query: Query<(&mut CanvasPlane, &Transform)>,
+    mut orbit_camera_query: Query<&mut OrbitCamera>,
+) {
+    let Some(plane_entity) = target else {
+        return;
+    };
+    if let Some(prev_entity) = active_plane.entity.filter(|&prev| prev != plane_entity)
+        && let Ok((mut prev_plane, _)) = canvas_query.get_mut(prev_entity)
+    {
+        prev_plane.active = false;
+    }
+    active_plane.entity = Some(plane_entity);
+    active_plane.camera_locked = true;
+
+    let Ok((mut canvas_plane, plane_transform)) = canvas_query.get_mut(plane_entity) else {
+        return;
+    };
+    canvas_plane.active = true;
+    if let Some(view) = canvas_plane.paint_view {
+        let cam_target = plane_transform.translation;
+        let cam_pos = cam_target + plane_transform.rotation * view;
+        // Update OrbitCamera to match stored position
+        for mut orbit in orbit_camera_query.iter_mut() {
+            orbit.target = cam_target;
+            // Calculate distance, yaw, pitch from position
+            let offset = cam_pos - cam_target;
+            orbit.distance = offset.length();
+            orbit.yaw = offset.x.atan2(offset.z);
+            orbit.pitch = (offset.y / orbit.distance).asin();
+            info!(
+                "Restored camera: distance={}, yaw={}, pitch={}",
+                orbit.distance, orbit.yaw, orbit.pitch
+            );
+        }
+    }
+    info!("Camera locked to canvas plane {}", canvas_plane.plane_id);
+}
+
+/// Unlock the camera when leaving paint mode
+fn exit_canvas_paint_mode(mut active_plane: ResMut<ActiveCanvasPlane>) {
+    active_plane.camera_locked = false;
+}
+
 /// Marker component indicating the material has been updated with the canvas texture
 #[derive(Component)]
 pub struct CanvasMaterialUpdated;
@@ -418,11 +643,13 @@ fn sync_active_plane_with_selection(
     added_selected: Query<Entity, (With<CanvasPlane>, Added<Selected>)>,
     mut removed_selected: RemovedComponents<Selected>,
     canvas_query: Query<Entity, With<CanvasPlane>>,
+    edit_mode: Res<EditModeState>,
+    mut mode_requests: EditModeRequests,
 ) {
-    // When a canvas plane is selected, make it the active plane
+    // When a canvas plane is selected, make it the active plane (the camera
+    // isn't locked to it until Tab enters paint mode)
     for entity in added_selected.iter() {
         active_plane.entity = Some(entity);
-        active_plane.camera_locked = false; // Not locked until Tab pressed
         info!("Canvas plane selected via click, set as active plane");
     }
 
@@ -431,8 +658,129 @@ fn sync_active_plane_with_selection(
         // Check if this entity is a canvas plane and is the active one
         if canvas_query.get(entity).is_ok() && active_plane.entity == Some(entity) {
             active_plane.entity = None;
-            active_plane.camera_locked = false;
             info!("Canvas plane deselected, cleared active plane");
+
+            if edit_mode.mode == EditorMode::Paint && edit_mode.target_entity == Some(entity) {
+                mode_requests.request_mode_change(EditorMode::Object);
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Camera at `position` looking at `target`
+    fn camera_at(position: Vec3, target: Vec3) -> GlobalTransform {
+        GlobalTransform::from(Transform::from_translation(position).looking_at(target, Vec3::Y))
+    }
+
+    #[test]
+    fn test_vertical_canvas_stands_upright_at_distance() {
+        // Looking down at 45 degrees
+        let camera = camera_at(Vec3::new(0.0, 3.0, 3.0), Vec3::ZERO);
+        let placement = CanvasPlacement {
+            world_size: Some(Vec2::new(2.0, 1.0)),
+            distance: 3.0,
+            orientation: CanvasOrientation::Vertical,
+        };
+        let (transform, size) = place_canvas(&camera, placement, 1.0, None);
+
+        assert_eq!(size, Vec2::new(2.0, 1.0));
+        assert!((transform.translation.distance(camera.translation()) - 3.0).abs() < 1e-4);
+        // Upright, front turned toward the camera
+        assert!(transform.up().abs_diff_eq(Vec3::Y, 1e-5));
+        assert!(transform.back().abs_diff_eq(Vec3::Z, 1e-5));
+    }
+
+    #[test]
+    fn test_facing_canvas_fills_the_view() {
+        let camera = camera_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO);
+        let (transform, size) = place_canvas(&camera, CanvasPlacement::default(), 2.0, None);
+
+        let height = (CANVAS_FILL_FOV / 2.0).tan() * DEFAULT_CANVAS_DISTANCE * 2.0;
+        assert!(size.abs_diff_eq(Vec2::new(height * 2.0, height), 1e-5));
+        assert!(
+            transform
+                .translation
+                .abs_diff_eq(Vec3::new(0.0, 0.0, 3.0), 1e-5)
+        );
+        assert!(transform.back().abs_diff_eq(Vec3::Z, 1e-5));
+    }
+
+    #[test]
+    fn test_horizontal_canvas_looked_at_from_straight_above() {
+        let camera = camera_at(Vec3::new(0.0, 4.0, 0.0), Vec3::ZERO);
+        let placement = CanvasPlacement {
+            orientation: CanvasOrientation::Horizontal,
+            ..default()
+        };
+        let (transform, _) = place_canvas(&camera, placement, 1.0, None);
+
+        assert!(transform.back().abs_diff_eq(Vec3::Y, 1e-5));
+        // Its top falls back to the camera's up
+        assert!(transform.up().abs_diff_eq(camera.up().as_vec3(), 1e-5));
+    }
+
+    #[test]
+    fn test_surface_aligned_canvas_sits_on_the_hit() {
+        let camera = camera_at(Vec3::new(0.0, 1.0, 4.0), Vec3::new(0.0, 1.0, 0.0));
+        let placement = CanvasPlacement {
+            orientation: CanvasOrientation::SurfaceAligned,
+            ..default()
+        };
+        // A wall at z = 0 whose triangle winds away from the camera
+        let (transform, size) = place_canvas(
+            &camera,
+            placement,
+            1.0,
+            Some((Vec3::new(0.0, 1.0, 0.0), Vec3::NEG_Z)),
+        );
+
+        assert!(transform.back().abs_diff_eq(Vec3::Z, 1e-5));
+        assert!(
+            transform
+                .translation
+                .abs_diff_eq(Vec3::new(0.0, 1.0, SURFACE_OFFSET), 1e-5)
+        );
+        // Sized for the distance to the surface, not the default distance
+        let height = (CANVAS_FILL_FOV / 2.0).tan() * 4.0 * 2.0;
+        assert!((size.y - height).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_paint_view_follows_a_moved_plane() {
+        let camera = camera_at(Vec3::new(1.0, 2.0, 5.0), Vec3::ZERO);
+        let (placed, _) = place_canvas(&camera, CanvasPlacement::default(), 1.0, None);
+        let view = view_in_plane_frame(&placed, camera.translation());
+
+        let moved = Transform::from_translation(placed.translation + Vec3::new(3.0, 0.0, -1.0))
+            .with_rotation(Quat::from_rotation_y(0.5) * placed.rotation);
+        let eye = moved.translation + moved.rotation * view;
+        // Same distance, still square on to the plane's front
+        assert!((eye.distance(moved.translation) - DEFAULT_CANVAS_DISTANCE).abs() < 1e-4);
+        assert!(
+            (eye - moved.translation)
+                .normalize()
+                .abs_diff_eq(moved.back().as_vec3(), 1e-4)
+        );
+    }
+
+    #[test]
+    fn test_reanchor_fills_the_view_with_the_canvas_height() {
+        let camera = camera_at(Vec3::new(0.0, 1.0, 6.0), Vec3::new(0.0, 1.0, 0.0));
+        let scale = Vec3::new(1.0, 2.0, 1.0);
+        let reanchored = reanchor_transform(&camera, Vec2::new(2.0, 1.0), scale);
+
+        // A 1 unit tall canvas scaled to 2 units, half of it in each half of the view
+        let distance = 1.0 / (CANVAS_FILL_FOV / 2.0).tan();
+        assert!(
+            reanchored
+                .translation
+                .abs_diff_eq(Vec3::new(0.0, 1.0, 6.0 - distance), 1e-4)
+        );
+        assert!(reanchored.back().abs_diff_eq(Vec3::Z, 1e-5));
+        assert_eq!(reanchored.scale, scale);
+    }
+}
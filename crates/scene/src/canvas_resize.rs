@@ -0,0 +1,158 @@
+//! Canvas resize and crop
+//!
+//! Resize reallocates every layer of the active canvas at the new size with
+//! the old content pinned to an anchor; crop is a resize whose offset moves
+//! the crop rectangle to the top-left corner. Either one is a single undo
+//! step and is recorded in the stroke log. The `CanvasTexture` and the plane
+//! mesh follow the pipeline in `sync_canvas_sizes`, which also covers undo.
+
+use bevy::ecs::message::Message;
+use bevy::prelude::*;
+use pentimento_ipc::CanvasAnchor;
+
+use crate::OutboundUiMessages;
+use crate::canvas_file::MAX_IMAGE_DIMENSION;
+use crate::canvas_plane::{ActiveCanvasPlane, CanvasPlane};
+use crate::paint_mode::StrokeIdGenerator;
+use crate::painting_system::PaintingResource;
+
+/// Message for resizing or cropping the active canvas
+#[derive(Message, Debug, Clone)]
+pub enum CanvasResizeEvent {
+    /// Resize to `width` x `height`, keeping content pinned to `anchor`
+    Resize {
+        width: u32,
+        height: u32,
+        anchor: CanvasAnchor,
+    },
+    /// Keep only the given pixel rectangle
+    Crop {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+}
+
+/// Handle resize and crop requests for the active canvas
+pub(crate) fn handle_canvas_resize_events(
+    mut events: MessageReader<CanvasResizeEvent>,
+    mut painting_res: ResMut<PaintingResource>,
+    mut stroke_ids: ResMut<StrokeIdGenerator>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    active_plane: Res<ActiveCanvasPlane>,
+    canvas_query: Query<&CanvasPlane>,
+) {
+    for event in events.read() {
+        let Some(canvas_plane) = active_plane.entity.and_then(|e| canvas_query.get(e).ok()) else {
+            outbound.send_error("canvas_not_active", "No canvas plane is active");
+            continue;
+        };
+        let plane_id = canvas_plane.plane_id;
+        let Some(pipeline) = painting_res.get_pipeline_mut(plane_id) else {
+            continue;
+        };
+        let (old_width, old_height) = (pipeline.width(), pipeline.height());
+
+        let (width, height, offset) = match *event {
+            CanvasResizeEvent::Resize {
+                width,
+                height,
+                anchor,
+            } => (
+                width,
+                height,
+                anchor_offset(anchor, (old_width, old_height), (width, height)),
+            ),
+            CanvasResizeEvent::Crop {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let inside = x.checked_add(width).is_some_and(|right| right <= old_width)
+                    && y.checked_add(height)
+                        .is_some_and(|bottom| bottom <= old_height);
+                if !inside {
+                    outbound.send_error(
+                        "canvas_crop_out_of_bounds",
+                        format!(
+                            "Crop {}x{} at ({}, {}) does not fit the {}x{} canvas",
+                            width, height, x, y, old_width, old_height
+                        ),
+                    );
+                    continue;
+                }
+                (width, height, (-(x as i32), -(y as i32)))
+            }
+        };
+
+        if width == 0 || height == 0 || width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION
+        {
+            outbound.send_error(
+                "canvas_size_invalid",
+                format!(
+                    "Canvas size {}x{} must be between 1 and {} pixels per side",
+                    width, height, MAX_IMAGE_DIMENSION
+                ),
+            );
+            continue;
+        }
+
+        if pipeline.resize_canvas(plane_id, stroke_ids.next(), width, height, offset) {
+            info!(
+                "Resized canvas plane {} from {}x{} to {}x{}",
+                plane_id, old_width, old_height, width, height
+            );
+        } else {
+            outbound.send_error(
+                "canvas_resize_failed",
+                "Cannot resize the canvas while a stroke or selection is in progress",
+            );
+        }
+    }
+}
+
+/// Where the old content's top-left corner lands in the resized canvas
+fn anchor_offset(anchor: CanvasAnchor, old: (u32, u32), new: (u32, u32)) -> (i32, i32) {
+    let spare_x = new.0 as i32 - old.0 as i32;
+    let spare_y = new.1 as i32 - old.1 as i32;
+    let x = match anchor {
+        CanvasAnchor::TopLeft | CanvasAnchor::Left | CanvasAnchor::BottomLeft => 0,
+        CanvasAnchor::Top | CanvasAnchor::Center | CanvasAnchor::Bottom => spare_x / 2,
+        CanvasAnchor::TopRight | CanvasAnchor::Right | CanvasAnchor::BottomRight => spare_x,
+    };
+    let y = match anchor {
+        CanvasAnchor::TopLeft | CanvasAnchor::Top | CanvasAnchor::TopRight => 0,
+        CanvasAnchor::Left | CanvasAnchor::Center | CanvasAnchor::Right => spare_y / 2,
+        CanvasAnchor::BottomLeft | CanvasAnchor::Bottom | CanvasAnchor::BottomRight => spare_y,
+    };
+    (x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchor_offset_grow_and_shrink() {
+        let old = (100, 50);
+        assert_eq!(
+            anchor_offset(CanvasAnchor::TopLeft, old, (200, 150)),
+            (0, 0)
+        );
+        assert_eq!(
+            anchor_offset(CanvasAnchor::Center, old, (200, 150)),
+            (50, 50)
+        );
+        assert_eq!(
+            anchor_offset(CanvasAnchor::BottomRight, old, (200, 150)),
+            (100, 100)
+        );
+        assert_eq!(anchor_offset(CanvasAnchor::Right, old, (60, 50)), (-40, 0));
+        assert_eq!(
+            anchor_offset(CanvasAnchor::Bottom, old, (100, 10)),
+            (0, -40)
+        );
+    }
+}
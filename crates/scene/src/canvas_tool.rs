@@ -0,0 +1,216 @@
+//! Bucket fill and gradient tools on canvas planes
+//!
+//! `Fill` and `Gradient` commands apply directly at canvas pixel positions.
+//! `ArmCanvasTool` puts one of them on the mouse instead of the brush: a
+//! click on the active canvas fills there, a drag draws a gradient from the
+//! press to the release. Presses outside the canvas are ignored.
+
+use bevy::ecs::message::Message;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use painting::{GradientKind as PaintGradientKind, canvas_pixel, uv_to_canvas};
+use pentimento_ipc::{CanvasTool, GradientKind};
+
+use crate::OutboundUiMessages;
+use crate::camera::MainCamera;
+use crate::canvas_plane::{ActiveCanvasPlane, CanvasPlane};
+use crate::paint_mode::{PaintMode, StrokeIdGenerator, ray_plane_intersection};
+use crate::painting_system::PaintingResource;
+
+/// Message for fill and gradient actions on the active canvas
+#[derive(Message, Debug, Clone)]
+pub enum CanvasToolEvent {
+    /// Put a tool on the mouse, or give the mouse back to the brush
+    Arm { tool: Option<CanvasTool> },
+    /// Bucket fill from a canvas pixel position
+    Fill {
+        position: Vec2,
+        tolerance: f32,
+        contiguous: bool,
+    },
+    /// Gradient between two canvas pixel positions
+    Gradient {
+        start: Vec2,
+        end: Vec2,
+        kind: GradientKind,
+        colors: Vec<[f32; 4]>,
+    },
+}
+
+/// Armed canvas tool state
+#[derive(Resource, Default)]
+pub struct CanvasToolState {
+    /// Tool applied by the next click or drag on the canvas
+    pub armed: Option<CanvasTool>,
+    /// Canvas pixel position where the gradient drag started
+    drag_start: Option<Vec2>,
+}
+
+impl CanvasToolState {
+    /// Whether canvas clicks belong to the armed tool instead of the brush
+    pub fn captures_input(&self) -> bool {
+        self.armed.is_some()
+    }
+}
+
+/// Plugin for the fill and gradient canvas tools
+pub struct CanvasToolPlugin;
+
+impl Plugin for CanvasToolPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CanvasToolState>()
+            .add_message::<CanvasToolEvent>()
+            .add_systems(
+                Update,
+                (handle_canvas_tool_input, handle_canvas_tool_events).chain(),
+            );
+    }
+}
+
+/// Turn clicks and drags on the active canvas into fills and gradients
+#[allow(clippy::too_many_arguments)]
+fn handle_canvas_tool_input(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    plane_query: Query<(&GlobalTransform, &CanvasPlane)>,
+    active_plane: Res<ActiveCanvasPlane>,
+    paint_mode: Res<PaintMode>,
+    mut state: ResMut<CanvasToolState>,
+    mut events: MessageWriter<CanvasToolEvent>,
+) {
+    if !paint_mode.active || !state.captures_input() {
+        state.drag_start = None;
+        return;
+    }
+
+    if key_input.just_pressed(KeyCode::Escape) {
+        events.write(CanvasToolEvent::Arm { tool: None });
+        state.drag_start = None;
+        return;
+    }
+
+    // Cursor UV on the active canvas plane (unbounded) and its pixel size
+    let cursor = (|| {
+        let plane_entity = active_plane.entity?;
+        let (camera, camera_transform) = camera_query.single().ok()?;
+        let (plane_transform, canvas_plane) = plane_query.get(plane_entity).ok()?;
+        let cursor = windows.single().ok()?.cursor_position()?;
+        let ray = camera.viewport_to_world(camera_transform, cursor).ok()?;
+        let (_, uv) = ray_plane_intersection(
+            ray,
+            plane_transform,
+            canvas_plane.world_width,
+            canvas_plane.world_height,
+        )?;
+        Some((uv, canvas_plane.width, canvas_plane.height))
+    })();
+
+    if mouse_button.just_pressed(MouseButton::Left) {
+        let Some((x, y)) =
+            cursor.and_then(|(uv, width, height)| uv_to_canvas(uv.x, uv.y, width, height))
+        else {
+            return;
+        };
+        let position = Vec2::new(x, y);
+        match &state.armed {
+            Some(CanvasTool::Fill {
+                tolerance,
+                contiguous,
+            }) => {
+                events.write(CanvasToolEvent::Fill {
+                    position,
+                    tolerance: *tolerance,
+                    contiguous: *contiguous,
+                });
+            }
+            Some(CanvasTool::Gradient { .. }) => state.drag_start = Some(position),
+            None => {}
+        }
+    } else if mouse_button.just_released(MouseButton::Left) {
+        // The gradient end may lie past the canvas edge
+        let (Some(start), Some((uv, width, height))) = (state.drag_start.take(), cursor) else {
+            return;
+        };
+        let end = uv * Vec2::new(width as f32, height as f32);
+        if let Some(CanvasTool::Gradient { kind, colors }) = &state.armed {
+            events.write(CanvasToolEvent::Gradient {
+                start,
+                end,
+                kind: *kind,
+                colors: colors.clone(),
+            });
+        }
+    }
+}
+
+/// Apply fills and gradients to the active canvas pipeline
+fn handle_canvas_tool_events(
+    mut events: MessageReader<CanvasToolEvent>,
+    mut painting_res: ResMut<PaintingResource>,
+    mut stroke_ids: ResMut<StrokeIdGenerator>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    mut state: ResMut<CanvasToolState>,
+    active_plane: Res<ActiveCanvasPlane>,
+    canvas_query: Query<&CanvasPlane>,
+) {
+    for event in events.read() {
+        if let CanvasToolEvent::Arm { tool } = event {
+            info!("Canvas tool armed: {:?}", tool);
+            state.armed = tool.clone();
+            state.drag_start = None;
+            continue;
+        }
+
+        let Some(canvas_plane) = active_plane.entity.and_then(|e| canvas_query.get(e).ok()) else {
+            outbound.send_error("canvas_not_active", "No canvas plane is active");
+            continue;
+        };
+        let plane_id = canvas_plane.plane_id;
+        let Some(pipeline) = painting_res.get_pipeline_mut(plane_id) else {
+            continue;
+        };
+
+        match event {
+            CanvasToolEvent::Arm { .. } => {}
+            CanvasToolEvent::Fill {
+                position,
+                tolerance,
+                contiguous,
+            } => {
+                let Some((x, y)) =
+                    canvas_pixel(position.x, position.y, pipeline.width(), pipeline.height())
+                else {
+                    debug!("Fill at {:?} is outside the canvas, ignoring", position);
+                    continue;
+                };
+                let tolerance = tolerance.clamp(0.0, 1.0);
+                if pipeline.fill(plane_id, stroke_ids.next(), x, y, tolerance, *contiguous) {
+                    info!("Filled canvas plane {} from ({}, {})", plane_id, x, y);
+                } else {
+                    outbound.send_error("fill_failed", "Can't fill while a stroke is in progress");
+                }
+            }
+            CanvasToolEvent::Gradient {
+                start,
+                end,
+                kind,
+                colors,
+            } => {
+                let kind = match kind {
+                    GradientKind::Linear => PaintGradientKind::Linear,
+                    GradientKind::Radial => PaintGradientKind::Radial,
+                };
+                if pipeline.gradient(plane_id, stroke_ids.next(), *start, *end, kind, colors) {
+                    info!("Applied {:?} gradient to canvas plane {}", kind, plane_id);
+                } else {
+                    outbound.send_error(
+                        "gradient_invalid",
+                        "A gradient needs at least one color and distinct start and end points",
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Object duplication
+//!
+//! `ObjectCommand::Duplicate` copies meshes and groups, with everything
+//! inside them, next to the originals. Copies share their source's mesh
+//! asset until one of them is painted, edited, or sculpted (see
+//! `InstancingPlugin`), get their own material (materials are addressed
+//! by object ID), and fresh IDs from [`IdAllocator`]; each is reported with
+//! `ObjectAdded`, parents before children, naming its source in
+//! `duplicated_from`.
+//!
+//! Lights, reference images, and canvas planes aren't duplicated.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use pentimento_ipc::{BevyToUi, ObjectCommand, SceneObject, Transform3D};
+
+use crate::canvas_plane::CanvasPlane;
+use crate::hierarchy::{ObjectGroup, SceneObjectFilter, ancestors};
+use crate::object_id::IdAllocator;
+use crate::reference_image::ReferenceImage;
+use crate::scene_light::SceneLight;
+use crate::selection::Selectable;
+use crate::{ObjectCommandEvent, OutboundUiMessages};
+
+/// Objects that can be duplicated
+type DuplicableObject = (
+    Entity,
+    &'static Selectable,
+    &'static Name,
+    &'static Transform,
+    &'static Visibility,
+    Option<&'static Mesh3d>,
+    Option<&'static MeshMaterial3d<StandardMaterial>>,
+    Has<ObjectGroup>,
+);
+
+/// Scene objects besides lights, reference images, and canvas planes
+type DuplicableFilter = (
+    SceneObjectFilter,
+    Without<SceneLight>,
+    Without<ReferenceImage>,
+    Without<CanvasPlane>,
+);
+
+/// Plugin for duplicating objects
+pub struct DuplicatePlugin;
+
+impl Plugin for DuplicatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, handle_duplicate_commands);
+    }
+}
+
+/// Apply `Duplicate` object commands
+#[allow(clippy::too_many_arguments)]
+fn handle_duplicate_commands(
+    mut commands: Commands,
+    mut events: MessageReader<ObjectCommandEvent>,
+    mut allocator: ResMut<IdAllocator>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    objects: Query<DuplicableObject, DuplicableFilter>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    selectables: Query<&Selectable>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for ObjectCommandEvent(command) in events.read() {
+        let ObjectCommand::Duplicate { ids } = command else {
+            continue;
+        };
+        let found: Vec<Entity> = objects
+            .iter()
+            .filter(|(_, selectable, ..)| ids.contains(&selectable.id))
+            .map(|(entity, ..)| entity)
+            .collect();
+        if found.len() < ids.len() {
+            warn!("Some of {:?} can't be duplicated", ids);
+        }
+        // Objects inside other duplicated objects are copied with them
+        let parent_of = |entity| parents.get(entity).ok().map(ChildOf::parent);
+        let roots = found.iter().copied().filter(|&entity| {
+            !ancestors(entity, parent_of).any(|ancestor| found.contains(&ancestor))
+        });
+
+        // Breadth first, so parents are reported before their children
+        let mut queue: VecDeque<PendingCopy> = roots
+            .map(|root| PendingCopy {
+                source: root,
+                parent: parent_of(root),
+                // Nearest selectable ancestor, as `SceneObject::parent_id` reports it
+                parent_id: ancestors(root, parent_of)
+                    .find_map(|ancestor| selectables.get(ancestor).ok())
+                    .map(|selectable| selectable.id.clone()),
+                root: true,
+            })
+            .collect();
+        let mut copied = 0;
+        while let Some(next) = queue.pop_front() {
+            let Ok((_, selectable, name, transform, visibility, mesh, material, is_group)) =
+                objects.get(next.source)
+            else {
+                // Helper entities (sculpt chunks, overlays) aren't scene objects
+                continue;
+            };
+
+            let id = allocator.allocate();
+            // The copied root is named apart from its source; its contents
+            // keep their names
+            let name = if next.root {
+                format!("{} copy", name)
+            } else {
+                name.to_string()
+            };
+            let mut copy = commands.spawn((
+                *transform,
+                *visibility,
+                Name::new(name.clone()),
+                Selectable { id: id.clone() },
+            ));
+            if let Some(mesh) = mesh {
+                copy.insert(mesh.clone());
+            }
+            let material_id = material.map(|material| {
+                let handle = match materials.get(&material.0).cloned() {
+                    Some(data) => materials.add(data),
+                    None => material.0.clone(),
+                };
+                copy.insert(MeshMaterial3d(handle));
+                // Materials are addressed by their object's ID
+                id.clone()
+            });
+            if is_group {
+                copy.insert(ObjectGroup);
+            }
+            if let Some(parent) = next.parent {
+                copy.insert(ChildOf(parent));
+            }
+            let entity = copy.id();
+            copied += 1;
+
+            outbound.send(BevyToUi::ObjectAdded {
+                object: SceneObject {
+                    id: id.clone(),
+                    name,
+                    transform: Transform3D {
+                        position: transform.translation.to_array(),
+                        rotation: transform.rotation.to_array(),
+                        scale: transform.scale.to_array(),
+                    },
+                    material_id,
+                    visible: *visibility != Visibility::Hidden,
+                    locked: false,
+                    parent_id: next.parent_id,
+                    subdivision_levels: 0,
+                    wireframe: None,
+                    // The copy shares its source's mesh
+                    instanced: mesh.is_some(),
+                },
+                duplicated_from: Some(selectable.id.clone()),
+            });
+
+            for &child in children.get(next.source).into_iter().flatten() {
+                queue.push_back(PendingCopy {
+                    source: child,
+                    parent: Some(entity),
+                    parent_id: Some(id.clone()),
+                    root: false,
+                });
+            }
+        }
+        if copied > 0 {
+            info!("Duplicated {} objects", copied);
+        }
+    }
+}
+
+/// An object waiting to be copied
+struct PendingCopy {
+    source: Entity,
+    /// Parent of the copy: the copy of the source's parent, or for a copied
+    /// root the source's own parent
+    parent: Option<Entity>,
+    parent_id: Option<String>,
+    /// Whether this is one of the duplicated objects rather than inside one
+    root: bool,
+}
@@ -1,31 +1,195 @@
-//! Edit mode state management
+//! Edit mode state machine
 //!
-//! Tracks whether the user is in a special editing mode (paint, sculpt, etc.)
-//! and coordinates between UI and backend state.
+//! The editor is in exactly one [`EditorMode`] at a time. Modes change only
+//! through [`request_mode_change`] (or the [`EditModeRequests`] system param,
+//! which queues [`EditModeEvent`]s applied at the end of the frame):
+//!
+//! 1. The requested mode's guard checks the change is possible and picks its
+//!    target entity (e.g. the selected mesh); a rejected request leaves the
+//!    current mode alone and sends the UI an error.
+//! 2. The current mode's exit hooks run (flush strokes, merge sculpt chunks,
+//!    ...).
+//! 3. The requested mode's enter hooks run with the target.
+//! 4. One `BevyToUi::EditModeChanged` tells the UI the new mode.
+//!
+//! Mode plugins register their guard and hooks with [`EditModeAppExt`] instead
+//! of toggling themselves, so two modes can never be active at once.
+//! Requesting the current mode on the same target does nothing; on another
+//! target it exits and re-enters.
+
+use std::collections::HashMap;
 
-use bevy::ecs::message::Message;
+use bevy::ecs::message::{Message, Messages};
+use bevy::ecs::system::{SystemId, SystemParam};
 use bevy::prelude::*;
-use pentimento_ipc::EditMode;
+use pentimento_ipc::{BevyToUi, EditMode};
+
+use crate::OutboundUiMessages;
+
+/// Editor modes; `Object` is the default scene editing mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EditorMode {
+    #[default]
+    Object,
+    /// Painting on the active canvas plane
+    Paint,
+    /// Painting on mesh surfaces
+    MeshPaint,
+    /// Editing a mesh's vertices, edges, and faces
+    MeshEdit,
+    /// Sculpting a mesh
+    Sculpt,
+}
+
+impl EditorMode {
+    /// Every mode
+    pub const ALL: [Self; 5] = [
+        Self::Object,
+        Self::Paint,
+        Self::MeshPaint,
+        Self::MeshEdit,
+        Self::Sculpt,
+    ];
+
+    /// The mode as the UI knows it
+    pub fn to_ipc(self) -> EditMode {
+        match self {
+            Self::Object => EditMode::None,
+            Self::Paint => EditMode::Paint,
+            Self::MeshPaint => EditMode::MeshPaint,
+            Self::MeshEdit => EditMode::MeshEdit,
+            Self::Sculpt => EditMode::Sculpt,
+        }
+    }
+
+    /// The mode the UI asked for
+    pub fn from_ipc(mode: EditMode) -> Self {
+        match mode {
+            EditMode::None => Self::Object,
+            EditMode::Paint => Self::Paint,
+            EditMode::MeshPaint => Self::MeshPaint,
+            EditMode::MeshEdit => Self::MeshEdit,
+            EditMode::Sculpt => Self::Sculpt,
+        }
+    }
+}
 
 /// Resource tracking the current edit mode
 #[derive(Resource, Default)]
 pub struct EditModeState {
     /// Current edit mode
-    pub mode: EditMode,
+    pub mode: EditorMode,
     /// Entity being edited (e.g., canvas plane in paint mode)
     pub target_entity: Option<Entity>,
 }
 
-/// Message for edit mode changes (internal)
+/// Message requesting a mode change, applied by [`request_mode_change`] at
+/// the end of the frame
 #[derive(Message, Debug, Clone)]
-pub enum EditModeEvent {
-    /// Enter a specific edit mode
-    Enter {
-        mode: EditMode,
-        target: Option<Entity>,
-    },
-    /// Exit current edit mode
-    Exit,
+pub struct EditModeEvent {
+    pub mode: EditorMode,
+    /// Entity to edit; `None` lets the mode's guard pick one
+    pub target: Option<Entity>,
+}
+
+/// Queues mode change requests from systems
+#[derive(SystemParam)]
+pub struct EditModeRequests<'w> {
+    events: MessageWriter<'w, EditModeEvent>,
+}
+
+impl EditModeRequests<'_> {
+    /// Request `mode`, letting its guard pick the target
+    pub fn request_mode_change(&mut self, mode: EditorMode) {
+        self.events.write(EditModeEvent { mode, target: None });
+    }
+
+    /// Request `mode` on `target`
+    pub fn request_mode_change_for(&mut self, mode: EditorMode, target: Entity) {
+        self.events.write(EditModeEvent {
+            mode,
+            target: Some(target),
+        });
+    }
+}
+
+/// Checks a requested mode can be entered and resolves its target; the error
+/// is shown to the user
+pub type EditModeGuard = SystemId<In<Option<Entity>>, Result<Option<Entity>, String>>;
+
+/// Guard and hooks one mode registered
+#[derive(Default)]
+struct ModeHooks {
+    guard: Option<EditModeGuard>,
+    enter: Vec<SystemId<In<Option<Entity>>>>,
+    exit: Vec<SystemId>,
+}
+
+/// Guards and hooks of every mode
+#[derive(Resource, Default)]
+pub struct EditModeHooks {
+    modes: HashMap<EditorMode, ModeHooks>,
+}
+
+/// Registration of mode guards and hooks
+pub trait EditModeAppExt {
+    /// Set the check run before entering `mode`, which returns the target to
+    /// enter it on
+    fn set_edit_mode_guard<M>(
+        &mut self,
+        mode: EditorMode,
+        guard: impl IntoSystem<In<Option<Entity>>, Result<Option<Entity>, String>, M> + 'static,
+    ) -> &mut Self;
+
+    /// Run `hook` with the target each time `mode` is entered
+    fn on_enter_edit_mode<M>(
+        &mut self,
+        mode: EditorMode,
+        hook: impl IntoSystem<In<Option<Entity>>, (), M> + 'static,
+    ) -> &mut Self;
+
+    /// Run `hook` each time `mode` is left, before the next mode is entered
+    /// (`EditModeState` still holds the mode and target being left)
+    fn on_exit_edit_mode<M>(
+        &mut self,
+        mode: EditorMode,
+        hook: impl IntoSystem<(), (), M> + 'static,
+    ) -> &mut Self;
+}
+
+impl EditModeAppExt for App {
+    fn set_edit_mode_guard<M>(
+        &mut self,
+        mode: EditorMode,
+        guard: impl IntoSystem<In<Option<Entity>>, Result<Option<Entity>, String>, M> + 'static,
+    ) -> &mut Self {
+        let id = self.world_mut().register_system(guard);
+        let mut hooks = self.world_mut().get_resource_or_init::<EditModeHooks>();
+        hooks.modes.entry(mode).or_default().guard = Some(id);
+        self
+    }
+
+    fn on_enter_edit_mode<M>(
+        &mut self,
+        mode: EditorMode,
+        hook: impl IntoSystem<In<Option<Entity>>, (), M> + 'static,
+    ) -> &mut Self {
+        let id = self.world_mut().register_system(hook);
+        let mut hooks = self.world_mut().get_resource_or_init::<EditModeHooks>();
+        hooks.modes.entry(mode).or_default().enter.push(id);
+        self
+    }
+
+    fn on_exit_edit_mode<M>(
+        &mut self,
+        mode: EditorMode,
+        hook: impl IntoSystem<(), (), M> + 'static,
+    ) -> &mut Self {
+        let id = self.world_mut().register_system(hook);
+        let mut hooks = self.world_mut().get_resource_or_init::<EditModeHooks>();
+        hooks.modes.entry(mode).or_default().exit.push(id);
+        self
+    }
 }
 
 pub struct EditModePlugin;
@@ -33,31 +197,241 @@ pub struct EditModePlugin;
 impl Plugin for EditModePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<EditModeState>()
+            .init_resource::<EditModeHooks>()
             .add_message::<EditModeEvent>()
-            .add_systems(Update, handle_edit_mode_events);
-    }
-}
-
-/// Handle edit mode transitions
-fn handle_edit_mode_events(
-    mut events: MessageReader<EditModeEvent>,
-    mut state: ResMut<EditModeState>,
-    // TODO: Add sender for BevyToUi messages when IPC is wired up
-) {
-    for event in events.read() {
-        match event {
-            EditModeEvent::Enter { mode, target } => {
-                state.mode = *mode;
-                state.target_entity = *target;
-                info!("Entered {:?} mode", mode);
-                // TODO: Send BevyToUi::EditModeChanged to UI
-            }
-            EditModeEvent::Exit => {
-                info!("Exited {:?} mode", state.mode);
-                state.mode = EditMode::None;
-                state.target_entity = None;
-                // TODO: Send BevyToUi::EditModeChanged to UI
+            // After Update's commands, so targets spawned this frame exist
+            .add_systems(PostUpdate, apply_edit_mode_requests);
+    }
+}
+
+/// Apply the mode change requests queued this frame, in order
+fn apply_edit_mode_requests(world: &mut World) {
+    let requests: Vec<EditModeEvent> = world
+        .resource_mut::<Messages<EditModeEvent>>()
+        .drain()
+        .collect();
+    for request in requests {
+        // Rejections are reported to the UI by `request_mode_change`
+        let _ = request_mode_change(world, request.mode, request.target);
+    }
+}
+
+/// Change to `mode` on `target` (or the target its guard picks): run the
+/// guard, the current mode's exit hooks, then the new mode's enter hooks, and
+/// tell the UI. A rejected change sends the UI an error and returns it.
+pub fn request_mode_change(
+    world: &mut World,
+    mode: EditorMode,
+    target: Option<Entity>,
+) -> Result<(), String> {
+    let (guard, enter, exit) = {
+        let hooks = world.resource::<EditModeHooks>();
+        let current = world.resource::<EditModeState>().mode;
+        let entering = hooks.modes.get(&mode);
+        (
+            entering.and_then(|hooks| hooks.guard),
+            entering.map_or_else(Vec::new, |hooks| hooks.enter.clone()),
+            hooks
+                .modes
+                .get(&current)
+                .map_or_else(Vec::new, |hooks| hooks.exit.clone()),
+        )
+    };
+
+    let target = match guard {
+        Some(guard) => world
+            .run_system_with(guard, target)
+            .unwrap_or_else(|error| Err(format!("Mode guard failed: {error}"))),
+        None => Ok(target),
+    };
+    let target = match target {
+        Ok(target) => target,
+        Err(message) => {
+            warn!("Cannot enter {:?} mode: {}", mode, message);
+            world
+                .resource_mut::<OutboundUiMessages>()
+                .send(BevyToUi::Error {
+                    code: "edit_mode".to_string(),
+                    message: message.clone(),
+                });
+            return Err(message);
+        }
+    };
+
+    // Object mode edits no particular entity
+    let target = target.filter(|_| mode != EditorMode::Object);
+    let state = world.resource::<EditModeState>();
+    if state.mode == mode && state.target_entity == target {
+        return Ok(());
+    }
+    let previous = state.mode;
+
+    for hook in exit {
+        if let Err(error) = world.run_system(hook) {
+            warn!("{:?} exit hook failed: {}", previous, error);
+        }
+    }
+    {
+        let mut state = world.resource_mut::<EditModeState>();
+        state.mode = mode;
+        state.target_entity = target;
+    }
+    for hook in enter {
+        if let Err(error) = world.run_system_with(hook, target) {
+            warn!("{:?} enter hook failed: {}", mode, error);
+        }
+    }
+
+    info!("Edit mode {:?} -> {:?} ({:?})", previous, mode, target);
+    world
+        .resource_mut::<OutboundUiMessages>()
+        .send(BevyToUi::EditModeChanged {
+            mode: mode.to_ipc(),
+        });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hook calls in order, as (mode, "enter" or "exit")
+    #[derive(Resource, Default)]
+    struct HookLog(Vec<(EditorMode, &'static str)>);
+
+    /// Modes besides Object only accept a target; MeshPaint also rejects
+    /// requests without one
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<OutboundUiMessages>()
+            .init_resource::<HookLog>()
+            .add_plugins(EditModePlugin);
+        for mode in EditorMode::ALL {
+            app.on_enter_edit_mode(
+                mode,
+                move |_: In<Option<Entity>>, mut log: ResMut<HookLog>| {
+                    log.0.push((mode, "enter"));
+                },
+            )
+            .on_exit_edit_mode(mode, move |mut log: ResMut<HookLog>| {
+                log.0.push((mode, "exit"));
+            });
+        }
+        app.set_edit_mode_guard(EditorMode::MeshPaint, |In(target): In<Option<Entity>>| {
+            target
+                .map(Some)
+                .ok_or_else(|| "Select a mesh to paint".to_string())
+        });
+        app
+    }
+
+    fn mode_changes(app: &mut App) -> Vec<EditMode> {
+        app.world_mut()
+            .resource_mut::<OutboundUiMessages>()
+            .drain()
+            .into_iter()
+            .filter_map(|message| match message {
+                BevyToUi::EditModeChanged { mode } => Some(mode),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_every_transition_exits_then_enters() {
+        for from in EditorMode::ALL {
+            for to in EditorMode::ALL {
+                let mut app = test_app();
+                let target = app.world_mut().spawn_empty().id();
+                request_mode_change(app.world_mut(), from, Some(target)).unwrap();
+                app.world_mut().resource_mut::<HookLog>().0.clear();
+                mode_changes(&mut app);
+
+                request_mode_change(app.world_mut(), to, Some(target)).unwrap();
+
+                let log = &app.world().resource::<HookLog>().0;
+                let state = app.world().resource::<EditModeState>();
+                assert_eq!(state.mode, to, "{from:?} -> {to:?}");
+                if from == to {
+                    assert!(log.is_empty(), "{from:?} re-entered");
+                    assert!(mode_changes(&mut app).is_empty());
+                } else {
+                    assert_eq!(log, &[(from, "exit"), (to, "enter")], "{from:?} -> {to:?}");
+                    assert_eq!(mode_changes(&mut app), vec![to.to_ipc()]);
+                }
             }
         }
     }
+
+    #[test]
+    fn test_rejected_request_keeps_current_mode() {
+        let mut app = test_app();
+        let mesh = app.world_mut().spawn_empty().id();
+        request_mode_change(app.world_mut(), EditorMode::Sculpt, Some(mesh)).unwrap();
+        app.world_mut().resource_mut::<HookLog>().0.clear();
+        app.world_mut().resource_mut::<OutboundUiMessages>().drain();
+
+        let result = request_mode_change(app.world_mut(), EditorMode::MeshPaint, None);
+
+        assert_eq!(result, Err("Select a mesh to paint".to_string()));
+        assert_eq!(
+            app.world().resource::<EditModeState>().mode,
+            EditorMode::Sculpt
+        );
+        assert!(app.world().resource::<HookLog>().0.is_empty());
+        let messages = app.world_mut().resource_mut::<OutboundUiMessages>().drain();
+        assert!(matches!(
+            messages.as_slice(),
+            [BevyToUi::Error { code, .. }] if code == "edit_mode"
+        ));
+    }
+
+    #[test]
+    fn test_queued_requests_apply_in_order() {
+        let mut app = test_app();
+        let mesh = app.world_mut().spawn_empty().id();
+        app.world_mut().write_message(EditModeEvent {
+            mode: EditorMode::MeshEdit,
+            target: Some(mesh),
+        });
+        app.world_mut().write_message(EditModeEvent {
+            mode: EditorMode::Sculpt,
+            target: Some(mesh),
+        });
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<HookLog>().0,
+            vec![
+                (EditorMode::Object, "exit"),
+                (EditorMode::MeshEdit, "enter"),
+                (EditorMode::MeshEdit, "exit"),
+                (EditorMode::Sculpt, "enter"),
+            ]
+        );
+        assert_eq!(
+            mode_changes(&mut app),
+            vec![EditMode::MeshEdit, EditMode::Sculpt]
+        );
+    }
+
+    #[test]
+    fn test_same_mode_on_another_target_re_enters() {
+        let mut app = test_app();
+        let first = app.world_mut().spawn_empty().id();
+        let second = app.world_mut().spawn_empty().id();
+        request_mode_change(app.world_mut(), EditorMode::Sculpt, Some(first)).unwrap();
+        app.world_mut().resource_mut::<HookLog>().0.clear();
+
+        request_mode_change(app.world_mut(), EditorMode::Sculpt, Some(second)).unwrap();
+
+        assert_eq!(
+            app.world().resource::<HookLog>().0,
+            vec![(EditorMode::Sculpt, "exit"), (EditorMode::Sculpt, "enter")]
+        );
+        assert_eq!(
+            app.world().resource::<EditModeState>().target_entity,
+            Some(second)
+        );
+    }
 }
@@ -9,20 +9,23 @@ use crate::MainCamera;
 #[cfg(feature = "selection")]
 use crate::gizmo_raycast::{GizmoGeometry, GizmoHandle, raycast_gizmo};
 #[cfg(feature = "selection")]
-use crate::selection::{Selected, SelectionState};
+use crate::selection::SelectionState;
 
 #[cfg(feature = "selection")]
-use super::state::GizmoState;
+use super::PointerOverUi;
+#[cfg(feature = "selection")]
+use super::pivot::GizmoPlacement;
 #[cfg(feature = "selection")]
-use super::transform::get_gizmo_transform;
+use super::state::GizmoState;
 
 /// Detect which gizmo handle the cursor is hovering over
 #[cfg(feature = "selection")]
 pub(crate) fn detect_gizmo_hover(
     window_query: Query<&Window, With<PrimaryWindow>>,
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    selected_query: Query<&Transform, With<Selected>>,
+    placement: GizmoPlacement,
     selection: Res<SelectionState>,
+    pointer_over_ui: Res<PointerOverUi>,
     mut gizmo_state: ResMut<GizmoState>,
     geometry: Res<GizmoGeometry>,
 ) {
@@ -40,6 +43,11 @@ pub(crate) fn detect_gizmo_hover(
         return;
     }
 
+    // The UI takes clicks where it is opaque
+    if pointer_over_ui.0 {
+        return;
+    }
+
     // Get cursor position
     let Ok(window) = window_query.single() else {
         return;
@@ -59,9 +67,7 @@ pub(crate) fn detect_gizmo_hover(
     };
 
     // Get gizmo center and orientation
-    let Some((gizmo_center, gizmo_orientation)) =
-        get_gizmo_transform(&selected_query, gizmo_state.coordinate_space)
-    else {
+    let Some((gizmo_center, gizmo_orientation)) = placement.transform(&gizmo_state) else {
         return;
     };
 
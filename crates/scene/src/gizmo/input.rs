@@ -2,13 +2,17 @@
 
 use bevy::input::mouse::MouseButton;
 use bevy::prelude::*;
-use pentimento_ipc::{GizmoAxis, GizmoMode};
+use pentimento_config::{Keymap, actions};
+use pentimento_ipc::{GizmoAxis, GizmoCommand, GizmoMode};
 
+#[cfg(feature = "selection")]
+use crate::edit_mode::{EditModeState, EditorMode};
 #[cfg(feature = "selection")]
 use crate::gizmo_raycast::GizmoHandle;
 #[cfg(feature = "selection")]
 use crate::selection::{Selected, SelectionState};
 
+use super::GizmoCommandEvent;
 use super::state::{GizmoState, handle_axis_key};
 
 /// Handle mouse clicks on gizmo handles
@@ -49,7 +53,9 @@ pub(crate) fn handle_gizmo_click(
 #[cfg(feature = "selection")]
 pub(crate) fn handle_gizmo_hotkeys(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     mouse_button: Res<ButtonInput<MouseButton>>,
+    edit_mode: Res<EditModeState>,
     mut gizmo_state: ResMut<GizmoState>,
     selection: Res<SelectionState>,
     // Use ParamSet to avoid Query conflict - both queries access Transform
@@ -71,17 +77,20 @@ pub(crate) fn handle_gizmo_hotkeys(
 
     // If not in active operation, check for mode initiation keys
     if !gizmo_state.is_active {
-        if key_input.just_pressed(KeyCode::KeyG) {
+        // The canvas being painted stays put; planes move in object mode
+        if edit_mode.mode == EditorMode::Paint {
+            return;
+        }
+        if keymap.just_pressed(actions::GIZMO_TRANSLATE, &key_input) {
             // Store original transforms for potential cancel
             gizmo_state.original_transforms = queries.p0().iter().map(|(e, t)| (e, *t)).collect();
             gizmo_state.start_operation(GizmoMode::Translate);
             info!("Gizmo: Translate mode activated");
-        } else if key_input.just_pressed(KeyCode::KeyS) && !shift_held {
-            // S without shift = Scale (Shift+S might be used for other things)
+        } else if keymap.just_pressed(actions::GIZMO_SCALE, &key_input) {
             gizmo_state.original_transforms = queries.p0().iter().map(|(e, t)| (e, *t)).collect();
             gizmo_state.start_operation(GizmoMode::Scale);
             info!("Gizmo: Scale mode activated");
-        } else if key_input.just_pressed(KeyCode::KeyR) {
+        } else if keymap.just_pressed(actions::GIZMO_ROTATE, &key_input) {
             gizmo_state.original_transforms = queries.p0().iter().map(|(e, t)| (e, *t)).collect();
             gizmo_state.start_operation(GizmoMode::Rotate);
             info!("Gizmo: Rotate mode activated");
@@ -90,7 +99,7 @@ pub(crate) fn handle_gizmo_hotkeys(
     }
 
     // Handle R toggle: Rotate → Orbit → cancel
-    if key_input.just_pressed(KeyCode::KeyR) {
+    if keymap.just_pressed(actions::GIZMO_ROTATE_CYCLE, &key_input) {
         match gizmo_state.mode {
             GizmoMode::Rotate => {
                 gizmo_state.mode = GizmoMode::Trackball;
@@ -116,13 +125,13 @@ pub(crate) fn handle_gizmo_hotkeys(
 
     // Handle axis constraints (X/Y/Z or Shift+X/Y/Z)
     // Blender-style toggle: X → Global X → Local X → None
-    if key_input.just_pressed(KeyCode::KeyX) {
+    if keymap.key_just_pressed(actions::GIZMO_AXIS_X, &key_input) {
         handle_axis_key(&mut gizmo_state, GizmoAxis::X, shift_held);
     }
-    if key_input.just_pressed(KeyCode::KeyY) {
+    if keymap.key_just_pressed(actions::GIZMO_AXIS_Y, &key_input) {
         handle_axis_key(&mut gizmo_state, GizmoAxis::Y, shift_held);
     }
-    if key_input.just_pressed(KeyCode::KeyZ) {
+    if keymap.key_just_pressed(actions::GIZMO_AXIS_Z, &key_input) {
         handle_axis_key(&mut gizmo_state, GizmoAxis::Z, shift_held);
     }
 
@@ -149,3 +158,70 @@ pub(crate) fn handle_gizmo_hotkeys(
         info!("Gizmo: Operation confirmed");
     }
 }
+
+/// Apply gizmo commands from the UI, mirroring the hotkeys
+#[cfg(feature = "selection")]
+pub(crate) fn handle_gizmo_commands(
+    mut events: MessageReader<GizmoCommandEvent>,
+    mut gizmo_state: ResMut<GizmoState>,
+    selection: Res<SelectionState>,
+    mut transforms: Query<(Entity, &mut Transform, Has<Selected>)>,
+) {
+    for GizmoCommandEvent(command) in events.read() {
+        match command {
+            GizmoCommand::SetSnapTarget { mode } => {
+                gizmo_state.snap = *mode;
+                info!("Gizmo: Snap target {:?}", mode);
+            }
+            GizmoCommand::SetPivot { pivot } => {
+                gizmo_state.pivot = *pivot;
+                info!("Gizmo: Pivot {:?}", pivot);
+            }
+            GizmoCommand::SetMode(GizmoMode::None) | GizmoCommand::Cancel => {
+                if gizmo_state.is_active {
+                    restore_original_transforms(&gizmo_state, &mut transforms);
+                    gizmo_state.cancel();
+                }
+            }
+            GizmoCommand::SetMode(mode) => {
+                if selection.selected_ids.is_empty() {
+                    continue;
+                }
+                // Switching modes mid-operation starts over from the originals
+                if gizmo_state.is_active {
+                    restore_original_transforms(&gizmo_state, &mut transforms);
+                } else {
+                    gizmo_state.original_transforms = transforms
+                        .iter()
+                        .filter(|(_, _, selected)| *selected)
+                        .map(|(e, t, _)| (e, *t))
+                        .collect();
+                }
+                gizmo_state.start_operation(*mode);
+            }
+            GizmoCommand::ConstrainAxis(axis) => {
+                if gizmo_state.is_active {
+                    gizmo_state.axis_constraint = *axis;
+                    gizmo_state.last_axis_pressed = (*axis != GizmoAxis::None).then_some(*axis);
+                }
+            }
+            GizmoCommand::Confirm => {
+                if gizmo_state.is_active {
+                    gizmo_state.confirm();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "selection")]
+fn restore_original_transforms(
+    gizmo_state: &GizmoState,
+    transforms: &mut Query<(Entity, &mut Transform, Has<Selected>)>,
+) {
+    for (entity, original) in &gizmo_state.original_transforms {
+        if let Ok((_, mut transform, _)) = transforms.get_mut(*entity) {
+            *transform = *original;
+        }
+    }
+}
@@ -18,45 +18,91 @@
 //! - First R: Rotate (single-axis rotation based on constraint)
 //! - Second R: Trackball (free rotation - horizontal mouse = Y, vertical mouse = X)
 //! - Third R: Cancel operation
+//!
+//! Snapping (`GizmoCommand::SetSnapTarget`) stays set across operations:
+//! - Grid: translation steps along the gizmo axes, of a fixed size or the
+//!   finest subdivision the ground grid currently shows
+//! - Surface: unconstrained moves place the active object on the surface under
+//!   the cursor, optionally turning its up axis onto the surface normal
+//!
+//! Rotation and scaling pivot around the point set with `GizmoCommand::SetPivot`
+//! (median of the selection, each object's origin, the active object, or the
+//! 3D cursor, which Shift+right click places); the gizmo is drawn there.
+//!
+//! Handles are only hovered where the UI is transparent (`PointerOverUi`), so
+//! a handle showing past the edge of a panel can still be grabbed.
 
 #[cfg(feature = "selection")]
 mod hover;
 #[cfg(feature = "selection")]
 mod input;
 #[cfg(feature = "selection")]
+mod pivot;
+#[cfg(feature = "selection")]
 mod render;
+#[cfg(feature = "selection")]
+mod snap;
 mod state;
 #[cfg(feature = "selection")]
 mod transform;
 
 use bevy::prelude::*;
+use pentimento_ipc::GizmoCommand;
 #[cfg(feature = "selection")]
-use pentimento_ipc::EditMode;
+use pentimento_ipc::{BevyToUi, CoordinateSpace, GizmoAxis, GizmoMode, PivotPoint, SnapTarget};
 
 #[cfg(feature = "selection")]
-use crate::edit_mode::EditModeState;
+use crate::OutboundUiMessages;
+#[cfg(feature = "selection")]
+use crate::edit_mode::{EditModeState, EditorMode};
 #[cfg(feature = "selection")]
 use crate::gizmo_raycast::GizmoGeometry;
+#[cfg(feature = "selection")]
+use crate::projection_painting::MeshRaycastCache;
 
 // Re-export main types
+#[cfg(feature = "selection")]
+pub use pivot::Cursor3D;
 pub use state::GizmoState;
 
+/// Move the selection in the camera's view plane, like an unconstrained grab.
+/// `delta` is in mouse pixels (x right, y down).
+#[derive(Message, Debug, Clone, Copy)]
+pub struct GizmoNudgeEvent {
+    pub delta: Vec2,
+}
+
+/// Gizmo command from the UI
+#[derive(Message, Debug, Clone)]
+pub struct GizmoCommandEvent(pub GizmoCommand);
+
+/// Whether the cursor is over an opaque part of the UI, set by the app from
+/// the captured UI alpha. Gizmo handles under the UI can't be hovered or grabbed.
+#[derive(Resource, Debug, Default)]
+pub struct PointerOverUi(pub bool);
+
 /// Plugin for transform gizmos
 pub struct GizmoPlugin;
 
 impl Plugin for GizmoPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<GizmoState>();
+        app.init_resource::<GizmoState>()
+            .init_resource::<PointerOverUi>()
+            .add_message::<GizmoNudgeEvent>()
+            .add_message::<GizmoCommandEvent>();
 
         // Only add gizmo systems if selection feature is enabled
         #[cfg(feature = "selection")]
         {
             use hover::{detect_gizmo_hover, handle_gizmo_mouse_input};
-            use input::{handle_gizmo_click, handle_gizmo_hotkeys};
+            use input::{handle_gizmo_click, handle_gizmo_commands, handle_gizmo_hotkeys};
+            use pivot::{place_cursor_3d, render_cursor_3d, spawn_cursor_3d};
             use render::render_gizmo;
-            use transform::apply_gizmo_transform;
+            use transform::{apply_gizmo_nudges, apply_gizmo_transform};
 
-            app.init_resource::<GizmoGeometry>();
+            app.init_resource::<GizmoGeometry>()
+                .init_resource::<MeshRaycastCache>();
+            app.add_systems(Startup, spawn_cursor_3d);
             app.add_systems(
                 Update,
                 (
@@ -64,9 +110,14 @@ impl Plugin for GizmoPlugin {
                     detect_gizmo_hover.after(sync_gizmo_visibility_with_edit_mode),
                     handle_gizmo_click.after(detect_gizmo_hover),
                     handle_gizmo_hotkeys.after(handle_gizmo_click),
-                    handle_gizmo_mouse_input.after(handle_gizmo_hotkeys),
+                    handle_gizmo_commands.after(handle_gizmo_hotkeys),
+                    handle_gizmo_mouse_input.after(handle_gizmo_commands),
                     apply_gizmo_transform.after(handle_gizmo_mouse_input),
-                    render_gizmo.after(apply_gizmo_transform),
+                    apply_gizmo_nudges.after(apply_gizmo_transform),
+                    render_gizmo.after(apply_gizmo_nudges),
+                    report_gizmo_status.after(apply_gizmo_nudges),
+                    place_cursor_3d,
+                    render_cursor_3d.after(place_cursor_3d),
                 ),
             );
         }
@@ -83,9 +134,51 @@ fn sync_gizmo_visibility_with_edit_mode(
     mut gizmo_state: ResMut<GizmoState>,
 ) {
     // Gizmo should only be visible in None mode (normal object manipulation)
-    let should_show_gizmo = edit_mode.mode == EditMode::None;
+    let should_show_gizmo = edit_mode.mode == EditorMode::Object;
 
     if gizmo_state.always_visible != should_show_gizmo {
         gizmo_state.always_visible = should_show_gizmo;
     }
 }
+
+/// Gizmo state as last reported to the UI
+#[cfg(feature = "selection")]
+type GizmoStatusSnapshot = (
+    GizmoMode,
+    GizmoAxis,
+    CoordinateSpace,
+    bool,
+    SnapTarget,
+    PivotPoint,
+);
+
+/// Report the gizmo operation, constraint, snap target and pivot when they change
+#[cfg(feature = "selection")]
+fn report_gizmo_status(
+    gizmo_state: Res<GizmoState>,
+    mut last_sent: Local<Option<GizmoStatusSnapshot>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let status = (
+        gizmo_state.mode,
+        gizmo_state.axis_constraint,
+        gizmo_state.coordinate_space,
+        gizmo_state.is_active,
+        gizmo_state.snap,
+        gizmo_state.pivot,
+    );
+    if *last_sent == Some(status) {
+        return;
+    }
+    *last_sent = Some(status);
+
+    let (mode, axis, coordinate_space, active, snap, pivot) = status;
+    outbound.send(BevyToUi::GizmoStatus {
+        mode,
+        axis,
+        coordinate_space,
+        active,
+        snap,
+        pivot,
+    });
+}
@@ -0,0 +1,266 @@
+//! Pivot points for rotation and scaling, and the 3D cursor
+//!
+//! The pivot is worked out in world space from the selection's original
+//! transforms when an operation runs; rotation and scaling then move each
+//! object's origin about it, while each object's own rotation and scale
+//! change the same as with individual origins.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use pentimento_ipc::{CoordinateSpace, PivotPoint};
+
+use crate::MainCamera;
+use crate::add_object::scene_point;
+use crate::edit_mode::{EditModeState, EditorMode};
+use crate::scene_bvh::SceneBvh;
+use crate::selection::{Selectable, Selected, SelectionState};
+
+use super::state::GizmoState;
+
+/// Half-length of the 3D cursor's axis cross, in world units
+const CURSOR_SIZE: f32 = 0.15;
+
+/// The 3D cursor: a point in the scene that rotation and scaling can pivot
+/// around. Shift+right click places it on the surface under the mouse (or the
+/// ground plane).
+#[derive(Component, Default)]
+pub struct Cursor3D;
+
+/// Where the pivot is for `pivot`: the average of `origins`, the `active`
+/// object's origin, or the `cursor`. Individual origins are placed at the
+/// average, which is where the gizmo is drawn for them.
+pub(crate) fn pivot_center(
+    pivot: PivotPoint,
+    origins: &[Vec3],
+    active: Option<Vec3>,
+    cursor: Option<Vec3>,
+) -> Option<Vec3> {
+    let median =
+        || (!origins.is_empty()).then(|| origins.iter().sum::<Vec3>() / origins.len() as f32);
+    match pivot {
+        PivotPoint::Median | PivotPoint::IndividualOrigins => median(),
+        PivotPoint::ActiveObject => active.or_else(median),
+        PivotPoint::Cursor3D => cursor.or_else(median),
+    }
+}
+
+/// Move a world-space origin about `pivot`: the offset from the pivot is
+/// stretched by `scale` along the axes of `frame`, then turned by `rotation`
+pub(crate) fn origin_about_pivot(
+    origin: Vec3,
+    pivot: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    frame: Quat,
+) -> Vec3 {
+    let offset = frame * (scale * (frame.inverse() * (origin - pivot)));
+    pivot + rotation * offset
+}
+
+/// Where the gizmo sits for the current selection: at the pivot, turned to
+/// the active object in local space
+#[derive(SystemParam)]
+pub(crate) struct GizmoPlacement<'w, 's> {
+    selected: Query<'w, 's, (&'static GlobalTransform, &'static Selectable), With<Selected>>,
+    selection: Res<'w, SelectionState>,
+    cursor: Query<'w, 's, &'static GlobalTransform, With<Cursor3D>>,
+}
+
+impl GizmoPlacement<'_, '_> {
+    /// Gizmo center and orientation, or `None` with nothing selected
+    pub(crate) fn transform(&self, gizmo_state: &GizmoState) -> Option<(Vec3, Quat)> {
+        let origins: Vec<Vec3> = self
+            .selected
+            .iter()
+            .map(|(transform, _)| transform.translation())
+            .collect();
+        let active = self.active();
+        let center = pivot_center(
+            gizmo_state.pivot,
+            &origins,
+            active.map(|transform| transform.translation()),
+            self.cursor.single().ok().map(|cursor| cursor.translation()),
+        )?;
+
+        let orientation = match gizmo_state.coordinate_space {
+            CoordinateSpace::Global => Quat::IDENTITY,
+            CoordinateSpace::Local => active
+                .or_else(|| self.selected.iter().next().map(|(transform, _)| transform))
+                .map_or(Quat::IDENTITY, |transform| transform.rotation()),
+        };
+        Some((center, orientation))
+    }
+
+    /// The last selected object
+    fn active(&self) -> Option<&GlobalTransform> {
+        let last = self.selection.selected_ids.last()?;
+        self.selected
+            .iter()
+            .find(|(_, selectable)| &selectable.id == last)
+            .map(|(transform, _)| transform)
+    }
+}
+
+/// Spawn the 3D cursor at the origin
+pub(crate) fn spawn_cursor_3d(mut commands: Commands) {
+    commands.spawn((Cursor3D, Transform::default()));
+}
+
+/// Place the 3D cursor with Shift+right click in object mode
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn place_cursor_3d(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    edit_mode: Res<EditModeState>,
+    gizmo_state: Res<GizmoState>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    bvh: Option<Res<SceneBvh>>,
+    mut cursor: Query<&mut Transform, With<Cursor3D>>,
+) {
+    let shift_held =
+        key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
+    if !shift_held
+        || !mouse_button.just_pressed(MouseButton::Right)
+        || edit_mode.mode != EditorMode::Object
+        || gizmo_state.is_active
+    {
+        return;
+    }
+
+    let Some(point) = window
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|position| {
+            let (camera, camera_transform) = camera.single().ok()?;
+            camera.viewport_to_world(camera_transform, position).ok()
+        })
+        .and_then(|ray| scene_point(ray, bvh.as_deref()))
+    else {
+        return;
+    };
+    if let Ok(mut transform) = cursor.single_mut() {
+        transform.translation = point;
+        info!("3D cursor placed at {:?}", point);
+    }
+}
+
+/// Draw the 3D cursor as a small axis cross
+pub(crate) fn render_cursor_3d(
+    mut gizmos: Gizmos,
+    cursor: Query<&GlobalTransform, With<Cursor3D>>,
+) {
+    let Ok(cursor) = cursor.single() else {
+        return;
+    };
+    let center = cursor.translation();
+    for (axis, color) in [
+        (Vec3::X, Color::srgb(0.9, 0.2, 0.2)),
+        (Vec3::Y, Color::srgb(0.2, 0.9, 0.2)),
+        (Vec3::Z, Color::srgb(0.2, 0.2, 0.9)),
+    ] {
+        gizmos.line(
+            center - axis * CURSOR_SIZE,
+            center + axis * CURSOR_SIZE,
+            color,
+        );
+    }
+    gizmos.circle(
+        Isometry3d::new(center, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+        CURSOR_SIZE * 0.5,
+        Color::WHITE,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn test_median_rotation_keeps_relative_positions() {
+        let cubes = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(3.0, 0.0, 2.0)];
+        let pivot = pivot_center(PivotPoint::Median, &cubes, None, None).unwrap();
+        assert_eq!(pivot, Vec3::new(2.0, 0.0, 1.0));
+
+        let quarter_turn = Quat::from_rotation_y(FRAC_PI_2);
+        let turned = cubes.map(|origin| {
+            origin_about_pivot(origin, pivot, quarter_turn, Vec3::ONE, Quat::IDENTITY)
+        });
+
+        // The pair turns as one: same distance apart, offset turned with it
+        let before = cubes[1] - cubes[0];
+        let after = turned[1] - turned[0];
+        assert!((after.length() - before.length()).abs() < 1e-5);
+        assert!(after.abs_diff_eq(quarter_turn * before, 1e-5));
+        // ...around a median that stays put
+        assert!(((turned[0] + turned[1]) / 2.0).abs_diff_eq(pivot, 1e-5));
+    }
+
+    #[test]
+    fn test_cursor_scaling_moves_toward_and_away_from_cursor() {
+        let cursor = Vec3::new(0.0, 0.0, 5.0);
+        let origins = [Vec3::new(2.0, 0.0, 5.0), Vec3::new(0.0, 4.0, 5.0)];
+        let pivot = pivot_center(PivotPoint::Cursor3D, &origins, None, Some(cursor)).unwrap();
+        assert_eq!(pivot, cursor);
+
+        let scale = |factor: f32| {
+            origins.map(|origin| {
+                origin_about_pivot(
+                    origin,
+                    pivot,
+                    Quat::IDENTITY,
+                    Vec3::splat(factor),
+                    Quat::IDENTITY,
+                )
+            })
+        };
+        let grown = scale(2.0);
+        assert!(grown[0].abs_diff_eq(Vec3::new(4.0, 0.0, 5.0), 1e-5));
+        assert!(grown[1].abs_diff_eq(Vec3::new(0.0, 8.0, 5.0), 1e-5));
+        let shrunk = scale(0.5);
+        assert!(shrunk[0].abs_diff_eq(Vec3::new(1.0, 0.0, 5.0), 1e-5));
+        assert!(shrunk[1].abs_diff_eq(Vec3::new(0.0, 2.0, 5.0), 1e-5));
+    }
+
+    #[test]
+    fn test_axis_scaling_follows_the_frame() {
+        let frame = Quat::from_rotation_z(FRAC_PI_2);
+        // Along the frame's X axis (world Y), the offset doubles; world X is untouched
+        let moved = origin_about_pivot(
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::ZERO,
+            Quat::IDENTITY,
+            Vec3::new(2.0, 1.0, 1.0),
+            frame,
+        );
+        assert!(moved.abs_diff_eq(Vec3::new(1.0, 2.0, 0.0), 1e-5));
+    }
+
+    #[test]
+    fn test_missing_active_or_cursor_falls_back_to_median() {
+        let origins = [Vec3::ZERO, Vec3::new(2.0, 0.0, 0.0)];
+        let median = Some(Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(
+            pivot_center(PivotPoint::ActiveObject, &origins, None, None),
+            median
+        );
+        assert_eq!(
+            pivot_center(PivotPoint::Cursor3D, &origins, None, None),
+            median
+        );
+        assert_eq!(
+            pivot_center(
+                PivotPoint::ActiveObject,
+                &origins,
+                Some(Vec3::new(2.0, 0.0, 0.0)),
+                None
+            ),
+            Some(Vec3::new(2.0, 0.0, 0.0))
+        );
+        assert_eq!(pivot_center(PivotPoint::Median, &[], None, None), None);
+    }
+}
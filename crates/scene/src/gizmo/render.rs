@@ -8,12 +8,12 @@ use pentimento_ipc::{GizmoAxis, GizmoMode};
 #[cfg(feature = "selection")]
 use crate::gizmo_raycast::{GizmoGeometry, GizmoHandle};
 #[cfg(feature = "selection")]
-use crate::selection::{Selected, SelectionState};
+use crate::selection::SelectionState;
 
 #[cfg(feature = "selection")]
-use super::state::GizmoState;
+use super::pivot::GizmoPlacement;
 #[cfg(feature = "selection")]
-use super::transform::get_gizmo_transform;
+use super::state::GizmoState;
 
 /// Render gizmo visualization using Bevy's gizmos API
 #[cfg(feature = "selection")]
@@ -22,7 +22,7 @@ pub(crate) fn render_gizmo(
     selection: Res<SelectionState>,
     geometry: Res<GizmoGeometry>,
     mut gizmos: Gizmos,
-    selected_query: Query<&Transform, With<Selected>>,
+    placement: GizmoPlacement,
 ) {
     // Determine if we should render the gizmo
     let should_render = gizmo_state.mode != GizmoMode::None
@@ -32,10 +32,8 @@ pub(crate) fn render_gizmo(
         return;
     }
 
-    // Get gizmo center and orientation
-    let Some((center, orientation)) =
-        get_gizmo_transform(&selected_query, gizmo_state.coordinate_space)
-    else {
+    // Get gizmo center (the pivot) and orientation
+    let Some((center, orientation)) = placement.transform(&gizmo_state) else {
         return;
     };
 
@@ -0,0 +1,249 @@
+//! Translation snapping: grid steps and placing objects on scene surfaces
+
+use bevy::camera::primitives::Aabb;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use painting::raycast::raycast_mesh;
+
+use crate::MainCamera;
+use crate::canvas_plane::CanvasPlane;
+use crate::outline::IdBufferMirror;
+use crate::projection_painting::MeshRaycastCache;
+use crate::reference_image::ReferenceImage;
+use crate::selection::{Selectable, Selected, SelectionState};
+
+/// Meshes that can be snapped onto: scene geometry, not overlays or ID-buffer copies
+type SnapSurfaceFilter = (
+    Without<IdBufferMirror>,
+    Without<CanvasPlane>,
+    Without<ReferenceImage>,
+);
+
+/// Round a world-space translation to steps of `size` along the axes of `frame`
+pub(crate) fn snap_to_grid(offset: Vec3, frame: Quat, size: f32) -> Vec3 {
+    if size <= 0.0 {
+        return offset;
+    }
+    let local = frame.inverse() * offset;
+    frame * ((local / size).round() * size)
+}
+
+/// Rigid motion that puts the active object on a surface; the rest of the
+/// selection follows it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SurfaceMotion {
+    /// Original world position of the active object
+    pivot: Vec3,
+    /// New world position of the active object
+    position: Vec3,
+    /// Rotation applied around the pivot
+    rotation: Quat,
+}
+
+impl SurfaceMotion {
+    /// Move an original world transform along with the active object
+    pub(crate) fn apply(&self, world: Transform) -> Transform {
+        Transform {
+            translation: self.position + self.rotation * (world.translation - self.pivot),
+            rotation: self.rotation * world.rotation,
+            scale: world.scale,
+        }
+    }
+}
+
+/// Place `world` on the surface at `hit` with outward `normal`.
+///
+/// With `bounds` the bottom of the bounding box (along the normal) touches the
+/// surface, otherwise the origin does. `align_to_normal` turns the object's up
+/// axis onto the normal.
+pub(crate) fn surface_motion(
+    world: Transform,
+    bounds: Option<&Aabb>,
+    hit: Vec3,
+    normal: Vec3,
+    align_to_normal: bool,
+) -> SurfaceMotion {
+    let rotation = if align_to_normal {
+        Quat::from_rotation_arc((world.rotation * Vec3::Y).normalize(), normal)
+    } else {
+        Quat::IDENTITY
+    };
+    let placed_rotation = rotation * world.rotation;
+
+    // Lowest point of the box along the normal, relative to the origin
+    let bottom = bounds.map_or(0.0, |aabb| {
+        let center = Vec3::from(aabb.center);
+        let half = Vec3::from(aabb.half_extents);
+        (0..8)
+            .map(|i| {
+                let sign = Vec3::new(
+                    if i & 1 == 0 { -1.0 } else { 1.0 },
+                    if i & 2 == 0 { -1.0 } else { 1.0 },
+                    if i & 4 == 0 { -1.0 } else { 1.0 },
+                );
+                let corner = placed_rotation * (world.scale * (center + half * sign));
+                normal.dot(corner)
+            })
+            .fold(f32::INFINITY, f32::min)
+    });
+
+    SurfaceMotion {
+        pivot: world.translation,
+        position: hit - normal * bottom,
+        rotation,
+    }
+}
+
+/// Surface under the cursor, ignoring the objects being moved
+#[derive(SystemParam)]
+pub(crate) struct SurfaceRaycast<'w, 's> {
+    window: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    camera: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<MainCamera>>,
+    meshes: Res<'w, Assets<Mesh>>,
+    cache: ResMut<'w, MeshRaycastCache>,
+    surfaces: Query<'w, 's, (Entity, &'static Mesh3d, &'static GlobalTransform), SnapSurfaceFilter>,
+    bounds: Query<'w, 's, &'static Aabb>,
+    parents: Query<'w, 's, &'static ChildOf>,
+    selected: Query<'w, 's, (), With<Selected>>,
+    selectables: Query<'w, 's, &'static Selectable>,
+    selection: Res<'w, SelectionState>,
+}
+
+impl SurfaceRaycast<'_, '_> {
+    /// The object snapping is measured from: the last selected one, unless it
+    /// is carried by a selected ancestor
+    pub(crate) fn active_entity(&self, moving: &[(Entity, Transform)]) -> Option<Entity> {
+        let last = self.selection.selected_ids.last();
+        let is_root = |entity: Entity| !self.moves_with_ancestor(entity);
+        moving
+            .iter()
+            .map(|(entity, _)| *entity)
+            .find(|entity| {
+                is_root(*entity)
+                    && self
+                        .selectables
+                        .get(*entity)
+                        .is_ok_and(|selectable| Some(&selectable.id) == last)
+            })
+            .or_else(|| {
+                moving
+                    .iter()
+                    .map(|(entity, _)| *entity)
+                    .find(|e| is_root(*e))
+            })
+    }
+
+    /// Motion placing `entity` (at original world transform `world`) on the
+    /// surface under the cursor, or `None` when the cursor misses the scene
+    pub(crate) fn motion(
+        &mut self,
+        entity: Entity,
+        world: Transform,
+        align_to_normal: bool,
+    ) -> Option<SurfaceMotion> {
+        let (hit, normal) = self.cursor_hit()?;
+        Some(surface_motion(
+            world,
+            self.bounds.get(entity).ok(),
+            hit,
+            normal,
+            align_to_normal,
+        ))
+    }
+
+    fn moves_with_ancestor(&self, entity: Entity) -> bool {
+        self.parents
+            .iter_ancestors(entity)
+            .any(|ancestor| self.selected.contains(ancestor))
+    }
+
+    /// Nearest hit point and world normal under the cursor
+    fn cursor_hit(&mut self) -> Option<(Vec3, Vec3)> {
+        let window = self.window.single().ok()?;
+        let cursor = window.cursor_position()?;
+        let (camera, camera_transform) = self.camera.single().ok()?;
+        let ray = camera.viewport_to_world(camera_transform, cursor).ok()?;
+        let origin = ray.origin;
+        let direction = *ray.direction;
+
+        let mut nearest: Option<(f32, Vec3, Vec3)> = None;
+        for (entity, mesh_handle, mesh_transform) in self.surfaces.iter() {
+            // The moving objects (and anything they carry) are not targets
+            if self.selected.contains(entity) || self.moves_with_ancestor(entity) {
+                continue;
+            }
+            let Some(mesh) = self.meshes.get(&mesh_handle.0) else {
+                continue;
+            };
+            let Some(mesh_data) = self.cache.get_or_build(entity, mesh, mesh_transform) else {
+                continue;
+            };
+
+            // Cast in mesh local space
+            let inverse = mesh_transform.affine().inverse();
+            let local_origin = inverse.transform_point3(origin);
+            let local_dir = inverse.transform_vector3(direction).normalize();
+            let Some(hit) = raycast_mesh(local_origin, local_dir, mesh_data) else {
+                continue;
+            };
+
+            let world_pos = mesh_transform.transform_point(hit.world_pos);
+            let distance = (world_pos - origin).length();
+            if nearest.is_some_and(|(best, _, _)| distance >= best) {
+                continue;
+            }
+            // Normals transform with the inverse transpose
+            let mut normal = inverse.matrix3.transpose().mul_vec3(hit.normal).normalize();
+            // Snap to the side facing the camera
+            if normal.dot(direction) > 0.0 {
+                normal = -normal;
+            }
+            nearest = Some((distance, world_pos, normal));
+        }
+
+        nearest.map(|(_, point, normal)| (point, normal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_cube_rests_on_the_hit_and_aligns_to_the_normal() {
+        let cube = Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5));
+        let world = Transform::from_xyz(3.0, 0.0, 0.0);
+
+        let motion = surface_motion(world, Some(&cube), Vec3::new(0.0, 2.0, 0.0), Vec3::Y, false);
+        let placed = motion.apply(world).translation;
+        assert!(placed.abs_diff_eq(Vec3::new(0.0, 2.5, 0.0), 1e-5));
+
+        // A second object keeps its offset from the active one
+        let other = motion.apply(Transform::from_xyz(4.0, 1.0, 0.0)).translation;
+        assert!(other.abs_diff_eq(Vec3::new(1.0, 3.5, 0.0), 1e-5));
+
+        // On a wall facing +X, the cube's up axis turns onto the normal
+        let wall = surface_motion(world, Some(&cube), Vec3::new(5.0, 1.0, 0.0), Vec3::X, true);
+        let placed = wall.apply(world);
+        let up = placed.rotation * Vec3::Y;
+        assert!(
+            placed
+                .translation
+                .abs_diff_eq(Vec3::new(5.5, 1.0, 0.0), 1e-5)
+        );
+        assert!(up.abs_diff_eq(Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn grid_snapping_rounds_in_the_gizmo_frame() {
+        let offset = Vec3::new(0.74, -0.26, 0.1);
+        let snapped = snap_to_grid(offset, Quat::IDENTITY, 0.5);
+        assert_eq!(snapped, Vec3::new(0.5, -0.5, 0.0));
+        assert_eq!(snap_to_grid(offset, Quat::IDENTITY, 0.0), offset);
+
+        let frame = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+        let snapped = snap_to_grid(frame * Vec3::new(0.9, 0.0, 0.0), frame, 1.0);
+        assert!(snapped.abs_diff_eq(frame * Vec3::X, 1e-5));
+    }
+}
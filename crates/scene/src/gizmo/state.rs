@@ -1,7 +1,7 @@
 //! GizmoState resource and state machine methods
 
 use bevy::prelude::*;
-use pentimento_ipc::{CoordinateSpace, GizmoAxis, GizmoMode};
+use pentimento_ipc::{CoordinateSpace, GizmoAxis, GizmoMode, PivotPoint, SnapTarget};
 
 #[cfg(feature = "selection")]
 use crate::gizmo_raycast::GizmoHandle;
@@ -40,6 +40,10 @@ pub struct GizmoState {
     pub rotation_grab_point: Option<Vec3>,
     /// Whether gizmo should always be visible when selection exists
     pub always_visible: bool,
+    /// What translations snap to; kept across operations
+    pub snap: SnapTarget,
+    /// What rotation and scaling pivot around; kept across operations
+    pub pivot: PivotPoint,
 }
 
 impl Default for GizmoState {
@@ -64,6 +68,8 @@ impl Default for GizmoState {
             #[cfg(feature = "selection")]
             rotation_grab_point: None,
             always_visible: true,
+            snap: SnapTarget::None,
+            pivot: PivotPoint::Median,
         }
     }
 }
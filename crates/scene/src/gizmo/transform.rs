@@ -1,44 +1,105 @@
 //! Gizmo transform application and projection math
 
+#[cfg(feature = "selection")]
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use pentimento_ipc::{CoordinateSpace, GizmoAxis, GizmoMode};
+#[cfg(feature = "selection")]
+use pentimento_ipc::{PivotPoint, SnapTarget};
 
 #[cfg(feature = "selection")]
 use crate::MainCamera;
 #[cfg(feature = "selection")]
-use crate::selection::Selected;
+use crate::grid::SceneGrid;
+#[cfg(feature = "selection")]
+use crate::selection::{Locked, Selected};
 
+#[cfg(feature = "selection")]
+use super::GizmoNudgeEvent;
+#[cfg(feature = "selection")]
+use super::pivot::{Cursor3D, origin_about_pivot, pivot_center};
+#[cfg(feature = "selection")]
+use super::snap::{SurfaceRaycast, snap_to_grid};
 use super::state::GizmoState;
 
-/// Calculate the gizmo center and orientation from selected objects
+/// Selected objects the gizmo moves; locked ones stay put
 #[cfg(feature = "selection")]
-pub(crate) fn get_gizmo_transform(
-    selected_query: &Query<&Transform, With<Selected>>,
-    coordinate_space: CoordinateSpace,
-) -> Option<(Vec3, Quat)> {
-    let mut center = Vec3::ZERO;
-    let mut count = 0;
-    let mut first_rotation = Quat::IDENTITY;
-
-    for (i, transform) in selected_query.iter().enumerate() {
-        center += transform.translation;
-        count += 1;
-        if i == 0 {
-            first_rotation = transform.rotation;
-        }
+type MovableSelection<'w, 's> =
+    Query<'w, 's, (Entity, &'static mut Transform), (With<Selected>, Without<Locked>)>;
+
+/// Parent lookups for applying world-space gizmo motion to the local
+/// transforms of objects inside a hierarchy
+#[cfg(feature = "selection")]
+#[derive(SystemParam)]
+pub(crate) struct GizmoParents<'w, 's> {
+    parents: Query<'w, 's, &'static ChildOf>,
+    globals: Query<'w, 's, &'static GlobalTransform>,
+    selected: Query<'w, 's, (), With<Selected>>,
+}
+
+#[cfg(feature = "selection")]
+impl GizmoParents<'_, '_> {
+    /// Whether a selected ancestor already carries this entity along
+    fn moves_with_ancestor(&self, entity: Entity) -> bool {
+        self.parents
+            .iter_ancestors(entity)
+            .any(|ancestor| self.selected.contains(ancestor))
     }
 
-    if count == 0 {
+    /// Global transform of the entity's parent (identity at the scene root)
+    fn parent_transform(&self, entity: Entity) -> GlobalTransform {
+        self.parents
+            .get(entity)
+            .ok()
+            .and_then(|child_of| self.globals.get(child_of.parent()).ok())
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// World pivot and gizmo frame shared by the whole selection for rotation
+/// and scaling, from the original transforms; `None` with individual origins
+#[cfg(feature = "selection")]
+fn shared_pivot(
+    gizmo_state: &GizmoState,
+    hierarchy: &GizmoParents,
+    active: Option<Entity>,
+    cursor: Option<Vec3>,
+) -> Option<(Vec3, Quat)> {
+    if gizmo_state.pivot == PivotPoint::IndividualOrigins {
         return None;
     }
+    let originals: Vec<(Entity, Transform)> = gizmo_state
+        .original_transforms
+        .iter()
+        .filter(|(entity, _)| !hierarchy.moves_with_ancestor(*entity))
+        .map(|(entity, original)| {
+            let world = hierarchy
+                .parent_transform(*entity)
+                .mul_transform(*original)
+                .compute_transform();
+            (*entity, world)
+        })
+        .collect();
+    let origins: Vec<Vec3> = originals
+        .iter()
+        .map(|(_, world)| world.translation)
+        .collect();
+    let active = active
+        .and_then(|active| originals.iter().find(|(entity, _)| *entity == active))
+        .map(|(_, world)| *world);
 
-    center /= count as f32;
-    let orientation = match coordinate_space {
+    let center = pivot_center(
+        gizmo_state.pivot,
+        &origins,
+        active.map(|world| world.translation),
+        cursor,
+    )?;
+    let frame = match gizmo_state.coordinate_space {
         CoordinateSpace::Global => Quat::IDENTITY,
-        CoordinateSpace::Local => first_rotation,
+        CoordinateSpace::Local => active.map_or(Quat::IDENTITY, |world| world.rotation),
     };
-
-    Some((center, orientation))
+    Some((center, frame))
 }
 
 /// Project a world-space direction to a screen-space direction.
@@ -60,16 +121,73 @@ fn project_to_screen_direction(world_dir: Vec3, camera_transform: &Transform) ->
     Vec2::new(view_dir.x, -view_dir.y).normalize_or_zero()
 }
 
+/// World units per pixel of mouse movement when translating
+#[cfg(feature = "selection")]
+const TRANSLATE_SENSITIVITY: f32 = 0.01;
+
+/// World-space offset for a mouse delta (pixels) in the camera's view plane
+#[cfg(feature = "selection")]
+fn view_plane_offset(delta: Vec2, camera_transform: &Transform) -> Vec3 {
+    let camera_right = camera_transform.rotation * Vec3::X;
+    let camera_up = camera_transform.rotation * Vec3::Y;
+    (camera_right * delta.x + camera_up * -delta.y) * TRANSLATE_SENSITIVITY
+}
+
+/// Move selected objects in the view plane by `GizmoNudgeEvent`s.
+/// Uses the same view-relative translation as an unconstrained grab, so a
+/// navigation device nudges objects the way dragging the mouse would.
+#[cfg(feature = "selection")]
+pub(crate) fn apply_gizmo_nudges(
+    mut events: MessageReader<GizmoNudgeEvent>,
+    gizmo_state: Res<GizmoState>,
+    mut selected_query: MovableSelection,
+    camera_query: Query<&Transform, (With<MainCamera>, Without<Selected>)>,
+    hierarchy: GizmoParents,
+) {
+    // An active grab owns the selection's transforms until confirmed or cancelled
+    if gizmo_state.is_active {
+        events.clear();
+        return;
+    }
+
+    let delta: Vec2 = events.read().map(|event| event.delta).sum();
+    if delta == Vec2::ZERO {
+        return;
+    }
+
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+
+    let offset = view_plane_offset(delta, camera_transform);
+    for (entity, mut transform) in selected_query.iter_mut() {
+        if hierarchy.moves_with_ancestor(entity) {
+            continue;
+        }
+        let parent = hierarchy.parent_transform(entity);
+        transform.translation += parent.affine().inverse().transform_vector3(offset);
+    }
+}
+
 /// Apply gizmo transform to selected objects
 /// Uses Blender-style behavior: transforms are calculated relative to original positions,
 /// so the object position is always original_position + (total_mouse_delta * sensitivity).
 /// This gives smooth, predictable movement without acceleration.
 /// Movement is camera-relative so objects follow the cursor regardless of view angle.
+/// Surface snapping replaces unconstrained translation with a rigid move that puts the
+/// active object on the surface under the cursor; without a hit the normal move applies.
+/// Grid snapping without a fixed size follows the ground grid's visible subdivision.
+/// Rotation and scaling move each origin about the shared pivot (unless each object
+/// pivots on its own origin) on top of changing the object's own rotation and scale.
 #[cfg(feature = "selection")]
 pub(crate) fn apply_gizmo_transform(
     gizmo_state: Res<GizmoState>,
-    mut selected_query: Query<(Entity, &mut Transform), With<Selected>>,
+    mut selected_query: MovableSelection,
     camera_query: Query<&Transform, (With<MainCamera>, Without<Selected>)>,
+    hierarchy: GizmoParents,
+    mut surface: SurfaceRaycast,
+    grid: Option<Res<SceneGrid>>,
+    cursor: Query<&GlobalTransform, With<Cursor3D>>,
 ) {
     if !gizmo_state.is_active {
         return;
@@ -85,7 +203,39 @@ pub(crate) fn apply_gizmo_transform(
     let camera_up = camera_transform.rotation * Vec3::Y;
 
     let delta = gizmo_state.accumulated_delta;
-    let sensitivity = 0.01;
+    let sensitivity = TRANSLATE_SENSITIVITY;
+
+    let surface_motion = match gizmo_state.snap {
+        SnapTarget::Surface { align_to_normal }
+            if gizmo_state.mode == GizmoMode::Translate
+                && gizmo_state.axis_constraint == GizmoAxis::None =>
+        {
+            surface
+                .active_entity(&gizmo_state.original_transforms)
+                .and_then(|active| {
+                    let (_, original) = gizmo_state
+                        .original_transforms
+                        .iter()
+                        .find(|(e, _)| *e == active)?;
+                    let world = hierarchy
+                        .parent_transform(active)
+                        .mul_transform(*original)
+                        .compute_transform();
+                    surface.motion(active, world, align_to_normal)
+                })
+        }
+        _ => None,
+    };
+
+    let pivot = match gizmo_state.mode {
+        GizmoMode::Rotate | GizmoMode::Trackball | GizmoMode::Scale => shared_pivot(
+            &gizmo_state,
+            &hierarchy,
+            surface.active_entity(&gizmo_state.original_transforms),
+            cursor.single().ok().map(|cursor| cursor.translation()),
+        ),
+        _ => None,
+    };
 
     // Apply transforms relative to original positions (stored when operation started)
     for (entity, mut transform) in selected_query.iter_mut() {
@@ -97,17 +247,47 @@ pub(crate) fn apply_gizmo_transform(
         else {
             continue;
         };
+        if hierarchy.moves_with_ancestor(entity) {
+            continue;
+        }
+
+        // Gizmo math is in world space; children convert through their parent
+        let parent = hierarchy.parent_transform(entity);
+        let parent_rotation = parent.rotation();
+        let world_rotation = parent_rotation * original.rotation;
+
+        // Rotation and scale axes: the shared gizmo's, or the object's own
+        let frame = match pivot {
+            Some((_, frame)) => frame,
+            None if gizmo_state.coordinate_space == CoordinateSpace::Local => world_rotation,
+            None => Quat::IDENTITY,
+        };
+        // Origin moved about the shared pivot (in world space), back in parent space
+        let pivot_translation = |rotation: Quat, scale: Vec3| match pivot {
+            Some((center, pivot_frame)) => {
+                let origin = parent.transform_point(original.translation);
+                let moved = origin_about_pivot(origin, center, rotation, scale, pivot_frame);
+                parent.affine().inverse().transform_point3(moved)
+            }
+            None => original.translation,
+        };
 
         match gizmo_state.mode {
             GizmoMode::Translate => {
+                if let Some(motion) = &surface_motion {
+                    let world = parent.mul_transform(*original).compute_transform();
+                    *transform = GlobalTransform::from(motion.apply(world)).reparented_to(&parent);
+                    continue;
+                }
+
                 // Camera-relative movement: project mouse input through camera orientation
                 // For Local mode, use the object's rotated axis directions
                 let (axis_x, axis_y, axis_z) =
                     if gizmo_state.coordinate_space == CoordinateSpace::Local {
                         (
-                            original.rotation * Vec3::X,
-                            original.rotation * Vec3::Y,
-                            original.rotation * Vec3::Z,
+                            world_rotation * Vec3::X,
+                            world_rotation * Vec3::Y,
+                            world_rotation * Vec3::Z,
                         )
                     } else {
                         (Vec3::X, Vec3::Y, Vec3::Z)
@@ -116,7 +296,7 @@ pub(crate) fn apply_gizmo_transform(
                 let base_movement = match gizmo_state.axis_constraint {
                     GizmoAxis::None => {
                         // Unconstrained: mouse movement in camera's view plane
-                        (camera_right * delta.x + camera_up * -delta.y) * sensitivity
+                        view_plane_offset(delta, camera_transform)
                     }
                     GizmoAxis::X => {
                         // Project mouse movement onto X axis based on camera view
@@ -157,9 +337,23 @@ pub(crate) fn apply_gizmo_transform(
                         (axis_y * move_y + axis_z * move_z) * sensitivity
                     }
                 };
+                let base_movement = match gizmo_state.snap {
+                    SnapTarget::Grid { size } => {
+                        let size = size
+                            .unwrap_or_else(|| grid.as_ref().map_or(1.0, |grid| grid.snap_step()));
+                        let frame = if gizmo_state.coordinate_space == CoordinateSpace::Local {
+                            world_rotation
+                        } else {
+                            Quat::IDENTITY
+                        };
+                        snap_to_grid(base_movement, frame, size)
+                    }
+                    _ => base_movement,
+                };
 
                 // Set position = original + offset (not incremental!)
-                transform.translation = original.translation + base_movement;
+                transform.translation = original.translation
+                    + parent.affine().inverse().transform_vector3(base_movement);
             }
             GizmoMode::Scale => {
                 // Scale factor based on total mouse X movement
@@ -176,6 +370,7 @@ pub(crate) fn apply_gizmo_transform(
                 // Scale is always in local space (it affects object's own axes)
                 // Set scale = original * multiplier (not incremental!)
                 transform.scale = original.scale * scale_multiplier;
+                transform.translation = pivot_translation(Quat::IDENTITY, scale_multiplier);
             }
             GizmoMode::Rotate => {
                 // Determine rotation axis based on constraint
@@ -186,11 +381,7 @@ pub(crate) fn apply_gizmo_transform(
                 };
 
                 // Get the actual rotation axis (local or global)
-                let axis = if gizmo_state.coordinate_space == CoordinateSpace::Local {
-                    original.rotation * base_axis
-                } else {
-                    base_axis
-                };
+                let axis = frame * base_axis;
 
                 // Calculate rotation amount based on grab point tangent (for handle drags)
                 // or simple horizontal mouse movement (for hotkey activation)
@@ -200,7 +391,10 @@ pub(crate) fn apply_gizmo_transform(
                     // while grabbing back and dragging right rotates the opposite way
 
                     // Calculate tangent at grab point
-                    let gizmo_center = original.translation;
+                    let gizmo_center = pivot.map_or_else(
+                        || parent.transform_point(original.translation),
+                        |(center, _)| center,
+                    );
                     let radial = (grab_point - gizmo_center).normalize();
                     let tangent = axis.cross(radial).normalize();
 
@@ -222,7 +416,8 @@ pub(crate) fn apply_gizmo_transform(
                 let rotation = Quat::from_axis_angle(axis, rotation_amount);
 
                 // Set rotation = delta_rotation * original (not incremental!)
-                transform.rotation = rotation * original.rotation;
+                transform.rotation = parent_rotation.inverse() * rotation * world_rotation;
+                transform.translation = pivot_translation(rotation, Vec3::ONE);
             }
             GizmoMode::Trackball => {
                 // Trackball rotation: free rotation based on mouse movement
@@ -231,16 +426,7 @@ pub(crate) fn apply_gizmo_transform(
                 // Combined gives intuitive "grab and spin" behavior
 
                 // Get axes (local or global)
-                let (axis_x, axis_y, axis_z) =
-                    if gizmo_state.coordinate_space == CoordinateSpace::Local {
-                        (
-                            original.rotation * Vec3::X,
-                            original.rotation * Vec3::Y,
-                            original.rotation * Vec3::Z,
-                        )
-                    } else {
-                        (Vec3::X, Vec3::Y, Vec3::Z)
-                    };
+                let (axis_x, axis_y, axis_z) = (frame * Vec3::X, frame * Vec3::Y, frame * Vec3::Z);
 
                 let rotation_x = -delta.y * sensitivity; // Vertical mouse rotates around X
                 let rotation_y = delta.x * sensitivity; // Horizontal mouse rotates around Y
@@ -273,7 +459,9 @@ pub(crate) fn apply_gizmo_transform(
                 };
 
                 // Apply trackball rotation to object
-                transform.rotation = trackball_rotation * original.rotation;
+                transform.rotation =
+                    parent_rotation.inverse() * trackball_rotation * world_rotation;
+                transform.translation = pivot_translation(trackball_rotation, Vec3::ONE);
             }
             GizmoMode::None => {}
         }
@@ -0,0 +1,635 @@
+//! Ground grid — an infinite reference grid with colored axes.
+//!
+//! The grid is a fullscreen post-process pass rather than a mesh: each pixel
+//! intersects its view ray with the grid plane and depth-tests the hit
+//! against the view's depth. Nothing is spawned, so the grid never shows up
+//! in raycasts, the outliner, snapping, painting targets, or the depth view
+//! bounds. The pass runs after tonemapping and before the view mode pass,
+//! so debug views replace it and outlines draw over it.
+//!
+//! Line spacing adapts to the camera's height above the plane: minor lines
+//! every `SceneGrid::step` with major lines every ten, fading the minor ones
+//! out as the camera climbs toward the next power of ten. Orthographic views
+//! looking down the X or Z axis swap to the YZ or XY plane.
+
+use bevy::asset::embedded_asset;
+use bevy::core_pipeline::FullscreenShader;
+use bevy::core_pipeline::core_3d::graph::{Core3d, Node3d};
+use bevy::prelude::*;
+use bevy::render::{
+    Render, RenderApp, RenderSystems,
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    extract_resource::{ExtractResource, ExtractResourcePlugin},
+    render_graph::{
+        NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
+    },
+    render_resource::{
+        BindGroupEntries, BindGroupLayoutDescriptor, BindGroupLayoutEntries, Buffer,
+        BufferInitDescriptor, BufferUsages, CachedRenderPipelineId, ColorTargetState, ColorWrites,
+        FragmentState, MultisampleState, Operations, PipelineCache, PrimitiveState,
+        RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, Sampler,
+        SamplerBindingType, SamplerDescriptor, ShaderStages, ShaderType, SpecializedRenderPipeline,
+        SpecializedRenderPipelines, TextureFormat, TextureSampleType,
+        binding_types::{
+            sampler, texture_2d, texture_depth_2d, texture_depth_2d_multisampled, uniform_buffer,
+        },
+    },
+    renderer::{RenderContext, RenderDevice},
+    view::{ExtractedView, ViewDepthTexture, ViewTarget},
+};
+use bevy::transform::TransformSystems;
+
+use crate::camera::enable_depth_texture_binding;
+
+/// Camera height (or orthographic half-height) that shows 1 unit minor lines
+const HEIGHT_PER_STEP: f32 = 5.0;
+
+/// Finest minor line spacing in world units
+const MIN_STEP: f32 = 0.001;
+
+/// Coarsest minor line spacing in world units
+const MAX_STEP: f32 = 10_000.0;
+
+/// Grid lines fade out this many camera heights away from the camera
+const FADE_DISTANCE_PER_HEIGHT: f32 = 40.0;
+
+/// How closely an orthographic view must look down an axis to use that
+/// axis' plane (cosine of the angle)
+const AXIS_ALIGNED: f32 = 0.999;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// Render graph label for the grid pass.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GridLabel;
+
+/// Marker component for the camera the grid is drawn on.  Extracted to the
+/// render world automatically.
+#[derive(Component, Clone, ExtractComponent)]
+pub struct GridCamera;
+
+/// World plane the grid lies in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridPlane {
+    /// The ground plane (normal +Y)
+    #[default]
+    XZ,
+    /// Front plane, used when looking down the Z axis orthographically
+    XY,
+    /// Side plane, used when looking down the X axis orthographically
+    YZ,
+}
+
+impl GridPlane {
+    /// Plane for a camera's projection and view direction: the ground plane
+    /// unless an orthographic view looks straight down the X or Z axis.
+    pub fn for_view(projection: &Projection, forward: Vec3) -> Self {
+        if !matches!(projection, Projection::Orthographic(_)) {
+            return Self::XZ;
+        }
+        let forward = forward.abs();
+        if forward.x >= AXIS_ALIGNED {
+            Self::YZ
+        } else if forward.z >= AXIS_ALIGNED {
+            Self::XY
+        } else {
+            Self::XZ
+        }
+    }
+
+    /// Unit normal of the plane.
+    pub fn normal(self) -> Vec3 {
+        match self {
+            Self::XZ => Vec3::Y,
+            Self::XY => Vec3::Z,
+            Self::YZ => Vec3::X,
+        }
+    }
+
+    /// The two world axes spanning the plane.
+    pub fn axes(self) -> (Vec3, Vec3) {
+        match self {
+            Self::XZ => (Vec3::X, Vec3::Z),
+            Self::XY => (Vec3::X, Vec3::Y),
+            Self::YZ => (Vec3::Y, Vec3::Z),
+        }
+    }
+}
+
+/// Grid state, updated from the grid camera each frame.  Extracted to the
+/// render world when it changes.
+#[derive(Resource, Clone, PartialEq, ExtractResource)]
+pub struct SceneGrid {
+    /// Whether the grid is drawn (`AppSettings::show_grid`)
+    pub visible: bool,
+    /// Plane the grid lies in
+    pub plane: GridPlane,
+    /// Minor line spacing in world units, a power of ten
+    pub step: f32,
+    /// How far the camera is toward the next coarser step (0-1); minor lines
+    /// fade out as it approaches 1
+    pub lod_fade: f32,
+    /// Distance from the camera at which the grid has faded out
+    pub fade_distance: f32,
+}
+
+impl Default for SceneGrid {
+    fn default() -> Self {
+        let (step, lod_fade) = grid_level(HEIGHT_PER_STEP);
+        Self {
+            visible: true,
+            plane: GridPlane::XZ,
+            step,
+            lod_fade,
+            fade_distance: HEIGHT_PER_STEP * FADE_DISTANCE_PER_HEIGHT,
+        }
+    }
+}
+
+impl SceneGrid {
+    /// Spacing of the finest clearly visible lines, which grid snapping
+    /// follows when no fixed size is set: the minor lines until they are
+    /// faded halfway, then the major lines.
+    pub fn snap_step(&self) -> f32 {
+        if self.lod_fade < 0.5 {
+            self.step
+        } else {
+            self.step * 10.0
+        }
+    }
+}
+
+/// Minor line spacing and fade toward the next level for a camera `height`
+/// above the grid.
+fn grid_level(height: f32) -> (f32, f32) {
+    let height = if height.is_finite() {
+        height
+    } else {
+        HEIGHT_PER_STEP
+    };
+    let level = (height.max(MIN_STEP * HEIGHT_PER_STEP) / HEIGHT_PER_STEP).log10();
+    let floor = level.floor();
+    let step = 10f32.powf(floor);
+    if step <= MIN_STEP {
+        return (MIN_STEP, 0.0);
+    }
+    if step >= MAX_STEP {
+        return (MAX_STEP, 0.0);
+    }
+    (step, level - floor)
+}
+
+/// Height the grid level is chosen from: the camera's distance to the plane,
+/// or half the visible height of an orthographic view.
+fn view_height(camera: &GlobalTransform, projection: &Projection, plane: GridPlane) -> f32 {
+    match projection {
+        Projection::Orthographic(ortho) => ortho.area.height() * 0.5,
+        _ => camera.translation().dot(plane.normal()).abs(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Plugin
+// ---------------------------------------------------------------------------
+
+pub struct GridPlugin;
+
+impl Plugin for GridPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/grid.wgsl");
+
+        app.init_resource::<SceneGrid>();
+        app.add_plugins(ExtractComponentPlugin::<GridCamera>::default());
+        app.add_plugins(ExtractResourcePlugin::<SceneGrid>::default());
+
+        app.add_systems(Update, enable_grid_depth_sampling);
+        app.add_systems(
+            PostUpdate,
+            update_scene_grid.after(TransformSystems::Propagate),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            warn!("GridPlugin: No RenderApp available");
+            return;
+        };
+
+        render_app.add_render_graph_node::<ViewNodeRunner<GridNode>>(Core3d, GridLabel);
+
+        // Draw before the view mode pass so debug views replace the grid
+        // and outlines (after the view mode pass) stay on top of it.
+        render_app.add_render_graph_edges(
+            Core3d,
+            (Node3d::Tonemapping, GridLabel, crate::ViewModeLabel),
+        );
+
+        render_app
+            .init_resource::<SpecializedRenderPipelines<GridPipeline>>()
+            .add_systems(
+                Render,
+                prepare_grid.in_set(RenderSystems::PrepareBindGroups),
+            );
+
+        info!("GridPlugin: render graph node registered");
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<GridPipeline>();
+        info!("GridPlugin: pipeline initialized");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Main-world systems
+// ---------------------------------------------------------------------------
+
+/// Let the grid pass depth-test against the scene of cameras it draws on.
+fn enable_grid_depth_sampling(mut cameras: Query<&mut Camera3d, Added<GridCamera>>) {
+    for mut camera in cameras.iter_mut() {
+        enable_depth_texture_binding(&mut camera);
+    }
+}
+
+/// Pick the grid plane and line spacing for the grid camera's view.
+fn update_scene_grid(
+    mut grid: ResMut<SceneGrid>,
+    cameras: Query<(&GlobalTransform, &Projection), With<GridCamera>>,
+) {
+    let Some((camera, projection)) = cameras.iter().next() else {
+        return;
+    };
+
+    let plane = GridPlane::for_view(projection, camera.forward().as_vec3());
+    let height = view_height(camera, projection, plane);
+    let (step, lod_fade) = grid_level(height);
+    let updated = SceneGrid {
+        plane,
+        step,
+        lod_fade,
+        fade_distance: height.max(HEIGHT_PER_STEP * MIN_STEP) * FADE_DISTANCE_PER_HEIGHT,
+        ..*grid
+    };
+    // Only flag a change (and re-extract) when the camera moved
+    grid.set_if_neq(updated);
+}
+
+// ---------------------------------------------------------------------------
+// Render world
+// ---------------------------------------------------------------------------
+
+/// Uniform data for the grid shader.
+#[derive(Clone, Copy, ShaderType)]
+struct GridUniform {
+    world_from_clip: Mat4,
+    clip_from_world: Mat4,
+    camera_position: Vec3,
+    fade_distance: f32,
+    normal: Vec3,
+    step: f32,
+    axis_u: Vec3,
+    lod_fade: f32,
+    axis_v: Vec3,
+    _padding: f32,
+    /// Color of the axis line along `axis_u`
+    u_color: Vec4,
+    /// Color of the axis line along `axis_v`
+    v_color: Vec4,
+    line_color: Vec4,
+}
+
+/// Color of the axis line along a world axis (X red, Y green, Z blue).
+fn axis_color(axis: Vec3) -> Vec4 {
+    let color = if axis == Vec3::X {
+        Color::srgb(0.9, 0.25, 0.3)
+    } else if axis == Vec3::Y {
+        Color::srgb(0.45, 0.75, 0.2)
+    } else {
+        Color::srgb(0.25, 0.5, 0.9)
+    };
+    linear(color)
+}
+
+/// Opaque linear color for a shader uniform.
+fn linear(color: Color) -> Vec4 {
+    let color = color.to_linear();
+    Vec4::new(color.red, color.green, color.blue, 1.0)
+}
+
+/// Render graph node compositing the grid onto the scene.
+#[derive(Default)]
+struct GridNode;
+
+impl ViewNode for GridNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewDepthTexture,
+        Option<&'static GridCamera>,
+    );
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (view_target, view_depth, grid_camera): bevy::ecs::query::QueryItem<
+            'w,
+            'w,
+            Self::ViewQuery,
+        >,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        if grid_camera.is_none() {
+            return Ok(());
+        }
+
+        let Some(grid) = world.get_resource::<SceneGrid>() else {
+            return Ok(());
+        };
+        if !grid.visible {
+            return Ok(());
+        }
+
+        let Some(prepared) = world.get_resource::<GridPrepared>() else {
+            return Ok(());
+        };
+        let Some(pipeline) = world.get_resource::<GridPipeline>() else {
+            return Ok(());
+        };
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(render_pipeline) = pipeline_cache.get_render_pipeline(prepared.pipeline_id) else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+
+        let layout = pipeline_cache.get_bind_group_layout(&prepared.layout);
+        let bind_group = render_context.render_device().create_bind_group(
+            "grid_bind_group",
+            &layout,
+            &BindGroupEntries::sequential((
+                prepared.uniform_buffer.as_entire_binding(),
+                view_depth.view(),
+                post_process.source,
+                &pipeline.sampler,
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("grid_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_render_pipeline(render_pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+/// Pipeline for the grid pass, specialized per view.
+#[derive(Resource)]
+struct GridPipeline {
+    sampler: Sampler,
+    shader: Handle<Shader>,
+    fullscreen_shader: FullscreenShader,
+}
+
+impl FromWorld for GridPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        Self {
+            sampler,
+            shader: world.load_asset("embedded://pentimento_scene/grid/shaders/grid.wgsl"),
+            fullscreen_shader: world.resource::<FullscreenShader>().clone(),
+        }
+    }
+}
+
+/// Shader variant of the grid pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GridPipelineKey {
+    /// Whether the view's depth texture is multisampled (MSAA on)
+    multisampled: bool,
+    texture_format: TextureFormat,
+}
+
+impl GridPipelineKey {
+    /// Bindings: uniform, view_depth, scene_texture, scene_sampler
+    fn layout(&self) -> BindGroupLayoutDescriptor {
+        let view_depth = if self.multisampled {
+            texture_depth_2d_multisampled()
+        } else {
+            texture_depth_2d()
+        };
+        let entries = BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                uniform_buffer::<GridUniform>(false),
+                view_depth,
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+            ),
+        );
+        BindGroupLayoutDescriptor::new("grid_bind_group_layout", &entries.to_vec())
+    }
+}
+
+impl SpecializedRenderPipeline for GridPipeline {
+    type Key = GridPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if key.multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+
+        RenderPipelineDescriptor {
+            label: Some("grid_pipeline".into()),
+            layout: vec![key.layout()],
+            vertex: self.fullscreen_shader.to_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: key.texture_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+/// Prepared data for the grid pass (created during Prepare phase).
+#[derive(Resource)]
+struct GridPrepared {
+    pipeline_id: CachedRenderPipelineId,
+    layout: BindGroupLayoutDescriptor,
+    uniform_buffer: Buffer,
+}
+
+/// Prepare the grid pipeline and uniforms for the grid camera each frame.
+#[allow(clippy::too_many_arguments)]
+fn prepare_grid(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<GridPipeline>>,
+    grid_pipeline: Option<Res<GridPipeline>>,
+    grid: Option<Res<SceneGrid>>,
+    views: Query<(&ExtractedView, &ViewTarget, &Msaa), With<GridCamera>>,
+) {
+    let (Some(grid), Some(grid_pipeline)) = (grid, grid_pipeline) else {
+        return;
+    };
+    let Some((view, view_target, msaa)) = views.iter().next().filter(|_| grid.visible) else {
+        commands.remove_resource::<GridPrepared>();
+        return;
+    };
+
+    let key = GridPipelineKey {
+        multisampled: msaa.samples() > 1,
+        texture_format: view_target.main_texture_format(),
+    };
+    let pipeline_id = pipelines.specialize(&pipeline_cache, &grid_pipeline, key);
+
+    let clip_from_world = view.clip_from_view * view.world_from_view.to_matrix().inverse();
+    let (axis_u, axis_v) = grid.plane.axes();
+    let uniform = GridUniform {
+        world_from_clip: clip_from_world.inverse(),
+        clip_from_world,
+        camera_position: view.world_from_view.translation(),
+        fade_distance: grid.fade_distance,
+        normal: grid.plane.normal(),
+        step: grid.step,
+        axis_u,
+        lod_fade: grid.lod_fade,
+        axis_v,
+        _padding: 0.0,
+        u_color: axis_color(axis_u),
+        v_color: axis_color(axis_v),
+        line_color: linear(Color::srgb(0.55, 0.55, 0.55)),
+    };
+
+    // Create uniform buffer using encase for proper alignment
+    let mut buffer = bevy::render::render_resource::encase::UniformBuffer::new(Vec::new());
+    buffer.write(&uniform).unwrap();
+    let uniform_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("grid_uniform_buffer"),
+        contents: buffer.as_ref(),
+        usage: BufferUsages::UNIFORM,
+    });
+
+    commands.insert_resource(GridPrepared {
+        pipeline_id,
+        layout: key.layout(),
+        uniform_buffer,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_level(height: f32, step: f32, lod_fade: f32) {
+        let (actual_step, actual_fade) = grid_level(height);
+        assert!(
+            (actual_step - step).abs() <= step * 1e-4,
+            "height {height}: step {actual_step}, expected {step}"
+        );
+        assert!(
+            (actual_fade - lod_fade).abs() < 1e-3,
+            "height {height}: fade {actual_fade}, expected {lod_fade}"
+        );
+    }
+
+    #[test]
+    fn step_follows_camera_height_in_powers_of_ten() {
+        assert_level(5.0, 1.0, 0.0);
+        assert_level(60.0, 10.0, 0.0792);
+        assert_level(0.6, 0.1, 0.0792);
+        // Halfway (in log scale) to the next level
+        assert_level(5.0 * 10f32.sqrt(), 1.0, 0.5);
+    }
+
+    #[test]
+    fn step_is_clamped_for_degenerate_heights() {
+        assert_level(0.0, MIN_STEP, 0.0);
+        assert_level(f32::NAN, 1.0, 0.0);
+        assert_level(1.0e9, MAX_STEP, 0.0);
+    }
+
+    #[test]
+    fn snap_step_uses_finest_clearly_visible_lines() {
+        let mut grid = SceneGrid {
+            step: 0.1,
+            lod_fade: 0.2,
+            ..default()
+        };
+        assert_eq!(grid.snap_step(), 0.1);
+        grid.lod_fade = 0.8;
+        assert_eq!(grid.snap_step(), 1.0);
+    }
+
+    #[test]
+    fn orthographic_axis_views_swap_the_plane() {
+        let ortho = Projection::Orthographic(OrthographicProjection::default_3d());
+        let perspective = Projection::default();
+
+        assert_eq!(GridPlane::for_view(&ortho, Vec3::NEG_Z), GridPlane::XY);
+        assert_eq!(GridPlane::for_view(&ortho, Vec3::X), GridPlane::YZ);
+        assert_eq!(GridPlane::for_view(&ortho, Vec3::NEG_Y), GridPlane::XZ);
+        // Off-axis orthographic and any perspective view keep the ground
+        let oblique = Vec3::new(1.0, -1.0, -1.0).normalize();
+        assert_eq!(GridPlane::for_view(&ortho, oblique), GridPlane::XZ);
+        assert_eq!(
+            GridPlane::for_view(&perspective, Vec3::NEG_Z),
+            GridPlane::XZ
+        );
+    }
+
+    #[test]
+    fn grid_follows_camera_and_keeps_visibility() {
+        let mut app = App::new();
+        app.insert_resource(SceneGrid {
+            visible: false,
+            ..default()
+        });
+        app.add_systems(Update, update_scene_grid);
+        app.world_mut().spawn((
+            GridCamera,
+            Projection::default(),
+            GlobalTransform::from(
+                Transform::from_xyz(0.0, 60.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ),
+        ));
+
+        app.update();
+
+        let grid = app.world().resource::<SceneGrid>();
+        assert!(!grid.visible);
+        assert_eq!(grid.plane, GridPlane::XZ);
+        assert!((grid.step - 10.0).abs() < 1e-3);
+        assert!((grid.fade_distance - 60.0 * FADE_DISTANCE_PER_HEIGHT).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,132 @@
+// Infinite grid composited onto the scene (ViewTarget post-processing)
+//
+// Each pixel's view ray is intersected with the grid plane. Where the hit is
+// in front of the scene (reverse-Z depth test against the view depth) the
+// grid lines and axes are blended over the scene color.
+//
+// Three line levels are drawn: minor (step), major (step * 10), and coarse
+// (step * 100). As lod_fade goes from 0 to 1 the minor lines fade out and the
+// major ones drop to minor strength, so the next level takes over seamlessly.
+// MULTISAMPLED is set when the view's depth texture is multisampled (MSAA on)
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct GridUniform {
+    world_from_clip: mat4x4<f32>,
+    clip_from_world: mat4x4<f32>,
+    camera_position: vec3<f32>,
+    fade_distance: f32,
+    normal: vec3<f32>,
+    step: f32,
+    axis_u: vec3<f32>,
+    lod_fade: f32,
+    axis_v: vec3<f32>,
+    _padding: f32,
+    u_color: vec4<f32>,
+    v_color: vec4<f32>,
+    line_color: vec4<f32>,
+}
+
+const MINOR_ALPHA: f32 = 0.2;
+const MAJOR_ALPHA: f32 = 0.45;
+const AXIS_ALPHA: f32 = 0.9;
+
+// Line half-widths in pixels
+const LINE_WIDTH: f32 = 1.0;
+const AXIS_WIDTH: f32 = 1.5;
+
+// Relative depth difference still counted as in front, so the grid is not
+// hidden by geometry lying exactly on the plane
+const DEPTH_TOLERANCE: f32 = 0.0001;
+
+@group(0) @binding(0)
+var<uniform> grid: GridUniform;
+
+#ifdef MULTISAMPLED
+@group(0) @binding(1)
+var view_depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(1)
+var view_depth_texture: texture_depth_2d;
+#endif
+
+@group(0) @binding(2)
+var scene_texture: texture_2d<f32>;
+
+@group(0) @binding(3)
+var scene_sampler: sampler;
+
+fn unproject(ndc: vec3<f32>) -> vec3<f32> {
+    let world = grid.world_from_clip * vec4<f32>(ndc, 1.0);
+    return world.xyz / world.w;
+}
+
+// Coverage of lines every `spacing` units, faded out where they get closer
+// than a few pixels apart so distant lines do not turn into moire
+fn lines(coord: vec2<f32>, spacing: f32) -> f32 {
+    let cells = coord / spacing;
+    let cells_per_pixel = max(fwidth(cells), vec2<f32>(1e-6));
+    let pixels = abs(fract(cells - 0.5) - 0.5) / cells_per_pixel;
+    let coverage = 1.0 - min(min(pixels.x, pixels.y) / LINE_WIDTH, 1.0);
+    let density = max(cells_per_pixel.x, cells_per_pixel.y);
+    return coverage * (1.0 - smoothstep(0.15, 0.4, density));
+}
+
+// Coverage of the axis line where `offset` (distance from the axis) is 0
+fn axis_line(offset: f32, units_per_pixel: f32) -> f32 {
+    return 1.0 - min(abs(offset) / (max(units_per_pixel, 1e-6) * AXIS_WIDTH), 1.0);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let scene_color = textureSample(scene_texture, scene_sampler, in.uv);
+
+    // View ray through this pixel; reverse-Z puts the near plane at 1
+    let ndc = vec2<f32>(in.uv.x * 2.0 - 1.0, 1.0 - in.uv.y * 2.0);
+    let origin = unproject(vec3<f32>(ndc, 1.0));
+    let direction = normalize(unproject(vec3<f32>(ndc, 0.5)) - origin);
+    let facing = dot(direction, grid.normal);
+    let t = -dot(origin, grid.normal) / select(facing, 1e-6, abs(facing) < 1e-6);
+    let hit = origin + direction * t;
+    let coord = vec2<f32>(dot(hit, grid.axis_u), dot(hit, grid.axis_v));
+
+    // Derivatives need uniform control flow, so every line is measured
+    // before pixels off the plane or behind the scene return early
+    let minor = lines(coord, grid.step);
+    let major = lines(coord, grid.step * 10.0);
+    let coarse = lines(coord, grid.step * 100.0);
+    let units_per_pixel = fwidth(coord);
+    // The axis along axis_u lies where the v coordinate is 0, and vice versa
+    let u_axis = axis_line(coord.y, units_per_pixel.y);
+    let v_axis = axis_line(coord.x, units_per_pixel.x);
+
+    if t <= 0.0 {
+        return scene_color;
+    }
+
+    let clip = grid.clip_from_world * vec4<f32>(hit, 1.0);
+    let grid_depth = clip.z / clip.w;
+    // Sample 0 when multisampled, mip 0 otherwise
+    let depth_coord = vec2<i32>(in.uv * vec2<f32>(textureDimensions(view_depth_texture)));
+    let view_depth = textureLoad(view_depth_texture, depth_coord, 0);
+    // Reverse-Z: larger is nearer
+    if grid_depth < view_depth * (1.0 - DEPTH_TOLERANCE) {
+        return scene_color;
+    }
+
+    // Fade with distance along the plane from the point under the camera
+    let offset = hit - grid.camera_position;
+    let planar = offset - grid.normal * dot(offset, grid.normal);
+    let fade = 1.0 - smoothstep(0.5 * grid.fade_distance, grid.fade_distance, length(planar));
+
+    let line = max(
+        max(minor * MINOR_ALPHA * (1.0 - grid.lod_fade),
+            major * mix(MAJOR_ALPHA, MINOR_ALPHA, grid.lod_fade)),
+        coarse * MAJOR_ALPHA,
+    );
+
+    var color = mix(scene_color.rgb, grid.line_color.rgb, line * fade);
+    color = mix(color, grid.v_color.rgb, v_axis * AXIS_ALPHA * fade);
+    color = mix(color, grid.u_color.rgb, u_axis * AXIS_ALPHA * fade);
+    return vec4<f32>(color, scene_color.a);
+}
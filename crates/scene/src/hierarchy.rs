@@ -0,0 +1,455 @@
+//! Object hierarchy
+//!
+//! Parents selectable objects to each other with Bevy's `ChildOf`
+//! relationship, so child transforms compose with their parent's and the
+//! gizmo moves children along with a selected parent. `ObjectCommand::Group`
+//! creates an empty transform node (`ObjectGroup`) for its members.
+//! Descendants of a selected group get `GroupSelected`, which the outline
+//! treats like `Selected`.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use pentimento_ipc::{BevyToUi, ObjectCommand, SceneObject, Transform3D, WireframeInfo};
+
+use crate::instancing::{is_instanced, mesh_users};
+use crate::object_id::IdAllocator;
+use crate::object_visibility::IsolationState;
+use crate::reference_image::{self, ReferenceImage};
+use crate::scene_light::SceneLight;
+use crate::selection::{Locked, Selectable, Selected, SelectionState};
+use crate::subdivision::{BaseMesh, Subdivision};
+#[cfg(feature = "wireframe")]
+use crate::wireframe::{ObjectWireframe, WireframeSettings};
+use crate::{ObjectCommandEvent, OutboundUiMessages};
+
+/// Empty transform node created by `ObjectCommand::Group`
+#[derive(Component, Debug)]
+pub struct ObjectGroup;
+
+/// Marker on descendants of a selected group, outlined like selected objects
+#[derive(Component, Debug)]
+pub struct GroupSelected;
+
+/// Objects reported in `SceneInfo`: meshes and groups
+pub(crate) type SceneObjectFilter = Or<(With<Mesh3d>, With<ObjectGroup>)>;
+
+/// Read access to scene objects as `SceneObject`, with parent IDs,
+/// subdivision levels, lock, instancing, and wireframe state
+#[derive(SystemParam)]
+pub struct SceneObjects<'w, 's> {
+    objects: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Selectable,
+            &'static Name,
+            &'static Transform,
+            &'static Visibility,
+        ),
+        SceneObjectFilter,
+    >,
+    parents: Query<'w, 's, &'static ChildOf>,
+    selectables: Query<'w, 's, &'static Selectable>,
+    subdivisions: Query<'w, 's, &'static Subdivision>,
+    meshes: Query<'w, 's, (&'static Mesh3d, Option<&'static BaseMesh>)>,
+    materials: Query<'w, 's, (), With<MeshMaterial3d<StandardMaterial>>>,
+    locked: Query<'w, 's, (), With<Locked>>,
+    isolation: Option<Res<'w, IsolationState>>,
+    #[cfg(feature = "wireframe")]
+    wireframes: Query<'w, 's, &'static ObjectWireframe>,
+    #[cfg(feature = "wireframe")]
+    wireframe_settings: Option<Res<'w, WireframeSettings>>,
+}
+
+impl SceneObjects<'_, '_> {
+    /// All scene objects; transforms are relative to the parent
+    pub fn infos(&self) -> Vec<SceneObject> {
+        let users = mesh_users(self.meshes.iter());
+        let mut objects = self
+            .objects
+            .iter()
+            .map(
+                |(entity, selectable, name, transform, visibility)| SceneObject {
+                    id: selectable.id.clone(),
+                    name: name.to_string(),
+                    transform: to_transform3d(transform),
+                    // Materials aren't shared yet; each is addressed by its object's ID
+                    material_id: self
+                        .materials
+                        .contains(entity)
+                        .then(|| selectable.id.clone()),
+                    visible: *visibility != Visibility::Hidden,
+                    locked: self.locked.contains(entity),
+                    // Nearest selectable ancestor; helper entities in between are skipped
+                    parent_id: self
+                        .parents
+                        .iter_ancestors(entity)
+                        .find_map(|ancestor| self.selectables.get(ancestor).ok())
+                        .map(|parent| parent.id.clone()),
+                    subdivision_levels: self
+                        .subdivisions
+                        .get(entity)
+                        .map_or(0, |subdivision| subdivision.levels),
+                    wireframe: self.object_wireframe(entity),
+                    instanced: self
+                        .meshes
+                        .get(entity)
+                        .is_ok_and(|(mesh, base)| is_instanced(&users, mesh, base)),
+                },
+            )
+            .collect::<Vec<_>>();
+        // IDs are time-ordered, so the outliner lists objects in creation
+        // order instead of query order
+        objects.sort_by(|a, b| a.id.cmp(&b.id));
+        objects
+    }
+
+    /// Whether `ObjectCommand::Isolate` is hiding the other objects
+    pub fn isolated(&self) -> bool {
+        self.isolation
+            .as_ref()
+            .is_some_and(|isolation| isolation.is_isolated())
+    }
+
+    /// Global wireframe overlay; `None` without the `wireframe` feature
+    pub fn wireframe(&self) -> Option<WireframeInfo> {
+        #[cfg(feature = "wireframe")]
+        {
+            self.wireframe_settings
+                .as_ref()
+                .map(|settings| settings.info())
+        }
+        #[cfg(not(feature = "wireframe"))]
+        {
+            None
+        }
+    }
+
+    fn object_wireframe(&self, _entity: Entity) -> Option<WireframeInfo> {
+        #[cfg(feature = "wireframe")]
+        {
+            self.wireframes.get(_entity).ok().map(ObjectWireframe::info)
+        }
+        #[cfg(not(feature = "wireframe"))]
+        {
+            None
+        }
+    }
+}
+
+/// Plugin for object parenting and grouping
+pub struct HierarchyPlugin;
+
+impl Plugin for HierarchyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                // Children must be moved off a reference image before it despawns
+                handle_hierarchy_commands.before(reference_image::handle_reference_object_commands),
+                sync_group_selection,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Apply `SetParent`, `Group`, and `Delete` object commands
+#[allow(clippy::too_many_arguments)]
+fn handle_hierarchy_commands(
+    mut commands: Commands,
+    mut events: MessageReader<ObjectCommandEvent>,
+    mut allocator: ResMut<IdAllocator>,
+    mut selection: ResMut<SelectionState>,
+    objects: Query<(Entity, &Selectable)>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    globals: Query<&GlobalTransform>,
+    lights: Query<(), With<SceneLight>>,
+    references: Query<(), With<ReferenceImage>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    // Parent changes queued this frame, so several commands in one frame
+    // see each other when checking for cycles
+    let mut pending: HashMap<Entity, Option<Entity>> = HashMap::new();
+    let parent_of = |pending: &HashMap<Entity, Option<Entity>>, entity: Entity| {
+        pending
+            .get(&entity)
+            .copied()
+            .unwrap_or_else(|| parents.get(entity).ok().map(ChildOf::parent))
+    };
+    let global_of = |entity: Entity| globals.get(entity).copied().unwrap_or_default();
+
+    for ObjectCommandEvent(command) in events.read() {
+        match command {
+            ObjectCommand::SetParent { id, parent_id } => {
+                let Some(entity) = find_object(&objects, id, &mut outbound) else {
+                    continue;
+                };
+                let global = global_of(entity);
+                let Some(parent_id) = parent_id else {
+                    commands
+                        .entity(entity)
+                        .remove::<ChildOf>()
+                        .insert(global.compute_transform());
+                    pending.insert(entity, None);
+                    info!("Moved {} to the scene root", id);
+                    continue;
+                };
+                let Some(parent) = find_object(&objects, parent_id, &mut outbound) else {
+                    continue;
+                };
+                if would_cycle(entity, parent, |e| parent_of(&pending, e)) {
+                    outbound.send_error(
+                        "object_parent_cycle",
+                        format!("Can't parent {} to {}, which is inside it", id, parent_id),
+                    );
+                    continue;
+                }
+                commands
+                    .entity(entity)
+                    .insert((ChildOf(parent), global.reparented_to(&global_of(parent))));
+                pending.insert(entity, Some(parent));
+                info!("Parented {} to {}", id, parent_id);
+            }
+            ObjectCommand::Group { ids, name } => {
+                let found: Vec<Entity> = ids
+                    .iter()
+                    .filter_map(|id| find_object(&objects, id, &mut outbound))
+                    .collect();
+                // Members inside other members move with them
+                let members: Vec<Entity> = found
+                    .iter()
+                    .copied()
+                    .filter(|&member| {
+                        !ancestors(member, |e| parent_of(&pending, e))
+                            .any(|ancestor| found.contains(&ancestor))
+                    })
+                    .collect();
+                let Some(&first) = members.first() else {
+                    continue;
+                };
+
+                let centroid = members
+                    .iter()
+                    .map(|&member| global_of(member).translation())
+                    .sum::<Vec3>()
+                    / members.len() as f32;
+                // Siblings stay under their parent; mixed members go to the root
+                let first_parent = parent_of(&pending, first);
+                let shared_parent = first_parent.filter(|_| {
+                    members
+                        .iter()
+                        .all(|&member| parent_of(&pending, member) == first_parent)
+                });
+
+                let group_id = allocator.allocate();
+                let group_global = GlobalTransform::from_translation(centroid);
+                let transform = match shared_parent {
+                    Some(parent) => group_global.reparented_to(&global_of(parent)),
+                    None => group_global.compute_transform(),
+                };
+                let group = commands
+                    .spawn((
+                        transform,
+                        Visibility::default(),
+                        Name::new(name.clone()),
+                        ObjectGroup,
+                        Selectable {
+                            id: group_id.clone(),
+                        },
+                    ))
+                    .id();
+                if let Some(parent) = shared_parent {
+                    commands.entity(group).insert(ChildOf(parent));
+                }
+                pending.insert(group, shared_parent);
+
+                for &member in &members {
+                    commands.entity(member).insert((
+                        ChildOf(group),
+                        global_of(member).reparented_to(&group_global),
+                    ));
+                    pending.insert(member, Some(group));
+                }
+
+                info!(
+                    "Grouped {} objects as '{}' (id: {})",
+                    members.len(),
+                    name,
+                    group_id
+                );
+                outbound.send(BevyToUi::ObjectAdded {
+                    object: SceneObject {
+                        id: group_id,
+                        name: name.clone(),
+                        transform: to_transform3d(&transform),
+                        material_id: None,
+                        visible: true,
+                        locked: false,
+                        parent_id: shared_parent
+                            .and_then(|parent| objects.get(parent).ok())
+                            .map(|(_, parent)| parent.id.clone()),
+                        subdivision_levels: 0,
+                        wireframe: None,
+                        instanced: false,
+                    },
+                    duplicated_from: None,
+                });
+            }
+            ObjectCommand::Delete { ids, recursive } => {
+                // Unknown IDs may belong to objects without `Selectable`;
+                // lights are deleted with `LightCommand`
+                let targets: Vec<Entity> = objects
+                    .iter()
+                    .filter(|(entity, selectable)| {
+                        ids.contains(&selectable.id) && !lights.contains(*entity)
+                    })
+                    .map(|(entity, _)| entity)
+                    .collect();
+
+                for &target in &targets {
+                    // Nearest ancestor that survives this command
+                    let new_parent = ancestors(target, |e| parent_of(&pending, e))
+                        .find(|ancestor| !targets.contains(ancestor));
+                    let inside_target = ancestors(target, |e| parent_of(&pending, e))
+                        .any(|ancestor| targets.contains(&ancestor));
+
+                    let target_children = children
+                        .get(target)
+                        .map(|children| children.to_vec())
+                        .unwrap_or_default();
+                    if *recursive {
+                        for descendant in children.iter_descendants(target) {
+                            if let Ok((_, selectable)) = objects.get(descendant) {
+                                selection.selected_ids.retain(|id| *id != selectable.id);
+                            }
+                        }
+                    } else {
+                        for child in target_children {
+                            if targets.contains(&child) {
+                                continue;
+                            }
+                            let global = global_of(child);
+                            match new_parent {
+                                Some(parent) => {
+                                    commands.entity(child).insert((
+                                        ChildOf(parent),
+                                        global.reparented_to(&global_of(parent)),
+                                    ));
+                                }
+                                None => {
+                                    commands
+                                        .entity(child)
+                                        .remove::<ChildOf>()
+                                        .insert(global.compute_transform());
+                                }
+                            }
+                            pending.insert(child, new_parent);
+                        }
+                    }
+
+                    if let Ok((_, selectable)) = objects.get(target) {
+                        selection.selected_ids.retain(|id| *id != selectable.id);
+                    }
+                    // Despawned with its deleted ancestor; reference images
+                    // despawn themselves
+                    if !inside_target && !references.contains(target) {
+                        commands.entity(target).despawn();
+                    }
+                }
+                if !targets.is_empty() {
+                    info!("Deleted {} objects", targets.len());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Keep `GroupSelected` on exactly the descendants of selected groups
+fn sync_group_selection(
+    mut commands: Commands,
+    selected_groups: Query<Entity, (With<ObjectGroup>, With<Selected>)>,
+    children: Query<&Children>,
+    marked: Query<Entity, With<GroupSelected>>,
+) {
+    let mut outlined: HashSet<Entity> = selected_groups
+        .iter()
+        .flat_map(|group| children.iter_descendants(group))
+        .collect();
+    for entity in marked.iter() {
+        if !outlined.remove(&entity) {
+            commands.entity(entity).try_remove::<GroupSelected>();
+        }
+    }
+    for entity in outlined {
+        commands.entity(entity).try_insert(GroupSelected);
+    }
+}
+
+/// Entity of the object with `id`, reporting unknown IDs to the UI
+fn find_object(
+    objects: &Query<(Entity, &Selectable)>,
+    id: &str,
+    outbound: &mut OutboundUiMessages,
+) -> Option<Entity> {
+    let entity = objects
+        .iter()
+        .find(|(_, selectable)| selectable.id == id)
+        .map(|(entity, _)| entity);
+    if entity.is_none() {
+        outbound.send_error("object_not_found", format!("No object with ID {}", id));
+    }
+    entity
+}
+
+/// Ancestors of `entity`, nearest first
+pub(crate) fn ancestors(
+    entity: Entity,
+    parent_of: impl Fn(Entity) -> Option<Entity>,
+) -> impl Iterator<Item = Entity> {
+    std::iter::successors(parent_of(entity), move |&ancestor| parent_of(ancestor))
+}
+
+/// Whether parenting `entity` to `parent` would make it its own ancestor
+fn would_cycle(
+    entity: Entity,
+    parent: Entity,
+    parent_of: impl Fn(Entity) -> Option<Entity>,
+) -> bool {
+    parent == entity || ancestors(parent, parent_of).any(|ancestor| ancestor == entity)
+}
+
+fn to_transform3d(transform: &Transform) -> Transform3D {
+    Transform3D {
+        position: transform.translation.to_array(),
+        rotation: transform.rotation.to_array(),
+        scale: transform.scale.to_array(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parenting_to_a_descendant_is_a_cycle() {
+        let [root, child, grandchild, other] =
+            [1, 2, 3, 4].map(|index| Entity::from_raw_u32(index).unwrap());
+        let parents = HashMap::from([(child, root), (grandchild, child)]);
+        let parent_of = |entity| parents.get(&entity).copied();
+
+        assert!(would_cycle(root, grandchild, parent_of));
+        assert!(would_cycle(child, child, parent_of));
+        assert!(!would_cycle(grandchild, root, parent_of));
+        assert!(!would_cycle(other, grandchild, parent_of));
+        assert_eq!(
+            ancestors(grandchild, parent_of).collect::<Vec<_>>(),
+            vec![child, root]
+        );
+    }
+}
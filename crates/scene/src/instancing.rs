@@ -0,0 +1,370 @@
+//! Copy-on-write for shared mesh and material assets
+//!
+//! Objects can share one `Mesh` asset (duplicates do) and one material.
+//! Sculpting, mesh editing, and mesh painting write into those assets, so
+//! entering one of these modes first gives the target its own copies when
+//! another entity uses the same ones; the others keep the originals.
+//! `ObjectCommand::MakeUnique` makes the copies on request ("make instance
+//! real"). The assets an object was split from are kept in
+//! [`SplitFromInstance`], for re-linking it later.
+//!
+//! An object is reported as `SceneObject::instanced` while another entity
+//! shows or edits its mesh. The enter hooks are registered before the mode
+//! plugins' (see `ScenePlugin`), so the modes only ever see the copies.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use pentimento_ipc::{BevyToUi, ObjectCommand, SceneInfo};
+
+use crate::edit_mode::{EditModeAppExt, EditorMode};
+use crate::hierarchy::SceneObjects;
+use crate::measure::SceneMeasurements;
+use crate::scene_light::SceneLights;
+use crate::selection::Selectable;
+use crate::subdivision::{BaseMesh, base_mesh_handle};
+use crate::{ObjectCommandEvent, OutboundUiMessages};
+
+/// Modes that write into their target's mesh or material
+const COPY_ON_WRITE_MODES: [EditorMode; 3] = [
+    EditorMode::MeshPaint,
+    EditorMode::MeshEdit,
+    EditorMode::Sculpt,
+];
+
+/// Assets an object shared with others before it got its own copies
+#[derive(Component, Debug, Clone, Default)]
+pub struct SplitFromInstance {
+    /// Editable mesh it used, if that was copied
+    pub mesh: Option<AssetId<Mesh>>,
+    /// Material it used, if that was copied
+    pub material: Option<AssetId<StandardMaterial>>,
+}
+
+/// Plugin copying shared assets before they are edited
+pub struct InstancingPlugin;
+
+impl Plugin for InstancingPlugin {
+    fn build(&self, app: &mut App) {
+        for mode in COPY_ON_WRITE_MODES {
+            app.on_enter_edit_mode(mode, copy_shared_assets);
+        }
+        app.add_systems(
+            Update,
+            (handle_make_unique_commands, report_instancing_changes),
+        );
+    }
+}
+
+/// Entities using each mesh asset, as `Mesh3d` or `BaseMesh`
+pub(crate) fn mesh_users<'a>(
+    objects: impl IntoIterator<Item = (&'a Mesh3d, Option<&'a BaseMesh>)>,
+) -> HashMap<AssetId<Mesh>, usize> {
+    let mut users = HashMap::new();
+    for (mesh, base) in objects {
+        *users.entry(mesh.id()).or_default() += 1;
+        if let Some(base) = base.filter(|base| base.0.id() != mesh.id()) {
+            *users.entry(base.0.id()).or_default() += 1;
+        }
+    }
+    users
+}
+
+/// Whether an object's editable mesh is used by another entity too
+pub(crate) fn is_instanced(
+    users: &HashMap<AssetId<Mesh>, usize>,
+    mesh: &Mesh3d,
+    base: Option<&BaseMesh>,
+) -> bool {
+    users
+        .get(&base_mesh_handle(mesh, base).id())
+        .is_some_and(|&count| count > 1)
+}
+
+/// Copies the mesh and material assets an entity shares
+#[derive(SystemParam)]
+struct SharedAssets<'w, 's> {
+    commands: Commands<'w, 's>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    mesh_users: Query<'w, 's, (&'static Mesh3d, Option<&'static BaseMesh>)>,
+    material_users: Query<'w, 's, &'static MeshMaterial3d<StandardMaterial>>,
+}
+
+impl SharedAssets<'_, '_> {
+    /// Give `entity` its own copies of the editable mesh and the material it
+    /// shares with other entities. Returns whether anything was copied.
+    fn make_unique(&mut self, entity: Entity) -> bool {
+        let mut split = SplitFromInstance::default();
+
+        if let Ok((mesh, base)) = self.mesh_users.get(entity)
+            && is_instanced(&mesh_users(self.mesh_users.iter()), mesh, base)
+        {
+            let shared = base_mesh_handle(mesh, base);
+            match self.meshes.get(shared).cloned() {
+                Some(data) => {
+                    let handle = self.meshes.add(data);
+                    // A subdivided object edits its base mesh; the preview
+                    // follows it
+                    if base.is_some() {
+                        self.commands.entity(entity).insert(BaseMesh(handle));
+                    } else {
+                        self.commands.entity(entity).insert(Mesh3d(handle));
+                    }
+                    split.mesh = Some(shared.id());
+                }
+                None => warn!("Shared mesh of {:?} is not loaded; not copied", entity),
+            }
+        }
+
+        if let Ok(material) = self.material_users.get(entity) {
+            let users = self
+                .material_users
+                .iter()
+                .filter(|other| other.id() == material.id())
+                .count();
+            if users > 1
+                && let Some(data) = self.materials.get(&material.0).cloned()
+            {
+                let handle = self.materials.add(data);
+                self.commands.entity(entity).insert(MeshMaterial3d(handle));
+                split.material = Some(material.id());
+            }
+        }
+
+        let copied = split.mesh.is_some() || split.material.is_some();
+        if copied {
+            debug!("Copied shared assets of {:?}: {:?}", entity, split);
+            self.commands.entity(entity).insert(split);
+        }
+        copied
+    }
+}
+
+/// Enter hook of the modes that edit their target's assets
+fn copy_shared_assets(In(target): In<Option<Entity>>, mut shared: SharedAssets) {
+    if let Some(entity) = target
+        && shared.make_unique(entity)
+    {
+        info!("Edited object {:?} no longer shares its assets", entity);
+    }
+}
+
+/// Apply `ObjectCommand::MakeUnique`
+fn handle_make_unique_commands(
+    mut events: MessageReader<ObjectCommandEvent>,
+    objects: Query<(Entity, &Selectable)>,
+    mut shared: SharedAssets,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for ObjectCommandEvent(command) in events.read() {
+        let ObjectCommand::MakeUnique { id } = command else {
+            continue;
+        };
+        let Some(entity) = objects
+            .iter()
+            .find(|(_, selectable)| selectable.id == *id)
+            .map(|(entity, _)| entity)
+        else {
+            warn!("MakeUnique: no object with ID {}", id);
+            outbound.send(BevyToUi::Error {
+                code: "object_not_found".to_string(),
+                message: format!("No object with ID {}", id),
+            });
+            continue;
+        };
+        if shared.make_unique(entity) {
+            info!("Made object {} unique", id);
+        } else {
+            debug!("Object {} shares no assets", id);
+        }
+    }
+}
+
+/// Report objects becoming or ceasing to be instances so the outliner can
+/// show them
+fn report_instancing_changes(
+    changed: Query<(), Or<(Changed<Mesh3d>, Changed<BaseMesh>)>>,
+    mut removed: RemovedComponents<Mesh3d>,
+    scene_objects: SceneObjects,
+    scene_lights: SceneLights,
+    scene_measurements: SceneMeasurements,
+    mut reported: Local<HashSet<String>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let removed = removed.read().count() > 0;
+    if changed.is_empty() && !removed {
+        return;
+    }
+
+    let objects = scene_objects.infos();
+    let instanced: HashSet<String> = objects
+        .iter()
+        .filter(|object| object.instanced)
+        .map(|object| object.id.clone())
+        .collect();
+    if instanced == *reported {
+        return;
+    }
+    *reported = instanced;
+
+    outbound.send(BevyToUi::SceneUpdated(SceneInfo {
+        objects,
+        lights: scene_lights.infos(),
+        wireframe: scene_objects.wireframe(),
+        isolated: scene_objects.isolated(),
+        annotations: scene_measurements.infos(),
+        ..default()
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::mesh::VertexAttributeValues;
+
+    use super::*;
+    use crate::duplicate::DuplicatePlugin;
+    use crate::edit_mode::{EditModePlugin, request_mode_change};
+    use crate::object_id::IdAllocator;
+
+    /// Sculpt stand-in: pushes every vertex of its target's mesh up
+    fn push_vertices_up(
+        In(target): In<Option<Entity>>,
+        objects: Query<(&Mesh3d, Option<&BaseMesh>)>,
+        mut meshes: ResMut<Assets<Mesh>>,
+    ) {
+        let Some((mesh, base)) = target.and_then(|entity| objects.get(entity).ok()) else {
+            return;
+        };
+        let mesh = meshes.get_mut(base_mesh_handle(mesh, base)).unwrap();
+        if let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+        {
+            for position in positions {
+                position[1] += 0.25;
+            }
+        }
+    }
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.register_required_components::<Mesh3d, Visibility>();
+        app.init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .init_resource::<OutboundUiMessages>()
+            .init_resource::<IdAllocator>()
+            .add_message::<ObjectCommandEvent>()
+            .add_plugins((EditModePlugin, InstancingPlugin, DuplicatePlugin));
+        // Registered after the copy-on-write hook, like the real modes
+        app.on_enter_edit_mode(EditorMode::Sculpt, push_vertices_up);
+        app
+    }
+
+    fn spawn_cube(app: &mut App) -> Entity {
+        let world = app.world_mut();
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Cuboid::new(1.0, 1.0, 1.0));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::default());
+        world
+            .spawn((
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::default(),
+                Name::new("Cube"),
+                Selectable { id: "cube".into() },
+            ))
+            .id()
+    }
+
+    fn command(app: &mut App, command: ObjectCommand) {
+        app.world_mut().write_message(ObjectCommandEvent(command));
+        app.update();
+    }
+
+    /// The copy `Duplicate` made of `source`
+    fn copy_of(app: &mut App, source: Entity) -> Entity {
+        app.world_mut()
+            .query_filtered::<Entity, With<Selectable>>()
+            .iter(app.world())
+            .find(|&entity| entity != source)
+            .unwrap()
+    }
+
+    fn positions(app: &App, entity: Entity) -> Vec<[f32; 3]> {
+        let mesh = app.world().get::<Mesh3d>(entity).unwrap();
+        let mesh = app.world().resource::<Assets<Mesh>>().get(&mesh.0).unwrap();
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => positions.clone(),
+            _ => panic!("cube has no positions"),
+        }
+    }
+
+    fn instanced(app: &mut App) -> Vec<bool> {
+        app.world_mut()
+            .run_system_once(|objects: SceneObjects| {
+                objects
+                    .infos()
+                    .into_iter()
+                    .map(|object| object.instanced)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sculpting_a_duplicate_leaves_the_original_alone() {
+        let mut app = test_app();
+        let cube = spawn_cube(&mut app);
+        command(
+            &mut app,
+            ObjectCommand::Duplicate {
+                ids: vec!["cube".into()],
+            },
+        );
+        app.update();
+        let copy = copy_of(&mut app, cube);
+        let original = positions(&app, cube);
+        assert_eq!(instanced(&mut app), vec![true, true]);
+        let mesh_count = app.world().resource::<Assets<Mesh>>().len();
+
+        request_mode_change(app.world_mut(), EditorMode::Sculpt, Some(copy)).unwrap();
+
+        assert_eq!(positions(&app, cube), original);
+        assert_ne!(positions(&app, copy), original);
+        assert_eq!(app.world().resource::<Assets<Mesh>>().len(), mesh_count + 1);
+        let split = app.world().get::<SplitFromInstance>(copy).unwrap();
+        assert_eq!(
+            split.mesh,
+            Some(app.world().get::<Mesh3d>(cube).unwrap().id())
+        );
+        assert_eq!(instanced(&mut app), vec![false, false]);
+    }
+
+    #[test]
+    fn test_make_unique_copies_only_shared_assets() {
+        let mut app = test_app();
+        let cube = spawn_cube(&mut app);
+        let mesh_count = app.world().resource::<Assets<Mesh>>().len();
+
+        // Nothing shared yet
+        command(&mut app, ObjectCommand::MakeUnique { id: "cube".into() });
+        assert!(app.world().get::<SplitFromInstance>(cube).is_none());
+
+        let mesh = app.world().get::<Mesh3d>(cube).unwrap().clone();
+        let other = app.world_mut().spawn(mesh).id();
+        command(&mut app, ObjectCommand::MakeUnique { id: "cube".into() });
+        assert_ne!(
+            app.world().get::<Mesh3d>(cube).unwrap().id(),
+            app.world().get::<Mesh3d>(other).unwrap().id()
+        );
+        assert_eq!(app.world().resource::<Assets<Mesh>>().len(), mesh_count + 1);
+        // The material wasn't shared
+        let split = app.world().get::<SplitFromInstance>(cube).unwrap();
+        assert!(split.material.is_none());
+    }
+}
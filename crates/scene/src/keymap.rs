@@ -0,0 +1,82 @@
+//! Hotkey rebinding
+//!
+//! Applies `SetKeybinding` requests to the [`Keymap`] resource and reports
+//! the full keymap back to the UI. Loading and saving the user's overrides
+//! is left to the app, which owns the settings file.
+
+use bevy::ecs::message::Message;
+use bevy::prelude::*;
+use pentimento_config::{KeyChord, Keymap, KeymapError};
+use pentimento_ipc::{BevyToUi, KeyBinding};
+
+use crate::OutboundUiMessages;
+
+/// Message for keymap changes
+#[derive(Message, Debug, Clone)]
+pub enum KeymapEvent {
+    /// Bind an action to a chord like "Shift+A"
+    SetBinding { action: String, chord: String },
+}
+
+pub struct KeymapPlugin;
+
+impl Plugin for KeymapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Keymap>()
+            .add_message::<KeymapEvent>()
+            .add_systems(Startup, send_initial_keymap)
+            .add_systems(Update, handle_keymap_events);
+    }
+}
+
+/// Tell the UI which bindings are live, including any loaded overrides
+fn send_initial_keymap(keymap: Res<Keymap>, mut outbound: ResMut<OutboundUiMessages>) {
+    outbound.send(keymap_message(&keymap));
+}
+
+/// Apply rebinding requests, rejecting invalid chords and conflicts
+fn handle_keymap_events(
+    mut events: MessageReader<KeymapEvent>,
+    mut keymap: ResMut<Keymap>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for event in events.read() {
+        match event {
+            KeymapEvent::SetBinding { action, chord } => {
+                let result = chord
+                    .parse::<KeyChord>()
+                    .and_then(|chord| keymap.bind(action, chord));
+                match result {
+                    Ok(()) => info!("Bound {} to {}", action, chord),
+                    Err(e) => {
+                        warn!("Rejected keybinding: {}", e);
+                        let code = match e {
+                            KeymapError::Conflict { .. } => "keymap_conflict",
+                            _ => "keymap_invalid",
+                        };
+                        outbound.send(BevyToUi::Error {
+                            code: code.to_string(),
+                            message: e.to_string(),
+                        });
+                    }
+                }
+                // Always answer with the current bindings so the UI can
+                // revert an edit that was rejected
+                outbound.send(keymap_message(&keymap));
+            }
+        }
+    }
+}
+
+/// Build the UI message listing every binding
+pub fn keymap_message(keymap: &Keymap) -> BevyToUi {
+    BevyToUi::KeymapChanged {
+        bindings: keymap
+            .bindings()
+            .map(|(action, chord)| KeyBinding {
+                action: action.to_string(),
+                chord: chord.to_string(),
+            })
+            .collect(),
+    }
+}
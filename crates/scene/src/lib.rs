@@ -4,8 +4,9 @@
 //! the native Bevy app and the WASM Tauri build.
 
 use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::ecs::message::Message;
 use bevy::prelude::*;
-use pentimento_ipc::BevyToUi;
+use pentimento_ipc::{BevyToUi, ObjectCommand};
 
 #[cfg(feature = "atmosphere")]
 use bevy::camera::Exposure;
@@ -16,88 +17,176 @@ use bevy::pbr::{Atmosphere, AtmosphereSettings};
 
 mod add_object;
 mod ambient_occlusion;
+mod brush_navigation;
+mod brush_tip;
 mod camera;
+mod canvas_file;
 mod canvas_plane;
-mod depth_view;
+mod canvas_resize;
+mod canvas_tool;
+#[cfg(feature = "selection")]
+mod duplicate;
 mod edit_mode;
 mod gizmo;
 #[cfg(feature = "selection")]
 mod gizmo_raycast;
+mod grid;
+#[cfg(feature = "selection")]
+mod hierarchy;
+#[cfg(feature = "selection")]
+mod instancing;
+mod keymap;
 mod lighting;
+mod measure;
 #[cfg(feature = "mesh_editing")]
 mod mesh_edit_highlight;
 #[cfg(feature = "mesh_editing")]
 mod mesh_edit_mode;
 #[cfg(feature = "mesh_editing")]
+mod mesh_edit_ops;
+#[cfg(feature = "mesh_editing")]
 mod mesh_edit_selection;
 #[cfg(feature = "mesh_painting")]
 mod mesh_paint_mode;
 #[cfg(feature = "mesh_painting")]
 mod mesh_painting_system;
+mod node_graph;
 #[cfg(feature = "mesh_painting")]
 mod normal_indicator;
+mod object_id;
+#[cfg(feature = "selection")]
+mod object_visibility;
 #[cfg(feature = "selection")]
 mod outline;
+mod paint_history;
 mod paint_mode;
 mod painting_system;
 pub mod pixel_coverage;
+mod pixel_selection;
 mod projection_mode;
 mod projection_painting;
+mod reference_image;
 mod render_camera;
+mod scene_bvh;
+mod scene_light;
 #[cfg(feature = "sculpting")]
 mod sculpt_mode;
 #[cfg(feature = "selection")]
 mod selection;
+#[cfg(feature = "selection")]
+mod subdivision;
+mod texture_registry;
+mod turntable;
+mod view_mode;
 #[cfg(feature = "wireframe")]
 mod wireframe;
 
-pub use add_object::{AddObjectEvent, AddObjectPlugin};
+pub use add_object::{AddMenuAnchor, AddObjectEvent, AddObjectPlugin};
 pub use ambient_occlusion::{AmbientOcclusionPlugin, SceneAmbientOcclusion};
-pub use camera::{CameraControllerPlugin, MainCamera, OrbitCamera};
+pub use brush_navigation::{BrushNavigation, BrushNavigationPlugin};
+pub use brush_tip::BrushTipEvent;
+pub use camera::{
+    CameraCommandEvent, CameraControllerPlugin, MainCamera, OrbitCamera, OrbitSettings,
+};
+pub use canvas_file::{CanvasFileEvent, MAX_IMAGE_DIMENSION};
 pub use canvas_plane::{
-    ActiveCanvasPlane, CanvasMaterialUpdated, CanvasPlane, CanvasPlaneEvent,
+    ActiveCanvasPlane, CanvasMaterialUpdated, CanvasPlacement, CanvasPlane, CanvasPlaneEvent,
     CanvasPlaneIdGenerator, CanvasPlanePlugin,
 };
-pub use depth_view::{
-    DepthViewBounds, DepthViewCamera, DepthViewLabel, DepthViewPlugin, DepthViewSettings,
+pub use canvas_resize::CanvasResizeEvent;
+pub use canvas_tool::{CanvasToolEvent, CanvasToolPlugin, CanvasToolState};
+#[cfg(feature = "selection")]
+pub use duplicate::DuplicatePlugin;
+pub use edit_mode::{
+    EditModeAppExt, EditModeEvent, EditModeGuard, EditModeHooks, EditModePlugin, EditModeRequests,
+    EditModeState, EditorMode, request_mode_change,
 };
-pub use edit_mode::{EditModeEvent, EditModePlugin, EditModeState};
-pub use gizmo::{GizmoPlugin, GizmoState};
+#[cfg(feature = "selection")]
+pub use gizmo::Cursor3D;
+pub use gizmo::{GizmoCommandEvent, GizmoNudgeEvent, GizmoPlugin, GizmoState, PointerOverUi};
 #[cfg(feature = "selection")]
 pub use gizmo_raycast::{GizmoGeometry, GizmoHandle};
+pub use grid::{GridCamera, GridPlane, GridPlugin, SceneGrid};
+#[cfg(feature = "selection")]
+pub use hierarchy::{GroupSelected, HierarchyPlugin, ObjectGroup, SceneObjects};
+#[cfg(feature = "selection")]
+pub use instancing::{InstancingPlugin, SplitFromInstance};
+pub use keymap::{KeymapEvent, KeymapPlugin, keymap_message};
 #[cfg(feature = "atmosphere")]
 pub use lighting::AtmosphereState;
 pub use lighting::{LightingPlugin, SceneLighting, SunLight};
+pub use measure::{
+    MeasureEvent, MeasureGizmos, MeasurePlugin, MeasureState, Measurement, SceneMeasurements,
+};
 #[cfg(feature = "mesh_editing")]
 pub use mesh_edit_highlight::MeshEditHighlightPlugin;
 #[cfg(feature = "mesh_editing")]
 pub use mesh_edit_mode::{EditableMesh, MeshEditEvent, MeshEditModePlugin, MeshEditState};
 #[cfg(feature = "mesh_editing")]
+pub use mesh_edit_ops::{MeshEditHistory, MeshEditOpsPlugin};
+#[cfg(feature = "mesh_editing")]
 pub use mesh_edit_selection::MeshEditSelectionPlugin;
 #[cfg(feature = "mesh_painting")]
 pub use mesh_paint_mode::{
     MeshIdGenerator, MeshPaintEvent, MeshPaintModePlugin, MeshPaintState, PaintableMesh,
 };
 #[cfg(feature = "mesh_painting")]
-pub use mesh_painting_system::{MeshPaintTexture, MeshPaintingResource, MeshPaintingSystemPlugin};
+pub use mesh_painting_system::{
+    ChannelDefaults, MeshPaintTexture, MeshPaintingResource, MeshPaintingSystemPlugin,
+};
+pub use node_graph::{
+    DEFAULT_BAKE_RESOLUTION, NodeGraphError, NodeGraphEvent, NodeGraphPlugin, NodeGraphs,
+};
 #[cfg(feature = "mesh_painting")]
 pub use normal_indicator::{NormalIndicatorPlugin, NormalIndicatorState};
+pub use object_id::{IdAllocator, ObjectIdPlugin, RestoreObjectsEvent};
 #[cfg(feature = "selection")]
-pub use outline::{OutlineCamera, OutlinePlugin};
+pub use object_visibility::{IsolationState, ObjectVisibilityPlugin};
+#[cfg(feature = "selection")]
+pub use outline::{OutlineCamera, OutlinePlugin, OutlineSettings};
+pub use paint_history::{DEFAULT_HISTORY_BUDGET_BYTES, HistoryBudget, PaintHistoryPlugin};
 pub use paint_mode::{PaintEvent, PaintMode, PaintModePlugin, StrokeIdGenerator, StrokeState};
-pub use painting_system::{CanvasTexture, PaintingResource, PaintingSystemPlugin};
-pub use pixel_coverage::{PixelCoveragePlugin, PixelCoverageState, estimate_pixel_coverage_cpu};
+pub use painting_system::{
+    CanvasTexture, PaintingResource, PaintingSystemPlugin, TextureUploadStats,
+};
+pub use pixel_coverage::{
+    PixelCoveragePlugin, PixelCoverageState, TexelDensity, estimate_pixel_coverage_cpu,
+    estimate_texel_density_cpu,
+};
+pub use pixel_selection::{PixelSelectionEvent, PixelSelectionPlugin, PixelSelectionTool};
 pub use projection_mode::{
     ProjectionEvent, ProjectionMode, ProjectionModePlugin, ProjectionTarget,
 };
-pub use projection_painting::{MeshRaycastCache, ProjectionPaintingPlugin, ProjectionTargets};
+pub use projection_painting::{
+    MeshRaycastCache, ProjectionPaintingPlugin, ProjectionStats, ProjectionTargets,
+};
+pub use reference_image::{
+    MAX_REFERENCE_TEXTURE_DIMENSION, ReferenceImage, ReferenceImageEvent, ReferenceImagePlugin,
+};
 pub use render_camera::{ActiveRenderCamera, RenderCamera, RenderCameraPlugin};
+pub use scene_bvh::{RaycastOptions, SceneBvh, SceneBvhPlugin, SceneHit};
+pub use scene_light::{LightCommandEvent, LightGizmos, SUN_LIGHT_ID, SceneLight, SceneLights};
 #[cfg(feature = "sculpting")]
 pub use sculpt_mode::{SculptEvent, SculptModePlugin, SculptState};
 #[cfg(feature = "selection")]
-pub use selection::{Selectable, Selected, SelectionPlugin, SelectionState};
+pub use selection::{
+    IgnoreSelectionClicks, Locked, Selectable, Selected, SelectionPlugin, SelectionState,
+};
+#[cfg(feature = "selection")]
+pub use subdivision::{
+    BaseMesh, BaseMeshChanged, DEFAULT_SUBDIVISION_FACE_BUDGET, MAX_SUBDIVISION_LEVELS,
+    Subdivision, SubdivisionPlugin, SubdivisionSettings,
+};
+pub use texture_registry::{
+    DEFAULT_TEXTURE_BUDGET_BYTES, TEXTURE_SLOTS, TextureRegistry, TextureRegistryEvent,
+    TextureRegistryPlugin,
+};
+pub use turntable::{TurntableEvent, TurntablePlugin, TurntableRequest};
+pub use view_mode::{
+    DepthViewBounds, ViewModeCamera, ViewModeLabel, ViewModePlugin, ViewModeSettings,
+};
 #[cfg(feature = "wireframe")]
-pub use wireframe::{WireframeOverlayPlugin, WireframeSettings};
+pub use wireframe::{ObjectWireframe, WireframeEvent, WireframeOverlayPlugin, WireframeSettings};
 
 /// Resource for queuing messages to send to the UI
 /// The rendering layer (app crate) should drain this and send to the webview
@@ -112,32 +201,73 @@ impl OutboundUiMessages {
         self.messages.push(msg);
     }
 
+    /// Log a warning and report it to the UI as `BevyToUi::Error`
+    pub fn send_error(&mut self, code: &str, message: impl Into<String>) {
+        let message = message.into();
+        warn!("{}", message);
+        self.send(BevyToUi::Error {
+            code: code.to_string(),
+            message,
+        });
+    }
+
     /// Take all queued messages, leaving the queue empty
+    ///
+    /// Messages a newer queued message replaces (`pentimento_ipc::Coalesce`)
+    /// are dropped, so a drag sends one `SceneUpdated` per frame, not dozens.
     pub fn drain(&mut self) -> Vec<BevyToUi> {
-        std::mem::take(&mut self.messages)
+        let queued = self.messages.len();
+        let messages = pentimento_ipc::coalesce(std::mem::take(&mut self.messages));
+        if messages.len() < queued {
+            trace!("Coalesced {} UI messages into {}", queued, messages.len());
+        }
+        messages
     }
 }
 
+/// Object command from the UI (select, delete, visibility, ...)
+/// Each plugin that owns a kind of object reads the commands that apply to it
+#[derive(Message, Debug, Clone)]
+pub struct ObjectCommandEvent(pub ObjectCommand);
+
 pub struct ScenePlugin;
 
 impl Plugin for ScenePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<OutboundUiMessages>();
+        app.add_message::<ObjectCommandEvent>();
 
         app.add_plugins(CameraControllerPlugin);
+        app.add_plugins(BrushNavigationPlugin);
         app.add_plugins(LightingPlugin);
         app.add_plugins(AmbientOcclusionPlugin);
-        app.add_plugins(DepthViewPlugin);
+        app.add_plugins(ViewModePlugin);
+        app.add_plugins(GridPlugin);
+        app.add_plugins(ObjectIdPlugin);
         app.add_plugins(AddObjectPlugin);
         app.add_plugins(EditModePlugin);
+        // Before the mode plugins, so modes that edit a mesh enter on a copy
+        // of a shared one
+        #[cfg(feature = "selection")]
+        app.add_plugins(InstancingPlugin);
         app.add_plugins(GizmoPlugin);
         app.add_plugins(CanvasPlanePlugin);
         app.add_plugins(PaintModePlugin);
         app.add_plugins(PaintingSystemPlugin);
+        app.add_plugins(PaintHistoryPlugin);
+        app.add_plugins(PixelSelectionPlugin);
+        app.add_plugins(CanvasToolPlugin);
         app.add_plugins(ProjectionModePlugin);
         app.add_plugins(ProjectionPaintingPlugin);
         app.add_plugins(RenderCameraPlugin);
         app.add_plugins(PixelCoveragePlugin);
+        app.add_plugins(KeymapPlugin);
+        app.add_plugins(ReferenceImagePlugin);
+        app.add_plugins(MeasurePlugin);
+        app.add_plugins(TextureRegistryPlugin);
+        app.add_plugins(NodeGraphPlugin);
+        app.add_plugins(TurntablePlugin);
+        app.add_plugins(SceneBvhPlugin);
 
         app.add_systems(Startup, setup_scene);
 
@@ -149,6 +279,7 @@ impl Plugin for ScenePlugin {
             app.add_plugins(MeshEditModePlugin);
             app.add_plugins(MeshEditSelectionPlugin);
             app.add_plugins(MeshEditHighlightPlugin);
+            app.add_plugins(MeshEditOpsPlugin);
         }
 
         #[cfg(feature = "mesh_painting")]
@@ -166,6 +297,10 @@ impl Plugin for ScenePlugin {
         #[cfg(feature = "selection")]
         {
             app.add_plugins(SelectionPlugin);
+            app.add_plugins(HierarchyPlugin);
+            app.add_plugins(DuplicatePlugin);
+            app.add_plugins(ObjectVisibilityPlugin);
+            app.add_plugins(SubdivisionPlugin);
             app.add_plugins(OutlinePlugin);
         }
 
@@ -179,6 +314,8 @@ fn setup_scene(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    #[cfg(feature = "selection")] mut allocator: ResMut<IdAllocator>,
+    #[cfg(feature = "mesh_painting")] mut mesh_ids: ResMut<MeshIdGenerator>,
 ) {
     // Camera with WebGL2-compatible tonemapping and orbit controls
     // TonyMcMapFace requires tonemapping_luts which needs zstd (not available in WASM)
@@ -189,7 +326,8 @@ fn setup_scene(
         Transform::from_translation(camera_position).looking_at(orbit_camera.target, Vec3::Y),
         Tonemapping::Reinhard,
         MainCamera,
-        DepthViewCamera,
+        ViewModeCamera,
+        GridCamera,
         orbit_camera,
         #[cfg(feature = "selection")]
         OutlineCamera,
@@ -224,11 +362,11 @@ fn setup_scene(
         .id();
     #[cfg(feature = "selection")]
     commands.entity(cube).insert(Selectable {
-        id: "cube".to_string(),
+        id: allocator.allocate(),
     });
     #[cfg(feature = "mesh_painting")]
     commands.entity(cube).insert(PaintableMesh {
-        mesh_id: 0,
+        mesh_id: mesh_ids.next(),
         storage_mode: painting::types::MeshStorageMode::Ptex {
             face_resolution: 32,
         },
@@ -251,11 +389,11 @@ fn setup_scene(
         .id();
     #[cfg(feature = "selection")]
     commands.entity(sphere).insert(Selectable {
-        id: "sphere".to_string(),
+        id: allocator.allocate(),
     });
     #[cfg(feature = "mesh_painting")]
     commands.entity(sphere).insert(PaintableMesh {
-        mesh_id: 1,
+        mesh_id: mesh_ids.next(),
         storage_mode: painting::types::MeshStorageMode::UvAtlas {
             resolution: (512, 512),
         },
@@ -278,11 +416,11 @@ fn setup_scene(
         .id();
     #[cfg(feature = "selection")]
     commands.entity(torus).insert(Selectable {
-        id: "torus".to_string(),
+        id: allocator.allocate(),
     });
     #[cfg(feature = "mesh_painting")]
     commands.entity(torus).insert(PaintableMesh {
-        mesh_id: 2,
+        mesh_id: mesh_ids.next(),
         storage_mode: painting::types::MeshStorageMode::Ptex {
             face_resolution: 32,
         },
@@ -1,113 +1,143 @@
 //! Configurable sun/sky lighting system
 //!
-//! Supports time-of-day simulation where sun position is calculated
-//! based on time (0-24 hours). Cloudiness affects ambient light color
-//! and sun intensity.
+//! Supports time-of-day simulation where the sun's position is calculated
+//! from the time (0-24 hours) and latitude, with warm light and a dimmed sun
+//! through dawn and dusk. Cloudiness affects ambient light color and sun
+//! intensity. A nonzero `animate_speed` plays the day cycle here rather than
+//! in the UI, which hears the time twice a second.
 //!
 //! With the `atmosphere` feature enabled, uses Bevy's built-in atmospheric
-//! scattering for realistic sky rendering.
+//! scattering for realistic sky rendering. The sky and its environment light
+//! follow the sun light every frame; clouds and pollution thicken the
+//! atmosphere's haze, whose scattering medium is rebuilt at most every
+//! `MEDIUM_REBUILD_INTERVAL` since each rebuild recomputes its lookup tables.
 
 #[cfg(not(feature = "atmosphere"))]
 use bevy::light::GlobalAmbientLight;
 use bevy::prelude::*;
-use pentimento_ipc::LightingSettings;
+use pentimento_ipc::{BevyToUi, LightingSettings};
+
+use crate::OutboundUiMessages;
+use crate::scene_light::{self, SUN_LIGHT_ID, SceneLight};
 
 #[cfg(feature = "atmosphere")]
-use bevy::pbr::ScatteringMedium;
+use bevy::pbr::{PhaseFunction, ScatteringMedium};
 #[cfg(feature = "atmosphere")]
 use bevy::prelude::light_consts::lux;
 
-/// Calculate sun direction from time of day and azimuth angle
-///
-/// Time is in hours (0.0-24.0). Sunrise is at 6:00, sunset at 18:00.
-/// Azimuth angle rotates the sun's path around Y-axis (0=east, 90=south, etc.)
-/// Returns a normalized direction vector pointing toward the sun.
+/// Sun elevation (degrees) below which it gives no direct light
+const NIGHT_ELEVATION: f32 = -4.0;
+/// Sun elevation (degrees) from which it gives full direct light
+const FULL_DAYLIGHT_ELEVATION: f32 = 8.0;
+/// Sun elevation (degrees) above which its light is no longer warmed
 #[cfg(not(feature = "atmosphere"))]
-fn calculate_sun_direction_from_time(time_of_day: f32, azimuth_angle: f32) -> Vec3 {
-    // Normalize time to 0-1 range within daylight hours (6:00-18:00)
-    // Before 6:00 or after 18:00, sun is below horizon
-    let clamped_time = time_of_day.clamp(6.0, 18.0);
-    let normalized_time = (clamped_time - 6.0) / 12.0; // 0.0 at sunrise, 1.0 at sunset
-
-    // Sun arc: rises in east (negative X), sets in west (positive X)
-    // Y is height, peaks at noon (normalized_time = 0.5)
-    let sun_angle = normalized_time * std::f32::consts::PI;
-
-    // Base direction without azimuth rotation
-    let base_dir = Vec3::new(
-        -sun_angle.cos(),          // East to west
-        sun_angle.sin().max(0.05), // Height (keep slightly above horizon for lighting)
-        -0.3,                      // Slight southern offset (typical for northern hemisphere)
-    );
+const WARM_SUN_ELEVATION: f32 = 25.0;
 
-    // Rotate by azimuth angle around Y axis
-    let azimuth_rad = azimuth_angle.to_radians();
-    let rotation = Quat::from_rotation_y(azimuth_rad);
+/// How often the UI hears the time of day while the day cycle plays
+const DAY_CYCLE_REPORT_INTERVAL: f32 = 0.5;
+
+/// Shortest time between rebuilds of the atmosphere's scattering medium
+#[cfg(feature = "atmosphere")]
+const MEDIUM_REBUILD_INTERVAL: f32 = 0.25;
+
+/// Calculate the direction toward the sun on an equinox day
+///
+/// Time is in hours (0.0-24.0) and latitude in degrees. The sun rises due
+/// east at 6:00, sets due west at 18:00, and at noon stands
+/// `90 - |latitude|` degrees up, to the south in the northern hemisphere.
+/// East is -X and south is -Z before the azimuth angle rotates the path
+/// around the Y axis. Returns a normalized direction vector pointing toward
+/// the sun, below the horizon at night.
+fn calculate_sun_direction(time_of_day: f32, latitude: f32, azimuth_angle: f32) -> Vec3 {
+    // 15 degrees per hour, zero at noon
+    let hour_angle = ((time_of_day - 12.0) * 15.0).to_radians();
+    let latitude = latitude.clamp(-90.0, 90.0).to_radians();
+
+    let up = latitude.cos() * hour_angle.cos();
+    let east = -hour_angle.sin();
+    let north = -latitude.sin() * hour_angle.cos();
+    let base_dir = Vec3::new(-east, up, north);
 
+    // Rotate by azimuth angle around Y axis
+    let rotation = Quat::from_rotation_y(azimuth_angle.to_radians());
     (rotation * base_dir).normalize()
 }
 
-/// Calculate sun color based on time of day (warmer at sunrise/sunset)
-#[cfg(not(feature = "atmosphere"))]
-fn calculate_sun_color_from_time(time_of_day: f32) -> [f32; 3] {
-    let clamped_time = time_of_day.clamp(6.0, 18.0);
-    let normalized_time = (clamped_time - 6.0) / 12.0;
-    let sun_height = (normalized_time * std::f32::consts::PI).sin();
-
-    // Low sun = warm orange, high sun = white
-    if sun_height < 0.3 {
-        // Sunrise/sunset: warm orange
-        let warmth = 1.0 - (sun_height / 0.3);
-        [
-            1.0,
-            0.98 - warmth * 0.3,  // More orange
-            0.95 - warmth * 0.45, // Less blue
-        ]
-    } else {
-        // Midday: slightly warm white
-        [1.0, 0.98, 0.95]
-    }
+/// Elevation of a sun direction above the horizon, in degrees
+fn sun_elevation(direction: Vec3) -> f32 {
+    direction.y.clamp(-1.0, 1.0).asin().to_degrees()
 }
 
-/// Calculate ambient color based on time and cloudiness
-#[cfg(not(feature = "atmosphere"))]
-fn calculate_ambient_color(time_of_day: f32, cloudiness: f32) -> [f32; 3] {
-    let clamped_time = time_of_day.clamp(6.0, 18.0);
-    let normalized_time = (clamped_time - 6.0) / 12.0;
-    let sun_height = (normalized_time * std::f32::consts::PI).sin();
-
-    // Base ambient color varies with time of day
-    let base_color = if sun_height < 0.3 {
-        // Sunrise/sunset: warm amber ambient
-        [0.8, 0.6, 0.4]
-    } else {
-        // Midday: sky blue ambient
-        [0.6, 0.7, 1.0]
-    };
+/// Share of direct sunlight at an elevation: none at night, fading in
+/// through dawn and out through dusk
+fn daylight_factor(elevation: f32) -> f32 {
+    smoothstep(NIGHT_ELEVATION, FULL_DAYLIGHT_ELEVATION, elevation)
+}
 
-    // Cloudiness shifts toward gray
-    let gray = [0.7, 0.7, 0.7];
+/// Hermite interpolation from 0 at `edge0` to 1 at `edge1`
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Blend two RGB colors
+#[cfg(not(feature = "atmosphere"))]
+fn mix_rgb(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
     [
-        base_color[0] + (gray[0] - base_color[0]) * cloudiness,
-        base_color[1] + (gray[1] - base_color[1]) * cloudiness,
-        base_color[2] + (gray[2] - base_color[2]) * cloudiness,
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
     ]
 }
 
-/// Calculate sun intensity based on time and cloudiness
+/// Calculate sun color from its elevation (warmer at sunrise/sunset) and
+/// cloudiness (overcast light is neutral)
 #[cfg(not(feature = "atmosphere"))]
-fn calculate_sun_intensity(time_of_day: f32, cloudiness: f32, base_intensity: f32) -> f32 {
-    let clamped_time = time_of_day.clamp(6.0, 18.0);
-    let normalized_time = (clamped_time - 6.0) / 12.0;
-    let sun_height = (normalized_time * std::f32::consts::PI).sin();
+fn calculate_sun_color(elevation: f32, cloudiness: f32) -> [f32; 3] {
+    let warmth = 1.0 - smoothstep(0.0, WARM_SUN_ELEVATION, elevation);
+    // Midday: slightly warm white; sunrise/sunset: warm orange
+    let color = mix_rgb([1.0, 0.98, 0.95], [1.0, 0.68, 0.5], warmth);
+    mix_rgb(color, [0.9, 0.92, 0.95], cloudiness)
+}
+
+/// Calculate ambient color based on sun elevation and cloudiness
+#[cfg(not(feature = "atmosphere"))]
+fn calculate_ambient_color(elevation: f32, cloudiness: f32) -> [f32; 3] {
+    // Midday: sky blue ambient; sunrise/sunset: warm amber ambient
+    let warmth = 1.0 - smoothstep(0.0, WARM_SUN_ELEVATION, elevation);
+    let base_color = mix_rgb([0.6, 0.7, 1.0], [0.8, 0.6, 0.4], warmth);
 
-    // Intensity based on sun height
-    let height_factor = sun_height.max(0.1);
+    // Cloudiness shifts toward gray
+    mix_rgb(base_color, [0.7, 0.7, 0.7], cloudiness)
+}
 
+/// Calculate sun intensity based on elevation and cloudiness
+#[cfg(not(feature = "atmosphere"))]
+fn calculate_sun_intensity(elevation: f32, cloudiness: f32, base_intensity: f32) -> f32 {
     // Cloudiness reduces intensity (clouds block light)
     let cloud_factor = 1.0 - (cloudiness * 0.8); // Max 80% reduction
 
-    base_intensity * height_factor * cloud_factor
+    base_intensity * daylight_factor(elevation) * cloud_factor
+}
+
+/// Aerosol density multiplier for the atmosphere: clouds and pollution
+/// thicken the haze, greying and dimming the sky
+#[cfg(feature = "atmosphere")]
+fn atmosphere_haze(cloudiness: f32, pollution: f32) -> f32 {
+    1.0 + cloudiness * 8.0 + pollution * 4.0
+}
+
+/// Earthlike scattering medium with its aerosol (Mie) term scaled by `haze`
+#[cfg(feature = "atmosphere")]
+fn hazy_medium(haze: f32) -> ScatteringMedium {
+    let mut medium = ScatteringMedium::default();
+    for term in &mut medium.terms {
+        if matches!(term.phase, PhaseFunction::Mie { .. }) {
+            term.absorption *= haze;
+            term.scattering *= haze;
+        }
+    }
+    medium
 }
 
 /// Calculate moon light intensity based on moon phase and time of day
@@ -204,16 +234,23 @@ impl Default for SceneLighting {
 pub struct AtmosphereState {
     /// Handle to the scattering medium asset
     pub medium: Handle<ScatteringMedium>,
+    /// Haze the medium was last built with (see `atmosphere_haze`)
+    haze: f32,
+    /// Elapsed seconds at the last rebuild of the medium
+    rebuilt_at: f32,
 }
 
-/// Plugin for configurable scene lighting
+/// Plugin for configurable scene lighting and editable scene lights
 pub struct LightingPlugin;
 
 impl Plugin for LightingPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SceneLighting>()
             .add_systems(Startup, setup_lighting)
-            .add_systems(Update, update_lighting);
+            .add_systems(Update, (advance_time_of_day, update_lighting).chain());
+        #[cfg(feature = "atmosphere")]
+        app.add_systems(Update, update_atmosphere_medium.after(update_lighting));
+        scene_light::build(app);
     }
 }
 
@@ -226,9 +263,12 @@ fn setup_lighting(
 ) {
     let settings = &lighting.settings;
 
-    // Calculate initial sun rotation from time of day
-    // Sun rotates around X-axis: midnight=0, noon=PI
-    let sun_angle = (settings.time_of_day / 24.0) * std::f32::consts::TAU;
+    // Initial sun position from time of day
+    let direction = calculate_sun_direction(
+        settings.time_of_day,
+        settings.latitude,
+        settings.azimuth_angle,
+    );
 
     // Spawn directional light (sun) with raw sunlight - atmosphere will attenuate it
     commands.spawn((
@@ -238,18 +278,26 @@ fn setup_lighting(
             shadows_enabled: true,
             ..default()
         },
-        Transform::from_rotation(Quat::from_rotation_x(sun_angle)),
+        Transform::default().looking_to(-direction, Vec3::Y),
         SunLight,
+        SceneLight {
+            id: SUN_LIGHT_ID.to_string(),
+        },
+        Name::new("Sun"),
     ));
 
     // Create and store the scattering medium for atmosphere
-    let medium = scattering_mediums.add(ScatteringMedium::default());
-    commands.insert_resource(AtmosphereState { medium });
+    let haze = atmosphere_haze(settings.cloudiness, settings.pollution);
+    let medium = scattering_mediums.add(hazy_medium(haze));
+    commands.insert_resource(AtmosphereState {
+        medium,
+        haze,
+        rebuilt_at: f32::NEG_INFINITY,
+    });
 
     // No GlobalAmbientLight - atmosphere IBL handles ambient lighting
     info!("Scene lighting initialized with atmosphere");
 }
-
 /// Spawn the sun light and ambient light (non-atmosphere version)
 #[cfg(not(feature = "atmosphere"))]
 fn setup_lighting(mut commands: Commands, lighting: Res<SceneLighting>) {
@@ -274,6 +322,10 @@ fn setup_lighting(mut commands: Commands, lighting: Res<SceneLighting>) {
         // looking_to takes the forward direction; sun shines in -direction
         Transform::default().looking_to(-direction, Vec3::Y),
         SunLight,
+        SceneLight {
+            id: SUN_LIGHT_ID.to_string(),
+        },
+        Name::new("Sun"),
     ));
 
     // Set global ambient light (it's a resource, not an entity)
@@ -290,9 +342,41 @@ fn setup_lighting(mut commands: Commands, lighting: Res<SceneLighting>) {
     info!("Scene lighting initialized");
 }
 
+/// Advance the time of day while the day cycle plays
+///
+/// Moves `time_of_day` by `animate_speed` hours per second, wrapping past
+/// midnight, and reports the settings to the UI every
+/// `DAY_CYCLE_REPORT_INTERVAL` so its slider follows along.
+fn advance_time_of_day(
+    time: Res<Time>,
+    mut lighting: ResMut<SceneLighting>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    mut since_report: Local<f32>,
+) {
+    let delta = time.delta_secs();
+    let settings = &lighting.settings;
+    if !settings.use_time_of_day || settings.animate_speed == 0.0 || delta == 0.0 {
+        *since_report = 0.0;
+        return;
+    }
+
+    let time_of_day = (settings.time_of_day + settings.animate_speed * delta).rem_euclid(24.0);
+    lighting.settings.time_of_day = time_of_day;
+
+    *since_report += delta;
+    if *since_report >= DAY_CYCLE_REPORT_INTERVAL {
+        *since_report = 0.0;
+        outbound.send(BevyToUi::LightingChanged {
+            settings: lighting.settings.clone(),
+        });
+    }
+}
+
 /// Update lighting when settings change (atmosphere version)
 ///
 /// Uses Bevy's change detection via `is_changed()` instead of a manual dirty flag.
+/// The atmosphere colors the sun by how much air its light crosses, so only
+/// the direction and illuminance are set here.
 #[cfg(feature = "atmosphere")]
 fn update_lighting(
     lighting: Res<SceneLighting>,
@@ -308,33 +392,29 @@ fn update_lighting(
     let azimuth_angle = lighting.settings.azimuth_angle;
     let pollution = lighting.settings.pollution;
 
+    let (direction, daylight) = if use_time_of_day {
+        let direction =
+            calculate_sun_direction(time_of_day, lighting.settings.latitude, azimuth_angle);
+        (direction, daylight_factor(sun_elevation(direction)))
+    } else {
+        // Use explicit direction from settings (still apply azimuth rotation)
+        let base_dir = Vec3::from_array(lighting.settings.sun_direction).normalize();
+        let rotation = Quat::from_rotation_y(azimuth_angle.to_radians());
+        ((rotation * base_dir).normalize(), 1.0)
+    };
+
     for (mut light, mut transform) in sun_query.iter_mut() {
-        if use_time_of_day {
-            // Convert time_of_day (0-24h) to sun rotation angle
-            // Sun rotates around X-axis: midnight=0, noon=PI
-            let sun_angle = (time_of_day / 24.0) * std::f32::consts::TAU;
-
-            // Combine rotations: sun arc (around X) + azimuth (around Y)
-            let azimuth_rad = azimuth_angle.to_radians();
-            let rotation = Quat::from_rotation_y(azimuth_rad) * Quat::from_rotation_x(sun_angle);
-            *transform = Transform::from_rotation(rotation);
-        } else {
-            // Use explicit direction from settings (still apply azimuth rotation)
-            let base_dir = Vec3::from_array(lighting.settings.sun_direction).normalize();
-            let azimuth_rad = azimuth_angle.to_radians();
-            let rotation = Quat::from_rotation_y(azimuth_rad);
-            let direction = (rotation * base_dir).normalize();
-            *transform = Transform::default().looking_to(-direction, Vec3::Y);
-        }
+        *transform = Transform::default().looking_to(-direction, Vec3::Y);
 
         // With atmosphere, use raw sunlight - atmosphere handles attenuation
-        // Cloudiness and pollution modulate illuminance
+        // Daylight, cloudiness, and pollution modulate illuminance
         let cloud_factor = 1.0 - (cloudiness * 0.3); // Up to 30% reduction for thick clouds
         let pollution_factor = 1.0 - (pollution * 0.4); // Up to 40% reduction for heavy pollution
-        light.illuminance = lux::RAW_SUNLIGHT * cloud_factor * pollution_factor;
+        light.illuminance = lux::RAW_SUNLIGHT * daylight * cloud_factor * pollution_factor;
     }
 
-    info!(
+    // Changes every frame while the day cycle plays
+    debug!(
         "Scene lighting updated (atmosphere): time={:.1}h, cloudiness={:.0}%, azimuth={:.0}°, pollution={:.0}%",
         time_of_day,
         cloudiness * 100.0,
@@ -343,6 +423,35 @@ fn update_lighting(
     );
 }
 
+/// Rebuild the atmosphere's scattering medium when the haze changes
+///
+/// Rebuilds at most every `MEDIUM_REBUILD_INTERVAL`, so dragging the cloud
+/// or pollution slider doesn't recompute the medium's lookup tables every
+/// frame; the last value is applied once the interval has passed.
+#[cfg(feature = "atmosphere")]
+fn update_atmosphere_medium(
+    time: Res<Time>,
+    lighting: Res<SceneLighting>,
+    state: Option<ResMut<AtmosphereState>>,
+    mut scattering_mediums: ResMut<Assets<ScatteringMedium>>,
+) {
+    let Some(mut state) = state else {
+        return;
+    };
+    let haze = atmosphere_haze(lighting.settings.cloudiness, lighting.settings.pollution);
+    let now = time.elapsed_secs();
+    if haze == state.haze || now - state.rebuilt_at < MEDIUM_REBUILD_INTERVAL {
+        return;
+    }
+
+    if let Some(medium) = scattering_mediums.get_mut(&state.medium) {
+        *medium = hazy_medium(haze);
+    }
+    state.haze = haze;
+    state.rebuilt_at = now;
+    debug!("Atmosphere haze set to {:.2}", haze);
+}
+
 /// Update lighting when settings change (non-atmosphere version)
 ///
 /// Uses Bevy's change detection via `is_changed()` instead of a manual dirty flag.
@@ -365,13 +474,15 @@ fn update_lighting(
     let moon_phase = lighting.settings.moon_phase;
     let azimuth_angle = lighting.settings.azimuth_angle;
     let pollution = lighting.settings.pollution;
+    let latitude = lighting.settings.latitude;
 
     // Determine sun direction, color, and intensity
     let (sun_direction, sun_color, sun_intensity) = if use_time_of_day {
-        // Calculate from time of day, cloudiness, and azimuth
-        let direction = calculate_sun_direction_from_time(time_of_day, azimuth_angle);
-        let color = calculate_sun_color_from_time(time_of_day);
-        let intensity = calculate_sun_intensity(time_of_day, cloudiness, base_sun_intensity);
+        // Calculate from time of day, latitude, cloudiness, and azimuth
+        let direction = calculate_sun_direction(time_of_day, latitude, azimuth_angle);
+        let elevation = sun_elevation(direction);
+        let color = calculate_sun_color(elevation, cloudiness);
+        let intensity = calculate_sun_intensity(elevation, cloudiness, base_sun_intensity);
         (direction, color, intensity)
     } else {
         // Use explicit values from settings (still apply azimuth rotation)
@@ -392,7 +503,8 @@ fn update_lighting(
 
     // Determine ambient color and intensity
     let (ambient_color, ambient_intensity) = if use_time_of_day {
-        let color = calculate_ambient_color(time_of_day, cloudiness);
+        let elevation = sun_elevation(sun_direction);
+        let color = calculate_ambient_color(elevation, cloudiness);
         // Cloudiness increases ambient (more scattered light)
         let intensity = base_ambient_intensity * (1.0 + cloudiness * 0.5);
         (color, intensity)
@@ -423,7 +535,8 @@ fn update_lighting(
     ambient_light.color = Color::srgb(ambient_color[0], ambient_color[1], ambient_color[2]);
     ambient_light.brightness = total_ambient_intensity;
 
-    info!(
+    // Changes every frame while the day cycle plays
+    debug!(
         "Scene lighting updated: time={:.1}h, cloudiness={:.0}%, moon={:.0}%, azimuth={:.0}°, pollution={:.0}%",
         time_of_day,
         cloudiness * 100.0,
@@ -432,3 +545,91 @@ fn update_lighting(
         pollution * 100.0
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_sun_path_follows_latitude() {
+        for latitude in [45.0, 0.0, -30.0] {
+            let noon = calculate_sun_direction(12.0, latitude, 0.0);
+            assert!(
+                (sun_elevation(noon) - (90.0 - latitude.abs())).abs() < 0.01,
+                "noon sun at {} degrees for latitude {latitude}",
+                sun_elevation(noon)
+            );
+            // Rises due east (-X), on the horizon
+            let sunrise = calculate_sun_direction(6.0, latitude, 0.0);
+            assert!(sun_elevation(sunrise).abs() < 0.01);
+            assert!(sunrise.x < -0.99);
+            assert!(sun_elevation(calculate_sun_direction(0.0, latitude, 0.0)) < 0.0);
+        }
+
+        // Noon sun to the south (-Z) up north, to the north down south
+        assert!(calculate_sun_direction(12.0, 45.0, 0.0).z < 0.0);
+        assert!(calculate_sun_direction(12.0, -30.0, 0.0).z > 0.0);
+    }
+
+    #[test]
+    fn test_daylight_ramps_through_dawn() {
+        assert_eq!(daylight_factor(-10.0), 0.0);
+        assert_eq!(daylight_factor(20.0), 1.0);
+        let mut previous = 0.0;
+        for elevation in -4..=8 {
+            let daylight = daylight_factor(elevation as f32);
+            assert!(daylight >= previous);
+            previous = daylight;
+        }
+        assert!(daylight_factor(2.0) > 0.0 && daylight_factor(2.0) < 1.0);
+    }
+
+    #[test]
+    fn test_day_cycle_advances_and_wraps() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<OutboundUiMessages>()
+            .insert_resource(SceneLighting {
+                settings: LightingSettings {
+                    time_of_day: 23.5,
+                    animate_speed: 2.0,
+                    ..default()
+                },
+            })
+            .add_systems(Update, advance_time_of_day);
+
+        let step = |app: &mut App, millis| {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_millis(millis));
+            app.update();
+            let time_of_day = app.world().resource::<SceneLighting>().settings.time_of_day;
+            let reports = app
+                .world_mut()
+                .resource_mut::<OutboundUiMessages>()
+                .drain()
+                .len();
+            (time_of_day, reports)
+        };
+
+        // Past midnight, with the time reported
+        let (time_of_day, reports) = step(&mut app, 500);
+        assert!((time_of_day - 0.5).abs() < 1e-4);
+        assert_eq!(reports, 1);
+
+        // Not reported again right away
+        let (time_of_day, reports) = step(&mut app, 100);
+        assert!((time_of_day - 0.7).abs() < 1e-4);
+        assert_eq!(reports, 0);
+
+        // Paused
+        app.world_mut()
+            .resource_mut::<SceneLighting>()
+            .settings
+            .animate_speed = 0.0;
+        let (time_of_day, _) = step(&mut app, 500);
+        assert!((time_of_day - 0.7).abs() < 1e-4);
+    }
+}
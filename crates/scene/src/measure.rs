@@ -0,0 +1,498 @@
+//! Measure tool
+//!
+//! `MeasureCommand::Start` arms the tool: from then on every two viewport
+//! clicks on scene surfaces (ray cast through the shared [`SceneBvh`]) place
+//! a measurement, until `MeasureCommand::Cancel`. A clicked point snaps to the
+//! nearest corner of the hit triangle when that corner is within
+//! `VERTEX_SNAP_RADIUS_PX` of the cursor; holding Ctrl places the exact hit.
+//!
+//! Measurements are lightweight entities: a [`Measurement`] with its world
+//! endpoints, drawn as a gizmo line with a screen-space distance label. They
+//! are listed in `SceneInfo::annotations`, and visibility and deletion go
+//! through `ObjectCommand` like any other object. Gizmos are hidden during
+//! turntable renders and the labels only draw on the window, so
+//! measurements never show up in rendered output.
+
+use bevy::camera::visibility::RenderLayers;
+use bevy::ecs::message::Message;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use pentimento_ipc::{AnnotationInfo, BevyToUi, MeasureCommand, ObjectCommand};
+
+use crate::camera::MainCamera;
+use crate::object_id::IdAllocator;
+use crate::scene_bvh::SceneBvh;
+use crate::{ObjectCommandEvent, OutboundUiMessages};
+
+/// Screen distance within which a clicked point snaps to a triangle corner
+const VERTEX_SNAP_RADIUS_PX: f32 = 12.0;
+
+/// Endpoint marker radius relative to its distance from the camera
+const ENDPOINT_SCALE: f32 = 0.008;
+
+/// Color of placed measurements
+const MEASURE_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// Color of the measurement being placed
+const PREVIEW_COLOR: Color = Color::srgba(1.0, 0.85, 0.2, 0.5);
+
+/// Message for measure tool commands requested by the UI
+#[derive(Message, Debug, Clone)]
+pub struct MeasureEvent(pub MeasureCommand);
+
+/// Measure tool state
+#[derive(Resource, Default)]
+pub struct MeasureState {
+    /// Whether viewport clicks place measurement points
+    pub active: bool,
+    /// First endpoint of the measurement being placed
+    start: Option<Vec3>,
+    /// Point a click would place, for the preview
+    hover: Option<Vec3>,
+}
+
+impl MeasureState {
+    /// Whether viewport clicks belong to the measure tool instead of selection
+    pub fn captures_input(&self) -> bool {
+        self.active
+    }
+}
+
+/// A measurement between two world-space points
+#[derive(Component, Debug, Clone)]
+pub struct Measurement {
+    /// Annotation ID reported to the UI
+    pub id: String,
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+impl Measurement {
+    /// Distance between the endpoints in world units
+    pub fn distance(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+}
+
+/// Gizmo group for measurements, drawn in front of scene geometry
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct MeasureGizmos;
+
+/// Counter for numbering measurement names
+#[derive(Resource, Default)]
+struct MeasurementCounter(u32);
+
+/// Read access to measurements as `AnnotationInfo`
+#[derive(SystemParam)]
+pub struct SceneMeasurements<'w, 's> {
+    measurements: Query<'w, 's, (&'static Measurement, &'static Name)>,
+}
+
+impl SceneMeasurements<'_, '_> {
+    /// All measurements, in ID (creation) order
+    pub fn infos(&self) -> Vec<AnnotationInfo> {
+        let mut infos: Vec<_> = self
+            .measurements
+            .iter()
+            .map(|(measurement, name)| AnnotationInfo {
+                id: measurement.id.clone(),
+                name: name.to_string(),
+                start: measurement.start.to_array(),
+                end: measurement.end.to_array(),
+                distance: measurement.distance(),
+            })
+            .collect();
+        infos.sort_by(|a, b| a.id.cmp(&b.id));
+        infos
+    }
+}
+
+/// Plugin for the measure tool
+pub struct MeasurePlugin;
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeasureState>()
+            .init_resource::<MeasurementCounter>()
+            .add_message::<MeasureEvent>()
+            .insert_gizmo_config(
+                MeasureGizmos,
+                GizmoConfig {
+                    depth_bias: -1.0,
+                    ..default()
+                },
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_measure_events,
+                    handle_measure_input,
+                    handle_measurement_object_commands,
+                    (draw_measurements, position_measurement_labels),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Arm and disarm the tool
+fn handle_measure_events(mut events: MessageReader<MeasureEvent>, mut state: ResMut<MeasureState>) {
+    for MeasureEvent(command) in events.read() {
+        match command {
+            MeasureCommand::Start => {
+                state.active = true;
+                state.start = None;
+                info!("Measure tool started");
+            }
+            MeasureCommand::Cancel => {
+                *state = MeasureState::default();
+                info!("Measure tool cancelled");
+            }
+        }
+    }
+}
+
+/// Surface points under the cursor, snapped to nearby vertices
+#[derive(SystemParam)]
+struct MeasurePicker<'w, 's> {
+    window: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    camera: Query<
+        'w,
+        's,
+        (
+            &'static Camera,
+            &'static GlobalTransform,
+            Option<&'static RenderLayers>,
+        ),
+        With<MainCamera>,
+    >,
+    bvh: Res<'w, SceneBvh>,
+    layers: Query<'w, 's, &'static RenderLayers>,
+}
+
+impl MeasurePicker<'_, '_> {
+    /// Scene point under the cursor, or `None` when it misses the scene;
+    /// with `snap` a triangle corner near the cursor is taken instead
+    fn point_under_cursor(&self, snap: bool) -> Option<Vec3> {
+        let cursor = self.window.single().ok()?.cursor_position()?;
+        let (camera, camera_transform, camera_layers) = self.camera.single().ok()?;
+        let ray = camera.viewport_to_world(camera_transform, cursor).ok()?;
+
+        // Only what the camera shows, not overlays on other layers
+        let camera_layers = camera_layers.cloned().unwrap_or_default();
+        let hit = self.bvh.raycast(ray, |entity| {
+            let entity_layers = self.layers.get(entity).cloned().unwrap_or_default();
+            camera_layers.intersects(&entity_layers)
+        })?;
+
+        let corners = snap.then(|| self.bvh.hit_triangle(&hit)).flatten();
+        Some(match corners {
+            Some(corners) => snap_to_vertex(hit.position, corners, cursor, |point| {
+                camera.world_to_viewport(camera_transform, point).ok()
+            }),
+            None => hit.position,
+        })
+    }
+}
+
+/// `hit`, or the corner nearest to it when that corner is within
+/// `VERTEX_SNAP_RADIUS_PX` of `cursor` on screen
+fn snap_to_vertex(
+    hit: Vec3,
+    corners: [Vec3; 3],
+    cursor: Vec2,
+    to_screen: impl Fn(Vec3) -> Option<Vec2>,
+) -> Vec3 {
+    let nearest = corners
+        .into_iter()
+        .min_by(|a, b| a.distance_squared(hit).total_cmp(&b.distance_squared(hit)))
+        .unwrap_or(hit);
+    match to_screen(nearest) {
+        Some(screen) if screen.distance(cursor) <= VERTEX_SNAP_RADIUS_PX => nearest,
+        _ => hit,
+    }
+}
+
+/// Place measurement points with left clicks while the tool is armed
+#[allow(clippy::too_many_arguments)]
+fn handle_measure_input(
+    mut commands: Commands,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    picker: MeasurePicker,
+    mut state: ResMut<MeasureState>,
+    mut allocator: ResMut<IdAllocator>,
+    mut counter: ResMut<MeasurementCounter>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    if !state.active {
+        return;
+    }
+
+    let ctrl_held =
+        key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
+    let point = picker.point_under_cursor(!ctrl_held);
+    state.hover = point;
+
+    if !mouse_button.just_pressed(MouseButton::Left) {
+        return;
+    }
+    // Clicks that miss the scene place nothing
+    let Some(point) = point else {
+        return;
+    };
+    let Some(start) = state.start.take() else {
+        state.start = Some(point);
+        return;
+    };
+
+    counter.0 += 1;
+    let measurement = Measurement {
+        id: allocator.allocate(),
+        start,
+        end: point,
+    };
+    let distance = measurement.distance();
+    info!(
+        "Measured {} between {:?} and {:?}",
+        format_distance(distance),
+        start,
+        point
+    );
+    outbound.send(BevyToUi::MeasureResult {
+        id: measurement.id.clone(),
+        start: start.to_array(),
+        end: point.to_array(),
+        distance,
+    });
+    commands.spawn(measurement_bundle(measurement, counter.0));
+}
+
+/// Entity for a measurement: the measurement itself is the label's UI node,
+/// positioned over its midpoint every frame
+fn measurement_bundle(measurement: Measurement, number: u32) -> impl Bundle {
+    let label = format_distance(measurement.distance());
+    (
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::axes(Val::Px(4.0), Val::Px(1.0)),
+            display: Display::None,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Text::new(label),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(MEASURE_COLOR),
+        Name::new(format!("Measurement {}", number)),
+        measurement,
+        // Don't block picking of the meshes behind the label
+        #[cfg(feature = "selection")]
+        bevy::picking::Pickable::IGNORE,
+    )
+}
+
+/// Distance with a unit suited to its size
+fn format_distance(distance: f32) -> String {
+    if distance >= 1.0 {
+        format!("{:.2} m", distance)
+    } else if distance >= 0.01 {
+        format!("{:.1} cm", distance * 100.0)
+    } else {
+        format!("{:.1} mm", distance * 1000.0)
+    }
+}
+
+/// Apply visibility toggles and deletions to measurements
+fn handle_measurement_object_commands(
+    mut commands: Commands,
+    mut events: MessageReader<ObjectCommandEvent>,
+    mut measurements: Query<(Entity, &Measurement, &mut Visibility)>,
+) {
+    for ObjectCommandEvent(command) in events.read() {
+        match command {
+            ObjectCommand::Delete { ids, .. } => {
+                for (entity, measurement, _) in measurements.iter() {
+                    if ids.contains(&measurement.id) {
+                        commands.entity(entity).despawn();
+                        info!("Deleted measurement {}", measurement.id);
+                    }
+                }
+            }
+            ObjectCommand::SetVisibility { id, visible } => {
+                for (_, measurement, mut visibility) in measurements.iter_mut() {
+                    if measurement.id == *id {
+                        *visibility = if *visible {
+                            Visibility::Inherited
+                        } else {
+                            Visibility::Hidden
+                        };
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Draw measurement lines and endpoints, and the one being placed
+fn draw_measurements(
+    mut gizmos: Gizmos<MeasureGizmos>,
+    camera: Query<&GlobalTransform, With<MainCamera>>,
+    measurements: Query<(&Measurement, &Visibility)>,
+    state: Res<MeasureState>,
+) {
+    let Ok(camera) = camera.single() else {
+        return;
+    };
+    let camera_rotation = camera.rotation();
+    let mut endpoint = |position: Vec3, color: Color| {
+        let radius = position.distance(camera.translation()) * ENDPOINT_SCALE;
+        gizmos.circle(Isometry3d::new(position, camera_rotation), radius, color);
+    };
+
+    for (measurement, visibility) in measurements.iter() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        endpoint(measurement.start, MEASURE_COLOR);
+        endpoint(measurement.end, MEASURE_COLOR);
+    }
+    if let Some(hover) = state.hover.filter(|_| state.active) {
+        endpoint(hover, PREVIEW_COLOR);
+    }
+    if let Some(start) = state.start {
+        endpoint(start, MEASURE_COLOR);
+    }
+
+    for (measurement, visibility) in measurements.iter() {
+        if *visibility != Visibility::Hidden {
+            gizmos.line(measurement.start, measurement.end, MEASURE_COLOR);
+        }
+    }
+    if let (Some(start), Some(hover)) = (state.start, state.hover) {
+        gizmos.line(start, hover, PREVIEW_COLOR);
+    }
+}
+
+/// Keep each label over its measurement's midpoint, hidden while the
+/// midpoint is behind the camera
+fn position_measurement_labels(
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut labels: Query<(&Measurement, &mut Node)>,
+) {
+    let Ok((camera, camera_transform)) = camera.single() else {
+        return;
+    };
+    for (measurement, mut node) in labels.iter_mut() {
+        let midpoint = (measurement.start + measurement.end) * 0.5;
+        let display = match camera.world_to_viewport(camera_transform, midpoint) {
+            Ok(position) => {
+                node.left = Val::Px(position.x);
+                node.top = Val::Px(position.y);
+                Display::Flex
+            }
+            Err(_) => Display::None,
+        };
+        if node.display != display {
+            node.display = display;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// Screen projection that drops Z and scales world units to pixels
+    fn flat(point: Vec3) -> Option<Vec2> {
+        Some(point.truncate() * 100.0)
+    }
+
+    #[test]
+    fn test_snaps_to_corner_near_cursor() {
+        let corners = [Vec3::ZERO, Vec3::X, Vec3::Y];
+        let hit = Vec3::new(0.95, 0.02, 0.0);
+        let cursor = flat(hit).unwrap();
+        assert_eq!(snap_to_vertex(hit, corners, cursor, flat), Vec3::X);
+
+        // Far from every corner on screen: the hit itself
+        let middle = Vec3::new(0.3, 0.3, 0.0);
+        let cursor = flat(middle).unwrap();
+        assert_eq!(snap_to_vertex(middle, corners, cursor, flat), middle);
+
+        // A corner behind the camera never snaps
+        assert_eq!(snap_to_vertex(hit, corners, cursor, |_| None), hit);
+    }
+
+    #[test]
+    fn test_format_distance_units() {
+        assert_eq!(format_distance(2.5), "2.50 m");
+        assert_eq!(format_distance(0.125), "12.5 cm");
+        assert_eq!(format_distance(0.004), "4.0 mm");
+    }
+
+    #[test]
+    fn test_two_clicks_place_measurements_until_cancelled() {
+        let mut app = App::new();
+        app.init_resource::<OutboundUiMessages>()
+            .init_resource::<IdAllocator>()
+            .init_resource::<MeasureState>()
+            .init_resource::<MeasurementCounter>()
+            .add_message::<MeasureEvent>()
+            .add_message::<ObjectCommandEvent>()
+            .add_systems(
+                Update,
+                (handle_measure_events, handle_measurement_object_commands).chain(),
+            );
+
+        app.world_mut()
+            .write_message(MeasureEvent(MeasureCommand::Start));
+        app.update();
+        assert!(app.world().resource::<MeasureState>().captures_input());
+
+        // Measurements placed by clicks coexist
+        let ids: Vec<String> = (0..2)
+            .map(|i| {
+                let measurement = Measurement {
+                    id: format!("measure-{i}"),
+                    start: Vec3::ZERO,
+                    end: Vec3::new(i as f32 + 1.0, 0.0, 0.0),
+                };
+                let id = measurement.id.clone();
+                app.world_mut()
+                    .spawn(measurement_bundle(measurement, i + 1));
+                id
+            })
+            .collect();
+        app.update();
+        let infos = app
+            .world_mut()
+            .run_system_once(|measurements: SceneMeasurements| measurements.infos())
+            .unwrap();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[1].distance, 2.0);
+        assert_eq!(infos[1].name, "Measurement 2");
+
+        app.world_mut()
+            .write_message(ObjectCommandEvent(ObjectCommand::Delete {
+                ids: vec![ids[0].clone()],
+                recursive: false,
+            }));
+        app.world_mut()
+            .write_message(MeasureEvent(MeasureCommand::Cancel));
+        app.update();
+
+        let mut remaining = app.world_mut().query::<&Measurement>();
+        let remaining: Vec<_> = remaining
+            .iter(app.world())
+            .map(|measurement| measurement.id.clone())
+            .collect();
+        assert_eq!(remaining, vec![ids[1].clone()]);
+        assert!(!app.world().resource::<MeasureState>().captures_input());
+    }
+}
@@ -4,9 +4,8 @@
 //! vertices, edges, and faces during mesh editing.
 
 use bevy::prelude::*;
-use pentimento_ipc::EditMode;
 
-use crate::edit_mode::EditModeState;
+use crate::edit_mode::{EditModeState, EditorMode};
 use crate::mesh_edit_mode::{EditableMesh, MeshEditState};
 
 /// Plugin for mesh edit selection highlighting
@@ -34,7 +33,7 @@ fn render_mesh_edit_selection(
     mut gizmos: Gizmos,
 ) {
     // Only render in mesh edit mode
-    if edit_mode.mode != EditMode::MeshEdit {
+    if edit_mode.mode != EditorMode::MeshEdit {
         return;
     }
 
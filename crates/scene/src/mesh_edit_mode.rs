@@ -5,18 +5,21 @@
 //! - 1/2/3 to switch between Vertex/Edge/Face selection modes
 //! - Click to select, Shift+click to add to selection
 //! - A to select all / deselect all
+//! - E / I to extrude / inset selected faces (see `mesh_edit_ops`)
 
 use bevy::ecs::message::Message;
 use bevy::prelude::*;
 use painting::half_edge::{FaceId, HalfEdgeId, HalfEdgeMesh, VertexId};
-use pentimento_ipc::{BevyToUi, EditMode, MeshEditTool, MeshSelectionMode};
+use pentimento_config::{Keymap, actions};
+use pentimento_ipc::{BevyToUi, MeshEditTool, MeshSelectionMode};
 use std::collections::HashSet;
 
 use crate::OutboundUiMessages;
-use crate::canvas_plane::ActiveCanvasPlane;
-use crate::edit_mode::EditModeState;
+use crate::canvas_plane::{ActiveCanvasPlane, CanvasPlane};
+use crate::edit_mode::{EditModeAppExt, EditModeRequests, EditModeState, EditorMode};
 #[cfg(feature = "selection")]
 use crate::selection::Selected;
+use crate::subdivision::{BaseMesh, base_mesh_handle};
 
 /// Component marking a mesh as editable with half-edge topology data
 #[derive(Component)]
@@ -46,6 +49,8 @@ pub struct MeshEditState {
     pub original_positions: std::collections::HashMap<VertexId, Vec3>,
     /// Whether a transform operation is currently active
     pub transform_active: bool,
+    /// Inset each selected face on its own instead of as connected regions
+    pub inset_individual: bool,
 }
 
 impl MeshEditState {
@@ -69,13 +74,10 @@ impl MeshEditState {
     }
 }
 
-/// Message for mesh edit mode transitions and commands
+/// Message for mesh edit mode commands (the mode itself is entered and left
+/// through `edit_mode`)
 #[derive(Message, Debug, Clone)]
 pub enum MeshEditEvent {
-    /// Enter mesh edit mode for the specified entity
-    Enter { entity: Entity },
-    /// Exit mesh edit mode
-    Exit,
     /// Set the selection mode (vertex/edge/face)
     SetSelectionMode(MeshSelectionMode),
     /// Set the active tool
@@ -86,6 +88,12 @@ pub enum MeshEditEvent {
     DeselectAll,
     /// Toggle select all (select if nothing selected, deselect if all selected)
     ToggleSelectAll,
+    /// Extrude the selected faces along their average normal
+    Extrude { offset: f32 },
+    /// Inset the selected faces (per face or per region, see `inset_individual`)
+    Inset { thickness: f32 },
+    /// Undo the last modeling operation
+    Undo,
 }
 
 /// Plugin for mesh edit mode
@@ -103,7 +111,10 @@ impl Plugin for MeshEditModePlugin {
                     handle_selection_mode_hotkeys,
                 )
                     .chain(),
-            );
+            )
+            .set_edit_mode_guard(EditorMode::MeshEdit, mesh_edit_mode_guard)
+            .on_enter_edit_mode(EditorMode::MeshEdit, enter_mesh_edit_mode)
+            .on_exit_edit_mode(EditorMode::MeshEdit, exit_mesh_edit_mode);
     }
 }
 
@@ -115,37 +126,26 @@ impl Plugin for MeshEditModePlugin {
 #[cfg(feature = "selection")]
 fn handle_tab_key_for_mesh_edit(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     edit_mode: Res<EditModeState>,
     active_plane: Res<ActiveCanvasPlane>,
-    _mesh_edit_state: Res<MeshEditState>,
     selected_meshes: Query<Entity, (With<Selected>, With<Mesh3d>)>,
-    mut events: MessageWriter<MeshEditEvent>,
+    mut mode_requests: EditModeRequests,
 ) {
-    if !key_input.just_pressed(KeyCode::Tab) {
-        return;
-    }
-
-    // Don't handle Tab if Ctrl is held (Ctrl+Tab is for sculpt mode)
-    let ctrl = key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
-    if ctrl {
-        return;
-    }
-
-    // Don't handle Tab if Shift is held (Shift+Tab is for paint mode)
-    let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
-    if shift {
+    // Exact modifier match, so Ctrl+Tab (sculpt) and Shift+Tab (paint) don't land here
+    if !keymap.just_pressed(actions::MODE_TOGGLE, &key_input) {
         return;
     }
 
     // Don't handle Tab if we're in Paint mode with a canvas selected
     // (canvas_plane.rs handles that case for camera lock)
-    if edit_mode.mode == EditMode::Paint && active_plane.entity.is_some() {
+    if edit_mode.mode == EditorMode::Paint && active_plane.entity.is_some() {
         return;
     }
 
     // If we're already in mesh edit mode, exit
-    if edit_mode.mode == EditMode::MeshEdit {
-        events.write(MeshEditEvent::Exit);
+    if edit_mode.mode == EditorMode::MeshEdit {
+        mode_requests.request_mode_change(EditorMode::Object);
         return;
     }
 
@@ -153,7 +153,7 @@ fn handle_tab_key_for_mesh_edit(
     if let Ok(entity) = selected_meshes.single() {
         // Check that it's not the active canvas plane
         if active_plane.entity != Some(entity) {
-            events.write(MeshEditEvent::Enter { entity });
+            mode_requests.request_mode_change_for(EditorMode::MeshEdit, entity);
         }
     }
 }
@@ -162,84 +162,127 @@ fn handle_tab_key_for_mesh_edit(
 #[cfg(not(feature = "selection"))]
 fn handle_tab_key_for_mesh_edit() {}
 
-/// Handle mesh edit events
-fn handle_mesh_edit_events(
+/// Mesh edit mode needs a mesh whose data is loaded: the requested one, or
+/// the selected one. Canvas planes are painted, not edited.
+#[cfg(feature = "selection")]
+fn mesh_edit_mode_guard(
+    In(target): In<Option<Entity>>,
+    selected_meshes: Query<Entity, (With<Selected>, With<Mesh3d>)>,
+    mesh_query: Query<(&Mesh3d, Option<&BaseMesh>), Without<CanvasPlane>>,
+    meshes: Res<Assets<Mesh>>,
+) -> Result<Option<Entity>, String> {
+    let entity = target
+        .or_else(|| selected_meshes.single().ok())
+        .ok_or_else(|| "Select a mesh to edit".to_string())?;
+    let Ok((mesh3d, base)) = mesh_query.get(entity) else {
+        return Err("Only meshes can be edited".to_string());
+    };
+    if !meshes.contains(base_mesh_handle(mesh3d, base)) {
+        return Err("The mesh hasn't loaded yet".to_string());
+    }
+    Ok(Some(entity))
+}
+
+/// Stub for non-selection builds: only explicit targets can be edited
+#[cfg(not(feature = "selection"))]
+fn mesh_edit_mode_guard(In(target): In<Option<Entity>>) -> Result<Option<Entity>, String> {
+    target
+        .map(Some)
+        .ok_or_else(|| "Select a mesh to edit".to_string())
+}
+
+/// Build the target's half-edge mesh and reset the edit state
+///
+/// A mesh that can't be converted leaves the mode again right away.
+#[allow(clippy::too_many_arguments)]
+fn enter_mesh_edit_mode(
+    In(target): In<Option<Entity>>,
     mut commands: Commands,
-    mut events: MessageReader<MeshEditEvent>,
-    mut edit_mode: ResMut<EditModeState>,
     mut mesh_edit_state: ResMut<MeshEditState>,
     mut outbound: ResMut<OutboundUiMessages>,
     meshes: Res<Assets<Mesh>>,
-    mesh_query: Query<&Mesh3d>,
+    mesh_query: Query<(&Mesh3d, Option<&BaseMesh>)>,
     editable_query: Query<&EditableMesh>,
+    mut mode_requests: EditModeRequests,
 ) {
-    for event in events.read() {
-        match event {
-            MeshEditEvent::Enter { entity } => {
-                // Build EditableMesh if not already present
-                if editable_query.get(*entity).is_err() {
-                    if let Ok(mesh3d) = mesh_query.get(*entity) {
-                        if let Some(mesh) = meshes.get(&mesh3d.0) {
-                            match HalfEdgeMesh::from_bevy_mesh(mesh) {
-                                Ok(half_edge_mesh) => {
-                                    commands.entity(*entity).insert(EditableMesh {
-                                        half_edge_mesh,
-                                        original_mesh_handle: mesh3d.0.clone(),
-                                    });
-                                    info!("Built half-edge mesh for entity {:?}", entity);
-                                }
-                                Err(e) => {
-                                    warn!("Failed to build half-edge mesh: {:?}", e);
-                                    continue;
-                                }
-                            }
-                        }
-                    }
+    let Some(entity) = target else {
+        return;
+    };
+
+    // Build EditableMesh if not already present
+    if editable_query.get(entity).is_err()
+        && let Ok((mesh3d, base)) = mesh_query.get(entity)
+    {
+        // Edit the base mesh, not a subdivision preview
+        let mesh_handle = base_mesh_handle(mesh3d, base);
+        if let Some(mesh) = meshes.get(mesh_handle) {
+            match HalfEdgeMesh::from_bevy_mesh(mesh) {
+                Ok(half_edge_mesh) => {
+                    commands.entity(entity).insert(EditableMesh {
+                        half_edge_mesh,
+                        original_mesh_handle: mesh_handle.clone(),
+                    });
+                    info!("Built half-edge mesh for entity {:?}", entity);
                 }
+                Err(e) => {
+                    warn!("Failed to build half-edge mesh: {:?}", e);
+                    outbound.send(BevyToUi::Error {
+                        code: "edit_mode".to_string(),
+                        message: format!("This mesh can't be edited: {e:?}"),
+                    });
+                    mode_requests.request_mode_change(EditorMode::Object);
+                    return;
+                }
+            }
+        }
+    }
 
-                // Enter mesh edit mode
-                edit_mode.mode = EditMode::MeshEdit;
-                edit_mode.target_entity = Some(*entity);
-                mesh_edit_state.target_entity = Some(*entity);
-                mesh_edit_state.clear_selection();
-                mesh_edit_state.selection_mode = MeshSelectionMode::Vertex;
-                mesh_edit_state.tool = MeshEditTool::Select;
+    mesh_edit_state.target_entity = Some(entity);
+    mesh_edit_state.clear_selection();
+    mesh_edit_state.selection_mode = MeshSelectionMode::Vertex;
+    mesh_edit_state.tool = MeshEditTool::Select;
 
-                info!("Entered mesh edit mode for entity {:?}", entity);
+    info!("Entered mesh edit mode for entity {:?}", entity);
 
-                // Notify UI
-                outbound.send(BevyToUi::EditModeChanged {
-                    mode: EditMode::MeshEdit,
-                });
-                outbound.send(BevyToUi::MeshEditModeChanged {
-                    active: true,
-                    selection_mode: mesh_edit_state.selection_mode,
-                    tool: mesh_edit_state.tool,
-                });
-                outbound.send(BevyToUi::MeshEditSelectionChanged {
-                    vertex_count: 0,
-                    edge_count: 0,
-                    face_count: 0,
-                });
-            }
-            MeshEditEvent::Exit => {
-                info!("Exited mesh edit mode");
+    // Notify UI
+    outbound.send(BevyToUi::MeshEditModeChanged {
+        active: true,
+        selection_mode: mesh_edit_state.selection_mode,
+        tool: mesh_edit_state.tool,
+    });
+    outbound.send(BevyToUi::MeshEditSelectionChanged {
+        vertex_count: 0,
+        edge_count: 0,
+        face_count: 0,
+    });
+}
 
-                edit_mode.mode = EditMode::None;
-                edit_mode.target_entity = None;
-                mesh_edit_state.target_entity = None;
-                mesh_edit_state.clear_selection();
+fn exit_mesh_edit_mode(
+    mut mesh_edit_state: ResMut<MeshEditState>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    info!("Exited mesh edit mode");
 
-                // Notify UI
-                outbound.send(BevyToUi::EditModeChanged {
-                    mode: EditMode::None,
-                });
-                outbound.send(BevyToUi::MeshEditModeChanged {
-                    active: false,
-                    selection_mode: MeshSelectionMode::Vertex,
-                    tool: MeshEditTool::Select,
-                });
-            }
+    mesh_edit_state.target_entity = None;
+    mesh_edit_state.clear_selection();
+
+    // Notify UI
+    outbound.send(BevyToUi::MeshEditModeChanged {
+        active: false,
+        selection_mode: MeshSelectionMode::Vertex,
+        tool: MeshEditTool::Select,
+    });
+}
+
+/// Handle mesh edit events
+fn handle_mesh_edit_events(
+    mut events: MessageReader<MeshEditEvent>,
+    mut mesh_edit_state: ResMut<MeshEditState>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    editable_query: Query<&EditableMesh>,
+) {
+    for event in events.read() {
+        match event {
             MeshEditEvent::SetSelectionMode(mode) => {
                 mesh_edit_state.selection_mode = *mode;
                 info!("Set selection mode to {:?}", mode);
@@ -284,6 +327,8 @@ fn handle_mesh_edit_events(
                     }
                 }
             }
+            // Modeling operations are applied by mesh_edit_ops
+            MeshEditEvent::Extrude { .. } | MeshEditEvent::Inset { .. } | MeshEditEvent::Undo => {}
         }
     }
 }
@@ -336,25 +381,26 @@ fn send_selection_changed(state: &MeshEditState, outbound: &mut OutboundUiMessag
 /// Handle hotkeys for selection mode (1/2/3) and select all (A)
 fn handle_selection_mode_hotkeys(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     edit_mode: Res<EditModeState>,
     mut events: MessageWriter<MeshEditEvent>,
 ) {
     // Only handle in mesh edit mode
-    if edit_mode.mode != EditMode::MeshEdit {
+    if edit_mode.mode != EditorMode::MeshEdit {
         return;
     }
 
     // 1/2/3 for selection modes
-    if key_input.just_pressed(KeyCode::Digit1) {
+    if keymap.just_pressed(actions::MESH_EDIT_VERTEX_MODE, &key_input) {
         events.write(MeshEditEvent::SetSelectionMode(MeshSelectionMode::Vertex));
-    } else if key_input.just_pressed(KeyCode::Digit2) {
+    } else if keymap.just_pressed(actions::MESH_EDIT_EDGE_MODE, &key_input) {
         events.write(MeshEditEvent::SetSelectionMode(MeshSelectionMode::Edge));
-    } else if key_input.just_pressed(KeyCode::Digit3) {
+    } else if keymap.just_pressed(actions::MESH_EDIT_FACE_MODE, &key_input) {
         events.write(MeshEditEvent::SetSelectionMode(MeshSelectionMode::Face));
     }
 
     // A for toggle select all
-    if key_input.just_pressed(KeyCode::KeyA) {
+    if keymap.just_pressed(actions::MESH_EDIT_SELECT_ALL, &key_input) {
         events.write(MeshEditEvent::ToggleSelectAll);
     }
 }
@@ -0,0 +1,377 @@
+//! Modeling operations in mesh edit mode
+//!
+//! Works on the selected faces:
+//! - E: extrude the selection, then move the mouse to set the distance
+//! - I: inset the selection, then move the mouse to set the thickness
+//!   (press I again to switch between region and individual faces)
+//! - Click or Enter to confirm, Escape or right click to cancel
+//! - Ctrl+Z: undo the last operation
+
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use painting::half_edge::{FaceId, FaceOffset, HalfEdgeError, HalfEdgeMesh};
+use pentimento_config::{Keymap, actions};
+use pentimento_ipc::BevyToUi;
+
+use crate::OutboundUiMessages;
+use crate::camera::MainCamera;
+use crate::edit_mode::{EditModeAppExt, EditModeState, EditorMode};
+use crate::mesh_edit_mode::{EditableMesh, MeshEditEvent, MeshEditState};
+use crate::mesh_edit_selection::handle_sub_object_click;
+
+/// Maximum undo steps kept per edit session
+const MAX_HISTORY: usize = 32;
+/// World units per pixel of mouse movement, matching gizmo translation
+const DRAG_SENSITIVITY: f32 = 0.01;
+
+/// Mesh snapshots taken before each operation, newest last
+#[derive(Resource, Default)]
+pub struct MeshEditHistory {
+    entries: Vec<(Entity, HalfEdgeMesh)>,
+}
+
+impl MeshEditHistory {
+    /// Remember the mesh of `entity` before changing it
+    pub fn push(&mut self, entity: Entity, mesh: HalfEdgeMesh) {
+        if self.entries.len() == MAX_HISTORY {
+            self.entries.remove(0);
+        }
+        self.entries.push((entity, mesh));
+    }
+
+    /// Take the most recent snapshot
+    pub fn pop(&mut self) -> Option<(Entity, HalfEdgeMesh)> {
+        self.entries.pop()
+    }
+
+    /// Whether there is anything to undo
+    pub fn can_undo(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    /// Drop all snapshots
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Which operation the mouse is adjusting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FaceOpKind {
+    Extrude,
+    Inset { individual: bool },
+}
+
+/// Operation waiting for the mouse to set its amount
+struct RunningFaceOp {
+    kind: FaceOpKind,
+    entity: Entity,
+    faces: Vec<FaceId>,
+    offset: FaceOffset,
+    /// Mouse movement since the operation started
+    accumulated_delta: Vec2,
+    amount: f32,
+}
+
+/// Interactive extrude or inset in progress
+#[derive(Resource, Default)]
+struct ActiveFaceOp(Option<RunningFaceOp>);
+
+/// Plugin for extrude and inset in mesh edit mode
+pub struct MeshEditOpsPlugin;
+
+impl Plugin for MeshEditOpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeshEditHistory>()
+            .init_resource::<ActiveFaceOp>()
+            .add_systems(
+                Update,
+                (
+                    handle_face_op_hotkeys,
+                    handle_face_op_events,
+                    drag_face_op,
+                    finish_face_op,
+                )
+                    .chain()
+                    .after(handle_sub_object_click),
+            )
+            .on_exit_edit_mode(EditorMode::MeshEdit, end_face_op_session);
+    }
+}
+
+/// Drop the running operation and the undo history of the edit session
+fn end_face_op_session(
+    mut mesh_edit_state: ResMut<MeshEditState>,
+    mut active: ResMut<ActiveFaceOp>,
+    mut history: ResMut<MeshEditHistory>,
+) {
+    active.0 = None;
+    mesh_edit_state.transform_active = false;
+    history.clear();
+}
+
+/// Start an interactive extrude (E) or inset (I), and undo with Ctrl+Z
+#[allow(clippy::too_many_arguments)]
+fn handle_face_op_hotkeys(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    edit_mode: Res<EditModeState>,
+    mut mesh_edit_state: ResMut<MeshEditState>,
+    mut active: ResMut<ActiveFaceOp>,
+    mut history: ResMut<MeshEditHistory>,
+    mut editable_query: Query<&mut EditableMesh>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut events: MessageWriter<MeshEditEvent>,
+) {
+    if edit_mode.mode != EditorMode::MeshEdit {
+        return;
+    }
+
+    // I again during an inset switches between region and individual faces
+    if let Some(running) = active.0.as_mut() {
+        if let FaceOpKind::Inset { individual } = running.kind
+            && keymap.just_pressed(actions::MESH_EDIT_INSET, &key_input)
+            && let Some((_, original)) = history.entries.last()
+            && let Ok(mut editable) = editable_query.get_mut(running.entity)
+        {
+            let mut mesh = original.clone();
+            match mesh.inset_faces(&running.faces, running.amount, !individual) {
+                Ok(offset) => {
+                    editable.half_edge_mesh = mesh;
+                    running.offset = offset;
+                    running.kind = FaceOpKind::Inset {
+                        individual: !individual,
+                    };
+                    mesh_edit_state.inset_individual = !individual;
+                    sync_bevy_mesh(&editable, &mut meshes);
+                    info!("Inset: individual faces {}", !individual);
+                }
+                Err(e) => warn!("Inset failed: {}", e),
+            }
+        }
+        return;
+    }
+
+    if keymap.just_pressed(actions::PAINT_UNDO, &key_input) {
+        events.write(MeshEditEvent::Undo);
+        return;
+    }
+
+    let kind = if keymap.just_pressed(actions::MESH_EDIT_EXTRUDE, &key_input) {
+        FaceOpKind::Extrude
+    } else if keymap.just_pressed(actions::MESH_EDIT_INSET, &key_input) {
+        FaceOpKind::Inset {
+            individual: mesh_edit_state.inset_individual,
+        }
+    } else {
+        return;
+    };
+
+    let Some(entity) = mesh_edit_state.target_entity else {
+        return;
+    };
+    let faces = selected_faces(&mesh_edit_state);
+    if faces.is_empty() {
+        info!("{:?}: select faces first", kind);
+        return;
+    }
+    let Ok(mut editable) = editable_query.get_mut(entity) else {
+        return;
+    };
+
+    let before = editable.half_edge_mesh.clone();
+    match run_face_op(&mut editable.half_edge_mesh, kind, &faces, 0.0) {
+        Ok(offset) => {
+            history.push(entity, before);
+            sync_bevy_mesh(&editable, &mut meshes);
+            mesh_edit_state.transform_active = true;
+            active.0 = Some(RunningFaceOp {
+                kind,
+                entity,
+                faces,
+                offset,
+                accumulated_delta: Vec2::ZERO,
+                amount: 0.0,
+            });
+            info!("{:?}: started", kind);
+        }
+        Err(e) => warn!("{:?} failed: {}", kind, e),
+    }
+}
+
+/// Apply extrude, inset, and undo requests with a fixed amount
+#[allow(clippy::too_many_arguments)]
+fn handle_face_op_events(
+    mut events: MessageReader<MeshEditEvent>,
+    mut mesh_edit_state: ResMut<MeshEditState>,
+    mut active: ResMut<ActiveFaceOp>,
+    mut history: ResMut<MeshEditHistory>,
+    mut editable_query: Query<&mut EditableMesh>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for event in events.read() {
+        let (kind, amount) = match event {
+            MeshEditEvent::Extrude { offset } => (FaceOpKind::Extrude, *offset),
+            MeshEditEvent::Inset { thickness } => (
+                FaceOpKind::Inset {
+                    individual: mesh_edit_state.inset_individual,
+                },
+                *thickness,
+            ),
+            MeshEditEvent::Undo => {
+                if active.0.is_some() {
+                    continue;
+                }
+                let Some((entity, mesh)) = history.pop() else {
+                    info!("Mesh edit undo: nothing to undo");
+                    continue;
+                };
+                if let Ok(mut editable) = editable_query.get_mut(entity) {
+                    editable.half_edge_mesh = mesh;
+                    sync_bevy_mesh(&editable, &mut meshes);
+                    // IDs created by the undone operation no longer exist
+                    mesh_edit_state.clear_selection();
+                    outbound.send(BevyToUi::MeshEditSelectionChanged {
+                        vertex_count: 0,
+                        edge_count: 0,
+                        face_count: 0,
+                    });
+                    info!("Mesh edit undo");
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let Some(entity) = mesh_edit_state.target_entity else {
+            continue;
+        };
+        let faces = selected_faces(&mesh_edit_state);
+        let Ok(mut editable) = editable_query.get_mut(entity) else {
+            continue;
+        };
+        let before = editable.half_edge_mesh.clone();
+        match run_face_op(&mut editable.half_edge_mesh, kind, &faces, amount) {
+            Ok(_) => {
+                history.push(entity, before);
+                sync_bevy_mesh(&editable, &mut meshes);
+            }
+            Err(e) => warn!("{:?} failed: {}", kind, e),
+        }
+    }
+}
+
+/// Follow the mouse while an operation is running
+fn drag_face_op(
+    mut motion_events: MessageReader<MouseMotion>,
+    mut active: ResMut<ActiveFaceOp>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    mut editable_query: Query<(&mut EditableMesh, &GlobalTransform)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Some(running) = active.0.as_mut() else {
+        motion_events.clear();
+        return;
+    };
+
+    let delta: Vec2 = motion_events.read().map(|event| event.delta).sum();
+    if delta == Vec2::ZERO {
+        return;
+    }
+    running.accumulated_delta += delta;
+
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    let Ok((mut editable, mesh_transform)) = editable_query.get_mut(running.entity) else {
+        return;
+    };
+
+    let drag = running.accumulated_delta;
+    // Amounts are in mesh-local units
+    running.amount = match running.kind {
+        // Project mouse movement onto the extrusion axis, like an
+        // axis-constrained gizmo move
+        FaceOpKind::Extrude => {
+            let axis = mesh_transform
+                .affine()
+                .transform_vector3(running.offset.direction());
+            let world_axis = axis.normalize_or_zero();
+            let camera_right = camera_transform.rotation * Vec3::X;
+            let camera_up = camera_transform.rotation * Vec3::Y;
+            let along = camera_right.dot(world_axis) * drag.x - camera_up.dot(world_axis) * drag.y;
+            along * DRAG_SENSITIVITY / axis.length().max(f32::EPSILON)
+        }
+        // Horizontal movement sets the thickness, like gizmo scaling
+        FaceOpKind::Inset { .. } => {
+            let scale = mesh_transform.compute_transform().scale.max_element();
+            (drag.x * DRAG_SENSITIVITY / scale.max(f32::EPSILON)).max(0.0)
+        }
+    };
+
+    running
+        .offset
+        .apply(&mut editable.half_edge_mesh, running.amount);
+    sync_bevy_mesh(&editable, &mut meshes);
+}
+
+/// Confirm or cancel the running operation
+fn finish_face_op(
+    key_input: Res<ButtonInput<KeyCode>>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mut active: ResMut<ActiveFaceOp>,
+    mut mesh_edit_state: ResMut<MeshEditState>,
+    mut history: ResMut<MeshEditHistory>,
+    mut editable_query: Query<&mut EditableMesh>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Some(running) = active.0.as_ref() else {
+        return;
+    };
+
+    if key_input.just_pressed(KeyCode::Escape) || mouse_button.just_pressed(MouseButton::Right) {
+        if let Some((entity, mesh)) = history.pop()
+            && let Ok(mut editable) = editable_query.get_mut(entity)
+        {
+            editable.half_edge_mesh = mesh;
+            sync_bevy_mesh(&editable, &mut meshes);
+        }
+        info!("{:?}: cancelled", running.kind);
+    } else if key_input.just_pressed(KeyCode::Enter) || mouse_button.just_pressed(MouseButton::Left)
+    {
+        info!("{:?}: confirmed at {:.3}", running.kind, running.amount);
+    } else {
+        return;
+    }
+
+    active.0 = None;
+    mesh_edit_state.transform_active = false;
+}
+
+/// Selected faces in a stable order
+fn selected_faces(state: &MeshEditState) -> Vec<FaceId> {
+    let mut faces: Vec<FaceId> = state.selected_faces.iter().copied().collect();
+    faces.sort_by_key(|face| face.0);
+    faces
+}
+
+/// Run an operation on `faces` at a fixed amount
+fn run_face_op(
+    mesh: &mut HalfEdgeMesh,
+    kind: FaceOpKind,
+    faces: &[FaceId],
+    amount: f32,
+) -> Result<FaceOffset, HalfEdgeError> {
+    match kind {
+        FaceOpKind::Extrude => mesh.extrude_faces(faces, amount),
+        FaceOpKind::Inset { individual } => mesh.inset_faces(faces, amount, individual),
+    }
+}
+
+/// Write the edited topology back to the displayed mesh
+fn sync_bevy_mesh(editable: &EditableMesh, meshes: &mut Assets<Mesh>) {
+    if let Some(mesh) = meshes.get_mut(&editable.original_mesh_handle) {
+        *mesh = editable.half_edge_mesh.to_bevy_mesh();
+    }
+}
@@ -1,15 +1,20 @@
 //! Sub-object selection for mesh edit mode
 //!
 //! Handles clicking to select vertices, edges, or faces within a mesh.
+//! - Alt+click an edge: select its edge loop
+//! - Ctrl+Alt+click an edge: select its edge ring
+//! - Double-click a face: select the connected region bounded by UV seams
+//!
+//! Shift adds to the selection, or removes a loop/region already selected.
 
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use painting::half_edge::{FaceId, HalfEdgeId, HalfEdgeMesh, VertexId};
-use pentimento_ipc::{BevyToUi, EditMode, MeshSelectionMode};
+use pentimento_ipc::{BevyToUi, MeshSelectionMode};
 
 use crate::OutboundUiMessages;
 use crate::camera::MainCamera;
-use crate::edit_mode::EditModeState;
+use crate::edit_mode::{EditModeState, EditorMode};
 use crate::mesh_edit_mode::{EditableMesh, MeshEditState};
 
 /// Result of a sub-object raycast
@@ -31,6 +36,8 @@ pub enum SubObjectHitType {
 const VERTEX_SELECT_THRESHOLD: f32 = 0.1;
 /// Threshold for edge selection (in world units, scaled by distance)
 const EDGE_SELECT_THRESHOLD: f32 = 0.05;
+/// Longest gap between two clicks on a face that counts as a double-click
+const DOUBLE_CLICK_SECONDS: f32 = 0.3;
 
 /// Plugin for sub-object selection
 pub struct MeshEditSelectionPlugin;
@@ -42,9 +49,12 @@ impl Plugin for MeshEditSelectionPlugin {
 }
 
 /// Handle mouse clicks for sub-object selection
-fn handle_sub_object_click(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn handle_sub_object_click(
     mouse_button: Res<ButtonInput<MouseButton>>,
     key_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut last_face_click: Local<Option<(f32, FaceId)>>,
     windows: Query<&Window, With<PrimaryWindow>>,
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     edit_mode: Res<EditModeState>,
@@ -52,8 +62,8 @@ fn handle_sub_object_click(
     editable_query: Query<(&EditableMesh, &GlobalTransform)>,
     mut outbound: ResMut<OutboundUiMessages>,
 ) {
-    // Only handle in mesh edit mode
-    if edit_mode.mode != EditMode::MeshEdit {
+    // Only handle in mesh edit mode, outside of a running extrude or inset
+    if edit_mode.mode != EditorMode::MeshEdit || mesh_edit_state.transform_active {
         return;
     }
 
@@ -89,15 +99,34 @@ fn handle_sub_object_click(
 
     let shift_held =
         key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
+    let alt_held = key_input.pressed(KeyCode::AltLeft) || key_input.pressed(KeyCode::AltRight);
+    let ctrl_held =
+        key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
 
     // Perform raycast and selection
-    if let Some(hit) = raycast_sub_object(
-        &ray,
-        &editable.half_edge_mesh,
-        mesh_transform,
-        mesh_edit_state.selection_mode,
-    ) {
-        handle_selection_hit(&mut mesh_edit_state, hit, shift_held);
+    let mesh = &editable.half_edge_mesh;
+    if let Some(hit) =
+        raycast_sub_object(&ray, mesh, mesh_transform, mesh_edit_state.selection_mode)
+    {
+        // Second click on the same face in time
+        let now = time.elapsed_secs();
+        let double_click = match hit.hit_type {
+            SubObjectHitType::Face(face_id) => {
+                let repeat = last_face_click
+                    .is_some_and(|(at, last)| last == face_id && now - at <= DOUBLE_CLICK_SECONDS);
+                *last_face_click = (!repeat).then_some((now, face_id));
+                repeat
+            }
+            _ => {
+                *last_face_click = None;
+                false
+            }
+        };
+
+        match expand_hit(mesh, hit.hit_type, alt_held, ctrl_held, double_click) {
+            Some(elements) => handle_multi_selection(&mut mesh_edit_state, elements, shift_held),
+            None => handle_selection_hit(&mut mesh_edit_state, hit, shift_held),
+        }
         send_selection_update(&mesh_edit_state, &mut outbound);
     } else if !shift_held {
         // Click on empty space without shift - deselect all
@@ -312,6 +341,77 @@ fn handle_selection_hit(state: &mut MeshEditState, hit: SubObjectHit, shift_held
     }
 }
 
+/// Grow a click into a loop, ring, or region:
+/// Alt = edge loop, Ctrl+Alt = edge ring, double-click = face region
+fn expand_hit(
+    mesh: &HalfEdgeMesh,
+    hit_type: SubObjectHitType,
+    alt_held: bool,
+    ctrl_held: bool,
+    double_click: bool,
+) -> Option<Vec<SubObjectHitType>> {
+    let elements: Vec<SubObjectHitType> = match hit_type {
+        SubObjectHitType::Edge(he_id) if alt_held && ctrl_held => mesh
+            .edge_ring(he_id)
+            .into_iter()
+            .map(SubObjectHitType::Edge)
+            .collect(),
+        SubObjectHitType::Edge(he_id) if alt_held => mesh
+            .edge_loop(he_id)
+            .into_iter()
+            .map(SubObjectHitType::Edge)
+            .collect(),
+        SubObjectHitType::Face(face_id) if double_click => mesh
+            .face_region(face_id)
+            .into_iter()
+            .map(SubObjectHitType::Face)
+            .collect(),
+        _ => return None,
+    };
+    Some(elements)
+}
+
+/// Select several elements at once; Shift adds them, or removes them when
+/// they are all selected already
+fn handle_multi_selection(
+    state: &mut MeshEditState,
+    elements: Vec<SubObjectHitType>,
+    shift_held: bool,
+) {
+    let is_selected = |state: &MeshEditState, element: &SubObjectHitType| match element {
+        SubObjectHitType::Vertex(vid) => state.selected_vertices.contains(vid),
+        SubObjectHitType::Edge(heid) => state.selected_edges.contains(heid),
+        SubObjectHitType::Face(fid) => state.selected_faces.contains(fid),
+    };
+    let deselect = shift_held && elements.iter().all(|e| is_selected(state, e));
+    if !shift_held {
+        state.clear_selection();
+    }
+
+    for element in elements {
+        match (element, deselect) {
+            (SubObjectHitType::Vertex(vid), false) => {
+                state.selected_vertices.insert(vid);
+            }
+            (SubObjectHitType::Vertex(vid), true) => {
+                state.selected_vertices.remove(&vid);
+            }
+            (SubObjectHitType::Edge(heid), false) => {
+                state.selected_edges.insert(heid);
+            }
+            (SubObjectHitType::Edge(heid), true) => {
+                state.selected_edges.remove(&heid);
+            }
+            (SubObjectHitType::Face(fid), false) => {
+                state.selected_faces.insert(fid);
+            }
+            (SubObjectHitType::Face(fid), true) => {
+                state.selected_faces.remove(&fid);
+            }
+        }
+    }
+}
+
 /// Send selection update to UI
 fn send_selection_update(state: &MeshEditState, outbound: &mut OutboundUiMessages) {
     outbound.send(BevyToUi::MeshEditSelectionChanged {
@@ -7,9 +7,26 @@
 //! # Architecture
 //!
 //! - `PaintableMesh` component marks meshes that can be painted on
+//! - Mesh paint mode (`EditorMode::MeshPaint`) needs a visible, unlocked
+//!   paintable mesh selected; leaving it ends the stroke in progress
 //! - Ray-mesh intersection finds the hit point and triangle
 //! - Vertex data (position, normal, UV, tangent) is interpolated using barycentric coords
 //! - `MeshPaintEvent` messages are emitted for the painting system to process
+//! - UV-atlas meshes without usable UVs get generated atlas UVs when paint
+//!   mode starts (see `painting::uv_unwrap`)
+//! - `MeshPaintEvent::ConvertStorage` moves paint between UV atlas and Ptex
+//!   storage (handled by the mesh painting system)
+//! - UV-atlas dabs are scaled by the hit face's texel density so brushes keep
+//!   their world size across charts; `MeshPaintEvent::ShowTexelDensity` tints
+//!   meshes by density to spot uneven packing
+//! - `MeshPaintEvent::SetTargetChannel` switches strokes between base color
+//!   and the roughness, metallic, normal, and emissive channels, each painted
+//!   into its own surface and material texture
+//! - `MeshPaintEvent::SetPaintTarget` switches strokes between the paint
+//!   texture and the mesh's vertex colors
+
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 use bevy::ecs::message::Message;
 use bevy::input::mouse::MouseButton;
@@ -17,11 +34,18 @@ use bevy::mesh::{Indices, VertexAttributeValues};
 use bevy::prelude::*;
 use bevy::window::{CursorMoved, PrimaryWindow};
 
+use painting::half_edge::{HalfEdgeError, HalfEdgeMesh};
 use painting::projection::build_tangent_space;
 use painting::types::{MeshHit, MeshStorageMode};
+use painting::uv_unwrap::generate_atlas_uvs;
+use pentimento_ipc::{MeshPaintChannel, MeshPaintTarget};
 
+use crate::brush_navigation::BrushNavigation;
 use crate::camera::MainCamera;
-use crate::paint_mode::{PaintMode, StrokeIdGenerator};
+use crate::edit_mode::{EditModeAppExt, EditModeState, EditorMode};
+use crate::paint_mode::StrokeIdGenerator;
+use crate::scene_bvh::SceneBvh;
+use crate::selection::{Locked, Selected};
 
 /// Component marking a mesh as paintable
 #[derive(Component)]
@@ -32,6 +56,16 @@ pub struct PaintableMesh {
     pub storage_mode: MeshStorageMode,
 }
 
+/// Gap between the charts of generated UV atlases, in texels
+const ATLAS_PADDING_PX: u32 = 4;
+
+/// Marks a mesh whose atlas UVs were generated rather than authored
+#[derive(Component)]
+pub(crate) struct GeneratedAtlasUvs {
+    /// Topology the UVs were generated for (see `topology_signature`)
+    topology: u64,
+}
+
 /// Resource for generating unique mesh IDs
 #[derive(Resource, Default)]
 pub struct MeshIdGenerator {
@@ -97,6 +131,42 @@ pub enum MeshPaintEvent {
     StrokeEnd,
     /// Stroke was cancelled
     StrokeCancel,
+    /// Move a mesh's paint to another storage mode (see
+    /// `painting::mesh_storage_conversion`)
+    ConvertStorage {
+        /// The mesh entity whose paint is converted
+        mesh_entity: Entity,
+        /// Storage mode to convert to
+        target: MeshStorageMode,
+    },
+    /// Toggle the texel density debug view (vertex colors: red where texels
+    /// are denser than the mesh average, blue where sparser)
+    ShowTexelDensity {
+        /// Whether to show the view
+        enabled: bool,
+    },
+    /// Paint later strokes into another material channel
+    SetTargetChannel {
+        /// Channel to paint
+        channel: MeshPaintChannel,
+    },
+    /// Set the value roughness and metallic strokes paint (0.0-1.0)
+    SetBrushValue {
+        /// Scalar channel value
+        value: f32,
+    },
+    /// Set how far normal strokes tilt the surface (0.0-1.0)
+    SetNormalStrength {
+        /// Tilt strength; 1.0 leans the normal 45 degrees along the stroke
+        strength: f32,
+    },
+    /// Paint later strokes into the paint texture or the vertex colors
+    SetPaintTarget {
+        /// Where strokes are stored
+        target: MeshPaintTarget,
+    },
+    /// Undo the last mesh stroke, restoring the channel it painted
+    Undo,
 }
 
 /// Plugin for mesh painting functionality
@@ -109,26 +179,190 @@ impl Plugin for MeshPaintModePlugin {
         app.init_resource::<MeshIdGenerator>()
             .init_resource::<MeshPaintState>()
             .add_message::<MeshPaintEvent>()
-            .add_systems(Update, handle_mesh_paint_input);
+            .add_systems(
+                Update,
+                (generate_missing_atlas_uvs, handle_mesh_paint_input).chain(),
+            )
+            .set_edit_mode_guard(EditorMode::MeshPaint, mesh_paint_mode_guard)
+            .on_exit_edit_mode(EditorMode::MeshPaint, exit_mesh_paint_mode);
+    }
+}
+
+/// Mesh paint mode needs a visible, unlocked paintable mesh: the requested
+/// one, or the selected one
+fn mesh_paint_mode_guard(
+    In(target): In<Option<Entity>>,
+    selected: Query<Entity, (With<Selected>, With<PaintableMesh>)>,
+    paintables: Query<&InheritedVisibility, (With<PaintableMesh>, Without<Locked>)>,
+) -> Result<Option<Entity>, String> {
+    let entity = target
+        .or_else(|| selected.single().ok())
+        .ok_or_else(|| "Select a paintable mesh to paint on".to_string())?;
+    match paintables.get(entity) {
+        Ok(visibility) if visibility.get() => Ok(Some(entity)),
+        Ok(_) => Err("Hidden meshes can't be painted".to_string()),
+        Err(_) => Err("The selected mesh is locked or not paintable".to_string()),
+    }
+}
+
+/// End the stroke in progress, so it lands before the mode changes
+fn exit_mesh_paint_mode(
+    mut mesh_paint_state: ResMut<MeshPaintState>,
+    mut mesh_paint_events: MessageWriter<MeshPaintEvent>,
+) {
+    if mesh_paint_state.current_stroke.take().is_some() {
+        mesh_paint_events.write(MeshPaintEvent::StrokeEnd);
+        info!("Mesh stroke ended by leaving mesh paint mode");
+    }
+    mesh_paint_state.active_mesh = None;
+}
+
+/// Generate atlas UVs when mesh paint mode starts on a UV-atlas mesh without
+/// usable UVs, and again when the mesh's topology changed since
+fn generate_missing_atlas_uvs(
+    mut commands: Commands,
+    edit_mode: Res<EditModeState>,
+    mut was_active: Local<bool>,
+    mut mesh_events: MessageReader<AssetEvent<Mesh>>,
+    paintables: Query<(Entity, &PaintableMesh, &Mesh3d, Option<&GeneratedAtlasUvs>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let active = edit_mode.mode == EditorMode::MeshPaint;
+    let entered = active && !*was_active;
+    *was_active = active;
+    let modified: HashSet<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if !active {
+        return;
+    }
+
+    for (entity, paintable, mesh3d, generated) in &paintables {
+        let MeshStorageMode::UvAtlas { resolution } = paintable.storage_mode else {
+            continue;
+        };
+        if !entered && !modified.contains(&mesh3d.0.id()) {
+            continue;
+        }
+        let Some(mesh) = meshes.get(&mesh3d.0) else {
+            continue;
+        };
+        // Authored UVs are left alone
+        if generated.is_none() && has_usable_uvs(mesh) {
+            continue;
+        }
+        let half_edge_mesh = match HalfEdgeMesh::from_bevy_mesh(mesh) {
+            Ok(half_edge_mesh) => half_edge_mesh,
+            Err(e) => {
+                warn!(
+                    "Cannot generate UVs for mesh {}: {:?}",
+                    paintable.mesh_id, e
+                );
+                continue;
+            }
+        };
+        if generated.is_some_and(|g| g.topology == topology_signature(&half_edge_mesh)) {
+            continue;
+        }
+
+        let (unwrapped, generated) = match unwrap_atlas_mesh(&half_edge_mesh, resolution) {
+            Ok(unwrapped) => unwrapped,
+            Err(e) => {
+                warn!(
+                    "Generated UV mesh {} is invalid: {:?}",
+                    paintable.mesh_id, e
+                );
+                continue;
+            }
+        };
+
+        if let Some(mesh) = meshes.get_mut(&mesh3d.0) {
+            *mesh = unwrapped;
+        }
+        commands.entity(entity).insert(generated);
+        info!(
+            "Generated atlas UVs for mesh {} ({}x{})",
+            paintable.mesh_id, resolution.0, resolution.1
+        );
     }
 }
 
+/// Copy of `half_edge_mesh` with generated atlas UVs, and the marker that
+/// records them.
+///
+/// Faces keep their order, so face ids of the source mesh stay valid.
+pub(crate) fn unwrap_atlas_mesh(
+    half_edge_mesh: &HalfEdgeMesh,
+    resolution: (u32, u32),
+) -> Result<(Mesh, GeneratedAtlasUvs), HalfEdgeError> {
+    let uvs = generate_atlas_uvs(half_edge_mesh, resolution, ATLAS_PADDING_PX);
+    let mut unwrapped = half_edge_mesh.to_bevy_mesh();
+    unwrapped.insert_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        uvs.iter().map(|uv| uv.to_array()).collect::<Vec<_>>(),
+    );
+    // Signature of the mesh as stored, which is what the next check reads
+    let stored = HalfEdgeMesh::from_bevy_mesh(&unwrapped)?;
+    let topology = topology_signature(&stored);
+    Ok((unwrapped, GeneratedAtlasUvs { topology }))
+}
+
+/// UVs that can address an atlas: present and not all the same point
+/// (`HalfEdgeMesh::to_bevy_mesh` writes zeros for meshes without UVs)
+pub(crate) fn has_usable_uvs(mesh: &Mesh) -> bool {
+    match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => uvs.iter().any(|uv| *uv != uvs[0]),
+        _ => false,
+    }
+}
+
+/// Hash of the face-vertex connectivity
+fn topology_signature(mesh: &HalfEdgeMesh) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    mesh.vertex_count().hash(&mut hasher);
+    for face in mesh.faces() {
+        let vertices = mesh.get_face_vertices(face.id);
+        vertices.len().hash(&mut hasher);
+        for vertex in vertices {
+            vertex.0.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
 /// Handle mesh painting input
 fn handle_mesh_paint_input(
     mouse_button: Res<ButtonInput<MouseButton>>,
     windows: Query<(Entity, &Window), With<PrimaryWindow>>,
     mut cursor_events: MessageReader<CursorMoved>,
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    mesh_query: Query<(Entity, &PaintableMesh, &Mesh3d, &GlobalTransform)>,
+    mesh_query: PaintableMeshQuery,
     meshes: Res<Assets<Mesh>>,
-    paint_mode: Res<PaintMode>,
+    edit_mode: Res<EditModeState>,
     mut mesh_paint_state: ResMut<MeshPaintState>,
     mut stroke_id_gen: ResMut<StrokeIdGenerator>,
     mut mesh_paint_events: MessageWriter<MeshPaintEvent>,
     time: Res<Time>,
+    bvh: Res<SceneBvh>,
+    navigation: Res<BrushNavigation>,
 ) {
-    // Only process if paint mode is active
-    if !paint_mode.active {
+    // Only process in mesh paint mode
+    if edit_mode.mode != EditorMode::MeshPaint {
+        return;
+    }
+
+    // The camera has the mouse: finish the stroke so it is logged whole
+    if navigation.navigating {
+        cursor_events.clear();
+        if mesh_paint_state.current_stroke.take().is_some() {
+            mesh_paint_events.write(MeshPaintEvent::StrokeEnd);
+            mesh_paint_state.active_mesh = None;
+            info!("Mesh stroke ended for navigation");
+        }
         return;
     }
 
@@ -153,6 +387,9 @@ fn handle_mesh_paint_input(
 
     // Handle stroke start
     if mouse_button.just_pressed(MouseButton::Left) {
+        if !navigation.starts_stroke() {
+            return;
+        }
         let cursor_pos = cursor_positions
             .last()
             .copied()
@@ -161,7 +398,7 @@ fn handle_mesh_paint_input(
             if let Some(ray) = camera.viewport_to_world(camera_transform, cursor_pos).ok() {
                 // Find closest mesh hit
                 if let Some((entity, paintable, hit)) =
-                    find_closest_mesh_hit(&ray, &mesh_query, &meshes)
+                    find_closest_mesh_hit(&ray, &mesh_query, &meshes, &bvh)
                 {
                     let stroke_id = stroke_id_gen.next();
                     let mesh_id = paintable.mesh_id;
@@ -242,34 +479,39 @@ fn handle_mesh_paint_input(
     }
 }
 
+/// Meshes strokes can land on; locked ones are skipped
+type PaintableMeshQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static PaintableMesh,
+        &'static Mesh3d,
+        &'static GlobalTransform,
+    ),
+    Without<Locked>,
+>;
+
 /// Find the closest paintable mesh hit by a ray
+///
+/// The scene BVH finds the mesh; its hit data (UV, tangent space) then comes
+/// from [`ray_mesh_intersection`] on that mesh alone.
 fn find_closest_mesh_hit<'a>(
     ray: &Ray3d,
-    mesh_query: &'a Query<(Entity, &PaintableMesh, &Mesh3d, &GlobalTransform)>,
+    mesh_query: &'a PaintableMeshQuery,
     meshes: &Assets<Mesh>,
+    bvh: &SceneBvh,
 ) -> Option<(Entity, &'a PaintableMesh, MeshHit)> {
-    let mut closest: Option<(Entity, &PaintableMesh, MeshHit, f32)> = None;
-
-    for (entity, paintable, mesh_handle, transform) in mesh_query.iter() {
-        let Some(mesh) = meshes.get(&mesh_handle.0) else {
-            continue;
-        };
-
-        if let Some(hit) = ray_mesh_intersection(ray, mesh, transform) {
-            let distance = ray.origin.distance(hit.world_pos);
-            if closest.is_none() || distance < closest.as_ref().unwrap().3 {
-                closest = Some((entity, paintable, hit, distance));
-            }
-        }
-    }
-
-    closest.map(|(e, p, h, _)| (e, p, h))
+    let scene_hit = bvh.raycast(*ray, |entity| mesh_query.contains(entity))?;
+    let (entity, paintable, mesh_handle, transform) = mesh_query.get(scene_hit.entity).ok()?;
+    let hit = ray_mesh_intersection(ray, meshes.get(&mesh_handle.0)?, transform)?;
+    Some((entity, paintable, hit))
 }
 
 /// Perform ray-mesh intersection and return hit data
 ///
-/// This performs a brute-force triangle intersection test. For large meshes,
-/// a BVH acceleration structure would be more efficient.
+/// This performs a brute-force triangle intersection test; use the
+/// [`SceneBvh`] to find which mesh a ray hits first.
 pub fn ray_mesh_intersection(
     ray: &Ray3d,
     mesh: &Mesh,
@@ -492,6 +734,29 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_generated_atlas_uvs_are_usable() {
+        let mut source = Torus::default().mesh().build();
+        source.remove_attribute(Mesh::ATTRIBUTE_UV_0);
+        let half_edge_mesh = HalfEdgeMesh::from_bevy_mesh(&source).unwrap();
+
+        // Round-tripping a UV-less mesh leaves placeholder UVs
+        let mut unwrapped = half_edge_mesh.to_bevy_mesh();
+        assert!(!has_usable_uvs(&source));
+        assert!(!has_usable_uvs(&unwrapped));
+
+        let uvs = generate_atlas_uvs(&half_edge_mesh, (256, 256), ATLAS_PADDING_PX);
+        unwrapped.insert_attribute(
+            Mesh::ATTRIBUTE_UV_0,
+            uvs.iter().map(|uv| uv.to_array()).collect::<Vec<_>>(),
+        );
+        assert!(has_usable_uvs(&unwrapped));
+
+        // Welding the per-corner vertices gives back the same surface
+        let stored = HalfEdgeMesh::from_bevy_mesh(&unwrapped).unwrap();
+        assert_eq!(stored.face_count(), half_edge_mesh.face_count());
+    }
+
     #[test]
     fn test_ray_triangle_intersection_parallel() {
         // Triangle in XY plane at z=0
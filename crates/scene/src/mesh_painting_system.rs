@@ -2,17 +2,68 @@
 //!
 //! This module connects MeshPaintEvent messages to mesh painting surfaces
 //! and handles GPU texture upload for painted meshes.
+//!
+//! `MeshPaintEvent::ConvertStorage` runs a time-sliced storage conversion
+//! (see `painting::mesh_storage_conversion`). Strokes on the mesh are ignored
+//! until it finishes; the converted surface, storage mode, paint texture and
+//! (for generated atlas UVs) the mesh are then swapped in the same frame.
+//!
+//! UV-atlas dabs are sized by the hit face's texel density relative to the
+//! mesh average (see `pixel_coverage::estimate_texel_density_cpu`), so a brush
+//! covers the same world area on charts packed at different densities.
+//! Dabs near a UV seam are repeated in the adjacent chart and each frame's
+//! dirty tiles are padded into the chart margins (see `painting::uv_seams`),
+//! so strokes across seams leave no gap.
+//!
+//! UV-atlas strokes paint the channel chosen with
+//! `MeshPaintEvent::SetTargetChannel`. Each channel other than base color
+//! gets its own surface on its first stroke, composited into a 16-bit
+//! material texture: roughness and metallic share one in the layout
+//! `StandardMaterial::metallic_roughness_texture` reads (green and blue),
+//! normals go to the normal map and emission to the emissive texture.
+//! Finished strokes keep the tiles they changed so `MeshPaintEvent::Undo`
+//! restores the channel they painted.
+//!
+//! With `MeshPaintEvent::SetPaintTarget` set to vertex colors, dabs blend the
+//! brush color into the mesh's `COLOR_0` attribute instead (see
+//! `painting::vertex_color`). The first such stroke seeds the attribute with
+//! the material's base color and turns the base color white, so the material
+//! shows the vertex colors. A per-mesh vertex octree finds the vertices in the
+//! brush radius and only the changed vertices are patched into the GPU vertex
+//! buffer (see `sculpting::patch_mesh_colors`), so a dab costs the vertices it
+//! covers rather than the size of the mesh. Finished strokes are logged as
+//! `VertexColorStroke` packets and keep their vertices' previous colors for
+//! undo.
 
 use bevy::asset::RenderAssetUsages;
+use bevy::ecs::system::SystemParam;
+use bevy::math::Affine3A;
+use bevy::mesh::VertexAttributeValues;
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use painting::BrushPreset;
+use painting::CpuSurface;
+use painting::constants::DEFAULT_SEAM_PADDING;
+use painting::half_edge::{HalfEdgeMesh, VertexId};
+use painting::mesh_storage_conversion::{MeshPaintSurface, StorageConversion};
 use painting::mesh_surface::{MeshPtexSurface, MeshUvSurface};
+use painting::projection::project_brush_to_surface;
+use painting::tiles::{TileCoord, TiledSurface};
 use painting::types::{BlendMode, MeshHit, MeshStorageMode};
+use painting::uv_seams::UvSeams;
+use painting::vertex_color::{VertexColorDab, VertexColorStroke, apply_vertex_color_dab};
+use pentimento_ipc::{BevyToUi, MeshPaintChannel, MeshPaintTarget};
+use sculpting::{MeshVertexPatchPlugin, MeshVertexPatches, VertexOctree, patch_mesh_colors};
 
-use crate::mesh_paint_mode::{MeshPaintEvent, PaintableMesh};
+use crate::OutboundUiMessages;
+use crate::mesh_paint_mode::{
+    GeneratedAtlasUvs, MeshPaintEvent, MeshPaintState, PaintableMesh, has_usable_uvs,
+    unwrap_atlas_mesh,
+};
+use crate::painting_system::{DirtyTileUploadBuffer, TextureUploadStats};
+use crate::pixel_coverage::{TexelDensity, estimate_texel_density_cpu};
 
 /// Resource holding painting surfaces for each paintable mesh
 #[derive(Resource)]
@@ -27,6 +78,100 @@ pub struct MeshPaintingResource {
     pub brush_preset: BrushPreset,
     /// Current blend mode
     pub blend_mode: BlendMode,
+    /// Material channel strokes paint into
+    pub target_channel: MeshPaintChannel,
+    /// Value roughness and metallic strokes paint (0.0-1.0)
+    pub brush_value: f32,
+    /// How far normal strokes tilt the surface (0.0-1.0)
+    pub normal_strength: f32,
+    /// UV surfaces of the channels other than base color, indexed by mesh_id
+    /// and channel; allocated on a channel's first stroke
+    channel_surfaces: HashMap<(u32, MeshPaintChannel), MeshUvSurface>,
+    /// Stroke in progress on a UV surface
+    stroke: Option<ActiveStroke>,
+    /// Finished UV strokes, oldest first
+    undo_stack: VecDeque<StrokeUndo>,
+    /// Running storage conversions indexed by mesh_id
+    conversions: HashMap<u32, ConversionJob>,
+    /// Seam edge lookups of UV-atlas meshes indexed by mesh_id
+    uv_seams: HashMap<u32, UvSeams>,
+    /// Per-face texel densities of UV-atlas meshes indexed by mesh_id
+    texel_densities: HashMap<u32, TexelDensity>,
+    /// Whether the texel density debug view is on
+    show_texel_density: bool,
+    /// Densities or the view toggle changed since the view was last updated
+    texel_density_view_dirty: bool,
+    /// Whether strokes paint the paint texture or the vertex colors
+    pub paint_target: MeshPaintTarget,
+    /// Vertex octrees of meshes painted with vertex colors, indexed by mesh_id
+    vertex_octrees: HashMap<u32, MeshVertexOctree>,
+    /// Vertex color stroke in progress
+    vertex_stroke: Option<ActiveVertexStroke>,
+    /// Finished vertex color strokes, oldest first
+    vertex_color_log: Vec<VertexColorStroke>,
+    /// Vertex colors put back by undo, written to their meshes by
+    /// `process_mesh_paint_events`
+    vertex_color_restores: Vec<(Entity, Vec<(u32, [f32; 4])>)>,
+}
+
+/// Strokes `MeshPaintEvent::Undo` can take back
+const MESH_UNDO_LIMIT: usize = 32;
+
+/// A UV stroke in progress
+struct ActiveStroke {
+    stroke_id: u64,
+    mesh_id: u32,
+    channel: MeshPaintChannel,
+    /// Pixels of the painted UV surface before the stroke (empty for Ptex)
+    before: Vec<[f32; 4]>,
+    /// Where the last dab landed, giving normal strokes their direction
+    last_pos: Option<Vec3>,
+}
+
+/// What a finished stroke changed, with the values from before it
+struct StrokeUndo {
+    stroke_id: u64,
+    mesh_id: u32,
+    changes: StrokeChanges,
+}
+
+enum StrokeChanges {
+    /// Tiles of a UV surface channel
+    Tiles {
+        channel: MeshPaintChannel,
+        tiles: Vec<(TileCoord, Vec<[f32; 4]>)>,
+    },
+    /// Vertex colors of a mesh entity, sorted by vertex index
+    VertexColors {
+        entity: Entity,
+        colors: Vec<(u32, [f32; 4])>,
+    },
+}
+
+/// A vertex color stroke in progress
+struct ActiveVertexStroke {
+    entity: Entity,
+    /// Dabs so far, in the mesh's local space
+    record: VertexColorStroke,
+    /// Color of each painted vertex before the stroke
+    before: HashMap<u32, [f32; 4]>,
+}
+
+/// Octree over a mesh's local vertex positions
+struct MeshVertexOctree {
+    mesh: AssetId<Mesh>,
+    vertex_count: usize,
+    octree: VertexOctree,
+}
+
+/// A running storage conversion and what to swap in once it finishes
+struct ConversionJob {
+    entity: Entity,
+    target: MeshStorageMode,
+    conversion: StorageConversion,
+    /// Mesh with generated atlas UVs, installed with the converted paint
+    unwrapped: Option<(Mesh, GeneratedAtlasUvs)>,
+    last_reported: f32,
 }
 
 impl Default for MeshPaintingResource {
@@ -44,6 +189,22 @@ impl MeshPaintingResource {
             brush_color: [0.0, 0.0, 0.0, 1.0],
             brush_preset: BrushPreset::default(),
             blend_mode: BlendMode::Normal,
+            target_channel: MeshPaintChannel::BaseColor,
+            brush_value: 0.5,
+            normal_strength: 0.5,
+            channel_surfaces: HashMap::new(),
+            stroke: None,
+            undo_stack: VecDeque::new(),
+            conversions: HashMap::new(),
+            uv_seams: HashMap::new(),
+            texel_densities: HashMap::new(),
+            show_texel_density: false,
+            texel_density_view_dirty: false,
+            paint_target: MeshPaintTarget::Texture,
+            vertex_octrees: HashMap::new(),
+            vertex_stroke: None,
+            vertex_color_log: Vec::new(),
+            vertex_color_restores: Vec::new(),
         }
     }
 
@@ -56,7 +217,7 @@ impl MeshPaintingResource {
     ) -> &mut MeshUvSurface {
         self.uv_surfaces
             .entry(mesh_id)
-            .or_insert_with(|| MeshUvSurface::new(mesh_id, width, height, 2))
+            .or_insert_with(|| MeshUvSurface::new(mesh_id, width, height, DEFAULT_SEAM_PADDING))
     }
 
     /// Get or create a Ptex surface for a mesh.
@@ -90,6 +251,296 @@ impl MeshPaintingResource {
         self.ptex_surfaces.get_mut(&mesh_id)
     }
 
+    /// Get a mesh's UV surface for a channel, if it has been painted.
+    pub fn get_channel_surface(
+        &self,
+        mesh_id: u32,
+        channel: MeshPaintChannel,
+    ) -> Option<&MeshUvSurface> {
+        match channel {
+            MeshPaintChannel::BaseColor => self.uv_surfaces.get(&mesh_id),
+            _ => self.channel_surfaces.get(&(mesh_id, channel)),
+        }
+    }
+
+    fn channel_surface_mut(
+        &mut self,
+        mesh_id: u32,
+        channel: MeshPaintChannel,
+    ) -> Option<&mut MeshUvSurface> {
+        match channel {
+            MeshPaintChannel::BaseColor => self.uv_surfaces.get_mut(&mesh_id),
+            _ => self.channel_surfaces.get_mut(&(mesh_id, channel)),
+        }
+    }
+
+    /// Whether a mesh has paint in any channel other than base color.
+    pub fn has_channel_paint(&self, mesh_id: u32) -> bool {
+        self.channel_surfaces.keys().any(|(id, _)| *id == mesh_id)
+    }
+
+    /// Whether a mesh's paint is being converted to another storage mode.
+    pub fn is_converting(&self, mesh_id: u32) -> bool {
+        self.conversions.contains_key(&mesh_id)
+    }
+
+    /// Texel density of a face relative to its mesh's average (1.0 when the
+    /// mesh's densities aren't known yet).
+    pub fn texel_density_factor(&self, mesh_id: u32, face_id: u32) -> f32 {
+        self.texel_densities
+            .get(&mesh_id)
+            .map_or(1.0, |density| density.factor(face_id))
+    }
+
+    /// Apply a dab of world radius `brush_size` to the stroke's channel of a
+    /// mesh's UV surfaces, sized by the hit face's texel density and repeated
+    /// across UV seams.
+    fn apply_uv_dab(&mut self, mesh_id: u32, hit: &MeshHit, uv: Vec2, brush_size: f32) {
+        let channel = self
+            .stroke
+            .as_ref()
+            .map_or(self.target_channel, |stroke| stroke.channel);
+        let color = self.dab_color(channel, hit);
+        if let Some(stroke) = self.stroke.as_mut() {
+            stroke.last_pos = Some(hit.world_pos);
+        }
+        let Some(color) = color else {
+            return;
+        };
+        let face_density = self.texel_density_factor(mesh_id, hit.face_id);
+        let opacity = self.brush_preset.opacity;
+        let hardness = self.brush_preset.hardness;
+        let blend_mode = self.blend_mode;
+        let surface = match channel {
+            MeshPaintChannel::BaseColor => self.uv_surfaces.get_mut(&mesh_id),
+            _ => self.channel_surfaces.get_mut(&(mesh_id, channel)),
+        };
+        let Some(surface) = surface else {
+            return;
+        };
+
+        // Convert world brush size to texture pixels: a fixed scale based on
+        // texture resolution, corrected per face for charts packed at
+        // different densities
+        let (width, height) = surface.dimensions();
+        let avg_res = (width + height) as f32 / 2.0;
+        let dab = project_brush_to_surface(
+            brush_size,
+            hit.world_pos,
+            uv,
+            hit.normal,
+            avg_res / 10.0,
+            face_density,
+        );
+        let radius = dab.size / 2.0;
+
+        surface.apply_dab(
+            dab.tex_pos,
+            radius,
+            color,
+            opacity,
+            hardness,
+            blend_mode,
+            dab.angle,
+            dab.aspect_ratio,
+        );
+        if let Some(seams) = self.uv_seams.get(&mesh_id) {
+            seams.apply_dab(
+                surface,
+                hit.face_id,
+                dab.tex_pos,
+                radius,
+                color,
+                opacity,
+                hardness,
+                blend_mode,
+                dab.angle,
+                dab.aspect_ratio,
+            );
+        }
+    }
+
+    /// Color a dab paints into `channel` at `hit`, or `None` for the first
+    /// dab of a normal stroke, which has no direction yet
+    fn dab_color(&self, channel: MeshPaintChannel, hit: &MeshHit) -> Option<[f32; 4]> {
+        match channel {
+            MeshPaintChannel::BaseColor | MeshPaintChannel::Emissive => Some(self.brush_color),
+            MeshPaintChannel::Roughness | MeshPaintChannel::Metallic => {
+                let value = self.brush_value.clamp(0.0, 1.0);
+                Some([value, value, value, 1.0])
+            }
+            MeshPaintChannel::Normal => {
+                let last_pos = self.stroke.as_ref()?.last_pos?;
+                tangent_space_normal(hit, hit.world_pos - last_pos, self.normal_strength)
+            }
+        }
+    }
+
+    /// Start a stroke into the target channel. UV meshes get the channel's
+    /// surface (at the base color atlas size) on its first stroke and keep
+    /// its pixels for undo; Ptex meshes only take base color.
+    fn begin_stroke(&mut self, stroke_id: u64, paintable: &PaintableMesh) {
+        self.end_stroke();
+        let channel = self.target_channel;
+        let mesh_id = paintable.mesh_id;
+        let before = match paintable.storage_mode {
+            MeshStorageMode::UvAtlas { resolution } => {
+                let (width, height) = self
+                    .uv_surfaces
+                    .get(&mesh_id)
+                    .map_or(resolution, |surface| surface.dimensions());
+                let surface = match channel {
+                    MeshPaintChannel::BaseColor => {
+                        self.get_or_create_uv_surface(mesh_id, width, height)
+                    }
+                    _ => self
+                        .channel_surfaces
+                        .entry((mesh_id, channel))
+                        .or_insert_with(|| {
+                            info!("Allocated {:?} paint surface for mesh {}", channel, mesh_id);
+                            MeshUvSurface::new(mesh_id, width, height, DEFAULT_SEAM_PADDING)
+                        }),
+                };
+                surface.surface().surface().pixels().to_vec()
+            }
+            MeshStorageMode::Ptex { .. } => {
+                if channel != MeshPaintChannel::BaseColor {
+                    warn!(
+                        "Ptex mesh {} only takes base color paint; ignoring the {:?} stroke",
+                        mesh_id, channel
+                    );
+                }
+                Vec::new()
+            }
+        };
+        self.stroke = Some(ActiveStroke {
+            stroke_id,
+            mesh_id,
+            channel,
+            before,
+            last_pos: None,
+        });
+    }
+
+    /// Start a vertex color stroke on `entity` with the current brush.
+    fn begin_vertex_stroke(&mut self, entity: Entity, mesh_id: u32, stroke_id: u64) {
+        self.end_stroke();
+        self.vertex_stroke = Some(ActiveVertexStroke {
+            entity,
+            record: VertexColorStroke::new(mesh_id, stroke_id, self.brush_color, self.blend_mode),
+            before: HashMap::new(),
+        });
+    }
+
+    /// Finished vertex color strokes, oldest first.
+    pub fn vertex_color_strokes(&self) -> &[VertexColorStroke] {
+        &self.vertex_color_log
+    }
+
+    /// Finish the stroke in progress, keeping what it changed for undo.
+    fn end_stroke(&mut self) {
+        self.end_vertex_stroke();
+        let Some(stroke) = self.stroke.take() else {
+            return;
+        };
+        let Some(surface) = self.get_channel_surface(stroke.mesh_id, stroke.channel) else {
+            return;
+        };
+        let tiles = changed_tiles(surface.surface(), &stroke.before);
+        if tiles.is_empty() {
+            return;
+        }
+        self.push_undo(StrokeUndo {
+            stroke_id: stroke.stroke_id,
+            mesh_id: stroke.mesh_id,
+            changes: StrokeChanges::Tiles {
+                channel: stroke.channel,
+                tiles,
+            },
+        });
+    }
+
+    /// Finish the vertex color stroke in progress, logging it and keeping the
+    /// previous colors of the vertices it painted for undo.
+    fn end_vertex_stroke(&mut self) {
+        let Some(stroke) = self.vertex_stroke.take() else {
+            return;
+        };
+        if stroke.before.is_empty() {
+            return;
+        }
+        let mut colors: Vec<(u32, [f32; 4])> = stroke.before.into_iter().collect();
+        colors.sort_unstable_by_key(|&(index, _)| index);
+        self.push_undo(StrokeUndo {
+            stroke_id: stroke.record.stroke_id,
+            mesh_id: stroke.record.mesh_id,
+            changes: StrokeChanges::VertexColors {
+                entity: stroke.entity,
+                colors,
+            },
+        });
+        self.vertex_color_log.push(stroke.record);
+    }
+
+    fn push_undo(&mut self, entry: StrokeUndo) {
+        self.undo_stack.push_back(entry);
+        if self.undo_stack.len() > MESH_UNDO_LIMIT {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Take back the last finished mesh stroke, restoring the tiles it
+    /// changed in the channel it painted, or queueing the vertex colors it
+    /// changed to be written back. Returns false with nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.end_stroke();
+        let Some(entry) = self.undo_stack.pop_back() else {
+            return false;
+        };
+        let (channel, tiles) = match entry.changes {
+            StrokeChanges::Tiles { channel, tiles } => (channel, tiles),
+            StrokeChanges::VertexColors { entity, colors } => {
+                if let Some(index) = self
+                    .vertex_color_log
+                    .iter()
+                    .rposition(|stroke| stroke.stroke_id == entry.stroke_id)
+                {
+                    self.vertex_color_log.remove(index);
+                }
+                info!(
+                    "Undid vertex color stroke {} (mesh {}, {} vertices)",
+                    entry.stroke_id,
+                    entry.mesh_id,
+                    colors.len()
+                );
+                self.vertex_color_restores.push((entity, colors));
+                return true;
+            }
+        };
+        let Some(surface) = self.channel_surface_mut(entry.mesh_id, channel) else {
+            return true;
+        };
+        let tiled = surface.surface_mut();
+        for (coord, pixels) in &tiles {
+            let (x, y, width, height) = tiled.get_tile_bounds(*coord);
+            let surface_width = tiled.surface().width as usize;
+            let surface_pixels = tiled.surface_mut().pixels_mut();
+            for (row, row_pixels) in pixels.chunks_exact(width as usize).enumerate() {
+                let start = (y as usize + row) * surface_width + x as usize;
+                surface_pixels[start..start + width as usize].copy_from_slice(row_pixels);
+            }
+            tiled.mark_region_dirty(x, y, width, height);
+        }
+        info!(
+            "Undid mesh stroke {} ({:?} of mesh {}, {} tiles)",
+            entry.stroke_id,
+            channel,
+            entry.mesh_id,
+            tiles.len()
+        );
+        true
+    }
+
     /// Set brush color.
     pub fn set_brush_color(&mut self, color: [f32; 4]) {
         self.brush_color = color;
@@ -119,6 +570,76 @@ pub struct MeshPaintTexture {
     pub needs_full_upload: bool,
     /// Whether any paint has been applied (don't touch material until painting)
     pub has_paint: bool,
+    /// 16-bit texture in the material's metallic/roughness slot, once either
+    /// channel is painted
+    pub metallic_roughness_image: Option<Handle<Image>>,
+    /// 16-bit tangent-space normal map, once normals are painted
+    pub normal_image: Option<Handle<Image>>,
+    /// 16-bit emissive texture, once emission is painted
+    pub emissive_image: Option<Handle<Image>>,
+    /// Material values that texels without channel paint keep
+    pub channel_defaults: ChannelDefaults,
+}
+
+/// Material values the roughness, metallic, and emissive textures composite
+/// paint over. The material's scalars become 1.0 (white for emission) once
+/// its textures carry them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelDefaults {
+    /// Perceptual roughness
+    pub roughness: f32,
+    /// Metalness
+    pub metallic: f32,
+    /// Linear emissive color
+    pub emissive: [f32; 3],
+}
+
+impl ChannelDefaults {
+    fn from_material(material: &StandardMaterial) -> Self {
+        Self {
+            roughness: material.perceptual_roughness,
+            metallic: material.metallic,
+            emissive: [
+                material.emissive.red,
+                material.emissive.green,
+                material.emissive.blue,
+            ],
+        }
+    }
+}
+
+impl Default for ChannelDefaults {
+    fn default() -> Self {
+        Self::from_material(&StandardMaterial::default())
+    }
+}
+
+/// Material textures the channels other than base color are composited into
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ChannelTexture {
+    /// Roughness in green, metallic in blue
+    MetallicRoughness,
+    Normal,
+    Emissive,
+}
+
+impl ChannelTexture {
+    const ALL: [Self; 3] = [Self::MetallicRoughness, Self::Normal, Self::Emissive];
+
+    /// Channels read into the texture, in `channel_texel` order
+    fn channels(self) -> &'static [MeshPaintChannel] {
+        match self {
+            Self::MetallicRoughness => &[MeshPaintChannel::Roughness, MeshPaintChannel::Metallic],
+            Self::Normal => &[MeshPaintChannel::Normal],
+            Self::Emissive => &[MeshPaintChannel::Emissive],
+        }
+    }
+}
+
+/// Vertex colors a mesh had before the texel density view tinted it
+#[derive(Component)]
+struct TexelDensityTint {
+    original: Option<VertexAttributeValues>,
 }
 
 /// Plugin for mesh painting system.
@@ -126,11 +647,21 @@ pub struct MeshPaintingSystemPlugin;
 
 impl Plugin for MeshPaintingSystemPlugin {
     fn build(&self, app: &mut App) {
+        // Vertex color dabs patch vertex buffers in place, like sculpt updates
+        if !app.is_plugin_added::<MeshVertexPatchPlugin>() {
+            app.add_plugins(MeshVertexPatchPlugin);
+        }
         app.init_resource::<MeshPaintingResource>().add_systems(
             Update,
             (
                 setup_mesh_paint_textures,
+                update_texel_densities,
+                update_uv_seams,
+                start_storage_conversions,
                 process_mesh_paint_events,
+                run_storage_conversions,
+                update_texel_density_view,
+                pad_uv_seams,
                 upload_mesh_dirty_tiles,
             )
                 .chain(),
@@ -163,24 +694,7 @@ fn setup_mesh_paint_textures(
             }
         };
 
-        // Create the paint texture image
-        let mut image = Image::new_fill(
-            Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            TextureDimension::D2,
-            &[0, 0, 0, 0], // Transparent
-            TextureFormat::Rgba8UnormSrgb,
-            RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
-        );
-
-        image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
-            | TextureUsages::COPY_DST
-            | TextureUsages::RENDER_ATTACHMENT;
-
-        let image_handle = images.add(image);
+        let image_handle = images.add(paint_texture_image(width, height));
 
         // Initialize the surface
         match paintable.storage_mode {
@@ -215,12 +729,20 @@ fn setup_mesh_paint_textures(
             (None, [0.8, 0.8, 0.8, 1.0])
         };
 
+        let channel_defaults = material_handle
+            .and_then(|material_ref| materials.get(&material_ref.0))
+            .map_or_else(ChannelDefaults::default, ChannelDefaults::from_material);
+
         commands.entity(entity).insert(MeshPaintTexture {
             image_handle,
             original_texture,
             original_base_color,
             needs_full_upload: false, // Don't upload until we have paint
             has_paint: false,
+            metallic_roughness_image: None,
+            normal_image: None,
+            emissive_image: None,
+            channel_defaults,
         });
 
         info!(
@@ -230,11 +752,532 @@ fn setup_mesh_paint_textures(
     }
 }
 
+/// Transparent paint texture of the given size
+fn paint_texture_image(width: u32, height: u32) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0], // Transparent
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+    );
+
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Linear 16-bit float texture of the given size for a material channel
+fn channel_texture_image(width: u32, height: u32) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0; 8],
+        TextureFormat::Rgba16Float,
+        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+    );
+
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Start conversions requested with `MeshPaintEvent::ConvertStorage`.
+///
+/// The mesh's current surface moves into the conversion job; a mesh that
+/// was never painted converts an empty surface.
+fn start_storage_conversions(
+    mut mesh_paint_events: MessageReader<MeshPaintEvent>,
+    mut painting_res: ResMut<MeshPaintingResource>,
+    mesh_paint_state: Res<MeshPaintState>,
+    paintables: Query<(&PaintableMesh, &Mesh3d)>,
+    meshes: Res<Assets<Mesh>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for event in mesh_paint_events.read() {
+        let MeshPaintEvent::ConvertStorage {
+            mesh_entity,
+            target,
+        } = event
+        else {
+            continue;
+        };
+        let Ok((paintable, mesh3d)) = paintables.get(*mesh_entity) else {
+            outbound.send_error(
+                "mesh_not_paintable",
+                "Storage conversion target is not a paintable mesh",
+            );
+            continue;
+        };
+        let mesh_id = paintable.mesh_id;
+        if painting_res.is_converting(mesh_id) {
+            warn!(
+                "Ignoring storage conversion of mesh {}: a conversion is already running",
+                mesh_id
+            );
+            continue;
+        }
+        if mesh_paint_state
+            .current_stroke
+            .as_ref()
+            .is_some_and(|stroke| stroke.mesh_id == mesh_id)
+        {
+            warn!(
+                "Ignoring storage conversion of mesh {} during a stroke",
+                mesh_id
+            );
+            continue;
+        }
+        if painting_res.has_channel_paint(mesh_id) {
+            outbound.send_error(
+                "storage_conversion_failed",
+                format!(
+                    "Cannot convert paint of mesh {}: only base color converts, and it has \
+                     roughness, metallic, normal, or emissive paint",
+                    mesh_id
+                ),
+            );
+            continue;
+        }
+
+        let prepared = match (paintable.storage_mode, *target) {
+            (MeshStorageMode::Ptex { .. }, MeshStorageMode::UvAtlas { resolution }) => meshes
+                .get(&mesh3d.0)
+                .ok_or_else(|| "the mesh is not loaded".to_string())
+                .and_then(|mesh| bake_face_uvs(mesh, resolution)),
+            (MeshStorageMode::UvAtlas { .. }, MeshStorageMode::Ptex { .. }) => meshes
+                .get(&mesh3d.0)
+                .and_then(face_uvs)
+                .map(|uvs| (uvs, None))
+                .ok_or_else(|| "the mesh has no UVs to sample the atlas with".to_string()),
+            (current, target) if current == target => {
+                info!("Mesh {} already uses {:?} storage", mesh_id, target);
+                continue;
+            }
+            (current, target) => Err(format!(
+                "changing {:?} storage to {:?} isn't supported",
+                current, target
+            )),
+        };
+        let (face_uvs, unwrapped) = match prepared {
+            Ok(prepared) => prepared,
+            Err(reason) => {
+                outbound.send_error(
+                    "storage_conversion_failed",
+                    format!("Cannot convert paint of mesh {}: {}", mesh_id, reason),
+                );
+                continue;
+            }
+        };
+
+        let conversion = match (paintable.storage_mode, *target) {
+            (
+                MeshStorageMode::Ptex { face_resolution },
+                MeshStorageMode::UvAtlas { resolution },
+            ) => {
+                let source = painting_res
+                    .ptex_surfaces
+                    .remove(&mesh_id)
+                    .unwrap_or_else(|| MeshPtexSurface::new(mesh_id, face_resolution));
+                StorageConversion::ptex_to_uv(source, face_uvs, resolution)
+            }
+            (
+                MeshStorageMode::UvAtlas { resolution },
+                MeshStorageMode::Ptex { face_resolution },
+            ) => {
+                let source = painting_res
+                    .uv_surfaces
+                    .remove(&mesh_id)
+                    .unwrap_or_else(|| {
+                        MeshUvSurface::new(
+                            mesh_id,
+                            resolution.0,
+                            resolution.1,
+                            DEFAULT_SEAM_PADDING,
+                        )
+                    });
+                StorageConversion::uv_to_ptex(source, face_uvs, face_resolution)
+            }
+            _ => unreachable!("unsupported conversions were rejected above"),
+        };
+
+        // Undo tiles index the old storage
+        painting_res
+            .undo_stack
+            .retain(|entry| entry.mesh_id != mesh_id);
+        info!(
+            "Started paint storage conversion of mesh {} to {:?}",
+            mesh_id, target
+        );
+        painting_res.conversions.insert(
+            mesh_id,
+            ConversionJob {
+                entity: *mesh_entity,
+                target: *target,
+                conversion,
+                unwrapped,
+                last_reported: 0.0,
+            },
+        );
+        outbound.send(BevyToUi::MeshStorageConversionProgress {
+            mesh_id,
+            progress: 0.0,
+        });
+    }
+}
+
+/// Face UVs for baking ptex paint into an atlas of `resolution`.
+///
+/// Meshes without usable UVs get generated atlas UVs; that mesh is returned
+/// so it can be installed with the converted paint.
+#[allow(clippy::type_complexity)]
+fn bake_face_uvs(
+    mesh: &Mesh,
+    resolution: (u32, u32),
+) -> Result<(Vec<[Vec2; 3]>, Option<(Mesh, GeneratedAtlasUvs)>), String> {
+    if has_usable_uvs(mesh) {
+        return face_uvs(mesh)
+            .map(|uvs| (uvs, None))
+            .ok_or_else(|| "the mesh UVs don't cover its triangles".to_string());
+    }
+
+    let half_edge_mesh = HalfEdgeMesh::from_bevy_mesh(mesh).map_err(|e| format!("{:?}", e))?;
+    let (unwrapped, generated) =
+        unwrap_atlas_mesh(&half_edge_mesh, resolution).map_err(|e| format!("{:?}", e))?;
+    let uvs = face_uvs(&unwrapped).unwrap_or_default();
+    // Ptex tiles are keyed by triangle, so generation must keep every one
+    let triangles = mesh.indices().map_or(mesh.count_vertices(), |i| i.len()) / 3;
+    if uvs.len() != triangles {
+        return Err(format!(
+            "generated UVs cover {} of {} faces",
+            uvs.len(),
+            triangles
+        ));
+    }
+    Ok((uvs, Some((unwrapped, generated))))
+}
+
+/// UVs of every triangle's corners, indexed by face id
+fn face_uvs(mesh: &Mesh) -> Option<Vec<[Vec2; 3]>> {
+    let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) else {
+        return None;
+    };
+    let uv = |index: usize| uvs.get(index).map(|uv| Vec2::from_array(*uv));
+    triangle_indices(mesh)
+        .chunks_exact(3)
+        .map(|tri| Some([uv(tri[0])?, uv(tri[1])?, uv(tri[2])?]))
+        .collect()
+}
+
+/// Vertex indices of a mesh's triangles, three per face
+fn triangle_indices(mesh: &Mesh) -> Vec<usize> {
+    match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..mesh.count_vertices()).collect(),
+    }
+}
+
+/// Advance running storage conversions by one frame's budget, report their
+/// progress, and swap the converted paint in once one finishes.
+fn run_storage_conversions(
+    mut commands: Commands,
+    mut painting_res: ResMut<MeshPaintingResource>,
+    mut paintables: Query<(&mut PaintableMesh, &Mesh3d, Option<&mut MeshPaintTexture>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let mesh_ids: Vec<u32> = painting_res.conversions.keys().copied().collect();
+    for mesh_id in mesh_ids {
+        let Some(job) = painting_res.conversions.get_mut(&mesh_id) else {
+            continue;
+        };
+        if !paintables.contains(job.entity) {
+            painting_res.conversions.remove(&mesh_id);
+            info!(
+                "Dropped paint storage conversion of mesh {}: the mesh is gone",
+                mesh_id
+            );
+            outbound.send(BevyToUi::MeshStorageConversionFinished {
+                mesh_id,
+                cancelled: true,
+            });
+            continue;
+        }
+        if !job.conversion.step() {
+            let progress = job.conversion.progress();
+            if progress - job.last_reported >= 0.01 {
+                job.last_reported = progress;
+                outbound.send(BevyToUi::MeshStorageConversionProgress { mesh_id, progress });
+            }
+            continue;
+        }
+
+        let Some(job) = painting_res.conversions.remove(&mesh_id) else {
+            continue;
+        };
+        let Ok((mut paintable, mesh3d, paint_texture)) = paintables.get_mut(job.entity) else {
+            continue;
+        };
+        match job.conversion.finish() {
+            MeshPaintSurface::Uv(surface) => {
+                let (width, height) = surface.dimensions();
+                painting_res.uv_surfaces.insert(mesh_id, surface);
+                // Resized in place, so a material already showing it keeps working
+                if let Some(mut paint_texture) = paint_texture {
+                    if let Some(image) = images.get_mut(&paint_texture.image_handle) {
+                        *image = paint_texture_image(width, height);
+                    }
+                    paint_texture.needs_full_upload = true;
+                }
+            }
+            MeshPaintSurface::Ptex(surface) => {
+                painting_res.ptex_surfaces.insert(mesh_id, surface);
+            }
+        }
+        paintable.storage_mode = job.target;
+        // Recomputed for the new UVs (or dropped along with the atlas)
+        painting_res.texel_densities.remove(&mesh_id);
+        painting_res.uv_seams.remove(&mesh_id);
+        painting_res.texel_density_view_dirty = true;
+        if let Some((mesh, generated)) = job.unwrapped {
+            if let Some(asset) = meshes.get_mut(&mesh3d.0) {
+                *asset = mesh;
+            }
+            commands.entity(job.entity).insert(generated);
+        }
+
+        info!(
+            "Converted paint of mesh {} to {:?} storage",
+            mesh_id, job.target
+        );
+        outbound.send(BevyToUi::MeshStorageConversionFinished {
+            mesh_id,
+            cancelled: false,
+        });
+    }
+}
+
+/// Meshes and materials vertex color strokes paint
+#[derive(SystemParam)]
+struct VertexColorTargets<'w, 's> {
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    patches: ResMut<'w, MeshVertexPatches>,
+    objects: Query<
+        'w,
+        's,
+        (
+            &'static Mesh3d,
+            &'static GlobalTransform,
+            Option<&'static MeshMaterial3d<StandardMaterial>>,
+        ),
+    >,
+}
+
+impl VertexColorTargets<'_, '_> {
+    /// Give `entity`'s mesh a vertex color attribute if it has none, seeded
+    /// with its material's base color, and turn the base color white so the
+    /// material shows the vertex colors.
+    fn prepare(&mut self, entity: Entity) {
+        let Ok((mesh3d, _, material_ref)) = self.objects.get(entity) else {
+            return;
+        };
+        let has_colors = self.meshes.get(&mesh3d.0).is_some_and(|mesh| {
+            matches!(
+                mesh.attribute(Mesh::ATTRIBUTE_COLOR),
+                Some(VertexAttributeValues::Float32x4(_))
+            )
+        });
+        if has_colors {
+            return;
+        }
+        let material =
+            material_ref.and_then(|material_ref| self.materials.get_mut(&material_ref.0));
+        let base_color = material.map_or([1.0; 4], |material| {
+            let color = material.base_color.to_linear();
+            material.base_color = Color::WHITE;
+            [color.red, color.green, color.blue, color.alpha]
+        });
+        // Tracked mutation: a new attribute changes the vertex layout, so the
+        // whole mesh is uploaded once
+        if let Some(mesh) = self.meshes.get_mut(&mesh3d.0) {
+            let colors = vec![base_color; mesh.count_vertices()];
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+    }
+
+    /// Blend a dab of world radius `brush_size` at `hit` into the vertex
+    /// colors of the stroke's mesh.
+    fn apply_dab(
+        &mut self,
+        painting_res: &mut MeshPaintingResource,
+        hit: &MeshHit,
+        brush_size: f32,
+    ) {
+        let Some(entity) = painting_res
+            .vertex_stroke
+            .as_ref()
+            .map(|stroke| stroke.entity)
+        else {
+            return;
+        };
+        let Ok((mesh3d, transform, _)) = self.objects.get(entity) else {
+            return;
+        };
+        let Some(mesh) = self.meshes.get_mut_untracked(&mesh3d.0) else {
+            return;
+        };
+        paint_vertex_colors(
+            painting_res,
+            mesh,
+            mesh3d.0.id(),
+            transform.affine(),
+            hit,
+            brush_size,
+            &mut self.patches,
+        );
+    }
+
+    /// Write back the vertex colors undo put back.
+    fn apply_restores(&mut self, painting_res: &mut MeshPaintingResource) {
+        for (entity, mut colors) in painting_res.vertex_color_restores.drain(..) {
+            let Ok((mesh3d, _, _)) = self.objects.get(entity) else {
+                continue;
+            };
+            let Some(mesh) = self.meshes.get_mut_untracked(&mesh3d.0) else {
+                continue;
+            };
+            if patch_mesh_colors(mesh, mesh3d.0.id(), &mut colors, &mut self.patches).is_none() {
+                warn!("Could not restore vertex colors of {:?}", entity);
+            }
+        }
+    }
+}
+
+/// Blend a vertex color dab into `mesh` (drawn with `world_from_local`),
+/// recording it in the stroke in progress and patching the changed vertices
+/// into the mesh's vertex buffer.
+///
+/// The world radius is converted with the transform's largest scale axis, so
+/// non-uniformly scaled meshes get a round dab in local space.
+fn paint_vertex_colors(
+    painting_res: &mut MeshPaintingResource,
+    mesh: &mut Mesh,
+    mesh_id: AssetId<Mesh>,
+    world_from_local: Affine3A,
+    hit: &MeshHit,
+    brush_size: f32,
+    patches: &mut MeshVertexPatches,
+) {
+    let Some(stroke) = painting_res.vertex_stroke.as_mut() else {
+        return;
+    };
+    if !matches!(
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+        Some(VertexAttributeValues::Float32x3(_))
+    ) {
+        return;
+    }
+    // Taken out for the dab so positions can be read alongside; put back below
+    let mut colors = match mesh.try_attribute_mut(Mesh::ATTRIBUTE_COLOR) {
+        Ok(VertexAttributeValues::Float32x4(colors)) => std::mem::take(colors),
+        _ => return,
+    };
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        unreachable!("positions were checked above");
+    };
+
+    let cached = painting_res
+        .vertex_octrees
+        .get(&stroke.record.mesh_id)
+        .is_some_and(|cached| cached.mesh == mesh_id && cached.vertex_count == positions.len());
+    if !cached {
+        let octree = VertexOctree::from_vertices(
+            positions
+                .iter()
+                .enumerate()
+                .map(|(index, position)| (VertexId(index as u32), Vec3::from_array(*position))),
+        );
+        painting_res.vertex_octrees.insert(
+            stroke.record.mesh_id,
+            MeshVertexOctree {
+                mesh: mesh_id,
+                vertex_count: positions.len(),
+                octree,
+            },
+        );
+    }
+    let octree = &painting_res.vertex_octrees[&stroke.record.mesh_id].octree;
+
+    let max_scale = [
+        world_from_local.matrix3.x_axis,
+        world_from_local.matrix3.y_axis,
+        world_from_local.matrix3.z_axis,
+    ]
+    .iter()
+    .map(|axis| axis.length())
+    .fold(0.0, f32::max);
+    let dab = VertexColorDab {
+        center: world_from_local
+            .inverse()
+            .transform_point3(hit.world_pos)
+            .to_array(),
+        radius: brush_size / max_scale.max(f32::EPSILON),
+        opacity: painting_res.brush_preset.opacity,
+        hardness: painting_res.brush_preset.hardness,
+    };
+    let candidates: Vec<u32> = octree
+        .query_sphere(Vec3::from_array(dab.center), dab.radius)
+        .into_iter()
+        .map(|id| id.0)
+        .collect();
+    let changed = apply_vertex_color_dab(
+        positions,
+        &mut colors,
+        &candidates,
+        &dab,
+        stroke.record.color,
+        stroke.record.blend_mode,
+    );
+    stroke.record.dabs.push(dab);
+
+    let mut updates: Vec<(u32, [f32; 4])> = changed
+        .into_iter()
+        .map(|(index, before)| {
+            stroke.before.entry(index).or_insert(before);
+            (index, colors[index as usize])
+        })
+        .collect();
+    if let Ok(VertexAttributeValues::Float32x4(attribute)) =
+        mesh.try_attribute_mut(Mesh::ATTRIBUTE_COLOR)
+    {
+        *attribute = colors;
+    }
+    if patch_mesh_colors(mesh, mesh_id, &mut updates, patches).is_none() {
+        warn!(
+            "Could not patch vertex colors of mesh {}",
+            stroke.record.mesh_id
+        );
+    }
+}
+
 /// Process mesh paint events and apply dabs to surfaces.
 fn process_mesh_paint_events(
     mut mesh_paint_events: MessageReader<MeshPaintEvent>,
     mut painting_res: ResMut<MeshPaintingResource>,
     mesh_query: Query<&PaintableMesh>,
+    mut vertex_targets: VertexColorTargets,
 ) {
     for event in mesh_paint_events.read() {
         match event {
@@ -244,50 +1287,91 @@ fn process_mesh_paint_events(
                 hit,
                 stroke_id,
             } => {
+                if painting_res.is_converting(*mesh_id) {
+                    debug!("Ignoring mesh stroke during a storage conversion");
+                    continue;
+                }
+                let Ok(paintable) = mesh_query.get(*mesh_entity) else {
+                    continue;
+                };
+                if painting_res.paint_target == MeshPaintTarget::VertexColors {
+                    info!(
+                        "Vertex color stroke start: mesh_id={}, stroke_id={}",
+                        mesh_id, stroke_id
+                    );
+                    vertex_targets.prepare(*mesh_entity);
+                    painting_res.begin_vertex_stroke(*mesh_entity, *mesh_id, *stroke_id);
+                    let brush_size = painting_res.brush_preset.base_size;
+                    vertex_targets.apply_dab(&mut painting_res, hit, brush_size);
+                    continue;
+                }
                 info!(
-                    "Mesh stroke start: mesh_id={}, stroke_id={}",
-                    mesh_id, stroke_id
+                    "Mesh stroke start: mesh_id={}, stroke_id={}, channel={:?}",
+                    mesh_id, stroke_id, painting_res.target_channel
                 );
-                apply_dab_to_mesh(&mut painting_res, &mesh_query, *mesh_entity, hit);
+                painting_res.begin_stroke(*stroke_id, paintable);
+                apply_dab_to_mesh(&mut painting_res, paintable, hit);
             }
             MeshPaintEvent::StrokeMove {
                 hit,
                 pressure,
                 speed: _,
             } => {
-                // We need to know which mesh - get from hit's context
-                // For now, iterate and find matching mesh by checking if surface exists
-                // In a real implementation, we'd track the active mesh entity
-                for (_, paintable) in mesh_query.iter().enumerate() {
-                    if painting_res.get_uv_surface(paintable.mesh_id).is_some()
-                        || painting_res.get_ptex_surface(paintable.mesh_id).is_some()
-                    {
-                        apply_dab_for_move(&mut painting_res, paintable.mesh_id, hit, *pressure);
-                        break;
-                    }
+                if painting_res.vertex_stroke.is_some() {
+                    let brush_size = painting_res.brush_preset.size_for_pressure(*pressure);
+                    vertex_targets.apply_dab(&mut painting_res, hit, brush_size);
+                    continue;
                 }
+                let Some(mesh_id) = painting_res.stroke.as_ref().map(|stroke| stroke.mesh_id)
+                else {
+                    continue;
+                };
+                apply_dab_for_move(&mut painting_res, mesh_id, hit, *pressure);
             }
             MeshPaintEvent::StrokeEnd => {
+                painting_res.end_stroke();
                 info!("Mesh stroke end");
             }
             MeshPaintEvent::StrokeCancel => {
+                painting_res.end_stroke();
                 info!("Mesh stroke cancelled");
             }
+            // Handled by `start_storage_conversions`
+            MeshPaintEvent::ConvertStorage { .. } => {}
+            MeshPaintEvent::ShowTexelDensity { enabled } => {
+                if painting_res.show_texel_density != *enabled {
+                    painting_res.show_texel_density = *enabled;
+                    painting_res.texel_density_view_dirty = true;
+                }
+            }
+            MeshPaintEvent::SetTargetChannel { channel } => {
+                painting_res.target_channel = *channel;
+            }
+            MeshPaintEvent::SetBrushValue { value } => {
+                painting_res.brush_value = value.clamp(0.0, 1.0);
+            }
+            MeshPaintEvent::SetNormalStrength { strength } => {
+                painting_res.normal_strength = strength.clamp(0.0, 1.0);
+            }
+            MeshPaintEvent::SetPaintTarget { target } => {
+                painting_res.paint_target = *target;
+            }
+            MeshPaintEvent::Undo => {
+                if !painting_res.undo() {
+                    debug!("No mesh stroke to undo");
+                }
+                vertex_targets.apply_restores(&mut painting_res);
+            }
         }
     }
 }
 
-/// Apply a dab to a mesh surface based on hit data.
+/// Apply the first dab of a stroke to a mesh surface based on hit data.
 fn apply_dab_to_mesh(
     painting_res: &mut MeshPaintingResource,
-    mesh_query: &Query<&PaintableMesh>,
-    mesh_entity: Entity,
+    paintable: &PaintableMesh,
     hit: &MeshHit,
 ) {
-    let Ok(paintable) = mesh_query.get(mesh_entity) else {
-        return;
-    };
-
     let brush_size = painting_res.brush_preset.base_size;
     let color = painting_res.brush_color;
     let opacity = painting_res.brush_preset.opacity;
@@ -295,32 +1379,15 @@ fn apply_dab_to_mesh(
     let blend_mode = painting_res.blend_mode;
 
     match paintable.storage_mode {
-        MeshStorageMode::UvAtlas { resolution } => {
+        MeshStorageMode::UvAtlas { .. } => {
             if let Some(uv) = hit.uv {
-                let surface = painting_res.get_or_create_uv_surface(
-                    paintable.mesh_id,
-                    resolution.0,
-                    resolution.1,
-                );
-
-                // Convert world brush size to texture pixels
-                // For simplicity, use a fixed scale based on texture resolution
-                let avg_res = (resolution.0 + resolution.1) as f32 / 2.0;
-                let texel_radius = brush_size * avg_res / 10.0; // Approximate scaling
-
-                surface.apply_dab(
-                    uv,
-                    texel_radius,
-                    color,
-                    opacity,
-                    hardness,
-                    blend_mode,
-                    0.0, // angle
-                    1.0, // aspect_ratio (circular)
-                );
+                painting_res.apply_uv_dab(paintable.mesh_id, hit, uv, brush_size);
             }
         }
         MeshStorageMode::Ptex { face_resolution } => {
+            if painting_res.target_channel != MeshPaintChannel::BaseColor {
+                return;
+            }
             let surface =
                 painting_res.get_or_create_ptex_surface(paintable.mesh_id, face_resolution);
 
@@ -357,27 +1424,21 @@ fn apply_dab_for_move(
     let blend_mode = painting_res.blend_mode;
 
     // Try UV surface first
-    if let Some(surface) = painting_res.get_uv_surface_mut(mesh_id) {
+    if painting_res.get_uv_surface(mesh_id).is_some() {
         if let Some(uv) = hit.uv {
-            let (width, height) = surface.dimensions();
-            let avg_res = (width + height) as f32 / 2.0;
-            let texel_radius = brush_size * avg_res / 10.0;
-
-            surface.apply_dab(
-                uv,
-                texel_radius,
-                color,
-                opacity,
-                hardness,
-                blend_mode,
-                0.0,
-                1.0,
-            );
+            painting_res.apply_uv_dab(mesh_id, hit, uv, brush_size);
         }
         return;
     }
 
-    // Try Ptex surface
+    // Ptex surfaces only take base color
+    if painting_res
+        .stroke
+        .as_ref()
+        .is_some_and(|stroke| stroke.channel != MeshPaintChannel::BaseColor)
+    {
+        return;
+    }
     if let Some(surface) = painting_res.get_ptex_surface_mut(mesh_id) {
         let face_resolution = surface.default_resolution;
         let local_coords = Vec2::new(
@@ -397,94 +1458,323 @@ fn apply_dab_for_move(
     }
 }
 
+/// Compute per-face texel densities of UV-atlas meshes that have none yet or
+/// whose mesh changed.
+fn update_texel_densities(
+    mut mesh_events: MessageReader<AssetEvent<Mesh>>,
+    mut painting_res: ResMut<MeshPaintingResource>,
+    paintables: Query<(&PaintableMesh, &Mesh3d)>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    let modified: HashSet<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (paintable, mesh3d) in &paintables {
+        let MeshStorageMode::UvAtlas { resolution } = paintable.storage_mode else {
+            continue;
+        };
+        let mesh_id = paintable.mesh_id;
+        if painting_res.texel_densities.contains_key(&mesh_id) && !modified.contains(&mesh3d.0.id())
+        {
+            continue;
+        }
+        let Some(density) = meshes
+            .get(&mesh3d.0)
+            .and_then(|mesh| mesh_texel_density(mesh, resolution))
+        else {
+            continue;
+        };
+        // Tinting the density view modifies the mesh without changing its
+        // densities
+        if painting_res.texel_densities.get(&mesh_id) == Some(&density) {
+            continue;
+        }
+        painting_res.texel_densities.insert(mesh_id, density);
+        painting_res.texel_density_view_dirty = true;
+    }
+}
+
+/// Build seam edge lookups of UV-atlas meshes that have none yet, whose mesh
+/// changed, or whose atlas was resized.
+fn update_uv_seams(
+    mut mesh_events: MessageReader<AssetEvent<Mesh>>,
+    mut painting_res: ResMut<MeshPaintingResource>,
+    paintables: Query<(&PaintableMesh, &Mesh3d)>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    let modified: HashSet<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (paintable, mesh3d) in &paintables {
+        let MeshStorageMode::UvAtlas { resolution } = paintable.storage_mode else {
+            continue;
+        };
+        let mesh_id = paintable.mesh_id;
+        if painting_res
+            .uv_seams
+            .get(&mesh_id)
+            .is_some_and(|seams| seams.resolution() == resolution)
+            && !modified.contains(&mesh3d.0.id())
+        {
+            continue;
+        }
+        let Some(mesh) = meshes.get(&mesh3d.0) else {
+            continue;
+        };
+        let Some(face_uvs) = face_uvs(mesh) else {
+            continue;
+        };
+
+        let seams = match HalfEdgeMesh::from_bevy_mesh_with_source_faces(mesh) {
+            Ok((half_edge_mesh, source_faces)) => {
+                UvSeams::with_seam_edges(&face_uvs, resolution, &half_edge_mesh, &source_faces)
+            }
+            Err(e) => {
+                warn!(
+                    "Painting mesh {} without seam bleeding, only padding: {}",
+                    mesh_id, e
+                );
+                UvSeams::new(&face_uvs, resolution)
+            }
+        };
+        info!(
+            "Found {} UV seam edges on mesh {}",
+            seams.seam_edge_count() / 2,
+            mesh_id
+        );
+        painting_res.uv_seams.insert(mesh_id, seams);
+    }
+}
+
+/// Bleed this frame's paint into the chart margins of its dirty tiles.
+fn pad_uv_seams(mut painting_res: ResMut<MeshPaintingResource>) {
+    let painting_res = &mut *painting_res;
+    for (mesh_id, surface) in &mut painting_res.uv_surfaces {
+        if !surface.has_dirty_tiles() {
+            continue;
+        }
+        if let Some(seams) = painting_res.uv_seams.get(mesh_id) {
+            seams.pad_dirty_tiles(surface);
+        }
+    }
+}
+
+/// Per-face texel densities of a mesh with atlas UVs. Computed in mesh space:
+/// the object's transform scales every face alike and drops out of the
+/// relative factors.
+fn mesh_texel_density(mesh: &Mesh, resolution: (u32, u32)) -> Option<TexelDensity> {
+    let positions: Vec<Vec3> = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)?
+        .as_float3()?
+        .iter()
+        .map(|p| Vec3::from_array(*p))
+        .collect();
+    let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) else {
+        return None;
+    };
+    let uvs: Vec<Vec2> = uvs.iter().map(|uv| Vec2::from_array(*uv)).collect();
+    let indices: Vec<u32> = triangle_indices(mesh)
+        .into_iter()
+        .map(|i| i as u32)
+        .collect();
+
+    Some(estimate_texel_density_cpu(
+        &positions,
+        &uvs,
+        &indices,
+        &Mat4::IDENTITY,
+        UVec2::new(resolution.0, resolution.1),
+    ))
+}
+
+/// Tint UV-atlas meshes by texel density while the debug view is on, and
+/// restore their vertex colors once it's turned off.
+fn update_texel_density_view(
+    mut commands: Commands,
+    mut painting_res: ResMut<MeshPaintingResource>,
+    paintables: Query<(Entity, &PaintableMesh, &Mesh3d, Option<&TexelDensityTint>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if !painting_res.texel_density_view_dirty {
+        return;
+    }
+    painting_res.texel_density_view_dirty = false;
+
+    for (entity, paintable, mesh3d, tint) in &paintables {
+        let density = painting_res
+            .texel_densities
+            .get(&paintable.mesh_id)
+            .filter(|_| painting_res.show_texel_density);
+        match (density, tint) {
+            (Some(density), tint) => {
+                let Some(mesh) = meshes.get_mut(&mesh3d.0) else {
+                    continue;
+                };
+                if tint.is_none() {
+                    commands.entity(entity).insert(TexelDensityTint {
+                        original: mesh.attribute(Mesh::ATTRIBUTE_COLOR).cloned(),
+                    });
+                }
+                let colors = density_tint_colors(mesh, density);
+                mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+            }
+            (None, Some(tint)) => {
+                if let Some(mesh) = meshes.get_mut(&mesh3d.0) {
+                    match &tint.original {
+                        Some(colors) => {
+                            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors.clone())
+                        }
+                        None => {
+                            mesh.remove_attribute(Mesh::ATTRIBUTE_COLOR);
+                        }
+                    }
+                }
+                commands.entity(entity).remove::<TexelDensityTint>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Vertex colors showing texel density: white at the mesh average, shading
+/// to red where texels are denser and to blue where they're sparser
+/// (saturating at 4x either way). Each vertex averages its faces.
+fn density_tint_colors(mesh: &Mesh, density: &TexelDensity) -> Vec<[f32; 4]> {
+    let mut sums = vec![(0.0f32, 0u32); mesh.count_vertices()];
+    for (face_id, tri) in triangle_indices(mesh).chunks_exact(3).enumerate() {
+        let log_factor = density.factor(face_id as u32).log2();
+        for &index in tri {
+            if let Some((sum, count)) = sums.get_mut(index) {
+                *sum += log_factor;
+                *count += 1;
+            }
+        }
+    }
+
+    sums.into_iter()
+        .map(|(sum, count)| {
+            let t = if count > 0 {
+                (sum / count as f32 / 2.0).clamp(-1.0, 1.0)
+            } else {
+                0.0
+            };
+            if t >= 0.0 {
+                [1.0, 1.0 - t, 1.0 - t, 1.0]
+            } else {
+                [1.0 + t, 1.0 + t, 1.0, 1.0]
+            }
+        })
+        .collect()
+}
+
 /// Upload dirty tiles to GPU for UV surfaces, compositing paint over original texture.
+///
+/// The first paint and full uploads replace the image data; after that only
+/// the dirty rects are composited and queued on the [`DirtyTileUploadBuffer`].
+/// Channel surfaces go to their own textures (see `upload_channel_textures`).
+#[allow(clippy::too_many_arguments)]
 fn upload_mesh_dirty_tiles(
-    painting_res: Res<MeshPaintingResource>,
+    mut painting_res: ResMut<MeshPaintingResource>,
     mut images: ResMut<Assets<Image>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut upload_buffer: Option<ResMut<DirtyTileUploadBuffer>>,
+    mut stats: Option<ResMut<TextureUploadStats>>,
     mut query: Query<(
         &PaintableMesh,
+        &Mesh3d,
         &mut MeshPaintTexture,
         Option<&MeshMaterial3d<StandardMaterial>>,
     )>,
 ) {
-    for (paintable, mut paint_texture, material_handle) in query.iter_mut() {
+    for (paintable, mesh3d, mut paint_texture, material_handle) in query.iter_mut() {
         match paintable.storage_mode {
             MeshStorageMode::UvAtlas { .. } => {
-                if let Some(surface) = painting_res.get_uv_surface(paintable.mesh_id) {
-                    if !surface.has_dirty_tiles() && !paint_texture.needs_full_upload {
+                for texture in upload_channel_textures(
+                    &mut painting_res,
+                    paintable.mesh_id,
+                    &mut paint_texture,
+                    &mut images,
+                    upload_buffer.as_deref_mut(),
+                    stats.as_deref_mut(),
+                ) {
+                    let material =
+                        material_handle.and_then(|material_ref| materials.get_mut(&material_ref.0));
+                    if let Some(material) = material {
+                        wire_channel_texture(
+                            texture,
+                            &paint_texture,
+                            material,
+                            meshes.get_mut(&mesh3d.0),
+                        );
+                    }
+                }
+
+                if let Some(surface) = painting_res.get_uv_surface_mut(paintable.mesh_id) {
+                    // Uploaded; padding and upload skip the mesh until it's painted again
+                    let dirty_tiles = surface.surface_mut().take_dirty_tiles();
+                    if dirty_tiles.is_empty() && !paint_texture.needs_full_upload {
                         continue;
                     }
 
-                    let cpu_surface = surface.surface().surface();
-                    let width = cpu_surface.width as usize;
-                    let height = cpu_surface.height as usize;
+                    let tiled = surface.surface();
+                    let cpu_surface = tiled.surface();
+                    let full_upload = paint_texture.needs_full_upload
+                        || !paint_texture.has_paint
+                        || upload_buffer.is_none();
 
-                    // Get original texture data for compositing
-                    let original_data: Option<Vec<u8>> = paint_texture
+                    // Original texture data for compositing
+                    let original_data = paint_texture
                         .original_texture
                         .as_ref()
-                        .and_then(|handle| images.get(handle).and_then(|img| img.data.clone()));
-
-                    // Composite paint over original
-                    let mut data = vec![0u8; width * height * 4];
-                    for y in 0..height {
-                        for x in 0..width {
-                            let idx = (y * width + x) * 4;
-
-                            // Get original pixel (from texture or base color)
-                            let (orig_r, orig_g, orig_b, orig_a) =
-                                if let Some(ref orig) = original_data {
-                                    if idx + 3 < orig.len() {
-                                        (orig[idx], orig[idx + 1], orig[idx + 2], orig[idx + 3])
-                                    } else {
-                                        color_to_srgb_u8(paint_texture.original_base_color)
-                                    }
-                                } else {
-                                    color_to_srgb_u8(paint_texture.original_base_color)
-                                };
-
-                            // Get paint pixel
-                            if let Some(paint_pixel) = cpu_surface.get_pixel(x as u32, y as u32) {
-                                let paint_alpha = paint_pixel[3];
-
-                                if paint_alpha > 0.001 {
-                                    // Alpha blend paint over original
-                                    let paint_r = linear_to_srgb_u8(paint_pixel[0]);
-                                    let paint_g = linear_to_srgb_u8(paint_pixel[1]);
-                                    let paint_b = linear_to_srgb_u8(paint_pixel[2]);
-                                    let paint_a = (paint_alpha * 255.0) as u8;
-
-                                    let alpha = paint_a as f32 / 255.0;
-                                    let inv_alpha = 1.0 - alpha;
-
-                                    data[idx] =
-                                        (paint_r as f32 * alpha + orig_r as f32 * inv_alpha) as u8;
-                                    data[idx + 1] =
-                                        (paint_g as f32 * alpha + orig_g as f32 * inv_alpha) as u8;
-                                    data[idx + 2] =
-                                        (paint_b as f32 * alpha + orig_b as f32 * inv_alpha) as u8;
-                                    data[idx + 3] = orig_a.max(paint_a);
-                                } else {
-                                    // No paint, use original
-                                    data[idx] = orig_r;
-                                    data[idx + 1] = orig_g;
-                                    data[idx + 2] = orig_b;
-                                    data[idx + 3] = orig_a;
-                                }
-                            } else {
-                                // No paint data, use original
-                                data[idx] = orig_r;
-                                data[idx + 1] = orig_g;
-                                data[idx + 2] = orig_b;
-                                data[idx + 3] = orig_a;
-                            }
+                        .and_then(|handle| images.get(handle))
+                        .and_then(|img| img.data.as_deref());
+                    let original = OriginalTexture {
+                        data: original_data,
+                        base_color: color_to_srgb_u8(paint_texture.original_base_color),
+                    };
+
+                    if full_upload {
+                        let (width, height) = (cpu_surface.width, cpu_surface.height);
+                        let mut data = Vec::new();
+                        composite_paint_rect(
+                            cpu_surface,
+                            &original,
+                            (0, 0, width, height),
+                            &mut data,
+                        );
+                        if let Some(stats) = stats.as_mut() {
+                            let tile_size = tiled.tile_size();
+                            stats.add_tiles(
+                                (width.div_ceil(tile_size) * height.div_ceil(tile_size)) as usize,
+                            );
                         }
-                    }
 
-                    // Upload to GPU texture
-                    if let Some(image) = images.get_mut(&paint_texture.image_handle) {
-                        image.data = Some(data);
+                        // Upload to GPU texture
+                        if let Some(image) = images.get_mut(&paint_texture.image_handle) {
+                            image.data = Some(data);
+                        }
+                    } else if let Some(buffer) = upload_buffer.as_mut() {
+                        let image_id = paint_texture.image_handle.id();
+                        for rect in tiled.coalesce_tile_rects(&dirty_tiles) {
+                            let (x, y, w, h) = rect;
+                            buffer.push(image_id, (x, y), (w, h), |data| {
+                                composite_paint_rect(cpu_surface, &original, rect, data)
+                            });
+                        }
+                        if let Some(stats) = stats.as_mut() {
+                            stats.add_tiles(dirty_tiles.len());
+                        }
                     }
 
                     // Apply texture to material on first paint
@@ -511,6 +1801,284 @@ fn upload_mesh_dirty_tiles(
     }
 }
 
+/// Upload the dirty tiles of a UV mesh's channel surfaces, creating each
+/// channel texture on its first paint. Returns the textures just created.
+fn upload_channel_textures(
+    painting_res: &mut MeshPaintingResource,
+    mesh_id: u32,
+    paint_texture: &mut MeshPaintTexture,
+    images: &mut Assets<Image>,
+    mut upload_buffer: Option<&mut DirtyTileUploadBuffer>,
+    mut stats: Option<&mut TextureUploadStats>,
+) -> Vec<ChannelTexture> {
+    let mut created = Vec::new();
+    for texture in ChannelTexture::ALL {
+        let channels = texture.channels();
+        let mut dirty_tiles = HashSet::new();
+        for &channel in channels {
+            if let Some(surface) = painting_res.channel_surfaces.get_mut(&(mesh_id, channel)) {
+                dirty_tiles.extend(surface.surface_mut().take_dirty_tiles());
+            }
+        }
+        if dirty_tiles.is_empty() {
+            continue;
+        }
+        let dirty_tiles: Vec<TileCoord> = dirty_tiles.into_iter().collect();
+
+        let surfaces: Vec<Option<&MeshUvSurface>> = channels
+            .iter()
+            .map(|&channel| painting_res.channel_surfaces.get(&(mesh_id, channel)))
+            .collect();
+        let Some(tiled) = surfaces
+            .iter()
+            .flatten()
+            .next()
+            .map(|surface| surface.surface())
+        else {
+            continue;
+        };
+        let sources: Vec<Option<&CpuSurface>> = surfaces
+            .iter()
+            .map(|surface| surface.map(|surface| surface.surface().surface()))
+            .collect();
+        let (width, height) = (tiled.surface().width, tiled.surface().height);
+        let defaults = paint_texture.channel_defaults;
+
+        let slot = match texture {
+            ChannelTexture::MetallicRoughness => &mut paint_texture.metallic_roughness_image,
+            ChannelTexture::Normal => &mut paint_texture.normal_image,
+            ChannelTexture::Emissive => &mut paint_texture.emissive_image,
+        };
+        let first_paint = slot.is_none();
+        let image_handle = slot
+            .get_or_insert_with(|| images.add(channel_texture_image(width, height)))
+            .clone();
+
+        match upload_buffer.as_deref_mut() {
+            Some(buffer) if !first_paint => {
+                for rect in tiled.coalesce_tile_rects(&dirty_tiles) {
+                    let (x, y, w, h) = rect;
+                    buffer.push_texels(image_handle.id(), (x, y), (w, h), 8, |data| {
+                        composite_channel_rect(texture, &sources, defaults, rect, data)
+                    });
+                }
+                if let Some(stats) = stats.as_deref_mut() {
+                    stats.add_tiles(dirty_tiles.len());
+                }
+            }
+            _ => {
+                let mut data = Vec::new();
+                composite_channel_rect(
+                    texture,
+                    &sources,
+                    defaults,
+                    (0, 0, width, height),
+                    &mut data,
+                );
+                if let Some(image) = images.get_mut(&image_handle) {
+                    image.data = Some(data);
+                }
+                if let Some(stats) = stats.as_deref_mut() {
+                    let tile_size = tiled.tile_size();
+                    stats.add_tiles(
+                        (width.div_ceil(tile_size) * height.div_ceil(tile_size)) as usize,
+                    );
+                }
+            }
+        }
+        if first_paint {
+            created.push(texture);
+        }
+    }
+    created
+}
+
+/// Point a material at a channel texture that was just created. The
+/// material's own values live on in the texture, so its scalars become 1.0.
+/// Normal maps need tangents, generated for meshes without them.
+fn wire_channel_texture(
+    texture: ChannelTexture,
+    paint_texture: &MeshPaintTexture,
+    material: &mut StandardMaterial,
+    mesh: Option<&mut Mesh>,
+) {
+    match texture {
+        ChannelTexture::MetallicRoughness => {
+            material.metallic_roughness_texture = paint_texture.metallic_roughness_image.clone();
+            material.perceptual_roughness = 1.0;
+            material.metallic = 1.0;
+        }
+        ChannelTexture::Normal => {
+            material.normal_map_texture = paint_texture.normal_image.clone();
+            if let Some(mesh) = mesh {
+                if !mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT) {
+                    if let Err(e) = mesh.generate_tangents() {
+                        warn!("Painted normals need mesh tangents: {}", e);
+                    }
+                }
+            }
+        }
+        ChannelTexture::Emissive => {
+            material.emissive_texture = paint_texture.emissive_image.clone();
+            material.emissive = LinearRgba::WHITE;
+        }
+    }
+}
+
+/// Append a rect of channel paint composited over the material's values as
+/// Rgba16Float rows; `sources` are the surfaces of `texture.channels()`
+fn composite_channel_rect(
+    texture: ChannelTexture,
+    sources: &[Option<&CpuSurface>],
+    defaults: ChannelDefaults,
+    (x, y, width, height): (u32, u32, u32, u32),
+    output: &mut Vec<u8>,
+) {
+    output.reserve((width * height * 8) as usize);
+    for py in y..y + height {
+        for px in x..x + width {
+            let paint = |i: usize| {
+                sources
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .and_then(|surface| surface.get_pixel(px, py))
+            };
+            for value in channel_texel(texture, [paint(0), paint(1)], defaults) {
+                output.extend_from_slice(&half::f16::from_f32(value).to_le_bytes());
+            }
+        }
+    }
+}
+
+/// Texel of a channel texture: paint (premultiplied, as dabs blend it onto a
+/// transparent surface) over the material's values
+fn channel_texel(
+    texture: ChannelTexture,
+    paint: [Option<[f32; 4]>; 2],
+    defaults: ChannelDefaults,
+) -> [f32; 4] {
+    let over = |paint: Option<[f32; 4]>, under: [f32; 3]| match paint {
+        Some(paint) => [0, 1, 2].map(|c| paint[c] + under[c] * (1.0 - paint[3])),
+        None => under,
+    };
+    match texture {
+        ChannelTexture::MetallicRoughness => {
+            let roughness = over(paint[0], [defaults.roughness; 3])[0];
+            let metallic = over(paint[1], [defaults.metallic; 3])[0];
+            [1.0, roughness, metallic, 1.0]
+        }
+        ChannelTexture::Normal => {
+            let [x, y, z] = over(paint[0], [0.5, 0.5, 1.0]);
+            let normal = (Vec3::new(x, y, z) * 2.0 - 1.0).normalize_or(Vec3::Z);
+            let encoded = normal * 0.5 + 0.5;
+            [encoded.x, encoded.y, encoded.z, 1.0]
+        }
+        ChannelTexture::Emissive => {
+            let [r, g, b] = over(paint[0], defaults.emissive);
+            [r, g, b, 1.0]
+        }
+    }
+}
+
+/// What paint is composited over: the material's texture, or its base color
+struct OriginalTexture<'a> {
+    /// RGBA8 data of the original texture, if the material had one
+    data: Option<&'a [u8]>,
+    /// sRGB base color, used where the texture has no data
+    base_color: (u8, u8, u8, u8),
+}
+
+/// Append a rect of paint composited over the original as RGBA8 rows
+fn composite_paint_rect(
+    cpu_surface: &CpuSurface,
+    original: &OriginalTexture,
+    (x, y, width, height): (u32, u32, u32, u32),
+    output: &mut Vec<u8>,
+) {
+    let surface_width = cpu_surface.width as usize;
+    output.reserve((width * height * 4) as usize);
+    for py in y..y + height {
+        for px in x..x + width {
+            let idx = (py as usize * surface_width + px as usize) * 4;
+
+            // Get original pixel (from texture or base color)
+            let (orig_r, orig_g, orig_b, orig_a) = match original.data {
+                Some(orig) if idx + 3 < orig.len() => {
+                    (orig[idx], orig[idx + 1], orig[idx + 2], orig[idx + 3])
+                }
+                _ => original.base_color,
+            };
+
+            // Get paint pixel
+            let pixel = match cpu_surface.get_pixel(px, py) {
+                Some(paint_pixel) if paint_pixel[3] > 0.001 => {
+                    // Alpha blend paint over original
+                    let paint_r = linear_to_srgb_u8(paint_pixel[0]);
+                    let paint_g = linear_to_srgb_u8(paint_pixel[1]);
+                    let paint_b = linear_to_srgb_u8(paint_pixel[2]);
+                    let paint_a = (paint_pixel[3] * 255.0) as u8;
+
+                    let alpha = paint_a as f32 / 255.0;
+                    let inv_alpha = 1.0 - alpha;
+
+                    [
+                        (paint_r as f32 * alpha + orig_r as f32 * inv_alpha) as u8,
+                        (paint_g as f32 * alpha + orig_g as f32 * inv_alpha) as u8,
+                        (paint_b as f32 * alpha + orig_b as f32 * inv_alpha) as u8,
+                        orig_a.max(paint_a),
+                    ]
+                }
+                // No paint, use original
+                _ => [orig_r, orig_g, orig_b, orig_a],
+            };
+            output.extend_from_slice(&pixel);
+        }
+    }
+}
+
+/// Tiles of `surface` whose pixels differ from `before`, a snapshot of the
+/// whole surface, with their snapshot pixels in row-major order
+fn changed_tiles(surface: &TiledSurface, before: &[[f32; 4]]) -> Vec<(TileCoord, Vec<[f32; 4]>)> {
+    let pixels = surface.surface().pixels();
+    if pixels.len() != before.len() {
+        return Vec::new();
+    }
+    let surface_width = surface.surface().width as usize;
+    let mut tiles = Vec::new();
+    for ty in 0..surface.tiles_y() {
+        for tx in 0..surface.tiles_x() {
+            let coord = TileCoord { x: tx, y: ty };
+            let (x, y, width, height) = surface.get_tile_bounds(coord);
+            let rows = (y..y + height).map(|row| {
+                let start = row as usize * surface_width + x as usize;
+                start..start + width as usize
+            });
+            if rows.clone().all(|row| pixels[row.clone()] == before[row]) {
+                continue;
+            }
+            tiles.push((coord, rows.flat_map(|row| before[row].to_vec()).collect()));
+        }
+    }
+    tiles
+}
+
+/// Normal map texel (tangent space, encoded to 0-1) tilting the surface
+/// along a world-space stroke direction; `strength` 1.0 leans it 45 degrees.
+fn tangent_space_normal(hit: &MeshHit, direction: Vec3, strength: f32) -> Option<[f32; 4]> {
+    let direction =
+        Vec2::new(direction.dot(hit.tangent), direction.dot(hit.bitangent)).try_normalize()?;
+    let normal = (direction * strength.clamp(0.0, 1.0))
+        .extend(1.0)
+        .normalize();
+    Some([
+        normal.x * 0.5 + 0.5,
+        normal.y * 0.5 + 0.5,
+        normal.z * 0.5 + 0.5,
+        1.0,
+    ])
+}
+
 /// Convert linear [f32; 4] color to sRGB (u8, u8, u8, u8).
 fn color_to_srgb_u8(color: [f32; 4]) -> (u8, u8, u8, u8) {
     (
@@ -530,3 +2098,210 @@ fn linear_to_srgb_u8(linear: f32) -> u8 {
     };
     (srgb.clamp(0.0, 1.0) * 255.0) as u8
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::mesh::{Indices, PrimitiveTopology};
+
+    /// Two unit quads side by side; the right one's chart has 4x the texel
+    /// density of the left one's.
+    fn two_chart_mesh() -> Mesh {
+        let positions: Vec<[f32; 3]> = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+        ];
+        let uvs: Vec<[f32; 2]> = vec![
+            [0.0, 0.0],
+            [0.2, 0.0],
+            [0.2, 0.2],
+            [0.0, 0.2],
+            [0.2, 0.0],
+            [1.0, 0.0],
+            [1.0, 0.8],
+            [0.2, 0.8],
+        ];
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(vec![0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7]))
+    }
+
+    /// Hit on the two-chart mesh at a point of its z=0 plane
+    fn hit_at(x: f32, y: f32) -> MeshHit {
+        let (local_x, chart_min, chart_size, first_face) = if x < 1.0 {
+            (x, Vec2::ZERO, 0.2, 0)
+        } else {
+            (x - 1.0, Vec2::new(0.2, 0.0), 0.8, 2)
+        };
+        MeshHit {
+            world_pos: Vec3::new(x, y, 0.0),
+            face_id: first_face + u32::from(y > local_x),
+            barycentric: Vec3::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0),
+            normal: Vec3::Z,
+            tangent: Vec3::X,
+            bitangent: Vec3::Y,
+            uv: Some(chart_min + Vec2::new(local_x, y) * chart_size),
+        }
+    }
+
+    /// Painted texels in one column of the atlas
+    fn painted_rows(painting_res: &MeshPaintingResource, column: u32) -> usize {
+        let surface = painting_res.get_uv_surface(0).unwrap().surface().surface();
+        (0..surface.height)
+            .filter(|&y| surface.get_pixel(column, y).unwrap()[3] > 0.5)
+            .count()
+    }
+
+    #[test]
+    fn test_brush_keeps_world_size_across_texel_densities() {
+        let mesh = two_chart_mesh();
+        let density = mesh_texel_density(&mesh, (512, 512)).unwrap();
+        assert!((density.faces[2] / density.faces[0] - 4.0).abs() < 0.01);
+
+        let mut painting_res = MeshPaintingResource::new();
+        painting_res.brush_preset.min_size = 1.0;
+        painting_res.brush_preset.max_size = 1.0;
+        painting_res.brush_preset.hardness = 1.0;
+        painting_res.brush_preset.opacity = 1.0;
+        painting_res.brush_color = [1.0, 0.0, 0.0, 1.0];
+        painting_res.texel_densities.insert(0, density.clone());
+        painting_res.get_or_create_uv_surface(0, 512, 512);
+
+        // A horizontal stroke through the middle of both quads, stopping short
+        // of the seam so neither chart's dabs spill into the other's column
+        for step in 0..90 {
+            let x = 0.1 + step as f32 * 0.02;
+            if (x - 1.0).abs() > 0.1 {
+                apply_dab_for_move(&mut painting_res, 0, &hit_at(x, 0.5), 1.0);
+            }
+        }
+
+        // Stroke width measured across the middle of each chart, in world units
+        let width_a = painted_rows(&painting_res, 51) as f32 / density.faces[0];
+        let width_b = painted_rows(&painting_res, 307) as f32 / density.faces[2];
+        assert!(width_a > 0.1, "stroke width {}", width_a);
+        assert!(
+            (width_a - width_b).abs() < 0.1 * width_a,
+            "stroke widths differ: {} vs {}",
+            width_a,
+            width_b
+        );
+    }
+
+    #[test]
+    fn test_roughness_stroke_paints_its_own_channel_and_undoes() {
+        let paintable = PaintableMesh {
+            mesh_id: 0,
+            storage_mode: MeshStorageMode::UvAtlas {
+                resolution: (512, 512),
+            },
+        };
+        let mut painting_res = MeshPaintingResource::new();
+        painting_res.brush_preset.base_size = 0.2;
+        painting_res.brush_preset.hardness = 1.0;
+        painting_res.brush_preset.opacity = 1.0;
+        painting_res.get_or_create_uv_surface(0, 512, 512);
+        painting_res.target_channel = MeshPaintChannel::Roughness;
+        painting_res.brush_value = 0.2;
+
+        painting_res.begin_stroke(1, &paintable);
+        apply_dab_to_mesh(&mut painting_res, &paintable, &hit_at(0.5, 0.5));
+        painting_res.end_stroke();
+
+        // UV (0.1, 0.1), with V flipped into texture rows
+        let roughness = painting_res
+            .get_channel_surface(0, MeshPaintChannel::Roughness)
+            .unwrap()
+            .surface()
+            .surface();
+        let texel = channel_texel(
+            ChannelTexture::MetallicRoughness,
+            [roughness.get_pixel(51, 460), None],
+            ChannelDefaults::default(),
+        );
+        assert!((texel[1] - 0.2).abs() < 0.01, "roughness {}", texel[1]);
+        assert_eq!(texel[2], 0.0);
+        let base_color = painting_res.get_uv_surface(0).unwrap().surface().surface();
+        assert!(base_color.pixels().iter().all(|pixel| pixel[3] == 0.0));
+
+        assert!(painting_res.undo());
+        let roughness = painting_res
+            .get_channel_surface(0, MeshPaintChannel::Roughness)
+            .unwrap()
+            .surface();
+        assert!(
+            roughness
+                .surface()
+                .pixels()
+                .iter()
+                .all(|pixel| pixel[3] == 0.0)
+        );
+        assert!(roughness.has_dirty_tiles());
+        assert!(!painting_res.undo());
+    }
+
+    #[test]
+    fn test_normal_strokes_tilt_along_their_direction() {
+        let hit = hit_at(0.5, 0.5);
+        assert_eq!(tangent_space_normal(&hit, Vec3::ZERO, 1.0), None);
+
+        let [x, y, z, _] = tangent_space_normal(&hit, Vec3::X, 1.0).unwrap();
+        let normal = Vec3::new(x, y, z) * 2.0 - 1.0;
+        assert!((normal.angle_between(Vec3::Z).to_degrees() - 45.0).abs() < 0.1);
+        assert!(normal.x > 0.0 && normal.y.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_vertex_color_stroke_paints_in_radius_and_undoes() {
+        let mut mesh = two_chart_mesh();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0f32; 4]; 8]);
+        let mesh_id = AssetId::<Mesh>::default();
+        let mut patches = MeshVertexPatches::default();
+        let mut painting_res = MeshPaintingResource::new();
+        painting_res.brush_preset.hardness = 1.0;
+        painting_res.brush_preset.opacity = 1.0;
+        painting_res.brush_color = [1.0, 0.0, 0.0, 1.0];
+
+        // Scaled 2x, so a world radius of 1.0 covers half a unit of the mesh
+        let entity = Entity::from_raw_u32(1).unwrap();
+        painting_res.begin_vertex_stroke(entity, 0, 7);
+        paint_vertex_colors(
+            &mut painting_res,
+            &mut mesh,
+            mesh_id,
+            Affine3A::from_scale(Vec3::splat(2.0)),
+            &hit_at(0.0, 0.0),
+            1.0,
+            &mut patches,
+        );
+
+        let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute(Mesh::ATTRIBUTE_COLOR)
+        else {
+            panic!("mesh lost its vertex colors");
+        };
+        assert_eq!(colors[0], [1.0, 0.0, 0.0, 1.0]);
+        assert!(colors[1..].iter().all(|color| *color == [1.0; 4]));
+        assert_eq!(patches.vertex_count(), 1);
+
+        painting_res.end_stroke();
+        assert_eq!(painting_res.vertex_color_strokes().len(), 1);
+        assert_eq!(painting_res.vertex_color_strokes()[0].dabs.len(), 1);
+
+        assert!(painting_res.undo());
+        assert!(painting_res.vertex_color_strokes().is_empty());
+        assert_eq!(
+            painting_res.vertex_color_restores,
+            vec![(entity, vec![(0, [1.0; 4])])]
+        );
+    }
+}
@@ -0,0 +1,1246 @@
+//! Material node graphs
+//!
+//! `UiToBevy::NodeGraphUpdate` carries the node graph of one material. The
+//! graph is validated by [`compile_graph`] (known node types, connections
+//! between existing sockets, socket types, one connection per input, no
+//! cycles) and sorted into evaluation order. Each input of its `Output` node
+//! then becomes either a constant `StandardMaterial` field or, when a
+//! `TextureSample` feeds it, a texture baked on the CPU at
+//! [`NodeGraphs::bake_resolution`]. Roughness and metallic bake into one
+//! texture laid out for `metallic_roughness_texture` (green and blue).
+//!
+//! Problems are reported one per `BevyToUi::Error` (code
+//! `node_graph_invalid`), each naming its node. A graph is re-evaluated only
+//! when it changed (node positions aside) or a texture it samples was
+//! modified.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+
+use bevy::asset::RenderAssetUsages;
+use bevy::ecs::message::Message;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use pentimento_ipc::{BevyToUi, NodeGraphState, NodeInfo};
+
+use crate::OutboundUiMessages;
+#[cfg(feature = "selection")]
+use crate::Selectable;
+use crate::texture_registry::TextureRegistry;
+
+/// Default size of textures baked from node graphs
+pub const DEFAULT_BAKE_RESOLUTION: u32 = 512;
+
+/// Node graph update from the UI
+#[derive(Message, Debug, Clone)]
+pub struct NodeGraphEvent(pub NodeGraphState);
+
+/// Type of the value on a socket
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SocketType {
+    Scalar,
+    Color,
+}
+
+/// A validation problem on one node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeGraphError {
+    pub node_id: String,
+    pub message: String,
+}
+
+impl NodeGraphError {
+    fn new(node_id: &str, message: impl Into<String>) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Value of a socket at one texel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeValue {
+    Scalar(f32),
+    /// Linear RGBA
+    Color([f32; 4]),
+}
+
+impl NodeValue {
+    /// As linear RGBA; scalars become opaque gray
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            Self::Scalar(value) => [value, value, value, 1.0],
+            Self::Color(color) => color,
+        }
+    }
+
+    /// As a scalar; colors give their red channel
+    pub fn scalar(self) -> f32 {
+        match self {
+            Self::Scalar(value) => value,
+            Self::Color(color) => color[0],
+        }
+    }
+
+    /// Combine two values channel by channel, broadcasting a scalar against
+    /// a color
+    fn zip(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        match (self, other) {
+            (Self::Scalar(a), Self::Scalar(b)) => Self::Scalar(f(a, b)),
+            _ => {
+                let (a, b) = (self.color(), other.color());
+                Self::Color([0, 1, 2, 3].map(|i| f(a[i], b[i])))
+            }
+        }
+    }
+}
+
+/// A node of the fixed vocabulary, with its data parsed
+#[derive(Clone, Debug, PartialEq)]
+enum NodeKind {
+    TextureSample {
+        texture_id: String,
+    },
+    ColorConstant([f32; 4]),
+    ScalarConstant(f32),
+    Multiply,
+    /// `factor` is used while the factor input isn't connected
+    Mix {
+        factor: f32,
+    },
+    Output,
+}
+
+/// An input socket: name, the type it takes (`None` for either), and
+/// whether it must be connected
+type InputSocket = (&'static str, Option<SocketType>, bool);
+
+impl NodeKind {
+    fn parse(node: &NodeInfo) -> Result<Self, String> {
+        let number = |key: &str| node.data.get(key).and_then(|value| value.as_f64());
+        match node.node_type.as_str() {
+            "TextureSample" => node
+                .data
+                .get("texture_id")
+                .and_then(|value| value.as_str())
+                .map(|texture_id| Self::TextureSample {
+                    texture_id: texture_id.to_string(),
+                })
+                .ok_or_else(|| "TextureSample needs a texture_id".to_string()),
+            "ColorConstant" => node
+                .data
+                .get("color")
+                .and_then(|value| value.as_array())
+                .filter(|channels| channels.len() == 4)
+                .and_then(|channels| {
+                    let mut color = [0.0; 4];
+                    for (out, channel) in color.iter_mut().zip(channels) {
+                        *out = channel.as_f64()? as f32;
+                    }
+                    Some(Self::ColorConstant(color))
+                })
+                .ok_or_else(|| "ColorConstant needs a color of four numbers".to_string()),
+            "ScalarConstant" => number("value")
+                .map(|value| Self::ScalarConstant(value as f32))
+                .ok_or_else(|| "ScalarConstant needs a value".to_string()),
+            "Multiply" => Ok(Self::Multiply),
+            "Mix" => Ok(Self::Mix {
+                factor: number("factor").unwrap_or(0.5) as f32,
+            }),
+            "Output" => Ok(Self::Output),
+            other => Err(format!("unknown node type {}", other)),
+        }
+    }
+
+    fn inputs(&self) -> &'static [InputSocket] {
+        use SocketType::{Color, Scalar};
+        match self {
+            Self::Multiply => &[("a", None, true), ("b", None, true)],
+            Self::Mix { .. } => &[
+                ("a", None, true),
+                ("b", None, true),
+                ("factor", Some(Scalar), false),
+            ],
+            Self::Output => &[
+                ("base_color", Some(Color), false),
+                ("roughness", Some(Scalar), false),
+                ("metallic", Some(Scalar), false),
+                ("emissive", Some(Color), false),
+            ],
+            _ => &[],
+        }
+    }
+
+    fn output_name(&self) -> Option<&'static str> {
+        match self {
+            Self::TextureSample { .. } | Self::ColorConstant(_) => Some("color"),
+            Self::ScalarConstant(_) => Some("value"),
+            Self::Multiply | Self::Mix { .. } => Some("result"),
+            Self::Output => None,
+        }
+    }
+}
+
+/// A validated graph, nodes in evaluation order
+#[derive(Debug)]
+pub struct CompiledGraph {
+    nodes: Vec<CompiledNode>,
+    /// Index of the `Output` node, if the graph has one
+    output: Option<usize>,
+}
+
+#[derive(Debug)]
+struct CompiledNode {
+    id: String,
+    kind: NodeKind,
+    /// Source node of each input, in `NodeKind::inputs` order
+    inputs: Vec<Option<usize>>,
+    output_type: SocketType,
+    /// Whether a `TextureSample` feeds the node (or is the node)
+    textured: bool,
+}
+
+/// Validate a graph and sort it into evaluation order
+pub fn compile_graph(state: &NodeGraphState) -> Result<CompiledGraph, Vec<NodeGraphError>> {
+    let mut errors = Vec::new();
+
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut kinds = Vec::new();
+    for node in &state.nodes {
+        if index.contains_key(node.id.as_str()) {
+            errors.push(NodeGraphError::new(&node.id, "duplicate node id"));
+            continue;
+        }
+        match NodeKind::parse(node) {
+            Ok(kind) => {
+                index.insert(&node.id, kinds.len());
+                kinds.push((node.id.as_str(), kind));
+            }
+            Err(message) => errors.push(NodeGraphError::new(&node.id, message)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut inputs: Vec<Vec<Option<usize>>> = kinds
+        .iter()
+        .map(|(_, kind)| vec![None; kind.inputs().len()])
+        .collect();
+    for connection in &state.connections {
+        let (Some(&from), Some(&to)) = (
+            index.get(connection.from_node.as_str()),
+            index.get(connection.to_node.as_str()),
+        ) else {
+            let (node, other) = if index.contains_key(connection.to_node.as_str()) {
+                (&connection.to_node, &connection.from_node)
+            } else {
+                (&connection.from_node, &connection.to_node)
+            };
+            errors.push(NodeGraphError::new(
+                node,
+                format!("connected to unknown node {}", other),
+            ));
+            continue;
+        };
+        let (from_id, from_kind) = &kinds[from];
+        if from_kind.output_name() != Some(connection.from_output.as_str()) {
+            errors.push(NodeGraphError::new(
+                from_id,
+                format!("has no output {}", connection.from_output),
+            ));
+            continue;
+        }
+        let (to_id, to_kind) = &kinds[to];
+        let Some(slot) = to_kind
+            .inputs()
+            .iter()
+            .position(|(name, _, _)| *name == connection.to_input)
+        else {
+            errors.push(NodeGraphError::new(
+                to_id,
+                format!("has no input {}", connection.to_input),
+            ));
+            continue;
+        };
+        if inputs[to][slot].replace(from).is_some() {
+            errors.push(NodeGraphError::new(
+                to_id,
+                format!("input {} is connected twice", connection.to_input),
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Kahn's algorithm; whatever is left over sits on a cycle
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); kinds.len()];
+    let mut pending: Vec<usize> = vec![0; kinds.len()];
+    for (to, sources) in inputs.iter().enumerate() {
+        for &from in sources.iter().flatten() {
+            dependents[from].push(to);
+            pending[to] += 1;
+        }
+    }
+    let mut ready: VecDeque<usize> = (0..kinds.len()).filter(|&i| pending[i] == 0).collect();
+    let mut order = Vec::with_capacity(kinds.len());
+    while let Some(node) = ready.pop_front() {
+        order.push(node);
+        for &dependent in &dependents[node] {
+            pending[dependent] -= 1;
+            if pending[dependent] == 0 {
+                ready.push_back(dependent);
+            }
+        }
+    }
+    if order.len() < kinds.len() {
+        let sorted: HashSet<usize> = order.into_iter().collect();
+        return Err((0..kinds.len())
+            .filter(|i| !sorted.contains(i))
+            .map(|i| NodeGraphError::new(kinds[i].0, "is part of a cycle"))
+            .collect());
+    }
+
+    let mut position = vec![0; kinds.len()];
+    for (sorted, &node) in order.iter().enumerate() {
+        position[node] = sorted;
+    }
+    let mut nodes: Vec<CompiledNode> = Vec::with_capacity(order.len());
+    let mut output = None;
+    for &node in &order {
+        let (id, kind) = &kinds[node];
+        let sources: Vec<Option<usize>> = inputs[node]
+            .iter()
+            .map(|source| source.map(|source| position[source]))
+            .collect();
+        for (&(name, expected, required), source) in kind.inputs().iter().zip(&sources) {
+            match source {
+                Some(source) => {
+                    let source = &nodes[*source];
+                    if expected == Some(SocketType::Scalar)
+                        && source.output_type == SocketType::Color
+                    {
+                        errors.push(NodeGraphError::new(
+                            id,
+                            format!(
+                                "input {} takes a scalar, got a color from {}",
+                                name, source.id
+                            ),
+                        ));
+                    }
+                }
+                None if required => {
+                    errors.push(NodeGraphError::new(
+                        id,
+                        format!("input {} is not connected", name),
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        let source_type = |slot: usize| {
+            sources[slot].map_or(SocketType::Scalar, |source| nodes[source].output_type)
+        };
+        let output_type = match kind {
+            NodeKind::TextureSample { .. } | NodeKind::ColorConstant(_) => SocketType::Color,
+            NodeKind::ScalarConstant(_) | NodeKind::Output => SocketType::Scalar,
+            NodeKind::Multiply | NodeKind::Mix { .. } => {
+                if source_type(0) == SocketType::Color || source_type(1) == SocketType::Color {
+                    SocketType::Color
+                } else {
+                    SocketType::Scalar
+                }
+            }
+        };
+        let textured = matches!(kind, NodeKind::TextureSample { .. })
+            || sources
+                .iter()
+                .flatten()
+                .any(|&source| nodes[source].textured);
+        if *kind == NodeKind::Output {
+            if output.is_some() {
+                errors.push(NodeGraphError::new(
+                    id,
+                    "only one Output node can drive the material",
+                ));
+            }
+            output = Some(nodes.len());
+        }
+        nodes.push(CompiledNode {
+            id: id.to_string(),
+            kind: kind.clone(),
+            inputs: sources,
+            output_type,
+            textured,
+        });
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(CompiledGraph { nodes, output })
+}
+
+/// What an `Output` input evaluates to
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputValue {
+    Constant(NodeValue),
+    /// Row-major linear RGBA texels at the bake resolution
+    Baked(Vec<[f32; 4]>),
+}
+
+/// Evaluated outputs of a graph, `None` where an input isn't connected
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MaterialOutputs {
+    pub base_color: Option<OutputValue>,
+    pub roughness: Option<OutputValue>,
+    pub metallic: Option<OutputValue>,
+    pub emissive: Option<OutputValue>,
+}
+
+impl CompiledGraph {
+    /// `TextureSample` nodes, as (node id, texture id)
+    pub fn texture_samples(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.nodes.iter().filter_map(|node| match &node.kind {
+            NodeKind::TextureSample { texture_id } => Some((node.id.as_str(), texture_id.as_str())),
+            _ => None,
+        })
+    }
+
+    /// Evaluate every connected `Output` input, baking those a texture feeds
+    /// at `resolution` squared
+    pub fn bake(
+        &self,
+        textures: &HashMap<String, TextureSampler>,
+        resolution: u32,
+    ) -> MaterialOutputs {
+        let output = |name: &str| {
+            let output = &self.nodes[self.output?];
+            let slot = output
+                .kind
+                .inputs()
+                .iter()
+                .position(|(input, _, _)| *input == name)?;
+            let source = output.inputs[slot]?;
+            Some(self.bake_node(source, textures, resolution))
+        };
+        MaterialOutputs {
+            base_color: output("base_color"),
+            roughness: output("roughness"),
+            metallic: output("metallic"),
+            emissive: output("emissive"),
+        }
+    }
+
+    fn bake_node(
+        &self,
+        target: usize,
+        textures: &HashMap<String, TextureSampler>,
+        resolution: u32,
+    ) -> OutputValue {
+        // Only the nodes feeding the target are evaluated
+        let mut needed = vec![false; self.nodes.len()];
+        needed[target] = true;
+        for node in (0..=target).rev() {
+            if needed[node] {
+                for &source in self.nodes[node].inputs.iter().flatten() {
+                    needed[source] = true;
+                }
+            }
+        }
+        let mut values = vec![NodeValue::Scalar(0.0); self.nodes.len()];
+        if !self.nodes[target].textured {
+            return OutputValue::Constant(self.evaluate(
+                target,
+                Vec2::ZERO,
+                &needed,
+                textures,
+                &mut values,
+            ));
+        }
+
+        let texels = (0..resolution * resolution)
+            .map(|i| {
+                let uv = Vec2::new(
+                    ((i % resolution) as f32 + 0.5) / resolution as f32,
+                    ((i / resolution) as f32 + 0.5) / resolution as f32,
+                );
+                self.evaluate(target, uv, &needed, textures, &mut values)
+                    .color()
+            })
+            .collect();
+        OutputValue::Baked(texels)
+    }
+
+    /// Value of node `target` at `uv`; `values` is scratch space holding one
+    /// value per node
+    fn evaluate(
+        &self,
+        target: usize,
+        uv: Vec2,
+        needed: &[bool],
+        textures: &HashMap<String, TextureSampler>,
+        values: &mut [NodeValue],
+    ) -> NodeValue {
+        for (i, node) in self.nodes[..=target].iter().enumerate() {
+            if !needed[i] {
+                continue;
+            }
+            let input = |slot: usize| node.inputs[slot].map(|source| values[source]);
+            values[i] = match &node.kind {
+                NodeKind::TextureSample { texture_id } => NodeValue::Color(
+                    textures
+                        .get(texture_id)
+                        .map_or([1.0, 0.0, 1.0, 1.0], |texture| texture.sample(uv)),
+                ),
+                NodeKind::ColorConstant(color) => NodeValue::Color(*color),
+                NodeKind::ScalarConstant(value) => NodeValue::Scalar(*value),
+                NodeKind::Multiply => match (input(0), input(1)) {
+                    (Some(a), Some(b)) => a.zip(b, |a, b| a * b),
+                    _ => NodeValue::Scalar(0.0),
+                },
+                NodeKind::Mix { factor } => {
+                    let factor = input(2).map_or(*factor, NodeValue::scalar);
+                    match (input(0), input(1)) {
+                        (Some(a), Some(b)) => a.zip(b, |a, b| a + (b - a) * factor),
+                        _ => NodeValue::Scalar(0.0),
+                    }
+                }
+                NodeKind::Output => NodeValue::Scalar(0.0),
+            };
+        }
+        values[target]
+    }
+}
+
+/// Linear RGBA texels of a texture a graph samples
+pub struct TextureSampler {
+    width: u32,
+    height: u32,
+    texels: Vec<[f32; 4]>,
+}
+
+impl TextureSampler {
+    /// Decode an RGBA8 image (sRGB or linear)
+    pub fn from_image(image: &Image) -> Result<Self, String> {
+        let srgb = match image.texture_descriptor.format {
+            TextureFormat::Rgba8UnormSrgb => true,
+            TextureFormat::Rgba8Unorm => false,
+            other => return Err(format!("unsupported texture format {:?}", other)),
+        };
+        let data = image
+            .data
+            .as_deref()
+            .ok_or_else(|| "the texture has no CPU data".to_string())?;
+        let decode = |value: u8| {
+            let value = value as f32 / 255.0;
+            if srgb {
+                Srgba::gamma_function(value)
+            } else {
+                value
+            }
+        };
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            texels: data
+                .chunks_exact(4)
+                .map(|texel| {
+                    [
+                        decode(texel[0]),
+                        decode(texel[1]),
+                        decode(texel[2]),
+                        texel[3] as f32 / 255.0,
+                    ]
+                })
+                .collect(),
+        })
+    }
+
+    /// Bilinear sample at `uv`, repeating past the edges
+    pub fn sample(&self, uv: Vec2) -> [f32; 4] {
+        if self.texels.is_empty() {
+            return [0.0; 4];
+        }
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = uv.y * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let texel = |dx: i64, dy: i64| {
+            let px = (x0 as i64 + dx).rem_euclid(self.width as i64) as usize;
+            let py = (y0 as i64 + dy).rem_euclid(self.height as i64) as usize;
+            self.texels
+                .get(py * self.width as usize + px)
+                .copied()
+                .unwrap_or_default()
+        };
+        let (t00, t10, t01, t11) = (texel(0, 0), texel(1, 0), texel(0, 1), texel(1, 1));
+        [0, 1, 2, 3].map(|c| {
+            let top = t00[c] + (t10[c] - t00[c]) * fx;
+            let bottom = t01[c] + (t11[c] - t01[c]) * fx;
+            top + (bottom - top) * fy
+        })
+    }
+}
+
+/// Textures baked for a material, reused by later bakes
+#[derive(Default)]
+struct BakedTextures {
+    base_color: Option<Handle<Image>>,
+    metallic_roughness: Option<Handle<Image>>,
+    emissive: Option<Handle<Image>>,
+}
+
+/// Graph state of one material
+struct MaterialGraph {
+    entity: Entity,
+    /// Hash of the graph last applied (see `graph_hash`)
+    hash: u64,
+    graph: NodeGraphState,
+    /// Textures the graph samples; modifying one re-bakes the graph
+    sampled: Vec<AssetId<Image>>,
+    baked: BakedTextures,
+    /// Material roughness and metallic from before the graph, baked into the
+    /// metallic/roughness texture where the graph leaves them unconnected
+    fallback: (f32, f32),
+}
+
+/// Node graphs driving materials, keyed by material ID
+#[derive(Resource)]
+pub struct NodeGraphs {
+    /// Size of the textures baked from graphs with texture nodes
+    pub bake_resolution: u32,
+    materials: HashMap<String, MaterialGraph>,
+}
+
+impl Default for NodeGraphs {
+    fn default() -> Self {
+        Self {
+            bake_resolution: DEFAULT_BAKE_RESOLUTION,
+            materials: HashMap::new(),
+        }
+    }
+}
+
+impl NodeGraphs {
+    /// Move the graph of material `from` to the ID `to` (a restored ID)
+    pub(crate) fn rename_material(&mut self, from: &str, to: &str) {
+        if let Some(mut graph) = self.materials.remove(from) {
+            graph.graph.material_id = to.to_string();
+            self.materials.insert(to.to_string(), graph);
+        }
+    }
+}
+
+/// Plugin evaluating material node graphs
+pub struct NodeGraphPlugin;
+
+impl Plugin for NodeGraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NodeGraphs>()
+            .add_message::<NodeGraphEvent>()
+            .add_systems(
+                Update,
+                (apply_node_graphs, release_removed_graph_materials).chain(),
+            );
+    }
+}
+
+/// Apply graphs the UI changed, and graphs sampling a modified texture, to
+/// their materials
+#[allow(clippy::too_many_arguments)]
+fn apply_node_graphs(
+    mut events: MessageReader<NodeGraphEvent>,
+    mut image_events: MessageReader<AssetEvent<Image>>,
+    mut graphs: ResMut<NodeGraphs>,
+    registry: Res<TextureRegistry>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mesh_materials: Query<&MeshMaterial3d<StandardMaterial>>,
+    #[cfg(feature = "selection")] selectables: Query<(Entity, &Selectable)>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let graphs = &mut *graphs;
+    let mut pending: Vec<NodeGraphState> = Vec::new();
+    for NodeGraphEvent(state) in events.read() {
+        let hash = graph_hash(state, graphs.bake_resolution);
+        if graphs
+            .materials
+            .get(&state.material_id)
+            .is_some_and(|graph| graph.hash == hash)
+        {
+            debug!("Node graph of {} is unchanged", state.material_id);
+            continue;
+        }
+        pending.retain(|queued| queued.material_id != state.material_id);
+        pending.push(state.clone());
+    }
+    let modified: HashSet<AssetId<Image>> = image_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    for (material_id, graph) in &graphs.materials {
+        if graph.sampled.iter().any(|id| modified.contains(id))
+            && !pending
+                .iter()
+                .any(|queued| &queued.material_id == material_id)
+        {
+            pending.push(graph.graph.clone());
+        }
+    }
+
+    for state in pending {
+        #[cfg(feature = "selection")]
+        let entity = selectables
+            .iter()
+            .find(|(_, selectable)| selectable.id == state.material_id)
+            .map(|(entity, _)| entity);
+        #[cfg(not(feature = "selection"))]
+        let entity: Option<Entity> = None;
+        let Some((entity, material)) = entity.and_then(|entity| {
+            let handle = mesh_materials.get(entity).ok()?;
+            Some((entity, materials.get_mut(&handle.0)?))
+        }) else {
+            warn!("No material {} for its node graph", state.material_id);
+            outbound.send(BevyToUi::Error {
+                code: "node_graph_failed".to_string(),
+                message: format!("Unknown material {}", state.material_id),
+            });
+            continue;
+        };
+
+        let graph = graphs
+            .materials
+            .entry(state.material_id.clone())
+            .or_insert_with(|| MaterialGraph {
+                entity,
+                hash: 0,
+                graph: NodeGraphState::default(),
+                sampled: Vec::new(),
+                baked: BakedTextures::default(),
+                fallback: (material.perceptual_roughness, material.metallic),
+            });
+        match apply_graph(
+            &state,
+            graph,
+            material,
+            &registry,
+            &mut images,
+            graphs.bake_resolution,
+        ) {
+            Ok(()) => {
+                graph.hash = graph_hash(&state, graphs.bake_resolution);
+                graph.graph = state;
+                info!("Applied node graph of {}", graph.graph.material_id);
+            }
+            Err(errors) => {
+                for error in errors {
+                    warn!(
+                        "Node graph of {}: node {} {}",
+                        state.material_id, error.node_id, error.message
+                    );
+                    outbound.send(BevyToUi::Error {
+                        code: "node_graph_invalid".to_string(),
+                        message: format!(
+                            "Node {} of material {}: {}",
+                            error.node_id, state.material_id, error.message
+                        ),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Drop the graphs (and baked textures) of materials whose entity went away
+fn release_removed_graph_materials(
+    mut removed: RemovedComponents<MeshMaterial3d<StandardMaterial>>,
+    mut graphs: ResMut<NodeGraphs>,
+) {
+    for entity in removed.read() {
+        graphs.materials.retain(|_, graph| graph.entity != entity);
+    }
+}
+
+/// Hash of what a graph evaluates to: its nodes (positions aside),
+/// connections, and the bake resolution
+fn graph_hash(state: &NodeGraphState, resolution: u32) -> u64 {
+    let mut nodes: Vec<(&str, &str, String)> = state
+        .nodes
+        .iter()
+        .map(|node| {
+            (
+                node.id.as_str(),
+                node.node_type.as_str(),
+                node.data.to_string(),
+            )
+        })
+        .collect();
+    nodes.sort();
+    let mut connections: Vec<(&str, &str, &str, &str)> = state
+        .connections
+        .iter()
+        .map(|connection| {
+            (
+                connection.from_node.as_str(),
+                connection.from_output.as_str(),
+                connection.to_node.as_str(),
+                connection.to_input.as_str(),
+            )
+        })
+        .collect();
+    connections.sort();
+
+    let mut hasher = DefaultHasher::new();
+    resolution.hash(&mut hasher);
+    nodes.hash(&mut hasher);
+    connections.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Validate and evaluate a graph, then write its outputs into the material
+fn apply_graph(
+    state: &NodeGraphState,
+    graph: &mut MaterialGraph,
+    material: &mut StandardMaterial,
+    registry: &TextureRegistry,
+    images: &mut Assets<Image>,
+    resolution: u32,
+) -> Result<(), Vec<NodeGraphError>> {
+    let compiled = compile_graph(state)?;
+
+    let mut samplers = HashMap::new();
+    let mut sampled = Vec::new();
+    let mut errors = Vec::new();
+    for (node_id, texture_id) in compiled.texture_samples() {
+        let Some(handle) = registry.get(texture_id) else {
+            errors.push(NodeGraphError::new(
+                node_id,
+                format!("unknown texture {}", texture_id),
+            ));
+            continue;
+        };
+        match images.get(handle).map(TextureSampler::from_image) {
+            Some(Ok(sampler)) => {
+                sampled.push(handle.id());
+                samplers.insert(texture_id.to_string(), sampler);
+            }
+            Some(Err(message)) => errors.push(NodeGraphError::new(node_id, message)),
+            None => errors.push(NodeGraphError::new(
+                node_id,
+                format!("texture {} is not loaded", texture_id),
+            )),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let outputs = compiled.bake(&samplers, resolution);
+    apply_outputs(outputs, graph, material, images, resolution);
+    graph.sampled = sampled;
+    Ok(())
+}
+
+/// Write evaluated outputs into the material's fields and baked textures
+fn apply_outputs(
+    outputs: MaterialOutputs,
+    graph: &mut MaterialGraph,
+    material: &mut StandardMaterial,
+    images: &mut Assets<Image>,
+    resolution: u32,
+) {
+    let baked = &mut graph.baked;
+    match outputs.base_color {
+        Some(OutputValue::Constant(value)) => {
+            clear_baked(&mut material.base_color_texture, &mut baked.base_color);
+            material.base_color = Color::LinearRgba(linear(value.color()));
+        }
+        Some(OutputValue::Baked(texels)) => {
+            material.base_color_texture = Some(store_baked(
+                &mut baked.base_color,
+                images,
+                &texels,
+                resolution,
+                true,
+            ));
+            material.base_color = Color::WHITE;
+        }
+        None => {
+            clear_baked(&mut material.base_color_texture, &mut baked.base_color);
+        }
+    }
+
+    let (fallback_roughness, fallback_metallic) = graph.fallback;
+    let (roughness, metallic) = (outputs.roughness, outputs.metallic);
+    if matches!(roughness, Some(OutputValue::Baked(_)))
+        || matches!(metallic, Some(OutputValue::Baked(_)))
+    {
+        let scalar = |output: &Option<OutputValue>, texel: usize, fallback: f32| match output {
+            Some(OutputValue::Baked(texels)) => texels[texel][0],
+            Some(OutputValue::Constant(value)) => value.scalar(),
+            None => fallback,
+        };
+        let texels: Vec<[f32; 4]> = (0..(resolution * resolution) as usize)
+            .map(|texel| {
+                [
+                    1.0,
+                    scalar(&roughness, texel, fallback_roughness),
+                    scalar(&metallic, texel, fallback_metallic),
+                    1.0,
+                ]
+            })
+            .collect();
+        material.metallic_roughness_texture = Some(store_baked(
+            &mut baked.metallic_roughness,
+            images,
+            &texels,
+            resolution,
+            false,
+        ));
+        material.perceptual_roughness = 1.0;
+        material.metallic = 1.0;
+    } else {
+        if clear_baked(
+            &mut material.metallic_roughness_texture,
+            &mut baked.metallic_roughness,
+        ) {
+            material.perceptual_roughness = fallback_roughness;
+            material.metallic = fallback_metallic;
+        }
+        if let Some(OutputValue::Constant(value)) = roughness {
+            material.perceptual_roughness = value.scalar();
+        }
+        if let Some(OutputValue::Constant(value)) = metallic {
+            material.metallic = value.scalar();
+        }
+    }
+
+    match outputs.emissive {
+        Some(OutputValue::Constant(value)) => {
+            clear_baked(&mut material.emissive_texture, &mut baked.emissive);
+            material.emissive = linear(value.color());
+        }
+        Some(OutputValue::Baked(texels)) => {
+            material.emissive_texture = Some(store_baked(
+                &mut baked.emissive,
+                images,
+                &texels,
+                resolution,
+                true,
+            ));
+            material.emissive = LinearRgba::WHITE;
+        }
+        None => {
+            clear_baked(&mut material.emissive_texture, &mut baked.emissive);
+        }
+    }
+}
+
+fn linear(color: [f32; 4]) -> LinearRgba {
+    LinearRgba::new(color[0], color[1], color[2], color[3])
+}
+
+/// Write baked texels into the material's baked texture, creating it on the
+/// first bake (the asset is kept so the material's handle stays valid)
+fn store_baked(
+    slot: &mut Option<Handle<Image>>,
+    images: &mut Assets<Image>,
+    texels: &[[f32; 4]],
+    resolution: u32,
+    srgb: bool,
+) -> Handle<Image> {
+    let image = baked_image(texels, resolution, srgb);
+    if let Some(handle) = slot.as_ref()
+        && let Some(existing) = images.get_mut(handle)
+    {
+        *existing = image;
+        return handle.clone();
+    }
+    let handle = images.add(image);
+    *slot = Some(handle.clone());
+    handle
+}
+
+/// Drop a baked texture, taking it out of the material slot still showing it.
+/// Returns whether there was one.
+fn clear_baked(texture: &mut Option<Handle<Image>>, baked: &mut Option<Handle<Image>>) -> bool {
+    let Some(handle) = baked.take() else {
+        return false;
+    };
+    if texture.as_ref() == Some(&handle) {
+        *texture = None;
+    }
+    true
+}
+
+/// RGBA8 image of baked linear texels, sRGB-encoded for color slots
+fn baked_image(texels: &[[f32; 4]], resolution: u32, srgb: bool) -> Image {
+    let encode = |value: f32| {
+        let value = if srgb {
+            Srgba::gamma_function_inverse(value)
+        } else {
+            value
+        };
+        (value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+    };
+    let data = texels
+        .iter()
+        .flat_map(|texel| {
+            [
+                encode(texel[0]),
+                encode(texel[1]),
+                encode(texel[2]),
+                (texel[3].clamp(0.0, 1.0) * 255.0 + 0.5) as u8,
+            ]
+        })
+        .collect();
+    Image::new(
+        Extent3d {
+            width: resolution,
+            height: resolution,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        if srgb {
+            TextureFormat::Rgba8UnormSrgb
+        } else {
+            TextureFormat::Rgba8Unorm
+        },
+        RenderAssetUsages::default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pentimento_ipc::NodeConnection;
+
+    fn node(id: &str, node_type: &str, data: serde_json::Value) -> NodeInfo {
+        NodeInfo {
+            id: id.into(),
+            node_type: node_type.into(),
+            position: [0.0, 0.0],
+            data,
+        }
+    }
+
+    fn connect(
+        from_node: &str,
+        from_output: &str,
+        to_node: &str,
+        to_input: &str,
+    ) -> NodeConnection {
+        NodeConnection {
+            from_node: from_node.into(),
+            from_output: from_output.into(),
+            to_node: to_node.into(),
+            to_input: to_input.into(),
+        }
+    }
+
+    /// 2x2 linear RGBA8 texture
+    fn texture(texels: [[u8; 4]; 4]) -> Image {
+        Image::new(
+            Extent3d {
+                width: 2,
+                height: 2,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            texels.concat(),
+            TextureFormat::Rgba8Unorm,
+            RenderAssetUsages::default(),
+        )
+    }
+
+    fn graph(state: &NodeGraphState) -> MaterialGraph {
+        MaterialGraph {
+            entity: Entity::PLACEHOLDER,
+            hash: 0,
+            graph: state.clone(),
+            sampled: Vec::new(),
+            baked: BakedTextures::default(),
+            fallback: (0.5, 0.0),
+        }
+    }
+
+    #[test]
+    fn test_cycle_is_rejected() {
+        let state = NodeGraphState {
+            material_id: "object-1".into(),
+            nodes: vec![
+                node("one", "ScalarConstant", serde_json::json!({ "value": 1.0 })),
+                node("a", "Multiply", serde_json::Value::Null),
+                node("b", "Multiply", serde_json::Value::Null),
+                node("output", "Output", serde_json::Value::Null),
+            ],
+            connections: vec![
+                connect("one", "value", "a", "a"),
+                connect("b", "result", "a", "b"),
+                connect("one", "value", "b", "a"),
+                connect("a", "result", "b", "b"),
+                connect("a", "result", "output", "roughness"),
+            ],
+        };
+
+        let errors = compile_graph(&state).unwrap_err();
+        let mut cyclic: Vec<&str> = errors.iter().map(|e| e.node_id.as_str()).collect();
+        cyclic.sort();
+        assert_eq!(cyclic, ["a", "b", "output"]);
+        assert!(errors.iter().all(|e| e.message.contains("cycle")));
+    }
+
+    #[test]
+    fn test_type_mismatch_names_the_node() {
+        let state = NodeGraphState {
+            material_id: "object-1".into(),
+            nodes: vec![
+                node(
+                    "red",
+                    "ColorConstant",
+                    serde_json::json!({ "color": [1.0, 0.0, 0.0, 1.0] }),
+                ),
+                node("output", "Output", serde_json::Value::Null),
+            ],
+            connections: vec![connect("red", "color", "output", "roughness")],
+        };
+
+        let errors = compile_graph(&state).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].node_id, "output");
+        assert!(errors[0].message.contains("scalar"));
+    }
+
+    #[test]
+    fn test_constant_graph_sets_material_fields() {
+        let state = NodeGraphState {
+            material_id: "object-1".into(),
+            nodes: vec![
+                node(
+                    "half",
+                    "ScalarConstant",
+                    serde_json::json!({ "value": 0.5 }),
+                ),
+                node(
+                    "rough",
+                    "ScalarConstant",
+                    serde_json::json!({ "value": 0.6 }),
+                ),
+                node("m", "Multiply", serde_json::Value::Null),
+                node("output", "Output", serde_json::Value::Null),
+            ],
+            connections: vec![
+                connect("half", "value", "m", "a"),
+                connect("rough", "value", "m", "b"),
+                connect("m", "result", "output", "roughness"),
+            ],
+        };
+        let mut images = Assets::<Image>::default();
+        let mut material = StandardMaterial::default();
+
+        apply_graph(
+            &state,
+            &mut graph(&state),
+            &mut material,
+            &TextureRegistry::default(),
+            &mut images,
+            4,
+        )
+        .unwrap();
+        assert!((material.perceptual_roughness - 0.3).abs() < 1e-6);
+        assert!(material.metallic_roughness_texture.is_none());
+        assert_eq!(images.len(), 0);
+    }
+
+    #[test]
+    fn test_mix_of_two_textures_matches_reference() {
+        let mut images = Assets::<Image>::default();
+        let mut registry = TextureRegistry::default();
+        registry.insert(
+            "primaries",
+            texture([
+                [255, 0, 0, 255],
+                [0, 255, 0, 255],
+                [0, 0, 255, 255],
+                [255, 255, 255, 255],
+            ]),
+            &mut images,
+        );
+        registry.insert("black", texture([[0, 0, 0, 255]; 4]), &mut images);
+        let state = NodeGraphState {
+            material_id: "object-1".into(),
+            nodes: vec![
+                node(
+                    "a",
+                    "TextureSample",
+                    serde_json::json!({ "texture_id": "primaries" }),
+                ),
+                node(
+                    "b",
+                    "TextureSample",
+                    serde_json::json!({ "texture_id": "black" }),
+                ),
+                node("mix", "Mix", serde_json::json!({ "factor": 0.5 })),
+                node("output", "Output", serde_json::Value::Null),
+            ],
+            connections: vec![
+                connect("a", "color", "mix", "a"),
+                connect("b", "color", "mix", "b"),
+                connect("mix", "result", "output", "base_color"),
+            ],
+        };
+        let mut material = StandardMaterial::default();
+        let mut material_graph = graph(&state);
+
+        apply_graph(
+            &state,
+            &mut material_graph,
+            &mut material,
+            &registry,
+            &mut images,
+            2,
+        )
+        .unwrap();
+
+        // Linear 0.5 is sRGB 188
+        let reference: [u8; 16] = [
+            188, 0, 0, 255, 0, 188, 0, 255, 0, 0, 188, 255, 188, 188, 188, 255,
+        ];
+        let baked = images
+            .get(material.base_color_texture.as_ref().unwrap())
+            .unwrap();
+        assert_eq!(
+            baked.texture_descriptor.format,
+            TextureFormat::Rgba8UnormSrgb
+        );
+        assert_eq!(baked.data.as_deref().unwrap(), &reference);
+        assert_eq!(material.base_color, Color::WHITE);
+        assert_eq!(material_graph.sampled.len(), 2);
+    }
+
+    #[test]
+    fn test_hash_ignores_node_positions() {
+        let mut state = NodeGraphState {
+            material_id: "object-1".into(),
+            nodes: vec![node(
+                "rough",
+                "ScalarConstant",
+                serde_json::json!({ "value": 0.6 }),
+            )],
+            connections: Vec::new(),
+        };
+        let hash = graph_hash(&state, 512);
+        state.nodes[0].position = [40.0, 10.0];
+        assert_eq!(graph_hash(&state, 512), hash);
+        state.nodes[0].data = serde_json::json!({ "value": 0.7 });
+        assert_ne!(graph_hash(&state, 512), hash);
+    }
+}
@@ -3,11 +3,13 @@
 //! Shows a visual indicator of the surface normal wherever the cursor
 //! intersects meshes in the scene. In paint mode, constrained to the active mesh.
 
+use bevy::mesh::VertexAttributeValues;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
 use crate::camera::MainCamera;
 use crate::mesh_paint_mode::{MeshPaintState, PaintableMesh, ray_mesh_intersection};
+use crate::scene_bvh::{SceneBvh, SceneHit};
 
 /// Resource controlling the normal indicator visualization
 #[derive(Resource)]
@@ -46,6 +48,7 @@ impl Default for NormalIndicatorState {
 }
 
 /// System that raycasts from cursor to find mesh intersections
+#[allow(clippy::too_many_arguments)]
 fn update_normal_indicator(
     mut indicator: ResMut<NormalIndicatorState>,
     windows: Query<&Window, With<PrimaryWindow>>,
@@ -56,6 +59,7 @@ fn update_normal_indicator(
     // Query paintable meshes for paint mode
     paintable_meshes: Query<(Entity, &Mesh3d, &GlobalTransform), With<PaintableMesh>>,
     meshes: Res<Assets<Mesh>>,
+    bvh: Res<SceneBvh>,
 ) {
     // Clear previous hit
     indicator.current_hit = None;
@@ -96,20 +100,16 @@ fn update_normal_indicator(
                 }
             }
         }
-    } else {
-        // Normal mode: test all meshes
-        for (_entity, mesh_handle, transform) in all_meshes.iter() {
-            let Some(mesh) = meshes.get(&mesh_handle.0) else {
-                continue;
-            };
-
-            if let Some(hit) = ray_mesh_intersection(&ray, mesh, transform) {
-                let distance = ray.origin.distance(hit.world_pos);
-                if closest.is_none() || distance < closest.as_ref().unwrap().2 {
-                    closest = Some((hit.world_pos, hit.normal, distance));
-                }
-            }
-        }
+    } else if let Some(hit) = bvh.raycast(ray, |_| true) {
+        // Normal mode: nearest hit on any visible mesh
+        let normal = all_meshes
+            .get(hit.entity)
+            .ok()
+            .and_then(|(_, mesh_handle, transform)| {
+                smooth_normal(meshes.get(&mesh_handle.0)?, &hit, transform)
+            })
+            .unwrap_or(hit.normal);
+        closest = Some((hit.position, normal, hit.distance));
     }
 
     // Store the closest hit
@@ -118,6 +118,21 @@ fn update_normal_indicator(
     }
 }
 
+/// World-space vertex normal interpolated at a scene hit, if the mesh has normals
+fn smooth_normal(mesh: &Mesh, hit: &SceneHit, transform: &GlobalTransform) -> Option<Vec3> {
+    let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+    else {
+        return None;
+    };
+    let mut corners = mesh.indices()?.iter().skip(hit.face_index as usize * 3);
+    let mut normal = Vec3::ZERO;
+    for weight in hit.barycentric.to_array() {
+        normal += Vec3::from(*normals.get(corners.next()?)?) * weight;
+    }
+    // Rotation only, like `ray_mesh_intersection`
+    (transform.rotation() * normal).try_normalize()
+}
+
 /// System that renders the normal indicator as a gizmo line
 /// Uses double-pass rendering for visibility against any background
 fn render_normal_indicator(indicator: Res<NormalIndicatorState>, mut gizmos: Gizmos) {
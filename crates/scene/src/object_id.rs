@@ -0,0 +1,517 @@
+//! Stable object IDs
+//!
+//! Every scene object's `Selectable` ID, which also addresses its material,
+//! comes from [`IdAllocator`]: a UUIDv7 string, unique across sessions so it
+//! survives save and load, and time-ordered so sorting by ID lists objects in
+//! creation order. Duplicates get fresh IDs and report their source with
+//! `ObjectAdded::duplicated_from`.
+//!
+//! [`RestoreObjectsEvent`] applies saved objects (an autosave) to the scene.
+//! Saved objects are matched to live ones by ID, then by name, and matched
+//! objects get their saved IDs back, so selections, node graphs, and saved
+//! references to them keep working.
+//!
+//! Every new `Selectable` is checked against the live objects; an ID that is
+//! already taken is logged and replaced with a fresh one.
+//!
+//! Input recordings and replays use [`IdAllocator::sequential`], so a
+//! replayed UI message names the same object it did when it was recorded.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use pentimento_ipc::SceneObject;
+#[cfg(feature = "selection")]
+use pentimento_ipc::{BevyToUi, SceneInfo};
+use uuid::Uuid;
+
+#[cfg(feature = "selection")]
+use crate::OutboundUiMessages;
+#[cfg(feature = "selection")]
+use crate::hierarchy::{SceneObjectFilter, SceneObjects};
+#[cfg(feature = "selection")]
+use crate::measure::SceneMeasurements;
+#[cfg(feature = "selection")]
+use crate::node_graph::NodeGraphs;
+#[cfg(feature = "selection")]
+use crate::scene_light::SceneLights;
+#[cfg(feature = "selection")]
+use crate::selection::{Locked, Selectable, SelectionState};
+
+/// Source of object IDs
+#[derive(Resource, Default)]
+pub struct IdAllocator {
+    /// Every ID handed out or reserved this session
+    issued: HashSet<String>,
+    /// Last sequential ID handed out, when not using UUIDv7s
+    sequence: Option<u128>,
+}
+
+impl IdAllocator {
+    /// Allocator handing out the same IDs every run: UUIDs 1, 2, 3, ... in
+    /// order, which still sort in creation order
+    pub fn sequential() -> Self {
+        Self {
+            sequence: Some(0),
+            ..default()
+        }
+    }
+
+    /// A fresh object ID
+    pub fn allocate(&mut self) -> String {
+        loop {
+            let uuid = match &mut self.sequence {
+                Some(last) => {
+                    *last += 1;
+                    Uuid::from_u128(*last)
+                }
+                None => Uuid::now_v7(),
+            };
+            let id = uuid.to_string();
+            if self.issued.insert(id.clone()) {
+                return id;
+            }
+            warn!("Generated object ID {} twice, generating another", id);
+        }
+    }
+
+    /// Record an ID that didn't come from `allocate` (a restored one), so
+    /// `allocate` never hands it out
+    pub fn reserve(&mut self, id: &str) {
+        if !self.issued.contains(id) {
+            self.issued.insert(id.to_string());
+        }
+    }
+}
+
+/// Saved objects to apply to the scene, e.g. from an autosave
+#[derive(Message, Debug, Clone)]
+pub struct RestoreObjectsEvent(pub Vec<SceneObject>);
+
+/// Scene objects as a restore changes them
+#[cfg(feature = "selection")]
+type RestoredObject = (
+    Entity,
+    &'static mut Selectable,
+    &'static Name,
+    &'static mut Transform,
+    &'static mut Visibility,
+    Has<Locked>,
+);
+
+/// Whether a restore changed the scene since the last `SceneUpdated`
+#[cfg(feature = "selection")]
+#[derive(Resource, Default)]
+struct ObjectsRestored(bool);
+
+/// Plugin for object ID allocation, collision checks, and restore
+pub struct ObjectIdPlugin;
+
+impl Plugin for ObjectIdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IdAllocator>()
+            .add_message::<RestoreObjectsEvent>();
+
+        #[cfg(feature = "selection")]
+        app.init_resource::<ObjectsRestored>()
+            .add_systems(Update, (restore_objects, report_restored_objects).chain())
+            .add_systems(PostUpdate, check_id_collisions);
+    }
+}
+
+/// Replace the ID of a new object when another object already has it
+#[cfg(feature = "selection")]
+fn check_id_collisions(
+    mut commands: Commands,
+    selectables: Query<(Entity, Ref<Selectable>)>,
+    mut allocator: ResMut<IdAllocator>,
+) {
+    let mut live: HashSet<&str> = selectables
+        .iter()
+        .filter(|(_, selectable)| !selectable.is_added())
+        .map(|(_, selectable)| selectable.into_inner().id.as_str())
+        .collect();
+
+    for (entity, selectable) in selectables.iter().filter(|(_, s)| s.is_added()) {
+        let selectable = selectable.into_inner();
+        if live.insert(&selectable.id) {
+            allocator.reserve(&selectable.id);
+            continue;
+        }
+        let id = allocator.allocate();
+        warn!(
+            "Object ID {} of {:?} is already taken, replacing it with {}",
+            selectable.id, entity, id
+        );
+        commands.entity(entity).insert(Selectable { id });
+    }
+}
+
+/// Apply saved transforms, visibility, locks, and IDs to the objects they
+/// match
+#[cfg(feature = "selection")]
+#[allow(clippy::too_many_arguments)]
+fn restore_objects(
+    mut commands: Commands,
+    mut events: MessageReader<RestoreObjectsEvent>,
+    mut objects: Query<RestoredObject, SceneObjectFilter>,
+    mut allocator: ResMut<IdAllocator>,
+    mut selection: ResMut<SelectionState>,
+    mut graphs: ResMut<NodeGraphs>,
+    mut restored: ResMut<ObjectsRestored>,
+) {
+    for RestoreObjectsEvent(saved) in events.read() {
+        let live: Vec<(Entity, String, String)> = objects
+            .iter()
+            .map(|(entity, selectable, name, ..)| (entity, selectable.id.clone(), name.to_string()))
+            .collect();
+        let pairs = match_saved_objects(
+            live.iter()
+                .map(|(entity, id, name)| (*entity, id.as_str(), name.as_str())),
+            saved,
+        );
+        let live_ids: HashSet<&str> = live.iter().map(|(_, id, _)| id.as_str()).collect();
+
+        for &(entity, index) in &pairs {
+            let saved = &saved[index];
+            let Ok((_, mut selectable, _, mut transform, mut visibility, locked)) =
+                objects.get_mut(entity)
+            else {
+                continue;
+            };
+            *transform = Transform {
+                translation: Vec3::from_array(saved.transform.position),
+                rotation: Quat::from_array(saved.transform.rotation),
+                scale: Vec3::from_array(saved.transform.scale),
+            };
+            *visibility = if saved.visible {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            if saved.locked && !locked {
+                commands.entity(entity).insert(Locked);
+            } else if !saved.locked && locked {
+                commands.entity(entity).remove::<Locked>();
+            }
+
+            // Another live object keeping the saved ID wins over the restore
+            if selectable.id != saved.id && !live_ids.contains(saved.id.as_str()) {
+                allocator.reserve(&saved.id);
+                for id in &mut selection.selected_ids {
+                    if *id == selectable.id {
+                        id.clone_from(&saved.id);
+                    }
+                }
+                graphs.rename_material(&selectable.id, &saved.id);
+                debug!("Restored ID {} of {}", saved.id, selectable.id);
+                selectable.id.clone_from(&saved.id);
+            }
+        }
+
+        info!("Restored {} of {} saved objects", pairs.len(), saved.len());
+        restored.0 = true;
+    }
+}
+
+/// Report restored objects so the outliner picks up their IDs
+#[cfg(feature = "selection")]
+fn report_restored_objects(
+    mut restored: ResMut<ObjectsRestored>,
+    scene_objects: SceneObjects,
+    scene_lights: SceneLights,
+    scene_measurements: SceneMeasurements,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    if !std::mem::take(&mut restored.0) {
+        return;
+    }
+
+    outbound.send(BevyToUi::SceneUpdated(SceneInfo {
+        objects: scene_objects.infos(),
+        lights: scene_lights.infos(),
+        wireframe: scene_objects.wireframe(),
+        isolated: scene_objects.isolated(),
+        annotations: scene_measurements.infos(),
+        ..default()
+    }));
+}
+
+/// Pair live objects (entity, ID, name) with the index of the saved object
+/// they restore: by ID first, then by name in order. Each saved object
+/// restores at most one live object.
+fn match_saved_objects<'a>(
+    live: impl IntoIterator<Item = (Entity, &'a str, &'a str)>,
+    saved: &[SceneObject],
+) -> Vec<(Entity, usize)> {
+    let by_id: HashMap<&str, usize> = saved
+        .iter()
+        .enumerate()
+        .map(|(index, object)| (object.id.as_str(), index))
+        .collect();
+    let mut used = vec![false; saved.len()];
+    let mut pairs = Vec::new();
+    let mut unmatched = Vec::new();
+
+    for (entity, id, name) in live {
+        match by_id.get(id) {
+            Some(&index) => {
+                used[index] = true;
+                pairs.push((entity, index));
+            }
+            None => unmatched.push((entity, name)),
+        }
+    }
+    for (entity, name) in unmatched {
+        let found = (0..saved.len()).find(|&index| !used[index] && saved[index].name == name);
+        if let Some(index) = found {
+            used[index] = true;
+            pairs.push((entity, index));
+        }
+    }
+    pairs
+}
+
+#[cfg(all(test, feature = "selection"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use bevy::ecs::system::RunSystemOnce;
+    use pentimento_ipc::{AddObjectRequest, ObjectCommand, PrimitiveType};
+
+    use super::*;
+    use crate::ObjectCommandEvent;
+    use crate::add_object::{AddObjectEvent, AddObjectPlugin};
+    use crate::duplicate::DuplicatePlugin;
+    use crate::hierarchy::{HierarchyPlugin, ObjectGroup};
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        // Registered by the visibility plugin in the app
+        app.register_required_components::<Mesh3d, Visibility>();
+        app.init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .init_resource::<OutboundUiMessages>()
+            .init_resource::<SelectionState>()
+            .init_resource::<NodeGraphs>()
+            .add_message::<ObjectCommandEvent>()
+            .add_plugins((
+                ObjectIdPlugin,
+                AddObjectPlugin,
+                HierarchyPlugin,
+                DuplicatePlugin,
+            ));
+        app
+    }
+
+    fn command(app: &mut App, command: ObjectCommand) {
+        app.world_mut().write_message(ObjectCommandEvent(command));
+        app.update();
+    }
+
+    /// `ObjectAdded` messages sent since the last call: (ID, duplicated from)
+    fn added(app: &mut App) -> Vec<(String, Option<String>)> {
+        let mut outbound = app.world_mut().resource_mut::<OutboundUiMessages>();
+        std::mem::take(&mut outbound.messages)
+            .into_iter()
+            .filter_map(|message| match message {
+                BevyToUi::ObjectAdded {
+                    object,
+                    duplicated_from,
+                } => Some((object.id, duplicated_from)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn infos(app: &mut App) -> Vec<SceneObject> {
+        app.world_mut()
+            .run_system_once(|objects: SceneObjects| objects.infos())
+            .unwrap()
+    }
+
+    /// Check that IDs are unique and parent IDs name live objects
+    fn assert_consistent(objects: &[SceneObject]) {
+        let ids: HashSet<&str> = objects.iter().map(|object| object.id.as_str()).collect();
+        assert_eq!(ids.len(), objects.len(), "duplicate IDs");
+        for object in objects {
+            if let Some(parent_id) = &object.parent_id {
+                assert!(ids.contains(parent_id.as_str()), "dangling parent ID");
+            }
+        }
+    }
+
+    #[test]
+    fn test_ids_survive_duplicate_delete_save_and_load() {
+        let mut app = test_app();
+        for index in 0..50 {
+            app.world_mut()
+                .write_message(AddObjectEvent(AddObjectRequest {
+                    primitive_type: PrimitiveType::Cube,
+                    position: None,
+                    name: Some(format!("Object {}", index)),
+                    subdivisions: None,
+                }));
+        }
+        app.update();
+        let originals: Vec<String> = added(&mut app).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(originals.len(), 50);
+
+        command(
+            &mut app,
+            ObjectCommand::Duplicate {
+                ids: originals[..10].to_vec(),
+            },
+        );
+        let copies = added(&mut app);
+        assert_eq!(copies.len(), 10);
+        for (id, source) in &copies {
+            assert!(!originals.contains(id));
+            assert!(originals[..10].contains(source.as_ref().unwrap()));
+        }
+        let copies: Vec<String> = copies.into_iter().map(|(id, _)| id).collect();
+
+        command(
+            &mut app,
+            ObjectCommand::Group {
+                ids: copies[..3].to_vec(),
+                name: "Group".to_string(),
+            },
+        );
+        assert_eq!(added(&mut app).len(), 1);
+
+        let deleted: Vec<String> = originals[40..]
+            .iter()
+            .chain(&copies[5..])
+            .cloned()
+            .collect();
+        command(
+            &mut app,
+            ObjectCommand::Delete {
+                ids: deleted.clone(),
+                recursive: false,
+            },
+        );
+        app.update();
+
+        // Save
+        let saved = infos(&mut app);
+        assert_eq!(saved.len(), 40 + 5 + 1);
+        assert_consistent(&saved);
+        assert!(saved.iter().all(|object| !deleted.contains(&object.id)));
+        let json = serde_json::to_string(&saved).unwrap();
+        let saved: Vec<SceneObject> = serde_json::from_str(&json).unwrap();
+
+        // Load into a new session, whose objects start out with fresh IDs
+        let mut app = test_app();
+        let mut entities = HashMap::new();
+        for object in saved.iter().rev() {
+            let id = app.world_mut().resource_mut::<IdAllocator>().allocate();
+            let mut entity = app.world_mut().spawn((
+                Name::new(object.name.clone()),
+                Transform::default(),
+                Visibility::default(),
+                Selectable { id },
+            ));
+            if object.name == "Group" {
+                entity.insert(ObjectGroup);
+            } else {
+                entity.insert(Mesh3d::default());
+            }
+            entities.insert(object.id.clone(), entity.id());
+        }
+        for object in &saved {
+            if let Some(parent_id) = &object.parent_id {
+                let child = entities[&object.id];
+                let parent = entities[parent_id];
+                app.world_mut().entity_mut(child).insert(ChildOf(parent));
+            }
+        }
+        app.update();
+        let first = app
+            .world()
+            .get::<Selectable>(entities[&saved[0].id])
+            .unwrap()
+            .id
+            .clone();
+        app.world_mut()
+            .resource_mut::<SelectionState>()
+            .selected_ids = vec![first];
+
+        app.world_mut()
+            .write_message(RestoreObjectsEvent(saved.clone()));
+        app.update();
+
+        let mut loaded = infos(&mut app);
+        let mut expected = saved.clone();
+        loaded.sort_by(|a, b| a.id.cmp(&b.id));
+        expected.sort_by(|a, b| a.id.cmp(&b.id));
+        let summary = |objects: &[SceneObject]| -> Vec<(String, String, Option<String>)> {
+            objects
+                .iter()
+                .map(|o| (o.id.clone(), o.name.clone(), o.parent_id.clone()))
+                .collect()
+        };
+        assert_eq!(summary(&loaded), summary(&expected));
+        assert_eq!(
+            app.world().resource::<SelectionState>().selected_ids,
+            vec![saved[0].id.clone()]
+        );
+
+        // Duplicates made after loading never reuse a restored ID
+        command(
+            &mut app,
+            ObjectCommand::Duplicate {
+                ids: saved.iter().map(|object| object.id.clone()).collect(),
+            },
+        );
+        app.update();
+        let objects = infos(&mut app);
+        assert!(objects.len() > saved.len());
+        assert_consistent(&objects);
+    }
+
+    #[test]
+    fn test_sequential_ids_repeat_across_runs() {
+        let ids = || {
+            let mut allocator = IdAllocator::sequential();
+            allocator.reserve("00000000-0000-0000-0000-000000000002");
+            [allocator.allocate(), allocator.allocate()]
+        };
+        assert_eq!(
+            ids(),
+            [
+                "00000000-0000-0000-0000-000000000001",
+                "00000000-0000-0000-0000-000000000003"
+            ]
+        );
+        assert_eq!(ids(), ids());
+    }
+
+    #[test]
+    fn test_taken_ids_are_replaced() {
+        let mut app = test_app();
+        let first = app
+            .world_mut()
+            .spawn((
+                Mesh3d::default(),
+                Name::new("A"),
+                Selectable { id: "a".into() },
+            ))
+            .id();
+        app.update();
+        let second = app
+            .world_mut()
+            .spawn((
+                Mesh3d::default(),
+                Name::new("B"),
+                Selectable { id: "a".into() },
+            ))
+            .id();
+        app.update();
+
+        let id = |entity| app.world().get::<Selectable>(entity).unwrap().id.clone();
+        assert_eq!(id(first), "a");
+        assert_ne!(id(second), "a");
+    }
+}
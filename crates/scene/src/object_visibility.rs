@@ -0,0 +1,390 @@
+//! Object visibility, isolation, and locking
+//!
+//! Applies `ObjectCommand::SetVisibility`, `Isolate`, and `SetLocked` to
+//! scene objects. Isolation hides every object but the isolated ones (and
+//! their ancestors and descendants) and remembers what it hid, so
+//! `Isolate { ids: [] }` restores the visibility from before.
+//!
+//! Hidden and [`Locked`] objects drop out of the selection; picking, the
+//! gizmo, projection painting, and sculpt mode skip them. Hiding or locking
+//! the object being sculpted leaves sculpt mode, and the one under a mesh
+//! paint stroke ends the stroke.
+//!
+//! Hotkeys: H hides the selection, Alt+H unhides everything, and / toggles
+//! isolation of the selection. They go through `ObjectCommandEvent` like
+//! the UI's commands.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use pentimento_config::{Keymap, actions};
+use pentimento_ipc::{BevyToUi, ObjectCommand, SceneInfo};
+
+#[cfg(feature = "sculpting")]
+use crate::edit_mode::{EditModeRequests, EditorMode};
+use crate::hierarchy::{SceneObjectFilter, SceneObjects, ancestors};
+use crate::measure::SceneMeasurements;
+#[cfg(feature = "mesh_painting")]
+use crate::mesh_paint_mode::{MeshPaintEvent, MeshPaintState};
+use crate::reference_image::ReferenceImage;
+use crate::scene_light::SceneLights;
+#[cfg(feature = "sculpting")]
+use crate::sculpt_mode::{SculptState, SculptingData};
+use crate::selection::{Locked, Selectable, Selected, SelectionState};
+use crate::{ObjectCommandEvent, OutboundUiMessages};
+
+/// Isolation state: what isolation hid, to restore when it ends
+#[derive(Resource, Default)]
+pub struct IsolationState {
+    /// Visibility of each object isolation hid, from before it hid them
+    hidden: Option<HashMap<Entity, Visibility>>,
+}
+
+/// Whether visibility or locks changed since the last `SceneUpdated`
+#[derive(Resource, Default)]
+struct VisibilityChanged(bool);
+
+impl IsolationState {
+    /// Whether isolation is hiding the other objects
+    pub fn is_isolated(&self) -> bool {
+        self.hidden.is_some()
+    }
+}
+
+/// Plugin for object visibility, isolation, and locking
+pub struct ObjectVisibilityPlugin;
+
+impl Plugin for ObjectVisibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IsolationState>()
+            .init_resource::<VisibilityChanged>()
+            .add_systems(
+                Update,
+                (
+                    handle_visibility_hotkeys,
+                    handle_visibility_commands,
+                    report_visibility_changes,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Modes working on one object, left when that object is hidden or locked
+#[derive(SystemParam)]
+struct ObjectModes<'w, 's> {
+    parents: Query<'w, 's, &'static ChildOf>,
+    #[cfg(feature = "sculpting")]
+    sculpt_state: Res<'w, SculptState>,
+    #[cfg(feature = "sculpting")]
+    sculpting_data: ResMut<'w, SculptingData>,
+    #[cfg(feature = "sculpting")]
+    mode_requests: EditModeRequests<'w>,
+    #[cfg(feature = "mesh_painting")]
+    mesh_paint_state: ResMut<'w, MeshPaintState>,
+    #[cfg(feature = "mesh_painting")]
+    mesh_paint_events: MessageWriter<'w, MeshPaintEvent>,
+}
+
+impl ObjectModes<'_, '_> {
+    /// Whether `target` is `entity` or one of its descendants
+    fn covers(&self, entity: Entity, target: Entity) -> bool {
+        target == entity
+            || self
+                .parents
+                .iter_ancestors(target)
+                .any(|ancestor| ancestor == entity)
+    }
+
+    /// Leave sculpt mode and end the mesh paint stroke if they work on
+    /// `entity` or one of its descendants
+    fn release(&mut self, _entity: Entity) {
+        #[cfg(feature = "sculpting")]
+        if let Some(target) = self.sculpt_state.target_entity
+            && self.covers(_entity, target)
+        {
+            info!("Leaving sculpt mode: its object was hidden or locked");
+            self.mode_requests.request_mode_change(EditorMode::Object);
+        }
+        #[cfg(feature = "mesh_painting")]
+        if let Some(mesh) = self.mesh_paint_state.active_mesh
+            && self.covers(_entity, mesh)
+        {
+            info!("Ending mesh stroke: its object was hidden or locked");
+            self.mesh_paint_events.write(MeshPaintEvent::StrokeEnd);
+            self.mesh_paint_state.current_stroke = None;
+            self.mesh_paint_state.active_mesh = None;
+        }
+    }
+
+    /// The visibility the user set on an object. Sculpt mode hides its
+    /// object while chunks draw it, keeping this aside for its exit.
+    fn visibility(&self, _entity: Entity, visibility: Visibility) -> Visibility {
+        #[cfg(feature = "sculpting")]
+        if self.sculpt_state.target_entity == Some(_entity)
+            && let Some(saved) = self.sculpting_data.target_visibility
+        {
+            return saved;
+        }
+        visibility
+    }
+
+    /// Set an object's visibility; for the sculpted object, the visibility
+    /// sculpt mode restores on exit
+    fn set_visibility(&mut self, _entity: Entity, visibility: &mut Visibility, value: Visibility) {
+        #[cfg(feature = "sculpting")]
+        if self.sculpt_state.target_entity == Some(_entity)
+            && self.sculpting_data.target_visibility.is_some()
+        {
+            self.sculpting_data.target_visibility = Some(value);
+            return;
+        }
+        *visibility = value;
+    }
+}
+
+/// H hides the selection, Alt+H unhides everything, / toggles isolation
+fn handle_visibility_hotkeys(
+    key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    selection: Res<SelectionState>,
+    isolation: Res<IsolationState>,
+    objects: Query<(&Selectable, &Visibility), SceneObjectFilter>,
+    mut events: MessageWriter<ObjectCommandEvent>,
+) {
+    if keymap.just_pressed(actions::OBJECT_HIDE, &key_input) {
+        for id in &selection.selected_ids {
+            events.write(ObjectCommandEvent(ObjectCommand::SetVisibility {
+                id: id.clone(),
+                visible: false,
+            }));
+        }
+    }
+
+    if keymap.just_pressed(actions::OBJECT_UNHIDE_ALL, &key_input) {
+        if isolation.is_isolated() {
+            events.write(ObjectCommandEvent(ObjectCommand::Isolate {
+                ids: Vec::new(),
+            }));
+        }
+        for (selectable, visibility) in objects.iter() {
+            if *visibility == Visibility::Hidden {
+                events.write(ObjectCommandEvent(ObjectCommand::SetVisibility {
+                    id: selectable.id.clone(),
+                    visible: true,
+                }));
+            }
+        }
+    }
+
+    if keymap.just_pressed(actions::OBJECT_ISOLATE_TOGGLE, &key_input) {
+        let ids = if isolation.is_isolated() {
+            Vec::new()
+        } else if selection.selected_ids.is_empty() {
+            return;
+        } else {
+            selection.selected_ids.clone()
+        };
+        events.write(ObjectCommandEvent(ObjectCommand::Isolate { ids }));
+    }
+}
+
+/// Apply `SetVisibility`, `Isolate`, and `SetLocked` object commands
+#[allow(clippy::too_many_arguments)]
+fn handle_visibility_commands(
+    mut commands: Commands,
+    mut events: MessageReader<ObjectCommandEvent>,
+    mut isolation: ResMut<IsolationState>,
+    mut changed: ResMut<VisibilityChanged>,
+    mut selection: ResMut<SelectionState>,
+    mut objects: Query<
+        (Entity, &Selectable, &mut Visibility, Has<ReferenceImage>),
+        SceneObjectFilter,
+    >,
+    locked: Query<(), With<Locked>>,
+    selected: Query<(), With<Selected>>,
+    mut modes: ObjectModes,
+) {
+    let mut deselect = |commands: &mut Commands, entity: Entity, id: &str| {
+        if selected.contains(entity) {
+            commands.entity(entity).remove::<Selected>();
+        }
+        selection.selected_ids.retain(|selected| selected != id);
+    };
+
+    for ObjectCommandEvent(command) in events.read() {
+        match command {
+            ObjectCommand::SetVisibility { id, visible } => {
+                let Some(entity) = find_object(&objects, id) else {
+                    continue;
+                };
+                if !visible {
+                    modes.release(entity);
+                    for (object, selectable, ..) in objects.iter() {
+                        if modes.covers(entity, object) {
+                            deselect(&mut commands, object, &selectable.id);
+                        }
+                    }
+                }
+                let Ok((_, _, mut visibility, is_reference)) = objects.get_mut(entity) else {
+                    continue;
+                };
+                // Reference images apply their own toggles
+                if !is_reference {
+                    let value = if *visible {
+                        Visibility::Inherited
+                    } else {
+                        Visibility::Hidden
+                    };
+                    modes.set_visibility(entity, &mut visibility, value);
+                }
+                changed.0 = true;
+            }
+            ObjectCommand::Isolate { ids } => {
+                restore_isolation(&mut isolation, &mut objects, &mut modes);
+                if ids.is_empty() {
+                    info!("Ended isolation");
+                    changed.0 = true;
+                    continue;
+                }
+
+                let roots: Vec<Entity> = ids
+                    .iter()
+                    .filter_map(|id| find_object(&objects, id))
+                    .collect();
+                if roots.is_empty() {
+                    warn!("Nothing to isolate among {:?}", ids);
+                    continue;
+                }
+                let parents = &modes.parents;
+                let others = isolation_hides(
+                    objects.iter().map(|(entity, ..)| entity),
+                    &roots,
+                    |entity| parents.get(entity).ok().map(ChildOf::parent),
+                );
+
+                let mut hidden = HashMap::new();
+                for entity in others {
+                    modes.release(entity);
+                    let Ok((_, selectable, mut visibility, _)) = objects.get_mut(entity) else {
+                        continue;
+                    };
+                    deselect(&mut commands, entity, &selectable.id);
+                    hidden.insert(entity, modes.visibility(entity, *visibility));
+                    modes.set_visibility(entity, &mut visibility, Visibility::Hidden);
+                }
+                info!("Isolated {} objects, hiding {}", roots.len(), hidden.len());
+                isolation.hidden = Some(hidden);
+                changed.0 = true;
+            }
+            ObjectCommand::SetLocked { id, locked: lock } => {
+                let Some(entity) = find_object(&objects, id) else {
+                    continue;
+                };
+                if *lock {
+                    modes.release(entity);
+                    deselect(&mut commands, entity, id);
+                    commands.entity(entity).insert(Locked);
+                } else if locked.contains(entity) {
+                    commands.entity(entity).remove::<Locked>();
+                }
+                info!("{} {}", if *lock { "Locked" } else { "Unlocked" }, id);
+                changed.0 = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Objects isolating `roots` hides: all but the roots, their ancestors
+/// (which would hide them otherwise), and their descendants
+fn isolation_hides(
+    objects: impl IntoIterator<Item = Entity>,
+    roots: &[Entity],
+    parent_of: impl Fn(Entity) -> Option<Entity> + Copy,
+) -> Vec<Entity> {
+    let kept: HashSet<Entity> = roots
+        .iter()
+        .flat_map(|&root| std::iter::once(root).chain(ancestors(root, parent_of)))
+        .collect();
+    objects
+        .into_iter()
+        .filter(|entity| {
+            !kept.contains(entity)
+                && !ancestors(*entity, parent_of).any(|ancestor| roots.contains(&ancestor))
+        })
+        .collect()
+}
+
+fn find_object(
+    objects: &Query<(Entity, &Selectable, &mut Visibility, Has<ReferenceImage>), SceneObjectFilter>,
+    id: &str,
+) -> Option<Entity> {
+    objects
+        .iter()
+        .find(|(_, selectable, ..)| selectable.id == id)
+        .map(|(entity, ..)| entity)
+}
+
+/// Give the objects isolation hid their visibility back
+fn restore_isolation(
+    isolation: &mut IsolationState,
+    objects: &mut Query<
+        (Entity, &Selectable, &mut Visibility, Has<ReferenceImage>),
+        SceneObjectFilter,
+    >,
+    modes: &mut ObjectModes,
+) {
+    let Some(hidden) = isolation.hidden.take() else {
+        return;
+    };
+    for (entity, previous) in hidden {
+        // Objects deleted while isolated are gone from the query
+        if let Ok((_, _, mut visibility, _)) = objects.get_mut(entity) {
+            modes.set_visibility(entity, &mut visibility, previous);
+        }
+    }
+}
+
+/// Report visibility and lock changes so the outliner can show them
+fn report_visibility_changes(
+    mut changed: ResMut<VisibilityChanged>,
+    scene_objects: SceneObjects,
+    scene_lights: SceneLights,
+    scene_measurements: SceneMeasurements,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    if !std::mem::take(&mut changed.0) {
+        return;
+    }
+
+    outbound.send(BevyToUi::SceneUpdated(SceneInfo {
+        objects: scene_objects.infos(),
+        lights: scene_lights.infos(),
+        wireframe: scene_objects.wireframe(),
+        isolated: scene_objects.isolated(),
+        annotations: scene_measurements.infos(),
+        ..default()
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isolation_keeps_ancestors_and_descendants() {
+        let [group, member, child, sibling, other] =
+            [1, 2, 3, 4, 5].map(|index| Entity::from_raw_u32(index).unwrap());
+        let parents = HashMap::from([(member, group), (child, member), (sibling, group)]);
+        let parent_of = |entity| parents.get(&entity).copied();
+
+        let hidden = isolation_hides([group, member, child, sibling, other], &[member], parent_of);
+        assert_eq!(hidden, vec![sibling, other]);
+
+        // Isolating the group keeps everything inside it
+        let hidden = isolation_hides([group, member, child, sibling, other], &[group], parent_of);
+        assert_eq!(hidden, vec![other]);
+    }
+}
@@ -1,8 +1,14 @@
 //! Edge detection post-process for Surface ID outline rendering
 //!
 //! This module implements a render graph node that reads the ID buffer
-//! and composites orange outlines onto the scene where entity IDs differ.
+//! and composites outlines onto the scene where selected objects end.
 //! Uses the standard Bevy post-processing pattern with ViewTarget::post_process_write().
+//!
+//! The node runs two passes over the scene, comparing the ID camera's depth
+//! (the selected surface) with the view's depth (the nearest surface):
+//! - Visible: depth test passes, the outline is drawn in full
+//! - Occluded: depth test fails, the outline is blended in at
+//!   `OutlineSettings::occluded_opacity` (skipped at 0)
 
 use bevy::asset::embedded_asset;
 use bevy::core_pipeline::FullscreenShader;
@@ -17,22 +23,23 @@ use bevy::render::{
         NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
     },
     render_resource::{
-        BindGroupEntries, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntries,
-        Buffer, BufferInitDescriptor, BufferUsages, CachedRenderPipelineId, ColorTargetState,
-        ColorWrites, FragmentState, MultisampleState, Operations, PipelineCache, PrimitiveState,
+        BindGroupEntries, BindGroupLayoutDescriptor, BindGroupLayoutEntries, Buffer,
+        BufferInitDescriptor, BufferUsages, CachedRenderPipelineId, ColorTargetState, ColorWrites,
+        FragmentState, MultisampleState, Operations, PipelineCache, PrimitiveState,
         RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, Sampler,
-        SamplerBindingType, SamplerDescriptor, ShaderStages, ShaderType, TextureFormat,
-        TextureSampleType,
-        binding_types::{sampler, texture_2d, uniform_buffer},
+        SamplerBindingType, SamplerDescriptor, ShaderStages, ShaderType, SpecializedRenderPipeline,
+        SpecializedRenderPipelines, TextureFormat, TextureSampleType, TextureView,
+        binding_types::{
+            sampler, texture_2d, texture_depth_2d, texture_depth_2d_multisampled, uniform_buffer,
+        },
     },
     renderer::{RenderContext, RenderDevice},
     texture::GpuImage,
-    view::ViewTarget,
+    view::{ViewDepthTexture, ViewTarget},
 };
 
-use super::OutlineCamera;
-use super::OutlineRenderTargets;
 use super::outline_settings::OutlineSettings;
+use super::{IdBufferCamera, OutlineCamera, OutlineRenderTargets};
 
 /// Plugin for edge detection post-processing
 pub struct EdgeDetectionPlugin;
@@ -42,8 +49,9 @@ impl Plugin for EdgeDetectionPlugin {
         // Embed the shader
         embedded_asset!(app, "shaders/edge_detection.wgsl");
 
-        // Extract OutlineCamera component to render world
+        // Extract the camera markers to the render world
         app.add_plugins(ExtractComponentPlugin::<OutlineCamera>::default());
+        app.add_plugins(ExtractComponentPlugin::<IdBufferCamera>::default());
         app.add_plugins(ExtractResourcePlugin::<OutlineSettings>::default());
         app.add_plugins(ExtractResourcePlugin::<OutlineRenderTargets>::default());
 
@@ -58,21 +66,24 @@ impl Plugin for EdgeDetectionPlugin {
         // This ensures proper per-view execution in Core3d subgraph (required for WASM/WebGL2)
         render_app
             .add_render_graph_node::<ViewNodeRunner<EdgeDetectionNode>>(Core3d, EdgeDetectionLabel);
-        // Run after DepthViewLabel (which itself runs after Tonemapping)
-        // so that outlines composite on top of the depth view when active.
+        // Run after ViewModeLabel (which itself runs after Tonemapping)
+        // so that outlines composite on top of debug view modes when active.
         render_app.add_render_graph_edges(
             Core3d,
             (
-                crate::DepthViewLabel,
+                crate::ViewModeLabel,
                 EdgeDetectionLabel,
                 Node3d::EndMainPassPostProcessing,
             ),
         );
 
-        render_app.add_systems(
-            Render,
-            prepare_edge_detection.in_set(RenderSystems::Prepare),
-        );
+        // Per-view depth textures are created in PrepareResources
+        render_app
+            .init_resource::<SpecializedRenderPipelines<EdgeDetectionPipeline>>()
+            .add_systems(
+                Render,
+                prepare_edge_detection.in_set(RenderSystems::PrepareBindGroups),
+            );
     }
 
     fn finish(&self, app: &mut App) {
@@ -93,10 +104,21 @@ pub struct EdgeDetectionLabel;
 /// Uniform data for edge detection shader
 #[derive(Clone, Copy, ShaderType)]
 pub struct EdgeDetectionUniform {
-    pub outline_color: Vec4,
+    pub active_color: Vec4,
+    pub selected_color: Vec4,
+    /// Outline width in ID buffer texels (physical window pixels)
     pub thickness: f32,
-    pub texture_size: Vec2,
-    pub _padding: f32,
+    pub occluded_opacity: f32,
+    pub _padding: Vec2,
+}
+
+/// Edge detection pass, selected in the shader with the `OCCLUDED` def
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutlinePass {
+    /// Outline portions in front of the rest of the scene
+    Visible,
+    /// Outline portions hidden behind other geometry
+    Occluded,
 }
 
 /// Render graph node for edge detection
@@ -105,14 +127,22 @@ pub struct EdgeDetectionUniform {
 pub struct EdgeDetectionNode;
 
 impl ViewNode for EdgeDetectionNode {
-    /// Query ViewTarget + optional OutlineCamera to filter to main camera only
-    type ViewQuery = (&'static ViewTarget, Option<&'static OutlineCamera>);
+    /// Query ViewTarget + depth + optional OutlineCamera to filter to main camera only
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewDepthTexture,
+        Option<&'static OutlineCamera>,
+    );
 
     fn run<'w>(
         &self,
         _graph: &mut RenderGraphContext,
         render_context: &mut RenderContext<'w>,
-        (view_target, outline_camera): bevy::ecs::query::QueryItem<'w, 'w, Self::ViewQuery>,
+        (view_target, view_depth, outline_camera): bevy::ecs::query::QueryItem<
+            'w,
+            'w,
+            Self::ViewQuery,
+        >,
         world: &'w World,
     ) -> Result<(), NodeRunError> {
         // Skip cameras without OutlineCamera marker (like ID buffer camera)
@@ -137,124 +167,159 @@ impl ViewNode for EdgeDetectionNode {
         };
         let pipeline_cache = world.resource::<PipelineCache>();
 
-        let Some(render_pipeline) = pipeline_cache.get_render_pipeline(pipeline.pipeline_id) else {
-            return Ok(());
+        let passes = [
+            (OutlinePass::Visible, prepared.visible_pipeline_id),
+            (OutlinePass::Occluded, prepared.occluded_pipeline_id),
+        ];
+        let pass_count = if settings.occluded_opacity > 0.0 {
+            2
+        } else {
+            1
         };
 
-        // Use ViewTarget's post_process_write() for proper ping-pong buffer handling
-        // This returns source (current scene) and destination (where we write)
-        let post_process = view_target.post_process_write();
-
-        // Create bind group with:
-        // - uniforms
-        // - id_buffer (for edge detection)
-        // - scene source texture (to composite onto)
-        let bind_group = render_context.render_device().create_bind_group(
-            "edge_detection_bind_group",
-            &pipeline.layout,
-            &BindGroupEntries::sequential((
-                prepared.uniform_buffer.as_entire_binding(),
-                &prepared.id_texture_view,
-                &pipeline.sampler,
-                post_process.source, // Scene texture to read from
-                &pipeline.sampler,   // Re-use sampler for scene
-            )),
-        );
-
-        // Render to ViewTarget's destination (composited scene + outlines)
-        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
-            label: Some("edge_detection_pass"),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: post_process.destination, // Write to ViewTarget's destination
-                resolve_target: None,
-                ops: Operations::default(),
-                depth_slice: None,
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
-
-        render_pass.set_render_pipeline(render_pipeline);
-        render_pass.set_bind_group(0, &bind_group, &[]);
-        render_pass.draw(0..3, 0..1);
+        let layout = pipeline_cache.get_bind_group_layout(&prepared.layout);
+        for (pass, pipeline_id) in passes.into_iter().take(pass_count) {
+            let Some(render_pipeline) = pipeline_cache.get_render_pipeline(pipeline_id) else {
+                return Ok(());
+            };
+
+            // Use ViewTarget's post_process_write() for proper ping-pong buffer handling
+            // This returns source (current scene) and destination (where we write);
+            // the occluded pass reads what the visible pass wrote
+            let post_process = view_target.post_process_write();
+
+            // Create bind group with:
+            // - uniforms
+            // - id_buffer and its depth (the selected surfaces)
+            // - view depth (the nearest surfaces)
+            // - scene source texture (to composite onto)
+            let bind_group = render_context.render_device().create_bind_group(
+                "edge_detection_bind_group",
+                &layout,
+                &BindGroupEntries::sequential((
+                    prepared.uniform_buffer.as_entire_binding(),
+                    &prepared.id_texture_view,
+                    &prepared.id_depth_view,
+                    view_depth.view(),
+                    post_process.source, // Scene texture to read from
+                    &pipeline.sampler,
+                )),
+            );
+
+            let label = match pass {
+                OutlinePass::Visible => "edge_detection_visible_pass",
+                OutlinePass::Occluded => "edge_detection_occluded_pass",
+            };
+
+            // Render to ViewTarget's destination (composited scene + outlines)
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some(label),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: post_process.destination, // Write to ViewTarget's destination
+                    resolve_target: None,
+                    ops: Operations::default(),
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_render_pipeline(render_pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
 
         Ok(())
     }
 }
 
-/// Pipeline for edge detection
+/// Pipeline for edge detection, specialized per pass and view
 #[derive(Resource)]
 pub struct EdgeDetectionPipeline {
-    pub layout: BindGroupLayout,
     pub sampler: Sampler,
-    pub pipeline_id: CachedRenderPipelineId,
+    shader: Handle<Shader>,
+    fullscreen_shader: FullscreenShader,
 }
 
 impl FromWorld for EdgeDetectionPipeline {
     fn from_world(world: &mut World) -> Self {
         let render_device = world.resource::<RenderDevice>();
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
 
-        // Create bind group layout entries
-        // Bindings: uniform, id_texture, id_sampler, scene_texture, scene_sampler
-        let layout_entries = BindGroupLayoutEntries::sequential(
+        Self {
+            sampler,
+            shader: world
+                .load_asset("embedded://pentimento_scene/outline/shaders/edge_detection.wgsl"),
+            fullscreen_shader: world.resource::<FullscreenShader>().clone(),
+        }
+    }
+}
+
+/// Shader variant of the edge detection pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeDetectionPipelineKey {
+    pass: OutlinePass,
+    /// Whether the view's depth texture is multisampled (MSAA on)
+    multisampled: bool,
+    texture_format: TextureFormat,
+}
+
+impl EdgeDetectionPipelineKey {
+    /// Bindings: uniform, id_texture, id_depth, view_depth, scene_texture, scene_sampler
+    fn layout(&self) -> BindGroupLayoutDescriptor {
+        let view_depth = if self.multisampled {
+            texture_depth_2d_multisampled()
+        } else {
+            texture_depth_2d()
+        };
+        let entries = BindGroupLayoutEntries::sequential(
             ShaderStages::FRAGMENT,
             (
                 uniform_buffer::<EdgeDetectionUniform>(false),
-                texture_2d(TextureSampleType::Float { filterable: true }), // ID buffer
-                sampler(SamplerBindingType::Filtering),                    // ID sampler
-                texture_2d(TextureSampleType::Float { filterable: true }), // Scene texture
-                sampler(SamplerBindingType::Filtering),                    // Scene sampler
+                texture_2d(TextureSampleType::Float { filterable: false }), // ID buffer
+                texture_depth_2d(),                                         // ID buffer depth
+                view_depth,                                                 // View depth
+                texture_2d(TextureSampleType::Float { filterable: true }),  // Scene texture
+                sampler(SamplerBindingType::Filtering),                     // Scene sampler
             ),
         );
+        BindGroupLayoutDescriptor::new("edge_detection_bind_group_layout", &entries.to_vec())
+    }
+}
 
-        // Create the descriptor for the pipeline
-        let layout_descriptor = BindGroupLayoutDescriptor::new(
-            "edge_detection_bind_group_layout",
-            &layout_entries.to_vec(),
-        );
-
-        // Create the actual layout for bind group creation
-        let layout = render_device
-            .create_bind_group_layout("edge_detection_bind_group_layout", &layout_entries);
-
-        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+impl SpecializedRenderPipeline for EdgeDetectionPipeline {
+    type Key = EdgeDetectionPipelineKey;
 
-        let shader =
-            world.load_asset("embedded://pentimento_scene/outline/shaders/edge_detection.wgsl");
-
-        let fullscreen_shader = world.resource::<FullscreenShader>();
-        let vertex_state = fullscreen_shader.to_vertex_state();
-
-        let pipeline_id =
-            world
-                .resource_mut::<PipelineCache>()
-                .queue_render_pipeline(RenderPipelineDescriptor {
-                    label: Some("edge_detection_pipeline".into()),
-                    layout: vec![layout_descriptor],
-                    vertex: vertex_state,
-                    fragment: Some(FragmentState {
-                        shader,
-                        shader_defs: vec![],
-                        entry_point: Some("fragment".into()),
-                        targets: vec![Some(ColorTargetState {
-                            // Use HDR format to match ViewTarget (atmosphere enables HDR)
-                            format: TextureFormat::Rgba16Float,
-                            blend: None,
-                            write_mask: ColorWrites::ALL,
-                        })],
-                    }),
-                    primitive: PrimitiveState::default(),
-                    depth_stencil: None,
-                    multisample: MultisampleState::default(),
-                    push_constant_ranges: vec![],
-                    zero_initialize_workgroup_memory: false,
-                });
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if key.pass == OutlinePass::Occluded {
+            shader_defs.push("OCCLUDED".into());
+        }
+        if key.multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
 
-        Self {
-            layout,
-            sampler,
-            pipeline_id,
+        RenderPipelineDescriptor {
+            label: Some("edge_detection_pipeline".into()),
+            layout: vec![key.layout()],
+            vertex: self.fullscreen_shader.to_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    // Match the ViewTarget (HDR when atmosphere is on)
+                    format: key.texture_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
         }
     }
 }
@@ -262,22 +327,30 @@ impl FromWorld for EdgeDetectionPipeline {
 /// Prepared data for edge detection (created during Prepare phase)
 #[derive(Resource)]
 pub struct EdgeDetectionPrepared {
+    pub visible_pipeline_id: CachedRenderPipelineId,
+    pub occluded_pipeline_id: CachedRenderPipelineId,
+    pub layout: BindGroupLayoutDescriptor,
     pub uniform_buffer: Buffer,
-    pub id_texture_view: bevy::render::render_resource::TextureView,
+    pub id_texture_view: TextureView,
+    pub id_depth_view: TextureView,
 }
 
 /// Prepare the edge detection data each frame
+#[allow(clippy::too_many_arguments)]
 fn prepare_edge_detection(
     mut commands: Commands,
     render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<EdgeDetectionPipeline>>,
+    edge_pipeline: Option<Res<EdgeDetectionPipeline>>,
     settings: Option<Res<OutlineSettings>>,
     targets: Option<Res<OutlineRenderTargets>>,
     gpu_images: Res<RenderAssets<GpuImage>>,
+    views: Query<(&ViewTarget, &Msaa), With<OutlineCamera>>,
+    id_views: Query<&ViewDepthTexture, With<IdBufferCamera>>,
 ) {
-    let Some(settings) = settings else {
-        return;
-    };
-    let Some(targets) = targets else {
+    let (Some(settings), Some(targets), Some(edge_pipeline)) = (settings, targets, edge_pipeline)
+    else {
         return;
     };
 
@@ -286,16 +359,30 @@ fn prepare_edge_detection(
         return;
     };
 
+    // Use the first matching camera; the ID camera's depth only exists once
+    // it has rendered
+    let (Some((view_target, msaa)), Some(id_depth)) = (views.iter().next(), id_views.iter().next())
+    else {
+        commands.remove_resource::<EdgeDetectionPrepared>();
+        return;
+    };
+
+    let key = |pass| EdgeDetectionPipelineKey {
+        pass,
+        multisampled: msaa.samples() > 1,
+        texture_format: view_target.main_texture_format(),
+    };
+    let visible_key = key(OutlinePass::Visible);
+    let visible_pipeline_id = pipelines.specialize(&pipeline_cache, &edge_pipeline, visible_key);
+    let occluded_pipeline_id =
+        pipelines.specialize(&pipeline_cache, &edge_pipeline, key(OutlinePass::Occluded));
+
     let uniform = EdgeDetectionUniform {
-        outline_color: Vec4::new(
-            settings.color.red,
-            settings.color.green,
-            settings.color.blue,
-            1.0,
-        ),
+        active_color: opaque(settings.active_color),
+        selected_color: opaque(settings.selected_color),
         thickness: settings.thickness,
-        texture_size: Vec2::new(id_texture.size.width as f32, id_texture.size.height as f32),
-        _padding: 0.0,
+        occluded_opacity: settings.occluded_opacity,
+        _padding: Vec2::ZERO,
     };
 
     // Create uniform buffer using encase for proper alignment
@@ -308,7 +395,15 @@ fn prepare_edge_detection(
     });
 
     commands.insert_resource(EdgeDetectionPrepared {
+        visible_pipeline_id,
+        occluded_pipeline_id,
+        layout: visible_key.layout(),
         uniform_buffer,
         id_texture_view: id_texture.texture_view.clone(),
+        id_depth_view: id_depth.view().clone(),
     });
 }
+
+fn opaque(color: LinearRgba) -> Vec4 {
+    Vec4::new(color.red, color.green, color.blue, 1.0)
+}
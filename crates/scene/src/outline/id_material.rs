@@ -11,7 +11,8 @@ use bevy::shader::ShaderRef;
 #[derive(Clone, Copy, ShaderType, Default)]
 pub struct EntityIdUniform {
     /// Entity ID encoded as normalized RGBA
-    /// R = low 8 bits, G = mid 8 bits, B = high 8 bits, A = 1.0
+    /// R = low 8 bits, G = mid 8 bits, B = high 8 bits,
+    /// A = `ACTIVE_ALPHA` or `SELECTED_ALPHA` (0 where nothing is selected)
     pub entity_color: Vec4,
 }
 
@@ -32,15 +33,23 @@ impl Material for EntityIdMaterial {
     }
 }
 
-/// Encode an entity's index as a color
-pub fn entity_to_color(entity: Entity) -> Vec4 {
+/// ID buffer alpha of the active object's pixels
+pub const ACTIVE_ALPHA: f32 = 1.0;
+
+/// ID buffer alpha of the other selected objects' pixels
+pub const SELECTED_ALPHA: f32 = 0.5;
+
+/// Encode an entity's index as a color, with alpha telling the edge
+/// detection pass whether it is the active object
+pub fn entity_to_color(entity: Entity, active: bool) -> Vec4 {
     let id: u32 = entity.index_u32();
     // Encode 24-bit entity index into RGB channels
     // Each channel gets 8 bits (0-255 range normalized to 0.0-1.0)
     let r = ((id & 0xFF) as f32) / 255.0;
     let g = (((id >> 8) & 0xFF) as f32) / 255.0;
     let b = (((id >> 16) & 0xFF) as f32) / 255.0;
-    Vec4::new(r, g, b, 1.0)
+    let a = if active { ACTIVE_ALPHA } else { SELECTED_ALPHA };
+    Vec4::new(r, g, b, a)
 }
 
 /// Decode a color back to entity index (for debugging)
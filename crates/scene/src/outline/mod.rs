@@ -1,13 +1,22 @@
 //! Surface ID (Cryptomatte) selection outline rendering
 //!
-//! Renders pixel-accurate orange outlines around selected 3D objects using
+//! Renders pixel-accurate outlines around selected 3D objects using
 //! a Surface ID / Cryptomatte-style approach:
 //! 1. ID Pass: Render selected objects to a texture with entity IDs as colors
 //! 2. Edge Detection: Post-process shader finds ID boundaries and composites onto scene
 //!
+//! The active (last selected) object gets its own color. Edge detection runs
+//! as a depth-tested pass, drawing outlines where the selected surface is the
+//! nearest one, and a depth-failed pass drawing the hidden portions dimmed.
+//! Outlines are `OutlineSettings::thickness` physical pixels wide since the ID
+//! buffer always matches the window's physical size. While an object is
+//! sculpted, its chunk meshes are outlined in its place.
+//!
 //! This approach is WebGL2-compatible for WASM builds.
 //! Uses Bevy's standard post-processing pattern with ViewTarget::post_process_write().
 
+use std::collections::HashMap;
+
 use bevy::asset::RenderAssetUsages;
 use bevy::asset::embedded_asset;
 use bevy::camera::ClearColorConfig;
@@ -33,8 +42,11 @@ pub use outline_settings::OutlineSettings;
 #[derive(Component, Clone, ExtractComponent)]
 pub struct OutlineCamera;
 
-use crate::camera::MainCamera;
-use crate::selection::Selected;
+use crate::camera::{MainCamera, enable_depth_texture_binding};
+use crate::hierarchy::GroupSelected;
+#[cfg(feature = "sculpting")]
+use crate::sculpt_mode::{SculptState, SculptingData};
+use crate::selection::{Selectable, Selected, SelectionState};
 use edge_detection::EdgeDetectionPlugin;
 use id_material::entity_to_color;
 
@@ -46,7 +58,8 @@ pub struct OutlineRenderTargets {
 }
 
 /// Marker for the ID buffer camera
-#[derive(Component)]
+/// Extracted so the edge detection pass can read the camera's depth texture
+#[derive(Component, Clone, ExtractComponent)]
 pub struct IdBufferCamera;
 
 /// Plugin for Surface ID selection outlines
@@ -64,10 +77,10 @@ impl Plugin for OutlinePlugin {
             .add_systems(
                 Update,
                 (
+                    enable_depth_sampling,
                     sync_id_camera_transform,
+                    sync_id_buffer_mirrors,
                     sync_id_mirror_transforms,
-                    add_selected_to_id_buffer,
-                    remove_deselected_from_id_buffer,
                     handle_window_resize,
                 )
                     .chain(),
@@ -108,8 +121,16 @@ fn setup_outline_system(
 
     // Spawn ID buffer camera (renders selected objects to ID texture)
     // Use Reinhard tonemapping for WASM/WebGL2 compatibility (TonyMcMapface requires tonemapping_luts)
+    // Without MSAA, so edge pixels keep exact IDs and the depth texture can
+    // be compared with the main camera's
     commands.spawn((
-        Camera3d::default(),
+        Camera3d {
+            depth_texture_usages: (TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::TEXTURE_BINDING)
+                .into(),
+            ..default()
+        },
+        Msaa::Off,
         Camera {
             order: -1, // Render before main camera
             clear_color: ClearColorConfig::Custom(Color::srgba(0.0, 0.0, 0.0, 0.0)),
@@ -171,57 +192,144 @@ fn sync_id_camera_transform(
     *id_transform = *main_transform;
 }
 
-/// When an entity is selected, set up ID buffer rendering
-fn add_selected_to_id_buffer(
+/// Entities that get an outline: selected, or inside a selected group
+type Outlined = Or<(With<Selected>, With<GroupSelected>)>;
+
+/// Let the outline pass sample the depth of cameras it draws on, to tell
+/// visible outline portions from occluded ones
+fn enable_depth_sampling(mut cameras: Query<&mut Camera3d, Added<OutlineCamera>>) {
+    for mut camera in cameras.iter_mut() {
+        enable_depth_texture_binding(&mut camera);
+    }
+}
+
+/// Component linking an ID buffer mirror to the mesh it copies
+#[derive(Component)]
+pub struct IdBufferMirror {
+    /// Entity whose mesh and transform are copied: the outlined object, or
+    /// one of its chunks while it is sculpted
+    pub source: Entity,
+    /// Outlined object whose ID the mirror renders
+    pub object: Entity,
+    /// Whether the mirror renders the active object's color
+    pub active: bool,
+}
+
+/// Keep one ID buffer mirror per mesh drawing an outlined object
+///
+/// Mirrors are spawned and despawned as objects are (de)selected, recolored
+/// when the active object changes, and follow mesh swaps such as a sculpt
+/// chunk switching detail levels. Hidden objects are not outlined.
+fn sync_id_buffer_mirrors(
     mut commands: Commands,
     mut id_materials: ResMut<Assets<EntityIdMaterial>>,
-    added_selected: Query<(Entity, &Mesh3d), Added<Selected>>,
-    meshes: Res<Assets<Mesh>>,
+    outlined: Query<
+        (Entity, &InheritedVisibility, Option<&Selectable>),
+        (Outlined, With<Mesh3d>, Without<IdBufferMirror>),
+    >,
+    source_meshes: Query<&Mesh3d, Without<IdBufferMirror>>,
+    mut mirrors: Query<(
+        Entity,
+        &mut IdBufferMirror,
+        &mut Mesh3d,
+        &MeshMaterial3d<EntityIdMaterial>,
+    )>,
+    selection: Option<Res<SelectionState>>,
+    #[cfg(feature = "sculpting")] sculpt_state: Option<Res<SculptState>>,
+    #[cfg(feature = "sculpting")] sculpting_data: Option<Res<SculptingData>>,
 ) {
-    for (entity, mesh_handle) in added_selected.iter() {
-        let entity_color = entity_to_color(entity);
+    let active_id = selection
+        .as_ref()
+        .and_then(|selection| selection.selected_ids.last());
+
+    // Mesh entity -> (outlined object, active)
+    let mut wanted: HashMap<Entity, (Entity, bool)> = HashMap::new();
+    for (entity, visibility, selectable) in outlined.iter() {
+        let active = selectable.is_some_and(|selectable| Some(&selectable.id) == active_id);
+
+        // The sculpted object is hidden while its chunks draw it
+        #[cfg(feature = "sculpting")]
+        if let (Some(state), Some(data)) = (&sculpt_state, &sculpting_data)
+            && state.active
+            && state.target_entity == Some(entity)
+        {
+            for &chunk in data.chunk_entities.values() {
+                wanted.insert(chunk, (entity, active));
+            }
+            continue;
+        }
+
+        if visibility.get() {
+            wanted.insert(entity, (entity, active));
+        }
+    }
+
+    for (mirror_entity, mut mirror, mut mesh, material) in mirrors.iter_mut() {
+        let Some((object, active)) = wanted.remove(&mirror.source) else {
+            commands.entity(mirror_entity).despawn();
+            debug!(
+                "Removed ID buffer mirror of {:?} for {:?}",
+                mirror.source, mirror.object
+            );
+            continue;
+        };
+
+        if (mirror.object, mirror.active) != (object, active) {
+            if let Some(id_material) = id_materials.get_mut(&material.0) {
+                id_material.entity_id.entity_color = entity_to_color(object, active);
+            }
+            mirror.object = object;
+            mirror.active = active;
+        }
+
+        if let Ok(source_mesh) = source_meshes.get(mirror.source)
+            && mesh.0 != source_mesh.0
+        {
+            mesh.0 = source_mesh.0.clone();
+        }
+    }
 
-        // Create ID material for this entity
+    for (source, (object, active)) in wanted {
+        let Ok(source_mesh) = source_meshes.get(source) else {
+            continue;
+        };
+        let entity_color = entity_to_color(object, active);
+
+        // Create ID material for this mirror
         let id_material = id_materials.add(EntityIdMaterial {
             entity_id: id_material::EntityIdUniform { entity_color },
         });
 
-        // Clone the mesh for the ID pass rendering
         // We need a separate entity on layer 1 with the ID material
-        if let Some(_mesh) = meshes.get(&mesh_handle.0) {
-            commands.spawn((
-                Mesh3d(mesh_handle.0.clone()),
-                MeshMaterial3d(id_material),
-                // Will be synced with the original entity's transform
-                Transform::default(),
-                GlobalTransform::default(),
-                // Required for mesh to be visible to any camera
-                Visibility::default(),
-                // Only visible to ID camera
-                RenderLayers::layer(1),
-                RenderToIdBuffer,
-                // Track which entity this is for
-                IdBufferMirror { source: entity },
-                Pickable::IGNORE,
-            ));
-
-            info!(
-                "Added entity {:?} to ID buffer with color {:?}",
-                entity, entity_color
-            );
-        }
+        commands.spawn((
+            Mesh3d(source_mesh.0.clone()),
+            MeshMaterial3d(id_material),
+            // Will be synced with the source entity's transform
+            Transform::default(),
+            GlobalTransform::default(),
+            // Required for mesh to be visible to any camera
+            Visibility::default(),
+            // Only visible to ID camera
+            RenderLayers::layer(1),
+            RenderToIdBuffer,
+            IdBufferMirror {
+                source,
+                object,
+                active,
+            },
+            Pickable::IGNORE,
+        ));
+
+        debug!(
+            "Added {:?} to ID buffer for {:?} with color {:?}",
+            source, object, entity_color
+        );
     }
 }
 
-/// Component linking an ID buffer mirror to its source entity
-#[derive(Component)]
-pub struct IdBufferMirror {
-    pub source: Entity,
-}
-
 /// Update ID buffer mirror transforms to match their source entities
 fn sync_id_mirror_transforms(
-    source_query: Query<&GlobalTransform, With<Selected>>,
+    source_query: Query<&GlobalTransform, Without<IdBufferMirror>>,
     mut mirror_query: Query<(&IdBufferMirror, &mut Transform)>,
 ) {
     for (mirror, mut transform) in mirror_query.iter_mut() {
@@ -235,24 +343,6 @@ fn sync_id_mirror_transforms(
     }
 }
 
-/// Remove ID buffer entities when their source is deselected
-fn remove_deselected_from_id_buffer(
-    mut commands: Commands,
-    mirror_query: Query<(Entity, &IdBufferMirror)>,
-    selected_query: Query<&Selected>,
-) {
-    for (mirror_entity, mirror) in mirror_query.iter() {
-        // If source entity no longer has Selected component, remove the mirror
-        if selected_query.get(mirror.source).is_err() {
-            commands.entity(mirror_entity).despawn();
-            info!(
-                "Removed ID buffer mirror for deselected entity {:?}",
-                mirror.source
-            );
-        }
-    }
-}
-
 /// Handle window resize by recreating render targets
 fn handle_window_resize(
     mut commands: Commands,
@@ -300,3 +390,309 @@ fn handle_window_resize(
 
 // Re-export OrbitCamera for setup
 use crate::camera::OrbitCamera;
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+
+    use super::*;
+    use crate::ambient_occlusion::SceneAmbientOcclusion;
+    use crate::view_mode::ViewModePlugin;
+
+    fn mirror_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<EntityIdMaterial>>()
+            .init_resource::<SelectionState>()
+            .add_systems(Update, sync_id_buffer_mirrors);
+        app
+    }
+
+    fn spawn_object(app: &mut App, id: &str) -> Entity {
+        let mesh = app
+            .world_mut()
+            .resource_mut::<Assets<Mesh>>()
+            .add(Cuboid::default());
+        app.world_mut()
+            .spawn((
+                Mesh3d(mesh),
+                InheritedVisibility::VISIBLE,
+                Selectable { id: id.into() },
+            ))
+            .id()
+    }
+
+    /// Mirrored mesh entity -> (outlined object, active)
+    fn mirrors(app: &mut App) -> HashMap<Entity, (Entity, bool)> {
+        let world = app.world_mut();
+        world
+            .query::<&IdBufferMirror>()
+            .iter(world)
+            .map(|mirror| (mirror.source, (mirror.object, mirror.active)))
+            .collect()
+    }
+
+    #[test]
+    fn test_active_object_is_mirrored_with_its_own_color() {
+        let mut app = mirror_app();
+        let first = spawn_object(&mut app, "first");
+        let second = spawn_object(&mut app, "second");
+        app.world_mut().entity_mut(first).insert(Selected);
+        app.world_mut().entity_mut(second).insert(Selected);
+        app.world_mut()
+            .resource_mut::<SelectionState>()
+            .selected_ids = vec!["first".into(), "second".into()];
+        app.update();
+        assert_eq!(
+            mirrors(&mut app),
+            HashMap::from([(first, (first, false)), (second, (second, true))])
+        );
+
+        // Reselecting the first object makes it active
+        app.world_mut()
+            .resource_mut::<SelectionState>()
+            .selected_ids = vec!["second".into(), "first".into()];
+        app.update();
+        assert_eq!(
+            mirrors(&mut app),
+            HashMap::from([(first, (first, true)), (second, (second, false))])
+        );
+        let world = app.world_mut();
+        let colors: Vec<Vec4> = world
+            .query::<(&IdBufferMirror, &MeshMaterial3d<EntityIdMaterial>)>()
+            .iter(world)
+            .filter(|(mirror, _)| mirror.object == first)
+            .map(|(_, material)| {
+                world
+                    .resource::<Assets<EntityIdMaterial>>()
+                    .get(&material.0)
+                    .unwrap()
+                    .entity_id
+                    .entity_color
+            })
+            .collect();
+        assert_eq!(colors, vec![entity_to_color(first, true)]);
+
+        app.world_mut().entity_mut(second).remove::<Selected>();
+        app.update();
+        assert_eq!(mirrors(&mut app), HashMap::from([(first, (first, true))]));
+    }
+
+    #[test]
+    fn test_hidden_objects_are_not_outlined() {
+        let mut app = mirror_app();
+        let object = spawn_object(&mut app, "object");
+        app.world_mut()
+            .entity_mut(object)
+            .insert((Selected, InheritedVisibility::HIDDEN));
+        app.update();
+        assert!(mirrors(&mut app).is_empty());
+    }
+
+    #[cfg(feature = "sculpting")]
+    #[test]
+    fn test_sculpt_chunks_are_outlined_in_place_of_their_object() {
+        use sculpting::ChunkId;
+
+        let mut app = mirror_app();
+        app.init_resource::<SculptState>()
+            .init_resource::<SculptingData>();
+        let object = spawn_object(&mut app, "object");
+        app.world_mut().entity_mut(object).insert(Selected);
+        app.world_mut()
+            .resource_mut::<SelectionState>()
+            .selected_ids = vec!["object".into()];
+        app.update();
+        assert_eq!(mirrors(&mut app), HashMap::from([(object, (object, true))]));
+
+        // Entering sculpt mode hides the object behind its chunks
+        let chunk_mesh = app
+            .world_mut()
+            .resource_mut::<Assets<Mesh>>()
+            .add(Cuboid::default());
+        let chunks = [0, 1].map(|_| app.world_mut().spawn(Mesh3d(chunk_mesh.clone())).id());
+        app.world_mut()
+            .entity_mut(object)
+            .insert(InheritedVisibility::HIDDEN);
+        let mut state = app.world_mut().resource_mut::<SculptState>();
+        state.active = true;
+        state.target_entity = Some(object);
+        app.world_mut()
+            .resource_mut::<SculptingData>()
+            .chunk_entities = HashMap::from([(ChunkId(0), chunks[0]), (ChunkId(1), chunks[1])]);
+        app.update();
+        assert_eq!(
+            mirrors(&mut app),
+            HashMap::from([(chunks[0], (object, true)), (chunks[1], (object, true))])
+        );
+
+        // A chunk switching to its reduced mesh is followed
+        let reduced = app
+            .world_mut()
+            .resource_mut::<Assets<Mesh>>()
+            .add(Cuboid::default());
+        app.world_mut()
+            .entity_mut(chunks[0])
+            .insert(Mesh3d(reduced.clone()));
+        app.update();
+        let world = app.world_mut();
+        let mirrored: Vec<Handle<Mesh>> = world
+            .query::<(&IdBufferMirror, &Mesh3d)>()
+            .iter(world)
+            .filter(|(mirror, _)| mirror.source == chunks[0])
+            .map(|(_, mesh)| mesh.0.clone())
+            .collect();
+        assert_eq!(mirrored, vec![reduced]);
+
+        // Leaving sculpt mode outlines the object again
+        app.world_mut().resource_mut::<SculptState>().active = false;
+        app.world_mut()
+            .entity_mut(object)
+            .insert(InheritedVisibility::VISIBLE);
+        app.update();
+        assert_eq!(mirrors(&mut app), HashMap::from([(object, (object, true))]));
+    }
+
+    /// Last screenshot captured by `capture`
+    #[derive(Resource, Default)]
+    struct CapturedFrame(Option<Image>);
+
+    /// Render a selected cube at `width`x`height` physical pixels with the
+    /// outline drawn in pure green, and return the screenshot
+    fn render_outlined_cube(width: u32, height: u32, thickness: f32) -> Image {
+        use bevy::log::LogPlugin;
+        use bevy::render::pipelined_rendering::PipelinedRenderingPlugin;
+        use bevy::window::WindowResolution;
+        use bevy::winit::WinitPlugin;
+
+        let mut app = App::new();
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        resolution: WindowResolution::new(width, height)
+                            .with_scale_factor_override(1.0),
+                        ..default()
+                    }),
+                    ..default()
+                })
+                .disable::<WinitPlugin>()
+                .disable::<LogPlugin>()
+                .disable::<PipelinedRenderingPlugin>(),
+        )
+        .init_resource::<SceneAmbientOcclusion>()
+        .init_resource::<CapturedFrame>()
+        .insert_resource(ClearColor(Color::BLACK))
+        .insert_resource(OutlineSettings {
+            active_color: LinearRgba::GREEN,
+            selected_color: LinearRgba::GREEN,
+            thickness,
+            occluded_opacity: 0.0,
+            enabled: true,
+        })
+        .insert_resource(SelectionState {
+            selected_ids: vec!["cube".into()],
+        })
+        .add_plugins((ViewModePlugin, OutlinePlugin));
+
+        // The scene renders offscreen since there is no window surface
+        let mut target = Image::new_fill(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::all(),
+        );
+        target.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_SRC
+            | TextureUsages::RENDER_ATTACHMENT;
+        let target = app.world_mut().resource_mut::<Assets<Image>>().add(target);
+
+        let world = app.world_mut();
+        world.spawn((
+            Camera3d::default(),
+            RenderTarget::Image(target.clone().into()),
+            Msaa::Sample4,
+            Tonemapping::None,
+            Transform::from_xyz(0.0, 0.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
+            MainCamera,
+            crate::ViewModeCamera,
+            OutlineCamera,
+        ));
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Cuboid::default());
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Color::BLACK,
+                unlit: true,
+                ..default()
+            });
+        world.spawn((
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            Transform::default(),
+            Selectable { id: "cube".into() },
+            Selected,
+        ));
+
+        app.finish();
+        app.cleanup();
+
+        // Pipelines compile in the background; wait until the outline shows
+        for _ in 0..50 {
+            let image = capture(&mut app, &target);
+            if outline_width(&image) > 0 {
+                return image;
+            }
+        }
+        panic!("no outline rendered at {width}x{height}");
+    }
+
+    /// Screenshot `target`, updating the app until it is captured
+    fn capture(app: &mut App, target: &Handle<Image>) -> Image {
+        app.world_mut().resource_mut::<CapturedFrame>().0 = None;
+        app.world_mut()
+            .spawn(Screenshot::image(target.clone()))
+            .observe(
+                |captured: On<ScreenshotCaptured>, mut frame: ResMut<CapturedFrame>| {
+                    frame.0 = Some(captured.image.clone());
+                },
+            );
+        for _ in 0..100 {
+            app.update();
+            if let Some(image) = app.world_mut().resource_mut::<CapturedFrame>().0.take() {
+                return image;
+            }
+        }
+        panic!("screenshot was never captured");
+    }
+
+    /// Width of the outline where the middle row enters the cube
+    fn outline_width(image: &Image) -> u32 {
+        let y = image.height() / 2;
+        (0..image.width())
+            .map(|x| image.get_color_at(x, y).unwrap().to_srgba())
+            .skip_while(|color| color.green < 0.5)
+            .take_while(|color| color.green >= 0.5 && color.red < 0.2 && color.blue < 0.2)
+            .count() as u32
+    }
+
+    #[test]
+    #[ignore = "needs a GPU adapter; run with --ignored"]
+    fn test_outline_thickness_is_constant_across_resolutions() {
+        for thickness in [1.0, 3.0] {
+            for (width, height) in [(320, 240), (960, 720)] {
+                let image = render_outlined_cube(width, height, thickness);
+                assert_eq!(
+                    outline_width(&image),
+                    thickness as u32,
+                    "outline width at {width}x{height}"
+                );
+            }
+        }
+    }
+}
@@ -1,26 +1,63 @@
 //! Outline configuration settings
 
-use bevy::color::palettes::css::ORANGE;
 use bevy::prelude::*;
 use bevy::render::extract_resource::ExtractResource;
+use pentimento_ipc::SelectionOutlineSettings;
 
 /// Configuration for the selection outline effect
+///
+/// Updated from `AppSettings::outline` (`UiToBevy::UpdateSettings`).
 #[derive(Resource, Clone, ExtractResource)]
 pub struct OutlineSettings {
-    /// Outline color
-    pub color: LinearRgba,
-    /// Outline thickness in pixels (1-5 recommended)
+    /// Outline color of the active (last selected) object
+    pub active_color: LinearRgba,
+    /// Outline color of the other selected objects
+    pub selected_color: LinearRgba,
+    /// Outline thickness in physical window pixels, independent of the
+    /// scene's render resolution (1-8)
     pub thickness: f32,
+    /// Opacity of outline portions hidden behind other geometry; 0 skips
+    /// the occluded pass
+    pub occluded_opacity: f32,
     /// Whether outlines are enabled
     pub enabled: bool,
 }
 
 impl Default for OutlineSettings {
     fn default() -> Self {
+        Self::from(&SelectionOutlineSettings::default())
+    }
+}
+
+impl From<&SelectionOutlineSettings> for OutlineSettings {
+    fn from(settings: &SelectionOutlineSettings) -> Self {
+        let [r, g, b] = settings.active_color;
+        let active_color = Color::srgb(r, g, b).to_linear();
+        let [r, g, b] = settings.selected_color;
+        let selected_color = Color::srgb(r, g, b).to_linear();
         Self {
-            color: Color::from(ORANGE).to_linear(),
-            thickness: 2.0,
-            enabled: true,
+            active_color,
+            selected_color,
+            thickness: settings.clamped_thickness(),
+            occluded_opacity: settings.occluded_opacity.clamp(0.0, 1.0),
+            enabled: settings.enabled,
+        }
+    }
+}
+
+impl OutlineSettings {
+    /// The settings as reported to the UI
+    pub fn to_ipc(&self) -> SelectionOutlineSettings {
+        let srgb = |color: LinearRgba| {
+            let color = Srgba::from(color);
+            [color.red, color.green, color.blue]
+        };
+        SelectionOutlineSettings {
+            enabled: self.enabled,
+            active_color: srgb(self.active_color),
+            selected_color: srgb(self.selected_color),
+            thickness: self.thickness,
+            occluded_opacity: self.occluded_opacity,
         }
     }
 }
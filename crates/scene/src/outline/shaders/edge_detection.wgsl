@@ -3,16 +3,30 @@
 //
 // The ID buffer provides current-frame boundary information.
 // Outlines are drawn at boundary positions, scene passes through elsewhere.
+// Thickness is in ID buffer texels, which are physical window pixels, so
+// neighbors are read with textureLoad in that texture's own resolution.
+//
+// Without OCCLUDED, outline pixels where the selected surface is the nearest
+// one are drawn (depth test passes); with OCCLUDED, the ones hidden behind
+// other geometry are blended in (depth test fails).
+// MULTISAMPLED is set when the view's depth texture is multisampled (MSAA on)
 
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
 
 struct EdgeDetectionUniform {
-    outline_color: vec4<f32>,
+    active_color: vec4<f32>,
+    selected_color: vec4<f32>,
     thickness: f32,
-    texture_size: vec2<f32>,
-    _padding: f32,
+    occluded_opacity: f32,
+    _padding: vec2<f32>,
 }
 
+// ID buffer alpha above this marks the active object (1.0 vs 0.5)
+const ACTIVE_ALPHA_THRESHOLD: f32 = 0.75;
+
+// Relative depth difference still counted as the same surface
+const DEPTH_TOLERANCE: f32 = 0.001;
+
 @group(0) @binding(0)
 var<uniform> uniforms: EdgeDetectionUniform;
 
@@ -20,46 +34,48 @@ var<uniform> uniforms: EdgeDetectionUniform;
 var id_texture: texture_2d<f32>;
 
 @group(0) @binding(2)
-var id_sampler: sampler;
+var id_depth_texture: texture_depth_2d;
 
+#ifdef MULTISAMPLED
 @group(0) @binding(3)
-var scene_texture: texture_2d<f32>;
+var view_depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(3)
+var view_depth_texture: texture_depth_2d;
+#endif
 
 @group(0) @binding(4)
+var scene_texture: texture_2d<f32>;
+
+@group(0) @binding(5)
 var scene_sampler: sampler;
 
-// Sample the ID at a pixel offset
-fn sample_id(uv: vec2<f32>, offset: vec2<f32>) -> vec4<f32> {
-    let pixel_size = 1.0 / uniforms.texture_size;
-    return textureSample(id_texture, id_sampler, uv + offset * pixel_size);
+// Load the ID at a texel offset, clamped to the buffer so objects running
+// off screen are not outlined along the screen border
+fn load_id(coord: vec2<i32>, offset: vec2<i32>) -> vec4<f32> {
+    let max_coord = vec2<i32>(textureDimensions(id_texture)) - vec2<i32>(1);
+    return textureLoad(id_texture, clamp(coord + offset, vec2<i32>(0), max_coord), 0);
 }
 
 // Check if the current pixel is on the edge boundary in the ID buffer
 // Returns true if center pixel has ID and any neighbor does NOT have ID
-fn is_boundary_edge(uv: vec2<f32>) -> bool {
-    let center_id = sample_id(uv, vec2<f32>(0.0, 0.0));
-
-    // If center pixel has no ID, it's not part of a selected object
-    if center_id.a < 0.01 {
-        return false;
-    }
-
-    let thickness = uniforms.thickness;
+fn is_boundary_edge(coord: vec2<i32>) -> bool {
+    let thickness = i32(round(uniforms.thickness));
 
     // Check neighbors in 8 directions
-    let offsets = array<vec2<f32>, 8>(
-        vec2<f32>(-thickness, 0.0),         // left
-        vec2<f32>(thickness, 0.0),          // right
-        vec2<f32>(0.0, -thickness),         // up
-        vec2<f32>(0.0, thickness),          // down
-        vec2<f32>(-thickness, -thickness),  // top-left
-        vec2<f32>(thickness, -thickness),   // top-right
-        vec2<f32>(-thickness, thickness),   // bottom-left
-        vec2<f32>(thickness, thickness),    // bottom-right
+    let offsets = array<vec2<i32>, 8>(
+        vec2<i32>(-thickness, 0),          // left
+        vec2<i32>(thickness, 0),           // right
+        vec2<i32>(0, -thickness),          // up
+        vec2<i32>(0, thickness),           // down
+        vec2<i32>(-thickness, -thickness), // top-left
+        vec2<i32>(thickness, -thickness),  // top-right
+        vec2<i32>(-thickness, thickness),  // bottom-left
+        vec2<i32>(thickness, thickness),   // bottom-right
     );
 
     for (var i = 0; i < 8; i++) {
-        let neighbor_id = sample_id(uv, offsets[i]);
+        let neighbor_id = load_id(coord, offsets[i]);
 
         // If any neighbor has NO ID, this is a boundary pixel
         if neighbor_id.a < 0.01 {
@@ -70,15 +86,47 @@ fn is_boundary_edge(uv: vec2<f32>) -> bool {
     return false;
 }
 
+// Whether the selected surface at this pixel is the nearest one
+fn is_visible(uv: vec2<f32>, id_coord: vec2<i32>) -> bool {
+    let object_depth = textureLoad(id_depth_texture, id_coord, 0);
+
+    // The view may render at another resolution than the ID buffer
+    // Sample 0 when multisampled, mip 0 otherwise
+    let view_coord = vec2<i32>(uv * vec2<f32>(textureDimensions(view_depth_texture)));
+    let view_depth = textureLoad(view_depth_texture, view_coord, 0);
+
+    // Reverse-Z: larger is nearer. The selected object wrote the view depth
+    // too, so where it is in front both depths match.
+    return object_depth >= view_depth * (1.0 - DEPTH_TOLERANCE);
+}
+
 @fragment
 fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     let scene_color = textureSample(scene_texture, scene_sampler, in.uv);
 
-    // Draw outline at current boundary positions only
-    if is_boundary_edge(in.uv) {
-        return uniforms.outline_color;
+    let id_coord = vec2<i32>(in.uv * vec2<f32>(textureDimensions(id_texture)));
+    let center_id = load_id(id_coord, vec2<i32>(0));
+
+    // If center pixel has no ID, it's not part of a selected object
+    if center_id.a < 0.01 || !is_boundary_edge(id_coord) {
+        return scene_color;
+    }
+
+    var outline_color = uniforms.selected_color;
+    if center_id.a > ACTIVE_ALPHA_THRESHOLD {
+        outline_color = uniforms.active_color;
     }
 
-    // Pass through scene unchanged
-    return scene_color;
+#ifdef OCCLUDED
+    if is_visible(in.uv, id_coord) {
+        return scene_color;
+    }
+    let blended = mix(scene_color.rgb, outline_color.rgb, uniforms.occluded_opacity);
+    return vec4<f32>(blended, scene_color.a);
+#else
+    if !is_visible(in.uv, id_coord) {
+        return scene_color;
+    }
+    return outline_color;
+#endif
 }
@@ -0,0 +1,240 @@
+//! Memory budget for canvas paint history
+//!
+//! A canvas's stroke log grows for the whole session. When the stroke logs
+//! and undo stacks of all canvases hold more than the budget, or the UI sends
+//! `UiToBevy::CompactHistory`, canvas logs are checkpointed one at a time,
+//! largest first: what was logged before the undo stack is baked into a
+//! compressed snapshot and its dab data dropped (see
+//! `PaintingPipeline::begin_checkpoint`). Undo is unaffected, and a replay
+//! starts from the latest checkpoint.
+//!
+//! Compression runs a band of rows at a time, for up to
+//! `COMPACTION_FRAME_BUDGET` per frame. Undo stacks keep their configured
+//! depth, so they alone can stay over the budget; that is logged once. Mesh
+//! paint keeps no stroke log, so only canvases count. `RenderStats` reports
+//! the usage.
+
+use std::time::Duration;
+
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use painting::CheckpointBuilder;
+use pentimento_config::SettingsFile;
+
+use crate::PaintingResource;
+
+/// Default byte budget for canvas stroke logs and undo stacks (1 GiB)
+pub const DEFAULT_HISTORY_BUDGET_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Longest time spent compressing a checkpoint per frame
+const COMPACTION_FRAME_BUDGET: Duration = Duration::from_millis(2);
+
+/// Byte budget and checkpointing state of the paint history
+#[derive(Resource)]
+pub struct HistoryBudget {
+    /// Bytes the stroke logs and undo stacks may hold before logs are checkpointed
+    pub budget_bytes: u64,
+    /// Bytes held at the last check
+    used_bytes: u64,
+    /// Checkpoint every canvas that has something to bake, budget or not
+    compaction_requested: bool,
+    /// Checkpoint being compressed
+    job: Option<CheckpointBuilder>,
+    /// Over budget with nothing left to checkpoint (already logged)
+    exhausted: bool,
+}
+
+impl Default for HistoryBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_BUDGET_BYTES)
+    }
+}
+
+impl HistoryBudget {
+    pub fn new(budget_bytes: u64) -> Self {
+        Self {
+            budget_bytes,
+            used_bytes: 0,
+            compaction_requested: false,
+            job: None,
+            exhausted: false,
+        }
+    }
+
+    /// Budget from the settings file, falling back to the default
+    pub fn from_settings(settings: &SettingsFile) -> Self {
+        Self::new(
+            settings
+                .paint_history_budget_mb
+                .map_or(DEFAULT_HISTORY_BUDGET_BYTES, |mb| mb * 1024 * 1024),
+        )
+    }
+
+    /// Bytes the stroke logs and undo stacks held at the last check
+    pub fn used_bytes(&self) -> u64 {
+        self.used_bytes
+    }
+
+    /// Checkpoint every canvas log now, whether or not it is over budget
+    pub fn request_compaction(&mut self) {
+        info!("Paint history compaction requested");
+        self.compaction_requested = true;
+    }
+
+    /// Whether a checkpoint is being compressed
+    pub fn is_compacting(&self) -> bool {
+        self.job.is_some()
+    }
+}
+
+/// Plugin checkpointing canvas stroke logs to stay within `HistoryBudget`
+pub struct PaintHistoryPlugin;
+
+impl Plugin for PaintHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HistoryBudget>()
+            .add_systems(Update, enforce_history_budget);
+    }
+}
+
+/// Start, continue, and store checkpoints while over budget or asked to
+fn enforce_history_budget(
+    mut budget: ResMut<HistoryBudget>,
+    painting: Option<Res<PaintingResource>>,
+) {
+    let Some(painting) = painting else {
+        return;
+    };
+    let used_bytes = painting.history_bytes() as u64;
+    budget.used_bytes = used_bytes;
+    let over_budget = used_bytes > budget.budget_bytes;
+
+    if budget.job.is_none() {
+        if !over_budget && !budget.compaction_requested {
+            budget.exhausted = false;
+            return;
+        }
+        budget.job = painting.begin_checkpoint();
+        if budget.job.is_none() {
+            if over_budget && !budget.exhausted {
+                warn!(
+                    "Paint history holds {} MiB, over its {} MiB budget, with nothing left to checkpoint",
+                    used_bytes / (1024 * 1024),
+                    budget.budget_bytes / (1024 * 1024)
+                );
+                budget.exhausted = true;
+            }
+            budget.compaction_requested = false;
+            return;
+        }
+    }
+
+    let Some(job) = budget.job.as_mut() else {
+        return;
+    };
+    let started = Instant::now();
+    let mut done = job.step();
+    while !done && started.elapsed() < COMPACTION_FRAME_BUDGET {
+        done = job.step();
+    }
+    if !done {
+        return;
+    }
+
+    if let Some(job) = budget.job.take() {
+        let checkpoint = job.finish();
+        debug!(
+            "Checkpointed canvas {} before stroke {} ({} KiB compressed)",
+            checkpoint.space_id,
+            checkpoint.next_stroke,
+            checkpoint.compressed_bytes() / 1024
+        );
+        // The canvas may have been removed while its checkpoint was compressed
+        if let Some(pipeline) = painting.get_pipeline(checkpoint.space_id) {
+            pipeline.log().apply_checkpoint(checkpoint);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paint(painting: &mut PaintingResource, plane_id: u32, stroke_id: u64) {
+        let pipeline = painting.get_or_create_pipeline(plane_id, 128, 128);
+        pipeline.begin_stroke(plane_id, stroke_id, 0);
+        for i in 0..40 {
+            pipeline.stroke_to(4.0 + i as f32 * 3.0, 4.0 + stroke_id as f32 * 4.0, 1.0);
+        }
+        pipeline.end_stroke();
+    }
+
+    fn app_with(painting: PaintingResource, budget: HistoryBudget) -> App {
+        let mut app = App::new();
+        app.insert_resource(painting)
+            .insert_resource(budget)
+            .add_systems(Update, enforce_history_budget);
+        app
+    }
+
+    /// Stroke IDs still logged for a canvas
+    fn logged(app: &App, plane_id: u32) -> Vec<u64> {
+        let painting = app.world().resource::<PaintingResource>();
+        let pipeline = painting.get_pipeline(plane_id).unwrap();
+        let mut ids: Vec<u64> = pipeline
+            .log()
+            .query_by_space(plane_id)
+            .iter()
+            .map(|packet| packet.header.stroke_id)
+            .collect();
+        ids.dedup();
+        ids
+    }
+
+    #[test]
+    fn test_over_budget_checkpoints_behind_the_undo_stack() {
+        let mut painting = PaintingResource::new();
+        for stroke_id in 1..=25 {
+            paint(&mut painting, 1, stroke_id);
+        }
+        let mut app = app_with(painting, HistoryBudget::new(0));
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        // The 20 undoable strokes stay logged
+        assert_eq!(logged(&app, 1), (6..=25).collect::<Vec<_>>());
+        let budget = app.world().resource::<HistoryBudget>();
+        assert!(budget.used_bytes() > 0);
+        assert!(budget.exhausted);
+    }
+
+    #[test]
+    fn test_under_budget_waits_for_a_request() {
+        let mut painting = PaintingResource::new();
+        for stroke_id in 1..=25 {
+            paint(&mut painting, 1, stroke_id);
+        }
+        for stroke_id in 26..=30 {
+            paint(&mut painting, 2, stroke_id);
+        }
+        let mut app = app_with(painting, HistoryBudget::default());
+
+        app.update();
+        assert_eq!(logged(&app, 1).len(), 25);
+
+        app.world_mut()
+            .resource_mut::<HistoryBudget>()
+            .request_compaction();
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(logged(&app, 1), (6..=25).collect::<Vec<_>>());
+        // Everything on the second canvas is still undoable
+        assert_eq!(logged(&app, 2), (26..=30).collect::<Vec<_>>());
+        let budget = app.world().resource::<HistoryBudget>();
+        assert!(!budget.compaction_requested);
+        assert!(!budget.exhausted);
+    }
+}
@@ -1,8 +1,11 @@
 //! Paint mode state and input handling
 //!
 //! This module provides the paint mode resource and handles input for
-//! stroke creation. When paint mode is active and a canvas plane is selected,
-//! left mouse button starts/continues a stroke, generating PaintEvents.
+//! stroke creation. In canvas paint mode (`EditorMode::Paint`), left mouse
+//! button starts/continues a stroke on the active plane, generating
+//! PaintEvents. `PaintMode::active` is set on entering either paint mode
+//! (canvas or mesh) and cleared, after flushing the stroke, on leaving it.
+//! Camera navigation ([`BrushNavigation`]) ends a stroke the same way.
 //!
 //! The actual dab generation is handled elsewhere (Phase 3) - this module
 //! just emits PaintEvents with world-space positions.
@@ -11,14 +14,19 @@ use bevy::ecs::message::Message;
 use bevy::input::mouse::MouseButton;
 use bevy::prelude::*;
 use bevy::window::{CursorMoved, PrimaryWindow};
+use pentimento_config::{Keymap, actions};
 
+use crate::brush_navigation::BrushNavigation;
 use crate::camera::MainCamera;
 use crate::canvas_plane::{ActiveCanvasPlane, CanvasPlane};
+use crate::canvas_tool::CanvasToolState;
+use crate::edit_mode::{EditModeAppExt, EditModeRequests, EditModeState, EditorMode};
+use crate::pixel_selection::PixelSelectionTool;
 
 /// Resource tracking paint tool state
 #[derive(Resource, Default)]
 pub struct PaintMode {
-    /// Whether paint mode is currently active
+    /// Whether a paint mode (canvas or mesh) is currently active
     pub active: bool,
     /// Current stroke state, if a stroke is in progress
     pub current_stroke: Option<StrokeState>,
@@ -101,44 +109,47 @@ impl Plugin for PaintModePlugin {
                     handle_paint_input.after(handle_paint_mode_toggle),
                 ),
             );
+        for mode in [EditorMode::Paint, EditorMode::MeshPaint] {
+            app.on_enter_edit_mode(mode, enter_paint_mode)
+                .on_exit_edit_mode(mode, exit_paint_mode);
+        }
+    }
+}
+
+fn enter_paint_mode(_target: In<Option<Entity>>, mut paint_mode: ResMut<PaintMode>) {
+    paint_mode.active = true;
+}
+
+/// Finish the stroke in progress, so it lands before the mode changes
+fn exit_paint_mode(mut paint_mode: ResMut<PaintMode>, mut paint_events: MessageWriter<PaintEvent>) {
+    if paint_mode.current_stroke.take().is_some() {
+        paint_events.write(PaintEvent::StrokeEnd);
+        info!("Stroke ended by leaving paint mode");
     }
+    paint_mode.active = false;
 }
 
 /// Handle paint mode toggle (Shift+Tab)
+///
+/// Leaves either paint mode; otherwise paints on the active canvas plane if
+/// there is one, and on the selected mesh if not.
 fn handle_paint_mode_toggle(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
+    edit_mode: Res<EditModeState>,
+    active_plane: Res<ActiveCanvasPlane>,
     mut paint_mode: ResMut<PaintMode>,
     mut paint_events: MessageWriter<PaintEvent>,
-    mut outbound: ResMut<crate::OutboundUiMessages>,
+    mut mode_requests: EditModeRequests,
 ) {
     // Shift+Tab to toggle paint mode
-    let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
-    let tab = key_input.just_pressed(KeyCode::Tab);
-
-    if shift && tab {
-        paint_mode.active = !paint_mode.active;
-        info!(
-            "Paint mode {}",
-            if paint_mode.active {
-                "enabled"
-            } else {
-                "disabled"
-            }
-        );
-
-        // Notify UI of mode change
-        let mode = if paint_mode.active {
-            pentimento_ipc::EditMode::Paint
-        } else {
-            pentimento_ipc::EditMode::None
+    if keymap.just_pressed(actions::MODE_PAINT, &key_input) {
+        let mode = match edit_mode.mode {
+            EditorMode::Paint | EditorMode::MeshPaint => EditorMode::Object,
+            _ if active_plane.entity.is_some() => EditorMode::Paint,
+            _ => EditorMode::MeshPaint,
         };
-        outbound.send(pentimento_ipc::BevyToUi::EditModeChanged { mode });
-
-        // Cancel any in-progress stroke when toggling off
-        if !paint_mode.active && paint_mode.current_stroke.is_some() {
-            paint_events.write(PaintEvent::StrokeCancel);
-            paint_mode.current_stroke = None;
-        }
+        mode_requests.request_mode_change(mode);
     }
 
     // Escape cancels current stroke
@@ -161,9 +172,29 @@ fn handle_paint_input(
     mut stroke_id_gen: ResMut<StrokeIdGenerator>,
     mut paint_events: MessageWriter<PaintEvent>,
     time: Res<Time>,
+    selection_tool: Res<PixelSelectionTool>,
+    canvas_tool: Res<CanvasToolState>,
+    edit_mode: Res<EditModeState>,
+    navigation: Res<BrushNavigation>,
 ) {
-    // Only process if paint mode is active and a plane is selected
-    if !paint_mode.active {
+    // Only process in canvas paint mode with a plane selected
+    if edit_mode.mode != EditorMode::Paint {
+        return;
+    }
+
+    // The camera has the mouse: finish the stroke so it is logged whole
+    if navigation.navigating {
+        cursor_events.clear();
+        if paint_mode.current_stroke.take().is_some() {
+            paint_events.write(PaintEvent::StrokeEnd);
+            info!("Stroke ended for navigation");
+        }
+        return;
+    }
+
+    // Canvas drags belong to the pixel selection while it is armed or floating,
+    // and clicks to the fill or gradient tool while one is armed
+    if selection_tool.captures_input() || canvas_tool.captures_input() {
         return;
     }
 
@@ -197,6 +228,9 @@ fn handle_paint_input(
 
     // Handle stroke start (just pressed) - use current cursor position
     if mouse_button.just_pressed(MouseButton::Left) {
+        if !navigation.starts_stroke() {
+            return;
+        }
         let cursor_pos = cursor_positions
             .last()
             .copied()
@@ -322,7 +356,7 @@ fn handle_paint_input(
 ///
 /// Returns the world-space intersection point and UV coordinates on the plane.
 /// The plane is a Rectangle mesh (XY plane in local space, -Z is forward/normal).
-fn ray_plane_intersection(
+pub(crate) fn ray_plane_intersection(
     ray: Ray3d,
     plane_transform: &GlobalTransform,
     world_width: f32,
@@ -13,13 +13,26 @@ use bevy::render::{
     renderer::RenderQueue,
     texture::GpuImage,
 };
-use std::collections::HashMap;
-
-use painting::{BlendMode, BrushPreset, PaintingPipeline};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+
+#[cfg(feature = "gpu-paint")]
+use bevy::render::renderer::RenderDevice;
+#[cfg(feature = "gpu-paint")]
+use painting::GpuDabCompositor;
+use painting::{
+    BlendMode, BrushPreset, CheckpointBuilder, PaintingPipeline, StrokeLogEvent, TileCoord,
+};
 use pentimento_ipc::{BevyToUi, BlendMode as IpcBlendMode, LayerInfo};
 
+use crate::brush_tip::{BrushTipEvent, handle_brush_tip_events};
+use crate::canvas_file::{
+    CanvasExports, CanvasFileEvent, finish_canvas_exports, handle_canvas_file_events,
+};
 use crate::canvas_plane::{ActiveCanvasPlane, CanvasPlane};
-use crate::paint_mode::PaintEvent;
+use crate::canvas_resize::{CanvasResizeEvent, handle_canvas_resize_events};
+use crate::paint_mode::{PaintEvent, StrokeIdGenerator};
 
 /// Resource holding painting pipelines for each canvas plane
 ///
@@ -34,8 +47,17 @@ pub struct PaintingResource {
     pub brush_preset: BrushPreset,
     /// Current blend mode
     pub blend_mode: BlendMode,
+    /// Stroke log listeners attached to every pipeline, including ones created later
+    stroke_listeners: Vec<StrokeListener>,
+    /// Canvas tiles uploaded since live projection last reprojected them, by plane_id
+    projection_dirty_tiles: HashMap<u32, HashSet<TileCoord>>,
+    /// Render device pipelines splat round-brush dabs on, once the renderer is up
+    #[cfg(feature = "gpu-paint")]
+    gpu_device: Option<(wgpu::Device, wgpu::Queue)>,
 }
 
+type StrokeListener = Arc<dyn Fn(StrokeLogEvent) + Send + Sync>;
+
 impl Default for PaintingResource {
     fn default() -> Self {
         Self::new()
@@ -50,9 +72,26 @@ impl PaintingResource {
             brush_color: [0.0, 0.0, 0.0, 1.0], // Default black
             brush_preset: BrushPreset::default(),
             blend_mode: BlendMode::Normal,
+            stroke_listeners: Vec::new(),
+            projection_dirty_tiles: HashMap::new(),
+            #[cfg(feature = "gpu-paint")]
+            gpu_device: None,
         }
     }
 
+    /// Listen to the stroke logs of all canvas planes, current and future
+    /// (used to journal strokes for crash recovery)
+    pub fn add_stroke_listener<F>(&mut self, listener: F)
+    where
+        F: Fn(StrokeLogEvent) + Send + Sync + 'static,
+    {
+        let listener: StrokeListener = Arc::new(listener);
+        for pipeline in self.pipelines.values() {
+            attach_listener(pipeline, &listener);
+        }
+        self.stroke_listeners.push(listener);
+    }
+
     /// Get or create a pipeline for a canvas plane
     pub fn get_or_create_pipeline(
         &mut self,
@@ -70,6 +109,11 @@ impl PaintingResource {
             pipeline.set_blend_mode(blend_mode);
             // Clear to transparent by default (glass effect - see scene behind)
             pipeline.clear([0.0, 0.0, 0.0, 0.0]);
+            for listener in &self.stroke_listeners {
+                attach_listener(&pipeline, listener);
+            }
+            #[cfg(feature = "gpu-paint")]
+            attach_gpu(&mut pipeline, self.gpu_device.as_ref());
             pipeline
         })
     }
@@ -84,6 +128,38 @@ impl PaintingResource {
         self.pipelines.get_mut(&plane_id)
     }
 
+    /// Bytes held by the stroke logs and undo stacks of all canvas planes
+    pub fn history_bytes(&self) -> usize {
+        self.pipelines
+            .values()
+            .map(PaintingPipeline::history_bytes)
+            .sum()
+    }
+
+    /// Start checkpointing the canvas plane with the most history that has
+    /// something older than its undo stack (see `HistoryBudget`)
+    pub fn begin_checkpoint(&self) -> Option<CheckpointBuilder> {
+        let mut pipelines: Vec<_> = self.pipelines.iter().collect();
+        pipelines.sort_by_key(|(_, pipeline)| std::cmp::Reverse(pipeline.history_bytes()));
+        pipelines
+            .into_iter()
+            .find_map(|(plane_id, pipeline)| pipeline.begin_checkpoint(*plane_id))
+    }
+
+    /// `LayerStateChanged` for a canvas plane's layers
+    pub fn layer_state(&self, plane_id: u32) -> Option<BevyToUi> {
+        self.get_pipeline(plane_id).map(layer_state_message)
+    }
+
+    /// Take the canvas tiles changed since the last call for a plane
+    /// (live projection reprojects only these)
+    pub fn take_projection_dirty_tiles(&mut self, plane_id: u32) -> Vec<TileCoord> {
+        self.projection_dirty_tiles
+            .get_mut(&plane_id)
+            .map(|tiles| tiles.drain().collect())
+            .unwrap_or_default()
+    }
+
     /// Set brush color for all pipelines
     pub fn set_brush_color(&mut self, color: [f32; 4]) {
         self.brush_color = color;
@@ -139,6 +215,25 @@ impl PaintingResource {
     }
 }
 
+fn attach_listener(pipeline: &PaintingPipeline, listener: &StrokeListener) {
+    let listener = Arc::clone(listener);
+    pipeline
+        .log()
+        .add_event_listener(move |event| listener(event));
+}
+
+/// Splat the pipeline's round-brush strokes on the render device, if it can
+#[cfg(feature = "gpu-paint")]
+fn attach_gpu(pipeline: &mut PaintingPipeline, device: Option<&(wgpu::Device, wgpu::Queue)>) {
+    let Some((device, queue)) = device else {
+        return;
+    };
+    match GpuDabCompositor::new(device.clone(), queue.clone()) {
+        Ok(compositor) => pipeline.enable_gpu(compositor),
+        Err(error) => warn!("Painting on the CPU: {}", error),
+    }
+}
+
 /// Component linking a CanvasPlane to its GPU texture
 #[derive(Component)]
 pub struct CanvasTexture {
@@ -146,17 +241,22 @@ pub struct CanvasTexture {
     pub image_handle: Handle<Image>,
     /// Whether this is the first frame (needs full upload)
     pub needs_full_upload: bool,
+    /// Pipeline content origin the image and plane were last sized for
+    pub content_origin: (i32, i32),
 }
 
-/// Single tile upload request for partial GPU texture updates
+/// Single rect upload request for partial GPU texture updates
 #[derive(Clone)]
 pub struct DirtyTileUpload {
     /// Pixel offset in texture (x, y)
     pub offset: (u32, u32),
-    /// Tile dimensions (width, height)
+    /// Rect dimensions (width, height)
     pub size: (u32, u32),
-    /// RGBA8 pixel data (row-major, size.0 * size.1 * 4 bytes)
-    pub data: Vec<u8>,
+    /// Byte range of its pixels (row-major, size.0 * size.1 * bytes_per_pixel
+    /// bytes) in [`DirtyTileUploadBuffer::data`]
+    pub range: Range<usize>,
+    /// Bytes per pixel of the texture format (4 for RGBA8)
+    pub bytes_per_pixel: u32,
 }
 
 /// Per-canvas dirty tile buffer
@@ -164,15 +264,83 @@ pub struct DirtyTileUpload {
 pub struct CanvasDirtyTileBuffer {
     /// Asset ID of the canvas texture (for GpuImage lookup)
     pub image_id: AssetId<Image>,
-    /// Pending tile uploads
+    /// Pending rect uploads
     pub tiles: Vec<DirtyTileUpload>,
 }
 
 /// Resource for buffering dirty tile uploads to be extracted to render world
+///
+/// Cleared at the start of every frame; canvases and painted meshes append
+/// their dirty rects during `Update`.
 #[derive(Resource, Default, Clone, ExtractResource)]
 pub struct DirtyTileUploadBuffer {
-    /// Per-canvas upload buffers
+    /// Per-texture upload buffers
     pub canvases: Vec<CanvasDirtyTileBuffer>,
+    /// RGBA8 pixels of every upload, reused from frame to frame
+    pub data: Vec<u8>,
+}
+
+impl DirtyTileUploadBuffer {
+    /// Queue a `size` rect at `offset` of the texture, its RGBA8 rows
+    /// appended to the data by `write`
+    pub fn push(
+        &mut self,
+        image_id: AssetId<Image>,
+        offset: (u32, u32),
+        size: (u32, u32),
+        write: impl FnOnce(&mut Vec<u8>),
+    ) {
+        self.push_texels(image_id, offset, size, 4, write);
+    }
+
+    /// Like [`Self::push`], for textures of any uncompressed format
+    pub fn push_texels(
+        &mut self,
+        image_id: AssetId<Image>,
+        offset: (u32, u32),
+        size: (u32, u32),
+        bytes_per_pixel: u32,
+        write: impl FnOnce(&mut Vec<u8>),
+    ) {
+        let start = self.data.len();
+        write(&mut self.data);
+        debug_assert_eq!(
+            self.data.len() - start,
+            (size.0 * size.1 * bytes_per_pixel) as usize
+        );
+        let upload = DirtyTileUpload {
+            offset,
+            size,
+            range: start..self.data.len(),
+            bytes_per_pixel,
+        };
+
+        match self.canvases.last_mut() {
+            Some(canvas) if canvas.image_id == image_id => canvas.tiles.push(upload),
+            _ => self.canvases.push(CanvasDirtyTileBuffer {
+                image_id,
+                tiles: vec![upload],
+            }),
+        }
+    }
+}
+
+/// Tiles written to GPU textures, read by the render stats
+#[derive(Resource, Default)]
+pub struct TextureUploadStats {
+    tiles_uploaded: u64,
+}
+
+impl TextureUploadStats {
+    /// Count tiles uploaded this frame
+    pub(crate) fn add_tiles(&mut self, tiles: usize) {
+        self.tiles_uploaded += tiles as u64;
+    }
+
+    /// Tiles uploaded since the last call
+    pub fn take_tiles_uploaded(&mut self) -> u64 {
+        std::mem::take(&mut self.tiles_uploaded)
+    }
 }
 
 /// Convert f32 RGBA surface data to u8 RGBA for GPU upload
@@ -184,12 +352,7 @@ fn surface_to_rgba8(surface_bytes: &[u8]) -> Vec<u8> {
     let mut output = Vec::with_capacity(f32_slice.len() * 4);
 
     for pixel in f32_slice {
-        // Convert f32 (0.0-1.0) to u8 (0-255) with sRGB gamma
-        // Apply gamma correction: linear to sRGB
-        output.push(linear_to_srgb_u8(pixel[0]));
-        output.push(linear_to_srgb_u8(pixel[1]));
-        output.push(linear_to_srgb_u8(pixel[2]));
-        output.push((pixel[3].clamp(0.0, 1.0) * 255.0) as u8); // Alpha stays linear
+        output.extend_from_slice(&pixel_to_rgba8(pixel));
     }
 
     output
@@ -198,30 +361,59 @@ fn surface_to_rgba8(surface_bytes: &[u8]) -> Vec<u8> {
 /// Convert tile f32 RGBA data to u8 RGBA for partial GPU upload
 /// Input: &[[f32; 4]] per pixel (from TiledSurface::get_tile_data)
 /// Output: Vec<u8> containing [u8; 4] per pixel
-fn tile_data_to_rgba8(tile_data: &[[f32; 4]]) -> Vec<u8> {
+pub(crate) fn tile_data_to_rgba8(tile_data: &[[f32; 4]]) -> Vec<u8> {
     let mut output = Vec::with_capacity(tile_data.len() * 4);
 
     for pixel in tile_data {
-        // Convert f32 (0.0-1.0) to u8 (0-255) with sRGB gamma
-        output.push(linear_to_srgb_u8(pixel[0]));
-        output.push(linear_to_srgb_u8(pixel[1]));
-        output.push(linear_to_srgb_u8(pixel[2]));
-        output.push((pixel[3].clamp(0.0, 1.0) * 255.0) as u8); // Alpha stays linear
+        output.extend_from_slice(&pixel_to_rgba8(pixel));
     }
 
     output
 }
 
+/// Append a rect of an f32 RGBA surface `stride` pixels wide to `output`
+/// as u8 RGBA rows
+fn rect_to_rgba8(
+    pixels: &[[f32; 4]],
+    stride: u32,
+    (x, y, width, height): (u32, u32, u32, u32),
+    output: &mut Vec<u8>,
+) {
+    output.reserve((width * height * 4) as usize);
+    for row in y..y + height {
+        let start = (row * stride + x) as usize;
+        for pixel in &pixels[start..start + width as usize] {
+            output.extend_from_slice(&pixel_to_rgba8(pixel));
+        }
+    }
+}
+
+/// Convert an f32 (0.0-1.0) pixel to u8 (0-255) with sRGB gamma
+#[inline]
+fn pixel_to_rgba8(pixel: &[f32; 4]) -> [u8; 4] {
+    [
+        linear_to_srgb_u8(pixel[0]),
+        linear_to_srgb_u8(pixel[1]),
+        linear_to_srgb_u8(pixel[2]),
+        (pixel[3].clamp(0.0, 1.0) * 255.0) as u8, // Alpha stays linear
+    ]
+}
+
 /// Convert linear float to sRGB u8
 #[inline]
 fn linear_to_srgb_u8(linear: f32) -> u8 {
+    (linear_to_srgb(linear) * 255.0) as u8
+}
+
+/// Convert linear float to sRGB-encoded float (both 0.0-1.0)
+#[inline]
+pub(crate) fn linear_to_srgb(linear: f32) -> f32 {
     let linear = linear.clamp(0.0, 1.0);
-    let srgb = if linear <= 0.0031308 {
+    if linear <= 0.0031308 {
         linear * 12.92
     } else {
         1.055 * linear.powf(1.0 / 2.4) - 0.055
-    };
-    (srgb * 255.0) as u8
+    }
 }
 
 /// Plugin for the painting system
@@ -232,20 +424,35 @@ impl Plugin for PaintingSystemPlugin {
         // Main world resources and systems
         app.init_resource::<PaintingResource>()
             .init_resource::<DirtyTileUploadBuffer>()
+            .init_resource::<TextureUploadStats>()
+            .init_resource::<CanvasExports>()
+            .init_resource::<StrokeIdGenerator>()
+            .add_message::<CanvasFileEvent>()
+            .add_message::<CanvasResizeEvent>()
+            .add_message::<BrushTipEvent>()
             // ExtractResourcePlugin must be added to main app, not render_app
             .add_plugins(bevy::render::extract_resource::ExtractResourcePlugin::<
                 DirtyTileUploadBuffer,
             >::default())
+            .add_systems(First, clear_dirty_tile_uploads)
             .add_systems(
                 Update,
                 (
                     setup_canvas_textures,
+                    handle_brush_tip_events,
                     process_paint_events,
+                    handle_canvas_resize_events,
+                    sync_canvas_sizes,
                     extract_dirty_tiles,
+                    handle_canvas_file_events,
+                    finish_canvas_exports,
                 )
                     .chain(),
             );
 
+        #[cfg(feature = "gpu-paint")]
+        app.add_systems(Update, enable_gpu_painting.before(process_paint_events));
+
         // Render world system for GPU tile uploads
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             warn!("PaintingSystemPlugin: RenderApp not available, skipping render world setup");
@@ -259,6 +466,23 @@ impl Plugin for PaintingSystemPlugin {
     }
 }
 
+/// `LayerStateChanged` listing a pipeline's layers
+fn layer_state_message(pipeline: &PaintingPipeline) -> BevyToUi {
+    let layers = pipeline
+        .layers
+        .layer_info()
+        .into_iter()
+        .map(|l| LayerInfo {
+            id: l.id,
+            name: l.name,
+            visible: l.visible,
+            opacity: l.opacity,
+            is_active: l.is_active,
+        })
+        .collect();
+    BevyToUi::LayerStateChanged { layers }
+}
+
 /// Setup textures for newly created canvas planes
 fn setup_canvas_textures(
     mut commands: Commands,
@@ -276,43 +500,10 @@ fn setup_canvas_textures(
         let pipeline = painting_res.get_or_create_pipeline(canvas_plane.plane_id, width, height);
 
         // Send initial layer state to UI
-        let layers = pipeline
-            .layers
-            .layer_info()
-            .into_iter()
-            .map(|l| LayerInfo {
-                id: l.id,
-                name: l.name,
-                visible: l.visible,
-                opacity: l.opacity,
-                is_active: l.is_active,
-            })
-            .collect();
-        outbound.send(BevyToUi::LayerStateChanged { layers });
-
-        // Get the surface data and convert to RGBA8
-        let surface_bytes = pipeline.surface_as_bytes();
-        let rgba8_data = surface_to_rgba8(surface_bytes);
-
-        // Create Bevy Image with Rgba8UnormSrgb format
-        // This is the standard format with best compatibility
-        let mut image = Image::new(
-            Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            TextureDimension::D2,
-            rgba8_data,
-            TextureFormat::Rgba8UnormSrgb,
-            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-        );
-
-        // Set texture usages for painting
-        image.texture_descriptor.usage =
-            TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::COPY_SRC;
+        outbound.send(layer_state_message(pipeline));
 
-        let handle = images.add(image);
+        let content_origin = pipeline.content_origin();
+        let handle = images.add(canvas_image(pipeline));
 
         // Update the material to use this texture
         if let Some(material) = materials.get_mut(&material_handle.0) {
@@ -328,6 +519,7 @@ fn setup_canvas_textures(
         commands.entity(entity).insert(CanvasTexture {
             image_handle: handle.clone(),
             needs_full_upload: false, // Already uploaded initial data
+            content_origin,
         });
 
         info!(
@@ -337,6 +529,54 @@ fn setup_canvas_textures(
     }
 }
 
+/// Build the GPU image for a pipeline's composited surface
+fn canvas_image(pipeline: &PaintingPipeline) -> Image {
+    // Get the surface data and convert to RGBA8
+    let rgba8_data = surface_to_rgba8(pipeline.surface_as_bytes());
+
+    // Create Bevy Image with Rgba8UnormSrgb format
+    // This is the standard format with best compatibility
+    let mut image = Image::new(
+        Extent3d {
+            width: pipeline.width(),
+            height: pipeline.height(),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        rgba8_data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+
+    // Set texture usages for painting
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::COPY_SRC;
+    image
+}
+
+/// Give the painting pipelines the render device once the renderer is up
+#[cfg(feature = "gpu-paint")]
+fn enable_gpu_painting(
+    render_device: Option<Res<RenderDevice>>,
+    render_queue: Option<Res<RenderQueue>>,
+    mut painting_res: ResMut<PaintingResource>,
+) {
+    if painting_res.gpu_device.is_some() {
+        return;
+    }
+    let (Some(render_device), Some(render_queue)) = (render_device, render_queue) else {
+        return;
+    };
+    let gpu = (
+        render_device.wgpu_device().clone(),
+        wgpu::Queue::clone(&render_queue.0),
+    );
+    for pipeline in painting_res.pipelines.values_mut() {
+        attach_gpu(pipeline, Some(&gpu));
+    }
+    painting_res.gpu_device = Some(gpu);
+}
+
 /// Process paint events and update the pipeline
 fn process_paint_events(
     mut paint_events: MessageReader<PaintEvent>,
@@ -439,54 +679,137 @@ fn process_paint_events(
     }
 }
 
+/// Follow canvas resizes, crops, and their undo
+///
+/// When a pipeline's size or content origin no longer matches its plane,
+/// the `CanvasTexture` image is reallocated and the plane mesh is rescaled
+/// at the same world size per pixel. The plane moves so content that
+/// survived the resize stays where it was in the world.
+fn sync_canvas_sizes(
+    mut painting_res: ResMut<PaintingResource>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut canvas_query: Query<(
+        &mut CanvasPlane,
+        &mut CanvasTexture,
+        &mut Transform,
+        &Mesh3d,
+    )>,
+) {
+    let painting_res = &mut *painting_res;
+
+    for (mut canvas_plane, mut canvas_texture, mut transform, mesh) in canvas_query.iter_mut() {
+        let Some(pipeline) = painting_res.pipelines.get_mut(&canvas_plane.plane_id) else {
+            continue;
+        };
+        let (width, height) = (pipeline.width(), pipeline.height());
+        let origin = pipeline.content_origin();
+        if (width, height) == (canvas_plane.width, canvas_plane.height)
+            && origin == canvas_texture.content_origin
+        {
+            continue;
+        }
+
+        // Old canvas center in new pixel coordinates, relative to the new center
+        let old_size = Vec2::new(canvas_plane.width as f32, canvas_plane.height as f32);
+        let new_size = Vec2::new(width as f32, height as f32);
+        let shift = Vec2::new(
+            (origin.0 - canvas_texture.content_origin.0) as f32,
+            (origin.1 - canvas_texture.content_origin.1) as f32,
+        );
+        let world_per_pixel = canvas_plane.world_width / old_size.x;
+        let center_delta = (new_size - old_size) * 0.5 - shift;
+        // Pixel rows run down the plane's local -Y
+        let offset = transform.rotation * Vec3::new(center_delta.x, -center_delta.y, 0.0);
+        transform.translation += offset * world_per_pixel;
+
+        let world_width = new_size.x * world_per_pixel;
+        let world_height = new_size.y * world_per_pixel;
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            // The flat axis has no extent, so this works for XY and XZ quads
+            let scale_y = world_height / canvas_plane.world_height;
+            mesh.scale_by(Vec3::new(
+                world_width / canvas_plane.world_width,
+                scale_y,
+                scale_y,
+            ));
+        }
+        canvas_plane.width = width;
+        canvas_plane.height = height;
+        canvas_plane.world_width = world_width;
+        canvas_plane.world_height = world_height;
+
+        if let Some(image) = images.get_mut(&canvas_texture.image_handle) {
+            *image = canvas_image(pipeline);
+        }
+        canvas_texture.content_origin = origin;
+
+        // The new image already holds every tile, and the old GPU texture may
+        // be smaller than the new tile grid, so skip this frame's tile uploads
+        let dirty_tiles = pipeline.take_dirty_tiles();
+        let projection_tiles = painting_res
+            .projection_dirty_tiles
+            .entry(canvas_plane.plane_id)
+            .or_default();
+        projection_tiles.clear();
+        projection_tiles.extend(dirty_tiles);
+
+        info!(
+            "Resized texture for canvas plane {} to {}x{}",
+            canvas_plane.plane_id, width, height
+        );
+    }
+}
+
+/// Drop last frame's uploads; the render world has its own copy by now
+fn clear_dirty_tile_uploads(mut buffer: ResMut<DirtyTileUploadBuffer>) {
+    buffer.canvases.clear();
+    buffer.data.clear();
+}
+
 /// Extract dirty tiles from painting pipelines into the upload buffer
 ///
 /// This system runs in the main world and prepares tile data for upload.
+/// Dirty tiles are merged into rects by `PaintingPipeline::coalesce_tile_rects`
+/// and converted straight from the composited surface into the buffer.
 /// The actual GPU upload happens in the render world via `upload_dirty_tiles_to_gpu`.
 fn extract_dirty_tiles(
     mut painting_res: ResMut<PaintingResource>,
     canvas_query: Query<(&CanvasPlane, &CanvasTexture)>,
     mut buffer: ResMut<DirtyTileUploadBuffer>,
+    mut stats: ResMut<TextureUploadStats>,
 ) {
-    buffer.canvases.clear();
+    let painting_res = &mut *painting_res;
 
     for (canvas_plane, canvas_texture) in canvas_query.iter() {
-        let Some(pipeline) = painting_res.get_pipeline_mut(canvas_plane.plane_id) else {
+        let Some(pipeline) = painting_res.pipelines.get_mut(&canvas_plane.plane_id) else {
             continue;
         };
 
+        // Dabs splatted on the GPU this frame reach the layer here
+        #[cfg(feature = "gpu-paint")]
+        pipeline.sync_gpu_stroke();
+
         let dirty_tiles = pipeline.take_dirty_tiles();
         if dirty_tiles.is_empty() {
             continue;
         }
-
-        let mut canvas_buffer = CanvasDirtyTileBuffer {
-            image_id: canvas_texture.image_handle.id(),
-            tiles: Vec::with_capacity(dirty_tiles.len()),
-        };
-
-        for tile_coord in dirty_tiles {
-            let (x, y, w, h) = pipeline.get_tile_bounds(tile_coord);
-            let tile_data = pipeline.get_tile_data(tile_coord);
-            let rgba8 = tile_data_to_rgba8(&tile_data);
-
-            debug!(
-                "  Tile at ({}, {}) {}x{}, {} bytes",
-                x,
-                y,
-                w,
-                h,
-                rgba8.len()
-            );
-
-            canvas_buffer.tiles.push(DirtyTileUpload {
-                offset: (x, y),
-                size: (w, h),
-                data: rgba8,
+        painting_res
+            .projection_dirty_tiles
+            .entry(canvas_plane.plane_id)
+            .or_default()
+            .extend(dirty_tiles.iter().copied());
+        stats.add_tiles(dirty_tiles.len());
+
+        let pixels: &[[f32; 4]] = bytemuck::cast_slice(pipeline.surface_as_bytes());
+        let image_id = canvas_texture.image_handle.id();
+        for rect in pipeline.coalesce_tile_rects(&dirty_tiles) {
+            let (x, y, w, h) = rect;
+            debug!("  Rect at ({}, {}) {}x{}", x, y, w, h);
+            buffer.push(image_id, (x, y), (w, h), |data| {
+                rect_to_rgba8(pixels, pipeline.width(), rect, data)
             });
         }
-
-        buffer.canvases.push(canvas_buffer);
     }
 }
 
@@ -526,10 +849,10 @@ fn upload_dirty_tiles_to_gpu(
                     },
                     aspect: wgpu::TextureAspect::All,
                 },
-                &tile.data,
+                &buffer.data[tile.range.clone()],
                 wgpu::TexelCopyBufferLayout {
                     offset: 0,
-                    bytes_per_row: Some(tile.size.0 * 4), // RGBA8 = 4 bytes per pixel
+                    bytes_per_row: Some(tile.size.0 * tile.bytes_per_pixel),
                     rows_per_image: Some(tile.size.1),
                 },
                 wgpu::Extent3d {
@@ -568,4 +891,44 @@ mod tests {
         assert_eq!(res.brush_color, [1.0, 0.0, 0.0, 1.0]);
         assert_eq!(res.get_pipeline(0).unwrap().color(), [1.0, 0.0, 0.0, 1.0]);
     }
+
+    #[test]
+    fn test_dirty_rects_share_one_data_buffer() {
+        let mut pipeline = PaintingPipeline::new(1024, 1024);
+        pipeline.set_color([1.0, 0.0, 0.0, 1.0]);
+        pipeline.begin_stroke(0, 1, 0);
+        pipeline.stroke_to(300.0, 300.0, 1.0);
+        pipeline.stroke_to(301.0, 300.0, 1.0);
+        pipeline.end_stroke();
+        let dirty_tiles = pipeline.take_dirty_tiles();
+        assert!(!dirty_tiles.is_empty());
+
+        let mut buffer = DirtyTileUploadBuffer::default();
+        let pixels: &[[f32; 4]] = bytemuck::cast_slice(pipeline.surface_as_bytes());
+        let image_id = AssetId::<Image>::default();
+        for rect in pipeline.coalesce_tile_rects(&dirty_tiles) {
+            let (x, y, w, h) = rect;
+            buffer.push(image_id, (x, y), (w, h), |data| {
+                rect_to_rgba8(pixels, pipeline.width(), rect, data)
+            });
+        }
+
+        assert_eq!(buffer.canvases.len(), 1);
+        let uploads = &buffer.canvases[0].tiles;
+        assert_eq!(uploads.last().unwrap().range.end, buffer.data.len());
+        // The dab's center is painted red
+        let tile = uploads
+            .iter()
+            .find(|tile| {
+                (tile.offset.0..tile.offset.0 + tile.size.0).contains(&300)
+                    && (tile.offset.1..tile.offset.1 + tile.size.1).contains(&300)
+            })
+            .unwrap();
+        let index = ((300 - tile.offset.1) * tile.size.0 + (300 - tile.offset.0)) as usize * 4;
+        let pixel = &buffer.data[tile.range.clone()][index..index + 4];
+        assert!(
+            pixel[0] > 200 && pixel[1] == 0 && pixel[3] > 200,
+            "{pixel:?}"
+        );
+    }
 }
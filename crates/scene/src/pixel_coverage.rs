@@ -14,6 +14,14 @@
 //! Self-occlusion is NOT handled by this approach — it overestimates coverage
 //! for concave meshes. A future GPU depth-buffer pass can replace this for
 //! exact pixel counting.
+//!
+//! ## Texel Density
+//!
+//! The same triangle projection measures how many atlas texels a triangle
+//! covers when its UVs are viewed through an orthographic camera over the
+//! unit UV square. [`estimate_texel_density_cpu`] divides that by the
+//! triangle's world area, which mesh painting uses to keep the brush the
+//! same world size across UV charts of different density.
 
 use bevy::math::{Mat4, Vec3, Vec4};
 use bevy::prelude::*;
@@ -29,6 +37,28 @@ pub struct PixelCoverageState {
     pub max_vertices: usize,
 }
 
+/// Texel density of a UV-mapped mesh, from [`estimate_texel_density_cpu`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TexelDensity {
+    /// Atlas texels per world unit of each triangle (0 for degenerate ones)
+    pub faces: Vec<f32>,
+    /// Texels per world unit of the whole mesh: total texel area over total
+    /// world area
+    pub mean: f32,
+}
+
+impl TexelDensity {
+    /// Density of a face relative to the whole mesh (1.0 = average).
+    ///
+    /// Unknown and degenerate faces report 1.0.
+    pub fn factor(&self, face_id: u32) -> f32 {
+        match self.faces.get(face_id as usize) {
+            Some(&density) if density > 0.0 && self.mean > 0.0 => density / self.mean,
+            _ => 1.0,
+        }
+    }
+}
+
 /// Plugin for pixel coverage computation.
 pub struct PixelCoveragePlugin;
 
@@ -90,54 +120,131 @@ pub fn estimate_pixel_coverage_cpu(
         let p1 = positions[i1];
         let p2 = positions[i2];
 
-        // Project to clip space
-        let c0 = mvp * Vec4::new(p0.x, p0.y, p0.z, 1.0);
-        let c1 = mvp * Vec4::new(p1.x, p1.y, p1.z, 1.0);
-        let c2 = mvp * Vec4::new(p2.x, p2.y, p2.z, 1.0);
-
-        // Skip if any vertex is behind the camera
-        if c0.w <= 0.0 || c1.w <= 0.0 || c2.w <= 0.0 {
+        // Backface culling: skip negative area (clockwise winding = backfacing)
+        let Some(signed_area) = projected_triangle_area(p0, p1, p2, &mvp, width, height) else {
+            continue;
+        };
+        if signed_area <= 0.0 {
             continue;
         }
 
-        // Perspective divide to NDC
-        let n0 = Vec3::new(c0.x / c0.w, c0.y / c0.w, c0.z / c0.w);
-        let n1 = Vec3::new(c1.x / c1.w, c1.y / c1.w, c1.z / c1.w);
-        let n2 = Vec3::new(c2.x / c2.w, c2.y / c2.w, c2.z / c2.w);
-
-        // Frustum cull: skip if all vertices are outside the same clip plane
-        if (n0.x < -1.0 && n1.x < -1.0 && n2.x < -1.0)
-            || (n0.x > 1.0 && n1.x > 1.0 && n2.x > 1.0)
-            || (n0.y < -1.0 && n1.y < -1.0 && n2.y < -1.0)
-            || (n0.y > 1.0 && n1.y > 1.0 && n2.y > 1.0)
-            || (n0.z < 0.0 && n1.z < 0.0 && n2.z < 0.0)
-            || (n0.z > 1.0 && n1.z > 1.0 && n2.z > 1.0)
+        total_screen_area += signed_area;
+    }
+
+    // Clamp to total resolution (can't cover more than the entire screen)
+    let max_pixels = (width * height) as u32;
+    (total_screen_area as u32).min(max_pixels)
+}
+
+/// Estimate the atlas texel density of every triangle of a UV-mapped mesh.
+///
+/// Each triangle's UVs are projected through an orthographic view of the
+/// unit UV square at the atlas `resolution`; the covered texel area over the
+/// triangle's world area gives its texels per world unit. Mirrored UVs count
+/// like regular ones. Triangles outside the UV square or without area get 0.
+///
+/// # Arguments
+/// * `positions` - Vertex positions in local/object space
+/// * `uvs` - Vertex UVs, one per position
+/// * `indices` - Triangle indices (must be a multiple of 3)
+/// * `model_matrix` - Local-to-world transform of the mesh
+/// * `resolution` - Atlas resolution in texels
+pub fn estimate_texel_density_cpu(
+    positions: &[Vec3],
+    uvs: &[Vec2],
+    indices: &[u32],
+    model_matrix: &Mat4,
+    resolution: UVec2,
+) -> TexelDensity {
+    // UV (0-1, V up) to NDC; texture rows run down like screen rows
+    let uv_to_ndc = Mat4::from_translation(Vec3::new(-1.0, -1.0, 0.5))
+        * Mat4::from_scale(Vec3::new(2.0, 2.0, 1.0));
+    let width = resolution.x as f32;
+    let height = resolution.y as f32;
+
+    let mut faces = Vec::with_capacity(indices.len() / 3);
+    let (mut total_texels, mut total_world) = (0.0f32, 0.0f32);
+    for tri in indices.chunks_exact(3) {
+        let [i0, i1, i2] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+        if [i0, i1, i2]
+            .iter()
+            .any(|&i| i >= positions.len() || i >= uvs.len())
         {
+            faces.push(0.0);
             continue;
         }
 
-        // NDC to screen coordinates
-        let s0x = (n0.x + 1.0) * 0.5 * width;
-        let s0y = (1.0 - n0.y) * 0.5 * height;
-        let s1x = (n1.x + 1.0) * 0.5 * width;
-        let s1y = (1.0 - n1.y) * 0.5 * height;
-        let s2x = (n2.x + 1.0) * 0.5 * width;
-        let s2y = (1.0 - n2.y) * 0.5 * height;
+        let [w0, w1, w2] = [i0, i1, i2].map(|i| model_matrix.transform_point3(positions[i]));
+        let world_area = 0.5 * (w1 - w0).cross(w2 - w0).length();
+        let [t0, t1, t2] = [i0, i1, i2].map(|i| uvs[i].extend(0.0));
+        let texel_area =
+            projected_triangle_area(t0, t1, t2, &uv_to_ndc, width, height).map_or(0.0, f32::abs);
 
-        // Signed area via cross product (positive = CCW = front-facing)
-        let signed_area = 0.5 * ((s1x - s0x) * (s2y - s0y) - (s2x - s0x) * (s1y - s0y));
-
-        // Backface culling: skip negative area (clockwise winding = backfacing)
-        if signed_area <= 0.0 {
+        if world_area <= 1e-12 || texel_area <= 0.0 {
+            faces.push(0.0);
             continue;
         }
+        total_texels += texel_area;
+        total_world += world_area;
+        faces.push((texel_area / world_area).sqrt());
+    }
 
-        total_screen_area += signed_area;
+    let mean = if total_world > 0.0 {
+        (total_texels / total_world).sqrt()
+    } else {
+        0.0
+    };
+    TexelDensity { faces, mean }
+}
+
+/// Signed screen-space area of a triangle (positive = CCW = front-facing).
+///
+/// Returns None if a vertex is behind the camera or the triangle lies
+/// entirely outside one clip plane.
+fn projected_triangle_area(
+    p0: Vec3,
+    p1: Vec3,
+    p2: Vec3,
+    mvp: &Mat4,
+    width: f32,
+    height: f32,
+) -> Option<f32> {
+    // Project to clip space
+    let c0 = mvp * Vec4::new(p0.x, p0.y, p0.z, 1.0);
+    let c1 = mvp * Vec4::new(p1.x, p1.y, p1.z, 1.0);
+    let c2 = mvp * Vec4::new(p2.x, p2.y, p2.z, 1.0);
+
+    // Skip if any vertex is behind the camera
+    if c0.w <= 0.0 || c1.w <= 0.0 || c2.w <= 0.0 {
+        return None;
     }
 
-    // Clamp to total resolution (can't cover more than the entire screen)
-    let max_pixels = (width * height) as u32;
-    (total_screen_area as u32).min(max_pixels)
+    // Perspective divide to NDC
+    let n0 = Vec3::new(c0.x / c0.w, c0.y / c0.w, c0.z / c0.w);
+    let n1 = Vec3::new(c1.x / c1.w, c1.y / c1.w, c1.z / c1.w);
+    let n2 = Vec3::new(c2.x / c2.w, c2.y / c2.w, c2.z / c2.w);
+
+    // Frustum cull: skip if all vertices are outside the same clip plane
+    if (n0.x < -1.0 && n1.x < -1.0 && n2.x < -1.0)
+        || (n0.x > 1.0 && n1.x > 1.0 && n2.x > 1.0)
+        || (n0.y < -1.0 && n1.y < -1.0 && n2.y < -1.0)
+        || (n0.y > 1.0 && n1.y > 1.0 && n2.y > 1.0)
+        || (n0.z < 0.0 && n1.z < 0.0 && n2.z < 0.0)
+        || (n0.z > 1.0 && n1.z > 1.0 && n2.z > 1.0)
+    {
+        return None;
+    }
+
+    // NDC to screen coordinates
+    let s0x = (n0.x + 1.0) * 0.5 * width;
+    let s0y = (1.0 - n0.y) * 0.5 * height;
+    let s1x = (n1.x + 1.0) * 0.5 * width;
+    let s1y = (1.0 - n1.y) * 0.5 * height;
+    let s2x = (n2.x + 1.0) * 0.5 * width;
+    let s2y = (1.0 - n2.y) * 0.5 * height;
+
+    // Signed area via cross product (positive = CCW = front-facing)
+    Some(0.5 * ((s1x - s0x) * (s2y - s0y) - (s2x - s0x) * (s1y - s0y)))
 }
 
 #[cfg(test)]
@@ -210,4 +317,47 @@ mod tests {
             "Front-facing triangle should have positive coverage"
         );
     }
+
+    #[test]
+    fn test_texel_density_per_face() {
+        // Two unit quads side by side; the second chart is 4x denser
+        let positions = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(2.0, 1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+        ];
+        let uvs = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.2, 0.0),
+            Vec2::new(0.2, 0.2),
+            Vec2::new(0.0, 0.2),
+            Vec2::new(0.2, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 0.8),
+            Vec2::new(0.2, 0.8),
+        ];
+        let indices = vec![0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7];
+
+        let density = estimate_texel_density_cpu(
+            &positions,
+            &uvs,
+            &indices,
+            &Mat4::from_scale(Vec3::splat(2.0)),
+            UVec2::new(100, 100),
+        );
+        assert_eq!(density.faces.len(), 4);
+        // 20 texels across one world unit, halved by the 2x model scale
+        assert!((density.faces[0] - 10.0).abs() < 1e-3);
+        assert!((density.faces[1] - 10.0).abs() < 1e-3);
+        assert!((density.faces[2] - 40.0).abs() < 1e-3);
+        assert!((density.factor(2) / density.factor(0) - 4.0).abs() < 1e-3);
+        // Mean over total areas: (400 + 6400) texels on 8 square world units
+        assert!((density.mean - 850.0f32.sqrt()).abs() < 1e-3);
+        assert_eq!(density.factor(99), 1.0);
+    }
 }
@@ -0,0 +1,380 @@
+//! Pixel selection and transform on canvas planes
+//!
+//! `BeginPixelSelection` arms the tool; the next left drag on the active
+//! canvas draws a rectangle or lasso and lifts those pixels of the active
+//! layer into a floating selection. While it floats, dragging moves it,
+//! `TransformSelection` moves/rotates/scales it, Enter commits and Escape
+//! cancels. The floating pixels are shown on a quad parented to the canvas
+//! plane, so the GPU previews the transform and the pipeline resamples only
+//! once, at commit.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::ecs::message::Message;
+use bevy::math::{Affine2, Mat2};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::window::PrimaryWindow;
+use painting::{FloatingSelection, SelectionShape};
+use pentimento_ipc::PixelSelectionMode;
+
+use crate::OutboundUiMessages;
+use crate::camera::MainCamera;
+use crate::canvas_plane::{ActiveCanvasPlane, CanvasPlane};
+use crate::paint_mode::{PaintMode, StrokeIdGenerator, ray_plane_intersection};
+use crate::painting_system::{PaintingResource, tile_data_to_rgba8};
+
+/// Distance in canvas pixels between recorded lasso points
+const LASSO_POINT_SPACING: f32 = 2.0;
+
+/// Offset of the overlay quad in front of its canvas plane (world units)
+const OVERLAY_DEPTH_OFFSET: f32 = 0.001;
+
+/// Message for pixel selection actions on the active canvas
+#[derive(Message, Debug, Clone)]
+pub enum PixelSelectionEvent {
+    /// Arm the tool for the next drag
+    Begin { mode: PixelSelectionMode },
+    /// Lift the pixels inside a shape (canvas pixel coordinates)
+    Lift { shape: SelectionShape },
+    /// Move (canvas pixels), rotate, and scale the floating selection
+    Transform {
+        translate: Vec2,
+        rotate_deg: f32,
+        scale: Vec2,
+    },
+    /// Resample the floating selection onto its layer
+    Commit,
+    /// Put the floating selection back, or disarm the tool
+    Cancel,
+}
+
+/// Pixel selection tool state
+#[derive(Resource, Default)]
+pub struct PixelSelectionTool {
+    /// Armed shape, waiting for a drag on the canvas
+    pub mode: Option<PixelSelectionMode>,
+    /// Whether the active canvas has a floating selection
+    pub floating: bool,
+    /// Canvas pixel positions of the selection drag in progress
+    path: Vec<Vec2>,
+    /// Last canvas pixel position while dragging a floating selection
+    drag_from: Option<Vec2>,
+}
+
+impl PixelSelectionTool {
+    /// Whether canvas drags belong to the selection instead of the brush
+    pub fn captures_input(&self) -> bool {
+        self.mode.is_some() || self.floating
+    }
+}
+
+/// Quad showing a floating selection on its canvas plane
+#[derive(Component)]
+struct SelectionOverlay {
+    plane_entity: Entity,
+    /// Source bounds of the selection this overlay was built for
+    bounds: (u32, u32, u32, u32),
+}
+
+/// Plugin for pixel selection on canvas planes
+pub struct PixelSelectionPlugin;
+
+impl Plugin for PixelSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PixelSelectionTool>()
+            .add_message::<PixelSelectionEvent>()
+            .add_systems(
+                Update,
+                (
+                    handle_pixel_selection_input,
+                    handle_pixel_selection_events,
+                    sync_selection_overlay,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Turn canvas drags into selection shapes and selection moves
+#[allow(clippy::too_many_arguments)]
+fn handle_pixel_selection_input(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    plane_query: Query<(&GlobalTransform, &CanvasPlane)>,
+    active_plane: Res<ActiveCanvasPlane>,
+    paint_mode: Res<PaintMode>,
+    mut tool: ResMut<PixelSelectionTool>,
+    mut events: MessageWriter<PixelSelectionEvent>,
+) {
+    if !paint_mode.active || !tool.captures_input() {
+        tool.path.clear();
+        tool.drag_from = None;
+        return;
+    }
+
+    if tool.floating && key_input.just_pressed(KeyCode::Enter) {
+        events.write(PixelSelectionEvent::Commit);
+        return;
+    }
+    if key_input.just_pressed(KeyCode::Escape) {
+        events.write(PixelSelectionEvent::Cancel);
+        tool.path.clear();
+        return;
+    }
+
+    let cursor_pixel = (|| {
+        let plane_entity = active_plane.entity?;
+        let (camera, camera_transform) = camera_query.single().ok()?;
+        let (plane_transform, canvas_plane) = plane_query.get(plane_entity).ok()?;
+        let cursor = windows.single().ok()?.cursor_position()?;
+        let ray = camera.viewport_to_world(camera_transform, cursor).ok()?;
+        let (_, uv) = ray_plane_intersection(
+            ray,
+            plane_transform,
+            canvas_plane.world_width,
+            canvas_plane.world_height,
+        )?;
+        Some(uv * Vec2::new(canvas_plane.width as f32, canvas_plane.height as f32))
+    })();
+
+    if mouse_button.just_pressed(MouseButton::Left) {
+        if tool.floating {
+            tool.drag_from = cursor_pixel;
+        } else {
+            tool.path.clear();
+            tool.path.extend(cursor_pixel);
+        }
+    } else if mouse_button.pressed(MouseButton::Left) {
+        let Some(pixel) = cursor_pixel else {
+            return;
+        };
+        if let Some(from) = tool.drag_from {
+            if pixel != from {
+                events.write(PixelSelectionEvent::Transform {
+                    translate: pixel - from,
+                    rotate_deg: 0.0,
+                    scale: Vec2::ONE,
+                });
+                tool.drag_from = Some(pixel);
+            }
+        } else if !tool.path.is_empty() {
+            match tool.mode {
+                Some(PixelSelectionMode::Rect) => {
+                    tool.path.truncate(1);
+                    tool.path.push(pixel);
+                }
+                Some(PixelSelectionMode::Lasso) => {
+                    let far_enough = tool
+                        .path
+                        .last()
+                        .is_none_or(|last| last.distance(pixel) >= LASSO_POINT_SPACING);
+                    if far_enough {
+                        tool.path.push(pixel);
+                    }
+                }
+                None => {}
+            }
+        }
+    } else if mouse_button.just_released(MouseButton::Left) {
+        tool.drag_from = None;
+        let path = std::mem::take(&mut tool.path);
+        let shape = match tool.mode {
+            Some(PixelSelectionMode::Rect) if path.len() >= 2 => SelectionShape::Rect {
+                min: path[0],
+                max: path[path.len() - 1],
+            },
+            Some(PixelSelectionMode::Lasso) if path.len() >= 3 => {
+                SelectionShape::Lasso { points: path }
+            }
+            _ => return,
+        };
+        events.write(PixelSelectionEvent::Lift { shape });
+    }
+}
+
+/// Apply selection actions to the active canvas pipeline
+fn handle_pixel_selection_events(
+    mut events: MessageReader<PixelSelectionEvent>,
+    mut painting_res: ResMut<PaintingResource>,
+    mut stroke_ids: ResMut<StrokeIdGenerator>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    mut tool: ResMut<PixelSelectionTool>,
+    active_plane: Res<ActiveCanvasPlane>,
+    canvas_query: Query<&CanvasPlane>,
+) {
+    for event in events.read() {
+        let Some(canvas_plane) = active_plane.entity.and_then(|e| canvas_query.get(e).ok()) else {
+            outbound.send_error("canvas_not_active", "No canvas plane is active");
+            continue;
+        };
+        let plane_id = canvas_plane.plane_id;
+        let Some(pipeline) = painting_res.get_pipeline_mut(plane_id) else {
+            continue;
+        };
+
+        match event {
+            PixelSelectionEvent::Begin { mode } => {
+                if pipeline.floating_selection().is_some() {
+                    outbound.send_error(
+                        "selection_floating",
+                        "Commit or cancel the floating selection first",
+                    );
+                    continue;
+                }
+                tool.mode = Some(*mode);
+                info!("Pixel selection armed ({:?})", mode);
+            }
+            PixelSelectionEvent::Lift { shape } => {
+                tool.mode = None;
+                if pipeline.lift_selection(shape) {
+                    tool.floating = true;
+                    info!("Lifted pixel selection on canvas plane {}", plane_id);
+                } else {
+                    outbound.send_error(
+                        "selection_empty",
+                        "The selection covers no pixels of the canvas",
+                    );
+                }
+            }
+            PixelSelectionEvent::Transform {
+                translate,
+                rotate_deg,
+                scale,
+            } => {
+                if !pipeline.transform_selection(*translate, *rotate_deg, *scale) {
+                    outbound.send_error(
+                        "selection_not_floating",
+                        "There is no floating selection to transform",
+                    );
+                }
+            }
+            PixelSelectionEvent::Commit => {
+                if pipeline.commit_selection(plane_id, stroke_ids.next()) {
+                    info!("Committed pixel selection on canvas plane {}", plane_id);
+                } else {
+                    outbound.send_error(
+                        "selection_not_floating",
+                        "There is no floating selection to commit",
+                    );
+                }
+            }
+            PixelSelectionEvent::Cancel => {
+                if !pipeline.cancel_selection() {
+                    tool.mode = None;
+                }
+            }
+        }
+    }
+}
+
+/// Keep one overlay quad per floating selection, placed by its transform
+#[allow(clippy::too_many_arguments)]
+fn sync_selection_overlay(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    painting_res: Res<PaintingResource>,
+    active_plane: Res<ActiveCanvasPlane>,
+    canvas_query: Query<&CanvasPlane>,
+    mut overlays: Query<(Entity, &SelectionOverlay, &mut Transform)>,
+    mut tool: ResMut<PixelSelectionTool>,
+) {
+    let active = active_plane.entity.and_then(|entity| {
+        let canvas_plane = canvas_query.get(entity).ok()?;
+        let floating = painting_res
+            .get_pipeline(canvas_plane.plane_id)?
+            .floating_selection()?;
+        Some((entity, canvas_plane, floating))
+    });
+    if tool.floating != active.is_some() {
+        tool.floating = active.is_some();
+    }
+
+    let mut shown = false;
+    for (entity, overlay, mut transform) in overlays.iter_mut() {
+        match active {
+            Some((plane_entity, canvas_plane, floating))
+                if overlay.plane_entity == plane_entity && overlay.bounds == floating.bounds =>
+            {
+                *transform = overlay_transform(canvas_plane, floating);
+                shown = true;
+            }
+            _ => commands.entity(entity).despawn(),
+        }
+    }
+
+    let Some((plane_entity, canvas_plane, floating)) = active else {
+        return;
+    };
+    if shown {
+        return;
+    }
+
+    let (_, _, width, height) = floating.bounds;
+    let image = Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        tile_data_to_rgba8(&floating.pixels),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    let material = StandardMaterial {
+        base_color_texture: Some(images.add(image)),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    };
+
+    commands.spawn((
+        Mesh3d(meshes.add(Rectangle::new(1.0, 1.0))),
+        MeshMaterial3d(materials.add(material)),
+        overlay_transform(canvas_plane, floating),
+        SelectionOverlay {
+            plane_entity,
+            bounds: floating.bounds,
+        },
+        ChildOf(plane_entity),
+        Name::new("FloatingSelection"),
+    ));
+}
+
+/// Local transform of the unit overlay quad on its canvas plane
+fn overlay_transform(canvas_plane: &CanvasPlane, floating: &FloatingSelection) -> Transform {
+    let (x, y, width, height) = floating.bounds;
+    // Unit quad (Y up) -> source canvas pixels (Y down)
+    let quad_to_source = Affine2::from_mat2_translation(
+        Mat2::from_diagonal(Vec2::new(width as f32, -(height as f32))),
+        Vec2::new(
+            x as f32 + width as f32 * 0.5,
+            y as f32 + height as f32 * 0.5,
+        ),
+    );
+    // Canvas pixels -> plane-local XY
+    let pixel_to_local = Affine2::from_mat2_translation(
+        Mat2::from_diagonal(Vec2::new(
+            canvas_plane.world_width / canvas_plane.width as f32,
+            -canvas_plane.world_height / canvas_plane.height as f32,
+        )),
+        Vec2::new(
+            -canvas_plane.world_width * 0.5,
+            canvas_plane.world_height * 0.5,
+        ),
+    );
+    let affine = pixel_to_local * floating.transform * quad_to_source;
+
+    Transform::from_matrix(Mat4::from_cols(
+        affine.matrix2.x_axis.extend(0.0).extend(0.0),
+        affine.matrix2.y_axis.extend(0.0).extend(0.0),
+        Vec4::Z,
+        affine.translation.extend(OVERLAY_DEPTH_OFFSET).extend(1.0),
+    ))
+}
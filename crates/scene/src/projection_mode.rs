@@ -13,6 +13,7 @@ use bevy::ecs::message::Message;
 use bevy::prelude::*;
 
 use painting::MeshStorageMode;
+use pentimento_ipc::ProjectionOptions;
 
 /// Resource tracking projection painting state
 #[derive(Resource, Default)]
@@ -21,6 +22,8 @@ pub struct ProjectionMode {
     pub enabled: bool,
     /// Live projection: project as user paints (vs batch project on demand)
     pub live_projection: bool,
+    /// Occlusion, culling, and falloff used by live projection
+    pub live_options: ProjectionOptions,
 }
 
 /// Component marking a mesh as a target for projection painting.
@@ -63,7 +66,12 @@ pub enum ProjectionEvent {
     /// Toggle live projection mode on/off
     SetLiveProjection { enabled: bool },
     /// Project current canvas contents to all visible meshes (one-shot)
-    ProjectToScene,
+    ProjectToScene { options: ProjectionOptions },
+    /// Project current canvas contents to the selected meshes only (one-shot)
+    ProjectToSelection { options: ProjectionOptions },
+    /// Take the last one-shot projection's paint off its meshes and project
+    /// the same canvas snapshot, from the same camera, where they are now
+    ReprojectLast,
     /// Clear projected paint from a specific mesh
     ClearProjection { mesh_entity: Entity },
     /// Clear all projected paint from all meshes
@@ -96,11 +104,17 @@ fn handle_projection_events(
                     if *enabled { "enabled" } else { "disabled" }
                 );
             }
-            ProjectionEvent::ProjectToScene => {
-                info!("Project to scene requested");
+            ProjectionEvent::ProjectToScene { options } => {
+                info!("Project to scene requested ({:?})", options);
                 // Actual projection is handled by the projection painting system
                 // This event signals it should run a full projection pass
             }
+            ProjectionEvent::ProjectToSelection { options } => {
+                info!("Project to selection requested ({:?})", options);
+            }
+            ProjectionEvent::ReprojectLast => {
+                info!("Reprojection of the last projection requested");
+            }
             ProjectionEvent::ClearProjection { mesh_entity } => {
                 info!("Clear projection for entity {:?}", mesh_entity);
                 // TODO: Clear the projection target's surface
@@ -1,30 +1,37 @@
 //! Projection painting system - projects canvas paint onto 3D meshes.
 //!
 //! This module provides the core systems for projection painting:
-//! - Raycasting from canvas pixels to scene geometry
-//! - Applying projected paint to mesh textures
+//! - Mapping mesh texels through the camera onto the canvas
+//! - Occlusion, backface, and angular falloff tests per texel
+//! - Applying projected paint to mesh textures over budgeted frames
+//! - Incremental live projection from cached per-texel hits
+//! - Re-applying the last one-shot projection after objects move
 //! - Managing projection target textures and GPU uploads
+//!
+//! Meshes may be scaled non-uniformly: texel positions go through the full
+//! world-from-local affine and normals through its inverse transpose.
 
 use bevy::asset::RenderAssetUsages;
-use bevy::math::{Vec2, Vec3};
+use bevy::math::{Affine3A, Vec2, Vec3};
 use bevy::mesh::{Indices, VertexAttributeValues};
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use painting::{
-    BlendMode,
-    projection::{
-        CanvasPlaneParams, canvas_uv_to_ray, pixel_to_canvas_uv, project_brush_size_to_depth,
-    },
+    BlendMode, TileCoord,
+    projection::{CanvasPlaneParams, world_to_canvas_uv},
     projection_target::{ProjectionTargetStorage, UvAtlasTarget},
-    raycast::{MeshRaycastData, raycast_mesh},
+    raycast::MeshRaycastData,
 };
+use pentimento_ipc::ProjectionOptions;
 
 use crate::camera::MainCamera;
 use crate::canvas_plane::{ActiveCanvasPlane, CanvasPlane};
 use crate::painting_system::PaintingResource;
 use crate::projection_mode::{ProjectionEvent, ProjectionMode, ProjectionTarget};
+use crate::scene_bvh::{RaycastOptions, SceneBvh};
+use crate::selection::{Locked, Selectable, Selected};
 
 /// Resource holding projection targets for each mesh
 #[derive(Resource, Default)]
@@ -82,6 +89,16 @@ impl ProjectionTargets {
 pub struct MeshRaycastCache {
     /// Cached mesh data by entity
     cache: HashMap<Entity, MeshRaycastData>,
+    /// Per-texel live projection results by entity
+    texel_hits: HashMap<Entity, TexelHitCache>,
+    /// Bumped whenever the camera, canvas, or a projectable mesh moves
+    transform_generation: u64,
+    /// Generation at which the camera or canvas last moved; every texel hit
+    /// built before it is stale
+    view_generation: u64,
+    /// Generation at which each mesh last moved; only that mesh's texel hits
+    /// built before it are stale
+    moved_at: HashMap<Entity, u64>,
 }
 
 impl MeshRaycastCache {
@@ -103,6 +120,141 @@ impl MeshRaycastCache {
     /// Invalidate cache for an entity (call when mesh changes)
     pub fn invalidate(&mut self, entity: Entity) {
         self.cache.remove(&entity);
+        self.texel_hits.remove(&entity);
+        self.moved_at.remove(&entity);
+    }
+
+    /// Current transform generation; texel hits built at an older one may be stale
+    pub fn transform_generation(&self) -> u64 {
+        self.transform_generation
+    }
+
+    /// Mark every cached texel hit stale (call when the camera or canvas moves)
+    pub fn bump_transform_generation(&mut self) {
+        self.transform_generation += 1;
+        self.view_generation = self.transform_generation;
+    }
+
+    /// Mark one mesh's cached texel hits stale (call when it moves)
+    pub fn mark_moved(&mut self, entity: Entity) {
+        self.transform_generation += 1;
+        self.moved_at.insert(entity, self.transform_generation);
+    }
+
+    /// Whether texel hits built for `entity` at `generation` still hold
+    fn is_current(&self, entity: Entity, generation: u64) -> bool {
+        generation >= self.view_generation
+            && self
+                .moved_at
+                .get(&entity)
+                .is_none_or(|&moved| generation >= moved)
+    }
+}
+
+/// A mesh texel and the canvas pixel the camera sees it through.
+#[derive(Debug, Clone, Copy)]
+struct TexelHit {
+    texel: (u32, u32),
+    canvas_pixel: (u32, u32),
+    strength: f32,
+}
+
+/// Live projection texel hits for one mesh, keyed by the canvas tile they
+/// fall in so a canvas change only revisits the texels behind it.
+struct TexelHitCache {
+    /// Transform generation the hits were computed at
+    generation: u64,
+    /// Next triangle to rasterize while the cache is still being built
+    next_triangle: Option<usize>,
+    by_canvas_tile: HashMap<TileCoord, Vec<TexelHit>>,
+}
+
+impl TexelHitCache {
+    fn new(generation: u64) -> Self {
+        Self {
+            generation,
+            next_triangle: Some(0),
+            by_canvas_tile: HashMap::new(),
+        }
+    }
+
+    /// Rasterize up to `budget` more texels; returns how many were visited
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        &mut self,
+        camera: &ProjectionCamera,
+        mesh_data: &MeshRaycastData,
+        world_from_local: &Affine3A,
+        occluders: &Occluders,
+        resolution: (u32, u32),
+        tile_size: u32,
+        budget: usize,
+    ) -> usize {
+        let Some(start) = self.next_triangle else {
+            return 0;
+        };
+        let by_canvas_tile = &mut self.by_canvas_tile;
+        let (next, visited) = visit_mesh_texels(
+            camera,
+            mesh_data,
+            world_from_local,
+            occluders,
+            resolution,
+            start,
+            budget,
+            |_| true,
+            |hit| {
+                let tile = TileCoord {
+                    x: hit.canvas_pixel.0 / tile_size,
+                    y: hit.canvas_pixel.1 / tile_size,
+                };
+                by_canvas_tile.entry(tile).or_default().push(hit);
+            },
+        );
+        self.next_triangle = next;
+        visited
+    }
+
+    fn is_complete(&self) -> bool {
+        self.next_triangle.is_none()
+    }
+
+    /// Copy the canvas into the texels behind `tiles`; returns the texel count
+    fn reproject(
+        &self,
+        tiles: &[TileCoord],
+        canvas_pixel: impl Fn((u32, u32)) -> [f32; 4],
+        target: &mut UvAtlasTarget,
+    ) -> usize {
+        let mut count = 0;
+        for hit in tiles
+            .iter()
+            .filter_map(|tile| self.by_canvas_tile.get(tile))
+            .flatten()
+        {
+            let mut color = canvas_pixel(hit.canvas_pixel);
+            color[3] *= hit.strength;
+            // Live projection mirrors the canvas, so erased paint clears texels too
+            let (x, y) = hit.texel;
+            let surface = target.surface_mut();
+            surface.surface_mut().set_pixel(x, y, color);
+            surface.mark_dirty(x, y);
+            count += 1;
+        }
+        count
+    }
+}
+
+/// Texels reprojected by live projection, read by the render stats.
+#[derive(Resource, Default)]
+pub struct ProjectionStats {
+    texels_reprojected: u64,
+}
+
+impl ProjectionStats {
+    /// Texels reprojected since the last call
+    pub fn take_texels_reprojected(&mut self) -> u64 {
+        std::mem::take(&mut self.texels_reprojected)
     }
 }
 
@@ -158,6 +310,173 @@ fn extract_mesh_raycast_data(mesh: &Mesh) -> Option<MeshRaycastData> {
     })
 }
 
+/// Texels rasterized per frame by a pending projection job.
+const PROJECTION_TEXEL_BUDGET: usize = 16_384;
+
+/// Fraction of the camera-to-texel distance an occluder must be in front by.
+const OCCLUSION_BIAS: f32 = 1e-3;
+
+/// Texture resolution for meshes that have no projection target yet.
+const DEFAULT_PROJECTION_RESOLUTION: (u32, u32) = (512, 512);
+
+/// Objects projected paint can land on.
+type Projectable = Or<(With<Selectable>, With<ProjectionTarget>)>;
+
+/// Meshes that can receive or block projected paint. Locked meshes only
+/// block it.
+type ProjectableMeshQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Mesh3d,
+        &'static GlobalTransform,
+        &'static InheritedVisibility,
+        Has<Selected>,
+        Has<Locked>,
+    ),
+    (Projectable, Without<CanvasPlane>),
+>;
+
+/// Camera and canvas placement a projection looks through.
+struct ProjectionCamera {
+    camera_pos: Vec3,
+    canvas_center: Vec3,
+    canvas_right: Vec3,
+    canvas_up: Vec3,
+    canvas_params: CanvasPlaneParams,
+    options: ProjectionOptions,
+}
+
+impl ProjectionCamera {
+    fn new(
+        canvas_plane: &CanvasPlane,
+        canvas_transform: &GlobalTransform,
+        camera_transform: &GlobalTransform,
+        options: ProjectionOptions,
+    ) -> Self {
+        // The plane may have been scaled with the gizmo since it was made
+        let scale = canvas_transform.scale();
+        Self {
+            camera_pos: camera_transform.translation(),
+            canvas_center: canvas_transform.translation(),
+            canvas_right: canvas_transform.right().as_vec3(),
+            canvas_up: canvas_transform.up().as_vec3(),
+            canvas_params: CanvasPlaneParams {
+                resolution: (canvas_plane.width, canvas_plane.height),
+                world_size: (
+                    canvas_plane.world_width * scale.x,
+                    canvas_plane.world_height * scale.y,
+                ),
+            },
+            options,
+        }
+    }
+
+    /// Canvas pixel on the line of sight to a world point.
+    fn canvas_pixel(&self, world_pos: Vec3) -> Option<(u32, u32)> {
+        let uv = world_to_canvas_uv(
+            self.camera_pos,
+            world_pos,
+            self.canvas_center,
+            self.canvas_right,
+            self.canvas_up,
+            &self.canvas_params,
+        )?;
+        let (width, height) = self.canvas_params.resolution;
+        Some((
+            ((uv.x * width as f32) as u32).min(width - 1),
+            ((uv.y * height as f32) as u32).min(height - 1),
+        ))
+    }
+
+    /// Projection strength for a surface point, from backface culling and
+    /// the angular falloff.
+    fn strength(&self, world_pos: Vec3, normal: Vec3) -> f32 {
+        let to_camera = (self.camera_pos - world_pos).normalize_or_zero();
+        let mut facing = normal.dot(to_camera);
+        if facing < 0.0 {
+            if self.options.backface_cull {
+                return 0.0;
+            }
+            // Without culling, back faces receive paint like two-sided faces
+            facing = -facing;
+        }
+
+        let angle = facing.clamp(0.0, 1.0).acos().to_degrees();
+        let start = self.options.falloff_angle_deg.clamp(0.0, 90.0);
+        if angle <= start {
+            1.0
+        } else {
+            ((90.0 - angle) / (90.0 - start)).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Canvas contents and camera captured when a projection was requested.
+struct ProjectionView {
+    camera: ProjectionCamera,
+    /// Composited canvas pixels, row-major
+    pixels: Vec<[f32; 4]>,
+}
+
+impl ProjectionView {
+    fn pixel(&self, (x, y): (u32, u32)) -> [f32; 4] {
+        let width = self.camera.canvas_params.resolution.0 as usize;
+        self.pixels[y as usize * width + x as usize]
+    }
+}
+
+/// Texel colors from before a projection painted over them.
+type TexelBackup = HashMap<(u32, u32), [f32; 4]>;
+
+/// A one-shot projection that fills target textures over several frames.
+struct ProjectionJob {
+    view: ProjectionView,
+    /// Meshes receiving paint, processed in order
+    targets: Vec<Entity>,
+    /// Index into `targets` of the mesh being rasterized
+    target_index: usize,
+    /// Next UV triangle to rasterize on the current mesh
+    next_triangle: usize,
+    /// What each target looked like before this projection
+    previous: HashMap<Entity, TexelBackup>,
+}
+
+impl ProjectionJob {
+    fn new(view: ProjectionView, targets: Vec<Entity>) -> Self {
+        Self {
+            view,
+            targets,
+            target_index: 0,
+            next_triangle: 0,
+            previous: HashMap::new(),
+        }
+    }
+}
+
+/// The last finished one-shot projection, kept for `ReprojectLast`.
+struct LastProjection {
+    /// Canvas snapshot and camera pose it was projected from
+    view: ProjectionView,
+    targets: Vec<Entity>,
+    previous: HashMap<Entity, TexelBackup>,
+}
+
+/// Queue of pending one-shot projections.
+#[derive(Resource, Default)]
+struct ProjectionJobs {
+    queue: VecDeque<ProjectionJob>,
+    last: Option<LastProjection>,
+}
+
+/// Scene meshes tested for occlusion along camera rays.
+struct Occluders<'a> {
+    bvh: &'a SceneBvh,
+    /// Meshes that can block paint; empty when occlusion is off
+    entities: HashSet<Entity>,
+}
+
 /// Plugin for projection painting systems
 pub struct ProjectionPaintingPlugin;
 
@@ -165,10 +484,14 @@ impl Plugin for ProjectionPaintingPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ProjectionTargets>()
             .init_resource::<MeshRaycastCache>()
+            .init_resource::<ProjectionJobs>()
+            .init_resource::<ProjectionStats>()
             .add_systems(
                 Update,
                 (
+                    track_projection_transforms,
                     handle_projection_events,
+                    run_projection_jobs,
                     setup_projection_textures,
                     live_projection_system,
                 )
@@ -178,180 +501,396 @@ impl Plugin for ProjectionPaintingPlugin {
 }
 
 /// Handle projection mode events (project to scene, etc.)
+#[allow(clippy::too_many_arguments)]
 fn handle_projection_events(
     mut events: MessageReader<ProjectionEvent>,
     mut projection_mode: ResMut<ProjectionMode>,
     painting_res: Res<PaintingResource>,
     active_plane: Res<ActiveCanvasPlane>,
     canvas_query: Query<(&CanvasPlane, &GlobalTransform)>,
-    mesh_query: Query<(Entity, &Mesh3d, &GlobalTransform), With<ProjectionTarget>>,
-    meshes: Res<Assets<Mesh>>,
+    mesh_query: ProjectableMeshQuery,
     camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    mut jobs: ResMut<ProjectionJobs>,
     mut targets: ResMut<ProjectionTargets>,
-    mut mesh_cache: ResMut<MeshRaycastCache>,
 ) {
     for event in events.read() {
-        match event {
-            ProjectionEvent::ProjectToScene => {
-                // One-shot projection of entire canvas
-                project_canvas_to_scene(
-                    &painting_res,
-                    &active_plane,
-                    &canvas_query,
-                    &mesh_query,
-                    &meshes,
-                    &camera_query,
-                    &mut targets,
-                    &mut mesh_cache,
-                );
-            }
+        let (options, selection_only) = match event {
+            ProjectionEvent::ProjectToScene { options } => (*options, false),
+            ProjectionEvent::ProjectToSelection { options } => (*options, true),
             ProjectionEvent::SetLiveProjection { enabled } => {
                 projection_mode.live_projection = *enabled;
                 projection_mode.enabled = *enabled;
+                continue;
+            }
+            ProjectionEvent::ReprojectLast => {
+                // Take the old paint off and project again where the meshes are now
+                let Some(last) = jobs.last.take() else {
+                    warn!("No finished projection to reproject");
+                    continue;
+                };
+                for (entity, backup) in &last.previous {
+                    if let Some(target) = targets.get_mut(*entity) {
+                        restore_texels(target, backup);
+                    }
+                }
+                info!(
+                    "Reprojecting the last projection onto {} meshes",
+                    last.targets.len()
+                );
+                jobs.queue
+                    .push_back(ProjectionJob::new(last.view, last.targets));
+                continue;
             }
-            _ => {}
+            _ => continue,
+        };
+
+        let Some(view) = capture_projection_view(
+            &painting_res,
+            &active_plane,
+            &canvas_query,
+            &camera_query,
+            options,
+        ) else {
+            warn!("Projection requested without an active canvas");
+            continue;
+        };
+
+        let targets: Vec<Entity> = mesh_query
+            .iter()
+            .filter(|(_, _, _, visibility, selected, locked)| {
+                visibility.get() && !locked && (*selected || !selection_only)
+            })
+            .map(|(entity, ..)| entity)
+            .collect();
+
+        if targets.is_empty() {
+            info!("No meshes to project onto");
+            continue;
         }
+
+        let (width, height) = view.camera.canvas_params.resolution;
+        info!(
+            "Projecting canvas {}x{} onto {} meshes",
+            width,
+            height,
+            targets.len()
+        );
+        jobs.queue.push_back(ProjectionJob::new(view, targets));
     }
 }
 
-/// Project the entire canvas to scene meshes
-fn project_canvas_to_scene(
+/// Snapshot the active canvas and camera for a projection job
+fn capture_projection_view(
     painting_res: &PaintingResource,
     active_plane: &ActiveCanvasPlane,
     canvas_query: &Query<(&CanvasPlane, &GlobalTransform)>,
-    mesh_query: &Query<(Entity, &Mesh3d, &GlobalTransform), With<ProjectionTarget>>,
-    meshes: &Assets<Mesh>,
     camera_query: &Query<&GlobalTransform, With<MainCamera>>,
-    targets: &mut ProjectionTargets,
-    mesh_cache: &mut MeshRaycastCache,
-) {
-    let Some(plane_entity) = active_plane.entity else {
-        return;
-    };
+    options: ProjectionOptions,
+) -> Option<ProjectionView> {
+    let (canvas_plane, canvas_transform) = canvas_query.get(active_plane.entity?).ok()?;
+    let camera_transform = camera_query.single().ok()?;
+    let pipeline = painting_res.get_pipeline(canvas_plane.plane_id)?;
 
-    let Ok((canvas_plane, canvas_transform)) = canvas_query.get(plane_entity) else {
-        return;
-    };
+    let (width, height) = (canvas_plane.width, canvas_plane.height);
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for py in 0..height {
+        for px in 0..width {
+            pixels.push(pipeline.get_pixel(px, py).unwrap_or_default());
+        }
+    }
 
-    let Ok(camera_transform) = camera_query.single() else {
-        return;
+    Some(ProjectionView {
+        camera: ProjectionCamera::new(canvas_plane, canvas_transform, camera_transform, options),
+        pixels,
+    })
+}
+
+/// Build raycast data for every visible projectable mesh and return them.
+///
+/// Every visible mesh can hide a texel, not only the projection targets.
+fn cache_visible_meshes(
+    mesh_query: &ProjectableMeshQuery,
+    meshes: &Assets<Mesh>,
+    mesh_cache: &mut MeshRaycastCache,
+) -> Vec<Entity> {
+    let mut visible = Vec::new();
+    for (entity, mesh_handle, transform, visibility, ..) in mesh_query.iter() {
+        if !visibility.get() {
+            continue;
+        }
+        let Some(mesh) = meshes.get(&mesh_handle.0) else {
+            continue;
+        };
+        if mesh_cache.get_or_build(entity, mesh, transform).is_some() {
+            visible.push(entity);
+        }
+    }
+    visible
+}
+
+/// Occluders for the given meshes, or none when occlusion is off
+fn collect_occluders<'a>(
+    options: &ProjectionOptions,
+    visible: &[Entity],
+    bvh: &'a SceneBvh,
+) -> Occluders<'a> {
+    let entities = if options.occlusion {
+        visible.iter().copied().collect()
+    } else {
+        HashSet::new()
     };
+    Occluders { bvh, entities }
+}
 
-    let Some(pipeline) = painting_res.get_pipeline(canvas_plane.plane_id) else {
+/// Advance the front projection job by one frame's texel budget
+fn run_projection_jobs(
+    mut jobs: ResMut<ProjectionJobs>,
+    mesh_query: ProjectableMeshQuery,
+    meshes: Res<Assets<Mesh>>,
+    mut targets: ResMut<ProjectionTargets>,
+    mut mesh_cache: ResMut<MeshRaycastCache>,
+    bvh: Res<SceneBvh>,
+) {
+    let Some(job) = jobs.queue.front_mut() else {
         return;
     };
 
-    let camera_pos = camera_transform.translation();
-    let canvas_center = canvas_transform.translation();
-    let canvas_right = canvas_transform.right().as_vec3();
-    let canvas_up = canvas_transform.up().as_vec3();
-
-    let canvas_params = CanvasPlaneParams {
-        resolution: (canvas_plane.width, canvas_plane.height),
-        world_size: (canvas_plane.world_width, canvas_plane.world_height),
-    };
+    let visible = cache_visible_meshes(&mesh_query, &meshes, &mut mesh_cache);
+    let occluders = collect_occluders(&job.view.camera.options, &visible, &bvh);
 
-    let camera_to_canvas_dist = (canvas_center - camera_pos).length();
+    let mut budget = PROJECTION_TEXEL_BUDGET;
+    while budget > 0 && job.target_index < job.targets.len() {
+        let entity = job.targets[job.target_index];
+        let mesh = mesh_query
+            .get(entity)
+            .ok()
+            .zip(mesh_cache.cache.get(&entity));
+        let Some(((_, _, transform, ..), mesh_data)) = mesh else {
+            job.target_index += 1;
+            job.next_triangle = 0;
+            continue;
+        };
 
-    info!(
-        "Projecting canvas {}x{} to scene",
-        canvas_plane.width, canvas_plane.height
-    );
+        if mesh_data.uvs.is_empty() {
+            warn!("Skipping projection onto {:?}: mesh has no UVs", entity);
+            job.target_index += 1;
+            job.next_triangle = 0;
+            continue;
+        }
 
-    // Iterate over all canvas pixels
-    for py in 0..canvas_plane.height {
-        for px in 0..canvas_plane.width {
-            // Get the pixel color from the canvas
-            let Some(pixel) = pipeline.get_pixel(px, py) else {
-                continue;
-            };
+        let target = targets.get_or_create(entity, DEFAULT_PROJECTION_RESOLUTION);
+        let (next, used) = project_mesh_texels(
+            &job.view,
+            mesh_data,
+            &transform.affine(),
+            &occluders,
+            target,
+            job.previous.entry(entity).or_default(),
+            job.next_triangle,
+            budget,
+        );
+        budget = budget.saturating_sub(used);
 
-            // Skip transparent pixels
-            if pixel[3] < 0.01 {
-                continue;
+        match next {
+            Some(triangle) => job.next_triangle = triangle,
+            None => {
+                job.target_index += 1;
+                job.next_triangle = 0;
             }
+        }
+    }
+
+    if job.target_index >= job.targets.len()
+        && let Some(job) = jobs.queue.pop_front()
+    {
+        jobs.last = Some(LastProjection {
+            view: job.view,
+            targets: job.targets,
+            previous: job.previous,
+        });
+        info!("Projection complete");
+    }
+}
 
-            // Convert pixel to UV
-            let canvas_uv = pixel_to_canvas_uv((px, py), (canvas_plane.width, canvas_plane.height));
-
-            // Create ray from camera through canvas point
-            let (ray_origin, ray_dir) = canvas_uv_to_ray(
-                camera_pos,
-                canvas_uv,
-                canvas_center,
-                canvas_right,
-                canvas_up,
-                &canvas_params,
+/// Paint a mesh's projection target from a canvas snapshot.
+///
+/// Each texel is painted with the canvas color on the camera ray through it,
+/// so paint lands wherever the camera "sees" through the canvas. Each texel's
+/// color from before its first paint goes into `previous`. See
+/// [`visit_mesh_texels`] for the budget and return value.
+#[allow(clippy::too_many_arguments)]
+fn project_mesh_texels(
+    view: &ProjectionView,
+    mesh_data: &MeshRaycastData,
+    world_from_local: &Affine3A,
+    occluders: &Occluders,
+    target: &mut UvAtlasTarget,
+    previous: &mut TexelBackup,
+    start_triangle: usize,
+    budget: usize,
+) -> (Option<usize>, usize) {
+    let (tex_w, tex_h) = target.resolution();
+    let tex_size = Vec2::new(tex_w as f32, tex_h as f32);
+    visit_mesh_texels(
+        &view.camera,
+        mesh_data,
+        world_from_local,
+        occluders,
+        (tex_w, tex_h),
+        start_triangle,
+        budget,
+        |canvas_pixel| view.pixel(canvas_pixel)[3] >= 0.01,
+        |hit| {
+            let (x, y) = hit.texel;
+            previous.entry(hit.texel).or_insert_with(|| {
+                target
+                    .surface()
+                    .surface()
+                    .get_pixel(x, y)
+                    .unwrap_or_default()
+            });
+            let texel = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            target.apply_projected_pixel(
+                texel / tex_size,
+                view.pixel(hit.canvas_pixel),
+                hit.strength,
+                BlendMode::Normal,
             );
+        },
+    )
+}
+
+/// Put back texels saved before a projection painted them
+fn restore_texels(target: &mut UvAtlasTarget, backup: &TexelBackup) {
+    let surface = target.surface_mut();
+    for (&(x, y), &color) in backup {
+        surface.surface_mut().set_pixel(x, y, color);
+        surface.mark_dirty(x, y);
+    }
+}
+
+/// Rasterize a mesh's UV triangles and report each texel that sees the canvas.
+///
+/// Each texel is mapped to its world position, culled and faded by
+/// [`ProjectionCamera::strength`], and tested for occlusion. `wants` filters
+/// canvas pixels before the (expensive) occlusion test. Starts at
+/// `start_triangle` and stops after the triangle that exhausts `budget` texels.
+///
+/// Returns the next triangle to rasterize (`None` once the mesh is done) and
+/// the number of texels visited.
+#[allow(clippy::too_many_arguments)]
+fn visit_mesh_texels(
+    camera: &ProjectionCamera,
+    mesh_data: &MeshRaycastData,
+    world_from_local: &Affine3A,
+    occluders: &Occluders,
+    resolution: (u32, u32),
+    start_triangle: usize,
+    budget: usize,
+    wants: impl Fn((u32, u32)) -> bool,
+    mut visit: impl FnMut(TexelHit),
+) -> (Option<usize>, usize) {
+    let tex_size = Vec2::new(resolution.0 as f32, resolution.1 as f32);
+    // Normals follow the inverse transpose, so non-uniform scale tilts them
+    // the way it tilts the surface
+    let normal_from_local = world_from_local.matrix3.inverse().transpose();
+    let mut visited = 0;
+
+    for tri_idx in start_triangle..mesh_data.triangle_count() {
+        if visited >= budget {
+            return (Some(tri_idx), visited);
+        }
 
-            // Find nearest mesh hit
-            let mut nearest_hit: Option<(Entity, painting::MeshHit, f32)> = None;
+        let (i0, i1, i2) = mesh_data.triangle_indices(tri_idx);
+        let (p0, p1, p2) = mesh_data.triangle_positions(tri_idx);
+        let corners = [i0, i1, i2].map(|i| mesh_data.uvs[i as usize] * tex_size);
+        let normals = [i0, i1, i2].map(|i| mesh_data.normals[i as usize]);
 
-            for (entity, mesh_handle, mesh_transform) in mesh_query.iter() {
-                let Some(mesh) = meshes.get(&mesh_handle.0) else {
+        let area = edge_function(corners[0], corners[1], corners[2]);
+        if area.abs() < 1e-12 {
+            continue;
+        }
+
+        let min = corners[0]
+            .min(corners[1])
+            .min(corners[2])
+            .floor()
+            .max(Vec2::ZERO);
+        let max = corners[0]
+            .max(corners[1])
+            .max(corners[2])
+            .ceil()
+            .min(tex_size);
+
+        for py in min.y as u32..max.y as u32 {
+            for px in min.x as u32..max.x as u32 {
+                visited += 1;
+
+                let texel = Vec2::new(px as f32 + 0.5, py as f32 + 0.5);
+                let w0 = edge_function(corners[1], corners[2], texel) / area;
+                let w1 = edge_function(corners[2], corners[0], texel) / area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
                     continue;
-                };
+                }
 
-                let Some(mesh_data) = mesh_cache.get_or_build(entity, mesh, mesh_transform) else {
+                let local_pos = p0 * w0 + p1 * w1 + p2 * w2;
+                let local_normal = normals[0] * w0 + normals[1] * w1 + normals[2] * w2;
+                let world_pos = world_from_local.transform_point3(local_pos);
+                let normal = normal_from_local.mul_vec3(local_normal).normalize_or_zero();
+
+                let strength = camera.strength(world_pos, normal);
+                if strength <= 0.0 {
                     continue;
-                };
+                }
 
-                // Transform ray to mesh local space
-                let inv_transform = mesh_transform.affine().inverse();
-                let local_origin = inv_transform.transform_point3(ray_origin);
-                let local_dir = inv_transform.transform_vector3(ray_dir).normalize();
-
-                if let Some(hit) = raycast_mesh(local_origin, local_dir, mesh_data) {
-                    let world_hit_pos = mesh_transform.transform_point(hit.world_pos);
-                    let dist = (world_hit_pos - ray_origin).length();
-
-                    let dominated = match &nearest_hit {
-                        Some((_, _, prev_dist)) => dist >= *prev_dist,
-                        None => false,
-                    };
-
-                    if !dominated {
-                        // Transform hit back to world space
-                        let mut world_hit = hit;
-                        world_hit.world_pos = world_hit_pos;
-                        // Transform normal to world space (use world_hit since hit was moved)
-                        let local_normal = world_hit.normal;
-                        world_hit.normal = mesh_transform
-                            .affine()
-                            .transform_vector3(local_normal)
-                            .normalize();
-
-                        nearest_hit = Some((entity, world_hit, dist));
-                    }
+                let Some(canvas_pixel) = camera.canvas_pixel(world_pos) else {
+                    continue;
+                };
+                if !wants(canvas_pixel) {
+                    continue;
                 }
-            }
 
-            // Apply paint to nearest hit
-            if let Some((entity, hit, dist)) = nearest_hit {
-                // Get or create target for this mesh
-                let target = targets.get_or_create(entity, (512, 512)); // Default resolution
-
-                if let Some(tex_coord) = target.hit_to_tex_coord(&hit) {
-                    // Calculate projected brush size based on depth
-                    let world_radius = project_brush_size_to_depth(
-                        0.5,
-                        &canvas_params,
-                        camera_to_canvas_dist,
-                        dist,
-                    );
-
-                    // Convert world radius to texture pixels
-                    let (tex_w, _tex_h) = target.resolution();
-                    let _tex_radius = world_radius * (tex_w as f32 / canvas_params.world_size.0);
-
-                    // Apply as a small dab (single pixel projection)
-                    target.apply_projected_pixel(tex_coord, pixel, 1.0, BlendMode::Normal);
+                if is_occluded(camera.camera_pos, world_pos, occluders) {
+                    continue;
                 }
+
+                visit(TexelHit {
+                    texel: (px, py),
+                    canvas_pixel,
+                    strength,
+                });
             }
         }
     }
 
-    info!("Projection complete");
+    (None, visited)
+}
+
+/// Signed double area of triangle (a, b, c)
+fn edge_function(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Whether any occluder is hit between the camera and a world point
+fn is_occluded(camera_pos: Vec3, world_pos: Vec3, occluders: &Occluders) -> bool {
+    if occluders.entities.is_empty() {
+        return false;
+    }
+    let to_texel = world_pos - camera_pos;
+    let Ok(direction) = Dir3::new(to_texel) else {
+        return false;
+    };
+    let options = RaycastOptions {
+        max_distance: to_texel.length() * (1.0 - OCCLUSION_BIAS),
+        ..default()
+    };
+    occluders
+        .bvh
+        .raycast_with(Ray3d::new(camera_pos, direction), options, |entity| {
+            occluders.entities.contains(&entity)
+        })
+        .is_some()
 }
 
 /// Setup textures for projection targets
@@ -410,17 +949,46 @@ fn setup_projection_textures(
     }
 }
 
-/// Live projection system - projects paint in real-time as user strokes
+/// Mark texel hits stale when anything a projection looks through moves
+///
+/// A moved mesh only invalidates its own hits, unless live projection tests
+/// occlusion: then it may now hide or reveal texels of every other mesh.
+fn track_projection_transforms(
+    camera_query: Query<(), (With<MainCamera>, Changed<GlobalTransform>)>,
+    canvas_query: Query<(), (With<CanvasPlane>, Changed<GlobalTransform>)>,
+    mesh_query: Query<Entity, (Projectable, Changed<GlobalTransform>)>,
+    projection_mode: Res<ProjectionMode>,
+    mut mesh_cache: ResMut<MeshRaycastCache>,
+) {
+    let occlusion = projection_mode.live_options.occlusion;
+    if !camera_query.is_empty() || !canvas_query.is_empty() || (occlusion && !mesh_query.is_empty())
+    {
+        mesh_cache.bump_transform_generation();
+        return;
+    }
+    for entity in &mesh_query {
+        mesh_cache.mark_moved(entity);
+    }
+}
+
+/// Live projection system - projects paint in real-time as user strokes.
+///
+/// Texel hits are cached per mesh (built over several frames within the texel
+/// budget) and only the texels behind canvas tiles changed since the last run
+/// are reprojected, so a stroke costs in proportion to its area.
+#[allow(clippy::too_many_arguments)]
 fn live_projection_system(
     projection_mode: Res<ProjectionMode>,
-    painting_res: Res<PaintingResource>,
+    mut painting_res: ResMut<PaintingResource>,
     active_plane: Res<ActiveCanvasPlane>,
     canvas_query: Query<(&CanvasPlane, &GlobalTransform)>,
-    _mesh_query: Query<(Entity, &Mesh3d, &GlobalTransform), With<ProjectionTarget>>,
-    _meshes: Res<Assets<Mesh>>,
-    _camera_query: Query<&GlobalTransform, With<MainCamera>>,
-    _targets: ResMut<ProjectionTargets>,
-    _mesh_cache: ResMut<MeshRaycastCache>,
+    mesh_query: ProjectableMeshQuery,
+    meshes: Res<Assets<Mesh>>,
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    mut targets: ResMut<ProjectionTargets>,
+    mut mesh_cache: ResMut<MeshRaycastCache>,
+    bvh: Res<SceneBvh>,
+    mut stats: ResMut<ProjectionStats>,
 ) {
     // Only run if live projection is enabled
     if !projection_mode.live_projection {
@@ -431,23 +999,455 @@ fn live_projection_system(
         return;
     };
 
-    let Ok((canvas_plane, _canvas_transform)) = canvas_query.get(plane_entity) else {
+    let Ok((canvas_plane, canvas_transform)) = canvas_query.get(plane_entity) else {
         return;
     };
 
-    let Some(pipeline) = painting_res.get_pipeline(canvas_plane.plane_id) else {
+    let Ok(camera_transform) = camera_query.single() else {
         return;
     };
 
-    // Only process if there are dirty tiles (new paint)
-    if !pipeline.has_dirty_tiles() {
+    let Some(tile_size) = painting_res
+        .get_pipeline(canvas_plane.plane_id)
+        .map(|pipeline| pipeline.tile_size())
+    else {
+        return;
+    };
+
+    let camera = ProjectionCamera::new(
+        canvas_plane,
+        canvas_transform,
+        camera_transform,
+        projection_mode.live_options,
+    );
+    let visible = cache_visible_meshes(&mesh_query, &meshes, &mut mesh_cache);
+
+    // Drop hit caches that went stale or whose mesh is no longer visible or
+    // got locked
+    let generation = mesh_cache.transform_generation;
+    let stale: Vec<Entity> = mesh_cache
+        .texel_hits
+        .iter()
+        .filter(|(entity, hits)| !mesh_cache.is_current(**entity, hits.generation))
+        .map(|(entity, _)| *entity)
+        .collect();
+    let MeshRaycastCache {
+        cache, texel_hits, ..
+    } = &mut *mesh_cache;
+    texel_hits.retain(|entity, _| {
+        !stale.contains(entity)
+            && visible.contains(entity)
+            && mesh_query.get(*entity).is_ok_and(|(.., locked)| !locked)
+    });
+
+    // Finish building hit caches before touching the canvas dirty tiles, so
+    // changes made meanwhile are reprojected once the caches are ready
+    let occluders = collect_occluders(&camera.options, &visible, &bvh);
+    let mut budget = PROJECTION_TEXEL_BUDGET;
+    let mut complete = true;
+    for entity in &visible {
+        let Some(mesh_data) = cache.get(entity).filter(|data| !data.uvs.is_empty()) else {
+            continue;
+        };
+        let Ok((_, _, transform, .., locked)) = mesh_query.get(*entity) else {
+            continue;
+        };
+        if locked {
+            continue;
+        }
+        let hits = texel_hits
+            .entry(*entity)
+            .or_insert_with(|| TexelHitCache::new(generation));
+        if hits.is_complete() {
+            continue;
+        }
+        if budget > 0 {
+            let resolution = targets
+                .get_or_create(*entity, DEFAULT_PROJECTION_RESOLUTION)
+                .resolution();
+            let visited = hits.build(
+                &camera,
+                mesh_data,
+                &transform.affine(),
+                &occluders,
+                resolution,
+                tile_size,
+                budget,
+            );
+            budget = budget.saturating_sub(visited);
+        }
+        complete &= hits.is_complete();
+    }
+    if !complete {
         return;
     }
 
-    // For live projection, we project dirty regions rather than the whole canvas
-    // This is more efficient for real-time updates
-    // (Full implementation would iterate dirty tiles and project only those pixels)
+    let dirty_tiles = painting_res.take_projection_dirty_tiles(canvas_plane.plane_id);
+    if dirty_tiles.is_empty() {
+        return;
+    }
+    let Some(pipeline) = painting_res.get_pipeline(canvas_plane.plane_id) else {
+        return;
+    };
+
+    let mut reprojected = 0;
+    for (entity, hits) in texel_hits.iter() {
+        let Some(target) = targets.get_mut(*entity) else {
+            continue;
+        };
+        reprojected += hits.reproject(
+            &dirty_tiles,
+            |(x, y)| pipeline.get_pixel(x, y).unwrap_or_default(),
+            target,
+        );
+    }
+    stats.texels_reprojected += reprojected as u64;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use painting::raycast::raycast_mesh;
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    /// Camera at +Z looking at the origin through a canvas whose left half is
+    /// red and right half blue.
+    fn split_canvas_view(options: ProjectionOptions) -> ProjectionView {
+        let (width, height) = (8, 8);
+        let pixels = (0..width * height)
+            .map(|i| if i % width < width / 2 { RED } else { BLUE })
+            .collect();
+        ProjectionView {
+            camera: ProjectionCamera {
+                camera_pos: Vec3::new(0.0, 0.0, 5.0),
+                canvas_center: Vec3::new(0.0, 0.0, 3.0),
+                canvas_right: Vec3::X,
+                canvas_up: Vec3::Y,
+                canvas_params: CanvasPlaneParams {
+                    resolution: (width, height),
+                    world_size: (2.0, 2.0),
+                },
+                options,
+            },
+            pixels,
+        }
+    }
+
+    /// A scene BVH holding `mesh` at the origin as `Entity::PLACEHOLDER`.
+    fn occluder_bvh(mesh: &Mesh) -> SceneBvh {
+        let mut bvh = SceneBvh::default();
+        bvh.insert(
+            Entity::PLACEHOLDER,
+            AssetId::default(),
+            mesh,
+            &GlobalTransform::IDENTITY,
+            true,
+        );
+        bvh.update();
+        bvh
+    }
+
+    /// Project onto a unit sphere, then read the texel under a ray along Z.
+    fn project_sphere(options: ProjectionOptions) -> impl Fn(f32, f32) -> [f32; 4] {
+        let mesh = Sphere::new(1.0).mesh().uv(32, 18);
+        let mesh_data = extract_mesh_raycast_data(&mesh).unwrap();
+        let transform = Affine3A::IDENTITY;
+        let bvh = occluder_bvh(&mesh);
+        let occluders = Occluders {
+            bvh: &bvh,
+            entities: if options.occlusion {
+                HashSet::from([Entity::PLACEHOLDER])
+            } else {
+                HashSet::new()
+            },
+        };
+        let mut target = UvAtlasTarget::new(256, 256);
+
+        let view = split_canvas_view(options);
+        let (next, _) = project_mesh_texels(
+            &view,
+            &mesh_data,
+            &transform,
+            &occluders,
+            &mut target,
+            &mut TexelBackup::new(),
+            0,
+            usize::MAX,
+        );
+        assert!(next.is_none());
+
+        move |x, z_sign| sphere_texel(&mesh_data, &target, x, z_sign)
+    }
+
+    /// Texel under a ray along Z at height 0.1, from the front or back.
+    fn sphere_texel(
+        mesh_data: &MeshRaycastData,
+        target: &UvAtlasTarget,
+        x: f32,
+        z_sign: f32,
+    ) -> [f32; 4] {
+        let origin = Vec3::new(x, 0.1, 5.0 * z_sign);
+        let hit = raycast_mesh(origin, Vec3::Z * -z_sign, mesh_data).unwrap();
+        let texel = hit.uv.unwrap() * 256.0;
+        target
+            .surface()
+            .surface()
+            .get_pixel(texel.x as u32, texel.y as u32)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_occlusion_leaves_far_hemisphere_untouched() {
+        let sample = project_sphere(ProjectionOptions {
+            occlusion: true,
+            backface_cull: false,
+            falloff_angle_deg: 90.0,
+        });
+
+        assert_eq!(sample(-0.5, 1.0), RED);
+        assert_eq!(sample(0.5, 1.0), BLUE);
+        assert_eq!(sample(-0.5, -1.0)[3], 0.0);
+        assert_eq!(sample(0.5, -1.0)[3], 0.0);
+    }
+
+    #[test]
+    fn test_projection_without_occlusion_mirrors_onto_back() {
+        let sample = project_sphere(ProjectionOptions {
+            occlusion: false,
+            backface_cull: false,
+            falloff_angle_deg: 90.0,
+        });
+
+        // The back receives the same world-space X as the front, which reads
+        // mirrored when viewed from behind
+        assert_eq!(sample(-0.5, -1.0), RED);
+        assert_eq!(sample(0.5, -1.0), BLUE);
+    }
+
+    #[test]
+    fn test_falloff_fades_grazing_texels() {
+        let view = split_canvas_view(ProjectionOptions {
+            occlusion: false,
+            backface_cull: true,
+            falloff_angle_deg: 30.0,
+        });
+        let point = Vec3::ZERO;
+
+        assert_eq!(view.camera.strength(point, Vec3::Z), 1.0);
+        let grazing = Vec3::new(60f32.to_radians().sin(), 0.0, 60f32.to_radians().cos());
+        assert!((view.camera.strength(point, grazing) - 0.5).abs() < 1e-3);
+        assert_eq!(view.camera.strength(point, -Vec3::Z), 0.0);
+    }
+
+    #[test]
+    fn test_projection_follows_the_canvas_transform() {
+        let canvas = CanvasPlane::new(0, 8, 8, 1.0, 1.0);
+        let camera = GlobalTransform::from_translation(Vec3::new(0.0, 0.0, 5.0));
+        let pixel_for = |transform: Transform| {
+            ProjectionCamera::new(
+                &canvas,
+                &GlobalTransform::from(transform),
+                &camera,
+                ProjectionOptions::default(),
+            )
+            .canvas_pixel(Vec3::new(1.0, 0.0, 0.0))
+        };
+        let placed = Transform::from_xyz(0.0, 0.0, 3.0);
 
-    // For now, trigger a full projection when there's activity
-    // A more optimized version would track which pixels changed
+        // The line of sight crosses the canvas 0.4 right of its center
+        assert_eq!(pixel_for(placed), Some((7, 4)));
+        // ...which is the center once the canvas is moved there
+        assert_eq!(
+            pixel_for(placed.with_translation(Vec3::new(0.4, 0.0, 3.0))),
+            Some((4, 4))
+        );
+        // Scaled to twice the width, the same point is closer to the middle
+        assert_eq!(
+            pixel_for(placed.with_scale(Vec3::new(2.0, 1.0, 1.0))),
+            Some((5, 4))
+        );
+    }
+
+    /// 64x64 canvas, 2x2 world units at z = 3 seen from z = 5, each pixel
+    /// colored with its own coordinates
+    fn coordinate_canvas_view(options: ProjectionOptions) -> ProjectionView {
+        let size = 64;
+        let pixels = (0..size * size)
+            .map(|i| {
+                let (x, y) = (i % size, i / size);
+                let coordinate = |c: u32| (c as f32 + 0.5) / size as f32;
+                [coordinate(x), coordinate(y), 0.0, 1.0]
+            })
+            .collect();
+        let mut view = split_canvas_view(options);
+        view.camera.canvas_params.resolution = (size, size);
+        view.pixels = pixels;
+        view
+    }
+
+    #[test]
+    fn test_projection_onto_non_uniformly_scaled_sphere() {
+        let options = ProjectionOptions {
+            occlusion: false,
+            backface_cull: true,
+            falloff_angle_deg: 30.0,
+        };
+        let view = coordinate_canvas_view(options);
+        let mesh = Sphere::new(1.0).mesh().uv(64, 32);
+        let mesh_data = extract_mesh_raycast_data(&mesh).unwrap();
+        let world_from_local = Affine3A::from_scale(Vec3::new(1.0, 2.0, 1.0));
+        let local_from_world = world_from_local.inverse();
+        let occluders = Occluders {
+            bvh: &SceneBvh::default(),
+            entities: HashSet::new(),
+        };
+        let mut target = UvAtlasTarget::new(256, 256);
+        project_mesh_texels(
+            &view,
+            &mesh_data,
+            &world_from_local,
+            &occluders,
+            &mut target,
+            &mut TexelBackup::new(),
+            0,
+            usize::MAX,
+        );
+
+        let camera_pos = view.camera.camera_pos;
+        for (px, py) in [(32, 13), (40, 20), (24, 32), (36, 50)] {
+            // Line of sight through the canvas pixel, in the sphere's local space
+            let on_canvas = Vec3::new(
+                (px as f32 + 0.5) / 32.0 - 1.0,
+                1.0 - (py as f32 + 0.5) / 32.0,
+                3.0,
+            );
+            let direction = on_canvas - camera_pos;
+            let origin = local_from_world.transform_point3(camera_pos);
+            let local_direction = local_from_world.transform_vector3(direction);
+
+            // Where it meets the ellipsoid x² + (y/2)² + z² = 1
+            let a = local_direction.length_squared();
+            let b = 2.0 * origin.dot(local_direction);
+            let c = origin.length_squared() - 1.0;
+            let t = (-b - (b * b - 4.0 * a * c).sqrt()) / (2.0 * a);
+            let world_hit = camera_pos + direction * t;
+            let normal = Vec3::new(world_hit.x, world_hit.y / 4.0, world_hit.z).normalize();
+
+            let hit = raycast_mesh(origin, local_direction.normalize(), &mesh_data).unwrap();
+            let texel = hit.uv.unwrap() * 256.0;
+            let [r, g, _, alpha] = target
+                .surface()
+                .surface()
+                .get_pixel(texel.x as u32, texel.y as u32)
+                .unwrap();
+
+            // The texel carries the canvas pixel it is seen through, within a texel
+            let seen = ((r / alpha * 64.0) as i32, (g / alpha * 64.0) as i32);
+            assert!(
+                (seen.0 - px).abs() <= 1 && (seen.1 - py).abs() <= 1,
+                "canvas pixel {:?} landed as {:?}",
+                (px, py),
+                seen
+            );
+            // ...faded by the angle to the ellipsoid's own normal
+            let expected = view.camera.strength(world_hit, normal);
+            assert!(
+                (alpha - expected).abs() < 0.1,
+                "strength at {:?}: {} instead of {}",
+                (px, py),
+                alpha,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_reprojection_starts_from_the_texels_before() {
+        let mesh = Sphere::new(1.0).mesh().uv(32, 18);
+        let mesh_data = extract_mesh_raycast_data(&mesh).unwrap();
+        let occluders = Occluders {
+            bvh: &SceneBvh::default(),
+            entities: HashSet::new(),
+        };
+        let view = split_canvas_view(ProjectionOptions::default());
+        let mut target = UvAtlasTarget::new(64, 64);
+        let mut previous = TexelBackup::new();
+        project_mesh_texels(
+            &view,
+            &mesh_data,
+            &Affine3A::IDENTITY,
+            &occluders,
+            &mut target,
+            &mut previous,
+            0,
+            usize::MAX,
+        );
+        assert!(!previous.is_empty());
+
+        restore_texels(&mut target, &previous);
+        for &(x, y) in previous.keys() {
+            assert_eq!(target.surface().surface().get_pixel(x, y), Some([0.0; 4]));
+        }
+    }
+
+    #[test]
+    fn test_moved_mesh_only_invalidates_its_own_hits() {
+        let mut cache = MeshRaycastCache::default();
+        let (moved, still) = (
+            Entity::from_raw_u32(1).unwrap(),
+            Entity::from_raw_u32(2).unwrap(),
+        );
+        let built = cache.transform_generation();
+
+        cache.mark_moved(moved);
+        assert!(!cache.is_current(moved, built));
+        assert!(cache.is_current(still, built));
+        assert!(cache.is_current(moved, cache.transform_generation()));
+
+        cache.bump_transform_generation();
+        assert!(!cache.is_current(still, built));
+    }
+
+    #[test]
+    fn test_live_reprojection_touches_only_dirty_tiles() {
+        let mesh = Sphere::new(1.0).mesh().uv(32, 18);
+        let mesh_data = extract_mesh_raycast_data(&mesh).unwrap();
+        let transform = Affine3A::IDENTITY;
+        let bvh = occluder_bvh(&mesh);
+        let occluders = Occluders {
+            bvh: &bvh,
+            entities: HashSet::from([Entity::PLACEHOLDER]),
+        };
+        let view = split_canvas_view(ProjectionOptions::default());
+
+        // The cache builds over several budgeted calls
+        let mut hits = TexelHitCache::new(0);
+        let mut builds = 0;
+        while !hits.is_complete() {
+            hits.build(
+                &view.camera,
+                &mesh_data,
+                &transform,
+                &occluders,
+                (256, 256),
+                4,
+                4096,
+            );
+            builds += 1;
+        }
+        assert!(builds > 1);
+        let total: usize = hits.by_canvas_tile.values().map(Vec::len).sum();
+
+        // Only the top-right canvas tile changed
+        let dirty = [TileCoord { x: 1, y: 0 }];
+        let mut target = UvAtlasTarget::new(256, 256);
+        let count = hits.reproject(&dirty, |pixel| view.pixel(pixel), &mut target);
+
+        assert_eq!(count, hits.by_canvas_tile[&dirty[0]].len());
+        assert!(count > 0 && count * 2 < total);
+        assert_eq!(sphere_texel(&mesh_data, &target, 0.5, 1.0), BLUE);
+        assert_eq!(sphere_texel(&mesh_data, &target, -0.5, 1.0)[3], 0.0);
+    }
 }
@@ -0,0 +1,389 @@
+//! Reference images
+//!
+//! Images loaded from disk as viewport references, shown either as a
+//! textured quad in the scene (`WorldPlane`: selectable and transformable
+//! with the gizmo like a canvas plane, but never painted on) or pinned to a
+//! viewport corner (`CameraPinned`: a Bevy UI image node, drawn over the 3D
+//! scene and under the UI overlay).
+//!
+//! Images larger than `MAX_REFERENCE_TEXTURE_DIMENSION` are down-scaled and
+//! every texture gets a mip chain built on the CPU, so a large photo neither
+//! spikes VRAM nor shimmers when seen small. New images are announced with
+//! `ObjectAdded` so they show up in the outliner; visibility and deletion go
+//! through `ObjectCommand` like any other object.
+
+use std::path::{Path, PathBuf};
+
+use bevy::asset::RenderAssetUsages;
+use bevy::ecs::message::Message;
+use bevy::image::{ImageSampler, ImageSamplerDescriptor};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, RgbaImage};
+use pentimento_ipc::{
+    BevyToUi, ObjectCommand, ReferenceImageMode, SceneObject, ScreenCorner, Transform3D,
+};
+
+use crate::camera::MainCamera;
+use crate::object_id::IdAllocator;
+#[cfg(feature = "selection")]
+use crate::selection::Selectable;
+use crate::{ObjectCommandEvent, OutboundUiMessages};
+
+/// Largest texture width or height kept for a reference image
+pub const MAX_REFERENCE_TEXTURE_DIMENSION: u32 = 4096;
+
+/// Distance in front of the camera where world-plane references appear
+const WORLD_PLANE_DISTANCE: f32 = 3.0;
+
+/// Height of a new world-plane reference in world units
+const WORLD_PLANE_HEIGHT: f32 = 1.5;
+
+/// Gap between a pinned reference and the viewport edges
+const PINNED_MARGIN_PX: f32 = 12.0;
+
+/// Message for reference image operations requested by the UI
+#[derive(Message, Debug, Clone)]
+pub enum ReferenceImageEvent {
+    /// Load an image file as a new reference
+    Add {
+        path: PathBuf,
+        mode: ReferenceImageMode,
+    },
+    /// Set a reference's opacity (0.0-1.0)
+    SetOpacity { id: String, opacity: f32 },
+}
+
+/// Component on reference image entities (quads and pinned UI nodes)
+#[derive(Component, Debug, Clone)]
+pub struct ReferenceImage {
+    /// Object ID reported to the UI
+    pub id: String,
+    /// Current opacity (0.0-1.0)
+    pub opacity: f32,
+}
+
+/// Plugin for reference images
+pub struct ReferenceImagePlugin;
+
+impl Plugin for ReferenceImagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ReferenceImageEvent>().add_systems(
+            Update,
+            (
+                handle_reference_image_events,
+                handle_reference_object_commands,
+            ),
+        );
+    }
+}
+
+/// Spawn reference images and apply opacity changes
+#[allow(clippy::too_many_arguments)]
+fn handle_reference_image_events(
+    mut commands: Commands,
+    mut events: MessageReader<ReferenceImageEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut allocator: ResMut<IdAllocator>,
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    mut references: Query<(Entity, &mut ReferenceImage, Option<&mut ImageNode>)>,
+    reference_materials: Query<&MeshMaterial3d<StandardMaterial>, With<ReferenceImage>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for event in events.read() {
+        match event {
+            ReferenceImageEvent::Add { path, mode } => {
+                let texture = match load_reference_texture(path) {
+                    Ok(texture) => texture,
+                    Err(message) => {
+                        warn!("{}", message);
+                        outbound.send(BevyToUi::Error {
+                            code: "reference_image_failed".to_string(),
+                            message,
+                        });
+                        continue;
+                    }
+                };
+                let aspect_ratio = texture.width() as f32 / texture.height() as f32;
+
+                let id = allocator.allocate();
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| id.clone());
+                let image_handle = images.add(texture);
+
+                let transform = match mode {
+                    ReferenceImageMode::WorldPlane => {
+                        let Ok(camera_transform) = camera_query.single() else {
+                            warn!("No main camera found for reference image");
+                            continue;
+                        };
+                        let camera_pos = camera_transform.translation();
+                        let position =
+                            camera_pos + *camera_transform.forward() * WORLD_PLANE_DISTANCE;
+
+                        // Face the camera (Rectangle's front face is +Z, see canvas planes)
+                        let mut transform =
+                            Transform::from_translation(position).looking_at(camera_pos, Vec3::Y);
+                        transform.rotate_local_y(std::f32::consts::PI);
+
+                        let mesh =
+                            Rectangle::new(WORLD_PLANE_HEIGHT * aspect_ratio, WORLD_PLANE_HEIGHT);
+                        let material = StandardMaterial {
+                            base_color_texture: Some(image_handle),
+                            alpha_mode: AlphaMode::Blend,
+                            unlit: true,
+                            double_sided: true,
+                            cull_mode: None,
+                            ..default()
+                        };
+
+                        #[allow(unused_variables)]
+                        let entity = commands
+                            .spawn((
+                                Mesh3d(meshes.add(mesh)),
+                                MeshMaterial3d(materials.add(material)),
+                                transform,
+                                Name::new(name.clone()),
+                                ReferenceImage {
+                                    id: id.clone(),
+                                    opacity: 1.0,
+                                },
+                            ))
+                            .id();
+
+                        #[cfg(feature = "selection")]
+                        commands
+                            .entity(entity)
+                            .insert(Selectable { id: id.clone() });
+
+                        transform
+                    }
+                    ReferenceImageMode::CameraPinned {
+                        corner,
+                        size,
+                        opacity,
+                    } => {
+                        let opacity = opacity.clamp(0.0, 1.0);
+                        let margin = Val::Px(PINNED_MARGIN_PX);
+                        let mut node = Node {
+                            position_type: PositionType::Absolute,
+                            height: Val::Vh(size.clamp(0.01, 1.0) * 100.0),
+                            aspect_ratio: Some(aspect_ratio),
+                            ..default()
+                        };
+                        match corner {
+                            ScreenCorner::TopLeft | ScreenCorner::TopRight => node.top = margin,
+                            ScreenCorner::BottomLeft | ScreenCorner::BottomRight => {
+                                node.bottom = margin
+                            }
+                        }
+                        match corner {
+                            ScreenCorner::TopLeft | ScreenCorner::BottomLeft => node.left = margin,
+                            ScreenCorner::TopRight | ScreenCorner::BottomRight => {
+                                node.right = margin
+                            }
+                        }
+
+                        // Default ZIndex: above the 3D scene, below the
+                        // full-screen UI overlay (ZIndex(i32::MAX))
+                        #[allow(unused_variables)]
+                        let entity = commands
+                            .spawn((
+                                node,
+                                ImageNode {
+                                    image: image_handle,
+                                    color: Color::WHITE.with_alpha(opacity),
+                                    ..default()
+                                },
+                                Name::new(name.clone()),
+                                ReferenceImage {
+                                    id: id.clone(),
+                                    opacity,
+                                },
+                            ))
+                            .id();
+
+                        // Don't block picking of the meshes behind the image
+                        #[cfg(feature = "selection")]
+                        commands
+                            .entity(entity)
+                            .insert(bevy::picking::Pickable::IGNORE);
+
+                        Transform::IDENTITY
+                    }
+                };
+
+                info!("Added reference image '{}' (id: {})", name, id);
+                outbound.send(BevyToUi::ObjectAdded {
+                    object: SceneObject {
+                        id,
+                        name,
+                        transform: Transform3D {
+                            position: transform.translation.to_array(),
+                            rotation: transform.rotation.to_array(),
+                            scale: transform.scale.to_array(),
+                        },
+                        material_id: None,
+                        visible: true,
+                        locked: false,
+                        parent_id: None,
+                        subdivision_levels: 0,
+                        wireframe: None,
+                        instanced: false,
+                    },
+                    duplicated_from: None,
+                });
+            }
+            ReferenceImageEvent::SetOpacity { id, opacity } => {
+                let opacity = opacity.clamp(0.0, 1.0);
+                for (entity, mut reference, image_node) in references.iter_mut() {
+                    if reference.id != *id {
+                        continue;
+                    }
+                    reference.opacity = opacity;
+                    if let Some(material) = reference_materials
+                        .get(entity)
+                        .ok()
+                        .and_then(|handle| materials.get_mut(&handle.0))
+                    {
+                        material.base_color.set_alpha(opacity);
+                    }
+                    if let Some(mut image_node) = image_node {
+                        image_node.color.set_alpha(opacity);
+                    }
+                    debug!("Set reference image {} opacity to {:.2}", id, opacity);
+                }
+            }
+        }
+    }
+}
+
+/// Apply visibility toggles and deletions to reference images
+pub(crate) fn handle_reference_object_commands(
+    mut commands: Commands,
+    mut events: MessageReader<ObjectCommandEvent>,
+    mut references: Query<(Entity, &ReferenceImage, &mut Visibility)>,
+) {
+    for ObjectCommandEvent(command) in events.read() {
+        match command {
+            ObjectCommand::Delete { ids, .. } => {
+                for (entity, reference, _) in references.iter() {
+                    if ids.contains(&reference.id) {
+                        // Texture, mesh, and material are freed with their last handles
+                        commands.entity(entity).despawn();
+                        info!("Deleted reference image {}", reference.id);
+                    }
+                }
+            }
+            ObjectCommand::SetVisibility { id, visible } => {
+                for (_, reference, mut visibility) in references.iter_mut() {
+                    if reference.id == *id {
+                        *visibility = if *visible {
+                            Visibility::Inherited
+                        } else {
+                            Visibility::Hidden
+                        };
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Decode an image file into a down-scaled, mip-mapped texture
+fn load_reference_texture(path: &Path) -> Result<Image, String> {
+    let image = image::ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .decode()
+        .map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    Ok(reference_texture(image))
+}
+
+/// Build an sRGB texture with a full mip chain, down-scaled so neither side
+/// exceeds `MAX_REFERENCE_TEXTURE_DIMENSION`
+fn reference_texture(image: DynamicImage) -> Image {
+    let image = if image.width().max(image.height()) > MAX_REFERENCE_TEXTURE_DIMENSION {
+        image.resize(
+            MAX_REFERENCE_TEXTURE_DIMENSION,
+            MAX_REFERENCE_TEXTURE_DIMENSION,
+            FilterType::Lanczos3,
+        )
+    } else {
+        image
+    };
+
+    let levels = mip_chain(image.into_rgba8());
+    let (width, height) = levels[0].dimensions();
+    let mut data = Vec::with_capacity(levels.iter().map(|level| level.as_raw().len()).sum());
+    for level in &levels {
+        data.extend_from_slice(level.as_raw());
+    }
+
+    // Render world only: the CPU copy is dropped once uploaded
+    let mut texture = Image::new_uninit(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    texture.data = Some(data);
+    texture.texture_descriptor.mip_level_count = levels.len() as u32;
+    texture.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor::linear());
+    texture
+}
+
+/// Mip levels from `base` down to 1x1, each half the size of the previous
+fn mip_chain(base: RgbaImage) -> Vec<RgbaImage> {
+    let mut levels = vec![base];
+    loop {
+        let (width, height) = levels[levels.len() - 1].dimensions();
+        if width == 1 && height == 1 {
+            return levels;
+        }
+        let next = imageops::resize(
+            &levels[levels.len() - 1],
+            (width / 2).max(1),
+            (height / 2).max(1),
+            FilterType::Triangle,
+        );
+        levels.push(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mip_chain_halves_to_one_pixel() {
+        let levels = mip_chain(RgbaImage::new(8, 3));
+        let sizes: Vec<_> = levels.iter().map(|level| level.dimensions()).collect();
+        assert_eq!(sizes, vec![(8, 3), (4, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn test_reference_texture_is_downscaled() {
+        let wide = DynamicImage::new_rgba8(MAX_REFERENCE_TEXTURE_DIMENSION * 2, 64);
+        let texture = reference_texture(wide);
+
+        assert_eq!(texture.width(), MAX_REFERENCE_TEXTURE_DIMENSION);
+        assert_eq!(texture.height(), 32);
+        assert_eq!(texture.texture_descriptor.mip_level_count, 13);
+        let expected_bytes: usize = mip_chain(RgbaImage::new(MAX_REFERENCE_TEXTURE_DIMENSION, 32))
+            .iter()
+            .map(|level| level.as_raw().len())
+            .sum();
+        assert_eq!(texture.data.as_ref().map(Vec::len), Some(expected_bytes));
+    }
+}
@@ -107,16 +107,12 @@ impl Plugin for RenderCameraPlugin {
 #[cfg(feature = "selection")]
 fn handle_render_camera_selection(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<pentimento_config::Keymap>,
     selected_cameras: Query<Entity, (With<crate::selection::Selected>, With<RenderCamera>)>,
     mut active_render_camera: ResMut<ActiveRenderCamera>,
 ) {
-    // Tab to activate selected render camera (without modifiers)
-    let tab = key_input.just_pressed(KeyCode::Tab);
-    let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
-    let ctrl = key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
-
-    // Only plain Tab, not Shift+Tab or Ctrl+Tab
-    if !tab || shift || ctrl {
+    // Tab to activate selected render camera (only plain Tab, not Shift+Tab or Ctrl+Tab)
+    if !keymap.just_pressed(pentimento_config::actions::MODE_TOGGLE, &key_input) {
         return;
     }
 
@@ -0,0 +1,1203 @@
+//! Ray casting acceleration shared by picking, projection painting, the mesh
+//! paint brush, and the normal indicator
+//!
+//! [`SceneBvh`] is a two-level bounding volume hierarchy. Each mesh asset gets
+//! a BVH over its triangles in local space, shared by every entity drawing
+//! it, and a top level BVH holds the entities' world-space bounds. A ray
+//! walks the top level, moves into each candidate's local space, and walks
+//! that mesh's BVH, nearest nodes first.
+//!
+//! Keeping it current is cheap: moving an entity refits the top level, and
+//! editing a mesh's vertices refits its BVH. A mesh whose triangles changed
+//! is rebuilt. Refits loosen the bounds, so a BVH is rebuilt after
+//! [`REBUILD_AFTER_REFITS`] of them, except while the gizmo drags: the drag
+//! only refits, and what it touched is rebuilt once it ends.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use bevy::camera::visibility::VisibilitySystems;
+use bevy::math::{Affine3A, Vec3, Vec3A};
+use bevy::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+use painting::raycast::ray_triangle_intersection;
+
+use crate::gizmo::GizmoState;
+
+/// Most triangles (or entities) a leaf holds before it is split
+const LEAF_SIZE: usize = 4;
+
+/// Centroid bins evaluated per split
+const SAH_BINS: usize = 12;
+
+/// Refits a BVH takes before it is rebuilt outside gizmo drags
+pub const REBUILD_AFTER_REFITS: u32 = 64;
+
+/// Nearest hit of a ray against the scene
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SceneHit {
+    /// Entity whose mesh was hit
+    pub entity: Entity,
+    /// Triangle index in the mesh's index buffer (index / 3)
+    pub face_index: u32,
+    /// Barycentric weights of the triangle's three vertices at the hit
+    pub barycentric: Vec3,
+    /// World-space distance along the ray
+    pub distance: f32,
+    /// World-space hit position
+    pub position: Vec3,
+    /// World-space geometric normal of the triangle, by its winding
+    pub normal: Vec3,
+    /// Whether the ray hit the triangle from behind
+    pub backface: bool,
+}
+
+/// How a ray is cast against the scene
+#[derive(Debug, Clone, Copy)]
+pub struct RaycastOptions {
+    /// Hits beyond this world-space distance are ignored
+    pub max_distance: f32,
+    /// Ignore triangles facing away from the ray, like mesh picking does
+    pub cull_backfaces: bool,
+}
+
+impl Default for RaycastOptions {
+    fn default() -> Self {
+        Self {
+            max_distance: f32::INFINITY,
+            cull_backfaces: false,
+        }
+    }
+}
+
+/// Axis-aligned bounds
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Bounds {
+    const EMPTY: Self = Self {
+        min: Vec3::INFINITY,
+        max: Vec3::NEG_INFINITY,
+    };
+
+    fn from_points(points: &[Vec3]) -> Self {
+        points.iter().fold(Self::EMPTY, |bounds, &point| Self {
+            min: bounds.min.min(point),
+            max: bounds.max.max(point),
+        })
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    fn is_empty(&self) -> bool {
+        self.min.cmpgt(self.max).any()
+    }
+
+    /// Half the surface area, which is all SAH comparisons need
+    fn half_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let size = self.max - self.min;
+        size.x * size.y + size.y * size.z + size.z * size.x
+    }
+
+    /// Bounds of these bounds after a transform
+    fn transformed(&self, affine: &Affine3A) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        let center = affine.transform_point3(self.center());
+        let half = (self.max - self.min) * 0.5;
+        let matrix = affine.matrix3;
+        let extent = matrix.x_axis.abs() * half.x
+            + matrix.y_axis.abs() * half.y
+            + matrix.z_axis.abs() * half.z;
+        let extent = Vec3::from(extent);
+        Self {
+            min: center - extent,
+            max: center + extent,
+        }
+    }
+
+    /// Distance at which the ray enters the bounds, if it does before `max_t`
+    ///
+    /// Axes the ray runs parallel to are tested by position, so flat bounds
+    /// (a plane's, say) and rays lying on a slab don't produce NaNs.
+    fn ray_entry(&self, ray: &LocalRay, max_t: f32) -> Option<f32> {
+        let mut t_near = 0.0f32;
+        // Widen the far side by a few ulps so rounding can't drop rays
+        // that graze a face of the bounds
+        let mut t_far = max_t * (1.0 + 4.0 * f32::EPSILON);
+        for axis in 0..3 {
+            let origin = ray.origin[axis];
+            if ray.direction[axis] == 0.0 {
+                if origin < self.min[axis] || origin > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (self.min[axis] - origin) * ray.inverse_direction[axis];
+            let t2 = (self.max[axis] - origin) * ray.inverse_direction[axis];
+            t_near = t_near.max(t1.min(t2));
+            t_far = t_far.min(t1.max(t2));
+            if t_near > t_far {
+                return None;
+            }
+        }
+        Some(t_near)
+    }
+}
+
+/// A ray in the space of the BVH being walked. The direction is the world
+/// direction carried into that space unnormalized, so distances along it
+/// stay world distances.
+struct LocalRay {
+    origin: Vec3,
+    direction: Vec3,
+    inverse_direction: Vec3,
+}
+
+impl LocalRay {
+    fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction,
+            inverse_direction: direction.recip(),
+        }
+    }
+}
+
+/// BVH node: interior when `count` is 0, with children at `start` and
+/// `start + 1`; otherwise a leaf over `order[start..start + count]`
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    bounds: Bounds,
+    start: u32,
+    count: u32,
+}
+
+impl Node {
+    fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+/// Build a BVH over primitives with the given bounds, returning its nodes
+/// and the primitive order its leaves index into
+///
+/// Splits by binned SAH along the axis with the widest centroid spread,
+/// falling back to a median split when every centroid lands in one bin.
+/// Children are always stored after their parent.
+fn build_nodes(bounds: &[Bounds]) -> (Vec<Node>, Vec<u32>) {
+    let mut order: Vec<u32> = (0..bounds.len() as u32).collect();
+    if bounds.is_empty() {
+        return (Vec::new(), order);
+    }
+    let centers: Vec<Vec3> = bounds.iter().map(Bounds::center).collect();
+
+    let mut nodes = vec![Node {
+        bounds: Bounds::EMPTY,
+        start: 0,
+        count: bounds.len() as u32,
+    }];
+    let mut pending = vec![0usize];
+    while let Some(index) = pending.pop() {
+        let Node { start, count, .. } = nodes[index];
+        let range = start as usize..(start + count) as usize;
+        let node_bounds = order[range.clone()]
+            .iter()
+            .fold(Bounds::EMPTY, |acc, &prim| acc.union(bounds[prim as usize]));
+        nodes[index].bounds = node_bounds;
+        if range.len() <= LEAF_SIZE {
+            continue;
+        }
+
+        let Some(split) = sah_split(&mut order[range.clone()], bounds, &centers, node_bounds)
+        else {
+            continue;
+        };
+
+        let left = nodes.len();
+        nodes.push(Node {
+            bounds: Bounds::EMPTY,
+            start,
+            count: split as u32,
+        });
+        nodes.push(Node {
+            bounds: Bounds::EMPTY,
+            start: start + split as u32,
+            count: count - split as u32,
+        });
+        nodes[index].start = left as u32;
+        nodes[index].count = 0;
+        pending.push(left);
+        pending.push(left + 1);
+    }
+
+    (nodes, order)
+}
+
+/// Partition `prims` for a split, returning the size of the left half, or
+/// `None` when keeping them in one leaf is cheaper
+fn sah_split(
+    prims: &mut [u32],
+    bounds: &[Bounds],
+    centers: &[Vec3],
+    node_bounds: Bounds,
+) -> Option<usize> {
+    let centroid_bounds = Bounds::from_points(
+        &prims
+            .iter()
+            .map(|&prim| centers[prim as usize])
+            .collect::<Vec<_>>(),
+    );
+    let spread = centroid_bounds.max - centroid_bounds.min;
+    let axis = if spread.x >= spread.y && spread.x >= spread.z {
+        0
+    } else if spread.y >= spread.z {
+        1
+    } else {
+        2
+    };
+    if spread[axis] <= 0.0 {
+        // Every centroid in one spot: split down the middle of the list
+        return (prims.len() > LEAF_SIZE * 4).then_some(prims.len() / 2);
+    }
+
+    let scale = SAH_BINS as f32 / spread[axis];
+    let bin_of = |prim: u32| {
+        (((centers[prim as usize][axis] - centroid_bounds.min[axis]) * scale) as usize)
+            .min(SAH_BINS - 1)
+    };
+    let mut bin_bounds = [Bounds::EMPTY; SAH_BINS];
+    let mut bin_counts = [0usize; SAH_BINS];
+    for &prim in prims.iter() {
+        let bin = bin_of(prim);
+        bin_bounds[bin] = bin_bounds[bin].union(bounds[prim as usize]);
+        bin_counts[bin] += 1;
+    }
+
+    // Cost of splitting after each bin, sweeping from both ends
+    let mut right_costs = [0.0f32; SAH_BINS];
+    let mut acc = Bounds::EMPTY;
+    let mut acc_count = 0;
+    for bin in (1..SAH_BINS).rev() {
+        acc = acc.union(bin_bounds[bin]);
+        acc_count += bin_counts[bin];
+        right_costs[bin - 1] = acc.half_area() * acc_count as f32;
+    }
+    let mut best: Option<(usize, f32)> = None;
+    let mut acc = Bounds::EMPTY;
+    let mut acc_count = 0;
+    for bin in 0..SAH_BINS - 1 {
+        acc = acc.union(bin_bounds[bin]);
+        acc_count += bin_counts[bin];
+        let cost = acc.half_area() * acc_count as f32 + right_costs[bin];
+        if best.is_none_or(|(_, best_cost)| cost < best_cost) {
+            best = Some((bin, cost));
+        }
+    }
+    let (split_bin, cost) = best?;
+    let leaf_cost = node_bounds.half_area() * prims.len() as f32;
+    if cost >= leaf_cost && prims.len() <= LEAF_SIZE * 4 {
+        return None;
+    }
+
+    // Partition in place: everything up to and including `split_bin` goes left
+    let mut left = 0;
+    for i in 0..prims.len() {
+        if bin_of(prims[i]) <= split_bin {
+            prims.swap(i, left);
+            left += 1;
+        }
+    }
+    if left == 0 || left == prims.len() {
+        return Some(prims.len() / 2);
+    }
+    Some(left)
+}
+
+/// Recompute node bounds bottom-up from the primitives' current bounds
+fn refit_nodes(nodes: &mut [Node], order: &[u32], bounds: impl Fn(u32) -> Bounds) {
+    // Children come after their parent, so walking backwards sees them first
+    for index in (0..nodes.len()).rev() {
+        let node = nodes[index];
+        nodes[index].bounds = if node.is_leaf() {
+            order[node.start as usize..(node.start + node.count) as usize]
+                .iter()
+                .fold(Bounds::EMPTY, |acc, &prim| acc.union(bounds(prim)))
+        } else {
+            let left = node.start as usize;
+            nodes[left].bounds.union(nodes[left + 1].bounds)
+        };
+    }
+}
+
+/// Walk a BVH nearest node first, calling `visit` for each primitive of the
+/// leaves the ray reaches. `visit` returns a closer hit distance to narrow
+/// the search with.
+fn traverse(
+    nodes: &[Node],
+    order: &[u32],
+    ray: &LocalRay,
+    mut max_t: f32,
+    mut visit: impl FnMut(u32, f32) -> Option<f32>,
+) {
+    let Some(root_entry) = nodes
+        .first()
+        .and_then(|root| root.bounds.ray_entry(ray, max_t))
+    else {
+        return;
+    };
+    let mut stack: Vec<(u32, f32)> = Vec::with_capacity(64);
+    stack.push((0, root_entry));
+    while let Some((index, entry)) = stack.pop() {
+        if entry > max_t {
+            continue;
+        }
+        let node = nodes[index as usize];
+        if node.is_leaf() {
+            for &prim in &order[node.start as usize..(node.start + node.count) as usize] {
+                if let Some(t) = visit(prim, max_t) {
+                    max_t = max_t.min(t);
+                }
+            }
+            continue;
+        }
+
+        let (left, right) = (node.start, node.start + 1);
+        let left_entry = nodes[left as usize].bounds.ray_entry(ray, max_t);
+        let right_entry = nodes[right as usize].bounds.ray_entry(ray, max_t);
+        match (left_entry, right_entry) {
+            (Some(l), Some(r)) if l <= r => {
+                stack.push((right, r));
+                stack.push((left, l));
+            }
+            (Some(l), Some(r)) => {
+                stack.push((left, l));
+                stack.push((right, r));
+            }
+            (Some(l), None) => stack.push((left, l)),
+            (None, Some(r)) => stack.push((right, r)),
+            (None, None) => {}
+        }
+    }
+}
+
+/// Triangle hit in a mesh's local space
+struct MeshBvhHit {
+    t: f32,
+    u: f32,
+    v: f32,
+    face: u32,
+    /// Unnormalized geometric normal in local space
+    normal: Vec3,
+    backface: bool,
+}
+
+/// Triangle BVH of one mesh in its local space
+struct MeshBvh {
+    positions: Vec<Vec3>,
+    /// Triangle vertex indices, in the mesh's order
+    triangles: Vec<[u32; 3]>,
+    nodes: Vec<Node>,
+    order: Vec<u32>,
+    /// Refits since the last build
+    refits: u32,
+}
+
+impl MeshBvh {
+    fn build(positions: Vec<Vec3>, triangles: Vec<[u32; 3]>) -> Self {
+        let mut bvh = Self {
+            positions,
+            triangles,
+            nodes: Vec::new(),
+            order: Vec::new(),
+            refits: 0,
+        };
+        bvh.rebuild();
+        bvh
+    }
+
+    fn rebuild(&mut self) {
+        let bounds: Vec<Bounds> = (0..self.triangles.len() as u32)
+            .map(|face| self.triangle_bounds(face))
+            .collect();
+        (self.nodes, self.order) = build_nodes(&bounds);
+        self.refits = 0;
+    }
+
+    /// Take new vertex positions for the same triangles
+    fn refit(&mut self, positions: Vec<Vec3>) {
+        self.positions = positions;
+        let mut nodes = std::mem::take(&mut self.nodes);
+        refit_nodes(&mut nodes, &self.order, |face| self.triangle_bounds(face));
+        self.nodes = nodes;
+        self.refits += 1;
+    }
+
+    fn bounds(&self) -> Bounds {
+        self.nodes.first().map_or(Bounds::EMPTY, |root| root.bounds)
+    }
+
+    fn triangle_positions(&self, face: u32) -> [Vec3; 3] {
+        self.triangles[face as usize].map(|i| self.positions[i as usize])
+    }
+
+    fn triangle_bounds(&self, face: u32) -> Bounds {
+        Bounds::from_points(&self.triangle_positions(face))
+    }
+
+    fn cast(&self, ray: &LocalRay, max_t: f32, cull_backfaces: bool) -> Option<MeshBvhHit> {
+        let mut best: Option<MeshBvhHit> = None;
+        traverse(&self.nodes, &self.order, ray, max_t, |face, max_t| {
+            let [v0, v1, v2] = self.triangle_positions(face);
+            let hit = ray_triangle_intersection(ray.origin, ray.direction, v0, v1, v2)?;
+            if hit.t >= max_t {
+                return None;
+            }
+            let normal = (v1 - v0).cross(v2 - v0);
+            let backface = normal.dot(ray.direction) > 0.0;
+            if backface && cull_backfaces {
+                return None;
+            }
+            best = Some(MeshBvhHit {
+                t: hit.t,
+                u: hit.u,
+                v: hit.v,
+                face,
+                normal,
+                backface,
+            });
+            Some(hit.t)
+        });
+        best
+    }
+}
+
+/// Vertex positions and triangles of a triangle-list mesh
+fn mesh_triangles(mesh: &Mesh) -> Option<(Vec<Vec3>, Vec<[u32; 3]>)> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+    let VertexAttributeValues::Float32x3(positions) = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?
+    else {
+        return None;
+    };
+    let positions: Vec<Vec3> = positions.iter().map(|&p| Vec3::from(p)).collect();
+    let indices: Vec<u32> = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.iter().map(|&i| i as u32).collect(),
+        Some(Indices::U32(indices)) => indices.clone(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    let triangles = indices
+        .chunks_exact(3)
+        .map(|tri| [tri[0], tri[1], tri[2]])
+        .filter(|tri| tri.iter().all(|&i| (i as usize) < positions.len()))
+        .collect();
+    Some((positions, triangles))
+}
+
+/// An entity in the top level
+struct SceneEntry {
+    entity: Entity,
+    mesh: AssetId<Mesh>,
+    world_from_local: Affine3A,
+    local_from_world: Affine3A,
+    /// World-space bounds of the mesh
+    bounds: Bounds,
+    visible: bool,
+}
+
+/// How out of date the top level is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TopLevelState {
+    Current,
+    /// Entities moved; their bounds need refitting
+    Refit,
+    /// Entities were added or removed
+    Rebuild,
+}
+
+/// Two-level BVH over scene meshes, for ray casts (see the module docs)
+#[derive(Resource)]
+pub struct SceneBvh {
+    meshes: HashMap<AssetId<Mesh>, MeshBvh>,
+    entries: Vec<SceneEntry>,
+    /// Index into `entries` by entity
+    entry_index: HashMap<Entity, usize>,
+    top_nodes: Vec<Node>,
+    top_order: Vec<u32>,
+    top_state: TopLevelState,
+    /// Refits of the top level since it was last built
+    top_refits: u32,
+    /// Only refit, never rebuild for tighter bounds (set during gizmo drags)
+    refit_only: bool,
+    /// Entities whose mesh asset wasn't loaded when they were inserted
+    waiting: HashMap<Entity, AssetId<Mesh>>,
+}
+
+impl Default for SceneBvh {
+    fn default() -> Self {
+        Self {
+            meshes: HashMap::new(),
+            entries: Vec::new(),
+            entry_index: HashMap::new(),
+            top_nodes: Vec::new(),
+            top_order: Vec::new(),
+            top_state: TopLevelState::Current,
+            top_refits: 0,
+            refit_only: false,
+            waiting: HashMap::new(),
+        }
+    }
+}
+
+impl SceneBvh {
+    /// Add an entity drawing `mesh`, or update it if it's already present
+    ///
+    /// The mesh BVH is built the first time its asset is seen. Returns false
+    /// (and leaves the entity out) when the mesh isn't a triangle list.
+    pub fn insert(
+        &mut self,
+        entity: Entity,
+        mesh_id: AssetId<Mesh>,
+        mesh: &Mesh,
+        transform: &GlobalTransform,
+        visible: bool,
+    ) -> bool {
+        if let Some(&index) = self.entry_index.get(&entity) {
+            if self.entries[index].mesh == mesh_id {
+                self.set_transform(entity, transform);
+                self.set_visible(entity, visible);
+                return true;
+            }
+            self.remove(entity);
+        }
+
+        if let Entry::Vacant(slot) = self.meshes.entry(mesh_id) {
+            let Some((positions, triangles)) = mesh_triangles(mesh) else {
+                return false;
+            };
+            slot.insert(MeshBvh::build(positions, triangles));
+        }
+
+        let world_from_local = transform.affine();
+        self.entry_index.insert(entity, self.entries.len());
+        self.entries.push(SceneEntry {
+            entity,
+            mesh: mesh_id,
+            world_from_local,
+            local_from_world: world_from_local.inverse(),
+            bounds: self.meshes[&mesh_id]
+                .bounds()
+                .transformed(&world_from_local),
+            visible,
+        });
+        self.top_state = TopLevelState::Rebuild;
+        true
+    }
+
+    /// Drop an entity, and its mesh's BVH if nothing else draws that mesh
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        self.waiting.remove(&entity);
+        let Some(index) = self.entry_index.remove(&entity) else {
+            return false;
+        };
+        let entry = self.entries.swap_remove(index);
+        if let Some(moved) = self.entries.get(index) {
+            self.entry_index.insert(moved.entity, index);
+        }
+        if !self.entries.iter().any(|other| other.mesh == entry.mesh) {
+            self.meshes.remove(&entry.mesh);
+        }
+        self.top_state = TopLevelState::Rebuild;
+        true
+    }
+
+    /// Move an entity; the top level is refit on the next update
+    pub fn set_transform(&mut self, entity: Entity, transform: &GlobalTransform) {
+        let Some(&index) = self.entry_index.get(&entity) else {
+            return;
+        };
+        let entry = &mut self.entries[index];
+        entry.world_from_local = transform.affine();
+        entry.local_from_world = entry.world_from_local.inverse();
+        entry.bounds = self.meshes[&entry.mesh]
+            .bounds()
+            .transformed(&entry.world_from_local);
+        self.mark_moved();
+    }
+
+    /// Show or hide an entity; hidden entities are never hit
+    pub fn set_visible(&mut self, entity: Entity, visible: bool) {
+        if let Some(&index) = self.entry_index.get(&entity) {
+            self.entries[index].visible = visible;
+        }
+    }
+
+    /// Take a modified mesh asset: refit when its triangles are the same,
+    /// rebuild when they changed
+    pub fn update_mesh(&mut self, mesh_id: AssetId<Mesh>, mesh: &Mesh) {
+        let Some(bvh) = self.meshes.get_mut(&mesh_id) else {
+            return;
+        };
+        let Some((positions, triangles)) = mesh_triangles(mesh) else {
+            return;
+        };
+        if triangles == bvh.triangles && positions.len() == bvh.positions.len() {
+            bvh.refit(positions);
+        } else {
+            *bvh = MeshBvh::build(positions, triangles);
+        }
+
+        let bounds = bvh.bounds();
+        for entry in self
+            .entries
+            .iter_mut()
+            .filter(|entry| entry.mesh == mesh_id)
+        {
+            entry.bounds = bounds.transformed(&entry.world_from_local);
+        }
+        self.mark_moved();
+    }
+
+    /// Only refit from now on, or allow rebuilds again (gizmo drags set this)
+    pub fn set_refit_only(&mut self, refit_only: bool) {
+        self.refit_only = refit_only;
+    }
+
+    /// Bring the top level up to date, and rebuild BVHs loosened by refits
+    /// unless only refits are allowed
+    pub fn update(&mut self) {
+        if !self.refit_only {
+            let mut rebuilt = false;
+            for bvh in self.meshes.values_mut() {
+                if bvh.refits >= REBUILD_AFTER_REFITS {
+                    bvh.rebuild();
+                    rebuilt = true;
+                }
+            }
+            if rebuilt {
+                for entry in &mut self.entries {
+                    entry.bounds = self.meshes[&entry.mesh]
+                        .bounds()
+                        .transformed(&entry.world_from_local);
+                }
+                self.mark_moved();
+            }
+            if self.top_refits >= REBUILD_AFTER_REFITS {
+                self.top_state = TopLevelState::Rebuild;
+            }
+        }
+
+        match self.top_state {
+            TopLevelState::Current => {}
+            TopLevelState::Refit => {
+                let entries = &self.entries;
+                refit_nodes(&mut self.top_nodes, &self.top_order, |index| {
+                    entries[index as usize].bounds
+                });
+                self.top_refits += 1;
+            }
+            TopLevelState::Rebuild => {
+                let bounds: Vec<Bounds> = self.entries.iter().map(|entry| entry.bounds).collect();
+                (self.top_nodes, self.top_order) = build_nodes(&bounds);
+                self.top_refits = 0;
+            }
+        }
+        self.top_state = TopLevelState::Current;
+    }
+
+    /// Whether an entity is in the BVH
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entry_index.contains_key(&entity)
+    }
+
+    /// Number of entities in the BVH
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the BVH holds no entities
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Nearest hit of `ray` on a visible entity `filter` accepts, front or
+    /// back facing
+    pub fn raycast(&self, ray: Ray3d, filter: impl Fn(Entity) -> bool) -> Option<SceneHit> {
+        self.raycast_with(ray, RaycastOptions::default(), filter)
+    }
+
+    /// [`raycast`](Self::raycast) with a distance limit and backface culling
+    pub fn raycast_with(
+        &self,
+        ray: Ray3d,
+        options: RaycastOptions,
+        filter: impl Fn(Entity) -> bool,
+    ) -> Option<SceneHit> {
+        let world_ray = LocalRay::new(ray.origin, *ray.direction);
+        let mut best: Option<(usize, MeshBvhHit)> = None;
+        let mut cast_entry = |index: u32, max_t: f32| {
+            let entry = &self.entries[index as usize];
+            if !entry.visible || !filter(entry.entity) {
+                return None;
+            }
+            let local_ray = LocalRay::new(
+                entry.local_from_world.transform_point3(ray.origin),
+                entry.local_from_world.transform_vector3(*ray.direction),
+            );
+            let hit = self.meshes[&entry.mesh].cast(&local_ray, max_t, options.cull_backfaces)?;
+            let t = hit.t;
+            best = Some((index as usize, hit));
+            Some(t)
+        };
+
+        if self.top_state == TopLevelState::Current {
+            traverse(
+                &self.top_nodes,
+                &self.top_order,
+                &world_ray,
+                options.max_distance,
+                cast_entry,
+            );
+        } else {
+            // Not updated since entities changed: test each one's bounds
+            let mut max_t = options.max_distance;
+            for (index, entry) in self.entries.iter().enumerate() {
+                if entry.bounds.ray_entry(&world_ray, max_t).is_some()
+                    && let Some(t) = cast_entry(index as u32, max_t)
+                {
+                    max_t = t;
+                }
+            }
+        }
+
+        let (index, hit) = best?;
+        let entry = &self.entries[index];
+        // Normals transform by the inverse transpose
+        let normal = entry.local_from_world.matrix3.transpose() * Vec3A::from(hit.normal);
+        Some(SceneHit {
+            entity: entry.entity,
+            face_index: hit.face,
+            barycentric: Vec3::new(1.0 - hit.u - hit.v, hit.u, hit.v),
+            distance: hit.t,
+            position: ray.origin + *ray.direction * hit.t,
+            normal: Vec3::from(normal).normalize_or_zero(),
+            backface: hit.backface,
+        })
+    }
+
+    /// World-space vertices of the triangle a hit landed on, in the mesh's
+    /// winding order (`None` once the entity or its mesh changed)
+    pub fn hit_triangle(&self, hit: &SceneHit) -> Option<[Vec3; 3]> {
+        let entry = &self.entries[*self.entry_index.get(&hit.entity)?];
+        let bvh = self.meshes.get(&entry.mesh)?;
+        if hit.face_index as usize >= bvh.triangles.len() {
+            return None;
+        }
+        Some(
+            bvh.triangle_positions(hit.face_index)
+                .map(|position| entry.world_from_local.transform_point3(position)),
+        )
+    }
+
+    fn mark_moved(&mut self) {
+        if self.top_state == TopLevelState::Current {
+            self.top_state = TopLevelState::Refit;
+        }
+    }
+}
+
+/// Plugin keeping [`SceneBvh`] in sync with the scene's meshes
+pub struct SceneBvhPlugin;
+
+impl Plugin for SceneBvhPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneBvh>().add_systems(
+            PostUpdate,
+            sync_scene_bvh
+                .after(TransformSystems::Propagate)
+                .after(VisibilitySystems::VisibilityPropagate),
+        );
+    }
+}
+
+/// Mesh entities whose mesh, transform, or visibility changed
+type ChangedMeshQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Mesh3d,
+        &'static GlobalTransform,
+        &'static InheritedVisibility,
+    ),
+    Or<(
+        Changed<Mesh3d>,
+        Changed<GlobalTransform>,
+        Changed<InheritedVisibility>,
+    )>,
+>;
+
+/// Apply this frame's mesh, transform, and visibility changes to the BVH
+fn sync_scene_bvh(
+    mut bvh: ResMut<SceneBvh>,
+    meshes: Res<Assets<Mesh>>,
+    mut mesh_events: MessageReader<AssetEvent<Mesh>>,
+    changed: ChangedMeshQuery,
+    all: Query<(&Mesh3d, &GlobalTransform, &InheritedVisibility)>,
+    mut removed: RemovedComponents<Mesh3d>,
+    gizmo: Option<Res<GizmoState>>,
+) {
+    let bvh = &mut *bvh;
+    bvh.set_refit_only(gizmo.is_some_and(|gizmo| gizmo.is_active));
+
+    for entity in removed.read() {
+        bvh.remove(entity);
+    }
+
+    for event in mesh_events.read() {
+        if let AssetEvent::Modified { id } = event
+            && let Some(mesh) = meshes.get(*id)
+        {
+            bvh.update_mesh(*id, mesh);
+        }
+    }
+
+    let waiting: Vec<Entity> = bvh.waiting.keys().copied().collect();
+    for entity in waiting {
+        match all.get(entity) {
+            Ok((mesh_handle, transform, visibility)) => {
+                if let Some(mesh) = meshes.get(&mesh_handle.0) {
+                    bvh.waiting.remove(&entity);
+                    bvh.insert(
+                        entity,
+                        mesh_handle.0.id(),
+                        mesh,
+                        transform,
+                        visibility.get(),
+                    );
+                }
+            }
+            Err(_) => {
+                bvh.waiting.remove(&entity);
+            }
+        }
+    }
+
+    for (entity, mesh_handle, transform, visibility) in changed.iter() {
+        match meshes.get(&mesh_handle.0) {
+            Some(mesh) => {
+                bvh.insert(
+                    entity,
+                    mesh_handle.0.id(),
+                    mesh,
+                    transform,
+                    visibility.get(),
+                );
+            }
+            None => {
+                bvh.remove(entity);
+                bvh.waiting.insert(entity, mesh_handle.0.id());
+            }
+        }
+    }
+
+    bvh.update();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seeded xorshift, so failures reproduce
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 40) as f32 / (1u64 << 24) as f32
+        }
+
+        fn range(&mut self, min: f32, max: f32) -> f32 {
+            min + (max - min) * self.next()
+        }
+    }
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw_u32(index).unwrap()
+    }
+
+    fn ray(origin: Vec3, direction: Vec3) -> Ray3d {
+        Ray3d::new(origin, Dir3::new(direction).unwrap())
+    }
+
+    /// A BVH holding `meshes` at the given translations
+    fn scene(meshes: &[(Mesh, Vec3)]) -> SceneBvh {
+        let mut bvh = SceneBvh::default();
+        for (index, (mesh, translation)) in meshes.iter().enumerate() {
+            let transform = GlobalTransform::from_translation(*translation);
+            let id = AssetId::Uuid {
+                uuid: bevy::asset::uuid::Uuid::from_u128(index as u128 + 1),
+            };
+            assert!(bvh.insert(entity(index as u32), id, mesh, &transform, true));
+        }
+        bvh.update();
+        bvh
+    }
+
+    /// Nearest hit distance by testing every triangle
+    fn brute_force(meshes: &[(Mesh, Vec3)], ray: Ray3d) -> Option<f32> {
+        meshes
+            .iter()
+            .filter_map(|(mesh, translation)| {
+                let (positions, triangles) = mesh_triangles(mesh)?;
+                triangles
+                    .iter()
+                    .filter_map(|tri| {
+                        let [v0, v1, v2] = tri.map(|i| positions[i as usize] + *translation);
+                        ray_triangle_intersection(ray.origin, *ray.direction, v0, v1, v2)
+                    })
+                    .map(|hit| hit.t)
+                    .min_by(f32::total_cmp)
+            })
+            .min_by(f32::total_cmp)
+    }
+
+    fn default_scene() -> Vec<(Mesh, Vec3)> {
+        vec![
+            (
+                Plane3d::default()
+                    .mesh()
+                    .size(10.0, 10.0)
+                    .subdivisions(8)
+                    .build(),
+                Vec3::ZERO,
+            ),
+            (
+                Mesh::from(Cuboid::new(1.0, 1.0, 1.0)),
+                Vec3::new(0.0, 0.5, 0.0),
+            ),
+            (Sphere::new(0.5).mesh().uv(32, 18), Vec3::new(2.0, 0.5, 0.0)),
+            (Mesh::from(Torus::new(0.3, 0.5)), Vec3::new(-2.0, 0.5, 0.0)),
+        ]
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let meshes = default_scene();
+        let bvh = scene(&meshes);
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..2000 {
+            let origin = Vec3::new(rng.range(-6.0, 6.0), rng.range(0.0, 4.0), 6.0);
+            let target = Vec3::new(rng.range(-3.0, 3.0), rng.range(-0.5, 1.5), 0.0);
+            let ray = ray(origin, target - origin);
+            let expected = brute_force(&meshes, ray);
+            let hit = bvh.raycast(ray, |_| true).map(|hit| hit.distance);
+            match (expected, hit) {
+                (Some(expected), Some(hit)) => assert!((expected - hit).abs() < 1e-4),
+                (expected, hit) => assert_eq!(expected, hit, "ray {ray:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_grazing_rays() {
+        let plane = Plane3d::default().mesh().size(10.0, 10.0).build();
+        let bvh = scene(&[(plane, Vec3::ZERO)]);
+
+        // Lying in the plane, whose bounds are flat: no hit, no NaN trouble
+        assert!(
+            bvh.raycast(ray(Vec3::new(-8.0, 0.0, 0.0), Vec3::X), |_| true)
+                .is_none()
+        );
+        assert!(
+            bvh.raycast(ray(Vec3::new(-8.0, 0.0, 5.0), Vec3::X), |_| true)
+                .is_none()
+        );
+
+        // Skimming in at a shallow angle lands where the plane is crossed
+        let direction = Vec3::new(1.0, -0.001, 0.0);
+        let hit = bvh
+            .raycast(ray(Vec3::new(-4.0, 0.001, 0.0), direction), |_| true)
+            .unwrap();
+        assert!((hit.position.x - -3.0).abs() < 1e-3, "{hit:?}");
+
+        // Straight down through the diagonal the two triangles share,
+        // whichever way it runs
+        for z in [1.0, -1.0] {
+            let hit = bvh.raycast(ray(Vec3::new(1.0, 1.0, z), -Vec3::Y), |_| true);
+            assert!(hit.is_some_and(|hit| (hit.distance - 1.0).abs() < 1e-5));
+        }
+
+        // Straight down onto the plane's edge and corner
+        assert!(
+            bvh.raycast(ray(Vec3::new(5.0, 1.0, 0.0), -Vec3::Y), |_| true)
+                .is_some()
+        );
+        assert!(
+            bvh.raycast(ray(Vec3::new(5.0, 1.0, 5.0), -Vec3::Y), |_| true)
+                .is_some()
+        );
+        assert!(
+            bvh.raycast(ray(Vec3::new(5.01, 1.0, 0.0), -Vec3::Y), |_| true)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_backface_handling() {
+        let plane = Plane3d::default().mesh().size(2.0, 2.0).build();
+        let cube = Mesh::from(Cuboid::new(1.0, 1.0, 1.0));
+        let bvh = scene(&[(plane, Vec3::ZERO), (cube, Vec3::new(0.0, 2.0, 0.0))]);
+        let culled = RaycastOptions {
+            cull_backfaces: true,
+            ..default()
+        };
+
+        let from_above = bvh.raycast(ray(Vec3::Y, -Vec3::Y), |_| true).unwrap();
+        assert!(!from_above.backface && from_above.entity == entity(0));
+        assert!(from_above.normal.abs_diff_eq(Vec3::Y, 1e-5));
+
+        // From below, the plane's back is hit first
+        let from_below = ray(-Vec3::Y, Vec3::Y);
+        let hit = bvh.raycast(from_below, |_| true).unwrap();
+        assert!(hit.backface && hit.entity == entity(0));
+        assert!((hit.distance - 1.0).abs() < 1e-5);
+
+        // Culling it doesn't hide the cube's bottom face behind it
+        let hit = bvh.raycast_with(from_below, culled, |_| true).unwrap();
+        assert!(!hit.backface && hit.entity == entity(1));
+        assert!((hit.distance - 2.5).abs() < 1e-5);
+
+        // From inside the cube only backfaces are in the way
+        let inside = ray(Vec3::new(0.0, 2.0, 0.0), Vec3::Y);
+        assert!(bvh.raycast(inside, |_| true).unwrap().backface);
+        assert!(bvh.raycast_with(inside, culled, |_| true).is_none());
+    }
+
+    #[test]
+    fn test_hit_reports_face_and_barycentric() {
+        let mesh = Sphere::new(1.0).mesh().uv(32, 18);
+        let (positions, triangles) = mesh_triangles(&mesh).unwrap();
+        let bvh = scene(&[(mesh, Vec3::ZERO)]);
+
+        let hit = bvh
+            .raycast(ray(Vec3::new(0.3, 0.2, 5.0), -Vec3::Z), |_| true)
+            .unwrap();
+        let corners = triangles[hit.face_index as usize].map(|i| positions[i as usize]);
+        let point = corners[0] * hit.barycentric.x
+            + corners[1] * hit.barycentric.y
+            + corners[2] * hit.barycentric.z;
+        assert!(point.abs_diff_eq(hit.position, 1e-4));
+        assert!((hit.barycentric.element_sum() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_hit_triangle_is_in_world_space() {
+        let offset = Vec3::new(4.0, 0.0, 0.0);
+        let bvh = scene(&[(Mesh::from(Cuboid::new(1.0, 1.0, 1.0)), offset)]);
+
+        let hit = bvh
+            .raycast(ray(Vec3::new(4.1, 0.2, 5.0), -Vec3::Z), |_| true)
+            .unwrap();
+        let corners = bvh.hit_triangle(&hit).unwrap();
+        let point = corners[0] * hit.barycentric.x
+            + corners[1] * hit.barycentric.y
+            + corners[2] * hit.barycentric.z;
+        assert!(point.abs_diff_eq(hit.position, 1e-4));
+        assert!(corners.iter().all(|corner| (corner.z - 0.5).abs() < 1e-5));
+
+        let stale = SceneHit {
+            entity: entity(7),
+            ..hit
+        };
+        assert!(bvh.hit_triangle(&stale).is_none());
+    }
+
+    #[test]
+    fn test_filter_and_visibility() {
+        let cube = Mesh::from(Cuboid::new(1.0, 1.0, 1.0));
+        let mut bvh = scene(&[
+            (cube.clone(), Vec3::ZERO),
+            (cube, Vec3::new(0.0, 0.0, -3.0)),
+        ]);
+        let ray = ray(Vec3::new(0.0, 0.0, 5.0), -Vec3::Z);
+
+        assert_eq!(bvh.raycast(ray, |_| true).unwrap().entity, entity(0));
+        let filtered = bvh.raycast(ray, |e| e != entity(0)).unwrap();
+        assert_eq!(filtered.entity, entity(1));
+        bvh.set_visible(entity(0), false);
+        assert_eq!(bvh.raycast(ray, |_| true).unwrap().entity, entity(1));
+    }
+
+    #[test]
+    fn test_transform_refit_and_topology_rebuild() {
+        let cube = Mesh::from(Cuboid::new(1.0, 1.0, 1.0));
+        let mut bvh = scene(&[(cube, Vec3::ZERO)]);
+        let down = |x: f32| ray(Vec3::new(x, 5.0, 0.0), -Vec3::Y);
+
+        // Moving refits the top level; the stale bounds are never used
+        bvh.set_transform(entity(0), &GlobalTransform::from_xyz(10.0, 0.0, 0.0));
+        assert!(bvh.raycast(down(10.0), |_| true).is_some());
+        bvh.update();
+        assert_eq!(bvh.top_state, TopLevelState::Current);
+        assert!(bvh.raycast(down(0.0), |_| true).is_none());
+        assert!(bvh.raycast(down(10.0), |_| true).is_some());
+
+        // Moving vertices refits the mesh BVH
+        let id = bvh.entries[0].mesh;
+        let mut tall = Mesh::from(Cuboid::new(1.0, 1.0, 1.0));
+        if let Some(VertexAttributeValues::Float32x3(positions)) =
+            tall.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+        {
+            positions.iter_mut().for_each(|p| p[1] *= 4.0);
+        }
+        bvh.update_mesh(id, &tall);
+        bvh.update();
+        assert_eq!(bvh.meshes[&id].refits, 1);
+        let hit = bvh.raycast(down(10.0), |_| true).unwrap();
+        assert!((hit.distance - 3.0).abs() < 1e-5);
+
+        // A different mesh rebuilds it
+        bvh.update_mesh(id, &Sphere::new(3.0).mesh().uv(16, 8));
+        assert_eq!(bvh.meshes[&id].refits, 0);
+        let hit = bvh.raycast(down(10.2), |_| true).unwrap();
+        assert!((hit.distance - 2.0).abs() < 0.1, "{hit:?}");
+    }
+
+    #[test]
+    fn test_refit_only_defers_rebuilds() {
+        let cube = Mesh::from(Cuboid::new(1.0, 1.0, 1.0));
+        let mut bvh = scene(&[(cube, Vec3::ZERO)]);
+        bvh.set_refit_only(true);
+        for step in 0..REBUILD_AFTER_REFITS + 1 {
+            bvh.set_transform(entity(0), &GlobalTransform::from_xyz(step as f32, 0.0, 0.0));
+            bvh.update();
+        }
+        assert!(bvh.top_refits > REBUILD_AFTER_REFITS);
+
+        bvh.set_refit_only(false);
+        bvh.update();
+        assert_eq!(bvh.top_refits, 0);
+    }
+}
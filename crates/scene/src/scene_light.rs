@@ -0,0 +1,444 @@
+//! Editable scene lights
+//!
+//! Point, spot, and extra directional lights added from the UI with
+//! `LightCommand`, next to the sun from `lighting`. Lights have `Selectable`
+//! ids, so the transform gizmo moves them like any other object. Each light
+//! (except the sun) gets an always-on-top billboard icon, plus its range as a
+//! wireframe while selected, drawn with Bevy gizmos. The UI gets the full
+//! light list in `LightsChanged` after every change.
+
+use bevy::ecs::message::Message;
+use bevy::ecs::system::SystemParam;
+use bevy::gizmos::config::{GizmoConfig, GizmoConfigGroup};
+use bevy::gizmos::gizmos::Gizmos;
+use bevy::math::Isometry3d;
+use bevy::prelude::*;
+use pentimento_ipc::{BevyToUi, LightCommand, LightInfo, LightType, Transform3D};
+
+use crate::OutboundUiMessages;
+use crate::camera::MainCamera;
+use crate::object_id::IdAllocator;
+#[cfg(feature = "selection")]
+use crate::selection::{Selectable, Selected, SelectionState};
+
+/// Light ID of the sun, which can be edited but not deleted
+pub const SUN_LIGHT_ID: &str = "sun";
+
+/// Light icon radius as a fraction of the distance to the camera
+const ICON_SCALE: f32 = 0.015;
+
+/// Icon color of a selected light
+const SELECTED_ICON_COLOR: Color = Color::srgb(1.0, 0.6, 0.1);
+
+/// Component on every light the UI can list and edit
+#[derive(Component, Debug, Clone)]
+pub struct SceneLight {
+    /// Light ID reported to the UI
+    pub id: String,
+}
+
+/// Light command from the UI
+#[derive(Message, Debug, Clone)]
+pub struct LightCommandEvent(pub LightCommand);
+
+/// Gizmo group for light icons, drawn in front of scene geometry
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct LightGizmos;
+
+/// Counter for numbering light names
+#[derive(Resource, Default)]
+pub(crate) struct LightCounter(u32);
+
+/// Read access to scene lights as `LightInfo`
+#[derive(SystemParam)]
+pub struct SceneLights<'w, 's> {
+    lights: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static SceneLight,
+            &'static Name,
+            &'static Transform,
+        ),
+    >,
+    point_lights: Query<'w, 's, &'static PointLight>,
+    spot_lights: Query<'w, 's, &'static SpotLight>,
+    directional_lights: Query<'w, 's, &'static DirectionalLight>,
+}
+
+impl SceneLights<'_, '_> {
+    /// All scene lights, in ID order
+    pub fn infos(&self) -> Vec<LightInfo> {
+        let mut infos: Vec<_> = self
+            .lights
+            .iter()
+            .filter_map(|(entity, light, name, transform)| {
+                let (light_type, color, intensity, shadows) =
+                    if let Ok(point) = self.point_lights.get(entity) {
+                        (
+                            LightType::Point { range: point.range },
+                            point.color,
+                            point.intensity,
+                            point.shadows_enabled,
+                        )
+                    } else if let Ok(spot) = self.spot_lights.get(entity) {
+                        (
+                            LightType::Spot {
+                                range: spot.range,
+                                inner_angle: spot.inner_angle,
+                                outer_angle: spot.outer_angle,
+                            },
+                            spot.color,
+                            spot.intensity,
+                            spot.shadows_enabled,
+                        )
+                    } else {
+                        let directional = self.directional_lights.get(entity).ok()?;
+                        (
+                            LightType::Directional,
+                            directional.color,
+                            directional.illuminance,
+                            directional.shadows_enabled,
+                        )
+                    };
+                let color = color.to_srgba();
+                Some(LightInfo {
+                    id: light.id.clone(),
+                    name: name.to_string(),
+                    light_type,
+                    color: [color.red, color.green, color.blue],
+                    intensity,
+                    transform: Transform3D {
+                        position: transform.translation.to_array(),
+                        rotation: transform.rotation.to_array(),
+                        scale: transform.scale.to_array(),
+                    },
+                    shadows,
+                })
+            })
+            .collect();
+        infos.sort_by(|a, b| a.id.cmp(&b.id));
+        infos
+    }
+}
+
+/// Register light commands, reporting, and icons (called by `LightingPlugin`)
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<LightCounter>()
+        .add_message::<LightCommandEvent>()
+        .insert_gizmo_config(
+            LightGizmos,
+            GizmoConfig {
+                depth_bias: -1.0,
+                ..default()
+            },
+        )
+        .add_systems(
+            Update,
+            (
+                handle_light_commands,
+                report_light_changes,
+                draw_light_gizmos,
+            )
+                .chain(),
+        );
+}
+
+/// Add, edit, and delete lights
+#[allow(clippy::too_many_arguments)]
+fn handle_light_commands(
+    mut commands: Commands,
+    mut events: MessageReader<LightCommandEvent>,
+    mut counter: ResMut<LightCounter>,
+    mut allocator: ResMut<IdAllocator>,
+    lights: Query<(Entity, &SceneLight)>,
+    mut point_lights: Query<&mut PointLight>,
+    mut spot_lights: Query<&mut SpotLight>,
+    mut directional_lights: Query<&mut DirectionalLight>,
+    #[cfg(feature = "selection")] mut selection: ResMut<SelectionState>,
+    #[cfg(feature = "selection")] selected: Query<Entity, With<Selected>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for LightCommandEvent(command) in events.read() {
+        match command {
+            LightCommand::Add {
+                light_type,
+                position,
+            } => {
+                counter.0 += 1;
+                let id = allocator.allocate();
+                let position = Vec3::from_array(*position);
+
+                let entity = match light_type {
+                    LightType::Point { range } => commands.spawn((
+                        PointLight {
+                            range: range.max(0.01),
+                            ..default()
+                        },
+                        Transform::from_translation(position),
+                        Name::new(format!("Point Light {}", counter.0)),
+                    )),
+                    LightType::Spot {
+                        range,
+                        inner_angle,
+                        outer_angle,
+                    } => {
+                        let (inner_angle, outer_angle) = spot_angles(*inner_angle, *outer_angle);
+                        commands.spawn((
+                            SpotLight {
+                                range: range.max(0.01),
+                                inner_angle,
+                                outer_angle,
+                                ..default()
+                            },
+                            // Shine straight down
+                            Transform::from_translation(position).looking_to(Vec3::NEG_Y, Vec3::Z),
+                            Name::new(format!("Spot Light {}", counter.0)),
+                        ))
+                    }
+                    LightType::Directional => commands.spawn((
+                        DirectionalLight::default(),
+                        Transform::from_translation(position).looking_to(Vec3::NEG_Y, Vec3::Z),
+                        Name::new(format!("Directional Light {}", counter.0)),
+                    )),
+                }
+                .insert(SceneLight { id: id.clone() })
+                .id();
+
+                // Select the new light so it can be moved right away
+                #[cfg(feature = "selection")]
+                {
+                    for previous in selected.iter() {
+                        commands.entity(previous).remove::<Selected>();
+                    }
+                    commands
+                        .entity(entity)
+                        .insert((Selectable { id: id.clone() }, Selected));
+                    selection.selected_ids = vec![id.clone()];
+                }
+
+                info!("Added light {} ({:?})", id, entity);
+            }
+            LightCommand::Update {
+                id,
+                color,
+                intensity,
+                range,
+                angles,
+            } => {
+                let Some(entity) = find_light(&lights, id, &mut outbound) else {
+                    continue;
+                };
+                let color = color.map(|[r, g, b]| Color::srgb(r, g, b));
+                let intensity = intensity.map(|intensity| intensity.max(0.0));
+                let range = range.map(|range| range.max(0.01));
+
+                if let Ok(mut light) = point_lights.get_mut(entity) {
+                    if let Some(color) = color {
+                        light.color = color;
+                    }
+                    if let Some(intensity) = intensity {
+                        light.intensity = intensity;
+                    }
+                    if let Some(range) = range {
+                        light.range = range;
+                    }
+                } else if let Ok(mut light) = spot_lights.get_mut(entity) {
+                    if let Some(color) = color {
+                        light.color = color;
+                    }
+                    if let Some(intensity) = intensity {
+                        light.intensity = intensity;
+                    }
+                    if let Some(range) = range {
+                        light.range = range;
+                    }
+                    if let Some([inner, outer]) = angles {
+                        (light.inner_angle, light.outer_angle) = spot_angles(*inner, *outer);
+                    }
+                } else if let Ok(mut light) = directional_lights.get_mut(entity) {
+                    if let Some(color) = color {
+                        light.color = color;
+                    }
+                    if let Some(intensity) = intensity {
+                        light.illuminance = intensity;
+                    }
+                }
+            }
+            LightCommand::Delete { id } => {
+                if id == SUN_LIGHT_ID {
+                    outbound.send_error("light_delete_sun", "The sun can't be deleted");
+                    continue;
+                }
+                let Some(entity) = find_light(&lights, id, &mut outbound) else {
+                    continue;
+                };
+                commands.entity(entity).despawn();
+                #[cfg(feature = "selection")]
+                selection
+                    .selected_ids
+                    .retain(|selected_id| selected_id != id);
+                info!("Deleted light {}", id);
+            }
+            LightCommand::SetShadows { id, enabled } => {
+                let Some(entity) = find_light(&lights, id, &mut outbound) else {
+                    continue;
+                };
+                if let Ok(mut light) = point_lights.get_mut(entity) {
+                    light.shadows_enabled = *enabled;
+                } else if let Ok(mut light) = spot_lights.get_mut(entity) {
+                    light.shadows_enabled = *enabled;
+                } else if let Ok(mut light) = directional_lights.get_mut(entity) {
+                    light.shadows_enabled = *enabled;
+                }
+            }
+        }
+    }
+}
+
+/// Filter for lights whose ID, transform, or light settings changed
+type LightChangedFilter = Or<(
+    Changed<SceneLight>,
+    Changed<Transform>,
+    Changed<PointLight>,
+    Changed<SpotLight>,
+    Changed<DirectionalLight>,
+)>;
+
+/// Send the light list when a light is added, edited, moved, or removed
+fn report_light_changes(
+    scene_lights: SceneLights,
+    changed: Query<(), (With<SceneLight>, LightChangedFilter)>,
+    mut removed: RemovedComponents<SceneLight>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let any_removed = removed.read().count() > 0;
+    if changed.is_empty() && !any_removed {
+        return;
+    }
+    outbound.send(BevyToUi::LightsChanged {
+        lights: scene_lights.infos(),
+    });
+}
+
+/// Draw light icons, and the range of selected lights
+fn draw_light_gizmos(
+    mut gizmos: Gizmos<LightGizmos>,
+    camera: Query<&GlobalTransform, With<MainCamera>>,
+    lights: Query<(Entity, &SceneLight, &GlobalTransform)>,
+    point_lights: Query<&PointLight>,
+    spot_lights: Query<&SpotLight>,
+    directional_lights: Query<&DirectionalLight>,
+    #[cfg(feature = "selection")] selected: Query<(), With<Selected>>,
+) {
+    let Ok(camera) = camera.single() else {
+        return;
+    };
+    let camera_rotation = camera.rotation();
+
+    for (entity, light, transform) in lights.iter() {
+        // The sun is lit from infinitely far away; it has no position to show
+        if light.id == SUN_LIGHT_ID {
+            continue;
+        }
+        #[cfg(feature = "selection")]
+        let is_selected = selected.contains(entity);
+        #[cfg(not(feature = "selection"))]
+        let is_selected = false;
+
+        let position = transform.translation();
+        let forward = *transform.forward();
+        let radius = position.distance(camera.translation()) * ICON_SCALE;
+
+        let (light_color, range, cone) = if let Ok(point) = point_lights.get(entity) {
+            (point.color, Some(point.range), None)
+        } else if let Ok(spot) = spot_lights.get(entity) {
+            (spot.color, Some(spot.range), Some(spot.outer_angle))
+        } else if let Ok(directional) = directional_lights.get(entity) {
+            (directional.color, None, None)
+        } else {
+            continue;
+        };
+        let color = if is_selected {
+            SELECTED_ICON_COLOR
+        } else {
+            light_color
+        };
+
+        // Billboard: a camera-facing circle with short rays around it
+        gizmos.circle(Isometry3d::new(position, camera_rotation), radius, color);
+        for i in 0..8 {
+            let angle = i as f32 * std::f32::consts::FRAC_PI_4;
+            let ray = camera_rotation * Vec3::new(angle.cos(), angle.sin(), 0.0);
+            gizmos.line(
+                position + ray * radius * 1.4,
+                position + ray * radius * 2.0,
+                color,
+            );
+        }
+        // Direction the light shines in
+        if range.is_none() || cone.is_some() {
+            gizmos.arrow(position, position + forward * radius * 5.0, color);
+        }
+
+        if !is_selected {
+            continue;
+        }
+        match (range, cone) {
+            (Some(range), None) => {
+                gizmos.sphere(Isometry3d::from_translation(position), range, light_color);
+            }
+            (Some(range), Some(outer_angle)) => {
+                // Cone from the light to its range at the outer angle
+                let base_center = position + forward * range * outer_angle.cos();
+                let base_radius = range * outer_angle.sin();
+                let base_rotation = Quat::from_rotation_arc(Vec3::Z, forward);
+                gizmos.circle(
+                    Isometry3d::new(base_center, base_rotation),
+                    base_radius,
+                    light_color,
+                );
+                for axis in [Vec3::X, Vec3::NEG_X, Vec3::Y, Vec3::NEG_Y] {
+                    let rim = base_center + base_rotation * axis * base_radius;
+                    gizmos.line(position, rim, light_color);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Entity of the light with `id`, reporting unknown IDs to the UI
+fn find_light(
+    lights: &Query<(Entity, &SceneLight)>,
+    id: &str,
+    outbound: &mut OutboundUiMessages,
+) -> Option<Entity> {
+    let entity = lights
+        .iter()
+        .find(|(_, light)| light.id == id)
+        .map(|(entity, _)| entity);
+    if entity.is_none() {
+        outbound.send_error("light_not_found", format!("No light with ID {}", id));
+    }
+    entity
+}
+
+/// Spot cone angles Bevy accepts: `0 <= inner <= outer <= PI/2`
+fn spot_angles(inner: f32, outer: f32) -> (f32, f32) {
+    let outer = outer.clamp(0.0, std::f32::consts::FRAC_PI_2);
+    (inner.clamp(0.0, outer), outer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spot_angles_are_clamped() {
+        assert_eq!(spot_angles(0.2, 0.5), (0.2, 0.5));
+        assert_eq!(spot_angles(0.8, 0.5), (0.5, 0.5));
+        assert_eq!(spot_angles(-1.0, 3.0), (0.0, std::f32::consts::FRAC_PI_2));
+    }
+}
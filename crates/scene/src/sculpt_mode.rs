@@ -4,31 +4,46 @@
 //! - Ctrl+Tab to enter/exit sculpt mode (requires mesh selected)
 //! - Brush-based deformation (Push, Pull, Smooth, etc.)
 //! - Screen-space adaptive tessellation
-//! - Mesh chunking for optimized GPU updates
+//! - Mesh chunking for optimized GPU updates, with each chunk drawn by its own
+//!   entity and far-away chunks switched to decimated copies
+//! - Uniform remesh on demand, run on the async compute pool
+//! - Sculpt mask (Mask brush, clear/invert), shown as darkened vertex colors
+//! - Mesh integrity audit and repair, on demand or every N strokes
 
 use bevy::ecs::message::Message;
 use bevy::input::mouse::MouseButton;
 use bevy::math::{Affine3A, Isometry3d};
-use bevy::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::picking::prelude::Pickable;
 use bevy::prelude::*;
+use bevy::tasks::futures::check_ready;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
 use bevy::window::{CursorMoved, PrimaryWindow};
 use painting::half_edge::HalfEdgeMesh;
-use pentimento_ipc::{BevyToUi, EditMode};
+use pentimento_config::{Keymap, actions};
+use pentimento_ipc::{BevyToUi, SculptChunkStats, SculptCommand, SculptDetailMode};
 use sculpting::{
-    BrushInput, BrushPreset, ChunkConfig, ChunkedMesh, DeformationType, FalloffCurve,
-    PipelineConfig, ScreenSpaceConfig, SculptingPipeline, TessellationConfig, TessellationMode,
-    partition_mesh,
+    Aabb, BrushInput, BrushPreset, ChunkConfig, ChunkDetail, ChunkId, ChunkMeshes, ChunkedMesh,
+    DeformationType, FalloffCurve, LodConfig, MeshDoctor, MeshHealthReport, MeshVertexPatchPlugin,
+    MeshVertexPatches, PipelineConfig, RemeshConfig, RemeshError, RemeshProgress, RemeshStats,
+    ScreenSpaceConfig, SculptingPipeline, SyncResult, TessellationConfig, TessellationMode,
+    partition_mesh, projected_size, remesh_uniform,
 };
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::OutboundUiMessages;
+use crate::brush_navigation::BrushNavigation;
 use crate::camera::MainCamera;
-use crate::edit_mode::EditModeState;
+use crate::canvas_plane::CanvasPlane;
+use crate::edit_mode::{EditModeAppExt, EditModeRequests, EditModeState, EditorMode};
 use crate::paint_mode::StrokeIdGenerator;
 use crate::pixel_coverage::{PixelCoverageState, estimate_pixel_coverage_cpu};
 use crate::render_camera::{ActiveRenderCamera, RenderCamera};
 #[cfg(feature = "selection")]
-use crate::selection::Selected;
+use crate::selection::{Locked, Selected};
+use crate::subdivision::{BaseMesh, BaseMeshChanged, base_mesh_handle};
 
 /// Mode for interactive brush adjustment (Blender-style F key)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -55,10 +70,16 @@ pub struct SculptState {
     pub brush_radius: f32,
     /// Brush strength (0.0 - 1.0)
     pub brush_strength: f32,
+    /// Dab spacing as a fraction of the brush radius
+    pub brush_spacing: f32,
+    /// Per-dab strength multiplier (0.0 - 1.0)
+    pub brush_flow: f32,
     /// Brush hardness (0.0 - 1.0). Defines the inner zone of full strength.
     pub brush_hardness: f32,
     /// Falloff curve type for the brush
     pub brush_falloff: FalloffCurve,
+    /// Whether dynamic topology (tessellation while sculpting) is enabled
+    pub dynamic_topology: bool,
     /// Tessellation configuration
     pub tessellation_config: TessellationConfig,
     /// Chunk sizing configuration
@@ -85,8 +106,11 @@ impl Default for SculptState {
             deformation_type: DeformationType::Push,
             brush_radius: 0.5,
             brush_strength: 1.0,
+            brush_spacing: 0.25,
+            brush_flow: 1.0,
             brush_hardness: 0.5,
             brush_falloff: FalloffCurve::Smooth,
+            dynamic_topology: true,
             tessellation_config: TessellationConfig::default(),
             chunk_config: ChunkConfig::default(),
             current_stroke_id: None,
@@ -99,6 +123,70 @@ impl Default for SculptState {
     }
 }
 
+impl SculptState {
+    /// Pipeline configuration for the current dynamic topology settings.
+    pub fn pipeline_config(&self) -> PipelineConfig {
+        PipelineConfig {
+            tessellation_enabled: self.dynamic_topology,
+            tessellation_config: self.tessellation_config.clone(),
+            chunk_config: self.chunk_config.clone(),
+            rebalance_after_stroke: true,
+            parallel_tessellation: true,
+        }
+    }
+
+    /// Detail size for the current tessellation mode: target edge length in
+    /// pixels (ScreenSpace) or local units (Constant), minimum edge length
+    /// (BudgetCurvature).
+    pub fn detail_size(&self) -> f32 {
+        let config = &self.tessellation_config;
+        match config.mode {
+            TessellationMode::ScreenSpace => config.target_pixels,
+            TessellationMode::Constant => config.target_edge_length,
+            TessellationMode::BudgetCurvature => config.min_edge_length,
+        }
+    }
+
+    /// Set the detail size for the current tessellation mode.
+    pub fn set_detail_size(&mut self, size: f32) {
+        let config = &mut self.tessellation_config;
+        match config.mode {
+            TessellationMode::ScreenSpace => config.target_pixels = size.max(1.0),
+            TessellationMode::Constant => config.target_edge_length = size.max(0.0001),
+            TessellationMode::BudgetCurvature => config.min_edge_length = size.max(0.0001),
+        }
+    }
+
+    /// UI message reporting the effective dynamic topology settings.
+    pub fn settings_message(&self) -> BevyToUi {
+        BevyToUi::SculptSettingsChanged {
+            dynamic_topology: self.dynamic_topology,
+            detail_mode: detail_mode_to_ipc(self.tessellation_config.mode),
+            detail_size: self.detail_size(),
+            max_vertices: self
+                .tessellation_config
+                .max_vertices
+                .map(|max| max.min(u32::MAX as usize) as u32),
+        }
+    }
+}
+
+fn detail_mode_from_ipc(mode: SculptDetailMode) -> TessellationMode {
+    match mode {
+        SculptDetailMode::ScreenSpace => TessellationMode::ScreenSpace,
+        SculptDetailMode::Constant => TessellationMode::Constant,
+        SculptDetailMode::Budget => TessellationMode::BudgetCurvature,
+    }
+}
+
+fn detail_mode_to_ipc(mode: TessellationMode) -> SculptDetailMode {
+    match mode {
+        TessellationMode::ScreenSpace => SculptDetailMode::ScreenSpace,
+        TessellationMode::Constant => SculptDetailMode::Constant,
+        TessellationMode::BudgetCurvature => SculptDetailMode::Budget,
+    }
+}
+
 /// Resource holding the active sculpting data
 #[derive(Resource, Default)]
 pub struct SculptingData {
@@ -106,9 +194,17 @@ pub struct SculptingData {
     pub chunked_mesh: Option<ChunkedMesh>,
     /// The sculpting pipeline
     pub pipeline: Option<SculptingPipeline>,
-    /// Chunk entities spawned for visualization
-    pub chunk_entities: Vec<Entity>,
-    /// Original mesh handle for restoration
+    /// Entity drawing each chunk while the sculpted object is hidden
+    pub chunk_entities: HashMap<ChunkId, Entity>,
+    /// Full and reduced GPU meshes of each chunk
+    pub chunk_meshes: HashMap<ChunkId, ChunkMeshes>,
+    /// Material of the sculpted object, shared by its chunk entities
+    pub chunk_material: Option<Handle<StandardMaterial>>,
+    /// Thresholds for drawing far-away chunks at reduced detail
+    pub lod: LodConfig,
+    /// Visibility of the sculpted object before its chunks replaced it
+    pub target_visibility: Option<Visibility>,
+    /// Original mesh handle, which receives the merged sculpt on exit
     pub original_mesh_handle: Option<Handle<Mesh>>,
     /// Mesh ID for stroke tracking
     pub mesh_id: u32,
@@ -120,26 +216,60 @@ pub struct SculptingData {
     /// Vertex positions in HalfEdgeMesh are in local space; this matrix
     /// is needed to correctly compute screen-space edge lengths.
     pub model_matrix: Option<Mat4>,
-    /// Cached vertex mapping from merge (original_id → unified_id).
-    /// Used for position-only GPU updates without full re-merge.
-    pub cached_vertex_mapping: Option<
-        std::collections::HashMap<painting::half_edge::VertexId, painting::half_edge::VertexId>,
-    >,
+    /// Vertices patched vs rebuilt by the most recent GPU sync.
+    pub last_gpu_sync: SyncResult,
+    /// Remesh running in the background, if any
+    pub remesh_job: Option<RemeshJob>,
+    /// Time-sliced mesh integrity audit
+    pub mesh_doctor: MeshDoctor,
+}
+
+/// A uniform remesh running on the async compute pool.
+///
+/// The task works on a merged copy of the sculpt, so the chunked mesh stays
+/// untouched until the result is re-partitioned and swapped in.
+pub struct RemeshJob {
+    task: Task<Result<(HalfEdgeMesh, RemeshStats), RemeshError>>,
+    progress: Arc<RemeshProgress>,
+    /// Last progress fraction sent to the UI
+    last_reported: f32,
 }
 
 /// Message for sculpt mode events
 #[derive(Message, Debug, Clone)]
 pub enum SculptEvent {
-    /// Enter sculpt mode for the specified entity
-    Enter { entity: Entity },
-    /// Exit sculpt mode
-    Exit,
     /// Set the deformation type
     SetDeformationType(DeformationType),
+    /// Switch to a built-in brush preset by ID, keeping the current radius
+    SelectBrushPreset(u32),
     /// Set brush radius
     SetBrushRadius(f32),
     /// Set brush strength
     SetBrushStrength(f32),
+    /// Set dab spacing (fraction of brush radius)
+    SetBrushSpacing(f32),
+    /// Set brush flow (per-dab strength multiplier)
+    SetFlow(f32),
+    /// Enable or disable dynamic topology
+    SetDynamicTopology(bool),
+    /// Set the tessellation mode used for dynamic topology
+    SetDetailMode(TessellationMode),
+    /// Set the detail size for the current tessellation mode
+    SetDetailSize(f32),
+    /// Cap the vertex budget (None = pixel coverage only)
+    SetVertexBudget(Option<usize>),
+    /// Uniformly remesh the whole sculpt to a target edge length (local units)
+    Remesh { target_edge_length: f32 },
+    /// Cancel the running remesh
+    CancelRemesh,
+    /// Clear the sculpt mask on the whole mesh
+    ClearMask,
+    /// Invert the sculpt mask on the whole mesh
+    InvertMask,
+    /// Audit and repair the sculpt mesh, then report its health to the UI
+    ValidateMesh,
+    /// Validate automatically every N strokes (0 = never)
+    SetAutoValidate(u32),
     /// Start a sculpt stroke
     StrokeStart {
         /// World-space position where stroke started
@@ -164,11 +294,49 @@ pub enum SculptEvent {
     StrokeCancel,
 }
 
+impl From<SculptCommand> for SculptEvent {
+    fn from(command: SculptCommand) -> Self {
+        match command {
+            SculptCommand::SetBrushSpacing { spacing } => SculptEvent::SetBrushSpacing(spacing),
+            SculptCommand::SetBrushFlow { flow } => SculptEvent::SetFlow(flow),
+            SculptCommand::SelectBrushPreset { preset_id } => {
+                SculptEvent::SelectBrushPreset(preset_id)
+            }
+            SculptCommand::SetDynamicTopology { enabled } => {
+                SculptEvent::SetDynamicTopology(enabled)
+            }
+            SculptCommand::SetDetailMode(mode) => {
+                SculptEvent::SetDetailMode(detail_mode_from_ipc(mode))
+            }
+            SculptCommand::SetDetailSize { pixels_or_world } => {
+                SculptEvent::SetDetailSize(pixels_or_world)
+            }
+            SculptCommand::SetVertexBudget { max_vertices } => {
+                SculptEvent::SetVertexBudget(max_vertices.map(|max| max as usize))
+            }
+            SculptCommand::Remesh { target_edge_length } => {
+                SculptEvent::Remesh { target_edge_length }
+            }
+            SculptCommand::CancelRemesh => SculptEvent::CancelRemesh,
+            SculptCommand::ClearMask => SculptEvent::ClearMask,
+            SculptCommand::InvertMask => SculptEvent::InvertMask,
+            SculptCommand::ValidateMesh => SculptEvent::ValidateMesh,
+            SculptCommand::SetAutoValidate { every_strokes } => {
+                SculptEvent::SetAutoValidate(every_strokes)
+            }
+        }
+    }
+}
+
 /// Plugin for sculpt mode functionality
 pub struct SculptModePlugin;
 
 impl Plugin for SculptModePlugin {
     fn build(&self, app: &mut App) {
+        // Also added by mesh painting, which patches vertex colors
+        if !app.is_plugin_added::<MeshVertexPatchPlugin>() {
+            app.add_plugins(MeshVertexPatchPlugin);
+        }
         app.init_resource::<SculptState>()
             .init_resource::<SculptingData>()
             .add_message::<SculptEvent>()
@@ -181,11 +349,17 @@ impl Plugin for SculptModePlugin {
                     handle_brush_adjustment,
                     handle_sculpt_input,
                     handle_sculpt_events,
+                    poll_sculpt_remesh,
+                    run_mesh_doctor,
+                    select_sculpt_chunk_detail,
                     sync_sculpt_chunks_to_gpu,
                     render_sculpt_brush_gizmo,
                 )
                     .chain(),
-            );
+            )
+            .set_edit_mode_guard(EditorMode::Sculpt, sculpt_mode_guard)
+            .on_enter_edit_mode(EditorMode::Sculpt, enter_sculpt_mode)
+            .on_exit_edit_mode(EditorMode::Sculpt, exit_sculpt_mode);
     }
 }
 
@@ -328,38 +502,50 @@ fn update_sculpt_budget(
 
 /// Handle Ctrl+Tab to toggle sculpt mode
 ///
-/// Ctrl+Tab enters sculpt mode when a mesh is selected.
-/// If already in sculpt mode, Ctrl+Tab exits.
-#[cfg(feature = "selection")]
+/// Ctrl+Tab enters sculpt mode on the selected mesh (see
+/// [`sculpt_mode_guard`]). If already in sculpt mode, Ctrl+Tab exits.
 fn handle_sculpt_mode_hotkey(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     edit_mode: Res<EditModeState>,
-    selected_meshes: Query<Entity, (With<Selected>, With<Mesh3d>)>,
-    mut events: MessageWriter<SculptEvent>,
+    mut mode_requests: EditModeRequests,
 ) {
-    // Check for Ctrl modifier
-    let ctrl = key_input.pressed(KeyCode::ControlLeft) || key_input.pressed(KeyCode::ControlRight);
-    let tab = key_input.just_pressed(KeyCode::Tab);
-
-    if !ctrl || !tab {
+    if !keymap.just_pressed(actions::MODE_SCULPT, &key_input) {
         return;
     }
 
-    // If already in sculpt mode, exit
-    if edit_mode.mode == EditMode::Sculpt {
-        events.write(SculptEvent::Exit);
-        return;
+    if edit_mode.mode == EditorMode::Sculpt {
+        mode_requests.request_mode_change(EditorMode::Object);
+    } else {
+        mode_requests.request_mode_change(EditorMode::Sculpt);
     }
+}
 
-    // If we have a mesh selected, enter sculpt mode
-    if let Ok(entity) = selected_meshes.single() {
-        events.write(SculptEvent::Enter { entity });
+/// Sculpt mode needs a visible, unlocked mesh: the requested one, or the
+/// selected one. Canvas planes are painted, not sculpted.
+#[cfg(feature = "selection")]
+fn sculpt_mode_guard(
+    In(target): In<Option<Entity>>,
+    selected_meshes: Query<Entity, (With<Selected>, With<Mesh3d>)>,
+    sculptable: Query<&InheritedVisibility, (With<Mesh3d>, Without<Locked>, Without<CanvasPlane>)>,
+) -> Result<Option<Entity>, String> {
+    let entity = target
+        .or_else(|| selected_meshes.single().ok())
+        .ok_or_else(|| "Select a mesh to sculpt".to_string())?;
+    match sculptable.get(entity) {
+        Ok(visibility) if visibility.get() => Ok(Some(entity)),
+        Ok(_) => Err("Hidden meshes can't be sculpted".to_string()),
+        Err(_) => Err("The selected mesh is locked or can't be sculpted".to_string()),
     }
 }
 
-/// Stub for non-selection builds
+/// Stub for non-selection builds: only explicit targets can be sculpted
 #[cfg(not(feature = "selection"))]
-fn handle_sculpt_mode_hotkey() {}
+fn sculpt_mode_guard(In(target): In<Option<Entity>>) -> Result<Option<Entity>, String> {
+    target
+        .map(Some)
+        .ok_or_else(|| "Select a mesh to sculpt".to_string())
+}
 
 /// Handle Blender-style brush adjustment (F for radius, Shift+F for strength)
 ///
@@ -369,6 +555,7 @@ fn handle_sculpt_mode_hotkey() {}
 /// - Press Escape: Cancel adjustment and restore original value
 fn handle_brush_adjustment(
     key_input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<Keymap>,
     mouse_button: Res<ButtonInput<MouseButton>>,
     windows: Query<&Window, With<PrimaryWindow>>,
     mut sculpt_state: ResMut<SculptState>,
@@ -384,11 +571,10 @@ fn handle_brush_adjustment(
     };
 
     let cursor_pos = window.cursor_position();
-    let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
 
     // Check for starting adjustment mode
-    if key_input.just_pressed(KeyCode::KeyF) && sculpt_state.adjust_mode == BrushAdjustMode::None {
-        if shift {
+    if sculpt_state.adjust_mode == BrushAdjustMode::None {
+        if keymap.just_pressed(actions::SCULPT_ADJUST_STRENGTH, &key_input) {
             // Shift+F: Strength adjustment
             sculpt_state.adjust_mode = BrushAdjustMode::Strength;
             sculpt_state.adjust_start_cursor = cursor_pos;
@@ -397,7 +583,9 @@ fn handle_brush_adjustment(
                 "Brush strength adjustment: drag horizontally (current: {:.2})",
                 sculpt_state.brush_strength
             );
-        } else {
+            return;
+        }
+        if keymap.just_pressed(actions::SCULPT_ADJUST_RADIUS, &key_input) {
             // F: Radius adjustment
             sculpt_state.adjust_mode = BrushAdjustMode::Radius;
             sculpt_state.adjust_start_cursor = cursor_pos;
@@ -406,8 +594,8 @@ fn handle_brush_adjustment(
                 "Brush radius adjustment: drag horizontally (current: {:.2})",
                 sculpt_state.brush_radius
             );
+            return;
         }
-        return;
     }
 
     // Handle active adjustment
@@ -512,18 +700,28 @@ fn handle_sculpt_input(
     windows: Query<(Entity, &Window), With<PrimaryWindow>>,
     mut cursor_events: MessageReader<CursorMoved>,
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    mesh_query: Query<(&Mesh3d, &GlobalTransform)>,
-    meshes: Res<Assets<Mesh>>,
+    transform_query: Query<&GlobalTransform>,
+    sculpting_data: Res<SculptingData>,
     sculpt_state: Res<SculptState>,
     mut stroke_id_gen: ResMut<StrokeIdGenerator>,
     mut sculpt_events: MessageWriter<SculptEvent>,
     time: Res<Time>,
+    navigation: Res<BrushNavigation>,
 ) {
     // Only process if sculpt mode is active
     if !sculpt_state.active {
         return;
     }
 
+    // The camera has the mouse: finish the stroke so it is logged whole
+    if navigation.navigating {
+        cursor_events.clear();
+        if sculpt_state.current_stroke_id.is_some() {
+            sculpt_events.write(SculptEvent::StrokeEnd);
+        }
+        return;
+    }
+
     // Don't process sculpting while adjusting brush parameters
     if sculpt_state.adjust_mode != BrushAdjustMode::None {
         // Consume cursor events to prevent them from accumulating
@@ -545,12 +743,12 @@ fn handle_sculpt_input(
         return;
     };
 
-    // Get target mesh
-    let Ok((mesh_handle, mesh_transform)) = mesh_query.get(target_entity) else {
+    // Get the chunks being sculpted
+    let Ok(mesh_transform) = transform_query.get(target_entity) else {
         return;
     };
 
-    let Some(mesh) = meshes.get(&mesh_handle.0) else {
+    let Some(chunked_mesh) = &sculpting_data.chunked_mesh else {
         return;
     };
 
@@ -565,6 +763,9 @@ fn handle_sculpt_input(
 
     // Handle stroke start
     if mouse_button.just_pressed(MouseButton::Left) {
+        if !navigation.starts_stroke() {
+            return;
+        }
         let cursor_pos = cursor_positions
             .last()
             .copied()
@@ -572,7 +773,7 @@ fn handle_sculpt_input(
         if let Some(cursor_pos) = cursor_pos {
             if let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_pos) {
                 if let Some((world_pos, normal)) =
-                    ray_mesh_intersection_simple(&ray, mesh, mesh_transform)
+                    ray_chunks_intersection(&ray, chunked_mesh, mesh_transform)
                 {
                     let stroke_id = stroke_id_gen.next();
                     sculpt_events.write(SculptEvent::StrokeStart {
@@ -596,7 +797,7 @@ fn handle_sculpt_input(
         for cursor_pos in positions_to_process {
             if let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_pos) {
                 if let Some((world_pos, normal)) =
-                    ray_mesh_intersection_simple(&ray, mesh, mesh_transform)
+                    ray_chunks_intersection(&ray, chunked_mesh, mesh_transform)
                 {
                     sculpt_events.write(SculptEvent::StrokeMove {
                         world_pos,
@@ -613,107 +814,90 @@ fn handle_sculpt_input(
     }
 }
 
-/// Simple ray-mesh intersection returning world position and normal
-fn ray_mesh_intersection_simple(
+/// Ray-cast the chunks being sculpted, returning world position and normal.
+///
+/// The sculpted object is hidden and its mesh isn't updated until sculpt mode
+/// exits, so the ray is tested against the chunks' current geometry. Chunks
+/// whose bounds the ray misses are skipped.
+fn ray_chunks_intersection(
     ray: &Ray3d,
-    mesh: &Mesh,
+    chunked_mesh: &ChunkedMesh,
     transform: &GlobalTransform,
 ) -> Option<(Vec3, Vec3)> {
-    // Get vertex positions
-    let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
-        Some(VertexAttributeValues::Float32x3(v)) => v,
-        _ => return None,
-    };
-
-    // Get indices
-    let indices = match mesh.indices() {
-        Some(Indices::U32(i)) => i.iter().map(|&x| x as usize).collect::<Vec<_>>(),
-        Some(Indices::U16(i)) => i.iter().map(|&x| x as usize).collect::<Vec<_>>(),
-        None => return None,
-    };
-
-    // Get optional normals
-    let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
-        Some(VertexAttributeValues::Float32x3(v)) => Some(v),
-        _ => None,
-    };
-
     // Transform ray to local space
     let inv_transform = transform.affine().inverse();
     let local_ray_origin = inv_transform.transform_point3(ray.origin);
     let local_ray_dir = inv_transform.transform_vector3(*ray.direction).normalize();
 
-    let mut closest_hit: Option<(f32, Vec3, Vec3)> = None; // (t, local_pos, barycentric)
+    let mut closest_hit: Option<(f32, Vec3, Vec3)> = None; // (t, local_pos, local_normal)
 
-    // Iterate through triangles
-    for triangle in indices.chunks(3) {
-        if triangle.len() != 3 {
+    for chunk in chunked_mesh.chunks.values() {
+        if !ray_hits_bounds(local_ray_origin, local_ray_dir, &chunk.bounds) {
             continue;
         }
 
-        let i0 = triangle[0];
-        let i1 = triangle[1];
-        let i2 = triangle[2];
+        let mesh = &chunk.mesh;
+        for face in mesh.faces() {
+            let verts = mesh.get_face_vertices(face.id);
+            if verts.len() < 3 {
+                continue;
+            }
 
-        let v0 = Vec3::from(positions[i0]);
-        let v1 = Vec3::from(positions[i1]);
-        let v2 = Vec3::from(positions[i2]);
+            // Fan-triangulate like the chunk's GPU mesh
+            for i in 1..(verts.len() - 1) {
+                let (Some(a), Some(b), Some(c)) = (
+                    mesh.vertex(verts[0]),
+                    mesh.vertex(verts[i]),
+                    mesh.vertex(verts[i + 1]),
+                ) else {
+                    continue;
+                };
+
+                // Möller–Trumbore intersection
+                let Some((t, u, v)) = ray_triangle_intersection(
+                    local_ray_origin,
+                    local_ray_dir,
+                    a.position,
+                    b.position,
+                    c.position,
+                ) else {
+                    continue;
+                };
+                if closest_hit.is_some_and(|(closest_t, _, _)| closest_t <= t) {
+                    continue;
+                }
 
-        // Möller–Trumbore intersection
-        if let Some((t, u, v)) =
-            ray_triangle_intersection(local_ray_origin, local_ray_dir, v0, v1, v2)
-        {
-            if t > 0.0 && (closest_hit.is_none() || t < closest_hit.as_ref().unwrap().0) {
                 let w = 1.0 - u - v;
-                let local_pos = v0 * w + v1 * u + v2 * v;
-                closest_hit = Some((t, local_pos, Vec3::new(w, u, v)));
+                let local_pos = a.position * w + b.position * u + c.position * v;
+                let local_normal = (a.normal * w + b.normal * u + c.normal * v)
+                    .try_normalize()
+                    .unwrap_or_else(|| {
+                        (b.position - a.position)
+                            .cross(c.position - a.position)
+                            .normalize_or(Vec3::Y)
+                    });
+                closest_hit = Some((t, local_pos, local_normal));
             }
         }
     }
 
-    let (_t, local_pos, barycentric) = closest_hit?;
+    let (_t, local_pos, local_normal) = closest_hit?;
 
-    // Transform position to world space
     let world_pos = transform.transform_point(local_pos);
-
-    // Get normal - find the triangle again to interpolate normal
-    let mut normal = Vec3::Y;
-    for triangle in indices.chunks(3) {
-        if triangle.len() != 3 {
-            continue;
-        }
-
-        let i0 = triangle[0];
-        let i1 = triangle[1];
-        let i2 = triangle[2];
-
-        let v0 = Vec3::from(positions[i0]);
-        let v1 = Vec3::from(positions[i1]);
-        let v2 = Vec3::from(positions[i2]);
-
-        // Check if this is the triangle we hit
-        let test_pos = v0 * barycentric.x + v1 * barycentric.y + v2 * barycentric.z;
-        if test_pos.distance(local_pos) < 0.001 {
-            if let Some(normals) = normals {
-                let n0 = Vec3::from(normals[i0]);
-                let n1 = Vec3::from(normals[i1]);
-                let n2 = Vec3::from(normals[i2]);
-                let local_normal =
-                    (n0 * barycentric.x + n1 * barycentric.y + n2 * barycentric.z).normalize();
-                normal = (transform.rotation() * local_normal).normalize();
-            } else {
-                let edge1 = v1 - v0;
-                let edge2 = v2 - v0;
-                let local_normal = edge1.cross(edge2).normalize();
-                normal = (transform.rotation() * local_normal).normalize();
-            }
-            break;
-        }
-    }
-
+    let normal = (transform.rotation() * local_normal).normalize();
     Some((world_pos, normal))
 }
 
+/// Slab test: whether a ray starting at `origin` passes through `bounds`.
+fn ray_hits_bounds(origin: Vec3, dir: Vec3, bounds: &Aabb) -> bool {
+    let inv_dir = dir.recip();
+    let t1 = (bounds.min - origin) * inv_dir;
+    let t2 = (bounds.max - origin) * inv_dir;
+    let t_near = t1.min(t2).max_element();
+    let t_far = t1.max(t2).min_element();
+    t_near <= t_far && t_far >= 0.0
+}
+
 /// Möller–Trumbore ray-triangle intersection
 fn ray_triangle_intersection(
     ray_origin: Vec3,
@@ -754,137 +938,186 @@ fn ray_triangle_intersection(
     if t > EPSILON { Some((t, u, v)) } else { None }
 }
 
-/// Handle sculpt mode events
-fn handle_sculpt_events(
-    mut events: MessageReader<SculptEvent>,
-    mut edit_mode: ResMut<EditModeState>,
+/// Partition the target into chunks and start sculpting it
+#[allow(clippy::too_many_arguments)]
+fn enter_sculpt_mode(
+    In(target): In<Option<Entity>>,
     mut sculpt_state: ResMut<SculptState>,
     mut sculpting_data: ResMut<SculptingData>,
     mut outbound: ResMut<OutboundUiMessages>,
-    mesh_query: Query<(&Mesh3d, &GlobalTransform)>,
+    mesh_query: Query<(
+        &Mesh3d,
+        Option<&BaseMesh>,
+        &GlobalTransform,
+        Option<&Visibility>,
+    )>,
     meshes: Res<Assets<Mesh>>,
     material_query: Query<&MeshMaterial3d<StandardMaterial>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut commands: Commands,
-    time: Res<Time>,
 ) {
-    for event in events.read() {
-        match event {
-            SculptEvent::Enter { entity } => {
-                // Enter sculpt mode
-                edit_mode.mode = EditMode::Sculpt;
-                edit_mode.target_entity = Some(*entity);
-                sculpt_state.active = true;
-                sculpt_state.target_entity = Some(*entity);
-                sculpt_state.deformation_type = DeformationType::Push;
-
-                info!("Entered sculpt mode for entity {:?}", entity);
-
-                // Enable double-sided rendering for the sculpted mesh
-                if let Ok(material_handle) = material_query.get(*entity) {
-                    if let Some(material) = materials.get_mut(&material_handle.0) {
-                        material.double_sided = true;
-                        material.cull_mode = None;
-                        info!("Enabled double-sided rendering for sculpt target");
-                    }
-                }
+    let Some(entity) = target else {
+        return;
+    };
 
-                // Initialize chunked mesh from entity
-                if let Ok((mesh_handle, global_transform)) = mesh_query.get(*entity) {
-                    // Store transforms for coordinate conversion
-                    let affine = global_transform.affine();
-                    sculpting_data.inverse_transform = Some(affine.inverse());
-                    sculpting_data.transform_rotation = Some(global_transform.rotation());
-                    sculpting_data.model_matrix = Some(global_transform.to_matrix());
-
-                    if let Some(bevy_mesh) = meshes.get(&mesh_handle.0) {
-                        match HalfEdgeMesh::from_bevy_mesh(bevy_mesh) {
-                            Ok(he_mesh) => {
-                                // Partition into chunks
-                                let partition_config =
-                                    sculpting::PartitionConfig::from(&sculpt_state.chunk_config);
-                                let chunked_mesh = partition_mesh(&he_mesh, &partition_config);
-
-                                info!(
-                                    "Created {} chunks from mesh with {} faces",
-                                    chunked_mesh.chunk_count(),
-                                    chunked_mesh.total_face_count()
-                                );
-
-                                // Create pipeline
-                                let mut preset = BrushPreset::push();
-                                preset.radius = sculpt_state.brush_radius;
-                                preset.strength = sculpt_state.brush_strength;
-                                preset.hardness = sculpt_state.brush_hardness;
-                                preset.falloff = sculpt_state.brush_falloff;
-
-                                // Enable tessellation for dynamic topology
-                                let pipeline_config = PipelineConfig {
-                                    tessellation_enabled: true,
-                                    tessellation_config: sculpt_state.tessellation_config.clone(),
-                                    chunk_config: sculpt_state.chunk_config.clone(),
-                                    rebalance_after_stroke: true,
-                                };
-
-                                let pipeline =
-                                    SculptingPipeline::with_config(preset, pipeline_config);
-
-                                sculpting_data.chunked_mesh = Some(chunked_mesh);
-                                sculpting_data.pipeline = Some(pipeline);
-                                sculpting_data.original_mesh_handle = Some(mesh_handle.0.clone());
-                                sculpting_data.mesh_id = entity.index().index();
-                            }
-                            Err(e) => {
-                                warn!("Failed to convert mesh to half-edge: {:?}", e);
-                            }
-                        }
-                    }
-                }
+    sculpt_state.active = true;
+    sculpt_state.target_entity = Some(entity);
+    sculpt_state.deformation_type = DeformationType::Push;
 
-                // Notify UI
-                outbound.send(BevyToUi::EditModeChanged {
-                    mode: EditMode::Sculpt,
-                });
-            }
-            SculptEvent::Exit => {
-                info!("Exited sculpt mode");
+    info!("Entered sculpt mode for entity {:?}", entity);
+
+    // Enable double-sided rendering for the sculpted mesh
+    if let Ok(material_handle) = material_query.get(entity) {
+        sculpting_data.chunk_material = Some(material_handle.0.clone());
+        if let Some(material) = materials.get_mut(&material_handle.0) {
+            material.double_sided = true;
+            material.cull_mode = None;
+            info!("Enabled double-sided rendering for sculpt target");
+        }
+    }
+
+    // Initialize chunked mesh from entity
+    if let Ok((mesh3d, base, global_transform, visibility)) = mesh_query.get(entity) {
+        // Sculpt the base mesh, not a subdivision preview
+        let mesh_handle = base_mesh_handle(mesh3d, base);
+        // Store transforms for coordinate conversion
+        let affine = global_transform.affine();
+        sculpting_data.inverse_transform = Some(affine.inverse());
+        sculpting_data.transform_rotation = Some(global_transform.rotation());
+        sculpting_data.model_matrix = Some(global_transform.to_matrix());
+
+        if let Some(bevy_mesh) = meshes.get(mesh_handle) {
+            match HalfEdgeMesh::from_bevy_mesh(bevy_mesh) {
+                Ok(he_mesh) => {
+                    // Partition into chunks
+                    let partition_config =
+                        sculpting::PartitionConfig::from(&sculpt_state.chunk_config);
+                    let chunked_mesh = partition_mesh(&he_mesh, &partition_config);
 
-                // Merge chunks back and update original mesh
-                if let Some(chunked_mesh) = sculpting_data.chunked_mesh.take() {
-                    let merged = sculpting::merge_chunks(&chunked_mesh);
                     info!(
-                        "Merged {} chunks back into single mesh with {} faces",
+                        "Created {} chunks from mesh with {} faces",
                         chunked_mesh.chunk_count(),
-                        merged.mesh.face_count()
+                        chunked_mesh.total_face_count()
                     );
-                    // TODO: Update the original mesh entity with merged.mesh
-                }
 
-                // Cleanup
-                sculpting_data.pipeline = None;
-                sculpting_data.original_mesh_handle = None;
-                sculpting_data.inverse_transform = None;
-                sculpting_data.transform_rotation = None;
-                sculpting_data.model_matrix = None;
-                sculpting_data.cached_vertex_mapping = None;
-
-                // Remove chunk entities
-                for entity in sculpting_data.chunk_entities.drain(..) {
-                    commands.entity(entity).despawn();
+                    // Create pipeline
+                    let mut preset = BrushPreset::push();
+                    preset.radius = sculpt_state.brush_radius;
+                    preset.strength = sculpt_state.brush_strength;
+                    preset.spacing = sculpt_state.brush_spacing;
+                    preset.flow = sculpt_state.brush_flow;
+                    preset.hardness = sculpt_state.brush_hardness;
+                    preset.falloff = sculpt_state.brush_falloff;
+
+                    let pipeline =
+                        SculptingPipeline::with_config(preset, sculpt_state.pipeline_config());
+
+                    sculpting_data.chunked_mesh = Some(chunked_mesh);
+                    sculpting_data.pipeline = Some(pipeline);
+                    sculpting_data.original_mesh_handle = Some(mesh_handle.clone());
+                    sculpting_data.mesh_id = entity.index().index();
+
+                    // Chunk entities draw the sculpt until exit
+                    sculpting_data.target_visibility =
+                        Some(visibility.copied().unwrap_or_default());
+                    commands.entity(entity).insert(Visibility::Hidden);
+                }
+                Err(e) => {
+                    warn!("Failed to convert mesh to half-edge: {:?}", e);
                 }
+            }
+        }
+    }
 
-                edit_mode.mode = EditMode::None;
-                edit_mode.target_entity = None;
-                sculpt_state.active = false;
-                sculpt_state.target_entity = None;
-                sculpt_state.current_stroke_id = None;
-                sculpt_state.last_world_pos = None;
+    // Notify UI
+    outbound.send(sculpt_state.settings_message());
+}
 
-                // Notify UI
-                outbound.send(BevyToUi::EditModeChanged {
-                    mode: EditMode::None,
-                });
-            }
+/// Merge the chunks back into the target's mesh and clean up (the brush
+/// cursor hides with `SculptState::active`)
+fn exit_sculpt_mode(
+    mut sculpt_state: ResMut<SculptState>,
+    mut sculpting_data: ResMut<SculptingData>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+    mut base_mesh_changes: MessageWriter<BaseMeshChanged>,
+) {
+    // Finish the stroke in progress, so it lands before the chunks merge
+    if let Some(stroke_id) = sculpt_state.current_stroke_id.take() {
+        info!(
+            "Sculpt stroke ended by leaving sculpt mode: id={}",
+            stroke_id
+        );
+        let SculptingData {
+            ref mut pipeline,
+            ref mut chunked_mesh,
+            ..
+        } = *sculpting_data;
+        if let (Some(pipeline), Some(chunked_mesh)) = (pipeline.as_mut(), chunked_mesh.as_mut()) {
+            pipeline.end_stroke(chunked_mesh);
+        }
+    }
+
+    info!("Exited sculpt mode");
+
+    // Merge chunks back and update original mesh
+    if let Some(chunked_mesh) = sculpting_data.chunked_mesh.take() {
+        let merged = sculpting::merge_chunks(&chunked_mesh);
+        info!(
+            "Merged {} chunks back into single mesh with {} faces",
+            chunked_mesh.chunk_count(),
+            merged.mesh.face_count()
+        );
+        if let Some(original) = &sculpting_data.original_mesh_handle
+            && let Some(bevy_mesh) = meshes.get_mut(original)
+            && let Some(new_mesh) = half_edge_to_bevy_mesh(&merged.mesh)
+        {
+            *bevy_mesh = new_mesh;
+        }
+    }
+
+    if let Some(entity) = sculpt_state.target_entity {
+        if let Some(visibility) = sculpting_data.target_visibility.take() {
+            commands.entity(entity).insert(visibility);
+        }
+        base_mesh_changes.write(BaseMeshChanged(entity));
+    }
+
+    // Cleanup
+    sculpting_data.pipeline = None;
+    sculpting_data.original_mesh_handle = None;
+    sculpting_data.chunk_material = None;
+    sculpting_data.inverse_transform = None;
+    sculpting_data.transform_rotation = None;
+    sculpting_data.model_matrix = None;
+    // Dropping the task cancels it
+    sculpting_data.remesh_job = None;
+    sculpting_data.mesh_doctor.cancel();
+
+    // Remove chunk entities and their meshes
+    for (_, entity) in sculpting_data.chunk_entities.drain() {
+        commands.entity(entity).despawn();
+    }
+    for (_, chunk_meshes) in sculpting_data.chunk_meshes.drain() {
+        chunk_meshes.remove(&mut meshes);
+    }
+
+    sculpt_state.active = false;
+    sculpt_state.target_entity = None;
+    sculpt_state.current_stroke_id = None;
+    sculpt_state.last_world_pos = None;
+}
+
+/// Handle sculpt mode events
+fn handle_sculpt_events(
+    mut events: MessageReader<SculptEvent>,
+    mut sculpt_state: ResMut<SculptState>,
+    mut sculpting_data: ResMut<SculptingData>,
+    mut outbound: ResMut<OutboundUiMessages>,
+    time: Res<Time>,
+) {
+    for event in events.read() {
+        match event {
             SculptEvent::SetDeformationType(deformation_type) => {
                 sculpt_state.deformation_type = *deformation_type;
 
@@ -897,6 +1130,30 @@ fn handle_sculpt_events(
 
                 info!("Set deformation type to {:?}", deformation_type);
             }
+            SculptEvent::SelectBrushPreset(preset_id) => {
+                let Some(mut preset) = sculpting::brush::builtin_presets()
+                    .into_iter()
+                    .nth(*preset_id as usize)
+                else {
+                    warn!("Unknown sculpt brush preset id={}", preset_id);
+                    continue;
+                };
+
+                sculpt_state.deformation_type = preset.deformation_type;
+                sculpt_state.brush_strength = preset.strength;
+                sculpt_state.brush_spacing = preset.spacing;
+                sculpt_state.brush_flow = preset.flow;
+                sculpt_state.brush_hardness = preset.hardness;
+                sculpt_state.brush_falloff = preset.falloff;
+
+                // Update pipeline preset
+                if let Some(pipeline) = &mut sculpting_data.pipeline {
+                    preset.radius = sculpt_state.brush_radius;
+                    pipeline.set_brush_preset(preset.clone());
+                }
+
+                info!("Selected sculpt brush preset: {}", preset.name);
+            }
             SculptEvent::SetBrushRadius(radius) => {
                 sculpt_state.brush_radius = radius.max(0.01);
 
@@ -921,11 +1178,142 @@ fn handle_sculpt_events(
 
                 info!("Set brush strength to {}", sculpt_state.brush_strength);
             }
+            SculptEvent::SetBrushSpacing(spacing) => {
+                sculpt_state.brush_spacing = spacing.max(0.0);
+
+                // Update pipeline preset
+                if let Some(pipeline) = &mut sculpting_data.pipeline {
+                    let mut preset = pipeline.brush_preset().clone();
+                    preset.spacing = sculpt_state.brush_spacing;
+                    pipeline.set_brush_preset(preset);
+                }
+
+                info!("Set brush spacing to {}", sculpt_state.brush_spacing);
+            }
+            SculptEvent::SetFlow(flow) => {
+                sculpt_state.brush_flow = flow.clamp(0.0, 1.0);
+
+                // Update pipeline preset
+                if let Some(pipeline) = &mut sculpting_data.pipeline {
+                    let mut preset = pipeline.brush_preset().clone();
+                    preset.flow = sculpt_state.brush_flow;
+                    pipeline.set_brush_preset(preset);
+                }
+
+                info!("Set brush flow to {}", sculpt_state.brush_flow);
+            }
+            SculptEvent::SetDynamicTopology(enabled) => {
+                sculpt_state.dynamic_topology = *enabled;
+                if let Some(pipeline) = &mut sculpting_data.pipeline {
+                    pipeline.set_config(sculpt_state.pipeline_config());
+                }
+
+                info!("Set dynamic topology to {}", enabled);
+            }
+            SculptEvent::SetDetailMode(mode) => {
+                sculpt_state.tessellation_config.mode = *mode;
+                if let Some(pipeline) = &mut sculpting_data.pipeline {
+                    pipeline.set_config(sculpt_state.pipeline_config());
+                }
+
+                info!("Set sculpt detail mode to {:?}", mode);
+            }
+            SculptEvent::SetDetailSize(size) => {
+                sculpt_state.set_detail_size(*size);
+                if let Some(pipeline) = &mut sculpting_data.pipeline {
+                    pipeline.set_config(sculpt_state.pipeline_config());
+                }
+
+                info!("Set sculpt detail size to {}", sculpt_state.detail_size());
+            }
+            SculptEvent::SetVertexBudget(max_vertices) => {
+                sculpt_state.tessellation_config.max_vertices = *max_vertices;
+                if let Some(pipeline) = &mut sculpting_data.pipeline {
+                    pipeline.set_config(sculpt_state.pipeline_config());
+                }
+
+                info!("Set sculpt vertex budget to {:?}", max_vertices);
+            }
+            SculptEvent::Remesh { target_edge_length } => {
+                if sculpting_data.remesh_job.is_some() {
+                    warn!("Ignoring remesh request: a remesh is already running");
+                    continue;
+                }
+                if sculpt_state.current_stroke_id.is_some() {
+                    warn!("Ignoring remesh request during a stroke");
+                    continue;
+                }
+                let Some(chunked_mesh) = &sculpting_data.chunked_mesh else {
+                    warn!("Ignoring remesh request: no mesh is being sculpted");
+                    continue;
+                };
+
+                let mut mesh = sculpting::merge_chunks(chunked_mesh).mesh;
+                let config = RemeshConfig::with_target_edge_length(*target_edge_length);
+                let progress = Arc::new(RemeshProgress::new());
+                let task_progress = progress.clone();
+                let task = AsyncComputeTaskPool::get().spawn(async move {
+                    remesh_uniform(&mut mesh, &config, &task_progress).map(|stats| (mesh, stats))
+                });
+
+                info!(
+                    "Started sculpt remesh with target edge length {}",
+                    target_edge_length
+                );
+                sculpting_data.remesh_job = Some(RemeshJob {
+                    task,
+                    progress,
+                    last_reported: 0.0,
+                });
+                outbound.send(BevyToUi::SculptRemeshProgress { progress: 0.0 });
+            }
+            SculptEvent::CancelRemesh => {
+                if let Some(job) = &sculpting_data.remesh_job {
+                    info!("Cancelling sculpt remesh");
+                    job.progress.cancel();
+                }
+            }
+            SculptEvent::ClearMask => {
+                if let Some(chunked_mesh) = &mut sculpting_data.chunked_mesh {
+                    chunked_mesh.clear_mask();
+                    info!("Cleared sculpt mask");
+                }
+            }
+            SculptEvent::InvertMask => {
+                if let Some(chunked_mesh) = &mut sculpting_data.chunked_mesh {
+                    chunked_mesh.invert_mask();
+                    info!("Inverted sculpt mask");
+                }
+            }
+            SculptEvent::ValidateMesh => {
+                let SculptingData {
+                    ref chunked_mesh,
+                    ref mut mesh_doctor,
+                    ..
+                } = *sculpting_data;
+                if let Some(chunked_mesh) = chunked_mesh {
+                    info!("Validating sculpt mesh");
+                    mesh_doctor.start_audit(chunked_mesh);
+                }
+            }
+            SculptEvent::SetAutoValidate(every_strokes) => {
+                sculpting_data.mesh_doctor.config.auto_every_strokes = *every_strokes;
+                info!(
+                    "Set sculpt auto-validate to every {} strokes",
+                    every_strokes
+                );
+            }
             SculptEvent::StrokeStart {
                 world_pos,
                 normal,
                 stroke_id,
             } => {
+                // The remesh result replaces the mesh, which would drop this stroke
+                if sculpting_data.remesh_job.is_some() {
+                    debug!("Ignoring sculpt stroke while remeshing");
+                    continue;
+                }
+
                 sculpt_state.current_stroke_id = Some(*stroke_id);
                 sculpt_state.last_world_pos = Some(*world_pos);
                 sculpt_state.last_time = time.elapsed_secs_f64();
@@ -1037,6 +1425,7 @@ fn handle_sculpt_events(
                     let SculptingData {
                         ref mut pipeline,
                         ref mut chunked_mesh,
+                        ref mut mesh_doctor,
                         ..
                     } = *sculpting_data;
 
@@ -1051,6 +1440,9 @@ fn handle_sculpt_events(
                                 result.chunks_split, result.chunks_merged
                             );
                         }
+                        if mesh_doctor.stroke_finished(chunked_mesh) {
+                            debug!("Started automatic sculpt mesh validation");
+                        }
                     }
                 }
 
@@ -1072,34 +1464,232 @@ fn handle_sculpt_events(
     }
 }
 
-/// Sync dirty chunks to GPU.
+/// Report remesh progress and swap in the result once the task finishes.
 ///
-/// Two paths:
-/// - **Topology changed**: full merge + rebuild Bevy mesh (expensive, O(V+F))
-/// - **Position only**: patch vertex positions/normals in-place (cheap, O(dirty vertices))
-fn sync_sculpt_chunks_to_gpu(
+/// The remeshed mesh is re-partitioned into fresh chunks, so every chunk is
+/// rebuilt on the next GPU sync. The target entity keeps its `PaintableMesh`
+/// component, but paint stored per vertex or per ptex face no longer lines
+/// up with the new layout and needs reprojection; the UI is warned about it.
+fn poll_sculpt_remesh(
     sculpt_state: Res<SculptState>,
     mut sculpting_data: ResMut<SculptingData>,
-    _mesh_query: Query<&Mesh3d>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut outbound: ResMut<OutboundUiMessages>,
 ) {
-    if !sculpt_state.active {
+    let Some(job) = &mut sculpting_data.remesh_job else {
+        return;
+    };
+
+    let Some(result) = check_ready(&mut job.task) else {
+        let fraction = job.progress.fraction();
+        if fraction - job.last_reported >= 0.01 {
+            job.last_reported = fraction;
+            outbound.send(BevyToUi::SculptRemeshProgress { progress: fraction });
+        }
         return;
+    };
+    sculpting_data.remesh_job = None;
+
+    let (mesh, stats) = match result {
+        Ok(remeshed) => remeshed,
+        Err(e) => {
+            if e == RemeshError::Cancelled {
+                info!("Sculpt remesh cancelled");
+            } else {
+                warn!("Sculpt remesh failed: {}", e);
+                outbound.send(BevyToUi::Error {
+                    code: "sculpt_remesh_failed".to_string(),
+                    message: format!("Remesh failed: {}", e),
+                });
+            }
+
+            // The sculpt is unchanged; report its current size
+            let (vertex_count, face_count) = sculpting_data
+                .chunked_mesh
+                .as_ref()
+                .map_or((0, 0), |chunked| {
+                    (chunked.total_vertex_count(), chunked.total_face_count())
+                });
+            outbound.send(BevyToUi::SculptRemeshFinished {
+                cancelled: true,
+                vertex_count: vertex_count as u32,
+                face_count: face_count as u32,
+            });
+            return;
+        }
+    };
+
+    let partition_config = sculpting::PartitionConfig::from(&sculpt_state.chunk_config);
+    let mut chunked_mesh = partition_mesh(&mesh, &partition_config);
+    for chunk in chunked_mesh.chunks.values_mut() {
+        chunk.mark_topology_changed();
+    }
+
+    info!(
+        "Remeshed sculpt in {} iterations: {} verts, {} faces in {} chunks",
+        stats.iterations,
+        stats.vertex_count,
+        stats.face_count,
+        chunked_mesh.chunk_count()
+    );
+
+    sculpting_data.chunked_mesh = Some(chunked_mesh);
+    sculpting_data.mesh_doctor.cancel();
+    if let Some(pipeline) = &mut sculpting_data.pipeline {
+        pipeline.invalidate_caches();
     }
 
-    let Some(_target_entity) = sculpt_state.target_entity else {
+    outbound.send(BevyToUi::Warning {
+        code: "sculpt_remesh_paint".to_string(),
+        message: "Remesh changed the mesh layout; existing paint needs to be reprojected"
+            .to_string(),
+    });
+    outbound.send(BevyToUi::SculptRemeshFinished {
+        cancelled: false,
+        vertex_count: stats.vertex_count as u32,
+        face_count: stats.face_count as u32,
+    });
+}
+
+/// Advance a running mesh audit by one frame's budget and report the result.
+///
+/// Paused while a stroke or remesh is running, so it never competes with
+/// them for frame time. Repairs renumber chunk vertices, which invalidates
+/// the pipeline's per-chunk caches.
+fn run_mesh_doctor(
+    sculpt_state: Res<SculptState>,
+    mut sculpting_data: ResMut<SculptingData>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    if sculpt_state.current_stroke_id.is_some() || sculpting_data.remesh_job.is_some() {
+        return;
+    }
+
+    let SculptingData {
+        ref mut chunked_mesh,
+        ref mut mesh_doctor,
+        ref mut pipeline,
+        ..
+    } = *sculpting_data;
+    let Some(chunked_mesh) = chunked_mesh else {
         return;
     };
+    let Some(report) = mesh_doctor.step(chunked_mesh) else {
+        return;
+    };
+
+    if report.repaired > 0
+        && let Some(pipeline) = pipeline
+    {
+        pipeline.invalidate_caches();
+    }
+    info!(
+        "Sculpt mesh validated: {} issues, {} repaired",
+        report.errors.len(),
+        report.repaired
+    );
+    outbound.send(health_report_message(&report));
+}
+
+fn health_report_message(report: &MeshHealthReport) -> BevyToUi {
+    BevyToUi::MeshHealthReport {
+        errors: report.errors.iter().map(ToString::to_string).collect(),
+        repaired: report.repaired as u32,
+        chunk_stats: report
+            .chunk_stats
+            .iter()
+            .map(|stats| SculptChunkStats {
+                chunk_id: stats.chunk_id.0,
+                vertex_count: stats.vertices as u32,
+                face_count: stats.faces as u32,
+                issue_count: stats.issues as u32,
+            })
+            .collect(),
+    }
+}
+
+/// How far past the brush radius chunks are kept at full detail during a stroke
+const BRUSH_DETAIL_MARGIN: f32 = 1.5;
 
-    // Get the mesh handle first (clone to avoid borrow issues)
-    let Some(original_handle) = sculpting_data.original_mesh_handle.clone() else {
+/// Pick full or reduced detail for each chunk from its projected size.
+///
+/// Chunks near the brush during a stroke always stay at full detail, so the
+/// area being sculpted is never drawn from a decimated copy.
+fn select_sculpt_chunk_detail(
+    sculpt_state: Res<SculptState>,
+    mut sculpting_data: ResMut<SculptingData>,
+) {
+    if !sculpt_state.active {
+        return;
+    }
+
+    let SculptingData {
+        ref chunked_mesh,
+        ref pipeline,
+        ref mut chunk_meshes,
+        ref inverse_transform,
+        ref lod,
+        ..
+    } = *sculpting_data;
+    let (Some(chunked_mesh), Some(pipeline)) = (chunked_mesh, pipeline) else {
         return;
     };
 
+    // The brush radius is in the mesh's local space, like the chunk bounds
+    let brush_chunks: HashSet<ChunkId> = sculpt_state
+        .last_world_pos
+        .filter(|_| sculpt_state.current_stroke_id.is_some())
+        .map(|world_pos| {
+            let local_pos =
+                inverse_transform.map_or(world_pos, |inv| inv.transform_point3(world_pos));
+            chunked_mesh.chunks_intersecting_sphere(
+                local_pos,
+                sculpt_state.brush_radius * BRUSH_DETAIL_MARGIN,
+            )
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    for (chunk_id, chunk) in &chunked_mesh.chunks {
+        let Some(meshes) = chunk_meshes.get_mut(chunk_id) else {
+            continue;
+        };
+        meshes.detail = if brush_chunks.contains(chunk_id) {
+            ChunkDetail::Full
+        } else {
+            let size = projected_size(&chunk.bounds, pipeline.screen_config());
+            meshes.detail.select(size, lod)
+        };
+    }
+}
+
+/// Sync dirty chunks to their GPU meshes and keep one entity per chunk.
+///
+/// Chunk meshes are patched in place when only positions changed and rebuilt
+/// when topology changed (see [`sculpting::sync_chunks_to_gpu`]). Each chunk
+/// entity draws its chunk's full or reduced mesh, whichever is selected and
+/// ready. Entities come and go with chunks, e.g. after rebalancing or a remesh.
+fn sync_sculpt_chunks_to_gpu(
+    sculpt_state: Res<SculptState>,
+    mut sculpting_data: ResMut<SculptingData>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut patches: ResMut<MeshVertexPatches>,
+    mut chunk_query: Query<&mut Mesh3d>,
+    mut commands: Commands,
+) {
+    if !sculpt_state.active {
+        return;
+    }
+
     // Destructure to allow split borrows
     let SculptingData {
         ref mut chunked_mesh,
-        ref mut cached_vertex_mapping,
+        ref mut chunk_meshes,
+        ref mut chunk_entities,
+        ref chunk_material,
+        ref model_matrix,
+        ref lod,
+        ref mut last_gpu_sync,
         ..
     } = *sculpting_data;
 
@@ -1107,102 +1697,66 @@ fn sync_sculpt_chunks_to_gpu(
         return;
     };
 
-    let dirty_chunks = chunked_mesh.dirty_chunks();
-    if dirty_chunks.is_empty() {
-        return;
+    // Deformation moves vertices without updating bounds, which LOD selection
+    // and brush ray casts rely on
+    for chunk in chunked_mesh.chunks.values_mut() {
+        if chunk.dirty {
+            chunk.recalculate_bounds();
+        }
     }
 
-    // Check if any chunk has topology changes (splits/collapses/rebalancing)
-    let has_topology_change = dirty_chunks.iter().any(|&id| {
-        chunked_mesh
-            .get_chunk(id)
-            .map_or(false, |c| c.topology_changed)
-    });
-
-    if has_topology_change || cached_vertex_mapping.is_none() {
-        // Full rebuild path: merge all chunks and rebuild Bevy mesh
-        debug!(
-            "sync_sculpt_to_gpu: merging chunks (topology_changed={}, cached={})",
-            has_topology_change,
-            cached_vertex_mapping.is_some()
-        );
-        let merge_start = std::time::Instant::now();
-        let merged = sculpting::merge_chunks(chunked_mesh);
-        debug!(
-            "sync_sculpt_to_gpu: merge done in {:?} ({} faces)",
-            merge_start.elapsed(),
-            merged.mesh.face_count()
-        );
+    let result =
+        sculpting::sync_chunks_to_gpu(chunked_mesh, &mut meshes, chunk_meshes, &mut patches, lod);
 
-        if let Some(bevy_mesh) = meshes.get_mut(&original_handle) {
-            if let Some(new_mesh) = half_edge_to_bevy_mesh(&merged.mesh) {
-                *bevy_mesh = new_mesh;
-            }
+    chunk_entities.retain(|chunk_id, entity| {
+        let exists = chunk_meshes.contains_key(chunk_id);
+        if !exists {
+            commands.entity(*entity).despawn();
         }
+        exists
+    });
 
-        // Cache the vertex mapping for future position-only updates
-        *cached_vertex_mapping = Some(merged.vertex_mapping);
-    } else if let Some(mapping) = cached_vertex_mapping {
-        // Position-only path: patch vertex buffers in-place
-        if let Some(bevy_mesh) = meshes.get_mut(&original_handle) {
-            // Patch positions
-            if let Some(VertexAttributeValues::Float32x3(positions)) =
-                bevy_mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+    let transform = Transform::from_matrix(model_matrix.unwrap_or(Mat4::IDENTITY));
+    for (chunk_id, chunk_mesh) in chunk_meshes.iter() {
+        let shown = chunk_mesh.shown();
+        if let Some(&entity) = chunk_entities.get(chunk_id) {
+            if let Ok(mut mesh3d) = chunk_query.get_mut(entity)
+                && mesh3d.0 != *shown
             {
-                let num_positions = positions.len();
-                for &chunk_id in &dirty_chunks {
-                    let Some(chunk) = chunked_mesh.get_chunk(chunk_id) else {
-                        continue;
-                    };
-                    for vertex in chunk.mesh.vertices() {
-                        if let Some(&original_id) = chunk.local_to_original.get(&vertex.id) {
-                            if let Some(&unified_id) = mapping.get(&original_id) {
-                                let idx = unified_id.0 as usize;
-                                if idx < num_positions {
-                                    positions[idx] = vertex.position.to_array();
-                                }
-                            }
-                        }
-                    }
-                }
+                mesh3d.0 = shown.clone();
             }
+            continue;
+        }
 
-            // Patch normals
-            if let Some(VertexAttributeValues::Float32x3(normals)) =
-                bevy_mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL)
-            {
-                let num_normals = normals.len();
-                for &chunk_id in &dirty_chunks {
-                    let Some(chunk) = chunked_mesh.get_chunk(chunk_id) else {
-                        continue;
-                    };
-                    for vertex in chunk.mesh.vertices() {
-                        if let Some(&original_id) = chunk.local_to_original.get(&vertex.id) {
-                            if let Some(&unified_id) = mapping.get(&original_id) {
-                                let idx = unified_id.0 as usize;
-                                if idx < num_normals {
-                                    normals[idx] = vertex.normal.to_array();
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        // Selection picks the sculpted object, never its chunks
+        let mut entity = commands.spawn((
+            Name::new(format!("Sculpt chunk {}", chunk_id.0)),
+            Mesh3d(shown.clone()),
+            transform,
+            Pickable::IGNORE,
+        ));
+        if let Some(material) = chunk_material {
+            entity.insert(MeshMaterial3d(material.clone()));
         }
+        chunk_entities.insert(*chunk_id, entity.id());
     }
 
-    // Clear dirty flags
-    for chunk_id in dirty_chunks {
-        if let Some(chunk) = chunked_mesh.get_chunk_mut(chunk_id) {
-            chunk.clear_dirty();
-        }
+    if result.chunks_rebuilt + result.chunks_patched + result.reduced_rebuilt == 0 {
+        return;
     }
+
+    trace!(
+        "sync_sculpt_to_gpu: {} vertices patched, {} rebuilt, {} reduced copies rebuilt",
+        result.vertices_updated, result.vertices_rebuilt, result.reduced_rebuilt
+    );
+    *last_gpu_sync = result;
 }
 
 /// Convert a HalfEdgeMesh to a Bevy Mesh
 fn half_edge_to_bevy_mesh(he_mesh: &HalfEdgeMesh) -> Option<Mesh> {
     let mut positions: Vec<[f32; 3]> = Vec::new();
     let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut colors: Vec<[f32; 4]> = Vec::new();
     let mut indices: Vec<u32> = Vec::new();
 
     // Validate: check that vertex IDs match array indices
@@ -1221,6 +1775,7 @@ fn half_edge_to_bevy_mesh(he_mesh: &HalfEdgeMesh) -> Option<Mesh> {
     for vertex in he_mesh.vertices() {
         positions.push(vertex.position.to_array());
         normals.push(vertex.normal.to_array());
+        colors.push(vertex.color.to_array());
     }
 
     let num_positions = positions.len() as u32;
@@ -1277,6 +1832,8 @@ fn half_edge_to_bevy_mesh(he_mesh: &HalfEdgeMesh) -> Option<Mesh> {
     );
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    // Painted vertex colors; the mask overlay only shows on the sculpt chunks
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
     mesh.insert_indices(Indices::U32(indices));
 
     Some(mesh)
@@ -1289,10 +1846,12 @@ fn render_sculpt_brush_gizmo(
     mut gizmos: Gizmos,
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     windows: Query<&Window, With<PrimaryWindow>>,
-    mesh_query: Query<(&Mesh3d, &GlobalTransform)>,
-    meshes: Res<Assets<Mesh>>,
+    transform_query: Query<&GlobalTransform>,
+    sculpting_data: Res<SculptingData>,
+    navigation: Res<BrushNavigation>,
 ) {
-    if !sculpt_state.active {
+    // Hidden while navigating
+    if !sculpt_state.active || navigation.navigating {
         return;
     }
 
@@ -1312,11 +1871,11 @@ fn render_sculpt_brush_gizmo(
         return;
     };
 
-    let Ok((mesh_handle, mesh_transform)) = mesh_query.get(target_entity) else {
+    let Ok(mesh_transform) = transform_query.get(target_entity) else {
         return;
     };
 
-    let Some(mesh) = meshes.get(&mesh_handle.0) else {
+    let Some(chunked_mesh) = &sculpting_data.chunked_mesh else {
         return;
     };
 
@@ -1324,7 +1883,8 @@ fn render_sculpt_brush_gizmo(
         return;
     };
 
-    let Some((world_pos, normal)) = ray_mesh_intersection_simple(&ray, mesh, mesh_transform) else {
+    let Some((world_pos, normal)) = ray_chunks_intersection(&ray, chunked_mesh, mesh_transform)
+    else {
         return;
     };
 
@@ -1,13 +1,25 @@
 //! Object selection system
 //!
 //! Provides click-to-select functionality for 3D objects.
-//! Uses Bevy's built-in MeshPickingPlugin for raycasting.
+//! Picking hits come from a `bevy_picking` backend that raycasts the
+//! [`SceneBvh`] instead of testing every triangle of every mesh.
 //! Outline rendering is handled by the separate outline module.
+//! `ObjectCommand::Select`/`Deselect` select by ID from the UI, which also
+//! covers objects without a mesh to click, like lights. Hidden and
+//! [`Locked`] objects can't be selected; clicks pass through locked ones.
 
+use bevy::camera::visibility::RenderLayers;
+use bevy::picking::PickingSystems;
+use bevy::picking::backend::ray::RayMap;
+use bevy::picking::backend::{HitData, PointerHits};
 use bevy::picking::prelude::*;
 use bevy::prelude::*;
+use pentimento_ipc::ObjectCommand;
 
+use crate::ObjectCommandEvent;
+use crate::measure::MeasureState;
 use crate::paint_mode::PaintMode;
+use crate::scene_bvh::{RaycastOptions, SceneBvh};
 
 /// Marker component for selectable objects
 #[derive(Component)]
@@ -20,6 +32,17 @@ pub struct Selectable {
 #[derive(Component)]
 pub struct Selected;
 
+/// Marker for pickable entities whose clicks are handled outside the scene
+/// (e.g. a UI drawn on a quad): clicking one neither selects it nor clears
+/// the selection
+#[derive(Component)]
+pub struct IgnoreSelectionClicks;
+
+/// Marker for objects locked against selection, transforms, painting, and
+/// sculpting (`ObjectCommand::SetLocked`)
+#[derive(Component, Debug)]
+pub struct Locked;
+
 /// Resource tracking current selection
 #[derive(Resource, Default)]
 pub struct SelectionState {
@@ -32,13 +55,59 @@ pub struct SelectionPlugin;
 
 impl Plugin for SelectionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(MeshPickingPlugin)
-            .init_resource::<SelectionState>()
-            .add_systems(Update, handle_click_selection);
+        app.init_resource::<SelectionState>()
+            .add_systems(PreUpdate, update_bvh_hits.in_set(PickingSystems::Backend))
+            .add_systems(Update, (handle_click_selection, handle_select_commands));
+    }
+}
+
+/// Picking backend: report the nearest front face under each pointer ray
+///
+/// Mirrors Bevy's mesh picking backend: hidden, non-hoverable, and
+/// other-layer meshes are skipped, and back faces don't count. Locked
+/// meshes are skipped too, so clicks reach whatever is behind them.
+fn update_bvh_hits(
+    ray_map: Res<RayMap>,
+    bvh: Res<SceneBvh>,
+    cameras: Query<(&Camera, Option<&RenderLayers>)>,
+    pickables: Query<&Pickable>,
+    layers: Query<&RenderLayers>,
+    locked: Query<(), With<Locked>>,
+    mut hits_writer: MessageWriter<PointerHits>,
+) {
+    let options = RaycastOptions {
+        cull_backfaces: true,
+        ..default()
+    };
+    for (&ray_id, &ray) in ray_map.iter() {
+        let Ok((camera, camera_layers)) = cameras.get(ray_id.camera) else {
+            continue;
+        };
+        let camera_layers = camera_layers.cloned().unwrap_or_default();
+        let hit = bvh.raycast_with(ray, options, |entity| {
+            let entity_layers = layers.get(entity).cloned().unwrap_or_default();
+            camera_layers.intersects(&entity_layers)
+                && pickables.get(entity).map_or(true, |p| p.is_hoverable)
+                && !locked.contains(entity)
+        });
+        if let Some(hit) = hit {
+            let data = HitData::new(
+                ray_id.camera,
+                hit.distance,
+                Some(hit.position),
+                Some(hit.normal),
+            );
+            hits_writer.write(PointerHits::new(
+                ray_id.pointer,
+                vec![(hit.entity, data)],
+                camera.order as f32,
+            ));
+        }
     }
 }
 
 /// Handle click events for selection using Pointer events
+#[allow(clippy::too_many_arguments)]
 fn handle_click_selection(
     mut commands: Commands,
     key_input: Res<ButtonInput<KeyCode>>,
@@ -46,10 +115,12 @@ fn handle_click_selection(
     mut click_events: MessageReader<Pointer<Click>>,
     selected_query: Query<(Entity, &Selectable), With<Selected>>,
     all_selectable: Query<(Entity, &Selectable)>,
+    ignored: Query<(), With<IgnoreSelectionClicks>>,
     paint_mode: Res<PaintMode>,
+    measure: Res<MeasureState>,
 ) {
-    // Don't process selection clicks when in paint mode
-    if paint_mode.active {
+    // Don't process selection clicks when in paint mode or measuring
+    if paint_mode.active || measure.captures_input() {
         return;
     }
     let shift_held =
@@ -58,7 +129,7 @@ fn handle_click_selection(
     // Process click events from the picking system
     for event in click_events.read() {
         // Only handle left clicks
-        if event.button != PointerButton::Primary {
+        if event.button != PointerButton::Primary || ignored.contains(event.entity) {
             continue;
         }
 
@@ -101,3 +172,47 @@ fn handle_click_selection(
         }
     }
 }
+
+/// Select and deselect objects by ID from the UI
+fn handle_select_commands(
+    mut commands: Commands,
+    mut selection: ResMut<SelectionState>,
+    mut events: MessageReader<ObjectCommandEvent>,
+    all_selectable: Query<(
+        Entity,
+        &Selectable,
+        Has<Selected>,
+        Has<Locked>,
+        Option<&InheritedVisibility>,
+    )>,
+) {
+    for ObjectCommandEvent(command) in events.read() {
+        match command {
+            ObjectCommand::Select { ids } => {
+                // Replaces the selection, like a click without Shift
+                selection.selected_ids.clear();
+                for (entity, selectable, is_selected, locked, visibility) in all_selectable.iter() {
+                    // Objects without visibility (lights) are always selectable
+                    let selectable_now = !locked && visibility.is_none_or(|v| v.get());
+                    if selectable_now && ids.contains(&selectable.id) {
+                        if !is_selected {
+                            commands.entity(entity).insert(Selected);
+                        }
+                        selection.selected_ids.push(selectable.id.clone());
+                    } else if is_selected {
+                        commands.entity(entity).remove::<Selected>();
+                    }
+                }
+            }
+            ObjectCommand::Deselect { ids } => {
+                for (entity, selectable, is_selected, ..) in all_selectable.iter() {
+                    if is_selected && ids.contains(&selectable.id) {
+                        commands.entity(entity).remove::<Selected>();
+                    }
+                }
+                selection.selected_ids.retain(|id| !ids.contains(id));
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Subdivision surface preview
+//!
+//! `ObjectCommand::SetSubdivision` shows a Loop-subdivided copy of an
+//! object's mesh without touching the mesh itself: the editable mesh moves
+//! into `BaseMesh` and `Mesh3d` points at the preview. Subdivision runs on
+//! the async compute pool, results are cached per level, and the cache is
+//! dropped whenever the base mesh changes (mesh edit operations write the
+//! asset, sculpting reports finished strokes with `BaseMeshChanged`).
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use bevy::tasks::futures::check_ready;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use painting::half_edge::{HalfEdgeError, HalfEdgeMesh, loop_face_count, subdivide_loop};
+use pentimento_config::SettingsFile;
+use pentimento_ipc::{BevyToUi, ObjectCommand, SceneInfo};
+
+use crate::hierarchy::SceneObjects;
+use crate::measure::SceneMeasurements;
+use crate::projection_painting::MeshRaycastCache;
+use crate::scene_light::SceneLights;
+use crate::selection::Selectable;
+use crate::{ObjectCommandEvent, OutboundUiMessages};
+
+/// Highest subdivision level `SetSubdivision` accepts
+pub const MAX_SUBDIVISION_LEVELS: u8 = 4;
+
+/// Default for the most faces a subdivision preview may produce
+pub const DEFAULT_SUBDIVISION_FACE_BUDGET: usize = 1_000_000;
+
+/// Subdivision preview limits
+#[derive(Resource, Debug, Clone)]
+pub struct SubdivisionSettings {
+    /// Levels whose result would exceed this many faces are refused
+    pub face_budget: usize,
+}
+
+impl Default for SubdivisionSettings {
+    fn default() -> Self {
+        Self {
+            face_budget: DEFAULT_SUBDIVISION_FACE_BUDGET,
+        }
+    }
+}
+
+impl SubdivisionSettings {
+    /// Settings from the settings file, falling back to the default budget
+    pub fn from_settings(settings: &SettingsFile) -> Self {
+        Self {
+            face_budget: settings
+                .subdivision_face_budget
+                .unwrap_or(DEFAULT_SUBDIVISION_FACE_BUDGET),
+        }
+    }
+}
+
+/// The editable mesh of an object whose `Mesh3d` shows a subdivision preview.
+///
+/// Editing and sculpting work on this mesh, never on the preview.
+#[derive(Component, Debug, Clone)]
+pub struct BaseMesh(pub Handle<Mesh>);
+
+/// Subdivision modifier on a mesh object
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subdivision {
+    /// Preview levels (1 to `MAX_SUBDIVISION_LEVELS`)
+    pub levels: u8,
+}
+
+/// Tell the subdivision preview that an object's base mesh was changed
+/// without a tracked asset write (sculpt strokes patch vertices in place)
+#[derive(Message, Debug, Clone, Copy)]
+pub struct BaseMeshChanged(pub Entity);
+
+/// Preview meshes per level and the subdivision currently running
+#[derive(Component, Default)]
+struct SubdivisionCache {
+    meshes: HashMap<u8, Handle<Mesh>>,
+    pending: Option<PendingSubdivision>,
+}
+
+struct PendingSubdivision {
+    levels: u8,
+    /// The base mesh changed after the task started; its result is discarded
+    stale: bool,
+    task: Task<Result<Mesh, HalfEdgeError>>,
+}
+
+impl SubdivisionCache {
+    fn invalidate(&mut self) {
+        self.meshes.clear();
+        if let Some(pending) = &mut self.pending {
+            pending.stale = true;
+        }
+    }
+}
+
+/// Plugin for the subdivision surface preview
+pub struct SubdivisionPlugin;
+
+impl Plugin for SubdivisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SubdivisionSettings>()
+            .add_message::<BaseMeshChanged>()
+            .add_systems(Update, handle_subdivision_commands)
+            // After Update, so edits and sculpt syncs of this frame are in
+            // the base mesh before a new subdivision copies it
+            .add_systems(
+                PostUpdate,
+                (
+                    invalidate_subdivision_caches,
+                    update_subdivision_previews,
+                    report_subdivision_changes,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The mesh to edit for an object: its base mesh while a preview is shown
+pub(crate) fn base_mesh_handle<'a>(
+    mesh: &'a Mesh3d,
+    base: Option<&'a BaseMesh>,
+) -> &'a Handle<Mesh> {
+    base.map_or(&mesh.0, |base| &base.0)
+}
+
+/// Apply `ObjectCommand::SetSubdivision`
+#[allow(clippy::too_many_arguments)]
+fn handle_subdivision_commands(
+    mut commands: Commands,
+    mut events: MessageReader<ObjectCommandEvent>,
+    settings: Res<SubdivisionSettings>,
+    objects: Query<(Entity, &Selectable)>,
+    mut targets: Query<(&mut Mesh3d, Option<&BaseMesh>, Option<&mut Subdivision>)>,
+    meshes: Res<Assets<Mesh>>,
+    mut raycast_cache: ResMut<MeshRaycastCache>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for ObjectCommandEvent(command) in events.read() {
+        let ObjectCommand::SetSubdivision { id, levels } = command else {
+            continue;
+        };
+        let levels = *levels;
+        if levels > MAX_SUBDIVISION_LEVELS {
+            outbound.send_error(
+                "invalid_subdivision_levels",
+                format!(
+                    "Subdivision levels must be 0-{}, got {}",
+                    MAX_SUBDIVISION_LEVELS, levels
+                ),
+            );
+            continue;
+        }
+
+        let Some(entity) = objects
+            .iter()
+            .find(|(_, selectable)| selectable.id == *id)
+            .map(|(entity, _)| entity)
+        else {
+            outbound.send_error("object_not_found", format!("No object with ID {}", id));
+            continue;
+        };
+        let Ok((mut mesh3d, base, subdivision)) = targets.get_mut(entity) else {
+            outbound.send_error(
+                "subdivision_unsupported",
+                format!("Object {} has no mesh to subdivide", id),
+            );
+            continue;
+        };
+        let base_handle = base_mesh_handle(&mesh3d, base).clone();
+
+        if levels == 0 {
+            if base.is_some() {
+                mesh3d.0 = base_handle;
+                raycast_cache.invalidate(entity);
+                commands
+                    .entity(entity)
+                    .remove::<(BaseMesh, Subdivision, SubdivisionCache)>();
+                info!("Removed subdivision preview from {}", id);
+            }
+            continue;
+        }
+
+        // Performance guard: each level quadruples the face count
+        let Some(mesh) = meshes.get(&base_handle) else {
+            outbound.send_error(
+                "subdivision_unsupported",
+                format!("Mesh of object {} is not loaded", id),
+            );
+            continue;
+        };
+        let faces = mesh.indices().map_or(mesh.count_vertices(), |i| i.len()) / 3;
+        let subdivided = loop_face_count(faces, levels);
+        if subdivided > settings.face_budget {
+            outbound.send_error(
+                "subdivision_budget",
+                format!(
+                    "{} subdivision levels would turn {} faces into {}, over the budget of {}",
+                    levels, faces, subdivided, settings.face_budget
+                ),
+            );
+            continue;
+        }
+
+        match subdivision {
+            Some(mut subdivision) => {
+                subdivision.set_if_neq(Subdivision { levels });
+            }
+            None => {
+                commands.entity(entity).insert((
+                    BaseMesh(base_handle),
+                    Subdivision { levels },
+                    SubdivisionCache::default(),
+                ));
+            }
+        }
+        info!("Subdivision preview of {} set to {} levels", id, levels);
+    }
+}
+
+/// Drop cached previews of base meshes that changed
+fn invalidate_subdivision_caches(
+    mut asset_events: MessageReader<AssetEvent<Mesh>>,
+    mut changes: MessageReader<BaseMeshChanged>,
+    mut targets: Query<(Entity, &BaseMesh, &mut SubdivisionCache)>,
+) {
+    let modified: HashSet<AssetId<Mesh>> = asset_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let changed: HashSet<Entity> = changes.read().map(|BaseMeshChanged(e)| *e).collect();
+    if modified.is_empty() && changed.is_empty() {
+        return;
+    }
+
+    for (entity, base, mut cache) in &mut targets {
+        if changed.contains(&entity) || modified.contains(&base.0.id()) {
+            cache.invalidate();
+        }
+    }
+}
+
+/// Collect finished subdivisions, start missing ones, and show the mesh for
+/// the current level once it is ready
+fn update_subdivision_previews(
+    mut commands: Commands,
+    mut targets: Query<(
+        Entity,
+        &mut Mesh3d,
+        &BaseMesh,
+        &Subdivision,
+        &mut SubdivisionCache,
+    )>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut raycast_cache: ResMut<MeshRaycastCache>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for (entity, mut mesh3d, base, subdivision, mut cache) in &mut targets {
+        if let Some(pending) = &mut cache.pending {
+            let Some(result) = check_ready(&mut pending.task) else {
+                continue;
+            };
+            let (levels, stale) = (pending.levels, pending.stale);
+            cache.pending = None;
+            match result {
+                // Started again below from the changed base mesh
+                Ok(_) if stale => {}
+                Ok(mesh) => {
+                    cache.meshes.insert(levels, meshes.add(mesh));
+                }
+                Err(e) => {
+                    outbound.send_error("subdivision_failed", format!("Subdivision failed: {}", e));
+                    mesh3d.0 = base.0.clone();
+                    raycast_cache.invalidate(entity);
+                    commands
+                        .entity(entity)
+                        .remove::<(BaseMesh, Subdivision, SubdivisionCache)>();
+                    continue;
+                }
+            }
+        }
+
+        if let Some(handle) = cache.meshes.get(&subdivision.levels) {
+            if mesh3d.0 != *handle {
+                mesh3d.0 = handle.clone();
+                raycast_cache.invalidate(entity);
+            }
+            continue;
+        }
+        if cache.pending.is_some() {
+            continue;
+        }
+
+        // The previous preview stays on screen until the new one is ready
+        let Some(base_mesh) = meshes.get(&base.0) else {
+            continue;
+        };
+        let mesh = base_mesh.clone();
+        let levels = subdivision.levels;
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            HalfEdgeMesh::from_bevy_mesh(&mesh)
+                .map(|mesh| subdivide_loop(&mesh, levels).to_bevy_mesh())
+        });
+        cache.pending = Some(PendingSubdivision {
+            levels,
+            stale: false,
+            task,
+        });
+    }
+}
+
+/// Report modifier changes so the outliner can show them
+fn report_subdivision_changes(
+    changed: Query<(), Changed<Subdivision>>,
+    mut removed: RemovedComponents<Subdivision>,
+    scene_objects: SceneObjects,
+    scene_lights: SceneLights,
+    scene_measurements: SceneMeasurements,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let removed = removed.read().count() > 0;
+    if changed.is_empty() && !removed {
+        return;
+    }
+
+    outbound.send(BevyToUi::SceneUpdated(SceneInfo {
+        objects: scene_objects.infos(),
+        lights: scene_lights.infos(),
+        wireframe: scene_objects.wireframe(),
+        isolated: scene_objects.isolated(),
+        annotations: scene_measurements.infos(),
+        ..default()
+    }));
+}
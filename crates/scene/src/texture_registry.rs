@@ -0,0 +1,571 @@
+//! Registry of dynamically created textures
+//!
+//! Textures made at runtime (diffusion results, imported images) are
+//! registered under a `texture_id` with [`TextureRegistry::insert`] instead
+//! of being added to `Assets<Image>` directly. The registry owns their
+//! handles and counts the material slots using each one:
+//!
+//! - `MaterialCommand::AssignTexture` puts a registered texture into a
+//!   material slot. A texture whose last slot is reassigned, or whose
+//!   material goes away, is freed right away.
+//! - `UiToBevy::DeleteTexture` frees a texture and clears the slots using it.
+//! - Textures that were never assigned stay until the registry is over its
+//!   byte budget, then the least recently used ones are evicted.
+//!
+//! Inserting under an existing id keeps the same `Image` asset (and so every
+//! material holding it), copying the pixels into the old buffer when the
+//! size and format match. `BevyToUi::TextureRegistryStats` reports the totals
+//! whenever they change.
+
+use std::collections::HashMap;
+
+use bevy::ecs::message::Message;
+use bevy::prelude::*;
+use pentimento_ipc::{BevyToUi, TextureRegistryStats};
+
+use crate::OutboundUiMessages;
+#[cfg(feature = "selection")]
+use crate::Selectable;
+
+/// Default byte budget for registered textures (512 MiB of pixels)
+pub const DEFAULT_TEXTURE_BUDGET_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Material slot names accepted by `AssignTexture`, as reported by
+/// `QueryKind::MaterialProperties`
+pub const TEXTURE_SLOTS: [&str; 5] = [
+    "base_color",
+    "metallic_roughness",
+    "normal",
+    "emissive",
+    "occlusion",
+];
+
+/// Texture registry request from the UI
+#[derive(Message, Debug, Clone)]
+pub enum TextureRegistryEvent {
+    /// Put a registered texture into a material slot
+    Assign {
+        material_id: String,
+        slot: String,
+        texture_id: String,
+    },
+    /// Free a texture and clear the slots using it
+    Delete { texture_id: String },
+}
+
+struct TextureEntry {
+    handle: Handle<Image>,
+    bytes: u64,
+    /// Material slots currently bound to this texture
+    users: u32,
+    /// Registry clock at the last insert or assignment
+    last_used: u64,
+}
+
+/// Owner of all dynamically created textures, keyed by `texture_id`
+#[derive(Resource)]
+pub struct TextureRegistry {
+    entries: HashMap<String, TextureEntry>,
+    /// Texture bound to each (material entity, slot)
+    bindings: HashMap<(Entity, String), String>,
+    budget_bytes: u64,
+    total_bytes: u64,
+    clock: u64,
+    evicted_count: u32,
+}
+
+impl Default for TextureRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEXTURE_BUDGET_BYTES)
+    }
+}
+
+impl TextureRegistry {
+    pub fn new(budget_bytes: u64) -> Self {
+        Self {
+            entries: HashMap::new(),
+            bindings: HashMap::new(),
+            budget_bytes,
+            total_bytes: 0,
+            clock: 0,
+            evicted_count: 0,
+        }
+    }
+
+    /// Register `image` under `texture_id`, replacing the pixels of an
+    /// existing texture with that id in place
+    ///
+    /// Unreferenced textures may be evicted afterwards to stay in budget.
+    pub fn insert(
+        &mut self,
+        texture_id: impl Into<String>,
+        image: Image,
+        images: &mut Assets<Image>,
+    ) -> Handle<Image> {
+        let texture_id = texture_id.into();
+        let bytes = image_bytes(&image);
+        let last_used = self.tick();
+
+        let handle = match self.entries.get_mut(&texture_id) {
+            Some(entry) => {
+                match images.get_mut(&entry.handle) {
+                    Some(existing) => replace_pixels(existing, image),
+                    // Removed from `Assets` behind the registry's back
+                    None => entry.handle = images.add(image),
+                }
+                self.total_bytes = self.total_bytes - entry.bytes + bytes;
+                entry.bytes = bytes;
+                entry.last_used = last_used;
+                entry.handle.clone()
+            }
+            None => {
+                let handle = images.add(image);
+                self.entries.insert(
+                    texture_id.clone(),
+                    TextureEntry {
+                        handle: handle.clone(),
+                        bytes,
+                        users: 0,
+                        last_used,
+                    },
+                );
+                self.total_bytes += bytes;
+                handle
+            }
+        };
+
+        self.evict_over_budget(images, Some(&texture_id));
+        handle
+    }
+
+    /// Handle of a registered texture
+    pub fn get(&self, texture_id: &str) -> Option<&Handle<Image>> {
+        self.entries.get(texture_id).map(|entry| &entry.handle)
+    }
+
+    pub fn contains(&self, texture_id: &str) -> bool {
+        self.entries.contains_key(texture_id)
+    }
+
+    /// Record that `slot` of the material on `material` now uses `texture_id`
+    /// and return the handle to put in the slot, or `None` if the id is unknown
+    ///
+    /// The texture previously bound to the slot loses a user.
+    pub fn bind(
+        &mut self,
+        material: Entity,
+        slot: &str,
+        texture_id: &str,
+        images: &mut Assets<Image>,
+    ) -> Option<Handle<Image>> {
+        let last_used = self.tick();
+        let entry = self.entries.get_mut(texture_id)?;
+        entry.users += 1;
+        entry.last_used = last_used;
+        let handle = entry.handle.clone();
+
+        if let Some(previous) = self
+            .bindings
+            .insert((material, slot.to_string()), texture_id.to_string())
+        {
+            self.release(&previous, images);
+        }
+        Some(handle)
+    }
+
+    /// Drop every binding of a material that no longer exists
+    pub fn unbind_material(&mut self, material: Entity, images: &mut Assets<Image>) {
+        let released: Vec<String> = self
+            .bindings
+            .extract_if(|(entity, _), _| *entity == material)
+            .map(|(_, texture_id)| texture_id)
+            .collect();
+        for texture_id in released {
+            self.release(&texture_id, images);
+        }
+    }
+
+    /// Free a texture regardless of its users and return the (material, slot)
+    /// bindings that used it, or `None` if the id is unknown
+    pub fn remove(
+        &mut self,
+        texture_id: &str,
+        images: &mut Assets<Image>,
+    ) -> Option<Vec<(Entity, String)>> {
+        if !self.entries.contains_key(texture_id) {
+            return None;
+        }
+        let bindings = self
+            .bindings
+            .extract_if(|_, bound| bound == texture_id)
+            .map(|(binding, _)| binding)
+            .collect();
+        self.free(texture_id, images);
+        Some(bindings)
+    }
+
+    /// Change the byte budget, evicting unreferenced textures if it shrank
+    pub fn set_budget_bytes(&mut self, budget_bytes: u64, images: &mut Assets<Image>) {
+        self.budget_bytes = budget_bytes;
+        self.evict_over_budget(images, None);
+    }
+
+    pub fn stats(&self) -> TextureRegistryStats {
+        TextureRegistryStats {
+            texture_count: self.entries.len() as u32,
+            referenced_count: self.entries.values().filter(|e| e.users > 0).count() as u32,
+            total_bytes: self.total_bytes,
+            budget_bytes: self.budget_bytes,
+            evicted_count: self.evicted_count,
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Take one user from a texture, freeing it when it was the last
+    fn release(&mut self, texture_id: &str, images: &mut Assets<Image>) {
+        let Some(entry) = self.entries.get_mut(texture_id) else {
+            return;
+        };
+        entry.users = entry.users.saturating_sub(1);
+        if entry.users == 0 {
+            debug!("Texture {} has no users left, freeing it", texture_id);
+            self.free(texture_id, images);
+        }
+    }
+
+    fn free(&mut self, texture_id: &str, images: &mut Assets<Image>) {
+        if let Some(entry) = self.entries.remove(texture_id) {
+            self.total_bytes -= entry.bytes;
+            images.remove(&entry.handle);
+        }
+    }
+
+    /// Evict the least recently used unreferenced textures until the registry
+    /// fits its budget, never evicting `keep`
+    fn evict_over_budget(&mut self, images: &mut Assets<Image>, keep: Option<&str>) {
+        while self.total_bytes > self.budget_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(id, entry)| entry.users == 0 && Some(id.as_str()) != keep)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(id, _)| id.clone());
+            let Some(texture_id) = oldest else {
+                // Everything left is in use
+                break;
+            };
+            debug!("Evicting unreferenced texture {} (over budget)", texture_id);
+            self.free(&texture_id, images);
+            self.evicted_count += 1;
+        }
+    }
+}
+
+/// Pixel bytes of an image (0 for GPU-only images)
+fn image_bytes(image: &Image) -> u64 {
+    image.data.as_ref().map_or(0, |data| data.len() as u64)
+}
+
+/// Replace an image's contents, reusing its pixel buffer when the layout matches
+fn replace_pixels(existing: &mut Image, image: Image) {
+    let same_layout = existing.texture_descriptor.size == image.texture_descriptor.size
+        && existing.texture_descriptor.format == image.texture_descriptor.format;
+    match (same_layout, existing.data.as_mut(), image.data.as_ref()) {
+        (true, Some(old), Some(new)) if old.len() == new.len() => old.copy_from_slice(new),
+        _ => *existing = image,
+    }
+}
+
+/// The texture field of a `StandardMaterial` slot
+fn material_slot<'a>(
+    material: &'a mut StandardMaterial,
+    slot: &str,
+) -> Option<&'a mut Option<Handle<Image>>> {
+    match slot {
+        "base_color" => Some(&mut material.base_color_texture),
+        "metallic_roughness" => Some(&mut material.metallic_roughness_texture),
+        "normal" => Some(&mut material.normal_map_texture),
+        "emissive" => Some(&mut material.emissive_texture),
+        "occlusion" => Some(&mut material.occlusion_texture),
+        _ => None,
+    }
+}
+
+/// Plugin for the texture registry
+pub struct TextureRegistryPlugin;
+
+impl Plugin for TextureRegistryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TextureRegistry>()
+            .add_message::<TextureRegistryEvent>()
+            .add_systems(
+                Update,
+                (
+                    handle_texture_registry_events,
+                    release_removed_materials,
+                    send_texture_registry_stats,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Assign and delete textures requested by the UI
+#[allow(clippy::too_many_arguments)]
+fn handle_texture_registry_events(
+    mut events: MessageReader<TextureRegistryEvent>,
+    mut registry: ResMut<TextureRegistry>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mesh_materials: Query<&MeshMaterial3d<StandardMaterial>>,
+    #[cfg(feature = "selection")] selectables: Query<(Entity, &Selectable)>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for event in events.read() {
+        match event {
+            TextureRegistryEvent::Assign {
+                material_id,
+                slot,
+                texture_id,
+            } => {
+                #[cfg(feature = "selection")]
+                let result = assign_texture(
+                    &mut registry,
+                    &mut images,
+                    &mut materials,
+                    &mesh_materials,
+                    &selectables,
+                    material_id,
+                    slot,
+                    texture_id,
+                );
+                #[cfg(not(feature = "selection"))]
+                let result: Result<(), String> = {
+                    let _ = (material_id, slot, texture_id);
+                    Err("Texture assignment needs the selection feature".to_string())
+                };
+                if let Err(message) = result {
+                    warn!("{}", message);
+                    outbound.send(BevyToUi::Error {
+                        code: "texture_assign_failed".to_string(),
+                        message,
+                    });
+                }
+            }
+            TextureRegistryEvent::Delete { texture_id } => {
+                let handle = registry.get(texture_id).cloned();
+                let Some(bindings) = registry.remove(texture_id, &mut images) else {
+                    outbound.send(BevyToUi::Error {
+                        code: "unknown_texture".to_string(),
+                        message: format!("No texture {} to delete", texture_id),
+                    });
+                    continue;
+                };
+                // Clear the slots so the materials don't keep the pixels alive
+                for (entity, slot) in bindings {
+                    let Ok(material_handle) = mesh_materials.get(entity) else {
+                        continue;
+                    };
+                    let Some(material) = materials.get_mut(&material_handle.0) else {
+                        continue;
+                    };
+                    if let Some(texture) = material_slot(material, &slot)
+                        && texture.as_ref() == handle.as_ref()
+                    {
+                        *texture = None;
+                    }
+                }
+                info!("Deleted texture {}", texture_id);
+            }
+        }
+    }
+}
+
+/// Put a registered texture into a slot of the material of object `material_id`
+/// (materials aren't shared yet, so a material ID is its object's ID)
+#[cfg(feature = "selection")]
+#[allow(clippy::too_many_arguments)]
+fn assign_texture(
+    registry: &mut TextureRegistry,
+    images: &mut Assets<Image>,
+    materials: &mut Assets<StandardMaterial>,
+    mesh_materials: &Query<&MeshMaterial3d<StandardMaterial>>,
+    selectables: &Query<(Entity, &Selectable)>,
+    material_id: &str,
+    slot: &str,
+    texture_id: &str,
+) -> Result<(), String> {
+    if !TEXTURE_SLOTS.contains(&slot) {
+        return Err(format!("Unknown texture slot {}", slot));
+    }
+    if !registry.contains(texture_id) {
+        return Err(format!("Unknown texture {}", texture_id));
+    }
+    let entity = selectables
+        .iter()
+        .find(|(_, selectable)| selectable.id == material_id)
+        .map(|(entity, _)| entity)
+        .ok_or_else(|| format!("Unknown material {}", material_id))?;
+    let material = mesh_materials
+        .get(entity)
+        .ok()
+        .and_then(|handle| materials.get_mut(&handle.0))
+        .ok_or_else(|| format!("Unknown material {}", material_id))?;
+
+    let handle = registry
+        .bind(entity, slot, texture_id, images)
+        .ok_or_else(|| format!("Unknown texture {}", texture_id))?;
+    if let Some(texture) = material_slot(material, slot) {
+        *texture = Some(handle);
+    }
+    info!(
+        "Assigned texture {} to {} of {}",
+        texture_id, slot, material_id
+    );
+    Ok(())
+}
+
+/// Release the textures of materials whose entity or material went away
+fn release_removed_materials(
+    mut removed: RemovedComponents<MeshMaterial3d<StandardMaterial>>,
+    mut registry: ResMut<TextureRegistry>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for entity in removed.read() {
+        registry.unbind_material(entity, &mut images);
+    }
+}
+
+/// Send `TextureRegistryStats` when the totals change
+fn send_texture_registry_stats(
+    registry: Res<TextureRegistry>,
+    mut last_sent: Local<Option<TextureRegistryStats>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    if !registry.is_changed() {
+        return;
+    }
+    let stats = registry.stats();
+    if last_sent.as_ref() != Some(&stats) {
+        outbound.send(BevyToUi::TextureRegistryStats(stats.clone()));
+        *last_sent = Some(stats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::RenderAssetUsages;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    fn solid_image(size: u32, value: u8) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[value, value, value, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        )
+    }
+
+    fn entities(count: usize) -> Vec<Entity> {
+        let mut world = World::new();
+        (0..count).map(|_| world.spawn_empty().id()).collect()
+    }
+
+    #[test]
+    fn test_replacing_same_size_keeps_asset() {
+        let mut images = Assets::<Image>::default();
+        let mut registry = TextureRegistry::default();
+
+        let first = registry.insert("diffusion_1", solid_image(4, 10), &mut images);
+        let second = registry.insert("diffusion_1", solid_image(4, 200), &mut images);
+
+        assert_eq!(first.id(), second.id());
+        assert_eq!(images.len(), 1);
+        assert_eq!(images.get(&second).unwrap().data.as_ref().unwrap()[0], 200);
+
+        // A new size still keeps the handle materials hold
+        let third = registry.insert("diffusion_1", solid_image(8, 50), &mut images);
+        assert_eq!(first.id(), third.id());
+        assert_eq!(registry.stats().total_bytes, 8 * 8 * 4);
+    }
+
+    #[test]
+    fn test_last_user_released_frees_texture() {
+        let mut images = Assets::<Image>::default();
+        let mut registry = TextureRegistry::default();
+        let [a, b] = entities(2)[..] else {
+            unreachable!()
+        };
+
+        registry.insert("tex_1", solid_image(4, 0), &mut images);
+        registry.insert("tex_2", solid_image(4, 0), &mut images);
+        registry
+            .bind(a, "base_color", "tex_1", &mut images)
+            .unwrap();
+        registry
+            .bind(b, "base_color", "tex_1", &mut images)
+            .unwrap();
+        assert_eq!(registry.stats().referenced_count, 1);
+
+        // Reassigning one slot leaves a user
+        registry
+            .bind(a, "base_color", "tex_2", &mut images)
+            .unwrap();
+        assert!(registry.contains("tex_1"));
+
+        registry.unbind_material(b, &mut images);
+        assert!(!registry.contains("tex_1"));
+        assert!(registry.contains("tex_2"));
+        assert_eq!(images.len(), 1);
+    }
+
+    #[test]
+    fn test_remove_reports_bindings() {
+        let mut images = Assets::<Image>::default();
+        let mut registry = TextureRegistry::default();
+        let [a] = entities(1)[..] else { unreachable!() };
+
+        registry.insert("tex_1", solid_image(4, 0), &mut images);
+        registry.bind(a, "normal", "tex_1", &mut images).unwrap();
+
+        let bindings = registry.remove("tex_1", &mut images).unwrap();
+        assert_eq!(bindings, vec![(a, "normal".to_string())]);
+        assert_eq!(images.len(), 0);
+        assert!(registry.remove("tex_1", &mut images).is_none());
+        assert!(registry.bind(a, "normal", "tex_1", &mut images).is_none());
+    }
+
+    #[test]
+    fn test_budget_evicts_least_recently_used_unreferenced() {
+        let mut images = Assets::<Image>::default();
+        // Room for two 4x4 RGBA textures
+        let mut registry = TextureRegistry::new(2 * 64);
+        let [a] = entities(1)[..] else { unreachable!() };
+
+        registry.insert("old", solid_image(4, 0), &mut images);
+        registry.insert("used", solid_image(4, 0), &mut images);
+        registry.bind(a, "base_color", "used", &mut images).unwrap();
+        registry.insert("new", solid_image(4, 0), &mut images);
+
+        assert!(!registry.contains("old"));
+        assert!(registry.contains("used"));
+        assert!(registry.contains("new"));
+        assert_eq!(registry.stats().evicted_count, 1);
+
+        // Referenced textures are kept even over budget
+        registry.set_budget_bytes(0, &mut images);
+        assert!(registry.contains("used"));
+        assert!(!registry.contains("new"));
+        assert_eq!(images.len(), 1);
+    }
+}
@@ -0,0 +1,590 @@
+//! Turntable render export
+//!
+//! Renders a 360° orbit around the main camera's target with a separate
+//! offscreen camera, so the interactive viewport keeps running: at most one
+//! turntable frame is captured per interactive frame. The offscreen camera
+//! copies the main camera's projection, tonemapping, exposure, SSAO, and
+//! atmosphere, and scene lights are shared. The UI overlay only draws on the
+//! window camera, and overlay gizmos are hidden while rendering, so neither
+//! shows up in the frames.
+//!
+//! Captured frames are written as `frame_NNNN.png` on a background thread.
+//! A `.mp4` path is encoded with an `ffmpeg` sidecar process (feature
+//! `ffmpeg`) from a `<name>_frames` directory next to it; without the feature
+//! the frames are kept there and the UI gets a warning.
+
+use std::any::TypeId;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::{Exposure, RenderTarget};
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::ecs::message::Message;
+use bevy::gizmos::config::GizmoConfigStore;
+use bevy::pbr::ScreenSpaceAmbientOcclusion;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use pentimento_ipc::BevyToUi;
+
+#[cfg(feature = "atmosphere")]
+use bevy::light::AtmosphereEnvironmentMapLight;
+#[cfg(feature = "atmosphere")]
+use bevy::pbr::{Atmosphere, AtmosphereSettings};
+
+use crate::camera::{MainCamera, OrbitCamera};
+use crate::{MAX_IMAGE_DIMENSION, OutboundUiMessages};
+
+/// Frames rendered by the offscreen camera before the first capture, so
+/// pipelines and per-view lookup textures are ready
+const WARMUP_FRAMES: u32 = 3;
+
+/// Captured frames allowed to wait for the writer thread
+const MAX_FRAMES_IN_FLIGHT: u32 = 4;
+
+/// Message for turntable render operations requested by the UI
+#[derive(Message, Debug, Clone)]
+pub enum TurntableEvent {
+    /// Start a render (rejected while another one is running)
+    Start(TurntableRequest),
+    /// Cancel the running render
+    Cancel,
+}
+
+/// Parameters of a turntable render
+#[derive(Debug, Clone)]
+pub struct TurntableRequest {
+    /// Frames in the full 360° orbit
+    pub frames: u32,
+    /// Animation length, used as the video frame rate
+    pub seconds: f32,
+    /// Frame width in pixels
+    pub width: u32,
+    /// Frame height in pixels
+    pub height: u32,
+    /// PNG sequence directory, or a `.mp4` file
+    pub path: PathBuf,
+}
+
+/// Marker for the offscreen turntable camera
+#[derive(Component)]
+struct TurntableCamera;
+
+/// Progress reported by the writer thread
+enum WriterStatus {
+    /// This many frames are on disk
+    Written(u32),
+    /// Everything is written (and encoded); the final output path
+    Finished(PathBuf),
+    Failed(String),
+}
+
+/// A render in progress
+struct ActiveTurntable {
+    total: u32,
+    /// Orbit the frames are rendered from; only `yaw` changes per frame
+    target: Vec3,
+    distance: f32,
+    pitch: f32,
+    start_yaw: f32,
+    /// Offscreen camera, despawned once every frame is captured
+    camera: Option<Entity>,
+    render_target: Handle<Image>,
+    warmup: u32,
+    next_frame: u32,
+    written: u32,
+    frames: Sender<(u32, Image)>,
+    status: Mutex<Receiver<WriterStatus>>,
+    cancelled: Arc<AtomicBool>,
+    /// Gizmo groups disabled for the render, re-enabled afterwards
+    hidden_gizmos: Vec<TypeId>,
+}
+
+/// The running turntable render, if any
+#[derive(Resource, Default)]
+struct TurntableRender(Option<ActiveTurntable>);
+
+/// Plugin for turntable render export
+pub struct TurntablePlugin;
+
+impl Plugin for TurntablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TurntableRender>()
+            .add_message::<TurntableEvent>()
+            .add_systems(Update, (handle_turntable_events, advance_turntable).chain());
+    }
+}
+
+/// Start and cancel renders
+fn handle_turntable_events(
+    mut commands: Commands,
+    mut events: MessageReader<TurntableEvent>,
+    mut render: ResMut<TurntableRender>,
+    mut images: ResMut<Assets<Image>>,
+    mut gizmo_store: ResMut<GizmoConfigStore>,
+    main_camera: Query<(Entity, &OrbitCamera), With<MainCamera>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for event in events.read() {
+        match event {
+            TurntableEvent::Start(request) => {
+                if render.0.is_some() {
+                    outbound.send_error("turntable_busy", "A turntable render is already running");
+                    continue;
+                }
+                if let Err(message) = validate_request(request) {
+                    outbound.send_error("turntable_invalid", message);
+                    continue;
+                }
+                let Ok((main_entity, orbit)) = main_camera.single() else {
+                    warn!("No main camera found for turntable render");
+                    continue;
+                };
+
+                let (frame_dir, video) = output_paths(&request.path);
+                if video.is_some() && !cfg!(feature = "ffmpeg") {
+                    outbound.send(BevyToUi::Warning {
+                        code: "turntable_mp4_unavailable".to_string(),
+                        message: format!(
+                            "Built without ffmpeg; writing PNG frames to {}",
+                            frame_dir.display()
+                        ),
+                    });
+                }
+                if let Err(e) = std::fs::create_dir_all(&frame_dir) {
+                    outbound.send_error(
+                        "turntable_failed",
+                        format!("Failed to create {}: {}", frame_dir.display(), e),
+                    );
+                    continue;
+                }
+
+                let render_target = images.add(render_target_image(request.width, request.height));
+                let camera = commands
+                    .spawn((
+                        Camera3d::default(),
+                        Camera {
+                            order: -2,
+                            ..default()
+                        },
+                        RenderTarget::Image(render_target.clone().into()),
+                        Transform::from_translation(orbit.calculate_position())
+                            .looking_at(orbit.target, Vec3::Y),
+                        TurntableCamera,
+                    ))
+                    .id();
+                commands.queue(move |world: &mut World| {
+                    copy_view_settings(world, main_entity, camera);
+                });
+
+                let (frame_tx, frame_rx) = mpsc::channel();
+                let (status_tx, status_rx) = mpsc::channel();
+                let cancelled = Arc::new(AtomicBool::new(false));
+                let job = WriterJob {
+                    total: request.frames,
+                    frame_dir,
+                    video: video
+                        .filter(|_| cfg!(feature = "ffmpeg"))
+                        .map(|path| (path, request.frames as f32 / request.seconds)),
+                };
+                let writer_cancelled = cancelled.clone();
+                std::thread::spawn(move || run_writer(job, frame_rx, status_tx, writer_cancelled));
+
+                info!(
+                    "Turntable render started: {} frames at {}x{} to {}",
+                    request.frames,
+                    request.width,
+                    request.height,
+                    request.path.display()
+                );
+                render.0 = Some(ActiveTurntable {
+                    total: request.frames,
+                    target: orbit.target,
+                    distance: orbit.distance,
+                    pitch: orbit.pitch,
+                    start_yaw: orbit.yaw,
+                    camera: Some(camera),
+                    render_target,
+                    warmup: WARMUP_FRAMES,
+                    next_frame: 0,
+                    written: 0,
+                    frames: frame_tx,
+                    status: Mutex::new(status_rx),
+                    cancelled,
+                    hidden_gizmos: hide_gizmos(&mut gizmo_store),
+                });
+            }
+            TurntableEvent::Cancel => {
+                if let Some(active) = render.0.take() {
+                    active.cancelled.store(true, Ordering::Relaxed);
+                    finish(&mut commands, &mut gizmo_store, active);
+                    info!("Turntable render cancelled");
+                }
+            }
+        }
+    }
+}
+
+/// Report writer progress and schedule the next frame capture
+fn advance_turntable(
+    mut commands: Commands,
+    mut render: ResMut<TurntableRender>,
+    mut gizmo_store: ResMut<GizmoConfigStore>,
+    mut cameras: Query<&mut Transform, With<TurntableCamera>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let Some(active) = render.0.as_mut() else {
+        return;
+    };
+
+    let statuses: Vec<_> = active.status.lock().unwrap().try_iter().collect();
+    let mut ended = false;
+    for status in statuses {
+        match status {
+            WriterStatus::Written(written) => {
+                active.written = written;
+                outbound.send(BevyToUi::RenderProgress {
+                    frame: written,
+                    total: active.total,
+                });
+                // Nothing left to capture (an mp4 may still be encoding)
+                if written == active.total
+                    && let Some(camera) = active.camera.take()
+                {
+                    commands.entity(camera).despawn();
+                }
+            }
+            WriterStatus::Finished(path) => {
+                info!("Turntable render written to {}", path.display());
+                outbound.send(BevyToUi::RenderFinished {
+                    path: path.display().to_string(),
+                });
+                ended = true;
+                break;
+            }
+            WriterStatus::Failed(message) => {
+                outbound.send_error("turntable_failed", message);
+                ended = true;
+                break;
+            }
+        }
+    }
+    if ended {
+        if let Some(active) = render.0.take() {
+            finish(&mut commands, &mut gizmo_store, active);
+        }
+        return;
+    }
+
+    if active.warmup > 0 {
+        active.warmup -= 1;
+        return;
+    }
+    if active.next_frame >= active.total
+        || active.next_frame - active.written >= MAX_FRAMES_IN_FLIGHT
+    {
+        return;
+    }
+    let Some(mut transform) = active
+        .camera
+        .and_then(|camera| cameras.get_mut(camera).ok())
+    else {
+        return;
+    };
+
+    let frame = active.next_frame;
+    let orbit = OrbitCamera {
+        target: active.target,
+        distance: active.distance,
+        pitch: active.pitch,
+        yaw: turntable_yaw(active.start_yaw, frame, active.total),
+        ..default()
+    };
+    *transform =
+        Transform::from_translation(orbit.calculate_position()).looking_at(orbit.target, Vec3::Y);
+
+    // Captured from this frame's render, after the transform above propagates
+    let frames = active.frames.clone();
+    commands
+        .spawn(Screenshot::image(active.render_target.clone()))
+        .observe(move |captured: On<ScreenshotCaptured>| {
+            // The writer is gone after a cancel or failure
+            let _ = frames.send((frame, captured.image.clone()));
+        });
+    active.next_frame += 1;
+}
+
+/// Despawn the camera and restore gizmos after a render ends
+fn finish(commands: &mut Commands, gizmo_store: &mut GizmoConfigStore, active: ActiveTurntable) {
+    if let Some(camera) = active.camera {
+        commands.entity(camera).despawn();
+    }
+    for (type_id, config, _) in gizmo_store.iter_mut() {
+        if active.hidden_gizmos.contains(type_id) {
+            config.enabled = true;
+        }
+    }
+}
+
+/// Disable every enabled gizmo group, returning the ones changed
+fn hide_gizmos(gizmo_store: &mut GizmoConfigStore) -> Vec<TypeId> {
+    let mut hidden = Vec::new();
+    for (type_id, config, _) in gizmo_store.iter_mut() {
+        if config.enabled {
+            config.enabled = false;
+            hidden.push(*type_id);
+        }
+    }
+    hidden
+}
+
+/// Give the offscreen camera the main camera's look
+fn copy_view_settings(world: &mut World, from: Entity, to: Entity) {
+    fn copy<T: Component + Clone>(world: &mut World, from: Entity, to: Entity) {
+        let Some(component) = world.get::<T>(from).cloned() else {
+            return;
+        };
+        if let Ok(mut entity) = world.get_entity_mut(to) {
+            entity.insert(component);
+        }
+    }
+
+    copy::<Projection>(world, from, to);
+    copy::<Tonemapping>(world, from, to);
+    copy::<Msaa>(world, from, to);
+    copy::<Exposure>(world, from, to);
+    copy::<ScreenSpaceAmbientOcclusion>(world, from, to);
+
+    #[cfg(feature = "atmosphere")]
+    {
+        copy::<Atmosphere>(world, from, to);
+        copy::<AtmosphereSettings>(world, from, to);
+        copy::<AtmosphereEnvironmentMapLight>(world, from, to);
+    }
+}
+
+fn validate_request(request: &TurntableRequest) -> Result<(), String> {
+    if request.frames == 0 {
+        return Err("Turntable needs at least one frame".to_string());
+    }
+    if !request.seconds.is_finite() || request.seconds <= 0.0 {
+        return Err(format!(
+            "Turntable length must be positive, got {}s",
+            request.seconds
+        ));
+    }
+    if request.width == 0
+        || request.height == 0
+        || request.width.max(request.height) > MAX_IMAGE_DIMENSION
+    {
+        return Err(format!(
+            "Turntable size {}x{} must be between 1 and {} pixels per side",
+            request.width, request.height, MAX_IMAGE_DIMENSION
+        ));
+    }
+    Ok(())
+}
+
+/// Camera yaw for `frame`; the last frame stops one step short of the first
+/// so the animation loops without a repeated frame
+fn turntable_yaw(start_yaw: f32, frame: u32, total: u32) -> f32 {
+    start_yaw + std::f32::consts::TAU * frame as f32 / total as f32
+}
+
+/// Frame directory and, for a `.mp4` path, the video file
+fn output_paths(path: &Path) -> (PathBuf, Option<PathBuf>) {
+    let is_video = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("mp4"));
+    if !is_video {
+        return (path.to_path_buf(), None);
+    }
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    (
+        path.with_file_name(format!("{}_frames", stem)),
+        Some(path.to_path_buf()),
+    )
+}
+
+fn frame_path(frame_dir: &Path, frame: u32) -> PathBuf {
+    frame_dir.join(format!("frame_{:04}.png", frame))
+}
+
+/// Offscreen color target the turntable camera renders into
+fn render_target_image(width: u32, height: u32) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_SRC
+        | TextureUsages::COPY_DST
+        | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Output of the writer thread
+struct WriterJob {
+    total: u32,
+    frame_dir: PathBuf,
+    /// Video file and frame rate, when encoding with ffmpeg
+    video: Option<(PathBuf, f32)>,
+}
+
+/// Write captured frames as PNGs, then encode the video if requested
+fn run_writer(
+    job: WriterJob,
+    frames: Receiver<(u32, Image)>,
+    status: Sender<WriterStatus>,
+    cancelled: Arc<AtomicBool>,
+) {
+    let mut written = 0;
+    // Ends when the render is cancelled and every sender is dropped
+    for (frame, image) in frames.iter() {
+        if cancelled.load(Ordering::Relaxed) {
+            return;
+        }
+        let path = frame_path(&job.frame_dir, frame);
+        // Alpha holds HDR brightness rather than coverage, so drop it
+        let result = image
+            .try_into_dynamic()
+            .map_err(|e| e.to_string())
+            .and_then(|image| image.to_rgb8().save(&path).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            let _ = status.send(WriterStatus::Failed(format!(
+                "Failed to write {}: {}",
+                path.display(),
+                e
+            )));
+            return;
+        }
+
+        written += 1;
+        let _ = status.send(WriterStatus::Written(written));
+        if written == job.total {
+            break;
+        }
+    }
+    if written < job.total || cancelled.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let result = match job.video {
+        Some((video, fps)) => encode_video(&job.frame_dir, &video, fps).map(|()| video),
+        None => Ok(job.frame_dir),
+    };
+    let _ = status.send(match result {
+        Ok(path) => WriterStatus::Finished(path),
+        Err(message) => WriterStatus::Failed(message),
+    });
+}
+
+/// Encode the frame sequence to H.264 with an `ffmpeg` sidecar process,
+/// removing the frames on success
+#[cfg(feature = "ffmpeg")]
+fn encode_video(frame_dir: &Path, video: &Path, fps: f32) -> Result<(), String> {
+    let output = std::process::Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-framerate"])
+        .arg(fps.to_string())
+        .arg("-i")
+        .arg(frame_dir.join("frame_%04d.png"))
+        // yuv420p needs even dimensions
+        .args([
+            "-vf",
+            "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+            "-c:v",
+            "libx264",
+            "-pix_fmt",
+            "yuv420p",
+        ])
+        .arg(video)
+        .output()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffmpeg failed to encode {}: {}",
+            video.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    if let Err(e) = std::fs::remove_dir_all(frame_dir) {
+        warn!("Failed to remove {}: {}", frame_dir.display(), e);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "ffmpeg"))]
+fn encode_video(_frame_dir: &Path, _video: &Path, _fps: f32) -> Result<(), String> {
+    unreachable!("videos are only requested with the ffmpeg feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turntable_yaw_covers_one_orbit() {
+        assert_eq!(turntable_yaw(0.5, 0, 8), 0.5);
+        let step = turntable_yaw(0.0, 1, 8);
+        assert!((step - std::f32::consts::FRAC_PI_4).abs() < 1e-6);
+        let last = turntable_yaw(0.0, 7, 8);
+        assert!((last + step - std::f32::consts::TAU).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_output_paths() {
+        let (frames, video) = output_paths(Path::new("/renders/spin"));
+        assert_eq!(frames, PathBuf::from("/renders/spin"));
+        assert_eq!(video, None);
+
+        let (frames, video) = output_paths(Path::new("/renders/spin.MP4"));
+        assert_eq!(frames, PathBuf::from("/renders/spin_frames"));
+        assert_eq!(video, Some(PathBuf::from("/renders/spin.MP4")));
+    }
+
+    #[test]
+    fn test_validate_request() {
+        let request = TurntableRequest {
+            frames: 120,
+            seconds: 4.0,
+            width: 1920,
+            height: 1080,
+            path: PathBuf::from("/renders/spin"),
+        };
+        assert!(validate_request(&request).is_ok());
+        assert!(
+            validate_request(&TurntableRequest {
+                frames: 0,
+                ..request.clone()
+            })
+            .is_err()
+        );
+        assert!(
+            validate_request(&TurntableRequest {
+                seconds: 0.0,
+                ..request.clone()
+            })
+            .is_err()
+        );
+        assert!(
+            validate_request(&TurntableRequest {
+                width: MAX_IMAGE_DIMENSION + 1,
+                ..request
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,739 @@
+//! View modes — debug views that replace the shaded scene.
+//!
+//! `Depth`, `Normals`, and `AmbientOcclusion` run one fullscreen
+//! post-process pass that reads the depth prepass, normal prepass, or SSAO
+//! texture and overwrites scene color; the pass is specialized per mode (and
+//! per MSAA setting) through shader defs. `UvChecker` instead swaps every
+//! standard material for an unlit procedural checker on UV_0 until the mode
+//! is left. Selection outlines and gizmos still render on top.
+//!
+//! Shadows and AO are disabled while a debug view is active since the lit
+//! scene output is overwritten; the AO view forces SSAO on instead, with
+//! MSAA off as SSAO requires. Everything a mode changes is restored before
+//! the next mode is applied, so switching between debug views never leaves
+//! a temporarily disabled state behind.
+
+use bevy::asset::{RenderAssetUsages, embedded_asset};
+use bevy::camera::primitives::Aabb;
+use bevy::core_pipeline::FullscreenShader;
+use bevy::core_pipeline::core_3d::graph::{Core3d, Node3d};
+use bevy::core_pipeline::prepass::{DepthPrepass, NormalPrepass, ViewPrepassTextures};
+use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::pbr::ScreenSpaceAmbientOcclusionResources;
+use bevy::prelude::*;
+use bevy::render::{
+    Render, RenderApp, RenderSystems,
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    extract_resource::{ExtractResource, ExtractResourcePlugin},
+    render_graph::{
+        NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
+    },
+    render_resource::{
+        BindGroupEntries, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, Buffer,
+        BufferBindingType, BufferInitDescriptor, BufferUsages, CachedRenderPipelineId,
+        ColorTargetState, ColorWrites, Extent3d, FragmentState, MultisampleState, Operations,
+        PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor,
+        RenderPipelineDescriptor, ShaderStages, ShaderType, SpecializedRenderPipeline,
+        SpecializedRenderPipelines, TextureDimension, TextureFormat, TextureSampleType,
+        TextureViewDimension,
+    },
+    renderer::{RenderContext, RenderDevice},
+    view::ViewTarget,
+};
+use pentimento_ipc::ViewMode;
+
+use crate::ambient_occlusion::SceneAmbientOcclusion;
+use crate::camera::MainCamera;
+use crate::lighting::SunLight;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// Render graph label for the view mode pass.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ViewModeLabel;
+
+/// Marker component added to the main camera so the view mode node can
+/// filter to the correct view.  Extracted to the render world automatically.
+#[derive(Component, Clone, ExtractComponent)]
+pub struct ViewModeCamera;
+
+/// Settings for the view mode.  Extracted to the render world each frame.
+#[derive(Resource, Clone, Default, ExtractResource)]
+pub struct ViewModeSettings {
+    pub mode: ViewMode,
+    /// Mode whose camera, light, and AO changes are currently in effect.
+    applied: ViewMode,
+    /// State changed by the applied mode, restored when it is left.
+    saved: SavedViewState,
+}
+
+/// What the applied view mode changed, so it can be put back exactly.
+#[derive(Clone, Default)]
+struct SavedViewState {
+    shadows_enabled: Option<bool>,
+    ao_enabled: Option<bool>,
+    msaa: Option<Msaa>,
+    /// Prepasses the view mode added (ones already present are left alone).
+    added_depth_prepass: bool,
+    added_normal_prepass: bool,
+}
+
+/// Computed scene depth bounds, updated each frame when the depth view is
+/// active.  Kept separate from `ViewModeSettings` to avoid triggering
+/// `is_changed()` on the settings resource every frame.
+#[derive(Resource, Clone, ExtractResource)]
+pub struct DepthViewBounds {
+    /// Camera near clipping plane (for depth linearization).
+    pub near_plane: f32,
+    /// Nearest scene depth in view-space units (for gradient normalization).
+    pub scene_near: f32,
+    /// Farthest scene depth in view-space units (for gradient normalization).
+    pub scene_far: f32,
+}
+
+impl Default for DepthViewBounds {
+    fn default() -> Self {
+        Self {
+            near_plane: 0.1,
+            scene_near: 0.1,
+            scene_far: 100.0,
+        }
+    }
+}
+
+/// Main camera state a view mode switches.
+type ViewModeCameraState = (
+    Entity,
+    &'static mut Msaa,
+    Has<DepthPrepass>,
+    Has<NormalPrepass>,
+);
+
+/// Original material of a mesh shown with the UV checker.
+#[derive(Component)]
+struct UvCheckerOriginal(Handle<StandardMaterial>);
+
+/// Shared UV checker material, created on first use.
+#[derive(Resource, Default)]
+struct UvCheckerMaterial(Option<Handle<StandardMaterial>>);
+
+/// Post-process variant of the view mode pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ViewModePass {
+    Depth,
+    Normals,
+    AmbientOcclusion,
+}
+
+impl ViewModePass {
+    /// The pass for a view mode; `None` when the scene color is kept.
+    fn for_mode(mode: ViewMode) -> Option<Self> {
+        match mode {
+            ViewMode::Depth => Some(Self::Depth),
+            ViewMode::Normals => Some(Self::Normals),
+            ViewMode::AmbientOcclusion => Some(Self::AmbientOcclusion),
+            ViewMode::Shaded | ViewMode::UvChecker => None,
+        }
+    }
+
+    fn shader_def(self) -> &'static str {
+        match self {
+            Self::Depth => "DEPTH",
+            Self::Normals => "NORMALS",
+            Self::AmbientOcclusion => "AMBIENT_OCCLUSION",
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Plugin
+// ---------------------------------------------------------------------------
+
+pub struct ViewModePlugin;
+
+impl Plugin for ViewModePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/view_mode.wgsl");
+
+        app.init_resource::<ViewModeSettings>();
+        app.init_resource::<DepthViewBounds>();
+        app.init_resource::<UvCheckerMaterial>();
+        app.add_plugins(ExtractComponentPlugin::<ViewModeCamera>::default());
+        app.add_plugins(ExtractResourcePlugin::<ViewModeSettings>::default());
+        app.add_plugins(ExtractResourcePlugin::<DepthViewBounds>::default());
+
+        // Main-world systems that switch camera prepasses, disable costly
+        // effects, override materials for the UV checker, and compute scene
+        // depth bounds for gradient normalization.
+        app.add_systems(
+            Update,
+            (
+                compute_scene_depth_bounds,
+                (apply_view_mode, sync_uv_checker_materials).chain(),
+            ),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            warn!("ViewModePlugin: No RenderApp available");
+            return;
+        };
+
+        render_app.add_render_graph_node::<ViewNodeRunner<ViewModeNode>>(Core3d, ViewModeLabel);
+
+        // Insert between Tonemapping and EndMainPassPostProcessing.
+        // EdgeDetectionPlugin (if present) will add its own edge
+        // ViewModeLabel → EdgeDetectionLabel so outlines render on top.
+        render_app.add_render_graph_edges(
+            Core3d,
+            (
+                Node3d::Tonemapping,
+                ViewModeLabel,
+                Node3d::EndMainPassPostProcessing,
+            ),
+        );
+
+        render_app
+            .init_resource::<SpecializedRenderPipelines<ViewModePipeline>>()
+            .add_systems(Render, prepare_view_mode.in_set(RenderSystems::Prepare));
+
+        info!("ViewModePlugin: render graph node registered");
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<ViewModePipeline>();
+        info!("ViewModePlugin: pipeline initialized");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Main-world systems
+// ---------------------------------------------------------------------------
+
+/// Undo what the previously applied mode changed, then apply the new mode:
+/// the prepasses its pass reads, shadows and AO off (their output is
+/// overwritten anyway), or SSAO on with MSAA off for the AO view.
+fn apply_view_mode(
+    mut commands: Commands,
+    mut settings: ResMut<ViewModeSettings>,
+    mut camera_query: Query<ViewModeCameraState, With<MainCamera>>,
+    mut sun_query: Query<&mut DirectionalLight, With<SunLight>>,
+    mut ao_resource: ResMut<SceneAmbientOcclusion>,
+) {
+    if settings.mode == settings.applied {
+        return;
+    }
+    let settings = &mut *settings;
+    let saved = std::mem::take(&mut settings.saved);
+    let mode = settings.mode;
+
+    // Restore the previous mode's changes first.
+    if let Some(shadows_enabled) = saved.shadows_enabled {
+        for mut light in sun_query.iter_mut() {
+            light.shadows_enabled = shadows_enabled;
+        }
+    }
+    if let Some(ao_enabled) = saved.ao_enabled {
+        set_ao_enabled(&mut ao_resource, ao_enabled);
+    }
+
+    if mode != ViewMode::Shaded {
+        // Capture current state before changing it.
+        for light in sun_query.iter() {
+            settings.saved.shadows_enabled = Some(light.shadows_enabled);
+        }
+        settings.saved.ao_enabled = Some(ao_resource.settings.enabled);
+
+        for mut light in sun_query.iter_mut() {
+            light.shadows_enabled = false;
+        }
+        set_ao_enabled(&mut ao_resource, mode == ViewMode::AmbientOcclusion);
+    }
+
+    let needs_depth = matches!(mode, ViewMode::Depth | ViewMode::AmbientOcclusion);
+    let needs_normals = matches!(mode, ViewMode::Normals | ViewMode::AmbientOcclusion);
+    for (entity, mut msaa, has_depth, has_normals) in camera_query.iter_mut() {
+        if let Some(previous) = saved.msaa {
+            *msaa = previous;
+        }
+        if mode == ViewMode::AmbientOcclusion {
+            settings.saved.msaa = Some(*msaa);
+            *msaa = Msaa::Off;
+        }
+
+        // Prepasses the previous mode added are gone unless this mode keeps them.
+        let has_depth = has_depth && !saved.added_depth_prepass;
+        let has_normals = has_normals && !saved.added_normal_prepass;
+        if needs_depth && !has_depth {
+            commands.entity(entity).insert(DepthPrepass);
+            settings.saved.added_depth_prepass = true;
+        } else if saved.added_depth_prepass {
+            commands.entity(entity).remove::<DepthPrepass>();
+        }
+        if needs_normals && !has_normals {
+            commands.entity(entity).insert(NormalPrepass);
+            settings.saved.added_normal_prepass = true;
+        } else if saved.added_normal_prepass {
+            commands.entity(entity).remove::<NormalPrepass>();
+        }
+    }
+
+    settings.applied = mode;
+    info!("View mode: {:?}", mode);
+}
+
+fn set_ao_enabled(ao_resource: &mut SceneAmbientOcclusion, enabled: bool) {
+    if ao_resource.settings.enabled != enabled {
+        ao_resource.settings.enabled = enabled;
+        ao_resource.dirty = true;
+    }
+}
+
+/// Show every standard-material mesh with the UV checker while that mode is
+/// applied (including meshes added meanwhile), and give the original
+/// materials back once it is left.
+fn sync_uv_checker_materials(
+    mut commands: Commands,
+    settings: Res<ViewModeSettings>,
+    mut checker: ResMut<UvCheckerMaterial>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    plain_meshes: Query<(Entity, &MeshMaterial3d<StandardMaterial>), Without<UvCheckerOriginal>>,
+    checker_meshes: Query<(Entity, &UvCheckerOriginal)>,
+) {
+    if settings.applied != ViewMode::UvChecker {
+        for (entity, original) in checker_meshes.iter() {
+            commands
+                .entity(entity)
+                .insert(MeshMaterial3d(original.0.clone()))
+                .remove::<UvCheckerOriginal>();
+        }
+        return;
+    }
+
+    if plain_meshes.is_empty() {
+        return;
+    }
+    let checker_material = checker
+        .0
+        .get_or_insert_with(|| {
+            materials.add(StandardMaterial {
+                base_color_texture: Some(images.add(uv_checker_image())),
+                unlit: true,
+                ..default()
+            })
+        })
+        .clone();
+    for (entity, material) in plain_meshes.iter() {
+        commands.entity(entity).insert((
+            UvCheckerOriginal(material.0.clone()),
+            MeshMaterial3d(checker_material.clone()),
+        ));
+    }
+}
+
+/// 8x8 checker tinted by U (red) and V (green) so orientation and
+/// stretching are visible.  Repeats outside 0..1.
+fn uv_checker_image() -> Image {
+    const SIZE: u32 = 256;
+    const CELLS: u32 = 8;
+    let cell = SIZE / CELLS;
+
+    let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let dark = (x / cell + y / cell) % 2 == 1;
+            let shade = if dark { 0.35 } else { 0.85 };
+            // Tint per cell so each one stays a uniform color.
+            let u = ((x / cell) as f32 + 0.5) / CELLS as f32;
+            let v = ((y / cell) as f32 + 0.5) / CELLS as f32;
+            let r = shade * (0.6 + 0.4 * u);
+            let g = shade * (0.6 + 0.4 * v);
+            let b = shade * 0.8;
+            data.extend([(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, 255]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::nearest()
+    });
+    image
+}
+
+/// Compute the scene depth bounds from mesh AABBs projected into camera
+/// view-space.  Updates `DepthViewBounds` each frame so the depth gradient
+/// automatically adapts to the actual scene content.
+fn compute_scene_depth_bounds(
+    settings: Res<ViewModeSettings>,
+    mut bounds: ResMut<DepthViewBounds>,
+    mesh_query: Query<(&Aabb, &GlobalTransform), With<Mesh3d>>,
+    camera_query: Query<(&GlobalTransform, &Projection), With<MainCamera>>,
+) {
+    if settings.mode != ViewMode::Depth {
+        return;
+    }
+
+    let Ok((camera_transform, projection)) = camera_query.single() else {
+        return;
+    };
+
+    // Read the actual camera near plane from the projection.
+    let near_plane = match projection {
+        Projection::Perspective(p) => p.near,
+        Projection::Orthographic(o) => o.near,
+        _ => 0.1,
+    };
+    bounds.near_plane = near_plane;
+
+    // World-to-view transform.
+    let world_to_view = camera_transform.affine().inverse();
+
+    let mut min_depth = f32::MAX;
+    let mut max_depth = f32::MIN;
+    let mut found_any = false;
+
+    for (aabb, global_transform) in mesh_query.iter() {
+        let center = aabb.center;
+        let he = aabb.half_extents;
+
+        // 8 corners of the AABB in local space.
+        let corners = [
+            center + Vec3A::new(-he.x, -he.y, -he.z),
+            center + Vec3A::new(-he.x, -he.y, he.z),
+            center + Vec3A::new(-he.x, he.y, -he.z),
+            center + Vec3A::new(-he.x, he.y, he.z),
+            center + Vec3A::new(he.x, -he.y, -he.z),
+            center + Vec3A::new(he.x, -he.y, he.z),
+            center + Vec3A::new(he.x, he.y, -he.z),
+            center + Vec3A::new(he.x, he.y, he.z),
+        ];
+
+        // Transform local → world → view and track depth.
+        let model = global_transform.affine();
+        for corner in &corners {
+            let world_pos = model.transform_point3a(*corner);
+            let view_pos = world_to_view.transform_point3a(world_pos);
+            // Bevy looks along -Z in view space; depth = -z.
+            let depth = -view_pos.z;
+            if depth > 0.0 {
+                min_depth = min_depth.min(depth);
+                max_depth = max_depth.max(depth);
+                found_any = true;
+            }
+        }
+    }
+
+    if found_any && max_depth > min_depth {
+        // 5% padding so boundary objects aren't pure white/black.
+        let range = max_depth - min_depth;
+        let padding = range * 0.05;
+        bounds.scene_near = (min_depth - padding).max(near_plane);
+        bounds.scene_far = max_depth + padding;
+    } else if found_any {
+        // Degenerate: all geometry at the same depth.
+        bounds.scene_near = (min_depth * 0.9).max(near_plane);
+        bounds.scene_far = max_depth * 1.1;
+    } else {
+        // No visible geometry — sensible fallback.
+        bounds.scene_near = near_plane;
+        bounds.scene_far = 100.0;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Render-world node
+// ---------------------------------------------------------------------------
+
+/// Uniform data passed to the view mode shader (read by the depth view).
+#[derive(Clone, Copy, ShaderType)]
+pub struct ViewModeUniform {
+    /// Camera near clipping plane (for linearization: linear_z = near_plane / raw_depth).
+    pub near_plane: f32,
+    /// Nearest scene depth in view-space units (for gradient normalization).
+    pub scene_near: f32,
+    /// Farthest scene depth in view-space units (for gradient normalization).
+    pub scene_far: f32,
+    pub _padding0: f32,
+}
+
+#[derive(Default)]
+pub struct ViewModeNode;
+
+impl ViewNode for ViewModeNode {
+    type ViewQuery = (&'static ViewTarget, Option<&'static ViewModeCamera>);
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (view_target, view_mode_camera): bevy::ecs::query::QueryItem<'w, 'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        // Only run on the main camera.
+        if view_mode_camera.is_none() {
+            return Ok(());
+        }
+
+        let Some(settings) = world.get_resource::<ViewModeSettings>() else {
+            return Ok(());
+        };
+        let Some(pass) = ViewModePass::for_mode(settings.mode) else {
+            return Ok(());
+        };
+
+        // Prepared data from an earlier mode (or a missing texture) is skipped.
+        let Some(prepared) = world.get_resource::<ViewModePrepared>() else {
+            return Ok(());
+        };
+        if prepared.pass != pass {
+            return Ok(());
+        }
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(render_pipeline) = pipeline_cache.get_render_pipeline(prepared.pipeline_id) else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+
+        let bind_group = render_context.render_device().create_bind_group(
+            "view_mode_bind_group",
+            &pipeline_cache.get_bind_group_layout(&prepared.layout),
+            &BindGroupEntries::sequential((
+                prepared.uniform_buffer.as_entire_binding(),
+                &prepared.source_texture_view,
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("view_mode_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_render_pipeline(render_pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pipeline
+// ---------------------------------------------------------------------------
+
+#[derive(Resource)]
+pub struct ViewModePipeline {
+    shader: Handle<Shader>,
+    fullscreen_shader: FullscreenShader,
+}
+
+impl FromWorld for ViewModePipeline {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            shader: world
+                .load_asset("embedded://pentimento_scene/view_mode/shaders/view_mode.wgsl"),
+            fullscreen_shader: world.resource::<FullscreenShader>().clone(),
+        }
+    }
+}
+
+/// Shader variant of the view mode pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ViewModePipelineKey {
+    pass: ViewModePass,
+    /// Whether the source texture is multisampled (MSAA on).
+    multisampled: bool,
+    texture_format: TextureFormat,
+}
+
+impl ViewModePipelineKey {
+    /// @binding(0) — uniform buffer
+    /// @binding(1) — depth, normal, or SSAO texture
+    fn layout(&self) -> BindGroupLayoutDescriptor {
+        let sample_type = match self.pass {
+            ViewModePass::Depth => TextureSampleType::Depth,
+            ViewModePass::Normals | ViewModePass::AmbientOcclusion => {
+                TextureSampleType::Float { filterable: false }
+            }
+        };
+        BindGroupLayoutDescriptor::new(
+            "view_mode_bind_group_layout",
+            &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: Some(ViewModeUniform::min_size()),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type,
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: self.multisampled,
+                    },
+                    count: None,
+                },
+            ],
+        )
+    }
+}
+
+impl SpecializedRenderPipeline for ViewModePipeline {
+    type Key = ViewModePipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = vec![key.pass.shader_def().into()];
+        if key.multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+
+        RenderPipelineDescriptor {
+            label: Some("view_mode_pipeline".into()),
+            layout: vec![key.layout()],
+            vertex: self.fullscreen_shader.to_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: key.texture_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Prepare system (render world)
+// ---------------------------------------------------------------------------
+
+/// Prepared per-frame data consumed by `ViewModeNode`.
+#[derive(Resource)]
+pub struct ViewModePrepared {
+    pass: ViewModePass,
+    pub pipeline_id: CachedRenderPipelineId,
+    pub layout: BindGroupLayoutDescriptor,
+    pub uniform_buffer: Buffer,
+    pub source_texture_view: bevy::render::render_resource::TextureView,
+}
+
+/// Runs in the Render schedule's Prepare set.  Specializes the pipeline for
+/// the current mode, creates the uniform buffer, and resolves the source
+/// texture view from the prepass or SSAO textures.
+#[allow(clippy::too_many_arguments)]
+fn prepare_view_mode(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<ViewModePipeline>>,
+    view_mode_pipeline: Option<Res<ViewModePipeline>>,
+    settings: Option<Res<ViewModeSettings>>,
+    bounds: Option<Res<DepthViewBounds>>,
+    views: Query<
+        (
+            &ViewTarget,
+            &Msaa,
+            &ViewPrepassTextures,
+            Option<&ScreenSpaceAmbientOcclusionResources>,
+        ),
+        With<ViewModeCamera>,
+    >,
+) {
+    let (Some(settings), Some(bounds), Some(view_mode_pipeline)) =
+        (settings, bounds, view_mode_pipeline)
+    else {
+        return;
+    };
+    let Some(pass) = ViewModePass::for_mode(settings.mode) else {
+        return;
+    };
+
+    // Use the first matching camera.
+    let Some((view_target, msaa, prepass_textures, ssao)) = views.iter().next() else {
+        commands.remove_resource::<ViewModePrepared>();
+        return;
+    };
+
+    // Right after a mode switch the texture may not exist yet.
+    let source = match pass {
+        ViewModePass::Depth => prepass_textures.depth.as_ref().map(|d| &d.texture),
+        ViewModePass::Normals => prepass_textures.normal.as_ref().map(|n| &n.texture),
+        ViewModePass::AmbientOcclusion => {
+            ssao.map(|ssao| &ssao.screen_space_ambient_occlusion_texture)
+        }
+    };
+    let Some(source) = source else {
+        commands.remove_resource::<ViewModePrepared>();
+        return;
+    };
+
+    let key = ViewModePipelineKey {
+        pass,
+        // The SSAO texture is never multisampled
+        multisampled: pass != ViewModePass::AmbientOcclusion && msaa.samples() > 1,
+        texture_format: view_target.main_texture_format(),
+    };
+    let pipeline_id = pipelines.specialize(&pipeline_cache, &view_mode_pipeline, key);
+
+    let uniform = ViewModeUniform {
+        near_plane: bounds.near_plane,
+        scene_near: bounds.scene_near,
+        scene_far: bounds.scene_far,
+        _padding0: 0.0,
+    };
+
+    let mut buffer = bevy::render::render_resource::encase::UniformBuffer::new(Vec::new());
+    buffer.write(&uniform).unwrap();
+
+    let uniform_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("view_mode_uniform_buffer"),
+        contents: buffer.as_ref(),
+        usage: BufferUsages::UNIFORM,
+    });
+
+    commands.insert_resource(ViewModePrepared {
+        pass,
+        pipeline_id,
+        layout: key.layout(),
+        uniform_buffer,
+        source_texture_view: source.default_view.clone(),
+    });
+}
@@ -0,0 +1,77 @@
+// View mode visualization shader
+// One variant per debug view, selected with shader defs:
+// - DEPTH: linearized greyscale depth, auto-normalized to the scene's depth range
+// - NORMALS: world-space normals from the normal prepass as RGB
+// - AMBIENT_OCCLUSION: the raw SSAO visibility term as greyscale
+// MULTISAMPLED is set when the prepass textures are multisampled (MSAA on)
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct ViewModeUniform {
+    near_plane: f32,
+    scene_near: f32,
+    scene_far: f32,
+    _padding0: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: ViewModeUniform;
+
+#ifdef DEPTH
+#ifdef MULTISAMPLED
+@group(0) @binding(1)
+var source_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(1)
+var source_texture: texture_depth_2d;
+#endif
+#else
+#ifdef MULTISAMPLED
+@group(0) @binding(1)
+var source_texture: texture_multisampled_2d<f32>;
+#else
+@group(0) @binding(1)
+var source_texture: texture_2d<f32>;
+#endif
+#endif
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let tex_size = vec2<f32>(textureDimensions(source_texture));
+    let tex_coord = vec2<i32>(in.uv * tex_size);
+
+    // Sample 0 when multisampled, mip 0 otherwise
+#ifdef DEPTH
+    // Load raw reverse-Z depth
+    // In Bevy's infinite reverse-Z: 1.0 = near plane, 0.0 = infinity
+    let raw_depth = textureLoad(source_texture, tex_coord, 0);
+
+    // Linearize depth: for infinite reverse-Z, view_z = near / raw_depth
+    var linear_depth: f32;
+    if (raw_depth <= 0.0001) {
+        // Sky / infinity — map to farthest scene depth
+        linear_depth = uniforms.scene_far;
+    } else {
+        linear_depth = uniforms.near_plane / raw_depth;
+    }
+
+    // Normalize to scene depth range: scene_near → white, scene_far → black
+    let depth_range = max(uniforms.scene_far - uniforms.scene_near, 0.001);
+    let normalized = 1.0 - saturate((linear_depth - uniforms.scene_near) / depth_range);
+
+    return vec4<f32>(normalized, normalized, normalized, 1.0);
+#endif
+
+#ifdef NORMALS
+    // The prepass stores normals remapped to 0..1, which is already a
+    // readable RGB encoding; the sky has no normal and stays black
+    let normal = textureLoad(source_texture, tex_coord, 0).rgb;
+    return vec4<f32>(normal, 1.0);
+#endif
+
+#ifdef AMBIENT_OCCLUSION
+    // 1.0 = unoccluded (white), 0.0 = fully occluded (black)
+    let visibility = textureLoad(source_texture, tex_coord, 0).r;
+    return vec4<f32>(visibility, visibility, visibility, 1.0);
+#endif
+}
@@ -1,11 +1,31 @@
 //! Wireframe overlay rendering
 //!
-//! Provides a faint wireframe overlay on all 3D objects.
+//! Provides a faint wireframe overlay on all 3D objects, plus per-object
+//! wireframes set with `SetWireframe`. Depth-tested wireframes use Bevy's
+//! line wireframe pass; see-through ones are drawn as gizmo lines on top of
+//! the scene. While sculpting, the sculpt target's wireframe is copied to its
+//! chunk meshes so density changes from tessellation stay visible.
 //! This feature requires the `wireframe` feature flag and only works on native
 //! builds (not WASM/WebGL2 due to GPU feature requirements).
 
-use bevy::pbr::wireframe::{WireframeConfig, WireframePlugin};
+use std::collections::{HashMap, HashSet};
+
+use bevy::ecs::message::Message;
+use bevy::gizmos::config::{GizmoConfig, GizmoConfigGroup};
+use bevy::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::pbr::wireframe::{
+    NoWireframe, Wireframe, WireframeColor, WireframeConfig, WireframePlugin,
+};
 use bevy::prelude::*;
+use pentimento_ipc::{BevyToUi, SceneInfo, WireframeInfo, WireframeTarget};
+
+use crate::OutboundUiMessages;
+use crate::hierarchy::SceneObjects;
+use crate::measure::SceneMeasurements;
+use crate::scene_light::SceneLights;
+#[cfg(feature = "sculpting")]
+use crate::sculpt_mode::{SculptState, SculptingData};
+use crate::selection::Selectable;
 
 /// Wireframe display settings
 #[derive(Resource)]
@@ -14,6 +34,8 @@ pub struct WireframeSettings {
     pub enabled: bool,
     /// Wireframe color
     pub color: Color,
+    /// Whether the wireframe is hidden behind nearer surfaces
+    pub depth_test: bool,
 }
 
 impl Default for WireframeSettings {
@@ -22,10 +44,63 @@ impl Default for WireframeSettings {
             enabled: true, // Enabled by default for debugging sculpt topology
             // Faint white wireframe
             color: Color::srgba(0.8, 0.8, 0.8, 0.5),
+            depth_test: true,
         }
     }
 }
 
+impl WireframeSettings {
+    /// Global overlay state as reported in `SceneInfo`
+    pub fn info(&self) -> WireframeInfo {
+        wireframe_info(self.enabled, self.color, self.depth_test)
+    }
+}
+
+/// Wireframe set on one object, overriding the global overlay
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ObjectWireframe {
+    pub enabled: bool,
+    pub color: Color,
+    /// Whether the wireframe is hidden behind nearer surfaces
+    pub depth_test: bool,
+}
+
+impl ObjectWireframe {
+    /// Object wireframe state as reported in `SceneObject`
+    pub fn info(&self) -> WireframeInfo {
+        wireframe_info(self.enabled, self.color, self.depth_test)
+    }
+}
+
+/// Message for changing the global or a per-object wireframe
+#[derive(Message, Debug, Clone)]
+pub struct WireframeEvent {
+    pub target: WireframeTarget,
+    pub enabled: bool,
+    /// New color; `None` keeps the current one
+    pub color: Option<[f32; 4]>,
+    pub depth_test: bool,
+}
+
+/// Gizmo group for see-through wireframes, drawn over the scene
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct SeeThroughWireframeGizmos;
+
+/// Unique edges of a mesh as vertex index pairs, rebuilt when the mesh asset
+/// changes. Positions are read every frame, so in-place vertex patches from
+/// sculpt strokes show up without a rebuild.
+#[derive(Default)]
+struct EdgeCache(HashMap<AssetId<Mesh>, Vec<[u32; 2]>>);
+
+/// Mesh objects as seen by the see-through wireframe pass
+type WireframeMeshData = (
+    &'static Mesh3d,
+    &'static GlobalTransform,
+    &'static InheritedVisibility,
+    Option<&'static ObjectWireframe>,
+    Has<NoWireframe>,
+);
+
 /// Plugin for wireframe overlay rendering
 pub struct WireframeOverlayPlugin;
 
@@ -33,14 +108,41 @@ impl Plugin for WireframeOverlayPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(WireframePlugin::default())
             .init_resource::<WireframeSettings>()
-            .add_systems(Update, sync_wireframe_config);
+            .add_message::<WireframeEvent>()
+            .insert_gizmo_config(
+                SeeThroughWireframeGizmos,
+                GizmoConfig {
+                    depth_bias: -1.0,
+                    ..default()
+                },
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_wireframe_events,
+                    sync_object_wireframes,
+                    report_wireframe_changes,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, sync_wireframe_config)
+            .add_systems(PostUpdate, draw_see_through_wireframes);
+
+        #[cfg(feature = "sculpting")]
+        app.add_systems(
+            Update,
+            follow_sculpt_chunks
+                .after(handle_wireframe_events)
+                .before(sync_object_wireframes),
+        );
     }
 }
 
 /// Sync WireframeConfig with WireframeSettings
 fn sync_wireframe_config(settings: Res<WireframeSettings>, mut config: ResMut<WireframeConfig>) {
     if settings.is_changed() {
-        config.global = settings.enabled;
+        // See-through wireframes are drawn as gizmos instead
+        config.global = settings.enabled && settings.depth_test;
         config.default_color = settings.color;
 
         if settings.enabled {
@@ -50,3 +152,210 @@ fn sync_wireframe_config(settings: Res<WireframeSettings>, mut config: ResMut<Wi
         }
     }
 }
+
+/// Apply `SetWireframe` to the global settings or an object
+fn handle_wireframe_events(
+    mut commands: Commands,
+    mut events: MessageReader<WireframeEvent>,
+    mut settings: ResMut<WireframeSettings>,
+    objects: Query<(Entity, &Selectable, Option<&ObjectWireframe>), With<Mesh3d>>,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    for event in events.read() {
+        let color = event.color.map(|[r, g, b, a]| Color::srgba(r, g, b, a));
+        match &event.target {
+            WireframeTarget::Global => {
+                settings.enabled = event.enabled;
+                settings.depth_test = event.depth_test;
+                if let Some(color) = color {
+                    settings.color = color;
+                }
+            }
+            WireframeTarget::Object(id) => {
+                let Some((entity, _, current)) = objects
+                    .iter()
+                    .find(|(_, selectable, _)| selectable.id == *id)
+                else {
+                    outbound
+                        .send_error("object_not_found", format!("No mesh object with ID {}", id));
+                    continue;
+                };
+                // A new object wireframe starts from the global color
+                let color = color
+                    .or(current.map(|wireframe| wireframe.color))
+                    .unwrap_or(settings.color);
+                commands.entity(entity).insert(ObjectWireframe {
+                    enabled: event.enabled,
+                    color,
+                    depth_test: event.depth_test,
+                });
+                debug!(
+                    "Wireframe for object {} {}",
+                    id,
+                    if event.enabled { "enabled" } else { "disabled" }
+                );
+            }
+        }
+    }
+}
+
+/// Give sculpt chunk meshes the wireframe of the object they replace
+#[cfg(feature = "sculpting")]
+fn follow_sculpt_chunks(
+    mut commands: Commands,
+    sculpt_state: Res<SculptState>,
+    sculpting_data: Res<SculptingData>,
+    wireframes: Query<Option<&ObjectWireframe>>,
+) {
+    let Some(target) = sculpt_state.target_entity.filter(|_| sculpt_state.active) else {
+        return;
+    };
+    let Ok(Some(target_wireframe)) = wireframes.get(target) else {
+        return;
+    };
+    for &chunk in sculpting_data.chunk_entities.values() {
+        if let Ok(current) = wireframes.get(chunk)
+            && current != Some(target_wireframe)
+        {
+            commands.entity(chunk).insert(*target_wireframe);
+        }
+    }
+}
+
+/// Map object wireframes onto Bevy's wireframe components. See-through and
+/// disabled wireframes opt out of the line pass (and so of the global overlay).
+fn sync_object_wireframes(
+    mut commands: Commands,
+    changed: Query<(Entity, &ObjectWireframe), Changed<ObjectWireframe>>,
+) {
+    for (entity, wireframe) in &changed {
+        let mut entity = commands.entity(entity);
+        if wireframe.enabled && wireframe.depth_test {
+            entity.remove::<NoWireframe>().insert((
+                Wireframe,
+                WireframeColor {
+                    color: wireframe.color,
+                },
+            ));
+        } else {
+            entity
+                .remove::<(Wireframe, WireframeColor)>()
+                .insert(NoWireframe);
+        }
+    }
+}
+
+/// Report wireframe changes so the UI checkboxes stay in sync
+fn report_wireframe_changes(
+    settings: Res<WireframeSettings>,
+    changed: Query<(), (Changed<ObjectWireframe>, With<Selectable>)>,
+    scene_objects: SceneObjects,
+    scene_lights: SceneLights,
+    scene_measurements: SceneMeasurements,
+    mut outbound: ResMut<OutboundUiMessages>,
+) {
+    let settings_changed = settings.is_changed() && !settings.is_added();
+    if !settings_changed && changed.is_empty() {
+        return;
+    }
+
+    outbound.send(BevyToUi::SceneUpdated(SceneInfo {
+        objects: scene_objects.infos(),
+        lights: scene_lights.infos(),
+        wireframe: scene_objects.wireframe(),
+        isolated: scene_objects.isolated(),
+        annotations: scene_measurements.infos(),
+        ..default()
+    }));
+}
+
+/// Draw see-through wireframes as gizmo lines over the scene
+fn draw_see_through_wireframes(
+    mut gizmos: Gizmos<SeeThroughWireframeGizmos>,
+    settings: Res<WireframeSettings>,
+    meshes: Res<Assets<Mesh>>,
+    mut mesh_events: MessageReader<AssetEvent<Mesh>>,
+    mut cache: Local<EdgeCache>,
+    objects: Query<WireframeMeshData>,
+) {
+    for event in mesh_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            cache.0.remove(id);
+        }
+    }
+
+    let global = (settings.enabled && !settings.depth_test).then_some(settings.color);
+    for (mesh3d, transform, visibility, wireframe, no_wireframe) in &objects {
+        let color = match wireframe {
+            Some(wireframe) if wireframe.enabled && !wireframe.depth_test => wireframe.color,
+            Some(_) => continue,
+            None if no_wireframe => continue,
+            None => match global {
+                Some(color) => color,
+                None => continue,
+            },
+        };
+        if !visibility.get() {
+            continue;
+        }
+        let Some(mesh) = meshes.get(&mesh3d.0) else {
+            continue;
+        };
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            continue;
+        };
+
+        let edges = cache
+            .0
+            .entry(mesh3d.id())
+            .or_insert_with(|| mesh_edges(mesh));
+        for &[a, b] in edges.iter() {
+            let (Some(a), Some(b)) = (positions.get(a as usize), positions.get(b as usize)) else {
+                continue;
+            };
+            gizmos.line(
+                transform.transform_point(Vec3::from(*a)),
+                transform.transform_point(Vec3::from(*b)),
+                color,
+            );
+        }
+    }
+}
+
+/// Unique triangle edges of a mesh (empty for non-triangle topologies)
+fn mesh_edges(mesh: &Mesh) -> Vec<[u32; 2]> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return Vec::new();
+    }
+    let indices: Vec<u32> = match mesh.indices() {
+        Some(Indices::U32(indices)) => indices.clone(),
+        Some(Indices::U16(indices)) => indices.iter().map(|&i| i as u32).collect(),
+        None => (0..mesh.count_vertices() as u32).collect(),
+    };
+
+    let mut seen = HashSet::new();
+    let mut edges = Vec::new();
+    for triangle in indices.chunks_exact(3) {
+        for (a, b) in [
+            (triangle[0], triangle[1]),
+            (triangle[1], triangle[2]),
+            (triangle[2], triangle[0]),
+        ] {
+            let edge = [a.min(b), a.max(b)];
+            if seen.insert(edge) {
+                edges.push(edge);
+            }
+        }
+    }
+    edges
+}
+
+fn wireframe_info(enabled: bool, color: Color, depth_test: bool) -> WireframeInfo {
+    WireframeInfo {
+        enabled,
+        color: color.to_srgba().to_f32_array(),
+        depth_test,
+    }
+}
@@ -0,0 +1,146 @@
+//! Replay a recorded sculpt stroke file and print the resulting mesh hash.
+//!
+//! ```text
+//! cargo run -p sculpting --features bevy --bin replay -- <strokes.pstk> [options]
+//!
+//!   --base <cube|sphere|torus>  base mesh (default: sphere)
+//!   --edge-length <len>         constant-mode tessellation edge length (default: 0.02)
+//!   --no-tessellation           deform only, never split or collapse
+//!   --chunk-faces <n>           target faces per chunk (default: 10000)
+//!   --repeat <n>                replay n times and fail if any hash differs
+//!   --obj <path>                write the resulting mesh as Wavefront OBJ
+//! ```
+//!
+//! Screen-space tessellation depends on the camera, which a stroke file
+//! doesn't record, so replay always uses constant-length tessellation.
+
+use sculpting::{
+    mesh_content_hash, primitive_base_mesh, read_packets, replay_packets, ChunkConfig,
+    PipelineConfig, TessellationMode,
+};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+
+struct Args {
+    strokes: String,
+    base: String,
+    config: PipelineConfig,
+    repeat: usize,
+    obj: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut strokes = None;
+    let mut base = "sphere".to_string();
+    let mut config = PipelineConfig::default();
+    config.tessellation_config.mode = TessellationMode::Constant;
+    let mut repeat = 1;
+    let mut obj = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} needs a value"));
+        match arg.as_str() {
+            "--base" => base = value("--base")?,
+            "--edge-length" => {
+                config.tessellation_config.target_edge_length = value("--edge-length")?
+                    .parse()
+                    .map_err(|_| "--edge-length must be a number")?;
+            }
+            "--no-tessellation" => config.tessellation_enabled = false,
+            "--chunk-faces" => {
+                let target: usize = value("--chunk-faces")?
+                    .parse()
+                    .map_err(|_| "--chunk-faces must be a positive integer")?;
+                config.chunk_config = ChunkConfig {
+                    min_faces: target / 2,
+                    max_faces: target * 2,
+                    target_faces: target,
+                };
+            }
+            "--repeat" => {
+                repeat = value("--repeat")?
+                    .parse()
+                    .map_err(|_| "--repeat must be a positive integer")?;
+            }
+            "--obj" => obj = Some(value("--obj")?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ => strokes = Some(arg),
+        }
+    }
+
+    Ok(Args {
+        strokes: strokes.ok_or("missing stroke file")?,
+        base,
+        config,
+        repeat: repeat.max(1),
+        obj,
+    })
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let base_mesh = primitive_base_mesh(&args.base)
+        .ok_or_else(|| format!("unknown base mesh {:?}", args.base))?;
+    let mut file = File::open(&args.strokes).map_err(|err| format!("{}: {err}", args.strokes))?;
+    let packets = read_packets(&mut file).map_err(|err| format!("{}: {err}", args.strokes))?;
+
+    let dab_count: usize = packets.iter().map(|packet| packet.dabs.len()).sum();
+    println!("{} packets, {} dabs", packets.len(), dab_count);
+
+    let mut result = replay_packets(&base_mesh, &packets, args.config.clone());
+    let hash = mesh_content_hash(&result);
+    println!(
+        "{} vertices, {} faces",
+        result.vertex_count(),
+        result.face_count()
+    );
+    println!("hash {hash:#018x} ({hash})");
+
+    for run in 1..args.repeat {
+        result = replay_packets(&base_mesh, &packets, args.config.clone());
+        let repeat_hash = mesh_content_hash(&result);
+        if repeat_hash != hash {
+            return Err(format!(
+                "replay {} produced hash {repeat_hash:#018x}, expected {hash:#018x}",
+                run + 1
+            ));
+        }
+    }
+    if args.repeat > 1 {
+        println!("{} replays matched", args.repeat);
+    }
+
+    if let Some(path) = &args.obj {
+        write_obj(path, &result).map_err(|err| format!("{path}: {err}"))?;
+        println!("wrote {path}");
+    }
+
+    Ok(())
+}
+
+fn write_obj(path: &str, mesh: &painting::half_edge::HalfEdgeMesh) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for vertex in mesh.vertices() {
+        let p = vertex.position;
+        writeln!(out, "v {} {} {}", p.x, p.y, p.z)?;
+    }
+    for face in mesh.faces() {
+        write!(out, "f")?;
+        for vertex_id in mesh.get_face_vertices(face.id) {
+            write!(out, " {}", vertex_id.0 + 1)?;
+        }
+        writeln!(out)?;
+    }
+    out.flush()
+}
+
+fn main() -> ExitCode {
+    match parse_args().and_then(run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("replay: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -88,8 +88,14 @@ pub struct BrushPreset {
     pub pressure_affects_radius: bool,
     /// Whether to use pressure sensitivity for strength
     pub pressure_affects_strength: bool,
-    /// Spacing between dabs as fraction of radius (0.1 = 10% of radius)
+    /// Spacing between dabs as fraction of radius (0.1 = 10% of radius).
+    /// Dabs are placed this far apart along the input path, however often
+    /// input samples arrive. 0.0 emits one dab per input sample.
     pub spacing: f32,
+    /// Per-dab strength multiplier (0.0 to 1.0). Lower flow builds up
+    /// deformation gradually over overlapping dabs.
+    #[serde(default = "default_flow")]
+    pub flow: f32,
     /// Auto-smooth strength applied after each dab (0.0 = off, 1.0 = full).
     /// Dampens high-frequency surface ripples from dab boundaries.
     #[serde(default = "default_autosmooth")]
@@ -100,6 +106,10 @@ pub struct BrushPreset {
     pub hardness: f32,
 }
 
+fn default_flow() -> f32 {
+    1.0
+}
+
 fn default_autosmooth() -> f32 {
     0.5
 }
@@ -108,6 +118,23 @@ fn default_hardness() -> f32 {
     0.5
 }
 
+/// Return the built-in sculpt brush presets. A preset's ID is its index.
+pub fn builtin_presets() -> Vec<BrushPreset> {
+    vec![
+        BrushPreset::push(),
+        BrushPreset::pull(),
+        BrushPreset::smooth(),
+        BrushPreset::flatten(),
+        BrushPreset::inflate(),
+        BrushPreset::pinch(),
+        BrushPreset::grab(),
+        BrushPreset::crease(),
+        BrushPreset::clay_strips(),
+        BrushPreset::scrape(),
+        BrushPreset::mask(),
+    ]
+}
+
 impl Default for BrushPreset {
     fn default() -> Self {
         Self {
@@ -119,6 +146,7 @@ impl Default for BrushPreset {
             pressure_affects_radius: false,
             pressure_affects_strength: true,
             spacing: 0.25,
+            flow: 1.0,
             autosmooth: 0.5,
             hardness: 0.5,
         }
@@ -210,6 +238,42 @@ impl BrushPreset {
         }
     }
 
+    /// Create a clay strips brush preset.
+    pub fn clay_strips() -> Self {
+        Self {
+            name: "Clay Strips".to_string(),
+            deformation_type: DeformationType::ClayStrips,
+            strength: 0.5,
+            falloff: FalloffCurve::Sharp,
+            spacing: 0.15,
+            hardness: 0.7,
+            ..Default::default()
+        }
+    }
+
+    /// Create a scrape brush preset.
+    pub fn scrape() -> Self {
+        Self {
+            name: "Scrape".to_string(),
+            deformation_type: DeformationType::Scrape,
+            strength: 0.4,
+            falloff: FalloffCurve::Smooth,
+            ..Default::default()
+        }
+    }
+
+    /// Create a mask brush preset.
+    pub fn mask() -> Self {
+        Self {
+            name: "Mask".to_string(),
+            deformation_type: DeformationType::Mask,
+            strength: 0.5,
+            falloff: FalloffCurve::Smooth,
+            autosmooth: 0.0,
+            ..Default::default()
+        }
+    }
+
     /// Get effective radius based on pressure.
     pub fn effective_radius(&self, pressure: f32) -> f32 {
         if self.pressure_affects_radius {
@@ -227,6 +291,11 @@ impl BrushPreset {
             self.strength
         }
     }
+
+    /// Get the strength of a single dab: effective strength scaled by flow.
+    pub fn dab_strength(&self, pressure: f32) -> f32 {
+        self.effective_strength(pressure) * self.flow
+    }
 }
 
 /// Input event for brush stroke.
@@ -251,9 +320,11 @@ pub struct StrokeState {
     pub mesh_id: u32,
     /// Starting timestamp
     pub start_time_ms: u64,
-    /// Last dab position (for spacing calculation)
+    /// Last dab position
     pub last_dab_position: Vec3,
-    /// Distance accumulated since last dab (for spacing)
+    /// Last input sample; dabs are interpolated between it and the next one
+    pub last_input: BrushInput,
+    /// Distance travelled along the input path since the last dab
     pub distance_since_dab: f32,
     /// Base position for delta compression (current packet)
     pub base_position: Vec3,
@@ -264,15 +335,16 @@ pub struct StrokeState {
 }
 
 impl StrokeState {
-    /// Create a new stroke state.
-    pub fn new(stroke_id: u64, mesh_id: u32, start_position: Vec3, timestamp_ms: u64) -> Self {
+    /// Create a new stroke state starting at `input`.
+    pub fn new(stroke_id: u64, mesh_id: u32, input: BrushInput) -> Self {
         Self {
             stroke_id,
             mesh_id,
-            start_time_ms: timestamp_ms,
-            last_dab_position: start_position,
+            start_time_ms: input.timestamp_ms,
+            last_dab_position: input.position,
+            last_input: input,
             distance_since_dab: 0.0,
-            base_position: start_position,
+            base_position: input.position,
             current_dabs: Vec::new(),
             completed_packets: Vec::new(),
         }
@@ -324,18 +396,18 @@ impl SculptBrushEngine {
         let stroke_id = self.next_stroke_id;
         self.next_stroke_id += 1;
 
-        self.active_stroke = Some(StrokeState::new(
-            stroke_id,
-            mesh_id,
-            input.position,
-            input.timestamp_ms,
-        ));
+        self.active_stroke = Some(StrokeState::new(stroke_id, mesh_id, input));
 
         stroke_id
     }
 
     /// Update the stroke with new input.
     ///
+    /// Dabs are placed along the segment from the previous input sample, exactly
+    /// `spacing * radius` apart measured along the whole input path, with
+    /// pressure, normal and timestamp interpolated. The number of dabs depends
+    /// only on the distance travelled, not on how many samples arrived.
+    ///
     /// Returns dabs generated from this input (may be empty if spacing not met).
     pub fn update_stroke(&mut self, input: BrushInput) -> Vec<DabResult> {
         // Take the stroke out to avoid borrow conflicts
@@ -344,45 +416,47 @@ impl SculptBrushEngine {
         };
 
         let mut results = Vec::new();
-        let effective_radius = self.preset.effective_radius(input.pressure);
-        let spacing_distance = effective_radius * self.preset.spacing;
+        let from = stroke.last_input;
+        let segment = input.position - from.position;
+        let length = segment.length();
+
+        // Spacing follows the radius, which may change with pressure
+        let avg_pressure = (from.pressure + input.pressure) * 0.5;
+        let spacing_distance = self.preset.effective_radius(avg_pressure) * self.preset.spacing;
 
-        // For grab brush (spacing = 0), always emit a dab
         if spacing_distance <= 0.0 {
+            // For grab brush (spacing = 0), always emit a dab
             let dab = self.create_dab(&mut stroke, input);
             results.push(dab);
             stroke.last_dab_position = input.position;
-            self.active_stroke = Some(stroke);
-            return results;
-        }
-
-        // Calculate distance from last dab
-        let distance = input.position.distance(stroke.last_dab_position);
-        stroke.distance_since_dab += distance;
-
-        // Emit dabs along the path if spacing is exceeded
-        if stroke.distance_since_dab >= spacing_distance {
-            let direction = (input.position - stroke.last_dab_position).normalize_or_zero();
-            let mut current_pos = stroke.last_dab_position;
-
-            while stroke.distance_since_dab >= spacing_distance {
-                current_pos += direction * spacing_distance;
-                stroke.distance_since_dab -= spacing_distance;
+        } else {
+            // Distance along this segment to the next dab. Clamped because the
+            // spacing may have shrunk with pressure since the last dab.
+            let mut offset = (spacing_distance - stroke.distance_since_dab).max(0.0);
 
+            while length > 0.0 && offset <= length {
+                let t = offset / length;
                 let dab_input = BrushInput {
-                    position: current_pos,
-                    normal: input.normal,
-                    pressure: input.pressure,
-                    timestamp_ms: input.timestamp_ms,
+                    position: from.position + segment * t,
+                    normal: from.normal.lerp(input.normal, t).normalize_or(input.normal),
+                    pressure: from.pressure + (input.pressure - from.pressure) * t,
+                    timestamp_ms: from.timestamp_ms
+                        + ((input.timestamp_ms.saturating_sub(from.timestamp_ms)) as f32 * t)
+                            as u64,
                 };
 
                 let dab = self.create_dab(&mut stroke, dab_input);
                 results.push(dab);
+                stroke.last_dab_position = dab_input.position;
+                offset += spacing_distance;
             }
 
-            stroke.last_dab_position = current_pos;
+            // Distance covered since the last dab placed (on this or an earlier segment)
+            stroke.distance_since_dab = length - (offset - spacing_distance);
         }
 
+        stroke.last_input = input;
+
         // Put the stroke back
         self.active_stroke = Some(stroke);
         results
@@ -450,7 +524,7 @@ impl SculptBrushEngine {
             position: input.position,
             normal: input.normal,
             radius: self.preset.effective_radius(input.pressure),
-            strength: self.preset.effective_strength(input.pressure),
+            strength: self.preset.dab_strength(input.pressure),
             dab,
         }
     }
@@ -469,10 +543,12 @@ impl SculptBrushEngine {
                 deformation_type: self.preset.deformation_type,
                 base_radius: (self.preset.radius * 1000.0) as u32,
                 strength: (self.preset.strength * 255.0) as u8,
+                flow: (self.preset.flow.clamp(0.0, 1.0) * 255.0) as u8,
                 flags: 0,
                 base_x: (stroke.base_position.x * base_scale) as i32,
                 base_y: (stroke.base_position.y * base_scale) as i32,
                 base_z: (stroke.base_position.z * base_scale) as i32,
+                lamport: 0,
             },
             dabs: stroke.current_dabs.clone(),
         }
@@ -488,7 +564,7 @@ pub struct DabResult {
     pub normal: Vec3,
     /// Effective radius
     pub radius: f32,
-    /// Effective strength
+    /// Effective strength, scaled by flow
     pub strength: f32,
     /// The compressed dab data
     pub dab: SculptDab,
@@ -554,6 +630,16 @@ mod tests {
         assert!((preset.strength - 0.5).abs() < 0.001);
     }
 
+    #[test]
+    fn test_builtin_presets_cover_plane_brushes() {
+        let types: Vec<DeformationType> = builtin_presets()
+            .iter()
+            .map(|preset| preset.deformation_type)
+            .collect();
+        assert!(types.contains(&DeformationType::ClayStrips));
+        assert!(types.contains(&DeformationType::Scrape));
+    }
+
     #[test]
     fn test_effective_radius_with_pressure() {
         let mut preset = BrushPreset::default();
@@ -626,4 +712,68 @@ mod tests {
 
         engine.end_stroke();
     }
+
+    /// Dabs for a straight 10cm stroke split into `samples` input events.
+    fn straight_stroke_dabs(preset: BrushPreset, samples: u64) -> Vec<DabResult> {
+        let mut engine = SculptBrushEngine::new(preset);
+        let input = |i: u64| BrushInput {
+            position: Vec3::new(0.1 * i as f32 / samples as f32, 0.0, 0.0),
+            normal: Vec3::Y,
+            pressure: 1.0,
+            timestamp_ms: i,
+        };
+
+        engine.begin_stroke(1, input(0));
+        let dabs: Vec<DabResult> = (1..=samples)
+            .flat_map(|i| engine.update_stroke(input(i)))
+            .collect();
+        engine.end_stroke();
+        dabs
+    }
+
+    #[test]
+    fn test_spacing_independent_of_sample_count() {
+        let preset = BrushPreset {
+            radius: 0.01,
+            spacing: 0.25,
+            ..Default::default()
+        };
+
+        for samples in [1, 3, 16, 101, 1000] {
+            let dabs = straight_stroke_dabs(preset.clone(), samples);
+            assert!(
+                (39..=41).contains(&dabs.len()),
+                "{} samples emitted {} dabs",
+                samples,
+                dabs.len()
+            );
+
+            // Consecutive dabs are one spacing apart along the path
+            for pair in dabs.windows(2) {
+                let gap = pair[0].position.distance(pair[1].position);
+                assert!(
+                    (gap - 0.0025).abs() < 1e-4,
+                    "gap {} with {} samples",
+                    gap,
+                    samples
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_flow_scales_dab_strength() {
+        let full = straight_stroke_dabs(BrushPreset::default(), 4);
+        let half = straight_stroke_dabs(
+            BrushPreset {
+                flow: 0.5,
+                ..Default::default()
+            },
+            4,
+        );
+
+        assert_eq!(full.len(), half.len());
+        assert!((full[0].strength - 0.5).abs() < 0.001);
+        assert!((half[0].strength - 0.25).abs() < 0.001);
+    }
 }
@@ -22,6 +22,10 @@ pub struct VertexBudget {
     pub pixel_coverage: u32,
     /// Whether the budget needs recalculation (e.g. render camera moved)
     pub stale: bool,
+    /// User cap on `max_vertices`, applied on top of pixel coverage
+    pub limit: Option<usize>,
+    /// `max_vertices` from pixel coverage alone, before `limit`
+    coverage_max: usize,
 }
 
 impl Default for VertexBudget {
@@ -32,6 +36,8 @@ impl Default for VertexBudget {
             remaining: isize::MAX,
             pixel_coverage: 0,
             stale: true,
+            limit: None,
+            coverage_max: usize::MAX,
         }
     }
 }
@@ -46,17 +52,27 @@ impl VertexBudget {
             remaining: max_vertices.max(100) as isize,
             pixel_coverage,
             stale: false,
+            limit: None,
+            coverage_max: max_vertices.max(100),
         }
     }
 
     /// Update the budget's max from new pixel coverage data.
     pub fn update_max(&mut self, pixel_coverage: u32, vertices_per_pixel: f32) {
         self.pixel_coverage = pixel_coverage;
-        self.max_vertices = ((pixel_coverage as f32 * vertices_per_pixel) as usize).max(100);
-        self.recalculate_remaining();
+        self.coverage_max = ((pixel_coverage as f32 * vertices_per_pixel) as usize).max(100);
+        self.apply_limit();
         self.stale = false;
     }
 
+    /// Cap the budget at `limit` vertices (`None` = pixel coverage only).
+    ///
+    /// The cap shares the 100-vertex floor of the coverage budget.
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+        self.apply_limit();
+    }
+
     /// Update the current vertex count and recalculate remaining.
     pub fn update_current(&mut self, current_vertices: usize) {
         self.current_vertices = current_vertices;
@@ -85,8 +101,38 @@ impl VertexBudget {
         self.remaining < 0
     }
 
+    /// Split the remaining capacity into `parts` shares, one per chunk
+    /// tessellated in parallel.
+    ///
+    /// Shares differ by at most one vertex and sum to `remaining`, so an
+    /// over-budget mesh (negative remaining) spreads its collapses the same way.
+    pub fn split_remaining(&self, parts: usize) -> Vec<VertexBudget> {
+        if parts == 0 {
+            return Vec::new();
+        }
+        let parts_isize = parts as isize;
+        let share = self.remaining.div_euclid(parts_isize);
+        let extra = self.remaining.rem_euclid(parts_isize) as usize;
+        (0..parts)
+            .map(|i| VertexBudget {
+                remaining: share + isize::from(i < extra),
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    fn apply_limit(&mut self) {
+        self.max_vertices = match self.limit {
+            Some(limit) => self.coverage_max.min(limit.max(100)),
+            None => self.coverage_max,
+        };
+        self.recalculate_remaining();
+    }
+
     fn recalculate_remaining(&mut self) {
-        self.remaining = self.max_vertices as isize - self.current_vertices as isize;
+        // An unlimited budget (usize::MAX) would wrap to -1 as isize
+        let max = self.max_vertices.min(isize::MAX as usize) as isize;
+        self.remaining = max - self.current_vertices as isize;
     }
 }
 
@@ -162,6 +208,53 @@ mod tests {
         assert!(budget.is_over_budget());
     }
 
+    #[test]
+    fn test_split_remaining_sums_to_remaining() {
+        let mut budget = VertexBudget::from_pixel_coverage(1000, 1.0);
+        budget.update_current(990);
+        let shares: Vec<isize> = budget
+            .split_remaining(4)
+            .iter()
+            .map(|b| b.remaining)
+            .collect();
+        assert_eq!(shares, vec![3, 3, 2, 2]);
+
+        // Over budget: every share collapses
+        budget.update_current(1005);
+        let shares: Vec<isize> = budget
+            .split_remaining(3)
+            .iter()
+            .map(|b| b.remaining)
+            .collect();
+        assert_eq!(shares, vec![-1, -2, -2]);
+        assert_eq!(shares.iter().sum::<isize>(), -5);
+
+        assert!(budget.split_remaining(0).is_empty());
+    }
+
+    #[test]
+    fn test_limit_caps_coverage_budget() {
+        let mut budget = VertexBudget::default();
+        budget.set_limit(Some(5000));
+        assert_eq!(budget.max_vertices, 5000);
+
+        // Coverage below the limit wins
+        budget.update_max(1000, 1.0);
+        assert_eq!(budget.max_vertices, 1000);
+
+        // Coverage above the limit is capped
+        budget.update_max(8000, 1.0);
+        assert_eq!(budget.max_vertices, 5000);
+
+        budget.set_limit(None);
+        assert_eq!(budget.max_vertices, 8000);
+
+        let mut unlimited = VertexBudget::default();
+        unlimited.set_limit(None);
+        unlimited.update_current(10);
+        assert!(unlimited.can_split());
+    }
+
     #[test]
     fn test_vertices_per_pixel_multiplier() {
         let budget = VertexBudget::from_pixel_coverage(1000, 2.0);
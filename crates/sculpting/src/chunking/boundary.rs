@@ -4,7 +4,7 @@
 //! duplicated in each adjacent chunk. This module tracks these relationships
 //! and ensures consistent positions and normals across chunk boundaries.
 
-use super::{ChunkId, ChunkedMesh};
+use super::{ChunkId, ChunkedMesh, MeshChunk};
 use glam::Vec3;
 use painting::half_edge::VertexId;
 
@@ -89,8 +89,11 @@ pub fn recalculate_boundary_normals(chunked_mesh: &mut ChunkedMesh) {
     let mut boundary_normals: std::collections::HashMap<VertexId, Vec<Vec3>> =
         std::collections::HashMap::new();
 
-    // First pass: gather all face normals for boundary vertices
-    for chunk in chunked_mesh.chunks.values() {
+    // First pass: gather all face normals for boundary vertices.
+    // Chunks are visited in ID order so the sums (and normals) are deterministic.
+    let mut chunks: Vec<&MeshChunk> = chunked_mesh.chunks.values().collect();
+    chunks.sort_by_key(|chunk| chunk.id.0);
+    for chunk in chunks {
         for (&local_id, _) in &chunk.boundary_vertices {
             // Get original vertex ID for grouping
             let original_id = match chunk.local_to_original.get(&local_id) {
@@ -125,8 +128,15 @@ pub fn recalculate_boundary_normals(chunked_mesh: &mut ChunkedMesh) {
         // Apply to all chunks containing this boundary vertex
         for chunk in chunked_mesh.chunks.values_mut() {
             if let Some(&local_id) = chunk.original_to_local.get(&original_id) {
-                if let Some(vertex) = chunk.mesh.vertex_mut(local_id) {
-                    vertex.normal = averaged;
+                let changed = match chunk.mesh.vertex_mut(local_id) {
+                    Some(vertex) if vertex.normal != averaged => {
+                        vertex.normal = averaged;
+                        true
+                    }
+                    _ => false,
+                };
+                if changed {
+                    chunk.mark_vertices_dirty([local_id]);
                 }
             }
         }
@@ -146,7 +156,7 @@ pub fn sync_vertex_position(
     // First, set in the source chunk
     if let Some(chunk) = chunked_mesh.chunks.get_mut(&chunk_id) {
         chunk.mesh.set_vertex_position(local_vertex_id, new_position);
-        chunk.mark_dirty();
+        chunk.mark_vertices_dirty([local_vertex_id]);
     }
 
     // Then sync to neighbors
@@ -5,6 +5,7 @@
 //! duplicates.
 
 use super::{boundary, ChunkId, ChunkedMesh};
+use crate::gpu::DirtyVertices;
 use crate::ChunkConfig;
 use glam::Vec3;
 use painting::half_edge::{Face, FaceId, HalfEdge, HalfEdgeId, HalfEdgeMesh, Vertex, VertexId};
@@ -32,6 +33,8 @@ pub fn merge_chunks(chunked_mesh: &ChunkedMesh) -> MergeResult {
     let mut original_vertices: HashMap<VertexId, Vec3> = HashMap::new();
     let mut original_normals: HashMap<VertexId, Vec3> = HashMap::new();
     let mut original_uvs: HashMap<VertexId, Option<glam::Vec2>> = HashMap::new();
+    let mut original_masks: HashMap<VertexId, f32> = HashMap::new();
+    let mut original_colors: HashMap<VertexId, glam::Vec4> = HashMap::new();
 
     for chunk in chunked_mesh.chunks.values() {
         for vertex in chunk.mesh.vertices() {
@@ -42,6 +45,8 @@ pub fn merge_chunks(chunked_mesh: &ChunkedMesh) -> MergeResult {
                 original_vertices.insert(original_id, vertex.position);
                 original_normals.insert(original_id, vertex.normal);
                 original_uvs.insert(original_id, vertex.uv);
+                original_masks.insert(original_id, vertex.mask);
+                original_colors.insert(original_id, vertex.color);
             }
         }
     }
@@ -66,6 +71,8 @@ pub fn merge_chunks(chunked_mesh: &ChunkedMesh) -> MergeResult {
             uv: original_uvs[&original_id],
             outgoing_half_edge: None,
             source_index: original_id.0,
+            mask: original_masks[&original_id],
+            color: original_colors[&original_id],
         });
     }
 
@@ -256,12 +263,16 @@ pub fn merge_two_chunks(
     let mut all_originals: HashMap<VertexId, Vec3> = HashMap::new();
     let mut all_normals: HashMap<VertexId, Vec3> = HashMap::new();
     let mut all_uvs: HashMap<VertexId, Option<glam::Vec2>> = HashMap::new();
+    let mut all_masks: HashMap<VertexId, f32> = HashMap::new();
+    let mut all_colors: HashMap<VertexId, glam::Vec4> = HashMap::new();
 
     for (local_id, &original_id) in &a.local_to_original {
         if let Some(v) = a.mesh.vertex(*local_id) {
             all_originals.insert(original_id, v.position);
             all_normals.insert(original_id, v.normal);
             all_uvs.insert(original_id, v.uv);
+            all_masks.insert(original_id, v.mask);
+            all_colors.insert(original_id, v.color);
         }
     }
 
@@ -272,6 +283,8 @@ pub fn merge_two_chunks(
             all_originals.insert(original_id, v.position);
             all_normals.insert(original_id, v.normal);
             all_uvs.insert(original_id, v.uv);
+            all_masks.insert(original_id, v.mask);
+            all_colors.insert(original_id, v.color);
         }
     }
 
@@ -295,6 +308,8 @@ pub fn merge_two_chunks(
             uv: all_uvs[&original_id],
             outgoing_half_edge: None,
             source_index: original_id.0,
+            mask: all_masks[&original_id],
+            color: all_colors[&original_id],
         });
     }
 
@@ -421,6 +436,7 @@ pub fn merge_two_chunks(
         boundary_vertices: HashMap::new(),
         dirty: true,
         topology_changed: true,
+        dirty_vertices: DirtyVertices::default(),
     };
 
     let merged_id = chunked_mesh.add_chunk(merged_chunk);
@@ -600,4 +616,33 @@ mod tests {
         // Should have same number of faces
         assert_eq!(result.mesh.face_count(), he_mesh.face_count());
     }
+
+    #[test]
+    fn test_mask_survives_partition_and_merge() {
+        let mesh = create_test_mesh(100);
+        let mut he_mesh = HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap();
+        for i in 0..he_mesh.vertex_count() {
+            he_mesh.set_vertex_mask(VertexId(i as u32), (i % 3) as f32 * 0.5);
+        }
+
+        let config = PartitionConfig {
+            target_faces: 50,
+            min_faces: 20,
+            max_faces: 100,
+        };
+        let mut chunked = partition_mesh(&he_mesh, &config);
+        let result = merge_chunks(&chunked);
+
+        for (&original_id, &unified_id) in &result.vertex_mapping {
+            let expected = he_mesh.vertex(original_id).unwrap().mask;
+            assert_eq!(result.mesh.vertex(unified_id).unwrap().mask, expected);
+        }
+
+        chunked.invert_mask();
+        let inverted = merge_chunks(&chunked);
+        for (&original_id, &unified_id) in &inverted.vertex_mapping {
+            let expected = 1.0 - he_mesh.vertex(original_id).unwrap().mask;
+            assert_eq!(inverted.mesh.vertex(unified_id).unwrap().mask, expected);
+        }
+    }
 }
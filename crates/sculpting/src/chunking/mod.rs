@@ -18,12 +18,14 @@ mod boundary;
 pub mod merge;
 pub mod partition;
 
+pub(crate) use boundary::build_boundary_relationships;
 pub use boundary::{
     get_original_vertex_id, is_boundary_vertex, sync_vertex_position, BoundaryVertex,
 };
 pub use merge::{merge_chunks, merge_two_chunks, rebalance_chunks, MergeResult};
 pub use partition::{partition_mesh, split_chunk, PartitionConfig};
 
+use crate::gpu::DirtyVertices;
 use crate::ChunkConfig;
 use glam::{Vec3, UVec3};
 use painting::half_edge::{HalfEdgeMesh, VertexId};
@@ -126,6 +128,11 @@ pub struct MeshChunk {
     pub dirty: bool,
     /// Whether topology has changed (need full buffer rebuild).
     pub topology_changed: bool,
+    /// Vertices whose position or normal changed since the last GPU sync.
+    ///
+    /// Lets position-only syncs patch just these vertices. Meaningless once
+    /// `topology_changed` is set, since the whole chunk is rebuilt then.
+    pub dirty_vertices: DirtyVertices,
 }
 
 impl MeshChunk {
@@ -144,6 +151,12 @@ impl MeshChunk {
         self.dirty = true;
     }
 
+    /// Mark vertices whose position or normal changed as needing a GPU update.
+    pub fn mark_vertices_dirty(&mut self, vertices: impl IntoIterator<Item = VertexId>) {
+        self.dirty = true;
+        self.dirty_vertices.mark_all(vertices);
+    }
+
     /// Mark this chunk as having topology changes.
     pub fn mark_topology_changed(&mut self) {
         self.topology_changed = true;
@@ -154,6 +167,7 @@ impl MeshChunk {
     pub fn clear_dirty(&mut self) {
         self.dirty = false;
         self.topology_changed = false;
+        self.dirty_vertices.clear();
     }
 
     /// Update the bounding box from current vertex positions.
@@ -494,11 +508,18 @@ impl ChunkedMesh {
             }
         };
 
-        // Update in neighboring chunks
+        // Update in neighboring chunks, skipping copies that are already in sync
+        // so untouched boundaries don't get re-uploaded
         for boundary_ref in boundary_refs {
             if let Some(neighbor) = self.chunks.get_mut(&boundary_ref.chunk_id) {
-                neighbor.mesh.set_vertex_position(boundary_ref.vertex_id, new_position);
-                neighbor.mark_dirty();
+                let in_sync = neighbor
+                    .mesh
+                    .vertex(boundary_ref.vertex_id)
+                    .is_some_and(|v| v.position == new_position);
+                if !in_sync {
+                    neighbor.mesh.set_vertex_position(boundary_ref.vertex_id, new_position);
+                    neighbor.mark_vertices_dirty([boundary_ref.vertex_id]);
+                }
             }
         }
     }
@@ -520,6 +541,40 @@ impl ChunkedMesh {
     pub fn recalculate_boundary_normals(&mut self) {
         boundary::recalculate_boundary_normals(self);
     }
+
+    /// Clear the sculpt mask of every vertex.
+    pub fn clear_mask(&mut self) {
+        self.update_masks(|_| 0.0);
+    }
+
+    /// Invert the sculpt mask of every vertex (`mask` becomes `1 - mask`).
+    pub fn invert_mask(&mut self) {
+        self.update_masks(|mask| 1.0 - mask);
+    }
+
+    /// Apply `update` to every vertex's mask, marking changed vertices dirty.
+    ///
+    /// Boundary copies get the same update in every chunk, so they stay in sync.
+    fn update_masks(&mut self, update: impl Fn(f32) -> f32) {
+        for chunk in self.chunks.values_mut() {
+            let changed: Vec<(VertexId, f32)> = chunk
+                .mesh
+                .vertices()
+                .iter()
+                .filter_map(|v| {
+                    let mask = update(v.mask);
+                    (mask != v.mask).then_some((v.id, mask))
+                })
+                .collect();
+            if changed.is_empty() {
+                continue;
+            }
+            for &(vertex_id, mask) in &changed {
+                chunk.mesh.set_vertex_mask(vertex_id, mask);
+            }
+            chunk.mark_vertices_dirty(changed.into_iter().map(|(vertex_id, _)| vertex_id));
+        }
+    }
 }
 
 impl Default for ChunkedMesh {
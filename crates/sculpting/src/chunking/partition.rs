@@ -5,6 +5,7 @@
 //! to create chunks of roughly equal face count.
 
 use super::{boundary, Aabb, ChunkId, ChunkedMesh, MeshChunk};
+use crate::gpu::DirtyVertices;
 use crate::ChunkConfig;
 use glam::Vec3;
 use painting::half_edge::{Face, FaceId, HalfEdgeMesh, Vertex, VertexId, HalfEdge, HalfEdgeId};
@@ -191,6 +192,7 @@ fn create_chunk_from_faces(mesh: &HalfEdgeMesh, face_ids: Vec<FaceId>) -> MeshCh
         boundary_vertices: HashMap::new(), // Built later
         dirty: false,
         topology_changed: false,
+        dirty_vertices: DirtyVertices::default(),
     }
 }
 
@@ -214,6 +216,8 @@ fn build_chunk_mesh(
             uv: source_vertex.uv,
             outgoing_half_edge: None, // Will be set when building half-edges
             source_index: source_vertex.source_index,
+            mask: source_vertex.mask,
+            color: source_vertex.color,
         });
     }
 
@@ -407,6 +411,7 @@ fn create_chunk_from_faces_local(
         boundary_vertices: HashMap::new(),
         dirty: false,
         topology_changed: false,
+        dirty_vertices: DirtyVertices::default(),
     }
 }
 
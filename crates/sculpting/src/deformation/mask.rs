@@ -0,0 +1,188 @@
+//! Sculpt mask: painting it and protecting masked vertices.
+//!
+//! The mask lives on each half-edge vertex (`Vertex::mask`), so it follows
+//! the vertex through chunking, merging and edge splits. A mask of 1.0 fully
+//! protects a vertex; every displacing brush is scaled by `1 - mask`.
+
+use glam::Vec3;
+use painting::half_edge::{HalfEdgeMesh, VertexId};
+use std::collections::HashMap;
+
+use super::DabInfo;
+use crate::brush::FalloffCurve;
+
+/// Paint mask values instead of displacing vertices.
+///
+/// Adds `falloff × strength` to each vertex's mask (clamped to 0.0-1.0).
+/// Positions don't change, so the returned map is always empty.
+pub fn apply_mask(
+    mesh: &mut HalfEdgeMesh,
+    vertices: &[VertexId],
+    dab: &DabInfo,
+    falloff: FalloffCurve,
+) -> HashMap<VertexId, Vec3> {
+    for &vertex_id in vertices {
+        let Some(vertex) = mesh.vertex(vertex_id) else {
+            continue;
+        };
+
+        let distance = vertex.position.distance(dab.position);
+        if distance > dab.radius {
+            continue;
+        }
+
+        let normalized_dist = distance / dab.radius;
+        let strength = falloff.evaluate_with_hardness(normalized_dist, dab.hardness) * dab.strength;
+        let mask = vertex.mask + strength;
+        mesh.set_vertex_mask(vertex_id, mask);
+    }
+
+    HashMap::new()
+}
+
+/// Scale each displacement since `original_positions` by `1 - mask`.
+///
+/// Applied after a brush has run, which is equivalent to scaling the brush's
+/// per-vertex strength for brushes whose displacement is linear in strength.
+pub fn attenuate_by_mask(mesh: &mut HalfEdgeMesh, original_positions: &HashMap<VertexId, Vec3>) {
+    for (&vertex_id, &original) in original_positions {
+        let Some(vertex) = mesh.vertex(vertex_id) else {
+            continue;
+        };
+        if vertex.mask <= 0.0 {
+            continue;
+        }
+
+        let displacement = vertex.position - original;
+        mesh.set_vertex_position(vertex_id, original + displacement * (1.0 - vertex.mask));
+    }
+}
+
+#[cfg(all(test, feature = "bevy"))]
+mod tests {
+    use super::*;
+    use crate::deformation::apply_deformation;
+    use crate::tessellation::split_edge;
+    use crate::types::DeformationType;
+    use bevy::asset::RenderAssetUsages;
+    use bevy::mesh::{Indices, Mesh, PrimitiveTopology};
+
+    /// Flat 4 × 4 quad grid on the XZ plane, centered on the origin.
+    fn build_flat_grid() -> HalfEdgeMesh {
+        let size = 4;
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::new();
+
+        for z in 0..=size {
+            for x in 0..=size {
+                positions.push([x as f32 * 0.1 - 0.2, 0.0, z as f32 * 0.1 - 0.2]);
+                normals.push([0.0, 1.0, 0.0]);
+            }
+        }
+        for z in 0..size {
+            for x in 0..size {
+                let v0 = (z * (size + 1) + x) as u32;
+                let v1 = v0 + 1;
+                let v2 = v0 + (size + 1) as u32;
+                let v3 = v2 + 1;
+                indices.extend_from_slice(&[v0, v2, v1, v1, v2, v3]);
+            }
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_indices(Indices::U32(indices));
+        HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap()
+    }
+
+    fn dab(strength: f32) -> DabInfo {
+        DabInfo {
+            position: Vec3::ZERO,
+            normal: Vec3::Y,
+            radius: 0.5,
+            strength,
+            hardness: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_fully_masked_vertices_do_not_move() {
+        let mut mesh = build_flat_grid();
+        let vertices: Vec<VertexId> = mesh.vertices().iter().map(|v| v.id).collect();
+
+        // Mask the left half of the grid
+        for &id in &vertices {
+            if mesh.vertex(id).unwrap().position.x < 0.0 {
+                mesh.set_vertex_mask(id, 1.0);
+            }
+        }
+        let before: Vec<Vec3> = vertices
+            .iter()
+            .map(|&id| mesh.vertex(id).unwrap().position)
+            .collect();
+
+        apply_deformation(
+            &mut mesh,
+            &vertices,
+            &dab(10.0),
+            DeformationType::Push,
+            FalloffCurve::Constant,
+            None,
+            None,
+        );
+
+        for (&id, &old) in vertices.iter().zip(&before) {
+            let vertex = mesh.vertex(id).unwrap();
+            if vertex.mask >= 1.0 {
+                assert_eq!(vertex.position, old, "masked vertex moved");
+            } else {
+                assert!(vertex.position.y > old.y, "unmasked vertex didn't move");
+            }
+        }
+    }
+
+    #[test]
+    fn test_mask_brush_paints_without_moving() {
+        let mut mesh = build_flat_grid();
+        let vertices: Vec<VertexId> = mesh.vertices().iter().map(|v| v.id).collect();
+
+        let moved = apply_deformation(
+            &mut mesh,
+            &vertices,
+            &dab(0.6),
+            DeformationType::Mask,
+            FalloffCurve::Constant,
+            None,
+            None,
+        );
+
+        assert!(moved.is_empty());
+        for vertex in mesh.vertices() {
+            assert_eq!(vertex.position.y, 0.0);
+            assert!((vertex.mask - 0.6).abs() < 1e-6);
+        }
+
+        // Clamped at fully masked
+        apply_mask(&mut mesh, &vertices, &dab(0.6), FalloffCurve::Constant);
+        assert!(mesh.vertices().iter().all(|v| v.mask == 1.0));
+    }
+
+    #[test]
+    fn test_split_interpolates_mask() {
+        let mut mesh = build_flat_grid();
+        let v0 = VertexId(0);
+        let v1 = VertexId(1);
+        mesh.set_vertex_mask(v0, 1.0);
+
+        // Boundary edge, only present as v1 -> v0
+        let edge = mesh.find_half_edge(v1, v0).unwrap();
+        let result = split_edge(&mut mesh, edge).unwrap();
+
+        assert!((mesh.vertex(result.new_vertex).unwrap().mask - 0.5).abs() < 1e-6);
+    }
+}
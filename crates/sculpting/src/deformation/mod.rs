@@ -4,6 +4,17 @@
 //! positions based on brush input. Each deformation type has different
 //! behavior and may require different context (e.g., neighboring vertices
 //! for smoothing).
+//!
+//! Every displacing brush respects the per-vertex sculpt mask: displacement
+//! is scaled by `1 - mask`, so fully masked vertices never move.
+
+mod mask;
+mod planar;
+
+pub use mask::{apply_mask, attenuate_by_mask};
+pub use planar::{
+    apply_clay_strips, apply_scrape, fit_surface_plane, SurfacePlane, CLAY_STRIPS_HEIGHT,
+};
 
 use glam::Vec3;
 use painting::half_edge::{HalfEdgeMesh, VertexId};
@@ -305,9 +316,11 @@ pub fn apply_autosmooth(
         let normal = vertex.normal.normalize_or_zero();
         let tangent_offset = offset - normal * offset.dot(normal);
 
-        // Scale by falloff and autosmooth strength
+        // Scale by falloff, autosmooth strength and mask
         let normalized_dist = distance / dab.radius;
-        let effective = falloff.evaluate_with_hardness(normalized_dist, dab.hardness) * autosmooth_strength;
+        let effective = falloff.evaluate_with_hardness(normalized_dist, dab.hardness)
+            * autosmooth_strength
+            * (1.0 - vertex.mask);
 
         targets.push((vid, vertex.position + tangent_offset * effective));
     }
@@ -530,7 +543,7 @@ pub fn apply_deformation(
     stroke_direction: Option<Vec3>,
     stroke_delta: Option<Vec3>,
 ) -> HashMap<VertexId, Vec3> {
-    match deformation_type {
+    let original_positions = match deformation_type {
         DeformationType::Push => apply_push(mesh, vertices, dab, falloff),
         DeformationType::Pull => apply_pull(mesh, vertices, dab, falloff),
         DeformationType::Grab => {
@@ -545,7 +558,15 @@ pub fn apply_deformation(
             let direction = stroke_direction.unwrap_or(Vec3::X);
             apply_crease(mesh, vertices, dab, falloff, direction)
         }
-    }
+        DeformationType::ClayStrips => {
+            apply_clay_strips(mesh, vertices, dab, falloff, stroke_direction)
+        }
+        DeformationType::Scrape => apply_scrape(mesh, vertices, dab, falloff),
+        DeformationType::Mask => return apply_mask(mesh, vertices, dab, falloff),
+    };
+
+    attenuate_by_mask(mesh, &original_positions);
+    original_positions
 }
 
 #[cfg(test)]
@@ -0,0 +1,329 @@
+//! Plane-based brushes: Clay Strips and Scrape.
+//!
+//! Both brushes fit a plane to the surface under the brush and move vertices
+//! relative to it. The fit is area-weighted over the faces touching the
+//! affected vertices, so dense regions of a dynamically tessellated mesh
+//! don't pull the plane toward themselves.
+
+use glam::Vec3;
+use painting::half_edge::{HalfEdgeMesh, VertexId};
+use std::collections::HashMap;
+
+use super::DabInfo;
+use crate::brush::FalloffCurve;
+
+/// Height of the clay strips target plane above the fitted surface, as a
+/// fraction of the brush radius.
+pub const CLAY_STRIPS_HEIGHT: f32 = 0.25;
+
+/// A plane fitted to the surface under a dab.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfacePlane {
+    /// Area-weighted centroid of the faces under the brush
+    pub origin: Vec3,
+    /// Area-weighted normal, oriented to agree with the dab normal
+    pub normal: Vec3,
+}
+
+impl SurfacePlane {
+    /// Signed distance from the plane (positive on the normal side).
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        (point - self.origin).dot(self.normal)
+    }
+}
+
+/// Fit a plane to the faces touching the vertices inside the dab radius.
+///
+/// Each face contributes its centroid and normal weighted by its area.
+/// Returns `None` if no vertex is inside the radius or the faces are
+/// degenerate.
+pub fn fit_surface_plane(
+    mesh: &HalfEdgeMesh,
+    vertices: &[VertexId],
+    dab: &DabInfo,
+) -> Option<SurfacePlane> {
+    let mut faces = Vec::new();
+    for &vertex_id in vertices {
+        let Some(vertex) = mesh.vertex(vertex_id) else {
+            continue;
+        };
+        if vertex.position.distance(dab.position) <= dab.radius {
+            faces.extend(mesh.get_vertex_faces(vertex_id));
+        }
+    }
+    // Sum in a fixed order so the fit is bit-for-bit reproducible on replay
+    faces.sort_unstable_by_key(|face_id| face_id.0);
+    faces.dedup();
+
+    let mut weighted_centroid = Vec3::ZERO;
+    let mut weighted_normal = Vec3::ZERO;
+    let mut total_area = 0.0;
+    for face_id in faces {
+        let positions: Vec<Vec3> = mesh
+            .get_face_vertices(face_id)
+            .into_iter()
+            .filter_map(|id| mesh.vertex(id).map(|v| v.position))
+            .collect();
+        if positions.len() < 3 {
+            continue;
+        }
+
+        // Fan-triangulate so quads and n-gons are weighted correctly
+        for i in 1..positions.len() - 1 {
+            let (a, b, c) = (positions[0], positions[i], positions[i + 1]);
+            let cross = (b - a).cross(c - a);
+            let area = cross.length() * 0.5;
+            weighted_centroid += (a + b + c) / 3.0 * area;
+            // |cross| is twice the area, so this is already area-weighted
+            weighted_normal += cross;
+            total_area += area;
+        }
+    }
+
+    if total_area <= f32::EPSILON {
+        return None;
+    }
+
+    let mut normal = weighted_normal.normalize_or_zero();
+    if normal.length_squared() < 0.01 {
+        normal = dab.normal.normalize_or_zero();
+    } else if normal.dot(dab.normal) < 0.0 {
+        normal = -normal;
+    }
+
+    Some(SurfacePlane {
+        origin: weighted_centroid / total_area,
+        normal,
+    })
+}
+
+/// Apply clay strips deformation - builds up flat layers of material.
+///
+/// Vertices are raised toward a plane offset [`CLAY_STRIPS_HEIGHT`] × radius
+/// above the fitted surface. The falloff is evaluated on a square footprint
+/// aligned with the stroke direction, which gives the strips their flat,
+/// hard-edged look. Each vertex moves at most `strength` of the way to the
+/// target plane, so one dab never raises anything by more than
+/// `strength * CLAY_STRIPS_HEIGHT * radius`.
+pub fn apply_clay_strips(
+    mesh: &mut HalfEdgeMesh,
+    vertices: &[VertexId],
+    dab: &DabInfo,
+    falloff: FalloffCurve,
+    stroke_direction: Option<Vec3>,
+) -> HashMap<VertexId, Vec3> {
+    let mut original_positions = HashMap::new();
+
+    let Some(plane) = fit_surface_plane(mesh, vertices, dab) else {
+        return original_positions;
+    };
+    let height = dab.radius * CLAY_STRIPS_HEIGHT;
+
+    // Square footprint axes in the fitted plane
+    let along = stroke_direction
+        .map(|dir| dir - plane.normal * dir.dot(plane.normal))
+        .and_then(|dir| dir.try_normalize())
+        .unwrap_or_else(|| plane.normal.any_orthonormal_vector());
+    let across = plane.normal.cross(along);
+
+    for &vertex_id in vertices {
+        let Some(vertex) = mesh.vertex(vertex_id) else {
+            continue;
+        };
+
+        let offset = vertex.position - dab.position;
+        if offset.length() > dab.radius {
+            continue;
+        }
+
+        // Chebyshev distance: constant along the edges of a square
+        let square_dist = offset.dot(along).abs().max(offset.dot(across).abs());
+        let normalized_dist = (square_dist / dab.radius).min(1.0);
+        let strength = falloff.evaluate_with_hardness(normalized_dist, dab.hardness) * dab.strength;
+
+        let depth = height - plane.signed_distance(vertex.position);
+        if depth <= 0.0 || strength <= 0.0 {
+            continue;
+        }
+
+        let displacement = plane.normal * depth.min(height) * strength.min(1.0);
+
+        original_positions.insert(vertex_id, vertex.position);
+        mesh.set_vertex_position(vertex_id, vertex.position + displacement);
+    }
+
+    original_positions
+}
+
+/// Apply scrape deformation - removes material above the fitted plane.
+///
+/// Only vertices above the plane move, and only toward it; nothing is
+/// pushed below the plane or raised.
+pub fn apply_scrape(
+    mesh: &mut HalfEdgeMesh,
+    vertices: &[VertexId],
+    dab: &DabInfo,
+    falloff: FalloffCurve,
+) -> HashMap<VertexId, Vec3> {
+    let mut original_positions = HashMap::new();
+
+    let Some(plane) = fit_surface_plane(mesh, vertices, dab) else {
+        return original_positions;
+    };
+
+    for &vertex_id in vertices {
+        let Some(vertex) = mesh.vertex(vertex_id) else {
+            continue;
+        };
+
+        let distance = vertex.position.distance(dab.position);
+        if distance > dab.radius {
+            continue;
+        }
+
+        let height = plane.signed_distance(vertex.position);
+        if height <= 0.0 {
+            continue;
+        }
+
+        let normalized_dist = distance / dab.radius;
+        let strength = falloff.evaluate_with_hardness(normalized_dist, dab.hardness) * dab.strength;
+        if strength <= 0.0 {
+            continue;
+        }
+
+        let new_pos = vertex.position - plane.normal * height * strength.min(1.0);
+
+        original_positions.insert(vertex_id, vertex.position);
+        mesh.set_vertex_position(vertex_id, new_pos);
+    }
+
+    original_positions
+}
+
+#[cfg(all(test, feature = "bevy"))]
+mod tests {
+    use super::*;
+    use bevy::asset::RenderAssetUsages;
+    use bevy::mesh::{Indices, Mesh, PrimitiveTopology};
+
+    /// Flat `size` × `size` quad grid on the XZ plane, centered on the origin,
+    /// with 0.1 spacing.
+    fn build_flat_grid(size: usize) -> HalfEdgeMesh {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::new();
+        let half = size as f32 * 0.05;
+
+        for z in 0..=size {
+            for x in 0..=size {
+                positions.push([x as f32 * 0.1 - half, 0.0, z as f32 * 0.1 - half]);
+                normals.push([0.0, 1.0, 0.0]);
+            }
+        }
+        for z in 0..size {
+            for x in 0..size {
+                let v0 = (z * (size + 1) + x) as u32;
+                let v1 = v0 + 1;
+                let v2 = v0 + (size + 1) as u32;
+                let v3 = v2 + 1;
+                indices.extend_from_slice(&[v0, v2, v1, v1, v2, v3]);
+            }
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_indices(Indices::U32(indices));
+        HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap()
+    }
+
+    fn all_vertices(mesh: &HalfEdgeMesh) -> Vec<VertexId> {
+        mesh.vertices().iter().map(|v| v.id).collect()
+    }
+
+    fn dab(strength: f32) -> DabInfo {
+        DabInfo {
+            position: Vec3::ZERO,
+            normal: Vec3::Y,
+            radius: 0.5,
+            strength,
+            hardness: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_fit_surface_plane_on_flat_grid() {
+        let mesh = build_flat_grid(10);
+        let plane = fit_surface_plane(&mesh, &all_vertices(&mesh), &dab(0.5)).unwrap();
+
+        assert!(plane.origin.y.abs() < 1e-5);
+        assert!(plane.normal.abs_diff_eq(Vec3::Y, 1e-5));
+    }
+
+    #[test]
+    fn test_scrape_never_moves_vertices_above_plane() {
+        let mut mesh = build_flat_grid(10);
+        let vertices = all_vertices(&mesh);
+
+        // Bumps and dents around the brush center
+        for (i, &id) in vertices.iter().enumerate() {
+            let position = mesh.vertex(id).unwrap().position;
+            let offset = if i % 3 == 0 { 0.05 } else { -0.03 } * (1.0 - position.length());
+            mesh.set_vertex_position(id, position + Vec3::Y * offset);
+        }
+
+        let dab = dab(0.8);
+        let plane = fit_surface_plane(&mesh, &vertices, &dab).unwrap();
+        let before: Vec<Vec3> = vertices
+            .iter()
+            .map(|&id| mesh.vertex(id).unwrap().position)
+            .collect();
+
+        let moved = apply_scrape(&mut mesh, &vertices, &dab, FalloffCurve::Smooth);
+        assert!(!moved.is_empty());
+
+        for (&id, &old) in vertices.iter().zip(&before) {
+            let new = mesh.vertex(id).unwrap().position;
+            let old_height = plane.signed_distance(old);
+            let new_height = plane.signed_distance(new);
+
+            if old_height <= 0.0 {
+                assert_eq!(new, old, "vertex below the plane moved");
+            } else {
+                assert!(new_height <= old_height + 1e-6, "vertex moved up");
+                assert!(new_height >= -1e-6, "vertex pushed through the plane");
+            }
+        }
+    }
+
+    #[test]
+    fn test_clay_strips_bounded_by_strength() {
+        let strength = 0.4;
+        let mut mesh = build_flat_grid(10);
+        let vertices = all_vertices(&mesh);
+        let dab = dab(strength);
+
+        let moved = apply_clay_strips(
+            &mut mesh,
+            &vertices,
+            &dab,
+            FalloffCurve::Constant,
+            Some(Vec3::X),
+        );
+        assert!(!moved.is_empty());
+
+        let max_rise = strength * CLAY_STRIPS_HEIGHT * dab.radius;
+        for (id, old) in moved {
+            let new = mesh.vertex(id).unwrap().position;
+            let rise = new - old;
+            // Straight up, never more than the strength allows
+            assert!(rise.x.abs() < 1e-6 && rise.z.abs() < 1e-6);
+            assert!(rise.y > 0.0);
+            assert!(rise.y <= max_rise + 1e-6);
+        }
+    }
+}
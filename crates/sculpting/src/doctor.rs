@@ -0,0 +1,682 @@
+//! Runtime mesh integrity audit and repair.
+//!
+//! Long sculpt sessions can leave small topology defects behind: stale twin
+//! pointers, faces whose half-edge loop no longer closes, vertices that lost
+//! their `local_to_original` mapping, or boundary links that point at a
+//! vertex the neighboring chunk has since renumbered. Any of these shows up
+//! as a hole after [`merge_chunks`](crate::merge_chunks).
+//!
+//! [`MeshDoctor`] audits a [`ChunkedMesh`] one chunk at a time, repairs what
+//! it can with the same tools tessellation uses (degenerate face removal,
+//! `compact`, twin rebuild and mapping repair), and produces a
+//! [`MeshHealthReport`]. Work is time-sliced: each [`MeshDoctor::step`]
+//! stops once its frame budget is spent, so an audit of a large mesh is
+//! spread over several frames instead of stalling the next stroke.
+
+use crate::chunking::{build_boundary_relationships, ChunkId, ChunkedMesh, MeshChunk};
+use crate::tessellation::compact_chunk;
+use painting::half_edge::{FaceId, VertexId};
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Longest face loop the audit will walk before calling it untraversable.
+const MAX_FACE_EDGES: usize = 64;
+
+/// A single integrity problem found in a chunk.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MeshIssue {
+    #[error(
+        "chunk {chunk}: face {face} half-edge loop doesn't close ({vertices} vertices walked)"
+    )]
+    UntraversableFace {
+        chunk: u32,
+        face: u32,
+        vertices: u32,
+    },
+    #[error("chunk {chunk}: face {face} uses vertex {vertex} more than once")]
+    DegenerateFace { chunk: u32, face: u32, vertex: u32 },
+    #[error("chunk {chunk}: face {face} references missing vertex {vertex}")]
+    MissingVertex { chunk: u32, face: u32, vertex: u32 },
+    #[error("chunk {chunk}: vertex {vertex} has no original vertex mapping")]
+    MissingMapping { chunk: u32, vertex: u32 },
+    #[error("chunk {chunk}: half-edge {half_edge} has an inconsistent or missing twin")]
+    TwinMismatch { chunk: u32, half_edge: u32 },
+    #[error("chunk {chunk}: vertex {vertex} isn't used by any face")]
+    OrphanedVertex { chunk: u32, vertex: u32 },
+    #[error("chunk {chunk}: vertex {vertex} has a stale or missing link to chunk {neighbor}")]
+    BoundaryMismatch {
+        chunk: u32,
+        vertex: u32,
+        neighbor: u32,
+    },
+}
+
+impl MeshIssue {
+    /// The chunk the issue was found in.
+    pub fn chunk(&self) -> ChunkId {
+        match *self {
+            MeshIssue::UntraversableFace { chunk, .. }
+            | MeshIssue::DegenerateFace { chunk, .. }
+            | MeshIssue::MissingVertex { chunk, .. }
+            | MeshIssue::MissingMapping { chunk, .. }
+            | MeshIssue::TwinMismatch { chunk, .. }
+            | MeshIssue::OrphanedVertex { chunk, .. }
+            | MeshIssue::BoundaryMismatch { chunk, .. } => ChunkId(chunk),
+        }
+    }
+
+    /// Whether rebuilding boundary relationships fixes this issue.
+    fn is_boundary(&self) -> bool {
+        matches!(self, MeshIssue::BoundaryMismatch { .. })
+    }
+
+    /// The face to remove, if the issue can only be fixed by dropping a face.
+    fn bad_face(&self) -> Option<FaceId> {
+        match *self {
+            MeshIssue::UntraversableFace { face, .. }
+            | MeshIssue::DegenerateFace { face, .. }
+            | MeshIssue::MissingVertex { face, .. } => Some(FaceId(face)),
+            _ => None,
+        }
+    }
+}
+
+/// Per-chunk audit summary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkHealth {
+    pub chunk_id: ChunkId,
+    pub vertices: usize,
+    pub faces: usize,
+    pub boundary_vertices: usize,
+    /// Issues still present after repair.
+    pub issues: usize,
+}
+
+/// Result of a full [`MeshDoctor`] audit.
+#[derive(Debug, Clone, Default)]
+pub struct MeshHealthReport {
+    /// Every issue found, including ones that were repaired.
+    pub errors: Vec<MeshIssue>,
+    /// How many of `errors` were repaired.
+    pub repaired: usize,
+    /// Per-chunk summary, ordered by chunk ID.
+    pub chunk_stats: Vec<ChunkHealth>,
+}
+
+impl MeshHealthReport {
+    /// True if no issues were found, or all of them were repaired.
+    pub fn is_healthy(&self) -> bool {
+        self.repaired == self.errors.len()
+    }
+}
+
+/// Configuration for [`MeshDoctor`].
+#[derive(Debug, Clone)]
+pub struct DoctorConfig {
+    /// Repair issues instead of only reporting them.
+    pub repair: bool,
+    /// Start an audit automatically after this many strokes (0 = never).
+    pub auto_every_strokes: u32,
+    /// Time one [`MeshDoctor::step`] may spend before yielding.
+    pub frame_budget: Duration,
+}
+
+impl Default for DoctorConfig {
+    fn default() -> Self {
+        Self {
+            repair: true,
+            auto_every_strokes: 0,
+            frame_budget: Duration::from_millis(2),
+        }
+    }
+}
+
+/// Time-sliced chunked mesh auditor.
+#[derive(Debug, Default)]
+pub struct MeshDoctor {
+    pub config: DoctorConfig,
+    /// Chunks still to audit in the running audit.
+    pending: VecDeque<ChunkId>,
+    /// Report being built by the running audit.
+    report: Option<MeshHealthReport>,
+    /// Whether the running audit changed any chunk's topology.
+    topology_repaired: bool,
+    strokes_since_audit: u32,
+}
+
+impl MeshDoctor {
+    pub fn new(config: DoctorConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Start a full audit, restarting any audit already running.
+    pub fn start_audit(&mut self, chunked_mesh: &ChunkedMesh) {
+        let mut chunk_ids: Vec<ChunkId> = chunked_mesh.chunks.keys().copied().collect();
+        chunk_ids.sort_by_key(|id| id.0);
+        self.pending = chunk_ids.into();
+        self.report = Some(MeshHealthReport::default());
+        self.topology_repaired = false;
+        self.strokes_since_audit = 0;
+    }
+
+    /// Whether an audit is in progress.
+    pub fn is_auditing(&self) -> bool {
+        self.report.is_some()
+    }
+
+    /// Drop the running audit without reporting.
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+        self.report = None;
+        self.topology_repaired = false;
+    }
+
+    /// Count a finished stroke, starting an audit if one is due.
+    ///
+    /// Returns true if an audit was started.
+    pub fn stroke_finished(&mut self, chunked_mesh: &ChunkedMesh) -> bool {
+        self.strokes_since_audit += 1;
+        let every = self.config.auto_every_strokes;
+        if every == 0 || self.strokes_since_audit < every || self.is_auditing() {
+            return false;
+        }
+        self.start_audit(chunked_mesh);
+        true
+    }
+
+    /// Audit (and repair) chunks until the frame budget runs out.
+    ///
+    /// Always processes at least one chunk. Returns the report once the last
+    /// chunk is done.
+    pub fn step(&mut self, chunked_mesh: &mut ChunkedMesh) -> Option<MeshHealthReport> {
+        let report = self.report.as_mut()?;
+        let start = Instant::now();
+
+        while let Some(chunk_id) = self.pending.pop_front() {
+            // Chunks can be split or merged between slices
+            if let Some((health, repaired)) =
+                check_chunk(chunked_mesh, chunk_id, self.config.repair, report)
+            {
+                self.topology_repaired |= repaired;
+                report.chunk_stats.push(health);
+            }
+            if start.elapsed() >= self.config.frame_budget {
+                break;
+            }
+        }
+        if !self.pending.is_empty() {
+            return None;
+        }
+
+        let mut report = self.report.take()?;
+        let boundary_issues = report.errors.iter().filter(|e| e.is_boundary()).count();
+        if self.config.repair && (boundary_issues > 0 || self.topology_repaired) {
+            // Repairs renumber vertices, so every chunk's links to them are stale
+            build_boundary_relationships(chunked_mesh);
+            report.repaired += boundary_issues;
+            for stats in &mut report.chunk_stats {
+                stats.issues -= report
+                    .errors
+                    .iter()
+                    .filter(|e| e.is_boundary() && e.chunk() == stats.chunk_id)
+                    .count();
+                if let Some(chunk) = chunked_mesh.get_chunk(stats.chunk_id) {
+                    stats.boundary_vertices = chunk.boundary_vertices.len();
+                }
+            }
+        }
+        self.topology_repaired = false;
+
+        if report.errors.is_empty() {
+            tracing::debug!("MeshDoctor: {} chunks healthy", report.chunk_stats.len());
+        } else {
+            tracing::warn!(
+                "MeshDoctor: found {} issues, repaired {}",
+                report.errors.len(),
+                report.repaired
+            );
+        }
+        Some(report)
+    }
+
+    /// Run a full audit without time slicing.
+    pub fn run_to_completion(&mut self, chunked_mesh: &mut ChunkedMesh) -> MeshHealthReport {
+        if !self.is_auditing() {
+            self.start_audit(chunked_mesh);
+        }
+        loop {
+            if let Some(report) = self.step(chunked_mesh) {
+                return report;
+            }
+        }
+    }
+}
+
+/// Audit one chunk, repair it if asked, and record its issues.
+///
+/// Returns the chunk's health and whether its topology was changed, or
+/// `None` if the chunk no longer exists.
+fn check_chunk(
+    chunked_mesh: &mut ChunkedMesh,
+    chunk_id: ChunkId,
+    repair: bool,
+    report: &mut MeshHealthReport,
+) -> Option<(ChunkHealth, bool)> {
+    let issues = audit_chunk(chunked_mesh, chunk_id);
+    let topology_issues = issues.iter().filter(|e| !e.is_boundary()).count();
+    let mut remaining = issues.len();
+    let mut repaired_topology = false;
+
+    if repair && topology_issues > 0 {
+        let mut next_original_vertex_id = chunked_mesh.next_original_vertex_id;
+        let chunk = chunked_mesh.get_chunk_mut(chunk_id)?;
+        repair_chunk(chunk, &issues, &mut next_original_vertex_id);
+        chunked_mesh.next_original_vertex_id = next_original_vertex_id;
+        repaired_topology = true;
+
+        // Boundary links are left for the final rebuild
+        let still_broken = audit_chunk(chunked_mesh, chunk_id)
+            .iter()
+            .filter(|e| !e.is_boundary())
+            .count();
+        report.repaired += topology_issues.saturating_sub(still_broken);
+        remaining = still_broken + (issues.len() - topology_issues);
+    }
+    report.errors.extend(issues);
+
+    let chunk = chunked_mesh.get_chunk(chunk_id)?;
+    let health = ChunkHealth {
+        chunk_id,
+        vertices: chunk.vertex_count(),
+        faces: chunk.face_count(),
+        boundary_vertices: chunk.boundary_vertices.len(),
+        issues: remaining,
+    };
+    Some((health, repaired_topology))
+}
+
+/// Remove broken faces, then compact the chunk so dead elements, twins and
+/// mappings are rebuilt.
+fn repair_chunk(chunk: &mut MeshChunk, issues: &[MeshIssue], next_original_vertex_id: &mut u32) {
+    let bad_faces: HashSet<FaceId> = issues.iter().filter_map(MeshIssue::bad_face).collect();
+    for &face_id in &bad_faces {
+        chunk.mesh.detach_face(face_id);
+    }
+
+    compact_chunk(chunk, next_original_vertex_id, "mesh doctor");
+    chunk.recalculate_bounds();
+    chunk.mark_topology_changed();
+}
+
+/// Check one chunk for integrity issues without changing it.
+///
+/// Returns an empty list if the chunk is healthy or doesn't exist.
+pub fn audit_chunk(chunked_mesh: &ChunkedMesh, chunk_id: ChunkId) -> Vec<MeshIssue> {
+    let Some(chunk) = chunked_mesh.get_chunk(chunk_id) else {
+        return Vec::new();
+    };
+    let mesh = &chunk.mesh;
+    let c = chunk_id.0;
+    let mut issues = Vec::new();
+    let mut used_vertices: HashSet<VertexId> = HashSet::new();
+
+    // Face traversability
+    for face in mesh.faces() {
+        let face_id = face.id;
+        if !mesh.is_face_valid(face_id) {
+            // Removed by collapse, waiting for compact
+            continue;
+        }
+
+        let mut loop_vertices = Vec::new();
+        let mut current = face.half_edge;
+        let closed = loop {
+            let Some(he) = mesh.half_edge(current) else {
+                break false;
+            };
+            if he.face != Some(face_id) || loop_vertices.len() >= MAX_FACE_EDGES {
+                break false;
+            }
+            loop_vertices.push(he.origin);
+            current = he.next;
+            if current == face.half_edge {
+                break true;
+            }
+        };
+        if !closed || loop_vertices.len() < 3 {
+            issues.push(MeshIssue::UntraversableFace {
+                chunk: c,
+                face: face_id.0,
+                vertices: loop_vertices.len() as u32,
+            });
+            continue;
+        }
+
+        if let Some(&missing) = loop_vertices.iter().find(|v| mesh.vertex(**v).is_none()) {
+            issues.push(MeshIssue::MissingVertex {
+                chunk: c,
+                face: face_id.0,
+                vertex: missing.0,
+            });
+            continue;
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(&repeated) = loop_vertices.iter().find(|v| !seen.insert(**v)) {
+            issues.push(MeshIssue::DegenerateFace {
+                chunk: c,
+                face: face_id.0,
+                vertex: repeated.0,
+            });
+            continue;
+        }
+        used_vertices.extend(loop_vertices);
+    }
+
+    // Twin consistency
+    for he in mesh.half_edges() {
+        if he.face.is_none() {
+            continue;
+        }
+        let Some(dest) = mesh.half_edge(he.next).map(|next| next.origin) else {
+            continue; // Reported as untraversable
+        };
+        let consistent = match he.twin {
+            Some(twin_id) => mesh.half_edge(twin_id).is_some_and(|twin| {
+                twin.origin == dest
+                    && twin.twin == Some(he.id)
+                    && mesh.half_edge(twin.next).map(|n| n.origin) == Some(he.origin)
+            }),
+            // A missing twin is only wrong if the reverse edge exists
+            None => mesh
+                .find_half_edge(dest, he.origin)
+                .and_then(|id| mesh.half_edge(id))
+                .is_none_or(|reverse| reverse.face.is_none()),
+        };
+        if !consistent {
+            issues.push(MeshIssue::TwinMismatch {
+                chunk: c,
+                half_edge: he.id.0,
+            });
+        }
+    }
+
+    // Mappings and orphans
+    for vertex in mesh.vertices() {
+        let vertex_id = vertex.id;
+        if !used_vertices.contains(&vertex_id) {
+            issues.push(MeshIssue::OrphanedVertex {
+                chunk: c,
+                vertex: vertex_id.0,
+            });
+        } else if !chunk.local_to_original.contains_key(&vertex_id) {
+            issues.push(MeshIssue::MissingMapping {
+                chunk: c,
+                vertex: vertex_id.0,
+            });
+        }
+    }
+
+    // Boundary links must point at the vertex the neighbor currently uses
+    let mut linked: HashSet<(VertexId, u32)> = HashSet::new();
+    for (&local_id, refs) in &chunk.boundary_vertices {
+        for boundary in refs {
+            linked.insert((local_id, boundary.chunk_id.0));
+            let ok = chunk.local_to_original.get(&local_id) == Some(&boundary.original_vertex_id)
+                && chunked_mesh
+                    .get_chunk(boundary.chunk_id)
+                    .and_then(|other| other.original_to_local.get(&boundary.original_vertex_id))
+                    == Some(&boundary.vertex_id);
+            if !ok {
+                issues.push(MeshIssue::BoundaryMismatch {
+                    chunk: c,
+                    vertex: local_id.0,
+                    neighbor: boundary.chunk_id.0,
+                });
+            }
+        }
+    }
+
+    // Open edges of a chunk are where it meets its neighbors; every vertex
+    // there that a neighbor also has needs a link to it
+    let mut open_vertices: Vec<VertexId> = mesh
+        .half_edges()
+        .iter()
+        .filter(|he| he.face.is_some() && he.twin.is_none())
+        .map(|he| he.origin)
+        .filter(|v| used_vertices.contains(v))
+        .collect();
+    open_vertices.sort_by_key(|v| v.0);
+    open_vertices.dedup();
+    let mut neighbors: Vec<&MeshChunk> = chunked_mesh
+        .chunks
+        .values()
+        .filter(|other| other.id != chunk_id)
+        .collect();
+    neighbors.sort_by_key(|other| other.id.0);
+    for local_id in open_vertices {
+        let Some(original) = chunk.local_to_original.get(&local_id) else {
+            continue; // Reported as a missing mapping
+        };
+        for other in &neighbors {
+            if other.original_to_local.contains_key(original)
+                && !linked.contains(&(local_id, other.id.0))
+            {
+                issues.push(MeshIssue::BoundaryMismatch {
+                    chunk: c,
+                    vertex: local_id.0,
+                    neighbor: other.id.0,
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(all(test, feature = "bevy"))]
+mod tests {
+    use super::*;
+    use crate::chunking::{partition_mesh, PartitionConfig};
+    use bevy::asset::RenderAssetUsages;
+    use bevy::mesh::{Indices, Mesh, PrimitiveTopology};
+    use painting::half_edge::HalfEdgeMesh;
+
+    /// `size` × `size` quad grid of unit extent on the XZ plane.
+    fn build_grid(size: usize) -> HalfEdgeMesh {
+        let step = 1.0 / size as f32;
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::new();
+
+        for z in 0..=size {
+            for x in 0..=size {
+                positions.push([x as f32 * step, 0.0, z as f32 * step]);
+                normals.push([0.0, 1.0, 0.0]);
+            }
+        }
+        for z in 0..size {
+            for x in 0..size {
+                let v0 = (z * (size + 1) + x) as u32;
+                let v1 = v0 + 1;
+                let v2 = v0 + (size + 1) as u32;
+                let v3 = v2 + 1;
+                indices.extend_from_slice(&[v0, v2, v1, v1, v2, v3]);
+            }
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_indices(Indices::U32(indices));
+        HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap()
+    }
+
+    /// 16×16 grid split into several chunks.
+    fn chunked_grid() -> ChunkedMesh {
+        let config = PartitionConfig {
+            target_faces: 128,
+            min_faces: 64,
+            max_faces: 256,
+        };
+        let chunked = partition_mesh(&build_grid(16), &config);
+        assert!(chunked.chunk_count() > 1);
+        chunked
+    }
+
+    fn first_chunk(chunked: &ChunkedMesh) -> ChunkId {
+        chunked
+            .chunks
+            .keys()
+            .copied()
+            .min_by_key(|id| id.0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_healthy_mesh_reports_no_issues() {
+        let mut chunked = chunked_grid();
+        let report = MeshDoctor::default().run_to_completion(&mut chunked);
+
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert!(report.is_healthy());
+        assert_eq!(report.chunk_stats.len(), chunked.chunk_count());
+        assert!(report
+            .chunk_stats
+            .iter()
+            .all(|stats| stats.boundary_vertices > 0));
+    }
+
+    #[test]
+    fn test_missing_mapping_is_repaired() {
+        let mut chunked = chunked_grid();
+        let chunk_id = first_chunk(&chunked);
+        let chunk = chunked.get_chunk_mut(chunk_id).unwrap();
+        let interior = (0..chunk.vertex_count() as u32)
+            .map(VertexId)
+            .find(|v| !chunk.boundary_vertices.contains_key(v))
+            .unwrap();
+        let original = chunk.local_to_original.remove(&interior).unwrap();
+        chunk.original_to_local.remove(&original);
+
+        let report = MeshDoctor::default().run_to_completion(&mut chunked);
+
+        assert!(report.errors.contains(&MeshIssue::MissingMapping {
+            chunk: chunk_id.0,
+            vertex: interior.0,
+        }));
+        assert!(report.is_healthy());
+        assert!(audit_chunk(&chunked, chunk_id).is_empty());
+        assert!(chunked.get_chunk(chunk_id).unwrap().topology_changed);
+    }
+
+    #[test]
+    fn test_orphaned_vertex_is_removed() {
+        let mut chunked = partition_mesh(&build_grid(4), &PartitionConfig::default());
+        let chunk_id = first_chunk(&chunked);
+        let chunk = chunked.get_chunk_mut(chunk_id).unwrap();
+        // A grid corner is used by a single triangle
+        let corner = (0..chunk.vertex_count() as u32)
+            .map(VertexId)
+            .find(|&v| chunk.mesh.get_vertex_faces(v).len() == 1)
+            .unwrap();
+        let face = chunk.mesh.get_vertex_faces(corner)[0];
+        chunk.mesh.detach_face(face);
+        let (vertices, faces) = (chunk.vertex_count(), chunk.face_count());
+
+        let report = MeshDoctor::default().run_to_completion(&mut chunked);
+
+        assert!(report.errors.contains(&MeshIssue::OrphanedVertex {
+            chunk: chunk_id.0,
+            vertex: corner.0,
+        }));
+        assert!(report.is_healthy(), "{:?}", report.errors);
+        let chunk = chunked.get_chunk(chunk_id).unwrap();
+        assert_eq!(chunk.vertex_count(), vertices - 1);
+        assert_eq!(chunk.face_count(), faces - 1);
+    }
+
+    #[test]
+    fn test_stale_boundary_link_is_rebuilt() {
+        let mut chunked = chunked_grid();
+        let chunk_id = first_chunk(&chunked);
+        let chunk = chunked.get_chunk_mut(chunk_id).unwrap();
+        let &vertex = chunk.boundary_vertices.keys().min_by_key(|v| v.0).unwrap();
+        chunk.boundary_vertices.remove(&vertex);
+
+        let issues = audit_chunk(&chunked, chunk_id);
+        assert!(issues.iter().any(
+            |issue| matches!(issue, MeshIssue::BoundaryMismatch { vertex: v, .. } if *v == vertex.0)
+        ));
+
+        let report = MeshDoctor::default().run_to_completion(&mut chunked);
+
+        assert!(report.is_healthy(), "{:?}", report.errors);
+        assert!(chunked
+            .get_chunk(chunk_id)
+            .unwrap()
+            .boundary_vertices
+            .contains_key(&vertex));
+        assert!(report.chunk_stats.iter().all(|stats| stats.issues == 0));
+    }
+
+    #[test]
+    fn test_report_only_mode_leaves_mesh_alone() {
+        let mut chunked = chunked_grid();
+        let chunk_id = first_chunk(&chunked);
+        let chunk = chunked.get_chunk_mut(chunk_id).unwrap();
+        let &vertex = chunk.boundary_vertices.keys().min_by_key(|v| v.0).unwrap();
+        chunk.boundary_vertices.remove(&vertex);
+
+        let mut doctor = MeshDoctor::new(DoctorConfig {
+            repair: false,
+            ..Default::default()
+        });
+        let report = doctor.run_to_completion(&mut chunked);
+
+        assert!(!report.is_healthy());
+        assert_eq!(report.repaired, 0);
+        assert!(!audit_chunk(&chunked, chunk_id).is_empty());
+    }
+
+    #[test]
+    fn test_zero_budget_audits_one_chunk_per_step() {
+        let mut chunked = chunked_grid();
+        let mut doctor = MeshDoctor::new(DoctorConfig {
+            frame_budget: Duration::ZERO,
+            ..Default::default()
+        });
+        doctor.start_audit(&chunked);
+
+        let mut steps = 1;
+        while doctor.step(&mut chunked).is_none() {
+            steps += 1;
+        }
+
+        assert_eq!(steps, chunked.chunk_count());
+        assert!(!doctor.is_auditing());
+    }
+
+    #[test]
+    fn test_auto_audit_every_n_strokes() {
+        let chunked = chunked_grid();
+        let mut doctor = MeshDoctor::new(DoctorConfig {
+            auto_every_strokes: 3,
+            ..Default::default()
+        });
+
+        assert!(!doctor.stroke_finished(&chunked));
+        assert!(!doctor.stroke_finished(&chunked));
+        assert!(doctor.stroke_finished(&chunked));
+        assert!(doctor.is_auditing());
+
+        doctor.cancel();
+        assert!(!doctor.is_auditing());
+    }
+}
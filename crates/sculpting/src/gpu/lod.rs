@@ -0,0 +1,418 @@
+//! Reduced-detail copies of sculpt chunks.
+//!
+//! A chunk that covers a few dozen pixels doesn't need all of its faces. Each
+//! chunk can have a decimated copy with about [`REDUCED_FACE_RATIO`] of its
+//! faces, built the first time the chunk is shown reduced and rebuilt after the
+//! chunk changes. [`ChunkDetail::select`] picks a level from the chunk's
+//! projected size. It uses a lower size to switch down than to switch back up,
+//! so a chunk near the threshold doesn't flicker while the camera moves.
+//!
+//! Decimation never collapses an edge that touches a chunk border. A reduced
+//! chunk therefore meets its full-detail neighbors without cracks.
+
+#[cfg(feature = "bevy")]
+use bevy::prelude::*;
+#[cfg(feature = "bevy")]
+use std::collections::HashMap;
+
+use crate::chunking::{Aabb, MeshChunk};
+#[cfg(feature = "bevy")]
+use crate::chunking::{ChunkId, ChunkedMesh};
+use crate::tessellation::{collapse_edge, ScreenSpaceConfig};
+use glam::{BVec3, Vec3};
+use painting::half_edge::{HalfEdgeId, HalfEdgeMesh, VertexId};
+use std::collections::HashSet;
+
+/// Fraction of a chunk's faces kept in its reduced copy.
+pub const REDUCED_FACE_RATIO: f32 = 0.25;
+
+/// Passes of shortest-edge collapses before decimation settles for what it has.
+const MAX_DECIMATION_PASSES: usize = 8;
+
+/// Which of a chunk's meshes is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChunkDetail {
+    /// Every face of the chunk
+    #[default]
+    Full,
+    /// The decimated copy
+    Reduced,
+}
+
+impl ChunkDetail {
+    /// Pick the detail level for a chunk whose bounds project to
+    /// `projected_size` pixels, given its current level.
+    pub fn select(self, projected_size: f32, config: &LodConfig) -> Self {
+        match self {
+            Self::Full if projected_size < config.reduce_below_px => Self::Reduced,
+            Self::Reduced if projected_size > config.restore_above_px => Self::Full,
+            current => current,
+        }
+    }
+}
+
+/// Thresholds for switching chunks between full and reduced detail.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LodConfig {
+    /// Full-detail chunks switch to reduced below this projected size (pixels)
+    pub reduce_below_px: f32,
+    /// Reduced chunks switch back to full detail above this projected size (pixels)
+    pub restore_above_px: f32,
+    /// Fraction of a chunk's faces kept in its reduced copy
+    pub face_ratio: f32,
+    /// Reduced copies rebuilt per GPU sync; chunks waiting their turn show full detail
+    pub max_rebuilds_per_sync: usize,
+}
+
+impl Default for LodConfig {
+    fn default() -> Self {
+        Self {
+            reduce_below_px: 160.0,
+            restore_above_px: 200.0,
+            face_ratio: REDUCED_FACE_RATIO,
+            max_rebuilds_per_sync: 8,
+        }
+    }
+}
+
+/// Largest on-screen extent of `bounds` in pixels.
+///
+/// Bounds entirely outside the view project to 0. Bounds reaching behind the
+/// camera are treated as infinitely large, since the camera is in or right
+/// next to them.
+pub fn projected_size(bounds: &Aabb, screen: &ScreenSpaceConfig) -> f32 {
+    let mut min = Vec3::splat(f32::INFINITY);
+    let mut max = Vec3::splat(f32::NEG_INFINITY);
+    for i in 0..8 {
+        let pick_max = BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0);
+        let corner = Vec3::select(pick_max, bounds.max, bounds.min);
+        let Some(ndc) = screen.project_to_ndc(corner) else {
+            return f32::INFINITY;
+        };
+        min = min.min(ndc);
+        max = max.max(ndc);
+    }
+
+    if max.x < -1.0 || min.x > 1.0 || max.y < -1.0 || min.y > 1.0 {
+        return 0.0;
+    }
+
+    let width = (max.x - min.x) * 0.5 * screen.viewport_width;
+    let height = (max.y - min.y) * 0.5 * screen.viewport_height;
+    width.max(height)
+}
+
+/// Build a copy of the chunk's mesh with about `face_ratio` of its faces.
+///
+/// Repeatedly collapses the shortest interior edges. Edges touching the chunk
+/// border are kept, so small chunks with long borders may stay above the
+/// target. The result is compacted and its vertex IDs don't match the chunk's.
+pub fn decimate_chunk(chunk: &MeshChunk, face_ratio: f32) -> HalfEdgeMesh {
+    let mut mesh = chunk.mesh.clone();
+    let mut faces = mesh.face_count();
+    let target = (faces as f32 * face_ratio.clamp(0.0, 1.0)).ceil() as usize;
+
+    for _ in 0..MAX_DECIMATION_PASSES {
+        if faces <= target {
+            break;
+        }
+        let removed = decimation_pass(&mut mesh, faces - target);
+        if removed == 0 {
+            break;
+        }
+        faces = faces.saturating_sub(removed);
+        // Collapses leave orphaned elements that read as borders until compacted
+        mesh.compact();
+    }
+
+    mesh
+}
+
+/// Collapse the shortest edges, leaving the neighborhood of each collapse
+/// alone for the rest of the pass, until `max_faces` faces are removed.
+/// Returns the number of faces removed.
+fn decimation_pass(mesh: &mut HalfEdgeMesh, max_faces: usize) -> usize {
+    let mut edges: Vec<(f32, HalfEdgeId, VertexId, VertexId)> = mesh
+        .half_edges()
+        .iter()
+        .filter_map(|he| {
+            // Border edges never collapse; interior ones are visited once
+            he.face?;
+            he.twin?;
+            let dest = mesh.half_edge(he.next)?.origin;
+            if he.origin.0 > dest.0 {
+                return None;
+            }
+            let length = mesh
+                .vertex(he.origin)?
+                .position
+                .distance_squared(mesh.vertex(dest)?.position);
+            Some((length, he.id, he.origin, dest))
+        })
+        .collect();
+    edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut touched: HashSet<VertexId> = HashSet::new();
+    let mut removed = 0;
+    for (_, edge, origin, dest) in edges {
+        if removed >= max_faces {
+            break;
+        }
+        if touched.contains(&origin) || touched.contains(&dest) {
+            continue;
+        }
+        let Some(result) = collapse_edge(mesh, edge) else {
+            continue;
+        };
+        removed += result.removed_faces.len();
+        touched.insert(result.removed_vertex);
+        touched.insert(result.surviving_vertex);
+        touched.extend(mesh.get_adjacent_vertices(result.surviving_vertex));
+    }
+
+    removed
+}
+
+/// A chunk's GPU meshes: full detail, plus a reduced copy once one is needed.
+#[cfg(feature = "bevy")]
+#[derive(Debug, Clone)]
+pub struct ChunkMeshes {
+    /// Mesh with every face of the chunk
+    pub full: Handle<Mesh>,
+    /// Decimated mesh, built the first time the chunk is shown reduced
+    pub reduced: Option<Handle<Mesh>>,
+    /// Whether `reduced` is missing or predates the chunk's latest edits
+    pub reduced_stale: bool,
+    /// Detail level picked for the chunk
+    pub detail: ChunkDetail,
+    /// Triangles in `full`
+    pub full_triangles: usize,
+    /// Triangles in `reduced`
+    pub reduced_triangles: usize,
+}
+
+#[cfg(feature = "bevy")]
+impl ChunkMeshes {
+    /// Upload the chunk's full-detail mesh.
+    pub fn new(chunk: &MeshChunk, meshes: &mut Assets<Mesh>) -> Self {
+        let mesh = super::build_chunk_mesh(chunk);
+        let full_triangles = triangle_count(&mesh);
+        Self {
+            full: meshes.add(mesh),
+            reduced: None,
+            reduced_stale: true,
+            detail: ChunkDetail::Full,
+            full_triangles,
+            reduced_triangles: 0,
+        }
+    }
+
+    /// Whether the reduced copy is selected and up to date.
+    fn shows_reduced(&self) -> bool {
+        self.detail == ChunkDetail::Reduced && self.reduced.is_some() && !self.reduced_stale
+    }
+
+    /// The mesh to draw: the reduced copy when it's selected and up to date,
+    /// otherwise full detail.
+    pub fn shown(&self) -> &Handle<Mesh> {
+        match &self.reduced {
+            Some(reduced) if self.shows_reduced() => reduced,
+            _ => &self.full,
+        }
+    }
+
+    /// Triangles in the mesh returned by [`shown`](Self::shown).
+    pub fn shown_triangles(&self) -> usize {
+        if self.shows_reduced() {
+            self.reduced_triangles
+        } else {
+            self.full_triangles
+        }
+    }
+
+    /// Remove both meshes from `meshes`.
+    pub fn remove(&self, meshes: &mut Assets<Mesh>) {
+        meshes.remove(&self.full);
+        if let Some(reduced) = &self.reduced {
+            meshes.remove(reduced);
+        }
+    }
+}
+
+/// Rebuild stale reduced copies for chunks that are set to show them, at most
+/// `config.max_rebuilds_per_sync` of them. Returns how many were rebuilt.
+#[cfg(feature = "bevy")]
+pub(super) fn rebuild_reduced_meshes(
+    chunked_mesh: &ChunkedMesh,
+    meshes: &mut Assets<Mesh>,
+    chunk_meshes: &mut HashMap<ChunkId, ChunkMeshes>,
+    config: &LodConfig,
+) -> usize {
+    use rayon::prelude::*;
+
+    let mut pending: Vec<ChunkId> = chunk_meshes
+        .iter()
+        .filter(|(_, m)| m.detail == ChunkDetail::Reduced && m.reduced_stale)
+        .map(|(id, _)| *id)
+        .collect();
+    pending.sort_by_key(|id| id.0);
+    pending.truncate(config.max_rebuilds_per_sync);
+
+    let decimated: Vec<(ChunkId, HalfEdgeMesh)> = pending
+        .par_iter()
+        .filter_map(|id| {
+            let chunk = chunked_mesh.get_chunk(*id)?;
+            Some((*id, decimate_chunk(chunk, config.face_ratio)))
+        })
+        .collect();
+
+    let rebuilt = decimated.len();
+    for (id, he_mesh) in decimated {
+        let Some(entry) = chunk_meshes.get_mut(&id) else {
+            continue;
+        };
+        let mesh = super::mesh_from_half_edge(&he_mesh);
+        entry.reduced_triangles = triangle_count(&mesh);
+        match &entry.reduced {
+            Some(handle) => {
+                if let Some(existing) = meshes.get_mut(handle) {
+                    *existing = mesh;
+                }
+            }
+            None => entry.reduced = Some(meshes.add(mesh)),
+        }
+        entry.reduced_stale = false;
+    }
+
+    rebuilt
+}
+
+#[cfg(feature = "bevy")]
+fn triangle_count(mesh: &Mesh) -> usize {
+    mesh.indices().map_or(0, |indices| indices.len() / 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Mat4;
+
+    #[cfg(feature = "bevy")]
+    fn chunked_sphere(target_faces: usize) -> ChunkedMesh {
+        use crate::chunking::{partition_mesh, PartitionConfig};
+        use bevy::math::primitives::Sphere;
+        use bevy::mesh::Meshable;
+
+        let mesh = Sphere::new(1.0).mesh().ico(12).unwrap();
+        let he_mesh = HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap();
+        let config = PartitionConfig {
+            target_faces,
+            min_faces: target_faces / 2,
+            max_faces: target_faces * 3 / 2,
+        };
+        partition_mesh(&he_mesh, &config)
+    }
+
+    fn screen_at_distance(distance: f32) -> ScreenSpaceConfig {
+        let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, distance), Vec3::ZERO, Vec3::Y);
+        let projection = Mat4::perspective_infinite_reverse_rh(1.0, 16.0 / 9.0, 0.1);
+        ScreenSpaceConfig::new(projection * view, 1920.0, 1080.0)
+    }
+
+    #[test]
+    fn test_select_has_hysteresis() {
+        let config = LodConfig::default();
+        let between = (config.reduce_below_px + config.restore_above_px) * 0.5;
+
+        assert_eq!(
+            ChunkDetail::Full.select(config.reduce_below_px - 1.0, &config),
+            ChunkDetail::Reduced
+        );
+        assert_eq!(
+            ChunkDetail::Reduced.select(config.restore_above_px + 1.0, &config),
+            ChunkDetail::Full
+        );
+        // Between the thresholds a chunk keeps its level
+        assert_eq!(
+            ChunkDetail::Full.select(between, &config),
+            ChunkDetail::Full
+        );
+        assert_eq!(
+            ChunkDetail::Reduced.select(between, &config),
+            ChunkDetail::Reduced
+        );
+    }
+
+    #[test]
+    fn test_projected_size_shrinks_with_distance() {
+        let bounds = Aabb::new(Vec3::splat(-0.5), Vec3::splat(0.5));
+        let near = projected_size(&bounds, &screen_at_distance(3.0));
+        let far = projected_size(&bounds, &screen_at_distance(30.0));
+
+        assert!(near > far * 5.0, "near {near}, far {far}");
+        assert!(far > 0.0);
+    }
+
+    #[test]
+    fn test_projected_size_outside_view_is_zero() {
+        let screen = screen_at_distance(3.0);
+        let beside = Aabb::new(Vec3::new(50.0, -0.5, -0.5), Vec3::new(51.0, 0.5, 0.5));
+        assert_eq!(projected_size(&beside, &screen), 0.0);
+
+        // The camera sits inside these bounds
+        let around = Aabb::new(Vec3::splat(-5.0), Vec3::splat(5.0));
+        assert_eq!(projected_size(&around, &screen), f32::INFINITY);
+    }
+
+    #[cfg(feature = "bevy")]
+    #[test]
+    fn test_decimate_closed_chunk_reaches_ratio() {
+        // One chunk holding the whole sphere has no border to keep
+        let chunked_mesh = chunked_sphere(10_000);
+        assert_eq!(chunked_mesh.chunk_count(), 1);
+        let chunk = chunked_mesh.chunks.values().next().unwrap();
+
+        let reduced = decimate_chunk(chunk, REDUCED_FACE_RATIO);
+        let target = (chunk.face_count() as f32 * REDUCED_FACE_RATIO).ceil() as usize;
+        assert!(
+            reduced.face_count() <= target + target / 10,
+            "{} faces left of {}",
+            reduced.face_count(),
+            chunk.face_count()
+        );
+        assert!(reduced.face_count() > 0);
+        // Compacted: every vertex is used by a face
+        assert!(reduced
+            .vertices()
+            .iter()
+            .all(|v| v.outgoing_half_edge.is_some()));
+    }
+
+    #[cfg(feature = "bevy")]
+    #[test]
+    fn test_decimate_keeps_chunk_borders() {
+        let chunked_mesh = chunked_sphere(1_000);
+        assert!(chunked_mesh.chunk_count() > 1);
+
+        for chunk in chunked_mesh.chunks.values() {
+            let reduced = decimate_chunk(chunk, REDUCED_FACE_RATIO);
+            assert!(reduced.face_count() < chunk.face_count());
+
+            let kept: Vec<[u32; 3]> = reduced
+                .vertices()
+                .iter()
+                .map(|v| v.position.to_array().map(f32::to_bits))
+                .collect();
+            for &local_id in chunk.boundary_vertices.keys() {
+                let position = chunk.mesh.vertex(local_id).unwrap().position;
+                let bits = position.to_array().map(f32::to_bits);
+                assert!(
+                    kept.contains(&bits),
+                    "chunk {:?} moved border vertex {:?}",
+                    chunk.id,
+                    local_id
+                );
+            }
+        }
+    }
+}
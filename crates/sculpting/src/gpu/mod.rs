@@ -0,0 +1,681 @@
+//! GPU synchronization for sculpted mesh chunks.
+//!
+//! This module handles efficient updates to Bevy GPU buffers when chunks
+//! are modified during sculpting. It supports two update modes:
+//!
+//! - **Full rebuild**: When topology changes (tessellation), rebuild the entire chunk mesh
+//! - **Vertex patching**: When only positions/normals change, write the chunk's
+//!   dirty vertices into its existing GPU buffer (see [`patch`])
+//!
+//! Chunk meshes share vertices (vertex index == `VertexId`), so a dirty vertex
+//! maps to exactly one slot in the vertex buffer.
+//!
+//! The sculpt mask is shown as a vertex color that darkens masked regions
+//! (see [`mask_color`]), multiplied into painted vertex colors (see
+//! [`overlay_color`]).
+//!
+//! Chunks far from the camera can be drawn from a decimated copy instead
+//! (see [`lod`]).
+
+pub mod lod;
+#[cfg(feature = "bevy")]
+pub mod patch;
+
+#[cfg(feature = "bevy")]
+use bevy::prelude::*;
+#[cfg(feature = "bevy")]
+use bevy::asset::{Assets, RenderAssetUsages};
+#[cfg(feature = "bevy")]
+use bevy::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+
+use crate::chunking::MeshChunk;
+#[cfg(feature = "bevy")]
+use crate::chunking::{ChunkId, ChunkedMesh};
+#[cfg(feature = "bevy")]
+use painting::half_edge::HalfEdgeMesh;
+use painting::half_edge::VertexId;
+#[cfg(feature = "bevy")]
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+#[cfg(feature = "bevy")]
+pub use lod::ChunkMeshes;
+pub use lod::{decimate_chunk, projected_size, ChunkDetail, LodConfig, REDUCED_FACE_RATIO};
+#[cfg(feature = "bevy")]
+pub use patch::{
+    patch_mesh_colors, patch_mesh_vertices, MeshVertexPatch, MeshVertexPatchPlugin,
+    MeshVertexPatches, VertexRangePatch,
+};
+
+/// How much a fully masked vertex is darkened (0.0 = not at all, 1.0 = black).
+pub const MASK_OVERLAY_DARKEN: f32 = 0.6;
+
+/// Vertex color for a mask value: white when unmasked, darker as the mask
+/// increases. Multiplied with the material's base color.
+pub fn mask_color(mask: f32) -> [f32; 4] {
+    let shade = 1.0 - mask.clamp(0.0, 1.0) * MASK_OVERLAY_DARKEN;
+    [shade, shade, shade, 1.0]
+}
+
+/// Vertex color of a chunk vertex: its painted color darkened by the mask
+/// overlay.
+pub fn overlay_color(color: glam::Vec4, mask: f32) -> [f32; 4] {
+    (color * glam::Vec4::from_array(mask_color(mask))).to_array()
+}
+
+/// Write mask colors for `(vertex_index, mask)` pairs into `mesh`'s COLOR
+/// attribute without marking the asset changed.
+///
+/// Call before [`patch_mesh_vertices`] so the packed ranges pick up the new
+/// colors. Returns `None` if the mesh has no `Float32x4` color attribute or
+/// an index is out of range.
+#[cfg(feature = "bevy")]
+pub fn write_mask_colors(mesh: &mut Mesh, masks: &[(u32, f32)]) -> Option<()> {
+    let Ok(VertexAttributeValues::Float32x4(colors)) =
+        mesh.try_attribute_mut(Mesh::ATTRIBUTE_COLOR)
+    else {
+        return None;
+    };
+    for &(index, mask) in masks {
+        *colors.get_mut(index as usize)? = mask_color(mask);
+    }
+    Some(())
+}
+
+/// Tracks which vertices have been modified and need normal recalculation.
+#[derive(Debug, Default, Clone)]
+pub struct DirtyVertices {
+    /// Set of vertex IDs that have been modified
+    pub modified: HashSet<VertexId>,
+}
+
+impl DirtyVertices {
+    /// Create a new empty dirty vertex tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark a vertex as modified.
+    pub fn mark(&mut self, vertex_id: VertexId) {
+        self.modified.insert(vertex_id);
+    }
+
+    /// Mark multiple vertices as modified.
+    pub fn mark_all(&mut self, vertices: impl IntoIterator<Item = VertexId>) {
+        self.modified.extend(vertices);
+    }
+
+    /// Clear all dirty flags.
+    pub fn clear(&mut self) {
+        self.modified.clear();
+    }
+
+    /// Check if any vertices are dirty.
+    pub fn is_empty(&self) -> bool {
+        self.modified.is_empty()
+    }
+
+    /// Get the number of dirty vertices.
+    pub fn len(&self) -> usize {
+        self.modified.len()
+    }
+}
+
+/// Result of syncing chunks to GPU.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SyncResult {
+    /// Number of chunks that were rebuilt (topology changed)
+    pub chunks_rebuilt: usize,
+    /// Number of chunks that were patched (position only)
+    pub chunks_patched: usize,
+    /// Number of chunks skipped (not dirty)
+    pub chunks_skipped: usize,
+    /// Number of vertices written in place by patched chunks
+    pub vertices_updated: usize,
+    /// Number of vertices re-uploaded by rebuilt chunks
+    pub vertices_rebuilt: usize,
+    /// Number of reduced-detail copies rebuilt
+    pub reduced_rebuilt: usize,
+}
+
+/// Sync all dirty chunks to GPU.
+///
+/// Chunks marked with `topology_changed` get a full rebuild. Chunks that are
+/// just `dirty` update their `dirty_vertices` in place and queue them in
+/// `patches`, leaving the mesh's GPU allocation untouched; they fall back to a
+/// rebuild when the mesh can't be patched or no dirty vertices were recorded.
+///
+/// `chunk_meshes` follows the chunked mesh: chunks without meshes get them,
+/// and meshes of chunks that no longer exist are removed. Dirty chunks' reduced
+/// copies go stale, and stale copies of chunks set to
+/// [`ChunkDetail::Reduced`] are rebuilt within `lod`'s per-sync limit.
+#[cfg(feature = "bevy")]
+pub fn sync_chunks_to_gpu(
+    chunked_mesh: &mut ChunkedMesh,
+    meshes: &mut Assets<Mesh>,
+    chunk_meshes: &mut HashMap<ChunkId, ChunkMeshes>,
+    patches: &mut MeshVertexPatches,
+    lod: &LodConfig,
+) -> SyncResult {
+    let mut result = SyncResult::default();
+
+    chunk_meshes.retain(|chunk_id, chunk_mesh| {
+        let exists = chunked_mesh.chunks.contains_key(chunk_id);
+        if !exists {
+            chunk_mesh.remove(meshes);
+        }
+        exists
+    });
+
+    for (chunk_id, chunk) in chunked_mesh.chunks.iter_mut() {
+        let Some(chunk_mesh) = chunk_meshes.get_mut(chunk_id) else {
+            chunk_meshes.insert(*chunk_id, ChunkMeshes::new(chunk, meshes));
+            result.chunks_rebuilt += 1;
+            result.vertices_rebuilt += chunk.vertex_count();
+            chunk.clear_dirty();
+            continue;
+        };
+
+        if !chunk.dirty && !chunk.topology_changed {
+            result.chunks_skipped += 1;
+            continue;
+        }
+
+        let rebuilt = result.chunks_rebuilt;
+        sync_chunk(chunk, meshes, &chunk_mesh.full, patches, &mut result);
+        if result.chunks_rebuilt > rebuilt {
+            chunk_mesh.full_triangles = meshes
+                .get(&chunk_mesh.full)
+                .and_then(|mesh| mesh.indices())
+                .map_or(0, |indices| indices.len() / 3);
+        }
+        chunk_mesh.reduced_stale = true;
+    }
+
+    result.reduced_rebuilt = lod::rebuild_reduced_meshes(chunked_mesh, meshes, chunk_meshes, lod);
+    result
+}
+
+/// Sync a single chunk to GPU.
+///
+/// Same as [`sync_chunks_to_gpu`] for one chunk. Returns whether the chunk
+/// was dirty.
+#[cfg(feature = "bevy")]
+pub fn sync_chunk_to_gpu(
+    chunk: &mut MeshChunk,
+    meshes: &mut Assets<Mesh>,
+    handle: &Handle<Mesh>,
+    patches: &mut MeshVertexPatches,
+) -> bool {
+    if !chunk.dirty && !chunk.topology_changed {
+        return false;
+    }
+
+    sync_chunk(chunk, meshes, handle, patches, &mut SyncResult::default());
+    true
+}
+
+/// Patch or rebuild one dirty chunk's mesh and clear its dirty state.
+#[cfg(feature = "bevy")]
+fn sync_chunk(
+    chunk: &mut MeshChunk,
+    meshes: &mut Assets<Mesh>,
+    handle: &Handle<Mesh>,
+    patches: &mut MeshVertexPatches,
+    result: &mut SyncResult,
+) {
+    let patched = if chunk.topology_changed || chunk.dirty_vertices.is_empty() {
+        None
+    } else {
+        patch_chunk_vertices(chunk, meshes, handle, patches)
+    };
+
+    match patched {
+        Some(vertices) => {
+            result.chunks_patched += 1;
+            result.vertices_updated += vertices;
+        }
+        None => {
+            // Full rebuild - topology has changed
+            if let Some(mesh) = meshes.get_mut(handle) {
+                *mesh = build_chunk_mesh(chunk);
+            }
+            result.chunks_rebuilt += 1;
+            result.vertices_rebuilt += chunk.vertex_count();
+        }
+    }
+
+    chunk.clear_dirty();
+}
+
+/// Write the chunk's dirty vertices into its mesh without marking the asset
+/// modified, queueing the GPU upload in `patches`.
+#[cfg(feature = "bevy")]
+fn patch_chunk_vertices(
+    chunk: &MeshChunk,
+    meshes: &mut Assets<Mesh>,
+    handle: &Handle<Mesh>,
+    patches: &mut MeshVertexPatches,
+) -> Option<usize> {
+    let mesh = meshes.get_mut_untracked(handle)?;
+
+    let mut updates: Vec<(u32, Vec3, Vec3)> = chunk
+        .dirty_vertices
+        .modified
+        .iter()
+        .filter_map(|&id| {
+            let vertex = chunk.mesh.vertex(id)?;
+            Some((id.0, vertex.position, vertex.normal))
+        })
+        .collect();
+
+    patch_mesh_vertices(mesh, handle.id(), &mut updates, patches)
+}
+
+/// Recalculate normals for vertices that have been modified.
+///
+/// This updates vertex normals based on the average of adjacent face normals.
+/// Should be called after deformation but before GPU sync.
+pub fn recalculate_normals_for_dirty(chunk: &mut MeshChunk, dirty: &DirtyVertices) {
+    use glam::Vec3;
+
+    for &vertex_id in &dirty.modified {
+        // Get all faces adjacent to this vertex
+        let face_ids = chunk.mesh.get_vertex_faces(vertex_id);
+        if face_ids.is_empty() {
+            continue;
+        }
+
+        // Calculate average face normal
+        let mut normal_sum = Vec3::ZERO;
+        for face_id in face_ids {
+            if let Some(face) = chunk.mesh.face(face_id) {
+                normal_sum += face.normal;
+            }
+        }
+
+        let averaged = normal_sum.normalize_or_zero();
+        if let Some(vertex) = chunk.mesh.vertex_mut(vertex_id) {
+            vertex.normal = averaged;
+        }
+    }
+}
+
+/// Recalculate face normals for faces that contain modified vertices.
+///
+/// This should be called before `recalculate_normals_for_dirty` to ensure
+/// face normals are up-to-date.
+pub fn recalculate_face_normals_for_dirty(chunk: &mut MeshChunk, dirty: &DirtyVertices) {
+    use glam::Vec3;
+    use std::collections::HashSet;
+
+    // Collect all faces that need updating
+    let mut dirty_faces: HashSet<painting::half_edge::FaceId> = HashSet::new();
+    for &vertex_id in &dirty.modified {
+        let face_ids = chunk.mesh.get_vertex_faces(vertex_id);
+        dirty_faces.extend(face_ids);
+    }
+
+    // Recalculate each dirty face's normal
+    for face_id in dirty_faces {
+        let face_verts = chunk.mesh.get_face_vertices(face_id);
+        if face_verts.len() < 3 {
+            continue;
+        }
+
+        // Get vertex positions
+        let positions: Vec<Vec3> = face_verts
+            .iter()
+            .filter_map(|&vid| chunk.mesh.vertex(vid).map(|v| v.position))
+            .collect();
+
+        if positions.len() < 3 {
+            continue;
+        }
+
+        // Calculate face normal from first three vertices
+        let v0 = positions[0];
+        let v1 = positions[1];
+        let v2 = positions[2];
+        let edge1 = v1 - v0;
+        let edge2 = v2 - v0;
+        let normal = edge1.cross(edge2).normalize_or_zero();
+
+        // Update face normal
+        if let Some(face) = chunk.mesh.face_mut(face_id) {
+            face.normal = normal;
+        }
+    }
+}
+
+/// Full normal recalculation pipeline for a chunk after deformation.
+///
+/// This updates both face normals and vertex normals for affected geometry.
+pub fn update_normals_after_deformation(chunk: &mut MeshChunk, dirty: &DirtyVertices) {
+    // First update face normals
+    recalculate_face_normals_for_dirty(chunk, dirty);
+    // Then update vertex normals based on new face normals
+    recalculate_normals_for_dirty(chunk, dirty);
+}
+
+/// Create full-detail Bevy meshes for all chunks in a chunked mesh.
+#[cfg(feature = "bevy")]
+pub fn create_chunk_meshes(
+    chunked_mesh: &ChunkedMesh,
+    meshes: &mut Assets<Mesh>,
+) -> HashMap<ChunkId, ChunkMeshes> {
+    chunked_mesh
+        .chunks
+        .iter()
+        .map(|(chunk_id, chunk)| (*chunk_id, ChunkMeshes::new(chunk, meshes)))
+        .collect()
+}
+
+/// Build a chunk's Bevy mesh with shared vertices.
+///
+/// Vertex `i` of the mesh is the chunk's `VertexId(i)`, so position-only
+/// updates can patch vertices by ID. Faces are fan-triangulated.
+#[cfg(feature = "bevy")]
+pub fn build_chunk_mesh(chunk: &MeshChunk) -> Mesh {
+    mesh_from_half_edge(&chunk.mesh)
+}
+
+/// Build a Bevy mesh whose vertex `i` is the half-edge mesh's `VertexId(i)`.
+#[cfg(feature = "bevy")]
+fn mesh_from_half_edge(he_mesh: &HalfEdgeMesh) -> Mesh {
+    let vertices = he_mesh.vertices();
+
+    let positions: Vec<[f32; 3]> = vertices.iter().map(|v| v.position.to_array()).collect();
+    let normals: Vec<[f32; 3]> = vertices.iter().map(|v| v.normal.to_array()).collect();
+    let uvs: Vec<[f32; 2]> = vertices
+        .iter()
+        .map(|v| v.uv.unwrap_or(Vec2::ZERO).to_array())
+        .collect();
+    let colors: Vec<[f32; 4]> = vertices
+        .iter()
+        .map(|v| overlay_color(v.color, v.mask))
+        .collect();
+
+    let mut indices: Vec<u32> = Vec::with_capacity(he_mesh.face_count() * 3);
+    for face in he_mesh.faces() {
+        let face_verts = he_mesh.get_face_vertices(face.id);
+        if face_verts.len() < 3 {
+            continue;
+        }
+        for i in 1..(face_verts.len() - 1) {
+            indices.push(face_verts[0].0);
+            indices.push(face_verts[i].0);
+            indices.push(face_verts[i + 1].0);
+        }
+    }
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+    mesh
+}
+
+/// Remove chunk meshes from assets.
+#[cfg(feature = "bevy")]
+pub fn remove_chunk_meshes(
+    chunk_meshes: &HashMap<ChunkId, ChunkMeshes>,
+    meshes: &mut Assets<Mesh>,
+) {
+    for chunk_mesh in chunk_meshes.values() {
+        chunk_mesh.remove(meshes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dirty_vertices() {
+        let mut dirty = DirtyVertices::new();
+        assert!(dirty.is_empty());
+
+        dirty.mark(VertexId(0));
+        dirty.mark(VertexId(1));
+        assert_eq!(dirty.len(), 2);
+
+        dirty.mark(VertexId(0)); // Duplicate
+        assert_eq!(dirty.len(), 2);
+
+        dirty.clear();
+        assert!(dirty.is_empty());
+    }
+
+    #[test]
+    fn test_dirty_vertices_mark_all() {
+        let mut dirty = DirtyVertices::new();
+        dirty.mark_all([VertexId(0), VertexId(1), VertexId(2)]);
+        assert_eq!(dirty.len(), 3);
+    }
+
+    #[test]
+    fn test_mask_color_darkens_masked_vertices() {
+        assert_eq!(mask_color(0.0), [1.0, 1.0, 1.0, 1.0]);
+        let masked = mask_color(1.0);
+        assert!((masked[0] - (1.0 - MASK_OVERLAY_DARKEN)).abs() < 1e-6);
+        assert_eq!(masked[3], 1.0);
+        // Out-of-range masks are clamped
+        assert_eq!(mask_color(2.0), masked);
+    }
+
+    #[test]
+    fn test_overlay_color_keeps_paint_under_mask() {
+        let red = glam::Vec4::new(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(overlay_color(red, 0.0), [1.0, 0.0, 0.0, 1.0]);
+        let masked = overlay_color(red, 1.0);
+        assert!((masked[0] - (1.0 - MASK_OVERLAY_DARKEN)).abs() < 1e-6);
+        assert_eq!(masked[1], 0.0);
+    }
+
+    #[cfg(feature = "bevy")]
+    mod sync {
+        use super::*;
+        use crate::chunking::{partition_mesh, PartitionConfig};
+        use crate::{BrushInput, BrushPreset, PipelineConfig, SculptingPipeline};
+        use bevy::math::primitives::Sphere;
+        use bevy::mesh::{Meshable, VertexAttributeValues};
+        use painting::half_edge::HalfEdgeMesh;
+
+        fn chunked_sphere() -> ChunkedMesh {
+            let mesh = Sphere::new(1.0).mesh().ico(12).unwrap();
+            let he_mesh = HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap();
+            let config = PartitionConfig {
+                target_faces: 500,
+                min_faces: 250,
+                max_faces: 750,
+            };
+            partition_mesh(&he_mesh, &config)
+        }
+
+        fn positions(mesh: &Mesh) -> &[[f32; 3]] {
+            match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+                Some(VertexAttributeValues::Float32x3(positions)) => positions,
+                _ => panic!("chunk mesh without positions"),
+            }
+        }
+
+        #[test]
+        fn test_position_only_sync_patches_dirty_vertices() {
+            let mut chunked_mesh = chunked_sphere();
+            let mut meshes = Assets::<Mesh>::default();
+            let mut handles = create_chunk_meshes(&chunked_mesh, &mut meshes);
+            let mut patches = MeshVertexPatches::default();
+            let lod = LodConfig::default();
+
+            let mut chunk_ids: Vec<ChunkId> = chunked_mesh.chunks.keys().copied().collect();
+            chunk_ids.sort_by_key(|id| id.0);
+            let chunk_id = chunk_ids[0];
+            let moved = Vec3::new(0.0, 2.0, 0.0);
+            crate::sync_vertex_position(&mut chunked_mesh, chunk_id, VertexId(7), moved);
+
+            let result = sync_chunks_to_gpu(
+                &mut chunked_mesh,
+                &mut meshes,
+                &mut handles,
+                &mut patches,
+                &lod,
+            );
+            assert_eq!(result.chunks_rebuilt, 0);
+            assert_eq!(result.vertices_rebuilt, 0);
+            assert!(result.chunks_patched >= 1);
+            assert_eq!(result.vertices_updated, result.chunks_patched);
+
+            // One single-vertex range per patched chunk
+            assert_eq!(patches.patches.len(), result.chunks_patched);
+            assert_eq!(patches.vertex_count(), result.chunks_patched);
+            let mesh = meshes.get(&handles[&chunk_id].full).unwrap();
+            assert_eq!(positions(mesh)[7], moved.to_array());
+
+            // Everything synced
+            assert!(chunked_mesh.dirty_chunks().is_empty());
+            assert!(
+                chunked_mesh
+                    .chunks
+                    .values()
+                    .all(|c| c.dirty_vertices.is_empty())
+            );
+        }
+
+        #[test]
+        fn test_stroke_without_tessellation_never_rebuilds() {
+            let mut chunked_mesh = chunked_sphere();
+            let mut meshes = Assets::<Mesh>::default();
+            let mut handles = create_chunk_meshes(&chunked_mesh, &mut meshes);
+            let mut patches = MeshVertexPatches::default();
+            let lod = LodConfig::default();
+
+            let config = PipelineConfig {
+                tessellation_enabled: false,
+                rebalance_after_stroke: false,
+                ..Default::default()
+            };
+            let mut preset = BrushPreset::smooth();
+            preset.radius = 0.1;
+            let mut pipeline = SculptingPipeline::with_config(preset, config);
+
+            let input = |step: u64| {
+                let angle = step as f32 * 0.02;
+                let position = Vec3::new(angle.cos(), 0.0, angle.sin());
+                BrushInput {
+                    position,
+                    normal: position,
+                    pressure: 1.0,
+                    timestamp_ms: step * 16,
+                }
+            };
+
+            // Settle boundary normals so the stroke is the only change
+            chunked_mesh.recalculate_boundary_normals();
+            sync_chunks_to_gpu(
+                &mut chunked_mesh,
+                &mut meshes,
+                &mut handles,
+                &mut patches,
+                &lod,
+            );
+            patches.patches.clear();
+
+            let total_vertices = chunked_mesh.total_vertex_count();
+            let mut vertices_updated = 0;
+            pipeline.begin_stroke(0, input(0));
+            for step in 1..10 {
+                pipeline.process_input(input(step), &mut chunked_mesh);
+                let result = sync_chunks_to_gpu(
+                    &mut chunked_mesh,
+                    &mut meshes,
+                    &mut handles,
+                    &mut patches,
+                    &lod,
+                );
+                assert_eq!(result.chunks_rebuilt, 0);
+                assert_eq!(result.vertices_rebuilt, 0);
+                vertices_updated += result.vertices_updated;
+
+                // A small brush only re-sends a small part of the mesh
+                assert!(patches.vertex_count() < total_vertices / 10);
+                patches.patches.clear();
+            }
+            pipeline.end_stroke(&mut chunked_mesh);
+
+            assert!(vertices_updated > 0);
+        }
+
+        #[test]
+        fn test_reduced_copies_follow_detail_and_edits() {
+            let mut chunked_mesh = chunked_sphere();
+            let mut meshes = Assets::<Mesh>::default();
+            let mut handles = HashMap::new();
+            let mut patches = MeshVertexPatches::default();
+            let lod = LodConfig::default();
+
+            // Missing chunk meshes are created by the sync
+            let result = sync_chunks_to_gpu(
+                &mut chunked_mesh,
+                &mut meshes,
+                &mut handles,
+                &mut patches,
+                &lod,
+            );
+            assert_eq!(handles.len(), chunked_mesh.chunk_count());
+            assert_eq!(result.chunks_rebuilt, chunked_mesh.chunk_count());
+            assert_eq!(result.reduced_rebuilt, 0);
+
+            let mut chunk_ids: Vec<ChunkId> = chunked_mesh.chunks.keys().copied().collect();
+            chunk_ids.sort_by_key(|id| id.0);
+            let chunk_id = chunk_ids[0];
+            let entry = handles.get_mut(&chunk_id).unwrap();
+            entry.detail = ChunkDetail::Reduced;
+            // Until its reduced copy exists the chunk stays at full detail
+            assert_eq!(entry.shown(), &entry.full);
+
+            let result = sync_chunks_to_gpu(
+                &mut chunked_mesh,
+                &mut meshes,
+                &mut handles,
+                &mut patches,
+                &lod,
+            );
+            assert_eq!(result.reduced_rebuilt, 1);
+            let entry = &handles[&chunk_id];
+            assert_eq!(Some(entry.shown()), entry.reduced.as_ref());
+            assert!(entry.shown_triangles() < entry.full_triangles);
+
+            // Editing the chunk makes the copy stale until the next sync rebuilds it
+            let moved = Vec3::new(0.0, 2.0, 0.0);
+            crate::sync_vertex_position(&mut chunked_mesh, chunk_id, VertexId(7), moved);
+            let result = sync_chunks_to_gpu(
+                &mut chunked_mesh,
+                &mut meshes,
+                &mut handles,
+                &mut patches,
+                &lod,
+            );
+            assert_eq!(result.reduced_rebuilt, 1);
+            assert!(!handles[&chunk_id].reduced_stale);
+
+            // Meshes of chunks that no longer exist are removed
+            let removed = chunked_mesh.remove_chunk(chunk_id).unwrap();
+            let full = handles[&chunk_id].full.clone();
+            sync_chunks_to_gpu(
+                &mut chunked_mesh,
+                &mut meshes,
+                &mut handles,
+                &mut patches,
+                &lod,
+            );
+            assert!(!handles.contains_key(&removed.id));
+            assert!(meshes.get(&full).is_none());
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! In-place vertex buffer patching for position-only sculpt updates and
+//! vertex color paint.
+//!
+//! Mutating a `Mesh` through `Assets::get_mut` makes Bevy free and re-upload
+//! the whole mesh, which for a 500k-vertex sculpt means reallocating and
+//! copying every vertex on each dab. Instead, position-only updates:
+//!
+//! 1. Write the new positions/normals into the main-world `Mesh` with
+//!    `Assets::get_mut_untracked`, so the asset isn't marked modified.
+//! 2. Pack the changed vertex ranges exactly like Bevy's interleaved vertex
+//!    buffer and queue them in [`MeshVertexPatches`].
+//! 3. Write those byte ranges straight into the mesh's existing slab
+//!    allocation in the render world.
+//!
+//! Mesh bounds aren't recomputed for patches; the next topology rebuild
+//! refreshes them.
+
+use std::sync::Arc;
+
+use bevy::asset::AssetId;
+use bevy::mesh::VertexAttributeValues;
+use bevy::prelude::*;
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::mesh::allocator::MeshAllocator;
+use bevy::render::renderer::RenderQueue;
+use bevy::render::{Render, RenderApp, RenderSystems};
+use tracing::{info_span, warn};
+
+/// Unchanged vertices between two dirty ones are re-sent rather than starting
+/// a new range, up to this many (fewer, larger buffer writes).
+const MAX_RANGE_GAP: u32 = 16;
+
+/// A run of consecutive vertices, packed like the mesh's GPU vertex buffer.
+#[derive(Debug, Clone)]
+pub struct VertexRangePatch {
+    /// Index of the first vertex in the range
+    pub first_vertex: u32,
+    /// Number of vertices in the range
+    pub vertex_count: u32,
+    /// `vertex_count * vertex_size` bytes of interleaved attribute data
+    pub data: Arc<[u8]>,
+}
+
+/// Vertex ranges to write into one mesh's vertex buffer.
+#[derive(Debug, Clone)]
+pub struct MeshVertexPatch {
+    /// Target mesh
+    pub mesh: AssetId<Mesh>,
+    /// Size of one interleaved vertex in bytes
+    pub vertex_size: u64,
+    pub ranges: Vec<VertexRangePatch>,
+}
+
+/// Vertex patches queued this frame, extracted to the render world.
+///
+/// Cleared at the start of every frame, so only the current frame's patches
+/// are extracted.
+#[derive(Resource, Debug, Clone, Default, ExtractResource)]
+pub struct MeshVertexPatches {
+    pub patches: Vec<MeshVertexPatch>,
+}
+
+impl MeshVertexPatches {
+    /// Whether no patches are queued.
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    /// Total number of vertices queued for upload (including gap vertices).
+    pub fn vertex_count(&self) -> usize {
+        self.patches
+            .iter()
+            .flat_map(|patch| &patch.ranges)
+            .map(|range| range.vertex_count as usize)
+            .sum()
+    }
+}
+
+/// Plugin that uploads [`MeshVertexPatches`] in the render world.
+pub struct MeshVertexPatchPlugin;
+
+impl Plugin for MeshVertexPatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeshVertexPatches>()
+            .add_plugins(ExtractResourcePlugin::<MeshVertexPatches>::default())
+            .add_systems(First, clear_mesh_vertex_patches);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            warn!("MeshVertexPatchPlugin: RenderApp not available, sculpt patches will not upload");
+            return;
+        };
+
+        // After PrepareAssets so rebuilt meshes are already allocated
+        render_app.add_systems(
+            Render,
+            write_mesh_vertex_patches.in_set(RenderSystems::PrepareResources),
+        );
+    }
+}
+
+/// Update positions and normals of `mesh` in place and queue the changed ranges.
+///
+/// `updates` holds `(vertex_index, position, normal)` and is sorted in place.
+/// The mesh must already be on the GPU with its current vertex layout; only
+/// the listed vertices (plus small gaps) are re-sent.
+///
+/// Returns the number of vertices updated, or `None` if the mesh can't be
+/// patched (missing or non-`Float32x3` attributes, data extracted to the
+/// render world, an index out of range). The caller should fall back to a
+/// full rebuild then.
+pub fn patch_mesh_vertices(
+    mesh: &mut Mesh,
+    mesh_id: AssetId<Mesh>,
+    updates: &mut [(u32, Vec3, Vec3)],
+    patches: &mut MeshVertexPatches,
+) -> Option<usize> {
+    if updates.is_empty() {
+        return Some(0);
+    }
+
+    let vertex_count = mesh.count_vertices();
+    let vertex_size = mesh.get_vertex_size();
+    // Buffer writes must be 4-byte aligned
+    if !vertex_size.is_multiple_of(4) {
+        return None;
+    }
+
+    updates.sort_unstable_by_key(|&(index, _, _)| index);
+    if updates.last()?.0 as usize >= vertex_count {
+        return None;
+    }
+
+    let Ok(VertexAttributeValues::Float32x3(positions)) =
+        mesh.try_attribute_mut(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    for &(index, position, _) in updates.iter() {
+        positions[index as usize] = position.to_array();
+    }
+    let Ok(VertexAttributeValues::Float32x3(normals)) =
+        mesh.try_attribute_mut(Mesh::ATTRIBUTE_NORMAL)
+    else {
+        return None;
+    };
+    for &(index, _, normal) in updates.iter() {
+        normals[index as usize] = normal.to_array();
+    }
+
+    let indices: Vec<u32> = updates.iter().map(|&(index, _, _)| index).collect();
+    queue_patch(mesh, mesh_id, &indices, vertex_size, patches)
+}
+
+/// Update vertex colors of `mesh` in place and queue the changed ranges.
+///
+/// Like [`patch_mesh_vertices`] for the `COLOR` attribute: `updates` holds
+/// `(vertex_index, linear_rgba)` and is sorted in place. Returns `None` if the
+/// mesh has no `Float32x4` color attribute or an index is out of range; the
+/// caller should insert the attribute (a full upload) first.
+pub fn patch_mesh_colors(
+    mesh: &mut Mesh,
+    mesh_id: AssetId<Mesh>,
+    updates: &mut [(u32, [f32; 4])],
+    patches: &mut MeshVertexPatches,
+) -> Option<usize> {
+    if updates.is_empty() {
+        return Some(0);
+    }
+
+    let vertex_count = mesh.count_vertices();
+    let vertex_size = mesh.get_vertex_size();
+    if !vertex_size.is_multiple_of(4) {
+        return None;
+    }
+
+    updates.sort_unstable_by_key(|&(index, _)| index);
+    if updates.last()?.0 as usize >= vertex_count {
+        return None;
+    }
+
+    let Ok(VertexAttributeValues::Float32x4(colors)) =
+        mesh.try_attribute_mut(Mesh::ATTRIBUTE_COLOR)
+    else {
+        return None;
+    };
+    for &(index, color) in updates.iter() {
+        colors[index as usize] = color;
+    }
+
+    let indices: Vec<u32> = updates.iter().map(|&(index, _)| index).collect();
+    queue_patch(mesh, mesh_id, &indices, vertex_size, patches)
+}
+
+/// Queue the ranges covering the sorted vertex `indices` of `mesh`, returning
+/// the number of distinct vertices.
+fn queue_patch(
+    mesh: &Mesh,
+    mesh_id: AssetId<Mesh>,
+    indices: &[u32],
+    vertex_size: u64,
+    patches: &mut MeshVertexPatches,
+) -> Option<usize> {
+    let mut ranges = Vec::new();
+    let mut start = indices[0];
+    let mut end = start;
+    for &index in &indices[1..] {
+        if index > end + MAX_RANGE_GAP {
+            ranges.push(pack_range(mesh, start, end + 1, vertex_size)?);
+            start = index;
+        }
+        end = index;
+    }
+    ranges.push(pack_range(mesh, start, end + 1, vertex_size)?);
+
+    patches.patches.push(MeshVertexPatch {
+        mesh: mesh_id,
+        vertex_size,
+        ranges,
+    });
+
+    let mut updated = indices.len();
+    // Duplicate indices only count once
+    updated -= indices.windows(2).filter(|w| w[0] == w[1]).count();
+    Some(updated)
+}
+
+/// Pack vertices `start..end` in Bevy's interleaved layout: attributes in ID
+/// order, each vertex's attributes back to back (see
+/// `Mesh::write_packed_vertex_buffer_data`).
+fn pack_range(mesh: &Mesh, start: u32, end: u32, vertex_size: u64) -> Option<VertexRangePatch> {
+    let vertex_size = vertex_size as usize;
+    let count = (end - start) as usize;
+    let mut data = vec![0u8; count * vertex_size];
+
+    let mut attribute_offset = 0;
+    for (attribute, values) in mesh.try_attributes().ok()? {
+        let attribute_size = attribute.format.size() as usize;
+        let bytes = values.get_bytes();
+        let source = bytes.get(start as usize * attribute_size..end as usize * attribute_size)?;
+        for (i, vertex_bytes) in source.chunks_exact(attribute_size).enumerate() {
+            let offset = i * vertex_size + attribute_offset;
+            data[offset..offset + attribute_size].copy_from_slice(vertex_bytes);
+        }
+        attribute_offset += attribute_size;
+    }
+
+    Some(VertexRangePatch {
+        first_vertex: start,
+        vertex_count: end - start,
+        data: data.into(),
+    })
+}
+
+/// Drop last frame's patches (runs in `First`).
+fn clear_mesh_vertex_patches(mut patches: ResMut<MeshVertexPatches>) {
+    if !patches.is_empty() {
+        patches.patches.clear();
+    }
+}
+
+/// Write queued patches into the meshes' vertex buffers (runs in PrepareResources).
+fn write_mesh_vertex_patches(
+    mut patches: ResMut<MeshVertexPatches>,
+    mesh_allocator: Res<MeshAllocator>,
+    render_queue: Res<RenderQueue>,
+) {
+    if patches.is_empty() {
+        return;
+    }
+
+    for patch in std::mem::take(&mut patches.patches) {
+        let Some(slice) = mesh_allocator.mesh_vertex_slice(&patch.mesh) else {
+            // Not uploaded yet - its first upload already has the patched data
+            continue;
+        };
+
+        let _span = info_span!("sculpt_vertex_patch", ranges = patch.ranges.len()).entered();
+        let allocated = slice.range.end - slice.range.start;
+        for range in &patch.ranges {
+            if range.first_vertex + range.vertex_count > allocated {
+                warn!(
+                    "Skipping sculpt vertex patch {}..{}: mesh only has {} vertices on the GPU",
+                    range.first_vertex,
+                    range.first_vertex + range.vertex_count,
+                    allocated
+                );
+                continue;
+            }
+
+            let offset = u64::from(slice.range.start + range.first_vertex) * patch.vertex_size;
+            render_queue.write_buffer(slice.buffer, offset, &range.data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::RenderAssetUsages;
+    use bevy::mesh::{Indices, PrimitiveTopology};
+
+    fn test_mesh(vertex_count: usize) -> Mesh {
+        let positions: Vec<[f32; 3]> = (0..vertex_count).map(|i| [i as f32, 0.0, 0.0]).collect();
+        let normals = vec![[0.0, 1.0, 0.0]; vertex_count];
+        let uvs = vec![[0.5, 0.5]; vertex_count];
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.insert_indices(Indices::U32(vec![0, 1, 2]));
+        mesh
+    }
+
+    #[test]
+    fn test_color_patch_matches_packed_vertex_buffer() {
+        let mut mesh = test_mesh(40);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0f32; 4]; 40]);
+        let mut patches = MeshVertexPatches::default();
+        let mut updates = vec![(30, [1.0, 0.0, 0.0, 1.0]), (2, [0.0, 1.0, 0.0, 1.0])];
+
+        let updated = patch_mesh_colors(&mut mesh, AssetId::default(), &mut updates, &mut patches);
+        assert_eq!(updated, Some(2));
+
+        let mut full = vec![0u8; mesh.get_vertex_buffer_size()];
+        mesh.write_packed_vertex_buffer_data(&mut full);
+        let patch = &patches.patches[0];
+        let vertex_size = patch.vertex_size as usize;
+        for range in &patch.ranges {
+            let start = range.first_vertex as usize * vertex_size;
+            assert_eq!(&full[start..start + range.data.len()], &*range.data);
+        }
+
+        // Meshes without colors can't be patched
+        let mut plain = test_mesh(4);
+        let mut updates = vec![(0, [1.0, 0.0, 0.0, 1.0])];
+        assert_eq!(
+            patch_mesh_colors(&mut plain, AssetId::default(), &mut updates, &mut patches),
+            None
+        );
+    }
+
+    #[test]
+    fn test_patch_matches_packed_vertex_buffer() {
+        let mut mesh = test_mesh(200);
+        let mut patches = MeshVertexPatches::default();
+        let mut updates = vec![
+            (150, Vec3::splat(3.0), Vec3::Z),
+            (10, Vec3::splat(1.0), Vec3::X),
+            (12, Vec3::splat(2.0), Vec3::X),
+        ];
+
+        let updated =
+            patch_mesh_vertices(&mut mesh, AssetId::default(), &mut updates, &mut patches);
+        assert_eq!(updated, Some(3));
+
+        // 10 and 12 share a range, 150 is too far away
+        let patch = &patches.patches[0];
+        let ranges: Vec<(u32, u32)> = patch
+            .ranges
+            .iter()
+            .map(|r| (r.first_vertex, r.vertex_count))
+            .collect();
+        assert_eq!(ranges, vec![(10, 3), (150, 1)]);
+
+        // Every range is byte-identical to the same slice of a full upload
+        let mut full = vec![0u8; mesh.get_vertex_buffer_size()];
+        mesh.write_packed_vertex_buffer_data(&mut full);
+        let vertex_size = patch.vertex_size as usize;
+        for range in &patch.ranges {
+            let start = range.first_vertex as usize * vertex_size;
+            assert_eq!(&full[start..start + range.data.len()], &*range.data);
+        }
+    }
+
+    #[test]
+    fn test_patch_rejects_out_of_range_vertex() {
+        let mut mesh = test_mesh(4);
+        let mut patches = MeshVertexPatches::default();
+        let mut updates = vec![(4, Vec3::ONE, Vec3::Y)];
+
+        assert_eq!(
+            patch_mesh_vertices(&mut mesh, AssetId::default(), &mut updates, &mut patches),
+            None
+        );
+        assert!(patches.is_empty());
+    }
+}
@@ -21,13 +21,19 @@
 //! - **Chunking**: Spatial partitioning for localized GPU updates
 //! - **Spatial**: Octree for efficient brush-to-vertex queries
 //! - **Pipeline**: Orchestrates stroke → deform → tessellate → GPU sync
+//! - **Remesh**: Uniform whole-mesh remeshing on demand
+//! - **Replay**: Stroke files and deterministic replay for regression tests
+//! - **Doctor**: Time-sliced mesh integrity audit and repair
 
 pub mod brush;
 pub mod budget;
 pub mod chunking;
 pub mod deformation;
+pub mod doctor;
 pub mod gpu;
 pub mod pipeline;
+pub mod remesh;
+pub mod replay;
 pub mod spatial;
 pub mod tessellation;
 pub mod types;
@@ -39,16 +45,25 @@ pub use chunking::{
     ChunkedMesh, MergeResult, MeshChunk, PartitionConfig,
 };
 pub use deformation::{
-    apply_autosmooth, apply_crease, apply_deformation, apply_flatten, apply_grab, apply_inflate,
-    apply_pinch, apply_pull, apply_push, apply_smooth, DabInfo, DeformationContext,
-    DeformationResult,
+    apply_autosmooth, apply_clay_strips, apply_mask, attenuate_by_mask, apply_crease, apply_deformation, apply_flatten,
+    apply_grab, apply_inflate, apply_pinch, apply_pull, apply_push, apply_scrape, apply_smooth,
+    fit_surface_plane, DabInfo, DeformationContext, DeformationResult, SurfacePlane,
+    CLAY_STRIPS_HEIGHT,
+};
+pub use doctor::{
+    audit_chunk, ChunkHealth, DoctorConfig, MeshDoctor, MeshHealthReport, MeshIssue,
 };
 pub use gpu::{
-    recalculate_face_normals_for_dirty, recalculate_normals_for_dirty,
-    update_normals_after_deformation, DirtyVertices, SyncResult,
+    decimate_chunk, mask_color, overlay_color, projected_size, recalculate_face_normals_for_dirty,
+    recalculate_normals_for_dirty, update_normals_after_deformation, ChunkDetail, DirtyVertices,
+    LodConfig, SyncResult, MASK_OVERLAY_DARKEN, REDUCED_FACE_RATIO,
 };
 #[cfg(feature = "bevy")]
-pub use gpu::{create_chunk_meshes, remove_chunk_meshes, sync_chunk_to_gpu, sync_chunks_to_gpu};
+pub use gpu::{
+    build_chunk_mesh, create_chunk_meshes, ChunkMeshes, patch_mesh_colors, patch_mesh_vertices,
+    remove_chunk_meshes, sync_chunk_to_gpu, sync_chunks_to_gpu, write_mask_colors,
+    MeshVertexPatch, MeshVertexPatchPlugin, MeshVertexPatches, VertexRangePatch,
+};
 pub use spatial::{OctreeConfig, VertexOctree};
 pub use tessellation::{
     calculate_collapse_position, calculate_edge_screen_length, calculate_split_position,
@@ -66,3 +81,10 @@ pub use types::{
 pub use pipeline::{
     DabProcessResult, PipelineConfig, SculptingPipeline, StrokeEndResult,
 };
+pub use remesh::{remesh_uniform, RemeshConfig, RemeshError, RemeshProgress, RemeshStats};
+pub use replay::{
+    decode_packet, mesh_content_hash, preset_for_header, read_packets, replay_packets,
+    write_packets, ReplayError,
+};
+#[cfg(feature = "bevy")]
+pub use replay::primitive_base_mesh;
@@ -18,9 +18,12 @@ use crate::spatial::{Aabb as SpatialAabb, VertexOctree};
 use crate::tessellation::{
     tessellate_at_brush, tessellate_at_brush_budget, ScreenSpaceConfig, TessellationStats,
 };
-use crate::types::{ChunkConfig, SculptStrokePacket, TessellationConfig, TessellationMode};
+use crate::types::{
+    ChunkConfig, DeformationType, SculptStrokePacket, TessellationConfig, TessellationMode,
+};
 use glam::Vec3;
 use painting::half_edge::VertexId;
+use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
 use tracing::{debug, error, trace};
 
@@ -57,6 +60,9 @@ pub struct PipelineConfig {
     pub chunk_config: ChunkConfig,
     /// Whether to rebalance chunks after each stroke.
     pub rebalance_after_stroke: bool,
+    /// Whether to tessellate affected chunks in parallel. Results are identical
+    /// to the serial path; disable for profiling or debugging.
+    pub parallel_tessellation: bool,
 }
 
 impl Default for PipelineConfig {
@@ -72,6 +78,7 @@ impl Default for PipelineConfig {
             chunk_config: ChunkConfig::default(),
             // Chunk rebalancing enabled - works with improved tessellation
             rebalance_after_stroke: true,
+            parallel_tessellation: true,
         }
     }
 }
@@ -115,39 +122,66 @@ pub struct SculptingPipeline {
     active_stroke_state: Option<ActiveStrokeState>,
     /// Per-chunk octrees for spatial queries (lazily built).
     chunk_octrees: HashMap<ChunkId, VertexOctree>,
+    /// Configuration set mid-stroke, applied when the next stroke begins.
+    pending_config: Option<PipelineConfig>,
 }
 
 impl SculptingPipeline {
     /// Create a new sculpting pipeline with the given brush preset.
     pub fn new(brush_preset: BrushPreset) -> Self {
-        Self {
-            brush_engine: SculptBrushEngine::new(brush_preset),
-            config: PipelineConfig::default(),
-            screen_config: ScreenSpaceConfig::default(),
-            budget: VertexBudget::default(),
-            active_stroke_state: None,
-            chunk_octrees: HashMap::new(),
-        }
+        Self::with_config(brush_preset, PipelineConfig::default())
     }
 
     /// Create a pipeline with custom configuration.
     pub fn with_config(brush_preset: BrushPreset, config: PipelineConfig) -> Self {
+        let mut budget = VertexBudget::default();
+        budget.set_limit(config.tessellation_config.max_vertices);
         Self {
             brush_engine: SculptBrushEngine::new(brush_preset),
             config,
             screen_config: ScreenSpaceConfig::default(),
-            budget: VertexBudget::default(),
+            budget,
             active_stroke_state: None,
             chunk_octrees: HashMap::new(),
+            pending_config: None,
+        }
+    }
+
+    /// Replace the pipeline configuration (dynamic topology, detail mode and
+    /// size, vertex budget).
+    ///
+    /// Takes effect immediately between strokes. During a stroke the change
+    /// is deferred to the next `begin_stroke`, so a stroke keeps one detail
+    /// level from start to finish.
+    pub fn set_config(&mut self, config: PipelineConfig) {
+        if self.is_stroke_active() {
+            self.pending_config = Some(config);
+        } else {
+            self.apply_config(config);
         }
     }
 
+    /// The configuration the next stroke will use.
+    pub fn next_config(&self) -> &PipelineConfig {
+        self.pending_config.as_ref().unwrap_or(&self.config)
+    }
+
+    fn apply_config(&mut self, config: PipelineConfig) {
+        self.budget.set_limit(config.tessellation_config.max_vertices);
+        self.config = config;
+    }
+
     /// Update the screen-space configuration (call when camera changes).
     /// Used in `ScreenSpace` tessellation mode.
     pub fn update_screen_config(&mut self, screen_config: ScreenSpaceConfig) {
         self.screen_config = screen_config;
     }
 
+    /// The screen-space configuration from the last camera update.
+    pub fn screen_config(&self) -> &ScreenSpaceConfig {
+        &self.screen_config
+    }
+
     /// Update the vertex budget from pixel coverage data.
     /// Used in `BudgetCurvature` tessellation mode.
     pub fn update_budget_from_coverage(&mut self, pixel_coverage: u32) {
@@ -169,6 +203,10 @@ impl SculptingPipeline {
     ///
     /// Returns the stroke ID.
     pub fn begin_stroke(&mut self, mesh_id: u32, input: BrushInput) -> u64 {
+        if let Some(config) = self.pending_config.take() {
+            self.apply_config(config);
+        }
+
         self.active_stroke_state = Some(ActiveStrokeState {
             affected_chunks: HashSet::new(),
             last_dab_position: Some(input.position),
@@ -235,6 +273,31 @@ impl SculptingPipeline {
         result
     }
 
+    /// Apply a dab decoded from a recorded stroke packet.
+    ///
+    /// Bypasses dab generation so a replayed stroke lands exactly where it
+    /// was recorded instead of being re-spaced. Call between `begin_stroke`
+    /// and `end_stroke`.
+    pub fn apply_recorded_dab(
+        &mut self,
+        dab: &DabResult,
+        chunked_mesh: &mut ChunkedMesh,
+    ) -> DabProcessResult {
+        let (last_pos, first_pos) = match &self.active_stroke_state {
+            Some(state) => (state.last_dab_position, state.first_dab_position),
+            None => return DabProcessResult::default(),
+        };
+
+        let result = self.apply_dab_internal(dab, last_pos, first_pos, chunked_mesh);
+
+        if let Some(state) = &mut self.active_stroke_state {
+            state.affected_chunks.extend(result.chunks_affected.iter().copied());
+            state.last_dab_position = Some(dab.position);
+        }
+
+        result
+    }
+
     /// End the current stroke and perform post-stroke processing.
     ///
     /// This triggers chunk rebalancing if configured.
@@ -309,7 +372,10 @@ impl SculptingPipeline {
 
         // Find affected chunks
         debug!("apply_dab_internal: finding chunks in sphere");
-        let affected_chunk_ids = chunked_mesh.chunks_intersecting_sphere(brush_center, influence_radius);
+        // Sorted so chunks are always processed (and allocate vertex IDs) in the same order
+        let mut affected_chunk_ids =
+            chunked_mesh.chunks_intersecting_sphere(brush_center, influence_radius);
+        affected_chunk_ids.sort_by_key(|id| id.0);
         debug!("apply_dab_internal: found {} affected chunks", affected_chunk_ids.len());
         trace!("apply_dab_internal: found {} affected chunks", affected_chunk_ids.len());
 
@@ -319,7 +385,8 @@ impl SculptingPipeline {
         let mut next_original_vertex_id = chunked_mesh.next_original_vertex_id;
 
         // Pre-compute total vertex count for budget mode (avoids borrow conflict inside chunk loop)
-        if self.config.tessellation_config.mode == TessellationMode::BudgetCurvature {
+        let budget_mode = self.config.tessellation_config.mode == TessellationMode::BudgetCurvature;
+        if budget_mode {
             self.budget.update_current(chunked_mesh.total_vertex_count());
         }
 
@@ -329,109 +396,67 @@ impl SculptingPipeline {
         // vertices exist before the brush tries to deform them. Without this,
         // new vertices from pass-through edge splits would be placed on the
         // un-deformed surface, creating dents and discontinuities.
-        if self.config.tessellation_enabled {
-            for &chunk_id in &affected_chunk_ids {
-                let chunk = match chunked_mesh.get_chunk_mut(chunk_id) {
-                    Some(c) => c,
-                    None => continue,
-                };
-
-                // Validate mesh BEFORE tessellation in debug builds.
-                // Gated behind env var to allow skipping during interactive testing:
-                //   PENTIMENTO_SKIP_MESH_VALIDATION=1 cargo run
-                #[cfg(debug_assertions)]
-                if std::env::var("PENTIMENTO_SKIP_MESH_VALIDATION").is_err() {
-                    if let Err(e) = chunk.mesh.validate_connectivity() {
-                        error!("MESH CORRUPT BEFORE tessellation: {}", e);
-                        panic!("Mesh corrupted before tessellation - bug is in chunk split: {}", e);
-                    }
-                }
-
-                let tess_start = std::time::Instant::now();
-                debug!(
-                    "apply_dab_internal: starting tessellation (faces={}, verts={})",
-                    chunk.mesh.face_count(),
-                    chunk.mesh.vertex_count()
-                );
-                let tess_stats = match self.config.tessellation_config.mode {
-                    TessellationMode::BudgetCurvature => {
-                        tessellate_at_brush_budget(
-                            chunk,
-                            brush_center,
-                            brush_radius,
-                            &self.config.tessellation_config,
-                            &mut self.budget,
-                            &mut next_original_vertex_id,
-                        )
-                    }
-                    TessellationMode::ScreenSpace => tessellate_at_brush(
-                        chunk,
-                        brush_center,
-                        brush_radius,
-                        &self.config.tessellation_config,
-                        &self.screen_config,
-                        &mut next_original_vertex_id,
-                    ),
-                };
-                debug!(
-                    "apply_dab_internal: tessellation done in {:?} - split={}, collapsed={}, faces={}",
-                    tess_start.elapsed(),
-                    tess_stats.edges_split,
-                    tess_stats.edges_collapsed,
-                    chunk.mesh.face_count(),
-                );
-
-                // Validate mesh after tessellation in debug builds
-                #[cfg(debug_assertions)]
-                if std::env::var("PENTIMENTO_SKIP_MESH_VALIDATION").is_err() {
-                    if let Err(e) = chunk.mesh.validate_connectivity() {
-                        error!("MESH CORRUPTION after tessellation: {}", e);
-                        panic!("Mesh corrupted by tessellation: {}", e);
-                    }
-                }
+        //
+        // Chunks are tessellated independently (in parallel unless disabled).
+        // Every chunk allocates original IDs from the same base, and the ranges
+        // are shifted into place afterwards in chunk ID order, so the IDs match
+        // what a serial pass over the same chunks would assign.
+        // Painting the mask never changes the surface, so it doesn't need detail
+        let painting_mask = self.brush_engine.preset.deformation_type == DeformationType::Mask;
+        if self.config.tessellation_enabled && !painting_mask {
+            let mut chunks: Vec<&mut MeshChunk> = chunked_mesh
+                .chunks
+                .values_mut()
+                .filter(|chunk| affected_chunk_ids.contains(&chunk.id))
+                .collect();
+            chunks.sort_by_key(|chunk| chunk.id.0);
+
+            // Each chunk gets an equal share of the budget so the outcome doesn't
+            // depend on which chunk finishes first
+            let mut budgets = self.budget.split_remaining(chunks.len());
+            let id_base = next_original_vertex_id;
+            let tess_config = &self.config.tessellation_config;
+            let screen_config = &self.screen_config;
+
+            let outcomes: Vec<ChunkTessellation> = if self.config.parallel_tessellation {
+                chunks
+                    .par_iter_mut()
+                    .zip(budgets.par_iter_mut())
+                    .map(|(chunk, budget)| {
+                        tessellate_chunk(chunk, dab, tess_config, screen_config, budget, id_base)
+                    })
+                    .collect()
+            } else {
+                chunks
+                    .iter_mut()
+                    .zip(budgets.iter_mut())
+                    .map(|(chunk, budget)| {
+                        tessellate_chunk(chunk, dab, tess_config, screen_config, budget, id_base)
+                    })
+                    .collect()
+            };
 
-                debug!(
-                    "SCULPT TESS: split={}, collapsed={}, chunk_faces={}",
-                    tess_stats.edges_split,
-                    tess_stats.edges_collapsed,
-                    chunk.mesh.face_count()
-                );
+            for (chunk, outcome) in chunks.into_iter().zip(outcomes) {
+                offset_allocated_ids(chunk, id_base, next_original_vertex_id - id_base);
+                next_original_vertex_id += outcome.ids_allocated;
 
-                if tess_stats.edges_split > 0 || tess_stats.edges_collapsed > 0 {
-                    chunk.mark_topology_changed();
-
-                    // CRITICAL: Recalculate normals after tessellation changed topology.
-                    // New faces inherit the original face's normal which is now wrong,
-                    // and new vertices only have interpolated normals that don't match
-                    // the actual post-split geometry.
-                    trace!("apply_dab_internal: recalculating normals after tessellation");
-                    let tessellated_vertices: HashSet<VertexId> = chunk
-                        .mesh
-                        .vertices()
-                        .iter()
-                        .filter(|v| {
-                            v.position.distance_squared(brush_center)
-                                <= (brush_radius * 1.5).powi(2)
-                        })
-                        .map(|v| v.id)
-                        .collect();
-                    let tess_dirty = DirtyVertices {
-                        modified: tessellated_vertices,
-                    };
-                    update_normals_after_deformation(chunk, &tess_dirty);
-                }
-
-                let existing = result.tessellation.get_or_insert(TessellationStats::default());
-                existing.edges_split += tess_stats.edges_split;
-                existing.edges_collapsed += tess_stats.edges_collapsed;
+                let existing = result
+                    .tessellation
+                    .get_or_insert(TessellationStats::default());
+                existing.edges_split += outcome.stats.edges_split;
+                existing.edges_collapsed += outcome.stats.edges_collapsed;
 
                 // Track this chunk as affected (tessellation happened)
-                if tess_stats.edges_split > 0 || tess_stats.edges_collapsed > 0 {
-                    result.chunks_affected.push(chunk_id);
+                if outcome.stats.edges_split > 0 || outcome.stats.edges_collapsed > 0 {
+                    result.chunks_affected.push(chunk.id);
                     // Invalidate octree since topology changed
-                    self.chunk_octrees.remove(&chunk_id);
+                    self.chunk_octrees.remove(&chunk.id);
                 }
             }
+
+            if budget_mode {
+                self.budget.update_current(chunked_mesh.total_vertex_count());
+            }
         }
 
         // ===== BOUNDARY SYNC between tessellation and deformation =====
@@ -492,7 +517,7 @@ impl SculptingPipeline {
 
             // Auto-smooth to dampen high-frequency dab ripples
             let autosmooth = self.brush_engine.preset.autosmooth;
-            if autosmooth > 0.0 && !affected_vertices.is_empty() {
+            if autosmooth > 0.0 && !painting_mask && !affected_vertices.is_empty() {
                 apply_autosmooth(
                     &mut chunk.mesh,
                     &affected_vertices,
@@ -507,15 +532,15 @@ impl SculptingPipeline {
                 result.chunks_affected.push(chunk_id);
             }
 
-            // Mark chunk dirty and track affected vertices
-            chunk.mark_dirty();
-
             // Update normals for affected region
             let dirty = DirtyVertices {
                 modified: affected_vertices.into_iter().collect(),
             };
             update_normals_after_deformation(chunk, &dirty);
 
+            // Keep the affected vertices until GPU sync so only they are re-uploaded
+            chunk.mark_vertices_dirty(dirty.modified);
+
             // Invalidate octree (positions changed)
             self.chunk_octrees.remove(&chunk_id);
         }
@@ -741,6 +766,143 @@ impl SculptingPipeline {
     }
 }
 
+/// Outcome of tessellating one chunk in pass 1.
+struct ChunkTessellation {
+    stats: TessellationStats,
+    /// Original IDs allocated from the shared base
+    ids_allocated: u32,
+}
+
+/// Tessellate one chunk around the dab and fix up its normals.
+///
+/// Only touches `chunk`, so chunks can be processed in parallel. New vertices
+/// get original IDs counting up from `id_base`.
+fn tessellate_chunk(
+    chunk: &mut MeshChunk,
+    dab: &DabResult,
+    config: &TessellationConfig,
+    screen_config: &ScreenSpaceConfig,
+    budget: &mut VertexBudget,
+    id_base: u32,
+) -> ChunkTessellation {
+    let brush_center = dab.position;
+    let brush_radius = dab.radius;
+    let mut next_original_vertex_id = id_base;
+
+    // Validate mesh BEFORE tessellation in debug builds.
+    // Gated behind env var to allow skipping during interactive testing:
+    //   PENTIMENTO_SKIP_MESH_VALIDATION=1 cargo run
+    #[cfg(debug_assertions)]
+    if std::env::var("PENTIMENTO_SKIP_MESH_VALIDATION").is_err() {
+        if let Err(e) = chunk.mesh.validate_connectivity() {
+            error!("MESH CORRUPT BEFORE tessellation: {}", e);
+            panic!("Mesh corrupted before tessellation - bug is in chunk split: {}", e);
+        }
+    }
+
+    let tess_start = std::time::Instant::now();
+    debug!(
+        "apply_dab_internal: starting tessellation (faces={}, verts={})",
+        chunk.mesh.face_count(),
+        chunk.mesh.vertex_count()
+    );
+    let tess_stats = match config.mode {
+        TessellationMode::BudgetCurvature => tessellate_at_brush_budget(
+            chunk,
+            brush_center,
+            brush_radius,
+            config,
+            budget,
+            &mut next_original_vertex_id,
+        ),
+        TessellationMode::ScreenSpace | TessellationMode::Constant => tessellate_at_brush(
+            chunk,
+            brush_center,
+            brush_radius,
+            config,
+            screen_config,
+            &mut next_original_vertex_id,
+        ),
+    };
+    debug!(
+        "apply_dab_internal: tessellation done in {:?} - split={}, collapsed={}, faces={}",
+        tess_start.elapsed(),
+        tess_stats.edges_split,
+        tess_stats.edges_collapsed,
+        chunk.mesh.face_count(),
+    );
+
+    // Validate mesh after tessellation in debug builds
+    #[cfg(debug_assertions)]
+    if std::env::var("PENTIMENTO_SKIP_MESH_VALIDATION").is_err() {
+        if let Err(e) = chunk.mesh.validate_connectivity() {
+            error!("MESH CORRUPTION after tessellation: {}", e);
+            panic!("Mesh corrupted by tessellation: {}", e);
+        }
+    }
+
+    debug!(
+        "SCULPT TESS: split={}, collapsed={}, chunk_faces={}",
+        tess_stats.edges_split,
+        tess_stats.edges_collapsed,
+        chunk.mesh.face_count()
+    );
+
+    if tess_stats.edges_split > 0 || tess_stats.edges_collapsed > 0 {
+        chunk.mark_topology_changed();
+
+        // CRITICAL: Recalculate normals after tessellation changed topology.
+        // New faces inherit the original face's normal which is now wrong,
+        // and new vertices only have interpolated normals that don't match
+        // the actual post-split geometry.
+        trace!("apply_dab_internal: recalculating normals after tessellation");
+        let tessellated_vertices: HashSet<VertexId> = chunk
+            .mesh
+            .vertices()
+            .iter()
+            .filter(|v| v.position.distance_squared(brush_center) <= (brush_radius * 1.5).powi(2))
+            .map(|v| v.id)
+            .collect();
+        let tess_dirty = DirtyVertices {
+            modified: tessellated_vertices,
+        };
+        update_normals_after_deformation(chunk, &tess_dirty);
+    }
+
+    ChunkTessellation {
+        stats: tess_stats,
+        ids_allocated: next_original_vertex_id - id_base,
+    }
+}
+
+/// Shift the original IDs a chunk allocated from `id_base` up by `offset`.
+///
+/// Only IDs at or above `id_base` can have been allocated by this dab; all
+/// older vertices map below it.
+fn offset_allocated_ids(chunk: &mut MeshChunk, id_base: u32, offset: u32) {
+    if offset == 0 {
+        return;
+    }
+
+    let shifted: Vec<(VertexId, VertexId)> = chunk
+        .local_to_original
+        .iter()
+        .filter(|(_, original)| original.0 >= id_base)
+        .map(|(&local, &original)| (local, original))
+        .collect();
+
+    // Remove every old key before inserting, so a shifted ID can't overwrite
+    // one that hasn't been moved yet
+    for (_, original) in &shifted {
+        chunk.original_to_local.remove(original);
+    }
+    for (local, original) in shifted {
+        let moved = VertexId(original.0 + offset);
+        chunk.local_to_original.insert(local, moved);
+        chunk.original_to_local.insert(moved, local);
+    }
+}
+
 /// Re-export the standalone rebalance function for direct use.
 pub use crate::chunking::merge::rebalance_chunks;
 
@@ -832,6 +994,42 @@ mod tests {
         assert!(!pipeline.config.rebalance_after_stroke);
     }
 
+    #[test]
+    fn test_config_change_mid_stroke_waits_for_next_stroke() {
+        let mut pipeline = SculptingPipeline::new(BrushPreset::default());
+        let mut chunked_mesh = ChunkedMesh::new();
+        let input = BrushInput {
+            position: Vec3::ZERO,
+            normal: Vec3::Y,
+            pressure: 1.0,
+            timestamp_ms: 0,
+        };
+
+        pipeline.begin_stroke(1, input);
+        let mut config = pipeline.config.clone();
+        config.tessellation_config.mode = TessellationMode::Constant;
+        config.tessellation_config.max_vertices = Some(2000);
+        pipeline.set_config(config);
+
+        // The running stroke keeps its detail settings
+        assert_eq!(
+            pipeline.config.tessellation_config.mode,
+            TessellationMode::ScreenSpace
+        );
+        assert_eq!(
+            pipeline.next_config().tessellation_config.mode,
+            TessellationMode::Constant
+        );
+
+        pipeline.end_stroke(&mut chunked_mesh);
+        pipeline.begin_stroke(1, input);
+        assert_eq!(
+            pipeline.config.tessellation_config.mode,
+            TessellationMode::Constant
+        );
+        assert_eq!(pipeline.budget.max_vertices, 2000);
+    }
+
     #[test]
     fn test_pipeline_invalidate_caches() {
         let preset = BrushPreset::default();
@@ -853,3 +1051,244 @@ mod tests {
         assert_eq!(chunked_mesh.chunk_count(), 0);
     }
 }
+
+#[cfg(all(test, feature = "bevy"))]
+mod parallel_tessellation_tests {
+    use super::*;
+    use crate::chunking::{PartitionConfig, partition_mesh};
+    use bevy::asset::RenderAssetUsages;
+    use bevy::math::primitives::Sphere;
+    use bevy::mesh::{Indices, Mesh, Meshable, PrimitiveTopology, VertexAttributeValues};
+    use painting::half_edge::HalfEdgeMesh;
+    use std::time::{Duration, Instant};
+
+    /// Chunk ID, vertex positions (as bits), face count and sorted ID mapping.
+    type ChunkSnapshot = (u32, Vec<[u32; 3]>, usize, Vec<(u32, u32)>);
+
+    /// Unit icosphere with `20 * (subdivisions + 1)^2 * 4^refinements` faces,
+    /// split into chunks. Bevy caps icosphere subdivisions below 80, so larger
+    /// spheres are refined by splitting every triangle into four.
+    fn chunked_sphere(subdivisions: u32, refinements: u32, target_faces: usize) -> ChunkedMesh {
+        let ico = Sphere::new(1.0).mesh().ico(subdivisions).unwrap();
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            ico.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("icosphere without positions");
+        };
+        let mut positions: Vec<Vec3> = positions.iter().map(|&p| Vec3::from_array(p)).collect();
+        let mut indices: Vec<u32> = ico.indices().unwrap().iter().map(|i| i as u32).collect();
+
+        for _ in 0..refinements {
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vec3>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let p = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(p);
+                    positions.len() as u32 - 1
+                })
+            };
+            let mut refined = Vec::with_capacity(indices.len() * 4);
+            for tri in indices.chunks_exact(3) {
+                let (a, b, c) = (tri[0], tri[1], tri[2]);
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                refined.extend_from_slice(&[a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+            }
+            indices = refined;
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        let positions: Vec<[f32; 3]> = positions.iter().map(|p| p.to_array()).collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, positions.clone());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_indices(Indices::U32(indices));
+
+        let he_mesh = HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap();
+        let config = PartitionConfig {
+            target_faces,
+            min_faces: target_faces / 2,
+            max_faces: target_faces * 3 / 2,
+        };
+        partition_mesh(&he_mesh, &config)
+    }
+
+    /// Run a stroke around the sphere's equator and return how long it took
+    /// and how many edges were split. The vertex budget allows `headroom`
+    /// vertices beyond the starting mesh.
+    fn run_stroke(
+        chunked_mesh: &mut ChunkedMesh,
+        mode: TessellationMode,
+        parallel: bool,
+        radius: f32,
+        steps: u64,
+        headroom: u32,
+    ) -> (Duration, usize) {
+        let config = PipelineConfig {
+            tessellation_config: TessellationConfig {
+                mode,
+                ..Default::default()
+            },
+            rebalance_after_stroke: false,
+            parallel_tessellation: parallel,
+            ..Default::default()
+        };
+        let mut preset = BrushPreset::push();
+        preset.radius = radius;
+        let mut pipeline = SculptingPipeline::with_config(preset, config);
+        pipeline.update_budget_from_coverage(chunked_mesh.total_vertex_count() as u32 + headroom);
+
+        let input = |step: u64| {
+            let angle = step as f32 * 0.05;
+            let position = Vec3::new(angle.cos(), 0.0, angle.sin());
+            BrushInput {
+                position,
+                normal: position,
+                pressure: 1.0,
+                timestamp_ms: step * 16,
+            }
+        };
+
+        let start = Instant::now();
+        let mut edges_split = 0;
+        pipeline.begin_stroke(0, input(0));
+        for step in 1..steps {
+            let result = pipeline.process_input(input(step), chunked_mesh);
+            edges_split += result.tessellation.map_or(0, |t| t.edges_split);
+        }
+        pipeline.end_stroke(chunked_mesh);
+        (start.elapsed(), edges_split)
+    }
+
+    /// Everything pass 1 can change, in chunk ID order.
+    fn snapshot(chunked_mesh: &ChunkedMesh) -> (u32, Vec<ChunkSnapshot>) {
+        let mut chunks: Vec<ChunkSnapshot> = chunked_mesh
+            .chunks
+            .values()
+            .map(|chunk| {
+                let positions = chunk
+                    .mesh
+                    .vertices()
+                    .iter()
+                    .map(|v| v.position.to_array().map(f32::to_bits))
+                    .collect();
+                let mut ids: Vec<(u32, u32)> = chunk
+                    .local_to_original
+                    .iter()
+                    .map(|(local, original)| (local.0, original.0))
+                    .collect();
+                ids.sort_unstable();
+                (chunk.id.0, positions, chunk.face_count(), ids)
+            })
+            .collect();
+        chunks.sort_by_key(|chunk| chunk.0);
+        (chunked_mesh.next_original_vertex_id, chunks)
+    }
+
+    /// Original IDs must stay unique across all chunks (boundary vertices share theirs).
+    fn assert_new_ids_unique(chunked_mesh: &ChunkedMesh, first_new_id: u32) {
+        let mut seen = HashSet::new();
+        for chunk in chunked_mesh.chunks.values() {
+            for original in chunk.local_to_original.values() {
+                if original.0 >= first_new_id {
+                    assert!(
+                        seen.insert(*original),
+                        "original ID {:?} allocated twice",
+                        original
+                    );
+                    assert!(original.0 < chunked_mesh.next_original_vertex_id);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_tessellation_matches_serial() {
+        for mode in [
+            TessellationMode::ScreenSpace,
+            TessellationMode::BudgetCurvature,
+        ] {
+            let mut serial = chunked_sphere(9, 0, 300);
+            let mut parallel = chunked_sphere(9, 0, 300);
+            let first_new_id = serial.next_original_vertex_id;
+
+            let (_, serial_splits) = run_stroke(&mut serial, mode, false, 0.4, 10, 500);
+            let (_, parallel_splits) = run_stroke(&mut parallel, mode, true, 0.4, 10, 500);
+
+            assert!(serial_splits > 0, "{mode:?} stroke should tessellate");
+            assert_eq!(serial_splits, parallel_splits);
+            assert!(
+                snapshot(&serial) == snapshot(&parallel),
+                "{mode:?} results differ"
+            );
+            assert_new_ids_unique(&parallel, first_new_id);
+        }
+    }
+
+    /// When the budget runs out mid-dab, the per-chunk shares must still give
+    /// the same chunks and allocated IDs as a serial pass.
+    #[test]
+    fn test_exhausted_budget_matches_serial() {
+        let mode = TessellationMode::BudgetCurvature;
+        let mut unlimited = chunked_sphere(9, 0, 300);
+        let (_, unlimited_splits) = run_stroke(&mut unlimited, mode, false, 0.4, 10, 1_000_000);
+
+        for headroom in [0, 1, 7, 40] {
+            let mut serial = chunked_sphere(9, 0, 300);
+            let mut parallel = chunked_sphere(9, 0, 300);
+            let first_new_id = serial.next_original_vertex_id;
+
+            let (_, serial_splits) = run_stroke(&mut serial, mode, false, 0.4, 10, headroom);
+            let (_, parallel_splits) = run_stroke(&mut parallel, mode, true, 0.4, 10, headroom);
+            assert!(
+                serial_splits < unlimited_splits,
+                "{headroom} vertices of headroom should cut tessellation short"
+            );
+            assert_eq!(serial_splits, parallel_splits);
+            assert!(
+                snapshot(&serial) == snapshot(&parallel),
+                "results differ with {headroom} vertices of headroom"
+            );
+            assert_new_ids_unique(&parallel, first_new_id);
+        }
+    }
+
+    /// Timed comparison on a 200k-face sphere. Run with
+    /// `cargo test --release -p sculpting --features bevy -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_parallel_tessellation_200k_faces() {
+        let mut serial = chunked_sphere(49, 1, 10000);
+        let mut parallel = chunked_sphere(49, 1, 10000);
+        assert_eq!(serial.total_face_count(), 200_000);
+
+        let (serial_time, _) = run_stroke(
+            &mut serial,
+            TessellationMode::ScreenSpace,
+            false,
+            0.5,
+            40,
+            500,
+        );
+        let (parallel_time, _) = run_stroke(
+            &mut parallel,
+            TessellationMode::ScreenSpace,
+            true,
+            0.5,
+            40,
+            500,
+        );
+
+        eprintln!(
+            "200k faces, {} chunks: serial {:?}, parallel {:?} ({:.2}x)",
+            serial.chunk_count(),
+            serial_time,
+            parallel_time,
+            serial_time.as_secs_f64() / parallel_time.as_secs_f64()
+        );
+        assert!(snapshot(&serial) == snapshot(&parallel), "results differ");
+    }
+}
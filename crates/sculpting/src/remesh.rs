@@ -0,0 +1,584 @@
+//! Uniform isotropic remeshing of a whole sculpt.
+//!
+//! Dynamic topology only refines under the brush, so after heavy sculpting
+//! the triangle distribution gets uneven. [`remesh_uniform`] rebuilds the
+//! whole mesh around one target edge length, following Botsch & Kobbelt's
+//! "A Remeshing Approach to Multiresolution Modeling" (2004). Each iteration:
+//!
+//! 1. Split edges longer than 4/3 × target
+//! 2. Collapse edges shorter than 4/5 × target
+//! 3. Flip edges that bring vertex valences closer to 6 (4 on boundaries)
+//! 4. Relax vertices toward their neighbor centroid in the tangent plane
+//!
+//! Splits and collapses reuse the tessellation primitives, so the same
+//! manifold safety checks apply. Vertices are not projected back onto the
+//! input surface; tangential relaxation keeps the drift small.
+//!
+//! ## Limitations
+//!
+//! Vertex UVs are interpolated by the split/collapse primitives but the
+//! vertex layout changes completely, so per-vertex and ptex paint data has
+//! to be reprojected by the caller.
+//!
+//! Remeshing can take seconds on large meshes. Run it off the main thread
+//! with a shared [`RemeshProgress`] to report progress and cancel.
+
+use glam::Vec3;
+use painting::half_edge::{FaceId, HalfEdgeMesh, VertexId};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use tracing::debug;
+
+use crate::tessellation::{can_collapse_edge_safe, collapse_edge, split_edge, CollapseCheck};
+
+/// How often (in edge operations) long passes check for cancellation.
+const CANCEL_CHECK_INTERVAL: usize = 1024;
+
+/// Maximum split sweeps per iteration. Each sweep at most halves the longest
+/// edges, so this covers edges up to 2^8 × the target length.
+const MAX_SPLIT_SWEEPS: usize = 8;
+
+/// Configuration for [`remesh_uniform`].
+#[derive(Debug, Clone)]
+pub struct RemeshConfig {
+    /// Target edge length in local mesh units
+    pub target_edge_length: f32,
+    /// Number of split/collapse/flip/relax iterations (default: 5)
+    pub iterations: usize,
+    /// Tangential relaxation strength per iteration (0.0 to 1.0, default: 0.5)
+    pub relaxation: f32,
+    /// Stop splitting once the mesh reaches this many faces (default: 2,000,000)
+    pub max_faces: usize,
+}
+
+impl Default for RemeshConfig {
+    fn default() -> Self {
+        Self {
+            target_edge_length: 0.02,
+            iterations: 5,
+            relaxation: 0.5,
+            max_faces: 2_000_000,
+        }
+    }
+}
+
+impl RemeshConfig {
+    /// Config with the given target edge length and default settings.
+    pub fn with_target_edge_length(target_edge_length: f32) -> Self {
+        Self {
+            target_edge_length,
+            ..Default::default()
+        }
+    }
+}
+
+/// Progress and cancellation shared between a remesh and its caller.
+#[derive(Debug, Default)]
+pub struct RemeshProgress {
+    /// Completed fraction (0.0 to 1.0) stored as `f32` bits
+    fraction: AtomicU32,
+    cancelled: AtomicBool,
+}
+
+impl RemeshProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Completed fraction of the remesh (0.0 to 1.0).
+    pub fn fraction(&self) -> f32 {
+        f32::from_bits(self.fraction.load(Ordering::Relaxed))
+    }
+
+    /// Ask the remesh to stop at the next check.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn set_fraction(&self, fraction: f32) {
+        self.fraction
+            .store(fraction.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    fn check(&self) -> Result<(), RemeshError> {
+        if self.is_cancelled() {
+            Err(RemeshError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Errors from [`remesh_uniform`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RemeshError {
+    #[error("remesh was cancelled")]
+    Cancelled,
+    #[error("invalid target edge length {0} (must be positive and finite)")]
+    InvalidTargetEdgeLength(f32),
+}
+
+/// Statistics from a completed remesh.
+#[derive(Debug, Clone, Default)]
+pub struct RemeshStats {
+    /// Iterations run
+    pub iterations: usize,
+    /// Total edges split
+    pub edges_split: usize,
+    /// Total edges collapsed
+    pub edges_collapsed: usize,
+    /// Total edges flipped
+    pub edges_flipped: usize,
+    /// Vertex count of the result
+    pub vertex_count: usize,
+    /// Face count of the result
+    pub face_count: usize,
+}
+
+/// Remesh `mesh` in place to a uniform edge length.
+///
+/// On cancellation `mesh` is left partially remeshed but valid; callers
+/// that want to keep the original should remesh a copy.
+pub fn remesh_uniform(
+    mesh: &mut HalfEdgeMesh,
+    config: &RemeshConfig,
+    progress: &RemeshProgress,
+) -> Result<RemeshStats, RemeshError> {
+    let target = config.target_edge_length;
+    if !target.is_finite() || target <= 0.0 {
+        return Err(RemeshError::InvalidTargetEdgeLength(target));
+    }
+
+    let high = target * 4.0 / 3.0;
+    let low = target * 4.0 / 5.0;
+    let mut stats = RemeshStats::default();
+    let total_steps = (config.iterations * 4).max(1) as f32;
+    let mut step = 0.0;
+    progress.set_fraction(0.0);
+
+    mesh.recalculate_face_normals();
+    mesh.recalculate_vertex_normals();
+
+    for iteration in 0..config.iterations {
+        progress.check()?;
+        stats.edges_split += split_long_edges(mesh, high, config.max_faces, progress)?;
+        step += 1.0;
+        progress.set_fraction(step / total_steps);
+
+        stats.edges_collapsed += collapse_short_edges(mesh, low, high, progress)?;
+        step += 1.0;
+        progress.set_fraction(step / total_steps);
+
+        stats.edges_flipped += flip_to_improve_valence(mesh, progress)?;
+        step += 1.0;
+        progress.set_fraction(step / total_steps);
+
+        relax_tangentially(mesh, config.relaxation);
+        mesh.recalculate_face_normals();
+        mesh.recalculate_vertex_normals();
+        step += 1.0;
+        progress.set_fraction(step / total_steps);
+
+        stats.iterations = iteration + 1;
+        debug!(
+            "remesh iteration {}: {} verts, {} faces",
+            iteration,
+            mesh.vertex_count(),
+            mesh.face_count()
+        );
+    }
+
+    stats.vertex_count = mesh.vertex_count();
+    stats.face_count = mesh.face_count();
+    progress.set_fraction(1.0);
+    Ok(stats)
+}
+
+/// Every edge of the live faces once, as a vertex pair with its length.
+fn collect_edges(mesh: &HalfEdgeMesh) -> Vec<(VertexId, VertexId, f32)> {
+    let mut seen = HashSet::new();
+    let mut edges = Vec::new();
+    for i in 0..mesh.face_count() {
+        let face_id = FaceId(i as u32);
+        if !mesh.is_face_valid(face_id) {
+            continue;
+        }
+        for he_id in mesh.get_face_half_edges(face_id) {
+            let Some(he) = mesh.half_edge(he_id) else {
+                continue;
+            };
+            let Some(dest) = mesh.get_half_edge_dest(he_id) else {
+                continue;
+            };
+            let key = (he.origin.0.min(dest.0), he.origin.0.max(dest.0));
+            if !seen.insert(key) {
+                continue;
+            }
+            let (Some(a), Some(b)) = (mesh.vertex(he.origin), mesh.vertex(dest)) else {
+                continue;
+            };
+            edges.push((he.origin, dest, a.position.distance(b.position)));
+        }
+    }
+    edges
+}
+
+/// Split every edge longer than `high`, longest first, until none remain.
+fn split_long_edges(
+    mesh: &mut HalfEdgeMesh,
+    high: f32,
+    max_faces: usize,
+    progress: &RemeshProgress,
+) -> Result<usize, RemeshError> {
+    let mut total = 0;
+    for _ in 0..MAX_SPLIT_SWEEPS {
+        let mut long_edges: Vec<_> = collect_edges(mesh)
+            .into_iter()
+            .filter(|&(_, _, len)| len > high)
+            .collect();
+        if long_edges.is_empty() {
+            break;
+        }
+        long_edges.sort_by(|a, b| {
+            b.2.total_cmp(&a.2)
+                .then((a.0 .0, a.1 .0).cmp(&(b.0 .0, b.1 .0)))
+        });
+
+        let mut splits = 0;
+        for (i, (v0, v1, _)) in long_edges.into_iter().enumerate() {
+            if i % CANCEL_CHECK_INTERVAL == 0 {
+                progress.check()?;
+            }
+            if mesh.face_count() >= max_faces {
+                break;
+            }
+            // Earlier splits may have replaced this edge
+            let Some(edge_id) = mesh.find_half_edge(v0, v1) else {
+                continue;
+            };
+            if split_edge(mesh, edge_id).is_some() {
+                splits += 1;
+            }
+        }
+
+        if splits == 0 {
+            break;
+        }
+        // Sequential splits can leave stale twins; the edge map is authoritative
+        mesh.rebuild_twins_from_edge_map();
+        total += splits;
+    }
+    Ok(total)
+}
+
+/// Collapse edges shorter than `low`, shortest first, unless the collapse
+/// would create an edge longer than `high`.
+fn collapse_short_edges(
+    mesh: &mut HalfEdgeMesh,
+    low: f32,
+    high: f32,
+    progress: &RemeshProgress,
+) -> Result<usize, RemeshError> {
+    let mut short_edges: Vec<_> = collect_edges(mesh)
+        .into_iter()
+        .filter(|&(_, _, len)| len < low)
+        .collect();
+    short_edges.sort_by(|a, b| {
+        a.2.total_cmp(&b.2)
+            .then((a.0 .0, a.1 .0).cmp(&(b.0 .0, b.1 .0)))
+    });
+
+    // Collapses break ring walks around their neighbors until the next
+    // compact, so each neighborhood is collapsed at most once per pass
+    let mut touched: HashSet<VertexId> = HashSet::new();
+    let mut collapses = 0;
+
+    for (i, (v0, v1, _)) in short_edges.into_iter().enumerate() {
+        if i % CANCEL_CHECK_INTERVAL == 0 {
+            progress.check()?;
+        }
+        if touched.contains(&v0) || touched.contains(&v1) {
+            continue;
+        }
+        if mesh.is_boundary_vertex(v0) || mesh.is_boundary_vertex(v1) {
+            continue;
+        }
+        let Some(edge_id) = mesh.find_half_edge(v0, v1) else {
+            continue;
+        };
+        let CollapseCheck::Safe(new_pos) = can_collapse_edge_safe(mesh, edge_id) else {
+            continue;
+        };
+
+        let neighbors_v0 = mesh.get_adjacent_vertices(v0);
+        let neighbors_v1 = mesh.get_adjacent_vertices(v1);
+        let creates_long_edge = neighbors_v0
+            .iter()
+            .chain(&neighbors_v1)
+            .filter(|&&n| n != v0 && n != v1)
+            .filter_map(|&n| mesh.vertex(n))
+            .any(|n| n.position.distance(new_pos) > high);
+        if creates_long_edge {
+            continue;
+        }
+
+        if collapse_edge(mesh, edge_id).is_some() {
+            collapses += 1;
+            touched.insert(v0);
+            touched.insert(v1);
+            touched.extend(neighbors_v0);
+            touched.extend(neighbors_v1);
+        }
+    }
+
+    if collapses > 0 {
+        mesh.compact();
+    }
+    Ok(collapses)
+}
+
+/// Flip interior edges where it reduces the squared valence deviation of
+/// the four vertices involved.
+fn flip_to_improve_valence(
+    mesh: &mut HalfEdgeMesh,
+    progress: &RemeshProgress,
+) -> Result<usize, RemeshError> {
+    let edges = collect_edges(mesh);
+    let mut valence: HashMap<VertexId, i32> = HashMap::new();
+    let mut target_valence: HashMap<VertexId, i32> = HashMap::new();
+    let mut touched: HashSet<VertexId> = HashSet::new();
+    let mut flips = 0;
+
+    for (i, (a, b, _)) in edges.into_iter().enumerate() {
+        if i % CANCEL_CHECK_INTERVAL == 0 {
+            progress.check()?;
+        }
+        if touched.contains(&a) || touched.contains(&b) {
+            continue;
+        }
+        let Some(edge_id) = mesh.find_half_edge(a, b) else {
+            continue;
+        };
+        let Some(he) = mesh.half_edge(edge_id) else {
+            continue;
+        };
+        let Some(twin) = he.twin.and_then(|t| mesh.half_edge(t)) else {
+            continue; // Boundary edge
+        };
+        let (Some(c), Some(d)) = (
+            mesh.half_edge(he.prev).map(|h| h.origin),
+            mesh.half_edge(twin.prev).map(|h| h.origin),
+        ) else {
+            continue;
+        };
+        if c == d || touched.contains(&c) || touched.contains(&d) {
+            continue;
+        }
+        // Flipping onto an existing edge would make the mesh non-manifold
+        if mesh.find_half_edge(c, d).is_some() || mesh.find_half_edge(d, c).is_some() {
+            continue;
+        }
+
+        let quad = [a, b, c, d];
+        for &v in &quad {
+            valence
+                .entry(v)
+                .or_insert_with(|| mesh.get_adjacent_vertices(v).len() as i32);
+            target_valence
+                .entry(v)
+                .or_insert_with(|| if mesh.is_boundary_vertex(v) { 4 } else { 6 });
+        }
+        let deviation = |delta: [i32; 4]| -> i32 {
+            quad.iter()
+                .zip(delta)
+                .map(|(v, dv)| (valence[v] + dv - target_valence[v]).pow(2))
+                .sum()
+        };
+        // The flip removes edge a-b and adds edge c-d
+        if deviation([-1, -1, 1, 1]) >= deviation([0; 4]) {
+            continue;
+        }
+        if !flip_keeps_orientation(mesh, a, b, c, d) {
+            continue;
+        }
+
+        if mesh.flip_edge_topology(edge_id) {
+            flips += 1;
+            touched.extend(quad);
+            *valence.get_mut(&a).unwrap() -= 1;
+            *valence.get_mut(&b).unwrap() -= 1;
+            *valence.get_mut(&c).unwrap() += 1;
+            *valence.get_mut(&d).unwrap() += 1;
+        }
+    }
+
+    if flips > 0 {
+        // Flips can leave edge map inconsistencies; compact cleans them up
+        mesh.compact();
+    }
+    Ok(flips)
+}
+
+/// Whether flipping edge a-b (faces abc, bad) to c-d (faces adc, bcd)
+/// keeps both new triangles facing the same way as the old pair.
+fn flip_keeps_orientation(
+    mesh: &HalfEdgeMesh,
+    a: VertexId,
+    b: VertexId,
+    c: VertexId,
+    d: VertexId,
+) -> bool {
+    let position = |v: VertexId| mesh.vertex(v).map(|v| v.position);
+    let (Some(pa), Some(pb), Some(pc), Some(pd)) =
+        (position(a), position(b), position(c), position(d))
+    else {
+        return false;
+    };
+
+    let old_normal = (pb - pa).cross(pc - pa) + (pa - pb).cross(pd - pb);
+    let new_adc = (pd - pa).cross(pc - pa);
+    let new_bcd = (pc - pb).cross(pd - pb);
+    new_adc.dot(old_normal) > 0.0 && new_bcd.dot(old_normal) > 0.0
+}
+
+/// Move interior vertices toward their neighbor centroid, restricted to the
+/// tangent plane so the surface doesn't shrink.
+fn relax_tangentially(mesh: &mut HalfEdgeMesh, strength: f32) {
+    let strength = strength.clamp(0.0, 1.0);
+    if strength == 0.0 {
+        return;
+    }
+
+    let mut targets: Vec<(VertexId, Vec3)> = Vec::new();
+    for vertex in mesh.vertices() {
+        let id = vertex.id;
+        if vertex.outgoing_half_edge.is_none() || mesh.is_boundary_vertex(id) {
+            continue;
+        }
+        let neighbors = mesh.get_adjacent_vertices(id);
+        if neighbors.is_empty() {
+            continue;
+        }
+        let centroid = neighbors
+            .iter()
+            .filter_map(|&n| mesh.vertex(n).map(|v| v.position))
+            .sum::<Vec3>()
+            / neighbors.len() as f32;
+
+        let normal = vertex.normal.normalize_or_zero();
+        let offset = centroid - vertex.position;
+        let tangent_offset = offset - normal * offset.dot(normal);
+        targets.push((id, vertex.position + tangent_offset * strength));
+    }
+
+    for (id, position) in targets {
+        mesh.set_vertex_position(id, position);
+    }
+}
+
+#[cfg(all(test, feature = "bevy"))]
+mod tests {
+    use super::*;
+    use bevy::asset::RenderAssetUsages;
+    use bevy::mesh::{Indices, Mesh, PrimitiveTopology};
+
+    /// `size` × `size` quad grid of unit extent on the XZ plane.
+    fn build_grid(size: usize) -> HalfEdgeMesh {
+        let step = 1.0 / size as f32;
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::new();
+
+        for z in 0..=size {
+            for x in 0..=size {
+                positions.push([x as f32 * step, 0.0, z as f32 * step]);
+                normals.push([0.0, 1.0, 0.0]);
+            }
+        }
+        for z in 0..size {
+            for x in 0..size {
+                let v0 = (z * (size + 1) + x) as u32;
+                let v1 = v0 + 1;
+                let v2 = v0 + (size + 1) as u32;
+                let v3 = v2 + 1;
+                indices.extend_from_slice(&[v0, v2, v1, v1, v2, v3]);
+            }
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_indices(Indices::U32(indices));
+        HalfEdgeMesh::from_bevy_mesh(&mesh).unwrap()
+    }
+
+    fn mean_edge_length(mesh: &HalfEdgeMesh) -> f32 {
+        let edges = collect_edges(mesh);
+        edges.iter().map(|e| e.2).sum::<f32>() / edges.len() as f32
+    }
+
+    #[test]
+    fn test_remesh_refines_toward_target() {
+        let mut mesh = build_grid(4); // 0.25 edges
+        let config = RemeshConfig::with_target_edge_length(0.05);
+        let progress = RemeshProgress::new();
+
+        let stats = remesh_uniform(&mut mesh, &config, &progress).unwrap();
+
+        assert!(stats.edges_split > 0);
+        assert_eq!(stats.face_count, mesh.face_count());
+        assert_eq!(progress.fraction(), 1.0);
+        mesh.validate_connectivity().unwrap();
+
+        let mean = mean_edge_length(&mesh);
+        assert!(
+            mean > 0.03 && mean < 0.07,
+            "mean edge length {mean} not near target 0.05"
+        );
+        // Still flat
+        assert!(mesh.vertices().iter().all(|v| v.position.y.abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_remesh_coarsens_toward_target() {
+        let mut mesh = build_grid(40); // 0.025 edges
+        let before = mesh.face_count();
+        let config = RemeshConfig::with_target_edge_length(0.1);
+
+        let stats = remesh_uniform(&mut mesh, &config, &RemeshProgress::new()).unwrap();
+
+        assert!(stats.edges_collapsed > 0);
+        assert!(mesh.face_count() < before);
+        mesh.validate_connectivity().unwrap();
+    }
+
+    #[test]
+    fn test_remesh_cancelled() {
+        let mut mesh = build_grid(4);
+        let progress = RemeshProgress::new();
+        progress.cancel();
+
+        let result = remesh_uniform(&mut mesh, &RemeshConfig::default(), &progress);
+        assert_eq!(result.unwrap_err(), RemeshError::Cancelled);
+    }
+
+    #[test]
+    fn test_remesh_rejects_invalid_target() {
+        let mut mesh = build_grid(2);
+        let config = RemeshConfig::with_target_edge_length(0.0);
+
+        let result = remesh_uniform(&mut mesh, &config, &RemeshProgress::new());
+        assert_eq!(
+            result.unwrap_err(),
+            RemeshError::InvalidTargetEdgeLength(0.0)
+        );
+    }
+}
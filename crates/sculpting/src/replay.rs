@@ -0,0 +1,474 @@
+//! Deterministic stroke replay.
+//!
+//! Recorded [`SculptStrokePacket`]s can be written to a compact binary file,
+//! read back and replayed onto a base mesh through the [`SculptingPipeline`].
+//! [`mesh_content_hash`] reduces the result to a single `u64` that ignores
+//! vertex and face ordering, so two replays of the same strokes can be
+//! compared, and regressions caught against golden hashes.
+//!
+//! ## File format
+//!
+//! All integers are little-endian.
+//!
+//! ```text
+//! magic "PSTK" | format version: u16 | packet count: u32
+//! per packet:
+//!   header version: u8 | mesh_id: u32 | stroke_id: u64 | timestamp_ms: u64
+//!   deformation_type: u8 | base_radius: u32 | strength: u8 | flow: u8
+//!   flags: u8 | base_x, base_y, base_z: i32 | dab count: u32
+//!   dabs: 8 bytes each, in `SculptDab` field order
+//! ```
+//!
+//! ## What a packet doesn't record
+//!
+//! Packets carry the brush type, radius, strength and flow, but not the
+//! falloff, hardness, spacing or autosmooth. Replay takes those from the
+//! built-in preset for the brush type, and assumes pressure affects strength
+//! (the preset default). Strokes made with edited presets replay
+//! deterministically but won't match the original session exactly.
+
+use glam::Vec3;
+use painting::half_edge::HalfEdgeMesh;
+use std::io::{self, Read, Write};
+use tracing::debug;
+
+use crate::brush::{builtin_presets, BrushInput, BrushPreset, DabResult};
+use crate::chunking::{merge_chunks, partition_mesh, PartitionConfig};
+use crate::pipeline::{PipelineConfig, SculptingPipeline};
+use crate::types::{DeformationType, SculptDab, SculptStrokeHeader, SculptStrokePacket};
+
+/// Magic bytes at the start of a stroke file.
+pub const STROKE_FILE_MAGIC: [u8; 4] = *b"PSTK";
+
+/// Current stroke file format version.
+pub const STROKE_FILE_VERSION: u16 = 1;
+
+/// Grid size for [`mesh_content_hash`] position quantization.
+pub const HASH_QUANTUM: f32 = 1e-5;
+
+/// Errors from reading a stroke file.
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("not a stroke file (bad magic bytes)")]
+    BadMagic,
+    #[error("unsupported stroke file version {0}")]
+    UnsupportedVersion(u16),
+    #[error("stroke file is truncated")]
+    Truncated,
+    #[error("unknown deformation type {0}")]
+    UnknownDeformationType(u8),
+}
+
+/// Write packets to a stroke file.
+pub fn write_packets(writer: &mut impl Write, packets: &[SculptStrokePacket]) -> io::Result<()> {
+    writer.write_all(&STROKE_FILE_MAGIC)?;
+    writer.write_all(&STROKE_FILE_VERSION.to_le_bytes())?;
+    writer.write_all(&(packets.len() as u32).to_le_bytes())?;
+
+    for packet in packets {
+        let header = &packet.header;
+        writer.write_all(&[header.version])?;
+        writer.write_all(&header.mesh_id.to_le_bytes())?;
+        writer.write_all(&header.stroke_id.to_le_bytes())?;
+        writer.write_all(&header.timestamp_ms.to_le_bytes())?;
+        writer.write_all(&[header.deformation_type as u8])?;
+        writer.write_all(&header.base_radius.to_le_bytes())?;
+        writer.write_all(&[header.strength, header.flow, header.flags])?;
+        writer.write_all(&header.base_x.to_le_bytes())?;
+        writer.write_all(&header.base_y.to_le_bytes())?;
+        writer.write_all(&header.base_z.to_le_bytes())?;
+        writer.write_all(&(packet.dabs.len() as u32).to_le_bytes())?;
+        writer.write_all(bytemuck::cast_slice(&packet.dabs))?;
+    }
+
+    Ok(())
+}
+
+/// Read packets from a stroke file.
+pub fn read_packets(reader: &mut impl Read) -> Result<Vec<SculptStrokePacket>, ReplayError> {
+    let mut reader = ByteReader(reader);
+
+    if reader.array::<4>()? != STROKE_FILE_MAGIC {
+        return Err(ReplayError::BadMagic);
+    }
+    let version = u16::from_le_bytes(reader.array()?);
+    if version != STROKE_FILE_VERSION {
+        return Err(ReplayError::UnsupportedVersion(version));
+    }
+
+    let packet_count = u32::from_le_bytes(reader.array()?);
+    let mut packets = Vec::new();
+    for _ in 0..packet_count {
+        let [version] = reader.array()?;
+        let mesh_id = u32::from_le_bytes(reader.array()?);
+        let stroke_id = u64::from_le_bytes(reader.array()?);
+        let timestamp_ms = u64::from_le_bytes(reader.array()?);
+        let [deformation_type] = reader.array()?;
+        let deformation_type = DeformationType::try_from(deformation_type)
+            .map_err(ReplayError::UnknownDeformationType)?;
+        let base_radius = u32::from_le_bytes(reader.array()?);
+        let [strength, flow, flags] = reader.array()?;
+        let base_x = i32::from_le_bytes(reader.array()?);
+        let base_y = i32::from_le_bytes(reader.array()?);
+        let base_z = i32::from_le_bytes(reader.array()?);
+
+        let dab_count = u32::from_le_bytes(reader.array()?) as usize;
+        let mut dabs = Vec::new();
+        for _ in 0..dab_count {
+            let bytes: [u8; 8] = reader.array()?;
+            dabs.push(bytemuck::cast(bytes));
+        }
+
+        packets.push(SculptStrokePacket {
+            header: SculptStrokeHeader {
+                version,
+                mesh_id,
+                stroke_id,
+                timestamp_ms,
+                deformation_type,
+                base_radius,
+                strength,
+                flow,
+                flags,
+                base_x,
+                base_y,
+                base_z,
+                lamport: 0,
+            },
+            dabs,
+        });
+    }
+
+    Ok(packets)
+}
+
+/// Reads fixed-size fields, reporting a short read as [`ReplayError::Truncated`].
+struct ByteReader<'a, R: Read>(&'a mut R);
+
+impl<R: Read> ByteReader<'_, R> {
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let mut bytes = [0; N];
+        self.0
+            .read_exact(&mut bytes)
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::UnexpectedEof => ReplayError::Truncated,
+                _ => ReplayError::Io(err),
+            })?;
+        Ok(bytes)
+    }
+}
+
+/// Brush preset that replays a packet: the built-in preset for its brush
+/// type with the recorded radius, strength and flow.
+pub fn preset_for_header(header: &SculptStrokeHeader) -> BrushPreset {
+    let mut preset = builtin_presets()
+        .into_iter()
+        .find(|preset| preset.deformation_type == header.deformation_type)
+        .unwrap_or_default();
+    preset.deformation_type = header.deformation_type;
+    preset.radius = header.base_radius as f32 / 1000.0;
+    preset.strength = header.strength as f32 / 255.0;
+    preset.flow = header.flow as f32 / 255.0;
+    preset
+}
+
+/// Decode a packet's dabs back to world-space dab results.
+///
+/// The header base is the position of the packet's last dab and each dab
+/// stores the delta from the dab before it, so positions are rebuilt
+/// backwards from the base.
+pub fn decode_packet(packet: &SculptStrokePacket, preset: &BrushPreset) -> Vec<DabResult> {
+    let header = &packet.header;
+    let mut position = Vec3::new(
+        header.base_x as f32,
+        header.base_y as f32,
+        header.base_z as f32,
+    ) / 1000.0;
+
+    let mut positions = vec![Vec3::ZERO; packet.dabs.len()];
+    for (slot, dab) in positions.iter_mut().zip(&packet.dabs).rev() {
+        *slot = position;
+        position -= dab_delta(dab);
+    }
+
+    positions
+        .into_iter()
+        .zip(&packet.dabs)
+        .map(|(position, &dab)| {
+            let pressure = dab.pressure as f32 / 255.0;
+            DabResult {
+                position,
+                normal: dab.decode_normal(),
+                radius: preset.radius * dab.radius_multiplier(),
+                strength: preset.dab_strength(pressure),
+                dab,
+            }
+        })
+        .collect()
+}
+
+fn dab_delta(dab: &SculptDab) -> Vec3 {
+    Vec3::new(dab.dx as f32, dab.dy as f32, dab.dz as f32) / 100.0
+}
+
+/// Replay packets onto a copy of `base_mesh` and return the merged result.
+///
+/// Consecutive packets with the same stroke ID are replayed as one stroke.
+/// The mesh ID in the headers is ignored; every packet applies to the base
+/// mesh.
+pub fn replay_packets(
+    base_mesh: &HalfEdgeMesh,
+    packets: &[SculptStrokePacket],
+    config: PipelineConfig,
+) -> HalfEdgeMesh {
+    let mut chunked_mesh = partition_mesh(base_mesh, &PartitionConfig::from(&config.chunk_config));
+    let mut pipeline = SculptingPipeline::with_config(BrushPreset::default(), config);
+
+    let mut current_stroke = None;
+    for packet in packets {
+        let preset = preset_for_header(&packet.header);
+        let dabs = decode_packet(packet, &preset);
+        let Some(first) = dabs.first() else {
+            continue;
+        };
+
+        if current_stroke != Some(packet.header.stroke_id) {
+            if current_stroke.is_some() {
+                pipeline.end_stroke(&mut chunked_mesh);
+            }
+            pipeline.set_brush_preset(preset);
+            pipeline.begin_stroke(
+                packet.header.mesh_id,
+                BrushInput {
+                    position: first.position,
+                    normal: first.normal,
+                    pressure: first.dab.pressure as f32 / 255.0,
+                    timestamp_ms: packet.header.timestamp_ms,
+                },
+            );
+            current_stroke = Some(packet.header.stroke_id);
+        }
+
+        for dab in &dabs {
+            pipeline.apply_recorded_dab(dab, &mut chunked_mesh);
+        }
+    }
+    if current_stroke.is_some() {
+        pipeline.end_stroke(&mut chunked_mesh);
+    }
+
+    let merged = merge_chunks(&chunked_mesh).mesh;
+    debug!(
+        "replayed {} packets: {} vertices, {} faces",
+        packets.len(),
+        merged.vertex_count(),
+        merged.face_count()
+    );
+    merged
+}
+
+/// Hash a mesh's shape independently of vertex and face order.
+///
+/// Vertex positions are quantized to [`HASH_QUANTUM`] and sorted; faces are
+/// rewritten as indices into that sorted list, rotated to start at their
+/// smallest index (keeping winding) and sorted. Vertices that quantize to
+/// the same position share an index.
+pub fn mesh_content_hash(mesh: &HalfEdgeMesh) -> u64 {
+    let quantize = |position: Vec3| {
+        (position / HASH_QUANTUM)
+            .round()
+            .to_array()
+            .map(|component| component as i64)
+    };
+
+    let mut positions: Vec<[i64; 3]> = mesh
+        .vertices()
+        .iter()
+        .map(|v| quantize(v.position))
+        .collect();
+    positions.sort_unstable();
+    positions.dedup();
+
+    let mut faces: Vec<Vec<u32>> = mesh
+        .faces()
+        .iter()
+        .map(|face| {
+            let mut indices: Vec<u32> = mesh
+                .get_face_vertices(face.id)
+                .into_iter()
+                .filter_map(|id| mesh.vertex(id))
+                .map(|vertex| {
+                    positions
+                        .binary_search(&quantize(vertex.position))
+                        .unwrap_or_default() as u32
+                })
+                .collect();
+            let start = indices
+                .iter()
+                .enumerate()
+                .min_by_key(|&(_, &index)| index)
+                .map_or(0, |(i, _)| i);
+            indices.rotate_left(start);
+            indices
+        })
+        .collect();
+    faces.sort_unstable();
+
+    let mut hasher = Fnv1a::new();
+    hasher.write(&(positions.len() as u64).to_le_bytes());
+    for position in &positions {
+        for component in position {
+            hasher.write(&component.to_le_bytes());
+        }
+    }
+    hasher.write(&(faces.len() as u64).to_le_bytes());
+    for face in &faces {
+        hasher.write(&(face.len() as u32).to_le_bytes());
+        for index in face {
+            hasher.write(&index.to_le_bytes());
+        }
+    }
+    hasher.finish()
+}
+
+/// 64-bit FNV-1a. Unlike `DefaultHasher` its output is fixed across Rust
+/// releases, which golden hashes depend on.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Base meshes matching the primitives the app spawns, by name.
+///
+/// Returns `None` for an unknown name.
+#[cfg(feature = "bevy")]
+pub fn primitive_base_mesh(name: &str) -> Option<HalfEdgeMesh> {
+    use bevy::math::primitives::{Cuboid, Sphere, Torus};
+    use bevy::mesh::{Mesh, Meshable};
+
+    let mesh: Mesh = match name {
+        "cube" => Cuboid::new(1.0, 1.0, 1.0).into(),
+        "sphere" => Sphere::new(0.5).mesh().uv(32, 18),
+        "torus" => Torus::new(0.3, 0.5).into(),
+        _ => return None,
+    };
+    HalfEdgeMesh::from_bevy_mesh(&mesh).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(stroke_id: u64, dabs: Vec<SculptDab>) -> SculptStrokePacket {
+        SculptStrokePacket {
+            header: SculptStrokeHeader {
+                version: 1,
+                mesh_id: 0,
+                stroke_id,
+                timestamp_ms: 1234,
+                deformation_type: DeformationType::ClayStrips,
+                base_radius: 250,
+                strength: 128,
+                flow: 200,
+                flags: 0,
+                base_x: 100,
+                base_y: -250,
+                base_z: 500,
+                lamport: 0,
+            },
+            dabs,
+        }
+    }
+
+    fn dab(dx: i8, dy: i8, dz: i8) -> SculptDab {
+        SculptDab {
+            dx,
+            dy,
+            dz,
+            pressure: 255,
+            radius_scale: 85,
+            normal_hint: 0x42,
+            _padding: [0, 0],
+        }
+    }
+
+    #[test]
+    fn test_stroke_file_round_trip() {
+        let packets = vec![
+            packet(0, vec![dab(0, 0, 0), dab(3, -2, 1)]),
+            packet(1, Vec::new()),
+        ];
+        let mut bytes = Vec::new();
+        write_packets(&mut bytes, &packets).unwrap();
+
+        let read = read_packets(&mut bytes.as_slice()).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].header.deformation_type, DeformationType::ClayStrips);
+        assert_eq!(read[0].header.base_y, -250);
+        assert_eq!(read[0].header.flow, 200);
+        assert_eq!(read[1].header.stroke_id, 1);
+        assert_eq!(
+            bytemuck::cast_slice::<SculptDab, u8>(&read[0].dabs),
+            bytemuck::cast_slice::<SculptDab, u8>(&packets[0].dabs)
+        );
+    }
+
+    #[test]
+    fn test_read_rejects_bad_files() {
+        let mut bytes = Vec::new();
+        write_packets(&mut bytes, &[packet(0, vec![dab(1, 1, 1)])]).unwrap();
+
+        assert!(matches!(
+            read_packets(&mut &bytes[..bytes.len() - 3]),
+            Err(ReplayError::Truncated)
+        ));
+        assert!(matches!(
+            read_packets(&mut &b"NOPE\x01\x00"[..]),
+            Err(ReplayError::BadMagic)
+        ));
+
+        // Deformation type byte follows magic, version, count and 21 header bytes
+        bytes[4 + 2 + 4 + 1 + 4 + 8 + 8] = 200;
+        assert!(matches!(
+            read_packets(&mut bytes.as_slice()),
+            Err(ReplayError::UnknownDeformationType(200))
+        ));
+    }
+
+    #[test]
+    fn test_decode_rebuilds_positions_from_last_dab() {
+        let packet = packet(0, vec![dab(0, 0, 0), dab(10, 0, 0), dab(0, -20, 5)]);
+        let preset = preset_for_header(&packet.header);
+        let dabs = decode_packet(&packet, &preset);
+
+        let base = Vec3::new(0.1, -0.25, 0.5);
+        assert!(dabs[2].position.abs_diff_eq(base, 1e-6));
+        assert!(dabs[1]
+            .position
+            .abs_diff_eq(base - Vec3::new(0.0, -0.2, 0.05), 1e-6));
+        assert!(dabs[0]
+            .position
+            .abs_diff_eq(base - Vec3::new(0.1, -0.2, 0.05), 1e-6));
+
+        // Built-in clay strips settings with the recorded strength and flow
+        assert_eq!(preset.falloff, BrushPreset::clay_strips().falloff);
+        assert!((dabs[0].strength - 128.0 / 255.0 * 200.0 / 255.0).abs() < 1e-6);
+        assert!((dabs[0].radius - 0.25 * dabs[0].dab.radius_multiplier()).abs() < 1e-6);
+    }
+}
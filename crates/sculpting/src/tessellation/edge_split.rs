@@ -230,11 +230,13 @@ pub fn split_edge_curvature_aware(
 }
 
 /// Calculate interpolated attributes for a split vertex.
+///
+/// Returns `(position, normal, uv, mask)`.
 pub fn interpolate_vertex_attributes(
     mesh: &HalfEdgeMesh,
     edge_id: HalfEdgeId,
     t: f32, // 0.0 = v0, 1.0 = v1, 0.5 = midpoint
-) -> Option<(Vec3, Vec3, Option<Vec2>)> {
+) -> Option<(Vec3, Vec3, Option<Vec2>, f32)> {
     let he = mesh.half_edge(edge_id)?;
     let v0 = mesh.vertex(he.origin)?;
 
@@ -247,8 +249,9 @@ pub fn interpolate_vertex_attributes(
         (Some(uv0), Some(uv1)) => Some(uv0.lerp(uv1, t)),
         _ => None,
     };
+    let mask = v0.mask + (v1.mask - v0.mask) * t;
 
-    Some((position, normal, uv))
+    Some((position, normal, uv, mask))
 }
 
 #[cfg(test)]
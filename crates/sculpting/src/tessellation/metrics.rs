@@ -6,7 +6,7 @@
 //! - Mesh quality metrics (aspect ratio, valence, degenerate faces)
 
 use crate::tessellation::TessellationDecision;
-use crate::types::TessellationConfig;
+use crate::types::{TessellationConfig, TessellationMode};
 use glam::{Mat4, Vec3, Vec4};
 use painting::half_edge::{FaceId, HalfEdgeMesh, VertexId};
 use tracing::debug;
@@ -107,7 +107,7 @@ impl ScreenSpaceConfig {
 /// Result of evaluating an edge for tessellation.
 #[derive(Debug, Clone, Copy)]
 pub struct EdgeEvaluation {
-    /// Edge length in screen pixels
+    /// Edge length in screen pixels (local mesh units in `Constant` mode)
     pub screen_length: f32,
     /// Decision based on thresholds
     pub decision: TessellationDecision,
@@ -171,16 +171,27 @@ fn estimate_screen_length_from_distance(
 ///
 /// Compares the edge's screen length to the configured thresholds
 /// and returns the appropriate action (split, collapse, or none).
+/// In `Constant` mode the edge's local-space length is compared against
+/// `target_edge_length` instead, ignoring the camera.
 pub fn evaluate_edge(
     v0: Vec3,
     v1: Vec3,
     config: &TessellationConfig,
     screen_config: &ScreenSpaceConfig,
 ) -> EdgeEvaluation {
-    let screen_length = calculate_edge_screen_length(v0, v1, screen_config);
+    let (screen_length, target) = match config.mode {
+        TessellationMode::Constant => (
+            calculate_world_edge_length(v0, v1),
+            config.target_edge_length,
+        ),
+        _ => (
+            calculate_edge_screen_length(v0, v1, screen_config),
+            config.target_pixels,
+        ),
+    };
 
-    let split_threshold = config.target_pixels * config.split_ratio;
-    let collapse_threshold = config.target_pixels * config.collapse_ratio;
+    let split_threshold = target * config.split_ratio;
+    let collapse_threshold = target * config.collapse_ratio;
 
     let decision = if screen_length > split_threshold {
         TessellationDecision::Split
@@ -504,6 +515,27 @@ mod tests {
         assert!(eval.screen_length > 0.0);
     }
 
+    #[test]
+    fn test_evaluate_edge_constant_ignores_camera() {
+        let config = TessellationConfig {
+            mode: TessellationMode::Constant,
+            target_edge_length: 0.1,
+            ..Default::default()
+        };
+        // Would be far off-screen with a real camera; Constant mode doesn't care
+        let screen_config = ScreenSpaceConfig::default();
+        let origin = Vec3::new(0.0, 0.0, -50.0);
+
+        let long = evaluate_edge(origin, origin + Vec3::X * 0.2, &config, &screen_config);
+        assert_eq!(long.decision, TessellationDecision::Split);
+
+        let short = evaluate_edge(origin, origin + Vec3::X * 0.01, &config, &screen_config);
+        assert_eq!(short.decision, TessellationDecision::Collapse);
+
+        let on_target = evaluate_edge(origin, origin + Vec3::X * 0.1, &config, &screen_config);
+        assert_eq!(on_target.decision, TessellationDecision::None);
+    }
+
     #[test]
     fn test_world_edge_length() {
         let v0 = Vec3::new(0.0, 0.0, 0.0);
@@ -565,65 +565,7 @@ fn collapse_pass_budget(
     // Compact after collapses or flips to remove dead elements and rebuild edge_map.
     // Flips can create edge_map inconsistencies that need cleanup even if no collapses occurred.
     if actual_collapses > 0 || actual_flips > 0 {
-        // Save boundary vertex original IDs BEFORE compact can lose them.
-        // Compact's liveness detection may falsely mark boundary vertices as dead,
-        // causing their local_to_original mapping to be silently lost. We save the
-        // mapping here so we can recover it after compact.
-        let boundary_originals: HashMap<VertexId, VertexId> = chunk
-            .boundary_vertices
-            .keys()
-            .filter_map(|&lid| chunk.local_to_original.get(&lid).map(|&oid| (lid, oid)))
-            .collect();
-
-        let compaction = chunk.mesh.compact();
-
-        // Update local_to_original and original_to_local with remapped vertex IDs
-        let old_l2o = std::mem::take(&mut chunk.local_to_original);
-        for (old_local, original) in old_l2o {
-            if let Some(&new_local) = compaction.vertex_map.get(&old_local) {
-                chunk.local_to_original.insert(new_local, original);
-                chunk.original_to_local.insert(original, new_local);
-            }
-        }
-
-        // Rebuild original_to_local from local_to_original
-        chunk.original_to_local.clear();
-        for (&local, &original) in &chunk.local_to_original {
-            chunk.original_to_local.insert(original, local);
-        }
-
-        // Update boundary_vertices with remapped vertex IDs
-        let old_boundary = std::mem::take(&mut chunk.boundary_vertices);
-        for (old_local, refs) in old_boundary {
-            if let Some(&new_local) = compaction.vertex_map.get(&old_local) {
-                chunk.boundary_vertices.insert(new_local, refs);
-            }
-        }
-
-        // Recover any boundary vertex mappings that were lost during compact.
-        // If compact removed a boundary vertex from vertex_map but it survived
-        // in the mesh (e.g., due to liveness detection disagreement), restore
-        // its original ID to maintain cross-chunk identity.
-        let mut boundary_recovered = 0;
-        for (&old_local, &original) in &boundary_originals {
-            if let Some(&new_local) = compaction.vertex_map.get(&old_local) {
-                if !chunk.local_to_original.contains_key(&new_local) {
-                    chunk.local_to_original.insert(new_local, original);
-                    chunk.original_to_local.insert(original, new_local);
-                    boundary_recovered += 1;
-                }
-            }
-        }
-        if boundary_recovered > 0 {
-            tracing::warn!(
-                "collapse_pass_budget: recovered {} boundary vertex mappings that \
-                 would have been lost during compact",
-                boundary_recovered
-            );
-        }
-
-        // Safety net: ensure ALL mesh vertices have local_to_original mappings.
-        repair_vertex_mappings(chunk, next_original_vertex_id);
+        compact_chunk(chunk, next_original_vertex_id, "collapse_pass_budget");
     }
 
     actual_collapses
@@ -883,69 +825,7 @@ fn collapse_pass(
     // Compact after collapses or flips to remove dead elements and rebuild edge_map.
     // Flips can create edge_map inconsistencies that need cleanup even if no collapses occurred.
     if actual_collapses > 0 || actual_flips > 0 {
-        // Save boundary vertex original IDs BEFORE compact can lose them.
-        // Compact's liveness detection may falsely mark boundary vertices as dead,
-        // causing their local_to_original mapping to be silently lost. We save the
-        // mapping here so we can recover it after compact.
-        let boundary_originals: HashMap<VertexId, VertexId> = chunk
-            .boundary_vertices
-            .keys()
-            .filter_map(|&lid| chunk.local_to_original.get(&lid).map(|&oid| (lid, oid)))
-            .collect();
-
-        let compaction = chunk.mesh.compact();
-
-        // Update local_to_original and original_to_local with remapped vertex IDs
-        let old_l2o = std::mem::take(&mut chunk.local_to_original);
-        for (old_local, original) in old_l2o {
-            if let Some(&new_local) = compaction.vertex_map.get(&old_local) {
-                chunk.local_to_original.insert(new_local, original);
-                chunk.original_to_local.insert(original, new_local);
-            }
-        }
-
-        // Rebuild original_to_local from local_to_original
-        chunk.original_to_local.clear();
-        for (&local, &original) in &chunk.local_to_original {
-            chunk.original_to_local.insert(original, local);
-        }
-
-        // Update boundary_vertices with remapped vertex IDs
-        let old_boundary = std::mem::take(&mut chunk.boundary_vertices);
-        for (old_local, refs) in old_boundary {
-            if let Some(&new_local) = compaction.vertex_map.get(&old_local) {
-                chunk.boundary_vertices.insert(new_local, refs);
-            }
-        }
-
-        // Recover any boundary vertex mappings that were lost during compact.
-        // If compact removed a boundary vertex from vertex_map but it survived
-        // in the mesh (e.g., due to liveness detection disagreement), restore
-        // its original ID to maintain cross-chunk identity.
-        let mut boundary_recovered = 0;
-        for (&old_local, &original) in &boundary_originals {
-            if let Some(&new_local) = compaction.vertex_map.get(&old_local) {
-                if !chunk.local_to_original.contains_key(&new_local) {
-                    chunk.local_to_original.insert(new_local, original);
-                    chunk.original_to_local.insert(original, new_local);
-                    boundary_recovered += 1;
-                }
-            }
-        }
-        if boundary_recovered > 0 {
-            tracing::warn!(
-                "collapse_pass: recovered {} boundary vertex mappings that \
-                 would have been lost during compact",
-                boundary_recovered
-            );
-        }
-
-        // Safety net: ensure ALL mesh vertices have local_to_original mappings.
-        // After compact(), some vertices may lose their mapping if compact's liveness
-        // detection differs from the mapping update (e.g., degenerate face removal
-        // or defensive half-edge skipping). Missing mappings cause merge_chunks to
-        // skip faces, creating visible holes in the mesh.
-        repair_vertex_mappings(chunk, next_original_vertex_id);
+        compact_chunk(chunk, next_original_vertex_id, "collapse_pass");
     }
 
     actual_collapses
@@ -1061,11 +941,14 @@ pub struct TessellationStats {
 ///
 /// The second case catches long edges like cube face diagonals that span
 /// across the brush area without having either endpoint inside it.
+///
+/// Edges are returned in ID order so split/collapse passes are deterministic
+/// (HashSet iteration order differs between runs).
 fn collect_edges_in_range(
     chunk: &MeshChunk,
     center: Vec3,
     radius: f32,
-) -> HashSet<HalfEdgeId> {
+) -> Vec<HalfEdgeId> {
     let radius_sq = radius * radius;
     let mut edges = HashSet::new();
     let mut in_range_vertices: HashSet<VertexId> = HashSet::new();
@@ -1114,6 +997,8 @@ fn collect_edges_in_range(
         }
     }
 
+    let mut edges: Vec<HalfEdgeId> = edges.into_iter().collect();
+    edges.sort_unstable_by_key(|id| id.0);
     edges
 }
 
@@ -1177,6 +1062,81 @@ fn evaluate_edge_in_chunk(
     evaluate_edge(v0_pos, v1_pos, config, screen_config)
 }
 
+/// Compact a chunk's mesh and carry its vertex mappings over to the new IDs.
+///
+/// `caller` names the pass in the warning logged when boundary mappings had
+/// to be recovered.
+pub(crate) fn compact_chunk(
+    chunk: &mut MeshChunk,
+    next_original_vertex_id: &mut u32,
+    caller: &str,
+) {
+    // Save boundary vertex original IDs BEFORE compact can lose them.
+    // Compact's liveness detection may falsely mark boundary vertices as dead,
+    // causing their local_to_original mapping to be silently lost. We save the
+    // mapping here so we can recover it after compact.
+    let boundary_originals: HashMap<VertexId, VertexId> = chunk
+        .boundary_vertices
+        .keys()
+        .filter_map(|&lid| chunk.local_to_original.get(&lid).map(|&oid| (lid, oid)))
+        .collect();
+
+    let compaction = chunk.mesh.compact();
+
+    // Update local_to_original and original_to_local with remapped vertex IDs
+    let old_l2o = std::mem::take(&mut chunk.local_to_original);
+    for (old_local, original) in old_l2o {
+        if let Some(&new_local) = compaction.vertex_map.get(&old_local) {
+            chunk.local_to_original.insert(new_local, original);
+            chunk.original_to_local.insert(original, new_local);
+        }
+    }
+
+    // Rebuild original_to_local from local_to_original
+    chunk.original_to_local.clear();
+    for (&local, &original) in &chunk.local_to_original {
+        chunk.original_to_local.insert(original, local);
+    }
+
+    // Update boundary_vertices with remapped vertex IDs
+    let old_boundary = std::mem::take(&mut chunk.boundary_vertices);
+    for (old_local, refs) in old_boundary {
+        if let Some(&new_local) = compaction.vertex_map.get(&old_local) {
+            chunk.boundary_vertices.insert(new_local, refs);
+        }
+    }
+
+    // Recover any boundary vertex mappings that were lost during compact.
+    // If compact removed a boundary vertex from vertex_map but it survived
+    // in the mesh (e.g., due to liveness detection disagreement), restore
+    // its original ID to maintain cross-chunk identity.
+    let mut boundary_recovered = 0;
+    for (&old_local, &original) in &boundary_originals {
+        if let Some(&new_local) = compaction.vertex_map.get(&old_local) {
+            if !chunk.local_to_original.contains_key(&new_local) {
+                chunk.local_to_original.insert(new_local, original);
+                chunk.original_to_local.insert(original, new_local);
+                boundary_recovered += 1;
+            }
+        }
+    }
+    if boundary_recovered > 0 {
+        tracing::warn!(
+            "{}: recovered {} boundary vertex mappings that \
+             would have been lost during compact",
+            caller,
+            boundary_recovered
+        );
+    }
+
+    // Safety net: ensure ALL mesh vertices have local_to_original mappings.
+    // After compact(), some vertices may lose their mapping if compact's liveness
+    // detection differs from the mapping update (e.g., degenerate face removal
+    // or defensive half-edge skipping). Missing mappings cause merge_chunks to
+    // skip faces, creating visible holes in the mesh.
+    repair_vertex_mappings(chunk, next_original_vertex_id);
+}
+
 /// Ensure all mesh vertices have `local_to_original` mappings.
 ///
 /// After `compact()`, some vertices may lose their mapping if the compaction's
@@ -1190,7 +1150,7 @@ fn evaluate_edge_in_chunk(
 ///
 /// This function scans all mesh vertices and assigns new globally unique original
 /// IDs to any that lack a mapping.
-fn repair_vertex_mappings(chunk: &mut MeshChunk, next_original_vertex_id: &mut u32) {
+pub(crate) fn repair_vertex_mappings(chunk: &mut MeshChunk, next_original_vertex_id: &mut u32) {
     let mut repaired_interior = 0;
     let mut recovered_boundary = 0;
     for i in 0..chunk.mesh.vertex_count() {
@@ -27,6 +27,34 @@ pub enum DeformationType {
     Pinch = 6,
     /// Crease along stroke path
     Crease = 7,
+    /// Build up flat strips of material on a fitted plane
+    ClayStrips = 8,
+    /// Shave off material above a fitted plane
+    Scrape = 9,
+    /// Paint the sculpt mask instead of displacing vertices
+    Mask = 10,
+}
+
+impl TryFrom<u8> for DeformationType {
+    type Error = u8;
+
+    /// Decode the `repr(u8)` discriminant, returning the byte if unknown.
+    fn try_from(value: u8) -> Result<Self, u8> {
+        Ok(match value {
+            0 => Self::Push,
+            1 => Self::Pull,
+            2 => Self::Grab,
+            3 => Self::Smooth,
+            4 => Self::Flatten,
+            5 => Self::Inflate,
+            6 => Self::Pinch,
+            7 => Self::Crease,
+            8 => Self::ClayStrips,
+            9 => Self::Scrape,
+            10 => Self::Mask,
+            other => return Err(other),
+        })
+    }
 }
 
 /// Header for a sculpt stroke packet.
@@ -48,12 +76,24 @@ pub struct SculptStrokeHeader {
     pub base_radius: u32,
     /// Brush strength 0-255
     pub strength: u8,
+    /// Brush flow 0-255 (per-dab strength multiplier)
+    #[serde(default = "full_flow")]
+    pub flow: u8,
     /// Reserved flags
     pub flags: u8,
     /// Base position for delta compression (fixed-point ×1000)
     pub base_x: i32,
     pub base_y: i32,
     pub base_z: i32,
+    /// Lamport clock of the stroke in a shared session (0 outside one;
+    /// not stored in stroke files)
+    #[serde(default)]
+    pub lamport: u64,
+}
+
+/// Flow for packets recorded before flow was added.
+fn full_flow() -> u8 {
+    u8::MAX
 }
 
 /// A single sculpt dab.
@@ -137,6 +177,9 @@ pub enum TessellationMode {
     /// Budget + curvature: global vertex budget from render camera pixel coverage,
     /// with curvature-prioritized split/collapse within that budget.
     BudgetCurvature,
+    /// Constant detail: per-edge evaluation against a fixed local-space edge
+    /// length, independent of the camera.
+    Constant,
 }
 
 /// Configuration for tessellation behavior.
@@ -165,6 +208,11 @@ pub struct TessellationConfig {
     /// Collapse edges smaller than target × collapse_ratio (default: 0.4)
     pub collapse_ratio: f32,
 
+    // --- Constant mode fields ---
+    /// Target edge length in local mesh units (default: 0.02).
+    /// Uses the same split/collapse ratios as ScreenSpace mode.
+    pub target_edge_length: f32,
+
     // --- BudgetCurvature mode fields ---
     /// Dihedral angle above which an edge is a split candidate (radians, default: 0.1 ~6°).
     /// Higher curvature edges are split first within the vertex budget.
@@ -178,6 +226,8 @@ pub struct TessellationConfig {
     /// Vertex-per-pixel multiplier for budget calculation (default: 1.0).
     /// The budget is `pixel_coverage * vertices_per_pixel`.
     pub vertices_per_pixel: f32,
+    /// Hard cap on the vertex budget (default: None = pixel coverage only).
+    pub max_vertices: Option<usize>,
 
     // --- Shared fields ---
     /// Minimum face count to preserve - never collapse below this (default: 4)
@@ -209,11 +259,14 @@ impl Default for TessellationConfig {
             target_pixels: 6.0,
             split_ratio: 1.5,
             collapse_ratio: 0.4,
+            // Constant
+            target_edge_length: 0.02,
             // BudgetCurvature
             curvature_split_threshold: 0.1,    // ~6 degrees
             curvature_collapse_threshold: 0.03, // ~2 degrees
             min_edge_length: 0.001,
             vertices_per_pixel: 1.0,
+            max_vertices: None,
             // Shared
             min_faces: 4,
             max_faces_per_chunk: 50000,
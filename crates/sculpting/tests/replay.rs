@@ -0,0 +1,169 @@
+//! Golden-hash tests for deterministic stroke replay.
+//!
+//! Each file in `tests/strokes/` was recorded against one of the app's
+//! primitive meshes. Replaying it must produce the same mesh every time,
+//! on every run. If a deformation or tessellation change alters the result
+//! on purpose, print the new hash with
+//! `cargo run -p sculpting --features bevy --bin replay -- <file> --base <mesh> --edge-length 0.03`
+//! and update the table below.
+#![cfg(feature = "bevy")]
+
+use glam::Vec3;
+use sculpting::{
+    mesh_content_hash, primitive_base_mesh, read_packets, replay_packets, BrushPreset, ChunkConfig,
+    DeformationType, PipelineConfig, SculptDab, SculptStrokeHeader, SculptStrokePacket,
+    TessellationConfig, TessellationMode,
+};
+use std::fs::File;
+use std::path::PathBuf;
+
+/// Stroke file, base mesh and expected content hash.
+const GOLDEN: &[(&str, &str, u64)] = &[
+    ("push_sphere.pstk", "sphere", 0xaffd_daa0_f845_e75e),
+    ("clay_strips_sphere.pstk", "sphere", 0x9fd0_424a_004f_3097),
+    ("mixed_torus.pstk", "torus", 0xadbc_1313_3b8c_89e9),
+];
+
+/// Constant-length tessellation: screen-space mode depends on the camera.
+fn golden_config() -> PipelineConfig {
+    PipelineConfig {
+        tessellation_config: TessellationConfig {
+            mode: TessellationMode::Constant,
+            target_edge_length: 0.03,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn stroke_file(name: &str) -> Vec<SculptStrokePacket> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/strokes")
+        .join(name);
+    read_packets(&mut File::open(&path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_recorded_strokes_match_golden_hashes() {
+    for &(file, base, expected) in GOLDEN {
+        let base_mesh = primitive_base_mesh(base).unwrap();
+        let packets = stroke_file(file);
+        let result = replay_packets(&base_mesh, &packets, golden_config());
+
+        assert_ne!(
+            mesh_content_hash(&result),
+            mesh_content_hash(&base_mesh),
+            "{file} didn't change the mesh"
+        );
+        assert_eq!(
+            mesh_content_hash(&result),
+            expected,
+            "{file} replayed to a different mesh"
+        );
+    }
+}
+
+#[test]
+fn test_content_hash_ignores_element_order() {
+    // Partitioning into several chunks and merging back renumbers every
+    // vertex and face
+    let config = PipelineConfig {
+        chunk_config: ChunkConfig {
+            min_faces: 100,
+            max_faces: 400,
+            target_faces: 200,
+        },
+        ..golden_config()
+    };
+    for base in ["cube", "sphere", "torus"] {
+        let base_mesh = primitive_base_mesh(base).unwrap();
+        let round_trip = replay_packets(&base_mesh, &[], config.clone());
+
+        assert_eq!(
+            mesh_content_hash(&round_trip),
+            mesh_content_hash(&base_mesh),
+            "{base} hash changed without any strokes"
+        );
+    }
+}
+
+/// Small xorshift generator so the fuzz test needs no extra dependencies.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn unit(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.next() as u8
+    }
+}
+
+/// Random strokes that start on the surface of the 0.5 radius sphere.
+fn random_packets(rng: &mut XorShift, strokes: u64) -> Vec<SculptStrokePacket> {
+    (0..strokes)
+        .map(|stroke_id| {
+            let direction = Vec3::new(rng.unit() - 0.5, rng.unit() - 0.5, rng.unit() - 0.5)
+                .normalize_or(Vec3::Z);
+            let base = direction * 0.5 * 1000.0;
+            let deformation_type = DeformationType::try_from(rng.byte() % 11).unwrap();
+            let dabs = (0..1 + rng.byte() % 24)
+                .map(|_| SculptDab {
+                    dx: (rng.byte() % 9) as i8 - 4,
+                    dy: (rng.byte() % 9) as i8 - 4,
+                    dz: (rng.byte() % 9) as i8 - 4,
+                    pressure: rng.byte(),
+                    radius_scale: rng.byte(),
+                    normal_hint: SculptDab::encode_normal(direction),
+                    _padding: [0, 0],
+                })
+                .collect();
+
+            SculptStrokePacket {
+                header: SculptStrokeHeader {
+                    version: 1,
+                    mesh_id: 0,
+                    stroke_id,
+                    timestamp_ms: stroke_id * 1000,
+                    deformation_type,
+                    base_radius: (BrushPreset::default().radius * 1000.0 * (0.3 + rng.unit()))
+                        as u32,
+                    strength: rng.byte(),
+                    flow: rng.byte(),
+                    flags: 0,
+                    base_x: base.x as i32,
+                    base_y: base.y as i32,
+                    base_z: base.z as i32,
+                    lamport: 0,
+                },
+                dabs,
+            }
+        })
+        .collect()
+}
+
+#[test]
+#[ignore = "slow; run with --ignored"]
+fn fuzz_random_strokes_replay_identically() {
+    let base_mesh = primitive_base_mesh("sphere").unwrap();
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+
+    for round in 0..10 {
+        let packets = random_packets(&mut rng, 6);
+        let first = replay_packets(&base_mesh, &packets, golden_config());
+        let second = replay_packets(&base_mesh, &packets, golden_config());
+        assert_eq!(
+            mesh_content_hash(&first),
+            mesh_content_hash(&second),
+            "round {round} replayed differently"
+        );
+    }
+}
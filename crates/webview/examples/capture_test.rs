@@ -2,6 +2,7 @@
 //!
 //! Run with: cargo run -p pentimento-webview --example capture_test
 
+use pentimento_frontend_core::blob::BlobRegistry;
 use pentimento_webview::OffscreenWebview;
 use std::time::Duration;
 
@@ -63,7 +64,7 @@ fn main() {
 
     println!("Creating offscreen webview (800x600)...");
 
-    let mut webview = OffscreenWebview::new(TEST_HTML, (800, 600))
+    let mut webview = OffscreenWebview::new(TEST_HTML, (800, 600), BlobRegistry::default())
         .expect("Failed to create webview");
 
     println!("Webview created. Pumping GTK events to allow content to load...");
@@ -0,0 +1,45 @@
+//! `pentimento-blob://` protocol for the WebKitGTK webviews
+//!
+//! Serves blobs from the app's `BlobRegistry` to `fetch()` in the page. The
+//! page itself is loaded from an HTML string, so the scheme is registered as
+//! CORS-enabled and every response allows any origin.
+
+use std::borrow::Cow;
+
+use pentimento_frontend_core::blob::{BLOB_SCHEME, BlobRegistry};
+use webkit2gtk::{SecurityManagerExt, WebContextExt, WebViewExt};
+use wry::http::{Request, Response, StatusCode, header};
+
+/// Handler for `wry::WebViewBuilder::with_custom_protocol(BLOB_SCHEME, ...)`
+pub(crate) fn blob_response(
+    blobs: &BlobRegistry,
+    request: &Request<Vec<u8>>,
+) -> Response<Cow<'static, [u8]>> {
+    let uri = request.uri().to_string();
+    let (status, body) = match blobs.get_url(&uri) {
+        // wry needs an owned body, so this is the one copy on the WebKit path
+        Some(bytes) => (StatusCode::OK, Cow::Owned(bytes.to_vec())),
+        None => {
+            tracing::warn!("Unknown or expired blob requested: {}", uri);
+            (StatusCode::NOT_FOUND, Cow::Borrowed(&[][..]))
+        }
+    };
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(body)
+        .unwrap_or_else(|_| Response::new(Cow::Borrowed(&[][..])))
+}
+
+/// Let pages fetch `pentimento-blob://` URLs across origins
+pub(crate) fn allow_blob_fetch(webkit_webview: &webkit2gtk::WebView) {
+    match webkit_webview
+        .context()
+        .and_then(|context| context.security_manager())
+    {
+        Some(security_manager) => security_manager.register_uri_scheme_as_cors_enabled(BLOB_SCHEME),
+        None => tracing::warn!("No WebKit security manager; blob fetches may be blocked"),
+    }
+}
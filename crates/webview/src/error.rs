@@ -25,6 +25,9 @@ pub enum WebviewError {
     #[error("Platform not supported")]
     PlatformNotSupported,
 
+    #[error("Overlay mode is not supported on this Wayland session: {0}")]
+    WaylandUnsupported(String),
+
     #[error("Initialization failed: {0}")]
     InitializationFailed(String),
 
@@ -6,6 +6,8 @@
 //! - CEF mode: Uses Chromium Embedded Framework for offscreen rendering (requires `cef` feature)
 //! - Dioxus mode: Native Rust UI with Dioxus (requires `dioxus` feature)
 
+#[cfg(target_os = "linux")]
+mod blob_protocol;
 mod error;
 
 #[cfg(target_os = "linux")]
@@ -28,12 +30,41 @@ pub use platform_linux_dioxus::LinuxDioxusRenderer;
 #[cfg(target_os = "linux")]
 pub use platform_linux_overlay::LinuxOverlayWebview;
 
-use pentimento_frontend_core::{CaptureResult, CompositeBackend, FrontendError};
+use pentimento_frontend_core::batch::batch_script;
+use pentimento_frontend_core::blob::BlobRegistry;
+use pentimento_frontend_core::{
+    AlphaMode8, BackendLifecycle, CaptureResult, CompositeBackend, FrontendError,
+};
 use pentimento_ipc::{BevyToUi, KeyboardEvent, MouseEvent, UiToBevy};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::mpsc;
 
+/// Hand every message queued for the UI since the last poll to the page
+/// with a single `eval`
+fn flush_to_ui(
+    to_ui_rx: &mut mpsc::UnboundedReceiver<BevyToUi>,
+    eval: impl FnOnce(&str) -> Result<(), WebviewError>,
+) {
+    let mut messages = Vec::new();
+    while let Ok(msg) = to_ui_rx.try_recv() {
+        messages.push(msg);
+    }
+    if messages.is_empty() {
+        return;
+    }
+
+    match batch_script(&messages) {
+        Ok(js) => {
+            tracing::trace!("Sending {} messages to the UI in one eval", messages.len());
+            if let Err(e) = eval(&js) {
+                tracing::warn!("Failed to eval JS for message injection: {:?}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to serialize messages for the UI: {}", e),
+    }
+}
+
 /// Offscreen webview that can be captured as a texture
 pub struct OffscreenWebview {
     #[cfg(target_os = "linux")]
@@ -46,21 +77,36 @@ pub struct OffscreenWebview {
 
     // IPC channels
     to_ui_tx: mpsc::UnboundedSender<BevyToUi>,
+    to_ui_rx: mpsc::UnboundedReceiver<BevyToUi>,
     from_ui_rx: mpsc::UnboundedReceiver<UiToBevy>,
 }
 
 impl OffscreenWebview {
     /// Create a new offscreen webview with the given HTML content
-    pub fn new(html_content: &str, size: (u32, u32)) -> Result<Self, WebviewError> {
+    ///
+    /// The page can fetch blobs from `blobs` at `pentimento-blob://<id>`.
+    pub fn new(
+        html_content: &str,
+        size: (u32, u32),
+        blobs: BlobRegistry,
+    ) -> Result<Self, WebviewError> {
         // Start NOT dirty - wait for warmup to complete before first capture
         let dirty = Arc::new(AtomicBool::new(false));
-        let (to_ui_tx, _to_ui_rx) = mpsc::unbounded_channel();
+        let (to_ui_tx, to_ui_rx) = mpsc::unbounded_channel();
         let (from_ui_tx, from_ui_rx) = mpsc::unbounded_channel();
 
         #[cfg(target_os = "linux")]
-        let inner =
-            platform_linux::LinuxWebview::new(html_content, size, dirty.clone(), from_ui_tx)?;
+        let inner = platform_linux::LinuxWebview::new(
+            html_content,
+            size,
+            dirty.clone(),
+            from_ui_tx,
+            blobs,
+        )?;
 
+        // The blob scheme isn't served on Windows yet
+        #[cfg(target_os = "windows")]
+        let _ = blobs;
         #[cfg(target_os = "windows")]
         let inner =
             platform_windows::WindowsWebview::new(html_content, size, dirty.clone(), from_ui_tx)?;
@@ -70,13 +116,19 @@ impl OffscreenWebview {
             dirty,
             size,
             to_ui_tx,
+            to_ui_rx,
             from_ui_rx,
         })
     }
 
     /// Poll for events. Call this each frame from Bevy's main loop.
+    /// Also injects pending messages into JavaScript once the page is ready.
     pub fn poll(&mut self) {
         self.inner.poll();
+
+        if self.is_ready() {
+            flush_to_ui(&mut self.to_ui_rx, |js| self.inner.eval(js));
+        }
     }
 
     /// Capture the framebuffer if the UI has changed since last capture.
@@ -103,6 +155,15 @@ impl OffscreenWebview {
         return true; // Windows implementation TBD
     }
 
+    /// Lifecycle state of the webview (warmup and resize frames included)
+    pub fn lifecycle(&self) -> BackendLifecycle {
+        #[cfg(target_os = "linux")]
+        return self.inner.lifecycle();
+
+        #[cfg(target_os = "windows")]
+        return BackendLifecycle::Ready; // Windows implementation TBD
+    }
+
     /// Force a capture regardless of dirty state
     pub fn capture(&mut self) -> Option<image::RgbaImage> {
         self.dirty.store(false, Ordering::SeqCst);
@@ -158,6 +219,11 @@ impl OffscreenWebview {
     pub fn eval(&self, js: &str) -> Result<(), WebviewError> {
         self.inner.eval(js)
     }
+
+    /// Destroy the webview before the app exits
+    pub fn shutdown(&mut self) {
+        self.inner.shutdown();
+    }
 }
 
 /// Overlay webview that composites via transparent child window
@@ -170,6 +236,7 @@ pub struct OverlayWebview {
 
     // IPC channels
     to_ui_tx: mpsc::UnboundedSender<BevyToUi>,
+    to_ui_rx: mpsc::UnboundedReceiver<BevyToUi>,
     from_ui_rx: mpsc::UnboundedReceiver<UiToBevy>,
 }
 
@@ -180,13 +247,15 @@ impl OverlayWebview {
     /// * `parent_window` - Raw window handle from Bevy's primary window
     /// * `html_content` - HTML content to load
     /// * `size` - Initial size (width, height)
+    /// * `blobs` - Blobs the page can fetch at `pentimento-blob://<id>`
     #[cfg(target_os = "linux")]
     pub fn new(
         parent_window: raw_window_handle::RawWindowHandle,
         html_content: &str,
         size: (u32, u32),
+        blobs: BlobRegistry,
     ) -> Result<Self, WebviewError> {
-        let (to_ui_tx, _to_ui_rx) = mpsc::unbounded_channel();
+        let (to_ui_tx, to_ui_rx) = mpsc::unbounded_channel();
         let (from_ui_tx, from_ui_rx) = mpsc::unbounded_channel();
 
         let inner = platform_linux_overlay::LinuxOverlayWebview::new(
@@ -194,19 +263,26 @@ impl OverlayWebview {
             html_content,
             size,
             from_ui_tx,
+            blobs,
         )?;
 
         Ok(Self {
             inner,
             size,
             to_ui_tx,
+            to_ui_rx,
             from_ui_rx,
         })
     }
 
     /// Poll for events. Call this each frame.
+    /// Also injects pending messages into JavaScript once the page is ready.
     pub fn poll(&mut self) {
         self.inner.poll();
+
+        if self.is_ready() {
+            flush_to_ui(&mut self.to_ui_rx, |js| self.inner.eval(js));
+        }
     }
 
     /// Check if the webview is ready
@@ -214,6 +290,11 @@ impl OverlayWebview {
         self.inner.is_ready()
     }
 
+    /// Lifecycle state of the overlay webview
+    pub fn lifecycle(&self) -> BackendLifecycle {
+        self.inner.lifecycle()
+    }
+
     /// Get the current size of the webview
     pub fn size(&self) -> (u32, u32) {
         self.size
@@ -225,6 +306,13 @@ impl OverlayWebview {
         self.inner.resize(width, height);
     }
 
+    /// Set the device scale factor (physical pixels per CSS pixel)
+    ///
+    /// Applied by the next `resize`, even if the size is unchanged.
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.inner.set_scale_factor(scale_factor);
+    }
+
     /// Forward a mouse event to the webview
     pub fn send_mouse_event(&mut self, event: MouseEvent) {
         self.inner.inject_mouse(event);
@@ -267,6 +355,11 @@ impl OverlayWebview {
     pub fn sync_visibility(&mut self, parent_visible: bool) {
         self.inner.sync_visibility(parent_visible);
     }
+
+    /// Destroy the overlay window before the app exits
+    pub fn shutdown(&mut self) {
+        self.inner.shutdown();
+    }
 }
 
 /// CEF-based offscreen webview that can be captured as a texture
@@ -290,7 +383,13 @@ pub struct CefWebview {
 #[cfg(feature = "cef")]
 impl CefWebview {
     /// Create a new CEF offscreen webview with the given HTML content
-    pub fn new(html_content: &str, size: (u32, u32)) -> Result<Self, WebviewError> {
+    ///
+    /// The page can fetch blobs from `blobs` at `pentimento-blob://<id>`.
+    pub fn new(
+        html_content: &str,
+        size: (u32, u32),
+        blobs: BlobRegistry,
+    ) -> Result<Self, WebviewError> {
         let dirty = Arc::new(AtomicBool::new(false));
         let (to_ui_tx, to_ui_rx) = mpsc::unbounded_channel();
         let (from_ui_tx, from_ui_rx) = mpsc::unbounded_channel();
@@ -301,6 +400,7 @@ impl CefWebview {
             size,
             dirty.clone(),
             from_ui_tx,
+            blobs,
         )?;
 
         Ok(Self {
@@ -318,21 +418,8 @@ impl CefWebview {
     pub fn poll(&mut self) {
         self.inner.poll();
 
-        // Inject any pending messages into JavaScript via the bridge's receiver function
-        while let Ok(msg) = self.to_ui_rx.try_recv() {
-            if let Ok(json) = serde_json::to_string(&msg) {
-                tracing::debug!("CEF: Injecting message to JS: {}", json);
-                // Call the bridge's receive function (set up by Svelte in native mode)
-                // Also log to console if __PENTIMENTO_RECEIVE__ is not defined
-                let js = format!(
-                    r#"if (window.__PENTIMENTO_RECEIVE__) {{ window.__PENTIMENTO_RECEIVE__('{}'); }} else {{ console.warn('CEF: __PENTIMENTO_RECEIVE__ not defined, message dropped'); }}"#,
-                    json.replace('\\', "\\\\").replace('\'', "\\'")
-                );
-                if let Err(e) = self.inner.eval(&js) {
-                    tracing::warn!("CEF: Failed to eval JS for message injection: {:?}", e);
-                }
-            }
-        }
+        // Inject pending messages into JavaScript via the bridge's batch receiver
+        flush_to_ui(&mut self.to_ui_rx, |js| self.inner.eval(js));
     }
 
     /// Capture the framebuffer if the UI has changed since last capture.
@@ -357,6 +444,21 @@ impl CefWebview {
         self.inner.is_ready()
     }
 
+    /// Set the device scale factor (physical pixels per CSS pixel)
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.inner.set_scale_factor(scale_factor);
+    }
+
+    /// Stop painting while the app is in the background
+    pub fn set_background(&mut self, background: bool) {
+        self.inner.set_hidden(background);
+    }
+
+    /// Lifecycle state of the browser
+    pub fn lifecycle(&self) -> BackendLifecycle {
+        self.inner.lifecycle()
+    }
+
     /// Force a capture regardless of dirty state
     ///
     /// Returns Arc-wrapped BGRA pixel data with dimensions (data, width, height).
@@ -413,6 +515,11 @@ impl CefWebview {
     pub fn show_dev_tools(&self) {
         self.inner.show_dev_tools();
     }
+
+    /// Close the browser and wait for it to go away before the app exits
+    pub fn shutdown(&mut self) {
+        self.inner.shutdown();
+    }
 }
 
 /// Dioxus-based native UI renderer
@@ -518,10 +625,14 @@ impl CompositeBackend for OffscreenWebview {
         self.is_ready()
     }
 
+    fn lifecycle(&self) -> BackendLifecycle {
+        OffscreenWebview::lifecycle(self)
+    }
+
     fn capture_if_dirty(&mut self) -> Option<CaptureResult> {
         OffscreenWebview::capture_if_dirty(self).map(|img| {
             let (width, height) = (img.width(), img.height());
-            CaptureResult::Rgba(img.into_raw(), width, height)
+            CaptureResult::Rgba(img.into_raw(), width, height, AlphaMode8::Straight)
         })
     }
 
@@ -533,6 +644,14 @@ impl CompositeBackend for OffscreenWebview {
         self.resize(width, height);
     }
 
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        OffscreenWebview::set_scale_factor(self, scale_factor);
+    }
+
+    fn mark_dirty(&self) {
+        OffscreenWebview::mark_dirty(self);
+    }
+
     fn send_mouse_event(&mut self, event: MouseEvent) {
         self.send_mouse_event(event);
     }
@@ -549,6 +668,10 @@ impl CompositeBackend for OffscreenWebview {
     fn try_recv_from_ui(&mut self) -> Option<UiToBevy> {
         OffscreenWebview::try_recv_from_ui(self)
     }
+
+    fn shutdown(&mut self) {
+        OffscreenWebview::shutdown(self);
+    }
 }
 
 impl CompositeBackend for OverlayWebview {
@@ -560,6 +683,10 @@ impl CompositeBackend for OverlayWebview {
         self.is_ready()
     }
 
+    fn lifecycle(&self) -> BackendLifecycle {
+        OverlayWebview::lifecycle(self)
+    }
+
     fn capture_if_dirty(&mut self) -> Option<CaptureResult> {
         // Overlay mode uses compositor-managed rendering, no capture needed
         Some(CaptureResult::CompositorManaged)
@@ -573,6 +700,18 @@ impl CompositeBackend for OverlayWebview {
         self.resize(width, height);
     }
 
+    fn set_position(&mut self, x: i32, y: i32) {
+        OverlayWebview::set_position(self, x, y);
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        OverlayWebview::set_scale_factor(self, scale_factor);
+    }
+
+    fn set_parent_visible(&mut self, visible: bool) {
+        self.sync_visibility(visible);
+    }
+
     fn send_mouse_event(&mut self, event: MouseEvent) {
         self.send_mouse_event(event);
     }
@@ -588,6 +727,10 @@ impl CompositeBackend for OverlayWebview {
     fn try_recv_from_ui(&mut self) -> Option<UiToBevy> {
         OverlayWebview::try_recv_from_ui(self)
     }
+
+    fn shutdown(&mut self) {
+        OverlayWebview::shutdown(self);
+    }
 }
 
 #[cfg(feature = "cef")]
@@ -600,9 +743,14 @@ impl CompositeBackend for CefWebview {
         self.is_ready()
     }
 
+    fn lifecycle(&self) -> BackendLifecycle {
+        CefWebview::lifecycle(self)
+    }
+
     fn capture_if_dirty(&mut self) -> Option<CaptureResult> {
-        CefWebview::capture_if_dirty(self)
-            .map(|(data, width, height)| CaptureResult::Bgra(data, width, height))
+        CefWebview::capture_if_dirty(self).map(|(data, width, height)| {
+            CaptureResult::Bgra(data, width, height, AlphaMode8::Premultiplied)
+        })
     }
 
     fn size(&self) -> (u32, u32) {
@@ -613,6 +761,14 @@ impl CompositeBackend for CefWebview {
         self.resize(width, height);
     }
 
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        CefWebview::set_scale_factor(self, scale_factor);
+    }
+
+    fn set_background(&mut self, background: bool) {
+        CefWebview::set_background(self, background);
+    }
+
     fn send_mouse_event(&mut self, event: MouseEvent) {
         self.send_mouse_event(event);
     }
@@ -632,4 +788,8 @@ impl CompositeBackend for CefWebview {
     fn show_dev_tools(&self) {
         CefWebview::show_dev_tools(self);
     }
+
+    fn shutdown(&mut self) {
+        CefWebview::shutdown(self);
+    }
 }
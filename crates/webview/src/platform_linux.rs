@@ -1,6 +1,9 @@
 //! Linux-specific webview implementation using GTK and WebKitGTK
 
+use crate::blob_protocol::{allow_blob_fetch, blob_response};
 use crate::error::WebviewError;
+use pentimento_frontend_core::blob::{BLOB_SCHEME, BlobRegistry};
+use pentimento_frontend_core::{BackendLifecycle, parse_ui_messages};
 use pentimento_ipc::{KeyboardEvent, MouseButton, MouseEvent, UiToBevy};
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -27,6 +30,8 @@ pub enum WebviewState {
     Ready,
     /// Resize in progress, waiting for stabilization
     Resizing { frames_remaining: u32 },
+    /// Webview and offscreen window destroyed by `shutdown`
+    ShutDown,
 }
 
 /// Number of frames to wait during warmup before first capture (~1 second at 60fps)
@@ -35,6 +40,9 @@ const WARMUP_FRAMES: u32 = 60;
 /// Number of GTK iterations per poll during warmup/initialization
 const WARMUP_GTK_ITERATIONS: u32 = 20;
 
+/// Maximum GTK iterations to drain after destroying the webview on shutdown
+const SHUTDOWN_GTK_ITERATIONS: u32 = 200;
+
 /// Number of frames to wait after resize before capture (increased for WebKit to process)
 const RESIZE_DEBOUNCE_FRAMES: u32 = 30;
 
@@ -46,6 +54,46 @@ const READY_GTK_ITERATIONS: u32 = 30;
 /// This allows RAF callbacks and WebKit layout/paint to complete
 const MOUSE_EVENT_SETTLE_FRAMES: u32 = 3;
 
+/// Injected into every page so captures follow actual UI changes instead of a
+/// fixed heartbeat. DOM mutations are coalesced to one `UiDirty` per animation
+/// frame, and running CSS transitions/animations (which don't mutate the DOM)
+/// report dirty every frame until they end.
+const DIRTY_NOTIFIER_SCRIPT: &str = r#"(function() {
+    if (window.__PENTIMENTO_DIRTY_NOTIFIER__) return;
+    window.__PENTIMENTO_DIRTY_NOTIFIER__ = true;
+
+    var scheduled = false;
+    var running = 0;
+
+    function notify() {
+        scheduled = false;
+        if (window.ipc) window.ipc.postMessage('{"type":"UiDirty"}');
+        if (running > 0) schedule();
+    }
+    function schedule() {
+        if (!scheduled) {
+            scheduled = true;
+            requestAnimationFrame(notify);
+        }
+    }
+    function started() { running++; schedule(); }
+    function ended() { running = Math.max(0, running - 1); schedule(); }
+
+    new MutationObserver(schedule).observe(document, {
+        childList: true,
+        subtree: true,
+        attributes: true,
+        characterData: true
+    });
+    window.addEventListener('resize', schedule);
+    document.addEventListener('transitionrun', started, true);
+    document.addEventListener('transitionend', ended, true);
+    document.addEventListener('transitioncancel', ended, true);
+    document.addEventListener('animationstart', started, true);
+    document.addEventListener('animationend', ended, true);
+    document.addEventListener('animationcancel', ended, true);
+})();"#;
+
 /// Linux webview implementation using GTK Fixed container
 pub struct LinuxWebview {
     webview: wry::WebView,
@@ -53,7 +101,6 @@ pub struct LinuxWebview {
     #[allow(dead_code)]
     container: gtk::Fixed,
     /// Offscreen window to host the container (needed for widget realization)
-    #[allow(dead_code)]
     offscreen_window: gtk::OffscreenWindow,
     size: (u32, u32),
     dirty: Arc<AtomicBool>,
@@ -78,6 +125,7 @@ impl LinuxWebview {
         size: (u32, u32),
         dirty: Arc<AtomicBool>,
         from_ui_tx: mpsc::UnboundedSender<UiToBevy>,
+        blobs: BlobRegistry,
     ) -> Result<Self, WebviewError> {
         // Initialize GTK if not already done
         if !gtk::is_initialized() {
@@ -102,6 +150,10 @@ impl LinuxWebview {
         // and the webview renders fuzzy. This matches overlay mode which works perfectly.
         let webview = wry::WebViewBuilder::new()
             .with_html(html_content)
+            .with_initialization_script(DIRTY_NOTIFIER_SCRIPT)
+            .with_custom_protocol(BLOB_SCHEME.to_string(), move |_id, request| {
+                blob_response(&blobs, &request)
+            })
             .with_transparent(true)
             .with_bounds(wry::Rect {
                 position: wry::dpi::PhysicalPosition::new(0, 0).into(),
@@ -109,7 +161,7 @@ impl LinuxWebview {
             })
             .with_ipc_handler(move |msg: wry::http::Request<String>| {
                 let body = msg.body();
-                if let Ok(ui_msg) = serde_json::from_str::<UiToBevy>(body) {
+                for ui_msg in parse_ui_messages(body).into_iter().flatten() {
                     // Mark dirty when UI sends UiDirty message
                     if matches!(ui_msg, UiToBevy::UiDirty) {
                         dirty_clone.store(true, Ordering::SeqCst);
@@ -126,6 +178,8 @@ impl LinuxWebview {
             WebviewError::WebviewCreate("Failed to find WebKitWebView in container".into())
         })?;
 
+        allow_blob_fetch(&webkit_webview);
+
         // CRITICAL: Set the webkit webview size to match the intended viewport
         // Without this, the viewport defaults to 200x200 and coordinate mapping breaks
         webkit_webview.set_size_request(size.0 as i32, size.1 as i32);
@@ -203,6 +257,8 @@ impl LinuxWebview {
         let iterations = match self.state {
             WebviewState::Initializing | WebviewState::WarmingUp { .. } => WARMUP_GTK_ITERATIONS,
             WebviewState::Ready | WebviewState::Resizing { .. } => READY_GTK_ITERATIONS,
+            // The widgets are gone; nothing left to pump for
+            WebviewState::ShutDown => return,
         };
 
         // Pump GTK events
@@ -329,7 +385,7 @@ impl LinuxWebview {
                     };
                 }
             }
-            WebviewState::Ready => {
+            WebviewState::Ready | WebviewState::ShutDown => {
                 // Normal operation, no transition needed
             }
         }
@@ -340,6 +396,47 @@ impl LinuxWebview {
         self.state == WebviewState::Ready
     }
 
+    /// Lifecycle state, with the warmup and resize frames still to go
+    pub fn lifecycle(&self) -> BackendLifecycle {
+        match self.state {
+            WebviewState::Initializing => BackendLifecycle::Initializing,
+            WebviewState::WarmingUp { frames_remaining } => BackendLifecycle::Loading {
+                frames_remaining: Some(frames_remaining),
+            },
+            WebviewState::Ready => BackendLifecycle::Ready,
+            WebviewState::Resizing { frames_remaining } => {
+                BackendLifecycle::Resizing { frames_remaining }
+            }
+            WebviewState::ShutDown => BackendLifecycle::Error,
+        }
+    }
+
+    /// Destroy the webview and its offscreen window, then let GTK process the
+    /// resulting events so the web process exits before the app does
+    pub fn shutdown(&mut self) {
+        if self.state == WebviewState::ShutDown {
+            return;
+        }
+        tracing::info!("Shutting down WebKit webview");
+        self.state = WebviewState::ShutDown;
+
+        self.webkit_webview.stop_loading();
+        // SAFETY: the widgets are only used from this thread and every later
+        // call on them is skipped in the `ShutDown` state. wry destroys the
+        // webview again on drop, which GTK ignores for destroyed widgets.
+        unsafe {
+            self.webkit_webview.destroy();
+            self.offscreen_window.destroy();
+        }
+
+        for _ in 0..SHUTDOWN_GTK_ITERATIONS {
+            if !gtk::events_pending() {
+                break;
+            }
+            gtk::main_iteration_do(false);
+        }
+    }
+
     pub fn capture(&mut self) -> Option<image::RgbaImage> {
         // Check if we have a cached snapshot ready
         if let Some(img) = self.snapshot_cache.borrow_mut().take() {
@@ -488,6 +585,9 @@ impl LinuxWebview {
     }
 
     pub fn resize(&mut self, width: u32, height: u32) {
+        if self.state == WebviewState::ShutDown {
+            return;
+        }
         tracing::info!("Webview resize: {:?} -> ({}, {})", self.size, width, height);
 
         self.size = (width, height);
@@ -571,6 +671,9 @@ impl LinuxWebview {
     }
 
     pub fn inject_mouse(&mut self, event: MouseEvent) {
+        if self.state == WebviewState::ShutDown {
+            return;
+        }
         // Log mouse events for debugging coordinate issues
         match &event {
             MouseEvent::ButtonDown { x, y, .. } => {
@@ -920,6 +1023,9 @@ impl LinuxWebview {
     }
 
     pub fn inject_keyboard(&mut self, event: KeyboardEvent) {
+        if self.state == WebviewState::ShutDown {
+            return;
+        }
         // Use JavaScript to dispatch DOM keyboard events
         let event_type = if event.pressed { "keydown" } else { "keyup" };
 
@@ -933,6 +1039,7 @@ impl LinuxWebview {
                     bubbles: true,
                     cancelable: true,
                     key: '{key}',
+                    code: '{code}',
                     shiftKey: {shift},
                     ctrlKey: {ctrl},
                     altKey: {alt},
@@ -948,6 +1055,8 @@ impl LinuxWebview {
             }})()"#,
             event_type = event_type,
             key = key_escaped,
+            // Physical codes are plain identifiers like "KeyA", nothing to escape
+            code = event.code,
             shift = event.modifiers.shift,
             ctrl = event.modifiers.ctrl,
             alt = event.modifiers.alt,
@@ -966,6 +1075,9 @@ impl LinuxWebview {
     }
 
     pub fn eval(&self, js: &str) -> Result<(), WebviewError> {
+        if self.state == WebviewState::ShutDown {
+            return Err(WebviewError::NotReady);
+        }
         self.webview
             .evaluate_script(js)
             .map_err(|e| WebviewError::EvalScript(e.to_string()))
@@ -22,6 +22,9 @@
 //! 2. Implement a RenderHandler that receives paint callbacks
 //! 3. Copy the BGRA pixel buffer to our RGBA framebuffer
 //!
+//! `shutdown` closes the browser and pumps the message loop until its
+//! `OnBeforeClose` arrives (or `SHUTDOWN_TIMEOUT` passes).
+//!
 //! # References
 //!
 //! - CEF C API: https://bitbucket.org/chromiumembedded/cef/wiki/GeneralUsage
@@ -31,22 +34,34 @@ use crate::error::WebviewError;
 use cef::args::Args;
 use cef::rc::Rc as _;
 use cef::{
-    App, Browser, BrowserSettings, CefString, CefStringUtf16, Client, DisplayHandler, ImplApp,
-    ImplBrowser, ImplBrowserHost, ImplClient, ImplDisplayHandler, ImplFrame, ImplRenderHandler,
-    KeyEvent, KeyEventType, LogSeverity, MouseButtonType, PaintElementType, Rect, RenderHandler,
-    Settings, WindowInfo, WrapApp, WrapClient, WrapDisplayHandler, WrapRenderHandler, api_hash,
-    sys, wrap_app, wrap_client, wrap_display_handler, wrap_render_handler,
+    App, Browser, BrowserSettings, Callback, CefString, CefStringUtf16, Client, DisplayHandler,
+    Frame, ImplApp, ImplBrowser, ImplBrowserHost, ImplClient, ImplDisplayHandler, ImplFrame,
+    ImplLifeSpanHandler, ImplRenderHandler, ImplRequest, ImplResourceHandler, ImplResponse,
+    ImplSchemeHandlerFactory, ImplSchemeRegistrar, KeyEvent, KeyEventType, LifeSpanHandler,
+    LogSeverity, MouseButtonType, PaintElementType, Rect, RenderHandler, Request, ResourceHandler,
+    ResourceReadCallback, Response, SchemeHandlerFactory, SchemeRegistrar, ScreenInfo, Settings,
+    WindowInfo, WrapApp, WrapClient, WrapDisplayHandler, WrapLifeSpanHandler, WrapRenderHandler,
+    WrapResourceHandler, WrapSchemeHandlerFactory, api_hash, sys, wrap_app, wrap_client,
+    wrap_display_handler, wrap_life_span_handler, wrap_render_handler, wrap_resource_handler,
+    wrap_scheme_handler_factory,
 };
+use pentimento_frontend_core::blob::{BLOB_SCHEME, BlobRegistry};
+use pentimento_frontend_core::keyboard::{key_character, windows_key_code};
+use pentimento_frontend_core::{BackendLifecycle, parse_ui_messages};
 use pentimento_ipc::{KeyboardEvent, MouseButton, MouseEvent, UiToBevy};
 use std::ffi::c_int;
 use std::mem::size_of;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 /// Global flag indicating whether CEF has been initialized
 static CEF_INITIALIZED: OnceLock<bool> = OnceLock::new();
 
+/// How long `shutdown` pumps the message loop waiting for the browser to close
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// CEF webview state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CefState {
@@ -54,6 +69,8 @@ pub enum CefState {
     Loading,
     /// Ready for capture
     Ready,
+    /// Browser closed by `shutdown`
+    Closed,
 }
 
 /// Shared framebuffer state between RenderHandler and LinuxCefWebview
@@ -65,9 +82,14 @@ pub(crate) struct SharedState {
     /// Dimensions of the framebuffer
     framebuffer_size: Mutex<(u32, u32)>,
     dirty: Arc<AtomicBool>,
+    /// Viewport size in physical pixels
     size: Mutex<(u32, u32)>,
+    /// Device scale factor reported to CEF (`ScreenInfo`)
+    scale_factor: Mutex<f64>,
     /// Channel for sending UI messages to Bevy (for IPC via console messages)
     from_ui_tx: mpsc::UnboundedSender<UiToBevy>,
+    /// Set by `OnBeforeClose`, the last callback for the browser
+    browser_closed: AtomicBool,
 }
 
 /// IPC message prefix used in console.log messages from JavaScript
@@ -101,6 +123,17 @@ impl OsrRenderHandler {
     }
 }
 
+/// View size in DIPs for a physical size at `scale_factor`
+fn view_size(size: (u32, u32), scale_factor: f64) -> (c_int, c_int) {
+    let scale_factor = if scale_factor > 0.0 {
+        scale_factor
+    } else {
+        1.0
+    };
+    let dips = |physical: u32| ((f64::from(physical) / scale_factor).round() as c_int).max(1);
+    (dips(size.0), dips(size.1))
+}
+
 // Macro generates RenderHandlerBuilder which wraps OsrRenderHandler
 wrap_render_handler! {
     pub(crate) struct RenderHandlerBuilder {
@@ -110,14 +143,30 @@ wrap_render_handler! {
     impl RenderHandler {
         fn view_rect(&self, _browser: Option<&mut Browser>, rect: Option<&mut Rect>) {
             if let Some(rect) = rect {
-                let size = self.handler.shared.size.lock().unwrap();
+                // CEF wants the view in DIPs and paints it at `screen_info`'s scale
+                let size = *self.handler.shared.size.lock().unwrap();
+                let scale_factor = *self.handler.shared.scale_factor.lock().unwrap();
+                let (width, height) = view_size(size, scale_factor);
                 rect.x = 0;
                 rect.y = 0;
-                rect.width = size.0 as c_int;
-                rect.height = size.1 as c_int;
+                rect.width = width;
+                rect.height = height;
             }
         }
 
+        fn screen_info(
+            &self,
+            _browser: Option<&mut Browser>,
+            screen_info: Option<&mut ScreenInfo>,
+        ) -> c_int {
+            let Some(screen_info) = screen_info else {
+                return 0;
+            };
+            let scale_factor = *self.handler.shared.scale_factor.lock().unwrap();
+            screen_info.device_scale_factor = scale_factor as f32;
+            1
+        }
+
         fn on_paint(
             &self,
             _browser: Option<&mut Browser>,
@@ -187,19 +236,14 @@ wrap_display_handler! {
             if let Some(msg) = message {
                 let msg_str = msg.to_string();
                 if let Some(json_str) = msg_str.strip_prefix(IPC_PREFIX) {
-                    // Parse the JSON message and send to Bevy
-                    match serde_json::from_str::<UiToBevy>(json_str) {
-                        Ok(ui_msg) => {
-                            // Mark dirty when UI sends UiDirty message
-                            if matches!(ui_msg, UiToBevy::UiDirty) {
-                                self.handler.shared.dirty.store(true, Ordering::SeqCst);
-                            }
-                            let _ = self.handler.shared.from_ui_tx.send(ui_msg);
-                            tracing::trace!("CEF IPC received: {}", json_str);
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to parse CEF IPC message: {} - {}", json_str, e);
+                    // Parse the JSON message (or batch) and send to Bevy
+                    tracing::trace!("CEF IPC received: {}", json_str);
+                    for ui_msg in parse_ui_messages(json_str).into_iter().flatten() {
+                        // Mark dirty when UI sends UiDirty message
+                        if matches!(ui_msg, UiToBevy::UiDirty) {
+                            self.handler.shared.dirty.store(true, Ordering::SeqCst);
                         }
+                        let _ = self.handler.shared.from_ui_tx.send(ui_msg);
                     }
                     // Return 1 to suppress the console message (we handled it)
                     return 1;
@@ -217,11 +261,43 @@ impl DisplayHandlerBuilder {
     }
 }
 
-// Macro generates ClientBuilder which wraps RenderHandler and DisplayHandler
+/// Life span handler reporting when the browser has closed
+#[derive(Clone)]
+pub(crate) struct OsrLifeSpanHandler {
+    shared: Arc<SharedState>,
+}
+
+impl OsrLifeSpanHandler {
+    fn new(shared: Arc<SharedState>) -> Self {
+        Self { shared }
+    }
+}
+
+// Macro generates LifeSpanHandlerBuilder which wraps OsrLifeSpanHandler
+wrap_life_span_handler! {
+    pub(crate) struct LifeSpanHandlerBuilder {
+        handler: OsrLifeSpanHandler,
+    }
+
+    impl LifeSpanHandler {
+        fn on_before_close(&self, _browser: Option<&mut Browser>) {
+            self.handler.shared.browser_closed.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+impl LifeSpanHandlerBuilder {
+    pub fn build(handler: OsrLifeSpanHandler) -> LifeSpanHandler {
+        Self::new(handler)
+    }
+}
+
+// Macro generates ClientBuilder which wraps the render, display, and life span handlers
 wrap_client! {
     pub(crate) struct ClientBuilder {
         render_handler: RenderHandler,
         display_handler: DisplayHandler,
+        life_span_handler: LifeSpanHandler,
     }
 
     impl Client {
@@ -232,6 +308,10 @@ wrap_client! {
         fn display_handler(&self) -> Option<cef::DisplayHandler> {
             Some(self.display_handler.clone())
         }
+
+        fn life_span_handler(&self) -> Option<cef::LifeSpanHandler> {
+            Some(self.life_span_handler.clone())
+        }
     }
 }
 
@@ -246,8 +326,10 @@ impl ClientBuilder {
     pub(crate) fn build(shared: Arc<SharedState>) -> Client {
         let render_handler =
             RenderHandlerBuilder::build(OsrRenderHandler::new(Arc::clone(&shared)));
-        let display_handler = DisplayHandlerBuilder::build(OsrDisplayHandler::new(shared));
-        Self::new(render_handler, display_handler)
+        let display_handler =
+            DisplayHandlerBuilder::build(OsrDisplayHandler::new(Arc::clone(&shared)));
+        let life_span_handler = LifeSpanHandlerBuilder::build(OsrLifeSpanHandler::new(shared));
+        Self::new(render_handler, display_handler, life_span_handler)
     }
 }
 
@@ -262,7 +344,11 @@ wrap_app! {
     }
 
     impl App {
-        // We don't need any custom app behavior for OSR
+        fn on_register_custom_schemes(&self, registrar: Option<&mut SchemeRegistrar>) {
+            if let Some(registrar) = registrar {
+                register_blob_scheme(registrar);
+            }
+        }
     }
 }
 
@@ -272,6 +358,142 @@ impl AppBuilder {
     }
 }
 
+/// Declare `pentimento-blob://` so pages may `fetch()` it
+///
+/// Every CEF process must register the same schemes; the helper does the
+/// same for the render processes.
+fn register_blob_scheme(registrar: &mut SchemeRegistrar) {
+    let options = sys::cef_scheme_options_t::CEF_SCHEME_OPTION_STANDARD as c_int
+        | sys::cef_scheme_options_t::CEF_SCHEME_OPTION_CORS_ENABLED as c_int
+        | sys::cef_scheme_options_t::CEF_SCHEME_OPTION_FETCH_ENABLED as c_int;
+    registrar.add_custom_scheme(Some(&CefString::from(BLOB_SCHEME)), options);
+}
+
+/// Serves one blob to the page
+#[derive(Clone)]
+pub(crate) struct BlobResourceHandler {
+    /// `None` answers 404 (unknown or expired ID)
+    bytes: Option<Arc<[u8]>>,
+    /// Bytes already handed to CEF
+    offset: Arc<AtomicUsize>,
+}
+
+// Macro generates BlobResourceHandlerBuilder which wraps BlobResourceHandler
+wrap_resource_handler! {
+    pub(crate) struct BlobResourceHandlerBuilder {
+        handler: BlobResourceHandler,
+    }
+
+    impl ResourceHandler {
+        fn open(
+            &self,
+            _request: Option<&mut Request>,
+            handle_request: Option<&mut c_int>,
+            _callback: Option<&mut Callback>,
+        ) -> c_int {
+            // The bytes are already in memory, so the request is handled right away
+            if let Some(handle_request) = handle_request {
+                *handle_request = 1;
+            }
+            1
+        }
+
+        fn response_headers(
+            &self,
+            response: Option<&mut Response>,
+            response_length: Option<&mut i64>,
+            _redirect_url: Option<&mut CefString>,
+        ) {
+            let Some(response) = response else { return };
+            let (status, length) = match &self.handler.bytes {
+                Some(bytes) => (200, bytes.len() as i64),
+                None => (404, 0),
+            };
+            response.set_status(status);
+            response.set_mime_type(Some(&CefString::from("application/octet-stream")));
+            response.set_header_by_name(
+                Some(&CefString::from("Access-Control-Allow-Origin")),
+                Some(&CefString::from("*")),
+                1,
+            );
+            if let Some(response_length) = response_length {
+                *response_length = length;
+            }
+        }
+
+        fn read(
+            &self,
+            data_out: *mut u8,
+            bytes_to_read: c_int,
+            bytes_read: Option<&mut c_int>,
+            _callback: Option<&mut ResourceReadCallback>,
+        ) -> c_int {
+            let Some(bytes_read) = bytes_read else { return 0 };
+            *bytes_read = 0;
+            let Some(bytes) = &self.handler.bytes else { return 0 };
+
+            let offset = self.handler.offset.load(Ordering::SeqCst);
+            let count = (bytes.len() - offset).min(bytes_to_read.max(0) as usize);
+            if count == 0 || data_out.is_null() {
+                return 0; // Done
+            }
+
+            // Safety: CEF guarantees data_out holds at least bytes_to_read bytes
+            unsafe {
+                std::ptr::copy_nonoverlapping(bytes[offset..].as_ptr(), data_out, count);
+            }
+            self.handler.offset.store(offset + count, Ordering::SeqCst);
+            *bytes_read = count as c_int;
+            1
+        }
+    }
+}
+
+impl BlobResourceHandlerBuilder {
+    pub fn build(handler: BlobResourceHandler) -> ResourceHandler {
+        Self::new(handler)
+    }
+}
+
+/// Looks up the blob a `pentimento-blob://<id>` request asks for
+#[derive(Clone)]
+pub(crate) struct BlobSchemeHandlerFactory {
+    blobs: BlobRegistry,
+}
+
+// Macro generates BlobSchemeHandlerFactoryBuilder which wraps BlobSchemeHandlerFactory
+wrap_scheme_handler_factory! {
+    pub(crate) struct BlobSchemeHandlerFactoryBuilder {
+        factory: BlobSchemeHandlerFactory,
+    }
+
+    impl SchemeHandlerFactory {
+        fn create(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            _scheme_name: Option<&CefString>,
+            request: Option<&mut Request>,
+        ) -> Option<ResourceHandler> {
+            let url = CefString::from(&request?.url()).to_string();
+            let bytes = self.factory.blobs.get_url(&url);
+            if bytes.is_none() {
+                tracing::warn!("Unknown or expired blob requested: {}", url);
+            }
+            Some(BlobResourceHandlerBuilder::build(BlobResourceHandler {
+                bytes,
+                offset: Arc::new(AtomicUsize::new(0)),
+            }))
+        }
+    }
+}
+
+impl BlobSchemeHandlerFactoryBuilder {
+    pub fn build(factory: BlobSchemeHandlerFactory) -> SchemeHandlerFactory {
+        Self::new(factory)
+    }
+}
+
 /// Find the CEF helper binary path
 ///
 /// The helper binary should be in the same directory as the main executable,
@@ -394,22 +616,35 @@ impl LinuxCefWebview {
     /// * `size` - Initial viewport size
     /// * `dirty` - Shared flag for dirty tracking
     /// * `from_ui_tx` - Channel for UI -> Bevy messages
+    /// * `blobs` - Blobs served at `pentimento-blob://<id>`
     pub fn new(
         html_content: &str,
         size: (u32, u32),
         dirty: Arc<AtomicBool>,
         from_ui_tx: mpsc::UnboundedSender<UiToBevy>,
+        blobs: BlobRegistry,
     ) -> Result<Self, WebviewError> {
         // Initialize CEF if not already done
         ensure_cef_initialized()?;
 
+        // Registering again replaces the factory of an earlier webview
+        let mut blob_factory =
+            BlobSchemeHandlerFactoryBuilder::build(BlobSchemeHandlerFactory { blobs });
+        cef::register_scheme_handler_factory(
+            Some(&CefString::from(BLOB_SCHEME)),
+            None,
+            Some(&mut blob_factory),
+        );
+
         // Create shared state for communication between handlers and this struct
         let shared = Arc::new(SharedState {
             framebuffer: Mutex::new(None),
             framebuffer_size: Mutex::new((0, 0)),
             dirty,
             size: Mutex::new(size),
+            scale_factor: Mutex::new(1.0),
             from_ui_tx: from_ui_tx.clone(),
+            browser_closed: AtomicBool::new(false),
         });
 
         // Create the client with render handler and display handler (for IPC)
@@ -463,6 +698,10 @@ impl LinuxCefWebview {
     ///
     /// Must be called each frame to process CEF events
     pub fn poll(&mut self) {
+        if self.state == CefState::Closed {
+            return;
+        }
+
         // Process CEF message loop work
         cef::do_message_loop_work();
 
@@ -514,6 +753,42 @@ impl LinuxCefWebview {
         self.state == CefState::Ready
     }
 
+    /// Lifecycle state (CEF doesn't report how far the page load is)
+    pub fn lifecycle(&self) -> BackendLifecycle {
+        match self.state {
+            CefState::Loading => BackendLifecycle::Loading {
+                frames_remaining: None,
+            },
+            CefState::Ready => BackendLifecycle::Ready,
+            CefState::Closed => BackendLifecycle::Error,
+        }
+    }
+
+    /// Close the browser and pump the message loop until it has closed
+    pub fn shutdown(&mut self) {
+        let Some(browser) = self.browser.take() else {
+            return;
+        };
+        tracing::info!("Closing CEF browser for shutdown");
+        self.state = CefState::Closed;
+        if let Some(host) = browser.host() {
+            host.close_browser(1); // force_close = true (as c_int)
+        }
+
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while !self.shared.browser_closed.load(Ordering::SeqCst) {
+            if Instant::now() >= deadline {
+                tracing::warn!(
+                    "CEF browser did not close within {:?}, exiting anyway",
+                    SHUTDOWN_TIMEOUT
+                );
+                break;
+            }
+            cef::do_message_loop_work();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
     /// Capture the current framebuffer as raw BGRA bytes
     ///
     /// Returns an Arc-wrapped BGRA pixel buffer along with dimensions (width, height).
@@ -544,6 +819,37 @@ impl LinuxCefWebview {
         }
     }
 
+    /// Set the device scale factor, e.g. after the window moved to another monitor
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        {
+            let mut current = self.shared.scale_factor.lock().unwrap();
+            if scale_factor <= 0.0 || (*current - scale_factor).abs() <= f64::EPSILON {
+                return;
+            }
+            *current = scale_factor;
+        }
+        tracing::info!("CEF device scale factor changed to {:.2}", scale_factor);
+
+        // CEF re-reads `ScreenInfo` and, with the new scale, the view size in DIPs
+        if let Some(browser) = &self.browser {
+            if let Some(host) = browser.host() {
+                host.notify_screen_info_changed();
+                host.was_resized();
+            }
+        }
+    }
+
+    /// Stop or resume painting, e.g. while the app is in the background
+    ///
+    /// CEF repaints the whole view when shown again.
+    pub fn set_hidden(&mut self, hidden: bool) {
+        if let Some(browser) = &self.browser {
+            if let Some(host) = browser.host() {
+                host.was_hidden(hidden as c_int);
+            }
+        }
+    }
+
     /// Inject a mouse event into the webview
     pub fn inject_mouse(&mut self, event: MouseEvent) {
         let Some(browser) = &self.browser else { return };
@@ -605,23 +911,14 @@ impl LinuxCefWebview {
         let Some(browser) = &self.browser else { return };
         let Some(host) = browser.host() else { return };
 
-        // Get the character from the key string
-        let char_code = event.key.chars().next().unwrap_or('\0');
+        // Virtual key code from the physical key, so it doesn't depend on the layout
+        let vk_code = windows_key_code(&event.code) as c_int;
 
-        // For Windows virtual key codes, letter keys must use uppercase (VK_KEY_A = 65, not 97)
-        // This is how Windows virtual key codes work - they're always uppercase for letters
-        let vk_code = if char_code.is_ascii_lowercase() {
-            char_code.to_ascii_uppercase() as c_int
-        } else {
-            char_code as c_int
-        };
-
-        // The actual character that would be typed (depends on shift state)
-        let typed_char = if event.modifiers.shift && char_code.is_ascii_lowercase() {
-            char_code.to_ascii_uppercase()
-        } else {
-            char_code
-        };
+        // The logical key is already layout- and shift-aware ("a" or "A" on the
+        // same key), and is None for named keys and dead keys
+        let character = key_character(&event.key);
+        let typed_char = character.map_or(0, |c| c as u16);
+        let unmodified_char = character.map_or(0, |c| c.to_lowercase().next().unwrap_or(c) as u16);
 
         // Build modifiers from the event
         let mut modifiers: u32 = 0;
@@ -646,14 +943,15 @@ impl LinuxCefWebview {
             windows_key_code: vk_code,
             native_key_code: 0, // Platform-specific, not needed for basic input
             is_system_key: 0,
-            character: typed_char as u16,
-            unmodified_character: char_code as u16,
+            character: typed_char,
+            unmodified_character: unmodified_char,
             focus_on_editable_field: 0,
         };
         host.send_key_event(Some(&key_event));
 
-        // Also send char event for key presses (for text input)
-        if event.pressed && !event.key.is_empty() && char_code != '\0' {
+        // Also send char event for key presses (for text input). Dead keys
+        // only type once composed, which is left to IME handling.
+        if event.pressed && character.is_some() {
             let char_event = KeyEvent {
                 size: size_of::<KeyEvent>(),
                 type_: KeyEventType::CHAR,
@@ -661,8 +959,8 @@ impl LinuxCefWebview {
                 windows_key_code: vk_code,
                 native_key_code: 0,
                 is_system_key: 0,
-                character: typed_char as u16,
-                unmodified_character: char_code as u16,
+                character: typed_char,
+                unmodified_character: unmodified_char,
                 focus_on_editable_field: 0,
             };
             host.send_key_event(Some(&char_event));
@@ -8,7 +8,10 @@
 //! - The 3D viewport area is click-through, passing events to Bevy underneath
 //! This allows both the UI and 3D scene to receive input appropriately.
 
+use crate::blob_protocol::{allow_blob_fetch, blob_response};
 use crate::error::WebviewError;
+use pentimento_frontend_core::blob::{BLOB_SCHEME, BlobRegistry};
+use pentimento_frontend_core::{BackendLifecycle, parse_ui_messages};
 use pentimento_ipc::{KeyboardEvent, MouseButton, MouseEvent, UiToBevy};
 use raw_window_handle::RawWindowHandle;
 use std::cell::RefCell;
@@ -30,6 +33,8 @@ pub enum OverlayState {
     Initializing,
     /// Content loaded, ready for use
     Ready,
+    /// Overlay window destroyed by `shutdown`
+    ShutDown,
 }
 
 /// Linux overlay webview using transparent GTK window
@@ -43,6 +48,10 @@ pub struct LinuxOverlayWebview {
     load_finished: Rc<RefCell<bool>>,
     /// Parent window XID for state tracking (X11 only)
     parent_xid: Option<u64>,
+    /// Device scale factor (physical pixels per CSS pixel)
+    scale_factor: f64,
+    /// The scale factor changed since the last `resize`
+    rescale_pending: bool,
 }
 
 impl LinuxOverlayWebview {
@@ -51,6 +60,7 @@ impl LinuxOverlayWebview {
         html_content: &str,
         size: (u32, u32),
         from_ui_tx: mpsc::UnboundedSender<UiToBevy>,
+        blobs: BlobRegistry,
     ) -> Result<Self, WebviewError> {
         // Initialize GTK if not already done
         if !gtk::is_initialized() {
@@ -108,6 +118,9 @@ impl LinuxOverlayWebview {
         let load_finished_clone = load_finished.clone();
         let webview = wry::WebViewBuilder::new()
             .with_html(html_content)
+            .with_custom_protocol(BLOB_SCHEME.to_string(), move |_id, request| {
+                blob_response(&blobs, &request)
+            })
             .with_transparent(true)
             .with_bounds(wry::Rect {
                 position: wry::dpi::PhysicalPosition::new(0, 0).into(),
@@ -115,7 +128,7 @@ impl LinuxOverlayWebview {
             })
             .with_ipc_handler(move |msg: wry::http::Request<String>| {
                 let body = msg.body();
-                if let Ok(ui_msg) = serde_json::from_str::<UiToBevy>(body) {
+                for ui_msg in parse_ui_messages(body).into_iter().flatten() {
                     let _ = from_ui_tx.send(ui_msg);
                 }
             })
@@ -127,6 +140,8 @@ impl LinuxOverlayWebview {
             WebviewError::WebviewCreate("Failed to find WebKitWebView in container".into())
         })?;
 
+        allow_blob_fetch(&webkit_webview);
+
         // Set the webkit_webview size to match the container
         webkit_webview.set_size_request(size.0 as i32, size.1 as i32);
 
@@ -140,7 +155,7 @@ impl LinuxOverlayWebview {
         });
 
         // Position the overlay window based on parent handle and set up window grouping
-        let parent_xid = Self::setup_window_relationship(&window, parent_handle, size);
+        let parent_xid = Self::setup_window_relationship(&window, parent_handle, size)?;
 
         // Show the window
         window.show_all();
@@ -166,6 +181,8 @@ impl LinuxOverlayWebview {
             state: OverlayState::Initializing,
             load_finished,
             parent_xid,
+            scale_factor: 1.0,
+            rescale_pending: false,
         })
     }
 
@@ -175,7 +192,7 @@ impl LinuxOverlayWebview {
         window: &gtk::Window,
         parent_handle: RawWindowHandle,
         _size: (u32, u32),
-    ) -> Option<u64> {
+    ) -> Result<Option<u64>, WebviewError> {
         let mut parent_xid: Option<u64> = None;
 
         // Try to get parent window position based on handle type
@@ -199,17 +216,19 @@ impl LinuxOverlayWebview {
                 Self::set_transient_for_x11(window, xid);
             }
             RawWindowHandle::Wayland(_handle) => {
-                // Wayland positioning is more complex due to security model
-                // The compositor controls window positioning
-                // Transient relationships work differently on Wayland
-                tracing::info!("Wayland parent window - positioning controlled by compositor");
+                // A toplevel can neither be positioned over nor parented to a
+                // Wayland window from another client connection, so it would
+                // float independently of Bevy; callers fall back to capture mode
+                return Err(WebviewError::WaylandUnsupported(
+                    "the overlay window cannot follow a Wayland parent".into(),
+                ));
             }
             _ => {
                 tracing::warn!("Unknown window handle type for parent positioning");
             }
         }
 
-        parent_xid
+        Ok(parent_xid)
     }
 
     /// Set the overlay window as transient for the parent X11 window
@@ -313,6 +332,10 @@ impl LinuxOverlayWebview {
     }
 
     pub fn poll(&mut self) {
+        if self.state == OverlayState::ShutDown {
+            return;
+        }
+
         // Pump GTK events
         for _ in 0..10 {
             if gtk::events_pending() {
@@ -326,7 +349,9 @@ impl LinuxOverlayWebview {
         // Update state
         if self.state == OverlayState::Initializing && *self.load_finished.borrow() {
             // Set viewport dimensions via JavaScript to ensure WebKit knows the size
-            let (width, height) = self.size;
+            let geometry =
+                OverlayGeometry::new(self.size, self.scale_factor, self.window.scale_factor());
+            let (width, height) = geometry.css_size;
             self.webkit_webview.evaluate_javascript(
                 &format!(
                     "document.body.style.width = '{}px'; \
@@ -388,6 +413,9 @@ impl LinuxOverlayWebview {
     /// Sync overlay visibility with the given parent window visibility state
     /// Called externally when the parent window state changes
     pub fn sync_visibility(&mut self, parent_visible: bool) {
+        if self.state == OverlayState::ShutDown {
+            return;
+        }
         let currently_visible = self.window.is_visible();
         if !parent_visible && currently_visible {
             self.window.hide();
@@ -402,18 +430,63 @@ impl LinuxOverlayWebview {
         self.state == OverlayState::Ready
     }
 
+    /// Lifecycle state (the overlay resizes in place, without settling)
+    pub fn lifecycle(&self) -> BackendLifecycle {
+        match self.state {
+            OverlayState::Initializing => BackendLifecycle::Initializing,
+            OverlayState::Ready => BackendLifecycle::Ready,
+            OverlayState::ShutDown => BackendLifecycle::Error,
+        }
+    }
+
+    /// Destroy the overlay window (and the webview in it), then let GTK
+    /// process the resulting events so the window is gone before the app exits
+    pub fn shutdown(&mut self) {
+        if self.state == OverlayState::ShutDown {
+            return;
+        }
+        tracing::info!("Shutting down overlay webview");
+        self.state = OverlayState::ShutDown;
+
+        self.webkit_webview.stop_loading();
+        self.window.hide();
+        // SAFETY: the window is only used from this thread and every later
+        // call on it is skipped in the `ShutDown` state. wry destroys the
+        // webview again on drop, which GTK ignores for destroyed widgets.
+        unsafe {
+            self.window.destroy();
+        }
+
+        for _ in 0..200 {
+            if !gtk::events_pending() {
+                break;
+            }
+            gtk::main_iteration_do(false);
+        }
+    }
+
     pub fn resize(&mut self, width: u32, height: u32) {
-        if self.size == (width, height) {
+        if self.state == OverlayState::ShutDown
+            || (self.size == (width, height) && !self.rescale_pending)
+        {
             return;
         }
 
         self.size = (width, height);
-
-        // Resize all components: window, container, webkit_webview, and wry webview bounds
-        self.window.resize(width as i32, height as i32);
-        self.container.set_size_request(width as i32, height as i32);
+        self.rescale_pending = false;
+        let geometry =
+            OverlayGeometry::new(self.size, self.scale_factor, self.window.scale_factor());
+        let (gtk_width, gtk_height) = geometry.size;
+        let (css_width, css_height) = geometry.css_size;
+
+        // Resize all components: window, container, webkit_webview, and wry webview bounds.
+        // GTK sizes are in GTK units; the zoom covers the part of the device scale GTK doesn't
+        self.window.resize(gtk_width as i32, gtk_height as i32);
+        self.container
+            .set_size_request(gtk_width as i32, gtk_height as i32);
         self.webkit_webview
-            .set_size_request(width as i32, height as i32);
+            .set_size_request(gtk_width as i32, gtk_height as i32);
+        self.webkit_webview.set_zoom_level(geometry.zoom);
 
         // Update wry webview bounds (critical for Linux with gtk::Fixed)
         // Use PhysicalSize since we receive physical pixels from Bevy
@@ -432,7 +505,7 @@ impl LinuxOverlayWebview {
                  document.body.style.height = '{}px'; \
                  document.documentElement.style.width = '{}px'; \
                  document.documentElement.style.height = '{}px';",
-                width, height, width, height
+                css_width, css_height, css_width, css_height
             ),
             None,
             None,
@@ -441,7 +514,7 @@ impl LinuxOverlayWebview {
         );
 
         // Update input regions for the new size
-        Self::update_input_regions(&self.window, width, height);
+        Self::update_input_regions(&self.window, gtk_width, gtk_height);
 
         // Pump GTK events to help the resize propagate
         for _ in 0..30 {
@@ -454,10 +527,24 @@ impl LinuxOverlayWebview {
     }
 
     pub fn set_position(&mut self, x: i32, y: i32) {
-        self.window.move_(x, y);
+        if self.state != OverlayState::ShutDown {
+            self.window.move_(x, y);
+        }
+    }
+
+    /// Set the device scale factor; the next `resize` applies it
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        if scale_factor > 0.0 && (scale_factor - self.scale_factor).abs() > f64::EPSILON {
+            self.scale_factor = scale_factor;
+            // Re-apply the GTK size and zoom even if the physical size is unchanged
+            self.rescale_pending = true;
+        }
     }
 
     pub fn set_visible(&mut self, visible: bool) {
+        if self.state == OverlayState::ShutDown {
+            return;
+        }
         if visible {
             self.window.show();
         } else {
@@ -466,8 +553,13 @@ impl LinuxOverlayWebview {
     }
 
     pub fn inject_mouse(&mut self, event: MouseEvent) {
+        if self.state == OverlayState::ShutDown {
+            return;
+        }
         // For overlay mode with click-through, we inject synthetic events via JavaScript.
         // Note: Synthetic events may not trigger all browser behaviors (e.g., :active styles).
+        // Coordinates arrive in physical pixels; the page wants CSS pixels.
+        let event = css_mouse_event(event, self.scale_factor);
         let js = match event {
             MouseEvent::Move { x, y } => {
                 format!(
@@ -554,6 +646,9 @@ impl LinuxOverlayWebview {
     }
 
     pub fn inject_keyboard(&mut self, event: KeyboardEvent) {
+        if self.state == OverlayState::ShutDown {
+            return;
+        }
         let event_type = if event.pressed { "keydown" } else { "keyup" };
         let key_escaped = event.key.replace('\\', "\\\\").replace('\'', "\\'");
 
@@ -563,6 +658,7 @@ impl LinuxOverlayWebview {
                 target.dispatchEvent(new KeyboardEvent('{event_type}', {{
                     bubbles: true, cancelable: true,
                     key: '{key}',
+                    code: '{code}',
                     shiftKey: {shift}, ctrlKey: {ctrl},
                     altKey: {alt}, metaKey: {meta},
                     view: window
@@ -570,6 +666,8 @@ impl LinuxOverlayWebview {
             }})()"#,
             event_type = event_type,
             key = key_escaped,
+            // Physical codes are plain identifiers like "KeyA", nothing to escape
+            code = event.code,
             shift = event.modifiers.shift,
             ctrl = event.modifiers.ctrl,
             alt = event.modifiers.alt,
@@ -580,8 +678,81 @@ impl LinuxOverlayWebview {
     }
 
     pub fn eval(&self, js: &str) -> Result<(), WebviewError> {
+        if self.state == OverlayState::ShutDown {
+            return Err(WebviewError::NotReady);
+        }
         self.webview
             .evaluate_script(js)
             .map_err(|e| WebviewError::EvalScript(e.to_string()))
     }
 }
+
+/// How the overlay's physical size maps onto GTK and WebKit
+///
+/// GTK sizes windows in units of its whole-number monitor scale; WebKit's zoom
+/// makes up the rest of a fractional device scale, so the page always lays out
+/// in the same CSS pixels as the Bevy window's logical size.
+struct OverlayGeometry {
+    /// Window size in GTK units
+    size: (u32, u32),
+    /// Page size in CSS pixels
+    css_size: (u32, u32),
+    /// WebKit zoom level, GTK units per CSS pixel
+    zoom: f64,
+}
+
+impl OverlayGeometry {
+    fn new(physical: (u32, u32), scale_factor: f64, gtk_scale: i32) -> Self {
+        let scale_factor = if scale_factor > 0.0 {
+            scale_factor
+        } else {
+            1.0
+        };
+        let gtk_scale = f64::from(gtk_scale.max(1));
+        let scaled = |pixels: u32, scale: f64| ((f64::from(pixels) / scale).round() as u32).max(1);
+        Self {
+            size: (scaled(physical.0, gtk_scale), scaled(physical.1, gtk_scale)),
+            css_size: (
+                scaled(physical.0, scale_factor),
+                scaled(physical.1, scale_factor),
+            ),
+            zoom: scale_factor / gtk_scale,
+        }
+    }
+}
+
+/// A mouse event in physical pixels moved to CSS pixels at `scale_factor`
+fn css_mouse_event(event: MouseEvent, scale_factor: f64) -> MouseEvent {
+    let scale = if scale_factor > 0.0 {
+        scale_factor as f32
+    } else {
+        1.0
+    };
+    match event {
+        MouseEvent::Move { x, y } => MouseEvent::Move {
+            x: x / scale,
+            y: y / scale,
+        },
+        MouseEvent::ButtonDown { button, x, y } => MouseEvent::ButtonDown {
+            button,
+            x: x / scale,
+            y: y / scale,
+        },
+        MouseEvent::ButtonUp { button, x, y } => MouseEvent::ButtonUp {
+            button,
+            x: x / scale,
+            y: y / scale,
+        },
+        MouseEvent::Scroll {
+            delta_x,
+            delta_y,
+            x,
+            y,
+        } => MouseEvent::Scroll {
+            delta_x,
+            delta_y,
+            x: x / scale,
+            y: y / scale,
+        },
+    }
+}
@@ -51,6 +51,11 @@ impl WindowsWebview {
         // TODO: Use SendInput or WebView2 input injection
     }
 
+    pub fn shutdown(&mut self) {
+        // No WebView2 controller or window is created yet, so there is nothing to close
+        tracing::warn!("Windows webview shutdown is not supported yet");
+    }
+
     pub fn eval(&self, _js: &str) -> Result<(), WebviewError> {
         // TODO: Implement script evaluation
         Err(WebviewError::PlatformNotSupported)
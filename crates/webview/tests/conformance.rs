@@ -0,0 +1,90 @@
+//! Backend conformance runs against real webviews
+//!
+//! These need a display (and CEF binaries for the CEF run), so they are
+//! ignored by default. Run one at a time, since GTK and CEF are initialized
+//! once per process:
+//!
+//! ```sh
+//! cargo test -p pentimento-webview --test conformance -- --ignored --exact webkit
+//! cargo test -p pentimento-webview --test conformance -- --ignored webkit_colors
+//! cargo test -p pentimento-webview --features cef --test conformance -- --ignored --exact cef
+//! cargo test -p pentimento-webview --features cef --test conformance -- --ignored cef_colors
+//! ```
+//!
+//! Overlay is not covered: it needs a parent window to attach to.
+
+use pentimento_frontend_core::blob::BlobRegistry;
+use pentimento_frontend_core::conformance::{
+    BackendCaps, COLOR_PATTERN_HTML, TEST_PAGE_HTML, TEST_PAGE_SIZE, run_color_check,
+    run_conformance,
+};
+
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore = "needs a display; run with --ignored"]
+fn webkit() {
+    gtk::init().expect("Failed to initialize GTK");
+    run_conformance(
+        || {
+            pentimento_webview::OffscreenWebview::new(
+                TEST_PAGE_HTML,
+                TEST_PAGE_SIZE,
+                BlobRegistry::default(),
+            )
+            .expect("Failed to create webview")
+        },
+        BackendCaps::default(),
+    );
+}
+
+#[cfg(feature = "cef")]
+#[test]
+#[ignore = "needs a display and CEF binaries; run with --ignored"]
+fn cef() {
+    run_conformance(
+        || {
+            pentimento_webview::CefWebview::new(
+                TEST_PAGE_HTML,
+                TEST_PAGE_SIZE,
+                BlobRegistry::default(),
+            )
+            .expect("Failed to create CEF webview")
+        },
+        BackendCaps::default(),
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore = "needs a display; run with --ignored"]
+fn webkit_colors() {
+    gtk::init().expect("Failed to initialize GTK");
+    run_color_check(
+        || {
+            pentimento_webview::OffscreenWebview::new(
+                COLOR_PATTERN_HTML,
+                TEST_PAGE_SIZE,
+                BlobRegistry::default(),
+            )
+            .expect("Failed to create webview")
+        },
+        BackendCaps::default(),
+    );
+}
+
+#[cfg(feature = "cef")]
+#[test]
+#[ignore = "needs a display and CEF binaries; run with --ignored"]
+fn cef_colors() {
+    run_color_check(
+        || {
+            pentimento_webview::CefWebview::new(
+                COLOR_PATTERN_HTML,
+                TEST_PAGE_SIZE,
+                BlobRegistry::default(),
+            )
+            .expect("Failed to create CEF webview")
+        },
+        BackendCaps::default(),
+    );
+}
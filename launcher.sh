@@ -271,7 +271,8 @@ setup_managed_state_env() {
     fi
 
     local state_dir="${LAUNCHER_STATE_ROOT}/${scope}"
-    mkdir -p "${state_dir}/xdg-state" "${state_dir}/xdg-data" "${state_dir}/xdg-cache"
+    mkdir -p "${state_dir}/xdg-state" "${state_dir}/xdg-data" "${state_dir}/xdg-cache" "${state_dir}/xdg-config"
+    export XDG_CONFIG_HOME="${state_dir}/xdg-config"
     export XDG_STATE_HOME="${state_dir}/xdg-state"
     export XDG_DATA_HOME="${state_dir}/xdg-data"
     export XDG_CACHE_HOME="${state_dir}/xdg-cache"
@@ -1,6 +1,7 @@
 import test from 'node:test';
 import assert from 'node:assert/strict';
 import { spawnSync } from 'node:child_process';
+import { readFileSync } from 'node:fs';
 
 function loadSamples() {
   const result = spawnSync(
@@ -32,6 +33,67 @@ function assertLayerInfo(layer) {
   assert.equal(typeof layer.is_active, 'boolean');
 }
 
+function assertAnnotationInfo(annotation) {
+  assert.equal(typeof annotation.id, 'string');
+  assert.equal(typeof annotation.name, 'string');
+  assertTuple(annotation.start, 3, 'AnnotationInfo.start');
+  assertTuple(annotation.end, 3, 'AnnotationInfo.end');
+  assert.equal(typeof annotation.distance, 'number');
+}
+
+function assertLightInfo(light) {
+  assert.equal(typeof light.id, 'string');
+  assert.equal(typeof light.name, 'string');
+  if (typeof light.light_type === 'string') {
+    assert.equal(light.light_type, 'Directional');
+  } else if ('Point' in light.light_type) {
+    assert.equal(typeof light.light_type.Point.range, 'number');
+  } else {
+    assert.equal(typeof light.light_type.Spot.range, 'number');
+    assert.equal(typeof light.light_type.Spot.inner_angle, 'number');
+    assert.equal(typeof light.light_type.Spot.outer_angle, 'number');
+  }
+  assertTuple(light.color, 3, 'LightInfo.color');
+  assert.equal(typeof light.intensity, 'number');
+  assertTuple(light.transform.position, 3, 'LightInfo.transform.position');
+  assert.equal(typeof light.shadows, 'boolean');
+}
+
+function assertSceneObject(object) {
+  assert.equal(typeof object.id, 'string');
+  assert.equal(typeof object.name, 'string');
+  assertTuple(object.transform.position, 3, 'SceneObject.transform.position');
+  assert.equal(typeof object.visible, 'boolean');
+  assert.equal(typeof object.locked, 'boolean');
+  assert.ok(object.parent_id === null || typeof object.parent_id === 'string');
+  assert.ok(Number.isInteger(object.subdivision_levels));
+  assert.equal(typeof object.instanced, 'boolean');
+  if (object.wireframe !== null) {
+    assertWireframeInfo(object.wireframe);
+  }
+}
+
+function assertWireframeInfo(wireframe) {
+  assert.equal(typeof wireframe.enabled, 'boolean');
+  assertTuple(wireframe.color, 4, 'WireframeInfo.color');
+  assert.equal(typeof wireframe.depth_test, 'boolean');
+}
+
+function assertSnapTarget(snap) {
+  if (snap === 'None') {
+    return;
+  }
+  if ('Grid' in snap) {
+    assert.ok(snap.Grid.size === null || typeof snap.Grid.size === 'number');
+  } else {
+    assert.equal(typeof snap.Surface.align_to_normal, 'boolean');
+  }
+}
+
+function assertPivotPoint(pivot) {
+  assert.match(pivot, /^(Median|IndividualOrigins|ActiveObject|Cursor3D)$/);
+}
+
 function assertBevyToUiMessage(message) {
   assert.equal(typeof message.type, 'string');
 
@@ -39,7 +101,35 @@ function assertBevyToUiMessage(message) {
     case 'Initialize':
       assert.ok(message.data);
       assert.ok(Array.isArray(message.data.scene_info.objects));
+      message.data.scene_info.objects.forEach(assertSceneObject);
+      assert.ok(Array.isArray(message.data.scene_info.lights));
+      message.data.scene_info.lights.forEach(assertLightInfo);
       assert.equal(typeof message.data.settings.render_scale, 'number');
+      assert.equal(typeof message.data.settings.ui_render_scale, 'number');
+      assert.equal(typeof message.data.settings.auto_performance, 'boolean');
+      assert.equal(typeof message.data.settings.navigation.dead_zone, 'number');
+      assert.ok(['Left', 'Right'].includes(message.data.settings.navigation.orbit_stick));
+      assert.equal(typeof message.data.settings.outline.thickness, 'number');
+      assert.equal(message.data.settings.outline.active_color.length, 3);
+      assert.equal(typeof message.data.settings.orbit.orbit_around_depth, 'boolean');
+      assert.equal(typeof message.data.settings.orbit.zoom_to_cursor, 'boolean');
+      return;
+    case 'SceneUpdated':
+      assert.ok(Array.isArray(message.data.objects));
+      message.data.objects.forEach(assertSceneObject);
+      assert.ok(Array.isArray(message.data.lights));
+      if (message.data.wireframe !== null) {
+        assertWireframeInfo(message.data.wireframe);
+      }
+      assert.equal(typeof message.data.isolated, 'boolean');
+      assert.ok(Array.isArray(message.data.annotations));
+      message.data.annotations.forEach(assertAnnotationInfo);
+      return;
+    case 'ObjectAdded':
+      assertSceneObject(message.data.object);
+      assert.ok(
+        message.data.duplicated_from === null || typeof message.data.duplicated_from === 'string',
+      );
       return;
     case 'ShowAddObjectMenu':
       assert.equal(typeof message.data.show, 'boolean');
@@ -51,8 +141,23 @@ function assertBevyToUiMessage(message) {
       assert.equal(typeof message.data.settings.enabled, 'boolean');
       assert.equal(typeof message.data.settings.quality_level, 'number');
       return;
+    case 'LightingChanged':
+      assertTuple(message.data.settings.sun_direction, 3, 'LightingSettings.sun_direction');
+      assert.equal(typeof message.data.settings.time_of_day, 'number');
+      assert.equal(typeof message.data.settings.use_time_of_day, 'boolean');
+      assert.equal(typeof message.data.settings.latitude, 'number');
+      assert.equal(typeof message.data.settings.animate_speed, 'number');
+      return;
+    case 'GizmoStatus':
+      assert.match(message.data.mode, /^(None|Translate|Rotate|Trackball|Scale)$/);
+      assert.match(message.data.axis, /^(None|X|Y|Z|XY|XZ|YZ)$/);
+      assert.match(message.data.coordinate_space, /^(Global|Local)$/);
+      assert.equal(typeof message.data.active, 'boolean');
+      assertSnapTarget(message.data.snap);
+      assertPivotPoint(message.data.pivot);
+      return;
     case 'EditModeChanged':
-      assert.match(message.data.mode, /^(None|Paint|MeshEdit|Sculpt)$/);
+      assert.match(message.data.mode, /^(None|Paint|MeshPaint|MeshEdit|Sculpt)$/);
       return;
     case 'MeshEditModeChanged':
       assert.equal(typeof message.data.active, 'boolean');
@@ -63,7 +168,130 @@ function assertBevyToUiMessage(message) {
       assert.ok(Array.isArray(message.data.layers));
       message.data.layers.forEach(assertLayerInfo);
       return;
+    case 'RenderStats':
+      assert.equal(typeof message.data.fps, 'number');
+      assert.equal(typeof message.data.frame_time_ms, 'number');
+      assert.equal(typeof message.data.captures_per_second, 'number');
+      assert.equal(typeof message.data.skipped_captures, 'number');
+      assert.equal(typeof message.data.projected_texels_per_frame, 'number');
+      assert.equal(typeof message.data.tiles_uploaded_per_frame, 'number');
+      assert.equal(typeof message.data.ui_renders_skipped, 'number');
+      assert.equal(typeof message.data.render_scale, 'number');
+      assert.equal(typeof message.data.scale_factor, 'number');
+      assert.equal(typeof message.data.history_bytes, 'number');
+      assert.equal(typeof message.data.history_budget_bytes, 'number');
+      return;
+    case 'TextureRegistryStats':
+      assert.equal(typeof message.data.texture_count, 'number');
+      assert.equal(typeof message.data.referenced_count, 'number');
+      assert.equal(typeof message.data.total_bytes, 'number');
+      assert.equal(typeof message.data.budget_bytes, 'number');
+      assert.equal(typeof message.data.evicted_count, 'number');
+      return;
+    case 'SculptSettingsChanged':
+      assert.equal(typeof message.data.dynamic_topology, 'boolean');
+      assert.match(message.data.detail_mode, /^(ScreenSpace|Constant|Budget)$/);
+      assert.equal(typeof message.data.detail_size, 'number');
+      assert.ok(message.data.max_vertices === null || typeof message.data.max_vertices === 'number');
+      return;
+    case 'SculptRemeshProgress':
+      assert.equal(typeof message.data.progress, 'number');
+      return;
+    case 'SculptRemeshFinished':
+      assert.equal(typeof message.data.cancelled, 'boolean');
+      assert.equal(typeof message.data.vertex_count, 'number');
+      assert.equal(typeof message.data.face_count, 'number');
+      return;
+    case 'MeshHealthReport':
+      assert.ok(Array.isArray(message.data.errors));
+      message.data.errors.forEach((error) => assert.equal(typeof error, 'string'));
+      assert.equal(typeof message.data.repaired, 'number');
+      assert.ok(Array.isArray(message.data.chunk_stats));
+      for (const stats of message.data.chunk_stats) {
+        assert.equal(typeof stats.chunk_id, 'number');
+        assert.equal(typeof stats.vertex_count, 'number');
+        assert.equal(typeof stats.face_count, 'number');
+        assert.equal(typeof stats.issue_count, 'number');
+      }
+      return;
+    case 'KeymapChanged':
+      assert.ok(Array.isArray(message.data.bindings));
+      for (const binding of message.data.bindings) {
+        assert.equal(typeof binding.action, 'string');
+        assert.equal(typeof binding.chord, 'string');
+      }
+      return;
+    case 'LightsChanged':
+      assert.ok(Array.isArray(message.data.lights));
+      message.data.lights.forEach(assertLightInfo);
+      return;
+    case 'MeasureResult':
+      assert.equal(typeof message.data.id, 'string');
+      assertTuple(message.data.start, 3, 'MeasureResult.start');
+      assertTuple(message.data.end, 3, 'MeasureResult.end');
+      assert.equal(typeof message.data.distance, 'number');
+      return;
+    case 'RecoveryAvailable':
+      assert.equal(typeof message.data.path, 'string');
+      assert.equal(typeof message.data.timestamp, 'number');
+      return;
+    case 'CanvasExported':
+      assert.equal(typeof message.data.path, 'string');
+      return;
+    case 'RenderProgress':
+      assert.equal(typeof message.data.frame, 'number');
+      assert.equal(typeof message.data.total, 'number');
+      return;
+    case 'RenderFinished':
+      assert.equal(typeof message.data.path, 'string');
+      return;
+    case 'MeshStorageConversionProgress':
+      assert.equal(typeof message.data.mesh_id, 'number');
+      assert.equal(typeof message.data.progress, 'number');
+      return;
+    case 'MeshStorageConversionFinished':
+      assert.equal(typeof message.data.mesh_id, 'number');
+      assert.equal(typeof message.data.cancelled, 'boolean');
+      return;
+    case 'UiLog':
+      assert.match(message.data.level, /^(Debug|Info|Warn|Error)$/);
+      assert.equal(typeof message.data.message, 'string');
+      assert.equal(typeof message.data.source, 'string');
+      assert.equal(typeof message.data.line, 'number');
+      return;
+    case 'BinaryBlob':
+      assert.equal(typeof message.data.id, 'number');
+      assert.equal(typeof message.data.kind.DiffusionPreview.task_id, 'string');
+      assert.equal(typeof message.data.kind.DiffusionPreview.width, 'number');
+      assert.equal(typeof message.data.kind.DiffusionPreview.height, 'number');
+      assert.equal(typeof message.data.byte_length, 'number');
+      return;
+    case 'ProtocolVersion':
+      assert.ok(Number.isInteger(message.data.version));
+      return;
+    case 'QueryResult':
+      assert.ok(Number.isInteger(message.data.request_id));
+      if ('Ok' in message.data.result) {
+        assert.ok(message.data.result.Ok === null || typeof message.data.result.Ok === 'object');
+      } else {
+        assert.equal(typeof message.data.result.Err, 'string');
+      }
+      return;
+    case 'CollabStatus':
+      assert.match(message.data.state, /^(Offline|Hosting|Joined)$/);
+      assert.ok(message.data.ticket === null || typeof message.data.ticket === 'string');
+      assert.ok(Number.isInteger(message.data.peers));
+      assert.ok(message.data.error === null || typeof message.data.error === 'string');
+      return;
+    case 'BackendLifecycleChanged':
+      assert.match(message.data.state, /^(Initializing|Loading|Ready|Resizing|Error)$/);
+      return;
+    case 'Warning':
+      assert.equal(typeof message.data.code, 'string');
+      assert.equal(typeof message.data.message, 'string');
+      return;
     case 'CloseMenus':
+    case 'CloseRequested':
       assert.equal(message.data, undefined);
       return;
     default:
@@ -76,34 +304,311 @@ function assertUiToBevyMessage(message) {
 
   switch (message.type) {
     case 'AddObject':
-      assert.equal(typeof message.data.primitive_type, 'string');
+      if (typeof message.data.primitive_type === 'string') {
+        assert.match(
+          message.data.primitive_type,
+          /^(Cube|Sphere|Cylinder|Plane|Torus|Cone|Capsule|Monkey)$/
+        );
+      } else {
+        assert.equal(typeof message.data.primitive_type.Grid.subdivisions, 'number');
+      }
       if (message.data.position !== null) {
         assertTuple(message.data.position, 3, 'AddObject.position');
       }
+      if (message.data.subdivisions !== null) {
+        assert.equal(typeof message.data.subdivisions, 'number');
+      }
       return;
     case 'UpdateLighting':
       assert.equal(typeof message.data.time_of_day, 'number');
       assert.equal(typeof message.data.moon_phase, 'number');
       assert.equal(typeof message.data.azimuth_angle, 'number');
       assert.equal(typeof message.data.pollution, 'number');
+      assert.equal(typeof message.data.latitude, 'number');
+      assert.equal(typeof message.data.animate_speed, 'number');
       assertTuple(message.data.sun_direction, 3, 'UpdateLighting.sun_direction');
       return;
+    case 'NodeGraphUpdate':
+      assert.equal(typeof message.data.material_id, 'string');
+      for (const node of message.data.nodes) {
+        assert.equal(typeof node.id, 'string');
+        assert.equal(typeof node.node_type, 'string');
+        assertTuple(node.position, 2, 'NodeInfo.position');
+      }
+      for (const connection of message.data.connections) {
+        assert.equal(typeof connection.from_node, 'string');
+        assert.equal(typeof connection.from_output, 'string');
+        assert.equal(typeof connection.to_node, 'string');
+        assert.equal(typeof connection.to_input, 'string');
+      }
+      return;
     case 'SetDepthView':
       assert.equal(typeof message.data.enabled, 'boolean');
       return;
+    case 'SetViewMode':
+      assert.match(message.data.mode, /^(Shaded|Depth|Normals|AmbientOcclusion|UvChecker)$/);
+      return;
+    case 'SetCompositeMode':
+      assert.match(message.data.mode, /^(Capture|Overlay|Cef|Dioxus|Tauri)$/);
+      return;
+    case 'SetKeybinding':
+      assert.equal(typeof message.data.action, 'string');
+      assert.equal(typeof message.data.chord, 'string');
+      return;
+    case 'RestoreAutosave':
+      assert.equal(typeof message.data.path, 'string');
+      return;
+    case 'AddReferenceImage':
+      assert.equal(typeof message.data.path, 'string');
+      if (typeof message.data.mode === 'string') {
+        assert.equal(message.data.mode, 'WorldPlane');
+      } else {
+        const pinned = message.data.mode.CameraPinned;
+        assert.match(pinned.corner, /^(TopLeft|TopRight|BottomLeft|BottomRight)$/);
+        assert.equal(typeof pinned.size, 'number');
+        assert.equal(typeof pinned.opacity, 'number');
+      }
+      return;
+    case 'SetReferenceImageOpacity':
+      assert.equal(typeof message.data.id, 'string');
+      assert.equal(typeof message.data.opacity, 'number');
+      return;
+    case 'RenderTurntable':
+      assert.equal(typeof message.data.frames, 'number');
+      assert.equal(typeof message.data.seconds, 'number');
+      assert.equal(typeof message.data.width, 'number');
+      assert.equal(typeof message.data.height, 'number');
+      assert.equal(typeof message.data.path, 'string');
+      return;
+    case 'CancelRender':
+    case 'RequestInitialize':
+    case 'CompactHistory':
+      assert.equal(message.data, undefined);
+      return;
+    case 'DeleteTexture':
+      assert.equal(typeof message.data.texture_id, 'string');
+      return;
+    case 'SetWireframe':
+      if (message.data.target !== 'Global') {
+        assert.equal(typeof message.data.target.Object, 'string');
+      }
+      assert.equal(typeof message.data.enabled, 'boolean');
+      if (message.data.color !== null) {
+        assertTuple(message.data.color, 4, 'SetWireframe.color');
+      }
+      assert.equal(typeof message.data.depth_test, 'boolean');
+      return;
+    case 'BlobConsumed':
+      assert.equal(typeof message.data.id, 'number');
+      return;
+    case 'ProtocolVersion':
+      assert.ok(Number.isInteger(message.data.version));
+      return;
+    case 'Query':
+      assert.ok(Number.isInteger(message.data.request_id));
+      if (typeof message.data.query === 'string') {
+        assert.equal(message.data.query, 'SceneInfo');
+      } else if ('ObjectAtPoint' in message.data.query) {
+        assert.equal(typeof message.data.query.ObjectAtPoint.x, 'number');
+        assert.equal(typeof message.data.query.ObjectAtPoint.y, 'number');
+      } else {
+        assert.equal(typeof message.data.query.MaterialProperties.material_id, 'string');
+      }
+      return;
+    case 'Collab':
+      if (typeof message.data === 'string') {
+        assert.match(message.data, /^(Host|Leave)$/);
+      } else {
+        assert.equal(typeof message.data.Join.ticket, 'string');
+      }
+      return;
+    case 'CloseResponse':
+      assert.match(message.data.decision, /^(Proceed|Cancel|Asking)$/);
+      return;
     case 'AddPaintCanvas':
       assert.ok(message.data.width === null || typeof message.data.width === 'number');
       assert.ok(message.data.height === null || typeof message.data.height === 'number');
+      assert.ok(message.data.world_size === null || message.data.world_size.length === 2);
+      assert.ok(message.data.distance === null || typeof message.data.distance === 'number');
+      assert.match(message.data.orientation, /^(FacingCamera|Horizontal|Vertical|SurfaceAligned)$/);
       return;
     case 'PaintCommand':
+      if (typeof message.data === 'string') {
+        assert.match(message.data, /^(Undo|CommitSelection|CancelSelection|ReprojectLast)$/);
+        return;
+      }
       assert.equal(typeof message.data, 'object');
+      if ('ExportCanvas' in message.data) {
+        const path = message.data.ExportCanvas.path;
+        assert.ok(path === null || typeof path === 'string');
+      } else if ('ImportCanvas' in message.data) {
+        assert.equal(typeof message.data.ImportCanvas.path, 'string');
+      } else if ('ResizeCanvas' in message.data) {
+        const { width, height, anchor } = message.data.ResizeCanvas;
+        assert.ok(Number.isInteger(width) && Number.isInteger(height));
+        assert.match(anchor, /^(Top|Bottom)?(Left|Right)?$|^Center$/);
+      } else if ('CropCanvas' in message.data) {
+        for (const key of ['x', 'y', 'width', 'height']) {
+          assert.ok(Number.isInteger(message.data.CropCanvas[key]));
+        }
+      } else if ('SetBrushFlow' in message.data) {
+        assert.equal(typeof message.data.SetBrushFlow.flow, 'number');
+      } else if ('BeginPixelSelection' in message.data) {
+        assert.match(message.data.BeginPixelSelection.mode, /^(Rect|Lasso)$/);
+      } else if ('TransformSelection' in message.data) {
+        const { translate, rotate_deg, scale } = message.data.TransformSelection;
+        assert.equal(translate.length, 2);
+        assert.equal(typeof rotate_deg, 'number');
+        assert.equal(scale.length, 2);
+      } else if ('SetBrushTip' in message.data) {
+        const { source } = message.data.SetBrushTip;
+        if (typeof source === 'string') {
+          assert.equal(source, 'Round');
+        } else {
+          assert.equal(typeof (source.BuiltIn ?? source.File), 'string');
+        }
+      } else if ('SetBrushTipRotation' in message.data) {
+        const { mode } = message.data.SetBrushTipRotation;
+        if (typeof mode === 'string') {
+          assert.match(mode, /^(FollowStroke|Random)$/);
+        } else {
+          assert.equal(typeof mode.Fixed, 'number');
+        }
+      } else if ('Fill' in message.data) {
+        const { x, y, tolerance, contiguous } = message.data.Fill;
+        assert.equal(typeof x, 'number');
+        assert.equal(typeof y, 'number');
+        assert.equal(typeof tolerance, 'number');
+        assert.equal(typeof contiguous, 'boolean');
+      } else if ('Gradient' in message.data) {
+        const { start, end, kind, colors } = message.data.Gradient;
+        assert.equal(start.length, 2);
+        assert.equal(end.length, 2);
+        assert.match(kind, /^(Linear|Radial)$/);
+        assert.ok(colors.every((color) => color.length === 4));
+      } else if ('SetMeshPaintChannel' in message.data) {
+        assert.match(
+          message.data.SetMeshPaintChannel.channel,
+          /^(BaseColor|Roughness|Metallic|Normal|Emissive)$/,
+        );
+      } else if ('SetMeshPaintValue' in message.data) {
+        assert.equal(typeof message.data.SetMeshPaintValue.value, 'number');
+      } else if ('SetNormalStrength' in message.data) {
+        assert.equal(typeof message.data.SetNormalStrength.strength, 'number');
+      } else if ('SetMeshPaintTarget' in message.data) {
+        assert.match(message.data.SetMeshPaintTarget.target, /^(Texture|VertexColors)$/);
+      } else if ('ReanchorCanvas' in message.data) {
+        const { plane_id } = message.data.ReanchorCanvas;
+        assert.ok(plane_id === null || Number.isInteger(plane_id));
+      } else if ('ArmCanvasTool' in message.data) {
+        const { tool } = message.data.ArmCanvasTool;
+        if (tool !== null) {
+          if ('Fill' in tool) {
+            assert.equal(typeof tool.Fill.tolerance, 'number');
+            assert.equal(typeof tool.Fill.contiguous, 'boolean');
+          } else {
+            assert.match(tool.Gradient.kind, /^(Linear|Radial)$/);
+            assert.ok(Array.isArray(tool.Gradient.colors));
+          }
+        }
+      } else if ('ProjectToScene' in message.data || 'ProjectToSelection' in message.data) {
+        const { options } = message.data.ProjectToScene ?? message.data.ProjectToSelection;
+        assert.equal(typeof options.occlusion, 'boolean');
+        assert.equal(typeof options.backface_cull, 'boolean');
+        assert.equal(typeof options.falloff_angle_deg, 'number');
+      }
+      return;
+    case 'ObjectCommand':
+      if ('SetParent' in message.data) {
+        assert.equal(typeof message.data.SetParent.id, 'string');
+        const { parent_id } = message.data.SetParent;
+        assert.ok(parent_id === null || typeof parent_id === 'string');
+      } else if ('Group' in message.data) {
+        assert.ok(Array.isArray(message.data.Group.ids));
+        assert.equal(typeof message.data.Group.name, 'string');
+      } else if ('Delete' in message.data) {
+        assert.ok(Array.isArray(message.data.Delete.ids));
+        assert.equal(typeof message.data.Delete.recursive, 'boolean');
+      } else if ('SetSubdivision' in message.data) {
+        assert.equal(typeof message.data.SetSubdivision.id, 'string');
+        assert.ok(Number.isInteger(message.data.SetSubdivision.levels));
+      } else if ('MakeUnique' in message.data) {
+        assert.equal(typeof message.data.MakeUnique.id, 'string');
+      } else if ('Isolate' in message.data) {
+        assert.ok(Array.isArray(message.data.Isolate.ids));
+      } else if ('SetLocked' in message.data) {
+        assert.equal(typeof message.data.SetLocked.id, 'string');
+        assert.equal(typeof message.data.SetLocked.locked, 'boolean');
+      } else {
+        assert.equal(typeof message.data, 'object');
+      }
+      return;
+    case 'CameraCommand':
+      if ('SetOrbitOptions' in message.data) {
+        const options = message.data.SetOrbitOptions;
+        assert.equal(typeof options.orbit_around_depth, 'boolean');
+        assert.equal(typeof options.zoom_to_cursor, 'boolean');
+      } else {
+        assert.equal(typeof message.data, 'object');
+      }
       return;
     case 'GizmoCommand':
-      assert.equal(typeof message.data, 'object');
+      if ('SetSnapTarget' in message.data) {
+        assertSnapTarget(message.data.SetSnapTarget.mode);
+      } else if ('SetPivot' in message.data) {
+        assertPivotPoint(message.data.SetPivot.pivot);
+      } else {
+        assert.equal(typeof message.data, 'object');
+      }
+      return;
+    case 'LightCommand':
+      if ('Add' in message.data) {
+        assertTuple(message.data.Add.position, 3, 'LightCommand.Add.position');
+      } else if ('Update' in message.data) {
+        assert.equal(typeof message.data.Update.id, 'string');
+        const { color, intensity, range, angles } = message.data.Update;
+        assert.ok(color === null || color.length === 3);
+        assert.ok(intensity === null || typeof intensity === 'number');
+        assert.ok(range === null || typeof range === 'number');
+        assert.ok(angles === null || angles.length === 2);
+      } else if ('Delete' in message.data) {
+        assert.equal(typeof message.data.Delete.id, 'string');
+      } else {
+        assert.equal(typeof message.data.SetShadows.id, 'string');
+        assert.equal(typeof message.data.SetShadows.enabled, 'boolean');
+      }
       return;
     case 'MeshEditCommand':
       assert.equal(typeof message.data, 'object');
       return;
+    case 'MeasureCommand':
+      assert.match(message.data, /^(Start|Cancel)$/);
+      return;
+    case 'SetEditMode':
+      assert.match(message.data.mode, /^(None|Paint|MeshPaint|MeshEdit|Sculpt)$/);
+      return;
+    case 'SculptCommand':
+      if (typeof message.data === 'string') {
+        assert.match(message.data, /^(CancelRemesh|ClearMask|InvertMask|ValidateMesh)$/);
+        return;
+      }
+      if ('SetBrushSpacing' in message.data) {
+        assert.equal(typeof message.data.SetBrushSpacing.spacing, 'number');
+      } else if ('SetBrushFlow' in message.data) {
+        assert.equal(typeof message.data.SetBrushFlow.flow, 'number');
+      } else if ('SelectBrushPreset' in message.data) {
+        assert.equal(typeof message.data.SelectBrushPreset.preset_id, 'number');
+      } else if ('SetDetailMode' in message.data) {
+        assert.match(message.data.SetDetailMode, /^(ScreenSpace|Constant|Budget)$/);
+      } else if ('Remesh' in message.data) {
+        assert.equal(typeof message.data.Remesh.target_edge_length, 'number');
+      } else if ('SetAutoValidate' in message.data) {
+        assert.equal(typeof message.data.SetAutoValidate.every_strokes, 'number');
+      } else {
+        const maxVertices = message.data.SetVertexBudget.max_vertices;
+        assert.ok(maxVertices === null || typeof maxVertices === 'number');
+      }
+      return;
     case 'StartDiffusion':
       assert.equal(typeof message.data.prompt, 'string');
       assert.equal(typeof message.data.guidance_scale, 'number');
@@ -127,7 +632,9 @@ test('rust ipc samples cover the active frontend contract surface', () => {
   assert.ok(inboundTypes.has('MeshEditModeChanged'));
   assert.ok(outboundTypes.has('UpdateLighting'));
   assert.ok(outboundTypes.has('SetDepthView'));
+  assert.ok(outboundTypes.has('SetViewMode'));
   assert.ok(outboundTypes.has('PaintCommand'));
+  assert.ok(outboundTypes.has('SculptCommand'));
 });
 
 test('rust ipc samples satisfy the JavaScript consumer expectations', () => {
@@ -136,3 +643,12 @@ test('rust ipc samples satisfy the JavaScript consumer expectations', () => {
   samples.bevy_to_ui.forEach(assertBevyToUiMessage);
   samples.ui_to_bevy.forEach(assertUiToBevyMessage);
 });
+
+test('typescript protocol version matches the rust contract', () => {
+  const samples = loadSamples();
+  const types = readFileSync('ui/src/lib/types.ts', 'utf8');
+  const match = types.match(/export const PROTOCOL_VERSION = (\d+);/);
+
+  assert.ok(match, 'ui/src/lib/types.ts must export PROTOCOL_VERSION');
+  assert.equal(Number(match[1]), samples.protocol_version);
+});
@@ -4,6 +4,8 @@ import assert from 'node:assert/strict';
 class FakeWindow extends EventTarget {
     __ELECTRON__ = true;
     __PENTIMENTO_RECEIVE__?: (msg: string) => void;
+    __PENTIMENTO_RECV_BATCH__?: (batch: string) => void;
+    __PENTIMENTO_IPC__?: { postMessage: (msg: string) => void };
     listenerCounts = new Map<string, Set<EventListenerOrEventListenerObject>>();
 
     override addEventListener(
@@ -183,3 +185,34 @@ test('bridge.dispose restores the previous native receiver', async () => {
     assert.equal(events.length, 0);
     assert.equal(fakeWindow.__PENTIMENTO_RECEIVE__, previousReceiver);
 });
+
+test('native messages are posted once per frame and batches are dispatched', async () => {
+    const { fakeWindow } = setupDom('native');
+    const posted: string[] = [];
+    fakeWindow.__PENTIMENTO_IPC__ = { postMessage: (msg: string) => posted.push(msg) };
+    const { bridge } = await importBridgeModule();
+    const received: string[] = [];
+    bridge.subscribe((message) => {
+        received.push(message.type);
+    });
+
+    bridge.markDirty();
+    bridge.cameraZoom(1);
+    bridge.cancelRender();
+    assert.equal(posted.length, 0);
+
+    await new Promise((resolve) => setTimeout(resolve, 25));
+    assert.equal(posted.length, 1);
+    assert.deepEqual(
+        JSON.parse(posted[0]).map((msg: { type: string }) => msg.type),
+        ['UiDirty', 'CameraCommand', 'CancelRender']
+    );
+
+    fakeWindow.__PENTIMENTO_RECV_BATCH__!(
+        JSON.stringify([{ type: 'CloseMenus' }, { type: 'CloseMenus' }])
+    );
+    assert.deepEqual(received, ['CloseMenus', 'CloseMenus']);
+
+    bridge.dispose();
+    assert.equal(fakeWindow.__PENTIMENTO_RECV_BATCH__, undefined);
+});
@@ -4,9 +4,32 @@
  * Supports multiple modes:
  * - Native modes (capture/overlay/cef): Uses __PENTIMENTO_IPC__ injected by Rust
  * - WASM modes (Tauri/Electron): Uses CustomEvents for WASM <-> JS communication
+ *
+ * In native modes messages are batched: Bevy delivers a frame's messages in one
+ * `__PENTIMENTO_RECV_BATCH__` call, and the bridge posts everything sent during
+ * an animation frame as one JSON array.
  */
 
-import type { BevyToUi, UiToBevy, LayoutInfo } from './types';
+import type {
+    BevyToUi,
+    UiToBevy,
+    LayoutInfo,
+    CloseDecision,
+    CanvasOrientation,
+    CompositeMode,
+    LightType,
+    MaterialProperties,
+    PrimitiveType,
+    QueryKind,
+    ReferenceImageMode,
+    SceneInfo,
+    SculptDetailMode,
+    OrbitOptions,
+    PivotPoint,
+    SnapTarget,
+    ViewMode,
+} from './types';
+import { PROTOCOL_VERSION } from './types';
 
 // Declare the IPC interface injected by Rust (native modes)
 declare global {
@@ -15,6 +38,8 @@ declare global {
             postMessage: (msg: string) => void;
         };
         __PENTIMENTO_RECEIVE__?: (msg: string) => void;
+        __PENTIMENTO_RECV_BATCH__?: (batch: string) => void;
+        __PENTIMENTO_DIRTY_NOTIFIER__?: boolean;
         ipc?: {
             postMessage: (msg: string) => void;
         };
@@ -27,6 +52,31 @@ declare global {
 type MessageHandler = (msg: BevyToUi) => void;
 type DisposeFn = () => void;
 
+/** Object hit by an `ObjectAtPoint` query */
+export interface ObjectHit {
+    object_id: string;
+    position: [number, number, number];
+    distance: number;
+}
+
+interface PendingQuery {
+    resolve: (value: unknown) => void;
+    reject: (error: Error) => void;
+    timer: ReturnType<typeof setTimeout>;
+}
+
+/** Bevy answers within a frame or two; this only catches a lost backend */
+const QUERY_TIMEOUT_MS = 5000;
+
+/** Run `callback` on the next animation frame (a timer where there are none) */
+function nextFrame(callback: () => void): void {
+    if (typeof requestAnimationFrame === 'function') {
+        requestAnimationFrame(callback);
+    } else {
+        setTimeout(callback, 16);
+    }
+}
+
 let activeAutoMarkDirtyCleanup: DisposeFn | null = null;
 
 function parseBevyMessage(raw: unknown): BevyToUi | null {
@@ -61,11 +111,18 @@ function getNativeIpc(): { postMessage: (msg: string) => void } | null {
 
 class BevyBridge {
     private handlers: Set<MessageHandler> = new Set();
+    private pendingQueries: Map<number, PendingQuery> = new Map();
+    private nextQueryId = 1;
     private layoutDebounceTimer: ReturnType<typeof setTimeout> | null = null;
     private readonly wasmMode: boolean;
     private readonly wasmMessageListener: EventListener | null = null;
     private readonly nativeMessageReceiver: ((msg: string) => void) | null = null;
     private readonly previousNativeMessageReceiver: ((msg: string) => void) | undefined;
+    private readonly nativeBatchReceiver: ((batch: string) => void) | null = null;
+    private readonly previousNativeBatchReceiver: ((batch: string) => void) | undefined;
+    /** Native messages waiting for the next animation frame */
+    private outbox: UiToBevy[] = [];
+    private flushScheduled = false;
 
     constructor() {
         this.wasmMode = isWasmMode();
@@ -78,7 +135,7 @@ class BevyBridge {
                     if (!msg) {
                         throw new Error('Invalid Bevy WASM message shape');
                     }
-                    this.handlers.forEach(handler => handler(msg));
+                    this.dispatch(msg);
                 } catch (e) {
                     console.error('Failed to parse Bevy WASM message:', e);
                 }
@@ -97,12 +154,36 @@ class BevyBridge {
                     if (!msg) {
                         throw new Error('Invalid IPC message shape');
                     }
-                    this.handlers.forEach(handler => handler(msg));
+                    this.dispatch(msg);
                 } catch (e) {
                     console.error('Failed to parse IPC message:', e);
                 }
             };
             window.__PENTIMENTO_RECEIVE__ = this.nativeMessageReceiver;
+
+            // All messages of a Bevy frame arrive in one call, as a JSON array
+            this.previousNativeBatchReceiver = window.__PENTIMENTO_RECV_BATCH__;
+            this.nativeBatchReceiver = (batchJson: string) => {
+                let batch: unknown;
+                try {
+                    batch = JSON.parse(batchJson);
+                    if (!Array.isArray(batch)) {
+                        throw new Error('IPC batch is not an array');
+                    }
+                } catch (e) {
+                    console.error('Failed to parse IPC batch:', e);
+                    return;
+                }
+                for (const raw of batch) {
+                    const msg = parseBevyMessage(raw);
+                    if (msg) {
+                        this.dispatch(msg);
+                    } else {
+                        console.error('Invalid IPC message shape in batch:', raw);
+                    }
+                }
+            };
+            window.__PENTIMENTO_RECV_BATCH__ = this.nativeBatchReceiver;
         }
     }
 
@@ -115,6 +196,67 @@ class BevyBridge {
         return () => this.handlers.delete(handler);
     }
 
+    private dispatch(msg: BevyToUi): void {
+        if (msg.type === 'ProtocolVersion') {
+            // Handshake right after Initialize: report our version back
+            if (msg.data.version !== PROTOCOL_VERSION) {
+                console.warn(
+                    `IPC protocol mismatch: UI speaks ${PROTOCOL_VERSION}, app speaks ${msg.data.version}`
+                );
+            }
+            this.send({ type: 'ProtocolVersion', data: { version: PROTOCOL_VERSION } });
+        }
+        if (msg.type === 'QueryResult') {
+            const pending = this.pendingQueries.get(msg.data.request_id);
+            if (pending) {
+                this.pendingQueries.delete(msg.data.request_id);
+                clearTimeout(pending.timer);
+                const result = msg.data.result;
+                if ('Ok' in result) {
+                    pending.resolve(result.Ok);
+                } else {
+                    pending.reject(new Error(result.Err));
+                }
+            }
+        }
+        this.handlers.forEach(handler => handler(msg));
+    }
+
+    /**
+     * Ask Bevy a question and wait for its `QueryResult`
+     *
+     * Resolves with the JSON documented on the `QueryKind` variant; rejects
+     * with Bevy's error message or on timeout.
+     */
+    query<T>(query: QueryKind): Promise<T> {
+        const requestId = this.nextQueryId++;
+        return new Promise<T>((resolve, reject) => {
+            const timer = setTimeout(() => {
+                this.pendingQueries.delete(requestId);
+                reject(new Error(`Query ${requestId} timed out`));
+            }, QUERY_TIMEOUT_MS);
+            this.pendingQueries.set(requestId, {
+                resolve: resolve as (value: unknown) => void,
+                reject,
+                timer,
+            });
+            this.send({ type: 'Query', data: { request_id: requestId, query } });
+        });
+    }
+
+    /** Object under a viewport point (CSS pixels), or null over empty space */
+    objectAtPoint(x: number, y: number): Promise<ObjectHit | null> {
+        return this.query({ ObjectAtPoint: { x, y } });
+    }
+
+    materialProperties(materialId: string): Promise<MaterialProperties> {
+        return this.query({ MaterialProperties: { material_id: materialId } });
+    }
+
+    sceneInfo(): Promise<SceneInfo> {
+        return this.query('SceneInfo');
+    }
+
     private send(msg: UiToBevy): void {
         if (this.wasmMode) {
             // WASM mode (Tauri/Electron): Send via CustomEvent to Bevy WASM
@@ -122,16 +264,49 @@ class BevyBridge {
                 detail: JSON.stringify(msg)
             }));
         } else {
-            // Native modes: Use IPC injected by Rust
-            const ipc = getNativeIpc();
-            if (ipc) {
-                ipc.postMessage(JSON.stringify(msg));
-            } else {
-                console.warn('IPC not available - running outside Pentimento?');
+            // Native modes: every postMessage is a round trip through the
+            // host, so a frame's messages go out together
+            this.outbox.push(msg);
+            if (!this.flushScheduled) {
+                this.flushScheduled = true;
+                nextFrame(() => this.flush());
             }
         }
     }
 
+    /** Post the queued native messages, as a JSON array if there are several */
+    private flush(): void {
+        this.flushScheduled = false;
+        const batch = this.outbox;
+        if (batch.length === 0) {
+            return;
+        }
+        this.outbox = [];
+
+        const ipc = getNativeIpc();
+        if (ipc) {
+            ipc.postMessage(JSON.stringify(batch.length === 1 ? batch[0] : batch));
+        } else {
+            console.warn('IPC not available - running outside Pentimento?');
+        }
+    }
+
+    /**
+     * Ask for the full app state again (after the page lost it, e.g. a hot
+     * reload); answered with the initialization sequence starting at `Initialize`
+     */
+    requestInitialize(): void {
+        this.send({ type: 'RequestInitialize' });
+    }
+
+    /**
+     * Checkpoint old paint history now instead of when its memory budget
+     * runs out (undo is unaffected)
+     */
+    compactHistory(): void {
+        this.send({ type: 'CompactHistory' });
+    }
+
     /**
      * Mark UI as dirty (needs re-capture)
      */
@@ -173,6 +348,26 @@ class BevyBridge {
             }
         }
 
+        if (
+            this.nativeBatchReceiver &&
+            window.__PENTIMENTO_RECV_BATCH__ === this.nativeBatchReceiver
+        ) {
+            if (this.previousNativeBatchReceiver) {
+                window.__PENTIMENTO_RECV_BATCH__ = this.previousNativeBatchReceiver;
+            } else {
+                delete window.__PENTIMENTO_RECV_BATCH__;
+            }
+        }
+
+        // Messages sent before disposal still reach Bevy
+        this.flush();
+
+        this.pendingQueries.forEach(pending => {
+            clearTimeout(pending.timer);
+            pending.reject(new Error('Bridge disposed'));
+        });
+        this.pendingQueries.clear();
+
         this.handlers.clear();
     }
 
@@ -205,6 +400,13 @@ class BevyBridge {
         });
     }
 
+    setOrbitOptions(options: OrbitOptions): void {
+        this.send({
+            type: 'CameraCommand',
+            data: { SetOrbitOptions: options }
+        });
+    }
+
     // Object manipulation
     selectObjects(ids: string[]): void {
         this.send({
@@ -213,13 +415,83 @@ class BevyBridge {
         });
     }
 
-    deleteObjects(ids: string[]): void {
+    // With `recursive`, children are deleted too; otherwise they move up a level
+    deleteObjects(ids: string[], recursive = false): void {
+        this.send({
+            type: 'ObjectCommand',
+            data: { Delete: { ids, recursive } }
+        });
+    }
+
+    // Bevy answers with an ObjectAdded per copy, naming its source in duplicated_from
+    duplicateObjects(ids: string[]): void {
+        this.send({
+            type: 'ObjectCommand',
+            data: { Duplicate: { ids } }
+        });
+    }
+
+    setObjectParent(id: string, parentId: string | null): void {
+        this.send({
+            type: 'ObjectCommand',
+            data: { SetParent: { id, parent_id: parentId } }
+        });
+    }
+
+    groupObjects(ids: string[], name: string): void {
+        this.send({
+            type: 'ObjectCommand',
+            data: { Group: { ids, name } }
+        });
+    }
+
+    // Subdivision preview levels (0-4); 0 shows the base mesh
+    setObjectSubdivision(id: string, levels: number): void {
+        this.send({
+            type: 'ObjectCommand',
+            data: { SetSubdivision: { id, levels } }
+        });
+    }
+
+    setObjectVisibility(id: string, visible: boolean): void {
+        this.send({
+            type: 'ObjectCommand',
+            data: { SetVisibility: { id, visible } }
+        });
+    }
+
+    // Hide every object but ids; an empty list ends isolation
+    isolateObjects(ids: string[]): void {
         this.send({
             type: 'ObjectCommand',
-            data: { Delete: { ids } }
+            data: { Isolate: { ids } }
         });
     }
 
+    // Give an instanced object its own mesh and material
+    makeObjectUnique(id: string): void {
+        this.send({
+            type: 'ObjectCommand',
+            data: { MakeUnique: { id } }
+        });
+    }
+
+    setObjectLocked(id: string, locked: boolean): void {
+        this.send({
+            type: 'ObjectCommand',
+            data: { SetLocked: { id, locked } }
+        });
+    }
+
+    // Reference images; Bevy answers with ObjectAdded
+    addReferenceImage(path: string, mode: ReferenceImageMode): void {
+        this.send({ type: 'AddReferenceImage', data: { path, mode } });
+    }
+
+    setReferenceImageOpacity(id: string, opacity: number): void {
+        this.send({ type: 'SetReferenceImageOpacity', data: { id, opacity } });
+    }
+
     // Material editing
     updateMaterialProperty(materialId: string, property: string, value: unknown): void {
         this.send({
@@ -265,6 +537,28 @@ class BevyBridge {
         this.send({ type: 'CancelDiffusion', data: { task_id: taskId } });
     }
 
+    // Free a registered texture; material slots using it are cleared
+    deleteTexture(textureId: string): void {
+        this.send({ type: 'DeleteTexture', data: { texture_id: textureId } });
+    }
+
+    // Binary blobs
+    /**
+     * Fetch the bytes announced by a `BinaryBlob` message and acknowledge
+     * them so Bevy can free the blob
+     */
+    async fetchBlob(id: number): Promise<ArrayBuffer> {
+        try {
+            const response = await fetch(`pentimento-blob://${id}`);
+            if (!response.ok) {
+                throw new Error(`Blob ${id} unavailable (HTTP ${response.status})`);
+            }
+            return await response.arrayBuffer();
+        } finally {
+            this.send({ type: 'BlobConsumed', data: { id } });
+        }
+    }
+
     // Lighting controls
     updateLighting(settings: {
         sunDirection?: [number, number, number];
@@ -278,6 +572,8 @@ class BevyBridge {
         moonPhase?: number;
         azimuthAngle?: number;
         pollution?: number;
+        latitude?: number;
+        animateSpeed?: number;
     }): void {
         this.send({
             type: 'UpdateLighting',
@@ -293,10 +589,48 @@ class BevyBridge {
                 moon_phase: settings.moonPhase ?? 0.5,
                 azimuth_angle: settings.azimuthAngle ?? 0.0,
                 pollution: settings.pollution ?? 0.0,
+                latitude: settings.latitude ?? 45.0,
+                animate_speed: settings.animateSpeed ?? 0.0,
+            }
+        });
+    }
+
+    // Scene lights; Bevy answers with LightsChanged (new lights become the selection)
+    addLight(lightType: LightType, position: [number, number, number]): void {
+        this.send({ type: 'LightCommand', data: { Add: { light_type: lightType, position } } });
+    }
+
+    updateLight(
+        id: string,
+        changes: {
+            color?: [number, number, number];
+            intensity?: number;
+            range?: number;
+            angles?: [number, number];
+        }
+    ): void {
+        this.send({
+            type: 'LightCommand',
+            data: {
+                Update: {
+                    id,
+                    color: changes.color ?? null,
+                    intensity: changes.intensity ?? null,
+                    range: changes.range ?? null,
+                    angles: changes.angles ?? null,
+                }
             }
         });
     }
 
+    deleteLight(id: string): void {
+        this.send({ type: 'LightCommand', data: { Delete: { id } } });
+    }
+
+    setLightShadows(id: string, enabled: boolean): void {
+        this.send({ type: 'LightCommand', data: { SetShadows: { id, enabled } } });
+    }
+
     // Ambient occlusion controls
     updateAmbientOcclusion(settings: {
         enabled: boolean;
@@ -315,9 +649,10 @@ class BevyBridge {
 
     // Add object to scene
     addObject(request: {
-        primitiveType: string;
+        primitiveType: PrimitiveType;
         position?: [number, number, number];
         name?: string;
+        subdivisions?: number;
     }): void {
         this.send({
             type: 'AddObject',
@@ -325,41 +660,182 @@ class BevyBridge {
                 primitive_type: request.primitiveType,
                 position: request.position ?? null,
                 name: request.name ?? null,
+                subdivisions: request.subdivisions ?? null,
             }
         });
     }
 
+    // Gizmo snapping; Bevy answers with GizmoStatus
+    setSnapTarget(mode: SnapTarget): void {
+        this.send({ type: 'GizmoCommand', data: { SetSnapTarget: { mode } } });
+    }
+
+    // Rotation/scale pivot; Bevy answers with GizmoStatus
+    setPivot(pivot: PivotPoint): void {
+        this.send({ type: 'GizmoCommand', data: { SetPivot: { pivot } } });
+    }
+
     // Depth view
     setDepthView(enabled: boolean): void {
         this.send({ type: 'SetDepthView', data: { enabled } });
     }
 
+    // Viewport debug view (depth, normals, AO, UV checker)
+    setViewMode(mode: ViewMode): void {
+        this.send({ type: 'SetViewMode', data: { mode } });
+    }
+
+    // Switch the compositing backend (the UI is reloaded and re-initialized)
+    setCompositeMode(mode: CompositeMode): void {
+        this.send({ type: 'SetCompositeMode', data: { mode } });
+    }
+
+    // Rebind a hotkey; Bevy answers with KeymapChanged, or an Error on a conflict
+    setKeybinding(action: string, chord: string): void {
+        this.send({ type: 'SetKeybinding', data: { action, chord } });
+    }
+
+    // Answer CloseRequested; 'Asking' holds the window open until a final answer
+    respondToClose(decision: CloseDecision): void {
+        this.send({ type: 'CloseResponse', data: { decision } });
+    }
+
+    // Restore the autosave offered by RecoveryAvailable
+    restoreAutosave(path: string): void {
+        this.send({ type: 'RestoreAutosave', data: { path } });
+    }
+
+    // Export the active canvas as PNG; Bevy answers with CanvasExported or an Error
+    exportCanvas(path?: string): void {
+        this.send({ type: 'PaintCommand', data: { ExportCanvas: { path: path ?? null } } });
+    }
+
+    // Replace the active canvas layer with an image (undoable)
+    importCanvas(path: string): void {
+        this.send({ type: 'PaintCommand', data: { ImportCanvas: { path } } });
+    }
+
+    // Render a 360° turntable; Bevy reports RenderProgress, then RenderFinished
+    renderTurntable(frames: number, seconds: number, width: number, height: number, path: string): void {
+        this.send({ type: 'RenderTurntable', data: { frames, seconds, width, height, path } });
+    }
+
+    cancelRender(): void {
+        this.send({ type: 'CancelRender' });
+    }
+
+    // Sculpt brush controls
+    setSculptBrushSpacing(spacing: number): void {
+        this.send({ type: 'SculptCommand', data: { SetBrushSpacing: { spacing } } });
+    }
+
+    setSculptBrushFlow(flow: number): void {
+        this.send({ type: 'SculptCommand', data: { SetBrushFlow: { flow } } });
+    }
+
+    selectSculptBrushPreset(presetId: number): void {
+        this.send({ type: 'SculptCommand', data: { SelectBrushPreset: { preset_id: presetId } } });
+    }
+
+    // Sculpt dynamic topology controls
+    setSculptDynamicTopology(enabled: boolean): void {
+        this.send({ type: 'SculptCommand', data: { SetDynamicTopology: { enabled } } });
+    }
+
+    setSculptDetailMode(mode: SculptDetailMode): void {
+        this.send({ type: 'SculptCommand', data: { SetDetailMode: mode } });
+    }
+
+    setSculptDetailSize(pixelsOrWorld: number): void {
+        this.send({ type: 'SculptCommand', data: { SetDetailSize: { pixels_or_world: pixelsOrWorld } } });
+    }
+
+    setSculptVertexBudget(maxVertices: number | null): void {
+        this.send({ type: 'SculptCommand', data: { SetVertexBudget: { max_vertices: maxVertices } } });
+    }
+
+    // Sculpt remesh
+    remeshSculpt(targetEdgeLength: number): void {
+        this.send({ type: 'SculptCommand', data: { Remesh: { target_edge_length: targetEdgeLength } } });
+    }
+
+    cancelSculptRemesh(): void {
+        this.send({ type: 'SculptCommand', data: 'CancelRemesh' });
+    }
+
+    // Sculpt mask
+    clearSculptMask(): void {
+        this.send({ type: 'SculptCommand', data: 'ClearMask' });
+    }
+
+    invertSculptMask(): void {
+        this.send({ type: 'SculptCommand', data: 'InvertMask' });
+    }
+
+    // Sculpt mesh validation
+    validateSculptMesh(): void {
+        this.send({ type: 'SculptCommand', data: 'ValidateMesh' });
+    }
+
+    setSculptAutoValidate(everyStrokes: number): void {
+        this.send({ type: 'SculptCommand', data: { SetAutoValidate: { every_strokes: everyStrokes } } });
+    }
+
     // Add paint canvas
-    addPaintCanvas(options?: { width?: number; height?: number }): void {
+    addPaintCanvas(options?: {
+        width?: number;
+        height?: number;
+        worldSize?: [number, number];
+        distance?: number;
+        orientation?: CanvasOrientation;
+    }): void {
         this.send({
             type: 'AddPaintCanvas',
             data: {
                 width: options?.width ?? null,
                 height: options?.height ?? null,
+                world_size: options?.worldSize ?? null,
+                distance: options?.distance ?? null,
+                orientation: options?.orientation ?? 'FacingCamera',
             }
         });
     }
+
+    // Move a canvas (the active one by default) back in front of the camera
+    reanchorCanvas(planeId?: number): void {
+        this.send({ type: 'PaintCommand', data: { ReanchorCanvas: { plane_id: planeId ?? null } } });
+    }
 }
 
 export const bridge = new BevyBridge();
 
 /**
  * Set up automatic dirty marking on DOM mutations
+ *
+ * Mutations are coalesced to one UiDirty per animation frame. Skipped when the
+ * native backend already injected its own dirty notifier (WebKit capture mode).
  */
 export function setupAutoMarkDirty(target: Node = document.body): DisposeFn {
     activeAutoMarkDirtyCleanup?.();
 
-    const observer = new MutationObserver(() => {
-        bridge.markDirty();
-    });
-    const handleResize = () => {
-        bridge.markDirty();
+    if (window.__PENTIMENTO_DIRTY_NOTIFIER__) {
+        return () => {};
+    }
+
+    let frameRequested = false;
+    const scheduleMarkDirty = () => {
+        if (frameRequested) {
+            return;
+        }
+        frameRequested = true;
+        requestAnimationFrame(() => {
+            frameRequested = false;
+            bridge.markDirty();
+        });
     };
+
+    const observer = new MutationObserver(scheduleMarkDirty);
+    const handleResize = scheduleMarkDirty;
     let cleanedUp = false;
 
     observer.observe(target, {
@@ -2,14 +2,38 @@
  * TypeScript types matching the Rust IPC protocol
  */
 
+/** IPC protocol version; must match `PROTOCOL_VERSION` in `pentimento_ipc` */
+export const PROTOCOL_VERSION = 26;
+
 // Edit mode
-export type EditMode = 'None' | 'Paint' | 'MeshEdit' | 'Sculpt';
+export type EditMode = 'None' | 'Paint' | 'MeshPaint' | 'MeshEdit' | 'Sculpt';
 
 export type GizmoMode = 'None' | 'Translate' | 'Rotate' | 'Trackball' | 'Scale';
 export type GizmoAxis = 'None' | 'X' | 'Y' | 'Z' | 'XY' | 'XZ' | 'YZ';
 export type CoordinateSpace = 'Global' | 'Local';
+export type SnapTarget =
+    | 'None'
+    | { Grid: { size: number | null } }
+    | { Surface: { align_to_normal: boolean } };
+export type PivotPoint = 'Median' | 'IndividualOrigins' | 'ActiveObject' | 'Cursor3D';
 export type MeshSelectionMode = 'Vertex' | 'Edge' | 'Face';
 export type MeshEditTool = 'Select' | 'Extrude' | 'LoopCut' | 'Knife' | 'Merge' | 'Inset';
+export type CompositeMode = 'Capture' | 'Overlay' | 'Cef' | 'Dioxus' | 'Tauri';
+export type FrontendLifecycle = 'Initializing' | 'Loading' | 'Ready' | 'Resizing' | 'Error';
+export type CloseDecision = 'Proceed' | 'Cancel' | 'Asking';
+
+export type ViewMode = 'Shaded' | 'Depth' | 'Normals' | 'AmbientOcclusion' | 'UvChecker';
+export type SculptDetailMode = 'ScreenSpace' | 'Constant' | 'Budget';
+export type UiLogLevel = 'Debug' | 'Info' | 'Warn' | 'Error';
+export type BlobKind = { DiffusionPreview: { task_id: string; width: number; height: number } };
+export type QueryKind =
+    | { ObjectAtPoint: { x: number; y: number } }
+    | { MaterialProperties: { material_id: string } }
+    | 'SceneInfo';
+/** Result of a query: `Ok` holds the JSON documented on its `QueryKind` */
+export type QueryResult = { Ok: unknown } | { Err: string };
+export type CollabCommand = 'Host' | { Join: { ticket: string } } | 'Leave';
+export type CollabState = 'Offline' | 'Hosting' | 'Joined';
 
 // Messages from Bevy to UI
 export type BevyToUi =
@@ -19,20 +43,44 @@ export type BevyToUi =
     | { type: 'MaterialUpdated'; data: { material_id: string; properties: MaterialProperties } }
     | { type: 'DiffusionProgress'; data: { task_id: string; progress: number; preview_available: boolean } }
     | { type: 'DiffusionComplete'; data: { task_id: string; texture_id: string } }
-    | { type: 'RenderStats'; data: { fps: number; frame_time_ms: number; draw_calls: number; triangles: number } }
+    | { type: 'TextureRegistryStats'; data: TextureRegistryStats }
+    | { type: 'RenderStats'; data: { fps: number; frame_time_ms: number; draw_calls: number; triangles: number; captures_per_second: number; skipped_captures: number; projected_texels_per_frame: number; tiles_uploaded_per_frame: number; ui_renders_skipped: number; render_scale: number; scale_factor: number; history_bytes: number; history_budget_bytes: number } }
     | { type: 'MouseEnter'; data: { region_id: string } }
     | { type: 'MouseLeave'; data: { region_id: string } }
     | { type: 'Error'; data: { code: string; message: string } }
+    | { type: 'Warning'; data: { code: string; message: string } }
     | { type: 'ShowAddObjectMenu'; data: { show: boolean; position: [number, number] | null } }
-    | { type: 'ObjectAdded'; data: { object: SceneObject } }
+    | { type: 'ObjectAdded'; data: { object: SceneObject; duplicated_from: string | null } }
     | { type: 'GizmoModeChanged'; data: { mode: GizmoMode } }
+    | { type: 'GizmoStatus'; data: { mode: GizmoMode; axis: GizmoAxis; coordinate_space: CoordinateSpace; active: boolean; snap: SnapTarget; pivot: PivotPoint } }
     | { type: 'AmbientOcclusionChanged'; data: { settings: AmbientOcclusionSettings } }
+    | { type: 'LightingChanged'; data: { settings: LightingSettings } }
     | { type: 'EditModeChanged'; data: { mode: EditMode } }
     | { type: 'ProjectionModeChanged'; data: { live_projection: boolean } }
     | { type: 'MeshEditModeChanged'; data: { active: boolean; selection_mode: MeshSelectionMode; tool: MeshEditTool } }
     | { type: 'MeshEditSelectionChanged'; data: { vertex_count: number; edge_count: number; face_count: number } }
     | { type: 'CloseMenus' }
-    | { type: 'LayerStateChanged'; data: { layers: LayerInfo[] } };
+    | { type: 'LayerStateChanged'; data: { layers: LayerInfo[] } }
+    | { type: 'SculptSettingsChanged'; data: { dynamic_topology: boolean; detail_mode: SculptDetailMode; detail_size: number; max_vertices: number | null } }
+    | { type: 'SculptRemeshProgress'; data: { progress: number } }
+    | { type: 'SculptRemeshFinished'; data: { cancelled: boolean; vertex_count: number; face_count: number } }
+    | { type: 'MeshHealthReport'; data: { errors: string[]; repaired: number; chunk_stats: SculptChunkStats[] } }
+    | { type: 'KeymapChanged'; data: { bindings: KeyBinding[] } }
+    | { type: 'LightsChanged'; data: { lights: LightInfo[] } }
+    | { type: 'MeasureResult'; data: { id: string; start: [number, number, number]; end: [number, number, number]; distance: number } }
+    | { type: 'RecoveryAvailable'; data: { path: string; timestamp: number } }
+    | { type: 'CanvasExported'; data: { path: string } }
+    | { type: 'RenderProgress'; data: { frame: number; total: number } }
+    | { type: 'RenderFinished'; data: { path: string } }
+    | { type: 'MeshStorageConversionProgress'; data: { mesh_id: number; progress: number } }
+    | { type: 'MeshStorageConversionFinished'; data: { mesh_id: number; cancelled: boolean } }
+    | { type: 'UiLog'; data: { level: UiLogLevel; message: string; source: string; line: number } }
+    | { type: 'BinaryBlob'; data: { id: number; kind: BlobKind; byte_length: number } }
+    | { type: 'ProtocolVersion'; data: { version: number } }
+    | { type: 'QueryResult'; data: { request_id: number; result: QueryResult } }
+    | { type: 'CollabStatus'; data: { state: CollabState; ticket: string | null; peers: number; error: string | null } }
+    | { type: 'BackendLifecycleChanged'; data: { state: FrontendLifecycle } }
+    | { type: 'CloseRequested' };
 
 // Messages from UI to Bevy
 export type UiToBevy =
@@ -43,22 +91,54 @@ export type UiToBevy =
     | { type: 'MaterialCommand'; data: MaterialCommand }
     | { type: 'StartDiffusion'; data: DiffusionRequest }
     | { type: 'CancelDiffusion'; data: { task_id: string } }
+    | { type: 'DeleteTexture'; data: { texture_id: string } }
     | { type: 'UpdateSettings'; data: AppSettings }
     | { type: 'NodeGraphUpdate'; data: NodeGraphState }
     | { type: 'UpdateLighting'; data: LightingSettings }
     | { type: 'UpdateAmbientOcclusion'; data: AmbientOcclusionSettings }
     | { type: 'AddObject'; data: AddObjectRequest }
     | { type: 'GizmoCommand'; data: GizmoCommand }
-    | { type: 'AddPaintCanvas'; data: { width: number | null; height: number | null } }
+    | { type: 'LightCommand'; data: LightCommand }
+    | { type: 'AddPaintCanvas'; data: AddPaintCanvasRequest }
     | { type: 'PaintCommand'; data: PaintCommand }
     | { type: 'MeshEditCommand'; data: MeshEditCommand }
-    | { type: 'SetDepthView'; data: { enabled: boolean } };
+    | { type: 'SculptCommand'; data: SculptCommand }
+    | { type: 'MeasureCommand'; data: MeasureCommand }
+    | { type: 'SetEditMode'; data: { mode: EditMode } }
+    | { type: 'SetDepthView'; data: { enabled: boolean } }
+    | { type: 'SetViewMode'; data: { mode: ViewMode } }
+    | { type: 'SetCompositeMode'; data: { mode: CompositeMode } }
+    | { type: 'SetKeybinding'; data: { action: string; chord: string } }
+    | { type: 'RestoreAutosave'; data: { path: string } }
+    | { type: 'AddReferenceImage'; data: { path: string; mode: ReferenceImageMode } }
+    | { type: 'SetReferenceImageOpacity'; data: { id: string; opacity: number } }
+    | { type: 'RenderTurntable'; data: { frames: number; seconds: number; width: number; height: number; path: string } }
+    | { type: 'CancelRender' }
+    | { type: 'SetWireframe'; data: { target: WireframeTarget; enabled: boolean; color: [number, number, number, number] | null; depth_test: boolean } }
+    | { type: 'BlobConsumed'; data: { id: number } }
+    | { type: 'ProtocolVersion'; data: { version: number } }
+    | { type: 'Query'; data: { request_id: number; query: QueryKind } }
+    | { type: 'Collab'; data: CollabCommand }
+    | { type: 'CloseResponse'; data: { decision: CloseDecision } }
+    | { type: 'RequestInitialize' }
+    | { type: 'CompactHistory' };
 
 // Scene types
 export interface SceneInfo {
     objects: SceneObject[];
     cameras: CameraInfo[];
     lights: LightInfo[];
+    wireframe: WireframeInfo | null;
+    isolated: boolean;
+    annotations: AnnotationInfo[];
+}
+
+export interface AnnotationInfo {
+    id: string;
+    name: string;
+    start: [number, number, number];
+    end: [number, number, number];
+    distance: number;
 }
 
 export interface SceneObject {
@@ -67,8 +147,21 @@ export interface SceneObject {
     transform: Transform3D;
     material_id: string | null;
     visible: boolean;
+    locked: boolean;
+    parent_id: string | null;
+    subdivision_levels: number;
+    wireframe: WireframeInfo | null;
+    instanced: boolean;
 }
 
+export interface WireframeInfo {
+    enabled: boolean;
+    color: [number, number, number, number];
+    depth_test: boolean;
+}
+
+export type WireframeTarget = 'Global' | { Object: string };
+
 export interface Transform3D {
     position: [number, number, number];
     rotation: [number, number, number, number];
@@ -89,12 +182,14 @@ export interface LightInfo {
     name: string;
     light_type: LightType;
     color: [number, number, number];
+    // Lumens for point and spot lights, lux for directional lights
     intensity: number;
     transform: Transform3D;
+    shadows: boolean;
 }
 
 export type LightType =
-    | { Directional: null }
+    | 'Directional'
     | { Point: { range: number } }
     | { Spot: { range: number; inner_angle: number; outer_angle: number } };
 
@@ -112,6 +207,14 @@ export interface TextureSlot {
     texture_id: string | null;
 }
 
+export interface TextureRegistryStats {
+    texture_count: number;
+    referenced_count: number;
+    total_bytes: number;
+    budget_bytes: number;
+    evicted_count: number;
+}
+
 // Layout types
 export interface LayoutInfo {
     regions: LayoutRegion[];
@@ -134,16 +237,23 @@ export type CameraCommand =
     | { Zoom: { delta: number } }
     | { SetPosition: { position: [number, number, number] } }
     | { SetTarget: { target: [number, number, number] } }
-    | { Reset: null };
+    | { Reset: null }
+    | { SetOrbitOptions: OrbitOptions };
 
 export type ObjectCommand =
     | { Select: { ids: string[] } }
     | { Deselect: { ids: string[] } }
-    | { Delete: { ids: string[] } }
+    | { Delete: { ids: string[]; recursive: boolean } }
     | { Duplicate: { ids: string[] } }
     | { Transform: { id: string; transform: Transform3D } }
     | { SetVisibility: { id: string; visible: boolean } }
-    | { Rename: { id: string; name: string } };
+    | { Isolate: { ids: string[] } }
+    | { SetLocked: { id: string; locked: boolean } }
+    | { Rename: { id: string; name: string } }
+    | { SetParent: { id: string; parent_id: string | null } }
+    | { Group: { ids: string[]; name: string } }
+    | { SetSubdivision: { id: string; levels: number } }
+    | { MakeUnique: { id: string } };
 
 export type MaterialCommand =
     | { UpdateProperty: { material_id: string; property: string; value: unknown } }
@@ -167,15 +277,60 @@ export interface DiffusionRequest {
 // Settings types
 export interface AppSettings {
     render_scale: number;
+    // UI texture resolution relative to the window (0.5-1.0)
+    ui_render_scale: number;
     vsync: boolean;
     msaa_samples: number;
+    // Lower render_scale and MSAA automatically when frames are slow;
+    // changing either by hand turns this off
+    auto_performance: boolean;
     show_wireframe: boolean;
     show_grid: boolean;
     diffusion_server_url: string | null;
+    navigation: NavigationDeviceSettings;
+    outline: SelectionOutlineSettings;
+    orbit: OrbitOptions;
+}
+
+export type GamepadStick = 'Left' | 'Right';
+
+// Gamepad / SpaceMouse navigation; speeds are pixels (or scroll lines) per second
+export interface NavigationDeviceSettings {
+    enabled: boolean;
+    orbit_stick: GamepadStick;
+    pan_stick: GamepadStick;
+    invert_y: boolean;
+    dead_zone: number;
+    orbit_speed: number;
+    pan_speed: number;
+    zoom_speed: number;
+}
+
+// Selection outline; colors are sRGB, thickness is in physical pixels (1-8)
+export interface SelectionOutlineSettings {
+    enabled: boolean;
+    active_color: [number, number, number];
+    selected_color: [number, number, number];
+    thickness: number;
+    // Opacity of outline portions hidden behind other geometry (0 = not drawn)
+    occluded_opacity: number;
+}
+
+// Viewport camera: orbit around the surface under the cursor, zoom toward the cursor
+export interface OrbitOptions {
+    orbit_around_depth: boolean;
+    zoom_to_cursor: boolean;
+}
+
+// Hotkey action (e.g. "gizmo.translate") and its chord (e.g. "Shift+A")
+export interface KeyBinding {
+    action: string;
+    chord: string;
 }
 
 // Node graph types
 export interface NodeGraphState {
+    material_id: string;
     nodes: NodeInfo[];
     connections: NodeConnection[];
 }
@@ -207,6 +362,8 @@ export interface LightingSettings {
     moon_phase: number;
     azimuth_angle: number;
     pollution: number;
+    latitude: number;
+    animate_speed: number;
 }
 
 // Ambient occlusion settings
@@ -217,12 +374,30 @@ export interface AmbientOcclusionSettings {
 }
 
 // Add object request
+export type PrimitiveType =
+  | 'Cube'
+  | 'Sphere'
+  | 'Cylinder'
+  | 'Plane'
+  | 'Torus'
+  | 'Cone'
+  | 'Capsule'
+  | 'Monkey'
+  | { Grid: { subdivisions: number } };
+
 export interface AddObjectRequest {
-  primitive_type: string;
+  primitive_type: PrimitiveType;
   position: [number, number, number] | null;
   name: string | null;
+  subdivisions: number | null;
 }
 
+export type ScreenCorner = 'TopLeft' | 'TopRight' | 'BottomLeft' | 'BottomRight';
+
+export type ReferenceImageMode =
+    | 'WorldPlane'
+    | { CameraPinned: { corner: ScreenCorner; size: number; opacity: number } };
+
 export interface LayerInfo {
     id: number;
     name: string;
@@ -231,29 +406,148 @@ export interface LayerInfo {
     is_active: boolean;
 }
 
+export type LightCommand =
+    | { Add: { light_type: LightType; position: [number, number, number] } }
+    | {
+          Update: {
+              id: string;
+              color: [number, number, number] | null;
+              intensity: number | null;
+              range: number | null;
+              angles: [number, number] | null;
+          };
+      }
+    | { Delete: { id: string } }
+    | { SetShadows: { id: string; enabled: boolean } };
+
 export type GizmoCommand =
     | { SetMode: GizmoMode }
     | { ConstrainAxis: GizmoAxis }
     | { Cancel: null }
-    | { Confirm: null };
+    | { Confirm: null }
+    | { SetSnapTarget: { mode: SnapTarget } }
+    | { SetPivot: { pivot: PivotPoint } };
+
+export interface ProjectionOptions {
+    occlusion: boolean;
+    backface_cull: boolean;
+    falloff_angle_deg: number;
+}
+
+export type CanvasAnchor =
+    | 'TopLeft'
+    | 'Top'
+    | 'TopRight'
+    | 'Left'
+    | 'Center'
+    | 'Right'
+    | 'BottomLeft'
+    | 'Bottom'
+    | 'BottomRight';
+
+export type PixelSelectionMode = 'Rect' | 'Lasso';
+
+export type BrushTipSource = 'Round' | { BuiltIn: string } | { File: string };
+
+export type TipRotationMode = { Fixed: number } | 'FollowStroke' | 'Random';
+
+export type GradientKind = 'Linear' | 'Radial';
+
+export type CanvasTool =
+    | { Fill: { tolerance: number; contiguous: boolean } }
+    | { Gradient: { kind: GradientKind; colors: [number, number, number, number][] } };
+
+export type MeshPaintChannel = 'BaseColor' | 'Roughness' | 'Metallic' | 'Normal' | 'Emissive';
+
+export type MeshPaintTarget = 'Texture' | 'VertexColors';
+
+export type CanvasOrientation = 'FacingCamera' | 'Horizontal' | 'Vertical' | 'SurfaceAligned';
+
+export interface AddPaintCanvasRequest {
+    width: number | null;
+    height: number | null;
+    /** Width and height in world units; null fills the view */
+    world_size: [number, number] | null;
+    /** Distance from the camera; null for the default (2) */
+    distance: number | null;
+    orientation: CanvasOrientation;
+}
 
 export type PaintCommand =
     | { SetBrushColor: { color: [number, number, number, number] } }
     | { SetBrushSize: { size: number } }
     | { SetBrushOpacity: { opacity: number } }
+    | { SetBrushFlow: { flow: number } }
     | { SetBrushHardness: { hardness: number } }
     | { SetBlendMode: { mode: 'Normal' | 'Erase' } }
     | { SelectBrushPreset: { preset_id: number } }
     | { Undo: null }
     | { SetLiveProjection: { enabled: boolean } }
-    | { ProjectToScene: null }
+    | { ProjectToScene: { options: ProjectionOptions } }
+    | { ProjectToSelection: { options: ProjectionOptions } }
+    | 'ReprojectLast'
     | { AddLayer: { name: string } }
     | { RemoveLayer: { layer_id: number } }
     | { SetActiveLayer: { layer_id: number } }
     | { SetLayerVisibility: { layer_id: number; visible: boolean } }
     | { SetLayerOpacity: { layer_id: number; opacity: number } }
     | { ReorderLayer: { layer_id: number; new_index: number } }
-    | { RenameLayer: { layer_id: number; name: string } };
+    | { RenameLayer: { layer_id: number; name: string } }
+    | { ExportCanvas: { path: string | null } }
+    | { ImportCanvas: { path: string } }
+    | { ResizeCanvas: { width: number; height: number; anchor: CanvasAnchor } }
+    | { CropCanvas: { x: number; y: number; width: number; height: number } }
+    | { BeginPixelSelection: { mode: PixelSelectionMode } }
+    | {
+          TransformSelection: {
+              translate: [number, number];
+              rotate_deg: number;
+              scale: [number, number];
+          };
+      }
+    | { CommitSelection: null }
+    | { CancelSelection: null }
+    | { SetBrushTip: { source: BrushTipSource } }
+    | { SetBrushTipRotation: { mode: TipRotationMode } }
+    | { Fill: { x: number; y: number; tolerance: number; contiguous: boolean } }
+    | {
+          Gradient: {
+              start: [number, number];
+              end: [number, number];
+              kind: GradientKind;
+              colors: [number, number, number, number][];
+          };
+      }
+    | { ArmCanvasTool: { tool: CanvasTool | null } }
+    | { SetMeshPaintChannel: { channel: MeshPaintChannel } }
+    | { SetMeshPaintValue: { value: number } }
+    | { SetNormalStrength: { strength: number } }
+    | { SetMeshPaintTarget: { target: MeshPaintTarget } }
+    | { ReanchorCanvas: { plane_id: number | null } };
+
+export type MeasureCommand = 'Start' | 'Cancel';
+
+export type SculptCommand =
+    | { SetBrushSpacing: { spacing: number } }
+    | { SetBrushFlow: { flow: number } }
+    | { SelectBrushPreset: { preset_id: number } }
+    | { SetDynamicTopology: { enabled: boolean } }
+    | { SetDetailMode: SculptDetailMode }
+    | { SetDetailSize: { pixels_or_world: number } }
+    | { SetVertexBudget: { max_vertices: number | null } }
+    | { Remesh: { target_edge_length: number } }
+    | 'CancelRemesh'
+    | 'ClearMask'
+    | 'InvertMask'
+    | 'ValidateMesh'
+    | { SetAutoValidate: { every_strokes: number } };
+
+export interface SculptChunkStats {
+    chunk_id: number;
+    vertex_count: number;
+    face_count: number;
+    issue_count: number;
+}
 
 export type MeshEditCommand =
     | { SetSelectionMode: MeshSelectionMode }
@@ -11,7 +11,12 @@ const app = mount(App, {
 const teardownAutoMarkDirty = setupAutoMarkDirty();
 
 if (import.meta.hot) {
-    import.meta.hot.dispose(() => {
+    // A hot-reloaded app starts without state; the backend doesn't notice
+    if (import.meta.hot.data.reloaded) {
+        bridge.requestInitialize();
+    }
+    import.meta.hot.dispose((data) => {
+        data.reloaded = true;
         teardownAutoMarkDirty();
         bridge.dispose();
         void unmount(app);